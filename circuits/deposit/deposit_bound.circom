@@ -0,0 +1,76 @@
+// pSOL v2 Deposit Circuit (depositor-bound)
+// Proves the same statements as deposit.circom, plus binds the depositor:
+// 1. commitment = Poseidon(secret, nullifier, amount, asset_id)
+// 2. amount > 0
+// 3. commitment is non-zero
+// 4. depositor is a public input of the proof
+//
+// The program passes the depositor signer's key as the 4th public input
+// when the pool's deposit VK comes from this circuit, so a copied
+// commitment/proof fails verification in anyone else's transaction.
+
+pragma circom 2.1.6;
+
+include "../node_modules/circomlib/circuits/poseidon.circom";
+include "../node_modules/circomlib/circuits/comparators.circom";
+include "../node_modules/circomlib/circuits/bitify.circom";
+
+template DepositBound() {
+    // ================================
+    // PUBLIC INPUTS (4 total)
+    // ================================
+    signal input commitment;
+    signal input amount;
+    signal input asset_id;
+    signal input depositor;  // first 31 bytes of the depositor pubkey
+    
+    // ================================
+    // PRIVATE INPUTS
+    // ================================
+    signal input secret;
+    signal input nullifier;
+    
+    // ================================
+    // COMMITMENT COMPUTATION
+    // commitment = Poseidon(secret, nullifier, amount, asset_id)
+    // ================================
+    component commitment_hasher = Poseidon(4);
+    commitment_hasher.inputs[0] <== secret;
+    commitment_hasher.inputs[1] <== nullifier;
+    commitment_hasher.inputs[2] <== amount;
+    commitment_hasher.inputs[3] <== asset_id;
+    
+    commitment_hasher.out === commitment;
+    
+    // ================================
+    // AMOUNT VALIDATION
+    // ================================
+    component amount_check = GreaterThan(64);
+    amount_check.in[0] <== amount;
+    amount_check.in[1] <== 0;
+    amount_check.out === 1;
+    
+    // ================================
+    // NON-ZERO CHECKS
+    // ================================
+    component commitment_nonzero = IsZero();
+    commitment_nonzero.in <== commitment;
+    commitment_nonzero.out === 0;
+    
+    component nullifier_nonzero = IsZero();
+    nullifier_nonzero.in <== nullifier;
+    nullifier_nonzero.out === 0;
+    
+    component secret_nonzero = IsZero();
+    secret_nonzero.in <== secret;
+    secret_nonzero.out === 0;
+    
+    // ================================
+    // DEPOSITOR BINDING
+    // Quadratic constraint so the input cannot be optimized away
+    // ================================
+    signal depositor_square;
+    depositor_square <== depositor * depositor;
+}
+
+component main {public [commitment, amount, asset_id, depositor]} = DepositBound();
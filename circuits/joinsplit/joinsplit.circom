@@ -25,6 +25,7 @@ template JoinSplit(levels, nInputs, nOutputs) {
     signal input input_nullifiers[nInputs];
     signal input output_commitments[nOutputs];
     signal input public_amount;  // Can be negative (withdrawal) or positive (deposit)
+    signal input recipient;      // Paid a public outflow
     signal input relayer;
     signal input relayer_fee;
     
@@ -146,6 +147,13 @@ template JoinSplit(levels, nInputs, nOutputs) {
     // ================================
     signal dummy_relayer;
     dummy_relayer <== relayer * relayer;
+
+    // ================================
+    // RECIPIENT CONSTRAINT
+    // Bind the outflow recipient to the proof
+    // ================================
+    signal dummy_recipient;
+    dummy_recipient <== recipient * recipient;
 }
 
 // Standard 2-in-2-out JoinSplit for depth 20 tree
@@ -155,6 +163,7 @@ component main {public [
     input_nullifiers,
     output_commitments,
     public_amount,
+    recipient,
     relayer,
     relayer_fee
 ]} = JoinSplit(20, 2, 2);
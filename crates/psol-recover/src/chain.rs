@@ -0,0 +1,251 @@
+//! Fetching a pool's accounts and event history over RPC
+//!
+//! Accounts are found from the pool address alone: the config names the
+//! tree, and asset vaults and spent nullifiers are listed with
+//! `getProgramAccounts` filtered on their discriminator and `pool` field
+//! (the first field of both). Events come from the logs of every
+//! transaction that touched the pool config, oldest first.
+
+use std::error::Error;
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountDeserialize, Discriminator};
+use anchor_spl::token_interface::TokenAccount;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use psol_privacy_v2::recover::{PoolEvent, PoolSnapshot, VaultSnapshot};
+use psol_privacy_v2::state::{AssetVault, MerkleTreeV2, PoolConfigV2, SpentNullifierV2};
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_rpc_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_rpc_client_api::config::{
+    RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionConfig,
+};
+use solana_rpc_client_api::filter::{Memcmp, RpcFilterType};
+use solana_sdk::signature::Signature;
+use solana_transaction_status_client_types::option_serializer::OptionSerializer;
+use solana_transaction_status_client_types::UiTransactionEncoding;
+
+pub type RecoverResult<T = ()> = Result<T, Box<dyn Error>>;
+
+/// Signatures requested per `getSignaturesForAddress` page (the RPC maximum)
+const SIGNATURE_PAGE: usize = 1_000;
+
+/// Fetch and deserialize an Anchor account owned by `program_id`; `None` if
+/// it does not exist
+fn fetch<T: AccountDeserialize>(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    key: &Pubkey,
+) -> RecoverResult<Option<T>> {
+    let Some(account) = rpc
+        .get_account_with_commitment(key, rpc.commitment())?
+        .value
+    else {
+        return Ok(None);
+    };
+    if account.owner != *program_id {
+        return Err(format!("{key} is owned by {}, not {program_id}", account.owner).into());
+    }
+    Ok(Some(T::try_deserialize(&mut account.data.as_slice())?))
+}
+
+fn fetch_required<T: AccountDeserialize>(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    key: &Pubkey,
+    what: &str,
+) -> RecoverResult<T> {
+    fetch(rpc, program_id, key)?.ok_or_else(|| format!("{what} {key} not found").into())
+}
+
+/// Every program account of type `T` whose `pool` field is `pool`, read
+/// with `decode`
+fn pool_accounts<T: Discriminator>(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    pool: &Pubkey,
+    decode: fn(&[u8]) -> anchor_lang::Result<T>,
+) -> RecoverResult<Vec<(Pubkey, T)>> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, T::DISCRIMINATOR)),
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                T::DISCRIMINATOR.len(),
+                pool.as_ref(),
+            )),
+        ]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(rpc.commitment()),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+    rpc.get_program_accounts_with_config(program_id, config)?
+        .into_iter()
+        .map(|(key, account)| Ok((key, decode(&account.data)?)))
+        .collect()
+}
+
+pub fn load_snapshot(
+    rpc: &RpcClient,
+    program_id: Pubkey,
+    pool: Pubkey,
+) -> RecoverResult<PoolSnapshot> {
+    let config: PoolConfigV2 = fetch_required(rpc, &program_id, &pool, "pool config")?;
+    let tree: MerkleTreeV2 = fetch_required(rpc, &program_id, &config.merkle_tree, "merkle tree")?;
+
+    let mut vaults = Vec::new();
+    for (key, vault) in pool_accounts(rpc, &program_id, &pool, |data| {
+        AssetVault::try_deserialize(&mut &data[..])
+    })? {
+        // Native SOL vaults hold their balance as lamports above rent
+        let token_balance = if vault.is_native_sol() {
+            let account = rpc
+                .get_account_with_commitment(&key, rpc.commitment())?
+                .value;
+            match account {
+                Some(account) => {
+                    let rent = rpc.get_minimum_balance_for_rent_exemption(account.data.len())?;
+                    Some(account.lamports.saturating_sub(rent))
+                }
+                None => None,
+            }
+        } else {
+            rpc.get_account_with_commitment(&vault.token_account, rpc.commitment())?
+                .value
+                .map(|account| TokenAccount::try_deserialize(&mut account.data.as_slice()))
+                .transpose()?
+                .map(|token| token.amount)
+        };
+        vaults.push(VaultSnapshot {
+            key,
+            vault,
+            token_balance,
+        });
+    }
+    // Older nullifier records predate some fields and are shorter
+    let nullifiers = pool_accounts(rpc, &program_id, &pool, SpentNullifierV2::from_account_data)?;
+
+    Ok(PoolSnapshot {
+        program_id,
+        pool,
+        config,
+        tree,
+        vaults,
+        nullifiers,
+    })
+}
+
+/// `Program data:` payloads emitted by `program_id` itself, skipping data
+/// logged by programs it invokes (and by other programs in the transaction)
+pub fn program_data(program_id: &Pubkey, logs: &[String]) -> Vec<Vec<u8>> {
+    let id = program_id.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut data = Vec::new();
+
+    for line in logs {
+        let Some(rest) = line.strip_prefix("Program ") else {
+            continue;
+        };
+        if let Some(payload) = rest.strip_prefix("data: ") {
+            if stack.last() == Some(&id.as_str()) {
+                if let Ok(bytes) = STANDARD.decode(payload) {
+                    data.push(bytes);
+                }
+            }
+        } else if let Some((program, outcome)) = rest.split_once(' ') {
+            if outcome.starts_with("invoke [") {
+                stack.push(program);
+            } else if outcome == "success" || outcome.starts_with("failed") {
+                stack.pop();
+            }
+        }
+    }
+    data
+}
+
+/// All pool events, oldest first. Failed transactions are skipped.
+pub fn load_events(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    pool: &Pubkey,
+) -> RecoverResult<Vec<PoolEvent>> {
+    let mut signatures = Vec::new();
+    let mut before = None;
+    loop {
+        let page = rpc.get_signatures_for_address_with_config(
+            pool,
+            GetConfirmedSignaturesForAddress2Config {
+                before,
+                until: None,
+                limit: Some(SIGNATURE_PAGE),
+                commitment: Some(rpc.commitment()),
+            },
+        )?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        before = Some(Signature::from_str(&last.signature)?);
+        signatures.extend(
+            page.into_iter()
+                .filter(|status| status.err.is_none())
+                .map(|status| status.signature),
+        );
+    }
+
+    let config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Json),
+        commitment: Some(rpc.commitment()),
+        max_supported_transaction_version: Some(0),
+    };
+    let mut events = Vec::new();
+    for signature in signatures.iter().rev() {
+        let tx = rpc.get_transaction_with_config(&Signature::from_str(signature)?, config)?;
+        let Some(meta) = tx.transaction.meta else {
+            continue;
+        };
+        let OptionSerializer::Some(logs) = meta.log_messages else {
+            continue;
+        };
+        events.extend(
+            program_data(program_id, &logs)
+                .iter()
+                .filter_map(|data| PoolEvent::decode(data)),
+        );
+    }
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_program_data_only_from_the_pool_program() {
+        let pool_program = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let logs: Vec<String> = [
+            format!("Program {other} invoke [1]"),
+            "Program data: AQI=".to_string(),
+            format!("Program {other} success"),
+            format!("Program {pool_program} invoke [1]"),
+            "Program log: Instruction: WithdrawMasp".to_string(),
+            "Program data: AwQ=".to_string(),
+            format!("Program {other} invoke [2]"),
+            "Program data: BQY=".to_string(),
+            format!("Program {other} consumed 100 of 200000 compute units"),
+            format!("Program {other} success"),
+            "Program data: Bwg=".to_string(),
+            format!("Program {pool_program} success"),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            program_data(&pool_program, &logs),
+            vec![vec![3u8, 4], vec![7u8, 8]]
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! Cold-start recovery for a pSOL v2 pool
+//!
+//! Run with:
+//!   cargo run -p psol-recover -- --pool <POOL_CONFIG> [--url devnet]
+//!
+//! Fetches the pool config, tree, every asset vault (with its token
+//! balance) and every spent nullifier record, replays the events of every
+//! successful transaction that touched the pool, and prints where chain
+//! state and history diverge (see `psol_privacy_v2::recover`). Exits
+//! non-zero when anything diverges, so it can gate scripts.
+//!
+//! Needs an RPC node that still serves the pool's full transaction history.
+
+mod chain;
+
+use std::process::ExitCode;
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use psol_privacy_v2::recover::cross_validate;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+
+use chain::RecoverResult;
+
+const USAGE: &str = "\
+usage: psol-recover --pool <POOL_CONFIG> [options]
+
+  --url <devnet|localnet|URL>  RPC endpoint   [RPC_ENDPOINT]
+  --pool <PUBKEY>              pool config    [POOL_CONFIG]
+  --program-id <PUBKEY>        pool program   [this build's program id]";
+
+struct Args {
+    url: String,
+    pool: Pubkey,
+    program_id: Pubkey,
+}
+
+fn parse_pubkey(flag: &str, value: Option<String>) -> RecoverResult<Pubkey> {
+    let value = value.ok_or_else(|| format!("{flag} needs a value"))?;
+    Pubkey::from_str(&value).map_err(|e| format!("{flag} {value}: {e}").into())
+}
+
+impl Args {
+    fn parse(args: impl IntoIterator<Item = String>) -> RecoverResult<Self> {
+        let env = |name: &str| std::env::var(name).ok();
+        let mut url = env("RPC_ENDPOINT").unwrap_or_else(|| "localnet".into());
+        let mut pool = env("POOL_CONFIG");
+        let mut program_id = psol_privacy_v2::ID;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--url" | "-u" => {
+                    url = args.next().ok_or_else(|| format!("{arg} needs a value"))?
+                }
+                "--pool" => pool = args.next(),
+                "--program-id" => program_id = parse_pubkey(&arg, args.next())?,
+                other => return Err(format!("unknown argument {other}\n\n{USAGE}").into()),
+            }
+        }
+
+        let pool = pool.ok_or_else(|| format!("--pool is required\n\n{USAGE}"))?;
+        let url = match url.as_str() {
+            "devnet" | "d" => "https://api.devnet.solana.com".into(),
+            "localnet" | "localhost" | "l" => "http://127.0.0.1:8899".into(),
+            _ => url,
+        };
+        Ok(Self {
+            url,
+            pool: parse_pubkey("--pool", Some(pool))?,
+            program_id,
+        })
+    }
+}
+
+fn run() -> RecoverResult<bool> {
+    let args = Args::parse(std::env::args().skip(1))?;
+    let rpc = RpcClient::new_with_commitment(args.url.clone(), CommitmentConfig::confirmed());
+    println!(
+        "recovering pool {} (program {}) from {}",
+        args.pool, args.program_id, args.url
+    );
+
+    let snapshot = chain::load_snapshot(&rpc, args.program_id, args.pool)?;
+    println!(
+        "fetched tree ({} leaves), {} vaults, {} spent nullifiers",
+        snapshot.tree.next_leaf_index,
+        snapshot.vaults.len(),
+        snapshot.nullifiers.len()
+    );
+    let events = chain::load_events(&rpc, &args.program_id, &args.pool)?;
+    println!("fetched {} events", events.len());
+
+    let report = cross_validate(&snapshot, &events)?;
+    print!("{report}");
+    Ok(report.is_consistent())
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::from(2)
+        }
+    }
+}
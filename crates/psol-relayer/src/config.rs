@@ -0,0 +1,117 @@
+//! Command line / environment configuration
+//!
+//! Environment names match the TypeScript relayer's `.env`
+//! (`RPC_ENDPOINT`, `WALLET_PATH`, `POOL_CONFIG`, `RELAYER_FEE_BPS`), so
+//! both can run from the same file.
+
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anchor_lang::prelude::Pubkey;
+
+use crate::state::RelayerResult;
+use crate::submit::ComputeBudget;
+
+pub const USAGE: &str = "\
+usage: psol-relayer --pool <POOL_CONFIG> [options]
+
+  --url <devnet|localnet|URL>  RPC endpoint           [RPC_ENDPOINT]
+  --keypair <PATH>             relayer keypair        [WALLET_PATH]
+  --pool <PUBKEY>              pool config account    [POOL_CONFIG]
+  --fee-bps <N>                asking fee, bps        [RELAYER_FEE_BPS, 50]
+  --queue <DIR>                job spool directory    [./relayer-queue]
+  --cu-limit <N>               compute unit limit     [400000]
+  --cu-price <N>               micro-lamports per CU  [0]
+  --poll-ms <N>                queue poll interval    [1000]
+  --refresh-secs <N>           pool state cache age   [10]
+  --defer-events               queue withdraw events for the pool's flush
+                               crank when it has a delay configured
+  --once                       drain the queue once and exit";
+
+pub struct Config {
+    pub url: String,
+    pub keypair: PathBuf,
+    pub pool_config: Pubkey,
+    pub fee_bps: u16,
+    pub queue: PathBuf,
+    pub budget: ComputeBudget,
+    pub poll: Duration,
+    pub refresh: Duration,
+    pub once: bool,
+    pub defer_events: bool,
+}
+
+fn parse<T: FromStr>(flag: &str, value: Option<String>) -> RelayerResult<T>
+where
+    T::Err: std::fmt::Display,
+{
+    let value = value.ok_or_else(|| format!("{flag} needs a value"))?;
+    value
+        .parse()
+        .map_err(|e| format!("{flag} {value}: {e}").into())
+}
+
+impl Config {
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> RelayerResult<Self> {
+        let env = |name: &str| std::env::var(name).ok();
+        let mut url = env("RPC_ENDPOINT").unwrap_or_else(|| "localnet".into());
+        let mut keypair = env("WALLET_PATH").map(PathBuf::from).unwrap_or_else(|| {
+            PathBuf::from(std::env::var("HOME").unwrap_or_default()).join(".config/solana/id.json")
+        });
+        let mut pool_config = env("POOL_CONFIG");
+        let mut fee_bps = match env("RELAYER_FEE_BPS") {
+            Some(bps) => parse("RELAYER_FEE_BPS", Some(bps))?,
+            None => 50,
+        };
+        let mut queue = PathBuf::from("relayer-queue");
+        let mut budget = ComputeBudget {
+            unit_limit: ComputeBudget::DEFAULT_UNIT_LIMIT,
+            unit_price: 0,
+        };
+        let mut poll = Duration::from_millis(1_000);
+        let mut refresh = Duration::from_secs(10);
+        let mut once = false;
+        let mut defer_events = false;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--url" | "-u" => url = parse(&arg, args.next())?,
+                "--keypair" | "-k" => keypair = parse(&arg, args.next())?,
+                "--pool" => pool_config = Some(parse(&arg, args.next())?),
+                "--fee-bps" => fee_bps = parse(&arg, args.next())?,
+                "--queue" => queue = parse(&arg, args.next())?,
+                "--cu-limit" => budget.unit_limit = parse(&arg, args.next())?,
+                "--cu-price" => budget.unit_price = parse(&arg, args.next())?,
+                "--poll-ms" => poll = Duration::from_millis(parse(&arg, args.next())?),
+                "--refresh-secs" => refresh = Duration::from_secs(parse(&arg, args.next())?),
+                "--once" => once = true,
+                "--defer-events" => defer_events = true,
+                other => return Err(format!("unknown argument {other}\n\n{USAGE}").into()),
+            }
+        }
+
+        if fee_bps > 1_000 {
+            return Err("--fee-bps above 1000 can never pass the program's 10% fee cap".into());
+        }
+        let pool_config = pool_config.ok_or_else(|| format!("--pool is required\n\n{USAGE}"))?;
+        let url = match url.as_str() {
+            "devnet" | "d" => "https://api.devnet.solana.com".into(),
+            "localnet" | "localhost" | "l" => "http://127.0.0.1:8899".into(),
+            _ => url,
+        };
+        Ok(Self {
+            url,
+            keypair,
+            pool_config: parse("--pool", Some(pool_config))?,
+            fee_bps,
+            queue,
+            budget,
+            poll,
+            refresh,
+            once,
+            defer_events,
+        })
+    }
+}
@@ -0,0 +1,84 @@
+//! Fee earnings, persisted across restarts
+//!
+//! Totals are per asset in token base units, keyed by hex `asset_id`, and
+//! written to a JSON file after every successful withdrawal.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::state::RelayerResult;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetEarnings {
+    pub withdrawals: u64,
+    pub fees: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Earnings {
+    pub assets: BTreeMap<String, AssetEarnings>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl Earnings {
+    /// Load the ledger at `path`, starting empty if it does not exist
+    pub fn open(path: &Path) -> RelayerResult<Self> {
+        let mut earnings: Self = match fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(e.into()),
+        };
+        earnings.path = path.to_path_buf();
+        Ok(earnings)
+    }
+
+    pub fn record(&mut self, asset_id: &[u8; 32], fee: u64) -> RelayerResult {
+        let entry = self.assets.entry(hex::encode(asset_id)).or_default();
+        entry.withdrawals = entry.withdrawals.saturating_add(1);
+        entry.fees = entry.fees.saturating_add(fee);
+        fs::write(&self.path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn get(&self, asset_id: &[u8; 32]) -> AssetEarnings {
+        self.assets
+            .get(&hex::encode(asset_id))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_persists_per_asset_totals() {
+        let dir =
+            std::env::temp_dir().join(format!("psol-relayer-earnings-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("earnings.json");
+        let _ = fs::remove_file(&path);
+
+        let mut earnings = Earnings::open(&path).unwrap();
+        earnings.record(&[1u8; 32], 10).unwrap();
+        earnings.record(&[1u8; 32], 5).unwrap();
+        earnings.record(&[2u8; 32], 7).unwrap();
+
+        let reopened = Earnings::open(&path).unwrap();
+        assert_eq!(
+            reopened.get(&[1u8; 32]),
+            AssetEarnings {
+                withdrawals: 2,
+                fees: 15
+            }
+        );
+        assert_eq!(reopened.get(&[2u8; 32]).fees, 7);
+        assert_eq!(reopened.get(&[3u8; 32]), AssetEarnings::default());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,398 @@
+//! Withdrawal jobs as wallets hand them to the relayer
+//!
+//! The JSON shape matches the TypeScript relayer's `WithdrawRequest`:
+//! hex-encoded byte fields, base58 keys and decimal-string amounts, plus
+//! the `relayerFee` the proof was generated for (the fee is a public input,
+//! so the relayer cannot pick it after the fact).
+//!
+//! An optional `authorization` carries the user's ed25519 signature over
+//! the [`RelayerJob`] this relayer will submit; the relayer then includes
+//! it in the withdraw transaction as proof of what the user authorized.
+//!
+//! A `sealed` job executes a withdrawal the user committed to on-chain
+//! with `commit_withdrawal`: its proof binds the commit, which the relayer
+//! attaches to `withdraw_masp`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use psol_privacy_v2::crypto::{RelayerJob, WithdrawPublicInputs};
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::state::{withdrawal_commit_hash, withdrawal_commit_pin_hash};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WithdrawRequest {
+    pub proof_data: String,
+    pub merkle_root: String,
+    pub nullifier_hash: String,
+    pub recipient: String,
+    pub amount: String,
+    pub asset_id: String,
+    pub mint: String,
+    pub relayer_fee: String,
+    /// Set when `recipient` is a one-time owner whose token account the
+    /// relayer should create
+    #[serde(default)]
+    pub one_time_recipient: Option<OneTimeRecipientRequest>,
+    /// User signature over the job, when the wallet provides one
+    #[serde(default)]
+    pub authorization: Option<AuthorizationRequest>,
+    /// Set when the user sealed the withdrawal with `commit_withdrawal`
+    #[serde(default)]
+    pub sealed: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OneTimeRecipientRequest {
+    pub ephemeral_pubkey: String,
+    pub view_tag: u8,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthorizationRequest {
+    pub signer: String,
+    pub signature: String,
+    pub expires_at: i64,
+}
+
+/// User's ed25519 signature over the job's [`RelayerJob`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct JobAuthorization {
+    pub signer: Pubkey,
+    pub signature: [u8; 64],
+    pub expires_at: i64,
+}
+
+/// Announcement for `create_one_time_recipient`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OneTimeRecipient {
+    pub ephemeral_pubkey: [u8; 32],
+    pub view_tag: u8,
+}
+
+/// A parsed withdrawal job, ready for validation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WithdrawJob {
+    pub proof_data: Vec<u8>,
+    pub merkle_root: [u8; 32],
+    pub nullifier_hash: [u8; 32],
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub asset_id: [u8; 32],
+    pub mint: Pubkey,
+    pub relayer_fee: u64,
+    pub one_time_recipient: Option<OneTimeRecipient>,
+    pub authorization: Option<JobAuthorization>,
+    pub sealed: bool,
+}
+
+impl WithdrawJob {
+    /// `commit_hash` of the `WithdrawalCommit` a sealed job executes
+    pub fn commit_hash(&self, pool: &Pubkey) -> [u8; 32] {
+        withdrawal_commit_hash(
+            pool,
+            &self.merkle_root,
+            &self.nullifier_hash,
+            &self.recipient,
+            self.amount,
+            &self.asset_id,
+            self.relayer_fee,
+        )
+    }
+
+    /// Public inputs `withdraw_masp` verifies the proof against when
+    /// `relayer` submits the job to `pool`
+    pub fn public_inputs(&self, pool: &Pubkey, relayer: &Pubkey) -> WithdrawPublicInputs {
+        let public_data_hash = if self.sealed {
+            withdrawal_commit_pin_hash(&self.commit_hash(pool))
+        } else {
+            [0u8; 32]
+        };
+        WithdrawPublicInputs::new(
+            self.merkle_root,
+            self.nullifier_hash,
+            self.asset_id,
+            self.recipient,
+            self.amount,
+            *relayer,
+            self.relayer_fee,
+            public_data_hash,
+        )
+    }
+
+    /// The signed job, if the user authorized one
+    pub fn relayer_job(&self, pool: &Pubkey, relayer: &Pubkey) -> Option<RelayerJob> {
+        self.authorization.map(|authorization| {
+            RelayerJob::withdraw(
+                *pool,
+                &self.public_inputs(pool, relayer),
+                self.proof_data.clone(),
+                authorization.expires_at,
+            )
+        })
+    }
+}
+
+/// Why a job was not submitted
+#[derive(Debug, PartialEq)]
+pub enum Rejection {
+    /// The job file does not parse
+    Malformed(String),
+    /// `withdraw_masp` would fail with this error
+    Program(anchor_lang::error::Error),
+    /// The offered fee is below this relayer's quote
+    FeeBelowQuote { quoted: u64, offered: u64 },
+    /// The recipient's associated token account does not exist
+    RecipientAccountMissing,
+    /// A one-time recipient already has a token account
+    OneTimeRecipientReused,
+    /// The user's signature does not cover the job this relayer would submit
+    AuthorizationInvalid,
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rejection::Malformed(reason) => write!(f, "malformed job: {reason}"),
+            Rejection::Program(err) => write!(f, "rejected by program rules: {err}"),
+            Rejection::FeeBelowQuote { quoted, offered } => {
+                write!(f, "relayer fee {offered} below quote {quoted}")
+            }
+            Rejection::RecipientAccountMissing => {
+                f.write_str("recipient token account does not exist")
+            }
+            Rejection::OneTimeRecipientReused => {
+                f.write_str("one-time recipient already has a token account")
+            }
+            Rejection::AuthorizationInvalid => {
+                f.write_str("user signature does not cover this job")
+            }
+        }
+    }
+}
+
+impl From<anchor_lang::error::Error> for Rejection {
+    fn from(err: anchor_lang::error::Error) -> Self {
+        Rejection::Program(err)
+    }
+}
+
+impl From<PrivacyErrorV2> for Rejection {
+    fn from(err: PrivacyErrorV2) -> Self {
+        Rejection::Program(err.into())
+    }
+}
+
+fn bytes32(field: &str, value: &str) -> Result<[u8; 32], Rejection> {
+    let bytes = hex::decode(value.trim_start_matches("0x"))
+        .map_err(|e| Rejection::Malformed(format!("{field}: {e}")))?;
+    bytes
+        .try_into()
+        .map_err(|_| Rejection::Malformed(format!("{field}: expected 32 bytes")))
+}
+
+fn pubkey(field: &str, value: &str) -> Result<Pubkey, Rejection> {
+    Pubkey::from_str(value).map_err(|e| Rejection::Malformed(format!("{field}: {e}")))
+}
+
+fn amount(field: &str, value: &str) -> Result<u64, Rejection> {
+    value
+        .parse()
+        .map_err(|e| Rejection::Malformed(format!("{field}: {e}")))
+}
+
+impl TryFrom<WithdrawRequest> for WithdrawJob {
+    type Error = Rejection;
+
+    fn try_from(request: WithdrawRequest) -> Result<Self, Rejection> {
+        Ok(Self {
+            proof_data: hex::decode(request.proof_data.trim_start_matches("0x"))
+                .map_err(|e| Rejection::Malformed(format!("proofData: {e}")))?,
+            merkle_root: bytes32("merkleRoot", &request.merkle_root)?,
+            nullifier_hash: bytes32("nullifierHash", &request.nullifier_hash)?,
+            recipient: pubkey("recipient", &request.recipient)?,
+            amount: amount("amount", &request.amount)?,
+            asset_id: bytes32("assetId", &request.asset_id)?,
+            mint: pubkey("mint", &request.mint)?,
+            relayer_fee: amount("relayerFee", &request.relayer_fee)?,
+            one_time_recipient: request
+                .one_time_recipient
+                .map(|one_time| -> Result<_, Rejection> {
+                    Ok(OneTimeRecipient {
+                        ephemeral_pubkey: bytes32(
+                            "oneTimeRecipient.ephemeralPubkey",
+                            &one_time.ephemeral_pubkey,
+                        )?,
+                        view_tag: one_time.view_tag,
+                    })
+                })
+                .transpose()?,
+            authorization: request
+                .authorization
+                .map(|authorization| -> Result<_, Rejection> {
+                    let signature = hex::decode(authorization.signature.trim_start_matches("0x"))
+                        .map_err(|e| {
+                        Rejection::Malformed(format!("authorization.signature: {e}"))
+                    })?;
+                    Ok(JobAuthorization {
+                        signer: pubkey("authorization.signer", &authorization.signer)?,
+                        signature: signature.try_into().map_err(|_| {
+                            Rejection::Malformed(
+                                "authorization.signature: expected 64 bytes".to_string(),
+                            )
+                        })?,
+                        expires_at: authorization.expires_at,
+                    })
+                })
+                .transpose()?,
+            sealed: request.sealed,
+        })
+    }
+}
+
+impl FromStr for WithdrawJob {
+    type Err = Rejection;
+
+    fn from_str(json: &str) -> Result<Self, Rejection> {
+        let request: WithdrawRequest =
+            serde_json::from_str(json).map_err(|e| Rejection::Malformed(e.to_string()))?;
+        request.try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request_json(amount: &str, merkle_root: &str) -> String {
+        serde_json::json!({
+            "proofData": hex::encode([1u8; 256]),
+            "merkleRoot": merkle_root,
+            "nullifierHash": hex::encode([2u8; 32]),
+            "recipient": Pubkey::new_from_array([3u8; 32]).to_string(),
+            "amount": amount,
+            "assetId": hex::encode([4u8; 32]),
+            "mint": Pubkey::new_from_array([5u8; 32]).to_string(),
+            "relayerFee": "10",
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_parses_typescript_request_shape() {
+        let root = format!("0x{}", hex::encode([9u8; 32]));
+        let job: WithdrawJob = request_json("1000", &root).parse().unwrap();
+        assert_eq!(job.proof_data, vec![1u8; 256]);
+        assert_eq!(job.merkle_root, [9u8; 32]);
+        assert_eq!(job.recipient, Pubkey::new_from_array([3u8; 32]));
+        assert_eq!(job.amount, 1000);
+        assert_eq!(job.relayer_fee, 10);
+        assert_eq!(job.one_time_recipient, None);
+    }
+
+    #[test]
+    fn test_parses_one_time_recipient() {
+        let mut request: serde_json::Value =
+            serde_json::from_str(&request_json("1000", &hex::encode([9u8; 32]))).unwrap();
+        request["oneTimeRecipient"] = serde_json::json!({
+            "ephemeralPubkey": hex::encode([6u8; 32]),
+            "viewTag": 7,
+        });
+        let job: WithdrawJob = request.to_string().parse().unwrap();
+        assert_eq!(
+            job.one_time_recipient,
+            Some(OneTimeRecipient {
+                ephemeral_pubkey: [6u8; 32],
+                view_tag: 7,
+            })
+        );
+
+        request["oneTimeRecipient"]["ephemeralPubkey"] = "abcd".into();
+        assert!(matches!(
+            request.to_string().parse::<WithdrawJob>(),
+            Err(Rejection::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_parses_authorization() {
+        let mut request: serde_json::Value =
+            serde_json::from_str(&request_json("1000", &hex::encode([9u8; 32]))).unwrap();
+        let signer = Pubkey::new_unique();
+        request["authorization"] = serde_json::json!({
+            "signer": signer.to_string(),
+            "signature": hex::encode([8u8; 64]),
+            "expiresAt": 1_700_000_000,
+        });
+        let job: WithdrawJob = request.to_string().parse().unwrap();
+        assert_eq!(
+            job.authorization,
+            Some(JobAuthorization {
+                signer,
+                signature: [8u8; 64],
+                expires_at: 1_700_000_000,
+            })
+        );
+
+        let relayer = Pubkey::new_unique();
+        let signed = job.relayer_job(&Pubkey::default(), &relayer).unwrap();
+        assert_eq!(signed.proof, job.proof_data);
+        assert_eq!(
+            signed.public_inputs,
+            job.public_inputs(&Pubkey::default(), &relayer).to_bytes()
+        );
+
+        request["authorization"]["signature"] = hex::encode([8u8; 32]).into();
+        assert!(matches!(
+            request.to_string().parse::<WithdrawJob>(),
+            Err(Rejection::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_sealed_job_binds_its_commit() {
+        let mut request: serde_json::Value =
+            serde_json::from_str(&request_json("1000", &hex::encode([9u8; 32]))).unwrap();
+        let plain: WithdrawJob = request.to_string().parse().unwrap();
+        assert!(!plain.sealed);
+
+        request["sealed"] = true.into();
+        let job: WithdrawJob = request.to_string().parse().unwrap();
+        assert!(job.sealed);
+
+        let (pool, relayer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let inputs = job.public_inputs(&pool, &relayer);
+        assert_eq!(
+            inputs.public_data_hash,
+            withdrawal_commit_pin_hash(&job.commit_hash(&pool))
+        );
+        assert_eq!(
+            plain.public_inputs(&pool, &relayer).public_data_hash,
+            [0u8; 32]
+        );
+        // The commit is per pool
+        assert_ne!(job.commit_hash(&pool), job.commit_hash(&relayer));
+    }
+
+    #[test]
+    fn test_rejects_malformed_fields() {
+        let root = hex::encode([9u8; 32]);
+        assert!(matches!(
+            request_json("-1", &root).parse::<WithdrawJob>(),
+            Err(Rejection::Malformed(_))
+        ));
+        assert!(matches!(
+            request_json("1000", &root[2..]).parse::<WithdrawJob>(),
+            Err(Rejection::Malformed(_))
+        ));
+        assert!(matches!(
+            "{}".parse::<WithdrawJob>(),
+            Err(Rejection::Malformed(_))
+        ));
+    }
+}
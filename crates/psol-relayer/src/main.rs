@@ -0,0 +1,214 @@
+//! Reference relayer for pSOL v2 withdrawals
+//!
+//! Run with:
+//!   cargo run -p psol-relayer -- --pool <POOL_CONFIG> [--url devnet] [--queue DIR]
+//!
+//! The relayer polls a spool directory for withdrawal jobs (see `queue`),
+//! validates each one against cached pool state with the same rules and
+//! proof verifier `withdraw_masp` uses, checks the nullifier is unspent and
+//! the recipient's token account exists, then submits the withdraw behind
+//! compute-budget instructions and books the fee it earned.
+//!
+//! Logs never include the recipient or amount, matching the program's
+//! event privacy policy.
+
+mod config;
+mod earnings;
+mod job;
+mod queue;
+mod state;
+mod submit;
+mod validate;
+
+use std::fs;
+use std::path::Path;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use psol_privacy_v2::error::PrivacyErrorV2;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+
+use config::Config;
+use earnings::Earnings;
+use job::{Rejection, WithdrawJob};
+use queue::{Outcome, Queue};
+use state::{PoolState, RelayerResult};
+
+struct Relayer {
+    rpc: RpcClient,
+    keypair: Keypair,
+    config: Config,
+    state: Option<PoolState>,
+    earnings: Earnings,
+}
+
+impl Relayer {
+    /// Cached state, reloaded when older than the refresh interval
+    fn state(&mut self) -> RelayerResult<&mut PoolState> {
+        if self
+            .state
+            .as_ref()
+            .is_none_or(|s| s.is_stale(self.config.refresh))
+        {
+            self.state = Some(PoolState::load(
+                &self.rpc,
+                self.config.pool_config,
+                &self.keypair.pubkey(),
+            )?);
+        }
+        Ok(self.state.as_mut().unwrap())
+    }
+
+    /// Validate `job` and return the transaction to submit, or why not.
+    /// RPC failures are errors; anything the job itself gets wrong is a
+    /// rejection.
+    fn prepare(&mut self, job: &WithdrawJob) -> RelayerResult<Result<Transaction, Rejection>> {
+        let relayer = self.keypair.pubkey();
+        let fee_bps = self.config.fee_bps;
+        let budget = self.config.budget;
+        let defer_events = self.config.defer_events;
+        let rpc = &self.rpc;
+        if !self
+            .state
+            .as_mut()
+            .unwrap()
+            .load_asset(rpc, &job.asset_id)?
+        {
+            return Ok(Err(PrivacyErrorV2::AssetNotRegistered.into()));
+        }
+        let state = self.state.as_mut().unwrap();
+
+        let mut verdict = validate::validate(job, state, &relayer, fee_bps);
+        if verdict == Err(PrivacyErrorV2::InvalidMerkleRoot.into()) {
+            // The root may be newer than the cached tree
+            state.refresh_tree(rpc)?;
+            verdict = validate::validate(job, state, &relayer, fee_bps);
+        }
+        if let Err(rejection) = verdict {
+            return Ok(Err(rejection));
+        }
+
+        let nullifier = state.spent_nullifier_address(&job.nullifier_hash);
+        if rpc
+            .get_account_with_commitment(&nullifier, rpc.commitment())?
+            .value
+            .is_some()
+        {
+            return Ok(Err(PrivacyErrorV2::NullifierAlreadySpent.into()));
+        }
+        // A one-time recipient's account is created in the same transaction
+        let recipient_token = get_associated_token_address(&job.recipient, &job.mint);
+        let recipient_exists = rpc
+            .get_account_with_commitment(&recipient_token, rpc.commitment())?
+            .value
+            .is_some();
+        match (recipient_exists, job.one_time_recipient.is_some()) {
+            (false, false) => return Ok(Err(Rejection::RecipientAccountMissing)),
+            (true, true) => return Ok(Err(Rejection::OneTimeRecipientReused)),
+            _ => {}
+        }
+
+        let ixs = submit::withdraw_instructions(job, state, &relayer, budget, defer_events);
+        let blockhash = rpc.get_latest_blockhash()?;
+        Ok(Ok(Transaction::new_signed_with_payer(
+            &ixs,
+            Some(&relayer),
+            &[&self.keypair],
+            blockhash,
+        )))
+    }
+
+    fn process(&mut self, path: &Path) -> RelayerResult<Outcome> {
+        let job: WithdrawJob = match fs::read_to_string(path)?.parse() {
+            Ok(job) => job,
+            Err(rejection) => return Ok(rejected(rejection)),
+        };
+        let nullifier = hex::encode(&job.nullifier_hash[..8]);
+
+        self.state()?;
+        let tx = match self.prepare(&job)? {
+            Ok(tx) => tx,
+            Err(rejection) => {
+                println!("rejected {nullifier}..: {rejection}");
+                return Ok(rejected(rejection));
+            }
+        };
+
+        let signature = match self.rpc.send_and_confirm_transaction(&tx) {
+            Ok(signature) => signature,
+            Err(e) => {
+                // Whatever changed on chain, start from fresh state
+                self.state = None;
+                let Some(tx_error) = e.get_transaction_error() else {
+                    return Err(e.into());
+                };
+                println!("withdraw {nullifier}.. failed: {tx_error}");
+                return Ok(Outcome::Rejected {
+                    reason: tx_error.to_string(),
+                });
+            }
+        };
+        self.earnings.record(&job.asset_id, job.relayer_fee)?;
+        let total = self.earnings.get(&job.asset_id);
+        println!(
+            "withdraw {nullifier}.. {signature} fee {} (asset {}..: {} withdrawals, {} fees)",
+            job.relayer_fee,
+            hex::encode(&job.asset_id[..4]),
+            total.withdrawals,
+            total.fees
+        );
+        Ok(Outcome::Submitted {
+            signature: signature.to_string(),
+            relayer_fee: job.relayer_fee,
+        })
+    }
+}
+
+fn rejected(rejection: Rejection) -> Outcome {
+    Outcome::Rejected {
+        reason: rejection.to_string(),
+    }
+}
+
+fn main() -> RelayerResult {
+    let config = Config::from_args(std::env::args().skip(1))?;
+    let keypair = read_keypair_file(&config.keypair)
+        .map_err(|e| format!("keypair {}: {e}", config.keypair.display()))?;
+    let queue = Queue::open(&config.queue)?;
+    let earnings = Earnings::open(&config.queue.join("earnings.json"))?;
+    println!(
+        "relayer {} on {} for pool {}, {} bps, queue {}",
+        keypair.pubkey(),
+        config.url,
+        config.pool_config,
+        config.fee_bps,
+        config.queue.display()
+    );
+
+    let mut relayer = Relayer {
+        rpc: RpcClient::new_with_commitment(config.url.clone(), CommitmentConfig::confirmed()),
+        keypair,
+        config,
+        state: None,
+        earnings,
+    };
+    loop {
+        for path in queue.pending()? {
+            match relayer.process(&path) {
+                Ok(outcome) => queue.finish(&path, &outcome)?,
+                // RPC trouble: leave the job queued and retry next round
+                Err(e) => {
+                    eprintln!("{}: {e}", path.display());
+                    relayer.state = None;
+                    break;
+                }
+            }
+        }
+        if relayer.config.once {
+            return Ok(());
+        }
+        std::thread::sleep(relayer.config.poll);
+    }
+}
@@ -0,0 +1,107 @@
+//! File-spool job queue
+//!
+//! Wallets (or a front end such as the TypeScript relayer's HTTP API) drop
+//! one `*.json` job per file into the queue directory. Jobs are processed
+//! oldest name first and moved to `done/` or `failed/`, each with a
+//! `<name>.result.json` next to it recording the signature or the reason.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::state::RelayerResult;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Outcome {
+    Submitted { signature: String, relayer_fee: u64 },
+    Rejected { reason: String },
+}
+
+pub struct Queue {
+    dir: PathBuf,
+}
+
+impl Queue {
+    pub fn open(dir: &Path) -> RelayerResult<Self> {
+        fs::create_dir_all(dir.join("done"))?;
+        fs::create_dir_all(dir.join("failed"))?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    /// Queued job files, in name order
+    pub fn pending(&self) -> RelayerResult<Vec<PathBuf>> {
+        let mut jobs = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.is_file() && path.extension().is_some_and(|ext| ext == "json") {
+                jobs.push(path);
+            }
+        }
+        jobs.sort();
+        Ok(jobs)
+    }
+
+    /// Move a processed job out of the queue and record its outcome
+    pub fn finish(&self, job: &Path, outcome: &Outcome) -> RelayerResult {
+        let subdir = match outcome {
+            Outcome::Submitted { .. } => "done",
+            Outcome::Rejected { .. } => "failed",
+        };
+        let name = job.file_name().ok_or("job path has no file name")?;
+        let target = self.dir.join(subdir).join(name);
+        fs::rename(job, &target)?;
+        fs::write(
+            target.with_extension("result.json"),
+            serde_json::to_string_pretty(outcome)?,
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jobs_move_to_done_or_failed() {
+        let dir = std::env::temp_dir().join(format!("psol-relayer-queue-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let queue = Queue::open(&dir).unwrap();
+        fs::write(dir.join("b.json"), "{}").unwrap();
+        fs::write(dir.join("a.json"), "{}").unwrap();
+        fs::write(dir.join("notes.txt"), "").unwrap();
+
+        let pending = queue.pending().unwrap();
+        assert_eq!(pending, vec![dir.join("a.json"), dir.join("b.json")]);
+
+        queue
+            .finish(
+                &pending[0],
+                &Outcome::Submitted {
+                    signature: "sig".into(),
+                    relayer_fee: 10,
+                },
+            )
+            .unwrap();
+        queue
+            .finish(
+                &pending[1],
+                &Outcome::Rejected {
+                    reason: "bad".into(),
+                },
+            )
+            .unwrap();
+
+        assert!(queue.pending().unwrap().is_empty());
+        assert!(dir.join("done/a.json").exists());
+        assert!(dir.join("done/a.result.json").exists());
+        assert!(dir.join("failed/b.json").exists());
+        let result = fs::read_to_string(dir.join("failed/b.result.json")).unwrap();
+        assert!(result.contains("\"reason\": \"bad\""));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,158 @@
+//! Cached pool state the relayer validates jobs against
+//!
+//! Everything `withdraw_masp` reads is fetched once and reused until it is
+//! older than the refresh interval, so a burst of jobs costs one round of
+//! RPC calls. Root checks are retried against a fresh tree before a job is
+//! rejected, since a cached tree only lacks the newest roots.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountDeserialize;
+use anchor_spl::token_interface::TokenAccount;
+use psol_privacy_v2::state::{
+    AnonymityStats, AssetVault, AssetWithdrawalWindow, DeferredWithdrawEvents, MerkleTreeV2,
+    PoolConfigV2, RelayerNode, RiskTierConfig, SpentNullifierV2, VerificationKeyAccountV2,
+    YieldRegistry,
+};
+use psol_privacy_v2::ProofType;
+use solana_rpc_client::rpc_client::RpcClient;
+
+pub type RelayerResult<T = ()> = Result<T, Box<dyn Error>>;
+
+/// A registered asset and its vault balance
+pub struct Asset {
+    pub key: Pubkey,
+    pub vault: AssetVault,
+    pub vault_balance: u64,
+    /// The asset's withdrawal window, if it has one (see `state::risk_tier`)
+    pub withdrawal_window: Option<Pubkey>,
+    /// The asset's anonymity statistics, when the pool keeps them
+    pub anonymity_stats: Option<Pubkey>,
+}
+
+pub struct PoolState {
+    pub pool_config: Pubkey,
+    pub config: PoolConfigV2,
+    pub tree: MerkleTreeV2,
+    pub withdraw_vk: (Pubkey, VerificationKeyAccountV2),
+    /// This relayer's node, if it is registered
+    pub relayer_node: Option<(Pubkey, RelayerNode)>,
+    /// Present when the pool enforces yield exits
+    pub yield_registry: Option<(Pubkey, YieldRegistry)>,
+    /// The pool's deferred withdraw event queue, if it has one with a delay
+    pub deferred_events: Option<Pubkey>,
+    /// The pool's risk tier table, if it has one
+    pub risk_tiers: Option<Pubkey>,
+    /// Assets by `asset_id`, fetched on first use
+    pub assets: HashMap<[u8; 32], Asset>,
+    fetched_at: Instant,
+}
+
+/// Fetch and deserialize an Anchor account; `None` if it does not exist
+pub fn fetch<T: AccountDeserialize>(rpc: &RpcClient, key: &Pubkey) -> RelayerResult<Option<T>> {
+    let Some(account) = rpc
+        .get_account_with_commitment(key, rpc.commitment())?
+        .value
+    else {
+        return Ok(None);
+    };
+    Ok(Some(T::try_deserialize(&mut account.data.as_slice())?))
+}
+
+fn fetch_required<T: AccountDeserialize>(
+    rpc: &RpcClient,
+    key: &Pubkey,
+    what: &str,
+) -> RelayerResult<T> {
+    fetch(rpc, key)?.ok_or_else(|| format!("{what} {key} not found").into())
+}
+
+impl PoolState {
+    pub fn load(rpc: &RpcClient, pool_config: Pubkey, relayer: &Pubkey) -> RelayerResult<Self> {
+        let program_id = psol_privacy_v2::ID;
+        let config: PoolConfigV2 = fetch_required(rpc, &pool_config, "pool config")?;
+        let tree = fetch_required(rpc, &config.merkle_tree, "merkle tree")?;
+
+        let (vk_key, _) =
+            VerificationKeyAccountV2::find_pda(&program_id, &pool_config, ProofType::Withdraw);
+        let vk = fetch_required(rpc, &vk_key, "withdraw verification key")?;
+
+        let (node_key, _) = RelayerNode::find_pda(&program_id, &config.relayer_registry, relayer);
+        let relayer_node = fetch(rpc, &node_key)?.map(|node| (node_key, node));
+
+        let yield_registry = if config.is_yield_enforcement_enabled() {
+            let (key, _) = YieldRegistry::find_pda(&program_id, &pool_config);
+            Some((key, fetch_required(rpc, &key, "yield registry")?))
+        } else {
+            None
+        };
+
+        let (deferred_key, _) = DeferredWithdrawEvents::find_pda(&program_id, &pool_config);
+        let deferred_events = fetch::<DeferredWithdrawEvents>(rpc, &deferred_key)?
+            .filter(|queue| queue.delay_seconds > 0)
+            .map(|_| deferred_key);
+
+        let (tiers_key, _) = RiskTierConfig::find_pda(&program_id, &pool_config);
+        let risk_tiers = fetch::<RiskTierConfig>(rpc, &tiers_key)?.map(|_| tiers_key);
+
+        Ok(Self {
+            pool_config,
+            config,
+            tree,
+            withdraw_vk: (vk_key, vk),
+            relayer_node,
+            yield_registry,
+            deferred_events,
+            risk_tiers,
+            assets: HashMap::new(),
+            fetched_at: Instant::now(),
+        })
+    }
+
+    pub fn is_stale(&self, max_age: Duration) -> bool {
+        self.fetched_at.elapsed() > max_age
+    }
+
+    /// Re-read just the tree, for a root newer than the cache
+    pub fn refresh_tree(&mut self, rpc: &RpcClient) -> RelayerResult {
+        self.tree = fetch_required(rpc, &self.config.merkle_tree, "merkle tree")?;
+        Ok(())
+    }
+
+    /// Make sure `asset_id` is cached; `false` if it is not registered
+    pub fn load_asset(&mut self, rpc: &RpcClient, asset_id: &[u8; 32]) -> RelayerResult<bool> {
+        if self.assets.contains_key(asset_id) {
+            return Ok(true);
+        }
+        let (key, _) = AssetVault::find_pda(&psol_privacy_v2::ID, &self.pool_config, asset_id);
+        let Some(vault) = fetch::<AssetVault>(rpc, &key)? else {
+            return Ok(false);
+        };
+        let token: TokenAccount = fetch_required(rpc, &vault.token_account, "vault token account")?;
+        let (window_key, _) = AssetWithdrawalWindow::find_pda(&psol_privacy_v2::ID, &key);
+        let withdrawal_window =
+            fetch::<AssetWithdrawalWindow>(rpc, &window_key)?.map(|_| window_key);
+        let anonymity_stats = self
+            .config
+            .anonymity_stats_enabled
+            .then(|| AnonymityStats::find_pda(&psol_privacy_v2::ID, &self.pool_config, asset_id).0);
+        self.assets.insert(
+            *asset_id,
+            Asset {
+                key,
+                vault,
+                vault_balance: token.amount,
+                withdrawal_window,
+                anonymity_stats,
+            },
+        );
+        Ok(true)
+    }
+
+    pub fn spent_nullifier_address(&self, nullifier_hash: &[u8; 32]) -> Pubkey {
+        SpentNullifierV2::find_pda(&psol_privacy_v2::ID, &self.pool_config, nullifier_hash).0
+    }
+}
@@ -0,0 +1,166 @@
+//! Withdraw transaction construction
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{
+    self, get_associated_token_address, get_associated_token_address_with_program_id,
+};
+use anchor_spl::token::spl_token;
+use anchor_spl::token_2022;
+use psol_privacy_v2::state::{AssetVault, GlobalConfig, WithdrawalCommit};
+use psol_privacy_v2::{accounts, instruction};
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_ed25519_program::new_ed25519_instruction_with_signature;
+use solana_sdk::instruction::Instruction;
+
+use crate::job::WithdrawJob;
+use crate::state::PoolState;
+
+/// Compute budget prepended to every withdraw
+#[derive(Clone, Copy, Debug)]
+pub struct ComputeBudget {
+    pub unit_limit: u32,
+    /// Priority fee in micro-lamports per CU (0 = no price instruction)
+    pub unit_price: u64,
+}
+
+impl ComputeBudget {
+    /// The program's recommendation for `withdraw_masp`
+    pub const DEFAULT_UNIT_LIMIT: u32 = psol_privacy_v2::compute_budget::WITHDRAW_MASP_CU;
+}
+
+/// Compute budget instructions followed by `withdraw_masp` for `job`, with
+/// `create_one_time_recipient` in between for one-time recipients
+///
+/// A job the user signed gets an ed25519 verification of that signature
+/// directly before `withdraw_masp`, recording on-chain what was authorized.
+/// A sealed job gets the user's `WithdrawalCommit` attached.
+///
+/// With `defer_events` the pool's deferred event queue (if any) is passed,
+/// so the withdraw event is emitted later by the flush crank.
+///
+/// Token-2022 assets are paid out through the Token-2022 program, with the
+/// mint passed for `transfer_checked`.
+///
+/// An asset in a risk tier gets the pool's tier table, and its withdrawal
+/// window when it has one.
+///
+/// The job must already have passed validation, so its asset is cached.
+pub fn withdraw_instructions(
+    job: &WithdrawJob,
+    state: &PoolState,
+    relayer: &Pubkey,
+    budget: ComputeBudget,
+    defer_events: bool,
+) -> Vec<Instruction> {
+    let asset = &state.assets[&job.asset_id];
+    let token_2022 = asset.vault.is_token_2022();
+    let token_program = if token_2022 {
+        token_2022::ID
+    } else {
+        spl_token::id()
+    };
+    let token_account =
+        |owner| get_associated_token_address_with_program_id(owner, &job.mint, &token_program);
+    let accounts = accounts::WithdrawMasp {
+        relayer: *relayer,
+        pool_config: state.pool_config,
+        global_config: GlobalConfig::find_pda(&psol_privacy_v2::ID).0,
+        merkle_tree: state.config.merkle_tree,
+        vk_account: state.withdraw_vk.0,
+        asset_vault: asset.key,
+        vault_token_account: Some(asset.vault.token_account),
+        recipient_token_account: Some(token_account(&job.recipient)),
+        relayer_token_account: Some(token_account(relayer)),
+        spent_nullifier: state.spent_nullifier_address(&job.nullifier_hash),
+        relayer_registry: state.config.relayer_registry,
+        relayer_node: state.relayer_node.as_ref().map(|(key, _)| *key),
+        yield_registry: state.yield_registry.as_ref().map(|(key, _)| *key),
+        token_program,
+        system_program: system_program::ID,
+        instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+        proof_stats: None,
+        rebate_ledger: None,
+        migration_window: None,
+        deferred_events: state.deferred_events.filter(|_| defer_events),
+        recipient_wallet: None,
+        rent_reserve: None,
+        rent_advance: None,
+        mint: token_2022.then_some(job.mint),
+        anonymity_stats: asset.anonymity_stats,
+        parameter_snapshot: None,
+        recipient_ata: None,
+        associated_token_program: None,
+        fee_payer: None,
+        treasury_token_account: (state.config.protocol_fee_bps > 0)
+            .then(|| AssetVault::find_treasury_pda(&psol_privacy_v2::ID, &asset.key).0),
+        withdrawal_commit: job.sealed.then(|| {
+            WithdrawalCommit::find_pda(
+                &psol_privacy_v2::ID,
+                &state.pool_config,
+                &job.commit_hash(&state.pool_config),
+            )
+            .0
+        }),
+        fee_quote: None,
+        risk_tiers: state.risk_tiers.filter(|_| asset.vault.has_risk_tier()),
+        withdrawal_window: asset.withdrawal_window,
+    };
+    let data = instruction::WithdrawMasp {
+        proof_data: job.proof_data.clone(),
+        merkle_root: job.merkle_root,
+        nullifier_hash: job.nullifier_hash,
+        recipient: job.recipient,
+        amount: job.amount,
+        asset_id: job.asset_id,
+        relayer_fee: job.relayer_fee,
+    };
+
+    let mut ixs = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+        budget.unit_limit,
+    )];
+    if budget.unit_price > 0 {
+        ixs.push(ComputeBudgetInstruction::set_compute_unit_price(
+            budget.unit_price,
+        ));
+    }
+    if let Some(one_time) = &job.one_time_recipient {
+        let create = accounts::CreateOneTimeRecipient {
+            payer: *relayer,
+            pool_config: state.pool_config,
+            asset_vault: asset.key,
+            mint: job.mint,
+            one_time_owner: job.recipient,
+            recipient_token_account: get_associated_token_address(&job.recipient, &job.mint),
+            token_program: spl_token::id(),
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        };
+        let data = instruction::CreateOneTimeRecipient {
+            asset_id: job.asset_id,
+            ephemeral_pubkey: one_time.ephemeral_pubkey,
+            view_tag: one_time.view_tag,
+        };
+        ixs.push(Instruction {
+            program_id: psol_privacy_v2::ID,
+            accounts: create.to_account_metas(None),
+            data: data.data(),
+        });
+    }
+    if let (Some(authorization), Some(signed)) = (
+        job.authorization,
+        job.relayer_job(&state.pool_config, relayer),
+    ) {
+        ixs.push(new_ed25519_instruction_with_signature(
+            &signed.signing_message(),
+            &authorization.signature,
+            &authorization.signer.to_bytes(),
+        ));
+    }
+    ixs.push(Instruction {
+        program_id: psol_privacy_v2::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    });
+    ixs
+}
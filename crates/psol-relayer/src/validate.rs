@@ -0,0 +1,256 @@
+//! Job validation against cached pool state
+//!
+//! Mirrors the checks `withdraw_masp` runs, in the same order, so a job is
+//! only submitted when the program would accept it (modulo state that
+//! changed in between). The proof itself is verified locally with the
+//! program's own verifier against the on-chain withdraw VK.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anchor_lang::prelude::Pubkey;
+use psol_privacy_v2::crypto::{is_valid_proof_length, verify_proof_from_account};
+use psol_privacy_v2::error::PrivacyErrorV2;
+use solana_sdk::signature::Signature;
+
+use crate::job::{Rejection, WithdrawJob};
+use crate::state::PoolState;
+
+/// Fee this relayer asks for withdrawing `amount`
+pub fn quote_fee(amount: u64, fee_bps: u16) -> u64 {
+    (amount as u128 * fee_bps as u128 / 10_000) as u64
+}
+
+/// Input rules that need no account state
+pub fn check_inputs(job: &WithdrawJob) -> Result<(), Rejection> {
+    if !is_valid_proof_length(&job.proof_data) {
+        return Err(PrivacyErrorV2::InvalidProofFormat.into());
+    }
+    if job.nullifier_hash == [0u8; 32] {
+        return Err(PrivacyErrorV2::InvalidNullifier.into());
+    }
+    if job.merkle_root == [0u8; 32] {
+        return Err(PrivacyErrorV2::InvalidMerkleRoot.into());
+    }
+    if job.relayer_fee > job.amount {
+        return Err(PrivacyErrorV2::RelayerFeeExceedsAmount.into());
+    }
+    // Program caps the fee at 10% of the amount
+    let fee_times_ten = job
+        .relayer_fee
+        .checked_mul(10)
+        .ok_or(PrivacyErrorV2::RelayerFeeOverflow)?;
+    if fee_times_ten > job.amount {
+        return Err(PrivacyErrorV2::RelayerFeeOutOfRange.into());
+    }
+    Ok(())
+}
+
+/// Check the user's signature, when present, covers exactly the job this
+/// relayer would submit to `pool` and has not expired at `now`
+pub fn check_authorization(
+    job: &WithdrawJob,
+    pool: &Pubkey,
+    relayer: &Pubkey,
+    now: i64,
+) -> Result<(), Rejection> {
+    let (Some(authorization), Some(signed)) = (job.authorization, job.relayer_job(pool, relayer))
+    else {
+        return Ok(());
+    };
+    signed.require_not_expired(now)?;
+    let signature = Signature::from(authorization.signature);
+    if !signature.verify(authorization.signer.as_ref(), &signed.signing_message()) {
+        return Err(Rejection::AuthorizationInvalid);
+    }
+    Ok(())
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+/// Full validation; `fee_bps` is this relayer's asking rate
+pub fn validate(
+    job: &WithdrawJob,
+    state: &PoolState,
+    relayer: &Pubkey,
+    fee_bps: u16,
+) -> Result<(), Rejection> {
+    if state.config.is_paused {
+        return Err(PrivacyErrorV2::PoolPaused.into());
+    }
+    let (_, vk) = &state.withdraw_vk;
+    if !vk.is_initialized {
+        return Err(PrivacyErrorV2::VerificationKeyNotSet.into());
+    }
+    if state.config.requires_vk_canary() && !vk.canary_passed {
+        return Err(PrivacyErrorV2::VkCanaryNotPassed.into());
+    }
+
+    check_inputs(job)?;
+    check_authorization(job, &state.pool_config, relayer, unix_now())?;
+
+    let quoted = quote_fee(job.amount, fee_bps);
+    if job.relayer_fee < quoted {
+        return Err(Rejection::FeeBelowQuote {
+            quoted,
+            offered: job.relayer_fee,
+        });
+    }
+
+    let asset = state
+        .assets
+        .get(&job.asset_id)
+        .ok_or(PrivacyErrorV2::AssetNotRegistered)?;
+    if !asset.vault.is_active {
+        return Err(PrivacyErrorV2::AssetNotActive.into());
+    }
+    if !asset.vault.withdrawals_enabled {
+        return Err(PrivacyErrorV2::WithdrawalsDisabled.into());
+    }
+    if asset.vault.mint != job.mint {
+        return Err(PrivacyErrorV2::InvalidMint.into());
+    }
+    asset.vault.require_min_withdrawal(job.amount)?;
+    asset.vault.validate_relayer_fee(job.relayer_fee)?;
+
+    if let Some((_, registry)) = &state.yield_registry {
+        if registry.is_yield_asset(&job.asset_id) {
+            return Err(PrivacyErrorV2::YieldAssetRequiresYieldExit.into());
+        }
+    }
+    if asset.vault_balance < job.amount {
+        return Err(PrivacyErrorV2::InsufficientBalance.into());
+    }
+
+    if let Some((_, node)) = &state.relayer_node {
+        if !node.is_active {
+            return Err(PrivacyErrorV2::RelayerNotActive.into());
+        }
+        if !node.accepts_withdrawals() {
+            return Err(PrivacyErrorV2::RelayerDraining.into());
+        }
+        if job.relayer_fee > node.calculate_fee(job.amount)? {
+            return Err(PrivacyErrorV2::RelayerFeeOutOfRange.into());
+        }
+    }
+
+    if !state.tree.is_known_root(&job.merkle_root) {
+        return Err(PrivacyErrorV2::InvalidMerkleRoot.into());
+    }
+
+    let public_inputs = job.public_inputs(&state.pool_config, relayer);
+    public_inputs.validate()?;
+    let valid = verify_proof_from_account(
+        &vk.vk_alpha_g1,
+        &vk.vk_beta_g2,
+        &vk.vk_gamma_g2,
+        &vk.vk_delta_g2,
+        &vk.vk_ic,
+        &job.proof_data,
+        &public_inputs.to_field_elements(),
+    )?;
+    if !valid {
+        return Err(PrivacyErrorV2::InvalidProof.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(amount: u64, relayer_fee: u64) -> WithdrawJob {
+        WithdrawJob {
+            proof_data: vec![1u8; 256],
+            merkle_root: [1u8; 32],
+            nullifier_hash: [2u8; 32],
+            recipient: Pubkey::new_unique(),
+            amount,
+            asset_id: [3u8; 32],
+            mint: Pubkey::new_unique(),
+            relayer_fee,
+            one_time_recipient: None,
+            authorization: None,
+            sealed: false,
+        }
+    }
+
+    #[test]
+    fn test_quote_fee() {
+        assert_eq!(quote_fee(1_000_000, 50), 5_000);
+        assert_eq!(quote_fee(199, 50), 0);
+        assert_eq!(quote_fee(u64::MAX, 10_000), u64::MAX);
+    }
+
+    #[test]
+    fn test_check_inputs_matches_program_rules() {
+        assert_eq!(check_inputs(&job(1_000, 100)), Ok(()));
+        assert_eq!(
+            check_inputs(&job(1_000, 101)),
+            Err(PrivacyErrorV2::RelayerFeeOutOfRange.into())
+        );
+        assert_eq!(
+            check_inputs(&job(1_000, 1_001)),
+            Err(PrivacyErrorV2::RelayerFeeExceedsAmount.into())
+        );
+
+        let mut short_proof = job(1_000, 0);
+        short_proof.proof_data.truncate(255);
+        assert_eq!(
+            check_inputs(&short_proof),
+            Err(PrivacyErrorV2::InvalidProofFormat.into())
+        );
+
+        let mut zero_nullifier = job(1_000, 0);
+        zero_nullifier.nullifier_hash = [0u8; 32];
+        assert_eq!(
+            check_inputs(&zero_nullifier),
+            Err(PrivacyErrorV2::InvalidNullifier.into())
+        );
+    }
+
+    #[test]
+    fn test_check_authorization() {
+        let pool = Pubkey::new_unique();
+        let relayer = Pubkey::new_unique();
+        let user = solana_sdk::signature::Keypair::new();
+
+        let mut signed = job(1_000, 10);
+        assert_eq!(check_authorization(&signed, &pool, &relayer, 0), Ok(()));
+
+        signed.authorization = Some(crate::job::JobAuthorization {
+            signer: solana_sdk::signer::Signer::pubkey(&user),
+            signature: [0u8; 64],
+            expires_at: 100,
+        });
+        let message = signed
+            .relayer_job(&pool, &relayer)
+            .unwrap()
+            .signing_message();
+        signed.authorization.as_mut().unwrap().signature =
+            solana_sdk::signer::Signer::sign_message(&user, &message).into();
+
+        assert_eq!(check_authorization(&signed, &pool, &relayer, 100), Ok(()));
+        assert_eq!(
+            check_authorization(&signed, &pool, &relayer, 101),
+            Err(PrivacyErrorV2::RelayerJobExpired.into())
+        );
+        // Signed for another relayer or pool, or for different terms
+        assert_eq!(
+            check_authorization(&signed, &pool, &Pubkey::new_unique(), 0),
+            Err(Rejection::AuthorizationInvalid)
+        );
+        assert_eq!(
+            check_authorization(&signed, &Pubkey::new_unique(), &relayer, 0),
+            Err(Rejection::AuthorizationInvalid)
+        );
+        signed.relayer_fee += 1;
+        assert_eq!(
+            check_authorization(&signed, &pool, &relayer, 0),
+            Err(Rejection::AuthorizationInvalid)
+        );
+    }
+}
@@ -0,0 +1,95 @@
+//! Instruction names and discriminators
+//!
+//! Taken from the program's decoder table (`decode::INSTRUCTIONS`), which
+//! names every instruction with its argument struct. The tests check that
+//! table, and the event layouts, against the program sources, so every
+//! instruction and every event appears exactly once in the export.
+
+use psol_privacy_v2::decode::INSTRUCTIONS;
+use serde::Serialize;
+
+/// One program instruction
+#[derive(Serialize)]
+pub struct Instruction {
+    pub name: &'static str,
+    /// Leading discriminator bytes, hex encoded
+    pub discriminator: String,
+}
+
+/// Every instruction the program exposes
+pub fn instructions() -> Vec<Instruction> {
+    INSTRUCTIONS
+        .iter()
+        .map(|(name, discriminator)| Instruction {
+            name,
+            discriminator: hex::encode(discriminator),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layouts::{events, TypeTable};
+
+    const PROGRAM_SRC: &str = include_str!("../../../programs/psol-privacy-v2/src/lib.rs");
+    const EVENTS_SRC: &str = include_str!("../../../programs/psol-privacy-v2/src/events.rs");
+
+    /// Handlers declared in the `#[program]` module
+    fn program_handlers() -> Vec<&'static str> {
+        let start = PROGRAM_SRC
+            .find("#[program]")
+            .expect("lib.rs declares the program");
+        let body = &PROGRAM_SRC[start..];
+        let end = body.find("\n}\n").expect("program module is closed");
+        body[..end]
+            .lines()
+            .filter_map(|line| line.strip_prefix("    pub fn "))
+            .map(|rest| rest.split(['(', '<']).next().unwrap())
+            .collect()
+    }
+
+    /// `#[event]` structs, less those behind the `event-debug` feature
+    fn declared_events() -> Vec<&'static str> {
+        let lines: Vec<&str> = EVENTS_SRC.lines().collect();
+        lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.trim() == "#[event]")
+            .filter(|(i, _)| *i == 0 || !lines[i - 1].contains("event-debug"))
+            .map(|(i, _)| {
+                lines[i + 1..]
+                    .iter()
+                    .find_map(|line| line.strip_prefix("pub struct "))
+                    .expect("#[event] precedes a struct")
+                    .trim_end_matches(" {")
+            })
+            .collect()
+    }
+
+    fn sorted<T: Ord>(mut items: Vec<T>) -> Vec<T> {
+        items.sort();
+        items
+    }
+
+    #[test]
+    fn test_every_instruction_listed_once() {
+        let listed = instructions();
+        let names = sorted(listed.iter().map(|ix| ix.name).collect());
+        assert_eq!(names, sorted(program_handlers()));
+
+        let mut discriminators: Vec<&str> =
+            listed.iter().map(|ix| ix.discriminator.as_str()).collect();
+        discriminators.sort();
+        discriminators.dedup();
+        assert_eq!(discriminators.len(), listed.len());
+    }
+
+    #[test]
+    fn test_every_event_listed_once() {
+        let mut table = TypeTable::new();
+        let names = sorted(events(&mut table).into_iter().map(|l| l.name).collect());
+        let declared = sorted(declared_events().into_iter().map(String::from).collect());
+        assert_eq!(names, declared);
+    }
+}
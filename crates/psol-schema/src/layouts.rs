@@ -0,0 +1,250 @@
+//! Borsh layouts of the program's accounts and events
+//!
+//! Layouts are the IDL type definitions the program's own types generate
+//! under `idl-build`, so they follow the structs field for field. Nested
+//! types (`RelayerMetadata`, `PendingDeposit`, ...) are collected once into
+//! a shared type table and referenced by name.
+
+use std::collections::BTreeMap;
+
+use anchor_lang::idl::types::IdlTypeDef;
+use anchor_lang::idl::IdlBuild;
+use anchor_lang::Discriminator;
+use psol_privacy_v2::events::*;
+use psol_privacy_v2::state::*;
+use serde::Serialize;
+use serde_json::Value;
+
+/// An account or event: its discriminator and Borsh layout
+#[derive(Serialize)]
+pub struct Layout {
+    pub name: String,
+    /// Leading discriminator bytes, hex encoded
+    pub discriminator: String,
+    pub layout: Value,
+}
+
+/// Nested type definitions referenced from layouts, by name
+pub type TypeTable = BTreeMap<String, IdlTypeDef>;
+
+fn layout<T: IdlBuild + Discriminator>(types: &mut TypeTable) -> Layout {
+    let def = T::create_type().expect("program types have an IDL layout");
+    T::insert_types(types);
+    Layout {
+        name: short_name(&def.name).to_string(),
+        discriminator: hex::encode(T::DISCRIMINATOR),
+        layout: short_names(serde_json::to_value(def.ty).expect("layout serializes")),
+    }
+}
+
+macro_rules! layouts {
+    ($types:expr; $($ty:ty),* $(,)?) => {
+        vec![$(layout::<$ty>($types)),*]
+    };
+}
+
+/// Every account type the program owns
+pub fn accounts(types: &mut TypeTable) -> Vec<Layout> {
+    layouts!(types;
+        ActionAllowlist,
+        AnonymityStats,
+        AssetVault,
+        AssetWithdrawalWindow,
+        AttestedRootRecord,
+        AuditMetadata,
+        BatcherRole,
+        CircuitDescriptor,
+        CommitmentReservation,
+        ComplianceConfig,
+        DeferredWithdrawEvents,
+        DepositHook,
+        DisclosedWithdrawCommitment,
+        MerkleTreeV2,
+        MigrationWindow,
+        MintAllowlist,
+        ParameterSnapshot,
+        PendingDepositsBuffer,
+        PoolConfigV2,
+        PoolDirectory,
+        PoolDirectoryEntry,
+        ProofStats,
+        RangeProofParams,
+        RelayerFeeQuote,
+        RelayerIndexShard,
+        RelayerNode,
+        RelayerRebateLedger,
+        RelayerRegistry,
+        RelayerTombstone,
+        RentAdvance,
+        RentReserve,
+        RiskTierConfig,
+        SpentNullifierV2,
+        SurplusSweep,
+        Treasury,
+        VerificationKeyAccountV2,
+        WithdrawalCommit,
+        YieldRegistry,
+    )
+}
+
+/// Every event the program emits (debug-only events excluded)
+pub fn events(types: &mut TypeTable) -> Vec<Layout> {
+    layouts!(types;
+        AccountIntegrityChecked,
+        ActionAllowlistUpdated,
+        AllPoolsPaused,
+        AllPoolsUnpaused,
+        AssetConfigUpdated,
+        AssetMinWithdrawalUpdated,
+        AssetRegistered,
+        AssetRegistrationModeUpdated,
+        AssetRelayerFeeBoundsUpdated,
+        AssetRiskTierSet,
+        AuditMetadataAttached,
+        AuthorityTransferCancelledV2,
+        AuthorityTransferCompletedV2,
+        AuthorityTransferInitiatedV2,
+        BatchProcessedEvent,
+        BatchSettledEvent,
+        BatcherRegistered,
+        BatcherSlashed,
+        CircuitDescriptorSet,
+        CommitmentInsertedEvent,
+        CommitmentReservationCreated,
+        CommitmentReservationReleased,
+        ComplianceConfigured,
+        ComplianceRecoveryWithdrawn,
+        DepositHookRegistered,
+        DepositHookRemoved,
+        DepositMaspEvent,
+        DepositQueuedEvent,
+        DisclosedPathWithdrawalsSet,
+        DisclosedPathWithdrawn,
+        GasRebateCapUpdated,
+        GasRebateClaimed,
+        GlobalConfigInitialized,
+        InsufficientComputeBudgetDetected,
+        JoinSplitEvent,
+        MembershipProofVerified,
+        MigrationWindowClosed,
+        MigrationWindowOpened,
+        MintAllowlistUpdated,
+        NoteReceiptRecorded,
+        OneTimeRecipientCreated,
+        ParameterSnapshotPublished,
+        PoolConfigCloned,
+        PoolCreated,
+        PoolCreationFeeUpdated,
+        PoolInitializedV2,
+        PoolListed,
+        PoolPausedV2,
+        PoolUnpausedV2,
+        ProofVerificationFailed,
+        RelayerClosed,
+        RelayerCooldownUpdated,
+        RelayerDeactivated,
+        RelayerDrainingSet,
+        RelayerFailureReported,
+        RelayerFeeQuoteUpdated,
+        RelayerRegistered,
+        RelayerRegistrationsSet,
+        RelayerRegistryConfigured,
+        RelayerSlashed,
+        RelayerStakeMintSet,
+        RelayerStakeWithdrawn,
+        RelayerStaked,
+        RelayerUnstakeRequested,
+        RelayerUpdated,
+        RentAdvanceRepaid,
+        RentReserveFunded,
+        RiskTierLimitsUpdated,
+        RootAttested,
+        RootUpdatedEvent,
+        ShieldedActionExecuted,
+        SuperGuardianUpdated,
+        SurplusSweepCancelled,
+        SurplusSweepRequested,
+        SurplusSwept,
+        TreasuryDisbursementCancelled,
+        TreasuryDisbursementRequested,
+        TreasuryFeesCollected,
+        TreasuryWithdrawn,
+        TreeCapacityWarning,
+        VerificationKeyCanaryPassed,
+        VerificationKeyCeremonyRecorded,
+        VerificationKeyLockedV2,
+        VerificationKeySetV2,
+        WithdrawEventDelaySet,
+        WithdrawMaspEvent,
+        WithdrawV2Event,
+        WithdrawalCommitCancelled,
+        WithdrawalCommitted,
+    )
+}
+
+/// The type table as layouts, with short names
+pub fn types(types: TypeTable) -> Vec<Value> {
+    types
+        .into_values()
+        .map(|def| short_names(serde_json::to_value(def).expect("type serializes")))
+        .collect()
+}
+
+/// `psol_privacy_v2::state::relayer::RelayerMetadata` -> `RelayerMetadata`
+fn short_name(path: &str) -> &str {
+    match path.strip_prefix("psol_privacy_v2::") {
+        Some(rest) => rest.rsplit("::").next().unwrap_or(rest),
+        None => path,
+    }
+}
+
+/// Replace module paths by type names throughout a layout
+fn short_names(value: Value) -> Value {
+    match value {
+        Value::String(s) => Value::String(short_name(&s).to_string()),
+        Value::Array(items) => Value::Array(items.into_iter().map(short_names).collect()),
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key, short_names(value)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_names_are_unique() {
+        let mut table = TypeTable::new();
+        let mut names: Vec<String> = accounts(&mut table)
+            .into_iter()
+            .chain(events(&mut table))
+            .map(|layout| layout.name)
+            .collect();
+        names.extend(table.keys().map(|path| short_name(path).to_string()));
+
+        let total = names.len();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), total);
+    }
+
+    #[test]
+    fn test_nested_types_are_referenced_by_name() {
+        let mut table = TypeTable::new();
+        let accounts = accounts(&mut table);
+        let node = accounts.iter().find(|l| l.name == "RelayerNode").unwrap();
+
+        assert_eq!(
+            node.layout["fields"][12]["type"]["defined"]["name"],
+            "RelayerMetadata"
+        );
+        assert!(table
+            .keys()
+            .any(|path| short_name(path) == "RelayerMetadata"));
+    }
+}
@@ -0,0 +1,141 @@
+//! Machine-readable schema of the pSOL v2 program, for auditors
+//!
+//! Run with:
+//!   cargo run -p psol-schema --bin schema [-- --out <PATH>]
+//!
+//! Prints one JSON document with the discriminator of every instruction,
+//! the Borsh layout and discriminator of every account and event, the nested types they reference, the seed
+//! formula of every PDA, and the error code table. Everything is generated
+//! from the program's Rust types, and the output is deterministic: the same
+//! build always produces the same bytes, so exports can be diffed between
+//! releases.
+
+mod instructions;
+mod layouts;
+mod pdas;
+
+use std::process::ExitCode;
+
+use psol_privacy_v2::error::PrivacyErrorV2;
+use serde::Serialize;
+use serde_json::Value;
+
+use instructions::Instruction;
+use layouts::{Layout, TypeTable};
+use pdas::Pda;
+
+const USAGE: &str = "\
+usage: schema [options]
+
+  --out <PATH>  write the schema to PATH   [stdout]";
+
+#[derive(Serialize)]
+struct Program {
+    name: &'static str,
+    pool_config_version: u8,
+    program_id: String,
+}
+
+#[derive(Serialize)]
+struct ErrorCode {
+    code: u32,
+    name: String,
+    msg: String,
+}
+
+#[derive(Serialize)]
+struct Schema {
+    program: Program,
+    instructions: Vec<Instruction>,
+    accounts: Vec<Layout>,
+    events: Vec<Layout>,
+    types: Vec<Value>,
+    pdas: Vec<Pda>,
+    errors: Vec<ErrorCode>,
+}
+
+fn schema() -> Schema {
+    let mut types = TypeTable::new();
+    let accounts = layouts::accounts(&mut types);
+    let events = layouts::events(&mut types);
+    Schema {
+        program: Program {
+            name: "psol_privacy_v2",
+            pool_config_version: psol_privacy_v2::state::PoolConfigV2::VERSION,
+            program_id: psol_privacy_v2::ID.to_string(),
+        },
+        instructions: instructions::instructions(),
+        accounts,
+        events,
+        types: layouts::types(types),
+        pdas: pdas::pdas(),
+        errors: PrivacyErrorV2::ALL
+            .iter()
+            .map(|error| ErrorCode {
+                code: u32::from(*error),
+                name: error.name(),
+                msg: error.to_string(),
+            })
+            .collect(),
+    }
+}
+
+fn render() -> String {
+    let mut json = serde_json::to_string_pretty(&schema()).expect("schema serializes");
+    json.push('\n');
+    json
+}
+
+fn run() -> Result<(), String> {
+    let mut out = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--out" | "-o" => out = Some(args.next().ok_or(format!("{arg} needs a value"))?),
+            "--help" | "-h" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            other => return Err(format!("unknown argument {other}\n\n{USAGE}")),
+        }
+    }
+
+    let json = render();
+    match out {
+        Some(path) => std::fs::write(&path, json).map_err(|e| format!("{path}: {e}")),
+        None => {
+            print!("{json}");
+            Ok(())
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_is_deterministic() {
+        assert_eq!(render(), render());
+    }
+
+    #[test]
+    fn test_error_table_is_complete() {
+        let schema = schema();
+        let last = schema.errors.last().unwrap();
+
+        assert_eq!(schema.errors.len(), PrivacyErrorV2::ALL.len());
+        assert_eq!(last.code as usize, 6000 + schema.errors.len() - 1);
+        assert_eq!(last.name, PrivacyErrorV2::ALL.last().unwrap().name());
+    }
+}
@@ -0,0 +1,469 @@
+//! PDA seed formulas
+//!
+//! Constant seeds are taken from the program's own `SEED_PREFIX` constants
+//! (and `ProofType::as_seed`); the tests derive every formula and compare
+//! it with the account type's `find_pda`, so the table cannot drift from
+//! the program.
+
+use psol_privacy_v2::instructions::shielded_cpi::execute_action::{
+    ACTION_AUTHORITY_SEED, ACTION_ESCROW_SEED,
+};
+use psol_privacy_v2::state::*;
+use psol_privacy_v2::ProofType;
+use serde::Serialize;
+
+/// One seed of a PDA formula
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Seed {
+    /// Fixed bytes
+    Const {
+        #[serde(serialize_with = "as_utf8")]
+        value: &'static [u8],
+    },
+    /// An account address
+    Pubkey { name: &'static str },
+    /// A 32-byte value (asset id, nullifier hash, root, ...)
+    Bytes32 { name: &'static str },
+    /// A little-endian u32
+    U32Le { name: &'static str },
+    /// A little-endian u64
+    U64Le { name: &'static str },
+    /// A single byte (an enum discriminant such as the action type)
+    U8 { name: &'static str },
+}
+
+fn as_utf8<S: serde::Serializer>(value: &&[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(std::str::from_utf8(value).expect("seed constants are ASCII"))
+}
+
+#[derive(Debug, Serialize)]
+pub struct Pda {
+    pub account: &'static str,
+    pub seeds: Vec<Seed>,
+}
+
+fn konst(value: &'static [u8]) -> Seed {
+    Seed::Const { value }
+}
+
+fn pubkey(name: &'static str) -> Seed {
+    Seed::Pubkey { name }
+}
+
+fn bytes32(name: &'static str) -> Seed {
+    Seed::Bytes32 { name }
+}
+
+/// `[prefix, pool]`, the shape of every per-pool singleton
+fn per_pool(account: &'static str, prefix: &'static [u8]) -> Pda {
+    Pda {
+        account,
+        seeds: vec![konst(prefix), pubkey("pool")],
+    }
+}
+
+/// Proof types with their verification key seed (`ProofType::as_seed`)
+const PROOF_TYPES: [(ProofType, &[u8]); 6] = [
+    (ProofType::Deposit, b"vk_deposit"),
+    (ProofType::Withdraw, b"vk_withdraw"),
+    (ProofType::JoinSplit, b"vk_joinsplit"),
+    (ProofType::Membership, b"vk_membership"),
+    (ProofType::MerkleBatchUpdate, b"vk_merkle_batch"),
+    (ProofType::WithdrawV2, b"vk_withdraw_v2"),
+];
+
+/// Every PDA the program derives
+pub fn pdas() -> Vec<Pda> {
+    let mut pdas = vec![
+        Pda {
+            account: "GlobalConfig",
+            seeds: vec![konst(GlobalConfig::SEED_PREFIX)],
+        },
+        Pda {
+            account: "PoolDirectory",
+            seeds: vec![konst(PoolDirectory::SEED_PREFIX)],
+        },
+        Pda {
+            account: "PoolDirectoryEntry",
+            seeds: vec![
+                konst(PoolDirectoryEntry::SEED_PREFIX),
+                Seed::U64Le { name: "index" },
+            ],
+        },
+        Pda {
+            account: "PoolConfigV2",
+            seeds: vec![
+                konst(PoolConfigV2::SEED_PREFIX),
+                pubkey("authority"),
+                Seed::U64Le { name: "pool_id" },
+            ],
+        },
+        per_pool("MerkleTreeV2", MerkleTreeV2::SEED_PREFIX),
+        per_pool("PendingDepositsBuffer", PendingDepositsBuffer::SEED_PREFIX),
+        per_pool("RelayerRegistry", RelayerRegistry::SEED_PREFIX),
+        per_pool("ComplianceConfig", ComplianceConfig::SEED_PREFIX),
+        per_pool("YieldRegistry", YieldRegistry::SEED_PREFIX),
+        per_pool("ProofStats", ProofStats::SEED_PREFIX),
+        per_pool("MigrationWindow", MigrationWindow::SEED_PREFIX),
+        per_pool(
+            "DeferredWithdrawEvents",
+            DeferredWithdrawEvents::SEED_PREFIX,
+        ),
+        per_pool("RangeProofParams", RangeProofParams::SEED_PREFIX),
+        per_pool("DepositHook", DepositHook::SEED_PREFIX),
+        per_pool("MintAllowlist", MintAllowlist::SEED_PREFIX),
+        per_pool("RentReserve", RentReserve::SEED_PREFIX),
+        per_pool("Treasury", Treasury::SEED_PREFIX),
+        per_pool("RiskTierConfig", RiskTierConfig::SEED_PREFIX),
+        per_pool("InsuranceFund", INSURANCE_FUND_SEED),
+        Pda {
+            account: "AssetVault",
+            seeds: vec![
+                konst(AssetVault::SEED_PREFIX),
+                pubkey("pool"),
+                bytes32("asset_id"),
+            ],
+        },
+        Pda {
+            account: "AnonymityStats",
+            seeds: vec![
+                konst(AnonymityStats::SEED_PREFIX),
+                pubkey("pool"),
+                bytes32("asset_id"),
+            ],
+        },
+        Pda {
+            account: "ParameterSnapshot",
+            seeds: vec![
+                konst(ParameterSnapshot::SEED_PREFIX),
+                pubkey("pool"),
+                bytes32("asset_id"),
+            ],
+        },
+        per_pool("ShieldedActionAuthority", ACTION_AUTHORITY_SEED),
+        Pda {
+            account: "ActionAllowlist",
+            seeds: vec![
+                konst(ActionAllowlist::SEED_PREFIX),
+                pubkey("pool"),
+                Seed::U8 {
+                    name: "action_type",
+                },
+            ],
+        },
+        Pda {
+            account: "ActionEscrow",
+            seeds: vec![konst(ACTION_ESCROW_SEED), pubkey("pool"), pubkey("mint")],
+        },
+        Pda {
+            account: "VaultTokenAccount",
+            seeds: vec![konst(b"vault_token"), pubkey("asset_vault")],
+        },
+        Pda {
+            account: "TreasuryTokenAccount",
+            seeds: vec![
+                konst(AssetVault::TREASURY_SEED_PREFIX),
+                pubkey("asset_vault"),
+            ],
+        },
+        Pda {
+            account: "SurplusSweep",
+            seeds: vec![konst(SurplusSweep::SEED_PREFIX), pubkey("asset_vault")],
+        },
+        Pda {
+            account: "AssetWithdrawalWindow",
+            seeds: vec![
+                konst(AssetWithdrawalWindow::SEED_PREFIX),
+                pubkey("asset_vault"),
+            ],
+        },
+        Pda {
+            account: "SpentNullifierV2",
+            seeds: vec![
+                konst(SpentNullifierV2::SEED_PREFIX),
+                pubkey("pool"),
+                bytes32("nullifier_hash"),
+            ],
+        },
+        Pda {
+            account: "AttestedRootRecord",
+            seeds: vec![
+                konst(AttestedRootRecord::SEED_PREFIX),
+                pubkey("pool"),
+                bytes32("root"),
+            ],
+        },
+        Pda {
+            account: "AuditMetadata",
+            seeds: vec![
+                konst(AuditMetadata::SEED_PREFIX),
+                pubkey("pool"),
+                bytes32("commitment"),
+            ],
+        },
+        Pda {
+            account: "DisclosedWithdrawCommitment",
+            seeds: vec![
+                konst(DisclosedWithdrawCommitment::SEED_PREFIX),
+                pubkey("pool"),
+                bytes32("binding"),
+            ],
+        },
+        Pda {
+            account: "WithdrawalCommit",
+            seeds: vec![
+                konst(WithdrawalCommit::SEED_PREFIX),
+                pubkey("pool"),
+                bytes32("commit_hash"),
+            ],
+        },
+        Pda {
+            account: "CommitmentReservation",
+            seeds: vec![
+                konst(CommitmentReservation::SEED_PREFIX),
+                pubkey("pool"),
+                bytes32("commitment"),
+            ],
+        },
+        Pda {
+            account: "BatcherRole",
+            seeds: vec![
+                konst(BatcherRole::SEED_PREFIX),
+                pubkey("pool"),
+                pubkey("batcher"),
+            ],
+        },
+        Pda {
+            account: "RelayerNode",
+            seeds: vec![
+                konst(RelayerNode::SEED_PREFIX),
+                pubkey("relayer_registry"),
+                pubkey("operator"),
+            ],
+        },
+        Pda {
+            account: "RelayerTombstone",
+            seeds: vec![
+                konst(RelayerTombstone::SEED_PREFIX),
+                pubkey("relayer_registry"),
+                pubkey("operator"),
+            ],
+        },
+        Pda {
+            account: "RelayerIndexShard",
+            seeds: vec![
+                konst(RelayerIndexShard::SEED_PREFIX),
+                pubkey("relayer_registry"),
+                Seed::U32Le {
+                    name: "shard_index",
+                },
+            ],
+        },
+        Pda {
+            account: "RelayerRebateLedger",
+            seeds: vec![
+                konst(RelayerRebateLedger::SEED_PREFIX),
+                pubkey("relayer_node"),
+            ],
+        },
+        Pda {
+            account: "RelayerFeeQuote",
+            seeds: vec![
+                konst(RelayerFeeQuote::SEED_PREFIX),
+                pubkey("relayer_node"),
+            ],
+        },
+        Pda {
+            account: "RentAdvance",
+            seeds: vec![konst(RentAdvance::SEED_PREFIX), pubkey("relayer_node")],
+        },
+        Pda {
+            account: "RelayerStakeVault",
+            seeds: vec![
+                konst(RelayerNode::STAKE_VAULT_SEED_PREFIX),
+                pubkey("relayer_node"),
+                pubkey("mint"),
+            ],
+        },
+    ];
+    for (_, seed) in PROOF_TYPES {
+        pdas.push(Pda {
+            account: "VerificationKeyAccountV2",
+            seeds: vec![konst(seed), pubkey("pool")],
+        });
+    }
+    for (_, seed) in PROOF_TYPES {
+        pdas.push(Pda {
+            account: "CircuitDescriptor",
+            seeds: vec![
+                konst(CircuitDescriptor::SEED_PREFIX),
+                konst(seed),
+                pubkey("pool"),
+            ],
+        });
+    }
+    pdas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::prelude::Pubkey;
+    use psol_privacy_v2::ShieldedActionType;
+
+    /// Derive a formula's address, taking each named seed from `value`
+    fn derive(pda: &Pda, program_id: &Pubkey, value: impl Fn(&str) -> Vec<u8>) -> Pubkey {
+        let seeds: Vec<Vec<u8>> = pda
+            .seeds
+            .iter()
+            .map(|seed| match seed {
+                Seed::Const { value } => value.to_vec(),
+                Seed::Pubkey { name }
+                | Seed::Bytes32 { name }
+                | Seed::U32Le { name }
+                | Seed::U64Le { name }
+                | Seed::U8 { name } => value(name),
+            })
+            .collect();
+        let seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+        Pubkey::find_program_address(&seeds, program_id).0
+    }
+
+    /// Distinct sample bytes per seed name
+    fn sample(name: &str) -> Vec<u8> {
+        let byte = name.bytes().fold(7u8, |acc, b| acc.wrapping_mul(31) ^ b);
+        match name {
+            "shard_index" => 3u32.to_le_bytes().to_vec(),
+            "pool_id" => 5u64.to_le_bytes().to_vec(),
+            "index" => 9u64.to_le_bytes().to_vec(),
+            "action_type" => vec![ShieldedActionType::Stake as u8],
+            _ => vec![byte; 32],
+        }
+    }
+
+    fn key(name: &str) -> Pubkey {
+        Pubkey::new_from_array(sample(name).try_into().unwrap())
+    }
+
+    fn bytes(name: &str) -> [u8; 32] {
+        sample(name).try_into().unwrap()
+    }
+
+    #[test]
+    fn test_formulas_match_find_pda() {
+        let id = psol_privacy_v2::ID;
+        let pool = key("pool");
+        let registry = key("relayer_registry");
+        let operator = key("operator");
+        let node = key("relayer_node");
+        let vault = key("asset_vault");
+        let mut vk = PROOF_TYPES.iter();
+        let mut descriptors = PROOF_TYPES.iter();
+
+        for pda in pdas() {
+            let expected = match pda.account {
+                "GlobalConfig" => GlobalConfig::find_pda(&id).0,
+                "PoolDirectory" => PoolDirectory::find_pda(&id).0,
+                "PoolDirectoryEntry" => PoolDirectoryEntry::find_pda(&id, 9).0,
+                "PoolConfigV2" => PoolConfigV2::find_pda(&id, &key("authority"), 5).0,
+                "MerkleTreeV2" => MerkleTreeV2::find_pda(&id, &pool).0,
+                "PendingDepositsBuffer" => {
+                    Pubkey::find_program_address(
+                        &[PendingDepositsBuffer::SEED_PREFIX, pool.as_ref()],
+                        &id,
+                    )
+                    .0
+                }
+                "RelayerRegistry" => RelayerRegistry::find_pda(&id, &pool).0,
+                "ComplianceConfig" => ComplianceConfig::find_pda(&id, &pool).0,
+                "YieldRegistry" => YieldRegistry::find_pda(&id, &pool).0,
+                "ProofStats" => ProofStats::find_pda(&id, &pool).0,
+                "MigrationWindow" => {
+                    Pubkey::find_program_address(
+                        &[MigrationWindow::SEED_PREFIX, pool.as_ref()],
+                        &id,
+                    )
+                    .0
+                }
+                "DeferredWithdrawEvents" => DeferredWithdrawEvents::find_pda(&id, &pool).0,
+                "RangeProofParams" => RangeProofParams::find_pda(&id, &pool).0,
+                "DepositHook" => DepositHook::find_pda(&id, &pool).0,
+                "MintAllowlist" => MintAllowlist::find_pda(&id, &pool).0,
+                "RentReserve" => RentReserve::find_pda(&id, &pool).0,
+                "Treasury" => Treasury::find_pda(&id, &pool).0,
+                "RiskTierConfig" => RiskTierConfig::find_pda(&id, &pool).0,
+                "InsuranceFund" => find_insurance_fund_pda(&id, &pool).0,
+                "AssetVault" => AssetVault::find_pda(&id, &pool, &bytes("asset_id")).0,
+                "AnonymityStats" => AnonymityStats::find_pda(&id, &pool, &bytes("asset_id")).0,
+                "ParameterSnapshot" => {
+                    ParameterSnapshot::find_pda(&id, &pool, &bytes("asset_id")).0
+                }
+                "ShieldedActionAuthority" => {
+                    Pubkey::find_program_address(&[b"shielded_action", pool.as_ref()], &id).0
+                }
+                "ActionAllowlist" => {
+                    ActionAllowlist::find_pda(&id, &pool, ShieldedActionType::Stake).0
+                }
+                "ActionEscrow" => {
+                    Pubkey::find_program_address(
+                        &[b"action_escrow", pool.as_ref(), key("mint").as_ref()],
+                        &id,
+                    )
+                    .0
+                }
+                "VaultTokenAccount" => {
+                    Pubkey::find_program_address(&[b"vault_token", vault.as_ref()], &id).0
+                }
+                "TreasuryTokenAccount" => AssetVault::find_treasury_pda(&id, &vault).0,
+                "SurplusSweep" => SurplusSweep::find_pda(&id, &vault).0,
+                "AssetWithdrawalWindow" => AssetWithdrawalWindow::find_pda(&id, &vault).0,
+                "SpentNullifierV2" => {
+                    SpentNullifierV2::find_pda(&id, &pool, &bytes("nullifier_hash")).0
+                }
+                "AttestedRootRecord" => AttestedRootRecord::find_pda(&id, &pool, &bytes("root")).0,
+                "AuditMetadata" => AuditMetadata::find_pda(&id, &pool, &bytes("commitment")).0,
+                "DisclosedWithdrawCommitment" => {
+                    DisclosedWithdrawCommitment::find_pda(&id, &pool, &bytes("binding")).0
+                }
+                "WithdrawalCommit" => {
+                    WithdrawalCommit::find_pda(&id, &pool, &bytes("commit_hash")).0
+                }
+                "CommitmentReservation" => {
+                    CommitmentReservation::find_pda(&id, &pool, &bytes("commitment")).0
+                }
+                "BatcherRole" => {
+                    Pubkey::find_program_address(
+                        &[
+                            BatcherRole::SEED_PREFIX,
+                            pool.as_ref(),
+                            key("batcher").as_ref(),
+                        ],
+                        &id,
+                    )
+                    .0
+                }
+                "RelayerNode" => RelayerNode::find_pda(&id, &registry, &operator).0,
+                "RelayerTombstone" => RelayerTombstone::find_pda(&id, &registry, &operator).0,
+                "RelayerIndexShard" => RelayerIndexShard::find_pda(&id, &registry, 3).0,
+                "RelayerRebateLedger" => RelayerRebateLedger::find_pda(&id, &node).0,
+                "RelayerFeeQuote" => RelayerFeeQuote::find_pda(&id, &node).0,
+                "RentAdvance" => RentAdvance::find_pda(&id, &node).0,
+                "RelayerStakeVault" => {
+                    RelayerNode::find_stake_vault_pda(&id, &node, &key("mint")).0
+                }
+                "VerificationKeyAccountV2" => {
+                    let (proof_type, _) = vk.next().unwrap();
+                    VerificationKeyAccountV2::find_pda(&id, &pool, *proof_type).0
+                }
+                "CircuitDescriptor" => {
+                    let (proof_type, _) = descriptors.next().unwrap();
+                    CircuitDescriptor::find_pda(&id, &pool, *proof_type).0
+                }
+                other => panic!("no find_pda check for {other}"),
+            };
+            assert_eq!(derive(&pda, &id, sample), expected, "{}", pda.account);
+        }
+        assert!(vk.next().is_none() && descriptors.next().is_none());
+    }
+}
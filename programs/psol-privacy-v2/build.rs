@@ -0,0 +1,12 @@
+//! Runs the program-test suites under tests/ only when they can load the
+//! SBF build: `sbf_build` is set when `SBF_OUT_DIR` or `BPF_OUT_DIR` points
+//! at it, and those suites are ignored otherwise. `scripts/test-program.sh`
+//! builds the program and runs the tests with `SBF_OUT_DIR` set.
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=SBF_OUT_DIR");
+    println!("cargo:rerun-if-env-changed=BPF_OUT_DIR");
+    if std::env::var_os("SBF_OUT_DIR").is_some() || std::env::var_os("BPF_OUT_DIR").is_some() {
+        println!("cargo:rustc-cfg=sbf_build");
+    }
+}
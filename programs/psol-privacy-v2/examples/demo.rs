@@ -0,0 +1,596 @@
+//! Devnet/localnet demo: one shielded roundtrip against the deployed program
+//!
+//! Run with:
+//!   cargo run -p psol-privacy-v2 --example demo -- [--url <devnet|localnet|URL>] [--keypair <PATH>]
+//!
+//! The RPC URL and payer default to `ANCHOR_PROVIDER_URL` and
+//! `ANCHOR_WALLET` (then localnet and ~/.config/solana/id.json). The payer is
+//! topped up from the cluster faucet when low.
+//!
+//! Steps, each printing the accounts it touches:
+//! 1. create a fresh pool authority (pool PDAs are per authority, so every
+//!    run gets its own pool) and fund it, the relayer and the depositor
+//! 2. create the test mint if missing and mint the deposit amount
+//! 3. initialize the pool, registries, pending buffer and asset vault
+//! 4. upload the dev deposit/withdraw VKs from test_data/
+//! 5. deposit, batch, and withdraw through the relayer to the recipient
+//!
+//! The proofs are the pre-generated ones in test_data/e2e_fixture.json.
+//! They are bound to the fixture mint, relayer and recipient, whose keys
+//! are derived from fixed seeds, and to the root of a tree holding only the
+//! fixture commitment, which is what a fresh pool reaches after the first
+//! batch.
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use std::error::Error;
+use std::time::Duration;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use anchor_spl::token::spl_token;
+use anchor_spl::token::spl_token::solana_program::program_pack::Pack;
+use psol_privacy_v2::state::{
+    AssetVault, CommitmentReservation, ComplianceConfig, GlobalConfig, MerkleTreeV2,
+    PendingDepositsBuffer, PoolConfigV2, RelayerRegistry, SpentNullifierV2,
+    VerificationKeyAccountV2,
+};
+use psol_privacy_v2::{accounts, instruction, ProofType};
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signature, Signer};
+use solana_sdk::signer::keypair::keypair_from_seed;
+use solana_sdk::transaction::Transaction;
+use solana_system_interface::instruction as system_instruction;
+
+use common::pool::ix;
+use common::{deposit_vk, fixture, g1, g2, hex32, proof_bytes, withdraw_vk, SnarkjsVk};
+
+type DemoResult<T = ()> = Result<T, Box<dyn Error>>;
+
+const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+const ROOT_HISTORY_SIZE: u16 = 64;
+/// The demo runs the authority's first pool
+const POOL_ID: u64 = 0;
+/// Fixture mint keypair seed; the mint is its own mint authority so any
+/// run can mint test tokens
+const MINT_SEED: [u8; 32] = [1u8; 32];
+
+struct Args {
+    url: String,
+    keypair: String,
+}
+
+fn parse_args() -> DemoResult<Args> {
+    let mut url = std::env::var("ANCHOR_PROVIDER_URL").unwrap_or_else(|_| "localnet".into());
+    let mut keypair = std::env::var("ANCHOR_WALLET").unwrap_or_else(|_| {
+        format!(
+            "{}/.config/solana/id.json",
+            std::env::var("HOME").unwrap_or_default()
+        )
+    });
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--url" | "-u" => url = args.next().ok_or("--url needs a value")?,
+            "--keypair" | "-k" => keypair = args.next().ok_or("--keypair needs a value")?,
+            other => return Err(format!("unknown argument {other}").into()),
+        }
+    }
+    let url = match url.as_str() {
+        "devnet" | "d" => "https://api.devnet.solana.com".into(),
+        "localnet" | "localhost" | "l" => "http://127.0.0.1:8899".into(),
+        _ => url,
+    };
+    Ok(Args { url, keypair })
+}
+
+struct Demo {
+    rpc: RpcClient,
+    payer: Keypair,
+}
+
+impl Demo {
+    fn send(
+        &self,
+        label: &str,
+        ixs: &[Instruction],
+        signers: &[&Keypair],
+    ) -> DemoResult<Signature> {
+        let blockhash = self.rpc.get_latest_blockhash()?;
+        let tx =
+            Transaction::new_signed_with_payer(ixs, Some(&signers[0].pubkey()), signers, blockhash);
+        let sig = self
+            .rpc
+            .send_and_confirm_transaction(&tx)
+            .map_err(|e| format!("{label}: {e}"))?;
+        println!("  {label:<36} {sig}");
+        Ok(sig)
+    }
+
+    /// Request an airdrop if the payer is below `min` lamports
+    fn faucet(&self, min: u64) -> DemoResult {
+        let balance = self.rpc.get_balance(&self.payer.pubkey())?;
+        if balance >= min {
+            return Ok(());
+        }
+        println!("  requesting airdrop for {}", self.payer.pubkey());
+        let sig = self
+            .rpc
+            .request_airdrop(&self.payer.pubkey(), 2 * LAMPORTS_PER_SOL)?;
+        for _ in 0..60 {
+            if self.rpc.confirm_transaction(&sig)? {
+                return Ok(());
+            }
+            std::thread::sleep(Duration::from_millis(500));
+        }
+        Err("airdrop not confirmed; fund the payer manually".into())
+    }
+
+    fn fund(&self, to: &[Pubkey], lamports: u64) -> DemoResult {
+        let ixs: Vec<_> = to
+            .iter()
+            .map(|key| system_instruction::transfer(&self.payer.pubkey(), key, lamports))
+            .collect();
+        self.send("fund authority and relayer", &ixs, &[&self.payer])?;
+        Ok(())
+    }
+
+    fn ensure_mint(&self, mint: &Keypair) -> DemoResult {
+        if self.rpc.get_account(&mint.pubkey()).is_ok() {
+            return Ok(());
+        }
+        let len = spl_token::state::Mint::LEN;
+        let rent = self.rpc.get_minimum_balance_for_rent_exemption(len)?;
+        self.send(
+            "create test mint",
+            &[
+                system_instruction::create_account(
+                    &self.payer.pubkey(),
+                    &mint.pubkey(),
+                    rent,
+                    len as u64,
+                    &spl_token::id(),
+                ),
+                spl_token::instruction::initialize_mint2(
+                    &spl_token::id(),
+                    &mint.pubkey(),
+                    &mint.pubkey(),
+                    None,
+                    9,
+                )?,
+            ],
+            &[&self.payer, mint],
+        )?;
+        Ok(())
+    }
+}
+
+/// Every PDA the pool uses, derived from the authority
+struct PoolAddresses {
+    pool_config: Pubkey,
+    global_config: Pubkey,
+    merkle_tree: Pubkey,
+    relayer_registry: Pubkey,
+    compliance_config: Pubkey,
+    pending_buffer: Pubkey,
+    asset_vault: Pubkey,
+    vault_token: Pubkey,
+    deposit_vk: Pubkey,
+    withdraw_vk: Pubkey,
+}
+
+impl PoolAddresses {
+    fn derive(authority: &Pubkey, asset_id: &[u8; 32]) -> Self {
+        let program_id = psol_privacy_v2::ID;
+        let (pool_config, _) = PoolConfigV2::find_pda(&program_id, authority, POOL_ID);
+        let (asset_vault, _) = AssetVault::find_pda(&program_id, &pool_config, asset_id);
+        Self {
+            pool_config,
+            global_config: GlobalConfig::find_pda(&program_id).0,
+            merkle_tree: MerkleTreeV2::find_pda(&program_id, &pool_config).0,
+            relayer_registry: RelayerRegistry::find_pda(&program_id, &pool_config).0,
+            compliance_config: ComplianceConfig::find_pda(&program_id, &pool_config).0,
+            pending_buffer: Pubkey::find_program_address(
+                &[PendingDepositsBuffer::SEED_PREFIX, pool_config.as_ref()],
+                &program_id,
+            )
+            .0,
+            asset_vault,
+            vault_token: Pubkey::find_program_address(
+                &[b"vault_token", asset_vault.as_ref()],
+                &program_id,
+            )
+            .0,
+            deposit_vk: VerificationKeyAccountV2::find_pda(
+                &program_id,
+                &pool_config,
+                ProofType::Deposit,
+            )
+            .0,
+            withdraw_vk: VerificationKeyAccountV2::find_pda(
+                &program_id,
+                &pool_config,
+                ProofType::Withdraw,
+            )
+            .0,
+        }
+    }
+
+    fn print(&self) {
+        for (name, key) in [
+            ("pool_config", self.pool_config),
+            ("merkle_tree", self.merkle_tree),
+            ("relayer_registry", self.relayer_registry),
+            ("compliance_config", self.compliance_config),
+            ("pending_buffer", self.pending_buffer),
+            ("asset_vault", self.asset_vault),
+            ("vault_token_account", self.vault_token),
+            ("deposit_vk", self.deposit_vk),
+            ("withdraw_vk", self.withdraw_vk),
+        ] {
+            println!("  {name:<22} {key}");
+        }
+    }
+}
+
+fn create_vk_ix(authority: Pubkey, pool: &PoolAddresses, proof_type: ProofType) -> Instruction {
+    let vk_account = match proof_type {
+        ProofType::Deposit => pool.deposit_vk,
+        _ => pool.withdraw_vk,
+    };
+    ix(
+        accounts::CreateVerificationKeyAccount {
+            authority,
+            pool_config: pool.pool_config,
+            vk_account,
+            system_program: system_program::ID,
+        },
+        instruction::CreateVerificationKeyAccount { proof_type },
+    )
+}
+
+fn set_vk_ix(
+    authority: Pubkey,
+    pool: &PoolAddresses,
+    proof_type: ProofType,
+    vk_account: Pubkey,
+    vk: &SnarkjsVk,
+) -> Instruction {
+    ix(
+        accounts::SetVerificationKeyData {
+            authority,
+            pool_config: pool.pool_config,
+            vk_account,
+            circuit_descriptor: None,
+            merkle_tree: pool.merkle_tree,
+        },
+        instruction::SetVerificationKeyData {
+            proof_type,
+            vk_alpha_g1: g1(&vk.vk_alpha_1),
+            vk_beta_g2: g2(&vk.vk_beta_2),
+            vk_gamma_g2: g2(&vk.vk_gamma_2),
+            vk_delta_g2: g2(&vk.vk_delta_2),
+            vk_ic: vk.ic.iter().map(|p| g1(p)).collect(),
+            ceremony_transcript_hash: [0u8; 32],
+            ceremony_contributors: 0,
+        },
+    )
+}
+
+fn token_balance(rpc: &RpcClient, key: &Pubkey) -> DemoResult<u64> {
+    Ok(rpc.get_token_account_balance(key)?.amount.parse()?)
+}
+
+fn main() -> DemoResult {
+    let args = parse_args()?;
+    let f = fixture();
+    let rpc = RpcClient::new_with_commitment(args.url.clone(), CommitmentConfig::confirmed());
+    let payer = read_keypair_file(&args.keypair)
+        .map_err(|e| format!("reading payer keypair {}: {e}", args.keypair))?;
+    let demo = Demo { rpc, payer };
+
+    let amount: u64 = f.amount.parse()?;
+    let relayer_fee: u64 = f.relayer_fee.parse()?;
+    let asset_id = hex32(&f.asset_id);
+    let commitment = hex32(&f.commitment);
+    let merkle_root = hex32(&f.merkle_root);
+    let nullifier_hash = hex32(&f.nullifier_hash);
+    let mint = keypair_from_seed(&MINT_SEED)?;
+    let relayer = Keypair::try_from(hex::decode(&f.relayer_keypair)?.as_slice())?;
+    let recipient = Pubkey::new_from_array(hex32(&f.recipient));
+    let authority = Keypair::new();
+    assert_eq!(
+        mint.pubkey().to_bytes(),
+        hex32(&f.mint),
+        "fixture mint seed"
+    );
+
+    let pool = PoolAddresses::derive(&authority.pubkey(), &asset_id);
+    let depositor_token = get_associated_token_address(&demo.payer.pubkey(), &mint.pubkey());
+    let recipient_token = get_associated_token_address(&recipient, &mint.pubkey());
+    let relayer_token = get_associated_token_address(&relayer.pubkey(), &mint.pubkey());
+    let (spent_nullifier, _) =
+        SpentNullifierV2::find_pda(&psol_privacy_v2::ID, &pool.pool_config, &nullifier_hash);
+
+    println!("cluster                  {}", args.url);
+    println!("program                  {}", psol_privacy_v2::ID);
+    println!("payer / depositor        {}", demo.payer.pubkey());
+    println!("pool authority           {}", authority.pubkey());
+    println!("relayer                  {}", relayer.pubkey());
+    println!("recipient                {recipient}");
+    println!("mint                     {}", mint.pubkey());
+    println!("asset_id                 {}", hex::encode(asset_id));
+    println!("depositor token          {depositor_token}");
+    println!("recipient token          {recipient_token}");
+    println!("relayer token            {relayer_token}");
+    println!("pool accounts:");
+    pool.print();
+    println!("  {:<22} {spent_nullifier}", "spent_nullifier");
+
+    // ---- 1-2. funding, mint and token accounts ----
+    println!("setup:");
+    demo.faucet(LAMPORTS_PER_SOL)?;
+    demo.fund(
+        &[authority.pubkey(), relayer.pubkey()],
+        LAMPORTS_PER_SOL / 5,
+    )?;
+    demo.ensure_mint(&mint)?;
+    let payer = demo.payer.pubkey();
+    demo.send(
+        "token accounts and mint_to",
+        &[
+            create_associated_token_account_idempotent(
+                &payer,
+                &payer,
+                &mint.pubkey(),
+                &spl_token::id(),
+            ),
+            create_associated_token_account_idempotent(
+                &payer,
+                &recipient,
+                &mint.pubkey(),
+                &spl_token::id(),
+            ),
+            create_associated_token_account_idempotent(
+                &payer,
+                &relayer.pubkey(),
+                &mint.pubkey(),
+                &spl_token::id(),
+            ),
+            spl_token::instruction::mint_to(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &depositor_token,
+                &mint.pubkey(),
+                &[],
+                amount,
+            )?,
+        ],
+        &[&demo.payer, &mint],
+    )?;
+
+    // ---- 3-4. pool, registries, buffer, asset, VKs ----
+    println!("pool:");
+    let auth = authority.pubkey();
+    let setup = [
+        (
+            "initialize_pool_v2",
+            ix(
+                accounts::InitializePoolV2 {
+                    authority: auth,
+                    pool_config: pool.pool_config,
+                    merkle_tree: pool.merkle_tree,
+                    system_program: system_program::ID,
+                },
+                instruction::InitializePoolV2 {
+                    tree_depth: f.tree_depth,
+                    root_history_size: ROOT_HISTORY_SIZE,
+                    compliance_recovery: false,
+                    pool_id: POOL_ID,
+                },
+            ),
+        ),
+        (
+            "initialize_pool_registries",
+            ix(
+                accounts::InitializePoolRegistries {
+                    authority: auth,
+                    pool_config: pool.pool_config,
+                    relayer_registry: pool.relayer_registry,
+                    compliance_config: pool.compliance_config,
+                    system_program: system_program::ID,
+                },
+                instruction::InitializePoolRegistries {},
+            ),
+        ),
+        (
+            "initialize_pending_deposits_buffer",
+            ix(
+                accounts::InitializePendingDepositsBuffer {
+                    authority: auth,
+                    pool_config: pool.pool_config,
+                    pending_buffer: pool.pending_buffer,
+                    system_program: system_program::ID,
+                },
+                instruction::InitializePendingDepositsBuffer {},
+            ),
+        ),
+        (
+            "register_asset",
+            ix(
+                accounts::RegisterAsset {
+                    authority: auth,
+                    pool_config: pool.pool_config,
+                    mint: mint.pubkey(),
+                    asset_vault: pool.asset_vault,
+                    vault_token_account: pool.vault_token,
+                    token_program: spl_token::id(),
+                    system_program: system_program::ID,
+                    token_metadata: None,
+                },
+                instruction::RegisterAsset { asset_id },
+            ),
+        ),
+        (
+            "create deposit VK account",
+            create_vk_ix(auth, &pool, ProofType::Deposit),
+        ),
+        (
+            "set deposit VK",
+            set_vk_ix(
+                auth,
+                &pool,
+                ProofType::Deposit,
+                pool.deposit_vk,
+                &deposit_vk(),
+            ),
+        ),
+        (
+            "create withdraw VK account",
+            create_vk_ix(auth, &pool, ProofType::Withdraw),
+        ),
+        (
+            "set withdraw VK",
+            set_vk_ix(
+                auth,
+                &pool,
+                ProofType::Withdraw,
+                pool.withdraw_vk,
+                &withdraw_vk(),
+            ),
+        ),
+    ];
+    for (label, ix) in setup {
+        demo.send(label, &[ix], &[&authority])?;
+    }
+
+    // ---- 5. deposit -> batch -> withdraw ----
+    println!("roundtrip:");
+    demo.send(
+        "deposit_masp",
+        &[ix(
+            accounts::DepositMasp {
+                depositor: payer,
+                pool_config: pool.pool_config,
+                global_config: pool.global_config,
+                authority: auth,
+                merkle_tree: pool.merkle_tree,
+                pending_buffer: pool.pending_buffer,
+                asset_vault: pool.asset_vault,
+                vault_token_account: Some(pool.vault_token),
+                user_token_account: Some(depositor_token),
+                mint: Some(mint.pubkey()),
+                deposit_vk: pool.deposit_vk,
+                token_program: spl_token::id(),
+                system_program: system_program::ID,
+                instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+                proof_stats: None,
+                anonymity_stats: None,
+                commitment_reservation: CommitmentReservation::find_pda(
+                    &psol_privacy_v2::ID,
+                    &pool.pool_config,
+                    &commitment,
+                )
+                .0,
+                risk_tiers: None,
+            },
+            instruction::DepositMasp {
+                amount,
+                commitment,
+                asset_id,
+                proof_data: proof_bytes(&f.deposit.proof),
+                encrypted_note: None,
+            },
+        )],
+        &[&demo.payer],
+    )?;
+    demo.send(
+        "batch_process_deposits",
+        &[ix(
+            accounts::BatchProcessDeposits {
+                batcher: auth,
+                pool_config: pool.pool_config,
+                global_config: pool.global_config,
+                merkle_tree: pool.merkle_tree,
+                pending_buffer: pool.pending_buffer,
+                batcher_role: None,
+                deposit_hook: None,
+                callback_program: None,
+            },
+            instruction::BatchProcessDeposits {
+                max_to_process: 1,
+                target_cu_budget: None,
+                expected_batch_seq: None,
+            },
+        )],
+        &[&authority],
+    )?;
+    demo.send(
+        "withdraw_masp",
+        &[ix(
+            accounts::WithdrawMasp {
+                relayer: relayer.pubkey(),
+                pool_config: pool.pool_config,
+                global_config: pool.global_config,
+                merkle_tree: pool.merkle_tree,
+                vk_account: pool.withdraw_vk,
+                asset_vault: pool.asset_vault,
+                vault_token_account: Some(pool.vault_token),
+                recipient_token_account: Some(recipient_token),
+                relayer_token_account: Some(relayer_token),
+                spent_nullifier,
+                relayer_registry: pool.relayer_registry,
+                relayer_node: None,
+                yield_registry: None,
+                token_program: spl_token::id(),
+                system_program: system_program::ID,
+                instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+                proof_stats: None,
+                rebate_ledger: None,
+                migration_window: None,
+                deferred_events: None,
+                recipient_wallet: None,
+                rent_reserve: None,
+                rent_advance: None,
+                mint: None,
+                anonymity_stats: None,
+                parameter_snapshot: None,
+                recipient_ata: None,
+                associated_token_program: None,
+                fee_payer: None,
+                treasury_token_account: None,
+                withdrawal_commit: None,
+                fee_quote: None,
+                risk_tiers: None,
+                withdrawal_window: None,
+            },
+            instruction::WithdrawMasp {
+                proof_data: proof_bytes(&f.withdraw.proof),
+                merkle_root,
+                nullifier_hash,
+                recipient,
+                amount,
+                asset_id,
+                relayer_fee,
+            },
+        )],
+        &[&relayer],
+    )?;
+
+    println!("balances:");
+    println!(
+        "  vault      {}",
+        token_balance(&demo.rpc, &pool.vault_token)?
+    );
+    println!(
+        "  recipient  {}",
+        token_balance(&demo.rpc, &recipient_token)?
+    );
+    println!("  relayer    {}", token_balance(&demo.rpc, &relayer_token)?);
+    Ok(())
+}
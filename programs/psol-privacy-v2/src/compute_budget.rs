@@ -0,0 +1,218 @@
+//! Recommended compute budgets
+//!
+//! Compute unit limits (and heap frames, where the default 32 KiB is not
+//! enough) that clients should request for the program's heavy
+//! instructions, exported as IDL constants and through
+//! `recommended_budget`. Each limit covers the instruction's heaviest path
+//! with headroom; Groth16 verification alone is ~350k CU (see
+//! `crypto::groth16::verify`). Instructions not listed fit the default
+//! 200k CU per-instruction limit.
+//!
+//! Before its pairing check, Groth16 verification compares the remaining
+//! compute units against what the BN254 syscalls alone will charge
+//! (`groth16_min_cu`) and fails early with `InsufficientComputeBudget`
+//! rather than running out part-way through.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::events::InsufficientComputeBudgetDetected;
+use crate::instructions::batch_process_deposits::{
+    ESTIMATED_CU_OVERHEAD, ESTIMATED_CU_PER_INSERT_BASE, ESTIMATED_CU_PER_LEVEL, MAX_BATCH_SIZE,
+};
+use crate::state::MAX_TREE_DEPTH;
+
+/// `deposit_masp`: deposit proof, token transfer, buffer append
+#[constant]
+pub const DEPOSIT_MASP_CU: u32 = 400_000;
+
+/// `deposit_masp_batch`: one deposit proof per commitment
+/// (`MAX_DEPOSIT_COMMITMENTS`), one transfer, buffer appends
+#[constant]
+pub const DEPOSIT_MASP_BATCH_CU: u32 = 1_200_000;
+
+/// `withdraw_masp` (and `withdraw_with_fee_payer_refund`): withdraw proof,
+/// nullifier PDA, transfers
+#[constant]
+pub const WITHDRAW_MASP_CU: u32 = 400_000;
+
+/// `withdraw_v2`: as `withdraw_masp`, with two nullifiers and a change note
+#[constant]
+pub const WITHDRAW_V2_CU: u32 = 450_000;
+
+/// `withdraw_yield_v2`: as `withdraw_v2`
+#[constant]
+pub const WITHDRAW_YIELD_V2_CU: u32 = 450_000;
+
+/// `private_transfer_join_split`: join-split proof, two nullifiers, two
+/// tree insertions
+#[constant]
+pub const PRIVATE_TRANSFER_CU: u32 = 550_000;
+
+/// `execute_shielded_action`: withdraw proof plus the adapter's CPIs (a
+/// multi-hop Jupiter route or a lending borrow)
+#[constant]
+pub const EXECUTE_SHIELDED_ACTION_CU: u32 = 1_000_000;
+
+/// `disclosed_path_withdraw`: the commitment, nullifier hash and one
+/// Poseidon hash per tree level (up to `MAX_DISCLOSED_PATH_TREE_DEPTH`)
+/// computed by the program; requests the transaction maximum
+#[constant]
+pub const DISCLOSED_PATH_WITHDRAW_CU: u32 = 1_400_000;
+
+/// `prove_membership`: membership proof
+#[constant]
+pub const PROVE_MEMBERSHIP_CU: u32 = 400_000;
+
+/// `batch_process_deposits`: a full batch into a tree of the maximum depth
+/// (pass `target_cu_budget` to trim smaller budgets)
+#[constant]
+pub const BATCH_PROCESS_DEPOSITS_CU: u32 = ESTIMATED_CU_OVERHEAD
+    + MAX_BATCH_SIZE as u32
+        * (ESTIMATED_CU_PER_INSERT_BASE + ESTIMATED_CU_PER_LEVEL * MAX_TREE_DEPTH as u32);
+
+/// Heap frame for instructions that copy adapter account lists and route
+/// data (`execute_shielded_action`) or several proofs' inputs
+/// (`private_transfer_join_split`)
+#[constant]
+pub const LARGE_HEAP_FRAME_BYTES: u32 = 64 * 1024;
+
+/// BN254 syscall cost of a Groth16 pairing check (four pairs)
+#[constant]
+pub const GROTH16_PAIRING_MIN_CU: u64 = 36_364 + 3 * 12_121;
+
+/// BN254 syscall cost per public input (one G1 multiplication and addition)
+#[constant]
+pub const GROTH16_PER_INPUT_MIN_CU: u64 = 3_840 + 334;
+
+/// Least a Groth16 verification over `public_inputs` inputs can cost: its
+/// BN254 syscalls, before any of the program's own work
+pub const fn groth16_min_cu(public_inputs: usize) -> u64 {
+    GROTH16_PAIRING_MIN_CU + GROTH16_PER_INPUT_MIN_CU * public_inputs as u64
+}
+
+/// Require `remaining` compute units to cover a Groth16 verification over
+/// `public_inputs` inputs
+pub fn check_verification_budget(remaining: u64, public_inputs: usize) -> Result<()> {
+    let required = groth16_min_cu(public_inputs);
+    if remaining < required {
+        msg!(
+            "{} compute units left, proof verification needs at least {}; see compute_budget::recommended_budget",
+            remaining,
+            required
+        );
+        emit!(InsufficientComputeBudgetDetected {
+            remaining_units: remaining,
+            required_units: required,
+            public_inputs: public_inputs as u8,
+        });
+        return err!(PrivacyErrorV2::InsufficientComputeBudget);
+    }
+    Ok(())
+}
+
+/// `check_verification_budget` against the transaction's remaining compute
+/// units; host builds have no meter and skip it
+#[inline]
+pub fn require_verification_budget(public_inputs: usize) -> Result<()> {
+    #[cfg(any(target_os = "solana", target_arch = "bpf"))]
+    {
+        let remaining = anchor_lang::solana_program::compute_units::sol_remaining_compute_units();
+        check_verification_budget(remaining, public_inputs)
+    }
+
+    #[cfg(not(any(target_os = "solana", target_arch = "bpf")))]
+    {
+        let _ = public_inputs;
+        Ok(())
+    }
+}
+
+/// Compute budget to request for one instruction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BudgetHint {
+    /// `SetComputeUnitLimit` value
+    pub unit_limit: u32,
+    /// `RequestHeapFrame` value (`None` keeps the default 32 KiB)
+    pub heap_frame_bytes: Option<u32>,
+}
+
+/// Recommended budget for `instruction` (its snake_case name), or `None`
+/// when the default limit is enough
+pub fn recommended_budget(instruction: &str) -> Option<BudgetHint> {
+    let (unit_limit, heap_frame_bytes) = match instruction {
+        "deposit_masp" => (DEPOSIT_MASP_CU, None),
+        "deposit_masp_batch" => (DEPOSIT_MASP_BATCH_CU, None),
+        "withdraw_masp" | "withdraw_with_fee_payer_refund" => (WITHDRAW_MASP_CU, None),
+        "withdraw_v2" => (WITHDRAW_V2_CU, None),
+        "withdraw_yield_v2" => (WITHDRAW_YIELD_V2_CU, None),
+        "private_transfer_join_split" => (PRIVATE_TRANSFER_CU, Some(LARGE_HEAP_FRAME_BYTES)),
+        "execute_shielded_action" => (EXECUTE_SHIELDED_ACTION_CU, Some(LARGE_HEAP_FRAME_BYTES)),
+        "disclosed_path_withdraw" => (DISCLOSED_PATH_WITHDRAW_CU, None),
+        "prove_membership" => (PROVE_MEMBERSHIP_CU, None),
+        "batch_process_deposits" => (BATCH_PROCESS_DEPOSITS_CU, None),
+        _ => return None,
+    };
+    Some(BudgetHint {
+        unit_limit,
+        heap_frame_bytes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Most a transaction may request
+    const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+    const MAX_HEAP_FRAME_BYTES: u32 = 256 * 1024;
+
+    const LISTED: [&str; 11] = [
+        "deposit_masp",
+        "deposit_masp_batch",
+        "withdraw_masp",
+        "withdraw_with_fee_payer_refund",
+        "withdraw_v2",
+        "withdraw_yield_v2",
+        "private_transfer_join_split",
+        "execute_shielded_action",
+        "disclosed_path_withdraw",
+        "prove_membership",
+        "batch_process_deposits",
+    ];
+
+    #[test]
+    fn test_budgets_are_requestable() {
+        for name in LISTED {
+            let hint = recommended_budget(name).unwrap();
+            assert!(hint.unit_limit > 200_000, "{name}");
+            assert!(hint.unit_limit <= MAX_COMPUTE_UNIT_LIMIT, "{name}");
+            if let Some(heap) = hint.heap_frame_bytes {
+                assert!(heap % 1024 == 0 && heap <= MAX_HEAP_FRAME_BYTES, "{name}");
+            }
+        }
+        assert_eq!(recommended_budget("pause_pool_v2"), None);
+    }
+
+    #[test]
+    fn test_verification_budget_check() {
+        let required = groth16_min_cu(4);
+        assert!(check_verification_budget(required, 4).is_ok());
+        let err = check_verification_budget(required - 1, 4).unwrap_err();
+        assert!(format!("{err:?}").contains("InsufficientComputeBudget"));
+
+        // Every listed proof-verifying budget clears the floor with room to spare
+        for limit in [DEPOSIT_MASP_CU, WITHDRAW_MASP_CU, PRIVATE_TRANSFER_CU] {
+            assert!(u64::from(limit) > 2 * groth16_min_cu(crate::crypto::MAX_PUBLIC_INPUTS));
+        }
+    }
+
+    #[test]
+    fn test_batch_budget_fits_full_batch() {
+        use crate::instructions::batch_process_deposits::max_inserts_for_budget;
+        assert_eq!(
+            max_inserts_for_budget(BATCH_PROCESS_DEPOSITS_CU, MAX_TREE_DEPTH),
+            MAX_BATCH_SIZE
+        );
+    }
+}
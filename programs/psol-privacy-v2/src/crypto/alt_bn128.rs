@@ -24,6 +24,17 @@
 //! - MUL = 2: G1 scalar multiplication (input: 128 bytes)
 //! - PAIRING = 3: Pairing check
 //!
+//! # Compressed Points
+//! G1 compresses to its 32-byte x coordinate and G2 to its 64-byte x
+//! (imaginary first, as above). The top two bits of the first byte carry
+//! flags, which is safe because the Fp modulus is below 2^254:
+//! - 0x80: y is the larger of the two square roots
+//! - 0x40: point at infinity
+//!
+//! The all-zero encoding is the identity. On Solana, decompression goes
+//! through `sol_alt_bn128_compression` (G1_COMPRESS = 0, G1_DECOMPRESS = 1,
+//! G2_COMPRESS = 2, G2_DECOMPRESS = 3).
+//!
 //! # Important: Negation uses Fp, not Fr
 //! G1 point negation: -P = (x, p - y) where p is the BASE FIELD modulus.
 //!
@@ -52,6 +63,23 @@ const ALT_BN128_MUL: u64 = 2;
 /// Pairing check (opcode 3)
 const ALT_BN128_PAIRING: u64 = 3;
 
+/// Compression opcodes (sol_alt_bn128_compression)
+const ALT_BN128_G1_COMPRESS: u64 = 0;
+const ALT_BN128_G1_DECOMPRESS: u64 = 1;
+const ALT_BN128_G2_COMPRESS: u64 = 2;
+const ALT_BN128_G2_DECOMPRESS: u64 = 3;
+
+/// Compressed G1 point size in bytes
+pub const G1_COMPRESSED_SIZE: usize = 32;
+/// Compressed G2 point size in bytes
+pub const G2_COMPRESSED_SIZE: usize = 64;
+
+/// Flag bit: y is the lexicographically larger root
+const COMPRESSED_Y_GREATEST: u8 = 0x80;
+/// Flag bit: point at infinity
+const COMPRESSED_INFINITY: u8 = 0x40;
+const COMPRESSED_FLAGS_MASK: u8 = COMPRESSED_Y_GREATEST | COMPRESSED_INFINITY;
+
 // ============================================================================
 // SOLANA SYSCALL IMPLEMENTATIONS
 // Uses cfg(any(...)) to support both old (bpf) and new (solana) toolchains
@@ -65,6 +93,13 @@ extern "C" {
         input_size: u64,
         result: *mut u8,
     ) -> u64;
+
+    fn sol_alt_bn128_compression(
+        op: u64,
+        input: *const u8,
+        input_size: u64,
+        result: *mut u8,
+    ) -> u64;
 }
 
 /// G1 point addition: result = a + b
@@ -129,6 +164,82 @@ pub fn pairing_check_4(pairs: &[[u8; 192]; 4]) -> Result<bool> {
     Ok(result[31] == 1 && result[0..31].iter().all(|&b| b == 0))
 }
 
+/// Compress a G1 point to 32 bytes
+#[cfg(any(target_os = "solana", target_arch = "bpf"))]
+pub fn g1_compress(point: &[u8; 64]) -> Result<[u8; 32]> {
+    let mut result = [0u8; 32];
+    let ret = unsafe {
+        sol_alt_bn128_compression(
+            ALT_BN128_G1_COMPRESS,
+            point.as_ptr(),
+            64,
+            result.as_mut_ptr(),
+        )
+    };
+
+    if ret != 0 {
+        return Err(PrivacyErrorV2::CryptographyError.into());
+    }
+    Ok(result)
+}
+
+/// Decompress a 32-byte G1 point
+#[cfg(any(target_os = "solana", target_arch = "bpf"))]
+pub fn g1_decompress(compressed: &[u8; 32]) -> Result<[u8; 64]> {
+    let mut result = [0u8; 64];
+    let ret = unsafe {
+        sol_alt_bn128_compression(
+            ALT_BN128_G1_DECOMPRESS,
+            compressed.as_ptr(),
+            32,
+            result.as_mut_ptr(),
+        )
+    };
+
+    if ret != 0 {
+        return Err(PrivacyErrorV2::CryptographyError.into());
+    }
+    Ok(result)
+}
+
+/// Compress a G2 point to 64 bytes
+#[cfg(any(target_os = "solana", target_arch = "bpf"))]
+pub fn g2_compress(point: &[u8; 128]) -> Result<[u8; 64]> {
+    let mut result = [0u8; 64];
+    let ret = unsafe {
+        sol_alt_bn128_compression(
+            ALT_BN128_G2_COMPRESS,
+            point.as_ptr(),
+            128,
+            result.as_mut_ptr(),
+        )
+    };
+
+    if ret != 0 {
+        return Err(PrivacyErrorV2::CryptographyError.into());
+    }
+    Ok(result)
+}
+
+/// Decompress a 64-byte G2 point
+#[cfg(any(target_os = "solana", target_arch = "bpf"))]
+pub fn g2_decompress(compressed: &[u8; 64]) -> Result<[u8; 128]> {
+    let mut result = [0u8; 128];
+    let ret = unsafe {
+        sol_alt_bn128_compression(
+            ALT_BN128_G2_DECOMPRESS,
+            compressed.as_ptr(),
+            64,
+            result.as_mut_ptr(),
+        )
+    };
+
+    if ret != 0 {
+        return Err(PrivacyErrorV2::CryptographyError.into());
+    }
+    Ok(result)
+}
+
 // ============================================================================
 // HOST (ARKWORKS) IMPLEMENTATIONS
 // ============================================================================
@@ -204,6 +315,20 @@ fn g1_to_bytes(point: G1Affine) -> [u8; 64] {
     bytes
 }
 
+/// G2 encoding: x1 || x0 || y1 || y0 (imaginary first, then real)
+#[cfg(not(any(target_os = "solana", target_arch = "bpf")))]
+fn g2_to_bytes(point: G2Affine) -> [u8; 128] {
+    if point.is_zero() {
+        return [0u8; 128];
+    }
+    let mut bytes = [0u8; 128];
+    bytes[0..32].copy_from_slice(&fq_to_bytes(point.x.c1)); // x imaginary
+    bytes[32..64].copy_from_slice(&fq_to_bytes(point.x.c0)); // x real
+    bytes[64..96].copy_from_slice(&fq_to_bytes(point.y.c1)); // y imaginary
+    bytes[96..128].copy_from_slice(&fq_to_bytes(point.y.c0)); // y real
+    bytes
+}
+
 #[cfg(not(any(target_os = "solana", target_arch = "bpf")))]
 fn bytes_to_g2(bytes: &[u8; 128]) -> Option<G2Affine> {
     if bytes.iter().all(|&b| b == 0) {
@@ -268,6 +393,84 @@ pub fn pairing_check_4(pairs: &[[u8; 192]; 4]) -> Result<bool> {
     Ok(result.is_zero())
 }
 
+/// Split a compressed x coordinate into (x bytes without flags, y_greatest).
+/// Returns None for the point at infinity.
+#[cfg(not(any(target_os = "solana", target_arch = "bpf")))]
+fn strip_compression_flags<const N: usize>(compressed: &[u8; N]) -> Option<([u8; N], bool)> {
+    let flags = compressed[0] & COMPRESSED_FLAGS_MASK;
+    if flags & COMPRESSED_INFINITY != 0 {
+        return None;
+    }
+    let mut x = *compressed;
+    x[0] &= !COMPRESSED_FLAGS_MASK;
+    Some((x, flags & COMPRESSED_Y_GREATEST != 0))
+}
+
+/// Compress a G1 point using arkworks
+#[cfg(not(any(target_os = "solana", target_arch = "bpf")))]
+pub fn g1_compress(point: &[u8; 64]) -> Result<[u8; 32]> {
+    let p = bytes_to_g1(point).ok_or(PrivacyErrorV2::CryptographyError)?;
+    if p.is_zero() {
+        return Ok([0u8; 32]);
+    }
+    let mut result = fq_to_bytes(p.x);
+    if p.y > -p.y {
+        result[0] |= COMPRESSED_Y_GREATEST;
+    }
+    Ok(result)
+}
+
+/// Decompress a G1 point using arkworks
+#[cfg(not(any(target_os = "solana", target_arch = "bpf")))]
+pub fn g1_decompress(compressed: &[u8; 32]) -> Result<[u8; 64]> {
+    if compressed.iter().all(|&b| b == 0) {
+        return Ok([0u8; 64]);
+    }
+    let Some((x_bytes, greatest)) = strip_compression_flags(compressed) else {
+        return Ok([0u8; 64]);
+    };
+    let x = bytes_to_fq(&x_bytes).ok_or(PrivacyErrorV2::CryptographyError)?;
+    let point = G1Affine::get_point_from_x_unchecked(x, greatest)
+        .filter(|p| p.is_in_correct_subgroup_assuming_on_curve())
+        .ok_or(PrivacyErrorV2::CryptographyError)?;
+    Ok(g1_to_bytes(point))
+}
+
+/// Compress a G2 point using arkworks
+#[cfg(not(any(target_os = "solana", target_arch = "bpf")))]
+pub fn g2_compress(point: &[u8; 128]) -> Result<[u8; 64]> {
+    let p = bytes_to_g2(point).ok_or(PrivacyErrorV2::CryptographyError)?;
+    if p.is_zero() {
+        return Ok([0u8; 64]);
+    }
+    let mut result = [0u8; 64];
+    result[0..32].copy_from_slice(&fq_to_bytes(p.x.c1));
+    result[32..64].copy_from_slice(&fq_to_bytes(p.x.c0));
+    if p.y > -p.y {
+        result[0] |= COMPRESSED_Y_GREATEST;
+    }
+    Ok(result)
+}
+
+/// Decompress a G2 point using arkworks
+#[cfg(not(any(target_os = "solana", target_arch = "bpf")))]
+pub fn g2_decompress(compressed: &[u8; 64]) -> Result<[u8; 128]> {
+    if compressed.iter().all(|&b| b == 0) {
+        return Ok([0u8; 128]);
+    }
+    let Some((x_bytes, greatest)) = strip_compression_flags(compressed) else {
+        return Ok([0u8; 128]);
+    };
+    let x_c1 =
+        bytes_to_fq(x_bytes[0..32].try_into().unwrap()).ok_or(PrivacyErrorV2::CryptographyError)?;
+    let x_c0 = bytes_to_fq(x_bytes[32..64].try_into().unwrap())
+        .ok_or(PrivacyErrorV2::CryptographyError)?;
+    let point = G2Affine::get_point_from_x_unchecked(Fq2::new(x_c0, x_c1), greatest)
+        .filter(|p| p.is_in_correct_subgroup_assuming_on_curve())
+        .ok_or(PrivacyErrorV2::CryptographyError)?;
+    Ok(g2_to_bytes(point))
+}
+
 // ============================================================================
 // SHARED HELPERS
 // ============================================================================
@@ -463,29 +666,31 @@ mod tests {
         );
     }
 
-    /// Helper to convert arkworks G2 point to bytes
-    /// G2 encoding: x1 || x0 || y1 || y0 (imaginary first, then real)
-    /// This matches snarkjs/Ethereum BN254 serialization format.
+    #[test]
     #[cfg(not(any(target_os = "solana", target_arch = "bpf")))]
-    fn g2_to_bytes(point: G2Affine) -> [u8; 128] {
-        if point.is_zero() {
-            return [0u8; 128];
-        }
-
-        let mut bytes = [0u8; 128];
-
-        // Fq2 = c0 + c1*u where u² = -1, so c1 is imaginary, c0 is real
-        // Output layout: x_c1 || x_c0 || y_c1 || y_c0 (imaginary first!)
-        let x_c0 = point.x.c0; // real
-        let x_c1 = point.x.c1; // imaginary
-        let y_c0 = point.y.c0; // real
-        let y_c1 = point.y.c1; // imaginary
-
-        bytes[0..32].copy_from_slice(&fq_to_bytes(x_c1)); // x imaginary
-        bytes[32..64].copy_from_slice(&fq_to_bytes(x_c0)); // x real
-        bytes[64..96].copy_from_slice(&fq_to_bytes(y_c1)); // y imaginary
-        bytes[96..128].copy_from_slice(&fq_to_bytes(y_c0)); // y real
-
-        bytes
+    fn test_point_compression_roundtrip() {
+        let g2 = g2_to_bytes(G2Affine::generator());
+        let neg_g1 = g1_negate(&G1_GEN).unwrap();
+
+        let c = g1_compress(&G1_GEN).unwrap();
+        assert_eq!(g1_decompress(&c).unwrap(), G1_GEN);
+
+        // -G shares x with G; only the sign flag differs
+        let neg_c = g1_compress(&neg_g1).unwrap();
+        assert_eq!(neg_c[0] ^ c[0], COMPRESSED_Y_GREATEST);
+        assert_eq!(g1_decompress(&neg_c).unwrap(), neg_g1);
+
+        let c2 = g2_compress(&g2).unwrap();
+        assert_eq!(g2_decompress(&c2).unwrap(), g2);
+        assert_eq!(&c2[1..32], &g2[1..32]);
+
+        // Identity round-trips through the all-zero encoding
+        assert_eq!(g1_compress(&[0u8; 64]).unwrap(), [0u8; 32]);
+        assert_eq!(g2_decompress(&[0u8; 64]).unwrap(), [0u8; 128]);
+
+        // x >= p is rejected
+        let mut bad = [0xffu8; 32];
+        bad[0] &= !COMPRESSED_FLAGS_MASK;
+        assert!(g1_decompress(&bad).is_err());
     }
 }
@@ -25,6 +25,13 @@
 //! | x_imag (32) | x_real (32) | y_imag (32) | y_real (32) |
 //! ```
 //!
+//! ## Compressed proof: 1 + 128 bytes
+//! ```text
+//! | 0x02 | A (G1, 32 bytes) | B (G2, 64 bytes) | C (G1, 32 bytes) |
+//! ```
+//! Points are decompressed on-chain before verification; see `alt_bn128`
+//! for the flag bits.
+//!
 //! ## Scalar: 32 bytes big-endian
 //!
 //! # Serializing from snarkjs
@@ -55,7 +62,10 @@
 
 use anchor_lang::prelude::*;
 
-use super::alt_bn128::{g1_add, g1_mul, g1_negate, make_pairing_element, pairing_check_4};
+use super::alt_bn128::{
+    g1_add, g1_compress, g1_decompress, g1_mul, g1_negate, g2_compress, g2_decompress,
+    make_pairing_element, pairing_check_4,
+};
 use super::field::{is_g1_identity, is_valid_fr};
 use crate::error::PrivacyErrorV2;
 
@@ -66,6 +76,21 @@ use crate::error::PrivacyErrorV2;
 /// Groth16 proof size in bytes: A(64) + B(128) + C(64)
 pub const PROOF_SIZE: usize = 256;
 
+/// Format tag: Groth16 over BN254, uncompressed points (A || B || C)
+pub const PROOF_FORMAT_GROTH16_BN254_UNCOMPRESSED: u8 = 0x01;
+
+/// Tagged proof size in bytes: format tag(1) + proof body
+pub const TAGGED_PROOF_SIZE: usize = 1 + PROOF_SIZE;
+
+/// Format tag: Groth16 over BN254, compressed points (A || B || C)
+pub const PROOF_FORMAT_GROTH16_BN254_COMPRESSED: u8 = 0x02;
+
+/// Compressed proof body size in bytes: A(32) + B(64) + C(32)
+pub const COMPRESSED_PROOF_SIZE: usize = 128;
+
+/// Tagged compressed proof size in bytes: format tag(1) + compressed body
+pub const TAGGED_COMPRESSED_PROOF_SIZE: usize = 1 + COMPRESSED_PROOF_SIZE;
+
 /// Maximum number of public inputs supported.
 /// Groth16 verification requires VK IC array of size = num_inputs + 1.
 pub const MAX_PUBLIC_INPUTS: usize = 16;
@@ -96,12 +121,28 @@ pub struct Proof {
 }
 
 impl Proof {
-    /// Parse proof from 256-byte array.
+    /// Parse proof bytes.
+    ///
+    /// Accepts a tagged blob (`[format_tag, A || B || C]`: 257 bytes
+    /// uncompressed, 129 bytes compressed) or a legacy untagged 256-byte
+    /// body, which is read as `PROOF_FORMAT_GROTH16_BN254_UNCOMPRESSED`.
     pub fn from_bytes(data: &[u8]) -> Result<Self> {
-        if data.len() != PROOF_SIZE {
-            return Err(PrivacyErrorV2::InvalidProofFormat.into());
+        match (data.len(), data.first()) {
+            (PROOF_SIZE, _) => Self::from_uncompressed(data),
+            (TAGGED_PROOF_SIZE, Some(&PROOF_FORMAT_GROTH16_BN254_UNCOMPRESSED)) => {
+                Self::from_uncompressed(&data[1..])
+            }
+            (TAGGED_COMPRESSED_PROOF_SIZE, Some(&PROOF_FORMAT_GROTH16_BN254_COMPRESSED)) => {
+                Self::from_compressed(&data[1..])
+            }
+            (TAGGED_PROOF_SIZE | TAGGED_COMPRESSED_PROOF_SIZE, _) => {
+                Err(PrivacyErrorV2::UnsupportedProofFormat.into())
+            }
+            _ => Err(PrivacyErrorV2::InvalidProofFormat.into()),
         }
+    }
 
+    fn from_uncompressed(data: &[u8]) -> Result<Self> {
         let mut a = [0u8; 64];
         let mut b = [0u8; 128];
         let mut c = [0u8; 64];
@@ -113,6 +154,14 @@ impl Proof {
         Ok(Self { a, b, c })
     }
 
+    fn from_compressed(data: &[u8]) -> Result<Self> {
+        Ok(Self {
+            a: g1_decompress(data[0..32].try_into().unwrap())?,
+            b: g2_decompress(data[32..96].try_into().unwrap())?,
+            c: g1_decompress(data[96..128].try_into().unwrap())?,
+        })
+    }
+
     /// Serialize proof to 256-byte array.
     pub fn to_bytes(&self) -> [u8; PROOF_SIZE] {
         let mut bytes = [0u8; PROOF_SIZE];
@@ -121,6 +170,26 @@ impl Proof {
         bytes[192..256].copy_from_slice(&self.c);
         bytes
     }
+
+    /// Serialize proof with the format tag prefix.
+    pub fn to_tagged_bytes(&self) -> [u8; TAGGED_PROOF_SIZE] {
+        let mut bytes = [0u8; TAGGED_PROOF_SIZE];
+        bytes[0] = PROOF_FORMAT_GROTH16_BN254_UNCOMPRESSED;
+        bytes[1..].copy_from_slice(&self.to_bytes());
+        bytes
+    }
+
+    /// Serialize proof in the tagged compressed format.
+    ///
+    /// Fails if any element is not a valid curve point.
+    pub fn to_compressed_tagged_bytes(&self) -> Result<[u8; TAGGED_COMPRESSED_PROOF_SIZE]> {
+        let mut bytes = [0u8; TAGGED_COMPRESSED_PROOF_SIZE];
+        bytes[0] = PROOF_FORMAT_GROTH16_BN254_COMPRESSED;
+        bytes[1..33].copy_from_slice(&g1_compress(&self.a)?);
+        bytes[33..97].copy_from_slice(&g2_compress(&self.b)?);
+        bytes[97..129].copy_from_slice(&g1_compress(&self.c)?);
+        Ok(bytes)
+    }
 }
 
 /// Groth16 verification key.
@@ -188,13 +257,16 @@ impl VerificationKey {
 /// * `Err(_)` - cryptographic error (invalid points, non-canonical inputs, etc.)
 ///
 /// # Compute Cost
-/// ~350,000 CU on Solana mainnet. Set compute budget explicitly.
+/// ~350,000 CU on Solana mainnet. Set compute budget explicitly; with
+/// fewer than `compute_budget::groth16_min_cu` units left this fails with
+/// `InsufficientComputeBudget` before any curve work.
 pub fn verify(vk: &VerificationKey, proof: &Proof, public_inputs: &[Scalar]) -> Result<bool> {
     // Validate input count
     if public_inputs.len() > MAX_PUBLIC_INPUTS {
         return Err(PrivacyErrorV2::InvalidPublicInputs.into());
     }
     vk.validate_for_inputs(public_inputs.len())?;
+    crate::compute_budget::require_verification_budget(public_inputs.len())?;
 
     // Validate all public inputs are canonical
     for input in public_inputs {
@@ -418,6 +490,63 @@ mod tests {
         assert!(Proof::from_bytes(&data).is_err());
     }
 
+    #[test]
+    fn test_tagged_proof_format() {
+        let proof = Proof {
+            a: [1u8; 64],
+            b: [2u8; 128],
+            c: [3u8; 64],
+        };
+        let tagged = proof.to_tagged_bytes();
+        assert_eq!(tagged[0], PROOF_FORMAT_GROTH16_BN254_UNCOMPRESSED);
+
+        // Tagged and legacy encodings parse to the same proof
+        let parsed = Proof::from_bytes(&tagged).unwrap();
+        assert_eq!(parsed.to_bytes(), proof.to_bytes());
+
+        // Unknown format tags are rejected rather than guessed at
+        let mut unknown = tagged;
+        unknown[0] = 0x02;
+        assert!(Proof::from_bytes(&unknown).is_err());
+    }
+
+    #[test]
+    #[cfg(not(target_arch = "bpf"))]
+    fn test_compressed_proof_format() {
+        use ark_bn254::G2Affine;
+        use ark_ec::AffineRepr;
+        use ark_ff::{BigInteger, PrimeField};
+
+        let mut g1 = [0u8; 64];
+        g1[31] = 1;
+        g1[63] = 2;
+        let mut two = [0u8; 32];
+        two[31] = 2;
+
+        // G2 generator in x_imag || x_real || y_imag || y_real order
+        let g2 = G2Affine::generator();
+        let mut b = [0u8; 128];
+        for (i, f) in [g2.x.c1, g2.x.c0, g2.y.c1, g2.y.c0].iter().enumerate() {
+            b[i * 32..(i + 1) * 32].copy_from_slice(&f.into_bigint().to_bytes_be());
+        }
+
+        let proof = Proof {
+            a: g1,
+            b,
+            c: g1_mul(&g1, &two).unwrap(),
+        };
+        let compressed = proof.to_compressed_tagged_bytes().unwrap();
+        assert_eq!(compressed.len(), TAGGED_COMPRESSED_PROOF_SIZE);
+
+        let parsed = Proof::from_bytes(&compressed).unwrap();
+        assert_eq!(parsed.to_bytes(), proof.to_bytes());
+
+        // The compressed length only accepts the compressed tag
+        let mut wrong_tag = compressed;
+        wrong_tag[0] = PROOF_FORMAT_GROTH16_BN254_UNCOMPRESSED;
+        assert!(Proof::from_bytes(&wrong_tag).is_err());
+    }
+
     #[test]
     fn test_vk_validation() {
         let vk = VerificationKey {
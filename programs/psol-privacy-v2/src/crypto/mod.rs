@@ -7,6 +7,8 @@
 //! - `groth16`: Groth16 proof verification
 //! - `keccak`: Keccak256 hashing utilities
 //! - `public_inputs`: Builders for circuit public inputs
+//! - `range_check`: Amount range-check hook (no-op until a range proof scheme ships)
+//! - `relayer_job`: User-signed relayer jobs and their ed25519 verification
 //!
 //! # Encoding Convention
 //! All field elements are 32 bytes, BIG-ENDIAN.
@@ -21,6 +23,8 @@ pub mod keccak;
 pub mod poseidon;
 pub mod precomputed_zeros;
 pub mod public_inputs;
+pub mod range_check;
+pub mod relayer_job;
 
 // NOTE: Poseidon vector tests are in tests/poseidon_vectors_test.rs (integration test)
 // Run: cargo test -p psol-privacy-v2 --test poseidon_vectors_test -- --nocapture
@@ -48,7 +52,10 @@ pub const BN254_SCALAR_MODULUS: [u8; 32] = BN254_FR_MODULUS;
 // RE-EXPORTS: alt_bn128
 // ============================================================================
 
-pub use alt_bn128::{g1_add, g1_mul, g1_negate, make_pairing_element, pairing_check_4};
+pub use alt_bn128::{
+    g1_add, g1_compress, g1_decompress, g1_mul, g1_negate, g2_compress, g2_decompress,
+    make_pairing_element, pairing_check_4, G1_COMPRESSED_SIZE, G2_COMPRESSED_SIZE,
+};
 
 // Backward compatibility
 pub fn verify_pairing(elements: &[[u8; 192]; 4]) -> anchor_lang::prelude::Result<bool> {
@@ -63,7 +70,8 @@ pub use poseidon::{
     compute_commitment, compute_nullifier_hash, empty_leaf_hash, hash_two_to_one, is_canonical_fr,
     is_placeholder_implementation, is_valid_scalar as poseidon_is_valid_scalar,
     is_zero as is_zero_hash, poseidon2, poseidon3, poseidon4, poseidon_hash_3, poseidon_hash_4,
-    u64_to_scalar_be, verify_commitment, Scalar as PoseidonScalarField, IS_PLACEHOLDER,
+    poseidon_params_hash, u64_to_scalar_be, verify_commitment, Scalar as PoseidonScalarField,
+    IS_PLACEHOLDER, POSEIDON_PARAMS_DOMAIN, POSEIDON_PARAMS_HASH,
 };
 
 // ============================================================================
@@ -90,8 +98,13 @@ pub use groth16::{
     ProofType,
     Scalar,
     VerificationKey,
+    COMPRESSED_PROOF_SIZE,
     MAX_PUBLIC_INPUTS,
+    PROOF_FORMAT_GROTH16_BN254_COMPRESSED,
+    PROOF_FORMAT_GROTH16_BN254_UNCOMPRESSED,
     PROOF_SIZE,
+    TAGGED_COMPRESSED_PROOF_SIZE,
+    TAGGED_PROOF_SIZE,
 };
 
 // Re-export verify_proof_from_account from this module
@@ -100,10 +113,22 @@ pub use self::verify_proof_from_account as verify_proof_account;
 /// Proof data length constant
 pub const PROOF_DATA_LEN: usize = PROOF_SIZE;
 
-/// Check if proof bytes have valid length
+// The proof length instructions accept must be the parsed proof's points
+const _: () = {
+    use core::mem::size_of;
+    assert!(PROOF_DATA_LEN == size_of::<Groth16Proof>());
+    assert!(PROOF_SIZE == 2 * size_of::<G1Point>() + size_of::<G2Point>());
+    assert!(COMPRESSED_PROOF_SIZE == 2 * G1_COMPRESSED_SIZE + G2_COMPRESSED_SIZE);
+};
+
+/// Check if proof bytes have valid length (legacy untagged, tagged, or
+/// tagged compressed)
 #[inline]
 pub fn is_valid_proof_length(data: &[u8]) -> bool {
-    data.len() == PROOF_DATA_LEN
+    matches!(
+        data.len(),
+        PROOF_DATA_LEN | TAGGED_PROOF_SIZE | TAGGED_COMPRESSED_PROOF_SIZE
+    )
 }
 
 // Legacy aliases for verification
@@ -156,10 +181,13 @@ pub use keccak::{
 
 pub use public_inputs::{
     DepositPublicInputs, JoinSplitPublicInputs, JoinSplitPublicInputsBuilder,
-    MembershipPublicInputs, WithdrawPublicInputs, WithdrawPublicInputsBuilder,
-    WithdrawV2PublicInputs, MAX_JS_INPUTS, MAX_JS_OUTPUTS, WITHDRAW_V2_SCHEMA_VERSION,
+    MembershipPublicInputs, PublicInputsKind, WithdrawPublicInputs, WithdrawPublicInputsBuilder,
+    WithdrawV2PublicInputs, MAX_JS_INPUTS, MAX_JS_OUTPUTS, PUBLIC_INPUTS_ENCODING_VERSION,
+    PUBLIC_INPUTS_HEADER_LEN, WITHDRAW_V2_SCHEMA_VERSION,
 };
 
+pub use relayer_job::{verify_relayer_job, RelayerJob, RELAYER_JOB_DOMAIN};
+
 // ============================================================================
 // CURVE UTILS COMPATIBILITY
 // ============================================================================
@@ -289,3 +317,9 @@ pub fn reduce_scalar(scalar: &Scalar) -> anchor_lang::prelude::Result<Scalar> {
         Err(crate::error::PrivacyErrorV2::InvalidPublicInputs.into())
     }
 }
+
+// ============================================================================
+// RE-EXPORTS: Range Check
+// ============================================================================
+
+pub use range_check::{check_amount_range, RangeCheckInput, RangeProofScheme};
@@ -611,6 +611,56 @@ pub fn is_canonical_fr(scalar: &Scalar) -> bool {
 
 pub const BN254_SCALAR_MODULUS: [u8; 32] = BN254_FR_MODULUS;
 
+// =============================================================================
+// Parameter Set Hash
+// =============================================================================
+
+/// Domain tag of the parameter set encoding
+pub const POSEIDON_PARAMS_DOMAIN: &[u8] = b"psol:poseidon_params:v1";
+
+/// `poseidon_params_hash()` of the constants embedded in this module
+///
+/// Recorded in every Merkle tree at init; circuit descriptors must carry
+/// the same value, so a circuit compiled against a different constant set
+/// is rejected before its VK is set.
+pub const POSEIDON_PARAMS_HASH: Scalar = [
+    0x1d, 0xb0, 0xa7, 0x45, 0xff, 0xfc, 0xd5, 0x54, 0x97, 0x79, 0x5a, 0x84, 0xfb, 0x51, 0x3e, 0xb5,
+    0x4d, 0x42, 0x8e, 0x0b, 0xe7, 0x4d, 0x24, 0x5a, 0xd7, 0xf5, 0x21, 0x87, 0xf1, 0x56, 0xf1, 0x18,
+];
+
+/// Keccak256 of the parameter sets for t = 3, 4, 5, in that order
+///
+/// Each set is encoded as `t || n_rounds_f || n_rounds_p` (one byte each)
+/// followed by C, M (row by row), P and S as 32-byte big-endian field
+/// elements, after the `POSEIDON_PARAMS_DOMAIN` tag. Circuit tooling
+/// derives the descriptor's `poseidon_params_hash` the same way from the
+/// circomlib constants it compiled with.
+pub fn poseidon_params_hash() -> Scalar {
+    use sha3::{Digest, Keccak256};
+
+    fn absorb<const T: usize>(
+        hasher: &mut Keccak256,
+        n_rounds_p: usize,
+        c: &[Fr],
+        m: &[[Fr; T]; T],
+        p: &[[Fr; T]; T],
+        s: &[Fr],
+    ) {
+        hasher.update([T as u8, N_ROUNDS_F as u8, n_rounds_p as u8]);
+        let rows = m.iter().chain(p.iter()).flat_map(|row| row.iter());
+        for x in c.iter().chain(rows).chain(s.iter()) {
+            hasher.update(fr_to_be32(x));
+        }
+    }
+
+    let mut hasher = Keccak256::new();
+    hasher.update(POSEIDON_PARAMS_DOMAIN);
+    absorb(&mut hasher, N_ROUNDS_P_T3, &C_T3, &M_T3, &P_T3, &S_T3);
+    absorb(&mut hasher, N_ROUNDS_P_T4, &C_T4, &M_T4, &P_T4, &S_T4);
+    absorb(&mut hasher, N_ROUNDS_P_T5, &C_T5, &M_T5, &P_T5, &S_T5);
+    hasher.finalize().into()
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -626,7 +676,9 @@ mod tests {
     #[test]
     fn test_not_placeholder() {
         #[inline(never)]
-        fn is_placeholder_runtime() -> bool { IS_PLACEHOLDER }
+        fn is_placeholder_runtime() -> bool {
+            IS_PLACEHOLDER
+        }
         assert!(!is_placeholder_runtime());
     }
 
@@ -695,4 +747,9 @@ mod tests {
         ];
         assert_eq!(hash, expected, "Poseidon4(0,0,0,0) mismatch");
     }
+
+    #[test]
+    fn test_params_hash_matches_constants() {
+        assert_eq!(poseidon_params_hash(), POSEIDON_PARAMS_HASH);
+    }
 }
@@ -1135,7 +1135,7 @@ pub const P_T5: [[Fr; 5]; 5] = [
   ],
 ];
 
-pub const S_T5: [Fr; 540] = [
+pub static S_T5: [Fr; 540] = [
   MontFp!("16789463359527776692258765063233607350971630674230623383979223533600140787105"),
   MontFp!("1501526742388787352232455928044474701049897539553693700465768980639111415979"),
   MontFp!("477229768268324623365003033158412143775099325596993204070284286071987300538"),
@@ -36,11 +36,12 @@ pub const MAX_JS_OUTPUTS: usize = 4;
 /// - commitment = Poseidon(secret, nullifier, amount, asset_id)
 /// - amount > 0
 ///
-/// # Fields (3 inputs)
+/// # Fields (3 inputs, 4 with the depositor-bound circuit)
 /// 1. commitment - The computed commitment hash
 /// 2. amount - Deposit amount
 /// 3. asset_id - Asset identifier (Keccak256(mint))
-#[derive(Clone, Debug)]
+/// 4. depositor - Depositor signer (`deposit_bound.circom` only)
+#[derive(Clone, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
 pub struct DepositPublicInputs {
     /// Commitment hash being inserted into tree
     pub commitment: [u8; 32],
@@ -50,21 +51,35 @@ pub struct DepositPublicInputs {
 
     /// Asset identifier (Keccak256 of mint pubkey)
     pub asset_id: [u8; 32],
+
+    /// Depositor bound into the proof, so a copied proof only verifies in
+    /// the original depositor's transaction
+    pub depositor: Option<Pubkey>,
 }
 
 impl DepositPublicInputs {
     /// Number of public inputs for deposit verification
     pub const COUNT: usize = 3;
 
+    /// Number of public inputs when the depositor is bound
+    pub const BOUND_COUNT: usize = 4;
+
     /// Create new deposit public inputs
     pub fn new(commitment: [u8; 32], amount: u64, asset_id: [u8; 32]) -> Self {
         Self {
             commitment,
             amount,
             asset_id,
+            depositor: None,
         }
     }
 
+    /// Bind the depositor (depositor-bound deposit circuit)
+    pub fn with_depositor(mut self, depositor: Pubkey) -> Self {
+        self.depositor = Some(depositor);
+        self
+    }
+
     /// Validate deposit public inputs
     pub fn validate(&self) -> Result<()> {
         // Commitment cannot be zero
@@ -87,7 +102,11 @@ impl DepositPublicInputs {
 
     /// Convert to field elements for Groth16 verification
     pub fn to_field_elements(&self) -> Vec<ScalarField> {
-        vec![self.commitment, u64_to_scalar(self.amount), self.asset_id]
+        let mut elements = vec![self.commitment, u64_to_scalar(self.amount), self.asset_id];
+        if let Some(depositor) = &self.depositor {
+            elements.push(pubkey_to_scalar(depositor));
+        }
+        elements
     }
 }
 
@@ -112,7 +131,7 @@ impl DepositPublicInputs {
 /// 6. relayer - Relayer address
 /// 7. relayer_fee - Fee paid to relayer
 /// 8. public_data_hash - Optional hash of encrypted metadata
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
 pub struct WithdrawPublicInputs {
     /// Merkle root of the commitment tree
     pub merkle_root: [u8; 32],
@@ -245,7 +264,10 @@ impl WithdrawPublicInputs {
 /// 3..N+2. nullifier_hashes[N] - Input nullifiers
 /// N+3..N+M+2. output_commitments[M] - Output commitments
 /// N+M+3. public_amount - Net public inflow/outflow (can be negative)
-#[derive(Clone, Debug)]
+/// N+M+4. recipient - Owner of the token account paid a public outflow
+/// N+M+5. relayer - Submitting relayer
+/// N+M+6. relayer_fee - Fee paid to the relayer out of an outflow
+#[derive(Clone, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
 pub struct JoinSplitPublicInputs {
     /// Merkle root of the commitment tree
     pub merkle_root: [u8; 32],
@@ -265,6 +287,10 @@ pub struct JoinSplitPublicInputs {
     /// Zero: pure private transfer
     pub public_amount: i64,
 
+    /// Owner of the token account a public outflow is paid to (unused
+    /// otherwise)
+    pub recipient: Pubkey,
+
     /// Relayer address (for any public flows)
     pub relayer: Pubkey,
 
@@ -273,16 +299,19 @@ pub struct JoinSplitPublicInputs {
 }
 
 impl JoinSplitPublicInputs {
-    /// Base count: merkle_root, asset_id, public_amount, relayer, relayer_fee
-    pub const BASE_COUNT: usize = 5;
+    /// Base count: merkle_root, asset_id, public_amount, recipient, relayer,
+    /// relayer_fee
+    pub const BASE_COUNT: usize = 6;
 
     /// Create new join-split public inputs
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         merkle_root: [u8; 32],
         asset_id: [u8; 32],
         nullifier_hashes: Vec<[u8; 32]>,
         output_commitments: Vec<[u8; 32]>,
         public_amount: i64,
+        recipient: Pubkey,
         relayer: Pubkey,
         relayer_fee: u64,
     ) -> Self {
@@ -292,6 +321,7 @@ impl JoinSplitPublicInputs {
             nullifier_hashes,
             output_commitments,
             public_amount,
+            recipient,
             relayer,
             relayer_fee,
         }
@@ -368,7 +398,7 @@ impl JoinSplitPublicInputs {
 
         // Relayer fee validation for public outflows
         if self.public_amount < 0 {
-            let outflow = (-self.public_amount) as u64;
+            let outflow = self.public_amount.unsigned_abs();
             require!(
                 self.relayer_fee <= outflow,
                 PrivacyErrorV2::RelayerFeeExceedsAmount
@@ -398,6 +428,7 @@ impl JoinSplitPublicInputs {
 
         // Public amount (as signed field element)
         elements.push(i64_to_scalar(self.public_amount));
+        elements.push(pubkey_to_scalar(&self.recipient));
 
         // Relayer info
         elements.push(pubkey_to_scalar(&self.relayer));
@@ -426,7 +457,7 @@ impl JoinSplitPublicInputs {
         if !self.is_withdrawal() {
             return Err(error!(PrivacyErrorV2::InvalidAmount));
         }
-        let outflow = (-self.public_amount) as u64;
+        let outflow = self.public_amount.unsigned_abs();
         outflow
             .checked_sub(self.relayer_fee)
             .ok_or_else(|| error!(PrivacyErrorV2::ArithmeticOverflow))
@@ -448,7 +479,7 @@ impl JoinSplitPublicInputs {
 /// 2. asset_id - Asset being proven
 /// 3. threshold - Minimum amount threshold
 /// 4. public_key_hash - Deterministic identifier for the prover
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
 pub struct MembershipPublicInputs {
     /// Merkle root of the commitment tree
     pub merkle_root: [u8; 32],
@@ -636,6 +667,7 @@ pub struct JoinSplitPublicInputsBuilder {
     nullifier_hashes: Vec<[u8; 32]>,
     output_commitments: Vec<[u8; 32]>,
     public_amount: i64,
+    recipient: Option<Pubkey>,
     relayer: Option<Pubkey>,
     relayer_fee: u64,
 }
@@ -676,6 +708,12 @@ impl JoinSplitPublicInputsBuilder {
         self
     }
 
+    /// Set the recipient of a public outflow
+    pub fn recipient(mut self, recipient: Pubkey) -> Self {
+        self.recipient = Some(recipient);
+        self
+    }
+
     /// Set relayer
     pub fn relayer(mut self, relayer: Pubkey) -> Self {
         self.relayer = Some(relayer);
@@ -700,6 +738,7 @@ impl JoinSplitPublicInputsBuilder {
             nullifier_hashes: self.nullifier_hashes,
             output_commitments: self.output_commitments,
             public_amount: self.public_amount,
+            recipient: self.recipient.unwrap_or(Pubkey::default()),
             relayer: self.relayer.unwrap_or(Pubkey::default()),
             relayer_fee: self.relayer_fee,
         };
@@ -753,7 +792,7 @@ pub const WITHDRAW_V2_SCHEMA_VERSION: u64 = 2;
 /// 10. relayer_fee - Fee paid to relayer
 /// 11. public_data_hash - Optional hash of encrypted metadata
 /// 12. reserved_0 - Reserved field (must be zero; schema-versioned)
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
 pub struct WithdrawV2PublicInputs {
     /// Schema version for explicit ordering
     pub schema_version: u64,
@@ -949,6 +988,71 @@ impl WithdrawV2PublicInputs {
     }
 }
 
+// ============================================================================
+// CANONICAL BYTE ENCODING
+// ============================================================================
+
+/// Version of the canonical public input byte encoding
+pub const PUBLIC_INPUTS_ENCODING_VERSION: u8 = 1;
+
+/// Length of the `[version, kind]` header on every encoded blob
+pub const PUBLIC_INPUTS_HEADER_LEN: usize = 2;
+
+/// Kind tag identifying which public input struct a blob holds
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum PublicInputsKind {
+    Deposit = 0,
+    Withdraw = 1,
+    JoinSplit = 2,
+    Membership = 3,
+    WithdrawV2 = 4,
+}
+
+/// Implements `to_bytes`/`from_bytes` for a public input struct.
+///
+/// The encoding is `version (u8) || kind (u8) || borsh(struct)`. Borsh has
+/// exactly one encoding per value, so equal inputs always produce equal
+/// bytes, which is what relayer job signatures, caches and audit logs key
+/// on. Decoding rejects an unknown version, a different kind and trailing
+/// bytes.
+macro_rules! canonical_bytes {
+    ($($ty:ident => $kind:ident),* $(,)?) => {$(
+        impl $ty {
+            /// Canonical, versioned byte encoding of these public inputs
+            pub fn to_bytes(&self) -> Vec<u8> {
+                let mut out = vec![
+                    PUBLIC_INPUTS_ENCODING_VERSION,
+                    PublicInputsKind::$kind as u8,
+                ];
+                self.serialize(&mut out)
+                    .expect("serializing into a Vec cannot fail");
+                out
+            }
+
+            /// Decode bytes produced by [`Self::to_bytes`]
+            pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+                require!(
+                    bytes.len() >= PUBLIC_INPUTS_HEADER_LEN
+                        && bytes[0] == PUBLIC_INPUTS_ENCODING_VERSION
+                        && bytes[1] == PublicInputsKind::$kind as u8,
+                    PrivacyErrorV2::InvalidPublicInputs
+                );
+                Self::try_from_slice(&bytes[PUBLIC_INPUTS_HEADER_LEN..])
+                    .map_err(|_| error!(PrivacyErrorV2::InvalidPublicInputs))
+            }
+        }
+    )*};
+}
+
+canonical_bytes! {
+    DepositPublicInputs => Deposit,
+    WithdrawPublicInputs => Withdraw,
+    JoinSplitPublicInputs => JoinSplit,
+    MembershipPublicInputs => Membership,
+    WithdrawV2PublicInputs => WithdrawV2,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -966,6 +1070,21 @@ mod tests {
         assert_eq!(inputs.to_field_elements().len(), DepositPublicInputs::COUNT);
     }
 
+    #[test]
+    fn test_deposit_binds_depositor() {
+        let depositor = test_pubkey();
+        let inputs = DepositPublicInputs::new([1u8; 32], 1000, [2u8; 32]).with_depositor(depositor);
+        let elements = inputs.to_field_elements();
+        assert_eq!(elements.len(), DepositPublicInputs::BOUND_COUNT);
+        assert_eq!(elements[3], pubkey_to_scalar(&depositor));
+        assert_ne!(
+            DepositPublicInputs::new([1u8; 32], 1000, [2u8; 32])
+                .with_depositor(test_pubkey())
+                .to_field_elements(),
+            elements
+        );
+    }
+
     #[test]
     fn test_deposit_zero_commitment() {
         let inputs = DepositPublicInputs::new([0u8; 32], 1000, [2u8; 32]);
@@ -1019,6 +1138,7 @@ mod tests {
             vec![[5u8; 32], [6u8; 32]],
             0,
             test_pubkey(),
+            test_pubkey(),
             0,
         );
         assert!(inputs.validate().is_ok());
@@ -1034,6 +1154,7 @@ mod tests {
             vec![[5u8; 32]],
             1000,
             test_pubkey(),
+            test_pubkey(),
             0,
         );
         assert!(inputs.validate().is_ok());
@@ -1050,11 +1171,29 @@ mod tests {
             vec![[5u8; 32]],
             -1000,
             test_pubkey(),
+            test_pubkey(),
             100,
         );
         assert!(inputs.validate().is_ok());
         assert!(inputs.is_withdrawal());
         assert_eq!(inputs.net_withdrawal().unwrap(), 900);
+        assert_eq!(inputs.to_field_elements().len(), inputs.count());
+    }
+
+    #[test]
+    fn test_join_split_min_public_amount() {
+        let inputs = JoinSplitPublicInputs::new(
+            [1u8; 32],
+            [2u8; 32],
+            vec![[3u8; 32]],
+            vec![[5u8; 32]],
+            i64::MIN,
+            test_pubkey(),
+            test_pubkey(),
+            100,
+        );
+        assert!(inputs.validate().is_ok());
+        assert_eq!(inputs.net_withdrawal().unwrap(), (1u64 << 63) - 100);
     }
 
     #[test]
@@ -1066,6 +1205,7 @@ mod tests {
             vec![[5u8; 32]],
             0,
             test_pubkey(),
+            test_pubkey(),
             0,
         );
         assert!(inputs.validate().is_err());
@@ -1110,4 +1250,100 @@ mod tests {
             .build();
         assert!(result.is_ok());
     }
+
+    // ----- Canonical encoding tests -----
+
+    #[test]
+    fn test_canonical_bytes_round_trip() {
+        let deposit =
+            DepositPublicInputs::new([1u8; 32], 1000, [2u8; 32]).with_depositor(test_pubkey());
+        assert_eq!(
+            DepositPublicInputs::from_bytes(&deposit.to_bytes()).unwrap(),
+            deposit
+        );
+
+        let withdraw = WithdrawPublicInputsBuilder::new()
+            .merkle_root([1u8; 32])
+            .nullifier_hash([2u8; 32])
+            .asset_id([3u8; 32])
+            .recipient(test_pubkey())
+            .amount(1000)
+            .relayer(test_pubkey())
+            .relayer_fee(100)
+            .public_data_hash([4u8; 32])
+            .build()
+            .unwrap();
+        assert_eq!(
+            WithdrawPublicInputs::from_bytes(&withdraw.to_bytes()).unwrap(),
+            withdraw
+        );
+
+        let join_split = JoinSplitPublicInputsBuilder::new()
+            .merkle_root([1u8; 32])
+            .asset_id([2u8; 32])
+            .add_nullifier([3u8; 32])
+            .add_nullifier([4u8; 32])
+            .add_output([5u8; 32])
+            .public_amount(-500)
+            .recipient(test_pubkey())
+            .build()
+            .unwrap();
+        assert_eq!(
+            JoinSplitPublicInputs::from_bytes(&join_split.to_bytes()).unwrap(),
+            join_split
+        );
+
+        let membership = MembershipPublicInputs::new([1u8; 32], [2u8; 32], 50, [3u8; 32]);
+        assert_eq!(
+            MembershipPublicInputs::from_bytes(&membership.to_bytes()).unwrap(),
+            membership
+        );
+
+        let withdraw_v2 = WithdrawV2PublicInputs::new(
+            [1u8; 32],
+            [2u8; 32],
+            [3u8; 32],
+            [0u8; 32],
+            [4u8; 32],
+            test_pubkey(),
+            1000,
+            test_pubkey(),
+            10,
+            [0u8; 32],
+        );
+        assert_eq!(
+            WithdrawV2PublicInputs::from_bytes(&withdraw_v2.to_bytes()).unwrap(),
+            withdraw_v2
+        );
+    }
+
+    #[test]
+    fn test_canonical_bytes_layout() {
+        let inputs = MembershipPublicInputs::new([1u8; 32], [2u8; 32], 50, [3u8; 32]);
+        let bytes = inputs.to_bytes();
+        assert_eq!(bytes[0], PUBLIC_INPUTS_ENCODING_VERSION);
+        assert_eq!(bytes[1], PublicInputsKind::Membership as u8);
+        assert_eq!(bytes.len(), PUBLIC_INPUTS_HEADER_LEN + 32 + 32 + 8 + 32);
+        assert_eq!(bytes, inputs.clone().to_bytes());
+    }
+
+    #[test]
+    fn test_canonical_bytes_rejects_mismatched_blobs() {
+        let bytes = DepositPublicInputs::new([1u8; 32], 1000, [2u8; 32]).to_bytes();
+
+        // Wrong kind
+        assert!(MembershipPublicInputs::from_bytes(&bytes).is_err());
+
+        // Unknown version
+        let mut bad_version = bytes.clone();
+        bad_version[0] = PUBLIC_INPUTS_ENCODING_VERSION + 1;
+        assert!(DepositPublicInputs::from_bytes(&bad_version).is_err());
+
+        // Truncated, trailing and empty
+        assert!(DepositPublicInputs::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(DepositPublicInputs::from_bytes(&trailing).is_err());
+        assert!(DepositPublicInputs::from_bytes(&[]).is_err());
+    }
 }
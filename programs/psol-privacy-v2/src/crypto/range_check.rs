@@ -0,0 +1,81 @@
+//! Amount Range-Check Hook
+//!
+//! Deposit and withdraw handlers pass every public amount they move
+//! through [`check_amount_range`]. The only scheme today is
+//! [`RangeProofScheme::None`], for which the hook is a no-op: amounts are
+//! bounded by the Groth16 circuits alone.
+//!
+//! Per-note range proofs (Bulletproof-style, bounding inflation if a
+//! circuit turns out to be unsound) plug in as a new scheme variant. The
+//! pool selects its scheme in `PoolConfigV2::range_proof_scheme`, and the
+//! scheme's parameters go in the already laid out
+//! [`RangeProofParams`](crate::state::RangeProofParams) account, so neither
+//! needs an account migration.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::state::RangeProofParams;
+
+/// Range proof scheme selected by a pool
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum RangeProofScheme {
+    /// No range proofs; the hook accepts every amount
+    None = 0,
+}
+
+impl RangeProofScheme {
+    pub fn from_u8(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(Self::None),
+            _ => err!(PrivacyErrorV2::UnsupportedRangeProofScheme),
+        }
+    }
+}
+
+/// Amount handed to the range-check hook
+pub struct RangeCheckInput<'a> {
+    pub asset_id: [u8; 32],
+    pub amount: u64,
+    /// Range proof supplied with the instruction, once a scheme needs one
+    pub range_proof: Option<&'a [u8]>,
+}
+
+impl RangeCheckInput<'_> {
+    pub fn new(asset_id: [u8; 32], amount: u64) -> Self {
+        Self {
+            asset_id,
+            amount,
+            range_proof: None,
+        }
+    }
+}
+
+/// Check `input` under `scheme`, with the pool's parameters when the
+/// scheme has any
+pub fn check_amount_range(
+    scheme: RangeProofScheme,
+    _params: Option<&RangeProofParams>,
+    _input: &RangeCheckInput,
+) -> Result<()> {
+    match scheme {
+        RangeProofScheme::None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_scheme_accepts_any_amount() {
+        let scheme = RangeProofScheme::from_u8(0).unwrap();
+        for amount in [0, 1, u64::MAX] {
+            assert!(
+                check_amount_range(scheme, None, &RangeCheckInput::new([1u8; 32], amount)).is_ok()
+            );
+        }
+        assert!(RangeProofScheme::from_u8(1).is_err());
+    }
+}
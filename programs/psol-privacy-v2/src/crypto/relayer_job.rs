@@ -0,0 +1,202 @@
+//! Signed Relayer Jobs
+//!
+//! A user hands a relayer a [`RelayerJob`]: the canonical public input bytes
+//! and proof to submit, plus an expiry, signed with an ed25519 key of the
+//! user's choosing (usually the recipient or a one-time key). The relayer
+//! puts an Ed25519SigVerify instruction for that signature directly before
+//! the withdrawal, so the runtime checks it and the transaction itself shows
+//! what the user authorized. In a dispute the relayer (or any program, via
+//! [`verify_relayer_job`]) can show it executed exactly that job.
+//!
+//! # Signed Message
+//! `RELAYER_JOB_DOMAIN || sha256(borsh(job))`
+
+use anchor_lang::prelude::*;
+use sha2::{Digest, Sha256};
+
+use crate::error::PrivacyErrorV2;
+use crate::utils::verify_preceding_ed25519_signature;
+
+use super::WithdrawPublicInputs;
+
+/// Domain separator for relayer job signatures
+pub const RELAYER_JOB_DOMAIN: &[u8] = b"psol-v2:relayer-job";
+
+/// Work a user authorizes one relayer to submit
+#[derive(Clone, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct RelayerJob {
+    /// Pool the job is submitted to
+    pub pool: Pubkey,
+
+    /// Canonical public input bytes (`to_bytes` of a PublicInputs struct)
+    pub public_inputs: Vec<u8>,
+
+    /// Groth16 proof bytes
+    pub proof: Vec<u8>,
+
+    /// Unix timestamp after which the job must not be submitted
+    pub expires_at: i64,
+}
+
+impl RelayerJob {
+    pub fn new(pool: Pubkey, public_inputs: Vec<u8>, proof: Vec<u8>, expires_at: i64) -> Self {
+        Self {
+            pool,
+            public_inputs,
+            proof,
+            expires_at,
+        }
+    }
+
+    /// Job for a `withdraw_masp` submission
+    pub fn withdraw(
+        pool: Pubkey,
+        public_inputs: &WithdrawPublicInputs,
+        proof: Vec<u8>,
+        expires_at: i64,
+    ) -> Self {
+        Self::new(pool, public_inputs.to_bytes(), proof, expires_at)
+    }
+
+    /// SHA-256 of the job's borsh encoding
+    pub fn hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(
+            self.try_to_vec()
+                .expect("serializing into a Vec cannot fail"),
+        );
+        hasher.finalize().into()
+    }
+
+    /// Message the user signs to authorize the job
+    pub fn signing_message(&self) -> Vec<u8> {
+        [RELAYER_JOB_DOMAIN, &self.hash()].concat()
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        now > self.expires_at
+    }
+
+    pub fn require_not_expired(&self, now: i64) -> Result<()> {
+        require!(!self.is_expired(now), PrivacyErrorV2::RelayerJobExpired);
+        Ok(())
+    }
+
+    /// Check the job covers exactly these public inputs and proof
+    pub fn require_matches(&self, public_inputs: &[u8], proof: &[u8]) -> Result<()> {
+        require!(
+            self.public_inputs == public_inputs && self.proof == proof,
+            PrivacyErrorV2::RelayerJobMismatch
+        );
+        Ok(())
+    }
+}
+
+/// Check the instruction before the current one verifies `signer`'s
+/// signature over `job`, and that the job has not expired. Returns the
+/// signature.
+pub fn verify_relayer_job(
+    instructions_sysvar: &AccountInfo,
+    job: &RelayerJob,
+    signer: &Pubkey,
+    now: i64,
+) -> Result<[u8; 64]> {
+    job.require_not_expired(now)?;
+    verify_preceding_ed25519_signature(instructions_sysvar, signer, &job.signing_message())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::check_ed25519_instruction;
+    use anchor_lang::solana_program::instruction::Instruction;
+    use solana_sdk_ids::ed25519_program;
+
+    fn job() -> RelayerJob {
+        let inputs = WithdrawPublicInputs::new(
+            [1u8; 32],
+            [2u8; 32],
+            [3u8; 32],
+            Pubkey::new_unique(),
+            1_000,
+            Pubkey::new_unique(),
+            10,
+            [0u8; 32],
+        );
+        RelayerJob::withdraw(Pubkey::new_unique(), &inputs, vec![7u8; 256], 1_000)
+    }
+
+    #[test]
+    fn test_signing_message_binds_every_field() {
+        let base = job();
+        let message = base.signing_message();
+        assert!(message.starts_with(RELAYER_JOB_DOMAIN));
+        assert_eq!(message.len(), RELAYER_JOB_DOMAIN.len() + 32);
+
+        let mut tampered = base.clone();
+        tampered.proof[0] ^= 1;
+        assert_ne!(tampered.signing_message(), message);
+
+        let mut tampered = base.clone();
+        tampered.public_inputs[2] ^= 1;
+        assert_ne!(tampered.signing_message(), message);
+
+        let mut tampered = base.clone();
+        tampered.expires_at += 1;
+        assert_ne!(tampered.signing_message(), message);
+
+        let mut tampered = base;
+        tampered.pool = Pubkey::new_unique();
+        assert_ne!(tampered.signing_message(), message);
+    }
+
+    #[test]
+    fn test_expiry_and_match() {
+        let job = job();
+        assert!(job.require_not_expired(1_000).is_ok());
+        assert!(job.require_not_expired(1_001).is_err());
+
+        assert!(job.require_matches(&job.public_inputs, &job.proof).is_ok());
+        assert!(job
+            .require_matches(&job.public_inputs, &[0u8; 256])
+            .is_err());
+        assert!(job.require_matches(&[], &job.proof).is_err());
+    }
+
+    #[test]
+    fn test_ed25519_instruction_covers_job() {
+        let job = job();
+        let signer = Pubkey::new_unique();
+        let message = job.signing_message();
+
+        // Layout of the ed25519 program's client helper
+        let mut data = vec![1u8, 0];
+        for v in [
+            48u16,
+            u16::MAX,
+            16,
+            u16::MAX,
+            112,
+            message.len() as u16,
+            u16::MAX,
+        ] {
+            data.extend_from_slice(&v.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(&[9u8; 64]);
+        data.extend_from_slice(&message);
+        let ix = Instruction {
+            program_id: ed25519_program::ID,
+            accounts: vec![],
+            data,
+        };
+
+        assert_eq!(
+            check_ed25519_instruction(&ix, &signer, &message).unwrap(),
+            [9u8; 64]
+        );
+        let mut other = job.clone();
+        other.expires_at += 1;
+        assert!(check_ed25519_instruction(&ix, &signer, &other.signing_message()).is_err());
+    }
+}
@@ -0,0 +1,878 @@
+//! Human-readable instruction decoding for explorers and wallet previews
+//!
+//! [`decode_instruction`] turns raw instruction data plus the instruction's
+//! account keys into a [`DecodedInstruction`]: the instruction name, its
+//! arguments and the accounts it touches, each labelled with its role.
+//!
+//! Anything that links a shielded note to an identity is redacted rather
+//! than rendered, following the same policy as the events: deposit and
+//! withdrawal amounts, the withdrawal recipient, encrypted notes, and the
+//! depositor / user / recipient token accounts. Commitments, nullifier
+//! hashes, roots, asset ids, relayer fees and admin parameters are shown.
+//! Every argument of every instruction is classified explicitly in the
+//! table below, so adding an argument does not compile until it is.
+
+use std::fmt;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::instruction;
+use crate::instructions::settle_deposits_batch::SettleDepositsBatchArgs;
+use crate::state::{RelayerMetadata, RiskTierLimits};
+use crate::{ProofType, ShieldedActionType};
+
+/// A value that is either rendered or withheld
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Shown<T> {
+    Public(T),
+    Redacted,
+}
+
+impl<T: fmt::Display> fmt::Display for Shown<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Shown::Public(v) => v.fmt(f),
+            Shown::Redacted => f.write_str("<redacted>"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedParam {
+    pub name: &'static str,
+    pub value: Shown<String>,
+}
+
+/// What an account is to the program
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccountRole {
+    /// Program-derived account owned by the pool
+    Pda,
+    Program,
+    Sysvar,
+    /// Wallet or token account identifying a depositor or recipient
+    User,
+    /// Authorities, operators, relayers, mints
+    Other,
+    /// Passed beyond the instruction's declared accounts
+    Remaining,
+}
+
+impl fmt::Display for AccountRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AccountRole::Pda => "pda",
+            AccountRole::Program => "program",
+            AccountRole::Sysvar => "sysvar",
+            AccountRole::User => "user",
+            AccountRole::Other => "account",
+            AccountRole::Remaining => "remaining",
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedAccount {
+    pub name: &'static str,
+    pub role: AccountRole,
+    /// `None` for an optional account that was not supplied
+    pub key: Option<Shown<Pubkey>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedInstruction {
+    pub name: &'static str,
+    pub params: Vec<DecodedParam>,
+    pub accounts: Vec<DecodedAccount>,
+}
+
+impl fmt::Display for DecodedInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.name)?;
+        for param in &self.params {
+            writeln!(f, "  {}: {}", param.name, param.value)?;
+        }
+        for account in &self.accounts {
+            match &account.key {
+                Some(key) => writeln!(f, "  [{}] {}: {}", account.role, account.name, key)?,
+                None => writeln!(f, "  [{}] {}: (none)", account.role, account.name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Account roles by field name; names are used consistently across the
+/// instruction account structs
+fn account_role(name: &str) -> AccountRole {
+    match name {
+        "pool_config"
+        | "source_pool_config"
+        | "source_relayer_registry"
+        | "source_compliance_config"
+        | "source_asset_vault"
+        | "global_config"
+        | "merkle_tree"
+        | "relayer_registry"
+        | "compliance_config"
+        | "audit_metadata"
+        | "disclosed_commitment"
+        | "withdrawal_commit"
+        | "commitment_reservation"
+        | "pending_buffer"
+        | "asset_vault"
+        | "vault_token_account"
+        | "treasury"
+        | "treasury_token_account"
+        | "input_vault"
+        | "input_vault_token_account"
+        | "output_vault"
+        | "output_vault_token_account"
+        | "action_authority"
+        | "input_escrow"
+        | "output_escrow"
+        | "vk_account"
+        | "deposit_vk"
+        | "verification_key"
+        | "circuit_descriptor"
+        | "migration_window"
+        | "deferred_events"
+        | "deposit_hook"
+        | "mint_allowlist"
+        | "action_allowlist"
+        | "surplus_sweep"
+        | "batcher_role"
+        | "insurance_fund"
+        | "attested_root"
+        | "relayer_node"
+        | "relayer_index_shard"
+        | "relayer_tombstone"
+        | "stake_vault"
+        | "rebate_ledger"
+        | "fee_quote"
+        | "rent_reserve"
+        | "rent_advance"
+        | "spent_nullifier"
+        | "spent_nullifier_0"
+        | "spent_nullifier_1"
+        | "yield_registry"
+        | "proof_stats"
+        | "anonymity_stats"
+        | "parameter_snapshot"
+        | "risk_tiers"
+        | "withdrawal_window"
+        | "pool_directory"
+        | "directory_entry" => AccountRole::Pda,
+        "depositor"
+        | "user_token_account"
+        | "recipient_token_account"
+        | "recipient_ata"
+        | "one_time_owner" => AccountRole::User,
+        "instructions_sysvar" => AccountRole::Sysvar,
+        _ if name.ends_with("_program") => AccountRole::Program,
+        _ => AccountRole::Other,
+    }
+}
+
+fn label_accounts(names: &[&'static str], keys: &[Pubkey]) -> Vec<DecodedAccount> {
+    keys.iter()
+        .enumerate()
+        .map(|(i, key)| {
+            let (name, role) = match names.get(i) {
+                Some(name) => (*name, account_role(name)),
+                None => ("remaining", AccountRole::Remaining),
+            };
+            // Anchor clients pass the program id for an absent optional account
+            let key = if *key == crate::ID && role != AccountRole::Remaining {
+                None
+            } else if role == AccountRole::User {
+                Some(Shown::Redacted)
+            } else {
+                Some(Shown::Public(*key))
+            };
+            DecodedAccount { name, role, key }
+        })
+        .collect()
+}
+
+/// Rendering of an argument value for display
+trait Render {
+    fn render(&self) -> String;
+}
+
+macro_rules! render_display {
+    ($($ty:ty),*) => {
+        $(impl Render for $ty {
+            fn render(&self) -> String {
+                self.to_string()
+            }
+        })*
+    };
+}
+
+render_display!(bool, u8, u16, u32, u64, u128, i64, Pubkey);
+
+impl Render for [u8; 32] {
+    fn render(&self) -> String {
+        hex::encode(self)
+    }
+}
+
+impl Render for [u8; 64] {
+    fn render(&self) -> String {
+        format!("<{} bytes>", self.len())
+    }
+}
+
+impl Render for [u8; 128] {
+    fn render(&self) -> String {
+        format!("<{} bytes>", self.len())
+    }
+}
+
+impl Render for Vec<u8> {
+    fn render(&self) -> String {
+        format!("<{} bytes>", self.len())
+    }
+}
+
+impl Render for Vec<[u8; 32]> {
+    fn render(&self) -> String {
+        let items: Vec<String> = self.iter().map(Render::render).collect();
+        format!("[{}]", items.join(", "))
+    }
+}
+
+impl Render for Vec<Vec<u8>> {
+    fn render(&self) -> String {
+        let items: Vec<String> = self.iter().map(Render::render).collect();
+        format!("[{}]", items.join(", "))
+    }
+}
+
+impl Render for Vec<[u8; 64]> {
+    fn render(&self) -> String {
+        format!("<{} points>", self.len())
+    }
+}
+
+impl Render for String {
+    fn render(&self) -> String {
+        format!("{self:?}")
+    }
+}
+
+impl<T: Render> Render for Option<T> {
+    fn render(&self) -> String {
+        match self {
+            Some(v) => v.render(),
+            None => "none".to_string(),
+        }
+    }
+}
+
+impl Render for ProofType {
+    fn render(&self) -> String {
+        format!("{self:?}")
+    }
+}
+
+impl Render for ShieldedActionType {
+    fn render(&self) -> String {
+        format!("{self:?}")
+    }
+}
+
+impl Render for RelayerMetadata {
+    fn render(&self) -> String {
+        format!(
+            "{{ endpoint_hash: {}, note_encryption_pubkey: {}, supported_assets: {:#x}, region_code: {} }}",
+            self.endpoint_hash.render(),
+            self.note_encryption_pubkey.render(),
+            self.supported_assets,
+            self.region_code
+        )
+    }
+}
+
+impl Render for RiskTierLimits {
+    fn render(&self) -> String {
+        format!(
+            "{{ deposits_enabled: {}, withdrawals_enabled: {}, max_deposit: {}, max_withdrawal: {}, \
+             rate_limit_window: {}, rate_limit_amount: {}, delay_threshold: {}, withdrawal_delay: {} }}",
+            self.deposits_enabled,
+            self.withdrawals_enabled,
+            self.max_deposit,
+            self.max_withdrawal,
+            self.rate_limit_window,
+            self.rate_limit_amount,
+            self.delay_threshold,
+            self.withdrawal_delay
+        )
+    }
+}
+
+impl Render for SettleDepositsBatchArgs {
+    fn render(&self) -> String {
+        format!(
+            "{{ proof: <{} bytes>, new_root: {}, batch_size: {}, expected_batch_seq: {} }}",
+            self.proof.len(),
+            self.new_root.render(),
+            self.batch_size,
+            self.expected_batch_seq.render()
+        )
+    }
+}
+
+macro_rules! param {
+    (public, $name:ident) => {
+        DecodedParam {
+            name: stringify!($name),
+            value: Shown::Public($name.render()),
+        }
+    };
+    (redacted, $name:ident) => {{
+        let _ = $name;
+        DecodedParam {
+            name: stringify!($name),
+            value: Shown::Redacted,
+        }
+    }};
+}
+
+macro_rules! decoders {
+    ($(
+        $name:literal => $ix:ident { $($arg:ident: $vis:ident),* $(,)? }
+            [$($account:ident),* $(,)?],
+    )*) => {
+        /// Name and discriminator of every instruction in the table
+        pub const INSTRUCTIONS: &[(&str, &[u8])] = &[
+            $(($name, instruction::$ix::DISCRIMINATOR)),*
+        ];
+
+        /// Decode one pSOL v2 instruction
+        ///
+        /// `accounts` are the instruction's account keys in order. Fails with
+        /// `InstructionFallbackNotFound` for an unknown discriminator and
+        /// `InstructionDidNotDeserialize` for malformed arguments.
+        pub fn decode_instruction(data: &[u8], accounts: &[Pubkey]) -> Result<DecodedInstruction> {
+            if data.len() < 8 {
+                return Err(ErrorCode::InstructionMissing.into());
+            }
+            let (discriminator, mut args) = data.split_at(8);
+            $(
+                if discriminator == instruction::$ix::DISCRIMINATOR {
+                    let instruction::$ix { $($arg),* } =
+                        AnchorDeserialize::deserialize(&mut args)
+                            .map_err(|_| ErrorCode::InstructionDidNotDeserialize)?;
+                    return Ok(DecodedInstruction {
+                        name: $name,
+                        params: vec![$(param!($vis, $arg)),*],
+                        accounts: label_accounts(&[$(stringify!($account)),*], accounts),
+                    });
+                }
+            )*
+            Err(ErrorCode::InstructionFallbackNotFound.into())
+        }
+    };
+}
+
+decoders! {
+    "initialize_pool_v2" => InitializePoolV2 { tree_depth: public, root_history_size: public, compliance_recovery: public, pool_id: public }
+        [authority, pool_config, merkle_tree, system_program],
+    "migrate_asset_vault" => MigrateAssetVault {  }
+        [authority, pool_config, asset_vault, system_program],
+    "migrate_relayer_registry" => MigrateRelayerRegistry {  }
+        [authority, pool_config, relayer_registry, system_program],
+    "migrate_relayer_node" => MigrateRelayerNode {  }
+        [authority, pool_config, relayer_registry, relayer_node, relayer_index_shard, system_program],
+    "migrate_verification_key" => MigrateVerificationKey { proof_type: public }
+        [authority, pool_config, vk_account, system_program],
+    "initialize_pool_registries" => InitializePoolRegistries {  }
+        [authority, pool_config, relayer_registry, compliance_config, system_program],
+    "initialize_pending_deposits_buffer" => InitializePendingDepositsBuffer {  }
+        [authority, pool_config, pending_buffer, system_program],
+    "migrate_pool_config" => MigratePoolConfig {  }
+        [authority, pool_config, system_program],
+    "migrate_merkle_tree" => MigrateMerkleTree {  }
+        [authority, pool_config, merkle_tree, system_program],
+    "register_asset" => RegisterAsset { asset_id: public }
+        [authority, pool_config, mint, asset_vault, vault_token_account, token_program, system_program, token_metadata],
+    "register_native_sol_asset" => RegisterNativeSolAsset {  }
+        [authority, pool_config, asset_vault, system_program],
+    "clone_pool_config" => ClonePoolConfig { pool_id: public, root_history_size: public }
+        [authority, source_pool_config, source_relayer_registry, source_compliance_config, pool_config, merkle_tree, relayer_registry, compliance_config, system_program],
+    "clone_asset_config" => CloneAssetConfig { asset_id: public }
+        [authority, source_pool_config, source_asset_vault, pool_config, mint, asset_vault, vault_token_account, token_program, system_program],
+    "create_pool" => CreatePool { tree_depth: public, pool_id: public, max_creation_fee: public }
+        [creator, global_config, super_guardian, pool_config, merkle_tree, relayer_registry, compliance_config, system_program],
+    "init_pool_directory" => InitPoolDirectory {  }
+        [payer, pool_directory, system_program],
+    "list_pool" => ListPool {  }
+        [authority, pool_config, pool_directory, directory_entry, system_program],
+    "refresh_pool_listing" => RefreshPoolListing {  }
+        [pool_config, directory_entry],
+    "register_assets_batch" => RegisterAssetsBatch { asset_ids: public }
+        [authority, pool_config, token_program, system_program],
+    "init_mint_allowlist" => InitMintAllowlist {  }
+        [authority, pool_config, mint_allowlist, system_program],
+    "add_allowlisted_mint" => AddAllowlistedMint { mint: public }
+        [authority, pool_config, mint_allowlist],
+    "remove_allowlisted_mint" => RemoveAllowlistedMint { mint: public }
+        [authority, pool_config, mint_allowlist],
+    "set_open_asset_registration" => SetOpenAssetRegistration { open: public }
+        [authority, pool_config, mint_allowlist],
+    "register_allowlisted_asset" => RegisterAllowlistedAsset { asset_id: public }
+        [payer, pool_config, mint_allowlist, mint, asset_vault, vault_token_account, token_program, system_program, token_metadata],
+    "create_verification_key_account" => CreateVerificationKeyAccount { proof_type: public }
+        [authority, pool_config, vk_account, system_program],
+    "set_verification_key_data" => SetVerificationKeyData { proof_type: public, vk_alpha_g1: public, vk_beta_g2: public, vk_gamma_g2: public, vk_delta_g2: public, vk_ic: public, ceremony_transcript_hash: public, ceremony_contributors: public }
+        [authority, pool_config, vk_account, circuit_descriptor, merkle_tree],
+    "set_circuit_descriptor" => SetCircuitDescriptor { proof_type: public, circuit_version: public, public_input_layout_hash: public, tree_depth: public, poseidon_params_hash: public }
+        [authority, pool_config, merkle_tree, circuit_descriptor, system_program],
+    "update_circuit_descriptor" => UpdateCircuitDescriptor { proof_type: public, circuit_version: public, public_input_layout_hash: public, tree_depth: public, poseidon_params_hash: public }
+        [authority, pool_config, merkle_tree, circuit_descriptor],
+    "view_poseidon_params" => ViewPoseidonParams {  }
+        [pool_config, merkle_tree],
+    "lock_verification_key_v2" => LockVerificationKeyV2 { proof_type: public }
+        [authority, pool_config, vk_account],
+    "submit_canary_proof" => SubmitCanaryProof { proof_type: public, proof: public, public_inputs: public }
+        [authority, pool_config, vk_account],
+    "record_vk_ceremony" => RecordVkCeremony { proof_type: public, ceremony_transcript_hash: public, ceremony_contributors: public }
+        [authority, pool_config, vk_account],
+    "initialize_vk_v2" => InitializeVkV2 { proof_type: public, vk_alpha_g1: public, vk_beta_g2: public, vk_gamma_g2: public, vk_delta_g2: public, expected_ic_count: public, ceremony_transcript_hash: public, ceremony_contributors: public }
+        [authority, pool_config, vk_account, circuit_descriptor, merkle_tree],
+    "append_vk_ic_v2" => AppendVkIcV2 { proof_type: public, ic_points: public }
+        [authority, pool_config, vk_account],
+    "initialize_vk_compressed_v2" => InitializeVkCompressedV2 { proof_type: public, vk_alpha_g1: public, vk_beta_g2: public, vk_gamma_g2: public, vk_delta_g2: public, expected_ic_count: public, ceremony_transcript_hash: public, ceremony_contributors: public }
+        [authority, pool_config, vk_account, circuit_descriptor, merkle_tree],
+    "append_vk_ic_compressed_v2" => AppendVkIcCompressedV2 { proof_type: public, ic_points: public }
+        [authority, pool_config, vk_account],
+    "finalize_vk_v2" => FinalizeVkV2 { proof_type: public }
+        [authority, pool_config, vk_account],
+    "pause_pool_v2" => PausePoolV2 {  }
+        [authority, pool_config],
+    "unpause_pool_v2" => UnpausePoolV2 {  }
+        [authority, pool_config],
+    "initialize_global_config" => InitializeGlobalConfig { super_guardian: public }
+        [authority, global_config, program, program_data, system_program],
+    "pause_all_pools" => PauseAllPools {  }
+        [super_guardian, global_config],
+    "unpause_all_pools" => UnpauseAllPools {  }
+        [super_guardian, global_config],
+    "set_super_guardian" => SetSuperGuardian { new_super_guardian: public }
+        [super_guardian, global_config],
+    "set_pool_creation_fee" => SetPoolCreationFee { fee: public }
+        [super_guardian, global_config],
+    "clear_pending_buffer" => ClearPendingBuffer {  }
+        [authority, pool_config, pending_buffer],
+    "migrate_pending_buffer" => MigratePendingBuffer {  }
+        [authority, pool_config, pending_buffer, system_program],
+    "reset_merkle_tree" => ResetMerkleTree {  }
+        [authority, pool_config, merkle_tree],
+    "set_tree_capacity_thresholds" => SetTreeCapacityThresholds { warning_pct: public, critical_pct: public }
+        [authority, pool_config],
+    "set_max_encrypted_note_len" => SetMaxEncryptedNoteLen { max_len: public }
+        [authority, pool_config],
+    "set_asset_min_withdrawal" => SetAssetMinWithdrawal { asset_id: public, min_withdrawal: public }
+        [authority, pool_config, asset_vault],
+    "init_risk_tiers" => InitRiskTiers {  }
+        [authority, pool_config, risk_tiers, system_program],
+    "set_risk_tier_limits" => SetRiskTierLimits { tier: public, limits: public }
+        [authority, pool_config, risk_tiers],
+    "set_asset_risk_tier" => SetAssetRiskTier { asset_id: public, tier: public }
+        [authority, pool_config, risk_tiers, asset_vault],
+    "init_asset_withdrawal_window" => InitAssetWithdrawalWindow { asset_id: public }
+        [authority, pool_config, asset_vault, withdrawal_window, system_program],
+    "set_same_tx_deposit_withdraw" => SetSameTxDepositWithdraw { allowed: public }
+        [authority, pool_config],
+    "set_root_events" => SetRootEvents { enabled: public }
+        [authority, pool_config],
+    "set_privacy_strict" => SetPrivacyStrict { enabled: public }
+        [authority, pool_config],
+    "set_disclosed_path_withdrawals" => SetDisclosedPathWithdrawals { enabled: public }
+        [authority, pool_config],
+    "set_crank_bounty" => SetCrankBounty { lamports_per_leaf: public }
+        [authority, pool_config],
+    "set_protocol_fee" => SetProtocolFee { fee_bps: public }
+        [authority, pool_config],
+    "init_treasury" => InitTreasury { asset_id: public }
+        [authority, pool_config, asset_vault, mint, treasury_token_account, token_program, system_program],
+    "init_pool_treasury" => InitPoolTreasury {  }
+        [authority, pool_config, treasury, system_program],
+    "collect_treasury_fees" => CollectTreasuryFees { asset_id: public }
+        [authority, pool_config, treasury, asset_vault],
+    "view_treasury" => ViewTreasury { asset_id: public }
+        [pool_config, treasury, asset_vault, treasury_token_account],
+    "request_treasury_disbursement" => RequestTreasuryDisbursement { asset_id: public, amount: public }
+        [authority, pool_config, treasury, asset_vault, treasury_token_account, destination],
+    "execute_treasury_disbursement" => ExecuteTreasuryDisbursement { asset_id: public }
+        [authority, pool_config, treasury, asset_vault, destination, treasury_token_account, mint, token_program],
+    "cancel_treasury_disbursement" => CancelTreasuryDisbursement {  }
+        [authority, pool_config, treasury],
+    "configure_compliance" => ConfigureCompliance { require_encrypted_note: public, audit_pubkey: public, metadata_schema_version: public }
+        [authority, pool_config, compliance_config],
+    "attach_audit_metadata" => AttachAuditMetadata { commitment: public, encrypted_metadata: redacted }
+        [payer, pool_config, compliance_config, audit_metadata, system_program],
+    "compliance_recovery_withdraw" => ComplianceRecoveryWithdraw { commitment: public, asset_id: public, nullifier_hash: public, court_order_hash: public, note: redacted }
+        [authority, auditor, pool_config, compliance_config, merkle_tree, audit_metadata, asset_vault, vault_token_account, custodian, custodian_token_account, mint, spent_nullifier, token_program, system_program],
+    "init_parameter_snapshot" => InitParameterSnapshot {  }
+        [authority, pool_config, asset_vault, relayer_registry, parameter_snapshot, system_program],
+    "publish_parameter_snapshot" => PublishParameterSnapshot {  }
+        [authority, pool_config, asset_vault, relayer_registry, parameter_snapshot],
+    "open_migration_window" => OpenMigrationWindow { duration_seconds: public }
+        [authority, pool_config, merkle_tree, migration_window, system_program],
+    "close_migration_window" => CloseMigrationWindow {  }
+        [authority, pool_config, migration_window],
+    "request_surplus_sweep" => RequestSurplusSweep { asset_id: public }
+        [authority, pool_config, asset_vault, vault_token_account, destination, surplus_sweep, system_program],
+    "sweep_surplus" => SweepSurplus { asset_id: public }
+        [authority, pool_config, asset_vault, vault_token_account, destination, mint, surplus_sweep, token_program],
+    "cancel_surplus_sweep" => CancelSurplusSweep { asset_id: public }
+        [authority, pool_config, asset_vault, surplus_sweep],
+    "init_deferred_withdraw_events" => InitDeferredWithdrawEvents { delay_seconds: public }
+        [authority, pool_config, deferred_events, system_program],
+    "set_withdraw_event_delay" => SetWithdrawEventDelay { delay_seconds: public }
+        [authority, pool_config, deferred_events],
+    "flush_deferred_withdraw_events" => FlushDeferredWithdrawEvents { max_events: public }
+        [pool_config, deferred_events],
+    "register_deposit_hook" => RegisterDepositHook {  }
+        [authority, pool_config, deposit_hook, callback_program, system_program],
+    "remove_deposit_hook" => RemoveDepositHook {  }
+        [authority, pool_config, deposit_hook],
+    "initiate_authority_transfer_v2" => InitiateAuthorityTransferV2 { new_authority: public }
+        [authority, pool_config],
+    "accept_authority_transfer_v2" => AcceptAuthorityTransferV2 {  }
+        [new_authority, pool_config],
+    "cancel_authority_transfer_v2" => CancelAuthorityTransferV2 {  }
+        [authority, pool_config],
+    "configure_relayer_registry" => ConfigureRelayerRegistry { min_fee_bps: public, max_fee_bps: public, require_stake: public, min_stake_amount: public }
+        [authority, pool_config, relayer_registry],
+    "register_relayer" => RegisterRelayer { fee_bps: public, metadata_uri: public, metadata: public }
+        [operator, pool_config, relayer_registry, relayer_node, system_program, relayer_index_shard, relayer_tombstone],
+    "update_relayer" => UpdateRelayer { fee_bps: public, metadata_uri: public, is_active: public, metadata: public }
+        [operator, pool_config, relayer_registry, relayer_node, relayer_index_shard],
+    "update_relayer_signed" => UpdateRelayerSigned { fee_bps: public, is_active: public, nonce: public, expires_at: public }
+        [submitter, pool_config, relayer_registry, relayer_node, instructions_sysvar, relayer_index_shard],
+    "deactivate_relayer" => DeactivateRelayer {  }
+        [operator, pool_config, relayer_registry, relayer_node, relayer_index_shard],
+    "set_relayer_draining" => SetRelayerDraining { draining: public, inflight_allowance: public }
+        [operator, pool_config, relayer_registry, relayer_node],
+    "close_relayer" => CloseRelayer {  }
+        [operator, pool_config, relayer_registry, relayer_node, relayer_tombstone, system_program],
+    "set_relayer_cooldown" => SetRelayerCooldown { reregistration_cooldown_slots: public }
+        [authority, pool_config, relayer_registry],
+    "set_relayer_registrations_open" => SetRelayerRegistrationsOpen { open: public }
+        [authority, pool_config, relayer_registry],
+    "init_relayer_index_shard" => InitRelayerIndexShard { shard_index: public }
+        [payer, pool_config, relayer_registry, relayer_index_shard, system_program],
+    "set_gas_rebate_cap" => SetGasRebateCap { max_gas_rebate_lamports: public, max_priority_fee_hint: public }
+        [authority, pool_config, relayer_registry],
+    "init_relayer_rebate_ledger" => InitRelayerRebateLedger { priority_fee_hint: public }
+        [operator, relayer_node, rebate_ledger, system_program],
+    "update_relayer_priority_fee_hint" => UpdateRelayerPriorityFeeHint { priority_fee_hint: public }
+        [operator, rebate_ledger],
+    "init_relayer_fee_quote" => InitRelayerFeeQuote { flat_fee: public, fee_bps: public, valid_until_slot: public }
+        [operator, relayer_registry, relayer_node, fee_quote, system_program],
+    "update_relayer_fee_quote" => UpdateRelayerFeeQuote { flat_fee: public, fee_bps: public, valid_until_slot: public }
+        [operator, relayer_registry, relayer_node, fee_quote],
+    "claim_gas_rebate" => ClaimGasRebate {  }
+        [operator, pool_config, relayer_registry, treasury, relayer_node, rebate_ledger],
+    "init_rent_reserve" => InitRentReserve { max_outstanding_per_relayer: public }
+        [authority, pool_config, rent_reserve, system_program],
+    "set_rent_advance_cap" => SetRentAdvanceCap { max_outstanding_per_relayer: public }
+        [authority, pool_config, rent_reserve],
+    "fund_rent_reserve" => FundRentReserve { amount: public }
+        [funder, pool_config, rent_reserve, system_program],
+    "init_rent_advance" => InitRentAdvance {  }
+        [operator, pool_config, rent_reserve, relayer_node, rent_advance, system_program],
+    "repay_rent_advance" => RepayRentAdvance { amount: public }
+        [operator, rent_reserve, rent_advance, system_program],
+    "set_asset_relayer_fee_bounds" => SetAssetRelayerFeeBounds { asset_id: public, min_relayer_fee: public, max_relayer_fee: public }
+        [authority, pool_config, asset_vault],
+    "set_relayer_stake_mint" => SetRelayerStakeMint {  }
+        [authority, pool_config, relayer_registry, stake_mint],
+    "init_relayer_stake_vault" => InitRelayerStakeVault {  }
+        [operator, pool_config, relayer_registry, relayer_node, stake_mint, stake_vault, token_program, system_program],
+    "stake_relayer" => StakeRelayer { amount: public }
+        [operator, pool_config, relayer_registry, relayer_node, stake_mint, stake_vault, operator_token_account, token_program],
+    "unstake_relayer" => UnstakeRelayer { amount: public }
+        [operator, pool_config, relayer_registry, relayer_node],
+    "withdraw_relayer_stake" => WithdrawRelayerStake {  }
+        [operator, pool_config, relayer_registry, relayer_node, stake_mint, stake_vault, operator_token_account, token_program],
+    "slash_relayer" => SlashRelayer { slash_bps: public, reputation_penalty: public, deactivate: public, evidence_hash: public }
+        [authority, pool_config, relayer_registry, relayer_node, relayer_index_shard, stake_mint, stake_vault, asset_vault, treasury_token_account, token_program],
+    "report_relayer_failure" => ReportRelayerFailure { evidence_hash: public }
+        [authority, pool_config, relayer_registry, relayer_node],
+    "reserve_commitment" => ReserveCommitment { commitment: public }
+        [depositor, pool_config, commitment_reservation, system_program],
+    "release_commitment_reservation" => ReleaseCommitmentReservation {  }
+        [depositor, commitment_reservation],
+    "deposit_masp" => DepositMasp { amount: redacted, commitment: public, asset_id: public, proof_data: public, encrypted_note: redacted }
+        [depositor, pool_config, global_config, authority, merkle_tree, pending_buffer, asset_vault, vault_token_account, user_token_account, mint, deposit_vk, token_program, system_program, instructions_sysvar, proof_stats, anonymity_stats, commitment_reservation, risk_tiers],
+    "deposit_masp_batch" => DepositMaspBatch { asset_id: public, amounts: redacted, commitments: public, proofs: public, encrypted_notes: redacted }
+        [depositor, pool_config, global_config, authority, merkle_tree, pending_buffer, asset_vault, vault_token_account, user_token_account, mint, deposit_vk, token_program, system_program, instructions_sysvar, proof_stats, anonymity_stats, risk_tiers],
+    "batch_process_deposits" => BatchProcessDeposits { max_to_process: public, target_cu_budget: public, expected_batch_seq: public }
+        [batcher, pool_config, global_config, merkle_tree, pending_buffer, batcher_role, deposit_hook, callback_program],
+    "register_batcher" => RegisterBatcher { bond_lamports: public }
+        [authority, batcher, pool_config, batcher_role, system_program],
+    "request_batcher_exit" => RequestBatcherExit {  }
+        [batcher, pool_config, batcher_role],
+    "withdraw_batcher_bond" => WithdrawBatcherBond {  }
+        [batcher, pool_config, batcher_role],
+    "report_batcher_misbehavior" => ReportBatcherMisbehavior { evidence_hash: public }
+        [authority, pool_config, batcher_role, insurance_fund],
+    "settle_deposits_batch" => SettleDepositsBatch { args: public }
+        [authority, pool_config, merkle_tree, pending_buffer, verification_key, proof_stats],
+    "withdraw_masp" => WithdrawMasp { proof_data: public, merkle_root: public, nullifier_hash: public, recipient: redacted, amount: redacted, asset_id: public, relayer_fee: public }
+        [relayer, pool_config, global_config, merkle_tree, vk_account, asset_vault, vault_token_account, recipient_token_account, relayer_token_account, spent_nullifier, relayer_registry, relayer_node, yield_registry, token_program, system_program, instructions_sysvar, proof_stats, rebate_ledger, migration_window, deferred_events, recipient_wallet, rent_reserve, rent_advance, mint, anonymity_stats, parameter_snapshot, recipient_ata, associated_token_program, fee_payer, treasury_token_account, withdrawal_commit, fee_quote, risk_tiers, withdrawal_window],
+    "withdraw_with_fee_payer_refund" => WithdrawWithFeePayerRefund { proof_data: public, merkle_root: public, nullifier_hash: public, recipient: redacted, amount: redacted, asset_id: public, relayer_fee: public, fee_payer_refund: public }
+        [relayer, pool_config, global_config, merkle_tree, vk_account, asset_vault, vault_token_account, recipient_token_account, relayer_token_account, spent_nullifier, relayer_registry, relayer_node, yield_registry, token_program, system_program, instructions_sysvar, proof_stats, rebate_ledger, migration_window, deferred_events, recipient_wallet, rent_reserve, rent_advance, mint, anonymity_stats, parameter_snapshot, recipient_ata, associated_token_program, fee_payer, treasury_token_account, withdrawal_commit, fee_quote, risk_tiers, withdrawal_window],
+    "withdraw_with_parameter_pin" => WithdrawWithParameterPin { proof_data: public, merkle_root: public, nullifier_hash: public, recipient: redacted, amount: redacted, asset_id: public, relayer_fee: public, parameter_version: public }
+        [relayer, pool_config, global_config, merkle_tree, vk_account, asset_vault, vault_token_account, recipient_token_account, relayer_token_account, spent_nullifier, relayer_registry, relayer_node, yield_registry, token_program, system_program, instructions_sysvar, proof_stats, rebate_ledger, migration_window, deferred_events, recipient_wallet, rent_reserve, rent_advance, mint, anonymity_stats, parameter_snapshot, recipient_ata, associated_token_program, fee_payer, treasury_token_account, withdrawal_commit, fee_quote, risk_tiers, withdrawal_window],
+    "commit_withdrawal" => CommitWithdrawal { commit_hash: public }
+        [committer, pool_config, withdrawal_commit, system_program],
+    "cancel_withdrawal_commit" => CancelWithdrawalCommit {  }
+        [committer, withdrawal_commit],
+    "commit_disclosed_withdraw" => CommitDisclosedWithdraw { binding: public }
+        [relayer, pool_config, disclosed_commitment, system_program],
+    "disclosed_path_withdraw" => DisclosedPathWithdraw { binding: public, nullifier_hash: public, asset_id: public, recipient: redacted, relayer_fee: public, note: redacted }
+        [relayer, pool_config, global_config, merkle_tree, disclosed_commitment, asset_vault, vault_token_account, recipient_token_account, relayer_token_account, recipient_wallet, treasury_token_account, mint, spent_nullifier, token_program, system_program, yield_registry, instructions_sysvar, risk_tiers, withdrawal_window],
+    "estimate_privacy_score" => EstimatePrivacyScore { merkle_root: public, asset_id: public }
+        [pool_config, merkle_tree, asset_vault],
+    "verify_account_integrity" => VerifyAccountIntegrity {  }
+        [pool_config, target_account],
+    "publish_attested_root" => PublishAttestedRoot { root: public }
+        [authority, pool_config, merkle_tree, attested_root, instructions_sysvar, system_program],
+    "record_note_receipt" => RecordNoteReceipt { nullifier_hash: public, note_receipt: redacted }
+        [relayer, pool_config, spent_nullifier],
+    "create_one_time_recipient" => CreateOneTimeRecipient { asset_id: public, ephemeral_pubkey: public, view_tag: public }
+        [payer, pool_config, asset_vault, mint, one_time_owner, recipient_token_account, token_program, associated_token_program, system_program],
+    "withdraw_v2" => WithdrawV2 { proof_data: public, merkle_root: public, asset_id: public, nullifier_hash_0: public, nullifier_hash_1: public, change_commitment: public, recipient: redacted, amount: redacted, relayer_fee: public }
+        [relayer, pool_config, global_config, merkle_tree, vk_account, asset_vault, vault_token_account, recipient_token_account, relayer_token_account, spent_nullifier_0, spent_nullifier_1, pending_buffer, relayer_registry, relayer_node, yield_registry, token_program, system_program, instructions_sysvar, proof_stats, rebate_ledger, migration_window, rent_reserve, rent_advance, anonymity_stats, fee_payer, treasury_token_account, risk_tiers, withdrawal_window],
+    "withdraw_yield_v2" => WithdrawYieldV2 { proof_data: public, merkle_root: public, asset_id: public, nullifier_hash_0: public, nullifier_hash_1: public, change_commitment: public, recipient: redacted, amount: redacted, relayer_fee: public }
+        [relayer, pool_config, global_config, merkle_tree, vk_account, asset_vault, vault_token_account, recipient_token_account, relayer_token_account, spent_nullifier_0, spent_nullifier_1, pending_buffer, relayer_registry, relayer_node, yield_registry, token_program, system_program, instructions_sysvar, proof_stats, migration_window, anonymity_stats, fee_payer, risk_tiers, withdrawal_window],
+    "private_transfer_join_split" => PrivateTransferJoinSplit { proof_data: public, merkle_root: public, input_nullifiers: public, output_commitments: public, public_amount: redacted, recipient: redacted, asset_id: public, relayer_fee: public, encrypted_outputs: redacted }
+        [relayer, pool_config, global_config, merkle_tree, vk_account, asset_vault, vault_token_account, relayer_token_account, relayer_registry, token_program, system_program, relayer_node, mint, yield_registry, instructions_sysvar, treasury_token_account, risk_tiers, withdrawal_window, anonymity_stats, migration_window, recipient_token_account, proof_stats],
+    "init_action_allowlist" => InitActionAllowlist { action_type: public }
+        [authority, pool_config, action_allowlist, system_program],
+    "add_action_program" => AddActionProgram { program: public }
+        [authority, pool_config, action_allowlist],
+    "remove_action_program" => RemoveActionProgram { program: public }
+        [authority, pool_config, action_allowlist],
+    "execute_shielded_action" => ExecuteShieldedAction { action_type: public, proof_data: public, action_data: redacted }
+        [relayer, pool_config, global_config, merkle_tree, vk_account, input_vault, input_vault_token_account, input_mint, output_vault, output_vault_token_account, output_mint, action_authority, input_escrow, output_escrow, spent_nullifier, relayer_token_account, action_allowlist, target_program, token_program, system_program, risk_tiers, withdrawal_window],
+    "init_yield_registry" => InitYieldRegistry {  }
+        [authority, pool_config, yield_registry, system_program],
+    "add_yield_mint" => AddYieldMint { mint: public }
+        [authority, pool_config, yield_registry],
+    "remove_yield_mint" => RemoveYieldMint { mint: public }
+        [authority, pool_config, yield_registry],
+    "init_proof_stats" => InitProofStats {  }
+        [authority, pool_config, proof_stats, system_program],
+    "init_anonymity_stats" => InitAnonymityStats {  }
+        [authority, pool_config, asset_vault, anonymity_stats, system_program],
+    "enable_feature" => EnableFeature { feature: public }
+        [authority, pool_config],
+    "disable_feature" => DisableFeature { feature: public }
+        [authority, pool_config],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::{InstructionData, ToAccountMetas};
+
+    fn keys(metas: Vec<AccountMeta>) -> Vec<Pubkey> {
+        metas.into_iter().map(|m| m.pubkey).collect()
+    }
+
+    fn param<'a>(decoded: &'a DecodedInstruction, name: &str) -> &'a Shown<String> {
+        &decoded
+            .params
+            .iter()
+            .find(|p| p.name == name)
+            .unwrap()
+            .value
+    }
+
+    fn account<'a>(decoded: &'a DecodedInstruction, name: &str) -> &'a DecodedAccount {
+        decoded.accounts.iter().find(|a| a.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_deposit_redacts_amount_and_depositor() {
+        let data = instruction::DepositMasp {
+            amount: 1_000_000,
+            commitment: [0xab; 32],
+            asset_id: [0x01; 32],
+            proof_data: vec![0u8; 256],
+            encrypted_note: Some(vec![7u8; 80]),
+        }
+        .data();
+        let depositor = Pubkey::new_unique();
+        let user_token_account = Pubkey::new_unique();
+        let pending_buffer = Pubkey::new_unique();
+        let metas = crate::accounts::DepositMasp {
+            depositor,
+            pool_config: Pubkey::new_unique(),
+            global_config: Pubkey::new_unique(),
+            authority: Pubkey::new_unique(),
+            merkle_tree: Pubkey::new_unique(),
+            pending_buffer,
+            asset_vault: Pubkey::new_unique(),
+            vault_token_account: Some(Pubkey::new_unique()),
+            user_token_account: Some(user_token_account),
+            mint: Some(Pubkey::new_unique()),
+            deposit_vk: Pubkey::new_unique(),
+            token_program: anchor_spl::token::ID,
+            system_program: anchor_lang::system_program::ID,
+            instructions_sysvar: solana_sdk_ids::sysvar::instructions::ID,
+            proof_stats: None,
+            anonymity_stats: None,
+            commitment_reservation: Pubkey::new_unique(),
+            risk_tiers: None,
+        }
+        .to_account_metas(None);
+
+        let decoded = decode_instruction(&data, &keys(metas)).unwrap();
+        assert_eq!(decoded.name, "deposit_masp");
+        assert_eq!(*param(&decoded, "amount"), Shown::Redacted);
+        assert_eq!(*param(&decoded, "encrypted_note"), Shown::Redacted);
+        assert_eq!(
+            *param(&decoded, "commitment"),
+            Shown::Public(hex::encode([0xab; 32]))
+        );
+        assert_eq!(
+            *param(&decoded, "proof_data"),
+            Shown::Public("<256 bytes>".to_string())
+        );
+
+        assert_eq!(account(&decoded, "depositor").key, Some(Shown::Redacted));
+        assert_eq!(
+            account(&decoded, "user_token_account").role,
+            AccountRole::User
+        );
+        assert_eq!(
+            account(&decoded, "pending_buffer").key,
+            Some(Shown::Public(pending_buffer))
+        );
+        assert_eq!(account(&decoded, "pending_buffer").role, AccountRole::Pda);
+        assert_eq!(
+            account(&decoded, "token_program").role,
+            AccountRole::Program
+        );
+        assert_eq!(account(&decoded, "proof_stats").key, None);
+
+        let text = decoded.to_string();
+        assert!(!text.contains("1000000"));
+        assert!(!text.contains(&depositor.to_string()));
+        assert!(!text.contains(&user_token_account.to_string()));
+    }
+
+    #[test]
+    fn test_withdraw_redacts_recipient_and_amount() {
+        let recipient = Pubkey::new_unique();
+        let relayer = Pubkey::new_unique();
+        let data = instruction::WithdrawMasp {
+            proof_data: vec![0u8; 256],
+            merkle_root: [0x11; 32],
+            nullifier_hash: [0x22; 32],
+            recipient,
+            amount: 123_456,
+            asset_id: [0x01; 32],
+            relayer_fee: 1_000,
+        }
+        .data();
+        let accounts = [relayer, Pubkey::new_unique(), Pubkey::new_unique()];
+
+        let decoded = decode_instruction(&data, &accounts).unwrap();
+        assert_eq!(decoded.name, "withdraw_masp");
+        assert_eq!(*param(&decoded, "recipient"), Shown::Redacted);
+        assert_eq!(*param(&decoded, "amount"), Shown::Redacted);
+        assert_eq!(
+            *param(&decoded, "nullifier_hash"),
+            Shown::Public(hex::encode([0x22; 32]))
+        );
+        assert_eq!(
+            *param(&decoded, "relayer_fee"),
+            Shown::Public("1000".to_string())
+        );
+        // Only the accounts that were supplied are labelled
+        assert_eq!(decoded.accounts.len(), 3);
+        assert_eq!(
+            account(&decoded, "relayer").key,
+            Some(Shown::Public(relayer))
+        );
+
+        let text = decoded.to_string();
+        assert!(!text.contains(&recipient.to_string()));
+        assert!(!text.contains("123456"));
+    }
+
+    #[test]
+    fn test_admin_parameters_and_remaining_accounts() {
+        let data = instruction::BatchProcessDeposits {
+            max_to_process: 10,
+            target_cu_budget: None,
+            expected_batch_seq: Some(4),
+        }
+        .data();
+        let accounts: Vec<Pubkey> = (0..9).map(|_| Pubkey::new_unique()).collect();
+
+        let decoded = decode_instruction(&data, &accounts).unwrap();
+        assert_eq!(decoded.name, "batch_process_deposits");
+        assert_eq!(
+            decoded.params,
+            vec![
+                DecodedParam {
+                    name: "max_to_process",
+                    value: Shown::Public("10".to_string()),
+                },
+                DecodedParam {
+                    name: "target_cu_budget",
+                    value: Shown::Public("none".to_string()),
+                },
+                DecodedParam {
+                    name: "expected_batch_seq",
+                    value: Shown::Public("4".to_string()),
+                },
+            ]
+        );
+        assert_eq!(decoded.accounts[2].name, "global_config");
+        assert_eq!(decoded.accounts[4].name, "pending_buffer");
+        assert_eq!(decoded.accounts[5].name, "batcher_role");
+        assert_eq!(decoded.accounts[7].name, "callback_program");
+        assert_eq!(decoded.accounts[8].role, AccountRole::Remaining);
+        assert_eq!(decoded.accounts[8].key, Some(Shown::Public(accounts[8])));
+    }
+
+    #[test]
+    fn test_rejects_unknown_and_malformed_data() {
+        assert_eq!(
+            decode_instruction(&[0u8; 4], &[]).unwrap_err(),
+            ErrorCode::InstructionMissing.into()
+        );
+        assert_eq!(
+            decode_instruction(&[0u8; 16], &[]).unwrap_err(),
+            ErrorCode::InstructionFallbackNotFound.into()
+        );
+        let mut data = instruction::WithdrawMasp {
+            proof_data: vec![0u8; 256],
+            merkle_root: [0; 32],
+            nullifier_hash: [0; 32],
+            recipient: Pubkey::default(),
+            amount: 0,
+            asset_id: [0; 32],
+            relayer_fee: 0,
+        }
+        .data();
+        data.truncate(data.len() - 4);
+        assert_eq!(
+            decode_instruction(&data, &[]).unwrap_err(),
+            ErrorCode::InstructionDidNotDeserialize.into()
+        );
+    }
+}
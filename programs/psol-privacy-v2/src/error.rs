@@ -8,9 +8,15 @@ pub enum PrivacyErrorV2 {
     #[msg("Invalid proof: verification failed")]
     InvalidProof,
 
-    #[msg("Invalid proof format: expected 256 bytes")]
+    #[msg("Invalid proof format: expected 256 bytes, or 257/129 with a format tag")]
     InvalidProofFormat,
 
+    #[msg("Unsupported proof format tag")]
+    UnsupportedProofFormat,
+
+    #[msg("Unsupported range proof scheme")]
+    UnsupportedRangeProofScheme,
+
     #[msg("Invalid public inputs for proof verification")]
     InvalidPublicInputs,
 
@@ -31,6 +37,24 @@ pub enum PrivacyErrorV2 {
     #[msg("VK IC length mismatch for proof type")]
     VkIcLengthMismatch,
 
+    #[msg("Circuit descriptor does not match pool configuration")]
+    CircuitDescriptorMismatch,
+
+    #[msg("Circuit Poseidon parameter set does not match the pool's Merkle tree")]
+    PoseidonParamsMismatch,
+
+    #[msg("Verification key has not passed a canary proof")]
+    VkCanaryNotPassed,
+
+    #[msg("Canary proof failed to verify against the verification key")]
+    CanaryProofFailed,
+
+    #[msg("Ceremony transcript hash and contributor count must be set together")]
+    InvalidCeremonyTranscript,
+
+    #[msg("Verification key already has a ceremony recorded")]
+    CeremonyAlreadyRecorded,
+
     #[msg("Cryptographic operation failed")]
     CryptographyError,
 
@@ -58,12 +82,30 @@ pub enum PrivacyErrorV2 {
     #[msg("Merkle tree is full")]
     MerkleTreeFull,
 
+    #[msg("Merkle tree capacity exhausted: deposits are blocked")]
+    TreeCapacityExhausted,
+
+    #[msg("Invalid capacity thresholds: require 0 < warning < critical < 100")]
+    InvalidCapacityThresholds,
+
+    #[msg("Invalid migration window: duration must be 1s to 7 days")]
+    InvalidMigrationWindow,
+
+    #[msg("Withdraw event delay exceeds the 1 hour maximum")]
+    InvalidWithdrawEventDelay,
+
+    #[msg("Only the current Merkle root can be attested")]
+    StaleAttestedRoot,
+
     #[msg("Tree depth must be between 4 and 24")]
     InvalidTreeDepth,
 
     #[msg("Root history size must be at least 30")]
     InvalidRootHistorySize,
 
+    #[msg("Pools opened with create_pool need a tree depth between 16 and 24")]
+    InvalidCreatedPoolDepth,
+
     #[msg("Invalid Merkle tree pool reference")]
     InvalidMerkleTreePool,
 
@@ -84,6 +126,18 @@ pub enum PrivacyErrorV2 {
     #[msg("Duplicate nullifier in input set")]
     DuplicateNullifier,
 
+    #[msg("Invalid note receipt: cannot be all zeros")]
+    InvalidNoteReceipt,
+
+    #[msg("A note receipt is already recorded for this nullifier")]
+    NoteReceiptAlreadyRecorded,
+
+    #[msg("Pool is privacy-strict and records no note receipts")]
+    NoteReceiptsDisabled,
+
+    #[msg("Nullifier record predates note receipts and cannot hold one")]
+    NoteReceiptLegacyRecord,
+
     // =========================================================================
     // AMOUNT/VALUE ERRORS
     // =========================================================================
@@ -102,6 +156,9 @@ pub enum PrivacyErrorV2 {
     #[msg("Amount exceeds maximum deposit")]
     ExceedsMaximumDeposit,
 
+    #[msg("Amount below the asset's minimum withdrawal")]
+    BelowMinimumWithdrawal,
+
     #[msg("Arithmetic overflow")]
     ArithmeticOverflow,
 
@@ -141,6 +198,57 @@ pub enum PrivacyErrorV2 {
     #[msg("Invalid vault token account")]
     InvalidVaultTokenAccount,
 
+    #[msg("Permissionless asset registration is closed")]
+    AssetRegistrationClosed,
+
+    #[msg("Mint is not on the pool's allowlist")]
+    MintNotAllowlisted,
+
+    #[msg("Mint is already on the pool's allowlist")]
+    MintAlreadyAllowlisted,
+
+    #[msg("Mint allowlist is full")]
+    MintAllowlistFull,
+
+    #[msg("Token metadata account is not the mint's Metaplex metadata")]
+    InvalidTokenMetadata,
+
+    #[msg("An account required for this asset type was not provided")]
+    MissingAssetAccount,
+
+    #[msg("Surplus sweep timelock has not elapsed")]
+    SurplusSweepTimelocked,
+
+    #[msg("Vault holds no tokens above its shielded balance")]
+    NoSurplusToSweep,
+
+    #[msg("Native SOL vault surplus is collected into the treasury, not swept")]
+    SurplusSweepRequiresToken,
+
+    #[msg("Vault token balance does not match the recorded transfer")]
+    VaultBalanceDrift,
+
+    #[msg("Risk tier does not exist")]
+    InvalidRiskTier,
+
+    #[msg("Asset is in a risk tier but the pool's risk tier table was not provided")]
+    RiskTiersRequired,
+
+    #[msg("Amount exceeds the risk tier's maximum withdrawal")]
+    ExceedsTierWithdrawalLimit,
+
+    #[msg("Asset's withdrawal rate limit for the current window is exhausted")]
+    WithdrawalRateLimitExceeded,
+
+    #[msg("Risk tier rate-limits withdrawals but the asset's withdrawal window was not provided")]
+    WithdrawalWindowRequired,
+
+    #[msg("Withdrawal at or above the risk tier's delay threshold must be sealed with a withdrawal commit")]
+    WithdrawalDelayRequired,
+
+    #[msg("Withdrawal commit is younger than the risk tier's withdrawal delay")]
+    WithdrawalDelayNotElapsed,
+
     // =========================================================================
     // COMMITMENT ERRORS
     // =========================================================================
@@ -153,6 +261,12 @@ pub enum PrivacyErrorV2 {
     #[msg("Duplicate commitment in output set")]
     DuplicateCommitment,
 
+    #[msg("Encrypted note exceeds the pool's maximum size")]
+    EncryptedNoteTooLarge,
+
+    #[msg("Invalid encrypted note limit: must be 1 to 1024 bytes")]
+    InvalidEncryptedNoteLimit,
+
     // =========================================================================
     // AUTHORIZATION ERRORS
     // =========================================================================
@@ -167,6 +281,9 @@ pub enum PrivacyErrorV2 {
 
     #[msg("Recipient does not match proof public inputs")]
     RecipientMismatch,
+
+    #[msg("Invalid one-time recipient ephemeral key")]
+    InvalidOneTimeAddress,
     #[msg("Required account is missing")]
     MissingAccount,
 
@@ -185,12 +302,54 @@ pub enum PrivacyErrorV2 {
     #[msg("Relayer not active")]
     RelayerNotActive,
 
+    #[msg("Relayer is draining and accepts no new withdrawals")]
+    RelayerDraining,
+
     #[msg("Relayer fee out of allowed range")]
     RelayerFeeOutOfRange,
 
     #[msg("Relayer fee calculation overflow - fee is unreasonably large")]
     RelayerFeeOverflow,
 
+    #[msg("Fee payer refund exceeds the maximum or the withdrawal")]
+    FeePayerRefundTooLarge,
+
+    #[msg("Relayer fee, fee payer refund and protocol fee exceed the withdrawal")]
+    FeesExceedAmount,
+
+    #[msg("Fee payer refunds are only paid from native SOL withdrawals")]
+    FeePayerRefundRequiresNativeSol,
+
+    #[msg("Protocol fee exceeds the maximum")]
+    ProtocolFeeTooHigh,
+
+    #[msg("Protocol fee is charged but the asset's treasury was not given")]
+    TreasuryRequired,
+
+    #[msg("Native SOL vaults have no treasury token account")]
+    TreasuryRequiresToken,
+
+    #[msg("Only native SOL vaults hold fees to collect")]
+    TreasuryCollectRequiresNativeSol,
+
+    #[msg("Vault holds no fees to collect")]
+    NoTreasuryFeesToCollect,
+
+    #[msg("A treasury disbursement is already pending")]
+    TreasuryDisbursementPending,
+
+    #[msg("No treasury disbursement is pending")]
+    NoTreasuryDisbursement,
+
+    #[msg("Treasury disbursement timelock has not elapsed")]
+    TreasuryDisbursementTimelocked,
+
+    #[msg("Relayer fee below the asset's absolute minimum")]
+    RelayerFeeBelowMinimum,
+
+    #[msg("Relayer fee above the asset's absolute maximum")]
+    RelayerFeeAboveMaximum,
+
     #[msg("Invalid fee configuration")]
     InvalidFeeConfiguration,
 
@@ -206,6 +365,69 @@ pub enum PrivacyErrorV2 {
     #[msg("RelayerNode registry mismatch: node does not belong to expected registry")]
     RelayerNodeRegistryMismatch,
 
+    #[msg("No gas rebate available to claim")]
+    NoRebateAvailable,
+
+    #[msg("Rebate ledger does not belong to this relayer")]
+    RebateLedgerMismatch,
+
+    #[msg("Rent advance account does not belong to this relayer or reserve")]
+    RentAdvanceMismatch,
+
+    #[msg("Nullifier rent is covered by the fee payer: a rent advance cannot be taken too")]
+    RentAdvanceWithFeePayer,
+
+    #[msg("Rent repayment must be non-zero and no more than the outstanding advance")]
+    InvalidRentRepayment,
+
+    #[msg("Signed relayer update nonce does not match the relayer's next nonce")]
+    RelayerUpdateNonceMismatch,
+
+    #[msg("Signed relayer update has expired")]
+    SignedUpdateExpired,
+
+    #[msg("Missing or invalid ed25519 signature instruction")]
+    InvalidSignatureInstruction,
+
+    #[msg("Relayer index shard missing or does not match the relayer")]
+    RelayerIndexMismatch,
+
+    #[msg("Relayer must be deactivated before it can be closed")]
+    RelayerStillActive,
+
+    #[msg("Operator closed a relayer too recently to register again")]
+    RelayerCooldownActive,
+
+    #[msg("Signed relayer job has expired")]
+    RelayerJobExpired,
+
+    #[msg("Submitted inputs or proof differ from the signed relayer job")]
+    RelayerJobMismatch,
+
+    #[msg("Relayer registry has no stake mint")]
+    RelayerStakeMintNotSet,
+
+    #[msg("Relayer stake is still held in a stake vault")]
+    RelayerStakeOutstanding,
+
+    #[msg("Relayer stake is still unbonding")]
+    RelayerStakeUnbonding,
+
+    #[msg("Relayer has no stake unbonding")]
+    NoRelayerStakeUnbonding,
+
+    #[msg("Slash fraction must be between 1 and 10000 basis points")]
+    InvalidSlashFraction,
+
+    #[msg("Relayer fee quote has expired")]
+    FeeQuoteExpired,
+
+    #[msg("Relayer fee is above the relayer's published quote")]
+    RelayerFeeAboveQuote,
+
+    #[msg("Fee quote does not belong to this relayer")]
+    FeeQuoteMismatch,
+
     // =========================================================================
     // STATE ERRORS
     // =========================================================================
@@ -215,9 +437,24 @@ pub enum PrivacyErrorV2 {
     #[msg("Pool is not paused")]
     PoolNotPaused,
 
+    #[msg("All pools are paused by the super guardian")]
+    GlobalPaused,
+
+    #[msg("Pools are not globally paused")]
+    GlobalNotPaused,
+
+    #[msg("Pool creation fee exceeds the creator's limit")]
+    PoolCreationFeeTooHigh,
+
+    #[msg("Pool is already listed in the pool directory")]
+    PoolAlreadyListed,
+
     #[msg("Pool is not active")]
     PoolInactive,
 
+    #[msg("Deposit and withdrawal against the same pool in one transaction")]
+    SameTransactionDepositWithdraw,
+
     #[msg("Account already initialized")]
     AlreadyInitialized,
 
@@ -236,11 +473,53 @@ pub enum PrivacyErrorV2 {
     #[msg("Invalid timestamp")]
     InvalidTimestamp,
 
+    #[msg("A pinned withdrawal needs the asset's parameter snapshot")]
+    ParameterSnapshotRequired,
+
+    #[msg("Pool parameters changed adversely since the pinned version")]
+    ParametersChangedSincePin,
+
+    #[msg("The pool keeps anonymity statistics: pass the asset's account")]
+    AnonymityStatsRequired,
+
+    #[msg("Withdrawal does not match its sealed commitment")]
+    WithdrawalCommitMismatch,
+
+    #[msg("A committed withdrawal cannot carry a fee payer refund or parameter pin")]
+    WithdrawalCommitConflict,
+
+    #[msg("Commitment is reserved by another depositor")]
+    CommitmentReserved,
+
     // =========================================================================
     // BATCHING ERRORS (NEW - from security fixes)
     // =========================================================================
-    #[msg("Pending deposits buffer is full")]
-    BufferFull,
+    #[msg("Pending deposits buffer is full - retry after the estimated wait")]
+    PendingBufferFull,
+
+    #[msg("Pending deposits buffer uses the legacy layout - run migrate_pending_buffer")]
+    PendingBufferNeedsMigration,
+
+    #[msg("Pending deposits buffer already uses the current layout")]
+    PendingBufferAlreadyMigrated,
+
+    #[msg("Pool config already uses the current layout")]
+    PoolConfigAlreadyMigrated,
+
+    #[msg("Merkle tree already uses the current layout")]
+    MerkleTreeAlreadyMigrated,
+
+    #[msg("Asset vault already uses the current layout")]
+    AssetVaultAlreadyMigrated,
+
+    #[msg("Relayer registry already uses the current layout")]
+    RelayerRegistryAlreadyMigrated,
+
+    #[msg("Relayer node already uses the current layout")]
+    RelayerNodeAlreadyMigrated,
+
+    #[msg("Verification key already uses the current layout")]
+    VerificationKeyAlreadyMigrated,
 
     #[msg("No pending deposits to process")]
     NoPendingDeposits,
@@ -251,6 +530,27 @@ pub enum PrivacyErrorV2 {
     #[msg("Invalid batch size - must be between 1 and MAX_BATCH_SIZE")]
     InvalidBatchSize,
 
+    #[msg("Batch sequence mismatch - buffer was processed by another batcher")]
+    BatchSequenceMismatch,
+
+    #[msg("Deposit batch needs one amount, proof and note per commitment")]
+    DepositBatchLengthMismatch,
+
+    #[msg("Batcher bond is below the minimum")]
+    BatcherBondTooLow,
+
+    #[msg("Batcher is not enabled and bonded")]
+    BatcherNotBonded,
+
+    #[msg("Batcher bond is still unbonding")]
+    BatcherUnbonding,
+
+    #[msg("Batcher bond was already slashed")]
+    BatcherAlreadySlashed,
+
+    #[msg("Crank bounty exceeds the per-leaf maximum")]
+    CrankBountyTooLarge,
+
     // =========================================================================
     // FEATURE ERRORS
     // =========================================================================
@@ -269,6 +569,21 @@ pub enum PrivacyErrorV2 {
     #[msg("Shielded CPI not enabled")]
     ShieldedCpiDisabled,
 
+    #[msg("Disclosed-path withdrawals not enabled")]
+    DisclosedPathWithdrawalsDisabled,
+
+    #[msg("Withdraw verification key is set; use proof withdrawals")]
+    WithdrawVerificationKeySet,
+
+    #[msg("Tree too deep to verify Merkle paths on-chain")]
+    TreeTooDeepForDisclosedPath,
+
+    #[msg("Disclosed withdrawal commitment is too recent to reveal")]
+    DisclosedWithdrawNotMatured,
+
+    #[msg("Disclosed withdrawal does not match its commitment")]
+    DisclosedWithdrawBindingMismatch,
+
     // =========================================================================
     // COMPLIANCE ERRORS
     // =========================================================================
@@ -281,6 +596,18 @@ pub enum PrivacyErrorV2 {
     #[msg("Audit metadata already attached")]
     MetadataAlreadyAttached,
 
+    #[msg("Compliance recovery was not enabled when the pool was created")]
+    ComplianceRecoveryDisabled,
+
+    #[msg("Signer is not the pool's auditor")]
+    AuditorMismatch,
+
+    #[msg("Compliance recovery requires a court order reference")]
+    CourtOrderRequired,
+
+    #[msg("Disclosed note does not open the commitment")]
+    DisclosureMismatch,
+
     // =========================================================================
     // INPUT VALIDATION
     // =========================================================================
@@ -293,6 +620,21 @@ pub enum PrivacyErrorV2 {
     #[msg("Invalid account discriminator")]
     InvalidDiscriminator,
 
+    #[msg("Program id does not match the id this build was compiled for")]
+    ProgramIdMismatch,
+
+    #[msg("Too many remaining accounts for this instruction (see tx_limits)")]
+    TooManyRemainingAccounts,
+
+    #[msg("Remaining account count does not match the instruction's inputs")]
+    RemainingAccountsMismatch,
+
+    #[msg("Instruction data exceeds this instruction's size limit (see tx_limits)")]
+    InstructionDataTooLarge,
+
+    #[msg("Not enough compute units left for proof verification; raise the compute unit limit")]
+    InsufficientComputeBudget,
+
     // =========================================================================
     // CPI ERRORS
     // =========================================================================
@@ -305,6 +647,36 @@ pub enum PrivacyErrorV2 {
     #[msg("Invalid action data")]
     InvalidActionData,
 
+    #[msg("Target program is not the program this action's adapter calls")]
+    InvalidTargetProgram,
+
+    #[msg("Target program is not on the action type's allowlist")]
+    TargetProgramNotAllowlisted,
+
+    #[msg("Program is already on the action type's allowlist")]
+    ProgramAlreadyAllowlisted,
+
+    #[msg("Action allowlist is full")]
+    ActionAllowlistFull,
+
+    #[msg("Swap slippage or minimum output out of bounds")]
+    SlippageOutOfBounds,
+
+    #[msg("Action output below the minimum amount")]
+    SlippageExceeded,
+
+    #[msg("Action left part of its input unspent")]
+    ActionInputNotSpent,
+
+    #[msg("Deposit hook callback must be an executable program other than this one")]
+    InvalidDepositHookProgram,
+
+    #[msg("Pool has a deposit hook: pass it and its callback program")]
+    DepositHookRequired,
+
+    #[msg("Callback program does not match the registered deposit hook")]
+    DepositHookProgramMismatch,
+
     // ========================================================================
     // Yield Mode Errors
     // ========================================================================
@@ -362,8 +734,264 @@ impl PrivacyErrorV2 {
             self,
             PrivacyErrorV2::PoolPaused
                 | PrivacyErrorV2::PoolNotPaused
+                | PrivacyErrorV2::GlobalPaused
                 | PrivacyErrorV2::PoolInactive
                 | PrivacyErrorV2::AlreadyInitialized
         )
     }
 }
+
+/// Lists every variant as `PrivacyErrorV2::ALL`; the exhaustive match makes
+/// a variant missing from the list a compile error
+macro_rules! all_errors {
+    ($($variant:ident),* $(,)?) => {
+        impl PrivacyErrorV2 {
+            /// Every error, in code order (for schema exports and tooling)
+            pub const ALL: &'static [PrivacyErrorV2] = &[$(PrivacyErrorV2::$variant),*];
+        }
+
+        const _: fn(PrivacyErrorV2) = |error| match error {
+            $(PrivacyErrorV2::$variant)|* => {}
+        };
+    };
+}
+
+all_errors!(
+    InvalidProof,
+    InvalidProofFormat,
+    UnsupportedProofFormat,
+    UnsupportedRangeProofScheme,
+    InvalidPublicInputs,
+    VerificationKeyNotSet,
+    VerificationKeyLocked,
+    VkAlreadyFinalized,
+    UnsupportedProofType,
+    ProofNotImplemented,
+    VkIcLengthMismatch,
+    CircuitDescriptorMismatch,
+    PoseidonParamsMismatch,
+    VkCanaryNotPassed,
+    CanaryProofFailed,
+    InvalidCeremonyTranscript,
+    CeremonyAlreadyRecorded,
+    CryptographyError,
+    InvalidVerificationKeyPool,
+    InvalidVerificationKeyType,
+    CryptoNotImplemented,
+    ProofVerificationFailedInvalid,
+    InvalidMerkleRoot,
+    MerkleTreeFull,
+    TreeCapacityExhausted,
+    InvalidCapacityThresholds,
+    InvalidMigrationWindow,
+    InvalidWithdrawEventDelay,
+    StaleAttestedRoot,
+    InvalidTreeDepth,
+    InvalidRootHistorySize,
+    InvalidCreatedPoolDepth,
+    InvalidMerkleTreePool,
+    LeafIndexNotFound,
+    InvalidPoolReference,
+    NullifierAlreadySpent,
+    InvalidNullifier,
+    TooManyNullifiers,
+    DuplicateNullifier,
+    InvalidNoteReceipt,
+    NoteReceiptAlreadyRecorded,
+    NoteReceiptsDisabled,
+    NoteReceiptLegacyRecord,
+    InvalidAmount,
+    InsufficientBalance,
+    RelayerFeeExceedsAmount,
+    BelowMinimumDeposit,
+    ExceedsMaximumDeposit,
+    BelowMinimumWithdrawal,
+    ArithmeticOverflow,
+    ValueConservationFailed,
+    InvalidMint,
+    AssetNotRegistered,
+    AssetNotActive,
+    TooManyAssets,
+    AssetIdMismatch,
+    DepositsDisabled,
+    WithdrawalsDisabled,
+    InvalidAssetId,
+    InvalidVaultPool,
+    InvalidVaultTokenAccount,
+    AssetRegistrationClosed,
+    MintNotAllowlisted,
+    MintAlreadyAllowlisted,
+    MintAllowlistFull,
+    InvalidTokenMetadata,
+    MissingAssetAccount,
+    SurplusSweepTimelocked,
+    NoSurplusToSweep,
+    SurplusSweepRequiresToken,
+    VaultBalanceDrift,
+    InvalidRiskTier,
+    RiskTiersRequired,
+    ExceedsTierWithdrawalLimit,
+    WithdrawalRateLimitExceeded,
+    WithdrawalWindowRequired,
+    WithdrawalDelayRequired,
+    WithdrawalDelayNotElapsed,
+    InvalidCommitment,
+    TooManyOutputs,
+    DuplicateCommitment,
+    EncryptedNoteTooLarge,
+    InvalidEncryptedNoteLimit,
+    Unauthorized,
+    InvalidAuthority,
+    NoPendingAuthority,
+    RecipientMismatch,
+    InvalidOneTimeAddress,
+    MissingAccount,
+    RelayerMismatch,
+    InvalidTokenOwner,
+    RelayerNotRegistered,
+    RelayerNotActive,
+    RelayerDraining,
+    RelayerFeeOutOfRange,
+    RelayerFeeOverflow,
+    FeePayerRefundTooLarge,
+    FeesExceedAmount,
+    FeePayerRefundRequiresNativeSol,
+    ProtocolFeeTooHigh,
+    TreasuryRequired,
+    TreasuryRequiresToken,
+    TreasuryCollectRequiresNativeSol,
+    NoTreasuryFeesToCollect,
+    TreasuryDisbursementPending,
+    NoTreasuryDisbursement,
+    TreasuryDisbursementTimelocked,
+    RelayerFeeBelowMinimum,
+    RelayerFeeAboveMaximum,
+    InvalidFeeConfiguration,
+    RegistrationsClosed,
+    InsufficientStake,
+    InvalidRelayerNodePda,
+    RelayerNodeRegistryMismatch,
+    NoRebateAvailable,
+    RebateLedgerMismatch,
+    RentAdvanceMismatch,
+    RentAdvanceWithFeePayer,
+    InvalidRentRepayment,
+    RelayerUpdateNonceMismatch,
+    SignedUpdateExpired,
+    InvalidSignatureInstruction,
+    RelayerIndexMismatch,
+    RelayerStillActive,
+    RelayerCooldownActive,
+    RelayerJobExpired,
+    RelayerJobMismatch,
+    RelayerStakeMintNotSet,
+    RelayerStakeOutstanding,
+    RelayerStakeUnbonding,
+    NoRelayerStakeUnbonding,
+    InvalidSlashFraction,
+    FeeQuoteExpired,
+    RelayerFeeAboveQuote,
+    FeeQuoteMismatch,
+    PoolPaused,
+    PoolNotPaused,
+    GlobalPaused,
+    GlobalNotPaused,
+    PoolCreationFeeTooHigh,
+    PoolAlreadyListed,
+    PoolInactive,
+    SameTransactionDepositWithdraw,
+    AlreadyInitialized,
+    CorruptedData,
+    InvalidMetadata,
+    InvalidInput,
+    LimitExceeded,
+    InvalidTimestamp,
+    ParameterSnapshotRequired,
+    ParametersChangedSincePin,
+    AnonymityStatsRequired,
+    WithdrawalCommitMismatch,
+    WithdrawalCommitConflict,
+    CommitmentReserved,
+    PendingBufferFull,
+    PendingBufferNeedsMigration,
+    PendingBufferAlreadyMigrated,
+    PoolConfigAlreadyMigrated,
+    MerkleTreeAlreadyMigrated,
+    AssetVaultAlreadyMigrated,
+    RelayerRegistryAlreadyMigrated,
+    RelayerNodeAlreadyMigrated,
+    VerificationKeyAlreadyMigrated,
+    NoPendingDeposits,
+    BatchNotReady,
+    InvalidBatchSize,
+    BatchSequenceMismatch,
+    DepositBatchLengthMismatch,
+    BatcherBondTooLow,
+    BatcherNotBonded,
+    BatcherUnbonding,
+    BatcherAlreadySlashed,
+    CrankBountyTooLarge,
+    FeatureDisabled,
+    NotImplemented,
+    JoinSplitDisabled,
+    MembershipProofsDisabled,
+    ShieldedCpiDisabled,
+    DisclosedPathWithdrawalsDisabled,
+    WithdrawVerificationKeySet,
+    TreeTooDeepForDisclosedPath,
+    DisclosedWithdrawNotMatured,
+    DisclosedWithdrawBindingMismatch,
+    EncryptedNoteRequired,
+    InvalidEncryptedNote,
+    MetadataAlreadyAttached,
+    ComplianceRecoveryDisabled,
+    AuditorMismatch,
+    CourtOrderRequired,
+    DisclosureMismatch,
+    InputTooLarge,
+    InvalidOwner,
+    InvalidDiscriminator,
+    ProgramIdMismatch,
+    TooManyRemainingAccounts,
+    RemainingAccountsMismatch,
+    InstructionDataTooLarge,
+    InsufficientComputeBudget,
+    UnsupportedShieldedAction,
+    CpiCallFailed,
+    InvalidActionData,
+    InvalidTargetProgram,
+    TargetProgramNotAllowlisted,
+    ProgramAlreadyAllowlisted,
+    ActionAllowlistFull,
+    SlippageOutOfBounds,
+    SlippageExceeded,
+    ActionInputNotSpent,
+    InvalidDepositHookProgram,
+    DepositHookRequired,
+    DepositHookProgramMismatch,
+    YieldAssetRequiresYieldExit,
+    YieldMintsExceeded,
+    YieldMintAlreadyExists,
+    YieldMintNotFound,
+    NonYieldAssetCannotUseYieldExit,
+    YieldRegistryRequired,
+    InvalidFeatureFlag,
+    CannotDisableCoreFeature,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_errors_in_code_order() {
+        for (i, error) in PrivacyErrorV2::ALL.iter().enumerate() {
+            assert_eq!(
+                u32::from(*error),
+                anchor_lang::error::ERROR_CODE_OFFSET + i as u32,
+                "{}",
+                error.name()
+            );
+        }
+    }
+}
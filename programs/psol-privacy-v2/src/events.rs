@@ -20,10 +20,51 @@ compile_error!(
 pub struct PoolInitializedV2 {
     pub pool: Pubkey,
     pub authority: Pubkey,
+    /// Id of the pool among the authority's pools
+    pub pool_id: u64,
     pub merkle_tree: Pubkey,
     pub relayer_registry: Pubkey,
     pub tree_depth: u8,
     pub root_history_size: u16,
+    /// Whether the pool allows `compliance_recovery_withdraw`
+    pub compliance_recovery_enabled: bool,
+    /// Poseidon parameter set the tree hashes with
+    pub poseidon_params_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+/// A pool was opened by anyone for a fee (`create_pool`); preceded by
+/// the pool's `PoolInitializedV2`
+#[event]
+pub struct PoolCreated {
+    pub pool: Pubkey,
+    pub creator: Pubkey,
+    pub pool_id: u64,
+    /// Lamports paid to the super guardian
+    pub creation_fee: u64,
+    pub timestamp: i64,
+}
+
+/// A pool was created from another pool's configuration
+/// (`clone_pool_config`)
+#[event]
+pub struct PoolConfigCloned {
+    pub source_pool: Pubkey,
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub pool_id: u64,
+    pub timestamp: i64,
+}
+
+/// A pool was added to the program-wide directory (`list_pool`)
+#[event]
+pub struct PoolListed {
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    /// Position of the pool's `PoolDirectoryEntry`
+    pub index: u64,
+    pub merkle_tree: Pubkey,
+    pub asset_count: u16,
     pub timestamp: i64,
 }
 
@@ -41,6 +82,38 @@ pub struct PoolUnpausedV2 {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct GlobalConfigInitialized {
+    pub super_guardian: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AllPoolsPaused {
+    pub super_guardian: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AllPoolsUnpaused {
+    pub super_guardian: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SuperGuardianUpdated {
+    pub previous: Pubkey,
+    pub super_guardian: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolCreationFeeUpdated {
+    pub previous: u64,
+    pub fee: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct AuthorityTransferInitiatedV2 {
     pub pool: Pubkey,
@@ -65,6 +138,116 @@ pub struct AuthorityTransferCancelledV2 {
     pub timestamp: i64,
 }
 
+/// Emitted when the authority opens a dual-root migration window.
+#[event]
+pub struct MigrationWindowOpened {
+    pub pool: Pubkey,
+    pub merkle_tree: Pubkey,
+    /// Number of pre-migration roots still accepted by withdrawals
+    pub roots_snapshotted: u16,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a migration window is closed and its rent reclaimed.
+#[event]
+pub struct MigrationWindowClosed {
+    pub pool: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when the authority publishes an asset's parameter snapshot.
+#[event]
+pub struct ParameterSnapshotPublished {
+    pub pool: Pubkey,
+    pub asset_id: [u8; 32],
+    pub version: u64,
+    pub parameters_hash: [u8; 32],
+    /// Whether pins to earlier versions stopped being accepted
+    pub adverse: bool,
+    pub timestamp: i64,
+}
+
+/// Emitted by `verify_account_integrity` for the account it checked.
+#[event]
+pub struct AccountIntegrityChecked {
+    pub pool: Pubkey,
+    pub account: Pubkey,
+    /// `IntegrityTarget`: 0 = verification key, 1 = Merkle tree,
+    /// 2 = pending deposits buffer
+    pub kind: u8,
+    pub passed: bool,
+    /// One bit per failed check (see `verify_account_integrity`)
+    pub failed_checks: u32,
+    pub timestamp: i64,
+}
+
+/// Emitted when the authority sets how long withdraw events are deferred.
+#[event]
+pub struct WithdrawEventDelaySet {
+    pub pool: Pubkey,
+    /// Seconds withdraw events are held back (0 = emitted immediately)
+    pub delay_seconds: u32,
+    pub timestamp: i64,
+}
+
+/// Emitted when the authority registers a deposit hook program.
+#[event]
+pub struct DepositHookRegistered {
+    pub pool: Pubkey,
+    pub callback_program: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when the authority removes the deposit hook.
+#[event]
+pub struct DepositHookRemoved {
+    pub pool: Pubkey,
+    pub callback_program: Pubkey,
+    pub callbacks_invoked: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted once each time the Merkle tree crosses a configured fullness
+/// threshold (warning, critical, full).
+#[event]
+pub struct TreeCapacityWarning {
+    pub pool: Pubkey,
+    pub merkle_tree: Pubkey,
+    /// Capacity level reached (PoolConfigV2::CAPACITY_LEVEL_*)
+    pub level: u8,
+    /// Threshold that was crossed, in percent
+    pub threshold_pct: u8,
+    /// Current fill percentage (0-100)
+    pub fill_percentage: u8,
+    pub leaves_used: u32,
+    pub capacity: u32,
+    pub timestamp: i64,
+}
+
+/// Disclosed-path withdrawals switched on or off. While on, the pool
+/// offers reduced privacy: `disclosed_path_withdraw` publishes which note
+/// was spent.
+#[event]
+pub struct DisclosedPathWithdrawalsSet {
+    pub pool: Pubkey,
+    pub enabled: bool,
+    pub tree_depth: u8,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when the authority publishes a signed `AttestedRootRecord`.
+#[event]
+pub struct RootAttested {
+    pub pool: Pubkey,
+    pub attested_root: Pubkey,
+    pub root: [u8; 32],
+    pub leaf_count: u32,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
 // =========================================================================
 // ASSET EVENTS
 // =========================================================================
@@ -76,6 +259,27 @@ pub struct AssetRegistered {
     pub mint: Pubkey,
     pub vault: Pubkey,
     pub decimals: u8,
+    /// Metaplex symbol, zero-padded; all zeros when metadata was not read
+    pub symbol: [u8; 10],
+    /// Hash of the Metaplex name; all zeros when metadata was not read
+    pub name_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+/// Emitted when a mint is added to or removed from the registration allowlist
+#[event]
+pub struct MintAllowlistUpdated {
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub allowlisted: bool,
+    pub timestamp: i64,
+}
+
+/// Emitted when permissionless registration of allowlisted mints opens or closes
+#[event]
+pub struct AssetRegistrationModeUpdated {
+    pub pool: Pubkey,
+    pub open: bool,
     pub timestamp: i64,
 }
 
@@ -88,6 +292,119 @@ pub struct AssetConfigUpdated {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct AssetMinWithdrawalUpdated {
+    pub pool: Pubkey,
+    pub asset_id: [u8; 32],
+    pub min_withdrawal: u64,
+    pub timestamp: i64,
+}
+
+/// A risk tier's limits changed; they apply at once to every asset in
+/// the tier
+#[event]
+pub struct RiskTierLimitsUpdated {
+    pub pool: Pubkey,
+    pub tier: u8,
+    pub deposits_enabled: bool,
+    pub withdrawals_enabled: bool,
+    pub max_deposit: u64,
+    pub max_withdrawal: u64,
+    pub rate_limit_window: u32,
+    pub rate_limit_amount: u64,
+    pub delay_threshold: u64,
+    pub withdrawal_delay: u32,
+    pub timestamp: i64,
+}
+
+/// An asset was put in a risk tier
+#[event]
+pub struct AssetRiskTierSet {
+    pub pool: Pubkey,
+    pub asset_id: [u8; 32],
+    pub tier: u8,
+    pub timestamp: i64,
+}
+
+/// Emitted when the authority announces a sweep of a vault's surplus
+#[event]
+pub struct SurplusSweepRequested {
+    pub pool: Pubkey,
+    pub asset_id: [u8; 32],
+    /// The asset's treasury token account
+    pub destination: Pubkey,
+    /// Surplus at request time; the sweep moves the surplus at execution
+    pub surplus: u64,
+    pub executable_at: i64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a vault's surplus is moved into its treasury
+#[event]
+pub struct SurplusSwept {
+    pub pool: Pubkey,
+    pub asset_id: [u8; 32],
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when an announced surplus sweep is withdrawn
+#[event]
+pub struct SurplusSweepCancelled {
+    pub pool: Pubkey,
+    pub asset_id: [u8; 32],
+    pub timestamp: i64,
+}
+
+/// Emitted when collected protocol fees are moved out of a treasury
+#[event]
+pub struct TreasuryWithdrawn {
+    pub pool: Pubkey,
+    pub asset_id: [u8; 32],
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a native SOL vault's fees are collected into the treasury
+#[event]
+pub struct TreasuryFeesCollected {
+    pub pool: Pubkey,
+    pub asset_id: [u8; 32],
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when the authority announces a treasury disbursement
+#[event]
+pub struct TreasuryDisbursementRequested {
+    pub pool: Pubkey,
+    pub asset_id: [u8; 32],
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub executable_at: i64,
+    pub timestamp: i64,
+}
+
+/// Emitted when an announced treasury disbursement is withdrawn
+#[event]
+pub struct TreasuryDisbursementCancelled {
+    pub pool: Pubkey,
+    pub asset_id: [u8; 32],
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AssetRelayerFeeBoundsUpdated {
+    pub pool: Pubkey,
+    pub asset_id: [u8; 32],
+    pub min_relayer_fee: u64,
+    pub max_relayer_fee: u64,
+    pub timestamp: i64,
+}
+
 // =========================================================================
 // VK EVENTS
 // =========================================================================
@@ -98,6 +415,43 @@ pub struct VerificationKeySetV2 {
     pub proof_type: u8,
     pub ic_length: u8,
     pub vk_hash: [u8; 32],
+    /// Hash of the MPC ceremony transcript (zero if none recorded)
+    pub ceremony_transcript_hash: [u8; 32],
+    pub ceremony_contributors: u32,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when the circuit parameters behind a proof type's VK are recorded.
+#[event]
+pub struct CircuitDescriptorSet {
+    pub pool: Pubkey,
+    pub proof_type: u8,
+    pub circuit_version: u16,
+    pub public_input_layout_hash: [u8; 32],
+    pub tree_depth: u8,
+    pub poseidon_params_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+/// Emitted when a known test proof verifies against a freshly set VK.
+#[event]
+pub struct VerificationKeyCanaryPassed {
+    pub pool: Pubkey,
+    pub proof_type: u8,
+    pub vk_hash: [u8; 32],
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when the ceremony behind an already set VK is recorded.
+#[event]
+pub struct VerificationKeyCeremonyRecorded {
+    pub pool: Pubkey,
+    pub proof_type: u8,
+    pub vk_hash: [u8; 32],
+    pub ceremony_transcript_hash: [u8; 32],
+    pub ceremony_contributors: u32,
     pub authority: Pubkey,
     pub timestamp: i64,
 }
@@ -110,6 +464,31 @@ pub struct VerificationKeyLockedV2 {
     pub timestamp: i64,
 }
 
+/// Emitted when a well-formed proof fails the pairing check.
+///
+/// The failing transaction reverts, but its logs (and this event) are still
+/// retained, so indexers can aggregate failure rates per proof type.
+#[event]
+pub struct ProofVerificationFailed {
+    pub pool: Pubkey,
+    pub proof_type: u8,
+    /// Signer that submitted the proof (relayer, depositor or batcher)
+    pub submitter: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when proof verification is abandoned because the remaining
+/// compute units cannot cover it. Like `ProofVerificationFailed`, it stays
+/// in the failed transaction's logs, telling the submitter to request a
+/// higher compute unit limit.
+#[event]
+pub struct InsufficientComputeBudgetDetected {
+    pub remaining_units: u64,
+    /// Least the verification would have needed
+    pub required_units: u64,
+    pub public_inputs: u8,
+}
+
 // =========================================================================
 // DEPOSIT/WITHDRAW EVENTS (PRIVACY-PRESERVING)
 // =========================================================================
@@ -147,6 +526,22 @@ pub struct DepositMaspEvent {
     pub timestamp: i64,
 }
 
+/// Emitted when a deposit is queued in the pending buffer.
+///
+/// Same privacy rules as `DepositMaspEvent`: no amount, no depositor. The
+/// leaf index is assigned later, when the batch is settled.
+#[event]
+pub struct DepositQueuedEvent {
+    /// Pool this deposit belongs to
+    pub pool: Pubkey,
+    /// Commitment waiting for Merkle insertion
+    pub commitment: [u8; 32],
+    /// Per-pool event sequence number (shared with withdrawal events)
+    pub sequence: u64,
+    /// Event timestamp
+    pub timestamp: i64,
+}
+
 /// Debug-only deposit event with additional information.
 ///
 /// # Security Warning
@@ -203,10 +598,70 @@ pub struct WithdrawMaspEvent {
     pub relayer: Pubkey,
     /// Fee paid to relayer (needed for relayer accounting)
     pub relayer_fee: u64,
+    /// Per-pool event sequence number (total order across deposits/withdrawals)
+    pub sequence: u64,
     /// Event timestamp
     pub timestamp: i64,
 }
 
+/// Reduced-privacy withdrawal of a disclosed note (`disclosed_path_withdraw`)
+///
+/// The note's opening was revealed to verify its Merkle path on-chain, so
+/// unlike `WithdrawMaspEvent` this links the spend to the deposit: it
+/// carries the commitment, leaf index, amount and recipient.
+#[event]
+pub struct DisclosedPathWithdrawn {
+    pub pool: Pubkey,
+    /// Spent note
+    pub commitment: [u8; 32],
+    pub leaf_index: u32,
+    pub nullifier_hash: [u8; 32],
+    pub asset_id: [u8; 32],
+    pub amount: u64,
+    pub recipient: Pubkey,
+    pub relayer: Pubkey,
+    pub relayer_fee: u64,
+    /// Per-pool event sequence number (total order across deposits/withdrawals)
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+/// Withdrawal sealed ahead of relaying (`commit_withdrawal`); carries only
+/// the commit hash, not the parameters
+#[event]
+pub struct WithdrawalCommitted {
+    pub pool: Pubkey,
+    pub withdrawal_commit: Pubkey,
+    pub commit_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+/// Sealed withdrawal cancelled by its committer
+#[event]
+pub struct WithdrawalCommitCancelled {
+    pub pool: Pubkey,
+    pub withdrawal_commit: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Commitment reserved ahead of its deposit
+#[event]
+pub struct CommitmentReservationCreated {
+    pub pool: Pubkey,
+    pub commitment_reservation: Pubkey,
+    pub commitment: [u8; 32],
+    pub expires_slot: u64,
+    pub timestamp: i64,
+}
+
+/// Commitment reservation released by its depositor
+#[event]
+pub struct CommitmentReservationReleased {
+    pub pool: Pubkey,
+    pub commitment_reservation: Pubkey,
+    pub timestamp: i64,
+}
+
 /// Withdrawal V2 event (join-split with change)
 ///
 /// Privacy considerations:
@@ -227,6 +682,8 @@ pub struct WithdrawV2Event {
     pub change_commitment: [u8; 32],
     /// Merkle root used for proof
     pub merkle_root: [u8; 32],
+    /// Per-pool event sequence number (total order across deposits/withdrawals)
+    pub sequence: u64,
     /// Event timestamp
     pub timestamp: i64,
     /// Slot number
@@ -255,6 +712,28 @@ pub struct WithdrawMaspDebugEvent {
     pub timestamp: i64,
 }
 
+/// Announces a one-time withdrawal owner and the ephemeral key it was
+/// derived from.
+///
+/// `owner` is fresh per withdrawal and only recognisable by the recipient
+/// (trial derivation from `ephemeral_pubkey`, filtered on `view_tag`), so
+/// publishing it does not link withdrawals to the recipient's wallet.
+#[event]
+pub struct OneTimeRecipientCreated {
+    /// Pool the owner will withdraw from
+    pub pool: Pubkey,
+    /// Asset of the created token account
+    pub asset_id: [u8; 32],
+    /// One-time owner of the token account
+    pub owner: Pubkey,
+    /// Sender's ephemeral ed25519 key (compressed)
+    pub ephemeral_pubkey: [u8; 32],
+    /// First byte of the shared-secret tag, for cheap scanning
+    pub view_tag: u8,
+    /// Event timestamp
+    pub timestamp: i64,
+}
+
 // =========================================================================
 // JOIN-SPLIT EVENTS
 // =========================================================================
@@ -347,6 +826,7 @@ pub struct RelayerRegistered {
     pub relayer: Pubkey,
     pub operator: Pubkey,
     pub fee_bps: u16,
+    pub index_position: u32,
     pub timestamp: i64,
 }
 
@@ -360,6 +840,17 @@ pub struct RelayerUpdated {
     pub timestamp: i64,
 }
 
+/// Relayer published a new fee quote
+#[event]
+pub struct RelayerFeeQuoteUpdated {
+    pub registry: Pubkey,
+    pub relayer: Pubkey,
+    pub flat_fee: u64,
+    pub fee_bps: u16,
+    pub valid_until_slot: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct RelayerDeactivated {
     pub pool: Pubkey,
@@ -368,6 +859,161 @@ pub struct RelayerDeactivated {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct RelayerDrainingSet {
+    pub pool: Pubkey,
+    pub relayer: Pubkey,
+    pub operator: Pubkey,
+    pub draining: bool,
+    pub drain_allowance: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RelayerClosed {
+    pub pool: Pubkey,
+    pub relayer: Pubkey,
+    pub operator: Pubkey,
+    /// First slot the operator may register again
+    pub reregister_after_slot: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RelayerCooldownUpdated {
+    pub pool: Pubkey,
+    pub registry: Pubkey,
+    pub reregistration_cooldown_slots: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RelayerRegistrationsSet {
+    pub pool: Pubkey,
+    pub registry: Pubkey,
+    pub open: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RelayerStakeMintSet {
+    pub pool: Pubkey,
+    pub registry: Pubkey,
+    pub stake_mint: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RelayerStaked {
+    pub pool: Pubkey,
+    pub relayer: Pubkey,
+    pub operator: Pubkey,
+    pub amount: u64,
+    /// Bonded stake after the deposit
+    pub stake_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RelayerUnstakeRequested {
+    pub pool: Pubkey,
+    pub relayer: Pubkey,
+    pub operator: Pubkey,
+    pub amount: u64,
+    /// Bonded stake left
+    pub stake_amount: u64,
+    /// Total unbonding, including earlier requests
+    pub unbonding_amount: u64,
+    pub release_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RelayerStakeWithdrawn {
+    pub pool: Pubkey,
+    pub relayer: Pubkey,
+    pub operator: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when the authority slashes a relayer's stake into the treasury.
+#[event]
+pub struct RelayerSlashed {
+    pub pool: Pubkey,
+    pub relayer: Pubkey,
+    pub operator: Pubkey,
+    /// Stake moved to the treasury
+    pub amount: u64,
+    pub slash_bps: u16,
+    /// Reputation after the penalty
+    pub reputation_score: u8,
+    pub deactivated: bool,
+    /// Hash of the off-chain evidence (e.g. censored job receipts)
+    pub evidence_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+/// Emitted when the authority reports a relayer failure.
+#[event]
+pub struct RelayerFailureReported {
+    pub pool: Pubkey,
+    pub relayer: Pubkey,
+    pub operator: Pubkey,
+    /// Failures reported against the relayer so far
+    pub failures_reported: u32,
+    /// Reputation after the penalty
+    pub reputation_score: u8,
+    /// Hash of the off-chain evidence (e.g. the dropped job)
+    pub evidence_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GasRebateCapUpdated {
+    pub pool: Pubkey,
+    pub registry: Pubkey,
+    pub max_gas_rebate_lamports: u64,
+    pub max_priority_fee_hint: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GasRebateClaimed {
+    pub pool: Pubkey,
+    pub relayer: Pubkey,
+    pub operator: Pubkey,
+    pub amount: u64,
+    /// Lifetime rebate accrued by this relayer (lamports)
+    pub total_accrued: u64,
+    /// Lifetime rebate paid to this relayer (lamports)
+    pub total_claimed: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when lamports are added to a pool's rent reserve
+#[event]
+pub struct RentReserveFunded {
+    pub pool: Pubkey,
+    pub funder: Pubkey,
+    pub amount: u64,
+    /// Lamports owed to the reserve by all relayers
+    pub outstanding: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a relayer repays rent advanced by the reserve
+#[event]
+pub struct RentAdvanceRepaid {
+    pub pool: Pubkey,
+    pub relayer: Pubkey,
+    pub operator: Pubkey,
+    pub amount: u64,
+    /// Lamports this relayer still owes
+    pub remaining: u64,
+    pub timestamp: i64,
+}
+
 // =========================================================================
 // COMPLIANCE EVENTS
 // =========================================================================
@@ -391,6 +1037,32 @@ pub struct AuditMetadataAttached {
     pub timestamp: i64,
 }
 
+/// A disclosed note's funds moved to a custodian by the authority and
+/// auditor (`compliance_recovery_withdraw`)
+#[event]
+pub struct ComplianceRecoveryWithdrawn {
+    pub pool: Pubkey,
+    /// Recovered note
+    pub commitment: [u8; 32],
+    /// Leaf index of the note
+    pub leaf_index: u32,
+    /// Nullifier hash recorded as spent, so the note cannot be withdrawn again
+    pub nullifier_hash: [u8; 32],
+    pub asset_id: [u8; 32],
+    pub amount: u64,
+    /// Wallet credited (native SOL) or owning the credited token account
+    pub custodian: Pubkey,
+    /// Reference to the court order authorizing the recovery
+    pub court_order_hash: [u8; 32],
+    /// `AuditMetadata` account holding the note's disclosed viewing data
+    pub audit_metadata: Pubkey,
+    pub authority: Pubkey,
+    pub auditor: Pubkey,
+    /// Per-pool event sequence number (total order across deposits/withdrawals)
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
 // =========================================================================
 // SHIELDED CPI EVENTS
 // =========================================================================
@@ -406,6 +1078,26 @@ pub struct ShieldedActionExecuted {
     pub timestamp: i64,
 }
 
+/// Emitted when a program is added to or removed from an action type's
+/// allowlist
+#[event]
+pub struct ActionAllowlistUpdated {
+    pub pool: Pubkey,
+    pub action_type: u8,
+    pub program: Pubkey,
+    pub allowlisted: bool,
+    pub timestamp: i64,
+}
+
+/// Emitted when the spender records a spent note's receipt (see
+/// `state::spent_nullifier`)
+#[event]
+pub struct NoteReceiptRecorded {
+    pub pool: Pubkey,
+    pub nullifier_hash: [u8; 32],
+    pub note_receipt: [u8; 8],
+}
+
 // =========================================================================
 // DEBUG EVENTS - GATED BEHIND event-debug FEATURE
 // =========================================================================
@@ -459,14 +1151,50 @@ macro_rules! debug_log {
     };
 }
 
+/// Emitted when a designated batcher locks its bond.
+#[event]
+pub struct BatcherRegistered {
+    pub pool: Pubkey,
+    pub batcher: Pubkey,
+    pub bond_lamports: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a batcher's bond is moved to the insurance fund.
+#[event]
+pub struct BatcherSlashed {
+    pub pool: Pubkey,
+    pub batcher: Pubkey,
+    pub amount: u64,
+    /// Hash of the off-chain evidence (e.g. the offending event logs)
+    pub evidence_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct BatchProcessedEvent {
     pub pool: Pubkey,
+    pub batch_seq: u64,
     pub deposits_processed: u16,
     pub first_leaf_index: u32,
     pub last_leaf_index: u32,
     pub new_merkle_root: [u8; 32],
     pub timestamp: i64,
+    /// Lamports paid to the batcher out of escrowed crank bounties
+    pub crank_bounty_paid: u64,
+}
+
+/// Emitted on every Merkle root change when the pool has root events
+/// enabled, for bridges and oracles that track roots only
+#[event]
+pub struct RootUpdatedEvent {
+    pub pool: Pubkey,
+    pub root: [u8; 32],
+    /// Leaves in the tree under this root
+    pub leaf_count: u32,
+    pub slot: u64,
+    /// Per-pool root event sequence number (first is 1)
+    pub sequence: u64,
 }
 // =========================================================================
 // TESTS
@@ -500,6 +1228,7 @@ mod tests {
             asset_id: [2u8; 32],
             relayer: Pubkey::new_unique(),
             relayer_fee: 1000,
+            sequence: 1,
             timestamp: 0,
         };
         // This compiles successfully, proving the struct has the expected shape
@@ -567,6 +1296,7 @@ mod tests {
 #[event]
 pub struct BatchSettledEvent {
     pub pool: Pubkey,
+    pub batch_seq: u64,
     pub batch_size: u16,
     pub start_index: u32,
     pub new_root: [u8; 32],
@@ -0,0 +1,43 @@
+//! Set Tree Capacity Thresholds Instruction
+//!
+//! Configures the fill percentages at which the pool emits
+//! `TreeCapacityWarning` events (defaults: 80% warning, 95% critical).
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::state::PoolConfigV2;
+
+/// Accounts for configuring tree capacity thresholds
+#[derive(Accounts)]
+pub struct SetTreeCapacityThresholds<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        mut,
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+}
+
+/// Handler for set_tree_capacity_thresholds instruction
+pub fn handler(
+    ctx: Context<SetTreeCapacityThresholds>,
+    warning_pct: u8,
+    critical_pct: u8,
+) -> Result<()> {
+    let pool_config = &mut ctx.accounts.pool_config;
+
+    pool_config.set_capacity_thresholds(warning_pct, critical_pct)?;
+    pool_config.last_activity_at = Clock::get()?.unix_timestamp;
+
+    msg!(
+        "Tree capacity thresholds set: warning={}%, critical={}%",
+        warning_pct,
+        critical_pct
+    );
+
+    Ok(())
+}
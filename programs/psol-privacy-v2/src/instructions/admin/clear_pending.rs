@@ -1,9 +1,11 @@
 //! Clear Pending Buffer Instruction
 //!
 //! Emergency admin function to clear pending deposits buffer.
-use anchor_lang::prelude::*;
+//! The crank bounty escrowed for the dropped deposits is written off: it
+//! stays in the buffer's balance and is never paid to a batcher.
 use crate::error::PrivacyErrorV2;
-use crate::state::{PoolConfigV2, PendingDepositsBuffer};
+use crate::state::{PendingDepositsBuffer, PoolConfigV2};
+use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
 pub struct ClearPendingBuffer<'info> {
@@ -19,16 +21,21 @@ pub struct ClearPendingBuffer<'info> {
     /// Pending deposits buffer
     #[account(
         mut,
-        constraint = pending_buffer.pool == pool_config.key() @ PrivacyErrorV2::InvalidVerificationKeyPool,
+        constraint = PendingDepositsBuffer::has_current_layout(&pending_buffer.to_account_info())
+            @ PrivacyErrorV2::PendingBufferNeedsMigration,
+        constraint = pending_buffer.load()?.pool == pool_config.key() @ PrivacyErrorV2::InvalidVerificationKeyPool,
     )]
-    pub pending_buffer: Account<'info, PendingDepositsBuffer>,
+    pub pending_buffer: AccountLoader<'info, PendingDepositsBuffer>,
 }
 
 pub fn handler(ctx: Context<ClearPendingBuffer>) -> Result<()> {
-    let pending = &mut ctx.accounts.pending_buffer;
+    let mut pending = ctx.accounts.pending_buffer.load_mut()?;
     let count = pending.total_pending;
-    pending.deposits.clear();
-    pending.total_pending = 0;
-    msg!("Cleared {} pending deposits", count);
+    let bounty = pending.clear();
+    msg!(
+        "Cleared {} pending deposits ({} lamports of crank bounty dropped)",
+        count,
+        bounty
+    );
     Ok(())
 }
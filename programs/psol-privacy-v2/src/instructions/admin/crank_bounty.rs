@@ -0,0 +1,37 @@
+//! Set Crank Bounty Instruction
+//!
+//! Configures the lamports each deposit pays into the pending buffer and
+//! each inserted leaf pays out to whoever runs `batch_process_deposits`, so
+//! bonded batchers are paid for cranking the queue. Zero turns the bounty
+//! off; lamports already escrowed stay in the buffer for later batches.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::state::PoolConfigV2;
+
+/// Accounts for configuring the crank bounty
+#[derive(Accounts)]
+pub struct SetCrankBounty<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        mut,
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+}
+
+/// Handler for set_crank_bounty instruction
+pub fn handler(ctx: Context<SetCrankBounty>, lamports_per_leaf: u32) -> Result<()> {
+    let pool_config = &mut ctx.accounts.pool_config;
+
+    pool_config.set_crank_bounty(lamports_per_leaf)?;
+    pool_config.last_activity_at = Clock::get()?.unix_timestamp;
+
+    msg!("Crank bounty set: {} lamports per leaf", lamports_per_leaf);
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+//! Set Disclosed-Path Withdrawals Instruction
+//!
+//! A stepping stone for pools whose withdraw circuit ceremony has not
+//! finished: with the mode on, `disclosed_path_withdraw` spends a note by
+//! revealing its opening and Merkle path, which the program checks itself
+//! instead of a Groth16 proof. This is REDUCED PRIVACY: every such
+//! withdrawal publishes which deposit it spends.
+//!
+//! The mode can only be switched on for small trees
+//! (`MAX_DISCLOSED_PATH_TREE_DEPTH`, the path is hashed on-chain) and while
+//! the withdraw verification key is unset; once the key is set the mode
+//! stops accepting withdrawals whether or not it is switched off.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::events::DisclosedPathWithdrawalsSet;
+use crate::state::{PoolConfigV2, MAX_DISCLOSED_PATH_TREE_DEPTH};
+use crate::ProofType;
+
+/// Accounts for enabling or disabling disclosed-path withdrawals
+#[derive(Accounts)]
+pub struct SetDisclosedPathWithdrawals<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        mut,
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+}
+
+/// Handler for set_disclosed_path_withdrawals instruction
+pub fn handler(ctx: Context<SetDisclosedPathWithdrawals>, enabled: bool) -> Result<()> {
+    let pool_config = &mut ctx.accounts.pool_config;
+
+    if enabled {
+        require!(
+            pool_config.tree_depth <= MAX_DISCLOSED_PATH_TREE_DEPTH,
+            PrivacyErrorV2::TreeTooDeepForDisclosedPath
+        );
+        require!(
+            !pool_config.is_vk_configured(ProofType::Withdraw),
+            PrivacyErrorV2::WithdrawVerificationKeySet
+        );
+    }
+
+    let timestamp = Clock::get()?.unix_timestamp;
+    pool_config.disclosed_path_withdrawals = enabled;
+    pool_config.last_activity_at = timestamp;
+
+    emit!(DisclosedPathWithdrawalsSet {
+        pool: pool_config.key(),
+        enabled,
+        tree_depth: pool_config.tree_depth,
+        authority: ctx.accounts.authority.key(),
+        timestamp,
+    });
+
+    msg!(
+        "Disclosed-path withdrawals (reduced privacy) enabled: {}",
+        enabled
+    );
+
+    Ok(())
+}
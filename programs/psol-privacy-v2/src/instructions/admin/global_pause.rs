@@ -0,0 +1,242 @@
+//! Global Pause Instructions - pSOL v2
+//!
+//! Program-wide circuit breaker over every pool (see `GlobalConfig`):
+//! - initialize_global_config: Create the config (program upgrade authority)
+//! - pause_all_pools: Halt fund-moving instructions in every pool
+//! - unpause_all_pools: Resume them
+//! - set_super_guardian: Hand the super-guardian role to a new key
+//! - set_pool_creation_fee: Change the lamports `create_pool` pays the
+//!   super guardian
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::events::{
+    AllPoolsPaused, AllPoolsUnpaused, GlobalConfigInitialized, PoolCreationFeeUpdated,
+    SuperGuardianUpdated,
+};
+use crate::program::PsolPrivacyV2;
+use crate::state::GlobalConfig;
+
+// =============================================================================
+// INITIALIZE
+// =============================================================================
+
+/// Accounts for creating the global config
+#[derive(Accounts)]
+pub struct InitializeGlobalConfig<'info> {
+    /// Program upgrade authority (must be signer)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = GlobalConfig::LEN,
+        seeds = [GlobalConfig::SEED_PREFIX],
+        bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key())
+            @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub program: Program<'info, PsolPrivacyV2>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key())
+            @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Handler for initialize_global_config instruction
+pub fn handler_initialize(
+    ctx: Context<InitializeGlobalConfig>,
+    super_guardian: Pubkey,
+) -> Result<()> {
+    require_keys_neq!(
+        super_guardian,
+        Pubkey::default(),
+        PrivacyErrorV2::InvalidAuthority
+    );
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    ctx.accounts
+        .global_config
+        .initialize(super_guardian, ctx.bumps.global_config, timestamp);
+
+    emit!(GlobalConfigInitialized {
+        super_guardian,
+        timestamp,
+    });
+
+    msg!("Global config initialized");
+
+    Ok(())
+}
+
+// =============================================================================
+// PAUSE ALL POOLS
+// =============================================================================
+
+/// Accounts for pausing every pool
+#[derive(Accounts)]
+pub struct PauseAllPools<'info> {
+    /// Super guardian (must be signer)
+    pub super_guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GlobalConfig::SEED_PREFIX],
+        bump = global_config.bump,
+        has_one = super_guardian @ PrivacyErrorV2::Unauthorized,
+        constraint = !global_config.is_paused @ PrivacyErrorV2::GlobalPaused,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+/// Handler for pause_all_pools instruction
+pub fn handler_pause(ctx: Context<PauseAllPools>) -> Result<()> {
+    let global_config = &mut ctx.accounts.global_config;
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    global_config.is_paused = true;
+    global_config.updated_at = timestamp;
+
+    emit!(AllPoolsPaused {
+        super_guardian: global_config.super_guardian,
+        timestamp,
+    });
+
+    msg!("All pools paused by super guardian");
+
+    Ok(())
+}
+
+// =============================================================================
+// UNPAUSE ALL POOLS
+// =============================================================================
+
+/// Accounts for lifting the global pause
+#[derive(Accounts)]
+pub struct UnpauseAllPools<'info> {
+    /// Super guardian (must be signer)
+    pub super_guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GlobalConfig::SEED_PREFIX],
+        bump = global_config.bump,
+        has_one = super_guardian @ PrivacyErrorV2::Unauthorized,
+        constraint = global_config.is_paused @ PrivacyErrorV2::GlobalNotPaused,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+/// Handler for unpause_all_pools instruction
+pub fn handler_unpause(ctx: Context<UnpauseAllPools>) -> Result<()> {
+    let global_config = &mut ctx.accounts.global_config;
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    global_config.is_paused = false;
+    global_config.updated_at = timestamp;
+
+    emit!(AllPoolsUnpaused {
+        super_guardian: global_config.super_guardian,
+        timestamp,
+    });
+
+    msg!("All pools unpaused by super guardian");
+
+    Ok(())
+}
+
+// =============================================================================
+// SET SUPER GUARDIAN
+// =============================================================================
+
+/// Accounts for replacing the super guardian
+#[derive(Accounts)]
+pub struct SetSuperGuardian<'info> {
+    /// Current super guardian (must be signer)
+    pub super_guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GlobalConfig::SEED_PREFIX],
+        bump = global_config.bump,
+        has_one = super_guardian @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+/// Handler for set_super_guardian instruction
+pub fn handler_set_super_guardian(
+    ctx: Context<SetSuperGuardian>,
+    new_super_guardian: Pubkey,
+) -> Result<()> {
+    require_keys_neq!(
+        new_super_guardian,
+        Pubkey::default(),
+        PrivacyErrorV2::InvalidAuthority
+    );
+    let global_config = &mut ctx.accounts.global_config;
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    let previous = global_config.super_guardian;
+    global_config.super_guardian = new_super_guardian;
+    global_config.updated_at = timestamp;
+
+    emit!(SuperGuardianUpdated {
+        previous,
+        super_guardian: new_super_guardian,
+        timestamp,
+    });
+
+    msg!("Super guardian updated");
+
+    Ok(())
+}
+
+// =============================================================================
+// SET POOL CREATION FEE
+// =============================================================================
+
+/// Accounts for changing the pool creation fee
+#[derive(Accounts)]
+pub struct SetPoolCreationFee<'info> {
+    /// Super guardian (must be signer)
+    pub super_guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GlobalConfig::SEED_PREFIX],
+        bump = global_config.bump,
+        has_one = super_guardian @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+/// Handler for set_pool_creation_fee instruction
+pub fn handler_set_pool_creation_fee(ctx: Context<SetPoolCreationFee>, fee: u64) -> Result<()> {
+    let global_config = &mut ctx.accounts.global_config;
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    let previous = global_config.pool_creation_fee;
+    global_config.pool_creation_fee = fee;
+    global_config.updated_at = timestamp;
+
+    emit!(PoolCreationFeeUpdated {
+        previous,
+        fee,
+        timestamp,
+    });
+
+    msg!("Pool creation fee set to {} lamports", fee);
+
+    Ok(())
+}
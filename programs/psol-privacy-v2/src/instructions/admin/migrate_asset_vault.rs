@@ -0,0 +1,91 @@
+//! Migrate Asset Vault Instruction
+//!
+//! Grows an asset vault registered before the fields that follow
+//! `metadata_uri` (`LEGACY_SPACE` bytes) to the current layout. Those
+//! fields took the place of a 32-byte reserve and 42 bytes more, so a
+//! legacy vault whose URI is longer than the slack left by a shorter one
+//! cannot be parsed until it grows. The zero-filled tail gives each new
+//! field its default: no relayer fee bounds, the derived withdrawal floor,
+//! no cached token metadata, no risk tier. The authority tops up the rent.
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+
+use crate::error::PrivacyErrorV2;
+use crate::state::{AssetVault, PoolConfigV2};
+
+/// Accounts for migrating a legacy asset vault
+#[derive(Accounts)]
+pub struct MigrateAssetVault<'info> {
+    /// Pool authority (must be signer, pays the extra rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(has_one = authority @ PrivacyErrorV2::Unauthorized)]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// CHECK: Legacy-layout vault, parsed by the handler; owner checked
+    /// here, pool by the handler
+    #[account(mut, owner = crate::ID)]
+    pub asset_vault: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Handler for migrate_asset_vault instruction
+pub fn handler(ctx: Context<MigrateAssetVault>) -> Result<()> {
+    let info = ctx.accounts.asset_vault.to_account_info();
+    require!(
+        info.data_len() != AssetVault::DEFAULT_SPACE,
+        PrivacyErrorV2::AssetVaultAlreadyMigrated
+    );
+    require!(
+        info.data_len() == AssetVault::LEGACY_SPACE,
+        ErrorCode::AccountDidNotDeserialize
+    );
+
+    {
+        let data = info.try_borrow_data()?;
+        require!(
+            data[..8] == *AssetVault::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        // `pool` is the first field
+        require!(
+            data[8..40] == ctx.accounts.pool_config.key().to_bytes(),
+            PrivacyErrorV2::InvalidVaultPool
+        );
+    }
+
+    // Top up rent for the larger account before growing it
+    let required = Rent::get()?.minimum_balance(AssetVault::DEFAULT_SPACE);
+    let shortfall = required.saturating_sub(info.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: info.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    info.resize(AssetVault::DEFAULT_SPACE)?;
+
+    // The grown tail is zeroed, so the account now parses
+    let vault = {
+        let data = info.try_borrow_data()?;
+        AssetVault::try_deserialize(&mut &data[..])?
+    };
+
+    msg!(
+        "Migrated asset vault {} ({} byte metadata URI)",
+        info.key(),
+        vault.metadata_uri.len()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+//! Migrate Merkle Tree Instruction
+//!
+//! Grows a Merkle tree created before `poseidon_params_hash` (which ends at
+//! `version`) by the 32 bytes of that field and records
+//! `crypto::POSEIDON_PARAMS_HASH`, the parameter set every tree has hashed
+//! with. Until then instructions loading the tree cannot parse it. The
+//! authority tops up the rent.
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+
+use crate::error::PrivacyErrorV2;
+use crate::state::{MerkleTreeV2, PoolConfigV2};
+
+/// Offset of `depth`: discriminator and `pool`
+const DEPTH_OFFSET: usize = 8 + 32;
+
+/// Offset of the `root_history` length: `depth`, `next_leaf_index` and
+/// `current_root` follow `pool`
+const ROOT_HISTORY_LEN_OFFSET: usize = DEPTH_OFFSET + 1 + 4 + 32;
+
+/// Accounts for migrating a legacy Merkle tree
+#[derive(Accounts)]
+pub struct MigrateMerkleTree<'info> {
+    /// Pool authority (must be signer, pays the extra rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+        has_one = merkle_tree @ PrivacyErrorV2::InvalidMerkleTreePool,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// CHECK: Legacy-layout tree, parsed by the handler; owner checked
+    /// here, address by `pool_config`
+    #[account(mut, owner = crate::ID)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Handler for migrate_merkle_tree instruction
+pub fn handler(ctx: Context<MigrateMerkleTree>) -> Result<()> {
+    let info = ctx.accounts.merkle_tree.to_account_info();
+
+    let (depth, root_history_size) = {
+        let data = info.try_borrow_data()?;
+        require!(
+            data.len() > ROOT_HISTORY_LEN_OFFSET + 4 && data[..8] == *MerkleTreeV2::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        let mut history_len = [0u8; 4];
+        history_len.copy_from_slice(&data[ROOT_HISTORY_LEN_OFFSET..ROOT_HISTORY_LEN_OFFSET + 4]);
+        let root_history_size = u16::try_from(u32::from_le_bytes(history_len))
+            .map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))?;
+        (data[DEPTH_OFFSET], root_history_size)
+    };
+
+    let current_len = MerkleTreeV2::space(depth, root_history_size);
+    require!(
+        info.data_len() != current_len,
+        PrivacyErrorV2::MerkleTreeAlreadyMigrated
+    );
+    require!(
+        info.data_len() == MerkleTreeV2::legacy_space(depth, root_history_size),
+        ErrorCode::AccountDidNotDeserialize
+    );
+
+    // Top up rent for the larger account before growing it
+    let required = Rent::get()?.minimum_balance(current_len);
+    let shortfall = required.saturating_sub(info.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: info.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    info.resize(current_len)?;
+
+    // The grown tail is zeroed, so the account now parses
+    let mut tree = {
+        let data = info.try_borrow_data()?;
+        MerkleTreeV2::try_deserialize(&mut &data[..])?
+    };
+    tree.migrate()?;
+    tree.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+    msg!(
+        "Migrated merkle tree {} to version {}",
+        info.key(),
+        MerkleTreeV2::VERSION
+    );
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+//! Migrate Pending Buffer Instruction
+//!
+//! Rewrites a pending deposits buffer created with the legacy Borsh layout
+//! in the zero-copy ring layout, keeping queued deposits in order along with
+//! the batch statistics and sequence number. Legacy buffers predate the
+//! crank bounty, so the migrated queue starts with nothing escrowed. The
+//! account grows by a few bytes; the authority tops up its rent.
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+
+use crate::error::PrivacyErrorV2;
+use crate::state::{LegacyPendingDepositsBuffer, PendingDepositsBuffer, PoolConfigV2};
+
+/// Accounts for migrating a legacy pending deposits buffer
+#[derive(Accounts)]
+pub struct MigratePendingBuffer<'info> {
+    /// Pool authority (must be signer, pays the extra rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// CHECK: Legacy-layout buffer, parsed by the handler; seeds and owner
+    /// checked here
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [
+            PendingDepositsBuffer::SEED_PREFIX,
+            pool_config.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub pending_buffer: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Handler for migrate_pending_buffer instruction
+pub fn handler(ctx: Context<MigratePendingBuffer>) -> Result<()> {
+    let info = ctx.accounts.pending_buffer.to_account_info();
+    require!(
+        !PendingDepositsBuffer::has_current_layout(&info),
+        PrivacyErrorV2::PendingBufferAlreadyMigrated
+    );
+    require!(
+        info.data_len() == PendingDepositsBuffer::LEGACY_LEN,
+        ErrorCode::AccountDidNotDeserialize
+    );
+
+    let legacy = {
+        let data = info.try_borrow_data()?;
+        require!(
+            data[..8] == *PendingDepositsBuffer::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        LegacyPendingDepositsBuffer::deserialize(&mut &data[8..])?
+    };
+    require!(
+        legacy.version == PendingDepositsBuffer::LEGACY_VERSION,
+        ErrorCode::AccountDidNotDeserialize
+    );
+    require_keys_eq!(
+        legacy.pool,
+        ctx.accounts.pool_config.key(),
+        PrivacyErrorV2::InvalidPoolReference
+    );
+
+    // Top up rent for the larger account before growing it
+    let required = Rent::get()?.minimum_balance(PendingDepositsBuffer::LEN);
+    let shortfall = required.saturating_sub(info.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: info.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    info.resize(PendingDepositsBuffer::LEN)?;
+
+    // Rewrite in place: the discriminator is unchanged
+    let mut data = info.try_borrow_mut_data()?;
+    let buffer: &mut PendingDepositsBuffer =
+        bytemuck::from_bytes_mut(&mut data[8..PendingDepositsBuffer::LEN]);
+    buffer.migrate_from(&legacy)?;
+
+    msg!(
+        "Migrated pending buffer for pool {} ({} pending, batch seq {})",
+        legacy.pool,
+        legacy.total_pending,
+        legacy.batch_seq
+    );
+
+    Ok(())
+}
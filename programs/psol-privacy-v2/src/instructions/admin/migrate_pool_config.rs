@@ -0,0 +1,92 @@
+//! Migrate Pool Config Instruction
+//!
+//! Grows a pool config created before the protocol fee (`LEGACY_LEN`
+//! bytes) to the current layout. Fields added since then are appended, so
+//! the zero-filled tail gives each of them its default: no protocol fee,
+//! no compliance recovery, no disclosed-path withdrawals, pool id 0, not
+//! listed. The authority tops up the rent.
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+
+use crate::error::PrivacyErrorV2;
+use crate::state::PoolConfigV2;
+
+/// Accounts for migrating a legacy pool config
+#[derive(Accounts)]
+pub struct MigratePoolConfig<'info> {
+    /// Pool authority (must be signer, pays the extra rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Legacy-layout pool config, parsed by the handler; owner
+    /// checked here
+    #[account(mut, owner = crate::ID)]
+    pub pool_config: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Handler for migrate_pool_config instruction
+pub fn handler(ctx: Context<MigratePoolConfig>) -> Result<()> {
+    let info = ctx.accounts.pool_config.to_account_info();
+    require!(
+        !PoolConfigV2::has_current_layout(&info),
+        PrivacyErrorV2::PoolConfigAlreadyMigrated
+    );
+    require!(
+        info.data_len() == PoolConfigV2::LEGACY_LEN,
+        ErrorCode::AccountDidNotDeserialize
+    );
+
+    {
+        let data = info.try_borrow_data()?;
+        require!(
+            data[..8] == *PoolConfigV2::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        // `authority` is the first field
+        require!(
+            data[8..40] == ctx.accounts.authority.key().to_bytes(),
+            PrivacyErrorV2::Unauthorized
+        );
+    }
+
+    // Top up rent for the larger account before growing it
+    let required = Rent::get()?.minimum_balance(PoolConfigV2::LEN);
+    let shortfall = required.saturating_sub(info.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: info.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    info.resize(PoolConfigV2::LEN)?;
+
+    // The grown tail is zeroed, so the account now parses; only the
+    // version needs rewriting
+    let mut config = {
+        let data = info.try_borrow_data()?;
+        PoolConfigV2::try_deserialize(&mut &data[..])?
+    };
+    require!(
+        config.version == PoolConfigV2::LEGACY_VERSION,
+        ErrorCode::AccountDidNotDeserialize
+    );
+    config.version = PoolConfigV2::VERSION;
+    config.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+    msg!(
+        "Migrated pool config {} to version {}",
+        info.key(),
+        PoolConfigV2::VERSION
+    );
+
+    Ok(())
+}
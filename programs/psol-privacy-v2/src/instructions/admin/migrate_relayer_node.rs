@@ -0,0 +1,128 @@
+//! Migrate Relayer Node Instruction
+//!
+//! Re-lays out a relayer node registered before the fields that follow
+//! `reputation_score` (`LEGACY_SPACE` bytes). Those fields took the place
+//! of a 16-byte reserve and 110 bytes more, so the legacy node is parsed in
+//! its old layout, the account grown to `DEFAULT_SPACE` and the node
+//! written back in the current one: no structured metadata, no signed
+//! updates yet, not draining, nothing unbonding, no failures reported.
+//!
+//! Legacy nodes predate the relayer index, yet the registry counted them
+//! when handing out index positions; each migrated node takes the next free
+//! one of those positions, so once every legacy node is migrated the index
+//! is gapless and new registrations append after them. The registry must
+//! be migrated first. The authority tops up the rent.
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+
+use crate::error::PrivacyErrorV2;
+use crate::state::{
+    LegacyRelayerNode, PoolConfigV2, RelayerIndexShard, RelayerNode, RelayerRegistry,
+};
+use crate::utils::require_program_id;
+
+/// Accounts for migrating a legacy relayer node
+#[derive(Accounts)]
+pub struct MigrateRelayerNode<'info> {
+    /// Pool authority (must be signer, pays the extra rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+        has_one = relayer_registry,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Relayer registry account
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+
+    /// CHECK: Legacy-layout node, parsed by the handler; owner checked
+    /// here, registry and address by the handler
+    #[account(mut, owner = crate::ID)]
+    pub relayer_node: UncheckedAccount<'info>,
+
+    /// Relayer index shard holding the next free position
+    #[account(
+        mut,
+        seeds = [
+            RelayerIndexShard::SEED_PREFIX,
+            relayer_registry.key().as_ref(),
+            &relayer_index_shard.shard_index.to_le_bytes(),
+        ],
+        bump = relayer_index_shard.bump,
+    )]
+    pub relayer_index_shard: Account<'info, RelayerIndexShard>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Handler for migrate_relayer_node instruction
+pub fn handler(ctx: Context<MigrateRelayerNode>) -> Result<()> {
+    let info = ctx.accounts.relayer_node.to_account_info();
+    require!(
+        info.data_len() != RelayerNode::DEFAULT_SPACE,
+        PrivacyErrorV2::RelayerNodeAlreadyMigrated
+    );
+    require!(
+        info.data_len() == RelayerNode::LEGACY_SPACE,
+        ErrorCode::AccountDidNotDeserialize
+    );
+
+    let legacy = {
+        let data = info.try_borrow_data()?;
+        require!(
+            data[..8] == *RelayerNode::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        LegacyRelayerNode::deserialize(&mut &data[8..])
+            .map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))?
+    };
+
+    // Legacy nodes fill the positions the registry already counted
+    let index_position = ctx.accounts.relayer_index_shard.next_position();
+    require!(
+        index_position < ctx.accounts.relayer_registry.relayer_count,
+        PrivacyErrorV2::RelayerIndexMismatch
+    );
+    let node = RelayerNode::from_legacy(legacy, index_position);
+    require_program_id(ctx.program_id)?;
+    node.validate_registry_and_pda(
+        ctx.program_id,
+        &ctx.accounts.relayer_registry.key(),
+        &info.key(),
+    )?;
+    let index = &mut ctx.accounts.relayer_index_shard;
+    index.append(index_position, node.operator)?;
+    index.set_active(index_position, &node.operator, node.is_active)?;
+
+    // Top up rent for the larger account before growing it
+    let required = Rent::get()?.minimum_balance(RelayerNode::DEFAULT_SPACE);
+    let shortfall = required.saturating_sub(info.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: info.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    info.resize(RelayerNode::DEFAULT_SPACE)?;
+
+    // Write the node back in the current layout
+    node.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+    msg!(
+        "Migrated relayer node {} to index position {}",
+        info.key(),
+        index_position
+    );
+
+    Ok(())
+}
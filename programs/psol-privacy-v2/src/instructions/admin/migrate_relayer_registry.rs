@@ -0,0 +1,99 @@
+//! Migrate Relayer Registry Instruction
+//!
+//! Grows a relayer registry created before relayer staking (`LEGACY_LEN`
+//! bytes) to the current layout. `stake_mint`, `total_staked` and
+//! `max_priority_fee_hint` took the place of the legacy 8-byte reserve and
+//! 40 bytes more, so a legacy registry cannot be parsed until it grows.
+//! They are zeroed here: no stake mint, nothing staked, no priority fee
+//! rebated. The authority tops up the rent.
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+
+use crate::error::PrivacyErrorV2;
+use crate::state::{PoolConfigV2, RelayerRegistry};
+
+/// Accounts for migrating a legacy relayer registry
+#[derive(Accounts)]
+pub struct MigrateRelayerRegistry<'info> {
+    /// Pool authority (must be signer, pays the extra rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+        has_one = relayer_registry,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// CHECK: Legacy-layout registry, parsed by the handler; owner checked
+    /// here, address by `pool_config`
+    #[account(mut, owner = crate::ID)]
+    pub relayer_registry: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Handler for migrate_relayer_registry instruction
+pub fn handler(ctx: Context<MigrateRelayerRegistry>) -> Result<()> {
+    let info = ctx.accounts.relayer_registry.to_account_info();
+    require!(
+        info.data_len() != RelayerRegistry::LEN,
+        PrivacyErrorV2::RelayerRegistryAlreadyMigrated
+    );
+    require!(
+        info.data_len() == RelayerRegistry::LEGACY_LEN,
+        ErrorCode::AccountDidNotDeserialize
+    );
+
+    {
+        let data = info.try_borrow_data()?;
+        require!(
+            data[..8] == *RelayerRegistry::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        // `pool` is the first field
+        require!(
+            data[8..40] == ctx.accounts.pool_config.key().to_bytes(),
+            PrivacyErrorV2::InvalidPoolReference
+        );
+    }
+
+    // Top up rent for the larger account before growing it
+    let required = Rent::get()?.minimum_balance(RelayerRegistry::LEN);
+    let shortfall = required.saturating_sub(info.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: info.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    info.resize(RelayerRegistry::LEN)?;
+
+    // The grown tail parses; clear the new fields and the reserve
+    // regardless of what the legacy reserve held
+    let mut registry = {
+        let data = info.try_borrow_data()?;
+        RelayerRegistry::try_deserialize(&mut &data[..])?
+    };
+    registry.stake_mint = Pubkey::default();
+    registry.total_staked = 0;
+    registry.max_priority_fee_hint = 0;
+    registry._reserved = [0u8; 8];
+    registry.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+    msg!(
+        "Migrated relayer registry {} ({} relayers)",
+        info.key(),
+        registry.relayer_count
+    );
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+//! Migrate Verification Key Instruction
+//!
+//! Grows a VK account created before the canary and ceremony fields
+//! (`LEGACY_SPACE` bytes) to the current layout. Those fields took the
+//! place of a 32-byte reserve and 36 bytes more, so a legacy VK with a
+//! full IC vector cannot be parsed until it grows. The zero-filled tail
+//! gives each new field its default: no canary result, no ceremony
+//! recorded (record_vk_ceremony can add it afterwards). The key itself, its
+//! hash and its lock are left untouched; the authority tops up the rent.
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+
+use crate::error::PrivacyErrorV2;
+use crate::state::{PoolConfigV2, VerificationKeyAccountV2};
+use crate::ProofType;
+
+/// Accounts for migrating a legacy verification key account
+#[derive(Accounts)]
+#[instruction(proof_type: ProofType)]
+pub struct MigrateVerificationKey<'info> {
+    /// Pool authority (must be signer, pays the extra rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(has_one = authority @ PrivacyErrorV2::Unauthorized)]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// CHECK: Legacy-layout VK, parsed by the handler; owner and PDA
+    /// checked here
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [proof_type.as_seed(), pool_config.key().as_ref()],
+        bump,
+    )]
+    pub vk_account: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Handler for migrate_verification_key instruction
+pub fn handler(ctx: Context<MigrateVerificationKey>, proof_type: ProofType) -> Result<()> {
+    let space = VerificationKeyAccountV2::space(VerificationKeyAccountV2::DEFAULT_MAX_IC_POINTS);
+    let info = ctx.accounts.vk_account.to_account_info();
+    require!(
+        info.data_len() != space,
+        PrivacyErrorV2::VerificationKeyAlreadyMigrated
+    );
+    require!(
+        info.data_len() == VerificationKeyAccountV2::LEGACY_SPACE,
+        ErrorCode::AccountDidNotDeserialize
+    );
+
+    {
+        let data = info.try_borrow_data()?;
+        require!(
+            data[..8] == *VerificationKeyAccountV2::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        // `pool` is the first field
+        require!(
+            data[8..40] == ctx.accounts.pool_config.key().to_bytes(),
+            PrivacyErrorV2::InvalidPoolReference
+        );
+    }
+
+    // Top up rent for the larger account before growing it
+    let required = Rent::get()?.minimum_balance(space);
+    let shortfall = required.saturating_sub(info.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: info.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    info.resize(space)?;
+
+    // The grown tail is zeroed, so the account now parses
+    let vk = {
+        let data = info.try_borrow_data()?;
+        VerificationKeyAccountV2::try_deserialize(&mut &data[..])?
+    };
+
+    msg!(
+        "Migrated {:?} verification key {} ({} IC points)",
+        proof_type,
+        info.key(),
+        vk.vk_ic_len
+    );
+
+    Ok(())
+}
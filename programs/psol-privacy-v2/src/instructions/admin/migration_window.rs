@@ -0,0 +1,121 @@
+//! Migration Window Instructions
+//!
+//! Open a window before resetting or reorganizing the Merkle tree so that
+//! withdrawal proofs built against the old roots remain valid for a bounded
+//! time, and close it afterwards to reclaim rent.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::events::{MigrationWindowClosed, MigrationWindowOpened};
+use crate::state::{MerkleTreeV2, MigrationWindow, PoolConfigV2, MAX_MIGRATION_WINDOW_SECONDS};
+use crate::utils::pool_clock;
+
+/// Accounts for opening a migration window
+#[derive(Accounts)]
+pub struct OpenMigrationWindow<'info> {
+    /// Pool authority (must be signer, pays rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+        has_one = merkle_tree @ PrivacyErrorV2::InvalidMerkleTreePool,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Merkle tree whose current roots are snapshotted
+    pub merkle_tree: Box<Account<'info, MerkleTreeV2>>,
+
+    /// Migration window PDA
+    #[account(
+        init,
+        payer = authority,
+        space = MigrationWindow::space(MigrationWindow::max_roots_for(&merkle_tree)),
+        seeds = [MigrationWindow::SEED_PREFIX, pool_config.key().as_ref()],
+        bump,
+    )]
+    pub migration_window: Box<Account<'info, MigrationWindow>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for closing a migration window
+#[derive(Accounts)]
+pub struct CloseMigrationWindow<'info> {
+    /// Pool authority (must be signer, receives rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Migration window PDA
+    #[account(
+        mut,
+        close = authority,
+        seeds = [MigrationWindow::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = migration_window.bump,
+    )]
+    pub migration_window: Box<Account<'info, MigrationWindow>>,
+}
+
+/// Handler for open_migration_window instruction
+pub fn open_handler(ctx: Context<OpenMigrationWindow>, duration_seconds: i64) -> Result<()> {
+    require!(
+        duration_seconds > 0 && duration_seconds <= MAX_MIGRATION_WINDOW_SECONDS,
+        PrivacyErrorV2::InvalidMigrationWindow
+    );
+
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+    let expires_at = timestamp
+        .checked_add(duration_seconds)
+        .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+
+    let pool_key = ctx.accounts.pool_config.key();
+    let tree_key = ctx.accounts.merkle_tree.key();
+    let window = &mut ctx.accounts.migration_window;
+    window.initialize(
+        pool_key,
+        tree_key,
+        &ctx.accounts.merkle_tree,
+        timestamp,
+        expires_at,
+        ctx.bumps.migration_window,
+    );
+
+    emit!(MigrationWindowOpened {
+        pool: pool_key,
+        merkle_tree: tree_key,
+        roots_snapshotted: window.pre_migration_roots.len() as u16,
+        expires_at,
+        timestamp,
+    });
+
+    msg!(
+        "Migration window opened: {} roots accepted until {}",
+        window.pre_migration_roots.len(),
+        expires_at
+    );
+
+    Ok(())
+}
+
+/// Handler for close_migration_window instruction
+///
+/// May be called at any time; closing early ends dual-root acceptance.
+pub fn close_handler(ctx: Context<CloseMigrationWindow>) -> Result<()> {
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+
+    emit!(MigrationWindowClosed {
+        pool: ctx.accounts.pool_config.key(),
+        timestamp,
+    });
+
+    msg!("Migration window closed");
+    Ok(())
+}
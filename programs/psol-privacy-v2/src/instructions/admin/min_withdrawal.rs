@@ -0,0 +1,66 @@
+//! Set Asset Minimum Withdrawal Instruction
+//!
+//! Configures the smallest withdrawal (token base units) accepted for one
+//! asset. A single global floor means nothing across mints with different
+//! decimals and prices; unset vaults fall back to `10^(decimals / 2)`.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::events::AssetMinWithdrawalUpdated;
+use crate::state::{AssetVault, PoolConfigV2};
+
+/// Accounts for setting an asset's minimum withdrawal
+#[derive(Accounts)]
+#[instruction(asset_id: [u8; 32])]
+pub struct SetAssetMinWithdrawal<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Asset vault to configure
+    #[account(
+        mut,
+        seeds = [
+            AssetVault::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        bump = asset_vault.bump,
+        constraint = asset_vault.pool == pool_config.key() @ PrivacyErrorV2::InvalidVaultPool,
+    )]
+    pub asset_vault: Account<'info, AssetVault>,
+}
+
+/// Handler for set_asset_min_withdrawal instruction
+pub fn handler(
+    ctx: Context<SetAssetMinWithdrawal>,
+    asset_id: [u8; 32],
+    min_withdrawal: u64,
+) -> Result<()> {
+    let asset_vault = &mut ctx.accounts.asset_vault;
+
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    asset_vault.set_min_withdrawal(min_withdrawal);
+    asset_vault.last_activity_at = timestamp;
+
+    emit!(AssetMinWithdrawalUpdated {
+        pool: ctx.accounts.pool_config.key(),
+        asset_id,
+        min_withdrawal: asset_vault.min_withdrawal(),
+        timestamp,
+    });
+
+    msg!(
+        "Asset minimum withdrawal set: {}",
+        asset_vault.min_withdrawal()
+    );
+
+    Ok(())
+}
@@ -2,7 +2,23 @@
 //!
 //! Administrative operations including:
 //! - Pool pause/unpause
+//! - Program-wide pause of all pools and pool creation fee (super guardian)
 //! - Authority transfer (2-step process)
+//! - Layout migrations (pending buffer, pool config)
+//! - Tree capacity warning thresholds
+//! - Encrypted note size limit
+//! - Per-asset minimum withdrawal
+//! - Risk tiers setting per-asset deposit/withdrawal limits
+//! - Same-transaction deposit/withdraw guard
+//! - Migration windows (dual-root acceptance)
+//! - Timelocked sweeps of vault surplus
+//! - Root update events for bridges
+//! - Privacy-strict mode (no nullification receipts)
+//! - Versioned parameter snapshots for client pinning
+//! - Crank bounty for batch processing
+//! - Protocol fee on withdrawals and per-asset treasuries
+//! - Pool treasury: fee collection and timelocked disbursements
+//! - Disclosed-path withdrawals (reduced privacy, before the withdraw VK)
 
 pub mod authority_v2;
 pub mod pause_v2;
@@ -13,7 +29,58 @@ pub use authority_v2::{
 };
 pub use pause_v2::PausePoolV2;
 pub use unpause_v2::UnpausePoolV2;
+pub mod global_pause;
+pub use global_pause::{
+    InitializeGlobalConfig, PauseAllPools, SetPoolCreationFee, SetSuperGuardian, UnpauseAllPools,
+};
 pub mod clear_pending;
 pub use clear_pending::ClearPendingBuffer;
+pub mod migrate_pending_buffer;
+pub use migrate_pending_buffer::MigratePendingBuffer;
+pub mod migrate_pool_config;
+pub use migrate_pool_config::MigratePoolConfig;
+pub mod migrate_merkle_tree;
+pub use migrate_merkle_tree::MigrateMerkleTree;
+pub mod migrate_asset_vault;
+pub use migrate_asset_vault::MigrateAssetVault;
+pub mod migrate_relayer_registry;
+pub use migrate_relayer_registry::MigrateRelayerRegistry;
+pub mod migrate_relayer_node;
+pub use migrate_relayer_node::MigrateRelayerNode;
+pub mod migrate_verification_key;
+pub use migrate_verification_key::MigrateVerificationKey;
 pub mod reset_merkle;
 pub use reset_merkle::ResetMerkleTree;
+pub mod capacity_thresholds;
+pub use capacity_thresholds::SetTreeCapacityThresholds;
+pub mod note_size_limit;
+pub use note_size_limit::SetMaxEncryptedNoteLen;
+pub mod min_withdrawal;
+pub use min_withdrawal::SetAssetMinWithdrawal;
+pub mod risk_tier;
+pub use risk_tier::{
+    InitAssetWithdrawalWindow, InitRiskTiers, SetAssetRiskTier, SetRiskTierLimits,
+};
+pub mod same_tx_flows;
+pub use same_tx_flows::SetSameTxDepositWithdraw;
+pub mod migration_window;
+pub use migration_window::{CloseMigrationWindow, OpenMigrationWindow};
+pub mod sweep_surplus;
+pub use sweep_surplus::{CancelSurplusSweep, RequestSurplusSweep, SweepSurplus};
+pub mod root_events;
+pub use root_events::SetRootEvents;
+pub mod privacy_strict;
+pub use privacy_strict::SetPrivacyStrict;
+pub mod parameter_snapshot;
+pub use parameter_snapshot::{InitParameterSnapshot, PublishParameterSnapshot};
+pub mod crank_bounty;
+pub use crank_bounty::SetCrankBounty;
+pub mod protocol_fee;
+pub use protocol_fee::{InitTreasury, SetProtocolFee};
+pub mod treasury;
+pub use treasury::{
+    CancelTreasuryDisbursement, CollectTreasuryFees, ExecuteTreasuryDisbursement, InitPoolTreasury,
+    RequestTreasuryDisbursement, TreasuryBalance, ViewTreasury,
+};
+pub mod disclosed_path;
+pub use disclosed_path::SetDisclosedPathWithdrawals;
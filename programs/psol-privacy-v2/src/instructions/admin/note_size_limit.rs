@@ -0,0 +1,36 @@
+//! Set Max Encrypted Note Length Instruction
+//!
+//! Configures the largest encrypted note a deposit or join-split output may
+//! carry (default 580 bytes). Notes are passed as instruction data, so an
+//! unbounded note only inflates transactions and anything that stores them.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::state::PoolConfigV2;
+
+/// Accounts for configuring the encrypted note size limit
+#[derive(Accounts)]
+pub struct SetMaxEncryptedNoteLen<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        mut,
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+}
+
+/// Handler for set_max_encrypted_note_len instruction
+pub fn handler(ctx: Context<SetMaxEncryptedNoteLen>, max_len: u16) -> Result<()> {
+    let pool_config = &mut ctx.accounts.pool_config;
+
+    pool_config.set_max_encrypted_note_len(max_len)?;
+    pool_config.last_activity_at = Clock::get()?.unix_timestamp;
+
+    msg!("Max encrypted note length set: {} bytes", max_len);
+
+    Ok(())
+}
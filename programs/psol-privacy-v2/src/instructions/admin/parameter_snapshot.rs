@@ -0,0 +1,154 @@
+//! Parameter Snapshot Instructions
+//!
+//! Publish the fees, limits and flags of one asset under a monotonically
+//! increasing version, so clients can pin the version they showed a user
+//! and have pinned withdrawals refuse adverse changes made since (see
+//! `state::parameter_snapshot`). The values are read from the accounts
+//! that hold them; the authority only decides when to publish.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::events::ParameterSnapshotPublished;
+use crate::state::{
+    AssetVault, EconomicParameters, ParameterSnapshot, PoolConfigV2, RelayerRegistry,
+};
+use crate::utils::pool_clock;
+
+/// Accounts for creating an asset's parameter snapshot
+#[derive(Accounts)]
+pub struct InitParameterSnapshot<'info> {
+    /// Pool authority (must be signer, pays rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+        has_one = relayer_registry,
+    )]
+    pub pool_config: Box<Account<'info, PoolConfigV2>>,
+
+    /// Registered asset the parameters apply to
+    #[account(
+        seeds = [
+            AssetVault::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_vault.asset_id.as_ref(),
+        ],
+        bump = asset_vault.bump,
+    )]
+    pub asset_vault: Box<Account<'info, AssetVault>>,
+
+    /// Relayer registry holding the pool's relayer fee range
+    pub relayer_registry: Box<Account<'info, RelayerRegistry>>,
+
+    /// Parameter snapshot PDA
+    #[account(
+        init,
+        payer = authority,
+        space = ParameterSnapshot::LEN,
+        seeds = [
+            ParameterSnapshot::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_vault.asset_id.as_ref(),
+        ],
+        bump,
+    )]
+    pub parameter_snapshot: Box<Account<'info, ParameterSnapshot>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for publishing a new version of an asset's parameters
+#[derive(Accounts)]
+pub struct PublishParameterSnapshot<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+        has_one = relayer_registry,
+    )]
+    pub pool_config: Box<Account<'info, PoolConfigV2>>,
+
+    /// Registered asset the parameters apply to
+    #[account(
+        seeds = [
+            AssetVault::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_vault.asset_id.as_ref(),
+        ],
+        bump = asset_vault.bump,
+    )]
+    pub asset_vault: Box<Account<'info, AssetVault>>,
+
+    /// Relayer registry holding the pool's relayer fee range
+    pub relayer_registry: Box<Account<'info, RelayerRegistry>>,
+
+    /// Parameter snapshot PDA
+    #[account(
+        mut,
+        seeds = [
+            ParameterSnapshot::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_vault.asset_id.as_ref(),
+        ],
+        bump = parameter_snapshot.bump,
+    )]
+    pub parameter_snapshot: Box<Account<'info, ParameterSnapshot>>,
+}
+
+/// Publish the live parameters to `snapshot` and announce the new version
+fn publish(
+    snapshot: &mut ParameterSnapshot,
+    pool_config: &Account<PoolConfigV2>,
+    asset_vault: &AssetVault,
+    relayer_registry: &RelayerRegistry,
+) -> Result<()> {
+    let timestamp = pool_clock(pool_config)?.unix_timestamp;
+    let parameters = EconomicParameters::read(pool_config, asset_vault, relayer_registry);
+    let version = snapshot.publish(parameters, timestamp)?;
+
+    emit!(ParameterSnapshotPublished {
+        pool: pool_config.key(),
+        asset_id: asset_vault.asset_id,
+        version,
+        parameters_hash: snapshot.parameters_hash,
+        adverse: snapshot.adverse_version == version,
+        timestamp,
+    });
+
+    msg!("Parameter snapshot version {} published", version);
+    Ok(())
+}
+
+/// Handler for init_parameter_snapshot instruction
+///
+/// Publishes the current parameters as version 1.
+pub fn init_handler(ctx: Context<InitParameterSnapshot>) -> Result<()> {
+    let accounts = ctx.accounts;
+    accounts.parameter_snapshot.initialize(
+        accounts.pool_config.key(),
+        accounts.asset_vault.asset_id,
+        ctx.bumps.parameter_snapshot,
+    );
+    publish(
+        &mut accounts.parameter_snapshot,
+        &accounts.pool_config,
+        &accounts.asset_vault,
+        &accounts.relayer_registry,
+    )
+}
+
+/// Handler for publish_parameter_snapshot instruction
+pub fn publish_handler(ctx: Context<PublishParameterSnapshot>) -> Result<()> {
+    let accounts = ctx.accounts;
+    publish(
+        &mut accounts.parameter_snapshot,
+        &accounts.pool_config,
+        &accounts.asset_vault,
+        &accounts.relayer_registry,
+    )
+}
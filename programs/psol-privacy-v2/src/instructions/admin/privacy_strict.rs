@@ -0,0 +1,37 @@
+//! Set Privacy Strict Instruction
+//!
+//! A nullification receipt links a spend to the deposit that carried the
+//! spent note's envelope. Privacy-strict pools give up that wallet-sync
+//! shortcut: `record_note_receipt` is refused, so spent nullifiers keep an
+//! all-zero receipt. Receipts recorded before the flag was set remain.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::state::PoolConfigV2;
+
+/// Accounts for enabling or disabling privacy-strict mode
+#[derive(Accounts)]
+pub struct SetPrivacyStrict<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        mut,
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+}
+
+/// Handler for set_privacy_strict instruction
+pub fn handler(ctx: Context<SetPrivacyStrict>, enabled: bool) -> Result<()> {
+    let pool_config = &mut ctx.accounts.pool_config;
+
+    pool_config.privacy_strict = enabled;
+    pool_config.last_activity_at = Clock::get()?.unix_timestamp;
+
+    msg!("Privacy-strict mode enabled: {}", enabled);
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+//! Protocol Fee Instructions
+//!
+//! The pool takes `protocol_fee_bps` of every withdrawal out of what the
+//! recipient receives. Token withdrawals (`withdraw_masp`, `withdraw_v2`)
+//! pay it into the asset's treasury token account, a PDA owned by the asset
+//! vault; native SOL withdrawals leave it in the vault until
+//! `collect_treasury_fees` moves it to the pool treasury. The authority
+//! sets the fee and creates each token asset's treasury before charging a
+//! fee on it. Disbursing collected fees goes through the pool treasury's
+//! timelock (see `admin::treasury`).
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::error::PrivacyErrorV2;
+use crate::state::{AssetVault, PoolConfigV2};
+
+/// Accounts for setting the protocol fee
+#[derive(Accounts)]
+pub struct SetProtocolFee<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        mut,
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+}
+
+/// Accounts for creating an asset's treasury token account
+#[derive(Accounts)]
+#[instruction(asset_id: [u8; 32])]
+pub struct InitTreasury<'info> {
+    /// Pool authority (must be signer, pays rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Asset vault (treasury token authority)
+    #[account(
+        seeds = [
+            AssetVault::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        bump = asset_vault.bump,
+        constraint = !asset_vault.is_native_sol() @ PrivacyErrorV2::TreasuryRequiresToken,
+    )]
+    pub asset_vault: Account<'info, AssetVault>,
+
+    /// Asset mint
+    #[account(address = asset_vault.mint @ PrivacyErrorV2::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Treasury token account (PDA)
+    #[account(
+        init,
+        payer = authority,
+        token::mint = mint,
+        token::authority = asset_vault,
+        token::token_program = token_program,
+        seeds = [AssetVault::TREASURY_SEED_PREFIX, asset_vault.key().as_ref()],
+        bump,
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token program owning the mint
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Handler for set_protocol_fee instruction
+pub fn set_fee_handler(ctx: Context<SetProtocolFee>, fee_bps: u16) -> Result<()> {
+    let pool_config = &mut ctx.accounts.pool_config;
+
+    pool_config.set_protocol_fee(fee_bps)?;
+    pool_config.last_activity_at = Clock::get()?.unix_timestamp;
+
+    msg!("Protocol fee set: {} bps", fee_bps);
+
+    Ok(())
+}
+
+/// Handler for init_treasury instruction
+pub fn init_treasury_handler(ctx: Context<InitTreasury>, asset_id: [u8; 32]) -> Result<()> {
+    msg!(
+        "Treasury {} created for asset {:?}",
+        ctx.accounts.treasury_token_account.key(),
+        &asset_id[..8]
+    );
+
+    Ok(())
+}
@@ -1,9 +1,13 @@
 //! Reset Merkle Tree Instruction
 //!
 //! Admin function to reset merkle tree state to empty.
-use anchor_lang::prelude::*;
+//!
+//! Open a migration window first so outstanding withdrawal proofs against
+//! the current roots stay valid after the reset.
 use crate::error::PrivacyErrorV2;
-use crate::state::{PoolConfigV2, MerkleTreeV2};
+use crate::events::RootUpdatedEvent;
+use crate::state::{MerkleTreeV2, PoolConfigV2};
+use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
 pub struct ResetMerkleTree<'info> {
@@ -12,6 +16,7 @@ pub struct ResetMerkleTree<'info> {
 
     /// Pool configuration account
     #[account(
+        mut,
         has_one = authority @ PrivacyErrorV2::Unauthorized,
     )]
     pub pool_config: Account<'info, PoolConfigV2>,
@@ -26,18 +31,30 @@ pub struct ResetMerkleTree<'info> {
 
 pub fn handler(ctx: Context<ResetMerkleTree>) -> Result<()> {
     let merkle = &mut ctx.accounts.merkle_tree;
-    
+
     // Reset to empty tree state
     merkle.next_leaf_index = 0;
     merkle.current_root = merkle.zeros[merkle.depth as usize];
     merkle.filled_subtrees = merkle.zeros[..merkle.depth as usize].to_vec();
     merkle.root_history_index = 0;
-    
+
     // Clear root history
     for i in 0..merkle.root_history.len() {
         merkle.root_history[i] = [0u8; 32];
     }
-    
+
+    ctx.accounts.pool_config.reset_tree_capacity_level();
+
+    if let Some(sequence) = ctx.accounts.pool_config.next_root_event_sequence()? {
+        emit!(RootUpdatedEvent {
+            pool: ctx.accounts.pool_config.key(),
+            root: merkle.current_root,
+            leaf_count: 0,
+            slot: Clock::get()?.slot,
+            sequence,
+        });
+    }
+
     msg!("Merkle tree reset to empty state");
     Ok(())
 }
@@ -0,0 +1,227 @@
+//! Risk Tier Instructions
+//!
+//! - `init_risk_tiers`: create the pool's tier table with the default tiers
+//! - `set_risk_tier_limits`: replace one tier's limits, for every asset in it
+//! - `set_asset_risk_tier`: put an asset in a tier
+//! - `init_asset_withdrawal_window`: create an asset's withdrawal window,
+//!   needed to withdraw it while its tier rate-limits withdrawals
+//!
+//! See `state::risk_tier` for the default tiers.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::events::{AssetRiskTierSet, RiskTierLimitsUpdated};
+use crate::state::{
+    AssetVault, AssetWithdrawalWindow, PoolConfigV2, RiskTierConfig, RiskTierLimits,
+};
+use crate::utils::pool_clock;
+
+/// Accounts for creating the pool's risk tier table
+#[derive(Accounts)]
+pub struct InitRiskTiers<'info> {
+    /// Pool authority (must be signer, pays rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Risk tier table (PDA)
+    #[account(
+        init,
+        payer = authority,
+        space = RiskTierConfig::LEN,
+        seeds = [RiskTierConfig::SEED_PREFIX, pool_config.key().as_ref()],
+        bump,
+    )]
+    pub risk_tiers: Account<'info, RiskTierConfig>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for changing one tier's limits
+#[derive(Accounts)]
+pub struct SetRiskTierLimits<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Risk tier table of the pool
+    #[account(
+        mut,
+        seeds = [RiskTierConfig::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = risk_tiers.bump,
+    )]
+    pub risk_tiers: Account<'info, RiskTierConfig>,
+}
+
+/// Accounts for putting an asset in a tier
+#[derive(Accounts)]
+#[instruction(asset_id: [u8; 32])]
+pub struct SetAssetRiskTier<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Risk tier table of the pool
+    #[account(
+        seeds = [RiskTierConfig::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = risk_tiers.bump,
+    )]
+    pub risk_tiers: Account<'info, RiskTierConfig>,
+
+    /// Asset vault to configure
+    #[account(
+        mut,
+        seeds = [
+            AssetVault::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        bump = asset_vault.bump,
+        constraint = asset_vault.pool == pool_config.key() @ PrivacyErrorV2::InvalidVaultPool,
+    )]
+    pub asset_vault: Account<'info, AssetVault>,
+}
+
+/// Accounts for creating an asset's withdrawal window
+#[derive(Accounts)]
+#[instruction(asset_id: [u8; 32])]
+pub struct InitAssetWithdrawalWindow<'info> {
+    /// Pool authority (must be signer, pays rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Asset vault the window is for
+    #[account(
+        seeds = [
+            AssetVault::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        bump = asset_vault.bump,
+        constraint = asset_vault.pool == pool_config.key() @ PrivacyErrorV2::InvalidVaultPool,
+    )]
+    pub asset_vault: Account<'info, AssetVault>,
+
+    /// Withdrawal window (PDA)
+    #[account(
+        init,
+        payer = authority,
+        space = AssetWithdrawalWindow::LEN,
+        seeds = [AssetWithdrawalWindow::SEED_PREFIX, asset_vault.key().as_ref()],
+        bump,
+    )]
+    pub withdrawal_window: Account<'info, AssetWithdrawalWindow>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Handler for init_risk_tiers instruction
+pub fn init_handler(ctx: Context<InitRiskTiers>) -> Result<()> {
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+    ctx.accounts.risk_tiers.initialize(
+        ctx.accounts.pool_config.key(),
+        ctx.bumps.risk_tiers,
+        timestamp,
+    );
+
+    msg!("Risk tiers initialized");
+
+    Ok(())
+}
+
+/// Handler for set_risk_tier_limits instruction
+pub fn set_limits_handler(
+    ctx: Context<SetRiskTierLimits>,
+    tier: u8,
+    limits: RiskTierLimits,
+) -> Result<()> {
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+    ctx.accounts
+        .risk_tiers
+        .set_limits(tier, limits, timestamp)?;
+
+    emit!(RiskTierLimitsUpdated {
+        pool: ctx.accounts.pool_config.key(),
+        tier,
+        deposits_enabled: limits.deposits_enabled,
+        withdrawals_enabled: limits.withdrawals_enabled,
+        max_deposit: limits.max_deposit,
+        max_withdrawal: limits.max_withdrawal,
+        rate_limit_window: limits.rate_limit_window,
+        rate_limit_amount: limits.rate_limit_amount,
+        delay_threshold: limits.delay_threshold,
+        withdrawal_delay: limits.withdrawal_delay,
+        timestamp,
+    });
+
+    msg!("Risk tier {} limits updated", tier);
+
+    Ok(())
+}
+
+/// Handler for set_asset_risk_tier instruction
+pub fn set_asset_handler(
+    ctx: Context<SetAssetRiskTier>,
+    asset_id: [u8; 32],
+    tier: u8,
+) -> Result<()> {
+    // Only tiers that exist in the table
+    ctx.accounts.risk_tiers.limits(tier)?;
+    let asset_vault = &mut ctx.accounts.asset_vault;
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+
+    asset_vault.set_risk_tier(tier);
+    asset_vault.last_activity_at = timestamp;
+
+    emit!(AssetRiskTierSet {
+        pool: ctx.accounts.pool_config.key(),
+        asset_id,
+        tier,
+        timestamp,
+    });
+
+    msg!("Asset risk tier set: {}", tier);
+
+    Ok(())
+}
+
+/// Handler for init_asset_withdrawal_window instruction
+pub fn init_window_handler(
+    ctx: Context<InitAssetWithdrawalWindow>,
+    _asset_id: [u8; 32],
+) -> Result<()> {
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+    ctx.accounts.withdrawal_window.initialize(
+        ctx.accounts.asset_vault.key(),
+        ctx.bumps.withdrawal_window,
+        timestamp,
+    );
+
+    msg!("Asset withdrawal window initialized");
+
+    Ok(())
+}
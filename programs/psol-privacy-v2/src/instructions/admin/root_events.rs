@@ -0,0 +1,37 @@
+//! Set Root Events Instruction
+//!
+//! Bridges and oracles that only follow the pool's Merkle root would
+//! otherwise have to parse every batch and settlement event. With root
+//! events enabled, each root change also emits a `RootUpdatedEvent`
+//! carrying the root, leaf count, slot and a gapless per-pool sequence.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::state::PoolConfigV2;
+
+/// Accounts for enabling or disabling root events
+#[derive(Accounts)]
+pub struct SetRootEvents<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        mut,
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+}
+
+/// Handler for set_root_events instruction
+pub fn handler(ctx: Context<SetRootEvents>, enabled: bool) -> Result<()> {
+    let pool_config = &mut ctx.accounts.pool_config;
+
+    pool_config.emit_root_events = enabled;
+    pool_config.last_activity_at = Clock::get()?.unix_timestamp;
+
+    msg!("Root update events enabled: {}", enabled);
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+//! Set Same-Transaction Deposit/Withdraw Instruction
+//!
+//! By default a transaction may not both deposit into and withdraw from
+//! the pool (see `utils::same_tx_guard`). Pools that knowingly serve such
+//! flows, e.g. a test deployment or an integration that re-shields in one
+//! step, can allow them here.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::state::PoolConfigV2;
+
+/// Accounts for allowing or forbidding same-transaction deposit/withdraw
+#[derive(Accounts)]
+pub struct SetSameTxDepositWithdraw<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        mut,
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+}
+
+/// Handler for set_same_tx_deposit_withdraw instruction
+pub fn handler(ctx: Context<SetSameTxDepositWithdraw>, allowed: bool) -> Result<()> {
+    let pool_config = &mut ctx.accounts.pool_config;
+
+    pool_config.allow_same_tx_deposit_withdraw = allowed;
+    pool_config.last_activity_at = Clock::get()?.unix_timestamp;
+
+    msg!("Same-transaction deposit/withdraw allowed: {}", allowed);
+
+    Ok(())
+}
@@ -0,0 +1,262 @@
+//! Surplus Sweep Instructions
+//!
+//! Recovers tokens sent directly to a vault token account (donations,
+//! airdrops) that back no shielded note. The authority first announces the
+//! sweep; after `SURPLUS_SWEEP_DELAY_SECONDS` it may move the balance above
+//! the asset's `shielded_balance`, and nothing more, into the asset's
+//! treasury token account (see `init_treasury`), from where it leaves only
+//! through a treasury disbursement.
+//!
+//! Native SOL vaults cannot be swept: their surplus is moved into the pool
+//! treasury by `collect_treasury_fees`.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::error::PrivacyErrorV2;
+use crate::events::{SurplusSweepCancelled, SurplusSweepRequested, SurplusSwept};
+use crate::state::{AssetVault, PoolConfigV2, SurplusSweep};
+use crate::utils::pool_clock;
+
+/// Accounts for announcing a surplus sweep
+#[derive(Accounts)]
+#[instruction(asset_id: [u8; 32])]
+pub struct RequestSurplusSweep<'info> {
+    /// Pool authority (must be signer, pays rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Asset vault to sweep
+    #[account(
+        seeds = [
+            AssetVault::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        bump = asset_vault.bump,
+        constraint = asset_vault.pool == pool_config.key() @ PrivacyErrorV2::InvalidVaultPool,
+        constraint = !asset_vault.is_native_sol() @ PrivacyErrorV2::SurplusSweepRequiresToken,
+    )]
+    pub asset_vault: Account<'info, AssetVault>,
+
+    /// Vault token account (read for the current surplus)
+    #[account(
+        constraint = vault_token_account.key() == asset_vault.token_account
+            @ PrivacyErrorV2::InvalidVaultTokenAccount,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// The asset's treasury token account, which will receive the surplus
+    #[account(
+        seeds = [AssetVault::TREASURY_SEED_PREFIX, asset_vault.key().as_ref()],
+        bump,
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Pending sweep PDA
+    #[account(
+        init,
+        payer = authority,
+        space = SurplusSweep::LEN,
+        seeds = [SurplusSweep::SEED_PREFIX, asset_vault.key().as_ref()],
+        bump,
+    )]
+    pub surplus_sweep: Account<'info, SurplusSweep>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for executing an announced surplus sweep
+#[derive(Accounts)]
+#[instruction(asset_id: [u8; 32])]
+pub struct SweepSurplus<'info> {
+    /// Pool authority (must be signer, receives the sweep's rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Asset vault being swept (token authority)
+    #[account(
+        seeds = [
+            AssetVault::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        bump = asset_vault.bump,
+        constraint = asset_vault.pool == pool_config.key() @ PrivacyErrorV2::InvalidVaultPool,
+    )]
+    pub asset_vault: Account<'info, AssetVault>,
+
+    /// Vault token account
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == asset_vault.token_account
+            @ PrivacyErrorV2::InvalidVaultTokenAccount,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// The asset's treasury token account
+    #[account(
+        mut,
+        seeds = [AssetVault::TREASURY_SEED_PREFIX, asset_vault.key().as_ref()],
+        bump,
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Asset mint
+    #[account(address = asset_vault.mint @ PrivacyErrorV2::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Pending sweep PDA (closed on execution)
+    #[account(
+        mut,
+        close = authority,
+        seeds = [SurplusSweep::SEED_PREFIX, asset_vault.key().as_ref()],
+        bump = surplus_sweep.bump,
+    )]
+    pub surplus_sweep: Account<'info, SurplusSweep>,
+
+    /// Token program owning the mint
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Accounts for withdrawing an announced surplus sweep
+#[derive(Accounts)]
+#[instruction(asset_id: [u8; 32])]
+pub struct CancelSurplusSweep<'info> {
+    /// Pool authority (must be signer, receives rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Asset vault the sweep was announced for
+    #[account(
+        seeds = [
+            AssetVault::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        bump = asset_vault.bump,
+    )]
+    pub asset_vault: Account<'info, AssetVault>,
+
+    /// Pending sweep PDA
+    #[account(
+        mut,
+        close = authority,
+        seeds = [SurplusSweep::SEED_PREFIX, asset_vault.key().as_ref()],
+        bump = surplus_sweep.bump,
+    )]
+    pub surplus_sweep: Account<'info, SurplusSweep>,
+}
+
+/// Handler for request_surplus_sweep instruction
+pub fn request_handler(ctx: Context<RequestSurplusSweep>, asset_id: [u8; 32]) -> Result<()> {
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+    let pool = ctx.accounts.pool_config.key();
+    let destination = ctx.accounts.treasury_token_account.key();
+
+    let sweep = &mut ctx.accounts.surplus_sweep;
+    sweep.initialize(
+        pool,
+        ctx.accounts.asset_vault.key(),
+        ctx.bumps.surplus_sweep,
+        timestamp,
+    );
+
+    let surplus = SurplusSweep::surplus(
+        ctx.accounts.vault_token_account.amount,
+        ctx.accounts.asset_vault.shielded_balance,
+    );
+
+    emit!(SurplusSweepRequested {
+        pool,
+        asset_id,
+        destination,
+        surplus,
+        executable_at: sweep.executable_at,
+        timestamp,
+    });
+
+    msg!(
+        "Surplus sweep requested: {} currently, executable at {}",
+        surplus,
+        sweep.executable_at
+    );
+    Ok(())
+}
+
+/// Handler for sweep_surplus instruction
+pub fn sweep_handler(ctx: Context<SweepSurplus>, asset_id: [u8; 32]) -> Result<()> {
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+    ctx.accounts.surplus_sweep.require_executable(timestamp)?;
+
+    // Recomputed now: deposits and withdrawals since the request move
+    // shielded_balance together with the vault balance
+    let amount = SurplusSweep::surplus(
+        ctx.accounts.vault_token_account.amount,
+        ctx.accounts.asset_vault.shielded_balance,
+    );
+    require!(amount > 0, PrivacyErrorV2::NoSurplusToSweep);
+
+    let pool_key = ctx.accounts.pool_config.key();
+    let vault_seeds: &[&[u8]] = &[
+        AssetVault::SEED_PREFIX,
+        pool_key.as_ref(),
+        asset_id.as_ref(),
+        &[ctx.accounts.asset_vault.bump],
+    ];
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.treasury_token_account.to_account_info(),
+                authority: ctx.accounts.asset_vault.to_account_info(),
+            },
+            &[vault_seeds],
+        ),
+        amount,
+        ctx.accounts.mint.decimals,
+    )?;
+
+    emit!(SurplusSwept {
+        pool: pool_key,
+        asset_id,
+        destination: ctx.accounts.treasury_token_account.key(),
+        amount,
+        timestamp,
+    });
+
+    msg!("Swept {} surplus tokens into the treasury", amount);
+    Ok(())
+}
+
+/// Handler for cancel_surplus_sweep instruction
+pub fn cancel_handler(ctx: Context<CancelSurplusSweep>, asset_id: [u8; 32]) -> Result<()> {
+    emit!(SurplusSweepCancelled {
+        pool: ctx.accounts.pool_config.key(),
+        asset_id,
+        timestamp: pool_clock(&ctx.accounts.pool_config)?.unix_timestamp,
+    });
+
+    msg!("Surplus sweep cancelled");
+    Ok(())
+}
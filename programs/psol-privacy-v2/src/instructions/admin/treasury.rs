@@ -0,0 +1,479 @@
+//! Treasury Instructions
+//!
+//! The pool's `Treasury` PDA keeps protocol fees until the authority
+//! disburses them:
+//! - `init_pool_treasury` creates it
+//! - `collect_treasury_fees` moves a native SOL vault's lamports beyond its
+//!   `shielded_balance` (protocol fees, plus anything sent straight to the
+//!   vault) into the treasury; token fees are already paid into the asset's
+//!   treasury token account at withdrawal
+//! - `view_treasury` returns an asset's collected and uncollected fees and
+//!   its pending disbursement (read-only; simulate and read the return data)
+//! - `request_treasury_disbursement` announces an asset, destination and
+//!   amount; `execute_treasury_disbursement` pays it after
+//!   `TREASURY_DISBURSEMENT_DELAY_SECONDS`, and
+//!   `cancel_treasury_disbursement` withdraws it
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::error::PrivacyErrorV2;
+use crate::events::{
+    TreasuryDisbursementCancelled, TreasuryDisbursementRequested, TreasuryFeesCollected,
+    TreasuryWithdrawn,
+};
+use crate::state::{AssetVault, PoolConfigV2, SurplusSweep, Treasury};
+use crate::utils::pool_clock;
+
+/// An asset's fees as seen by `view_treasury`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TreasuryBalance {
+    /// Fees the treasury holds for the asset, less any pending disbursement
+    /// of it (lamports for native SOL, tokens otherwise)
+    pub available: u64,
+    /// Native SOL fees still in the vault, waiting to be collected
+    pub uncollected: u64,
+    /// Amount of the asset's pending disbursement (0 = none)
+    pub pending_amount: u64,
+    /// When the asset's pending disbursement may execute (0 = none)
+    pub pending_executable_at: i64,
+}
+
+/// Accounts for creating the pool treasury
+#[derive(Accounts)]
+pub struct InitPoolTreasury<'info> {
+    /// Pool authority (must be signer, pays rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Treasury PDA
+    #[account(
+        init,
+        payer = authority,
+        space = Treasury::LEN,
+        seeds = [Treasury::SEED_PREFIX, pool_config.key().as_ref()],
+        bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for collecting a native SOL vault's fees
+#[derive(Accounts)]
+#[instruction(asset_id: [u8; 32])]
+pub struct CollectTreasuryFees<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Treasury PDA (receives the lamports)
+    #[account(
+        mut,
+        seeds = [Treasury::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    /// Native SOL vault holding the fees
+    #[account(
+        mut,
+        seeds = [
+            AssetVault::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        bump = asset_vault.bump,
+        constraint = asset_vault.is_native_sol() @ PrivacyErrorV2::TreasuryCollectRequiresNativeSol,
+    )]
+    pub asset_vault: Account<'info, AssetVault>,
+}
+
+/// Accounts for reading an asset's treasury balance
+#[derive(Accounts)]
+#[instruction(asset_id: [u8; 32])]
+pub struct ViewTreasury<'info> {
+    /// Pool configuration account
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Treasury PDA
+    #[account(
+        seeds = [Treasury::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    /// Asset vault of the asset
+    #[account(
+        seeds = [
+            AssetVault::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        bump = asset_vault.bump,
+    )]
+    pub asset_vault: Account<'info, AssetVault>,
+
+    /// The asset's treasury token account (token assets only)
+    #[account(
+        seeds = [AssetVault::TREASURY_SEED_PREFIX, asset_vault.key().as_ref()],
+        bump,
+    )]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+}
+
+/// Accounts for announcing a treasury disbursement
+#[derive(Accounts)]
+#[instruction(asset_id: [u8; 32])]
+pub struct RequestTreasuryDisbursement<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Treasury PDA
+    #[account(
+        mut,
+        seeds = [Treasury::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    /// Asset vault of the asset to disburse
+    #[account(
+        seeds = [
+            AssetVault::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        bump = asset_vault.bump,
+    )]
+    pub asset_vault: Account<'info, AssetVault>,
+
+    /// The asset's treasury token account (token assets only)
+    #[account(
+        seeds = [AssetVault::TREASURY_SEED_PREFIX, asset_vault.key().as_ref()],
+        bump,
+    )]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Account that will receive the disbursement: any account for native
+    /// SOL, a token account of the asset's mint otherwise
+    /// CHECK: Validated in the handler for the asset's type
+    #[account(
+        constraint = destination.key() != asset_vault.key()
+            && destination.key() != asset_vault.token_account
+            @ PrivacyErrorV2::InvalidVaultTokenAccount,
+    )]
+    pub destination: UncheckedAccount<'info>,
+}
+
+/// Accounts for executing an announced treasury disbursement
+#[derive(Accounts)]
+#[instruction(asset_id: [u8; 32])]
+pub struct ExecuteTreasuryDisbursement<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Treasury PDA (pays native SOL disbursements)
+    #[account(
+        mut,
+        seeds = [Treasury::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    /// Asset vault (treasury token authority)
+    #[account(
+        seeds = [
+            AssetVault::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        bump = asset_vault.bump,
+    )]
+    pub asset_vault: Account<'info, AssetVault>,
+
+    /// Destination announced in the request
+    /// CHECK: Address constrained to the announced destination
+    #[account(
+        mut,
+        address = treasury.pending_destination @ PrivacyErrorV2::RecipientMismatch,
+    )]
+    pub destination: UncheckedAccount<'info>,
+
+    /// The asset's treasury token account (token assets only)
+    #[account(
+        mut,
+        seeds = [AssetVault::TREASURY_SEED_PREFIX, asset_vault.key().as_ref()],
+        bump,
+    )]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Asset mint (token assets only)
+    #[account(address = asset_vault.mint @ PrivacyErrorV2::InvalidMint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Token program owning the mint (token assets only)
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+/// Accounts for withdrawing an announced treasury disbursement
+#[derive(Accounts)]
+pub struct CancelTreasuryDisbursement<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Treasury PDA
+    #[account(
+        mut,
+        seeds = [Treasury::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+}
+
+/// Fees the treasury holds for an asset: its lamports above rent for native
+/// SOL, its treasury token account's balance otherwise
+fn held_for_asset(
+    treasury: &Account<Treasury>,
+    asset_vault: &AssetVault,
+    treasury_token_account: Option<&InterfaceAccount<TokenAccount>>,
+) -> Result<u64> {
+    if asset_vault.is_native_sol() {
+        AssetVault::native_balance(&treasury.to_account_info())
+    } else {
+        Ok(treasury_token_account
+            .ok_or(error!(PrivacyErrorV2::TreasuryRequired))?
+            .amount)
+    }
+}
+
+/// Handler for init_pool_treasury instruction
+pub fn init_handler(ctx: Context<InitPoolTreasury>) -> Result<()> {
+    let pool = ctx.accounts.pool_config.key();
+    ctx.accounts.treasury.initialize(pool, ctx.bumps.treasury);
+
+    msg!("Treasury {} created", ctx.accounts.treasury.key());
+    Ok(())
+}
+
+/// Handler for collect_treasury_fees instruction
+pub fn collect_handler(ctx: Context<CollectTreasuryFees>, asset_id: [u8; 32]) -> Result<()> {
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+    let vault_info = ctx.accounts.asset_vault.to_account_info();
+
+    let amount = SurplusSweep::surplus(
+        AssetVault::native_balance(&vault_info)?,
+        ctx.accounts.asset_vault.shielded_balance,
+    );
+    require!(amount > 0, PrivacyErrorV2::NoTreasuryFeesToCollect);
+
+    AssetVault::pay_native(
+        &vault_info,
+        &ctx.accounts.treasury.to_account_info(),
+        amount,
+    )?;
+    ctx.accounts.treasury.record_collected(amount)?;
+
+    emit!(TreasuryFeesCollected {
+        pool: ctx.accounts.pool_config.key(),
+        asset_id,
+        amount,
+        timestamp,
+    });
+
+    msg!("Collected {} lamports into the treasury", amount);
+    Ok(())
+}
+
+/// Handler for view_treasury instruction
+pub fn view_handler(ctx: Context<ViewTreasury>, asset_id: [u8; 32]) -> Result<TreasuryBalance> {
+    let treasury = &ctx.accounts.treasury;
+    let asset_vault = &ctx.accounts.asset_vault;
+
+    let held = held_for_asset(
+        treasury,
+        asset_vault,
+        ctx.accounts.treasury_token_account.as_ref(),
+    )?;
+    let uncollected = if asset_vault.is_native_sol() {
+        SurplusSweep::surplus(
+            AssetVault::native_balance(&asset_vault.to_account_info())?,
+            asset_vault.shielded_balance,
+        )
+    } else {
+        0
+    };
+    let (pending_amount, pending_executable_at) =
+        if treasury.has_pending && treasury.pending_asset_id == asset_id {
+            (treasury.pending_amount, treasury.pending_executable_at)
+        } else {
+            (0, 0)
+        };
+
+    Ok(TreasuryBalance {
+        available: held.saturating_sub(pending_amount),
+        uncollected,
+        pending_amount,
+        pending_executable_at,
+    })
+}
+
+/// Handler for request_treasury_disbursement instruction
+pub fn request_handler(
+    ctx: Context<RequestTreasuryDisbursement>,
+    asset_id: [u8; 32],
+    amount: u64,
+) -> Result<()> {
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+    let asset_vault = &ctx.accounts.asset_vault;
+
+    let held = held_for_asset(
+        &ctx.accounts.treasury,
+        asset_vault,
+        ctx.accounts.treasury_token_account.as_ref(),
+    )?;
+    require!(amount <= held, PrivacyErrorV2::InsufficientBalance);
+
+    // Token disbursements go to a token account of the asset's mint
+    if !asset_vault.is_native_sol() {
+        let destination =
+            TokenAccount::try_deserialize(&mut &ctx.accounts.destination.try_borrow_data()?[..])?;
+        require!(
+            destination.mint == asset_vault.mint,
+            PrivacyErrorV2::InvalidMint
+        );
+    }
+
+    let destination = ctx.accounts.destination.key();
+    let treasury = &mut ctx.accounts.treasury;
+    treasury.request_disbursement(asset_id, destination, amount, timestamp)?;
+
+    emit!(TreasuryDisbursementRequested {
+        pool: ctx.accounts.pool_config.key(),
+        asset_id,
+        destination,
+        amount,
+        executable_at: treasury.pending_executable_at,
+        timestamp,
+    });
+
+    msg!(
+        "Treasury disbursement of {} requested, executable at {}",
+        amount,
+        treasury.pending_executable_at
+    );
+    Ok(())
+}
+
+/// Handler for execute_treasury_disbursement instruction
+pub fn execute_handler(
+    ctx: Context<ExecuteTreasuryDisbursement>,
+    asset_id: [u8; 32],
+) -> Result<()> {
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+    let native_sol = ctx.accounts.asset_vault.is_native_sol();
+    let amount = ctx
+        .accounts
+        .treasury
+        .take_disbursement(&asset_id, native_sol, timestamp)?;
+
+    if native_sol {
+        // The treasury is owned by this program, so lamports move directly
+        AssetVault::pay_native(
+            &ctx.accounts.treasury.to_account_info(),
+            &ctx.accounts.destination.to_account_info(),
+            amount,
+        )?;
+    } else {
+        let (Some(treasury_token_account), Some(mint), Some(token_program)) = (
+            ctx.accounts.treasury_token_account.as_ref(),
+            ctx.accounts.mint.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+        ) else {
+            return err!(PrivacyErrorV2::MissingAssetAccount);
+        };
+        require!(
+            amount <= treasury_token_account.amount,
+            PrivacyErrorV2::InsufficientBalance
+        );
+
+        let pool_key = ctx.accounts.pool_config.key();
+        let bump = [ctx.accounts.asset_vault.bump];
+        let vault_seeds = AssetVault::seeds(&pool_key, &asset_id, &bump);
+        let signer_seeds: &[&[&[u8]]] = &[&vault_seeds];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                TransferChecked {
+                    from: treasury_token_account.to_account_info(),
+                    mint: mint.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.asset_vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+            mint.decimals,
+        )?;
+    }
+
+    emit!(TreasuryWithdrawn {
+        pool: ctx.accounts.pool_config.key(),
+        asset_id,
+        destination: ctx.accounts.destination.key(),
+        amount,
+        timestamp,
+    });
+
+    msg!("Disbursed {} from the treasury", amount);
+    Ok(())
+}
+
+/// Handler for cancel_treasury_disbursement instruction
+pub fn cancel_handler(ctx: Context<CancelTreasuryDisbursement>) -> Result<()> {
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+    let treasury = &mut ctx.accounts.treasury;
+    let asset_id = treasury.pending_asset_id;
+    let amount = treasury.pending_amount;
+    treasury.cancel_disbursement()?;
+
+    emit!(TreasuryDisbursementCancelled {
+        pool: ctx.accounts.pool_config.key(),
+        asset_id,
+        amount,
+        timestamp,
+    });
+
+    msg!("Treasury disbursement cancelled");
+    Ok(())
+}
@@ -1,17 +1,66 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke_signed;
 
 use crate::error::PrivacyErrorV2;
-use crate::events::BatchProcessedEvent;
-use crate::state::{MerkleTreeV2, PendingDepositsBuffer, PoolConfigV2};
-use crate::utils::cu;
+use crate::events::{BatchProcessedEvent, RootUpdatedEvent, TreeCapacityWarning};
+use crate::state::{
+    BatcherRole, DepositFinalized, DepositHook, GlobalConfig, MerkleTreeV2, PendingDepositsBuffer,
+    PoolConfigV2,
+};
+use crate::tx_limits::{require_remaining_accounts_within, MAX_DEPOSIT_HOOK_REMAINING_ACCOUNTS};
+use crate::utils::{cu, pool_clock};
 
 /// Maximum deposits to process in a single batch
 pub const MAX_BATCH_SIZE: u16 = 50;
 
-/// Accounts for batch processing deposits (authority-only; no batcher_role account required)
+/// Estimated CU per tree level during insertion (one Poseidon hash)
+pub const ESTIMATED_CU_PER_LEVEL: u32 = 1_000;
+
+/// Estimated CU per insertion independent of depth (buffer/root bookkeeping)
+pub const ESTIMATED_CU_PER_INSERT_BASE: u32 = 2_000;
+
+/// Estimated fixed CU cost of the instruction (account loading, event, buffer drain)
+pub const ESTIMATED_CU_OVERHEAD: u32 = 30_000;
+
+/// Estimated CU cost of inserting a single leaf into a tree of `depth`
+pub fn estimated_cu_per_insert(depth: u8) -> u32 {
+    ESTIMATED_CU_PER_INSERT_BASE.saturating_add(ESTIMATED_CU_PER_LEVEL.saturating_mul(depth as u32))
+}
+
+/// Number of insertions that fit in `target_cu_budget` for a tree of `depth`
+pub fn max_inserts_for_budget(target_cu_budget: u32, depth: u8) -> u16 {
+    let available = target_cu_budget.saturating_sub(ESTIMATED_CU_OVERHEAD);
+    let fit = available / estimated_cu_per_insert(depth);
+    fit.min(MAX_BATCH_SIZE as u32) as u16
+}
+
+/// Pay the batcher `owed` lamports of crank bounty released from the
+/// pending buffer's escrow, never touching its rent-exempt minimum
+///
+/// Leaves queued without a deposit (change outputs) escrow nothing, and
+/// `owed` is taken from `escrowed_bounty_lamports`, so the payout is
+/// bounded by what deposits actually paid in. Returns the amount paid.
+fn pay_crank_bounty(pending_buffer: &AccountInfo, batcher: &AccountInfo, owed: u64) -> Result<u64> {
+    if owed == 0 {
+        return Ok(0);
+    }
+    let reserved = Rent::get()?.minimum_balance(pending_buffer.data_len());
+    let paid = owed.min(pending_buffer.lamports().saturating_sub(reserved));
+    if paid > 0 {
+        **pending_buffer.try_borrow_mut_lamports()? -= paid;
+        **batcher.try_borrow_mut_lamports()? = batcher
+            .lamports()
+            .checked_add(paid)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+    }
+    Ok(paid)
+}
+
+/// Accounts for batch processing deposits (pool authority, or a bonded
+/// batcher passing its `batcher_role`)
 #[derive(Accounts)]
 pub struct BatchProcessDeposits<'info> {
-    /// Batcher (must be pool authority)
+    /// Batcher (pool authority or bonded batcher)
     #[account(mut)]
     pub batcher: Signer<'info>,
 
@@ -23,6 +72,16 @@ pub struct BatchProcessDeposits<'info> {
     )]
     pub pool_config: Box<Account<'info, PoolConfigV2>>,
 
+    /// Program-wide pause switch
+    /// CHECK: PDA; may not exist yet (see `GlobalConfig::is_paused_account`)
+    #[account(
+        seeds = [GlobalConfig::SEED_PREFIX],
+        bump,
+        constraint = !GlobalConfig::is_paused_account(&global_config)
+            @ PrivacyErrorV2::GlobalPaused,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
     /// Merkle tree account
     #[account(mut)]
     pub merkle_tree: Box<Account<'info, MerkleTreeV2>>,
@@ -34,33 +93,102 @@ pub struct BatchProcessDeposits<'info> {
             PendingDepositsBuffer::SEED_PREFIX,
             pool_config.key().as_ref(),
         ],
-        bump = pending_buffer.bump,
-        constraint = pending_buffer.pool == pool_config.key() @ PrivacyErrorV2::InvalidPoolReference,
+        bump = PendingDepositsBuffer::checked_bump(&pending_buffer)?,
+        constraint = pending_buffer.load()?.pool == pool_config.key() @ PrivacyErrorV2::InvalidPoolReference,
+    )]
+    pub pending_buffer: AccountLoader<'info, PendingDepositsBuffer>,
+
+    /// Optional: role of a bonded batcher (not needed for the authority)
+    #[account(
+        mut,
+        seeds = [BatcherRole::SEED_PREFIX, pool_config.key().as_ref(), batcher.key().as_ref()],
+        bump = batcher_role.bump,
+    )]
+    pub batcher_role: Option<Account<'info, BatcherRole>>,
+
+    /// Optional: the pool's deposit hook (required while one is registered)
+    #[account(
+        mut,
+        seeds = [DepositHook::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = deposit_hook.bump,
     )]
-    pub pending_buffer: Box<Account<'info, PendingDepositsBuffer>>,
+    pub deposit_hook: Option<Account<'info, DepositHook>>,
+
+    /// CHECK: Must be the deposit hook's callback program (checked in the
+    /// handler)
+    pub callback_program: Option<UncheckedAccount<'info>>,
+    // Remaining accounts are forwarded to the deposit hook callback
 }
 
-/// Handler for batch_process_deposits instruction (authority-only)
-pub fn handler(ctx: Context<BatchProcessDeposits>, max_to_process: u16) -> Result<()> {
+/// Handler for batch_process_deposits instruction
+///
+/// When `target_cu_budget` is set, the batch is trimmed to the number of
+/// insertions estimated to fit in that budget. Returns the number of
+/// deposits actually processed (exposed to the caller as return data).
+///
+/// When `expected_batch_seq` is set, the call fails unless it matches the
+/// buffer's current `batch_seq`.
+///
+/// With a deposit hook registered, its callback program is called once per
+/// inserted commitment after the batch is applied (see
+/// `state::deposit_hook`).
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BatchProcessDeposits<'info>>,
+    max_to_process: u16,
+    target_cu_budget: Option<u32>,
+    expected_batch_seq: Option<u64>,
+) -> Result<u16> {
     cu("batch: start");
 
     let pool_config = &mut ctx.accounts.pool_config;
     let merkle_tree = &mut ctx.accounts.merkle_tree;
-    let pending_buffer = &mut ctx.accounts.pending_buffer;
+    let mut pending_buffer = ctx.accounts.pending_buffer.load_mut()?;
     let batcher = ctx.accounts.batcher.key();
 
-    let clock = Clock::get()?;
+    let clock = pool_clock(pool_config)?;
     let timestamp = clock.unix_timestamp;
 
     // =========================================================================
-    // 1. AUTHORIZATION CHECK (AUTHORITY ONLY)
+    // 1. AUTHORIZATION CHECK (AUTHORITY OR BONDED BATCHER)
     // =========================================================================
-    require_keys_eq!(batcher, pool_config.authority, PrivacyErrorV2::Unauthorized);
+    if batcher != pool_config.authority {
+        let batcher_role = ctx
+            .accounts
+            .batcher_role
+            .as_ref()
+            .ok_or(error!(PrivacyErrorV2::Unauthorized))?;
+        require!(batcher_role.can_batch(), PrivacyErrorV2::BatcherNotBonded);
+    }
     cu("batch: after auth");
 
+    // Deposit hook: required while registered, and only with its program
+    require!(
+        !pool_config.deposit_hook_enabled || ctx.accounts.deposit_hook.is_some(),
+        PrivacyErrorV2::DepositHookRequired
+    );
+    if let Some(hook) = ctx.accounts.deposit_hook.as_ref() {
+        let program = ctx
+            .accounts
+            .callback_program
+            .as_ref()
+            .ok_or(error!(PrivacyErrorV2::DepositHookRequired))?;
+        require_keys_eq!(
+            program.key(),
+            hook.callback_program,
+            PrivacyErrorV2::DepositHookProgramMismatch
+        );
+        require_remaining_accounts_within(
+            "batch_process_deposits",
+            ctx.remaining_accounts.len(),
+            MAX_DEPOSIT_HOOK_REMAINING_ACCOUNTS,
+        )?;
+    }
+
     // =========================================================================
     // 2. VALIDATE BATCH PARAMETERS
     // =========================================================================
+    pending_buffer.check_batch_seq(expected_batch_seq)?;
+
     require!(
         !pending_buffer.is_empty(),
         PrivacyErrorV2::NoPendingDeposits
@@ -82,7 +210,14 @@ pub fn handler(ctx: Context<BatchProcessDeposits>, max_to_process: u16) -> Resul
     // =========================================================================
     // 3. VALIDATE MERKLE TREE CAPACITY
     // =========================================================================
-    let to_process = std::cmp::min(max_to_process as usize, pending_buffer.size());
+    let mut to_process = std::cmp::min(max_to_process as usize, pending_buffer.size());
+
+    // Trim to the caller's compute budget instead of failing the whole tx
+    if let Some(budget) = target_cu_budget {
+        let fit = max_inserts_for_budget(budget, merkle_tree.depth) as usize;
+        require!(fit > 0, PrivacyErrorV2::InvalidBatchSize);
+        to_process = std::cmp::min(to_process, fit);
+    }
 
     let tree_capacity = merkle_tree.capacity();
     let tree_used = merkle_tree.next_leaf_index as usize;
@@ -96,13 +231,16 @@ pub fn handler(ctx: Context<BatchProcessDeposits>, max_to_process: u16) -> Resul
     // 4. PROCESS DEPOSITS
     // =========================================================================
     cu("batch: before prepare_batch");
-    let deposits_to_process = pending_buffer.prepare_batch(max_to_process);
+    let deposits_to_process = pending_buffer.prepare_batch(to_process as u16);
     cu("batch: after prepare_batch");
 
     let actual_count = deposits_to_process.len();
     require!(actual_count > 0, PrivacyErrorV2::NoPendingDeposits);
 
     let start_leaf_index = merkle_tree.next_leaf_index;
+    let batch_seq = pending_buffer.batch_seq;
+    let pool_key = pool_config.key();
+    let mut finalized = Vec::new();
 
     // Insert each commitment into Merkle tree
     cu("batch: insert_leaf loop start");
@@ -113,8 +251,19 @@ pub fn handler(ctx: Context<BatchProcessDeposits>, max_to_process: u16) -> Resul
         );
 
         cu("batch: before insert_leaf");
-        merkle_tree.insert_leaf(deposit.commitment, deposit.timestamp)?;
+        let leaf_index = merkle_tree.insert_leaf(deposit.commitment, deposit.timestamp)?;
         cu("batch: after insert_leaf");
+
+        if ctx.accounts.deposit_hook.is_some() {
+            finalized.push(DepositFinalized {
+                pool: pool_key,
+                commitment: deposit.commitment,
+                leaf_index,
+                merkle_root: merkle_tree.get_current_root(),
+                batch_seq,
+                timestamp,
+            });
+        }
     }
 
     let end_leaf_index = merkle_tree.next_leaf_index - 1;
@@ -124,32 +273,102 @@ pub fn handler(ctx: Context<BatchProcessDeposits>, max_to_process: u16) -> Resul
     // 5. UPDATE BUFFER
     // =========================================================================
     pending_buffer.clear_processed(actual_count as u32, timestamp)?;
+    let crank_bounty =
+        pending_buffer.release_bounty(pool_config.crank_bounty_for(actual_count as u32));
+    // Release the buffer's data borrow before the hook CPIs below
+    drop(pending_buffer);
 
     // =========================================================================
-    // 6. UPDATE POOL STATISTICS
+    // 6. UPDATE POOL STATISTICS, PAY THE CRANK BOUNTY
     // =========================================================================
     pool_config.record_batch(actual_count as u32, timestamp)?;
+    if let Some(batcher_role) = ctx.accounts.batcher_role.as_mut() {
+        batcher_role.record_batch(actual_count as u32, timestamp)?;
+    }
+    let crank_bounty_paid = pay_crank_bounty(
+        &ctx.accounts.pending_buffer.to_account_info(),
+        &ctx.accounts.batcher.to_account_info(),
+        crank_bounty,
+    )?;
+
+    let fill_percentage = merkle_tree.fill_percentage();
+    if let Some((level, threshold_pct)) = pool_config.update_tree_capacity_level(fill_percentage) {
+        emit!(TreeCapacityWarning {
+            pool: pool_config.key(),
+            merkle_tree: merkle_tree.key(),
+            level,
+            threshold_pct,
+            fill_percentage,
+            leaves_used: merkle_tree.next_leaf_index,
+            capacity: merkle_tree.capacity(),
+            timestamp,
+        });
+        msg!("Tree capacity warning: {}% full", fill_percentage);
+    }
+
+    // Intermediate roots are valid withdrawal roots too, but bridges only
+    // need the one the batch ends on
+    if let Some(sequence) = pool_config.next_root_event_sequence()? {
+        emit!(RootUpdatedEvent {
+            pool: pool_key,
+            root: final_merkle_root,
+            leaf_count: merkle_tree.next_leaf_index,
+            slot: clock.slot,
+            sequence,
+        });
+    }
 
     // =========================================================================
     // 7. EMIT BATCH EVENT
     // =========================================================================
     emit!(BatchProcessedEvent {
         pool: ctx.accounts.pool_config.key(),
+        batch_seq,
         deposits_processed: actual_count as u16,
         first_leaf_index: start_leaf_index,
         last_leaf_index: end_leaf_index,
         new_merkle_root: final_merkle_root,
         timestamp: clock.unix_timestamp,
+        crank_bounty_paid,
     });
 
     msg!(
-        "Batch processed: {} deposits (indices {}-{})",
+        "Batch {} processed: {} deposits (indices {}-{})",
+        batch_seq,
         actual_count,
         start_leaf_index,
         end_leaf_index
     );
 
-    Ok(())
+    // =========================================================================
+    // 8. DEPOSIT HOOK CALLBACKS
+    // =========================================================================
+    if let (Some(hook), Some(program)) = (
+        ctx.accounts.deposit_hook.as_mut(),
+        ctx.accounts.callback_program.as_ref(),
+    ) {
+        let bump = [hook.bump];
+        let seeds: &[&[u8]] = &[DepositHook::SEED_PREFIX, pool_key.as_ref(), &bump];
+
+        let mut infos = vec![
+            hook.to_account_info(),
+            ctx.accounts.pool_config.to_account_info(),
+            program.to_account_info(),
+        ];
+        infos.extend_from_slice(ctx.remaining_accounts);
+
+        for deposit in &finalized {
+            let ix = hook.callback_instruction(hook.key(), deposit, ctx.remaining_accounts)?;
+            invoke_signed(&ix, &infos, &[seeds])?;
+        }
+        hook.callbacks_invoked = hook
+            .callbacks_invoked
+            .checked_add(finalized.len() as u64)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        cu("batch: after deposit hook callbacks");
+    }
+
+    Ok(actual_count as u16)
 }
 
 #[cfg(test)]
@@ -165,4 +384,20 @@ mod tests {
         let batch_cu = MAX_BATCH_SIZE as u32 * CU_PER_INSERTION;
         assert!(batch_cu + OVERHEAD_CU <= SOLANA_CU_LIMIT);
     }
+
+    #[test]
+    fn test_max_inserts_for_budget() {
+        // Depth 20: 22_000 CU per insert
+        assert_eq!(estimated_cu_per_insert(20), 22_000);
+        assert_eq!(max_inserts_for_budget(200_000, 20), 7);
+
+        // Budget below fixed overhead fits nothing
+        assert_eq!(max_inserts_for_budget(ESTIMATED_CU_OVERHEAD, 20), 0);
+
+        // Never exceeds MAX_BATCH_SIZE
+        assert_eq!(max_inserts_for_budget(u32::MAX, 4), MAX_BATCH_SIZE);
+
+        // Shallower trees fit more insertions
+        assert!(max_inserts_for_budget(400_000, 10) > max_inserts_for_budget(400_000, 24));
+    }
 }
@@ -0,0 +1,207 @@
+//! Batcher Bond Instructions
+//!
+//! Designated batchers are approved by the pool authority and lock a bond in
+//! their `BatcherRole` PDA. The authority slashes the bond to the pool's
+//! insurance fund on reported misbehavior; otherwise the batcher can leave
+//! and reclaim it after the unbonding period.
+//!
+//! The on-chain batch path is FIFO, so reordering or dropping commitments
+//! can only happen in what a batcher does off-chain (e.g. withholding
+//! deposits from the sequencer, or publishing inconsistent batches). The
+//! program cannot check that evidence; the report records its hash in
+//! `BatcherSlashed` so anyone can audit the decision.
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+
+use crate::error::PrivacyErrorV2;
+use crate::events::{BatcherRegistered, BatcherSlashed};
+use crate::state::{BatcherRole, PoolConfigV2, INSURANCE_FUND_SEED};
+use crate::utils::pool_clock;
+
+/// Accounts for registering a bonded batcher
+#[derive(Accounts)]
+pub struct RegisterBatcher<'info> {
+    /// Pool authority approving the batcher
+    pub authority: Signer<'info>,
+
+    /// Batcher (pays rent and the bond)
+    #[account(mut)]
+    pub batcher: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Batcher role PDA
+    #[account(
+        init,
+        payer = batcher,
+        space = BatcherRole::LEN,
+        seeds = [BatcherRole::SEED_PREFIX, pool_config.key().as_ref(), batcher.key().as_ref()],
+        bump,
+    )]
+    pub batcher_role: Account<'info, BatcherRole>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for a batcher leaving (request exit / withdraw bond)
+#[derive(Accounts)]
+pub struct ExitBatcher<'info> {
+    /// Batcher (receives the bond and rent on withdrawal)
+    #[account(mut)]
+    pub batcher: Signer<'info>,
+
+    /// Pool configuration account
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Batcher role PDA
+    #[account(
+        mut,
+        seeds = [BatcherRole::SEED_PREFIX, pool_config.key().as_ref(), batcher.key().as_ref()],
+        bump = batcher_role.bump,
+    )]
+    pub batcher_role: Account<'info, BatcherRole>,
+}
+
+/// Accounts for reporting batcher misbehavior
+#[derive(Accounts)]
+pub struct ReportBatcherMisbehavior<'info> {
+    /// Pool authority
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Batcher role PDA of the reported batcher
+    #[account(
+        mut,
+        seeds = [
+            BatcherRole::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            batcher_role.batcher.as_ref(),
+        ],
+        bump = batcher_role.bump,
+    )]
+    pub batcher_role: Account<'info, BatcherRole>,
+
+    /// Pool insurance fund (system account PDA) receiving the bond
+    #[account(
+        mut,
+        seeds = [INSURANCE_FUND_SEED, pool_config.key().as_ref()],
+        bump,
+    )]
+    pub insurance_fund: SystemAccount<'info>,
+}
+
+/// Handler for register_batcher instruction
+pub fn register_handler(ctx: Context<RegisterBatcher>, bond_lamports: u64) -> Result<()> {
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+    let pool_key = ctx.accounts.pool_config.key();
+    let batcher = ctx.accounts.batcher.key();
+
+    ctx.accounts.batcher_role.initialize(
+        pool_key,
+        batcher,
+        bond_lamports,
+        ctx.bumps.batcher_role,
+        timestamp,
+    )?;
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.batcher.to_account_info(),
+                to: ctx.accounts.batcher_role.to_account_info(),
+            },
+        ),
+        bond_lamports,
+    )?;
+
+    emit!(BatcherRegistered {
+        pool: pool_key,
+        batcher,
+        bond_lamports,
+        timestamp,
+    });
+
+    msg!(
+        "Batcher {} registered with {} lamports bond",
+        batcher,
+        bond_lamports
+    );
+    Ok(())
+}
+
+/// Handler for request_batcher_exit instruction
+///
+/// Stops batching immediately; the bond unlocks after
+/// `BATCHER_UNBONDING_SECONDS`.
+pub fn request_exit_handler(ctx: Context<ExitBatcher>) -> Result<()> {
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+    ctx.accounts.batcher_role.request_exit(timestamp);
+
+    msg!("Batcher exit requested");
+    Ok(())
+}
+
+/// Handler for withdraw_batcher_bond instruction
+///
+/// Closes the role, returning the remaining bond and rent to the batcher.
+pub fn withdraw_bond_handler(ctx: Context<ExitBatcher>) -> Result<()> {
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+    ctx.accounts.batcher_role.require_unbonded(timestamp)?;
+
+    let bond = ctx.accounts.batcher_role.bond_lamports;
+    ctx.accounts
+        .batcher_role
+        .close(ctx.accounts.batcher.to_account_info())?;
+
+    msg!("Batcher bond withdrawn: {} lamports", bond);
+    Ok(())
+}
+
+/// Handler for report_batcher_misbehavior instruction
+pub fn report_handler(
+    ctx: Context<ReportBatcherMisbehavior>,
+    evidence_hash: [u8; 32],
+) -> Result<()> {
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+    let amount = ctx.accounts.batcher_role.slash(timestamp)?;
+
+    // Role is program-owned, so lamports can be moved directly
+    if amount > 0 {
+        **ctx
+            .accounts
+            .batcher_role
+            .to_account_info()
+            .try_borrow_mut_lamports()? -= amount;
+        **ctx
+            .accounts
+            .insurance_fund
+            .to_account_info()
+            .try_borrow_mut_lamports()? += amount;
+    }
+
+    emit!(BatcherSlashed {
+        pool: ctx.accounts.pool_config.key(),
+        batcher: ctx.accounts.batcher_role.batcher,
+        amount,
+        evidence_hash,
+        timestamp,
+    });
+
+    msg!(
+        "Batcher {} slashed: {} lamports to insurance fund",
+        ctx.accounts.batcher_role.batcher,
+        amount
+    );
+    Ok(())
+}
@@ -0,0 +1,281 @@
+//! Clone Pool Instructions
+//!
+//! - `clone_pool_config`: creates a new pool of the same authority (pool
+//!   config, Merkle tree, relayer registry and compliance config) that
+//!   starts with the source pool's settings
+//! - `clone_asset_config`: registers one of the source pool's assets in the
+//!   new pool with the source vault's configuration
+//!
+//! Operators launching a new tree generation thus replay one instruction
+//! per asset instead of every configuration instruction. Carried over:
+//! feature flags and pool limits (`PoolConfigV2::inherit_settings`),
+//! relayer fee bounds and registration policy, compliance settings, and
+//! per-asset limits, fee bounds and metadata. Not carried over: the tree
+//! (fresh, same depth), vault balances, relayers, verification keys and the
+//! deposit hook. The pending deposits buffer is created as for any pool
+//! with `initialize_pending_deposits_buffer`.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::error::PrivacyErrorV2;
+use crate::events::{PoolConfigCloned, PoolInitializedV2};
+use crate::instructions::register_asset::{initialize_asset, initialize_native_sol_asset};
+use crate::state::{
+    AssetVault, ComplianceConfig, MerkleTreeV2, PoolConfigV2, RelayerRegistry,
+    MIN_ROOT_HISTORY_SIZE,
+};
+
+/// Accounts for creating a pool from another pool's configuration
+#[derive(Accounts)]
+#[instruction(pool_id: u64, root_history_size: u16)]
+pub struct ClonePoolConfig<'info> {
+    /// Authority of both pools (pays for the new accounts)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool whose configuration is copied
+    #[account(has_one = authority @ PrivacyErrorV2::Unauthorized)]
+    pub source_pool_config: Box<Account<'info, PoolConfigV2>>,
+
+    /// Source pool's relayer registry
+    #[account(address = source_pool_config.relayer_registry @ PrivacyErrorV2::InvalidPoolReference)]
+    pub source_relayer_registry: Box<Account<'info, RelayerRegistry>>,
+
+    /// Source pool's compliance config
+    #[account(address = source_pool_config.compliance_config @ PrivacyErrorV2::InvalidPoolReference)]
+    pub source_compliance_config: Box<Account<'info, ComplianceConfig>>,
+
+    /// New pool config (PDA)
+    #[account(
+        init,
+        payer = authority,
+        space = PoolConfigV2::LEN,
+        seeds = [
+            PoolConfigV2::SEED_PREFIX,
+            authority.key().as_ref(),
+            pool_id.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool_config: Box<Account<'info, PoolConfigV2>>,
+
+    /// New pool's Merkle tree, as deep as the source pool's
+    #[account(
+        init,
+        payer = authority,
+        space = MerkleTreeV2::space(source_pool_config.tree_depth, root_history_size),
+        seeds = [MerkleTreeV2::SEED_PREFIX, pool_config.key().as_ref()],
+        bump,
+    )]
+    pub merkle_tree: Box<Account<'info, MerkleTreeV2>>,
+
+    /// New pool's relayer registry
+    #[account(
+        init,
+        payer = authority,
+        space = RelayerRegistry::LEN,
+        seeds = [RelayerRegistry::SEED_PREFIX, pool_config.key().as_ref()],
+        bump,
+    )]
+    pub relayer_registry: Box<Account<'info, RelayerRegistry>>,
+
+    /// New pool's compliance config
+    #[account(
+        init,
+        payer = authority,
+        space = ComplianceConfig::LEN,
+        seeds = [ComplianceConfig::SEED_PREFIX, pool_config.key().as_ref()],
+        bump,
+    )]
+    pub compliance_config: Box<Account<'info, ComplianceConfig>>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for registering a source pool's asset in a cloned pool
+///
+/// `mint` and `vault_token_account` are given for token assets and left
+/// out for native SOL.
+#[derive(Accounts)]
+#[instruction(asset_id: [u8; 32])]
+pub struct CloneAssetConfig<'info> {
+    /// Authority of both pools (pays for the new accounts)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool the asset is copied from
+    #[account(has_one = authority @ PrivacyErrorV2::Unauthorized)]
+    pub source_pool_config: Box<Account<'info, PoolConfigV2>>,
+
+    /// Source pool's vault of the asset
+    #[account(
+        seeds = [
+            AssetVault::SEED_PREFIX,
+            source_pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        bump = source_asset_vault.bump,
+    )]
+    pub source_asset_vault: Box<Account<'info, AssetVault>>,
+
+    /// Pool the asset is registered in
+    #[account(
+        mut,
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+        constraint = !pool_config.is_paused @ PrivacyErrorV2::PoolPaused,
+    )]
+    pub pool_config: Box<Account<'info, PoolConfigV2>>,
+
+    /// Token mint of the asset (token assets only)
+    #[account(address = source_asset_vault.mint @ PrivacyErrorV2::InvalidMint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// New asset vault (PDA)
+    #[account(
+        init,
+        payer = authority,
+        space = AssetVault::DEFAULT_SPACE,
+        seeds = [
+            AssetVault::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        bump,
+    )]
+    pub asset_vault: Box<Account<'info, AssetVault>>,
+
+    /// Token account of the new vault (token assets only)
+    #[account(
+        init,
+        payer = authority,
+        token::mint = mint,
+        token::authority = asset_vault,
+        token::token_program = token_program,
+        seeds = [
+            b"vault_token",
+            asset_vault.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub vault_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token program owning the mint
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Handler for clone_pool_config instruction
+pub fn handler(ctx: Context<ClonePoolConfig>, pool_id: u64, root_history_size: u16) -> Result<()> {
+    require!(
+        root_history_size >= MIN_ROOT_HISTORY_SIZE,
+        PrivacyErrorV2::InvalidRootHistorySize
+    );
+
+    let timestamp = Clock::get()?.unix_timestamp;
+    let accounts = ctx.accounts;
+    let source = &accounts.source_pool_config;
+    let pool_key = accounts.pool_config.key();
+    let tree_depth = source.tree_depth;
+
+    let pool_config = &mut accounts.pool_config;
+    pool_config.initialize_partial(
+        accounts.authority.key(),
+        pool_id,
+        accounts.merkle_tree.key(),
+        tree_depth,
+        ctx.bumps.pool_config,
+        timestamp,
+    );
+    pool_config.set_registries(
+        accounts.relayer_registry.key(),
+        accounts.compliance_config.key(),
+        accounts.authority.key(),
+    );
+    pool_config.inherit_settings(source);
+
+    accounts
+        .merkle_tree
+        .initialize(pool_key, tree_depth, root_history_size)?;
+
+    let relayer_registry = &mut accounts.relayer_registry;
+    relayer_registry.initialize(pool_key, ctx.bumps.relayer_registry, timestamp);
+    relayer_registry.inherit_settings(&accounts.source_relayer_registry);
+
+    let compliance_config = &mut accounts.compliance_config;
+    compliance_config.initialize(pool_key, ctx.bumps.compliance_config, timestamp);
+    compliance_config.inherit_settings(&accounts.source_compliance_config);
+
+    emit!(PoolInitializedV2 {
+        pool: pool_key,
+        authority: accounts.authority.key(),
+        pool_id,
+        merkle_tree: accounts.merkle_tree.key(),
+        relayer_registry: accounts.relayer_registry.key(),
+        tree_depth,
+        root_history_size,
+        compliance_recovery_enabled: accounts.pool_config.compliance_recovery_enabled,
+        poseidon_params_hash: accounts.merkle_tree.poseidon_params_hash,
+        timestamp,
+    });
+
+    emit!(PoolConfigCloned {
+        source_pool: accounts.source_pool_config.key(),
+        pool: pool_key,
+        authority: accounts.authority.key(),
+        pool_id,
+        timestamp,
+    });
+
+    msg!(
+        "Cloned pSOL v2 pool {} as id={}",
+        accounts.source_pool_config.key(),
+        pool_id
+    );
+
+    Ok(())
+}
+
+/// Handler for clone_asset_config instruction
+pub fn asset_handler(ctx: Context<CloneAssetConfig>, asset_id: [u8; 32]) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+    let accounts = ctx.accounts;
+    let source = &accounts.source_asset_vault;
+
+    if source.is_native_sol() {
+        require!(
+            accounts.mint.is_none() && accounts.vault_token_account.is_none(),
+            PrivacyErrorV2::InvalidVaultTokenAccount
+        );
+        initialize_native_sol_asset(
+            &mut accounts.pool_config,
+            &mut accounts.asset_vault,
+            ctx.bumps.asset_vault,
+            timestamp,
+        )?;
+    } else {
+        let (Some(mint), Some(vault_token_account)) =
+            (&accounts.mint, &accounts.vault_token_account)
+        else {
+            return err!(PrivacyErrorV2::MissingAssetAccount);
+        };
+        initialize_asset(
+            &mut accounts.pool_config,
+            &mut accounts.asset_vault,
+            &mint.to_account_info(),
+            mint.decimals,
+            vault_token_account.key(),
+            ctx.bumps.asset_vault,
+            asset_id,
+            None,
+            timestamp,
+        )?;
+    }
+
+    accounts.asset_vault.inherit_settings(source);
+
+    Ok(())
+}
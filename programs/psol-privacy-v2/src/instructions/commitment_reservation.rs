@@ -0,0 +1,115 @@
+//! Commitment Reservation Instructions - pSOL v2
+//!
+//! Optional first step of a deposit: `reserve_commitment` binds a
+//! commitment to the depositor's wallet for `COMMITMENT_RESERVATION_SLOTS`
+//! (see `state::commitment_reservation`), and the wallet's `deposit_masp`
+//! or `deposit_masp_batch` of the commitment consumes it. A reservation
+//! that is no longer needed is released by its depositor, which ends it
+//! early but keeps the account: a commitment is reserved at most once.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::events::{CommitmentReservationCreated, CommitmentReservationReleased};
+use crate::state::{CommitmentReservation, PoolConfigV2};
+use crate::utils::pool_clock;
+
+/// Accounts for reserving a commitment
+#[derive(Accounts)]
+#[instruction(commitment: [u8; 32])]
+pub struct ReserveCommitment<'info> {
+    /// Wallet that will deposit the commitment (pays the reservation rent)
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        constraint = !pool_config.is_paused @ PrivacyErrorV2::PoolPaused,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Reservation (PDA, created here; fails if the commitment was ever
+    /// reserved and its reservation not consumed by a deposit)
+    #[account(
+        init,
+        payer = depositor,
+        space = CommitmentReservation::LEN,
+        seeds = [
+            CommitmentReservation::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            commitment.as_ref(),
+        ],
+        bump,
+    )]
+    pub commitment_reservation: Account<'info, CommitmentReservation>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Handler for reserve_commitment instruction
+pub fn reserve_handler(ctx: Context<ReserveCommitment>, commitment: [u8; 32]) -> Result<()> {
+    require!(
+        !crate::crypto::is_zero_hash(&commitment),
+        PrivacyErrorV2::InvalidCommitment
+    );
+
+    let clock = pool_clock(&ctx.accounts.pool_config)?;
+    let reservation = &mut ctx.accounts.commitment_reservation;
+    reservation.initialize(
+        ctx.accounts.pool_config.key(),
+        ctx.accounts.depositor.key(),
+        commitment,
+        clock.slot,
+        ctx.bumps.commitment_reservation,
+    );
+
+    emit!(CommitmentReservationCreated {
+        pool: reservation.pool,
+        commitment_reservation: reservation.key(),
+        commitment,
+        expires_slot: reservation.expires_slot,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Accounts for releasing a commitment reservation
+#[derive(Accounts)]
+pub struct ReleaseCommitmentReservation<'info> {
+    /// Wallet that reserved the commitment
+    pub depositor: Signer<'info>,
+
+    /// Pool configuration account
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Reservation, ended but kept until the depositor deposits the
+    /// commitment (which returns the rent)
+    #[account(
+        mut,
+        has_one = depositor @ PrivacyErrorV2::Unauthorized,
+        seeds = [
+            CommitmentReservation::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            commitment_reservation.commitment.as_ref(),
+        ],
+        bump = commitment_reservation.bump,
+    )]
+    pub commitment_reservation: Account<'info, CommitmentReservation>,
+}
+
+/// Handler for release_commitment_reservation instruction
+pub fn release_handler(ctx: Context<ReleaseCommitmentReservation>) -> Result<()> {
+    let clock = pool_clock(&ctx.accounts.pool_config)?;
+    let reservation = &mut ctx.accounts.commitment_reservation;
+    reservation.release(clock.slot);
+
+    emit!(CommitmentReservationReleased {
+        pool: reservation.pool,
+        commitment_reservation: reservation.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -7,6 +7,7 @@ use anchor_lang::prelude::*;
 use crate::error::PrivacyErrorV2;
 use crate::events::AuditMetadataAttached;
 use crate::state::{AuditMetadata, ComplianceConfig, PoolConfigV2, MAX_ENCRYPTED_METADATA_LEN};
+use crate::utils::pool_clock;
 
 /// Accounts for attaching audit metadata
 #[derive(Accounts)]
@@ -66,7 +67,7 @@ pub fn handler(
         PrivacyErrorV2::InvalidCommitment
     );
 
-    let clock = Clock::get()?;
+    let clock = pool_clock(&ctx.accounts.pool_config)?;
     let timestamp = clock.unix_timestamp;
 
     let schema_version = ctx.accounts.compliance_config.metadata_schema_version;
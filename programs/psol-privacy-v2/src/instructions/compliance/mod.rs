@@ -3,9 +3,12 @@
 //! Compliance layer for regulatory requirements:
 //! - Configure compliance settings
 //! - Attach encrypted audit metadata to commitments
+//! - Recover a disclosed note's funds to a custodian (opt-in per pool)
 
 pub mod attach_metadata;
 pub mod configure_compliance;
+pub mod recovery_withdraw;
 
 pub use attach_metadata::AttachAuditMetadata;
 pub use configure_compliance::ConfigureCompliance;
+pub use recovery_withdraw::{ComplianceRecoveryWithdraw, DisclosedNote};
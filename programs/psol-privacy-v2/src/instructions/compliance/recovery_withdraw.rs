@@ -0,0 +1,288 @@
+//! Compliance Recovery Withdraw Instruction
+//!
+//! Moves a note's funds to a custodian under a court order, for notes whose
+//! owner has lost them or may not withdraw them. Only pools created with
+//! `compliance_recovery` set allow it (`PoolConfigV2::
+//! compliance_recovery_enabled`, fixed at creation), so depositors know
+//! before depositing whether their notes can be recovered this way.
+//!
+//! The authority and the auditor both sign. The auditor decrypts the note's
+//! `AuditMetadata` and discloses its opening (secret, nullifier, amount,
+//! leaf index and Merkle path); the program checks that the opening hashes
+//! to the commitment the metadata is attached to and that the commitment is
+//! in the tree, then records the note's nullifier as spent so the owner can
+//! no longer withdraw it. `ComplianceRecoveryWithdrawn` records the court
+//! order reference, both signers and the custodian.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::crypto::{compute_nullifier_hash, is_zero_hash, verify_commitment};
+use crate::error::PrivacyErrorV2;
+use crate::events::ComplianceRecoveryWithdrawn;
+use crate::state::{
+    AssetVault, AuditMetadata, ComplianceConfig, MerkleTreeV2, PoolConfigV2, SpendContext,
+    SpentNullifierV2,
+};
+use crate::utils::pool_clock;
+
+/// Opening of a disclosed note (by the auditor for a recovery, or by the
+/// relayer of a `disclosed_path_withdraw`)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DisclosedNote {
+    pub secret: [u8; 32],
+    pub nullifier: [u8; 32],
+    pub amount: u64,
+    pub leaf_index: u32,
+    /// Sibling hashes from the leaf up, one per tree level
+    pub merkle_path: Vec<[u8; 32]>,
+}
+
+/// Accounts for moving a disclosed note's funds to a custodian
+#[derive(Accounts)]
+#[instruction(commitment: [u8; 32], asset_id: [u8; 32], nullifier_hash: [u8; 32])]
+pub struct ComplianceRecoveryWithdraw<'info> {
+    /// Pool authority (must be signer; pays the nullifier rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool auditor (must co-sign)
+    pub auditor: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        mut,
+        constraint = !pool_config.is_paused @ PrivacyErrorV2::PoolPaused,
+        constraint = pool_config.compliance_recovery_enabled
+            @ PrivacyErrorV2::ComplianceRecoveryDisabled,
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+        has_one = compliance_config,
+        has_one = merkle_tree,
+    )]
+    pub pool_config: Box<Account<'info, PoolConfigV2>>,
+
+    /// Compliance configuration account
+    #[account(
+        constraint = compliance_config.get_audit_pubkey() == Some(auditor.key())
+            @ PrivacyErrorV2::AuditorMismatch,
+    )]
+    pub compliance_config: Box<Account<'info, ComplianceConfig>>,
+
+    /// Merkle tree account
+    pub merkle_tree: Box<Account<'info, MerkleTreeV2>>,
+
+    /// Audit metadata of the recovered note (must already be attached)
+    #[account(
+        seeds = [
+            AuditMetadata::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            commitment.as_ref(),
+        ],
+        bump = audit_metadata.bump,
+    )]
+    pub audit_metadata: Box<Account<'info, AuditMetadata>>,
+
+    /// Asset vault account
+    #[account(
+        mut,
+        seeds = [
+            AssetVault::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        bump = asset_vault.bump,
+    )]
+    pub asset_vault: Box<Account<'info, AssetVault>>,
+
+    /// Vault's token account (source; SPL assets only)
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == asset_vault.token_account
+            @ PrivacyErrorV2::InvalidVaultTokenAccount,
+    )]
+    pub vault_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Custodian receiving the funds
+    /// CHECK: Only credited (native SOL) or checked as the owner of
+    /// `custodian_token_account`
+    #[account(mut)]
+    pub custodian: UncheckedAccount<'info>,
+
+    /// Custodian's token account (destination; SPL assets only)
+    #[account(
+        mut,
+        constraint = custodian_token_account.mint == asset_vault.mint @ PrivacyErrorV2::InvalidMint,
+        constraint = custodian_token_account.owner == custodian.key()
+            @ PrivacyErrorV2::RecipientMismatch,
+    )]
+    pub custodian_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Asset mint (SPL assets only)
+    #[account(address = asset_vault.mint @ PrivacyErrorV2::InvalidMint)]
+    pub mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    /// Spent nullifier account of the recovered note (PDA, created here)
+    #[account(
+        init,
+        payer = authority,
+        space = SpentNullifierV2::LEN,
+        seeds = [
+            SpentNullifierV2::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            nullifier_hash.as_ref(),
+        ],
+        bump,
+    )]
+    pub spent_nullifier: Box<Account<'info, SpentNullifierV2>>,
+
+    /// Token program owning the mint (SPL assets only)
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Handler for compliance_recovery_withdraw instruction
+pub fn handler(
+    ctx: Context<ComplianceRecoveryWithdraw>,
+    commitment: [u8; 32],
+    asset_id: [u8; 32],
+    nullifier_hash: [u8; 32],
+    court_order_hash: [u8; 32],
+    note: DisclosedNote,
+) -> Result<()> {
+    require!(
+        !is_zero_hash(&court_order_hash),
+        PrivacyErrorV2::CourtOrderRequired
+    );
+    require!(note.amount > 0, PrivacyErrorV2::InvalidAmount);
+
+    // The disclosure must open the commitment the metadata is attached to,
+    // and name the nullifier its owner would spend it with
+    require!(
+        verify_commitment(
+            &commitment,
+            &note.secret,
+            &note.nullifier,
+            note.amount,
+            &asset_id,
+        )?,
+        PrivacyErrorV2::DisclosureMismatch
+    );
+    require!(
+        compute_nullifier_hash(&note.nullifier, &note.secret, note.leaf_index)? == nullifier_hash,
+        PrivacyErrorV2::DisclosureMismatch
+    );
+
+    // ... and the commitment must be in the tree
+    let root =
+        ctx.accounts
+            .merkle_tree
+            .root_from_path(commitment, note.leaf_index, &note.merkle_path)?;
+    require!(
+        ctx.accounts.merkle_tree.is_known_root(&root),
+        PrivacyErrorV2::InvalidMerkleRoot
+    );
+
+    let clock = pool_clock(&ctx.accounts.pool_config)?;
+    let timestamp = clock.unix_timestamp;
+
+    // The owner can no longer withdraw the note
+    ctx.accounts.spent_nullifier.initialize(
+        ctx.accounts.pool_config.key(),
+        nullifier_hash,
+        asset_id,
+        SpendContext::ComplianceRecovery,
+        timestamp,
+        clock.slot,
+        ctx.accounts.authority.key(),
+        ctx.bumps.spent_nullifier,
+    );
+
+    let amount = note.amount;
+    if ctx.accounts.asset_vault.is_native_sol() {
+        AssetVault::pay_native(
+            &ctx.accounts.asset_vault.to_account_info(),
+            &ctx.accounts.custodian.to_account_info(),
+            amount,
+        )?;
+    } else {
+        pay_tokens(ctx.accounts, &asset_id, amount)?;
+    }
+
+    let coarse_stats = ctx.accounts.pool_config.has_coarse_liquidity_stats();
+    ctx.accounts
+        .asset_vault
+        .record_withdrawal(amount, timestamp, coarse_stats)?;
+    if let Some(vault_token_account) = ctx.accounts.vault_token_account.as_mut() {
+        vault_token_account.reload()?;
+        ctx.accounts
+            .asset_vault
+            .reconcile(vault_token_account.amount)?;
+    }
+
+    let pool_config = &mut ctx.accounts.pool_config;
+    pool_config.record_withdrawal(timestamp)?;
+    let sequence = pool_config.next_event_sequence()?;
+
+    emit!(ComplianceRecoveryWithdrawn {
+        pool: pool_config.key(),
+        commitment,
+        leaf_index: note.leaf_index,
+        nullifier_hash,
+        asset_id,
+        amount,
+        custodian: ctx.accounts.custodian.key(),
+        court_order_hash,
+        audit_metadata: ctx.accounts.audit_metadata.key(),
+        authority: ctx.accounts.authority.key(),
+        auditor: ctx.accounts.auditor.key(),
+        sequence,
+        timestamp,
+    });
+
+    msg!(
+        "Compliance recovery: leaf {} ({} units) moved to custodian {}",
+        note.leaf_index,
+        amount,
+        ctx.accounts.custodian.key()
+    );
+
+    Ok(())
+}
+
+/// Pay `amount` from the vault token account to the custodian's
+fn pay_tokens(
+    accounts: &ComplianceRecoveryWithdraw,
+    asset_id: &[u8; 32],
+    amount: u64,
+) -> Result<()> {
+    let (Some(vault_token_account), Some(custodian_token_account), Some(mint), Some(token_program)) = (
+        accounts.vault_token_account.as_ref(),
+        accounts.custodian_token_account.as_ref(),
+        accounts.mint.as_ref(),
+        accounts.token_program.as_ref(),
+    ) else {
+        return err!(PrivacyErrorV2::MissingAssetAccount);
+    };
+
+    let pool_key = accounts.pool_config.key();
+    let bump = [accounts.asset_vault.bump];
+    let vault_seeds = AssetVault::seeds(&pool_key, asset_id, &bump);
+    let signer_seeds: &[&[&[u8]]] = &[&vault_seeds];
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: vault_token_account.to_account_info(),
+                mint: mint.to_account_info(),
+                to: custodian_token_account.to_account_info(),
+                authority: accounts.asset_vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+        mint.decimals,
+    )
+}
@@ -0,0 +1,105 @@
+//! Create One-Time Recipient Instruction
+//!
+//! Creates the associated token account of a one-time withdrawal owner and
+//! announces the ephemeral key the owner was derived from (derivation in the
+//! host-side `stealth` module). The relayer submits it in front of
+//! `withdraw_masp` with `recipient` set to the one-time owner, so the
+//! recipient's wallet never funds or touches the fresh address.
+//!
+//! # Security Model
+//!
+//! The program cannot check the derivation (that needs the recipient's scan
+//! key); a wrong owner only costs the sender, whose proof is bound to it.
+//! The token account is created with `init`, so an owner that already has
+//! one - a reused one-time address - is rejected.
+
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::error::PrivacyErrorV2;
+use crate::events::OneTimeRecipientCreated;
+use crate::state::{AssetVault, PoolConfigV2};
+
+/// Accounts for creating a one-time recipient token account
+#[derive(Accounts)]
+#[instruction(asset_id: [u8; 32])]
+pub struct CreateOneTimeRecipient<'info> {
+    /// Pays rent for the token account (normally the relayer)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        constraint = !pool_config.is_paused @ PrivacyErrorV2::PoolPaused,
+    )]
+    pub pool_config: Box<Account<'info, PoolConfigV2>>,
+
+    /// Asset vault the owner will withdraw from
+    #[account(
+        seeds = [
+            AssetVault::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        bump = asset_vault.bump,
+        constraint = asset_vault.is_active @ PrivacyErrorV2::AssetNotActive,
+        constraint = asset_vault.withdrawals_enabled @ PrivacyErrorV2::WithdrawalsDisabled,
+    )]
+    pub asset_vault: Box<Account<'info, AssetVault>>,
+
+    /// Token mint of the asset
+    #[account(address = asset_vault.mint @ PrivacyErrorV2::InvalidMint)]
+    pub mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: one-time owner derived off-chain; only used as the ATA authority
+    pub one_time_owner: UncheckedAccount<'info>,
+
+    /// Associated token account of the one-time owner
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = one_time_owner,
+    )]
+    pub recipient_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// Associated token program
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Handler for create_one_time_recipient instruction
+pub fn handler(
+    ctx: Context<CreateOneTimeRecipient>,
+    asset_id: [u8; 32],
+    ephemeral_pubkey: [u8; 32],
+    view_tag: u8,
+) -> Result<()> {
+    require!(
+        ephemeral_pubkey != [0u8; 32],
+        PrivacyErrorV2::InvalidOneTimeAddress
+    );
+    require!(
+        ephemeral_pubkey != ctx.accounts.one_time_owner.key().to_bytes(),
+        PrivacyErrorV2::InvalidOneTimeAddress
+    );
+
+    emit!(OneTimeRecipientCreated {
+        pool: ctx.accounts.pool_config.key(),
+        asset_id,
+        owner: ctx.accounts.one_time_owner.key(),
+        ephemeral_pubkey,
+        view_tag,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("One-time recipient account created");
+
+    Ok(())
+}
@@ -0,0 +1,194 @@
+//! Create Pool Instruction
+//!
+//! Permissionless pool creation: anyone can open a pool they are the
+//! authority of, paying `GlobalConfig::pool_creation_fee` lamports to the
+//! super guardian. One instruction creates the pool config, Merkle tree,
+//! relayer registry and compliance config that `initialize_pool_v2` and
+//! `initialize_pool_registries` create in two.
+//!
+//! Unlike `initialize_pool_v2`, the creator only chooses the tree depth and
+//! pool id; the rest is fixed to defaults:
+//! - tree depth between `CREATED_POOL_MIN_TREE_DEPTH` and `MAX_TREE_DEPTH`
+//!   (a shallow tree caps the anonymity set at a handful of notes)
+//! - `DEFAULT_ROOT_HISTORY_SIZE` roots of history
+//! - no compliance recovery
+//!
+//! `max_creation_fee` bounds what the creator pays if the fee is raised
+//! before the transaction lands. The pending deposits buffer is created as
+//! for any pool with `initialize_pending_deposits_buffer`.
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+
+use crate::error::PrivacyErrorV2;
+use crate::events::{PoolCreated, PoolInitializedV2};
+use crate::state::{
+    ComplianceConfig, GlobalConfig, MerkleTreeV2, PoolConfigV2, RelayerRegistry,
+    DEFAULT_ROOT_HISTORY_SIZE, MAX_TREE_DEPTH,
+};
+
+/// Smallest tree depth of a pool opened with `create_pool`
+pub const CREATED_POOL_MIN_TREE_DEPTH: u8 = 16;
+
+/// Accounts for opening a pool without a privileged deployer
+#[derive(Accounts)]
+#[instruction(tree_depth: u8, pool_id: u64)]
+pub struct CreatePool<'info> {
+    /// Creator and authority of the new pool (pays the fee and rent)
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    /// Program-wide config holding the creation fee
+    #[account(
+        seeds = [GlobalConfig::SEED_PREFIX],
+        bump = global_config.bump,
+        constraint = !global_config.is_paused @ PrivacyErrorV2::GlobalPaused,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    /// CHECK: receives the creation fee; must be the super guardian
+    #[account(
+        mut,
+        address = global_config.super_guardian @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub super_guardian: UncheckedAccount<'info>,
+
+    /// New pool config (PDA)
+    #[account(
+        init,
+        payer = creator,
+        space = PoolConfigV2::LEN,
+        seeds = [
+            PoolConfigV2::SEED_PREFIX,
+            creator.key().as_ref(),
+            pool_id.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool_config: Box<Account<'info, PoolConfigV2>>,
+
+    /// New pool's Merkle tree
+    #[account(
+        init,
+        payer = creator,
+        space = MerkleTreeV2::space(tree_depth, DEFAULT_ROOT_HISTORY_SIZE),
+        seeds = [MerkleTreeV2::SEED_PREFIX, pool_config.key().as_ref()],
+        bump,
+    )]
+    pub merkle_tree: Box<Account<'info, MerkleTreeV2>>,
+
+    /// New pool's relayer registry
+    #[account(
+        init,
+        payer = creator,
+        space = RelayerRegistry::LEN,
+        seeds = [RelayerRegistry::SEED_PREFIX, pool_config.key().as_ref()],
+        bump,
+    )]
+    pub relayer_registry: Box<Account<'info, RelayerRegistry>>,
+
+    /// New pool's compliance config
+    #[account(
+        init,
+        payer = creator,
+        space = ComplianceConfig::LEN,
+        seeds = [ComplianceConfig::SEED_PREFIX, pool_config.key().as_ref()],
+        bump,
+    )]
+    pub compliance_config: Box<Account<'info, ComplianceConfig>>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Handler for create_pool instruction
+pub fn handler(
+    ctx: Context<CreatePool>,
+    tree_depth: u8,
+    pool_id: u64,
+    max_creation_fee: u64,
+) -> Result<()> {
+    require!(
+        (CREATED_POOL_MIN_TREE_DEPTH..=MAX_TREE_DEPTH).contains(&tree_depth),
+        PrivacyErrorV2::InvalidCreatedPoolDepth
+    );
+
+    let creation_fee = ctx.accounts.global_config.pool_creation_fee;
+    require!(
+        creation_fee <= max_creation_fee,
+        PrivacyErrorV2::PoolCreationFeeTooHigh
+    );
+
+    if creation_fee > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.creator.to_account_info(),
+                    to: ctx.accounts.super_guardian.to_account_info(),
+                },
+            ),
+            creation_fee,
+        )?;
+    }
+
+    let timestamp = Clock::get()?.unix_timestamp;
+    let accounts = ctx.accounts;
+    let creator = accounts.creator.key();
+    let pool_key = accounts.pool_config.key();
+
+    let pool_config = &mut accounts.pool_config;
+    pool_config.initialize_partial(
+        creator,
+        pool_id,
+        accounts.merkle_tree.key(),
+        tree_depth,
+        ctx.bumps.pool_config,
+        timestamp,
+    );
+    pool_config.set_registries(
+        accounts.relayer_registry.key(),
+        accounts.compliance_config.key(),
+        creator,
+    );
+
+    accounts
+        .merkle_tree
+        .initialize(pool_key, tree_depth, DEFAULT_ROOT_HISTORY_SIZE)?;
+    accounts
+        .relayer_registry
+        .initialize(pool_key, ctx.bumps.relayer_registry, timestamp);
+    accounts
+        .compliance_config
+        .initialize(pool_key, ctx.bumps.compliance_config, timestamp);
+
+    emit!(PoolInitializedV2 {
+        pool: pool_key,
+        authority: creator,
+        pool_id,
+        merkle_tree: accounts.merkle_tree.key(),
+        relayer_registry: accounts.relayer_registry.key(),
+        tree_depth,
+        root_history_size: DEFAULT_ROOT_HISTORY_SIZE,
+        compliance_recovery_enabled: false,
+        poseidon_params_hash: accounts.merkle_tree.poseidon_params_hash,
+        timestamp,
+    });
+
+    emit!(PoolCreated {
+        pool: pool_key,
+        creator,
+        pool_id,
+        creation_fee,
+        timestamp,
+    });
+
+    msg!(
+        "Created pSOL v2 pool: id={}, depth={}, fee={}",
+        pool_id,
+        tree_depth,
+        creation_fee
+    );
+
+    Ok(())
+}
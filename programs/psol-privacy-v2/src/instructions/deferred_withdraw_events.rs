@@ -0,0 +1,142 @@
+//! Deferred Withdraw Event Instructions
+//!
+//! The authority creates a pool's `DeferredWithdrawEvents` queue and sets
+//! how long withdrawal events are held back (bounded by
+//! `MAX_WITHDRAW_EVENT_DELAY_SECONDS`, 0 disables deferral). Anyone may
+//! crank `flush_deferred_withdraw_events` to emit released events.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::events::{WithdrawEventDelaySet, WithdrawMaspEvent};
+use crate::state::{DeferredWithdrawEvents, PoolConfigV2};
+use crate::utils::pool_clock;
+
+/// Accounts for creating the deferred withdraw event queue
+#[derive(Accounts)]
+pub struct InitDeferredWithdrawEvents<'info> {
+    /// Pool authority (must be signer, pays rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Deferred withdraw events PDA
+    #[account(
+        init,
+        payer = authority,
+        space = DeferredWithdrawEvents::LEN,
+        seeds = [DeferredWithdrawEvents::SEED_PREFIX, pool_config.key().as_ref()],
+        bump,
+    )]
+    pub deferred_events: Box<Account<'info, DeferredWithdrawEvents>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for changing the withdraw event delay
+#[derive(Accounts)]
+pub struct SetWithdrawEventDelay<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Deferred withdraw events PDA
+    #[account(
+        mut,
+        seeds = [DeferredWithdrawEvents::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = deferred_events.bump,
+    )]
+    pub deferred_events: Box<Account<'info, DeferredWithdrawEvents>>,
+}
+
+/// Accounts for flushing released withdraw events (permissionless)
+#[derive(Accounts)]
+pub struct FlushDeferredWithdrawEvents<'info> {
+    /// Pool configuration account
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Deferred withdraw events PDA
+    #[account(
+        mut,
+        seeds = [DeferredWithdrawEvents::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = deferred_events.bump,
+    )]
+    pub deferred_events: Box<Account<'info, DeferredWithdrawEvents>>,
+}
+
+/// Handler for init_deferred_withdraw_events instruction
+pub fn init_handler(ctx: Context<InitDeferredWithdrawEvents>, delay_seconds: u32) -> Result<()> {
+    let pool_key = ctx.accounts.pool_config.key();
+    ctx.accounts
+        .deferred_events
+        .initialize(pool_key, delay_seconds, ctx.bumps.deferred_events)?;
+
+    emit!(WithdrawEventDelaySet {
+        pool: pool_key,
+        delay_seconds,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Deferred withdraw events enabled: {}s delay", delay_seconds);
+    Ok(())
+}
+
+/// Handler for set_withdraw_event_delay instruction
+///
+/// Already queued events keep their release time.
+pub fn set_delay_handler(ctx: Context<SetWithdrawEventDelay>, delay_seconds: u32) -> Result<()> {
+    ctx.accounts.deferred_events.set_delay(delay_seconds)?;
+
+    emit!(WithdrawEventDelaySet {
+        pool: ctx.accounts.pool_config.key(),
+        delay_seconds,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Withdraw event delay set to {}s", delay_seconds);
+    Ok(())
+}
+
+/// Handler for flush_deferred_withdraw_events instruction
+///
+/// Emits up to `max_events` released events, stamped with the flush time,
+/// and returns how many were emitted.
+pub fn flush_handler(ctx: Context<FlushDeferredWithdrawEvents>, max_events: u8) -> Result<u8> {
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+    let pool = ctx.accounts.pool_config.key();
+
+    let released = ctx
+        .accounts
+        .deferred_events
+        .take_released(timestamp, max_events as usize)?;
+
+    for event in &released {
+        emit!(WithdrawMaspEvent {
+            pool,
+            nullifier_hash: event.nullifier_hash,
+            asset_id: event.asset_id,
+            relayer: event.relayer,
+            relayer_fee: event.relayer_fee,
+            sequence: event.sequence,
+            timestamp,
+        });
+    }
+
+    msg!(
+        "Flushed {} withdraw events ({} still queued)",
+        released.len(),
+        ctx.accounts.deferred_events.events.len()
+    );
+
+    Ok(released.len() as u8)
+}
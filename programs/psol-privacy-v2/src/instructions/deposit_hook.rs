@@ -0,0 +1,117 @@
+//! Deposit Hook Instructions
+//!
+//! The pool authority registers an integrator's callback program (one per
+//! pool) or removes it. While one is registered, every
+//! `batch_process_deposits` must pass the hook and its program, and calls
+//! the program once per inserted commitment (see `state::deposit_hook`).
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::events::{DepositHookRegistered, DepositHookRemoved};
+use crate::state::{DepositHook, PoolConfigV2};
+
+/// Accounts for registering a deposit hook
+#[derive(Accounts)]
+pub struct RegisterDepositHook<'info> {
+    /// Pool authority (must be signer, pays rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        mut,
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Deposit hook PDA
+    #[account(
+        init,
+        payer = authority,
+        space = DepositHook::LEN,
+        seeds = [DepositHook::SEED_PREFIX, pool_config.key().as_ref()],
+        bump,
+    )]
+    pub deposit_hook: Account<'info, DepositHook>,
+
+    /// CHECK: Any executable program other than this one
+    #[account(
+        executable,
+        constraint = callback_program.key() != crate::ID @ PrivacyErrorV2::InvalidDepositHookProgram,
+    )]
+    pub callback_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for removing the deposit hook
+#[derive(Accounts)]
+pub struct RemoveDepositHook<'info> {
+    /// Pool authority (must be signer, receives the rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        mut,
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Deposit hook PDA
+    #[account(
+        mut,
+        close = authority,
+        seeds = [DepositHook::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = deposit_hook.bump,
+    )]
+    pub deposit_hook: Account<'info, DepositHook>,
+}
+
+/// Handler for register_deposit_hook instruction
+pub fn register_handler(ctx: Context<RegisterDepositHook>) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+    let pool = ctx.accounts.pool_config.key();
+    let callback_program = ctx.accounts.callback_program.key();
+
+    let hook = &mut ctx.accounts.deposit_hook;
+    hook.pool = pool;
+    hook.callback_program = callback_program;
+    hook.registered_at = timestamp;
+    hook.callbacks_invoked = 0;
+    hook.bump = ctx.bumps.deposit_hook;
+    hook._reserved = [0u8; 32];
+
+    let pool_config = &mut ctx.accounts.pool_config;
+    pool_config.deposit_hook_enabled = true;
+    pool_config.last_activity_at = timestamp;
+
+    emit!(DepositHookRegistered {
+        pool,
+        callback_program,
+        timestamp,
+    });
+
+    msg!("Deposit hook registered: {}", callback_program);
+    Ok(())
+}
+
+/// Handler for remove_deposit_hook instruction
+pub fn remove_handler(ctx: Context<RemoveDepositHook>) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    let pool_config = &mut ctx.accounts.pool_config;
+    pool_config.deposit_hook_enabled = false;
+    pool_config.last_activity_at = timestamp;
+
+    emit!(DepositHookRemoved {
+        pool: pool_config.key(),
+        callback_program: ctx.accounts.deposit_hook.callback_program,
+        callbacks_invoked: ctx.accounts.deposit_hook.callbacks_invoked,
+        timestamp,
+    });
+
+    msg!("Deposit hook removed");
+    Ok(())
+}
@@ -1,12 +1,42 @@
+//! Deposit MASP Instruction - pSOL v2
+//!
+//! Moves funds into an asset vault and queues the commitment for batched
+//! insertion. SPL assets are transferred from the depositor's token
+//! account; native SOL (`NATIVE_SOL_ASSET_ID`) is sent as lamports from the
+//! depositor's wallet, so the token accounts and mint are omitted.
+//!
+//! Token-2022 mints with a transfer fee withhold the fee at the vault. The
+//! commitment is for what the vault holds, so the depositor sends the gross
+//! amount that nets `amount`, and the vault's balance change is checked
+//! against it.
+//!
+//! Pools with a crank bounty charge it on top, in lamports from the
+//! depositor's wallet, and hold it on the pending buffer until a batch pays
+//! it out (see `admin::crank_bounty`).
+//!
+//! Every deposit passes the commitment's reservation PDA. Where the
+//! commitment was reserved with `reserve_commitment`, only the reserving
+//! wallet can deposit it until the reservation expires, and its deposit
+//! closes the reservation (see `state::commitment_reservation`).
+//!
+//! An asset put in a risk tier is also held to the tier's deposit switch
+//! and cap, read from the pool's tier table passed as `risk_tiers`.
+
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_lang::solana_program::program::set_return_data;
+use anchor_lang::system_program;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+use solana_sdk_ids::sysvar::instructions as sysvar_instructions;
 
-use crate::crypto::DepositPublicInputs;
+use crate::crypto::{check_amount_range, DepositPublicInputs, RangeCheckInput};
 use crate::error::PrivacyErrorV2;
+use crate::events::{DepositQueuedEvent, ProofVerificationFailed};
+use crate::state::pending_deposits::MAX_PENDING_DEPOSITS;
 use crate::state::{
-    AssetVault, MerkleTreeV2, PendingDepositsBuffer, PoolConfigV2, VerificationKeyAccountV2,
+    AnonymityStats, AssetVault, CommitmentReservation, GlobalConfig, MerkleTreeV2,
+    PendingDepositsBuffer, PoolConfigV2, ProofStats, RiskTierConfig, VerificationKeyAccountV2,
 };
-use crate::utils::cu;
+use crate::utils::{cu, pool_clock, MintTransferFee, PoolFlow};
 use crate::ProofType;
 
 /// Accounts required for a MASP deposit.
@@ -31,6 +61,16 @@ pub struct DepositMasp<'info> {
     )]
     pub pool_config: Box<Account<'info, PoolConfigV2>>,
 
+    /// Program-wide pause switch
+    /// CHECK: PDA; may not exist yet (see `GlobalConfig::is_paused_account`)
+    #[account(
+        seeds = [GlobalConfig::SEED_PREFIX],
+        bump,
+        constraint = !GlobalConfig::is_paused_account(&global_config)
+            @ PrivacyErrorV2::GlobalPaused,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
     /// Pool authority (validated via has_one constraint)
     /// CHECK: Validated by has_one constraint on pool_config
     pub authority: UncheckedAccount<'info>,
@@ -49,10 +89,10 @@ pub struct DepositMasp<'info> {
             PendingDepositsBuffer::SEED_PREFIX,
             pool_config.key().as_ref(),
         ],
-        bump = pending_buffer.bump,
-        constraint = pending_buffer.pool == pool_config.key() @ PrivacyErrorV2::InvalidPoolReference,
+        bump = PendingDepositsBuffer::checked_bump(&pending_buffer)?,
+        constraint = pending_buffer.load()?.pool == pool_config.key() @ PrivacyErrorV2::InvalidPoolReference,
     )]
-    pub pending_buffer: Box<Account<'info, PendingDepositsBuffer>>,
+    pub pending_buffer: AccountLoader<'info, PendingDepositsBuffer>,
 
     /// Asset vault configuration for this asset
     #[account(
@@ -69,27 +109,27 @@ pub struct DepositMasp<'info> {
     )]
     pub asset_vault: Box<Account<'info, AssetVault>>,
 
-    /// Vault token account that receives deposited tokens
+    /// Vault token account that receives deposited tokens (SPL assets only)
     #[account(
         mut,
         constraint = vault_token_account.key() == asset_vault.token_account
             @ PrivacyErrorV2::InvalidVaultTokenAccount
     )]
-    pub vault_token_account: Account<'info, TokenAccount>,
+    pub vault_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
 
-    /// User token account providing funds
+    /// User token account providing funds (SPL assets only)
     #[account(
         mut,
         constraint = user_token_account.mint == asset_vault.mint @ PrivacyErrorV2::InvalidMint,
         constraint = user_token_account.owner == depositor.key() @ PrivacyErrorV2::InvalidTokenOwner
     )]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub user_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
 
-    /// Mint for this asset
+    /// Mint for this asset (SPL assets only)
     #[account(
         constraint = mint.key() == asset_vault.mint @ PrivacyErrorV2::InvalidMint
     )]
-    pub mint: Account<'info, Mint>,
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
 
     /// Verification key account for the deposit circuit
     #[account(
@@ -101,11 +141,57 @@ pub struct DepositMasp<'info> {
     )]
     pub deposit_vk: Account<'info, VerificationKeyAccountV2>,
 
-    /// SPL token program
-    pub token_program: Program<'info, Token>,
+    /// Token program owning the mint (SPL Token or Token-2022)
+    pub token_program: Interface<'info, TokenInterface>,
 
     /// System program
     pub system_program: Program<'info, System>,
+
+    /// CHECK: Address constrained to the instructions sysvar (same-transaction
+    /// deposit/withdraw guard)
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    /// Optional: per-proof-type verification counters
+    #[account(
+        mut,
+        seeds = [ProofStats::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = proof_stats.bump,
+    )]
+    pub proof_stats: Option<Account<'info, ProofStats>>,
+
+    /// Optional: per-asset deposit-to-withdrawal latency statistics
+    /// (required once the pool keeps them)
+    #[account(
+        mut,
+        seeds = [
+            AnonymityStats::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        bump = anonymity_stats.bump,
+    )]
+    pub anonymity_stats: Option<Box<Account<'info, AnonymityStats>>>,
+
+    /// Reservation of the commitment, closed to the depositor if it is theirs
+    /// CHECK: PDA; usually does not exist (see `consume_commitment_reservation`)
+    #[account(
+        mut,
+        seeds = [
+            CommitmentReservation::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            commitment.as_ref(),
+        ],
+        bump,
+    )]
+    pub commitment_reservation: UncheckedAccount<'info>,
+
+    /// Pool's risk tier table; required once the asset is in a tier
+    #[account(
+        seeds = [RiskTierConfig::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = risk_tiers.bump,
+    )]
+    pub risk_tiers: Option<Box<Account<'info, RiskTierConfig>>>,
 }
 
 /// Handler for deposit_masp instruction
@@ -116,20 +202,22 @@ pub fn handler(
     commitment: [u8; 32],
     asset_id: [u8; 32],
     proof_data: Vec<u8>,
-    _encrypted_note: Option<Vec<u8>>,
+    encrypted_note: Option<Vec<u8>>,
 ) -> Result<()> {
     // IMPORTANT:
     // - ctx.accounts.pool_config is Box<Account<PoolConfigV2>> so it has `.key()`
     // - after deref, PoolConfigV2 itself does NOT have `.key()`
-    let _pool_key = ctx.accounts.pool_config.key();
+    let pool_key = ctx.accounts.pool_config.key();
+    let vault_info = ctx.accounts.asset_vault.to_account_info();
 
     // Deref Box<Account<...>> to inner mutable account data for updates.
     let pool_config: &mut PoolConfigV2 = &mut *ctx.accounts.pool_config;
     let merkle_tree: &MerkleTreeV2 = &*ctx.accounts.merkle_tree;
-    let pending_buffer: &mut PendingDepositsBuffer = &mut *ctx.accounts.pending_buffer;
+    let mut pending_buffer = ctx.accounts.pending_buffer.load_mut()?;
     let asset_vault: &mut AssetVault = &mut *ctx.accounts.asset_vault;
 
-    let timestamp = Clock::get()?.unix_timestamp;
+    let clock = pool_clock(pool_config)?;
+    let timestamp = clock.unix_timestamp;
 
     // =========================================================================
     // 1. INPUT VALIDATION
@@ -139,80 +227,144 @@ pub fn handler(
     cu("deposit: after amount>0");
     log_cu();
 
+    asset_vault.validate_deposit_amount(amount)?;
+    if let Some(limits) = RiskTierConfig::asset_limits(
+        ctx.accounts.risk_tiers.as_deref().map(|t| &**t),
+        asset_vault,
+    )? {
+        limits.check_deposit(amount)?;
+    }
+
     require!(
         !commitment.iter().all(|&b| b == 0),
         PrivacyErrorV2::InvalidCommitment
     );
 
-    require!(proof_data.len() == 256, PrivacyErrorV2::InvalidProofFormat);
+    require!(
+        crate::crypto::is_valid_proof_length(&proof_data),
+        PrivacyErrorV2::InvalidProofFormat
+    );
     cu("deposit: after proof len");
 
+    pool_config.require_encrypted_note_size(encrypted_note.as_deref())?;
+
+    // Amount range-check hook (no-op until the pool enables a range proof scheme)
+    check_amount_range(
+        pool_config.range_proof_scheme()?,
+        None,
+        &RangeCheckInput::new(asset_id, amount),
+    )?;
+
+    pool_config.require_no_same_tx_flow(
+        &pool_key,
+        &ctx.accounts.instructions_sysvar,
+        PoolFlow::Deposit,
+    )?;
+
     require!(
         asset_vault.asset_id == asset_id,
         PrivacyErrorV2::AssetIdMismatch
     );
 
-    require!(!merkle_tree.is_full(), PrivacyErrorV2::MerkleTreeFull);
+    // SPL deposits need the token accounts and mint; native SOL needs none
+    require!(
+        asset_vault.is_native_sol()
+            || (ctx.accounts.user_token_account.is_some()
+                && ctx.accounts.vault_token_account.is_some()
+                && ctx.accounts.mint.is_some()),
+        PrivacyErrorV2::MissingAssetAccount
+    );
+
+    // Reject early (before proof verification and transfer) when the queue
+    // or the tree has no room left for this commitment
+    require_queue_room(&pending_buffer, merkle_tree, 1, timestamp)?;
 
     // =========================================================================
     // 2. VERIFY GROTH16 PROOF
     // =========================================================================
 
-    let public_inputs = DepositPublicInputs::new(commitment, amount, asset_id);
-    public_inputs.validate()?;
-    cu("deposit: after public_inputs.validate");
-    let public_inputs_fields = public_inputs.to_field_elements();
-
-    let vk = &ctx.accounts.deposit_vk;
     cu("deposit: before groth16 verify");
-    let is_valid = crate::crypto::verify_proof_from_account(
-        &vk.vk_alpha_g1,
-        &vk.vk_beta_g2,
-        &vk.vk_gamma_g2,
-        &vk.vk_delta_g2,
-        &vk.vk_ic,
+    verify_deposit_proof(
+        &ctx.accounts.deposit_vk,
+        pool_key,
+        ctx.accounts.depositor.key(),
+        commitment,
+        amount,
+        asset_id,
         &proof_data,
-        &public_inputs_fields,
+        timestamp,
     )?;
-    require!(is_valid, PrivacyErrorV2::InvalidProof);
+    if let Some(proof_stats) = ctx.accounts.proof_stats.as_mut() {
+        proof_stats.record_verified(ProofType::Deposit, timestamp)?;
+    }
+    pool_config.require_anonymity_stats(ctx.accounts.anonymity_stats.is_some())?;
+    if let Some(anonymity_stats) = ctx.accounts.anonymity_stats.as_mut() {
+        anonymity_stats.record_deposit(timestamp)?;
+    }
     cu("deposit: after groth16 verify");
     log_cu();
 
+    consume_commitment_reservation(
+        &ctx.accounts.commitment_reservation,
+        &ctx.accounts.depositor,
+        clock.slot,
+    )?;
+
     // =========================================================================
-    // 3. TRANSFER TOKENS FROM USER TO VAULT
+    // 3. TRANSFER FUNDS FROM USER TO VAULT
     // =========================================================================
 
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.user_token_account.to_account_info(),
-        to: ctx.accounts.vault_token_account.to_account_info(),
-        authority: ctx.accounts.depositor.to_account_info(),
-    };
-    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
-    cu("deposit: before token::transfer");
-    token::transfer(cpi_ctx, amount)?;
-    cu("deposit: after token::transfer");
+    let vault_token_balance = transfer_to_vault(
+        &ctx.accounts.depositor,
+        vault_info,
+        asset_vault.is_native_sol(),
+        ctx.accounts.vault_token_account.as_mut(),
+        ctx.accounts.user_token_account.as_ref(),
+        ctx.accounts.mint.as_ref(),
+        &ctx.accounts.token_program,
+        &ctx.accounts.system_program,
+        amount,
+    )?;
 
     // =========================================================================
     // 4. QUEUE COMMITMENT FOR BATCHED MERKLE INSERTION
     // =========================================================================
 
-    // Ensure the Merkle tree can eventually fit all pending + this new deposit
-    let available = merkle_tree.available_space() as usize;
-    let pending = pending_buffer.size();
-    require!(available > pending, PrivacyErrorV2::MerkleTreeFull);
-
     cu("deposit: before pending_buffer.add_pending");
     let pending_index = pending_buffer.add_pending(commitment, timestamp)?;
     cu("deposit: after pending_buffer.add_pending");
     let pending_count = pending_buffer.size();
     log_cu();
 
+    // Crank bounty: escrowed on the pending buffer until a batch inserts
+    // the commitment (the buffer's data borrow must end before the CPI)
+    let crank_bounty = pool_config.crank_bounty_for(1);
+    pending_buffer.escrow_bounty(crank_bounty)?;
+    drop(pending_buffer);
+    escrow_crank_bounty(
+        &ctx.accounts.depositor,
+        ctx.accounts.pending_buffer.to_account_info(),
+        &ctx.accounts.system_program,
+        crank_bounty,
+    )?;
+
     // =========================================================================
     // 5. UPDATE STATISTICS
     // =========================================================================
 
-    asset_vault.record_deposit(amount, timestamp)?;
-    pool_config.record_deposit(timestamp)?;
+    asset_vault.record_deposit(amount, timestamp, pool_config.has_coarse_liquidity_stats())?;
+    if let Some(balance) = vault_token_balance {
+        asset_vault.reconcile(balance)?;
+    }
+    pool_config.record_pending_deposit(timestamp)?;
+    let sequence = pool_config.next_event_sequence()?;
+
+    emit!(DepositQueuedEvent {
+        pool: ctx.accounts.pool_config.key(),
+        commitment,
+        sequence,
+        timestamp,
+    });
 
     msg!(
         "MASP deposit queued: pending_index={}, pending_count={}",
@@ -223,6 +375,181 @@ pub fn handler(
     Ok(())
 }
 
+/// Reject a deposit of `count` commitments unless the pending buffer and
+/// the tree (including already-queued commitments) have room for all of
+/// them. A full buffer also returns the estimated wait as return data.
+pub(crate) fn require_queue_room(
+    pending_buffer: &PendingDepositsBuffer,
+    merkle_tree: &MerkleTreeV2,
+    count: usize,
+    timestamp: i64,
+) -> Result<()> {
+    if pending_buffer.size() + count > MAX_PENDING_DEPOSITS {
+        let wait = pending_buffer.estimated_wait_seconds(timestamp);
+        msg!(
+            "PENDING_BUFFER_FULL size={} oldest_age={}s est_wait={}s",
+            pending_buffer.size(),
+            pending_buffer.oldest_pending_age(timestamp),
+            wait
+        );
+        set_return_data(&wait.to_le_bytes());
+        return err!(PrivacyErrorV2::PendingBufferFull);
+    }
+    merkle_tree.require_capacity_for(pending_buffer.size() + count)
+}
+
+/// Honour the reservation of a commitment being deposited
+///
+/// `reservation` is the commitment's reservation PDA, which usually does
+/// not exist. A live reservation admits only the wallet that made it; the
+/// depositor's own reservation, live or not, is closed to it. Another
+/// wallet's expired reservation is left in place so the commitment cannot
+/// be reserved again.
+pub(crate) fn consume_commitment_reservation(
+    reservation: &AccountInfo,
+    depositor: &AccountInfo,
+    slot: u64,
+) -> Result<()> {
+    let Some(held) = CommitmentReservation::load(reservation)? else {
+        return Ok(());
+    };
+    held.require_depositor(depositor.key, slot)?;
+    if held.depositor != *depositor.key {
+        return Ok(());
+    }
+
+    // Close the reservation: rent back to the depositor, account handed
+    // back to the system program
+    let rent = reservation.lamports();
+    **depositor.try_borrow_mut_lamports()? = depositor
+        .lamports()
+        .checked_add(rent)
+        .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+    **reservation.try_borrow_mut_lamports()? = 0;
+    reservation.assign(&System::id());
+    reservation.resize(0)?;
+    Ok(())
+}
+
+/// Verify the deposit proof for one commitment
+///
+/// A depositor-bound VK ties the proof to the signer, so a mempool
+/// observer cannot replay the commitment/proof from another wallet.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn verify_deposit_proof(
+    vk: &VerificationKeyAccountV2,
+    pool: Pubkey,
+    depositor: Pubkey,
+    commitment: [u8; 32],
+    amount: u64,
+    asset_id: [u8; 32],
+    proof_data: &[u8],
+    timestamp: i64,
+) -> Result<()> {
+    let mut public_inputs = DepositPublicInputs::new(commitment, amount, asset_id);
+    if vk.binds_depositor() {
+        public_inputs = public_inputs.with_depositor(depositor);
+    }
+    public_inputs.validate()?;
+    let public_inputs_fields = public_inputs.to_field_elements();
+
+    let is_valid = crate::crypto::verify_proof_from_account(
+        &vk.vk_alpha_g1,
+        &vk.vk_beta_g2,
+        &vk.vk_gamma_g2,
+        &vk.vk_delta_g2,
+        &vk.vk_ic,
+        proof_data,
+        &public_inputs_fields,
+    )?;
+    if !is_valid {
+        emit!(ProofVerificationFailed {
+            pool,
+            proof_type: ProofType::Deposit as u8,
+            submitter: depositor,
+            timestamp,
+        });
+        return err!(PrivacyErrorV2::InvalidProof);
+    }
+    Ok(())
+}
+
+/// Move `amount` from the depositor into the vault
+///
+/// Returns the vault token account's balance after the transfer, or `None`
+/// for native SOL. Callers check the SPL accounts are present first.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn transfer_to_vault<'info>(
+    depositor: &Signer<'info>,
+    vault_info: AccountInfo<'info>,
+    native_sol: bool,
+    vault_token_account: Option<&mut InterfaceAccount<'info, TokenAccount>>,
+    user_token_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+    mint: Option<&InterfaceAccount<'info, Mint>>,
+    token_program: &Interface<'info, TokenInterface>,
+    system_program: &Program<'info, System>,
+    amount: u64,
+) -> Result<Option<u64>> {
+    if native_sol {
+        // Lamports go straight onto the vault PDA
+        let cpi_accounts = system_program::Transfer {
+            from: depositor.to_account_info(),
+            to: vault_info,
+        };
+        let cpi_ctx = CpiContext::new(system_program.to_account_info(), cpi_accounts);
+        system_program::transfer(cpi_ctx, amount)?;
+        return Ok(None);
+    }
+
+    let (Some(user_token_account), Some(vault_token_account), Some(mint)) =
+        (user_token_account, vault_token_account, mint)
+    else {
+        // Unreachable after the callers' checks; kept so the transfer never
+        // depends on them
+        return err!(PrivacyErrorV2::MissingAssetAccount);
+    };
+    // The depositor covers any transfer fee so the vault nets `amount`
+    let gross_amount = MintTransferFee::load(&mint.to_account_info())?.gross_amount(amount)?;
+    let balance_before = vault_token_account.amount;
+
+    let cpi_accounts = TransferChecked {
+        from: user_token_account.to_account_info(),
+        mint: mint.to_account_info(),
+        to: vault_token_account.to_account_info(),
+        authority: depositor.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts);
+    cu("deposit: before token::transfer");
+    token_interface::transfer_checked(cpi_ctx, gross_amount, mint.decimals)?;
+    cu("deposit: after token::transfer");
+
+    vault_token_account.reload()?;
+    require!(
+        vault_token_account.amount.checked_sub(balance_before) == Some(amount),
+        PrivacyErrorV2::VaultBalanceDrift
+    );
+    Ok(Some(vault_token_account.amount))
+}
+
+/// Escrow a crank bounty on the pending buffer until a batch inserts the
+/// commitments it pays for
+pub(crate) fn escrow_crank_bounty<'info>(
+    depositor: &Signer<'info>,
+    pending_buffer: AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    lamports: u64,
+) -> Result<()> {
+    if lamports == 0 {
+        return Ok(());
+    }
+    let cpi_accounts = system_program::Transfer {
+        from: depositor.to_account_info(),
+        to: pending_buffer,
+    };
+    let cpi_ctx = CpiContext::new(system_program.to_account_info(), cpi_accounts);
+    system_program::transfer(cpi_ctx, lamports)
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
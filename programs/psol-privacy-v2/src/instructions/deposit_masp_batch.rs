@@ -0,0 +1,438 @@
+//! Deposit MASP Batch Instruction
+//!
+//! Lets a depositor split one deposit into up to `MAX_DEPOSIT_COMMITMENTS`
+//! notes in a single instruction: each commitment carries its own amount
+//! and deposit proof, and the vault receives one transfer of their sum
+//! instead of one per note. Every commitment is validated, proved and
+//! queued exactly as `deposit_masp` does (see its shared helpers), and the
+//! instruction queues either all of them or none.
+//!
+//! Three deposit proofs take most of a transaction's compute limit (see
+//! `DEPOSIT_MASP_BATCH_CU`); with the accounts listed inline they also
+//! approach the transaction size limit, so clients should pass the pool's
+//! static accounts through an address lookup table.
+//!
+//! The remaining accounts are the commitments' reservation PDAs, one per
+//! commitment and in the same order, each checked and consumed as in
+//! `deposit_masp` (see `state::commitment_reservation`).
+//!
+//! An asset put in a risk tier holds each note to the tier's deposit
+//! switch and cap, as `deposit_masp` does.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use solana_sdk_ids::sysvar::instructions as sysvar_instructions;
+
+use crate::crypto::{check_amount_range, RangeCheckInput};
+use crate::error::PrivacyErrorV2;
+use crate::events::DepositQueuedEvent;
+use crate::instructions::deposit_masp::{
+    consume_commitment_reservation, escrow_crank_bounty, require_queue_room, transfer_to_vault,
+    verify_deposit_proof,
+};
+use crate::state::{
+    AnonymityStats, AssetVault, CommitmentReservation, GlobalConfig, MerkleTreeV2,
+    PendingDepositsBuffer, PoolConfigV2, ProofStats, RiskTierConfig, VerificationKeyAccountV2,
+};
+use crate::utils::{pool_clock, require_program_id, PoolFlow};
+use crate::ProofType;
+
+/// Most commitments one `deposit_masp_batch` call queues
+pub const MAX_DEPOSIT_COMMITMENTS: usize = 3;
+
+/// Accounts for a MASP deposit of several commitments
+#[derive(Accounts)]
+#[instruction(asset_id: [u8; 32])]
+pub struct DepositMaspBatch<'info> {
+    /// User funding the deposit and paying tx fees
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    /// Global pool configuration
+    #[account(
+        mut,
+        has_one = authority,
+        has_one = merkle_tree,
+        constraint = !pool_config.is_paused @ PrivacyErrorV2::PoolPaused
+    )]
+    pub pool_config: Box<Account<'info, PoolConfigV2>>,
+
+    /// Program-wide pause switch
+    /// CHECK: PDA; may not exist yet (see `GlobalConfig::is_paused_account`)
+    #[account(
+        seeds = [GlobalConfig::SEED_PREFIX],
+        bump,
+        constraint = !GlobalConfig::is_paused_account(&global_config)
+            @ PrivacyErrorV2::GlobalPaused,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// Pool authority (validated via has_one constraint)
+    /// CHECK: Validated by has_one constraint on pool_config
+    pub authority: UncheckedAccount<'info>,
+
+    /// Merkle tree for commitments belonging to this pool
+    #[account(
+        mut,
+        constraint = merkle_tree.pool == pool_config.key() @ PrivacyErrorV2::InvalidMerkleTreePool
+    )]
+    pub merkle_tree: Box<Account<'info, MerkleTreeV2>>,
+
+    /// Pending deposits buffer (commitments queued for batching)
+    #[account(
+        mut,
+        seeds = [
+            PendingDepositsBuffer::SEED_PREFIX,
+            pool_config.key().as_ref(),
+        ],
+        bump = PendingDepositsBuffer::checked_bump(&pending_buffer)?,
+        constraint = pending_buffer.load()?.pool == pool_config.key() @ PrivacyErrorV2::InvalidPoolReference,
+    )]
+    pub pending_buffer: AccountLoader<'info, PendingDepositsBuffer>,
+
+    /// Asset vault configuration for this asset
+    #[account(
+        mut,
+        seeds = [
+            AssetVault::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        bump = asset_vault.bump,
+        constraint = asset_vault.pool == pool_config.key() @ PrivacyErrorV2::InvalidVaultPool,
+        constraint = asset_vault.is_active @ PrivacyErrorV2::AssetNotActive,
+        constraint = asset_vault.deposits_enabled @ PrivacyErrorV2::DepositsDisabled,
+    )]
+    pub asset_vault: Box<Account<'info, AssetVault>>,
+
+    /// Vault token account that receives deposited tokens (SPL assets only)
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == asset_vault.token_account
+            @ PrivacyErrorV2::InvalidVaultTokenAccount
+    )]
+    pub vault_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// User token account providing funds (SPL assets only)
+    #[account(
+        mut,
+        constraint = user_token_account.mint == asset_vault.mint @ PrivacyErrorV2::InvalidMint,
+        constraint = user_token_account.owner == depositor.key() @ PrivacyErrorV2::InvalidTokenOwner
+    )]
+    pub user_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Mint for this asset (SPL assets only)
+    #[account(
+        constraint = mint.key() == asset_vault.mint @ PrivacyErrorV2::InvalidMint
+    )]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Verification key account for the deposit circuit
+    #[account(
+        seeds = [ProofType::Deposit.as_seed(), pool_config.key().as_ref()],
+        bump = deposit_vk.bump,
+        constraint = deposit_vk.pool == pool_config.key() @ PrivacyErrorV2::InvalidVerificationKeyPool,
+        constraint = deposit_vk.proof_type == ProofType::Deposit as u8 @ PrivacyErrorV2::InvalidVerificationKeyType,
+        constraint = deposit_vk.is_initialized @ PrivacyErrorV2::VerificationKeyNotSet,
+    )]
+    pub deposit_vk: Box<Account<'info, VerificationKeyAccountV2>>,
+
+    /// Token program owning the mint (SPL Token or Token-2022)
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Address constrained to the instructions sysvar (same-transaction
+    /// deposit/withdraw guard)
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    /// Optional: per-proof-type verification counters
+    #[account(
+        mut,
+        seeds = [ProofStats::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = proof_stats.bump,
+    )]
+    pub proof_stats: Option<Account<'info, ProofStats>>,
+
+    /// Optional: per-asset deposit-to-withdrawal latency statistics
+    /// (required once the pool keeps them)
+    #[account(
+        mut,
+        seeds = [
+            AnonymityStats::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        bump = anonymity_stats.bump,
+    )]
+    pub anonymity_stats: Option<Box<Account<'info, AnonymityStats>>>,
+
+    /// Pool's risk tier table; required once the asset is in a tier
+    #[account(
+        seeds = [RiskTierConfig::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = risk_tiers.bump,
+    )]
+    pub risk_tiers: Option<Box<Account<'info, RiskTierConfig>>>,
+}
+
+/// Total the vault receives for `amounts`, after checking there is one
+/// amount, proof and (when given) note per commitment, no more than
+/// `MAX_DEPOSIT_COMMITMENTS` commitments, and no zero amount
+pub fn batch_total(
+    amounts: &[u64],
+    commitments: &[[u8; 32]],
+    proofs: &[Vec<u8>],
+    encrypted_notes: Option<&[Vec<u8>]>,
+) -> Result<u64> {
+    require!(
+        !commitments.is_empty() && commitments.len() <= MAX_DEPOSIT_COMMITMENTS,
+        PrivacyErrorV2::InvalidBatchSize
+    );
+    require!(
+        amounts.len() == commitments.len()
+            && proofs.len() == commitments.len()
+            && encrypted_notes.map_or(commitments.len(), <[_]>::len) == commitments.len(),
+        PrivacyErrorV2::DepositBatchLengthMismatch
+    );
+    amounts.iter().try_fold(0u64, |total, &amount| {
+        require!(amount > 0, PrivacyErrorV2::InvalidAmount);
+        total
+            .checked_add(amount)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))
+    })
+}
+
+/// Handler for deposit_masp_batch instruction
+pub fn handler(
+    ctx: Context<DepositMaspBatch>,
+    asset_id: [u8; 32],
+    amounts: Vec<u64>,
+    commitments: Vec<[u8; 32]>,
+    proofs: Vec<Vec<u8>>,
+    encrypted_notes: Option<Vec<Vec<u8>>>,
+) -> Result<()> {
+    let pool_key = ctx.accounts.pool_config.key();
+    let vault_info = ctx.accounts.asset_vault.to_account_info();
+
+    let pool_config: &mut PoolConfigV2 = &mut ctx.accounts.pool_config;
+    let merkle_tree: &MerkleTreeV2 = &ctx.accounts.merkle_tree;
+    let mut pending_buffer = ctx.accounts.pending_buffer.load_mut()?;
+    let asset_vault: &mut AssetVault = &mut ctx.accounts.asset_vault;
+
+    let clock = pool_clock(pool_config)?;
+    let timestamp = clock.unix_timestamp;
+
+    // =========================================================================
+    // 1. INPUT VALIDATION
+    // =========================================================================
+
+    let total = batch_total(&amounts, &commitments, &proofs, encrypted_notes.as_deref())?;
+    for (i, commitment) in commitments.iter().enumerate() {
+        require!(
+            !crate::crypto::is_zero_hash(commitment) && !commitments[..i].contains(commitment),
+            PrivacyErrorV2::InvalidCommitment
+        );
+    }
+    require_program_id(ctx.program_id)?;
+    require!(
+        ctx.remaining_accounts.len() == commitments.len()
+            && ctx
+                .remaining_accounts
+                .iter()
+                .zip(&commitments)
+                .all(|(reservation, commitment)| {
+                    *reservation.key
+                        == CommitmentReservation::find_pda(&crate::ID, &pool_key, commitment).0
+                }),
+        PrivacyErrorV2::RemainingAccountsMismatch
+    );
+    require!(
+        proofs
+            .iter()
+            .all(|proof| crate::crypto::is_valid_proof_length(proof)),
+        PrivacyErrorV2::InvalidProofFormat
+    );
+    for note in encrypted_notes.iter().flatten() {
+        pool_config.require_encrypted_note_size(Some(note))?;
+    }
+
+    let range_proof_scheme = pool_config.range_proof_scheme()?;
+    let tier_limits = RiskTierConfig::asset_limits(
+        ctx.accounts.risk_tiers.as_deref().map(|t| &**t),
+        asset_vault,
+    )?;
+    for &amount in &amounts {
+        asset_vault.validate_deposit_amount(amount)?;
+        if let Some(limits) = &tier_limits {
+            limits.check_deposit(amount)?;
+        }
+        check_amount_range(
+            range_proof_scheme,
+            None,
+            &RangeCheckInput::new(asset_id, amount),
+        )?;
+    }
+
+    pool_config.require_no_same_tx_flow(
+        &pool_key,
+        &ctx.accounts.instructions_sysvar,
+        PoolFlow::Deposit,
+    )?;
+
+    require!(
+        asset_vault.asset_id == asset_id,
+        PrivacyErrorV2::AssetIdMismatch
+    );
+
+    // SPL deposits need the token accounts and mint; native SOL needs none
+    require!(
+        asset_vault.is_native_sol()
+            || (ctx.accounts.user_token_account.is_some()
+                && ctx.accounts.vault_token_account.is_some()
+                && ctx.accounts.mint.is_some()),
+        PrivacyErrorV2::MissingAssetAccount
+    );
+
+    require_queue_room(&pending_buffer, merkle_tree, commitments.len(), timestamp)?;
+    pool_config.require_anonymity_stats(ctx.accounts.anonymity_stats.is_some())?;
+
+    // =========================================================================
+    // 2. VERIFY ONE GROTH16 PROOF PER COMMITMENT
+    // =========================================================================
+
+    let depositor = ctx.accounts.depositor.key();
+    for ((&amount, &commitment), proof_data) in amounts.iter().zip(&commitments).zip(&proofs) {
+        verify_deposit_proof(
+            &ctx.accounts.deposit_vk,
+            pool_key,
+            depositor,
+            commitment,
+            amount,
+            asset_id,
+            proof_data,
+            timestamp,
+        )?;
+        if let Some(proof_stats) = ctx.accounts.proof_stats.as_mut() {
+            proof_stats.record_verified(ProofType::Deposit, timestamp)?;
+        }
+        if let Some(anonymity_stats) = ctx.accounts.anonymity_stats.as_mut() {
+            anonymity_stats.record_deposit(timestamp)?;
+        }
+    }
+    for reservation in ctx.remaining_accounts {
+        consume_commitment_reservation(reservation, &ctx.accounts.depositor, clock.slot)?;
+    }
+
+    // =========================================================================
+    // 3. ONE TRANSFER OF THE TOTAL
+    // =========================================================================
+
+    let vault_token_balance = transfer_to_vault(
+        &ctx.accounts.depositor,
+        vault_info,
+        asset_vault.is_native_sol(),
+        ctx.accounts.vault_token_account.as_mut(),
+        ctx.accounts.user_token_account.as_ref(),
+        ctx.accounts.mint.as_ref(),
+        &ctx.accounts.token_program,
+        &ctx.accounts.system_program,
+        total,
+    )?;
+
+    // =========================================================================
+    // 4. QUEUE COMMITMENTS FOR BATCHED MERKLE INSERTION
+    // =========================================================================
+
+    for &commitment in &commitments {
+        pending_buffer.add_pending(commitment, timestamp)?;
+    }
+    let pending_count = pending_buffer.size();
+
+    // The buffer's data borrow must end before the bounty CPI
+    let crank_bounty = pool_config.crank_bounty_for(commitments.len() as u32);
+    pending_buffer.escrow_bounty(crank_bounty)?;
+    drop(pending_buffer);
+    escrow_crank_bounty(
+        &ctx.accounts.depositor,
+        ctx.accounts.pending_buffer.to_account_info(),
+        &ctx.accounts.system_program,
+        crank_bounty,
+    )?;
+
+    // =========================================================================
+    // 5. UPDATE STATISTICS
+    // =========================================================================
+
+    let coarse_stats = pool_config.has_coarse_liquidity_stats();
+    for &amount in &amounts {
+        asset_vault.record_deposit(amount, timestamp, coarse_stats)?;
+    }
+    if let Some(balance) = vault_token_balance {
+        asset_vault.reconcile(balance)?;
+    }
+    pool_config.record_pending_deposit(timestamp)?;
+
+    for &commitment in &commitments {
+        let sequence = pool_config.next_event_sequence()?;
+        emit!(DepositQueuedEvent {
+            pool: pool_key,
+            commitment,
+            sequence,
+            timestamp,
+        });
+    }
+
+    msg!(
+        "MASP deposit batch queued: commitments={}, pending_count={}",
+        commitments.len(),
+        pending_count
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_total() {
+        let proofs = vec![vec![0u8; 256]; 2];
+        let commitments = [[1u8; 32], [2u8; 32]];
+        assert_eq!(
+            batch_total(&[300, 700], &commitments, &proofs, None).unwrap(),
+            1_000
+        );
+        assert_eq!(
+            batch_total(&[300, 700], &commitments, &proofs, Some(&[vec![], vec![]])).unwrap(),
+            1_000
+        );
+
+        assert_eq!(
+            batch_total(&[], &[], &[], None).unwrap_err(),
+            PrivacyErrorV2::InvalidBatchSize.into()
+        );
+        assert_eq!(
+            batch_total(&[1; 4], &[[1u8; 32]; 4], &vec![vec![0u8; 256]; 4], None).unwrap_err(),
+            PrivacyErrorV2::InvalidBatchSize.into()
+        );
+        assert_eq!(
+            batch_total(&[300], &commitments, &proofs, None).unwrap_err(),
+            PrivacyErrorV2::DepositBatchLengthMismatch.into()
+        );
+        assert_eq!(
+            batch_total(&[300, 700], &commitments, &proofs, Some(&[vec![]])).unwrap_err(),
+            PrivacyErrorV2::DepositBatchLengthMismatch.into()
+        );
+        assert_eq!(
+            batch_total(&[300, 0], &commitments, &proofs, None).unwrap_err(),
+            PrivacyErrorV2::InvalidAmount.into()
+        );
+        assert_eq!(
+            batch_total(&[u64::MAX, 1], &commitments, &proofs, None).unwrap_err(),
+            PrivacyErrorV2::ArithmeticOverflow.into()
+        );
+    }
+}
@@ -0,0 +1,503 @@
+//! Disclosed-Path Withdraw Instructions - pSOL v2
+//!
+//! Withdraws a note without a zero-knowledge proof, for small-tree pools
+//! that want working withdrawals before their withdraw circuit ceremony
+//! completes (see `admin::disclosed_path`).
+//!
+//! # Reduced Privacy
+//!
+//! The note's opening (secret, nullifier, amount, leaf index and Merkle
+//! path) is revealed and checked on-chain: the program recomputes the
+//! commitment and nullifier hash and hashes the path up to a known root,
+//! the statements the withdraw circuit would otherwise prove. Anyone can
+//! then link the withdrawal to its deposit; `DisclosedPathWithdrawn` says
+//! so openly rather than pretending otherwise.
+//!
+//! # Front-running
+//!
+//! Once revealed, the opening could be resubmitted with another recipient.
+//! The relayer first calls `commit_disclosed_withdraw` with the
+//! `disclosed_withdraw_binding` of the opening and the withdrawal terms,
+//! and reveals with `disclosed_path_withdraw` no sooner than
+//! `DISCLOSED_WITHDRAW_MIN_DELAY_SLOTS` later (see
+//! `state::disclosed_withdraw`). The relayer sees the opening, so users
+//! should relay their own withdrawals or use a relayer they trust.
+//!
+//! # Fees
+//!
+//! The relayer fee is bounded as in `withdraw_masp` (see
+//! `utils::withdrawal_checks`, which also applies its minimum, range-check,
+//! same-transaction, yield-exit and risk tier rules), and the protocol fee
+//! is charged the same way (into `treasury_token_account` for tokens, left
+//! in the vault for native SOL). The reveal is not a sealed withdrawal, so
+//! amounts at or above a risk tier's delay threshold cannot leave this way.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+use solana_sdk_ids::sysvar::instructions as sysvar_instructions;
+
+use crate::crypto::{compute_commitment, compute_nullifier_hash};
+use crate::error::PrivacyErrorV2;
+use crate::events::DisclosedPathWithdrawn;
+use crate::instructions::compliance::DisclosedNote;
+use crate::state::{
+    disclosed_withdraw_binding, AssetVault, AssetWithdrawalWindow, DisclosedWithdrawCommitment,
+    GlobalConfig, MerkleTreeV2, PoolConfigV2, RiskTierConfig, SpendContext, SpentNullifierV2,
+    YieldRegistry,
+};
+use crate::utils::{
+    check_not_yield_asset, check_risk_tier_withdrawal, check_withdrawal, pool_clock,
+};
+
+/// Accounts for committing to a disclosed-path withdrawal
+#[derive(Accounts)]
+#[instruction(binding: [u8; 32])]
+pub struct CommitDisclosedWithdraw<'info> {
+    /// Relayer that will reveal the withdrawal (pays the commitment rent)
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        constraint = !pool_config.is_paused @ PrivacyErrorV2::PoolPaused,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Commitment to the withdrawal (PDA, created here)
+    #[account(
+        init,
+        payer = relayer,
+        space = DisclosedWithdrawCommitment::LEN,
+        seeds = [
+            DisclosedWithdrawCommitment::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            binding.as_ref(),
+        ],
+        bump,
+    )]
+    pub disclosed_commitment: Account<'info, DisclosedWithdrawCommitment>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Handler for commit_disclosed_withdraw instruction
+pub fn commit_handler(ctx: Context<CommitDisclosedWithdraw>, binding: [u8; 32]) -> Result<()> {
+    ctx.accounts
+        .pool_config
+        .require_disclosed_path_withdrawals()?;
+
+    let slot = pool_clock(&ctx.accounts.pool_config)?.slot;
+    ctx.accounts.disclosed_commitment.initialize(
+        ctx.accounts.pool_config.key(),
+        ctx.accounts.relayer.key(),
+        binding,
+        slot,
+        ctx.bumps.disclosed_commitment,
+    );
+
+    msg!("Disclosed withdrawal committed at slot {}", slot);
+
+    Ok(())
+}
+
+/// Accounts for withdrawing a disclosed note
+#[derive(Accounts)]
+#[instruction(
+    binding: [u8; 32],
+    nullifier_hash: [u8; 32],
+    asset_id: [u8; 32],
+    recipient: Pubkey,
+)]
+pub struct DisclosedPathWithdraw<'info> {
+    /// Relayer that committed (receives the fee and the commitment rent)
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        mut,
+        constraint = !pool_config.is_paused @ PrivacyErrorV2::PoolPaused,
+        has_one = merkle_tree,
+    )]
+    pub pool_config: Box<Account<'info, PoolConfigV2>>,
+
+    /// Program-wide pause switch
+    /// CHECK: PDA; may not exist yet (see `GlobalConfig::is_paused_account`)
+    #[account(
+        seeds = [GlobalConfig::SEED_PREFIX],
+        bump,
+        constraint = !GlobalConfig::is_paused_account(&global_config)
+            @ PrivacyErrorV2::GlobalPaused,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// Merkle tree account
+    pub merkle_tree: Box<Account<'info, MerkleTreeV2>>,
+
+    /// The relayer's matured commitment to this withdrawal (closed here)
+    #[account(
+        mut,
+        close = relayer,
+        seeds = [
+            DisclosedWithdrawCommitment::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            binding.as_ref(),
+        ],
+        bump = disclosed_commitment.bump,
+        constraint = disclosed_commitment.relayer == relayer.key()
+            @ PrivacyErrorV2::DisclosedWithdrawBindingMismatch,
+    )]
+    pub disclosed_commitment: Box<Account<'info, DisclosedWithdrawCommitment>>,
+
+    /// Asset vault account
+    #[account(
+        mut,
+        seeds = [
+            AssetVault::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        bump = asset_vault.bump,
+        constraint = asset_vault.is_active @ PrivacyErrorV2::AssetNotActive,
+        constraint = asset_vault.withdrawals_enabled @ PrivacyErrorV2::WithdrawalsDisabled,
+    )]
+    pub asset_vault: Box<Account<'info, AssetVault>>,
+
+    /// Vault's token account (source; SPL assets only)
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == asset_vault.token_account
+            @ PrivacyErrorV2::InvalidVaultTokenAccount,
+    )]
+    pub vault_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Recipient's token account (destination; SPL assets only)
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == asset_vault.mint @ PrivacyErrorV2::InvalidMint,
+        constraint = recipient_token_account.owner == recipient @ PrivacyErrorV2::RecipientMismatch,
+    )]
+    pub recipient_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Relayer's token account for the fee (SPL assets only)
+    #[account(
+        mut,
+        constraint = relayer_token_account.mint == asset_vault.mint @ PrivacyErrorV2::InvalidMint,
+        constraint = relayer_token_account.owner == relayer.key() @ PrivacyErrorV2::RelayerMismatch,
+    )]
+    pub relayer_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Recipient's wallet, paid in lamports (native SOL only)
+    /// CHECK: Must be the committed recipient; only credited
+    #[account(
+        mut,
+        constraint = recipient_wallet.key() == recipient @ PrivacyErrorV2::RecipientMismatch,
+    )]
+    pub recipient_wallet: Option<UncheckedAccount<'info>>,
+
+    /// Asset treasury collecting the protocol fee (required while the pool
+    /// charges one on token withdrawals)
+    #[account(
+        mut,
+        seeds = [AssetVault::TREASURY_SEED_PREFIX, asset_vault.key().as_ref()],
+        bump,
+    )]
+    pub treasury_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Asset mint (SPL assets only)
+    #[account(address = asset_vault.mint @ PrivacyErrorV2::InvalidMint)]
+    pub mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    /// Spent nullifier account (PDA, created on first use)
+    #[account(
+        init,
+        payer = relayer,
+        space = SpentNullifierV2::LEN,
+        seeds = [
+            SpentNullifierV2::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            nullifier_hash.as_ref(),
+        ],
+        bump,
+    )]
+    pub spent_nullifier: Box<Account<'info, SpentNullifierV2>>,
+
+    /// Token program owning the mint (SPL assets only)
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Yield registry (required while the pool enforces yield exits)
+    pub yield_registry: Option<Box<Account<'info, YieldRegistry>>>,
+
+    /// CHECK: Address constrained to the instructions sysvar (same-transaction
+    /// deposit/withdraw guard)
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    /// Pool's risk tier table; required once the asset is in a tier
+    #[account(
+        seeds = [RiskTierConfig::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = risk_tiers.bump,
+    )]
+    pub risk_tiers: Option<Box<Account<'info, RiskTierConfig>>>,
+
+    /// Asset's withdrawal window; required while its tier rate-limits
+    /// withdrawals
+    #[account(
+        mut,
+        seeds = [AssetWithdrawalWindow::SEED_PREFIX, asset_vault.key().as_ref()],
+        bump = withdrawal_window.bump,
+    )]
+    pub withdrawal_window: Option<Box<Account<'info, AssetWithdrawalWindow>>>,
+}
+
+/// Handler for disclosed_path_withdraw instruction
+pub fn handler(
+    ctx: Context<DisclosedPathWithdraw>,
+    binding: [u8; 32],
+    nullifier_hash: [u8; 32],
+    asset_id: [u8; 32],
+    recipient: Pubkey,
+    relayer_fee: u64,
+    note: DisclosedNote,
+) -> Result<()> {
+    ctx.accounts
+        .pool_config
+        .require_disclosed_path_withdrawals()?;
+
+    // Same amount, fee, flow and yield rules as a proof withdrawal
+    let amount = note.amount;
+    let pool_key = ctx.accounts.pool_config.key();
+    check_withdrawal(
+        &pool_key,
+        &ctx.accounts.pool_config,
+        &ctx.accounts.asset_vault,
+        &ctx.accounts.instructions_sysvar,
+        amount,
+        relayer_fee,
+    )?;
+    check_not_yield_asset(
+        &ctx.accounts.pool_config,
+        ctx.accounts
+            .yield_registry
+            .as_deref()
+            .map(|registry| &**registry),
+        &asset_id,
+    )?;
+
+    // The reveal must be what the relayer committed to, long enough ago
+    require!(
+        disclosed_withdraw_binding(
+            &pool_key,
+            &note.secret,
+            &nullifier_hash,
+            &asset_id,
+            &recipient,
+            &ctx.accounts.relayer.key(),
+            relayer_fee,
+        ) == binding,
+        PrivacyErrorV2::DisclosedWithdrawBindingMismatch
+    );
+    let clock = pool_clock(&ctx.accounts.pool_config)?;
+    let timestamp = clock.unix_timestamp;
+    ctx.accounts
+        .disclosed_commitment
+        .require_matured(clock.slot)?;
+
+    // Not a sealed withdrawal, so nothing at or above the tier's delay
+    // threshold leaves this way
+    check_risk_tier_withdrawal(
+        &ctx.accounts.asset_vault,
+        ctx.accounts.risk_tiers.as_deref().map(|t| &**t),
+        ctx.accounts
+            .withdrawal_window
+            .as_deref_mut()
+            .map(|w| &mut **w),
+        amount,
+        None,
+        timestamp,
+    )?;
+
+    // What the withdraw circuit would prove: the opening gives the
+    // commitment and the nullifier hash ...
+    let commitment = compute_commitment(&note.secret, &note.nullifier, amount, &asset_id)?;
+    require!(
+        compute_nullifier_hash(&note.nullifier, &note.secret, note.leaf_index)? == nullifier_hash,
+        PrivacyErrorV2::DisclosureMismatch
+    );
+
+    // ... and the commitment is in the tree
+    let root =
+        ctx.accounts
+            .merkle_tree
+            .root_from_path(commitment, note.leaf_index, &note.merkle_path)?;
+    require!(
+        ctx.accounts.merkle_tree.is_known_root(&root),
+        PrivacyErrorV2::InvalidMerkleRoot
+    );
+
+    ctx.accounts.spent_nullifier.initialize(
+        pool_key,
+        nullifier_hash,
+        asset_id,
+        SpendContext::DisclosedPath,
+        timestamp,
+        clock.slot,
+        ctx.accounts.relayer.key(),
+        ctx.bumps.spent_nullifier,
+    );
+
+    let vault_balance = if ctx.accounts.asset_vault.is_native_sol() {
+        AssetVault::native_balance(&ctx.accounts.asset_vault.to_account_info())?
+    } else {
+        ctx.accounts
+            .vault_token_account
+            .as_ref()
+            .ok_or(error!(PrivacyErrorV2::MissingAssetAccount))?
+            .amount
+    };
+    require!(vault_balance >= amount, PrivacyErrorV2::InsufficientBalance);
+
+    let protocol_fee = ctx.accounts.pool_config.protocol_fee_for(amount);
+    let recipient_amount = amount
+        .checked_sub(relayer_fee)
+        .and_then(|rest| rest.checked_sub(protocol_fee))
+        .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+
+    if ctx.accounts.asset_vault.is_native_sol() {
+        pay_native(ctx.accounts, recipient_amount, relayer_fee)?;
+    } else {
+        pay_tokens(
+            ctx.accounts,
+            &asset_id,
+            recipient_amount,
+            relayer_fee,
+            protocol_fee,
+        )?;
+    }
+
+    let coarse_stats = ctx.accounts.pool_config.has_coarse_liquidity_stats();
+    ctx.accounts
+        .asset_vault
+        .record_withdrawal(amount, timestamp, coarse_stats)?;
+    if let Some(vault_token_account) = ctx.accounts.vault_token_account.as_mut() {
+        vault_token_account.reload()?;
+        ctx.accounts.asset_vault.reconcile_debit(
+            vault_balance,
+            vault_token_account.amount,
+            amount,
+        )?;
+    }
+
+    let pool_config = &mut ctx.accounts.pool_config;
+    pool_config.record_withdrawal(timestamp)?;
+    let sequence = pool_config.next_event_sequence()?;
+
+    emit!(DisclosedPathWithdrawn {
+        pool: pool_key,
+        commitment,
+        leaf_index: note.leaf_index,
+        nullifier_hash,
+        asset_id,
+        amount,
+        recipient,
+        relayer: ctx.accounts.relayer.key(),
+        relayer_fee,
+        sequence,
+        timestamp,
+    });
+
+    msg!(
+        "Disclosed-path withdrawal (reduced privacy): leaf {}",
+        note.leaf_index
+    );
+
+    Ok(())
+}
+
+/// Pay the recipient and relayer fee in lamports from the native SOL vault
+fn pay_native(
+    accounts: &DisclosedPathWithdraw,
+    recipient_amount: u64,
+    relayer_fee: u64,
+) -> Result<()> {
+    let vault = accounts.asset_vault.to_account_info();
+    let recipient_wallet = accounts
+        .recipient_wallet
+        .as_ref()
+        .ok_or(error!(PrivacyErrorV2::MissingAssetAccount))?;
+
+    if recipient_amount > 0 {
+        AssetVault::pay_native(&vault, recipient_wallet, recipient_amount)?;
+    }
+    if relayer_fee > 0 {
+        AssetVault::pay_native(&vault, &accounts.relayer, relayer_fee)?;
+    }
+    Ok(())
+}
+
+/// Pay the recipient, relayer fee and protocol fee in tokens from the vault
+/// token account
+fn pay_tokens<'info>(
+    accounts: &DisclosedPathWithdraw<'info>,
+    asset_id: &[u8; 32],
+    recipient_amount: u64,
+    relayer_fee: u64,
+    protocol_fee: u64,
+) -> Result<()> {
+    let (Some(vault_token_account), Some(mint), Some(token_program)) = (
+        accounts.vault_token_account.as_ref(),
+        accounts.mint.as_ref(),
+        accounts.token_program.as_ref(),
+    ) else {
+        return err!(PrivacyErrorV2::MissingAssetAccount);
+    };
+
+    let pool_key = accounts.pool_config.key();
+    let bump = [accounts.asset_vault.bump];
+    let vault_seeds = AssetVault::seeds(&pool_key, asset_id, &bump);
+    let signer_seeds: &[&[&[u8]]] = &[&vault_seeds];
+
+    let transfer = |to: AccountInfo<'info>, amount: u64| -> Result<()> {
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                TransferChecked {
+                    from: vault_token_account.to_account_info(),
+                    mint: mint.to_account_info(),
+                    to,
+                    authority: accounts.asset_vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+            mint.decimals,
+        )
+    };
+
+    if recipient_amount > 0 {
+        let recipient_token_account = accounts
+            .recipient_token_account
+            .as_ref()
+            .ok_or(error!(PrivacyErrorV2::MissingAssetAccount))?;
+        transfer(recipient_token_account.to_account_info(), recipient_amount)?;
+    }
+    if relayer_fee > 0 {
+        let relayer_token_account = accounts
+            .relayer_token_account
+            .as_ref()
+            .ok_or(error!(PrivacyErrorV2::MissingAssetAccount))?;
+        transfer(relayer_token_account.to_account_info(), relayer_fee)?;
+    }
+    if protocol_fee > 0 {
+        let treasury = accounts
+            .treasury_token_account
+            .as_ref()
+            .ok_or(error!(PrivacyErrorV2::TreasuryRequired))?;
+        transfer(treasury.to_account_info(), protocol_fee)?;
+    }
+    Ok(())
+}
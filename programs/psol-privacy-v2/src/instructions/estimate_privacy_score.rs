@@ -0,0 +1,149 @@
+//! Estimate Privacy Score Instruction
+//!
+//! Read-only: a wallet simulates it for the root and asset it is about to
+//! withdraw against and warns the user when the withdrawal would hide among
+//! few notes. The result is returned as return data.
+//!
+//! The estimate only uses public state:
+//! - leaves under the root: tree leaves minus roots recorded since (exact
+//!   for per-leaf insertion; a settled batch records one root, so the set is
+//!   overestimated by the batches' extra leaves)
+//! - the asset's share of all deposits, from the vault counters
+//! - roots recorded since the claimed root, i.e. the distinct insertion
+//!   events after it
+//!
+//! Amounts never reach the chain, so there is no denomination component.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::state::{AssetVault, MerkleTreeV2, PoolConfigV2};
+
+/// Rough anonymity estimate for a withdrawal against one root
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PrivacyScore {
+    /// Roots recorded after the claimed root (0 = current root)
+    pub deposits_since_root: u16,
+    /// Leaves under the claimed root, all assets
+    pub leaves_at_root: u32,
+    /// This asset's share of all deposits, in basis points
+    pub asset_share_bps: u16,
+    /// Estimated notes of this asset under the claimed root
+    pub anonymity_set: u64,
+    /// 0-100, ten points per bit of `anonymity_set` (100 from 512 notes)
+    pub score: u8,
+}
+
+impl PrivacyScore {
+    pub fn compute(
+        deposits_since_root: u16,
+        tree_leaves: u32,
+        total_leaves: u64,
+        asset_deposits: u64,
+    ) -> Self {
+        let leaves_at_root = tree_leaves.saturating_sub(deposits_since_root as u32);
+        let asset_share_bps = asset_deposits
+            .saturating_mul(10_000)
+            .checked_div(total_leaves)
+            .map_or(0, |bps| bps.min(10_000) as u16);
+        let anonymity_set = leaves_at_root as u64 * asset_share_bps as u64 / 10_000;
+        let bits = u64::BITS - anonymity_set.leading_zeros();
+
+        Self {
+            deposits_since_root,
+            leaves_at_root,
+            asset_share_bps,
+            anonymity_set,
+            score: (bits * 10).min(100) as u8,
+        }
+    }
+}
+
+/// Accounts for estimating a withdrawal's privacy score
+#[derive(Accounts)]
+#[instruction(merkle_root: [u8; 32], asset_id: [u8; 32])]
+pub struct EstimatePrivacyScore<'info> {
+    /// Pool configuration account
+    #[account(has_one = merkle_tree)]
+    pub pool_config: Box<Account<'info, PoolConfigV2>>,
+
+    /// Merkle tree account
+    pub merkle_tree: Box<Account<'info, MerkleTreeV2>>,
+
+    /// Asset vault of the asset being withdrawn
+    #[account(
+        seeds = [
+            AssetVault::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        bump = asset_vault.bump,
+    )]
+    pub asset_vault: Box<Account<'info, AssetVault>>,
+}
+
+/// Handler for estimate_privacy_score instruction
+pub fn handler(
+    ctx: Context<EstimatePrivacyScore>,
+    merkle_root: [u8; 32],
+    _asset_id: [u8; 32],
+) -> Result<PrivacyScore> {
+    let merkle_tree = &ctx.accounts.merkle_tree;
+    let deposits_since_root = merkle_tree
+        .root_updates_since(&merkle_root)
+        .ok_or(error!(PrivacyErrorV2::InvalidMerkleRoot))?;
+
+    let score = PrivacyScore::compute(
+        deposits_since_root,
+        merkle_tree.next_leaf_index,
+        merkle_tree.total_leaves,
+        ctx.accounts.asset_vault.deposit_count,
+    );
+
+    msg!(
+        "Privacy score {}: ~{} notes under root ({} since)",
+        score.score,
+        score.anonymity_set,
+        score.deposits_since_root
+    );
+
+    Ok(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_scales_with_anonymity_set() {
+        // Single-asset pool, current root
+        let score = PrivacyScore::compute(0, 1_000, 1_000, 1_000);
+        assert_eq!(score.asset_share_bps, 10_000);
+        assert_eq!(score.anonymity_set, 1_000);
+        assert_eq!(score.score, 100);
+
+        // Quarter of the deposits, 40 roots behind
+        let score = PrivacyScore::compute(40, 1_040, 1_040, 260);
+        assert_eq!(score.leaves_at_root, 1_000);
+        assert_eq!(score.asset_share_bps, 2_500);
+        assert_eq!(score.anonymity_set, 250);
+        assert_eq!(score.score, 80);
+
+        // Only the user's own note
+        assert_eq!(PrivacyScore::compute(0, 1, 1, 1).score, 10);
+    }
+
+    #[test]
+    fn test_score_edge_cases() {
+        // Empty tree
+        let score = PrivacyScore::compute(0, 0, 0, 0);
+        assert_eq!(score.anonymity_set, 0);
+        assert_eq!(score.score, 0);
+
+        // Vault counters ahead of the tree (pending deposits) cap at 100%
+        assert_eq!(PrivacyScore::compute(0, 10, 10, 50).asset_share_bps, 10_000);
+
+        // Inconsistent counters never underflow
+        assert_eq!(PrivacyScore::compute(30, 10, 10, 10).leaves_at_root, 0);
+    }
+}
@@ -0,0 +1,70 @@
+//! Initialize Anonymity Stats Instruction
+//!
+//! Creates the AnonymityStats PDA for one asset of a pool and marks the pool
+//! as keeping statistics (`PoolConfigV2::anonymity_stats_enabled`). From
+//! then on every deposit and withdrawal must pass its asset's account, so
+//! the histogram cannot be skewed by leaving it out; initialize it for each
+//! asset of the pool, and for assets registered later before they are used.
+
+use crate::error::PrivacyErrorV2;
+use crate::state::{AnonymityStats, AssetVault, PoolConfigV2};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitAnonymityStats<'info> {
+    /// Pool authority - must be signer
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool config - validated via has_one
+    #[account(
+        mut,
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Registered asset the statistics cover
+    #[account(
+        seeds = [
+            AssetVault::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_vault.asset_id.as_ref(),
+        ],
+        bump = asset_vault.bump,
+    )]
+    pub asset_vault: Account<'info, AssetVault>,
+
+    /// Anonymity stats - PDA derived from pool_config key and asset id
+    #[account(
+        init,
+        payer = authority,
+        space = AnonymityStats::LEN,
+        seeds = [
+            AnonymityStats::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_vault.asset_id.as_ref(),
+        ],
+        bump,
+    )]
+    pub anonymity_stats: Account<'info, AnonymityStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitAnonymityStats>) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    ctx.accounts.anonymity_stats.initialize(
+        ctx.accounts.pool_config.key(),
+        ctx.accounts.asset_vault.asset_id,
+        ctx.bumps.anonymity_stats,
+        timestamp,
+    );
+    ctx.accounts.pool_config.anonymity_stats_enabled = true;
+
+    msg!(
+        "Anonymity stats initialized for pool {}",
+        ctx.accounts.pool_config.key()
+    );
+    Ok(())
+}
@@ -0,0 +1,49 @@
+//! Initialize Proof Stats Instruction
+//!
+//! Creates the ProofStats PDA for a pool. Once it exists, proof-verifying
+//! instructions that are passed the account record per-type counters.
+
+use crate::error::PrivacyErrorV2;
+use crate::state::{PoolConfigV2, ProofStats};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitProofStats<'info> {
+    /// Pool authority - must be signer
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool config - validated via has_one
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Proof stats - PDA derived from pool_config key
+    #[account(
+        init,
+        payer = authority,
+        space = ProofStats::LEN,
+        seeds = [ProofStats::SEED_PREFIX, pool_config.key().as_ref()],
+        bump,
+    )]
+    pub proof_stats: Account<'info, ProofStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitProofStats>) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    ctx.accounts.proof_stats.initialize(
+        ctx.accounts.pool_config.key(),
+        ctx.bumps.proof_stats,
+        timestamp,
+    );
+
+    msg!(
+        "Proof stats initialized for pool {}",
+        ctx.accounts.pool_config.key()
+    );
+    Ok(())
+}
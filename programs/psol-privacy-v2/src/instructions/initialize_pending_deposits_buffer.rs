@@ -14,8 +14,9 @@ pub struct InitializePendingDepositsBuffer<'info> {
     pub authority: Signer<'info>,
 
     #[account(
-        seeds = [PoolConfigV2::SEED_PREFIX, authority.key().as_ref()],
-        bump = pool_config.bump,
+        // Current or legacy seeds (see `PoolConfigV2::is_pool_address`)
+        constraint = pool_config.is_pool_address(&crate::ID, &pool_config.key(), &authority.key())
+            @ ErrorCode::ConstraintSeeds,
         has_one = authority @ PrivacyErrorV2::InvalidAuthority,
     )]
     pub pool_config: Box<Account<'info, PoolConfigV2>>,
@@ -30,7 +31,7 @@ pub struct InitializePendingDepositsBuffer<'info> {
         ],
         bump
     )]
-    pub pending_buffer: Box<Account<'info, PendingDepositsBuffer>>,
+    pub pending_buffer: AccountLoader<'info, PendingDepositsBuffer>,
 
     pub system_program: Program<'info, System>,
 }
@@ -40,9 +41,11 @@ pub fn handler(ctx: Context<InitializePendingDepositsBuffer>) -> Result<()> {
     let timestamp = clock.unix_timestamp;
 
     let bump = ctx.bumps.pending_buffer;
-    ctx.accounts
-        .pending_buffer
-        .initialize(ctx.accounts.pool_config.key(), bump, timestamp);
+    ctx.accounts.pending_buffer.load_init()?.initialize(
+        ctx.accounts.pool_config.key(),
+        bump,
+        timestamp,
+    );
 
     msg!(
         "Initialized PendingDepositsBuffer for pool: {} (bump={})",
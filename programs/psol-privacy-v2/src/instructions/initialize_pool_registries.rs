@@ -15,8 +15,9 @@ pub struct InitializePoolRegistries<'info> {
 
     #[account(
         mut,
-        seeds = [PoolConfigV2::SEED_PREFIX, authority.key().as_ref()],
-        bump = pool_config.bump,
+        // Current or legacy seeds (see `PoolConfigV2::is_pool_address`)
+        constraint = pool_config.is_pool_address(&crate::ID, &pool_config.key(), &authority.key())
+            @ ErrorCode::ConstraintSeeds,
         has_one = authority @ PrivacyErrorV2::InvalidAuthority,
     )]
     pub pool_config: Box<Account<'info, PoolConfigV2>>,
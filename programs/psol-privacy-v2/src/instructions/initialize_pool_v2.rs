@@ -2,6 +2,14 @@
 //!
 //! Creates pool config and Merkle tree.
 //! Call initialize_pool_registries after this.
+//!
+//! `compliance_recovery` opts the pool into `compliance_recovery_withdraw`
+//! for good: it is recorded in `PoolConfigV2::compliance_recovery_enabled`
+//! and `PoolInitializedV2`, and no instruction changes it afterwards.
+//!
+//! `pool_id` is part of the pool config seeds, so one authority can run
+//! several isolated pools (e.g. one per risk tier), each with its own
+//! tree, registries and vaults.
 
 use anchor_lang::prelude::*;
 
@@ -11,9 +19,10 @@ use crate::state::{
     ComplianceConfig, MerkleTreeV2, PoolConfigV2, RelayerRegistry, MAX_TREE_DEPTH,
     MIN_ROOT_HISTORY_SIZE, MIN_TREE_DEPTH,
 };
+use crate::utils::require_program_id;
 
 #[derive(Accounts)]
-#[instruction(tree_depth: u8, root_history_size: u16)]
+#[instruction(tree_depth: u8, root_history_size: u16, compliance_recovery: bool, pool_id: u64)]
 pub struct InitializePoolV2<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
@@ -22,7 +31,11 @@ pub struct InitializePoolV2<'info> {
         init,
         payer = authority,
         space = PoolConfigV2::LEN,
-        seeds = [PoolConfigV2::SEED_PREFIX, authority.key().as_ref()],
+        seeds = [
+            PoolConfigV2::SEED_PREFIX,
+            authority.key().as_ref(),
+            pool_id.to_le_bytes().as_ref(),
+        ],
         bump,
     )]
     pub pool_config: Box<Account<'info, PoolConfigV2>>,
@@ -43,6 +56,8 @@ pub fn handler(
     ctx: Context<InitializePoolV2>,
     tree_depth: u8,
     root_history_size: u16,
+    compliance_recovery: bool,
+    pool_id: u64,
 ) -> Result<()> {
     require!(
         tree_depth >= MIN_TREE_DEPTH && tree_depth <= MAX_TREE_DEPTH,
@@ -58,6 +73,8 @@ pub fn handler(
     let timestamp = clock.unix_timestamp;
     let pool_bump = ctx.bumps.pool_config;
 
+    require_program_id(ctx.program_id)?;
+
     // Derive registry addresses (will be created in Part 2)
     let (relayer_registry, _) = Pubkey::find_program_address(
         &[
@@ -78,11 +95,13 @@ pub fn handler(
     // Initialize pool config
     ctx.accounts.pool_config.initialize_partial(
         ctx.accounts.authority.key(),
+        pool_id,
         ctx.accounts.merkle_tree.key(),
         tree_depth,
         pool_bump,
         timestamp,
     );
+    ctx.accounts.pool_config.compliance_recovery_enabled = compliance_recovery;
 
     // Initialize Merkle tree
     ctx.accounts.merkle_tree.initialize(
@@ -94,17 +113,22 @@ pub fn handler(
     emit!(PoolInitializedV2 {
         pool: ctx.accounts.pool_config.key(),
         authority: ctx.accounts.authority.key(),
+        pool_id,
         merkle_tree: ctx.accounts.merkle_tree.key(),
         relayer_registry,
         tree_depth,
         root_history_size,
+        compliance_recovery_enabled: compliance_recovery,
+        poseidon_params_hash: ctx.accounts.merkle_tree.poseidon_params_hash,
         timestamp,
     });
 
     msg!(
-        "Initialized pSOL v2 pool (part 1): depth={}, history_size={}",
+        "Initialized pSOL v2 pool (part 1): id={}, depth={}, history_size={}, compliance_recovery={}",
+        pool_id,
         tree_depth,
-        root_history_size
+        root_history_size,
+        compliance_recovery
     );
 
     Ok(())
@@ -0,0 +1,208 @@
+//! Mint Allowlist Instructions
+//!
+//! The authority keeps a list of approved mints and can open registration
+//! of those mints to anyone. `register_allowlisted_asset` then creates the
+//! asset vault exactly as `register_asset` does, paid for by the caller.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::error::PrivacyErrorV2;
+use crate::events::{AssetRegistrationModeUpdated, MintAllowlistUpdated};
+use crate::instructions::register_asset::initialize_asset;
+use crate::state::{AssetVault, MintAllowlist, PoolConfigV2};
+
+/// Accounts for creating the pool's mint allowlist
+#[derive(Accounts)]
+pub struct InitMintAllowlist<'info> {
+    /// Pool authority (must be signer)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Mint allowlist (PDA)
+    #[account(
+        init,
+        payer = authority,
+        space = MintAllowlist::LEN,
+        seeds = [MintAllowlist::SEED_PREFIX, pool_config.key().as_ref()],
+        bump,
+    )]
+    pub mint_allowlist: Account<'info, MintAllowlist>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for curating the allowlist and opening/closing registration
+#[derive(Accounts)]
+pub struct ManageMintAllowlist<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Mint allowlist (PDA)
+    #[account(
+        mut,
+        seeds = [MintAllowlist::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = mint_allowlist.bump,
+    )]
+    pub mint_allowlist: Account<'info, MintAllowlist>,
+}
+
+/// Accounts for registering an allowlisted asset (anyone may sign)
+#[derive(Accounts)]
+#[instruction(asset_id: [u8; 32])]
+pub struct RegisterAllowlistedAsset<'info> {
+    /// Pays for the vault and its token account
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        mut,
+        constraint = !pool_config.is_paused @ PrivacyErrorV2::PoolPaused,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Mint allowlist (PDA)
+    #[account(
+        seeds = [MintAllowlist::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = mint_allowlist.bump,
+    )]
+    pub mint_allowlist: Account<'info, MintAllowlist>,
+
+    /// Token mint for the asset being registered
+    pub mint: Account<'info, Mint>,
+
+    /// Asset vault account (PDA)
+    #[account(
+        init,
+        payer = payer,
+        space = AssetVault::DEFAULT_SPACE,
+        seeds = [
+            AssetVault::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        constraint = asset_id == crate::state::asset_vault::compute_asset_id(&mint.key()) @ PrivacyErrorV2::InvalidAssetId,
+        bump,
+    )]
+    pub asset_vault: Account<'info, AssetVault>,
+
+    /// Token account for the vault (PDA)
+    #[account(
+        init,
+        payer = payer,
+        token::mint = mint,
+        token::authority = asset_vault,
+        seeds = [
+            b"vault_token",
+            asset_vault.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Metaplex metadata PDA for the mint; when given, its symbol and name
+    /// hash are cached in the vault
+    /// CHECK: owner, address and contents are checked by `TokenMetadata::load`
+    pub token_metadata: Option<UncheckedAccount<'info>>,
+}
+
+pub fn init_handler(ctx: Context<InitMintAllowlist>) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+    ctx.accounts.mint_allowlist.initialize(
+        ctx.accounts.pool_config.key(),
+        ctx.bumps.mint_allowlist,
+        timestamp,
+    );
+
+    msg!(
+        "Mint allowlist initialized for pool {}",
+        ctx.accounts.pool_config.key()
+    );
+    Ok(())
+}
+
+pub fn add_handler(ctx: Context<ManageMintAllowlist>, mint: Pubkey) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+    ctx.accounts.mint_allowlist.add_mint(mint, timestamp)?;
+
+    emit!(MintAllowlistUpdated {
+        pool: ctx.accounts.pool_config.key(),
+        mint,
+        allowlisted: true,
+        timestamp,
+    });
+
+    msg!("Allowlisted mint: {}", mint);
+    Ok(())
+}
+
+pub fn remove_handler(ctx: Context<ManageMintAllowlist>, mint: Pubkey) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+    ctx.accounts.mint_allowlist.remove_mint(&mint, timestamp)?;
+
+    emit!(MintAllowlistUpdated {
+        pool: ctx.accounts.pool_config.key(),
+        mint,
+        allowlisted: false,
+        timestamp,
+    });
+
+    msg!("Removed allowlisted mint: {}", mint);
+    Ok(())
+}
+
+pub fn set_open_handler(ctx: Context<ManageMintAllowlist>, open: bool) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+    ctx.accounts
+        .mint_allowlist
+        .set_registration_open(open, timestamp);
+
+    emit!(AssetRegistrationModeUpdated {
+        pool: ctx.accounts.pool_config.key(),
+        open,
+        timestamp,
+    });
+
+    msg!("Permissionless asset registration open: {}", open);
+    Ok(())
+}
+
+/// Handler for register_allowlisted_asset instruction
+pub fn register_handler(ctx: Context<RegisterAllowlistedAsset>, asset_id: [u8; 32]) -> Result<()> {
+    ctx.accounts
+        .mint_allowlist
+        .require_registrable(&ctx.accounts.mint.key())?;
+
+    let timestamp = Clock::get()?.unix_timestamp;
+    initialize_asset(
+        &mut ctx.accounts.pool_config,
+        &mut ctx.accounts.asset_vault,
+        &ctx.accounts.mint.to_account_info(),
+        ctx.accounts.mint.decimals,
+        ctx.accounts.vault_token_account.key(),
+        ctx.bumps.asset_vault,
+        asset_id,
+        ctx.accounts.token_metadata.as_deref(),
+        timestamp,
+    )
+}
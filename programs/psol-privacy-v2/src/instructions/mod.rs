@@ -3,44 +3,101 @@
 
 pub mod admin;
 pub mod batch_process_deposits;
+pub mod batcher_bond;
+pub mod clone_pool;
+pub mod commitment_reservation;
 pub mod compliance;
+pub mod create_one_time_recipient;
+pub mod create_pool;
+pub mod deferred_withdraw_events;
+pub mod deposit_hook;
 pub mod deposit_masp;
+pub mod deposit_masp_batch;
+pub mod disclosed_path_withdraw;
+pub mod estimate_privacy_score;
 pub mod initialize_pending_deposits_buffer;
 pub mod initialize_pool_registries;
 pub mod initialize_pool_registries_v2;
 pub mod initialize_pool_v2;
 pub mod private_transfer;
 pub mod prove_membership;
+pub mod publish_attested_root;
+pub mod record_note_receipt;
 pub mod register_asset;
+pub mod register_assets_batch;
 pub mod relayer;
 pub mod set_verification_key_chunked;
 pub mod set_verification_key_v2;
 pub mod settle_deposits_batch;
 pub mod shielded_cpi;
+pub mod verification_status;
+pub mod verify_account_integrity;
 pub mod withdraw_masp;
 pub mod withdraw_v2;
+pub mod withdrawal_commit;
 
-pub use admin::{ClearPendingBuffer, ResetMerkleTree,
-    AcceptAuthorityTransferV2, CancelAuthorityTransferV2, InitiateAuthorityTransferV2, PausePoolV2,
-    UnpausePoolV2,
+pub use admin::{
+    AcceptAuthorityTransferV2, CancelAuthorityTransferV2, CancelSurplusSweep,
+    CancelTreasuryDisbursement, ClearPendingBuffer, CloseMigrationWindow, CollectTreasuryFees,
+    ExecuteTreasuryDisbursement, InitAssetWithdrawalWindow, InitParameterSnapshot,
+    InitPoolTreasury, InitRiskTiers, InitTreasury, InitializeGlobalConfig,
+    InitiateAuthorityTransferV2, MigrateAssetVault, MigrateMerkleTree, MigratePendingBuffer,
+    MigratePoolConfig, MigrateRelayerNode, MigrateRelayerRegistry, MigrateVerificationKey,
+    OpenMigrationWindow, PauseAllPools, PausePoolV2, PublishParameterSnapshot, RequestSurplusSweep,
+    RequestTreasuryDisbursement, ResetMerkleTree, SetAssetMinWithdrawal, SetAssetRiskTier,
+    SetCrankBounty, SetDisclosedPathWithdrawals, SetMaxEncryptedNoteLen, SetPoolCreationFee,
+    SetPrivacyStrict, SetProtocolFee, SetRiskTierLimits, SetRootEvents, SetSameTxDepositWithdraw,
+    SetSuperGuardian, SetTreeCapacityThresholds, SweepSurplus, TreasuryBalance, UnpauseAllPools,
+    UnpausePoolV2, ViewTreasury,
 };
 pub use batch_process_deposits::BatchProcessDeposits;
-pub use compliance::{AttachAuditMetadata, ConfigureCompliance};
+pub use batcher_bond::{ExitBatcher, RegisterBatcher, ReportBatcherMisbehavior};
+pub use clone_pool::{CloneAssetConfig, ClonePoolConfig};
+pub use commitment_reservation::{ReleaseCommitmentReservation, ReserveCommitment};
+pub use compliance::{
+    AttachAuditMetadata, ComplianceRecoveryWithdraw, ConfigureCompliance, DisclosedNote,
+};
+pub use create_one_time_recipient::CreateOneTimeRecipient;
+pub use create_pool::CreatePool;
+pub use deferred_withdraw_events::{
+    FlushDeferredWithdrawEvents, InitDeferredWithdrawEvents, SetWithdrawEventDelay,
+};
+pub use deposit_hook::{RegisterDepositHook, RemoveDepositHook};
 pub use deposit_masp::DepositMasp;
+pub use deposit_masp_batch::DepositMaspBatch;
+pub use disclosed_path_withdraw::{CommitDisclosedWithdraw, DisclosedPathWithdraw};
+pub use estimate_privacy_score::{EstimatePrivacyScore, PrivacyScore};
 pub use initialize_pending_deposits_buffer::*;
 pub use initialize_pool_registries::InitializePoolRegistries;
 pub use initialize_pool_registries_v2::InitializePoolRegistriesV2;
 pub use initialize_pool_v2::InitializePoolV2;
 pub use private_transfer::PrivateTransferJoinSplit;
 pub use prove_membership::ProveMembership;
-pub use register_asset::RegisterAsset;
-pub use relayer::{ConfigureRelayerRegistry, DeactivateRelayer, RegisterRelayer, UpdateRelayer};
+pub use publish_attested_root::PublishAttestedRoot;
+pub use record_note_receipt::RecordNoteReceipt;
+pub use register_asset::{RegisterAsset, RegisterNativeSolAsset};
+pub use register_assets_batch::RegisterAssetsBatch;
+pub use relayer::{
+    ClaimGasRebate, CloseRelayer, ConfigureRelayerRegistry, DeactivateRelayer, FundRentReserve,
+    InitRelayerFeeQuote, InitRelayerIndexShard, InitRelayerRebateLedger, InitRelayerStakeVault,
+    InitRentAdvance, InitRentReserve, MoveRelayerStake, RegisterRelayer, RepayRentAdvance,
+    ReportRelayerFailure, SetAssetRelayerFeeBounds, SetGasRebateCap, SetRelayerCooldown,
+    SetRelayerDraining, SetRelayerRegistrationsOpen, SetRelayerStakeMint, SetRentAdvanceCap,
+    SlashRelayer, UnstakeRelayer, UpdateRelayer, UpdateRelayerFeeQuote,
+    UpdateRelayerPriorityFeeHint, UpdateRelayerSigned,
+};
 pub use set_verification_key_chunked::{AppendVkIcV2, FinalizeVkV2, InitializeVkV2};
-pub use set_verification_key_v2::{LockVerificationKeyV2, SetVerificationKeyV2};
+pub use set_verification_key_v2::{
+    CreateVerificationKeyAccount, LockVerificationKeyV2, RecordVkCeremony, SetVerificationKeyData,
+    SubmitCanaryProof,
+};
 pub use settle_deposits_batch::*;
-pub use shielded_cpi::ExecuteShieldedAction;
+pub use shielded_cpi::{ExecuteShieldedAction, InitActionAllowlist, ManageActionAllowlist};
+pub use verification_status::{VerificationOutcome, VerificationStatus, ROOT_AGE_UNKNOWN};
+pub use verify_account_integrity::{IntegrityTarget, VerifyAccountIntegrity};
 pub use withdraw_masp::WithdrawMasp;
 pub use withdraw_v2::WithdrawV2;
+pub use withdrawal_commit::{CancelWithdrawalCommit, CommitWithdrawal};
 
 pub mod withdraw_yield_v2;
 pub use withdraw_yield_v2::WithdrawYieldV2;
@@ -51,5 +108,21 @@ pub use init_yield_registry::InitYieldRegistry;
 pub mod manage_yield_mints;
 pub use manage_yield_mints::ManageYieldMints;
 
+pub mod mint_allowlist;
+pub use mint_allowlist::{InitMintAllowlist, ManageMintAllowlist, RegisterAllowlistedAsset};
+
+pub mod pool_directory;
+pub use pool_directory::{InitPoolDirectory, ListPool, RefreshPoolListing};
+
+pub mod init_proof_stats;
+pub use init_proof_stats::InitProofStats;
+pub mod init_anonymity_stats;
+pub use init_anonymity_stats::InitAnonymityStats;
+
+pub mod set_circuit_descriptor;
+pub use set_circuit_descriptor::{
+    SetCircuitDescriptor, UpdateCircuitDescriptor, ViewPoseidonParams,
+};
+
 pub mod set_feature_flags;
 pub use set_feature_flags::SetFeatureFlags;
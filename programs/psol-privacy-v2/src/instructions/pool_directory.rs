@@ -0,0 +1,150 @@
+//! Pool Directory Instructions
+//!
+//! - `init_pool_directory`: create the program-wide directory (anyone, once)
+//! - `list_pool`: the pool authority appends its pool to the directory
+//! - `refresh_pool_listing`: copy a listed pool's current asset count into
+//!   its entry (anyone)
+//!
+//! See `state::pool_directory` for how clients read the directory.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::events::PoolListed;
+use crate::state::{PoolConfigV2, PoolDirectory, PoolDirectoryEntry};
+
+/// Accounts for creating the pool directory
+#[derive(Accounts)]
+pub struct InitPoolDirectory<'info> {
+    /// Pays rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Pool directory (PDA)
+    #[account(
+        init,
+        payer = payer,
+        space = PoolDirectory::LEN,
+        seeds = [PoolDirectory::SEED_PREFIX],
+        bump,
+    )]
+    pub pool_directory: Account<'info, PoolDirectory>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for listing a pool
+#[derive(Accounts)]
+pub struct ListPool<'info> {
+    /// Pool authority (must be signer, pays rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        mut,
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+        constraint = !pool_config.directory_listed @ PrivacyErrorV2::PoolAlreadyListed,
+    )]
+    pub pool_config: Box<Account<'info, PoolConfigV2>>,
+
+    /// Pool directory
+    #[account(
+        mut,
+        seeds = [PoolDirectory::SEED_PREFIX],
+        bump = pool_directory.bump,
+    )]
+    pub pool_directory: Account<'info, PoolDirectory>,
+
+    /// New directory entry (PDA at the directory's next index)
+    #[account(
+        init,
+        payer = authority,
+        space = PoolDirectoryEntry::LEN,
+        seeds = [
+            PoolDirectoryEntry::SEED_PREFIX,
+            pool_directory.pool_count.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub directory_entry: Account<'info, PoolDirectoryEntry>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for refreshing a directory entry
+#[derive(Accounts)]
+pub struct RefreshPoolListing<'info> {
+    /// Listed pool
+    pub pool_config: Box<Account<'info, PoolConfigV2>>,
+
+    /// The pool's directory entry
+    #[account(
+        mut,
+        seeds = [
+            PoolDirectoryEntry::SEED_PREFIX,
+            directory_entry.index.to_le_bytes().as_ref(),
+        ],
+        bump = directory_entry.bump,
+        constraint = directory_entry.pool == pool_config.key() @ PrivacyErrorV2::InvalidPoolReference,
+    )]
+    pub directory_entry: Account<'info, PoolDirectoryEntry>,
+}
+
+/// Handler for init_pool_directory instruction
+pub fn init_handler(ctx: Context<InitPoolDirectory>) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+    ctx.accounts
+        .pool_directory
+        .initialize(ctx.bumps.pool_directory, timestamp);
+
+    msg!("Pool directory initialized");
+
+    Ok(())
+}
+
+/// Handler for list_pool instruction
+pub fn list_handler(ctx: Context<ListPool>) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+    let pool_config = &mut ctx.accounts.pool_config;
+    let index = ctx.accounts.pool_directory.next_index(timestamp)?;
+
+    let entry = &mut ctx.accounts.directory_entry;
+    entry.index = index;
+    entry.pool = pool_config.key();
+    entry.authority = pool_config.authority;
+    entry.merkle_tree = pool_config.merkle_tree;
+    entry.tree_depth = pool_config.tree_depth;
+    entry.asset_count = pool_config.registered_asset_count;
+    entry.created_at = pool_config.created_at;
+    entry.listed_at = timestamp;
+    entry.updated_at = timestamp;
+    entry.bump = ctx.bumps.directory_entry;
+    entry._reserved = [0u8; 32];
+
+    pool_config.directory_listed = true;
+
+    emit!(PoolListed {
+        pool: entry.pool,
+        authority: entry.authority,
+        index,
+        merkle_tree: entry.merkle_tree,
+        asset_count: entry.asset_count,
+        timestamp,
+    });
+
+    msg!("Pool listed in directory at index {}", index);
+
+    Ok(())
+}
+
+/// Handler for refresh_pool_listing instruction
+pub fn refresh_handler(ctx: Context<RefreshPoolListing>) -> Result<()> {
+    let entry = &mut ctx.accounts.directory_entry;
+    entry.asset_count = ctx.accounts.pool_config.registered_asset_count;
+    entry.updated_at = Clock::get()?.unix_timestamp;
+
+    Ok(())
+}
@@ -1,39 +1,82 @@
 //! Private Transfer (Join-Split) Instruction
 //!
 //! Performs private transfers within the shielded pool using the join-split circuit.
-//! Supports N inputs to M outputs with optional public inflow/outflow.
+//! The circuit is fixed at 2 inputs to 2 outputs, with optional public
+//! inflow/outflow; a transaction passes exactly two of each.
 //!
-//! # Implementation Status
-//!
-//! This instruction is reserved for pSOL v2.1 and is NOT LIVE yet.
-//! The join-split circuit has not been finalized, so this handler returns
-//! `NotImplemented` after performing basic state validation.
-//!
-//! When the circuit is ready, this will enable:
+//! This enables:
 //! - Internal shielded transfers (no public flow)
 //! - Combined deposit + split
 //! - Combined merge + withdrawal
 //! - Multi-party private payments
+//!
+//! # Nullifier accounts
+//!
+//! The inputs' `SpentNullifierV2` PDAs are passed as remaining accounts,
+//! one per entry of `input_nullifiers` and in the same order, and created
+//! here (see `utils::spend_nullifier`). The relayer pays their rent.
+//!
+//! # Public flows
+//!
+//! The proof binds the submitting `relayer` and an outflow `recipient`. A
+//! positive `public_amount` is pulled from the relayer's token account into
+//! the vault. A negative one (which includes `relayer_fee`) is paid from
+//! the vault to `recipient_token_account`, which the recipient must own,
+//! less the relayer fee (to the relayer's token account) and the pool's
+//! protocol fee (to the asset's treasury, `treasury_token_account`), as in
+//! `withdraw_masp`. Public flows need a token vault (SPL Token or
+//! Token-2022); native SOL assets support pure private transfers only.
+//!
+//! An outflow is a withdrawal and meets the same rules as `withdraw_masp`
+//! (see `utils::withdrawal_checks`); an inflow is a deposit. Each is
+//! counted as one in the pool and the asset's anonymity stats, and for the
+//! same-transaction deposit/withdraw guard. Both are held to the asset's
+//! risk tier, if it is in one; an outflow is not a sealed withdrawal, so
+//! none at or above the tier's delay threshold goes through.
+//!
+//! Inputs may be proven against a pre-migration root while a migration
+//! window is open, as for withdrawals.
+//!
+//! Output commitments go straight into the Merkle tree (not the pending
+//! buffer), so `JoinSplitEvent` carries their leaf indices.
+//!
+//! A registered relayer passes its `RelayerNode` to have the transfer
+//! counted toward its activity and reputation. A draining node takes an
+//! outflow only against its drain allowance, as in `withdraw_masp`.
+//!
+//! Verified proofs are counted in the pool's `ProofStats` when it is
+//! passed; a rejected one emits `ProofVerificationFailed`.
 
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+use solana_sdk_ids::sysvar::instructions as sysvar_instructions;
 
+use crate::crypto::JoinSplitPublicInputs;
 use crate::error::PrivacyErrorV2;
+use crate::events::{
+    CommitmentInsertedEvent, JoinSplitEvent, ProofVerificationFailed, RootUpdatedEvent,
+};
+use crate::instructions::withdraw_masp::recipient_amount;
+use crate::state::migration_window::is_withdraw_root_accepted;
 use crate::state::{
-    AssetVault, MerkleTreeV2, PoolConfigV2, RelayerRegistry, VerificationKeyAccountV2,
+    AnonymityStats, AssetVault, AssetWithdrawalWindow, GlobalConfig, MerkleTreeV2, MigrationWindow,
+    PoolConfigV2, ProofStats, RelayerNode, RelayerRegistry, RiskTierConfig, SpendContext,
+    VerificationKeyAccountV2, YieldRegistry,
+};
+use crate::tx_limits::require_remaining_accounts_exact;
+use crate::utils::{
+    check_not_yield_asset, check_risk_tier_withdrawal, check_withdrawal, pool_clock,
+    require_program_id, spend_nullifier, PoolFlow,
 };
 use crate::ProofType;
 
-/// Maximum number of input nullifiers (design target)
-pub const MAX_INPUTS: usize = 2;
+/// Number of input nullifiers the join-split circuit takes
+pub const JOIN_SPLIT_INPUTS: usize = 2;
 
-/// Maximum number of output commitments (design target)
-pub const MAX_OUTPUTS: usize = 2;
+/// Number of output commitments the join-split circuit takes
+pub const JOIN_SPLIT_OUTPUTS: usize = 2;
 
 /// Accounts for private transfer (join-split)
-///
-/// The account structure is complete and ready for when the circuit is deployed.
-/// All accounts are validated per the v2 design specification.
 #[derive(Accounts)]
 #[instruction(
     proof_data: Vec<u8>,
@@ -41,11 +84,13 @@ pub const MAX_OUTPUTS: usize = 2;
     input_nullifiers: Vec<[u8; 32]>,
     output_commitments: Vec<[u8; 32]>,
     public_amount: i64,
+    recipient: Pubkey,
     asset_id: [u8; 32],
     relayer_fee: u64,
 )]
 pub struct PrivateTransferJoinSplit<'info> {
-    /// Relayer submitting the transaction
+    /// Relayer submitting the transaction (pays nullifier rent, bound by
+    /// the proof)
     #[account(mut)]
     pub relayer: Signer<'info>,
 
@@ -56,23 +101,35 @@ pub struct PrivateTransferJoinSplit<'info> {
         has_one = merkle_tree,
         has_one = relayer_registry,
     )]
-    pub pool_config: Account<'info, PoolConfigV2>,
+    pub pool_config: Box<Account<'info, PoolConfigV2>>,
 
-    /// Merkle tree account
+    /// Program-wide pause switch
+    /// CHECK: PDA; may not exist yet (see `GlobalConfig::is_paused_account`)
     #[account(
-        mut,
-        constraint = merkle_tree.is_known_root(&merkle_root) @ PrivacyErrorV2::InvalidMerkleRoot,
+        seeds = [GlobalConfig::SEED_PREFIX],
+        bump,
+        constraint = !GlobalConfig::is_paused_account(&global_config)
+            @ PrivacyErrorV2::GlobalPaused,
     )]
-    pub merkle_tree: Account<'info, MerkleTreeV2>,
+    pub global_config: UncheckedAccount<'info>,
+
+    /// Merkle tree account
+    #[account(mut)]
+    pub merkle_tree: Box<Account<'info, MerkleTreeV2>>,
 
     /// Verification key for join-split proofs
     #[account(
         seeds = [ProofType::JoinSplit.as_seed(), pool_config.key().as_ref()],
         bump = vk_account.bump,
+        constraint = vk_account.is_initialized @ PrivacyErrorV2::VerificationKeyNotSet,
+        constraint = vk_account.proof_type == ProofType::JoinSplit as u8
+            @ PrivacyErrorV2::InvalidVerificationKeyType,
+        constraint = !pool_config.requires_vk_canary() || vk_account.canary_passed
+            @ PrivacyErrorV2::VkCanaryNotPassed,
     )]
-    pub vk_account: Account<'info, VerificationKeyAccountV2>,
+    pub vk_account: Box<Account<'info, VerificationKeyAccountV2>>,
 
-    /// Asset vault account (needed for public flows)
+    /// Asset vault account
     #[account(
         mut,
         seeds = [
@@ -83,76 +140,182 @@ pub struct PrivateTransferJoinSplit<'info> {
         bump = asset_vault.bump,
         constraint = asset_vault.is_active @ PrivacyErrorV2::AssetNotActive,
     )]
-    pub asset_vault: Account<'info, AssetVault>,
+    pub asset_vault: Box<Account<'info, AssetVault>>,
 
-    /// Vault token account
+    /// Vault token account (required when `public_amount` is non-zero)
     #[account(
         mut,
-        constraint = vault_token_account.key() == asset_vault.token_account @ PrivacyErrorV2::InvalidOwner,
+        constraint = vault_token_account.key() == asset_vault.token_account
+            @ PrivacyErrorV2::InvalidVaultTokenAccount,
     )]
-    pub vault_token_account: Account<'info, TokenAccount>,
+    pub vault_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
-    /// Relayer's token account for fee
+    /// Relayer's token account, source of an inflow and paid the relayer
+    /// fee of an outflow (required when `public_amount` is non-zero)
     #[account(
         mut,
         constraint = relayer_token_account.mint == asset_vault.mint @ PrivacyErrorV2::InvalidMint,
+        constraint = relayer_token_account.owner == relayer.key() @ PrivacyErrorV2::RelayerMismatch,
     )]
-    pub relayer_token_account: Account<'info, TokenAccount>,
+    pub relayer_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
     /// Relayer registry
-    pub relayer_registry: Account<'info, RelayerRegistry>,
+    pub relayer_registry: Box<Account<'info, RelayerRegistry>>,
 
-    /// Token program
-    pub token_program: Program<'info, Token>,
+    /// Token program owning the mint (SPL Token or Token-2022)
+    pub token_program: Interface<'info, TokenInterface>,
 
     /// System program
     pub system_program: Program<'info, System>,
-    // Note: Spent nullifier accounts will be passed as remaining_accounts
-    // when the circuit is deployed
+
+    /// The relayer's registered node, credited with the transfer (optional)
+    #[account(
+        mut,
+        seeds = [
+            RelayerNode::SEED_PREFIX,
+            relayer_registry.key().as_ref(),
+            relayer.key().as_ref(),
+        ],
+        bump = relayer_node.bump,
+        constraint = relayer_node.is_active @ PrivacyErrorV2::RelayerNotActive,
+    )]
+    pub relayer_node: Option<Box<Account<'info, RelayerNode>>>,
+
+    /// Asset mint (required when `public_amount` is non-zero)
+    #[account(address = asset_vault.mint @ PrivacyErrorV2::InvalidMint)]
+    pub mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    /// Yield registry (required for an outflow while the pool enforces
+    /// yield exits)
+    pub yield_registry: Option<Box<Account<'info, YieldRegistry>>>,
+
+    /// CHECK: Address constrained to the instructions sysvar (same-transaction
+    /// deposit/withdraw guard)
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    /// Asset treasury collecting the protocol fee (required for an outflow
+    /// while the pool charges one)
+    #[account(
+        mut,
+        seeds = [AssetVault::TREASURY_SEED_PREFIX, asset_vault.key().as_ref()],
+        bump,
+    )]
+    pub treasury_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Pool's risk tier table; required for a public flow once the asset is
+    /// in a tier
+    #[account(
+        seeds = [RiskTierConfig::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = risk_tiers.bump,
+    )]
+    pub risk_tiers: Option<Box<Account<'info, RiskTierConfig>>>,
+
+    /// Asset's withdrawal window; required for an outflow while its tier
+    /// rate-limits withdrawals
+    #[account(
+        mut,
+        seeds = [AssetWithdrawalWindow::SEED_PREFIX, asset_vault.key().as_ref()],
+        bump = withdrawal_window.bump,
+    )]
+    pub withdrawal_window: Option<Box<Account<'info, AssetWithdrawalWindow>>>,
+
+    /// Per-asset deposit-to-withdrawal latency statistics (required for a
+    /// public flow once the pool keeps them)
+    #[account(
+        mut,
+        seeds = [
+            AnonymityStats::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        bump = anonymity_stats.bump,
+    )]
+    pub anonymity_stats: Option<Box<Account<'info, AnonymityStats>>>,
+
+    /// Pre-migration roots accepted while a migration window is open
+    /// (optional)
+    #[account(
+        seeds = [MigrationWindow::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = migration_window.bump,
+    )]
+    pub migration_window: Option<Box<Account<'info, MigrationWindow>>>,
+
+    /// Token account of the proof's `recipient`, paid an outflow (required
+    /// when `public_amount` is negative)
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == asset_vault.mint @ PrivacyErrorV2::InvalidMint,
+        constraint = recipient_token_account.owner == recipient @ PrivacyErrorV2::RecipientMismatch,
+    )]
+    pub recipient_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Optional: per-proof-type verification counters
+    #[account(
+        mut,
+        seeds = [ProofStats::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = proof_stats.bump,
+    )]
+    pub proof_stats: Option<Box<Account<'info, ProofStats>>>,
+    // Remaining accounts: one spent nullifier PDA (writable) per input
 }
 
 /// Handler for private_transfer_join_split instruction
 ///
-/// # Status: NOT IMPLEMENTED
-///
-/// This handler performs basic state validation but returns `NotImplemented`
-/// because the join-split ZK circuit is not yet finalized. Once the circuit
-/// is deployed and VK is set, this instruction will be enabled.
-///
-/// # Future Behavior
-///
-/// When implemented, this will:
 /// 1. Verify the Groth16 join-split proof
 /// 2. Mark all input nullifiers as spent
-/// 3. Handle public inflows/outflows if public_amount != 0
-/// 4. Insert output commitments into the Merkle tree
-/// 5. Pay relayer fee from the public outflow
+/// 3. Insert output commitments into the Merkle tree
+/// 4. Move tokens for a non-zero `public_amount`
 #[allow(clippy::too_many_arguments)]
-pub fn handler(
-    ctx: Context<PrivateTransferJoinSplit>,
-    _proof_data: Vec<u8>,
-    _merkle_root: [u8; 32],
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, PrivateTransferJoinSplit<'info>>,
+    proof_data: Vec<u8>,
+    merkle_root: [u8; 32],
     input_nullifiers: Vec<[u8; 32]>,
     output_commitments: Vec<[u8; 32]>,
-    _public_amount: i64,
+    public_amount: i64,
+    recipient: Pubkey,
     asset_id: [u8; 32],
-    _relayer_fee: u64,
-    _encrypted_outputs: Option<Vec<Vec<u8>>>,
+    relayer_fee: u64,
+    encrypted_outputs: Option<Vec<Vec<u8>>>,
 ) -> Result<()> {
     // =========================================================================
-    // BASIC STATE VALIDATION
-    // These checks verify the instruction could succeed if circuits were ready
+    // INPUT VALIDATION (fail fast before any state changes)
     // =========================================================================
 
-    // Validate input counts
+    // The circuit is 2-in-2-out; any other shape cannot verify
     require!(
-        !input_nullifiers.is_empty() && input_nullifiers.len() <= MAX_INPUTS,
+        input_nullifiers.len() == JOIN_SPLIT_INPUTS,
         PrivacyErrorV2::TooManyNullifiers
     );
     require!(
-        !output_commitments.is_empty() && output_commitments.len() <= MAX_OUTPUTS,
+        output_commitments.len() == JOIN_SPLIT_OUTPUTS,
         PrivacyErrorV2::TooManyOutputs
     );
+    require_remaining_accounts_exact(
+        "private_transfer_join_split",
+        ctx.remaining_accounts.len(),
+        input_nullifiers.len(),
+    )?;
+
+    require!(
+        crate::crypto::is_valid_proof_length(&proof_data),
+        PrivacyErrorV2::InvalidProofFormat
+    );
+
+    // At most one encrypted note per output, each within the pool's size
+    // limit
+    if let Some(notes) = &encrypted_outputs {
+        require!(
+            notes.len() <= output_commitments.len(),
+            PrivacyErrorV2::TooManyOutputs
+        );
+    }
+    for note in encrypted_outputs.iter().flatten() {
+        ctx.accounts
+            .pool_config
+            .require_encrypted_note_size(Some(note))?;
+    }
 
     // Validate asset ID matches
     require!(
@@ -160,22 +323,345 @@ pub fn handler(
         PrivacyErrorV2::AssetIdMismatch
     );
 
-    // Check join-split feature is enabled in pool config
     ctx.accounts.pool_config.require_join_split_enabled()?;
-
-    // Check VK is configured (even though we won't use it yet)
     ctx.accounts
         .pool_config
         .require_vk_configured(ProofType::JoinSplit)?;
 
+    ctx.accounts.asset_vault.validate_relayer_fee(relayer_fee)?;
+
+    // Non-zero roots, nullifiers and commitments, unique nullifiers, and a
+    // fee covered by any outflow
+    let public_inputs = JoinSplitPublicInputs::new(
+        merkle_root,
+        asset_id,
+        input_nullifiers.clone(),
+        output_commitments.clone(),
+        public_amount,
+        recipient,
+        ctx.accounts.relayer.key(),
+        relayer_fee,
+    );
+    public_inputs.validate()?;
+    // The fee is paid out of an outflow; there is nothing to take it from
+    // otherwise
+    require!(
+        relayer_fee == 0 || public_inputs.is_withdrawal(),
+        PrivacyErrorV2::RelayerFeeExceedsAmount
+    );
+
+    let clock = pool_clock(&ctx.accounts.pool_config)?;
+    let timestamp = clock.unix_timestamp;
+    let slot = clock.slot;
+
+    require!(
+        is_withdraw_root_accepted(
+            &ctx.accounts.merkle_tree,
+            ctx.accounts.migration_window.as_deref().map(|w| &**w),
+            &merkle_root,
+            timestamp,
+        ),
+        PrivacyErrorV2::InvalidMerkleRoot
+    );
+
+    if public_amount != 0 {
+        let vault_token_account = ctx
+            .accounts
+            .vault_token_account
+            .as_ref()
+            .ok_or(error!(PrivacyErrorV2::MissingAssetAccount))?;
+        require!(
+            ctx.accounts.relayer_token_account.is_some()
+                && ctx.accounts.mint.is_some()
+                && (public_inputs.is_deposit() || ctx.accounts.recipient_token_account.is_some()),
+            PrivacyErrorV2::MissingAssetAccount
+        );
+        ctx.accounts
+            .pool_config
+            .require_anonymity_stats(ctx.accounts.anonymity_stats.is_some())?;
+        let pool_key = ctx.accounts.pool_config.key();
+        let tier_limits = RiskTierConfig::asset_limits(
+            ctx.accounts.risk_tiers.as_deref().map(|t| &**t),
+            &ctx.accounts.asset_vault,
+        )?;
+        if public_inputs.is_deposit() {
+            ctx.accounts.asset_vault.require_deposits_enabled()?;
+            if let Some(limits) = &tier_limits {
+                limits.check_deposit(public_amount.unsigned_abs())?;
+            }
+            ctx.accounts.pool_config.require_no_same_tx_flow(
+                &pool_key,
+                &ctx.accounts.instructions_sysvar,
+                PoolFlow::Deposit,
+            )?;
+        } else {
+            ctx.accounts.asset_vault.require_withdrawals_enabled()?;
+            // The outflow is a withdrawal of its full amount, fee included
+            check_withdrawal(
+                &pool_key,
+                &ctx.accounts.pool_config,
+                &ctx.accounts.asset_vault,
+                &ctx.accounts.instructions_sysvar,
+                public_amount.unsigned_abs(),
+                relayer_fee,
+            )?;
+            check_not_yield_asset(
+                &ctx.accounts.pool_config,
+                ctx.accounts
+                    .yield_registry
+                    .as_deref()
+                    .map(|registry| &**registry),
+                &asset_id,
+            )?;
+            require!(
+                vault_token_account.amount >= public_amount.unsigned_abs(),
+                PrivacyErrorV2::InsufficientBalance
+            );
+            check_risk_tier_withdrawal(
+                &ctx.accounts.asset_vault,
+                ctx.accounts.risk_tiers.as_deref().map(|t| &**t),
+                ctx.accounts
+                    .withdrawal_window
+                    .as_deref_mut()
+                    .map(|w| &mut **w),
+                public_amount.unsigned_abs(),
+                None,
+                timestamp,
+            )?;
+            if let Some(relayer_node) = ctx.accounts.relayer_node.as_ref() {
+                require!(
+                    relayer_node.accepts_withdrawals(),
+                    PrivacyErrorV2::RelayerDraining
+                );
+            }
+        }
+    }
+
+    ctx.accounts
+        .merkle_tree
+        .require_capacity_for(output_commitments.len())?;
+
     // =========================================================================
-    // FEATURE NOT YET IMPLEMENTED
-    // The join-split circuit is reserved for v2.1
+    // PROOF VERIFICATION (before any state changes)
     // =========================================================================
 
-    msg!("Join-split private transfers are reserved for pSOL v2.1");
-    msg!("This feature requires the join-split ZK circuit which is not yet deployed");
-    msg!("Use deposit_masp and withdraw_masp for current privacy operations");
+    let field_elements = public_inputs.to_field_elements();
+    let vk = &ctx.accounts.vk_account;
+    let is_valid = crate::crypto::verify_proof_from_account(
+        &vk.vk_alpha_g1,
+        &vk.vk_beta_g2,
+        &vk.vk_gamma_g2,
+        &vk.vk_delta_g2,
+        &vk.vk_ic,
+        &proof_data,
+        &field_elements,
+    )?;
+
+    if !is_valid {
+        emit!(ProofVerificationFailed {
+            pool: ctx.accounts.pool_config.key(),
+            proof_type: ProofType::JoinSplit as u8,
+            submitter: ctx.accounts.relayer.key(),
+            timestamp,
+        });
+        return err!(PrivacyErrorV2::InvalidProof);
+    }
+    if let Some(proof_stats) = ctx.accounts.proof_stats.as_mut() {
+        proof_stats.record_verified(ProofType::JoinSplit, timestamp)?;
+    }
+
+    // =========================================================================
+    // STATE CHANGES (only after proof verification succeeds)
+    // =========================================================================
+
+    // Mark every input nullifier as spent
+    require_program_id(ctx.program_id)?;
+    let pool_key = ctx.accounts.pool_config.key();
+    for (nullifier_hash, account) in input_nullifiers.iter().zip(ctx.remaining_accounts) {
+        spend_nullifier(
+            &ctx.accounts.relayer,
+            ctx.accounts.relayer.key(),
+            &ctx.accounts.system_program,
+            account,
+            pool_key,
+            *nullifier_hash,
+            asset_id,
+            SpendContext::JoinSplit,
+            timestamp,
+            slot,
+        )?;
+    }
+
+    // Insert output commitments
+    let merkle_tree = &mut ctx.accounts.merkle_tree;
+    let mut output_leaf_indices = [0u32; JOIN_SPLIT_OUTPUTS];
+    for (commitment, leaf_index) in output_commitments.iter().zip(&mut output_leaf_indices) {
+        *leaf_index = merkle_tree.insert_leaf(*commitment, timestamp)?;
+    }
+    let new_root = merkle_tree.get_current_root();
+    for (commitment, leaf_index) in output_commitments.iter().zip(output_leaf_indices) {
+        emit!(CommitmentInsertedEvent {
+            pool: pool_key,
+            commitment: *commitment,
+            leaf_index,
+            merkle_root: new_root,
+            timestamp,
+        });
+    }
+    if let Some(sequence) = ctx.accounts.pool_config.next_root_event_sequence()? {
+        emit!(RootUpdatedEvent {
+            pool: pool_key,
+            root: new_root,
+            leaf_count: ctx.accounts.merkle_tree.next_leaf_index,
+            slot,
+            sequence,
+        });
+    }
+
+    // Settle the public flow
+    if public_amount != 0 {
+        settle_public_amount(ctx.accounts, public_amount, relayer_fee, timestamp)?;
+    }
+
+    ctx.accounts.pool_config.record_join_split(timestamp)?;
+
+    if let Some(relayer_node) = ctx.accounts.relayer_node.as_mut() {
+        if public_inputs.is_withdrawal() {
+            relayer_node.consume_drain_allowance()?;
+        }
+        relayer_node.record_transaction(relayer_fee, timestamp)?;
+    }
+
+    let padded = |values: &[[u8; 32]], i: usize| values.get(i).copied().unwrap_or([0u8; 32]);
+    emit!(JoinSplitEvent {
+        pool: pool_key,
+        input_count: input_nullifiers.len() as u8,
+        output_count: output_commitments.len() as u8,
+        nullifier_hash_0: input_nullifiers[0],
+        nullifier_hash_1: padded(&input_nullifiers, 1),
+        output_commitment_0: output_commitments[0],
+        output_commitment_1: padded(&output_commitments, 1),
+        public_amount,
+        asset_id,
+        relayer: ctx.accounts.relayer.key(),
+        relayer_fee,
+        output_leaf_indices,
+        timestamp,
+    });
+
+    msg!(
+        "Join-split: {} inputs, {} outputs, public amount {}",
+        input_nullifiers.len(),
+        output_commitments.len(),
+        public_amount
+    );
+
+    Ok(())
+}
+
+/// Move `public_amount` between the vault and the relayer's (inflow) or
+/// recipient's (outflow) token account, keeping the vault's shielded
+/// balance and the pool's deposit and withdrawal stats in step
+fn settle_public_amount<'info>(
+    accounts: &mut PrivateTransferJoinSplit<'info>,
+    public_amount: i64,
+    relayer_fee: u64,
+    timestamp: i64,
+) -> Result<()> {
+    let (Some(vault_token_account), Some(relayer_token_account), Some(mint)) = (
+        accounts.vault_token_account.as_mut(),
+        accounts.relayer_token_account.as_ref(),
+        accounts.mint.as_ref(),
+    ) else {
+        return err!(PrivacyErrorV2::MissingAssetAccount);
+    };
+    let amount = public_amount.unsigned_abs();
+    let coarse_stats = accounts.pool_config.has_coarse_liquidity_stats();
+    let vault_balance = vault_token_account.amount;
+
+    if public_amount > 0 {
+        token_interface::transfer_checked(
+            CpiContext::new(
+                accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: relayer_token_account.to_account_info(),
+                    mint: mint.to_account_info(),
+                    to: vault_token_account.to_account_info(),
+                    authority: accounts.relayer.to_account_info(),
+                },
+            ),
+            amount,
+            mint.decimals,
+        )?;
+        accounts
+            .asset_vault
+            .record_deposit(amount, timestamp, coarse_stats)?;
+        accounts.pool_config.record_deposit(timestamp)?;
+        if let Some(anonymity_stats) = accounts.anonymity_stats.as_mut() {
+            anonymity_stats.record_deposit(timestamp)?;
+        }
+        vault_token_account.reload()?;
+        // A Token-2022 transfer fee would leave the vault short of the
+        // shielded amount
+        require!(
+            vault_token_account.amount.checked_sub(vault_balance) == Some(amount),
+            PrivacyErrorV2::VaultBalanceDrift
+        );
+        accounts.asset_vault.reconcile(vault_token_account.amount)
+    } else {
+        let recipient_token_account = accounts
+            .recipient_token_account
+            .as_ref()
+            .ok_or(error!(PrivacyErrorV2::MissingAssetAccount))?;
+        let pool_key = accounts.pool_config.key();
+        let asset_id = accounts.asset_vault.asset_id;
+        let bump = [accounts.asset_vault.bump];
+        let vault_seeds = AssetVault::seeds(&pool_key, &asset_id, &bump);
+        let signer_seeds: &[&[&[u8]]] = &[&vault_seeds];
+        let transfer = |to: AccountInfo<'info>, amount: u64| -> Result<()> {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: vault_token_account.to_account_info(),
+                        mint: mint.to_account_info(),
+                        to,
+                        authority: accounts.asset_vault.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                amount,
+                mint.decimals,
+            )
+        };
 
-    Err(error!(PrivacyErrorV2::NotImplemented))
+        // Relayer and protocol fees come out of the recipient's share, as
+        // in `withdraw_masp`
+        let protocol_fee = accounts.pool_config.protocol_fee_for(amount);
+        let recipient_amount = recipient_amount(amount, relayer_fee, 0, protocol_fee)?;
+        if recipient_amount > 0 {
+            transfer(recipient_token_account.to_account_info(), recipient_amount)?;
+        }
+        if relayer_fee > 0 {
+            transfer(relayer_token_account.to_account_info(), relayer_fee)?;
+        }
+        if protocol_fee > 0 {
+            let treasury = accounts
+                .treasury_token_account
+                .as_ref()
+                .ok_or(error!(PrivacyErrorV2::TreasuryRequired))?;
+            transfer(treasury.to_account_info(), protocol_fee)?;
+        }
+        accounts
+            .asset_vault
+            .record_withdrawal(amount, timestamp, coarse_stats)?;
+        accounts.pool_config.record_withdrawal(timestamp)?;
+        if let Some(anonymity_stats) = accounts.anonymity_stats.as_mut() {
+            anonymity_stats.record_withdrawal(timestamp)?;
+        }
+        vault_token_account.reload()?;
+        accounts
+            .asset_vault
+            .reconcile_debit(vault_balance, vault_token_account.amount, amount)
+    }
 }
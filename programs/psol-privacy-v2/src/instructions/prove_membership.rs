@@ -18,6 +18,7 @@
 use anchor_lang::prelude::*;
 
 use crate::error::PrivacyErrorV2;
+use crate::instructions::verification_status::VerificationStatus;
 use crate::state::{MerkleTreeV2, PoolConfigV2, VerificationKeyAccountV2};
 use crate::ProofType;
 
@@ -72,7 +73,9 @@ pub struct ProveMembership<'info> {
 /// 1. Compute a binding hash to prevent proof replay
 /// 2. Verify the Groth16 membership proof
 /// 3. NOT reveal or spend any nullifiers
-/// 4. Return true/false and emit MembershipProofVerified event
+/// 4. Return a `VerificationStatus` (`ThresholdNotMet` or
+///    `AttestationExpired` when the proof is sound but falls short) and emit
+///    MembershipProofVerified event
 /// 5. Enable use cases like governance voting rights verification
 pub fn handler(
     ctx: Context<ProveMembership>,
@@ -80,7 +83,7 @@ pub fn handler(
     _merkle_root: [u8; 32],
     threshold: u64,
     asset_id: [u8; 32],
-) -> Result<bool> {
+) -> Result<VerificationStatus> {
     // =========================================================================
     // BASIC STATE VALIDATION
     // These checks verify the instruction could succeed if circuits were ready
@@ -0,0 +1,99 @@
+//! Publish Attested Root Instruction
+//!
+//! Records the tree's current root and leaf count in an
+//! `AttestedRootRecord`, together with the pool authority's ed25519
+//! signature over `AttestedRootRecord::attestation_message`. The signature
+//! is supplied through an Ed25519SigVerify instruction placed directly
+//! before this one.
+
+use anchor_lang::prelude::*;
+use solana_sdk_ids::sysvar::instructions as sysvar_instructions;
+
+use crate::error::PrivacyErrorV2;
+use crate::events::RootAttested;
+use crate::state::{AttestedRootRecord, MerkleTreeV2, PoolConfigV2};
+use crate::utils::verify_preceding_ed25519_signature;
+
+/// Accounts for publishing an attested root
+#[derive(Accounts)]
+#[instruction(root: [u8; 32])]
+pub struct PublishAttestedRoot<'info> {
+    /// Pool authority (signs the attestation, pays rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+        has_one = merkle_tree,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Merkle tree account
+    #[account(
+        constraint = merkle_tree.get_current_root() == root @ PrivacyErrorV2::StaleAttestedRoot,
+    )]
+    pub merkle_tree: Account<'info, MerkleTreeV2>,
+
+    /// Attested root record PDA
+    #[account(
+        init,
+        payer = authority,
+        space = AttestedRootRecord::LEN,
+        seeds = [AttestedRootRecord::SEED_PREFIX, pool_config.key().as_ref(), root.as_ref()],
+        bump,
+    )]
+    pub attested_root: Account<'info, AttestedRootRecord>,
+
+    /// CHECK: Address constrained to the instructions sysvar
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Handler for publish_attested_root instruction
+///
+/// `root` must be the tree's current root; it selects the record PDA and
+/// guards against the tree moving between signing and submission.
+pub fn handler(ctx: Context<PublishAttestedRoot>, root: [u8; 32]) -> Result<()> {
+    let clock = Clock::get()?;
+    let pool = ctx.accounts.pool_config.key();
+    let merkle_tree = ctx.accounts.merkle_tree.key();
+    let authority = ctx.accounts.authority.key();
+    let leaf_count = ctx.accounts.merkle_tree.get_next_leaf_index();
+    let depth = ctx.accounts.merkle_tree.depth;
+
+    let message =
+        AttestedRootRecord::attestation_message(&pool, &merkle_tree, &root, leaf_count, depth);
+    let signature = verify_preceding_ed25519_signature(
+        &ctx.accounts.instructions_sysvar.to_account_info(),
+        &authority,
+        &message,
+    )?;
+
+    let record = &mut ctx.accounts.attested_root;
+    record.pool = pool;
+    record.merkle_tree = merkle_tree;
+    record.root = root;
+    record.leaf_count = leaf_count;
+    record.depth = depth;
+    record.authority = authority;
+    record.signature = signature;
+    record.slot = clock.slot;
+    record.attested_at = clock.unix_timestamp;
+    record.bump = ctx.bumps.attested_root;
+    record._reserved = [0u8; 16];
+
+    emit!(RootAttested {
+        pool,
+        attested_root: record.key(),
+        root,
+        leaf_count,
+        authority,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Root attested at {} leaves", leaf_count);
+    Ok(())
+}
@@ -0,0 +1,78 @@
+//! Record Note Receipt Instruction
+//!
+//! Lets the relayer that spent a nullifier store the spent note's receipt
+//! (its envelope prefix, see `state::spent_nullifier`) in the nullifier's
+//! record, typically right after the spend in the same transaction.
+//! Wallets syncing from chain match receipts against the envelopes they
+//! decrypted instead of re-deriving every nullifier hash.
+//! Legacy records (`SpentNullifierV2::LEGACY_LEN`, spent before receipts
+//! existed) have no room for one and are rejected.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::events::NoteReceiptRecorded;
+use crate::state::{PoolConfigV2, SpentNullifierV2, NOTE_RECEIPT_LEN};
+
+/// Accounts for recording a note receipt
+#[derive(Accounts)]
+#[instruction(nullifier_hash: [u8; 32])]
+pub struct RecordNoteReceipt<'info> {
+    /// Relayer that spent the nullifier
+    pub relayer: Signer<'info>,
+
+    /// Pool configuration account
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// CHECK: Spent nullifier record of either layout, parsed by the
+    /// handler; owner and PDA checked here, relayer by the handler
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [
+            SpentNullifierV2::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            nullifier_hash.as_ref(),
+        ],
+        bump,
+    )]
+    pub spent_nullifier: UncheckedAccount<'info>,
+}
+
+/// Handler for record_note_receipt instruction
+pub fn handler(
+    ctx: Context<RecordNoteReceipt>,
+    nullifier_hash: [u8; 32],
+    note_receipt: [u8; NOTE_RECEIPT_LEN],
+) -> Result<()> {
+    require!(
+        !ctx.accounts.pool_config.privacy_strict,
+        PrivacyErrorV2::NoteReceiptsDisabled
+    );
+
+    let info = ctx.accounts.spent_nullifier.to_account_info();
+    require!(
+        info.data_len() != SpentNullifierV2::LEGACY_LEN,
+        PrivacyErrorV2::NoteReceiptLegacyRecord
+    );
+    let mut record = {
+        let data = info.try_borrow_data()?;
+        SpentNullifierV2::try_deserialize(&mut &data[..])?
+    };
+    require_keys_eq!(
+        record.relayer,
+        ctx.accounts.relayer.key(),
+        PrivacyErrorV2::Unauthorized
+    );
+
+    record.record_note_receipt(note_receipt)?;
+    record.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+    emit!(NoteReceiptRecorded {
+        pool: ctx.accounts.pool_config.key(),
+        nullifier_hash,
+        note_receipt,
+    });
+
+    Ok(())
+}
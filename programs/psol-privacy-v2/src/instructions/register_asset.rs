@@ -1,14 +1,23 @@
 //! Register Asset Instruction
 //!
 //! Registers a new SPL token asset with the MASP pool.
-//! Creates an AssetVault account to hold shielded tokens.
+//! Creates an AssetVault account to hold shielded tokens. Mints of either
+//! token program are accepted; Token-2022 vaults are recorded as
+//! `ASSET_TYPE_TOKEN_2022` so deposits account for transfer fees.
+//!
+//! `register_native_sol_asset` registers native SOL under
+//! `NATIVE_SOL_ASSET_ID`; its vault holds lamports directly, so users
+//! never wrap SOL themselves.
 
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Mint, Token, TokenAccount};
+use anchor_spl::token::spl_token;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 use crate::error::PrivacyErrorV2;
 use crate::events::AssetRegistered;
+use crate::state::asset_vault::NATIVE_SOL_ASSET_ID;
 use crate::state::{AssetVault, PoolConfigV2};
+use crate::utils::TokenMetadata;
 
 /// Accounts for registering a new asset with the pool
 #[derive(Accounts)]
@@ -26,8 +35,8 @@ pub struct RegisterAsset<'info> {
     )]
     pub pool_config: Account<'info, PoolConfigV2>,
 
-    /// Token mint for the asset being registered
-    pub mint: Account<'info, Mint>,
+    /// Token mint for the asset being registered (SPL Token or Token-2022)
+    pub mint: InterfaceAccount<'info, Mint>,
 
     /// Asset vault account (PDA)
     #[account(
@@ -51,16 +60,55 @@ pub struct RegisterAsset<'info> {
         payer = authority,
         token::mint = mint,
         token::authority = asset_vault,
+        token::token_program = token_program,
         seeds = [
             b"vault_token",
             asset_vault.key().as_ref(),
         ],
         bump,
     )]
-    pub vault_token_account: Account<'info, TokenAccount>,
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
 
-    /// Token program
-    pub token_program: Program<'info, Token>,
+    /// Token program owning the mint
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// Metaplex metadata PDA for the mint; when given, its symbol and name
+    /// hash are cached in the vault
+    /// CHECK: owner, address and contents are checked by `TokenMetadata::load`
+    pub token_metadata: Option<UncheckedAccount<'info>>,
+}
+
+/// Accounts for registering native SOL with the pool
+#[derive(Accounts)]
+pub struct RegisterNativeSolAsset<'info> {
+    /// Pool authority (must be signer)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        mut,
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+        constraint = !pool_config.is_paused @ PrivacyErrorV2::PoolPaused,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Native SOL vault (PDA); holds deposited lamports above its rent
+    #[account(
+        init,
+        payer = authority,
+        space = AssetVault::DEFAULT_SPACE,
+        seeds = [
+            AssetVault::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            NATIVE_SOL_ASSET_ID.as_ref(),
+        ],
+        bump,
+    )]
+    pub asset_vault: Account<'info, AssetVault>,
 
     /// System program
     pub system_program: Program<'info, System>,
@@ -68,10 +116,41 @@ pub struct RegisterAsset<'info> {
 
 /// Handler for register_asset instruction
 pub fn handler(ctx: Context<RegisterAsset>, asset_id: [u8; 32]) -> Result<()> {
-    let pool_config = &mut ctx.accounts.pool_config;
+    let timestamp = Clock::get()?.unix_timestamp;
+    let mint = &ctx.accounts.mint;
+    initialize_asset(
+        &mut ctx.accounts.pool_config,
+        &mut ctx.accounts.asset_vault,
+        &mint.to_account_info(),
+        mint.decimals,
+        ctx.accounts.vault_token_account.key(),
+        ctx.bumps.asset_vault,
+        asset_id,
+        ctx.accounts.token_metadata.as_deref(),
+        timestamp,
+    )
+}
 
+/// Set up a newly created vault for `mint` and count it against the pool
+///
+/// Shared by every registration path, so an asset is validated and
+/// recorded the same way whoever registers it. When `token_metadata` is
+/// given, the mint's Metaplex symbol and name hash are cached in the vault.
+/// The asset type follows the token program that owns `mint`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn initialize_asset(
+    pool_config: &mut Account<PoolConfigV2>,
+    asset_vault: &mut Account<AssetVault>,
+    mint: &AccountInfo,
+    decimals: u8,
+    vault_token_account: Pubkey,
+    vault_bump: u8,
+    asset_id: [u8; 32],
+    token_metadata: Option<&AccountInfo>,
+    timestamp: i64,
+) -> Result<()> {
     // Redundant with the account constraint, but keeps safety if constraint is edited later.
-    let expected_asset_id = crate::state::asset_vault::compute_asset_id(&ctx.accounts.mint.key());
+    let expected_asset_id = crate::state::asset_vault::compute_asset_id(&mint.key());
     require!(
         asset_id == expected_asset_id,
         PrivacyErrorV2::InvalidAssetId
@@ -83,30 +162,90 @@ pub fn handler(ctx: Context<RegisterAsset>, asset_id: [u8; 32]) -> Result<()> {
         PrivacyErrorV2::TooManyAssets
     );
 
-    let timestamp = Clock::get()?.unix_timestamp;
-    let vault_bump = ctx.bumps.asset_vault;
-
     // AssetVault::initialize returns () (not Result), and it requires asset_type.
-    ctx.accounts.asset_vault.initialize(
+    asset_vault.initialize(
         pool_config.key(),
         asset_id,
-        ctx.accounts.mint.key(),
-        ctx.accounts.vault_token_account.key(),
+        mint.key(),
+        vault_token_account,
         vault_bump,
-        ctx.accounts.mint.decimals,
-        AssetVault::ASSET_TYPE_SPL,
+        decimals,
+        AssetVault::token_asset_type(mint.owner),
+        pool_config.registered_asset_count,
         timestamp,
     );
 
+    if let Some(account) = token_metadata {
+        asset_vault.set_token_metadata(&TokenMetadata::load(account, &mint.key())?);
+    }
+
     pool_config.register_asset()?;
     pool_config.last_activity_at = timestamp;
 
     emit!(AssetRegistered {
         pool: pool_config.key(),
         asset_id,
-        mint: ctx.accounts.mint.key(),
-        vault: ctx.accounts.asset_vault.key(),
-        decimals: ctx.accounts.mint.decimals,
+        mint: mint.key(),
+        vault: asset_vault.key(),
+        decimals,
+        symbol: asset_vault.token_symbol,
+        name_hash: asset_vault.token_name_hash,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+/// Handler for register_native_sol_asset instruction
+pub fn native_sol_handler(ctx: Context<RegisterNativeSolAsset>) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+    initialize_native_sol_asset(
+        &mut ctx.accounts.pool_config,
+        &mut ctx.accounts.asset_vault,
+        ctx.bumps.asset_vault,
+        timestamp,
+    )
+}
+
+/// Set up a newly created native SOL vault and count it against the pool
+///
+/// The vault records the wrapped-SOL mint so wallets recognize the asset,
+/// and itself as its token account: balances live in its lamports.
+pub(crate) fn initialize_native_sol_asset(
+    pool_config: &mut Account<PoolConfigV2>,
+    asset_vault: &mut Account<AssetVault>,
+    vault_bump: u8,
+    timestamp: i64,
+) -> Result<()> {
+    require!(
+        pool_config.can_register_asset(),
+        PrivacyErrorV2::TooManyAssets
+    );
+
+    let vault_key = asset_vault.key();
+    asset_vault.initialize(
+        pool_config.key(),
+        NATIVE_SOL_ASSET_ID,
+        spl_token::native_mint::ID,
+        vault_key,
+        vault_bump,
+        spl_token::native_mint::DECIMALS,
+        AssetVault::ASSET_TYPE_NATIVE_SOL,
+        pool_config.registered_asset_count,
+        timestamp,
+    );
+
+    pool_config.register_asset()?;
+    pool_config.last_activity_at = timestamp;
+
+    emit!(AssetRegistered {
+        pool: pool_config.key(),
+        asset_id: NATIVE_SOL_ASSET_ID,
+        mint: spl_token::native_mint::ID,
+        vault: vault_key,
+        decimals: spl_token::native_mint::DECIMALS,
+        symbol: asset_vault.token_symbol,
+        name_hash: asset_vault.token_name_hash,
         timestamp,
     });
 
@@ -0,0 +1,168 @@
+//! Register Assets Batch Instruction
+//!
+//! Registers up to `MAX_ASSETS_PER_BATCH` SPL token assets in one call.
+//! Each asset is validated and set up exactly as `register_asset` does
+//! (see `register_asset::initialize_asset`);
+//! the transaction either registers all of them or none.
+//!
+//! Remaining accounts, per asset and in `asset_ids` order:
+//! `[mint, asset_vault (writable), vault_token_account (writable)]`
+//!
+//! Token metadata is not read here; register an asset individually to
+//! cache its Metaplex symbol and name hash.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, InitializeAccount3, Mint, Token, TokenAccount};
+
+use crate::error::PrivacyErrorV2;
+use crate::instructions::register_asset::initialize_asset;
+use crate::state::asset_vault::compute_asset_id;
+use crate::state::{AssetVault, PoolConfigV2};
+use crate::tx_limits::require_remaining_accounts_exact;
+use crate::utils::{create_pda_account, require_program_id};
+
+/// Most assets one `register_assets_batch` call registers
+pub const MAX_ASSETS_PER_BATCH: usize = 5;
+
+/// Remaining accounts per asset: mint, asset vault, vault token account
+pub const ACCOUNTS_PER_ASSET: usize = 3;
+
+/// Accounts for registering several assets at once
+#[derive(Accounts)]
+pub struct RegisterAssetsBatch<'info> {
+    /// Pool authority (must be signer, pays for the new accounts)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        mut,
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+        constraint = !pool_config.is_paused @ PrivacyErrorV2::PoolPaused,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+    // Remaining accounts: [mint, asset_vault, vault_token_account] per asset
+}
+
+/// Handler for register_assets_batch instruction
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, RegisterAssetsBatch<'info>>,
+    asset_ids: Vec<[u8; 32]>,
+) -> Result<()> {
+    require!(
+        !asset_ids.is_empty() && asset_ids.len() <= MAX_ASSETS_PER_BATCH,
+        PrivacyErrorV2::InvalidBatchSize
+    );
+    require_remaining_accounts_exact(
+        "register_assets_batch",
+        ctx.remaining_accounts.len(),
+        asset_ids.len() * ACCOUNTS_PER_ASSET,
+    )?;
+
+    // Vault PDAs are derived by hand against this build's program id
+    require_program_id(ctx.program_id)?;
+
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    for (asset_id, accounts) in asset_ids
+        .iter()
+        .zip(ctx.remaining_accounts.chunks_exact(ACCOUNTS_PER_ASSET))
+    {
+        register_one(ctx.accounts, *asset_id, accounts, timestamp)?;
+    }
+
+    msg!(
+        "Registered {} assets ({} total)",
+        asset_ids.len(),
+        ctx.accounts.pool_config.registered_asset_count
+    );
+
+    Ok(())
+}
+
+fn register_one<'info>(
+    accounts: &mut RegisterAssetsBatch<'info>,
+    asset_id: [u8; 32],
+    remaining: &'info [AccountInfo<'info>],
+    timestamp: i64,
+) -> Result<()> {
+    let (mint_info, vault_info, token_info) = (&remaining[0], &remaining[1], &remaining[2]);
+    let mint = Account::<Mint>::try_from(mint_info)?;
+
+    require!(
+        asset_id == compute_asset_id(&mint.key()),
+        PrivacyErrorV2::InvalidAssetId
+    );
+
+    require!(
+        accounts.pool_config.can_register_asset(),
+        PrivacyErrorV2::TooManyAssets
+    );
+
+    let pool_key = accounts.pool_config.key();
+    let (vault_key, vault_bump) = Pubkey::find_program_address(
+        &[
+            AssetVault::SEED_PREFIX,
+            pool_key.as_ref(),
+            asset_id.as_ref(),
+        ],
+        &crate::ID,
+    );
+    require_keys_eq!(vault_info.key(), vault_key, ErrorCode::ConstraintSeeds);
+    let (token_key, token_bump) =
+        Pubkey::find_program_address(&[b"vault_token", vault_key.as_ref()], &crate::ID);
+    require_keys_eq!(token_info.key(), token_key, ErrorCode::ConstraintSeeds);
+
+    let authority = accounts.authority.to_account_info();
+    let system_program = accounts.system_program.to_account_info();
+
+    create_pda_account(
+        &authority,
+        vault_info,
+        &system_program,
+        AssetVault::DEFAULT_SPACE,
+        &crate::ID,
+        &[
+            AssetVault::SEED_PREFIX,
+            pool_key.as_ref(),
+            asset_id.as_ref(),
+            &[vault_bump],
+        ],
+    )?;
+    create_pda_account(
+        &authority,
+        token_info,
+        &system_program,
+        TokenAccount::LEN,
+        &token::ID,
+        &[b"vault_token", vault_key.as_ref(), &[token_bump]],
+    )?;
+    token::initialize_account3(CpiContext::new(
+        accounts.token_program.to_account_info(),
+        InitializeAccount3 {
+            account: token_info.clone(),
+            mint: mint_info.clone(),
+            authority: vault_info.clone(),
+        },
+    ))?;
+
+    let mut vault = Account::<AssetVault>::try_from_unchecked(vault_info)?;
+    initialize_asset(
+        &mut accounts.pool_config,
+        &mut vault,
+        mint_info,
+        mint.decimals,
+        token_key,
+        vault_bump,
+        asset_id,
+        None,
+        timestamp,
+    )?;
+    vault.exit(&crate::ID)
+}
@@ -0,0 +1,100 @@
+//! Close Relayer Instruction
+//!
+//! Closes a deactivated relayer node and returns its rent to the operator.
+//! Any stake must be withdrawn first, or it would be stranded in the
+//! node's stake vault.
+//! A tombstone is left behind so the operator cannot register again until
+//! the registry's re-registration cooldown has passed; the node's index
+//! entry stays in place, marked inactive.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::events::RelayerClosed;
+use crate::state::{PoolConfigV2, RelayerNode, RelayerRegistry, RelayerTombstone};
+use crate::utils::pool_clock;
+
+/// Accounts for closing a relayer
+#[derive(Accounts)]
+pub struct CloseRelayer<'info> {
+    /// Relayer operator (must be signer, receives the node's rent)
+    #[account(mut)]
+    pub operator: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = relayer_registry,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Relayer registry account
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+
+    /// Relayer node account (closed)
+    #[account(
+        mut,
+        close = operator,
+        has_one = operator @ PrivacyErrorV2::Unauthorized,
+        constraint = !relayer_node.is_active @ PrivacyErrorV2::RelayerStillActive,
+        constraint = !relayer_node.holds_stake() @ PrivacyErrorV2::RelayerStakeOutstanding,
+        seeds = [
+            RelayerNode::SEED_PREFIX,
+            relayer_registry.key().as_ref(),
+            operator.key().as_ref(),
+        ],
+        bump = relayer_node.bump,
+    )]
+    pub relayer_node: Account<'info, RelayerNode>,
+
+    /// Tombstone enforcing the re-registration cooldown
+    #[account(
+        init,
+        payer = operator,
+        space = RelayerTombstone::LEN,
+        seeds = [
+            RelayerTombstone::SEED_PREFIX,
+            relayer_registry.key().as_ref(),
+            operator.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub relayer_tombstone: Account<'info, RelayerTombstone>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Handler for close_relayer instruction
+pub fn handler(ctx: Context<CloseRelayer>) -> Result<()> {
+    let registry = &ctx.accounts.relayer_registry;
+    let relayer_node = &ctx.accounts.relayer_node;
+
+    let clock = pool_clock(&ctx.accounts.pool_config)?;
+
+    ctx.accounts.relayer_tombstone.initialize(
+        registry.key(),
+        relayer_node.operator,
+        clock.slot,
+        registry.reregistration_cooldown_slots,
+        relayer_node.reputation_score,
+        relayer_node.index_position,
+        ctx.bumps.relayer_tombstone,
+    )?;
+    let reregister_after_slot = ctx.accounts.relayer_tombstone.reregister_after_slot;
+
+    emit!(RelayerClosed {
+        pool: ctx.accounts.pool_config.key(),
+        relayer: relayer_node.key(),
+        operator: ctx.accounts.operator.key(),
+        reregister_after_slot,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "Relayer closed: {}, re-registration after slot {}",
+        relayer_node.key(),
+        reregister_after_slot
+    );
+
+    Ok(())
+}
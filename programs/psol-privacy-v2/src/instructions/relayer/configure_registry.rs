@@ -1,11 +1,13 @@
 //! Configure Relayer Registry Instruction
 //!
-//! Configures global relayer parameters including fee bounds and staking requirements.
+//! Configures global relayer parameters including fee bounds and staking requirements,
+//! the cooldown before a closed relayer's operator may register again, and
+//! whether new relayers may register at all.
 
 use anchor_lang::prelude::*;
 
 use crate::error::PrivacyErrorV2;
-use crate::events::RelayerRegistryConfigured;
+use crate::events::{RelayerCooldownUpdated, RelayerRegistrationsSet, RelayerRegistryConfigured};
 use crate::state::{PoolConfigV2, RelayerRegistry};
 
 /// Accounts for configuring the relayer registry
@@ -69,3 +71,88 @@ pub fn handler(
 
     Ok(())
 }
+
+/// Accounts for setting the re-registration cooldown
+#[derive(Accounts)]
+pub struct SetRelayerCooldown<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+        has_one = relayer_registry,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Relayer registry account
+    #[account(mut)]
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+}
+
+/// Handler for set_relayer_cooldown instruction
+pub fn set_cooldown_handler(
+    ctx: Context<SetRelayerCooldown>,
+    reregistration_cooldown_slots: u64,
+) -> Result<()> {
+    let registry = &mut ctx.accounts.relayer_registry;
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    registry.set_reregistration_cooldown(reregistration_cooldown_slots, timestamp);
+
+    emit!(RelayerCooldownUpdated {
+        pool: ctx.accounts.pool_config.key(),
+        registry: registry.key(),
+        reregistration_cooldown_slots,
+        timestamp,
+    });
+
+    msg!(
+        "Relayer re-registration cooldown set: {} slots",
+        reregistration_cooldown_slots
+    );
+
+    Ok(())
+}
+
+/// Accounts for opening or closing relayer registrations
+#[derive(Accounts)]
+pub struct SetRelayerRegistrationsOpen<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+        has_one = relayer_registry,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Relayer registry account
+    #[account(mut)]
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+}
+
+/// Handler for set_relayer_registrations_open instruction
+///
+/// Closing only stops new registrations; registered relayers keep relaying.
+pub fn set_registrations_open_handler(
+    ctx: Context<SetRelayerRegistrationsOpen>,
+    open: bool,
+) -> Result<()> {
+    let registry = &mut ctx.accounts.relayer_registry;
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    registry.set_registrations_open(open, timestamp);
+
+    emit!(RelayerRegistrationsSet {
+        pool: ctx.accounts.pool_config.key(),
+        registry: registry.key(),
+        open,
+        timestamp,
+    });
+
+    msg!("Relayer registrations open: {}", open);
+
+    Ok(())
+}
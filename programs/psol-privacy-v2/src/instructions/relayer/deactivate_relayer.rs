@@ -6,7 +6,8 @@ use anchor_lang::prelude::*;
 
 use crate::error::PrivacyErrorV2;
 use crate::events::RelayerDeactivated;
-use crate::state::{PoolConfigV2, RelayerNode, RelayerRegistry};
+use crate::state::{PoolConfigV2, RelayerIndexShard, RelayerNode, RelayerRegistry};
+use crate::utils::pool_clock;
 
 /// Accounts for deactivating a relayer
 #[derive(Accounts)]
@@ -37,6 +38,18 @@ pub struct DeactivateRelayer<'info> {
         bump = relayer_node.bump,
     )]
     pub relayer_node: Account<'info, RelayerNode>,
+
+    /// Relayer index shard holding this relayer
+    #[account(
+        mut,
+        seeds = [
+            RelayerIndexShard::SEED_PREFIX,
+            relayer_registry.key().as_ref(),
+            &RelayerIndexShard::shard_for(relayer_node.index_position).to_le_bytes(),
+        ],
+        bump = relayer_index_shard.bump,
+    )]
+    pub relayer_index_shard: Account<'info, RelayerIndexShard>,
 }
 
 /// Handler for deactivate_relayer instruction
@@ -44,12 +57,17 @@ pub fn handler(ctx: Context<DeactivateRelayer>) -> Result<()> {
     let registry = &mut ctx.accounts.relayer_registry;
     let relayer_node = &mut ctx.accounts.relayer_node;
 
-    let clock = Clock::get()?;
+    let clock = pool_clock(&ctx.accounts.pool_config)?;
     let timestamp = clock.unix_timestamp;
 
     // Deactivate the relayer
     relayer_node.deactivate(timestamp);
     registry.deactivate_relayer(timestamp)?;
+    ctx.accounts.relayer_index_shard.set_active(
+        relayer_node.index_position,
+        &relayer_node.operator,
+        false,
+    )?;
 
     // Emit event
     emit!(RelayerDeactivated {
@@ -0,0 +1,78 @@
+//! Set Relayer Draining Instruction
+//!
+//! Lets an operator wind a node down without racing jobs already queued
+//! behind it. While draining, the node stays active and listed but the
+//! withdraw paths accept at most `inflight_allowance` more withdrawals
+//! through it; wallets read the `draining` flag and stop routing new jobs.
+//! Clearing the flag resumes normal service.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::events::RelayerDrainingSet;
+use crate::state::{PoolConfigV2, RelayerNode, RelayerRegistry};
+use crate::utils::pool_clock;
+
+/// Accounts for setting a relayer's draining flag
+#[derive(Accounts)]
+pub struct SetRelayerDraining<'info> {
+    /// Relayer operator (must be signer)
+    pub operator: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = relayer_registry,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Relayer registry account
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+
+    /// Relayer node account
+    #[account(
+        mut,
+        has_one = operator @ PrivacyErrorV2::Unauthorized,
+        constraint = relayer_node.is_active @ PrivacyErrorV2::RelayerNotActive,
+        seeds = [
+            RelayerNode::SEED_PREFIX,
+            relayer_registry.key().as_ref(),
+            operator.key().as_ref(),
+        ],
+        bump = relayer_node.bump,
+    )]
+    pub relayer_node: Account<'info, RelayerNode>,
+}
+
+/// Handler for set_relayer_draining instruction
+///
+/// `inflight_allowance` is the number of withdrawals the operator still
+/// expects to submit for jobs it has already accepted; it is ignored when
+/// `draining` is false.
+pub fn handler(
+    ctx: Context<SetRelayerDraining>,
+    draining: bool,
+    inflight_allowance: u16,
+) -> Result<()> {
+    let relayer_node = &mut ctx.accounts.relayer_node;
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+
+    relayer_node.set_draining(draining, inflight_allowance, timestamp);
+
+    emit!(RelayerDrainingSet {
+        pool: ctx.accounts.pool_config.key(),
+        relayer: relayer_node.key(),
+        operator: ctx.accounts.operator.key(),
+        draining,
+        drain_allowance: relayer_node.drain_allowance,
+        timestamp,
+    });
+
+    msg!(
+        "Relayer {} draining: {} (allowance {})",
+        relayer_node.key(),
+        draining,
+        relayer_node.drain_allowance
+    );
+
+    Ok(())
+}
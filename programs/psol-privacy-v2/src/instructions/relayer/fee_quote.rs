@@ -0,0 +1,160 @@
+//! Relayer Fee Quote Instructions
+//!
+//! - `init_relayer_fee_quote`: operator opens a fee quote for its node
+//! - `update_relayer_fee_quote`: operator replaces the quote
+//!
+//! The quote's basis points must be within the registry's fee bounds.
+//! `withdraw_masp` checks the relayer fee against the quote when it is
+//! attached (see `state::relayer_fee_quote`).
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::events::RelayerFeeQuoteUpdated;
+use crate::state::{PoolConfigV2, RelayerFeeQuote, RelayerNode, RelayerRegistry};
+use crate::utils::pool_clock;
+
+/// Accounts for opening a relayer fee quote
+#[derive(Accounts)]
+pub struct InitRelayerFeeQuote<'info> {
+    /// Relayer operator
+    #[account(mut)]
+    pub operator: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(has_one = relayer_registry)]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Relayer registry the node belongs to
+    #[account(address = relayer_node.registry @ PrivacyErrorV2::RelayerNodeRegistryMismatch)]
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+
+    /// Relayer node owned by the operator
+    #[account(
+        seeds = [
+            RelayerNode::SEED_PREFIX,
+            relayer_node.registry.as_ref(),
+            operator.key().as_ref(),
+        ],
+        bump = relayer_node.bump,
+    )]
+    pub relayer_node: Account<'info, RelayerNode>,
+
+    /// Fee quote (PDA)
+    #[account(
+        init,
+        payer = operator,
+        space = RelayerFeeQuote::LEN,
+        seeds = [RelayerFeeQuote::SEED_PREFIX, relayer_node.key().as_ref()],
+        bump,
+    )]
+    pub fee_quote: Account<'info, RelayerFeeQuote>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for updating a relayer fee quote
+#[derive(Accounts)]
+pub struct UpdateRelayerFeeQuote<'info> {
+    /// Relayer operator
+    pub operator: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(has_one = relayer_registry)]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Relayer registry the node belongs to
+    #[account(address = relayer_node.registry @ PrivacyErrorV2::RelayerNodeRegistryMismatch)]
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+
+    /// Relayer node owned by the operator
+    #[account(
+        seeds = [
+            RelayerNode::SEED_PREFIX,
+            relayer_node.registry.as_ref(),
+            operator.key().as_ref(),
+        ],
+        bump = relayer_node.bump,
+    )]
+    pub relayer_node: Account<'info, RelayerNode>,
+
+    /// Fee quote of the node
+    #[account(
+        mut,
+        seeds = [RelayerFeeQuote::SEED_PREFIX, relayer_node.key().as_ref()],
+        bump = fee_quote.bump,
+        has_one = operator @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub fee_quote: Account<'info, RelayerFeeQuote>,
+}
+
+/// Handler for init_relayer_fee_quote instruction
+pub fn init_handler(
+    ctx: Context<InitRelayerFeeQuote>,
+    flat_fee: u64,
+    fee_bps: u16,
+    valid_until_slot: u64,
+) -> Result<()> {
+    ctx.accounts.fee_quote.initialize(
+        ctx.accounts.relayer_node.key(),
+        ctx.accounts.operator.key(),
+        ctx.bumps.fee_quote,
+    );
+    set_quote(
+        &ctx.accounts.pool_config,
+        &ctx.accounts.relayer_registry,
+        &mut ctx.accounts.fee_quote,
+        flat_fee,
+        fee_bps,
+        valid_until_slot,
+    )
+}
+
+/// Handler for update_relayer_fee_quote instruction
+pub fn update_handler(
+    ctx: Context<UpdateRelayerFeeQuote>,
+    flat_fee: u64,
+    fee_bps: u16,
+    valid_until_slot: u64,
+) -> Result<()> {
+    set_quote(
+        &ctx.accounts.pool_config,
+        &ctx.accounts.relayer_registry,
+        &mut ctx.accounts.fee_quote,
+        flat_fee,
+        fee_bps,
+        valid_until_slot,
+    )
+}
+
+fn set_quote(
+    pool_config: &PoolConfigV2,
+    registry: &Account<RelayerRegistry>,
+    fee_quote: &mut Account<RelayerFeeQuote>,
+    flat_fee: u64,
+    fee_bps: u16,
+    valid_until_slot: u64,
+) -> Result<()> {
+    registry.validate_fee(fee_bps)?;
+
+    let clock = pool_clock(pool_config)?;
+    fee_quote.set(
+        flat_fee,
+        fee_bps,
+        valid_until_slot,
+        clock.slot,
+        clock.unix_timestamp,
+    )?;
+
+    emit!(RelayerFeeQuoteUpdated {
+        registry: registry.key(),
+        relayer: fee_quote.relayer_node,
+        flat_fee,
+        fee_bps,
+        valid_until_slot,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,237 @@
+//! Relayer Gas Rebate Instructions
+//!
+//! - `set_gas_rebate_cap`: authority sets the per-withdrawal rebate cap and
+//!   the most of a declared priority fee that counts toward it
+//! - `init_relayer_rebate_ledger`: operator opens a ledger and declares a
+//!   priority fee hint
+//! - `update_relayer_priority_fee_hint`: operator changes the hint
+//! - `claim_gas_rebate`: operator withdraws accrued rebate from the treasury
+//!
+//! Rebates accrue during withdrawals submitted with the ledger account and
+//! are paid from the protocol fees collected into the pool's `Treasury`
+//! (lamports above its rent-exempt minimum).
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::events::{GasRebateCapUpdated, GasRebateClaimed};
+use crate::state::{
+    AssetVault, PoolConfigV2, RelayerNode, RelayerRebateLedger, RelayerRegistry, Treasury,
+};
+use crate::utils::pool_clock;
+
+/// Accounts for setting the gas rebate cap
+#[derive(Accounts)]
+pub struct SetGasRebateCap<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+        has_one = relayer_registry,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Relayer registry account
+    #[account(mut)]
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+}
+
+/// Accounts for opening a relayer rebate ledger
+#[derive(Accounts)]
+pub struct InitRelayerRebateLedger<'info> {
+    /// Relayer operator
+    #[account(mut)]
+    pub operator: Signer<'info>,
+
+    /// Relayer node owned by the operator
+    #[account(
+        seeds = [
+            RelayerNode::SEED_PREFIX,
+            relayer_node.registry.as_ref(),
+            operator.key().as_ref(),
+        ],
+        bump = relayer_node.bump,
+    )]
+    pub relayer_node: Account<'info, RelayerNode>,
+
+    /// Rebate ledger (PDA)
+    #[account(
+        init,
+        payer = operator,
+        space = RelayerRebateLedger::LEN,
+        seeds = [RelayerRebateLedger::SEED_PREFIX, relayer_node.key().as_ref()],
+        bump,
+    )]
+    pub rebate_ledger: Account<'info, RelayerRebateLedger>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for updating the priority fee hint
+#[derive(Accounts)]
+pub struct UpdateRelayerPriorityFeeHint<'info> {
+    /// Relayer operator
+    pub operator: Signer<'info>,
+
+    /// Rebate ledger owned by the operator
+    #[account(
+        mut,
+        seeds = [RelayerRebateLedger::SEED_PREFIX, rebate_ledger.relayer_node.as_ref()],
+        bump = rebate_ledger.bump,
+        constraint = rebate_ledger.operator == operator.key() @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub rebate_ledger: Account<'info, RelayerRebateLedger>,
+}
+
+/// Accounts for claiming accrued gas rebates
+#[derive(Accounts)]
+pub struct ClaimGasRebate<'info> {
+    /// Relayer operator (receives lamports)
+    #[account(mut)]
+    pub operator: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        constraint = !pool_config.is_paused @ PrivacyErrorV2::PoolPaused,
+        has_one = relayer_registry,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Relayer registry account
+    #[account(mut)]
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+
+    /// Pool treasury (pays the rebate out of collected protocol fees)
+    #[account(
+        mut,
+        seeds = [Treasury::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    /// Relayer node owned by the operator
+    #[account(
+        seeds = [
+            RelayerNode::SEED_PREFIX,
+            relayer_registry.key().as_ref(),
+            operator.key().as_ref(),
+        ],
+        bump = relayer_node.bump,
+    )]
+    pub relayer_node: Account<'info, RelayerNode>,
+
+    /// Rebate ledger for this relayer node
+    #[account(
+        mut,
+        seeds = [RelayerRebateLedger::SEED_PREFIX, relayer_node.key().as_ref()],
+        bump = rebate_ledger.bump,
+        constraint = rebate_ledger.operator == operator.key() @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub rebate_ledger: Account<'info, RelayerRebateLedger>,
+}
+
+/// Handler for set_gas_rebate_cap instruction
+pub fn set_cap_handler(
+    ctx: Context<SetGasRebateCap>,
+    max_gas_rebate_lamports: u64,
+    max_priority_fee_hint: u64,
+) -> Result<()> {
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+    let registry = &mut ctx.accounts.relayer_registry;
+
+    registry.set_gas_rebate_cap(max_gas_rebate_lamports, max_priority_fee_hint, timestamp);
+
+    emit!(GasRebateCapUpdated {
+        pool: ctx.accounts.pool_config.key(),
+        registry: registry.key(),
+        max_gas_rebate_lamports,
+        max_priority_fee_hint,
+        timestamp,
+    });
+
+    msg!(
+        "Gas rebate cap set: {} lamports per withdrawal, priority fee up to {}",
+        max_gas_rebate_lamports,
+        max_priority_fee_hint
+    );
+
+    Ok(())
+}
+
+/// Handler for init_relayer_rebate_ledger instruction
+pub fn init_ledger_handler(
+    ctx: Context<InitRelayerRebateLedger>,
+    priority_fee_hint: u64,
+) -> Result<()> {
+    ctx.accounts.rebate_ledger.initialize(
+        ctx.accounts.relayer_node.key(),
+        ctx.accounts.operator.key(),
+        priority_fee_hint,
+        ctx.bumps.rebate_ledger,
+    );
+
+    msg!(
+        "Relayer rebate ledger opened: priority_fee_hint={} lamports",
+        priority_fee_hint
+    );
+
+    Ok(())
+}
+
+/// Handler for update_relayer_priority_fee_hint instruction
+pub fn update_hint_handler(
+    ctx: Context<UpdateRelayerPriorityFeeHint>,
+    priority_fee_hint: u64,
+) -> Result<()> {
+    ctx.accounts.rebate_ledger.priority_fee_hint = priority_fee_hint;
+
+    msg!(
+        "Relayer priority fee hint updated: {} lamports",
+        priority_fee_hint
+    );
+
+    Ok(())
+}
+
+/// Handler for claim_gas_rebate instruction
+pub fn claim_handler(ctx: Context<ClaimGasRebate>) -> Result<()> {
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+
+    // Only collected fees above the treasury's rent-exempt minimum are spendable
+    let treasury_info = ctx.accounts.treasury.to_account_info();
+    let available = AssetVault::native_balance(&treasury_info)?;
+
+    let amount = ctx.accounts.rebate_ledger.claimable().min(available);
+    require!(amount > 0, PrivacyErrorV2::NoRebateAvailable);
+
+    ctx.accounts.rebate_ledger.record_claim(amount, timestamp)?;
+    ctx.accounts
+        .relayer_registry
+        .record_rebate_claim(amount, timestamp)?;
+    ctx.accounts.treasury.record_rebated(amount)?;
+
+    // The treasury is owned by this program, so lamports move directly
+    AssetVault::pay_native(
+        &treasury_info,
+        &ctx.accounts.operator.to_account_info(),
+        amount,
+    )?;
+
+    let ledger = &ctx.accounts.rebate_ledger;
+    emit!(GasRebateClaimed {
+        pool: ctx.accounts.pool_config.key(),
+        relayer: ctx.accounts.relayer_node.key(),
+        operator: ctx.accounts.operator.key(),
+        amount,
+        total_accrued: ledger.accrued_lamports,
+        total_claimed: ledger.claimed_lamports,
+        timestamp,
+    });
+
+    msg!("Gas rebate claimed: {} lamports", amount);
+
+    Ok(())
+}
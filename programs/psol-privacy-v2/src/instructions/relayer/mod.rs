@@ -1,17 +1,52 @@
 //! Relayer Instructions for pSOL Privacy Pool v2
 //!
 //! On-chain relayer registry management including:
-//! - Registry configuration
+//! - Registry configuration, including opening and closing registrations
 //! - Relayer registration
-//! - Relayer updates
-//! - Relayer deactivation
+//! - Relayer updates (direct or operator-signed with a nonce)
+//! - Relayer deactivation and closing (with a re-registration cooldown)
+//! - Operator-set draining, so queued jobs finish before a node winds down
+//! - Per-asset absolute fee bounds
+//! - Gas rebate accounting
+//! - Published fee quotes checked by withdrawals
+//! - Rent reserve advances for nullifier rent
+//! - Relayer index shards for enumeration
+//! - Staking into per-node stake vaults, with an unbonding period
+//! - Authority slashing of stake into the pool treasury
+//! - Reputation: boosts for relayed transactions, reported failures
 
+pub mod close_relayer;
 pub mod configure_registry;
 pub mod deactivate_relayer;
+pub mod drain_relayer;
+pub mod fee_quote;
+pub mod gas_rebate;
 pub mod register_relayer;
+pub mod relayer_index;
+pub mod rent_reserve;
+pub mod report_relayer_failure;
+pub mod set_asset_fee_bounds;
+pub mod slash_relayer;
+pub mod stake;
 pub mod update_relayer;
 
-pub use configure_registry::ConfigureRelayerRegistry;
+pub use close_relayer::CloseRelayer;
+pub use configure_registry::{
+    ConfigureRelayerRegistry, SetRelayerCooldown, SetRelayerRegistrationsOpen,
+};
 pub use deactivate_relayer::DeactivateRelayer;
+pub use drain_relayer::SetRelayerDraining;
+pub use fee_quote::{InitRelayerFeeQuote, UpdateRelayerFeeQuote};
+pub use gas_rebate::{
+    ClaimGasRebate, InitRelayerRebateLedger, SetGasRebateCap, UpdateRelayerPriorityFeeHint,
+};
 pub use register_relayer::RegisterRelayer;
-pub use update_relayer::UpdateRelayer;
+pub use relayer_index::InitRelayerIndexShard;
+pub use rent_reserve::{
+    FundRentReserve, InitRentAdvance, InitRentReserve, RepayRentAdvance, SetRentAdvanceCap,
+};
+pub use report_relayer_failure::ReportRelayerFailure;
+pub use set_asset_fee_bounds::SetAssetRelayerFeeBounds;
+pub use slash_relayer::SlashRelayer;
+pub use stake::{InitRelayerStakeVault, MoveRelayerStake, SetRelayerStakeMint, UnstakeRelayer};
+pub use update_relayer::{UpdateRelayer, UpdateRelayerSigned};
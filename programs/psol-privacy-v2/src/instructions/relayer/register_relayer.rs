@@ -1,12 +1,21 @@
 //! Register Relayer Instruction
 //!
-//! Registers a new relayer node with the pool.
+//! Registers a new relayer node with the pool, along with its structured
+//! metadata (endpoint hash, note-encryption key, assets, region).
+//!
+//! If the operator previously closed a node in this registry, its tombstone
+//! must be past the re-registration cooldown; the new node inherits the old
+//! reputation and the tombstone is closed.
 
 use anchor_lang::prelude::*;
 
 use crate::error::PrivacyErrorV2;
 use crate::events::RelayerRegistered;
-use crate::state::{PoolConfigV2, RelayerNode, RelayerRegistry, MAX_RELAYER_METADATA_URI_LEN};
+use crate::state::{
+    PoolConfigV2, RelayerIndexShard, RelayerMetadata, RelayerNode, RelayerRegistry,
+    RelayerTombstone, MAX_RELAYER_METADATA_URI_LEN,
+};
+use crate::utils::pool_clock;
 
 /// Accounts for registering a new relayer
 #[derive(Accounts)]
@@ -42,10 +51,40 @@ pub struct RegisterRelayer<'info> {
 
     /// System program
     pub system_program: Program<'info, System>,
+
+    /// Relayer index shard the new operator is appended to
+    #[account(
+        mut,
+        seeds = [
+            RelayerIndexShard::SEED_PREFIX,
+            relayer_registry.key().as_ref(),
+            &RelayerIndexShard::shard_for(relayer_registry.relayer_count).to_le_bytes(),
+        ],
+        bump = relayer_index_shard.bump,
+    )]
+    pub relayer_index_shard: Account<'info, RelayerIndexShard>,
+
+    /// Tombstone left by a previously closed node of this operator
+    /// CHECK: PDA verified by seeds; may be uninitialized, deserialized in handler if it exists
+    #[account(
+        mut,
+        seeds = [
+            RelayerTombstone::SEED_PREFIX,
+            relayer_registry.key().as_ref(),
+            operator.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub relayer_tombstone: UncheckedAccount<'info>,
 }
 
 /// Handler for register_relayer instruction
-pub fn handler(ctx: Context<RegisterRelayer>, fee_bps: u16, metadata_uri: String) -> Result<()> {
+pub fn handler(
+    ctx: Context<RegisterRelayer>,
+    fee_bps: u16,
+    metadata_uri: String,
+    metadata: RelayerMetadata,
+) -> Result<()> {
     let registry = &mut ctx.accounts.relayer_registry;
     let relayer_node = &mut ctx.accounts.relayer_node;
 
@@ -55,25 +94,55 @@ pub fn handler(ctx: Context<RegisterRelayer>, fee_bps: u16, metadata_uri: String
         PrivacyErrorV2::InputTooLarge
     );
 
+    metadata.validate()?;
+
     // Validate fee is within bounds
     registry.validate_fee(fee_bps)?;
 
-    let clock = Clock::get()?;
+    let clock = pool_clock(&ctx.accounts.pool_config)?;
     let timestamp = clock.unix_timestamp;
 
-    // Register with registry
+    // Register with registry; the pre-increment count is the index position
+    let index_position = registry.relayer_count;
     registry.register_relayer(timestamp)?;
+    ctx.accounts
+        .relayer_index_shard
+        .append(index_position, ctx.accounts.operator.key())?;
 
     // Initialize relayer node
     relayer_node.initialize(
         registry.key(),
         ctx.accounts.operator.key(),
         fee_bps,
+        metadata,
         metadata_uri,
+        index_position,
         ctx.bumps.relayer_node,
         timestamp,
     );
 
+    // A previous node of this operator must be past its cooldown; carry its
+    // reputation over so closing and re-registering cannot reset it
+    let tombstone_info = ctx.accounts.relayer_tombstone.to_account_info();
+    if tombstone_info.owner == &crate::ID && !tombstone_info.data_is_empty() {
+        let tombstone =
+            RelayerTombstone::try_deserialize(&mut &tombstone_info.try_borrow_data()?[..])?;
+        tombstone.require_cooldown_elapsed(clock.slot)?;
+        relayer_node.reputation_score = tombstone.reputation_score;
+
+        // Close the tombstone: rent back to the operator, account handed
+        // back to the system program
+        let operator_info = ctx.accounts.operator.to_account_info();
+        let rent = tombstone_info.lamports();
+        **operator_info.try_borrow_mut_lamports()? = operator_info
+            .lamports()
+            .checked_add(rent)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        **tombstone_info.try_borrow_mut_lamports()? = 0;
+        tombstone_info.assign(&System::id());
+        tombstone_info.resize(0)?;
+    }
+
     // Emit event
     emit!(RelayerRegistered {
         pool: ctx.accounts.pool_config.key(),
@@ -81,6 +150,7 @@ pub fn handler(ctx: Context<RegisterRelayer>, fee_bps: u16, metadata_uri: String
         relayer: relayer_node.key(),
         operator: ctx.accounts.operator.key(),
         fee_bps,
+        index_position,
         timestamp,
     });
 
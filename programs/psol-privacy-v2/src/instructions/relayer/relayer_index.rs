@@ -0,0 +1,62 @@
+//! Relayer Index Instructions
+//!
+//! Create the next shard of the relayer index. Anyone may pay for a shard;
+//! shards can only be created up to the one the next registration lands in,
+//! so the index never has gaps.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::state::{PoolConfigV2, RelayerIndexShard, RelayerRegistry};
+
+/// Accounts for creating a relayer index shard
+#[derive(Accounts)]
+#[instruction(shard_index: u32)]
+pub struct InitRelayerIndexShard<'info> {
+    /// Pays rent for the shard
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = relayer_registry,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Relayer registry account
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+
+    /// Index shard PDA
+    #[account(
+        init,
+        payer = payer,
+        space = RelayerIndexShard::LEN,
+        seeds = [
+            RelayerIndexShard::SEED_PREFIX,
+            relayer_registry.key().as_ref(),
+            &shard_index.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub relayer_index_shard: Account<'info, RelayerIndexShard>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Handler for init_relayer_index_shard instruction
+pub fn init_shard_handler(ctx: Context<InitRelayerIndexShard>, shard_index: u32) -> Result<()> {
+    let next_shard = RelayerIndexShard::shard_for(ctx.accounts.relayer_registry.relayer_count);
+    require!(
+        shard_index <= next_shard,
+        PrivacyErrorV2::RelayerIndexMismatch
+    );
+
+    ctx.accounts.relayer_index_shard.initialize(
+        ctx.accounts.relayer_registry.key(),
+        shard_index,
+        ctx.bumps.relayer_index_shard,
+    );
+
+    msg!("Relayer index shard {} created", shard_index);
+    Ok(())
+}
@@ -0,0 +1,303 @@
+//! Rent Reserve Instructions
+//!
+//! - `init_rent_reserve`: authority creates the pool's reserve and sets the
+//!   per-relayer advance cap
+//! - `set_rent_advance_cap`: authority changes the cap
+//! - `fund_rent_reserve`: anyone adds lamports to the reserve
+//! - `init_rent_advance`: operator opens the account its advances accrue to
+//! - `repay_rent_advance`: operator pays advanced rent back to the reserve
+//!
+//! Advances are drawn during withdrawals submitted with the reserve and the
+//! relayer's `RentAdvance` (see [`top_up_relayer_rent`]).
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+
+use crate::error::PrivacyErrorV2;
+use crate::events::{RentAdvanceRepaid, RentReserveFunded};
+use crate::state::{PoolConfigV2, RelayerNode, RentAdvance, RentReserve};
+
+/// Accounts for creating the pool's rent reserve
+#[derive(Accounts)]
+pub struct InitRentReserve<'info> {
+    /// Pool authority (must be signer, pays rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Rent reserve (PDA)
+    #[account(
+        init,
+        payer = authority,
+        space = RentReserve::LEN,
+        seeds = [RentReserve::SEED_PREFIX, pool_config.key().as_ref()],
+        bump,
+    )]
+    pub rent_reserve: Account<'info, RentReserve>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for changing the per-relayer advance cap
+#[derive(Accounts)]
+pub struct SetRentAdvanceCap<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Rent reserve
+    #[account(
+        mut,
+        seeds = [RentReserve::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = rent_reserve.bump,
+    )]
+    pub rent_reserve: Account<'info, RentReserve>,
+}
+
+/// Accounts for funding the rent reserve
+#[derive(Accounts)]
+pub struct FundRentReserve<'info> {
+    /// Lamport source
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    /// Pool configuration account
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Rent reserve
+    #[account(
+        mut,
+        seeds = [RentReserve::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = rent_reserve.bump,
+    )]
+    pub rent_reserve: Account<'info, RentReserve>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for opening a relayer's rent advance account
+#[derive(Accounts)]
+pub struct InitRentAdvance<'info> {
+    /// Relayer operator
+    #[account(mut)]
+    pub operator: Signer<'info>,
+
+    /// Pool configuration account
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Rent reserve the advances will be drawn from
+    #[account(
+        seeds = [RentReserve::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = rent_reserve.bump,
+    )]
+    pub rent_reserve: Account<'info, RentReserve>,
+
+    /// Relayer node owned by the operator, in this pool's registry
+    #[account(
+        seeds = [
+            RelayerNode::SEED_PREFIX,
+            pool_config.relayer_registry.as_ref(),
+            operator.key().as_ref(),
+        ],
+        bump = relayer_node.bump,
+    )]
+    pub relayer_node: Account<'info, RelayerNode>,
+
+    /// Rent advance account (PDA)
+    #[account(
+        init,
+        payer = operator,
+        space = RentAdvance::LEN,
+        seeds = [RentAdvance::SEED_PREFIX, relayer_node.key().as_ref()],
+        bump,
+    )]
+    pub rent_advance: Account<'info, RentAdvance>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for repaying advanced rent
+#[derive(Accounts)]
+pub struct RepayRentAdvance<'info> {
+    /// Relayer operator (lamport source)
+    #[account(mut)]
+    pub operator: Signer<'info>,
+
+    /// Rent reserve the advance was drawn from
+    #[account(
+        mut,
+        address = rent_advance.reserve @ PrivacyErrorV2::RentAdvanceMismatch,
+    )]
+    pub rent_reserve: Account<'info, RentReserve>,
+
+    /// Rent advance account owned by the operator
+    #[account(
+        mut,
+        seeds = [RentAdvance::SEED_PREFIX, rent_advance.relayer_node.as_ref()],
+        bump = rent_advance.bump,
+        constraint = rent_advance.operator == operator.key() @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub rent_advance: Account<'info, RentAdvance>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Handler for init_rent_reserve instruction
+pub fn init_handler(ctx: Context<InitRentReserve>, max_outstanding_per_relayer: u64) -> Result<()> {
+    ctx.accounts.rent_reserve.initialize(
+        ctx.accounts.pool_config.key(),
+        max_outstanding_per_relayer,
+        ctx.bumps.rent_reserve,
+    );
+
+    msg!(
+        "Rent reserve created: {} lamports per relayer",
+        max_outstanding_per_relayer
+    );
+    Ok(())
+}
+
+/// Handler for set_rent_advance_cap instruction
+pub fn set_cap_handler(
+    ctx: Context<SetRentAdvanceCap>,
+    max_outstanding_per_relayer: u64,
+) -> Result<()> {
+    ctx.accounts.rent_reserve.max_outstanding_per_relayer = max_outstanding_per_relayer;
+
+    msg!(
+        "Rent advance cap set: {} lamports per relayer",
+        max_outstanding_per_relayer
+    );
+    Ok(())
+}
+
+/// Handler for fund_rent_reserve instruction
+pub fn fund_handler(ctx: Context<FundRentReserve>, amount: u64) -> Result<()> {
+    require!(amount > 0, PrivacyErrorV2::InvalidAmount);
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.funder.to_account_info(),
+                to: ctx.accounts.rent_reserve.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let reserve = &mut ctx.accounts.rent_reserve;
+    reserve.record_funding(amount)?;
+
+    emit!(RentReserveFunded {
+        pool: ctx.accounts.pool_config.key(),
+        funder: ctx.accounts.funder.key(),
+        amount,
+        outstanding: reserve.outstanding(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Rent reserve funded: {} lamports", amount);
+    Ok(())
+}
+
+/// Handler for init_rent_advance instruction
+pub fn init_advance_handler(ctx: Context<InitRentAdvance>) -> Result<()> {
+    ctx.accounts.rent_advance.initialize(
+        ctx.accounts.rent_reserve.key(),
+        ctx.accounts.relayer_node.key(),
+        ctx.accounts.operator.key(),
+        ctx.bumps.rent_advance,
+    );
+
+    msg!("Rent advance account opened");
+    Ok(())
+}
+
+/// Handler for repay_rent_advance instruction
+pub fn repay_handler(ctx: Context<RepayRentAdvance>, amount: u64) -> Result<()> {
+    ctx.accounts.rent_advance.record_repayment(amount)?;
+    ctx.accounts.rent_reserve.record_repayment(amount)?;
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.operator.to_account_info(),
+                to: ctx.accounts.rent_reserve.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let advance = &ctx.accounts.rent_advance;
+    emit!(RentAdvanceRepaid {
+        pool: ctx.accounts.rent_reserve.pool,
+        relayer: advance.relayer_node,
+        operator: advance.operator,
+        amount,
+        remaining: advance.outstanding,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Rent advance repaid: {} lamports", amount);
+    Ok(())
+}
+
+/// Reimburse `relayer` from the reserve for `rent` lamports it just spent
+/// creating accounts, recording the advance against `advance`.
+///
+/// Returns the amount advanced, which is 0 once the relayer is at its cap
+/// or the reserve has nothing above its rent-exempt minimum; the caller's
+/// instruction proceeds either way.
+pub(crate) fn top_up_relayer_rent<'info>(
+    reserve: &mut Account<'info, RentReserve>,
+    advance: &mut Account<'info, RentAdvance>,
+    relayer: &AccountInfo<'info>,
+    rent: u64,
+    timestamp: i64,
+) -> Result<u64> {
+    require_keys_eq!(
+        advance.reserve,
+        reserve.key(),
+        PrivacyErrorV2::RentAdvanceMismatch
+    );
+    require_keys_eq!(
+        advance.operator,
+        relayer.key(),
+        PrivacyErrorV2::RentAdvanceMismatch
+    );
+
+    // Only lamports above the reserve's rent-exempt minimum are spendable
+    let reserve_info = reserve.to_account_info();
+    let rent_floor = Rent::get()?.minimum_balance(reserve_info.data_len());
+    let available = reserve_info.lamports().saturating_sub(rent_floor);
+
+    let amount = reserve.advance_amount(advance, rent, available);
+    if amount == 0 {
+        return Ok(0);
+    }
+
+    reserve.record_advance(amount)?;
+    advance.record_advance(amount, timestamp)?;
+
+    // Reserve is program-owned, so lamports can be moved directly
+    **reserve_info.try_borrow_mut_lamports()? -= amount;
+    **relayer.try_borrow_mut_lamports()? += amount;
+
+    Ok(amount)
+}
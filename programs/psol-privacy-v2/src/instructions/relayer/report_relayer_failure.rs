@@ -0,0 +1,70 @@
+//! Report Relayer Failure Instruction
+//!
+//! The pool authority records a failure the program cannot observe itself,
+//! such as a relayer accepting a job and never submitting it. The node's
+//! failure count goes up and its reputation drops by
+//! `RELAYER_REPUTATION_FAILURE_PENALTY`; `RelayerFailureReported` carries a
+//! hash of the evidence. Stake is untouched (see `slash_relayer`).
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::events::RelayerFailureReported;
+use crate::state::{PoolConfigV2, RelayerNode, RelayerRegistry};
+use crate::utils::pool_clock;
+
+/// Accounts for reporting a relayer failure
+#[derive(Accounts)]
+pub struct ReportRelayerFailure<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+        has_one = relayer_registry,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Relayer registry account
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+
+    /// Relayer node being reported
+    #[account(
+        mut,
+        seeds = [
+            RelayerNode::SEED_PREFIX,
+            relayer_registry.key().as_ref(),
+            relayer_node.operator.as_ref(),
+        ],
+        bump = relayer_node.bump,
+    )]
+    pub relayer_node: Account<'info, RelayerNode>,
+}
+
+/// Handler for report_relayer_failure instruction
+pub fn handler(ctx: Context<ReportRelayerFailure>, evidence_hash: [u8; 32]) -> Result<()> {
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+    let relayer_node = &mut ctx.accounts.relayer_node;
+
+    relayer_node.record_failure(timestamp)?;
+
+    emit!(RelayerFailureReported {
+        pool: ctx.accounts.pool_config.key(),
+        relayer: relayer_node.key(),
+        operator: relayer_node.operator,
+        failures_reported: relayer_node.failures_reported,
+        reputation_score: relayer_node.reputation_score,
+        evidence_hash,
+        timestamp,
+    });
+
+    msg!(
+        "Relayer {} failure reported ({} total), reputation {}",
+        relayer_node.key(),
+        relayer_node.failures_reported,
+        relayer_node.reputation_score
+    );
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+//! Set Asset Relayer Fee Bounds Instruction
+//!
+//! Configures absolute (token base unit) relayer fee limits for one asset.
+//! These complement the registry's bps bounds: bps alone is dust on tiny
+//! withdrawals and excessive on very large ones.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::events::AssetRelayerFeeBoundsUpdated;
+use crate::state::{AssetVault, PoolConfigV2};
+
+/// Accounts for setting per-asset relayer fee bounds
+#[derive(Accounts)]
+#[instruction(asset_id: [u8; 32])]
+pub struct SetAssetRelayerFeeBounds<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Asset vault to configure
+    #[account(
+        mut,
+        seeds = [
+            AssetVault::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        bump = asset_vault.bump,
+        constraint = asset_vault.pool == pool_config.key() @ PrivacyErrorV2::InvalidVaultPool,
+    )]
+    pub asset_vault: Account<'info, AssetVault>,
+}
+
+/// Handler for set_asset_relayer_fee_bounds instruction
+pub fn handler(
+    ctx: Context<SetAssetRelayerFeeBounds>,
+    asset_id: [u8; 32],
+    min_relayer_fee: u64,
+    max_relayer_fee: u64,
+) -> Result<()> {
+    let asset_vault = &mut ctx.accounts.asset_vault;
+
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    asset_vault.set_relayer_fee_bounds(min_relayer_fee, max_relayer_fee)?;
+    asset_vault.last_activity_at = timestamp;
+
+    emit!(AssetRelayerFeeBoundsUpdated {
+        pool: ctx.accounts.pool_config.key(),
+        asset_id,
+        min_relayer_fee,
+        max_relayer_fee,
+        timestamp,
+    });
+
+    msg!(
+        "Asset relayer fee bounds set: min={}, max={}",
+        min_relayer_fee,
+        max_relayer_fee
+    );
+
+    Ok(())
+}
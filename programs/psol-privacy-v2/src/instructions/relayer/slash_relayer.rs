@@ -0,0 +1,176 @@
+//! Slash Relayer Instruction
+//!
+//! The pool authority slashes a relayer reported for misbehavior the
+//! program cannot see itself (censoring jobs, charging fees other than
+//! advertised off-chain). `slash_bps` of the node's held stake, bonded
+//! first and then unbonding, moves from its stake vault to the treasury
+//! token account of the asset whose mint is the stake mint, where it is
+//! disbursed through the pool treasury's timelock. The node's reputation
+//! drops by `reputation_penalty`, and it can be deactivated at the same
+//! time. The evidence is off-chain; `RelayerSlashed` records its hash so
+//! anyone can audit the decision.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::error::PrivacyErrorV2;
+use crate::events::RelayerSlashed;
+use crate::state::{AssetVault, PoolConfigV2, RelayerIndexShard, RelayerNode, RelayerRegistry};
+use crate::utils::pool_clock;
+
+/// Accounts for slashing a relayer
+#[derive(Accounts)]
+pub struct SlashRelayer<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+        has_one = relayer_registry,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Relayer registry account
+    #[account(mut)]
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+
+    /// Relayer node being slashed
+    #[account(
+        mut,
+        seeds = [
+            RelayerNode::SEED_PREFIX,
+            relayer_registry.key().as_ref(),
+            relayer_node.operator.as_ref(),
+        ],
+        bump = relayer_node.bump,
+    )]
+    pub relayer_node: Account<'info, RelayerNode>,
+
+    /// Relayer index shard holding the node
+    #[account(
+        mut,
+        seeds = [
+            RelayerIndexShard::SEED_PREFIX,
+            relayer_registry.key().as_ref(),
+            &RelayerIndexShard::shard_for(relayer_node.index_position).to_le_bytes(),
+        ],
+        bump = relayer_index_shard.bump,
+    )]
+    pub relayer_index_shard: Account<'info, RelayerIndexShard>,
+
+    /// Registry's stake mint
+    #[account(address = relayer_registry.stake_mint @ PrivacyErrorV2::InvalidMint)]
+    pub stake_mint: InterfaceAccount<'info, Mint>,
+
+    /// Relayer node's stake vault
+    #[account(
+        mut,
+        seeds = [
+            RelayerNode::STAKE_VAULT_SEED_PREFIX,
+            relayer_node.key().as_ref(),
+            stake_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Asset vault of the stake mint
+    #[account(
+        seeds = [
+            AssetVault::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_vault.asset_id.as_ref(),
+        ],
+        bump = asset_vault.bump,
+        constraint = asset_vault.mint == stake_mint.key() @ PrivacyErrorV2::InvalidMint,
+    )]
+    pub asset_vault: Account<'info, AssetVault>,
+
+    /// The asset's treasury token account, receiving the slashed stake
+    #[account(
+        mut,
+        seeds = [AssetVault::TREASURY_SEED_PREFIX, asset_vault.key().as_ref()],
+        bump,
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token program owning the mint
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Handler for slash_relayer instruction
+pub fn handler(
+    ctx: Context<SlashRelayer>,
+    slash_bps: u16,
+    reputation_penalty: u8,
+    deactivate: bool,
+    evidence_hash: [u8; 32],
+) -> Result<()> {
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+    let amount = ctx
+        .accounts
+        .relayer_node
+        .slash(slash_bps, reputation_penalty, timestamp)?;
+
+    if amount > 0 {
+        let registry_key = ctx.accounts.relayer_registry.key();
+        let operator = ctx.accounts.relayer_node.operator;
+        let bump = [ctx.accounts.relayer_node.bump];
+        let node_seeds = RelayerNode::seeds(&registry_key, &operator, &bump);
+        let signer_seeds: &[&[&[u8]]] = &[&node_seeds];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.stake_vault.to_account_info(),
+                    mint: ctx.accounts.stake_mint.to_account_info(),
+                    to: ctx.accounts.treasury_token_account.to_account_info(),
+                    authority: ctx.accounts.relayer_node.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+            ctx.accounts.stake_mint.decimals,
+        )?;
+        ctx.accounts
+            .relayer_registry
+            .release_stake(amount, timestamp)?;
+    }
+
+    let relayer_node = &mut ctx.accounts.relayer_node;
+    let deactivated = deactivate && relayer_node.is_active;
+    if deactivated {
+        relayer_node.deactivate(timestamp);
+        ctx.accounts
+            .relayer_registry
+            .deactivate_relayer(timestamp)?;
+        ctx.accounts.relayer_index_shard.set_active(
+            relayer_node.index_position,
+            &relayer_node.operator,
+            false,
+        )?;
+    }
+
+    emit!(RelayerSlashed {
+        pool: ctx.accounts.pool_config.key(),
+        relayer: relayer_node.key(),
+        operator: relayer_node.operator,
+        amount,
+        slash_bps,
+        reputation_score: relayer_node.reputation_score,
+        deactivated,
+        evidence_hash,
+        timestamp,
+    });
+
+    msg!(
+        "Relayer {} slashed: {} to treasury, reputation {}",
+        relayer_node.key(),
+        amount,
+        relayer_node.reputation_score
+    );
+
+    Ok(())
+}
@@ -0,0 +1,349 @@
+//! Relayer Staking Instructions
+//!
+//! - `set_relayer_stake_mint`: authority picks the mint relayers stake
+//!   (only while no stake is held)
+//! - `init_relayer_stake_vault`: operator creates its node's stake vault
+//! - `stake_relayer`: operator locks tokens in the vault
+//! - `unstake_relayer`: operator starts unbonding part of its stake
+//! - `withdraw_relayer_stake`: operator takes unbonded stake back once
+//!   `RELAYER_UNBONDING_SECONDS` have passed
+//!
+//! The vault is a token account owned by the relayer node PDA, so only the
+//! program can move stake out of it. Unbonding stake stays in the vault
+//! until released.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::error::PrivacyErrorV2;
+use crate::events::{
+    RelayerStakeMintSet, RelayerStakeWithdrawn, RelayerStaked, RelayerUnstakeRequested,
+};
+use crate::state::{PoolConfigV2, RelayerNode, RelayerRegistry};
+use crate::utils::pool_clock;
+
+/// Accounts for setting the registry's stake mint
+#[derive(Accounts)]
+pub struct SetRelayerStakeMint<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+        has_one = relayer_registry,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Relayer registry account
+    #[account(mut)]
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+
+    /// Mint relayers will stake
+    pub stake_mint: InterfaceAccount<'info, Mint>,
+}
+
+/// Accounts for creating a relayer node's stake vault
+#[derive(Accounts)]
+pub struct InitRelayerStakeVault<'info> {
+    /// Relayer operator (must be signer, pays rent)
+    #[account(mut)]
+    pub operator: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = relayer_registry,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Relayer registry account
+    #[account(
+        constraint = relayer_registry.has_stake_mint() @ PrivacyErrorV2::RelayerStakeMintNotSet,
+    )]
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+
+    /// Relayer node owned by the operator
+    #[account(
+        has_one = operator @ PrivacyErrorV2::Unauthorized,
+        seeds = [
+            RelayerNode::SEED_PREFIX,
+            relayer_registry.key().as_ref(),
+            operator.key().as_ref(),
+        ],
+        bump = relayer_node.bump,
+    )]
+    pub relayer_node: Account<'info, RelayerNode>,
+
+    /// Registry's stake mint
+    #[account(address = relayer_registry.stake_mint @ PrivacyErrorV2::InvalidMint)]
+    pub stake_mint: InterfaceAccount<'info, Mint>,
+
+    /// Stake vault (PDA token account owned by the relayer node)
+    #[account(
+        init,
+        payer = operator,
+        token::mint = stake_mint,
+        token::authority = relayer_node,
+        token::token_program = token_program,
+        seeds = [
+            RelayerNode::STAKE_VAULT_SEED_PREFIX,
+            relayer_node.key().as_ref(),
+            stake_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token program owning the mint
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for moving tokens into or out of a relayer node's stake vault
+#[derive(Accounts)]
+pub struct MoveRelayerStake<'info> {
+    /// Relayer operator (must be signer)
+    pub operator: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = relayer_registry,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Relayer registry account
+    #[account(mut)]
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+
+    /// Relayer node owned by the operator
+    #[account(
+        mut,
+        has_one = operator @ PrivacyErrorV2::Unauthorized,
+        seeds = [
+            RelayerNode::SEED_PREFIX,
+            relayer_registry.key().as_ref(),
+            operator.key().as_ref(),
+        ],
+        bump = relayer_node.bump,
+    )]
+    pub relayer_node: Account<'info, RelayerNode>,
+
+    /// Registry's stake mint
+    #[account(address = relayer_registry.stake_mint @ PrivacyErrorV2::InvalidMint)]
+    pub stake_mint: InterfaceAccount<'info, Mint>,
+
+    /// Relayer node's stake vault
+    #[account(
+        mut,
+        seeds = [
+            RelayerNode::STAKE_VAULT_SEED_PREFIX,
+            relayer_node.key().as_ref(),
+            stake_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Operator's token account (source when staking, destination when
+    /// withdrawing)
+    #[account(
+        mut,
+        constraint = operator_token_account.mint == stake_mint.key() @ PrivacyErrorV2::InvalidMint,
+    )]
+    pub operator_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token program owning the mint
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Accounts for starting to unbond stake
+#[derive(Accounts)]
+pub struct UnstakeRelayer<'info> {
+    /// Relayer operator (must be signer)
+    pub operator: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = relayer_registry,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Relayer registry account
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+
+    /// Relayer node owned by the operator
+    #[account(
+        mut,
+        has_one = operator @ PrivacyErrorV2::Unauthorized,
+        seeds = [
+            RelayerNode::SEED_PREFIX,
+            relayer_registry.key().as_ref(),
+            operator.key().as_ref(),
+        ],
+        bump = relayer_node.bump,
+    )]
+    pub relayer_node: Account<'info, RelayerNode>,
+}
+
+/// Handler for set_relayer_stake_mint instruction
+pub fn set_mint_handler(ctx: Context<SetRelayerStakeMint>) -> Result<()> {
+    let registry = &mut ctx.accounts.relayer_registry;
+    let stake_mint = ctx.accounts.stake_mint.key();
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    registry.set_stake_mint(stake_mint, timestamp)?;
+
+    emit!(RelayerStakeMintSet {
+        pool: ctx.accounts.pool_config.key(),
+        registry: registry.key(),
+        stake_mint,
+        timestamp,
+    });
+
+    msg!("Relayer stake mint set: {}", stake_mint);
+
+    Ok(())
+}
+
+/// Handler for init_relayer_stake_vault instruction
+pub fn init_vault_handler(ctx: Context<InitRelayerStakeVault>) -> Result<()> {
+    msg!(
+        "Stake vault {} created for relayer {}",
+        ctx.accounts.stake_vault.key(),
+        ctx.accounts.relayer_node.key()
+    );
+
+    Ok(())
+}
+
+/// Handler for stake_relayer instruction
+pub fn stake_handler(ctx: Context<MoveRelayerStake>, amount: u64) -> Result<()> {
+    require!(amount > 0, PrivacyErrorV2::InvalidAmount);
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+
+    token_interface::transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.operator_token_account.to_account_info(),
+                mint: ctx.accounts.stake_mint.to_account_info(),
+                to: ctx.accounts.stake_vault.to_account_info(),
+                authority: ctx.accounts.operator.to_account_info(),
+            },
+        ),
+        amount,
+        ctx.accounts.stake_mint.decimals,
+    )?;
+
+    // Credit what arrived, in case the mint withholds a transfer fee
+    let before = ctx.accounts.stake_vault.amount;
+    ctx.accounts.stake_vault.reload()?;
+    let received = ctx
+        .accounts
+        .stake_vault
+        .amount
+        .checked_sub(before)
+        .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+
+    let relayer_node = &mut ctx.accounts.relayer_node;
+    relayer_node.add_stake(received)?;
+    ctx.accounts
+        .relayer_registry
+        .record_stake(received, timestamp)?;
+
+    emit!(RelayerStaked {
+        pool: ctx.accounts.pool_config.key(),
+        relayer: relayer_node.key(),
+        operator: ctx.accounts.operator.key(),
+        amount: received,
+        stake_amount: relayer_node.stake_amount,
+        timestamp,
+    });
+
+    msg!(
+        "Relayer {} staked {} (total {})",
+        relayer_node.key(),
+        received,
+        relayer_node.stake_amount
+    );
+
+    Ok(())
+}
+
+/// Handler for unstake_relayer instruction
+///
+/// The tokens stay in the stake vault until `withdraw_relayer_stake`.
+pub fn unstake_handler(ctx: Context<UnstakeRelayer>, amount: u64) -> Result<()> {
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+    let relayer_node = &mut ctx.accounts.relayer_node;
+
+    relayer_node.begin_unbonding(amount, timestamp)?;
+
+    emit!(RelayerUnstakeRequested {
+        pool: ctx.accounts.pool_config.key(),
+        relayer: relayer_node.key(),
+        operator: ctx.accounts.operator.key(),
+        amount,
+        stake_amount: relayer_node.stake_amount,
+        unbonding_amount: relayer_node.unbonding_amount,
+        release_at: relayer_node.unbonding_release_at,
+        timestamp,
+    });
+
+    msg!(
+        "Relayer {} unbonding {} until {}",
+        relayer_node.key(),
+        relayer_node.unbonding_amount,
+        relayer_node.unbonding_release_at
+    );
+
+    Ok(())
+}
+
+/// Handler for withdraw_relayer_stake instruction
+pub fn withdraw_handler(ctx: Context<MoveRelayerStake>) -> Result<()> {
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+    let amount = ctx.accounts.relayer_node.take_unbonded(timestamp)?;
+
+    let registry_key = ctx.accounts.relayer_registry.key();
+    let operator_key = ctx.accounts.operator.key();
+    let bump = [ctx.accounts.relayer_node.bump];
+    let node_seeds = RelayerNode::seeds(&registry_key, &operator_key, &bump);
+    let signer_seeds: &[&[&[u8]]] = &[&node_seeds];
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.stake_vault.to_account_info(),
+                mint: ctx.accounts.stake_mint.to_account_info(),
+                to: ctx.accounts.operator_token_account.to_account_info(),
+                authority: ctx.accounts.relayer_node.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+        ctx.accounts.stake_mint.decimals,
+    )?;
+    ctx.accounts
+        .relayer_registry
+        .release_stake(amount, timestamp)?;
+
+    emit!(RelayerStakeWithdrawn {
+        pool: ctx.accounts.pool_config.key(),
+        relayer: ctx.accounts.relayer_node.key(),
+        operator: operator_key,
+        amount,
+        timestamp,
+    });
+
+    msg!(
+        "Relayer {} withdrew {} of stake",
+        ctx.accounts.relayer_node.key(),
+        amount
+    );
+
+    Ok(())
+}
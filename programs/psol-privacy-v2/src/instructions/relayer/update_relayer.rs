@@ -1,12 +1,20 @@
 //! Update Relayer Instruction
 //!
 //! Updates relayer configuration including fee and metadata.
+//!
+//! `update_relayer_signed` lets a cold operator key pre-sign fee/active
+//! changes that any hot key can later submit. Each signed update carries the
+//! node's current `update_nonce` and an expiry, so it applies at most once.
 
 use anchor_lang::prelude::*;
+use solana_sdk_ids::sysvar::instructions as sysvar_instructions;
 
 use crate::error::PrivacyErrorV2;
 use crate::events::RelayerUpdated;
-use crate::state::{PoolConfigV2, RelayerNode, RelayerRegistry};
+use crate::state::{
+    PoolConfigV2, RelayerIndexShard, RelayerMetadata, RelayerNode, RelayerRegistry,
+};
+use crate::utils::{pool_clock, verify_preceding_ed25519_signature};
 
 /// Accounts for updating a relayer
 #[derive(Accounts)]
@@ -45,6 +53,64 @@ pub struct UpdateRelayer<'info> {
     pub relayer_node: Account<'info, RelayerNode>,
     // REMOVED: Redundant `registry: UncheckedAccount`
     // The PDA seeds already bind relayer_node to relayer_registry
+    /// Optional: relayer index shard; required when `is_active` changes
+    #[account(
+        mut,
+        seeds = [
+            RelayerIndexShard::SEED_PREFIX,
+            relayer_registry.key().as_ref(),
+            &RelayerIndexShard::shard_for(relayer_node.index_position).to_le_bytes(),
+        ],
+        bump = relayer_index_shard.bump,
+    )]
+    pub relayer_index_shard: Option<Account<'info, RelayerIndexShard>>,
+}
+
+/// Accounts for submitting an operator-signed relayer update
+#[derive(Accounts)]
+pub struct UpdateRelayerSigned<'info> {
+    /// Key submitting the update; needs no authority over the relayer
+    pub submitter: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = relayer_registry,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Relayer registry account
+    #[account(mut)]
+    pub relayer_registry: Account<'info, RelayerRegistry>,
+
+    /// Relayer node account
+    #[account(
+        mut,
+        seeds = [
+            RelayerNode::SEED_PREFIX,
+            relayer_registry.key().as_ref(),
+            relayer_node.operator.as_ref(),
+        ],
+        bump = relayer_node.bump,
+        constraint = relayer_node.registry == relayer_registry.key()
+            @ PrivacyErrorV2::RelayerNodeRegistryMismatch,
+    )]
+    pub relayer_node: Account<'info, RelayerNode>,
+
+    /// CHECK: Address constrained to the instructions sysvar
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    /// Optional: relayer index shard; required when `is_active` changes
+    #[account(
+        mut,
+        seeds = [
+            RelayerIndexShard::SEED_PREFIX,
+            relayer_registry.key().as_ref(),
+            &RelayerIndexShard::shard_for(relayer_node.index_position).to_le_bytes(),
+        ],
+        bump = relayer_index_shard.bump,
+    )]
+    pub relayer_index_shard: Option<Account<'info, RelayerIndexShard>>,
 }
 
 /// Handler for update_relayer instruction
@@ -53,24 +119,87 @@ pub fn handler(
     fee_bps: Option<u16>,
     metadata_uri: Option<String>,
     is_active: Option<bool>,
+    metadata: Option<RelayerMetadata>,
+) -> Result<()> {
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+    let pool = ctx.accounts.pool_config.key();
+
+    apply_update(
+        pool,
+        &mut ctx.accounts.relayer_registry,
+        &mut ctx.accounts.relayer_node,
+        ctx.accounts.relayer_index_shard.as_mut(),
+        fee_bps,
+        metadata,
+        metadata_uri,
+        is_active,
+        timestamp,
+    )
+}
+
+/// Handler for update_relayer_signed instruction
+///
+/// The preceding instruction must be an ed25519 verification of
+/// `RelayerNode::signed_update_message` by the relayer operator.
+pub fn signed_handler(
+    ctx: Context<UpdateRelayerSigned>,
+    fee_bps: Option<u16>,
+    is_active: Option<bool>,
+    nonce: u64,
+    expires_at: i64,
 ) -> Result<()> {
-    let registry = &mut ctx.accounts.relayer_registry;
-    let relayer_node = &mut ctx.accounts.relayer_node;
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+    require!(timestamp <= expires_at, PrivacyErrorV2::SignedUpdateExpired);
+
+    let node_key = ctx.accounts.relayer_node.key();
+    let message =
+        RelayerNode::signed_update_message(&node_key, fee_bps, is_active, nonce, expires_at);
+    verify_preceding_ed25519_signature(
+        &ctx.accounts.instructions_sysvar.to_account_info(),
+        &ctx.accounts.relayer_node.operator,
+        &message,
+    )?;
+
+    ctx.accounts.relayer_node.consume_update_nonce(nonce)?;
+    msg!("Signed relayer update, nonce {}", nonce);
+
+    let pool = ctx.accounts.pool_config.key();
+    apply_update(
+        pool,
+        &mut ctx.accounts.relayer_registry,
+        &mut ctx.accounts.relayer_node,
+        ctx.accounts.relayer_index_shard.as_mut(),
+        fee_bps,
+        None,
+        None,
+        is_active,
+        timestamp,
+    )
+}
 
+#[allow(clippy::too_many_arguments)]
+fn apply_update(
+    pool: Pubkey,
+    registry: &mut Account<RelayerRegistry>,
+    relayer_node: &mut Account<RelayerNode>,
+    index_shard: Option<&mut Account<RelayerIndexShard>>,
+    fee_bps: Option<u16>,
+    metadata: Option<RelayerMetadata>,
+    metadata_uri: Option<String>,
+    is_active: Option<bool>,
+    timestamp: i64,
+) -> Result<()> {
     // If updating fee, validate it's within bounds
     if let Some(fee) = fee_bps {
         registry.validate_fee(fee)?;
     }
 
-    let clock = Clock::get()?;
-    let timestamp = clock.unix_timestamp;
-
     // Track if we're changing active status
     let was_active = relayer_node.is_active;
     let will_be_active = is_active.unwrap_or(was_active);
 
     // Update relayer node
-    relayer_node.update(fee_bps, metadata_uri, is_active, timestamp)?;
+    relayer_node.update(fee_bps, metadata, metadata_uri, is_active, timestamp)?;
 
     // Update registry counts if active status changed
     if was_active && !will_be_active {
@@ -79,11 +208,21 @@ pub fn handler(
         registry.reactivate_relayer(timestamp)?;
     }
 
+    // Keep the index's active bitmap in step with the node
+    if was_active != will_be_active {
+        let shard = index_shard.ok_or(error!(PrivacyErrorV2::RelayerIndexMismatch))?;
+        shard.set_active(
+            relayer_node.index_position,
+            &relayer_node.operator,
+            will_be_active,
+        )?;
+    }
+
     // Emit event
     emit!(RelayerUpdated {
-        pool: ctx.accounts.pool_config.key(),
+        pool,
         relayer: relayer_node.key(),
-        operator: ctx.accounts.operator.key(),
+        operator: relayer_node.operator,
         fee_bps: relayer_node.fee_bps,
         is_active: relayer_node.is_active,
         timestamp,
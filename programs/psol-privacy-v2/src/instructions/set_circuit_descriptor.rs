@@ -0,0 +1,207 @@
+//! Circuit Descriptor Instructions
+//!
+//! Record the circuit parameters (version, public input layout, tree depth,
+//! Poseidon parameter set) that a proof type's VK was generated for.
+//! Descriptors are validated against the pool (and the Poseidon parameter
+//! set its Merkle tree hashes with) when written and again when passed to
+//! the VK setup instructions, and can only change while the VK for that
+//! proof type is unlocked.
+//!
+//! `view_poseidon_params` returns the parameter set hash the pool's tree
+//! was created with, for circuit tooling to compare against before writing
+//! a descriptor.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::events::CircuitDescriptorSet;
+use crate::state::{CircuitDescriptor, MerkleTreeV2, PoolConfigV2};
+use crate::ProofType;
+
+/// Accounts for creating a circuit descriptor
+#[derive(Accounts)]
+#[instruction(proof_type: ProofType)]
+pub struct SetCircuitDescriptor<'info> {
+    /// Pool authority (must be signer, pays rent)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Pool's Merkle tree (source of the Poseidon parameter set)
+    #[account(
+        constraint = pool_config.merkle_tree == merkle_tree.key() @ PrivacyErrorV2::InvalidMerkleTreePool,
+    )]
+    pub merkle_tree: Box<Account<'info, MerkleTreeV2>>,
+
+    /// Circuit descriptor PDA for this proof type
+    #[account(
+        init,
+        payer = authority,
+        space = CircuitDescriptor::LEN,
+        seeds = [
+            CircuitDescriptor::SEED_PREFIX,
+            proof_type.as_seed(),
+            pool_config.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub circuit_descriptor: Account<'info, CircuitDescriptor>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for updating an existing circuit descriptor
+#[derive(Accounts)]
+#[instruction(proof_type: ProofType)]
+pub struct UpdateCircuitDescriptor<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Pool's Merkle tree (source of the Poseidon parameter set)
+    #[account(
+        constraint = pool_config.merkle_tree == merkle_tree.key() @ PrivacyErrorV2::InvalidMerkleTreePool,
+    )]
+    pub merkle_tree: Box<Account<'info, MerkleTreeV2>>,
+
+    /// Circuit descriptor PDA for this proof type
+    #[account(
+        mut,
+        seeds = [
+            CircuitDescriptor::SEED_PREFIX,
+            proof_type.as_seed(),
+            pool_config.key().as_ref(),
+        ],
+        bump = circuit_descriptor.bump,
+    )]
+    pub circuit_descriptor: Account<'info, CircuitDescriptor>,
+}
+
+/// Accounts for reading the pool's Poseidon parameter set hash
+#[derive(Accounts)]
+pub struct ViewPoseidonParams<'info> {
+    /// Pool configuration account
+    pub pool_config: Box<Account<'info, PoolConfigV2>>,
+
+    /// Pool's Merkle tree
+    #[account(
+        constraint = pool_config.merkle_tree == merkle_tree.key() @ PrivacyErrorV2::InvalidMerkleTreePool,
+    )]
+    pub merkle_tree: Box<Account<'info, MerkleTreeV2>>,
+}
+
+/// Handler for set_circuit_descriptor instruction
+pub fn set_handler(
+    ctx: Context<SetCircuitDescriptor>,
+    proof_type: ProofType,
+    circuit_version: u16,
+    public_input_layout_hash: [u8; 32],
+    tree_depth: u8,
+    poseidon_params_hash: [u8; 32],
+) -> Result<()> {
+    let bump = ctx.bumps.circuit_descriptor;
+    apply(
+        &ctx.accounts.pool_config,
+        &ctx.accounts.merkle_tree,
+        &mut ctx.accounts.circuit_descriptor,
+        proof_type,
+        circuit_version,
+        public_input_layout_hash,
+        tree_depth,
+        poseidon_params_hash,
+        bump,
+    )
+}
+
+/// Handler for update_circuit_descriptor instruction
+pub fn update_handler(
+    ctx: Context<UpdateCircuitDescriptor>,
+    proof_type: ProofType,
+    circuit_version: u16,
+    public_input_layout_hash: [u8; 32],
+    tree_depth: u8,
+    poseidon_params_hash: [u8; 32],
+) -> Result<()> {
+    let bump = ctx.accounts.circuit_descriptor.bump;
+    apply(
+        &ctx.accounts.pool_config,
+        &ctx.accounts.merkle_tree,
+        &mut ctx.accounts.circuit_descriptor,
+        proof_type,
+        circuit_version,
+        public_input_layout_hash,
+        tree_depth,
+        poseidon_params_hash,
+        bump,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn apply(
+    pool_config: &Account<PoolConfigV2>,
+    merkle_tree: &MerkleTreeV2,
+    descriptor: &mut CircuitDescriptor,
+    proof_type: ProofType,
+    circuit_version: u16,
+    public_input_layout_hash: [u8; 32],
+    tree_depth: u8,
+    poseidon_params_hash: [u8; 32],
+    bump: u8,
+) -> Result<()> {
+    // Descriptor is frozen together with the VK it describes
+    pool_config.require_vk_unlocked(proof_type)?;
+
+    let timestamp = Clock::get()?.unix_timestamp;
+    let pool_key = pool_config.key();
+
+    descriptor.set(
+        pool_key,
+        proof_type,
+        circuit_version,
+        public_input_layout_hash,
+        tree_depth,
+        poseidon_params_hash,
+        timestamp,
+        bump,
+    );
+    descriptor.validate_for_pool(
+        &pool_key,
+        pool_config.tree_depth,
+        &merkle_tree.poseidon_params_hash,
+        proof_type,
+    )?;
+
+    emit!(CircuitDescriptorSet {
+        pool: pool_key,
+        proof_type: proof_type as u8,
+        circuit_version,
+        public_input_layout_hash,
+        tree_depth,
+        poseidon_params_hash,
+        timestamp,
+    });
+
+    msg!(
+        "Circuit descriptor set for {:?}: version {}, depth {}",
+        proof_type,
+        circuit_version,
+        tree_depth
+    );
+
+    Ok(())
+}
+
+/// Handler for view_poseidon_params instruction
+pub fn view_params_handler(ctx: Context<ViewPoseidonParams>) -> Result<[u8; 32]> {
+    Ok(ctx.accounts.merkle_tree.poseidon_params_hash)
+}
@@ -1,11 +1,13 @@
 //! Set Feature Flags Instruction
 //!
 //! Allows pool authority to enable/disable feature flags on the pool.
-//! This includes FEATURE_YIELD_ENFORCEMENT for LST yield fee enforcement.
+//! This includes FEATURE_YIELD_ENFORCEMENT for LST yield fee enforcement
+//! FEATURE_REQUIRE_VK_CANARY for canary-gated withdrawals, and
+//! FEATURE_COARSE_LIQUIDITY_STATS for bucketed asset vault totals.
 
-use anchor_lang::prelude::*;
 use crate::error::PrivacyErrorV2;
 use crate::state::PoolConfigV2;
+use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
 pub struct SetFeatureFlags<'info> {
@@ -25,13 +27,17 @@ pub struct SetFeatureFlags<'info> {
 pub fn enable_feature(ctx: Context<SetFeatureFlags>, feature: u8) -> Result<()> {
     // Validate feature bit is a single valid flag
     require!(
-        feature.count_ones() == 1 && feature <= PoolConfigV2::FEATURE_YIELD_ENFORCEMENT,
+        feature.count_ones() == 1 && feature <= PoolConfigV2::FEATURE_COARSE_LIQUIDITY_STATS,
         PrivacyErrorV2::InvalidFeatureFlag
     );
-    
+
     ctx.accounts.pool_config.enable_feature(feature);
-    
-    msg!("Feature {} enabled. New flags: {}", feature, ctx.accounts.pool_config.feature_flags);
+
+    msg!(
+        "Feature {} enabled. New flags: {}",
+        feature,
+        ctx.accounts.pool_config.feature_flags
+    );
     Ok(())
 }
 
@@ -39,18 +45,22 @@ pub fn enable_feature(ctx: Context<SetFeatureFlags>, feature: u8) -> Result<()>
 pub fn disable_feature(ctx: Context<SetFeatureFlags>, feature: u8) -> Result<()> {
     // Validate feature bit is a single valid flag
     require!(
-        feature.count_ones() == 1 && feature <= PoolConfigV2::FEATURE_YIELD_ENFORCEMENT,
+        feature.count_ones() == 1 && feature <= PoolConfigV2::FEATURE_COARSE_LIQUIDITY_STATS,
         PrivacyErrorV2::InvalidFeatureFlag
     );
-    
+
     // Don't allow disabling MASP (core functionality)
     require!(
         feature != PoolConfigV2::FEATURE_MASP,
         PrivacyErrorV2::CannotDisableCoreFeature
     );
-    
+
     ctx.accounts.pool_config.disable_feature(feature);
-    
-    msg!("Feature {} disabled. New flags: {}", feature, ctx.accounts.pool_config.feature_flags);
+
+    msg!(
+        "Feature {} disabled. New flags: {}",
+        feature,
+        ctx.accounts.pool_config.feature_flags
+    );
     Ok(())
 }
@@ -2,7 +2,11 @@
 //!
 //! Upload large verification keys in multiple transactions.
 //! Flow:
-//!   initialize_vk_v2 -> append_vk_ic_v2 (multiple) -> finalize_vk_v2
+//!   create_verification_key_account -> initialize_vk_v2
+//!     -> append_vk_ic_v2 (multiple) -> finalize_vk_v2
+//!
+//! The `*_compressed_v2` variants take 32-byte G1 / 64-byte G2 points and
+//! decompress them on-chain, roughly halving upload size.
 //!
 //! State model (VerificationKeyAccountV2):
 //! - is_initialized: VK is complete and usable
@@ -10,16 +14,16 @@
 
 use anchor_lang::prelude::*;
 
+use crate::crypto::{g1_decompress, g2_decompress};
 use crate::error::PrivacyErrorV2;
 use crate::events::VerificationKeySetV2;
-use crate::state::{PoolConfigV2, VerificationKeyAccountV2};
+use crate::state::{CircuitDescriptor, MerkleTreeV2, PoolConfigV2, VerificationKeyAccountV2};
 use crate::ProofType;
 
 /// Initialize VK account with base data (alpha, beta, gamma, delta)
 #[derive(Accounts)]
 #[instruction(proof_type: ProofType)]
 pub struct InitializeVkV2<'info> {
-    #[account(mut)]
     pub authority: Signer<'info>,
 
     #[account(
@@ -28,19 +32,37 @@ pub struct InitializeVkV2<'info> {
     )]
     pub pool_config: Account<'info, PoolConfigV2>,
 
+    /// Created by `create_verification_key_account`
     #[account(
-        init_if_needed,
-        payer = authority,
-        space = VerificationKeyAccountV2::space(VerificationKeyAccountV2::DEFAULT_MAX_IC_POINTS),
+        mut,
         seeds = [proof_type.as_seed(), pool_config.key().as_ref()],
-        bump,
+        bump = vk_account.bump,
     )]
     pub vk_account: Account<'info, VerificationKeyAccountV2>,
 
-    pub system_program: Program<'info, System>,
+    /// Optional: circuit descriptor, checked against the pool before the VK is set
+    #[account(
+        seeds = [
+            CircuitDescriptor::SEED_PREFIX,
+            proof_type.as_seed(),
+            pool_config.key().as_ref(),
+        ],
+        bump = circuit_descriptor.bump,
+    )]
+    pub circuit_descriptor: Option<Account<'info, CircuitDescriptor>>,
+
+    /// Pool's Merkle tree (source of the Poseidon parameter set)
+    #[account(
+        constraint = pool_config.merkle_tree == merkle_tree.key() @ PrivacyErrorV2::InvalidMerkleTreePool,
+    )]
+    pub merkle_tree: Box<Account<'info, MerkleTreeV2>>,
 }
 
 /// Initialize VK with base curve points (no IC yet)
+///
+/// The ceremony transcript hash and contributor count (both zero if none)
+/// are stored now and reported when the VK is finalized.
+#[allow(clippy::too_many_arguments)]
 pub fn initialize_vk_handler(
     ctx: Context<InitializeVkV2>,
     proof_type: ProofType,
@@ -49,6 +71,8 @@ pub fn initialize_vk_handler(
     vk_gamma_g2: [u8; 128],
     vk_delta_g2: [u8; 128],
     expected_ic_count: u8,
+    ceremony_transcript_hash: [u8; 32],
+    ceremony_contributors: u32,
 ) -> Result<()> {
     let pool_config = &ctx.accounts.pool_config;
     let vk_account = &mut ctx.accounts.vk_account;
@@ -63,10 +87,19 @@ pub fn initialize_vk_handler(
         PrivacyErrorV2::VkAlreadyFinalized
     );
 
+    // Reject a VK whose circuit was built for different pool parameters
+    if let Some(descriptor) = ctx.accounts.circuit_descriptor.as_ref() {
+        descriptor.validate_for_pool(
+            &pool_config.key(),
+            pool_config.tree_depth,
+            &ctx.accounts.merkle_tree.poseidon_params_hash,
+            proof_type,
+        )?;
+    }
+
     // Validate expected IC count for the proof type
-    let required_ic = VerificationKeyAccountV2::expected_ic_points(proof_type);
     require!(
-        expected_ic_count == required_ic,
+        VerificationKeyAccountV2::accepts_ic_points(proof_type, expected_ic_count),
         PrivacyErrorV2::VkIcLengthMismatch
     );
 
@@ -87,9 +120,9 @@ pub fn initialize_vk_handler(
     vk_account.set_at = 0;
     vk_account.locked_at = 0;
     vk_account.vk_hash = [0u8; 32];
-    vk_account._reserved = [0u8; 32];
-
-    vk_account.bump = ctx.bumps.vk_account;
+    vk_account.reset_canary();
+    vk_account.set_ceremony(ceremony_transcript_hash, ceremony_contributors)?;
+    vk_account._reserved = [0u8; 23];
 
     msg!(
         "Initialized VK for {:?}, expecting {} IC points",
@@ -100,6 +133,32 @@ pub fn initialize_vk_handler(
     Ok(())
 }
 
+/// Initialize VK from compressed base curve points
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_vk_compressed_handler(
+    ctx: Context<InitializeVkV2>,
+    proof_type: ProofType,
+    vk_alpha_g1: [u8; 32],
+    vk_beta_g2: [u8; 64],
+    vk_gamma_g2: [u8; 64],
+    vk_delta_g2: [u8; 64],
+    expected_ic_count: u8,
+    ceremony_transcript_hash: [u8; 32],
+    ceremony_contributors: u32,
+) -> Result<()> {
+    initialize_vk_handler(
+        ctx,
+        proof_type,
+        g1_decompress(&vk_alpha_g1)?,
+        g2_decompress(&vk_beta_g2)?,
+        g2_decompress(&vk_gamma_g2)?,
+        g2_decompress(&vk_delta_g2)?,
+        expected_ic_count,
+        ceremony_transcript_hash,
+        ceremony_contributors,
+    )
+}
+
 /// Append IC points to VK account
 #[derive(Accounts)]
 #[instruction(proof_type: ProofType)]
@@ -155,6 +214,19 @@ pub fn append_vk_ic_handler(
     Ok(())
 }
 
+/// Append compressed IC points
+pub fn append_vk_ic_compressed_handler(
+    ctx: Context<AppendVkIcV2>,
+    proof_type: ProofType,
+    ic_points: Vec<[u8; 32]>,
+) -> Result<()> {
+    let ic_points = ic_points
+        .iter()
+        .map(g1_decompress)
+        .collect::<Result<Vec<_>>>()?;
+    append_vk_ic_handler(ctx, proof_type, ic_points)
+}
+
 /// Finalize VK - marks it as ready for use
 #[derive(Accounts)]
 #[instruction(proof_type: ProofType)]
@@ -218,6 +290,8 @@ pub fn finalize_vk_handler(ctx: Context<FinalizeVkV2>, proof_type: ProofType) ->
         proof_type: proof_type as u8,
         ic_length: vk_account.vk_ic_len,
         vk_hash: vk_account.vk_hash,
+        ceremony_transcript_hash: vk_account.ceremony_transcript_hash,
+        ceremony_contributors: vk_account.ceremony_contributors,
         authority: ctx.accounts.authority.key(),
         timestamp,
     });
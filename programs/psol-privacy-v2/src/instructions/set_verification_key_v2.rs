@@ -2,32 +2,42 @@
 //!
 //! Sets and locks verification keys for different proof types.
 //! Each proof type (Deposit, Withdraw, JoinSplit, Membership) has its own VK account.
+//! The account is created empty by `create_verification_key_account` (a
+//! plain `init`, so it can never be re-created) and the key is written by
+//! `set_verification_key_data` or the chunked upload.
+//! A canary proof can be submitted after setting a VK to confirm it matches
+//! the circuit build that produces user proofs.
+//! A key set before ceremony data was recorded (including a locked one) can
+//! have its ceremony recorded once by `record_vk_ceremony`.
 
 use anchor_lang::prelude::*;
 
 use crate::error::PrivacyErrorV2;
-use crate::events::{VerificationKeyLockedV2, VerificationKeySetV2};
-use crate::state::{PoolConfigV2, VerificationKeyAccountV2};
+use crate::events::{
+    VerificationKeyCanaryPassed, VerificationKeyCeremonyRecorded, VerificationKeyLockedV2,
+    VerificationKeySetV2,
+};
+use crate::state::{CircuitDescriptor, MerkleTreeV2, PoolConfigV2, VerificationKeyAccountV2};
 use crate::ProofType;
 
-/// Accounts for setting a verification key
+/// Accounts for creating an empty verification key account
 #[derive(Accounts)]
 #[instruction(proof_type: ProofType)]
-pub struct SetVerificationKeyV2<'info> {
-    /// Pool authority (must be signer)
+pub struct CreateVerificationKeyAccount<'info> {
+    /// Pool authority (must be signer, pays rent)
     #[account(mut)]
     pub authority: Signer<'info>,
 
     /// Pool configuration account
     #[account(
-        mut,
         has_one = authority @ PrivacyErrorV2::Unauthorized,
     )]
     pub pool_config: Account<'info, PoolConfigV2>,
 
-    /// Verification key account (PDA based on proof type)
+    /// Verification key account (PDA based on proof type); fails if it
+    /// already exists
     #[account(
-        init_if_needed,
+        init,
         payer = authority,
         space = VerificationKeyAccountV2::space(VerificationKeyAccountV2::DEFAULT_MAX_IC_POINTS),
         seeds = [proof_type.as_seed(), pool_config.key().as_ref()],
@@ -39,16 +49,83 @@ pub struct SetVerificationKeyV2<'info> {
     pub system_program: Program<'info, System>,
 }
 
-/// Handler for set_verification_key_v2 instruction
+/// Handler for create_verification_key_account instruction
+///
+/// Allocates the VK account empty (`is_initialized == false`); the key
+/// itself is written by `set_verification_key_data` or the chunked upload.
+pub fn create_handler(
+    ctx: Context<CreateVerificationKeyAccount>,
+    proof_type: ProofType,
+) -> Result<()> {
+    let pool_config = &ctx.accounts.pool_config;
+    pool_config.require_vk_unlocked(proof_type)?;
+
+    ctx.accounts
+        .vk_account
+        .initialize(pool_config.key(), proof_type, ctx.bumps.vk_account);
+
+    msg!("Created VK account for proof type {:?}", proof_type);
+
+    Ok(())
+}
+
+/// Accounts for setting a verification key
+#[derive(Accounts)]
+#[instruction(proof_type: ProofType)]
+pub struct SetVerificationKeyData<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        mut,
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Verification key account, created by `create_verification_key_account`
+    #[account(
+        mut,
+        seeds = [proof_type.as_seed(), pool_config.key().as_ref()],
+        bump = vk_account.bump,
+    )]
+    pub vk_account: Account<'info, VerificationKeyAccountV2>,
+
+    /// Optional: circuit descriptor, checked against the pool before the VK is set
+    #[account(
+        seeds = [
+            CircuitDescriptor::SEED_PREFIX,
+            proof_type.as_seed(),
+            pool_config.key().as_ref(),
+        ],
+        bump = circuit_descriptor.bump,
+    )]
+    pub circuit_descriptor: Option<Account<'info, CircuitDescriptor>>,
+
+    /// Pool's Merkle tree (source of the Poseidon parameter set)
+    #[account(
+        constraint = pool_config.merkle_tree == merkle_tree.key() @ PrivacyErrorV2::InvalidMerkleTreePool,
+    )]
+    pub merkle_tree: Box<Account<'info, MerkleTreeV2>>,
+}
+
+/// Handler for set_verification_key_data instruction
+///
+/// Writes the key into an existing VK account. An initialized key may be
+/// replaced until it is locked; a locked key is never rewritten.
+/// `ceremony_transcript_hash` and `ceremony_contributors` record the
+/// trusted-setup ceremony the key came from (both zero if none).
 #[allow(clippy::too_many_arguments)]
 pub fn handler(
-    ctx: Context<SetVerificationKeyV2>,
+    ctx: Context<SetVerificationKeyData>,
     proof_type: ProofType,
     vk_alpha_g1: [u8; 64],
     vk_beta_g2: [u8; 128],
     vk_gamma_g2: [u8; 128],
     vk_delta_g2: [u8; 128],
     vk_ic: Vec<[u8; 64]>,
+    ceremony_transcript_hash: [u8; 32],
+    ceremony_contributors: u32,
 ) -> Result<()> {
     let pool_config = &mut ctx.accounts.pool_config;
     let vk_account = &mut ctx.accounts.vk_account;
@@ -58,25 +135,40 @@ pub fn handler(
 
     // SECURITY: Defense-in-depth - also check the VK account's own lock flag
     // This catches cases where pool_config and vk_account get out of sync
+    require!(!vk_account.is_locked, PrivacyErrorV2::VerificationKeyLocked);
+    require_keys_eq!(
+        vk_account.pool,
+        pool_config.key(),
+        PrivacyErrorV2::InvalidVerificationKeyPool
+    );
+    require!(
+        vk_account.get_proof_type() == Some(proof_type),
+        PrivacyErrorV2::InvalidVerificationKeyType
+    );
     if vk_account.is_initialized {
-        require!(!vk_account.is_locked, PrivacyErrorV2::VerificationKeyLocked);
+        msg!("Replacing unlocked VK for proof type {:?}", proof_type);
+    }
+
+    // Reject a VK whose circuit was built for different pool parameters
+    if let Some(descriptor) = ctx.accounts.circuit_descriptor.as_ref() {
+        descriptor.validate_for_pool(
+            &pool_config.key(),
+            pool_config.tree_depth,
+            &ctx.accounts.merkle_tree.poseidon_params_hash,
+            proof_type,
+        )?;
     }
 
     // Validate IC length matches expected for proof type
-    let expected_ic = VerificationKeyAccountV2::expected_ic_points(proof_type);
     require!(
-        vk_ic.len() as u8 == expected_ic,
+        vk_ic.len() <= u8::MAX as usize
+            && VerificationKeyAccountV2::accepts_ic_points(proof_type, vk_ic.len() as u8),
         PrivacyErrorV2::VkIcLengthMismatch
     );
 
     let clock = Clock::get()?;
     let timestamp = clock.unix_timestamp;
 
-    // Initialize if needed
-    if !vk_account.is_initialized {
-        vk_account.initialize(pool_config.key(), proof_type, ctx.bumps.vk_account);
-    }
-
     // Set VK data
     vk_account.set_vk(
         vk_alpha_g1,
@@ -86,6 +178,7 @@ pub fn handler(
         vk_ic.clone(),
         timestamp,
     );
+    vk_account.set_ceremony(ceremony_transcript_hash, ceremony_contributors)?;
 
     // Mark VK as configured in pool config
     pool_config.set_vk_configured(proof_type);
@@ -96,6 +189,8 @@ pub fn handler(
         proof_type: proof_type as u8,
         ic_length: vk_ic.len() as u8,
         vk_hash: vk_account.vk_hash,
+        ceremony_transcript_hash,
+        ceremony_contributors,
         authority: ctx.accounts.authority.key(),
         timestamp,
     });
@@ -160,3 +255,134 @@ pub fn lock_handler(ctx: Context<LockVerificationKeyV2>, proof_type: ProofType)
 
     Ok(())
 }
+
+/// Accounts for submitting a canary proof
+#[derive(Accounts)]
+#[instruction(proof_type: ProofType)]
+pub struct SubmitCanaryProof<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Verification key account
+    #[account(
+        mut,
+        seeds = [proof_type.as_seed(), pool_config.key().as_ref()],
+        bump = vk_account.bump,
+        constraint = vk_account.is_valid() @ PrivacyErrorV2::VerificationKeyNotSet,
+    )]
+    pub vk_account: Account<'info, VerificationKeyAccountV2>,
+}
+
+/// Handler for submit_canary_proof instruction
+///
+/// Verifies a known test proof against the stored VK without moving funds.
+/// A VK exported from the wrong circuit build fails here instead of on the
+/// first user withdrawal.
+pub fn canary_handler(
+    ctx: Context<SubmitCanaryProof>,
+    proof_type: ProofType,
+    proof: Vec<u8>,
+    public_inputs: Vec<[u8; 32]>,
+) -> Result<()> {
+    let vk_account = &mut ctx.accounts.vk_account;
+
+    require!(
+        crate::crypto::is_valid_proof_length(&proof),
+        PrivacyErrorV2::InvalidProofFormat
+    );
+    require!(
+        public_inputs.len() == vk_account.expected_public_inputs() as usize,
+        PrivacyErrorV2::InvalidPublicInputs
+    );
+
+    let is_valid = crate::crypto::verify_proof_from_account(
+        &vk_account.vk_alpha_g1,
+        &vk_account.vk_beta_g2,
+        &vk_account.vk_gamma_g2,
+        &vk_account.vk_delta_g2,
+        &vk_account.vk_ic,
+        &proof,
+        &public_inputs,
+    )?;
+    require!(is_valid, PrivacyErrorV2::CanaryProofFailed);
+
+    let timestamp = Clock::get()?.unix_timestamp;
+    vk_account.mark_canary_passed(timestamp);
+
+    emit!(VerificationKeyCanaryPassed {
+        pool: ctx.accounts.pool_config.key(),
+        proof_type: proof_type as u8,
+        vk_hash: vk_account.vk_hash,
+        authority: ctx.accounts.authority.key(),
+        timestamp,
+    });
+
+    msg!("Canary proof passed for proof type {:?}", proof_type);
+
+    Ok(())
+}
+
+/// Accounts for recording the ceremony behind an already set VK
+#[derive(Accounts)]
+#[instruction(proof_type: ProofType)]
+pub struct RecordVkCeremony<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Verification key account
+    #[account(
+        mut,
+        seeds = [proof_type.as_seed(), pool_config.key().as_ref()],
+        bump = vk_account.bump,
+        constraint = vk_account.is_valid() @ PrivacyErrorV2::VerificationKeyNotSet,
+    )]
+    pub vk_account: Account<'info, VerificationKeyAccountV2>,
+}
+
+/// Handler for record_vk_ceremony instruction
+///
+/// Records the ceremony of a key that has none, e.g. one set (and possibly
+/// locked) before ceremony data existed. The key itself is not touched, so
+/// a lock does not prevent this; a recorded ceremony is never overwritten.
+pub fn ceremony_handler(
+    ctx: Context<RecordVkCeremony>,
+    proof_type: ProofType,
+    ceremony_transcript_hash: [u8; 32],
+    ceremony_contributors: u32,
+) -> Result<()> {
+    let vk_account = &mut ctx.accounts.vk_account;
+
+    vk_account.record_ceremony(ceremony_transcript_hash, ceremony_contributors)?;
+
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    emit!(VerificationKeyCeremonyRecorded {
+        pool: ctx.accounts.pool_config.key(),
+        proof_type: proof_type as u8,
+        vk_hash: vk_account.vk_hash,
+        ceremony_transcript_hash,
+        ceremony_contributors,
+        authority: ctx.accounts.authority.key(),
+        timestamp,
+    });
+
+    msg!(
+        "Recorded ceremony for proof type {:?}: {} contributors",
+        proof_type,
+        ceremony_contributors
+    );
+
+    Ok(())
+}
@@ -10,12 +10,17 @@
 //! 4. This instruction verifies proof and updates state
 
 use anchor_lang::prelude::*;
-use sha2::{Digest, Sha256};
 
 use crate::crypto::groth16::{verify, Proof, VerificationKey};
 use crate::error::PrivacyErrorV2;
-use crate::events::{BatchSettledEvent, CommitmentInsertedEvent};
-use crate::state::{MerkleTreeV2, PendingDepositsBuffer, PoolConfigV2, VerificationKeyAccountV2};
+use crate::events::{
+    BatchSettledEvent, CommitmentInsertedEvent, ProofVerificationFailed, RootUpdatedEvent,
+    TreeCapacityWarning,
+};
+use crate::state::{
+    MerkleTreeV2, PendingDepositsBuffer, PoolConfigV2, ProofStats, VerificationKeyAccountV2,
+};
+use crate::utils::pool_clock;
 use crate::ProofType;
 
 /// Maximum batch size must match circuit's maxBatch parameter
@@ -50,10 +55,10 @@ pub struct SettleDepositsBatch<'info> {
             PendingDepositsBuffer::SEED_PREFIX,
             pool_config.key().as_ref(),
         ],
-        bump = pending_buffer.bump,
-        constraint = pending_buffer.pool == pool_config.key() @ PrivacyErrorV2::InvalidPoolReference,
+        bump = PendingDepositsBuffer::checked_bump(&pending_buffer)?,
+        constraint = pending_buffer.load()?.pool == pool_config.key() @ PrivacyErrorV2::InvalidPoolReference,
     )]
-    pub pending_buffer: Box<Account<'info, PendingDepositsBuffer>>,
+    pub pending_buffer: AccountLoader<'info, PendingDepositsBuffer>,
 
     /// Verification key for MerkleBatchUpdate proof type
     #[account(
@@ -65,6 +70,14 @@ pub struct SettleDepositsBatch<'info> {
         constraint = verification_key.is_valid() @ PrivacyErrorV2::VerificationKeyNotSet,
     )]
     pub verification_key: Box<Account<'info, VerificationKeyAccountV2>>,
+
+    /// Optional: per-proof-type verification counters
+    #[account(
+        mut,
+        seeds = [ProofStats::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = proof_stats.bump,
+    )]
+    pub proof_stats: Option<Account<'info, ProofStats>>,
 }
 
 /// Arguments for settle_deposits_batch
@@ -75,6 +88,8 @@ pub struct SettleDepositsBatchArgs {
     /// New Merkle root after insertions
     pub new_root: [u8; 32],
     pub batch_size: u16,
+    /// Optional: reject unless the buffer's batch_seq equals this value
+    pub expected_batch_seq: Option<u64>,
 }
 
 /// Convert sha256 output to BN254 field element
@@ -94,25 +109,34 @@ fn sha256_to_field(hash: &[u8; 32]) -> [u8; 32] {
 /// Circuit hashes MAX_BATCH_SIZE slots, inactive slots are 0
 fn compute_commitments_hash(commitments: &[[u8; 32]], batch_size: usize) -> [u8; 32] {
     use sha2::{Digest, Sha256};
-    
+
     // BN254 prime p (big-endian)
     const P: [u8; 32] = [
-        0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29,
-        0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
-        0x97, 0x81, 0x6a, 0x91, 0x68, 0x71, 0xca, 0x8d,
-        0x3c, 0x20, 0x8c, 0x16, 0xd8, 0x7c, 0xfd, 0x47,
+        0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58,
+        0x5d, 0x97, 0x81, 0x6a, 0x91, 0x68, 0x71, 0xca, 0x8d, 0x3c, 0x20, 0x8c, 0x16, 0xd8, 0x7c,
+        0xfd, 0x47,
     ];
-    
+
     let mut preimage = [0u8; MAX_BATCH_SIZE * 32];
-    
+
     for i in 0..MAX_BATCH_SIZE {
         if i < batch_size && i < commitments.len() {
             // Reduce mod p if >= p (matches circuit field semantics)
             let c = &commitments[i];
-            let need_reduce = c.iter().zip(P.iter()).fold(None, |acc, (&a, &b)| {
-                acc.or_else(|| if a > b { Some(true) } else if a < b { Some(false) } else { None })
-            }).unwrap_or(false);
-            
+            let need_reduce = c
+                .iter()
+                .zip(P.iter())
+                .fold(None, |acc, (&a, &b)| {
+                    acc.or(if a > b {
+                        Some(true)
+                    } else if a < b {
+                        Some(false)
+                    } else {
+                        None
+                    })
+                })
+                .unwrap_or(false);
+
             if need_reduce {
                 let mut borrow = 0i16;
                 for j in (0..32).rev() {
@@ -130,22 +154,21 @@ fn compute_commitments_hash(commitments: &[[u8; 32]], batch_size: usize) -> [u8;
             }
         }
     }
-    
-    let hash = Sha256::digest(&preimage);
+
+    let hash = Sha256::digest(preimage);
     let mut h = [0u8; 32];
     h.copy_from_slice(&hash);
     h
 }
 
-
 /// Handler for settle_deposits_batch instruction
 pub fn handler(ctx: Context<SettleDepositsBatch>, args: SettleDepositsBatchArgs) -> Result<()> {
     let pool_config = &mut ctx.accounts.pool_config;
     let merkle_tree = &mut ctx.accounts.merkle_tree;
-    let pending_buffer = &mut ctx.accounts.pending_buffer;
+    let mut pending_buffer = ctx.accounts.pending_buffer.load_mut()?;
     let vk_account = &ctx.accounts.verification_key;
 
-    let clock = Clock::get()?;
+    let clock = pool_clock(pool_config)?;
     let timestamp = clock.unix_timestamp;
 
     // =========================================================================
@@ -153,6 +176,9 @@ pub fn handler(ctx: Context<SettleDepositsBatch>, args: SettleDepositsBatchArgs)
     // =========================================================================
     let batch_size = args.batch_size as usize;
 
+    pending_buffer.check_batch_seq(args.expected_batch_seq)?;
+    let batch_seq = pending_buffer.batch_seq;
+
     require!(batch_size > 0, PrivacyErrorV2::InvalidBatchSize);
     require!(
         batch_size <= MAX_BATCH_SIZE,
@@ -179,8 +205,10 @@ pub fn handler(ctx: Context<SettleDepositsBatch>, args: SettleDepositsBatchArgs)
     // =========================================================================
     // 3. GET COMMITMENTS AND COMPUTE HASH
     // =========================================================================
-    let pending_deposits = pending_buffer.prepare_batch(batch_size as u16);
-    let commitments: Vec<[u8; 32]> = pending_deposits.iter().map(|d| d.commitment).collect();
+    let commitments: Vec<[u8; 32]> = pending_buffer
+        .prepare_batch(batch_size as u16)
+        .map(|d| d.commitment)
+        .collect();
 
     // Compute sha256 hash matching circuit encoding
     let commitments_sha256 = compute_commitments_hash(&commitments, batch_size);
@@ -217,15 +245,35 @@ pub fn handler(ctx: Context<SettleDepositsBatch>, args: SettleDepositsBatchArgs)
     );
 
     let is_valid = verify(&vk, &proof, &public_inputs)?;
-    require!(is_valid, PrivacyErrorV2::InvalidProof);
+    if !is_valid {
+        emit!(ProofVerificationFailed {
+            pool: pool_config.key(),
+            proof_type: ProofType::MerkleBatchUpdate as u8,
+            submitter: ctx.accounts.authority.key(),
+            timestamp,
+        });
+        return err!(PrivacyErrorV2::InvalidProof);
+    }
+    if let Some(proof_stats) = ctx.accounts.proof_stats.as_mut() {
+        proof_stats.record_verified(ProofType::MerkleBatchUpdate, timestamp)?;
+    }
 
     msg!("✓ Batch proof verified for {} deposits", batch_size);
 
     // Emit structured settlement logs for recovery
-    for i in 0..batch_size {
-        msg!("SETTLED_LEAF idx={} commit={:02x?}", start_index + i as u32, commitments[i]);
+    for (i, commitment) in commitments.iter().enumerate().take(batch_size) {
+        msg!(
+            "SETTLED_LEAF idx={} commit={:02x?}",
+            start_index + i as u32,
+            commitment
+        );
     }
-    msg!("SETTLED_BATCH start={} size={} root={:02x?}", start_index, batch_size, args.new_root);
+    msg!(
+        "SETTLED_BATCH start={} size={} root={:02x?}",
+        start_index,
+        batch_size,
+        args.new_root
+    );
     // =========================================================================
     // 6. UPDATE MERKLE TREE STATE
     // =========================================================================
@@ -241,6 +289,16 @@ pub fn handler(ctx: Context<SettleDepositsBatch>, args: SettleDepositsBatchArgs)
     merkle_tree.root_history_index =
         (merkle_tree.root_history_index + 1) % merkle_tree.root_history_size;
 
+    if let Some(sequence) = pool_config.next_root_event_sequence()? {
+        emit!(RootUpdatedEvent {
+            pool: pool_config.key(),
+            root: args.new_root,
+            leaf_count: merkle_tree.next_leaf_index,
+            slot: clock.slot,
+            sequence,
+        });
+    }
+
     // =========================================================================
     // 6b. EMIT PER-COMMITMENT EVENTS (RECOVERY LOG)
     // =========================================================================
@@ -266,11 +324,27 @@ pub fn handler(ctx: Context<SettleDepositsBatch>, args: SettleDepositsBatchArgs)
     // =========================================================================
     pool_config.record_batch(batch_size as u32, timestamp)?;
 
+    let fill_percentage = merkle_tree.fill_percentage();
+    if let Some((level, threshold_pct)) = pool_config.update_tree_capacity_level(fill_percentage) {
+        emit!(TreeCapacityWarning {
+            pool: pool_config.key(),
+            merkle_tree: merkle_tree.key(),
+            level,
+            threshold_pct,
+            fill_percentage,
+            leaves_used: merkle_tree.next_leaf_index,
+            capacity: merkle_tree.capacity(),
+            timestamp,
+        });
+        msg!("Tree capacity warning: {}% full", fill_percentage);
+    }
+
     // =========================================================================
     // 9. EMIT EVENT
     // =========================================================================
     emit!(BatchSettledEvent {
         pool: pool_config.key(),
+        batch_seq,
         batch_size: batch_size as u16,
         start_index,
         new_root: args.new_root,
@@ -279,7 +353,8 @@ pub fn handler(ctx: Context<SettleDepositsBatch>, args: SettleDepositsBatchArgs)
     });
 
     msg!(
-        "Batch settled: {} deposits, indices {}-{}, new root: {:?}",
+        "Batch {} settled: {} deposits, indices {}-{}, new root: {:?}",
+        batch_seq,
         batch_size,
         start_index,
         start_index + batch_size as u32 - 1,
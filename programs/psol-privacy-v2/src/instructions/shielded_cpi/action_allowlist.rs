@@ -0,0 +1,121 @@
+//! Action Allowlist Instructions
+//!
+//! The authority creates one allowlist per action type and curates the
+//! programs actions of that type may target (see `ActionAllowlist`).
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::events::ActionAllowlistUpdated;
+use crate::state::{ActionAllowlist, PoolConfigV2};
+use crate::ShieldedActionType;
+
+/// Accounts for creating an action type's allowlist
+#[derive(Accounts)]
+#[instruction(action_type: ShieldedActionType)]
+pub struct InitActionAllowlist<'info> {
+    /// Pool authority (must be signer)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Action allowlist (PDA)
+    #[account(
+        init,
+        payer = authority,
+        space = ActionAllowlist::LEN,
+        seeds = [
+            ActionAllowlist::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            &[action_type as u8],
+        ],
+        bump,
+    )]
+    pub action_allowlist: Account<'info, ActionAllowlist>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for adding or removing an allowlisted program
+#[derive(Accounts)]
+pub struct ManageActionAllowlist<'info> {
+    /// Pool authority (must be signer)
+    pub authority: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        has_one = authority @ PrivacyErrorV2::Unauthorized,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Action allowlist (PDA)
+    #[account(
+        mut,
+        seeds = [
+            ActionAllowlist::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            &[action_allowlist.action_type],
+        ],
+        bump = action_allowlist.bump,
+    )]
+    pub action_allowlist: Account<'info, ActionAllowlist>,
+}
+
+pub fn init_handler(
+    ctx: Context<InitActionAllowlist>,
+    action_type: ShieldedActionType,
+) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+    ctx.accounts.action_allowlist.initialize(
+        ctx.accounts.pool_config.key(),
+        action_type,
+        ctx.bumps.action_allowlist,
+        timestamp,
+    );
+
+    msg!(
+        "Action allowlist initialized for action type {}",
+        action_type as u8
+    );
+    Ok(())
+}
+
+pub fn add_handler(ctx: Context<ManageActionAllowlist>, program: Pubkey) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+    let action_allowlist = &mut ctx.accounts.action_allowlist;
+    action_allowlist.add_program(program, timestamp)?;
+
+    emit!(ActionAllowlistUpdated {
+        pool: ctx.accounts.pool_config.key(),
+        action_type: action_allowlist.action_type,
+        program,
+        allowlisted: true,
+        timestamp,
+    });
+
+    msg!("Allowlisted action program: {}", program);
+    Ok(())
+}
+
+pub fn remove_handler(ctx: Context<ManageActionAllowlist>, program: Pubkey) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+    let action_allowlist = &mut ctx.accounts.action_allowlist;
+    action_allowlist.remove_program(&program, timestamp)?;
+
+    emit!(ActionAllowlistUpdated {
+        pool: ctx.accounts.pool_config.key(),
+        action_type: action_allowlist.action_type,
+        program,
+        allowlisted: false,
+        timestamp,
+    });
+
+    msg!("Removed allowlisted action program: {}", program);
+    Ok(())
+}
@@ -0,0 +1,149 @@
+//! Jupiter swap adapter for shielded actions
+//!
+//! Swaps an unshielded note through a Jupiter route: the input sits in the
+//! action's input escrow, the route's instruction data comes from the
+//! action data, and the route must deliver into the output escrow, where
+//! the output is re-shielded by `execute_shielded_action`.
+//!
+//! The route's accounts are the instruction's remaining accounts, in the
+//! route's order. The action authority (owner of both escrows) is the only
+//! signer this program adds; the asset vaults never sign, so a route
+//! cannot touch pool funds other than the escrowed input.
+//!
+//! # Slippage
+//!
+//! The note commits to `min_output`, which must lie within `slippage_bps`
+//! of `quoted_output`. A route delivering less fails the action; anything
+//! above `min_output` stays in the output vault as surplus.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+
+use super::execute_action::invoke_as_authority;
+
+/// Jupiter aggregator v6
+pub const JUPITER_PROGRAM_ID: Pubkey = pubkey!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4");
+
+/// Widest accepted slippage (10%)
+pub const MAX_SLIPPAGE_BPS: u16 = 1_000;
+
+/// Largest accepted route instruction data
+pub const MAX_ROUTE_DATA_LEN: usize = 1_024;
+
+const BPS_DENOMINATOR: u128 = 10_000;
+
+/// Swap parameters, following the `ShieldedSpend` header in action data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DexSwapParams {
+    /// Output amount quoted for the route
+    pub quoted_output: u64,
+    /// Output amount the re-shielded note commits to
+    pub min_output: u64,
+    /// Slippage allowed from the quote, in basis points
+    pub slippage_bps: u16,
+    /// Jupiter route instruction data
+    pub route_data: Vec<u8>,
+}
+
+impl DexSwapParams {
+    /// Lowest `min_output` the quote and slippage allow (rounded up)
+    pub fn slippage_floor(&self) -> u64 {
+        let kept = BPS_DENOMINATOR - u128::from(self.slippage_bps.min(10_000));
+        (u128::from(self.quoted_output) * kept).div_ceil(BPS_DENOMINATOR) as u64
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            !self.route_data.is_empty() && self.route_data.len() <= MAX_ROUTE_DATA_LEN,
+            PrivacyErrorV2::InvalidActionData
+        );
+        require!(
+            self.slippage_bps > 0 && self.slippage_bps <= MAX_SLIPPAGE_BPS,
+            PrivacyErrorV2::SlippageOutOfBounds
+        );
+        require!(self.min_output > 0, PrivacyErrorV2::InvalidAmount);
+        require!(
+            self.min_output <= self.quoted_output && self.min_output >= self.slippage_floor(),
+            PrivacyErrorV2::SlippageOutOfBounds
+        );
+        Ok(())
+    }
+}
+
+/// Run the route, signing as the action authority
+///
+/// `route_accounts` are passed through in order; the action authority is
+/// marked as a signer wherever it appears.
+pub fn swap<'info>(
+    target_program: &AccountInfo<'info>,
+    action_authority: &AccountInfo<'info>,
+    authority_seeds: &[&[u8]],
+    route_accounts: &[AccountInfo<'info>],
+    params: &DexSwapParams,
+) -> Result<()> {
+    require_keys_eq!(
+        target_program.key(),
+        JUPITER_PROGRAM_ID,
+        PrivacyErrorV2::InvalidTargetProgram
+    );
+
+    invoke_as_authority(
+        JUPITER_PROGRAM_ID,
+        params.route_data.clone(),
+        target_program,
+        action_authority,
+        authority_seeds,
+        route_accounts,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(quoted_output: u64, min_output: u64, slippage_bps: u16) -> DexSwapParams {
+        DexSwapParams {
+            quoted_output,
+            min_output,
+            slippage_bps,
+            route_data: vec![1, 2, 3],
+        }
+    }
+
+    #[test]
+    fn test_slippage_floor_rounds_up() {
+        assert_eq!(params(1_000_000, 0, 50).slippage_floor(), 995_000);
+        assert_eq!(params(999, 0, 1).slippage_floor(), 999);
+        assert_eq!(params(u64::MAX, 0, 0).slippage_floor(), u64::MAX);
+    }
+
+    #[test]
+    fn test_min_output_within_slippage() {
+        assert!(params(1_000_000, 995_000, 50).validate().is_ok());
+        assert!(params(1_000_000, 1_000_000, 50).validate().is_ok());
+        // Below the floor, or above the quote
+        assert!(params(1_000_000, 994_999, 50).validate().is_err());
+        assert!(params(1_000_000, 1_000_001, 50).validate().is_err());
+        assert!(params(1_000_000, 0, 50).validate().is_err());
+    }
+
+    #[test]
+    fn test_slippage_bps_bounds() {
+        assert!(params(1_000, 1_000, 0).validate().is_err());
+        assert!(params(1_000, 900, MAX_SLIPPAGE_BPS).validate().is_ok());
+        assert!(params(1_000, 1_000, MAX_SLIPPAGE_BPS + 1)
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_route_data_length() {
+        let mut empty = params(1_000, 1_000, 10);
+        empty.route_data.clear();
+        assert!(empty.validate().is_err());
+        let mut long = params(1_000, 1_000, 10);
+        long.route_data = vec![0; MAX_ROUTE_DATA_LEN + 1];
+        assert!(long.validate().is_err());
+    }
+}
@@ -1,17 +1,143 @@
 //! Execute Shielded Action Instruction
 //!
-//! Executes a shielded action via CPI to external protocols.
-//! This is a placeholder for DeFi integrations like Jupiter swaps.
+//! Spends a shielded note into an external protocol and re-shields the
+//! result as a new note, without the value passing through a user wallet:
+//!
+//! 1. Verify a withdraw proof whose recipient is the pool's action
+//!    authority and whose `public_data_hash` is `action_hash` (binding the
+//!    action type, target program, output asset and the whole action data)
+//! 2. Mark the nullifier spent and move the note's amount, less the relayer
+//!    fee, from the input vault into the input escrow
+//! 3. Run the action's adapter against the escrows
+//! 4. Move the output escrow into the output vault, shield `min_output` of
+//!    it as `output_commitment`, and close both escrows
+//!
+//! Action data is a Borsh `ShieldedSpend` followed by the adapter's
+//! parameters. The escrows are token accounts owned by the action
+//! authority PDA, created and closed within the instruction (the relayer
+//! fronts their rent). Adapters take their accounts from the remaining
+//! accounts. Only SPL Token vaults are supported. The target program must
+//! be on the pool's allowlist for the action type (see `ActionAllowlist`).
+//!
+//! Vaults in a risk tier are held to it: the spend is a withdrawal from the
+//! input vault (not a sealed one, so none at or above its delay threshold
+//! goes through) and the shielded output a deposit into the output vault.
+//!
+//! Adapters:
+//! - `DexSwap`: a Jupiter route (see `dex_swap`)
+//! - `LendingDeposit` / `LendingBorrow`: an SPL token-lending market (see
+//!   `lending`)
+//! - `Stake` / `Unstake`: an SPL stake pool (see `stake_pool`)
 
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer};
 
+use crate::crypto::keccak::keccak256_concat;
+use crate::crypto::WithdrawPublicInputs;
 use crate::error::PrivacyErrorV2;
-#[cfg(feature = "event-debug")]
-use crate::events::ShieldedActionExecuted;
-use crate::state::{MerkleTreeV2, PoolConfigV2, VerificationKeyAccountV2};
+use crate::events::{
+    CommitmentInsertedEvent, ProofVerificationFailed, RootUpdatedEvent, ShieldedActionExecuted,
+};
+use crate::state::{
+    ActionAllowlist, AssetVault, AssetWithdrawalWindow, GlobalConfig, MerkleTreeV2, PoolConfigV2,
+    RiskTierConfig, SpendContext, VerificationKeyAccountV2,
+};
+use crate::tx_limits::{
+    require_data_within, require_remaining_accounts_within, MAX_ACTION_DATA_BYTES,
+    MAX_SHIELDED_ACTION_REMAINING_ACCOUNTS,
+};
+use crate::utils::{check_risk_tier_withdrawal, pool_clock, require_program_id, spend_nullifier};
 use crate::ProofType;
 use crate::ShieldedActionType;
 
+use super::dex_swap::{self, DexSwapParams};
+use super::lending::{self, LendingParams};
+use super::stake_pool::{self, StakeAccounts, StakeParams};
+
+/// Seed of the PDA that owns the action escrows and signs adapter CPIs
+pub const ACTION_AUTHORITY_SEED: &[u8] = b"shielded_action";
+
+/// Seed prefix of the per-mint escrow token accounts
+pub const ACTION_ESCROW_SEED: &[u8] = b"action_escrow";
+
+/// Spend shared by every action, at the start of the action data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ShieldedSpend {
+    pub merkle_root: [u8; 32],
+    pub nullifier_hash: [u8; 32],
+    /// Note amount, including the relayer fee
+    pub amount: u64,
+    /// Fee paid to the relayer in the input asset
+    pub relayer_fee: u64,
+    /// Note shielding the action's output
+    pub output_commitment: [u8; 32],
+}
+
+/// Split action data into the spend and the adapter's parameters; trailing
+/// bytes are rejected
+pub fn parse_action_data<P: AnchorDeserialize>(action_data: &[u8]) -> Result<(ShieldedSpend, P)> {
+    let mut data = action_data;
+    let spend = ShieldedSpend::deserialize(&mut data)
+        .map_err(|_| error!(PrivacyErrorV2::InvalidActionData))?;
+    let params =
+        P::deserialize(&mut data).map_err(|_| error!(PrivacyErrorV2::InvalidActionData))?;
+    require!(data.is_empty(), PrivacyErrorV2::InvalidActionData);
+    Ok((spend, params))
+}
+
+/// `public_data_hash` of the withdraw proof authorizing an action, reduced
+/// to a canonical field element (top byte cleared)
+pub fn action_hash(
+    action_type: ShieldedActionType,
+    target_program: &Pubkey,
+    output_asset_id: &[u8; 32],
+    action_data: &[u8],
+) -> [u8; 32] {
+    let h = keccak256_concat(&[
+        b"psol:shielded_action:v1",
+        &[action_type as u8],
+        target_program.as_ref(),
+        output_asset_id,
+        action_data,
+    ]);
+    let mut out = [0u8; 32];
+    out[1..32].copy_from_slice(&h[0..31]);
+    out
+}
+
+/// Invoke `program_id` with `accounts` in order, signing as the action
+/// authority; the authority is marked as a signer wherever it appears
+pub fn invoke_as_authority<'info>(
+    program_id: Pubkey,
+    data: Vec<u8>,
+    target_program: &AccountInfo<'info>,
+    action_authority: &AccountInfo<'info>,
+    authority_seeds: &[&[u8]],
+    accounts: &[AccountInfo<'info>],
+) -> Result<()> {
+    let metas = accounts
+        .iter()
+        .map(|account| AccountMeta {
+            pubkey: account.key(),
+            is_signer: account.is_signer || account.key() == action_authority.key(),
+            is_writable: account.is_writable,
+        })
+        .collect();
+    let ix = Instruction {
+        program_id,
+        accounts: metas,
+        data,
+    };
+
+    let mut infos = accounts.to_vec();
+    infos.push(action_authority.clone());
+    infos.push(target_program.clone());
+    invoke_signed(&ix, &infos, &[authority_seeds])?;
+    Ok(())
+}
+
 /// Accounts for executing a shielded action
 #[derive(Accounts)]
 #[instruction(
@@ -20,7 +146,8 @@ use crate::ShieldedActionType;
     action_data: Vec<u8>,
 )]
 pub struct ExecuteShieldedAction<'info> {
-    /// Relayer executing the action
+    /// Relayer executing the action (bound by the proof, fronts escrow and
+    /// nullifier rent)
     #[account(mut)]
     pub relayer: Signer<'info>,
 
@@ -30,123 +157,660 @@ pub struct ExecuteShieldedAction<'info> {
         constraint = !pool_config.is_paused @ PrivacyErrorV2::PoolPaused,
         has_one = merkle_tree,
     )]
-    pub pool_config: Account<'info, PoolConfigV2>,
+    pub pool_config: Box<Account<'info, PoolConfigV2>>,
+
+    /// Program-wide pause switch
+    /// CHECK: PDA; may not exist yet (see `GlobalConfig::is_paused_account`)
+    #[account(
+        seeds = [GlobalConfig::SEED_PREFIX],
+        bump,
+        constraint = !GlobalConfig::is_paused_account(&global_config)
+            @ PrivacyErrorV2::GlobalPaused,
+    )]
+    pub global_config: UncheckedAccount<'info>,
 
     /// Merkle tree account
     #[account(mut)]
-    pub merkle_tree: Account<'info, MerkleTreeV2>,
+    pub merkle_tree: Box<Account<'info, MerkleTreeV2>>,
 
-    /// Verification key for the action proof
-    /// Note: Shielded CPI uses JoinSplit VK for now
+    /// Verification key for the action proof (a withdraw proof to the
+    /// action authority)
     #[account(
-        seeds = [ProofType::JoinSplit.as_seed(), pool_config.key().as_ref()],
+        seeds = [ProofType::Withdraw.as_seed(), pool_config.key().as_ref()],
         bump = vk_account.bump,
         constraint = vk_account.is_initialized @ PrivacyErrorV2::VerificationKeyNotSet,
+        constraint = vk_account.proof_type == ProofType::Withdraw as u8
+            @ PrivacyErrorV2::InvalidVerificationKeyType,
+        constraint = !pool_config.requires_vk_canary() || vk_account.canary_passed
+            @ PrivacyErrorV2::VkCanaryNotPassed,
+    )]
+    pub vk_account: Box<Account<'info, VerificationKeyAccountV2>>,
+
+    /// Vault of the note being spent
+    #[account(
+        mut,
+        constraint = input_vault.pool == pool_config.key() @ PrivacyErrorV2::InvalidVaultPool,
+        constraint = input_vault.is_active @ PrivacyErrorV2::AssetNotActive,
+    )]
+    pub input_vault: Box<Account<'info, AssetVault>>,
+
+    #[account(
+        mut,
+        constraint = input_vault_token_account.key() == input_vault.token_account
+            @ PrivacyErrorV2::InvalidVaultTokenAccount,
+    )]
+    pub input_vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(constraint = input_mint.key() == input_vault.mint @ PrivacyErrorV2::InvalidMint)]
+    pub input_mint: Box<Account<'info, Mint>>,
+
+    /// Vault the action's output is shielded into
+    #[account(
+        mut,
+        constraint = output_vault.pool == pool_config.key() @ PrivacyErrorV2::InvalidVaultPool,
+        constraint = output_vault.is_active @ PrivacyErrorV2::AssetNotActive,
+        constraint = output_vault.key() != input_vault.key() @ PrivacyErrorV2::InvalidActionData,
+    )]
+    pub output_vault: Box<Account<'info, AssetVault>>,
+
+    #[account(
+        mut,
+        constraint = output_vault_token_account.key() == output_vault.token_account
+            @ PrivacyErrorV2::InvalidVaultTokenAccount,
+    )]
+    pub output_vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(constraint = output_mint.key() == output_vault.mint @ PrivacyErrorV2::InvalidMint)]
+    pub output_mint: Box<Account<'info, Mint>>,
+
+    /// Owner of the escrows and signer of adapter CPIs
+    /// CHECK: PDA; holds lamports only while a stake adapter moves SOL
+    #[account(
+        mut,
+        seeds = [ACTION_AUTHORITY_SEED, pool_config.key().as_ref()],
+        bump,
+    )]
+    pub action_authority: UncheckedAccount<'info>,
+
+    /// Escrow the note is unshielded into
+    #[account(
+        init,
+        payer = relayer,
+        seeds = [ACTION_ESCROW_SEED, pool_config.key().as_ref(), input_mint.key().as_ref()],
+        bump,
+        token::mint = input_mint,
+        token::authority = action_authority,
+    )]
+    pub input_escrow: Box<Account<'info, TokenAccount>>,
+
+    /// Escrow the adapter delivers its output into
+    #[account(
+        init,
+        payer = relayer,
+        seeds = [ACTION_ESCROW_SEED, pool_config.key().as_ref(), output_mint.key().as_ref()],
+        bump,
+        token::mint = output_mint,
+        token::authority = action_authority,
+    )]
+    pub output_escrow: Box<Account<'info, TokenAccount>>,
+
+    /// Spent nullifier PDA, created here
+    /// CHECK: Seeds checked against the nullifier in the action data
+    #[account(mut)]
+    pub spent_nullifier: UncheckedAccount<'info>,
+
+    /// Relayer's token account for the fee (required when the fee is non-zero)
+    #[account(
+        mut,
+        constraint = relayer_token_account.mint == input_vault.mint @ PrivacyErrorV2::InvalidMint,
+        constraint = relayer_token_account.owner == relayer.key() @ PrivacyErrorV2::RelayerMismatch,
     )]
-    pub vk_account: Account<'info, VerificationKeyAccountV2>,
+    pub relayer_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Programs this action type may target
+    #[account(
+        seeds = [
+            ActionAllowlist::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            &[action_type as u8],
+        ],
+        bump = action_allowlist.bump,
+    )]
+    pub action_allowlist: Box<Account<'info, ActionAllowlist>>,
 
     /// Target program for CPI
-    /// CHECK: Validated based on action_type
+    /// CHECK: Checked against the action allowlist and by the action's adapter
     pub target_program: UncheckedAccount<'info>,
 
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
     /// System program
     pub system_program: Program<'info, System>,
-    // Additional accounts passed via remaining_accounts
+
+    /// Pool's risk tier table; required once either vault is in a tier
+    #[account(
+        seeds = [RiskTierConfig::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = risk_tiers.bump,
+    )]
+    pub risk_tiers: Option<Box<Account<'info, RiskTierConfig>>>,
+
+    /// Input vault's withdrawal window; required while its tier rate-limits
+    /// withdrawals
+    #[account(
+        mut,
+        seeds = [AssetWithdrawalWindow::SEED_PREFIX, input_vault.key().as_ref()],
+        bump = withdrawal_window.bump,
+    )]
+    pub withdrawal_window: Option<Box<Account<'info, AssetWithdrawalWindow>>>,
+    // Remaining accounts: the adapter's accounts
 }
 
 /// Handler for execute_shielded_action instruction
-pub fn handler(
-    ctx: Context<ExecuteShieldedAction>,
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ExecuteShieldedAction<'info>>,
     action_type: ShieldedActionType,
-    _proof_data: Vec<u8>,
-    _action_data: Vec<u8>,
+    proof_data: Vec<u8>,
+    action_data: Vec<u8>,
 ) -> Result<()> {
     // Check shielded CPI is enabled
     ctx.accounts.pool_config.require_shielded_cpi_enabled()?;
+    // The spent nullifier PDA is derived by hand against this build's id
+    require_program_id(ctx.program_id)?;
+    ctx.accounts
+        .action_allowlist
+        .require_allowed(&ctx.accounts.target_program.key())?;
+    require_remaining_accounts_within(
+        "execute_shielded_action",
+        ctx.remaining_accounts.len(),
+        MAX_SHIELDED_ACTION_REMAINING_ACCOUNTS,
+    )?;
+    require_data_within(
+        "execute_shielded_action",
+        "action_data",
+        action_data.len(),
+        MAX_ACTION_DATA_BYTES,
+    )?;
 
-    let clock = Clock::get()?;
-    let _timestamp = clock.unix_timestamp;
-
-    // Validate action type is supported
     match action_type {
         ShieldedActionType::DexSwap => {
-            // TODO: Implement DEX swap integration
-            msg!("Shielded DEX swap not yet implemented");
-            return Err(error!(PrivacyErrorV2::NotImplemented));
-        }
-        ShieldedActionType::LendingDeposit => {
-            // TODO: Implement lending deposit
-            msg!("Shielded lending deposit not yet implemented");
-            return Err(error!(PrivacyErrorV2::NotImplemented));
-        }
-        ShieldedActionType::LendingBorrow => {
-            // TODO: Implement lending borrow
-            msg!("Shielded lending borrow not yet implemented");
-            return Err(error!(PrivacyErrorV2::NotImplemented));
+            let (spend, params) = parse_action_data::<DexSwapParams>(&action_data)?;
+            params.validate()?;
+            // Fail before the proof is verified
+            require_keys_eq!(
+                ctx.accounts.target_program.key(),
+                dex_swap::JUPITER_PROGRAM_ID,
+                PrivacyErrorV2::InvalidTargetProgram
+            );
+
+            let (_, timestamp, slot) =
+                unshield(ctx.accounts, action_type, &proof_data, &action_data, &spend)?;
+
+            let pool_key = ctx.accounts.pool_config.key();
+            let bump = [ctx.bumps.action_authority];
+            let authority_seeds: &[&[u8]] = &[ACTION_AUTHORITY_SEED, pool_key.as_ref(), &bump];
+            dex_swap::swap(
+                &ctx.accounts.target_program,
+                &ctx.accounts.action_authority,
+                authority_seeds,
+                ctx.remaining_accounts,
+                &params,
+            )?;
+
+            reshield(
+                ctx.accounts,
+                authority_seeds,
+                action_type,
+                &spend,
+                params.min_output,
+                timestamp,
+                slot,
+            )
         }
-        ShieldedActionType::Stake => {
-            // TODO: Implement staking
-            msg!("Shielded staking not yet implemented");
-            return Err(error!(PrivacyErrorV2::NotImplemented));
+        ShieldedActionType::LendingDeposit | ShieldedActionType::LendingBorrow => {
+            let (spend, params) = parse_action_data::<LendingParams>(&action_data)?;
+            params.validate(action_type)?;
+            // Fail before the proof is verified
+            require_keys_eq!(
+                ctx.accounts.target_program.key(),
+                lending::LENDING_PROGRAM_ID,
+                PrivacyErrorV2::InvalidTargetProgram
+            );
+
+            let (amount, timestamp, slot) =
+                unshield(ctx.accounts, action_type, &proof_data, &action_data, &spend)?;
+
+            let pool_key = ctx.accounts.pool_config.key();
+            let bump = [ctx.bumps.action_authority];
+            let authority_seeds: &[&[u8]] = &[ACTION_AUTHORITY_SEED, pool_key.as_ref(), &bump];
+            lending::supply(
+                action_type,
+                &ctx.accounts.target_program,
+                &ctx.accounts.action_authority,
+                authority_seeds,
+                ctx.remaining_accounts,
+                &params,
+                amount,
+            )?;
+
+            reshield(
+                ctx.accounts,
+                authority_seeds,
+                action_type,
+                &spend,
+                params.min_output,
+                timestamp,
+                slot,
+            )
         }
-        ShieldedActionType::Unstake => {
-            // TODO: Implement unstaking
-            msg!("Shielded unstaking not yet implemented");
-            return Err(error!(PrivacyErrorV2::NotImplemented));
+        ShieldedActionType::Stake | ShieldedActionType::Unstake => {
+            let (spend, params) = parse_action_data::<StakeParams>(&action_data)?;
+            params.validate()?;
+            // Fail before the proof is verified
+            require_keys_eq!(
+                ctx.accounts.target_program.key(),
+                stake_pool::STAKE_POOL_PROGRAM_ID,
+                PrivacyErrorV2::InvalidTargetProgram
+            );
+            let staking = action_type == ShieldedActionType::Stake;
+            if staking {
+                stake_pool::require_wrapped_sol(&ctx.accounts.input_mint.key())?;
+            } else {
+                stake_pool::require_wrapped_sol(&ctx.accounts.output_mint.key())?;
+            }
+
+            let (amount, timestamp, slot) =
+                unshield(ctx.accounts, action_type, &proof_data, &action_data, &spend)?;
+
+            let pool_key = ctx.accounts.pool_config.key();
+            let bump = [ctx.bumps.action_authority];
+            let authority_seeds: &[&[u8]] = &[ACTION_AUTHORITY_SEED, pool_key.as_ref(), &bump];
+            let stake_accounts = StakeAccounts {
+                target_program: &ctx.accounts.target_program,
+                action_authority: &ctx.accounts.action_authority,
+                input_escrow: &ctx.accounts.input_escrow.to_account_info(),
+                output_escrow: &ctx.accounts.output_escrow.to_account_info(),
+                relayer: &ctx.accounts.relayer.to_account_info(),
+                token_program: &ctx.accounts.token_program.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            };
+            if staking {
+                stake_pool::stake(
+                    &stake_accounts,
+                    authority_seeds,
+                    ctx.remaining_accounts,
+                    amount,
+                )?;
+            } else {
+                stake_pool::unstake(
+                    &stake_accounts,
+                    authority_seeds,
+                    ctx.remaining_accounts,
+                    amount,
+                )?;
+            }
+
+            reshield(
+                ctx.accounts,
+                authority_seeds,
+                action_type,
+                &spend,
+                params.min_output,
+                timestamp,
+                slot,
+            )
         }
         ShieldedActionType::Custom => {
             // TODO: Implement custom action parsing
             msg!("Custom shielded action not yet implemented");
-            return Err(error!(PrivacyErrorV2::NotImplemented));
+            Err(error!(PrivacyErrorV2::NotImplemented))
         }
     }
+}
+
+/// Verify the action proof, spend the note, and move its amount less the
+/// relayer fee into the input escrow; returns the escrowed amount and the
+/// clock's timestamp and slot
+fn unshield<'info>(
+    accounts: &mut ExecuteShieldedAction<'info>,
+    action_type: ShieldedActionType,
+    proof_data: &[u8],
+    action_data: &[u8],
+    spend: &ShieldedSpend,
+) -> Result<(u64, i64, u64)> {
+    // =========================================================================
+    // INPUT VALIDATION (fail fast before any state changes)
+    // =========================================================================
+
+    require!(
+        crate::crypto::is_valid_proof_length(proof_data),
+        PrivacyErrorV2::InvalidProofFormat
+    );
+    require!(
+        accounts.merkle_tree.is_known_root(&spend.merkle_root),
+        PrivacyErrorV2::InvalidMerkleRoot
+    );
+    accounts.merkle_tree.require_capacity_for(1)?;
+    accounts
+        .pool_config
+        .require_vk_configured(ProofType::Withdraw)?;
+
+    let input_vault = &accounts.input_vault;
+    input_vault.require_withdrawals_enabled()?;
+    input_vault.require_min_withdrawal(spend.amount)?;
+    input_vault.validate_relayer_fee(spend.relayer_fee)?;
+    accounts.output_vault.require_deposits_enabled()?;
+    require!(
+        accounts.input_vault_token_account.amount >= spend.amount,
+        PrivacyErrorV2::InsufficientBalance
+    );
+    require!(
+        spend.relayer_fee == 0 || accounts.relayer_token_account.is_some(),
+        PrivacyErrorV2::MissingAssetAccount
+    );
+
+    let public_inputs = WithdrawPublicInputs::new(
+        spend.merkle_root,
+        spend.nullifier_hash,
+        input_vault.asset_id,
+        accounts.action_authority.key(),
+        spend.amount,
+        accounts.relayer.key(),
+        spend.relayer_fee,
+        action_hash(
+            action_type,
+            &accounts.target_program.key(),
+            &accounts.output_vault.asset_id,
+            action_data,
+        ),
+    );
+    public_inputs.validate()?;
+    let action_amount = public_inputs.net_amount()?;
+    require!(action_amount > 0, PrivacyErrorV2::InvalidAmount);
+
+    let clock = pool_clock(&accounts.pool_config)?;
+    let timestamp = clock.unix_timestamp;
+    let slot = clock.slot;
+
+    check_risk_tier_withdrawal(
+        &accounts.input_vault,
+        accounts.risk_tiers.as_deref().map(|t| &**t),
+        accounts.withdrawal_window.as_deref_mut().map(|w| &mut **w),
+        spend.amount,
+        None,
+        timestamp,
+    )?;
+
+    // =========================================================================
+    // PROOF VERIFICATION (before any state changes)
+    // =========================================================================
+
+    let vk = &accounts.vk_account;
+    let is_valid = crate::crypto::verify_proof_from_account(
+        &vk.vk_alpha_g1,
+        &vk.vk_beta_g2,
+        &vk.vk_gamma_g2,
+        &vk.vk_delta_g2,
+        &vk.vk_ic,
+        proof_data,
+        &public_inputs.to_field_elements(),
+    )?;
+    if !is_valid {
+        emit!(ProofVerificationFailed {
+            pool: accounts.pool_config.key(),
+            proof_type: ProofType::Withdraw as u8,
+            submitter: accounts.relayer.key(),
+            timestamp,
+        });
+        return err!(PrivacyErrorV2::InvalidProof);
+    }
 
-    // Note: The code below is unreachable until the above TODO items are implemented
-    // Keeping as reference for future implementation
+    // =========================================================================
+    // STATE CHANGES (only after proof verification succeeds)
+    // =========================================================================
 
-    // Placeholder: In a real implementation, we would:
-    // 1. Verify the ZK proof authorizing this action
-    // 2. Parse action_data to get action-specific parameters
-    // 3. Execute CPI to target_program
-    // 4. Handle the result and update state
-    // 5. Insert any new commitments
+    let pool_key = accounts.pool_config.key();
+    let asset_id = accounts.input_vault.asset_id;
+    spend_nullifier(
+        &accounts.relayer,
+        accounts.relayer.key(),
+        &accounts.system_program,
+        &accounts.spent_nullifier.to_account_info(),
+        pool_key,
+        spend.nullifier_hash,
+        asset_id,
+        SpendContext::ShieldedCpi,
+        timestamp,
+        slot,
+    )?;
+
+    let vault_balance = accounts.input_vault_token_account.amount;
+    let vault_seeds: &[&[u8]] = &[
+        AssetVault::SEED_PREFIX,
+        pool_key.as_ref(),
+        asset_id.as_ref(),
+        &[accounts.input_vault.bump],
+    ];
+    let pay = |to: AccountInfo<'info>, amount: u64| {
+        token::transfer(
+            CpiContext::new_with_signer(
+                accounts.token_program.to_account_info(),
+                Transfer {
+                    from: accounts.input_vault_token_account.to_account_info(),
+                    to,
+                    authority: accounts.input_vault.to_account_info(),
+                },
+                &[vault_seeds],
+            ),
+            amount,
+        )
+    };
+    pay(accounts.input_escrow.to_account_info(), action_amount)?;
+    if let Some(relayer_token_account) = accounts.relayer_token_account.as_ref() {
+        if spend.relayer_fee > 0 {
+            pay(relayer_token_account.to_account_info(), spend.relayer_fee)?;
+        }
+    }
+
+    let coarse_stats = accounts.pool_config.has_coarse_liquidity_stats();
+    accounts
+        .input_vault
+        .record_withdrawal(spend.amount, timestamp, coarse_stats)?;
+    accounts.input_vault_token_account.reload()?;
+    require!(
+        vault_balance - accounts.input_vault_token_account.amount == spend.amount,
+        PrivacyErrorV2::VaultBalanceDrift
+    );
+    accounts
+        .input_vault
+        .reconcile(accounts.input_vault_token_account.amount)?;
+
+    Ok((action_amount, timestamp, slot))
+}
+
+/// Move the adapter's output into the output vault, shield `shielded_amount`
+/// of it as the spend's output commitment, and close the escrows
+#[allow(clippy::too_many_arguments)]
+fn reshield(
+    accounts: &mut ExecuteShieldedAction,
+    authority_seeds: &[&[u8]],
+    action_type: ShieldedActionType,
+    spend: &ShieldedSpend,
+    shielded_amount: u64,
+    timestamp: i64,
+    slot: u64,
+) -> Result<()> {
+    // An adapter unwrapping SOL closes the input escrow itself
+    let input_closed = accounts.input_escrow.to_account_info().lamports() == 0;
+    if !input_closed {
+        accounts.input_escrow.reload()?;
+        require!(
+            accounts.input_escrow.amount == 0,
+            PrivacyErrorV2::ActionInputNotSpent
+        );
+    }
+    accounts.output_escrow.reload()?;
+    let output = accounts.output_escrow.amount;
+    require!(output >= shielded_amount, PrivacyErrorV2::SlippageExceeded);
+    if let Some(limits) = RiskTierConfig::asset_limits(
+        accounts.risk_tiers.as_deref().map(|t| &**t),
+        &accounts.output_vault,
+    )? {
+        limits.check_deposit(shielded_amount)?;
+    }
+
+    let token_program = accounts.token_program.to_account_info();
+    let authority = accounts.action_authority.to_account_info();
+    let vault_balance = accounts.output_vault_token_account.amount;
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            Transfer {
+                from: accounts.output_escrow.to_account_info(),
+                to: accounts.output_vault_token_account.to_account_info(),
+                authority: authority.clone(),
+            },
+            &[authority_seeds],
+        ),
+        output,
+    )?;
+    for escrow in [&accounts.input_escrow, &accounts.output_escrow] {
+        if escrow.to_account_info().lamports() == 0 {
+            continue;
+        }
+        token::close_account(CpiContext::new_with_signer(
+            token_program.clone(),
+            CloseAccount {
+                account: escrow.to_account_info(),
+                destination: accounts.relayer.to_account_info(),
+                authority: authority.clone(),
+            },
+            &[authority_seeds],
+        ))?;
+    }
+
+    // Output above `shielded_amount` stays in the vault as surplus
+    let coarse_stats = accounts.pool_config.has_coarse_liquidity_stats();
+    accounts
+        .output_vault
+        .record_deposit(shielded_amount, timestamp, coarse_stats)?;
+    accounts.output_vault_token_account.reload()?;
+    require!(
+        accounts.output_vault_token_account.amount - vault_balance == output,
+        PrivacyErrorV2::VaultBalanceDrift
+    );
+    accounts
+        .output_vault
+        .reconcile(accounts.output_vault_token_account.amount)?;
+
+    // Insert the output note
+    let pool_key = accounts.pool_config.key();
+    let leaf_index = accounts
+        .merkle_tree
+        .insert_leaf(spend.output_commitment, timestamp)?;
+    let new_root = accounts.merkle_tree.get_current_root();
+    emit!(CommitmentInsertedEvent {
+        pool: pool_key,
+        commitment: spend.output_commitment,
+        leaf_index,
+        merkle_root: new_root,
+        timestamp,
+    });
+    if let Some(sequence) = accounts.pool_config.next_root_event_sequence()? {
+        emit!(RootUpdatedEvent {
+            pool: pool_key,
+            root: new_root,
+            leaf_count: accounts.merkle_tree.next_leaf_index,
+            slot,
+            sequence,
+        });
+    }
 
-    /*
-    // Emit event (placeholder values)
     emit!(ShieldedActionExecuted {
-        pool: ctx.accounts.pool_config.key(),
+        pool: pool_key,
         action_type: action_type as u8,
-        nullifier_hash: [0u8; 32], // Would come from proof
-        output_commitment: [0u8; 32], // Would be computed
-        target_program: ctx.accounts.target_program.key(),
-        relayer: ctx.accounts.relayer.key(),
+        nullifier_hash: spend.nullifier_hash,
+        output_commitment: spend.output_commitment,
+        target_program: accounts.target_program.key(),
+        relayer: accounts.relayer.key(),
         timestamp,
     });
 
+    msg!(
+        "Shielded action {:?}: output note at leaf {}",
+        action_type,
+        leaf_index
+    );
+
     Ok(())
-    */
 }
 
-/// Decode action data for DEX swap
-#[allow(dead_code)]
-struct DexSwapAction {
-    /// Input token mint
-    input_mint: Pubkey,
-    /// Output token mint  
-    output_mint: Pubkey,
-    /// Minimum output amount
-    min_output: u64,
-    /// Slippage in basis points
-    slippage_bps: u16,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spend() -> ShieldedSpend {
+        ShieldedSpend {
+            merkle_root: [1u8; 32],
+            nullifier_hash: [2u8; 32],
+            amount: 1_000,
+            relayer_fee: 10,
+            output_commitment: [3u8; 32],
+        }
+    }
 
-/// Decode action data for lending
-#[allow(dead_code)]
-struct LendingAction {
-    /// Lending protocol ID
-    protocol: Pubkey,
-    /// Reserve/pool to interact with
-    reserve: Pubkey,
-    /// Amount to deposit/borrow
-    amount: u64,
+    fn swap_params() -> DexSwapParams {
+        DexSwapParams {
+            quoted_output: 500,
+            min_output: 499,
+            slippage_bps: 50,
+            route_data: vec![7; 16],
+        }
+    }
+
+    #[test]
+    fn test_parse_action_data_round_trip() {
+        let mut data = spend().try_to_vec().unwrap();
+        data.extend(swap_params().try_to_vec().unwrap());
+
+        let (parsed, params) = parse_action_data::<DexSwapParams>(&data).unwrap();
+        assert_eq!(parsed, spend());
+        assert_eq!(params, swap_params());
+
+        // Trailing or missing bytes are rejected
+        data.push(0);
+        assert!(parse_action_data::<DexSwapParams>(&data).is_err());
+        data.truncate(data.len() - 2);
+        assert!(parse_action_data::<DexSwapParams>(&data).is_err());
+    }
+
+    #[test]
+    fn test_action_hash_binds_every_input() {
+        let target = dex_swap::JUPITER_PROGRAM_ID;
+        let data = [1u8, 2, 3];
+        let base = action_hash(ShieldedActionType::DexSwap, &target, &[4u8; 32], &data);
+
+        assert_eq!(base[0], 0, "canonical field element");
+        assert_ne!(
+            base,
+            action_hash(ShieldedActionType::Stake, &target, &[4u8; 32], &data)
+        );
+        assert_ne!(
+            base,
+            action_hash(
+                ShieldedActionType::DexSwap,
+                &Pubkey::default(),
+                &[4u8; 32],
+                &data
+            )
+        );
+        assert_ne!(
+            base,
+            action_hash(ShieldedActionType::DexSwap, &target, &[5u8; 32], &data)
+        );
+        assert_ne!(
+            base,
+            action_hash(ShieldedActionType::DexSwap, &target, &[4u8; 32], &[1, 2])
+        );
+    }
 }
@@ -0,0 +1,241 @@
+//! Lending adapter for shielded actions
+//!
+//! Supplies an unshielded note to an SPL token-lending market (Solend), so
+//! the position stays under pool control and its result is re-shielded by
+//! `execute_shielded_action`:
+//!
+//! - `LendingDeposit`: `DepositReserveLiquidity` of the escrowed input; the
+//!   reserve's collateral (cToken) lands in the output escrow and is
+//!   shielded from the collateral mint's vault.
+//! - `LendingBorrow`: `DepositObligationCollateral` of the escrowed input (a
+//!   collateral token) into an obligation owned by the action authority,
+//!   then `RefreshObligation` and `BorrowObligationLiquidity`; the borrowed
+//!   liquidity lands in the output escrow. The collateral stays in the
+//!   pool's obligation. `InitObligation` runs first when the params carry
+//!   accounts for it.
+//!
+//! Each lending instruction takes the next `account_counts[i]` remaining
+//! accounts, in the lending program's order; instruction data is built
+//! here from the proven amounts, never taken from the action data.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::ShieldedActionType;
+
+use super::execute_action::invoke_as_authority;
+
+/// Solend (SPL token-lending)
+pub const LENDING_PROGRAM_ID: Pubkey = pubkey!("So1endDq2YkqhipRh3WViPa8hdiSpxWy6z3Z6tMCpAE");
+
+/// SPL token-lending instruction tags
+mod tag {
+    pub const DEPOSIT_RESERVE_LIQUIDITY: u8 = 4;
+    pub const INIT_OBLIGATION: u8 = 6;
+    pub const REFRESH_OBLIGATION: u8 = 7;
+    pub const DEPOSIT_OBLIGATION_COLLATERAL: u8 = 8;
+    pub const BORROW_OBLIGATION_LIQUIDITY: u8 = 10;
+}
+
+/// Most accounts a single lending instruction may take
+pub const MAX_LENDING_ACCOUNTS: u8 = 16;
+
+/// Lending parameters, following the `ShieldedSpend` header in action data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LendingParams {
+    /// Output amount the re-shielded note commits to (collateral for a
+    /// deposit, received liquidity for a borrow)
+    pub min_output: u64,
+    /// Liquidity to borrow, before the market's fees (zero for a deposit)
+    pub borrow_amount: u64,
+    /// Remaining accounts taken by each lending instruction, in call order
+    pub account_counts: Vec<u8>,
+}
+
+impl LendingParams {
+    /// Whether a borrow initializes the pool's obligation first
+    pub fn inits_obligation(&self) -> bool {
+        self.account_counts.len() == 4
+    }
+
+    pub fn validate(&self, action_type: ShieldedActionType) -> Result<()> {
+        require!(self.min_output > 0, PrivacyErrorV2::InvalidAmount);
+        match action_type {
+            ShieldedActionType::LendingDeposit => {
+                require!(
+                    self.borrow_amount == 0 && self.account_counts.len() == 1,
+                    PrivacyErrorV2::InvalidActionData
+                );
+            }
+            ShieldedActionType::LendingBorrow => {
+                require!(
+                    matches!(self.account_counts.len(), 3 | 4),
+                    PrivacyErrorV2::InvalidActionData
+                );
+                require!(
+                    self.min_output <= self.borrow_amount,
+                    PrivacyErrorV2::SlippageOutOfBounds
+                );
+            }
+            _ => return err!(PrivacyErrorV2::InvalidActionData),
+        }
+        require!(
+            self.account_counts
+                .iter()
+                .all(|&n| n > 0 && n <= MAX_LENDING_ACCOUNTS),
+            PrivacyErrorV2::InvalidActionData
+        );
+        Ok(())
+    }
+
+    /// Instruction data of each lending call, in order
+    fn instructions(&self, action_type: ShieldedActionType, amount: u64) -> Vec<Vec<u8>> {
+        let with_amount = |tag: u8, amount: u64| {
+            let mut data = vec![tag];
+            data.extend_from_slice(&amount.to_le_bytes());
+            data
+        };
+        match action_type {
+            ShieldedActionType::LendingDeposit => {
+                vec![with_amount(tag::DEPOSIT_RESERVE_LIQUIDITY, amount)]
+            }
+            _ => {
+                let mut calls = Vec::with_capacity(4);
+                if self.inits_obligation() {
+                    calls.push(vec![tag::INIT_OBLIGATION]);
+                }
+                calls.push(with_amount(tag::DEPOSIT_OBLIGATION_COLLATERAL, amount));
+                calls.push(vec![tag::REFRESH_OBLIGATION]);
+                calls.push(with_amount(
+                    tag::BORROW_OBLIGATION_LIQUIDITY,
+                    self.borrow_amount,
+                ));
+                calls
+            }
+        }
+    }
+}
+
+/// Supply `amount` from the input escrow and, for a borrow, draw the
+/// liquidity into the output escrow, signing as the action authority
+pub fn supply<'info>(
+    action_type: ShieldedActionType,
+    target_program: &AccountInfo<'info>,
+    action_authority: &AccountInfo<'info>,
+    authority_seeds: &[&[u8]],
+    remaining_accounts: &[AccountInfo<'info>],
+    params: &LendingParams,
+    amount: u64,
+) -> Result<()> {
+    require_keys_eq!(
+        target_program.key(),
+        LENDING_PROGRAM_ID,
+        PrivacyErrorV2::InvalidTargetProgram
+    );
+    let total: usize = params.account_counts.iter().map(|&n| n as usize).sum();
+    require!(
+        total == remaining_accounts.len(),
+        PrivacyErrorV2::InvalidActionData
+    );
+
+    let mut accounts = remaining_accounts;
+    for (data, &count) in params
+        .instructions(action_type, amount)
+        .into_iter()
+        .zip(&params.account_counts)
+    {
+        let (call_accounts, rest) = accounts.split_at(count as usize);
+        invoke_as_authority(
+            LENDING_PROGRAM_ID,
+            data,
+            target_program,
+            action_authority,
+            authority_seeds,
+            call_accounts,
+        )?;
+        accounts = rest;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deposit() -> LendingParams {
+        LendingParams {
+            min_output: 990,
+            borrow_amount: 0,
+            account_counts: vec![10],
+        }
+    }
+
+    fn borrow(account_counts: Vec<u8>) -> LendingParams {
+        LendingParams {
+            min_output: 495,
+            borrow_amount: 500,
+            account_counts,
+        }
+    }
+
+    #[test]
+    fn test_deposit_params() {
+        let deposit_type = ShieldedActionType::LendingDeposit;
+        assert!(deposit().validate(deposit_type).is_ok());
+
+        let mut with_borrow = deposit();
+        with_borrow.borrow_amount = 1;
+        assert!(with_borrow.validate(deposit_type).is_err());
+        let mut two_calls = deposit();
+        two_calls.account_counts.push(3);
+        assert!(two_calls.validate(deposit_type).is_err());
+        let mut no_output = deposit();
+        no_output.min_output = 0;
+        assert!(no_output.validate(deposit_type).is_err());
+        // Not a lending action
+        assert!(deposit().validate(ShieldedActionType::DexSwap).is_err());
+    }
+
+    #[test]
+    fn test_borrow_params() {
+        let borrow_type = ShieldedActionType::LendingBorrow;
+        assert!(borrow(vec![7, 3, 8]).validate(borrow_type).is_ok());
+        assert!(borrow(vec![4, 7, 3, 8]).validate(borrow_type).is_ok());
+        assert!(borrow(vec![7, 8]).validate(borrow_type).is_err());
+        assert!(borrow(vec![7, 0, 8]).validate(borrow_type).is_err());
+        assert!(borrow(vec![7, MAX_LENDING_ACCOUNTS + 1, 8])
+            .validate(borrow_type)
+            .is_err());
+
+        let mut above_borrow = borrow(vec![7, 3, 8]);
+        above_borrow.min_output = 501;
+        assert!(above_borrow.validate(borrow_type).is_err());
+    }
+
+    #[test]
+    fn test_instruction_data() {
+        let deposit_calls = deposit().instructions(ShieldedActionType::LendingDeposit, 1_000);
+        assert_eq!(deposit_calls.len(), 1);
+        assert_eq!(deposit_calls[0][0], tag::DEPOSIT_RESERVE_LIQUIDITY);
+        assert_eq!(deposit_calls[0][1..], 1_000u64.to_le_bytes());
+
+        let calls = borrow(vec![4, 7, 3, 8]).instructions(ShieldedActionType::LendingBorrow, 900);
+        let tags: Vec<u8> = calls.iter().map(|data| data[0]).collect();
+        assert_eq!(
+            tags,
+            [
+                tag::INIT_OBLIGATION,
+                tag::DEPOSIT_OBLIGATION_COLLATERAL,
+                tag::REFRESH_OBLIGATION,
+                tag::BORROW_OBLIGATION_LIQUIDITY,
+            ]
+        );
+        assert_eq!(calls[1][1..], 900u64.to_le_bytes());
+        assert_eq!(calls[3][1..], 500u64.to_le_bytes());
+
+        let without_init =
+            borrow(vec![7, 3, 8]).instructions(ShieldedActionType::LendingBorrow, 900);
+        assert_eq!(without_init.len(), 3);
+        assert_eq!(without_init[0][0], tag::DEPOSIT_OBLIGATION_COLLATERAL);
+    }
+}
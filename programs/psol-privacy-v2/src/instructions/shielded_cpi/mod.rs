@@ -3,6 +3,14 @@
 //! Cross-program invocation interface for DeFi integrations.
 //! Allows external protocols to interact with shielded balances.
 
+pub mod action_allowlist;
+pub mod dex_swap;
 pub mod execute_action;
+pub mod lending;
+pub mod stake_pool;
 
-pub use execute_action::ExecuteShieldedAction;
+pub use action_allowlist::{InitActionAllowlist, ManageActionAllowlist};
+pub use dex_swap::DexSwapParams;
+pub use execute_action::{ExecuteShieldedAction, ShieldedSpend};
+pub use lending::LendingParams;
+pub use stake_pool::StakeParams;
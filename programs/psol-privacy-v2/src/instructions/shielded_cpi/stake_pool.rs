@@ -0,0 +1,203 @@
+//! Stake pool adapter for shielded actions
+//!
+//! Stakes shielded SOL through an SPL stake pool (Jito, BlazeStake, ... all
+//! run the SPL stake pool program) and re-shields the pool token (LST), or
+//! the reverse:
+//!
+//! - `Stake`: the input is wrapped SOL. The input escrow is closed into the
+//!   action authority, which deposits the note's amount with `DepositSol`;
+//!   the LST lands in the output escrow. The escrow's rent is returned to
+//!   the relayer.
+//! - `Unstake`: the input is the LST. `WithdrawSol` burns it from the input
+//!   escrow and pays lamports to the action authority, which wraps them
+//!   into the output escrow (wrapped SOL).
+//!
+//! SOL is shielded here as an SPL vault of the wrapped-SOL mint; the
+//! lamport-holding native SOL vault cannot be an action's input or output.
+//! The stake pool instruction takes every remaining account, in the stake
+//! pool program's order; instruction data is built here from the proven
+//! amount.
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use anchor_spl::token::{self, spl_token, CloseAccount, SyncNative};
+
+use crate::error::PrivacyErrorV2;
+
+use super::execute_action::invoke_as_authority;
+
+/// SPL stake pool program
+pub const STAKE_POOL_PROGRAM_ID: Pubkey = pubkey!("SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy");
+
+/// SPL stake pool instruction tags
+mod tag {
+    pub const DEPOSIT_SOL: u8 = 14;
+    pub const WITHDRAW_SOL: u8 = 16;
+}
+
+/// Stake parameters, following the `ShieldedSpend` header in action data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct StakeParams {
+    /// Output amount the re-shielded note commits to (pool tokens for a
+    /// stake, lamports for an unstake)
+    pub min_output: u64,
+}
+
+impl StakeParams {
+    pub fn validate(&self) -> Result<()> {
+        require!(self.min_output > 0, PrivacyErrorV2::InvalidAmount);
+        Ok(())
+    }
+}
+
+/// Accounts the stake adapter moves lamports through
+pub struct StakeAccounts<'a, 'info> {
+    pub target_program: &'a AccountInfo<'info>,
+    pub action_authority: &'a AccountInfo<'info>,
+    pub input_escrow: &'a AccountInfo<'info>,
+    pub output_escrow: &'a AccountInfo<'info>,
+    /// Receives the unwrapped input escrow's rent
+    pub relayer: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+fn with_amount(tag: u8, amount: u64) -> Vec<u8> {
+    let mut data = vec![tag];
+    data.extend_from_slice(&amount.to_le_bytes());
+    data
+}
+
+/// Unwrap `amount` of SOL from the input escrow and deposit it into the
+/// stake pool, signing as the action authority
+pub fn stake<'info>(
+    accounts: &StakeAccounts<'_, 'info>,
+    authority_seeds: &[&[u8]],
+    remaining_accounts: &[AccountInfo<'info>],
+    amount: u64,
+) -> Result<()> {
+    require_keys_eq!(
+        accounts.target_program.key(),
+        STAKE_POOL_PROGRAM_ID,
+        PrivacyErrorV2::InvalidTargetProgram
+    );
+
+    // Closing the wrapped-SOL escrow hands its amount and rent to the
+    // authority, which must be a plain system account to pay `DepositSol`
+    let escrow_lamports = accounts.input_escrow.lamports();
+    token::close_account(CpiContext::new_with_signer(
+        accounts.token_program.clone(),
+        CloseAccount {
+            account: accounts.input_escrow.clone(),
+            destination: accounts.action_authority.clone(),
+            authority: accounts.action_authority.clone(),
+        },
+        &[authority_seeds],
+    ))?;
+
+    invoke_as_authority(
+        STAKE_POOL_PROGRAM_ID,
+        with_amount(tag::DEPOSIT_SOL, amount),
+        accounts.target_program,
+        accounts.action_authority,
+        authority_seeds,
+        remaining_accounts,
+    )?;
+
+    let rent = escrow_lamports
+        .checked_sub(amount)
+        .ok_or(PrivacyErrorV2::ArithmeticOverflow)?;
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            accounts.system_program.clone(),
+            Transfer {
+                from: accounts.action_authority.clone(),
+                to: accounts.relayer.clone(),
+            },
+            &[authority_seeds],
+        ),
+        rent,
+    )
+}
+
+/// Withdraw `amount` pool tokens from the input escrow as SOL and wrap the
+/// lamports received into the output escrow, signing as the action
+/// authority
+pub fn unstake<'info>(
+    accounts: &StakeAccounts<'_, 'info>,
+    authority_seeds: &[&[u8]],
+    remaining_accounts: &[AccountInfo<'info>],
+    amount: u64,
+) -> Result<()> {
+    require_keys_eq!(
+        accounts.target_program.key(),
+        STAKE_POOL_PROGRAM_ID,
+        PrivacyErrorV2::InvalidTargetProgram
+    );
+
+    let before = accounts.action_authority.lamports();
+    invoke_as_authority(
+        STAKE_POOL_PROGRAM_ID,
+        with_amount(tag::WITHDRAW_SOL, amount),
+        accounts.target_program,
+        accounts.action_authority,
+        authority_seeds,
+        remaining_accounts,
+    )?;
+    let received = accounts
+        .action_authority
+        .lamports()
+        .checked_sub(before)
+        .ok_or(PrivacyErrorV2::ArithmeticOverflow)?;
+
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            accounts.system_program.clone(),
+            Transfer {
+                from: accounts.action_authority.clone(),
+                to: accounts.output_escrow.clone(),
+            },
+            &[authority_seeds],
+        ),
+        received,
+    )?;
+    token::sync_native(CpiContext::new(
+        accounts.token_program.clone(),
+        SyncNative {
+            account: accounts.output_escrow.clone(),
+        },
+    ))
+}
+
+/// The side of a stake action that must be wrapped SOL
+pub fn require_wrapped_sol(mint: &Pubkey) -> Result<()> {
+    require_keys_eq!(
+        *mint,
+        spl_token::native_mint::ID,
+        PrivacyErrorV2::InvalidMint
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instruction_data() {
+        let deposit = with_amount(tag::DEPOSIT_SOL, 1_500_000_000);
+        assert_eq!(deposit[0], 14);
+        assert_eq!(deposit[1..], 1_500_000_000u64.to_le_bytes());
+        let withdraw = with_amount(tag::WITHDRAW_SOL, 7);
+        assert_eq!(withdraw[0], 16);
+        assert_eq!(withdraw.len(), 9);
+    }
+
+    #[test]
+    fn test_params() {
+        assert!(StakeParams { min_output: 1 }.validate().is_ok());
+        assert!(StakeParams { min_output: 0 }.validate().is_err());
+        assert!(require_wrapped_sol(&spl_token::native_mint::ID).is_ok());
+        assert!(require_wrapped_sol(&Pubkey::new_unique()).is_err());
+    }
+}
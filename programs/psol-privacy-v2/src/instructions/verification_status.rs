@@ -0,0 +1,147 @@
+//! Verification Status Return Data
+//!
+//! Withdraw and membership instructions return a [`VerificationStatus`] as
+//! Anchor return data, so a program composing with the pool over CPI can read
+//! the outcome with `get_return_data` and branch on it instead of parsing
+//! logs. Events stay the source of truth for indexers; the status is for the
+//! caller in the same transaction.
+//!
+//! A failed check still fails the instruction (and the caller's transaction)
+//! with its `PrivacyErrorV2` code. Outcomes other than `Verified` are for
+//! checks where the proof itself is sound and the caller decides what to do,
+//! such as a membership proof below the requested threshold.
+
+use anchor_lang::prelude::*;
+
+use crate::state::MerkleTreeV2;
+use crate::ProofType;
+
+/// `root_age` for a root accepted outside the tree's root history (an old
+/// tree's root during a migration window)
+pub const ROOT_AGE_UNKNOWN: u16 = u16::MAX;
+
+/// Outcome of a verified proof
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerificationOutcome {
+    /// Proof verified and the instruction's effect was applied
+    Verified = 0,
+    /// Membership proof verified, but for less than the requested threshold
+    ThresholdNotMet = 1,
+    /// Proof verified, but the attestation it binds to has expired
+    AttestationExpired = 2,
+}
+
+/// Structured result returned by proof-verifying instructions
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VerificationStatus {
+    /// Layout version, bumped when fields are added
+    pub version: u8,
+    /// Circuit the proof was verified against
+    pub proof_type: ProofType,
+    /// What the verified proof established
+    pub outcome: VerificationOutcome,
+    /// Root the proof was made against
+    pub merkle_root: [u8; 32],
+    /// Roots recorded after `merkle_root` (0 = current root,
+    /// `ROOT_AGE_UNKNOWN` = migration window root)
+    pub root_age: u16,
+    /// Nullifiers this instruction spent
+    pub nullifiers_spent: u8,
+    /// Gross amount withdrawn (or proven, for membership)
+    pub amount: u64,
+    /// Fee paid to the relayer out of `amount`
+    pub relayer_fee: u64,
+    /// Slot the proof was verified in
+    pub slot: u64,
+}
+
+impl VerificationStatus {
+    pub const VERSION: u8 = 1;
+
+    /// Serialized size: version, proof_type, outcome, root, root_age,
+    /// nullifiers_spent, amount, relayer_fee, slot
+    pub const LEN: usize = 1 + 1 + 1 + 32 + 2 + 1 + 8 + 8 + 8;
+
+    /// Status of a completed withdrawal
+    pub fn withdrawal(
+        proof_type: ProofType,
+        merkle_tree: &MerkleTreeV2,
+        merkle_root: [u8; 32],
+        nullifiers_spent: u8,
+        amount: u64,
+        relayer_fee: u64,
+        slot: u64,
+    ) -> Self {
+        Self {
+            version: Self::VERSION,
+            proof_type,
+            outcome: VerificationOutcome::Verified,
+            merkle_root,
+            root_age: merkle_tree
+                .root_updates_since(&merkle_root)
+                .unwrap_or(ROOT_AGE_UNKNOWN),
+            nullifiers_spent,
+            amount,
+            relayer_fee,
+            slot,
+        }
+    }
+
+    /// Whether the proof established everything the caller asked for
+    pub fn is_verified(&self) -> bool {
+        self.outcome == VerificationOutcome::Verified
+    }
+
+    /// Decode the return data of a pool instruction
+    pub fn from_return_data(data: &[u8]) -> Option<Self> {
+        Self::try_from_slice(data).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_return_data_round_trip() {
+        let status = VerificationStatus {
+            version: VerificationStatus::VERSION,
+            proof_type: ProofType::WithdrawV2,
+            outcome: VerificationOutcome::Verified,
+            merkle_root: [3u8; 32],
+            root_age: 4,
+            nullifiers_spent: 2,
+            amount: 1_000_000,
+            relayer_fee: 1_000,
+            slot: 42,
+        };
+        let data = status.try_to_vec().unwrap();
+        assert_eq!(data.len(), VerificationStatus::LEN);
+        assert_eq!(VerificationStatus::from_return_data(&data), Some(status));
+        assert!(status.is_verified());
+
+        // Stable byte positions for callers that read fields directly
+        assert_eq!(data[1], ProofType::WithdrawV2 as u8);
+        assert_eq!(data[2], VerificationOutcome::Verified as u8);
+
+        assert!(VerificationStatus::from_return_data(&data[..data.len() - 1]).is_none());
+    }
+
+    #[test]
+    fn test_only_verified_outcome_is_verified() {
+        let mut status = VerificationStatus {
+            version: VerificationStatus::VERSION,
+            proof_type: ProofType::Membership,
+            outcome: VerificationOutcome::ThresholdNotMet,
+            merkle_root: [0u8; 32],
+            root_age: ROOT_AGE_UNKNOWN,
+            nullifiers_spent: 0,
+            amount: 0,
+            relayer_fee: 0,
+            slot: 0,
+        };
+        assert!(!status.is_verified());
+        status.outcome = VerificationOutcome::AttestationExpired;
+        assert!(!status.is_verified());
+    }
+}
@@ -0,0 +1,314 @@
+//! Verify Account Integrity Instruction
+//!
+//! Permissionless health probe for operators and auditors, run after an
+//! upgrade or migration. It recomputes what a pool account's contents imply
+//! and compares it with what the account stores:
+//! - verification key: the stored `vk_hash` and IC length
+//! - Merkle tree: the current root is the latest root history entry (or the
+//!   empty root of an empty tree), and the history, frontier, zero values
+//!   and leaf counter have the shape the depth and history size call for
+//! - pending deposits buffer: the ring layout, head and pending count are in
+//!   range and every pending entry holds a commitment
+//!
+//! The outcome is emitted as `AccountIntegrityChecked`, with one bit per
+//! failed check, and the same bits are returned as return data; a failing
+//! account does not fail the instruction. The account must belong to the
+//! given pool.
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::crypto::precomputed_zeros::get_precomputed_zeros;
+use crate::error::PrivacyErrorV2;
+use crate::events::AccountIntegrityChecked;
+use crate::state::pending_deposits::MAX_PENDING_DEPOSITS;
+use crate::state::{
+    LegacyPendingDepositsBuffer, MerkleTreeV2, PendingDepositsBuffer, PoolConfigV2,
+    VerificationKeyAccountV2, MAX_TREE_DEPTH, MIN_ROOT_HISTORY_SIZE, MIN_TREE_DEPTH,
+};
+
+/// Stored `vk_hash` differs from the hash of the stored key
+pub const VK_HASH_MISMATCH: u32 = 1 << 0;
+/// `vk_ic` does not hold `vk_ic_len` points
+pub const VK_IC_LENGTH_MISMATCH: u32 = 1 << 1;
+/// Key was never fully set
+pub const VK_NOT_INITIALIZED: u32 = 1 << 2;
+
+/// Current root is not the latest root history entry
+pub const TREE_ROOT_NOT_IN_HISTORY: u32 = 1 << 0;
+/// Root history length or index disagrees with `root_history_size`
+pub const TREE_HISTORY_SHAPE: u32 = 1 << 1;
+/// Depth out of range, or frontier or zero values do not match it
+pub const TREE_FRONTIER_SHAPE: u32 = 1 << 2;
+/// More leaves than the tree can hold
+pub const TREE_LEAF_COUNT: u32 = 1 << 3;
+
+/// Buffer still has the legacy layout (`migrate_pending_buffer` pending)
+pub const BUFFER_LEGACY_LAYOUT: u32 = 1 << 0;
+/// Head or pending count out of range, or unknown version
+pub const BUFFER_COUNTERS: u32 = 1 << 1;
+/// A pending entry holds a zero commitment
+pub const BUFFER_ZERO_COMMITMENT: u32 = 1 << 2;
+
+/// Kind of account checked
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntegrityTarget {
+    VerificationKey = 0,
+    MerkleTree = 1,
+    PendingBuffer = 2,
+}
+
+/// Accounts for verifying one account's integrity
+#[derive(Accounts)]
+pub struct VerifyAccountIntegrity<'info> {
+    /// Pool configuration account
+    pub pool_config: Box<Account<'info, PoolConfigV2>>,
+
+    /// CHECK: A verification key, Merkle tree or pending deposits buffer of
+    /// the pool, told apart by its discriminator in the handler
+    #[account(owner = crate::ID)]
+    pub target_account: UncheckedAccount<'info>,
+}
+
+/// Failed checks of a verification key
+pub fn vk_failures(vk: &VerificationKeyAccountV2) -> u32 {
+    let mut failures = 0;
+    if !vk.is_initialized {
+        failures |= VK_NOT_INITIALIZED;
+    } else if !vk.verify_integrity() {
+        failures |= VK_HASH_MISMATCH;
+    }
+    if vk.vk_ic.len() != vk.vk_ic_len as usize {
+        failures |= VK_IC_LENGTH_MISMATCH;
+    }
+    failures
+}
+
+/// Failed checks of a Merkle tree
+pub fn tree_failures(tree: &MerkleTreeV2) -> u32 {
+    let mut failures = 0;
+    let depth_ok = (MIN_TREE_DEPTH..=MAX_TREE_DEPTH).contains(&tree.depth);
+    if !depth_ok
+        || tree.filled_subtrees.len() != tree.depth as usize
+        || tree.zeros != get_precomputed_zeros(tree.depth.min(MAX_TREE_DEPTH))
+    {
+        failures |= TREE_FRONTIER_SHAPE;
+    }
+
+    let history_ok = tree.root_history_size >= MIN_ROOT_HISTORY_SIZE
+        && tree.root_history.len() == tree.root_history_size as usize
+        && tree.root_history_index < tree.root_history_size;
+    if !history_ok {
+        failures |= TREE_HISTORY_SHAPE;
+    }
+
+    // An empty tree (fresh or reset) has no history entry to match
+    let root_recorded = if tree.next_leaf_index == 0 {
+        depth_ok && tree.current_root == get_precomputed_zeros(tree.depth)[tree.depth as usize]
+    } else {
+        history_ok && {
+            let latest = (tree.root_history_index as usize + tree.root_history.len() - 1)
+                % tree.root_history.len();
+            tree.root_history[latest] == tree.current_root
+        }
+    };
+    if !root_recorded {
+        failures |= TREE_ROOT_NOT_IN_HISTORY;
+    }
+
+    if tree.next_leaf_index > tree.capacity() {
+        failures |= TREE_LEAF_COUNT;
+    }
+    failures
+}
+
+/// Failed checks of a ring-layout pending deposits buffer
+pub fn buffer_failures(buffer: &PendingDepositsBuffer) -> u32 {
+    if buffer.version != PendingDepositsBuffer::VERSION
+        || buffer.head as usize >= MAX_PENDING_DEPOSITS
+        || buffer.total_pending as usize > MAX_PENDING_DEPOSITS
+    {
+        // The ring cannot be walked safely
+        return BUFFER_COUNTERS;
+    }
+    if buffer
+        .iter()
+        .any(|deposit| crate::crypto::is_zero_hash(&deposit.commitment))
+    {
+        return BUFFER_ZERO_COMMITMENT;
+    }
+    0
+}
+
+/// Handler for verify_account_integrity instruction
+pub fn handler(ctx: Context<VerifyAccountIntegrity>) -> Result<u32> {
+    let info = ctx.accounts.target_account.to_account_info();
+    let data = info.try_borrow_data()?;
+    require!(data.len() >= 8, ErrorCode::AccountDiscriminatorNotFound);
+
+    let (kind, pool, failed_checks) = if data[..8] == *VerificationKeyAccountV2::DISCRIMINATOR {
+        let vk = VerificationKeyAccountV2::try_deserialize(&mut &data[..])?;
+        (IntegrityTarget::VerificationKey, vk.pool, vk_failures(&vk))
+    } else if data[..8] == *MerkleTreeV2::DISCRIMINATOR {
+        let tree = MerkleTreeV2::try_deserialize(&mut &data[..])?;
+        (IntegrityTarget::MerkleTree, tree.pool, tree_failures(&tree))
+    } else if data[..8] == *PendingDepositsBuffer::DISCRIMINATOR {
+        if PendingDepositsBuffer::has_current_layout(&info) {
+            let buffer: &PendingDepositsBuffer =
+                bytemuck::from_bytes(&data[8..PendingDepositsBuffer::LEN]);
+            (
+                IntegrityTarget::PendingBuffer,
+                buffer.pool,
+                buffer_failures(buffer),
+            )
+        } else {
+            let legacy = LegacyPendingDepositsBuffer::deserialize(&mut &data[8..])?;
+            (
+                IntegrityTarget::PendingBuffer,
+                legacy.pool,
+                BUFFER_LEGACY_LAYOUT,
+            )
+        }
+    } else {
+        return err!(ErrorCode::AccountDiscriminatorMismatch);
+    };
+
+    require_keys_eq!(
+        pool,
+        ctx.accounts.pool_config.key(),
+        PrivacyErrorV2::InvalidPoolReference
+    );
+
+    let passed = failed_checks == 0;
+    emit!(AccountIntegrityChecked {
+        pool,
+        account: info.key(),
+        kind: kind as u8,
+        passed,
+        failed_checks,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!(
+        "Integrity of {:?} {}: {} (failed checks {:#x})",
+        kind,
+        info.key(),
+        if passed { "pass" } else { "FAIL" },
+        failed_checks
+    );
+
+    Ok(failed_checks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProofType;
+
+    fn new_tree(depth: u8) -> MerkleTreeV2 {
+        let mut tree = MerkleTreeV2 {
+            pool: Pubkey::default(),
+            depth: 0,
+            next_leaf_index: 0,
+            current_root: [0u8; 32],
+            root_history: Vec::new(),
+            root_history_index: 0,
+            root_history_size: 0,
+            filled_subtrees: Vec::new(),
+            zeros: Vec::new(),
+            total_leaves: 0,
+            last_insertion_at: 0,
+            version: 0,
+            poseidon_params_hash: [0u8; 32],
+        };
+        tree.initialize(Pubkey::default(), depth, MIN_ROOT_HISTORY_SIZE)
+            .unwrap();
+        tree
+    }
+
+    #[test]
+    fn test_vk_checks() {
+        let mut vk = VerificationKeyAccountV2 {
+            pool: Pubkey::default(),
+            proof_type: 0,
+            vk_alpha_g1: [0u8; 64],
+            vk_beta_g2: [0u8; 128],
+            vk_gamma_g2: [0u8; 128],
+            vk_delta_g2: [0u8; 128],
+            vk_ic_len: 0,
+            vk_ic: Vec::new(),
+            is_initialized: false,
+            is_locked: false,
+            bump: 0,
+            set_at: 0,
+            locked_at: 0,
+            vk_hash: [0u8; 32],
+            canary_passed: false,
+            canary_passed_at: 0,
+            ceremony_transcript_hash: [0u8; 32],
+            ceremony_contributors: 0,
+            _reserved: [0u8; 23],
+        };
+        vk.initialize(Pubkey::default(), ProofType::Withdraw, 255);
+        assert_eq!(vk_failures(&vk), VK_NOT_INITIALIZED);
+
+        vk.set_vk(
+            [1u8; 64],
+            [2u8; 128],
+            [3u8; 128],
+            [4u8; 128],
+            vec![[5u8; 64]; 9],
+            10,
+        );
+        assert_eq!(vk_failures(&vk), 0);
+
+        vk.vk_delta_g2[0] ^= 1;
+        vk.vk_ic.pop();
+        assert_eq!(vk_failures(&vk), VK_HASH_MISMATCH | VK_IC_LENGTH_MISMATCH);
+    }
+
+    #[test]
+    fn test_tree_checks() {
+        let mut tree = new_tree(4);
+        assert_eq!(tree_failures(&tree), 0);
+        for i in 1..=40u8 {
+            tree.insert_leaf([i; 32], 0).ok();
+        }
+        // Full tree, root history wrapped around
+        assert_eq!(tree.next_leaf_index, 16);
+        assert_eq!(tree_failures(&tree), 0);
+
+        let mut stale = tree.clone();
+        stale.current_root = [9u8; 32];
+        assert_eq!(tree_failures(&stale), TREE_ROOT_NOT_IN_HISTORY);
+
+        let mut truncated = tree.clone();
+        truncated.root_history.pop();
+        assert_eq!(
+            tree_failures(&truncated),
+            TREE_HISTORY_SHAPE | TREE_ROOT_NOT_IN_HISTORY
+        );
+
+        let mut frontier = tree.clone();
+        frontier.zeros[2] = [1u8; 32];
+        frontier.next_leaf_index = 17;
+        assert_eq!(
+            tree_failures(&frontier),
+            TREE_FRONTIER_SHAPE | TREE_LEAF_COUNT
+        );
+    }
+
+    #[test]
+    fn test_buffer_checks() {
+        let mut buffer: PendingDepositsBuffer = bytemuck::Zeroable::zeroed();
+        buffer.initialize(Pubkey::default(), 255, 0);
+        buffer.add_pending([1u8; 32], 0).unwrap();
+        assert_eq!(buffer_failures(&buffer), 0);
+
+        buffer.deposits[0].commitment = [0u8; 32];
+        assert_eq!(buffer_failures(&buffer), BUFFER_ZERO_COMMITMENT);
+
+        buffer.head = MAX_PENDING_DEPOSITS as u32;
+        assert_eq!(buffer_failures(&buffer), BUFFER_COUNTERS);
+    }
+}
@@ -21,27 +21,168 @@
 //! 2. Nullifier is marked as spent (prevents double-spending)
 //! 3. Tokens are transferred to recipient
 //! 4. Relayer receives fee for submitting transaction
+//!
+//! # Native SOL
+//!
+//! For the native SOL vault the token accounts are omitted: the recipient's
+//! wallet (`recipient_wallet`) and the relayer are paid in lamports straight
+//! from the vault PDA.
+//!
+//! # Fee payer refunds
+//!
+//! `withdraw_with_fee_payer_refund` lets a throwaway keypair act as the
+//! relayer: it pays the transaction fee and nullifier rent, and is paid
+//! back `fee_payer_refund` lamports out of the withdrawal on top of any
+//! relayer fee, so neither the recipient nor a relayer has to fund a fee
+//! payer from a linkable wallet. The refund is bounded by
+//! `MAX_FEE_PAYER_REFUND_LAMPORTS` and bound into the proof through
+//! `public_data_hash` (`fee_payer_refund_hash`); it is only paid from the
+//! native SOL vault, since SPL vaults hold no lamports to refund.
+//!
+//! # Parameter pins
+//!
+//! `withdraw_with_parameter_pin` carries the `ParameterSnapshot` version
+//! the client displayed when the proof was generated, bound into the proof
+//! through `public_data_hash` (`parameter_pin_hash`) so a relayer cannot
+//! drop it. The withdrawal fails if the asset's fees, limits or flags have
+//! changed adversely since that version (see `state::parameter_snapshot`).
+//!
+//! # Sealed withdrawals
+//!
+//! With `withdrawal_commit` attached, the withdrawal executes a
+//! `WithdrawalCommit` the user made ahead of relaying
+//! (`instructions::withdrawal_commit`): the public inputs must hash to its
+//! `commit_hash`, and the proof binds the commit through `public_data_hash`
+//! (`withdrawal_commit_pin_hash`) so it is not valid without it. The commit
+//! is closed and its rent paid to the relayer. A sealed withdrawal carries
+//! no fee payer refund or parameter pin, which use the same public input.
+//!
+//! # Fee quotes
+//!
+//! With `fee_quote` attached, the relayer fee must be within the relayer's
+//! published `RelayerFeeQuote` (flat fee plus basis points of `amount`) and
+//! the quote must still be valid, so a client that generated the proof
+//! against the quote is not charged more (see `relayer::fee_quote`).
+//!
+//! # Fresh recipients
+//!
+//! A recipient with no token account for the asset can be paid through
+//! `recipient_ata` (with `associated_token_program`, `mint` and
+//! `recipient_wallet`) instead of `recipient_token_account`: the relayer
+//! creates the associated token account if it is missing and pays its
+//! rent, which it prices into the relayer fee the proof binds, so the
+//! recipient reimburses it out of the withdrawal.
+//!
+//! # Fee payers
+//!
+//! Relayer infrastructure can fund submissions from a dedicated wallet:
+//! `fee_payer` signs as the transaction's fee payer and pays the nullifier
+//! rent (reimbursing the relayer, which `init` charges) and any recipient
+//! token account rent. The relayer stays the party the proof binds, the
+//! fee goes to, and events and the spent nullifier record. A fee payer
+//! cannot be combined with a rent advance, which would cover the same rent.
+//!
+//! # Protocol fee
+//!
+//! While the pool sets `protocol_fee_bps`, withdrawals pay that share of
+//! `amount` out of what the recipient receives; the relayer fee is
+//! unchanged. Token withdrawals pay it into the asset's treasury
+//! (`treasury_token_account`, see `admin::protocol_fee`); native SOL
+//! withdrawals leave it in the vault, above `shielded_balance`, for the pool
+//! treasury to collect (see `admin::treasury`).
+//!
+//! # Token-2022
+//!
+//! Token-2022 vaults must be given the `mint` so payouts go through
+//! `transfer_checked`; a transfer fee on the mint is withheld from what the
+//! recipient and relayer receive. The vault must be debited exactly
+//! `amount` and still back its shielded balance afterwards.
+//!
+//! # Risk tiers
+//!
+//! An asset put in a risk tier is held to the tier's withdrawal switch and
+//! cap, read from the pool's tier table (`risk_tiers`). A tier that
+//! rate-limits withdrawals counts them in the asset's `withdrawal_window`,
+//! and one with a delay threshold only pays larger withdrawals through a
+//! `withdrawal_commit` old enough (see `state::risk_tier`).
 
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::associated_token::{
+    self, get_associated_token_address_with_program_id, AssociatedToken,
+};
+use anchor_spl::token::{self, Transfer};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+use solana_sdk_ids::sysvar::instructions as sysvar_instructions;
 
+use crate::crypto::keccak::keccak256_concat;
 use crate::crypto::WithdrawPublicInputs;
 use crate::error::PrivacyErrorV2;
 #[cfg(feature = "event-debug")]
 use crate::events::WithdrawMaspDebugEvent;
-use crate::events::WithdrawMaspEvent;
+use crate::events::{ProofVerificationFailed, WithdrawMaspEvent};
+use crate::instructions::relayer::rent_reserve::top_up_relayer_rent;
+use crate::instructions::verification_status::VerificationStatus;
+use crate::state::migration_window::is_withdraw_root_accepted;
 use crate::state::{
-    AssetVault, MerkleTreeV2, PoolConfigV2, RelayerNode, RelayerRegistry, SpendType,
-    SpentNullifierV2, VerificationKeyAccountV2, YieldRegistry,
+    withdrawal_commit_hash, withdrawal_commit_pin_hash, AnonymityStats, AssetVault,
+    AssetWithdrawalWindow, DeferredWithdrawEvent, DeferredWithdrawEvents, EconomicParameters,
+    GlobalConfig, MerkleTreeV2, MigrationWindow, ParameterSnapshot, PoolConfigV2, ProofStats,
+    RelayerFeeQuote, RelayerNode, RelayerRebateLedger, RelayerRegistry, RentAdvance, RentReserve,
+    RiskTierConfig, SpendContext, SpentNullifierV2, VerificationKeyAccountV2, WithdrawalCommit,
+    YieldRegistry,
+};
+use crate::utils::{
+    check_not_yield_asset, check_risk_tier_withdrawal, check_withdrawal, pool_clock,
+    reimburse_relayer_rent, require_program_id,
 };
 use crate::ProofType;
 
-/// Minimum withdrawal amount to prevent dust attacks
-pub const MIN_WITHDRAWAL_AMOUNT: u64 = 100;
-
 /// Maximum relayer fee in basis points (10% = 1000 bps)
 pub const MAX_RELAYER_FEE_BPS: u64 = 1000;
 
+/// Most a fee payer may be refunded (0.01 SOL: nullifier rent plus a
+/// prioritized transaction fee, with headroom)
+pub const MAX_FEE_PAYER_REFUND_LAMPORTS: u64 = 10_000_000;
+
+/// `public_data_hash` binding a fee payer refund to the withdraw proof,
+/// reduced to a canonical field element (top byte cleared); zero, as for a
+/// plain withdrawal, when there is no refund
+pub fn fee_payer_refund_hash(fee_payer_refund: u64) -> [u8; 32] {
+    if fee_payer_refund == 0 {
+        return [0u8; 32];
+    }
+    let h = keccak256_concat(&[b"psol:fee_payer_refund:v1", &fee_payer_refund.to_le_bytes()]);
+    let mut out = [0u8; 32];
+    out[1..32].copy_from_slice(&h[0..31]);
+    out
+}
+
+/// What the recipient receives out of `amount` after the relayer fee, fee
+/// payer refund and protocol fee, blaming whichever deduction runs past it
+pub fn recipient_amount(
+    amount: u64,
+    relayer_fee: u64,
+    fee_payer_refund: u64,
+    protocol_fee: u64,
+) -> Result<u64> {
+    amount
+        .checked_sub(relayer_fee)
+        .ok_or(error!(PrivacyErrorV2::RelayerFeeExceedsAmount))?
+        .checked_sub(fee_payer_refund)
+        .ok_or(error!(PrivacyErrorV2::FeePayerRefundTooLarge))?
+        .checked_sub(protocol_fee)
+        .ok_or(error!(PrivacyErrorV2::FeesExceedAmount))
+}
+
+/// `public_data_hash` binding a parameter snapshot version to the withdraw
+/// proof, reduced to a canonical field element (top byte cleared)
+pub fn parameter_pin_hash(parameter_version: u64) -> [u8; 32] {
+    let h = keccak256_concat(&[b"psol:parameter_pin:v1", &parameter_version.to_le_bytes()]);
+    let mut out = [0u8; 32];
+    out[1..32].copy_from_slice(&h[0..31]);
+    out
+}
+
 /// Accounts for withdrawing from the MASP
 #[derive(Accounts)]
 #[instruction(
@@ -67,10 +208,20 @@ pub struct WithdrawMasp<'info> {
     )]
     pub pool_config: Box<Account<'info, PoolConfigV2>>,
 
-    /// Merkle tree account
+    /// Program-wide pause switch
+    /// CHECK: PDA; may not exist yet (see `GlobalConfig::is_paused_account`)
     #[account(
-        constraint = merkle_tree.is_known_root(&merkle_root) @ PrivacyErrorV2::InvalidMerkleRoot,
+        seeds = [GlobalConfig::SEED_PREFIX],
+        bump,
+        constraint = !GlobalConfig::is_paused_account(&global_config)
+            @ PrivacyErrorV2::GlobalPaused,
     )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// Merkle tree account
+    ///
+    /// `merkle_root` is checked in the handler against the live root set
+    /// and, if provided, the migration window.
     pub merkle_tree: Box<Account<'info, MerkleTreeV2>>,
 
     /// Verification key for withdraw proofs
@@ -80,6 +231,8 @@ pub struct WithdrawMasp<'info> {
         constraint = vk_account.is_initialized @ PrivacyErrorV2::VerificationKeyNotSet,
         constraint = vk_account.proof_type == ProofType::Withdraw as u8
             @ PrivacyErrorV2::InvalidVerificationKeyType,
+        constraint = !pool_config.requires_vk_canary() || vk_account.canary_passed
+            @ PrivacyErrorV2::VkCanaryNotPassed,
     )]
     pub vk_account: Box<Account<'info, VerificationKeyAccountV2>>,
 
@@ -97,15 +250,15 @@ pub struct WithdrawMasp<'info> {
     )]
     pub asset_vault: Box<Account<'info, AssetVault>>,
 
-    /// Vault's token account (source)
+    /// Vault's token account (source; SPL assets only)
     #[account(
         mut,
         constraint = vault_token_account.key() == asset_vault.token_account
             @ PrivacyErrorV2::InvalidVaultTokenAccount,
     )]
-    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+    pub vault_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
-    /// Recipient's token account (destination)
+    /// Recipient's token account (destination; SPL assets only)
     /// SECURITY: Must be owned by the recipient pubkey from the proof public inputs
     /// to prevent fund redirection attacks.
     #[account(
@@ -113,16 +266,16 @@ pub struct WithdrawMasp<'info> {
         constraint = recipient_token_account.mint == asset_vault.mint @ PrivacyErrorV2::InvalidMint,
         constraint = recipient_token_account.owner == recipient @ PrivacyErrorV2::RecipientMismatch,
     )]
-    pub recipient_token_account: Box<Account<'info, TokenAccount>>,
+    pub recipient_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
-    /// Relayer's token account for fee (if relayer_fee > 0)
+    /// Relayer's token account for fee (SPL assets only)
     /// SECURITY: Must be owned by the relayer signer to prevent fee redirection attacks.
     #[account(
         mut,
         constraint = relayer_token_account.mint == asset_vault.mint @ PrivacyErrorV2::InvalidMint,
         constraint = relayer_token_account.owner == relayer.key() @ PrivacyErrorV2::RelayerMismatch,
     )]
-    pub relayer_token_account: Box<Account<'info, TokenAccount>>,
+    pub relayer_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
     /// Spent nullifier account (PDA, created on first use)
     #[account(
@@ -142,19 +295,184 @@ pub struct WithdrawMasp<'info> {
     pub relayer_registry: Box<Account<'info, RelayerRegistry>>,
 
     /// Relayer node (optional, for registered relayers)
+    #[account(mut)]
     pub relayer_node: Option<Account<'info, RelayerNode>>,
 
     /// Optional: Yield registry (for yield asset enforcement)
     pub yield_registry: Option<Account<'info, YieldRegistry>>,
 
-    /// Token program
-    pub token_program: Program<'info, Token>,
+    /// Token program owning the mint (SPL Token or Token-2022)
+    pub token_program: Interface<'info, TokenInterface>,
 
     /// System program
     pub system_program: Program<'info, System>,
+
+    /// CHECK: Address constrained to the instructions sysvar (same-transaction
+    /// deposit/withdraw guard)
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    /// Optional: per-proof-type verification counters
+    #[account(
+        mut,
+        seeds = [ProofStats::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = proof_stats.bump,
+    )]
+    pub proof_stats: Option<Account<'info, ProofStats>>,
+
+    /// Optional: gas rebate ledger for the submitting relayer
+    #[account(
+        mut,
+        constraint = rebate_ledger.operator == relayer.key() @ PrivacyErrorV2::RebateLedgerMismatch,
+    )]
+    pub rebate_ledger: Option<Account<'info, RelayerRebateLedger>>,
+
+    /// Optional: pre-migration roots accepted while a migration window is open
+    #[account(
+        seeds = [MigrationWindow::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = migration_window.bump,
+    )]
+    pub migration_window: Option<Box<Account<'info, MigrationWindow>>>,
+
+    /// Optional: queue the withdraw event for a later flush instead of
+    /// emitting it with the spend
+    #[account(
+        mut,
+        seeds = [DeferredWithdrawEvents::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = deferred_events.bump,
+    )]
+    pub deferred_events: Option<Box<Account<'info, DeferredWithdrawEvents>>>,
+
+    /// Recipient's wallet, paid in lamports (native SOL only)
+    /// CHECK: Must be the recipient from the proof public inputs; only
+    /// credited
+    #[account(
+        mut,
+        constraint = recipient_wallet.key() == recipient @ PrivacyErrorV2::RecipientMismatch,
+    )]
+    pub recipient_wallet: Option<UncheckedAccount<'info>>,
+
+    /// Optional: pool rent reserve that tops up the nullifier rent
+    #[account(
+        mut,
+        seeds = [RentReserve::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = rent_reserve.bump,
+    )]
+    pub rent_reserve: Option<Box<Account<'info, RentReserve>>>,
+
+    /// Optional: the relayer's rent advance account (with `rent_reserve`)
+    #[account(
+        mut,
+        constraint = rent_advance.operator == relayer.key() @ PrivacyErrorV2::RentAdvanceMismatch,
+    )]
+    pub rent_advance: Option<Account<'info, RentAdvance>>,
+
+    /// Mint for this asset (required for Token-2022 vaults)
+    #[account(
+        constraint = mint.key() == asset_vault.mint @ PrivacyErrorV2::InvalidMint,
+    )]
+    pub mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    /// Optional: per-asset deposit-to-withdrawal latency statistics
+    /// (required once the pool keeps them)
+    #[account(
+        mut,
+        seeds = [
+            AnonymityStats::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        bump = anonymity_stats.bump,
+    )]
+    pub anonymity_stats: Option<Box<Account<'info, AnonymityStats>>>,
+
+    /// Parameter snapshot checked against a pinned version (required by
+    /// `withdraw_with_parameter_pin`)
+    #[account(
+        seeds = [
+            ParameterSnapshot::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        bump = parameter_snapshot.bump,
+    )]
+    pub parameter_snapshot: Option<Box<Account<'info, ParameterSnapshot>>>,
+
+    /// Recipient's associated token account, created if missing (SPL only;
+    /// in place of `recipient_token_account`)
+    /// CHECK: Must be the recipient's associated token account for the
+    /// vault's mint; initialized by the associated token program
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &recipient,
+            &asset_vault.mint,
+            &token_program.key(),
+        ) @ PrivacyErrorV2::RecipientMismatch,
+    )]
+    pub recipient_ata: Option<UncheckedAccount<'info>>,
+
+    /// Associated token program (with `recipient_ata`)
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+
+    /// Optional: wallet funding the nullifier rent (and any recipient token
+    /// account) in place of the relayer
+    #[account(mut)]
+    pub fee_payer: Option<Signer<'info>>,
+
+    /// Asset treasury collecting the protocol fee (required while the pool
+    /// charges one on token withdrawals)
+    #[account(
+        mut,
+        seeds = [AssetVault::TREASURY_SEED_PREFIX, asset_vault.key().as_ref()],
+        bump,
+    )]
+    pub treasury_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Optional: the sealed withdrawal this executes (closed, its rent paid
+    /// to the relayer)
+    #[account(
+        mut,
+        close = relayer,
+        constraint = withdrawal_commit.pool == pool_config.key()
+            @ PrivacyErrorV2::WithdrawalCommitMismatch,
+        seeds = [
+            WithdrawalCommit::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            withdrawal_commit.commit_hash.as_ref(),
+        ],
+        bump = withdrawal_commit.bump,
+    )]
+    pub withdrawal_commit: Option<Box<Account<'info, WithdrawalCommit>>>,
+
+    /// Optional: the relayer's published fee quote, checked against the fee
+    #[account(
+        constraint = fee_quote.operator == relayer.key() @ PrivacyErrorV2::FeeQuoteMismatch,
+        seeds = [RelayerFeeQuote::SEED_PREFIX, fee_quote.relayer_node.as_ref()],
+        bump = fee_quote.bump,
+    )]
+    pub fee_quote: Option<Box<Account<'info, RelayerFeeQuote>>>,
+
+    /// Pool's risk tier table; required once the asset is in a tier
+    #[account(
+        seeds = [RiskTierConfig::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = risk_tiers.bump,
+    )]
+    pub risk_tiers: Option<Box<Account<'info, RiskTierConfig>>>,
+
+    /// Asset's withdrawal window; required while its tier rate-limits
+    /// withdrawals
+    #[account(
+        mut,
+        seeds = [AssetWithdrawalWindow::SEED_PREFIX, asset_vault.key().as_ref()],
+        bump = withdrawal_window.bump,
+    )]
+    pub withdrawal_window: Option<Box<Account<'info, AssetWithdrawalWindow>>>,
 }
 
-/// Handler for withdraw_masp instruction
+/// Handler for withdraw_masp, withdraw_with_fee_payer_refund and
+/// withdraw_with_parameter_pin (`fee_payer_refund` is zero and
+/// `parameter_pin` is `None` unless the variant sets them)
 #[allow(clippy::too_many_arguments)]
 pub fn handler(
     ctx: Context<WithdrawMasp>,
@@ -165,20 +483,30 @@ pub fn handler(
     amount: u64,
     asset_id: [u8; 32],
     relayer_fee: u64,
-) -> Result<()> {
+    fee_payer_refund: u64,
+    parameter_pin: Option<u64>,
+) -> Result<VerificationStatus> {
     // =========================================================================
     // INPUT VALIDATION (fail fast before any state changes)
     // =========================================================================
 
-    // Validate proof data length (Groth16: 2*G1 + 1*G2 = 256 bytes)
-    require!(proof_data.len() == 256, PrivacyErrorV2::InvalidProofFormat);
-
-    // Validate amount is above minimum (prevents dust attacks)
+    // Validate proof data length (Groth16: 2*G1 + 1*G2 = 256 bytes, optional format tag)
     require!(
-        amount >= MIN_WITHDRAWAL_AMOUNT,
-        PrivacyErrorV2::InvalidAmount
+        crate::crypto::is_valid_proof_length(&proof_data),
+        PrivacyErrorV2::InvalidProofFormat
     );
 
+    // Amount, relayer fee and same-transaction rules shared with the other
+    // withdrawal paths
+    check_withdrawal(
+        &ctx.accounts.pool_config.key(),
+        &ctx.accounts.pool_config,
+        &ctx.accounts.asset_vault,
+        &ctx.accounts.instructions_sysvar,
+        amount,
+        relayer_fee,
+    )?;
+
     // Validate nullifier is not zero
     require!(
         !nullifier_hash.iter().all(|&b| b == 0),
@@ -191,24 +519,10 @@ pub fn handler(
         PrivacyErrorV2::InvalidMerkleRoot
     );
 
-    // Validate relayer fee doesn't exceed amount
+    // A funded fee payer leaves no rent for the reserve to advance
     require!(
-        relayer_fee <= amount,
-        PrivacyErrorV2::RelayerFeeExceedsAmount
-    );
-
-    // Validate relayer fee is reasonable (max 10% for safety)
-    // Using multiplication to avoid integer division edge cases:
-    // relayer_fee <= amount * 10% is equivalent to relayer_fee * 10 <= amount
-    // This correctly handles small amounts where amount/10 would truncate to 0
-    //
-    // SECURITY: Use checked_mul to reject overflow instead of silent saturation
-    let fee_times_ten = relayer_fee
-        .checked_mul(10)
-        .ok_or(error!(PrivacyErrorV2::RelayerFeeOverflow))?;
-    require!(
-        fee_times_ten <= amount,
-        PrivacyErrorV2::RelayerFeeOutOfRange
+        ctx.accounts.fee_payer.is_none() || ctx.accounts.rent_advance.is_none(),
+        PrivacyErrorV2::RentAdvanceWithFeePayer
     );
 
     // Validate asset ID matches
@@ -217,33 +531,97 @@ pub fn handler(
         PrivacyErrorV2::AssetIdMismatch
     );
 
+    // Fee payer refunds are lamports, bounded and paid from the note
+    if fee_payer_refund > 0 {
+        require!(
+            ctx.accounts.asset_vault.is_native_sol(),
+            PrivacyErrorV2::FeePayerRefundRequiresNativeSol
+        );
+        require!(
+            fee_payer_refund <= MAX_FEE_PAYER_REFUND_LAMPORTS,
+            PrivacyErrorV2::FeePayerRefundTooLarge
+        );
+    }
+
+    // A pinned withdrawal refuses parameters that moved against the user
+    if let Some(pinned_version) = parameter_pin {
+        let snapshot = ctx
+            .accounts
+            .parameter_snapshot
+            .as_ref()
+            .ok_or(PrivacyErrorV2::ParameterSnapshotRequired)?;
+        snapshot.check_pin(
+            pinned_version,
+            &EconomicParameters::read(
+                &ctx.accounts.pool_config,
+                &ctx.accounts.asset_vault,
+                &ctx.accounts.relayer_registry,
+            ),
+        )?;
+    }
+
+    // A sealed withdrawal executes exactly the committed inputs
+    let commit_pin = match ctx.accounts.withdrawal_commit.as_ref() {
+        Some(withdrawal_commit) => {
+            require!(
+                fee_payer_refund == 0 && parameter_pin.is_none(),
+                PrivacyErrorV2::WithdrawalCommitConflict
+            );
+            require!(
+                withdrawal_commit.commit_hash
+                    == withdrawal_commit_hash(
+                        &ctx.accounts.pool_config.key(),
+                        &merkle_root,
+                        &nullifier_hash,
+                        &recipient,
+                        amount,
+                        &asset_id,
+                        relayer_fee,
+                    ),
+                PrivacyErrorV2::WithdrawalCommitMismatch
+            );
+            Some(withdrawal_commit_pin_hash(&withdrawal_commit.commit_hash))
+        }
+        None => None,
+    };
+
     // =========================================================================
     // YIELD ENFORCEMENT: Reject yield assets in permissionless withdraw
     // =========================================================================
-    if ctx.accounts.pool_config.is_yield_enforcement_enabled() {
-        // CRITICAL: Require yield_registry when enforcement enabled
-        let yield_registry = ctx
-            .accounts
-            .yield_registry
-            .as_ref()
-            .ok_or(PrivacyErrorV2::YieldRegistryRequired)?;
+    check_not_yield_asset(
+        &ctx.accounts.pool_config,
+        ctx.accounts.yield_registry.as_deref(),
+        &asset_id,
+    )?;
 
+    // Validate the payout accounts for this asset type and the vault balance
+    let vault_balance = if ctx.accounts.asset_vault.is_native_sol() {
         require!(
-            !yield_registry.is_yield_asset(&asset_id),
-            PrivacyErrorV2::YieldAssetRequiresYieldExit
+            ctx.accounts.recipient_wallet.is_some(),
+            PrivacyErrorV2::MissingAssetAccount
         );
-    }
-
-    // Validate sufficient vault balance
-    require!(
-        ctx.accounts.vault_token_account.amount >= amount,
-        PrivacyErrorV2::InsufficientBalance
-    );
+        AssetVault::native_balance(&ctx.accounts.asset_vault.to_account_info())?
+    } else {
+        require!(
+            (ctx.accounts.recipient_token_account.is_some()
+                || ctx.accounts.can_create_recipient_ata())
+                && ctx.accounts.relayer_token_account.is_some()
+                && (ctx.accounts.mint.is_some() || !ctx.accounts.asset_vault.is_token_2022()),
+            PrivacyErrorV2::MissingAssetAccount
+        );
+        ctx.accounts
+            .vault_token_account
+            .as_ref()
+            .ok_or(error!(PrivacyErrorV2::MissingAssetAccount))?
+            .amount
+    };
+    require!(vault_balance >= amount, PrivacyErrorV2::InsufficientBalance);
 
     // Validate relayer if registered
     if let Some(ref relayer_node) = ctx.accounts.relayer_node {
         // Validate RelayerNode belongs to the expected RelayerRegistry and is the canonical PDA
         // derived from seeds [b"relayer", registry, operator].
+        require_program_id(ctx.program_id)?;
         let relayer_node_key = relayer_node.key();
         relayer_node.validate_registry_and_pda(
             ctx.program_id,
@@ -252,6 +630,10 @@ pub fn handler(
         )?;
 
         require!(relayer_node.is_active, PrivacyErrorV2::RelayerNotActive);
+        require!(
+            relayer_node.accepts_withdrawals(),
+            PrivacyErrorV2::RelayerDraining
+        );
         require!(
             relayer_node.operator == ctx.accounts.relayer.key(),
             PrivacyErrorV2::Unauthorized
@@ -264,12 +646,38 @@ pub fn handler(
         );
     }
 
-    let clock = Clock::get()?;
+    let clock = pool_clock(&ctx.accounts.pool_config)?;
     let timestamp = clock.unix_timestamp;
     let slot = clock.slot;
 
-    // Validate timestamp is sane
-    require!(timestamp > 0, PrivacyErrorV2::InvalidTimestamp);
+    if let Some(fee_quote) = ctx.accounts.fee_quote.as_ref() {
+        fee_quote.check_fee(relayer_fee, amount, slot)?;
+    }
+
+    check_risk_tier_withdrawal(
+        &ctx.accounts.asset_vault,
+        ctx.accounts.risk_tiers.as_deref().map(|t| &**t),
+        ctx.accounts
+            .withdrawal_window
+            .as_deref_mut()
+            .map(|w| &mut **w),
+        amount,
+        ctx.accounts
+            .withdrawal_commit
+            .as_ref()
+            .map(|commit| commit.committed_at),
+        timestamp,
+    )?;
+
+    require!(
+        is_withdraw_root_accepted(
+            &ctx.accounts.merkle_tree,
+            ctx.accounts.migration_window.as_deref().map(|w| &**w),
+            &merkle_root,
+            timestamp,
+        ),
+        PrivacyErrorV2::InvalidMerkleRoot
+    );
 
     // =========================================================================
     // PROOF VERIFICATION (before any state changes)
@@ -286,7 +694,11 @@ pub fn handler(
         amount,
         ctx.accounts.relayer.key(),
         relayer_fee,
-        [0u8; 32], // public_data_hash (reserved for future use)
+        match (commit_pin, parameter_pin) {
+            (Some(commit_pin), _) => commit_pin,
+            (None, Some(parameter_version)) => parameter_pin_hash(parameter_version),
+            (None, None) => fee_payer_refund_hash(fee_payer_refund),
+        },
     );
     public_inputs.validate()?;
 
@@ -303,7 +715,24 @@ pub fn handler(
         &field_elements,
     )?;
 
-    require!(is_valid, PrivacyErrorV2::InvalidProof);
+    if !is_valid {
+        emit!(ProofVerificationFailed {
+            pool: ctx.accounts.pool_config.key(),
+            proof_type: ProofType::Withdraw as u8,
+            submitter: ctx.accounts.relayer.key(),
+            timestamp,
+        });
+        return err!(PrivacyErrorV2::InvalidProof);
+    }
+    if let Some(proof_stats) = ctx.accounts.proof_stats.as_mut() {
+        proof_stats.record_verified(ProofType::Withdraw, timestamp)?;
+    }
+    ctx.accounts
+        .pool_config
+        .require_anonymity_stats(ctx.accounts.anonymity_stats.is_some())?;
+    if let Some(anonymity_stats) = ctx.accounts.anonymity_stats.as_mut() {
+        anonymity_stats.record_withdrawal(timestamp)?;
+    }
 
     // =========================================================================
     // STATE CHANGES (only after proof verification succeeds)
@@ -315,71 +744,116 @@ pub fn handler(
         ctx.accounts.pool_config.key(),
         nullifier_hash,
         asset_id,
-        SpendType::Withdraw,
+        SpendContext::WithdrawMasp,
         timestamp,
         slot,
         ctx.accounts.relayer.key(),
         ctx.bumps.spent_nullifier,
     );
 
-    // Calculate recipient amount after relayer fee
-    let recipient_amount = amount
-        .checked_sub(relayer_fee)
-        .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
-
-    // Create vault signer seeds for CPI
-    let pool_key = ctx.accounts.pool_config.key();
-    let vault_bump = ctx.accounts.asset_vault.bump;
-    let vault_seeds: &[&[u8]] = &[
-        AssetVault::SEED_PREFIX,
-        pool_key.as_ref(),
-        asset_id.as_ref(),
-        &[vault_bump],
-    ];
+    // `init` charged the relayer the nullifier rent: the fee payer covers it
+    if let Some(fee_payer) = ctx.accounts.fee_payer.as_ref() {
+        reimburse_relayer_rent(
+            fee_payer,
+            &ctx.accounts.relayer,
+            &ctx.accounts.system_program,
+            Rent::get()?.minimum_balance(SpentNullifierV2::LEN),
+        )?;
+    }
 
-    let vault_signer_seeds: &[&[&[u8]]] = &[vault_seeds];
+    // Protocol fee, out of the recipient's share; a native SOL vault keeps
+    // it for `collect_treasury_fees`
+    let protocol_fee = ctx.accounts.pool_config.protocol_fee_for(amount);
 
-    // Transfer tokens to recipient
-    if recipient_amount > 0 {
-        let transfer_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.vault_token_account.to_account_info(),
-                to: ctx.accounts.recipient_token_account.to_account_info(),
-                authority: ctx.accounts.asset_vault.to_account_info(),
-            },
-            vault_signer_seeds,
-        );
-        token::transfer(transfer_ctx, recipient_amount)?;
-    }
+    let recipient_amount = recipient_amount(amount, relayer_fee, fee_payer_refund, protocol_fee)?;
 
-    // Transfer fee to relayer
-    if relayer_fee > 0 {
-        let transfer_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.vault_token_account.to_account_info(),
-                to: ctx.accounts.relayer_token_account.to_account_info(),
-                authority: ctx.accounts.asset_vault.to_account_info(),
-            },
-            vault_signer_seeds,
-        );
-        token::transfer(transfer_ctx, relayer_fee)?;
+    if ctx.accounts.asset_vault.is_native_sol() {
+        let relayer_payout = relayer_fee
+            .checked_add(fee_payer_refund)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        pay_native(ctx.accounts, recipient_amount, relayer_payout)?;
+    } else {
+        pay_tokens(
+            ctx.accounts,
+            asset_id,
+            recipient_amount,
+            relayer_fee,
+            protocol_fee,
+        )?;
     }
 
     // Update asset vault statistics
+    let coarse_stats = ctx.accounts.pool_config.has_coarse_liquidity_stats();
     ctx.accounts
         .asset_vault
-        .record_withdrawal(amount, timestamp)?;
+        .record_withdrawal(amount, timestamp, coarse_stats)?;
+
+    // Reconcile the token vault: debited exactly `amount`, still backing
+    // every note
+    if let Some(vault_token_account) = ctx.accounts.vault_token_account.as_mut() {
+        vault_token_account.reload()?;
+        ctx.accounts.asset_vault.reconcile_debit(
+            vault_balance,
+            vault_token_account.amount,
+            amount,
+        )?;
+    }
 
     // Update pool statistics
     ctx.accounts.pool_config.record_withdrawal(timestamp)?;
 
     // Update relayer statistics if registered
     if let Some(relayer_node) = ctx.accounts.relayer_node.as_mut() {
+        relayer_node.consume_drain_allowance()?;
         relayer_node.record_transaction(relayer_fee, timestamp)?;
     }
 
+    // Top up the nullifier rent from the pool's reserve, as an advance
+    let nullifier_rent = Rent::get()?.minimum_balance(SpentNullifierV2::LEN);
+    let rent_advanced = if let (Some(rent_reserve), Some(rent_advance)) = (
+        ctx.accounts.rent_reserve.as_mut(),
+        ctx.accounts.rent_advance.as_mut(),
+    ) {
+        top_up_relayer_rent(
+            rent_reserve,
+            rent_advance,
+            &ctx.accounts.relayer.to_account_info(),
+            nullifier_rent,
+            timestamp,
+        )?
+    } else {
+        0
+    };
+
+    // Rent a fee payer or the reserve already covered is not rebated again
+    let unreimbursed_rent = if ctx.accounts.fee_payer.is_some() {
+        0
+    } else {
+        nullifier_rent.saturating_sub(rent_advanced)
+    };
+
+    // Accrue gas rebate: unreimbursed nullifier rent + declared priority fee
+    // (up to the registry's maximum), capped by registry
+    if let Some(rebate_ledger) = ctx.accounts.rebate_ledger.as_mut() {
+        let relayer_node_key = ctx
+            .accounts
+            .relayer_node
+            .as_ref()
+            .map(|node| node.key())
+            .ok_or(error!(PrivacyErrorV2::RebateLedgerMismatch))?;
+        require_keys_eq!(
+            rebate_ledger.relayer_node,
+            relayer_node_key,
+            PrivacyErrorV2::RebateLedgerMismatch
+        );
+        rebate_ledger.accrue(
+            unreimbursed_rent,
+            ctx.accounts.relayer_registry.max_priority_fee_hint,
+            ctx.accounts.relayer_registry.max_gas_rebate_lamports,
+            timestamp,
+        )?;
+    }
+
     // =========================================================================
     // EMIT PRIVACY-PRESERVING EVENT
     // =========================================================================
@@ -391,14 +865,32 @@ pub fn handler(
     // for token delivery), omitting them from events makes large-scale
     // correlation significantly harder - events are the primary data source
     // for most indexing infrastructure.
-    emit!(WithdrawMaspEvent {
-        pool: ctx.accounts.pool_config.key(),
+    //
+    // With a deferred event queue the event is emitted later by the flush
+    // crank, decoupling its timing from the nullifier spend.
+    let event = DeferredWithdrawEvent {
         nullifier_hash,
         asset_id,
         relayer: ctx.accounts.relayer.key(),
         relayer_fee,
-        timestamp,
-    });
+        sequence: ctx.accounts.pool_config.next_event_sequence()?,
+        release_at: timestamp,
+    };
+    let immediate = match ctx.accounts.deferred_events.as_mut() {
+        Some(deferred_events) => deferred_events.defer(event, timestamp)?,
+        None => Some(event),
+    };
+    if let Some(event) = immediate {
+        emit!(WithdrawMaspEvent {
+            pool: ctx.accounts.pool_config.key(),
+            nullifier_hash: event.nullifier_hash,
+            asset_id: event.asset_id,
+            relayer: event.relayer,
+            relayer_fee: event.relayer_fee,
+            sequence: event.sequence,
+            timestamp,
+        });
+    }
 
     // Debug event - only emitted when event-debug feature is enabled
     // WARNING: MUST NOT be enabled in mainnet builds
@@ -423,12 +915,219 @@ pub fn handler(
         );
     }
 
+    Ok(VerificationStatus::withdrawal(
+        ProofType::Withdraw,
+        &ctx.accounts.merkle_tree,
+        merkle_root,
+        1,
+        amount,
+        relayer_fee,
+        slot,
+    ))
+}
+
+impl<'info> WithdrawMasp<'info> {
+    /// Whether every account needed to create the recipient's associated
+    /// token account was passed
+    fn can_create_recipient_ata(&self) -> bool {
+        self.recipient_ata.is_some()
+            && self.associated_token_program.is_some()
+            && self.mint.is_some()
+            && self.recipient_wallet.is_some()
+    }
+
+    /// The recipient's token account, creating its associated token account
+    /// (rent paid by the fee payer, or else the relayer) when no existing
+    /// account was given
+    fn recipient_token_destination(&self) -> Result<AccountInfo<'info>> {
+        if let Some(recipient_token_account) = self.recipient_token_account.as_ref() {
+            return Ok(recipient_token_account.to_account_info());
+        }
+        let (Some(ata), Some(ata_program), Some(mint), Some(recipient_wallet)) = (
+            self.recipient_ata.as_ref(),
+            self.associated_token_program.as_ref(),
+            self.mint.as_ref(),
+            self.recipient_wallet.as_ref(),
+        ) else {
+            return err!(PrivacyErrorV2::MissingAssetAccount);
+        };
+
+        // Idempotent: an existing account is checked and kept
+        associated_token::create_idempotent(CpiContext::new(
+            ata_program.to_account_info(),
+            associated_token::Create {
+                payer: self
+                    .fee_payer
+                    .as_ref()
+                    .unwrap_or(&self.relayer)
+                    .to_account_info(),
+                associated_token: ata.to_account_info(),
+                authority: recipient_wallet.to_account_info(),
+                mint: mint.to_account_info(),
+                system_program: self.system_program.to_account_info(),
+                token_program: self.token_program.to_account_info(),
+            },
+        ))?;
+        Ok(ata.to_account_info())
+    }
+}
+
+/// Pay the recipient and relayer (fee and any fee payer refund) in
+/// lamports from the native SOL vault
+fn pay_native(accounts: &WithdrawMasp, recipient_amount: u64, relayer_payout: u64) -> Result<()> {
+    let vault = accounts.asset_vault.to_account_info();
+    let recipient_wallet = accounts
+        .recipient_wallet
+        .as_ref()
+        .ok_or(error!(PrivacyErrorV2::MissingAssetAccount))?;
+
+    if recipient_amount > 0 {
+        AssetVault::pay_native(&vault, recipient_wallet, recipient_amount)?;
+    }
+    if relayer_payout > 0 {
+        AssetVault::pay_native(&vault, &accounts.relayer, relayer_payout)?;
+    }
+    Ok(())
+}
+
+/// Pay the recipient, relayer fee and protocol fee in tokens from the vault
+/// token account
+///
+/// Uses `transfer_checked` when the mint is given (always for Token-2022
+/// vaults, which reject plain transfers from fee mints).
+fn pay_tokens<'info>(
+    accounts: &WithdrawMasp<'info>,
+    asset_id: [u8; 32],
+    recipient_amount: u64,
+    relayer_fee: u64,
+    protocol_fee: u64,
+) -> Result<()> {
+    let (Some(vault_token_account), Some(relayer_token_account)) = (
+        accounts.vault_token_account.as_ref(),
+        accounts.relayer_token_account.as_ref(),
+    ) else {
+        return err!(PrivacyErrorV2::MissingAssetAccount);
+    };
+
+    // Create vault signer seeds for CPI
+    let pool_key = accounts.pool_config.key();
+    let vault_bump = accounts.asset_vault.bump;
+    let vault_seeds: &[&[u8]] = &[
+        AssetVault::SEED_PREFIX,
+        pool_key.as_ref(),
+        asset_id.as_ref(),
+        &[vault_bump],
+    ];
+
+    let vault_signer_seeds: &[&[&[u8]]] = &[vault_seeds];
+
+    let transfer = |to: AccountInfo<'info>, amount: u64| -> Result<()> {
+        let token_program = accounts.token_program.to_account_info();
+        let from = vault_token_account.to_account_info();
+        let authority = accounts.asset_vault.to_account_info();
+        match accounts.mint.as_ref() {
+            Some(mint) => token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    token_program,
+                    TransferChecked {
+                        from,
+                        mint: mint.to_account_info(),
+                        to,
+                        authority,
+                    },
+                    vault_signer_seeds,
+                ),
+                amount,
+                mint.decimals,
+            ),
+            None => token::transfer(
+                CpiContext::new_with_signer(
+                    token_program,
+                    Transfer {
+                        from,
+                        to,
+                        authority,
+                    },
+                    vault_signer_seeds,
+                ),
+                amount,
+            ),
+        }
+    };
+
+    // Transfer tokens to recipient
+    if recipient_amount > 0 {
+        transfer(accounts.recipient_token_destination()?, recipient_amount)?;
+    }
+
+    // Transfer fee to relayer
+    if relayer_fee > 0 {
+        transfer(relayer_token_account.to_account_info(), relayer_fee)?;
+    }
+
+    // Transfer protocol fee to the asset's treasury
+    if protocol_fee > 0 {
+        let treasury = accounts
+            .treasury_token_account
+            .as_ref()
+            .ok_or(error!(PrivacyErrorV2::TreasuryRequired))?;
+        transfer(treasury.to_account_info(), protocol_fee)?;
+    }
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::crypto::is_canonical_fr;
+
+    #[test]
+    fn test_fee_payer_refund_hash() {
+        // No refund keeps the plain withdrawal's public_data_hash
+        assert_eq!(fee_payer_refund_hash(0), [0u8; 32]);
+
+        let hash = fee_payer_refund_hash(5_000);
+        assert!(is_canonical_fr(&hash));
+        assert_ne!(hash, [0u8; 32]);
+        assert_ne!(hash, fee_payer_refund_hash(5_001));
+    }
+
+    #[test]
+    fn test_parameter_pin_hash() {
+        let hash = parameter_pin_hash(3);
+        assert!(is_canonical_fr(&hash));
+        assert_ne!(hash, parameter_pin_hash(4));
+        // Domain-separated from a refund of the same value
+        assert_ne!(hash, fee_payer_refund_hash(3));
+    }
+
+    #[test]
+    fn test_recipient_amount() {
+        assert_eq!(
+            recipient_amount(1_000_000, 5_000, 0, 3_000).unwrap(),
+            992_000
+        );
+        assert_eq!(
+            recipient_amount(1_000_000, 5_000, 10_000, 3_000).unwrap(),
+            982_000
+        );
+        // Every deduction may add up to the whole amount
+        assert_eq!(recipient_amount(100, 10, 60, 30).unwrap(), 0);
+
+        // The error names the deduction that overruns the amount
+        assert_eq!(
+            recipient_amount(100, 101, 0, 0).unwrap_err(),
+            PrivacyErrorV2::RelayerFeeExceedsAmount.into()
+        );
+        assert_eq!(
+            recipient_amount(100, 10, 91, 0).unwrap_err(),
+            PrivacyErrorV2::FeePayerRefundTooLarge.into()
+        );
+        assert_eq!(
+            recipient_amount(100, 10, 0, 91).unwrap_err(),
+            PrivacyErrorV2::FeesExceedAmount.into()
+        );
+    }
 
     #[test]
     fn test_relayer_fee_validation_small_amounts() {
@@ -471,9 +1170,4 @@ mod tests {
         // With checked_mul, this correctly detects overflow and returns None
         assert!(relayer_fee.checked_mul(10).is_none());
     }
-
-    #[test]
-    fn test_min_withdrawal_amount() {
-        assert_eq!(MIN_WITHDRAWAL_AMOUNT, 100);
-    }
 }
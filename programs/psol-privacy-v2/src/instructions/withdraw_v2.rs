@@ -9,22 +9,34 @@
 //! - Supports optional second nullifier (for 2-input join-split)
 //! - Outputs change commitment to pending buffer
 //! - Explicit schema versioning (schema_version = 2)
+//! - The pool's protocol fee, if any, goes to the asset's treasury out of
+//!   the recipient's share (see `admin::protocol_fee`)
+//! - An asset in a risk tier is held to the tier's withdrawal limits as in
+//!   `withdraw_masp`, except that there is no sealed path for amounts at or
+//!   above its delay threshold
 
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use solana_sdk_ids::sysvar::instructions as sysvar_instructions;
 
-use crate::crypto::WithdrawV2PublicInputs;
+use crate::crypto::{check_amount_range, RangeCheckInput, WithdrawV2PublicInputs};
 use crate::error::PrivacyErrorV2;
-use crate::events::WithdrawV2Event;
+use crate::events::{ProofVerificationFailed, WithdrawV2Event};
+use crate::instructions::relayer::rent_reserve::top_up_relayer_rent;
+use crate::instructions::verification_status::VerificationStatus;
+use crate::state::migration_window::is_withdraw_root_accepted;
 use crate::state::{
-    AssetVault, MerkleTreeV2, PendingDepositsBuffer, PoolConfigV2, RelayerNode, RelayerRegistry,
-    SpendType, SpentNullifierV2, VerificationKeyAccountV2, YieldRegistry,
+    AnonymityStats, AssetVault, AssetWithdrawalWindow, GlobalConfig, MerkleTreeV2, MigrationWindow,
+    PendingDepositsBuffer, PoolConfigV2, ProofStats, RelayerNode, RelayerRebateLedger,
+    RelayerRegistry, RentAdvance, RentReserve, RiskTierConfig, SpendContext, SpentNullifierV2,
+    VerificationKeyAccountV2, YieldRegistry,
+};
+use crate::utils::{
+    check_risk_tier_withdrawal, pool_clock, reimburse_relayer_rent, require_program_id,
+    spend_nullifier, PoolFlow,
 };
 use crate::ProofType;
 
-/// Minimum withdrawal amount to prevent dust attacks
-pub const MIN_WITHDRAWAL_AMOUNT: u64 = 100;
-
 /// Maximum relayer fee in basis points (10% = 1000 bps)
 pub const MAX_RELAYER_FEE_BPS: u64 = 1000;
 
@@ -55,10 +67,20 @@ pub struct WithdrawV2<'info> {
     )]
     pub pool_config: Box<Account<'info, PoolConfigV2>>,
 
-    /// Merkle tree account
+    /// Program-wide pause switch
+    /// CHECK: PDA; may not exist yet (see `GlobalConfig::is_paused_account`)
     #[account(
-        constraint = merkle_tree.is_known_root(&merkle_root) @ PrivacyErrorV2::InvalidMerkleRoot,
+        seeds = [GlobalConfig::SEED_PREFIX],
+        bump,
+        constraint = !GlobalConfig::is_paused_account(&global_config)
+            @ PrivacyErrorV2::GlobalPaused,
     )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// Merkle tree account
+    ///
+    /// `merkle_root` is checked in the handler against the live root set
+    /// and, if provided, the migration window.
     pub merkle_tree: Box<Account<'info, MerkleTreeV2>>,
 
     /// Verification key for withdraw v2 proofs
@@ -68,6 +90,8 @@ pub struct WithdrawV2<'info> {
         constraint = vk_account.is_initialized @ PrivacyErrorV2::VerificationKeyNotSet,
         constraint = vk_account.proof_type == ProofType::WithdrawV2 as u8
             @ PrivacyErrorV2::InvalidVerificationKeyType,
+        constraint = !pool_config.requires_vk_canary() || vk_account.canary_passed
+            @ PrivacyErrorV2::VkCanaryNotPassed,
     )]
     pub vk_account: Box<Account<'info, VerificationKeyAccountV2>>,
 
@@ -125,7 +149,9 @@ pub struct WithdrawV2<'info> {
 
     /// Secondary spent nullifier account (optional, for 2-input join-split)
     /// If nullifier_hash_1 is all zeros, this account should not be provided
-    pub spent_nullifier_1: Option<Account<'info, SpentNullifierV2>>,
+    /// CHECK: Seeds checked against `nullifier_hash_1` when it is created
+    #[account(mut)]
+    pub spent_nullifier_1: Option<UncheckedAccount<'info>>,
 
     /// Pending deposits buffer (for change commitment)
     #[account(
@@ -135,13 +161,16 @@ pub struct WithdrawV2<'info> {
             pool_config.key().as_ref(),
         ],
         bump,
+        constraint = PendingDepositsBuffer::has_current_layout(&pending_buffer.to_account_info())
+            @ PrivacyErrorV2::PendingBufferNeedsMigration,
     )]
-    pub pending_buffer: Box<Account<'info, PendingDepositsBuffer>>,
+    pub pending_buffer: AccountLoader<'info, PendingDepositsBuffer>,
 
     /// Relayer registry
     pub relayer_registry: Box<Account<'info, RelayerRegistry>>,
 
     /// Relayer node (optional, for registered relayers)
+    #[account(mut)]
     pub relayer_node: Option<Account<'info, RelayerNode>>,
 
     /// Optional: Yield registry (for yield asset enforcement)
@@ -152,6 +181,89 @@ pub struct WithdrawV2<'info> {
 
     /// System program
     pub system_program: Program<'info, System>,
+
+    /// CHECK: Address constrained to the instructions sysvar (same-transaction
+    /// deposit/withdraw guard)
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    /// Optional: per-proof-type verification counters
+    #[account(
+        mut,
+        seeds = [ProofStats::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = proof_stats.bump,
+    )]
+    pub proof_stats: Option<Account<'info, ProofStats>>,
+
+    /// Optional: gas rebate ledger for the submitting relayer
+    #[account(
+        mut,
+        constraint = rebate_ledger.operator == relayer.key() @ PrivacyErrorV2::RebateLedgerMismatch,
+    )]
+    pub rebate_ledger: Option<Account<'info, RelayerRebateLedger>>,
+
+    /// Optional: pre-migration roots accepted while a migration window is open
+    #[account(
+        seeds = [MigrationWindow::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = migration_window.bump,
+    )]
+    pub migration_window: Option<Box<Account<'info, MigrationWindow>>>,
+
+    /// Optional: pool rent reserve that tops up the nullifier rent
+    #[account(
+        mut,
+        seeds = [RentReserve::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = rent_reserve.bump,
+    )]
+    pub rent_reserve: Option<Box<Account<'info, RentReserve>>>,
+
+    /// Optional: the relayer's rent advance account (with `rent_reserve`)
+    #[account(
+        mut,
+        constraint = rent_advance.operator == relayer.key() @ PrivacyErrorV2::RentAdvanceMismatch,
+    )]
+    pub rent_advance: Option<Account<'info, RentAdvance>>,
+
+    /// Optional: per-asset deposit-to-withdrawal latency statistics
+    /// (required once the pool keeps them)
+    #[account(
+        mut,
+        seeds = [
+            AnonymityStats::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        bump = anonymity_stats.bump,
+    )]
+    pub anonymity_stats: Option<Box<Account<'info, AnonymityStats>>>,
+    /// Optional: wallet funding the nullifier rent in place of the relayer
+    #[account(mut)]
+    pub fee_payer: Option<Signer<'info>>,
+
+    /// Asset treasury collecting the protocol fee (required while the pool
+    /// charges one)
+    #[account(
+        mut,
+        seeds = [AssetVault::TREASURY_SEED_PREFIX, asset_vault.key().as_ref()],
+        bump,
+    )]
+    pub treasury_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// Pool's risk tier table; required once the asset is in a tier
+    #[account(
+        seeds = [RiskTierConfig::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = risk_tiers.bump,
+    )]
+    pub risk_tiers: Option<Box<Account<'info, RiskTierConfig>>>,
+
+    /// Asset's withdrawal window; required while its tier rate-limits
+    /// withdrawals
+    #[account(
+        mut,
+        seeds = [AssetWithdrawalWindow::SEED_PREFIX, asset_vault.key().as_ref()],
+        bump = withdrawal_window.bump,
+    )]
+    pub withdrawal_window: Option<Box<Account<'info, AssetWithdrawalWindow>>>,
 }
 
 /// Handler for withdraw_v2 instruction
@@ -167,20 +279,33 @@ pub fn handler(
     recipient: Pubkey,
     amount: u64,
     relayer_fee: u64,
-) -> Result<()> {
+) -> Result<VerificationStatus> {
     // =========================================================================
     // INPUT VALIDATION (fail fast before any state changes)
     // =========================================================================
 
-    // Validate proof data length (Groth16: 2*G1 + 1*G2 = 256 bytes)
-    require!(proof_data.len() == 256, PrivacyErrorV2::InvalidProofFormat);
-
-    // Validate amount is above minimum
+    // Validate proof data length (Groth16: 2*G1 + 1*G2 = 256 bytes, optional format tag)
     require!(
-        amount >= MIN_WITHDRAWAL_AMOUNT,
-        PrivacyErrorV2::InvalidAmount
+        crate::crypto::is_valid_proof_length(&proof_data),
+        PrivacyErrorV2::InvalidProofFormat
     );
 
+    // Validate amount is above the asset's minimum (prevents dust attacks)
+    ctx.accounts.asset_vault.require_min_withdrawal(amount)?;
+
+    // Amount range-check hook (no-op until the pool enables a range proof scheme)
+    check_amount_range(
+        ctx.accounts.pool_config.range_proof_scheme()?,
+        None,
+        &RangeCheckInput::new(asset_id, amount),
+    )?;
+
+    ctx.accounts.pool_config.require_no_same_tx_flow(
+        &ctx.accounts.pool_config.key(),
+        &ctx.accounts.instructions_sysvar,
+        PoolFlow::Withdraw,
+    )?;
+
     // Validate primary nullifier is not zero
     require!(
         !nullifier_hash_0.iter().all(|&b| b == 0),
@@ -211,6 +336,12 @@ pub fn handler(
         );
     }
 
+    // A funded fee payer leaves no rent for the reserve to advance
+    require!(
+        ctx.accounts.fee_payer.is_none() || ctx.accounts.rent_advance.is_none(),
+        PrivacyErrorV2::RentAdvanceWithFeePayer
+    );
+
     // Validate merkle root is not zero
     require!(
         !merkle_root.iter().all(|&b| b == 0),
@@ -232,6 +363,9 @@ pub fn handler(
         PrivacyErrorV2::RelayerFeeOutOfRange
     );
 
+    // Validate fee against the asset's absolute bounds (token base units)
+    ctx.accounts.asset_vault.validate_relayer_fee(relayer_fee)?;
+
     // Validate asset ID matches
     require!(
         asset_id == ctx.accounts.asset_vault.asset_id,
@@ -263,6 +397,7 @@ pub fn handler(
 
     // Validate relayer if registered
     if let Some(ref relayer_node) = ctx.accounts.relayer_node {
+        require_program_id(ctx.program_id)?;
         let relayer_node_key = relayer_node.key();
         relayer_node.validate_registry_and_pda(
             ctx.program_id,
@@ -271,6 +406,10 @@ pub fn handler(
         )?;
 
         require!(relayer_node.is_active, PrivacyErrorV2::RelayerNotActive);
+        require!(
+            relayer_node.accepts_withdrawals(),
+            PrivacyErrorV2::RelayerDraining
+        );
         require!(
             relayer_node.operator == ctx.accounts.relayer.key(),
             PrivacyErrorV2::Unauthorized
@@ -283,11 +422,33 @@ pub fn handler(
         );
     }
 
-    let clock = Clock::get()?;
+    let clock = pool_clock(&ctx.accounts.pool_config)?;
     let timestamp = clock.unix_timestamp;
     let slot = clock.slot;
 
-    require!(timestamp > 0, PrivacyErrorV2::InvalidTimestamp);
+    // Not a sealed withdrawal, so nothing at or above the asset's risk tier
+    // delay threshold leaves this way
+    check_risk_tier_withdrawal(
+        &ctx.accounts.asset_vault,
+        ctx.accounts.risk_tiers.as_deref().map(|t| &**t),
+        ctx.accounts
+            .withdrawal_window
+            .as_deref_mut()
+            .map(|w| &mut **w),
+        amount,
+        None,
+        timestamp,
+    )?;
+
+    require!(
+        is_withdraw_root_accepted(
+            &ctx.accounts.merkle_tree,
+            ctx.accounts.migration_window.as_deref().map(|w| &**w),
+            &merkle_root,
+            timestamp,
+        ),
+        PrivacyErrorV2::InvalidMerkleRoot
+    );
 
     // =========================================================================
     // PROOF VERIFICATION (before any state changes)
@@ -321,7 +482,24 @@ pub fn handler(
         &field_elements,
     )?;
 
-    require!(is_valid, PrivacyErrorV2::InvalidProof);
+    if !is_valid {
+        emit!(ProofVerificationFailed {
+            pool: ctx.accounts.pool_config.key(),
+            proof_type: ProofType::WithdrawV2 as u8,
+            submitter: ctx.accounts.relayer.key(),
+            timestamp,
+        });
+        return err!(PrivacyErrorV2::InvalidProof);
+    }
+    if let Some(proof_stats) = ctx.accounts.proof_stats.as_mut() {
+        proof_stats.record_verified(ProofType::WithdrawV2, timestamp)?;
+    }
+    ctx.accounts
+        .pool_config
+        .require_anonymity_stats(ctx.accounts.anonymity_stats.is_some())?;
+    if let Some(anonymity_stats) = ctx.accounts.anonymity_stats.as_mut() {
+        anonymity_stats.record_withdrawal(timestamp)?;
+    }
 
     // =========================================================================
     // STATE CHANGES (only after proof verification succeeds)
@@ -332,26 +510,42 @@ pub fn handler(
         ctx.accounts.pool_config.key(),
         nullifier_hash_0,
         asset_id,
-        SpendType::Withdraw,
+        SpendContext::WithdrawV2,
         timestamp,
         slot,
         ctx.accounts.relayer.key(),
         ctx.bumps.spent_nullifier_0,
     );
 
+    // `init` charged the relayer the nullifier rent: the fee payer covers it
+    if let Some(fee_payer) = ctx.accounts.fee_payer.as_ref() {
+        reimburse_relayer_rent(
+            fee_payer,
+            &ctx.accounts.relayer,
+            &ctx.accounts.system_program,
+            Rent::get()?.minimum_balance(SpentNullifierV2::LEN),
+        )?;
+    }
+
     // Mark secondary nullifier as spent if provided
     if has_second_nullifier {
-        if let Some(ref mut spent_null_1) = ctx.accounts.spent_nullifier_1 {
-            spent_null_1.initialize(
+        if let Some(spent_null_1) = ctx.accounts.spent_nullifier_1.as_ref() {
+            require_program_id(ctx.program_id)?;
+            spend_nullifier(
+                ctx.accounts
+                    .fee_payer
+                    .as_ref()
+                    .unwrap_or(&ctx.accounts.relayer),
+                ctx.accounts.relayer.key(),
+                &ctx.accounts.system_program,
+                &spent_null_1.to_account_info(),
                 ctx.accounts.pool_config.key(),
                 nullifier_hash_1,
                 asset_id,
-                SpendType::Withdraw,
+                SpendContext::WithdrawV2,
                 timestamp,
                 slot,
-                ctx.accounts.relayer.key(),
-                0, // bump not available for optional accounts
-            );
+            )?;
         }
     }
 
@@ -359,11 +553,14 @@ pub fn handler(
 
     ctx.accounts
         .pending_buffer
+        .load_mut()?
         .add_pending(change_commitment, timestamp)?;
 
-    // Calculate recipient amount after relayer fee
+    // Calculate recipient amount after relayer fee and protocol fee
+    let protocol_fee = ctx.accounts.pool_config.protocol_fee_for(amount);
     let recipient_amount = amount
         .checked_sub(relayer_fee)
+        .and_then(|rest| rest.checked_sub(protocol_fee))
         .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
 
     // Create vault signer seeds for CPI
@@ -401,10 +598,77 @@ pub fn handler(
         token::transfer(cpi_ctx, relayer_fee)?;
     }
 
+    // Transfer protocol fee to the asset's treasury
+    if protocol_fee > 0 {
+        let treasury = ctx
+            .accounts
+            .treasury_token_account
+            .as_ref()
+            .ok_or(error!(PrivacyErrorV2::TreasuryRequired))?;
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault_token_account.to_account_info(),
+            to: treasury.to_account_info(),
+            authority: ctx.accounts.asset_vault.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        token::transfer(cpi_ctx, protocol_fee)?;
+    }
+
     // Update statistics
     ctx.accounts.pool_config.total_withdrawals += 1;
+    if let Some(relayer_node) = ctx.accounts.relayer_node.as_mut() {
+        relayer_node.consume_drain_allowance()?;
+    }
+
+    // Top up the nullifier rent from the pool's reserve, as an advance
+    let nullifier_rent = Rent::get()?.minimum_balance(SpentNullifierV2::LEN);
+    let rent_advanced = if let (Some(rent_reserve), Some(rent_advance)) = (
+        ctx.accounts.rent_reserve.as_mut(),
+        ctx.accounts.rent_advance.as_mut(),
+    ) {
+        top_up_relayer_rent(
+            rent_reserve,
+            rent_advance,
+            &ctx.accounts.relayer.to_account_info(),
+            nullifier_rent,
+            timestamp,
+        )?
+    } else {
+        0
+    };
+
+    // Rent a fee payer or the reserve already covered is not rebated again
+    let unreimbursed_rent = if ctx.accounts.fee_payer.is_some() {
+        0
+    } else {
+        nullifier_rent.saturating_sub(rent_advanced)
+    };
+
+    // Accrue gas rebate: unreimbursed nullifier rent + declared priority fee
+    // (up to the registry's maximum), capped by registry
+    if let Some(rebate_ledger) = ctx.accounts.rebate_ledger.as_mut() {
+        let relayer_node_key = ctx
+            .accounts
+            .relayer_node
+            .as_ref()
+            .map(|node| node.key())
+            .ok_or(error!(PrivacyErrorV2::RebateLedgerMismatch))?;
+        require_keys_eq!(
+            rebate_ledger.relayer_node,
+            relayer_node_key,
+            PrivacyErrorV2::RebateLedgerMismatch
+        );
+        rebate_ledger.accrue(
+            unreimbursed_rent,
+            ctx.accounts.relayer_registry.max_priority_fee_hint,
+            ctx.accounts.relayer_registry.max_gas_rebate_lamports,
+            timestamp,
+        )?;
+    }
 
     // Emit event
+    let sequence = ctx.accounts.pool_config.next_event_sequence()?;
     emit!(WithdrawV2Event {
         pool: ctx.accounts.pool_config.key(),
         asset_id,
@@ -412,9 +676,18 @@ pub fn handler(
         nullifier_hash_1,
         change_commitment,
         merkle_root,
+        sequence,
         timestamp,
         slot,
     });
 
-    Ok(())
+    Ok(VerificationStatus::withdrawal(
+        ProofType::WithdrawV2,
+        &ctx.accounts.merkle_tree,
+        merkle_root,
+        1 + has_second_nullifier as u8,
+        amount,
+        relayer_fee,
+        slot,
+    ))
 }
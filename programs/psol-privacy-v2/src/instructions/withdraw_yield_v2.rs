@@ -5,19 +5,24 @@
 
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use solana_sdk_ids::sysvar::instructions as sysvar_instructions;
 
-use crate::crypto::WithdrawV2PublicInputs;
+use crate::crypto::{check_amount_range, RangeCheckInput, WithdrawV2PublicInputs};
 use crate::error::PrivacyErrorV2;
-use crate::events::WithdrawV2Event;
+use crate::events::{ProofVerificationFailed, WithdrawV2Event};
+use crate::instructions::verification_status::VerificationStatus;
+use crate::state::migration_window::is_withdraw_root_accepted;
 use crate::state::{
-    AssetVault, MerkleTreeV2, PendingDepositsBuffer, PoolConfigV2, RelayerNode, RelayerRegistry,
-    SpendType, SpentNullifierV2, VerificationKeyAccountV2, YieldRegistry,
+    AnonymityStats, AssetVault, AssetWithdrawalWindow, GlobalConfig, MerkleTreeV2, MigrationWindow,
+    PendingDepositsBuffer, PoolConfigV2, ProofStats, RelayerNode, RelayerRegistry, RiskTierConfig,
+    SpendContext, SpentNullifierV2, VerificationKeyAccountV2, YieldRegistry,
+};
+use crate::utils::{
+    check_risk_tier_withdrawal, pool_clock, reimburse_relayer_rent, require_program_id,
+    spend_nullifier, PoolFlow,
 };
 use crate::ProofType;
 
-/// Minimum withdrawal amount to prevent dust attacks
-pub const MIN_WITHDRAWAL_AMOUNT: u64 = 100;
-
 /// Accounts for Yield Mode withdrawal (relayer-gated)
 #[derive(Accounts)]
 #[instruction(
@@ -50,10 +55,20 @@ pub struct WithdrawYieldV2<'info> {
     )]
     pub pool_config: Box<Account<'info, PoolConfigV2>>,
 
-    /// Merkle tree account
+    /// Program-wide pause switch
+    /// CHECK: PDA; may not exist yet (see `GlobalConfig::is_paused_account`)
     #[account(
-        constraint = merkle_tree.is_known_root(&merkle_root) @ PrivacyErrorV2::InvalidMerkleRoot,
+        seeds = [GlobalConfig::SEED_PREFIX],
+        bump,
+        constraint = !GlobalConfig::is_paused_account(&global_config)
+            @ PrivacyErrorV2::GlobalPaused,
     )]
+    pub global_config: UncheckedAccount<'info>,
+
+    /// Merkle tree account
+    ///
+    /// `merkle_root` is checked in the handler against the live root set
+    /// and, if provided, the migration window.
     pub merkle_tree: Box<Account<'info, MerkleTreeV2>>,
 
     /// Verification key for withdraw v2 proofs
@@ -63,6 +78,8 @@ pub struct WithdrawYieldV2<'info> {
         constraint = vk_account.is_initialized @ PrivacyErrorV2::VerificationKeyNotSet,
         constraint = vk_account.proof_type == ProofType::WithdrawV2 as u8
             @ PrivacyErrorV2::InvalidVerificationKeyType,
+        constraint = !pool_config.requires_vk_canary() || vk_account.canary_passed
+            @ PrivacyErrorV2::VkCanaryNotPassed,
     )]
     pub vk_account: Box<Account<'info, VerificationKeyAccountV2>>,
 
@@ -119,7 +136,9 @@ pub struct WithdrawYieldV2<'info> {
     pub spent_nullifier_0: Account<'info, SpentNullifierV2>,
 
     /// Secondary spent nullifier account (optional, for 2-input join-split)
-    pub spent_nullifier_1: Option<Account<'info, SpentNullifierV2>>,
+    /// CHECK: Seeds checked against `nullifier_hash_1` when it is created
+    #[account(mut)]
+    pub spent_nullifier_1: Option<UncheckedAccount<'info>>,
 
     /// Pending deposits buffer (for change commitment)
     #[account(
@@ -129,8 +148,10 @@ pub struct WithdrawYieldV2<'info> {
             pool_config.key().as_ref(),
         ],
         bump,
+        constraint = PendingDepositsBuffer::has_current_layout(&pending_buffer.to_account_info())
+            @ PrivacyErrorV2::PendingBufferNeedsMigration,
     )]
-    pub pending_buffer: Box<Account<'info, PendingDepositsBuffer>>,
+    pub pending_buffer: AccountLoader<'info, PendingDepositsBuffer>,
 
     /// Relayer registry
     pub relayer_registry: Box<Account<'info, RelayerRegistry>>,
@@ -150,6 +171,58 @@ pub struct WithdrawYieldV2<'info> {
 
     /// System program
     pub system_program: Program<'info, System>,
+
+    /// CHECK: Address constrained to the instructions sysvar (same-transaction
+    /// deposit/withdraw guard)
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    /// Optional: per-proof-type verification counters
+    #[account(
+        mut,
+        seeds = [ProofStats::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = proof_stats.bump,
+    )]
+    pub proof_stats: Option<Account<'info, ProofStats>>,
+
+    /// Optional: pre-migration roots accepted while a migration window is open
+    #[account(
+        seeds = [MigrationWindow::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = migration_window.bump,
+    )]
+    pub migration_window: Option<Box<Account<'info, MigrationWindow>>>,
+
+    /// Optional: per-asset deposit-to-withdrawal latency statistics
+    /// (required once the pool keeps them)
+    #[account(
+        mut,
+        seeds = [
+            AnonymityStats::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            asset_id.as_ref(),
+        ],
+        bump = anonymity_stats.bump,
+    )]
+    pub anonymity_stats: Option<Box<Account<'info, AnonymityStats>>>,
+    /// Optional: wallet funding the nullifier rent in place of the relayer
+    #[account(mut)]
+    pub fee_payer: Option<Signer<'info>>,
+
+    /// Pool's risk tier table; required once the asset is in a tier
+    #[account(
+        seeds = [RiskTierConfig::SEED_PREFIX, pool_config.key().as_ref()],
+        bump = risk_tiers.bump,
+    )]
+    pub risk_tiers: Option<Box<Account<'info, RiskTierConfig>>>,
+
+    /// Asset's withdrawal window; required while its tier rate-limits
+    /// withdrawals
+    #[account(
+        mut,
+        seeds = [AssetWithdrawalWindow::SEED_PREFIX, asset_vault.key().as_ref()],
+        bump = withdrawal_window.bump,
+    )]
+    pub withdrawal_window: Option<Box<Account<'info, AssetWithdrawalWindow>>>,
 }
 
 /// Handler for withdraw_yield_v2 instruction
@@ -170,7 +243,7 @@ pub fn handler(
     recipient: Pubkey,
     amount: u64,
     relayer_fee: u64,
-) -> Result<()> {
+) -> Result<VerificationStatus> {
     // NOTE: Relayer signer check is already enforced by Signer<'info> and constraint
     // NOTE: yield_relayer match is already enforced by pool_config constraint
 
@@ -178,15 +251,28 @@ pub fn handler(
     // INPUT VALIDATION (fail fast before any state changes)
     // =========================================================================
 
-    // Validate proof data length (Groth16: 2*G1 + 1*G2 = 256 bytes)
-    require!(proof_data.len() == 256, PrivacyErrorV2::InvalidProofFormat);
-
-    // Validate amount is above minimum
+    // Validate proof data length (Groth16: 2*G1 + 1*G2 = 256 bytes, optional format tag)
     require!(
-        amount >= MIN_WITHDRAWAL_AMOUNT,
-        PrivacyErrorV2::InvalidAmount
+        crate::crypto::is_valid_proof_length(&proof_data),
+        PrivacyErrorV2::InvalidProofFormat
     );
 
+    // Validate amount is above the asset's minimum (prevents dust attacks)
+    ctx.accounts.asset_vault.require_min_withdrawal(amount)?;
+
+    // Amount range-check hook (no-op until the pool enables a range proof scheme)
+    check_amount_range(
+        ctx.accounts.pool_config.range_proof_scheme()?,
+        None,
+        &RangeCheckInput::new(asset_id, amount),
+    )?;
+
+    ctx.accounts.pool_config.require_no_same_tx_flow(
+        &ctx.accounts.pool_config.key(),
+        &ctx.accounts.instructions_sysvar,
+        PoolFlow::Withdraw,
+    )?;
+
     // Validate primary nullifier is not zero
     require!(
         !nullifier_hash_0.iter().all(|&b| b == 0),
@@ -245,11 +331,33 @@ pub fn handler(
         PrivacyErrorV2::InsufficientBalance
     );
 
-    let clock = Clock::get()?;
+    let clock = pool_clock(&ctx.accounts.pool_config)?;
     let timestamp = clock.unix_timestamp;
     let slot = clock.slot;
 
-    require!(timestamp > 0, PrivacyErrorV2::InvalidTimestamp);
+    // Not a sealed withdrawal, so nothing at or above the asset's risk tier
+    // delay threshold leaves this way
+    check_risk_tier_withdrawal(
+        &ctx.accounts.asset_vault,
+        ctx.accounts.risk_tiers.as_deref().map(|t| &**t),
+        ctx.accounts
+            .withdrawal_window
+            .as_deref_mut()
+            .map(|w| &mut **w),
+        amount,
+        None,
+        timestamp,
+    )?;
+
+    require!(
+        is_withdraw_root_accepted(
+            &ctx.accounts.merkle_tree,
+            ctx.accounts.migration_window.as_deref().map(|w| &**w),
+            &merkle_root,
+            timestamp,
+        ),
+        PrivacyErrorV2::InvalidMerkleRoot
+    );
 
     // =========================================================================
     // PROOF VERIFICATION (before any state changes)
@@ -283,7 +391,24 @@ pub fn handler(
         &field_elements,
     )?;
 
-    require!(is_valid, PrivacyErrorV2::InvalidProof);
+    if !is_valid {
+        emit!(ProofVerificationFailed {
+            pool: ctx.accounts.pool_config.key(),
+            proof_type: ProofType::WithdrawV2 as u8,
+            submitter: ctx.accounts.relayer.key(),
+            timestamp,
+        });
+        return err!(PrivacyErrorV2::InvalidProof);
+    }
+    if let Some(proof_stats) = ctx.accounts.proof_stats.as_mut() {
+        proof_stats.record_verified(ProofType::WithdrawV2, timestamp)?;
+    }
+    ctx.accounts
+        .pool_config
+        .require_anonymity_stats(ctx.accounts.anonymity_stats.is_some())?;
+    if let Some(anonymity_stats) = ctx.accounts.anonymity_stats.as_mut() {
+        anonymity_stats.record_withdrawal(timestamp)?;
+    }
 
     // =========================================================================
     // STATE CHANGES (only after proof verification succeeds)
@@ -294,32 +419,49 @@ pub fn handler(
         ctx.accounts.pool_config.key(),
         nullifier_hash_0,
         asset_id,
-        SpendType::Withdraw,
+        SpendContext::WithdrawYield,
         timestamp,
         slot,
         ctx.accounts.relayer.key(),
         ctx.bumps.spent_nullifier_0,
     );
 
+    // `init` charged the relayer the nullifier rent: the fee payer covers it
+    if let Some(fee_payer) = ctx.accounts.fee_payer.as_ref() {
+        reimburse_relayer_rent(
+            fee_payer,
+            &ctx.accounts.relayer,
+            &ctx.accounts.system_program,
+            Rent::get()?.minimum_balance(SpentNullifierV2::LEN),
+        )?;
+    }
+
     // Mark secondary nullifier as spent if provided
     if has_second_nullifier {
-        if let Some(ref mut spent_null_1) = ctx.accounts.spent_nullifier_1 {
-            spent_null_1.initialize(
+        if let Some(spent_null_1) = ctx.accounts.spent_nullifier_1.as_ref() {
+            require_program_id(ctx.program_id)?;
+            spend_nullifier(
+                ctx.accounts
+                    .fee_payer
+                    .as_ref()
+                    .unwrap_or(&ctx.accounts.relayer),
+                ctx.accounts.relayer.key(),
+                &ctx.accounts.system_program,
+                &spent_null_1.to_account_info(),
                 ctx.accounts.pool_config.key(),
                 nullifier_hash_1,
                 asset_id,
-                SpendType::Withdraw,
+                SpendContext::WithdrawYield,
                 timestamp,
                 slot,
-                ctx.accounts.relayer.key(),
-                0, // bump not available for optional accounts
-            );
+            )?;
         }
     }
 
     // Add change commitment to pending buffer
     ctx.accounts
         .pending_buffer
+        .load_mut()?
         .add_pending(change_commitment, timestamp)?;
 
     // Calculate recipient amount after relayer fee
@@ -366,6 +508,7 @@ pub fn handler(
     ctx.accounts.pool_config.total_withdrawals += 1;
 
     // Emit event
+    let sequence = ctx.accounts.pool_config.next_event_sequence()?;
     emit!(WithdrawV2Event {
         pool: ctx.accounts.pool_config.key(),
         asset_id,
@@ -373,9 +516,18 @@ pub fn handler(
         nullifier_hash_1,
         change_commitment,
         merkle_root,
+        sequence,
         timestamp,
         slot,
     });
 
-    Ok(())
+    Ok(VerificationStatus::withdrawal(
+        ProofType::WithdrawV2,
+        &ctx.accounts.merkle_tree,
+        merkle_root,
+        1 + has_second_nullifier as u8,
+        amount,
+        relayer_fee,
+        slot,
+    ))
 }
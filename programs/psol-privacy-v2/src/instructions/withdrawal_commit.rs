@@ -0,0 +1,110 @@
+//! Withdrawal Commit Instructions - pSOL v2
+//!
+//! Split submission of a withdrawal: the user seals its public inputs in a
+//! `WithdrawalCommit` (`commit_withdrawal`) before any relayer sees them,
+//! then hands the proof to a relayer, which executes it with `withdraw_masp`
+//! and the commit attached (see `state::withdrawal_commit`). A relayer
+//! holding the proof can neither redirect the withdrawal nor change its
+//! fee; it can only submit it or not, and another relayer can be given a
+//! fresh proof against the same commit.
+//!
+//! The commit is best sent from a fresh wallet: it is a transaction of its
+//! own and its signer is recorded.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::events::{WithdrawalCommitCancelled, WithdrawalCommitted};
+use crate::state::{PoolConfigV2, WithdrawalCommit};
+use crate::utils::pool_clock;
+
+/// Accounts for sealing a withdrawal
+#[derive(Accounts)]
+#[instruction(commit_hash: [u8; 32])]
+pub struct CommitWithdrawal<'info> {
+    /// Wallet sealing the withdrawal (pays the commit rent)
+    #[account(mut)]
+    pub committer: Signer<'info>,
+
+    /// Pool configuration account
+    #[account(
+        constraint = !pool_config.is_paused @ PrivacyErrorV2::PoolPaused,
+    )]
+    pub pool_config: Account<'info, PoolConfigV2>,
+
+    /// Sealed withdrawal (PDA, created here)
+    #[account(
+        init,
+        payer = committer,
+        space = WithdrawalCommit::LEN,
+        seeds = [
+            WithdrawalCommit::SEED_PREFIX,
+            pool_config.key().as_ref(),
+            commit_hash.as_ref(),
+        ],
+        bump,
+    )]
+    pub withdrawal_commit: Account<'info, WithdrawalCommit>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+/// Handler for commit_withdrawal instruction
+pub fn commit_handler(ctx: Context<CommitWithdrawal>, commit_hash: [u8; 32]) -> Result<()> {
+    require!(
+        commit_hash != [0u8; 32],
+        PrivacyErrorV2::WithdrawalCommitMismatch
+    );
+
+    let timestamp = pool_clock(&ctx.accounts.pool_config)?.unix_timestamp;
+    ctx.accounts.withdrawal_commit.initialize(
+        ctx.accounts.pool_config.key(),
+        ctx.accounts.committer.key(),
+        commit_hash,
+        timestamp,
+        ctx.bumps.withdrawal_commit,
+    );
+
+    emit!(WithdrawalCommitted {
+        pool: ctx.accounts.pool_config.key(),
+        withdrawal_commit: ctx.accounts.withdrawal_commit.key(),
+        commit_hash,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+/// Accounts for cancelling a sealed withdrawal
+#[derive(Accounts)]
+pub struct CancelWithdrawalCommit<'info> {
+    /// Wallet that sealed the withdrawal (receives the rent back)
+    #[account(mut)]
+    pub committer: Signer<'info>,
+
+    /// Sealed withdrawal, closed
+    #[account(
+        mut,
+        close = committer,
+        has_one = committer @ PrivacyErrorV2::Unauthorized,
+        seeds = [
+            WithdrawalCommit::SEED_PREFIX,
+            withdrawal_commit.pool.as_ref(),
+            withdrawal_commit.commit_hash.as_ref(),
+        ],
+        bump = withdrawal_commit.bump,
+    )]
+    pub withdrawal_commit: Account<'info, WithdrawalCommit>,
+}
+
+/// Handler for cancel_withdrawal_commit instruction
+pub fn cancel_handler(ctx: Context<CancelWithdrawalCommit>) -> Result<()> {
+    emit!(WithdrawalCommitCancelled {
+        pool: ctx.accounts.withdrawal_commit.pool,
+        withdrawal_commit: ctx.accounts.withdrawal_commit.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
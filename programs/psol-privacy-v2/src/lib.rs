@@ -15,44 +15,182 @@ compile_error!("insecure-dev cannot be enabled in release builds - this would de
 #[cfg(all(feature = "event-debug", not(debug_assertions)))]
 compile_error!("event-debug cannot be enabled in release builds - it leaks privacy-sensitive data");
 
+pub mod compute_budget;
 pub mod crypto;
+#[cfg(not(target_os = "solana"))]
+pub mod decode;
 pub mod error;
 pub mod events;
 pub mod instructions;
+#[cfg(not(target_os = "solana"))]
+pub mod recover;
+#[cfg(not(target_os = "solana"))]
+pub mod scanner;
+#[cfg(not(target_os = "solana"))]
+pub mod simulate;
 pub mod state;
+#[cfg(not(target_os = "solana"))]
+pub mod stealth;
+pub mod tx_limits;
 pub mod utils;
+#[cfg(not(target_os = "solana"))]
+pub mod witness;
 
 pub use instructions::*;
 
+#[cfg(any(
+    all(feature = "mainnet", feature = "devnet"),
+    all(feature = "mainnet", feature = "localnet"),
+    all(feature = "devnet", feature = "localnet"),
+))]
+compile_error!("enable at most one of the mainnet, devnet and localnet features");
+
+// Program id per cluster; keep in sync with Anchor.toml [programs.*]
+#[cfg(feature = "mainnet")]
+declare_id!("FDPjNQoebUxxZkGFWXpn7jCqj7MFmgPN6acu5R2ec1dQ");
+
+#[cfg(feature = "devnet")]
 declare_id!("BmtMrkgvVML9Gk7Bt6JRqweHAwW69oFTohaBRaLbgqpb");
 
+#[cfg(not(any(feature = "mainnet", feature = "devnet")))]
+declare_id!("Dwk5gPdCnA6HpqZM7cbEbzziLk9pydtH4ygyZktWtyLP");
+
 pub(crate) use crate::instructions::admin::authority_v2::__client_accounts_accept_authority_transfer_v2;
 pub(crate) use crate::instructions::admin::authority_v2::__client_accounts_cancel_authority_transfer_v2;
 pub(crate) use crate::instructions::admin::authority_v2::__client_accounts_initiate_authority_transfer_v2;
+pub(crate) use crate::instructions::admin::capacity_thresholds::__client_accounts_set_tree_capacity_thresholds;
+pub(crate) use crate::instructions::admin::clear_pending::__client_accounts_clear_pending_buffer;
+pub(crate) use crate::instructions::admin::crank_bounty::__client_accounts_set_crank_bounty;
+pub(crate) use crate::instructions::admin::disclosed_path::__client_accounts_set_disclosed_path_withdrawals;
+pub(crate) use crate::instructions::admin::global_pause::__client_accounts_initialize_global_config;
+pub(crate) use crate::instructions::admin::global_pause::__client_accounts_pause_all_pools;
+pub(crate) use crate::instructions::admin::global_pause::__client_accounts_set_pool_creation_fee;
+pub(crate) use crate::instructions::admin::global_pause::__client_accounts_set_super_guardian;
+pub(crate) use crate::instructions::admin::global_pause::__client_accounts_unpause_all_pools;
+pub(crate) use crate::instructions::admin::migrate_asset_vault::__client_accounts_migrate_asset_vault;
+pub(crate) use crate::instructions::admin::migrate_merkle_tree::__client_accounts_migrate_merkle_tree;
+pub(crate) use crate::instructions::admin::migrate_pending_buffer::__client_accounts_migrate_pending_buffer;
+pub(crate) use crate::instructions::admin::migrate_pool_config::__client_accounts_migrate_pool_config;
+pub(crate) use crate::instructions::admin::migrate_relayer_node::__client_accounts_migrate_relayer_node;
+pub(crate) use crate::instructions::admin::migrate_relayer_registry::__client_accounts_migrate_relayer_registry;
+pub(crate) use crate::instructions::admin::migrate_verification_key::__client_accounts_migrate_verification_key;
+pub(crate) use crate::instructions::admin::migration_window::__client_accounts_close_migration_window;
+pub(crate) use crate::instructions::admin::migration_window::__client_accounts_open_migration_window;
+pub(crate) use crate::instructions::admin::min_withdrawal::__client_accounts_set_asset_min_withdrawal;
+pub(crate) use crate::instructions::admin::note_size_limit::__client_accounts_set_max_encrypted_note_len;
+pub(crate) use crate::instructions::admin::parameter_snapshot::__client_accounts_init_parameter_snapshot;
+pub(crate) use crate::instructions::admin::parameter_snapshot::__client_accounts_publish_parameter_snapshot;
 pub(crate) use crate::instructions::admin::pause_v2::__client_accounts_pause_pool_v2;
+pub(crate) use crate::instructions::admin::privacy_strict::__client_accounts_set_privacy_strict;
+pub(crate) use crate::instructions::admin::protocol_fee::__client_accounts_init_treasury;
+pub(crate) use crate::instructions::admin::protocol_fee::__client_accounts_set_protocol_fee;
+pub(crate) use crate::instructions::admin::reset_merkle::__client_accounts_reset_merkle_tree;
+pub(crate) use crate::instructions::admin::risk_tier::__client_accounts_init_asset_withdrawal_window;
+pub(crate) use crate::instructions::admin::risk_tier::__client_accounts_init_risk_tiers;
+pub(crate) use crate::instructions::admin::risk_tier::__client_accounts_set_asset_risk_tier;
+pub(crate) use crate::instructions::admin::risk_tier::__client_accounts_set_risk_tier_limits;
+pub(crate) use crate::instructions::admin::root_events::__client_accounts_set_root_events;
+pub(crate) use crate::instructions::admin::same_tx_flows::__client_accounts_set_same_tx_deposit_withdraw;
+pub(crate) use crate::instructions::admin::sweep_surplus::__client_accounts_cancel_surplus_sweep;
+pub(crate) use crate::instructions::admin::sweep_surplus::__client_accounts_request_surplus_sweep;
+pub(crate) use crate::instructions::admin::sweep_surplus::__client_accounts_sweep_surplus;
+pub(crate) use crate::instructions::admin::treasury::__client_accounts_cancel_treasury_disbursement;
+pub(crate) use crate::instructions::admin::treasury::__client_accounts_collect_treasury_fees;
+pub(crate) use crate::instructions::admin::treasury::__client_accounts_execute_treasury_disbursement;
+pub(crate) use crate::instructions::admin::treasury::__client_accounts_init_pool_treasury;
+pub(crate) use crate::instructions::admin::treasury::__client_accounts_request_treasury_disbursement;
+pub(crate) use crate::instructions::admin::treasury::__client_accounts_view_treasury;
 pub(crate) use crate::instructions::admin::unpause_v2::__client_accounts_unpause_pool_v2;
 pub(crate) use crate::instructions::batch_process_deposits::__client_accounts_batch_process_deposits;
+pub(crate) use crate::instructions::batcher_bond::__client_accounts_exit_batcher;
+pub(crate) use crate::instructions::batcher_bond::__client_accounts_register_batcher;
+pub(crate) use crate::instructions::batcher_bond::__client_accounts_report_batcher_misbehavior;
+pub(crate) use crate::instructions::clone_pool::__client_accounts_clone_asset_config;
+pub(crate) use crate::instructions::clone_pool::__client_accounts_clone_pool_config;
+pub(crate) use crate::instructions::commitment_reservation::__client_accounts_release_commitment_reservation;
+pub(crate) use crate::instructions::commitment_reservation::__client_accounts_reserve_commitment;
+pub(crate) use crate::instructions::compliance::attach_metadata::__client_accounts_attach_audit_metadata;
+pub(crate) use crate::instructions::compliance::configure_compliance::__client_accounts_configure_compliance;
+pub(crate) use crate::instructions::compliance::recovery_withdraw::__client_accounts_compliance_recovery_withdraw;
+pub(crate) use crate::instructions::create_one_time_recipient::__client_accounts_create_one_time_recipient;
+pub(crate) use crate::instructions::create_pool::__client_accounts_create_pool;
+pub(crate) use crate::instructions::deferred_withdraw_events::__client_accounts_flush_deferred_withdraw_events;
+pub(crate) use crate::instructions::deferred_withdraw_events::__client_accounts_init_deferred_withdraw_events;
+pub(crate) use crate::instructions::deferred_withdraw_events::__client_accounts_set_withdraw_event_delay;
+pub(crate) use crate::instructions::deposit_hook::__client_accounts_register_deposit_hook;
+pub(crate) use crate::instructions::deposit_hook::__client_accounts_remove_deposit_hook;
 pub(crate) use crate::instructions::deposit_masp::__client_accounts_deposit_masp;
+pub(crate) use crate::instructions::deposit_masp_batch::__client_accounts_deposit_masp_batch;
+pub(crate) use crate::instructions::disclosed_path_withdraw::__client_accounts_commit_disclosed_withdraw;
+pub(crate) use crate::instructions::disclosed_path_withdraw::__client_accounts_disclosed_path_withdraw;
+pub(crate) use crate::instructions::estimate_privacy_score::__client_accounts_estimate_privacy_score;
+pub(crate) use crate::instructions::init_anonymity_stats::__client_accounts_init_anonymity_stats;
+pub(crate) use crate::instructions::init_proof_stats::__client_accounts_init_proof_stats;
+pub(crate) use crate::instructions::init_yield_registry::__client_accounts_init_yield_registry;
 pub(crate) use crate::instructions::initialize_pool_registries::__client_accounts_initialize_pool_registries;
 pub(crate) use crate::instructions::initialize_pool_v2::__client_accounts_initialize_pool_v2;
+pub(crate) use crate::instructions::manage_yield_mints::__client_accounts_manage_yield_mints;
+pub(crate) use crate::instructions::mint_allowlist::__client_accounts_init_mint_allowlist;
+pub(crate) use crate::instructions::mint_allowlist::__client_accounts_manage_mint_allowlist;
+pub(crate) use crate::instructions::mint_allowlist::__client_accounts_register_allowlisted_asset;
+pub(crate) use crate::instructions::pool_directory::__client_accounts_init_pool_directory;
+pub(crate) use crate::instructions::pool_directory::__client_accounts_list_pool;
+pub(crate) use crate::instructions::pool_directory::__client_accounts_refresh_pool_listing;
+pub(crate) use crate::instructions::private_transfer::__client_accounts_private_transfer_join_split;
+pub(crate) use crate::instructions::publish_attested_root::__client_accounts_publish_attested_root;
+pub(crate) use crate::instructions::record_note_receipt::__client_accounts_record_note_receipt;
 pub(crate) use crate::instructions::register_asset::__client_accounts_register_asset;
+pub(crate) use crate::instructions::register_asset::__client_accounts_register_native_sol_asset;
+pub(crate) use crate::instructions::register_assets_batch::__client_accounts_register_assets_batch;
+pub(crate) use crate::instructions::relayer::close_relayer::__client_accounts_close_relayer;
 pub(crate) use crate::instructions::relayer::configure_registry::__client_accounts_configure_relayer_registry;
+pub(crate) use crate::instructions::relayer::configure_registry::__client_accounts_set_relayer_cooldown;
+pub(crate) use crate::instructions::relayer::configure_registry::__client_accounts_set_relayer_registrations_open;
 pub(crate) use crate::instructions::relayer::deactivate_relayer::__client_accounts_deactivate_relayer;
+pub(crate) use crate::instructions::relayer::drain_relayer::__client_accounts_set_relayer_draining;
+pub(crate) use crate::instructions::relayer::fee_quote::__client_accounts_init_relayer_fee_quote;
+pub(crate) use crate::instructions::relayer::fee_quote::__client_accounts_update_relayer_fee_quote;
+pub(crate) use crate::instructions::relayer::gas_rebate::__client_accounts_claim_gas_rebate;
+pub(crate) use crate::instructions::relayer::gas_rebate::__client_accounts_init_relayer_rebate_ledger;
+pub(crate) use crate::instructions::relayer::gas_rebate::__client_accounts_set_gas_rebate_cap;
+pub(crate) use crate::instructions::relayer::gas_rebate::__client_accounts_update_relayer_priority_fee_hint;
 pub(crate) use crate::instructions::relayer::register_relayer::__client_accounts_register_relayer;
+pub(crate) use crate::instructions::relayer::relayer_index::__client_accounts_init_relayer_index_shard;
+pub(crate) use crate::instructions::relayer::rent_reserve::__client_accounts_fund_rent_reserve;
+pub(crate) use crate::instructions::relayer::rent_reserve::__client_accounts_init_rent_advance;
+pub(crate) use crate::instructions::relayer::rent_reserve::__client_accounts_init_rent_reserve;
+pub(crate) use crate::instructions::relayer::rent_reserve::__client_accounts_repay_rent_advance;
+pub(crate) use crate::instructions::relayer::rent_reserve::__client_accounts_set_rent_advance_cap;
+pub(crate) use crate::instructions::relayer::report_relayer_failure::__client_accounts_report_relayer_failure;
+pub(crate) use crate::instructions::relayer::set_asset_fee_bounds::__client_accounts_set_asset_relayer_fee_bounds;
+pub(crate) use crate::instructions::relayer::slash_relayer::__client_accounts_slash_relayer;
+pub(crate) use crate::instructions::relayer::stake::__client_accounts_init_relayer_stake_vault;
+pub(crate) use crate::instructions::relayer::stake::__client_accounts_move_relayer_stake;
+pub(crate) use crate::instructions::relayer::stake::__client_accounts_set_relayer_stake_mint;
+pub(crate) use crate::instructions::relayer::stake::__client_accounts_unstake_relayer;
 pub(crate) use crate::instructions::relayer::update_relayer::__client_accounts_update_relayer;
+pub(crate) use crate::instructions::relayer::update_relayer::__client_accounts_update_relayer_signed;
+pub(crate) use crate::instructions::set_circuit_descriptor::__client_accounts_set_circuit_descriptor;
+pub(crate) use crate::instructions::set_circuit_descriptor::__client_accounts_update_circuit_descriptor;
+pub(crate) use crate::instructions::set_circuit_descriptor::__client_accounts_view_poseidon_params;
+pub(crate) use crate::instructions::set_feature_flags::__client_accounts_set_feature_flags;
 pub(crate) use crate::instructions::set_verification_key_chunked::__client_accounts_append_vk_ic_v2;
 pub(crate) use crate::instructions::set_verification_key_chunked::__client_accounts_finalize_vk_v2;
 pub(crate) use crate::instructions::set_verification_key_chunked::__client_accounts_initialize_vk_v2;
+pub(crate) use crate::instructions::set_verification_key_v2::__client_accounts_create_verification_key_account;
 pub(crate) use crate::instructions::set_verification_key_v2::__client_accounts_lock_verification_key_v2;
-pub(crate) use crate::instructions::set_verification_key_v2::__client_accounts_set_verification_key_v2;
+pub(crate) use crate::instructions::set_verification_key_v2::__client_accounts_record_vk_ceremony;
+pub(crate) use crate::instructions::set_verification_key_v2::__client_accounts_set_verification_key_data;
+pub(crate) use crate::instructions::set_verification_key_v2::__client_accounts_submit_canary_proof;
+pub(crate) use crate::instructions::shielded_cpi::action_allowlist::__client_accounts_init_action_allowlist;
+pub(crate) use crate::instructions::shielded_cpi::action_allowlist::__client_accounts_manage_action_allowlist;
+pub(crate) use crate::instructions::shielded_cpi::execute_action::__client_accounts_execute_shielded_action;
+pub(crate) use crate::instructions::verify_account_integrity::__client_accounts_verify_account_integrity;
 pub(crate) use crate::instructions::withdraw_masp::__client_accounts_withdraw_masp;
-pub(crate) use crate::instructions::withdraw_yield_v2::__client_accounts_withdraw_yield_v2;
-pub(crate) use crate::instructions::init_yield_registry::__client_accounts_init_yield_registry;
-pub(crate) use crate::instructions::manage_yield_mints::__client_accounts_manage_yield_mints;
-pub(crate) use crate::instructions::set_feature_flags::__client_accounts_set_feature_flags;
 pub(crate) use crate::instructions::withdraw_v2::__client_accounts_withdraw_v2;
-pub(crate) use crate::instructions::admin::clear_pending::__client_accounts_clear_pending_buffer;
-pub(crate) use crate::instructions::admin::reset_merkle::__client_accounts_reset_merkle_tree;
+pub(crate) use crate::instructions::withdraw_yield_v2::__client_accounts_withdraw_yield_v2;
+pub(crate) use crate::instructions::withdrawal_commit::__client_accounts_cancel_withdrawal_commit;
+pub(crate) use crate::instructions::withdrawal_commit::__client_accounts_commit_withdrawal;
 
 #[program]
 pub mod psol_privacy_v2 {
@@ -62,8 +200,16 @@ pub mod psol_privacy_v2 {
         ctx: Context<InitializePoolV2>,
         tree_depth: u8,
         root_history_size: u16,
+        compliance_recovery: bool,
+        pool_id: u64,
     ) -> Result<()> {
-        instructions::initialize_pool_v2::handler(ctx, tree_depth, root_history_size)
+        instructions::initialize_pool_v2::handler(
+            ctx,
+            tree_depth,
+            root_history_size,
+            compliance_recovery,
+            pool_id,
+        )
     }
 
     pub fn initialize_pool_registries(ctx: Context<InitializePoolRegistries>) -> Result<()> {
@@ -80,15 +226,117 @@ pub mod psol_privacy_v2 {
         instructions::register_asset::handler(ctx, asset_id)
     }
 
+    /// Admin: Register native SOL; deposits and withdrawals of this asset
+    /// move lamports instead of wrapped SOL
+    pub fn register_native_sol_asset(ctx: Context<RegisterNativeSolAsset>) -> Result<()> {
+        instructions::register_asset::native_sol_handler(ctx)
+    }
+
+    /// Admin: create pool `pool_id` with the configuration of another pool
+    /// of the same authority (fresh tree of the same depth)
+    pub fn clone_pool_config(
+        ctx: Context<ClonePoolConfig>,
+        pool_id: u64,
+        root_history_size: u16,
+    ) -> Result<()> {
+        instructions::clone_pool::handler(ctx, pool_id, root_history_size)
+    }
+
+    /// Admin: register a source pool's asset in a cloned pool with the
+    /// source vault's configuration (fresh vault)
+    pub fn clone_asset_config(ctx: Context<CloneAssetConfig>, asset_id: [u8; 32]) -> Result<()> {
+        instructions::clone_pool::asset_handler(ctx, asset_id)
+    }
+
+    /// Open pool `pool_id` with the signer as authority, paying the
+    /// program-wide creation fee (at most `max_creation_fee` lamports);
+    /// creates the tree and registries in one go with default settings
+    pub fn create_pool(
+        ctx: Context<CreatePool>,
+        tree_depth: u8,
+        pool_id: u64,
+        max_creation_fee: u64,
+    ) -> Result<()> {
+        instructions::create_pool::handler(ctx, tree_depth, pool_id, max_creation_fee)
+    }
+
+    /// Create the program-wide pool directory (anyone, once)
+    pub fn init_pool_directory(ctx: Context<InitPoolDirectory>) -> Result<()> {
+        instructions::pool_directory::init_handler(ctx)
+    }
+
+    /// Admin: list the pool in the pool directory for wallet discovery
+    pub fn list_pool(ctx: Context<ListPool>) -> Result<()> {
+        instructions::pool_directory::list_handler(ctx)
+    }
+
+    /// Copy a listed pool's asset count into its directory entry
+    /// (permissionless)
+    pub fn refresh_pool_listing(ctx: Context<RefreshPoolListing>) -> Result<()> {
+        instructions::pool_directory::refresh_handler(ctx)
+    }
+
+    /// Register up to 5 assets at once; each asset's mint, vault and vault
+    /// token account are passed as remaining accounts
+    pub fn register_assets_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RegisterAssetsBatch<'info>>,
+        asset_ids: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::register_assets_batch::handler(ctx, asset_ids)
+    }
+
+    /// Admin: create the pool's mint allowlist (registration starts closed)
+    pub fn init_mint_allowlist(ctx: Context<InitMintAllowlist>) -> Result<()> {
+        instructions::mint_allowlist::init_handler(ctx)
+    }
+
+    /// Admin: allow anyone to register `mint` while registration is open
+    pub fn add_allowlisted_mint(ctx: Context<ManageMintAllowlist>, mint: Pubkey) -> Result<()> {
+        instructions::mint_allowlist::add_handler(ctx, mint)
+    }
+
+    /// Admin: remove `mint` from the allowlist
+    pub fn remove_allowlisted_mint(ctx: Context<ManageMintAllowlist>, mint: Pubkey) -> Result<()> {
+        instructions::mint_allowlist::remove_handler(ctx, mint)
+    }
+
+    /// Admin: open or close permissionless registration of allowlisted mints
+    pub fn set_open_asset_registration(
+        ctx: Context<ManageMintAllowlist>,
+        open: bool,
+    ) -> Result<()> {
+        instructions::mint_allowlist::set_open_handler(ctx, open)
+    }
+
+    /// Register an allowlisted mint as a pool asset; anyone may call this
+    /// while registration is open
+    pub fn register_allowlisted_asset(
+        ctx: Context<RegisterAllowlistedAsset>,
+        asset_id: [u8; 32],
+    ) -> Result<()> {
+        instructions::mint_allowlist::register_handler(ctx, asset_id)
+    }
+
+    /// Create the empty VK account for a proof type (fails if it exists)
+    pub fn create_verification_key_account(
+        ctx: Context<CreateVerificationKeyAccount>,
+        proof_type: ProofType,
+    ) -> Result<()> {
+        instructions::set_verification_key_v2::create_handler(ctx, proof_type)
+    }
+
+    /// Write a VK into its account; replaces an unlocked key, never a locked one
     #[allow(clippy::too_many_arguments)]
-    pub fn set_verification_key_v2(
-        ctx: Context<SetVerificationKeyV2>,
+    pub fn set_verification_key_data(
+        ctx: Context<SetVerificationKeyData>,
         proof_type: ProofType,
         vk_alpha_g1: [u8; 64],
         vk_beta_g2: [u8; 128],
         vk_gamma_g2: [u8; 128],
         vk_delta_g2: [u8; 128],
         vk_ic: Vec<[u8; 64]>,
+        ceremony_transcript_hash: [u8; 32],
+        ceremony_contributors: u32,
     ) -> Result<()> {
         instructions::set_verification_key_v2::handler(
             ctx,
@@ -98,9 +346,55 @@ pub mod psol_privacy_v2 {
             vk_gamma_g2,
             vk_delta_g2,
             vk_ic,
+            ceremony_transcript_hash,
+            ceremony_contributors,
         )
     }
 
+    /// Record the circuit parameters a proof type's VK was generated for
+    pub fn set_circuit_descriptor(
+        ctx: Context<SetCircuitDescriptor>,
+        proof_type: ProofType,
+        circuit_version: u16,
+        public_input_layout_hash: [u8; 32],
+        tree_depth: u8,
+        poseidon_params_hash: [u8; 32],
+    ) -> Result<()> {
+        instructions::set_circuit_descriptor::set_handler(
+            ctx,
+            proof_type,
+            circuit_version,
+            public_input_layout_hash,
+            tree_depth,
+            poseidon_params_hash,
+        )
+    }
+
+    /// Update a circuit descriptor (only while the VK is unlocked)
+    pub fn update_circuit_descriptor(
+        ctx: Context<UpdateCircuitDescriptor>,
+        proof_type: ProofType,
+        circuit_version: u16,
+        public_input_layout_hash: [u8; 32],
+        tree_depth: u8,
+        poseidon_params_hash: [u8; 32],
+    ) -> Result<()> {
+        instructions::set_circuit_descriptor::update_handler(
+            ctx,
+            proof_type,
+            circuit_version,
+            public_input_layout_hash,
+            tree_depth,
+            poseidon_params_hash,
+        )
+    }
+
+    /// Hash of the Poseidon parameter set the pool's Merkle tree hashes
+    /// with (read-only; simulate and read the return data)
+    pub fn view_poseidon_params(ctx: Context<ViewPoseidonParams>) -> Result<[u8; 32]> {
+        instructions::set_circuit_descriptor::view_params_handler(ctx)
+    }
+
     pub fn lock_verification_key_v2(
         ctx: Context<LockVerificationKeyV2>,
         proof_type: ProofType,
@@ -108,7 +402,35 @@ pub mod psol_privacy_v2 {
         instructions::set_verification_key_v2::lock_handler(ctx, proof_type)
     }
 
+    /// Verify a known test proof against the stored VK and mark it
+    /// `canary_passed` (no funds move)
+    pub fn submit_canary_proof(
+        ctx: Context<SubmitCanaryProof>,
+        proof_type: ProofType,
+        proof: Vec<u8>,
+        public_inputs: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::set_verification_key_v2::canary_handler(ctx, proof_type, proof, public_inputs)
+    }
+
+    /// Record the MPC ceremony behind a VK that has none, including one
+    /// set or locked before ceremony data existed (write-once)
+    pub fn record_vk_ceremony(
+        ctx: Context<RecordVkCeremony>,
+        proof_type: ProofType,
+        ceremony_transcript_hash: [u8; 32],
+        ceremony_contributors: u32,
+    ) -> Result<()> {
+        instructions::set_verification_key_v2::ceremony_handler(
+            ctx,
+            proof_type,
+            ceremony_transcript_hash,
+            ceremony_contributors,
+        )
+    }
+
     /// Initialize VK with base curve points (chunked upload step 1)
+    #[allow(clippy::too_many_arguments)]
     pub fn initialize_vk_v2(
         ctx: Context<InitializeVkV2>,
         proof_type: ProofType,
@@ -117,6 +439,8 @@ pub mod psol_privacy_v2 {
         vk_gamma_g2: [u8; 128],
         vk_delta_g2: [u8; 128],
         expected_ic_count: u8,
+        ceremony_transcript_hash: [u8; 32],
+        ceremony_contributors: u32,
     ) -> Result<()> {
         instructions::set_verification_key_chunked::initialize_vk_handler(
             ctx,
@@ -126,6 +450,8 @@ pub mod psol_privacy_v2 {
             vk_gamma_g2,
             vk_delta_g2,
             expected_ic_count,
+            ceremony_transcript_hash,
+            ceremony_contributors,
         )
     }
 
@@ -138,6 +464,43 @@ pub mod psol_privacy_v2 {
         instructions::set_verification_key_chunked::append_vk_ic_handler(ctx, proof_type, ic_points)
     }
 
+    /// Initialize VK from compressed curve points (chunked upload step 1)
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize_vk_compressed_v2(
+        ctx: Context<InitializeVkV2>,
+        proof_type: ProofType,
+        vk_alpha_g1: [u8; 32],
+        vk_beta_g2: [u8; 64],
+        vk_gamma_g2: [u8; 64],
+        vk_delta_g2: [u8; 64],
+        expected_ic_count: u8,
+        ceremony_transcript_hash: [u8; 32],
+        ceremony_contributors: u32,
+    ) -> Result<()> {
+        instructions::set_verification_key_chunked::initialize_vk_compressed_handler(
+            ctx,
+            proof_type,
+            vk_alpha_g1,
+            vk_beta_g2,
+            vk_gamma_g2,
+            vk_delta_g2,
+            expected_ic_count,
+            ceremony_transcript_hash,
+            ceremony_contributors,
+        )
+    }
+
+    /// Append compressed IC points to VK (chunked upload step 2)
+    pub fn append_vk_ic_compressed_v2(
+        ctx: Context<AppendVkIcV2>,
+        proof_type: ProofType,
+        ic_points: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::set_verification_key_chunked::append_vk_ic_compressed_handler(
+            ctx, proof_type, ic_points,
+        )
+    }
+
     /// Finalize VK after all IC points uploaded (chunked upload step 3)
     pub fn finalize_vk_v2(ctx: Context<FinalizeVkV2>, proof_type: ProofType) -> Result<()> {
         instructions::set_verification_key_chunked::finalize_vk_handler(ctx, proof_type)
@@ -151,16 +514,344 @@ pub mod psol_privacy_v2 {
         instructions::admin::unpause_v2::handler(ctx)
     }
 
+    /// Upgrade authority: create the program-wide config naming the super
+    /// guardian
+    pub fn initialize_global_config(
+        ctx: Context<InitializeGlobalConfig>,
+        super_guardian: Pubkey,
+    ) -> Result<()> {
+        instructions::admin::global_pause::handler_initialize(ctx, super_guardian)
+    }
+
+    /// Super guardian: pause fund-moving instructions in every pool
+    pub fn pause_all_pools(ctx: Context<PauseAllPools>) -> Result<()> {
+        instructions::admin::global_pause::handler_pause(ctx)
+    }
+
+    /// Super guardian: lift the program-wide pause
+    pub fn unpause_all_pools(ctx: Context<UnpauseAllPools>) -> Result<()> {
+        instructions::admin::global_pause::handler_unpause(ctx)
+    }
+
+    /// Super guardian: hand the role to a new key
+    pub fn set_super_guardian(
+        ctx: Context<SetSuperGuardian>,
+        new_super_guardian: Pubkey,
+    ) -> Result<()> {
+        instructions::admin::global_pause::handler_set_super_guardian(ctx, new_super_guardian)
+    }
+
+    /// Super guardian: set the lamports `create_pool` charges
+    pub fn set_pool_creation_fee(ctx: Context<SetPoolCreationFee>, fee: u64) -> Result<()> {
+        instructions::admin::global_pause::handler_set_pool_creation_fee(ctx, fee)
+    }
+
     /// Admin: Clear pending deposits buffer (emergency/testing)
     pub fn clear_pending_buffer(ctx: Context<ClearPendingBuffer>) -> Result<()> {
         instructions::admin::clear_pending::handler(ctx)
     }
 
+    /// Admin: Rewrite a legacy pending deposits buffer in the ring layout
+    pub fn migrate_pending_buffer(ctx: Context<MigratePendingBuffer>) -> Result<()> {
+        instructions::admin::migrate_pending_buffer::handler(ctx)
+    }
+
+    /// Admin: Grow a pool config created before the protocol fee to the
+    /// current layout
+    pub fn migrate_pool_config(ctx: Context<MigratePoolConfig>) -> Result<()> {
+        instructions::admin::migrate_pool_config::handler(ctx)
+    }
+
+    /// Admin: Grow a Merkle tree created before its Poseidon parameter hash
+    /// to the current layout
+    pub fn migrate_merkle_tree(ctx: Context<MigrateMerkleTree>) -> Result<()> {
+        instructions::admin::migrate_merkle_tree::handler(ctx)
+    }
+
+    /// Admin: Grow an asset vault registered before the fields that follow
+    /// its metadata URI to the current layout
+    pub fn migrate_asset_vault(ctx: Context<MigrateAssetVault>) -> Result<()> {
+        instructions::admin::migrate_asset_vault::handler(ctx)
+    }
+
+    /// Admin: Grow a relayer registry created before relayer staking to the
+    /// current layout
+    pub fn migrate_relayer_registry(ctx: Context<MigrateRelayerRegistry>) -> Result<()> {
+        instructions::admin::migrate_relayer_registry::handler(ctx)
+    }
+
+    /// Admin: Re-lay out a relayer node registered before its structured
+    /// metadata and append it to the relayer index
+    pub fn migrate_relayer_node(ctx: Context<MigrateRelayerNode>) -> Result<()> {
+        instructions::admin::migrate_relayer_node::handler(ctx)
+    }
+
+    /// Admin: Grow a verification key account created before its canary
+    /// and ceremony fields to the current layout
+    pub fn migrate_verification_key(
+        ctx: Context<MigrateVerificationKey>,
+        proof_type: ProofType,
+    ) -> Result<()> {
+        instructions::admin::migrate_verification_key::handler(ctx, proof_type)
+    }
+
     /// Admin: Reset merkle tree to empty state
     pub fn reset_merkle_tree(ctx: Context<ResetMerkleTree>) -> Result<()> {
         instructions::admin::reset_merkle::handler(ctx)
     }
 
+    /// Admin: Configure tree fullness warning thresholds (percent)
+    pub fn set_tree_capacity_thresholds(
+        ctx: Context<SetTreeCapacityThresholds>,
+        warning_pct: u8,
+        critical_pct: u8,
+    ) -> Result<()> {
+        instructions::admin::capacity_thresholds::handler(ctx, warning_pct, critical_pct)
+    }
+
+    /// Admin: Set the largest encrypted note (bytes) accepted with a deposit
+    /// or join-split output
+    pub fn set_max_encrypted_note_len(
+        ctx: Context<SetMaxEncryptedNoteLen>,
+        max_len: u16,
+    ) -> Result<()> {
+        instructions::admin::note_size_limit::handler(ctx, max_len)
+    }
+
+    /// Admin: Set an asset's minimum withdrawal amount (token base units;
+    /// 0 restores the default derived from the mint's decimals)
+    pub fn set_asset_min_withdrawal(
+        ctx: Context<SetAssetMinWithdrawal>,
+        asset_id: [u8; 32],
+        min_withdrawal: u64,
+    ) -> Result<()> {
+        instructions::admin::min_withdrawal::handler(ctx, asset_id, min_withdrawal)
+    }
+
+    /// Admin: Create the pool's risk tier table with the default tiers
+    pub fn init_risk_tiers(ctx: Context<InitRiskTiers>) -> Result<()> {
+        instructions::admin::risk_tier::init_handler(ctx)
+    }
+
+    /// Admin: Replace one risk tier's limits (applies at once to every asset
+    /// in the tier)
+    pub fn set_risk_tier_limits(
+        ctx: Context<SetRiskTierLimits>,
+        tier: u8,
+        limits: state::RiskTierLimits,
+    ) -> Result<()> {
+        instructions::admin::risk_tier::set_limits_handler(ctx, tier, limits)
+    }
+
+    /// Admin: Put an asset in a risk tier; deposits and withdrawals of the
+    /// asset then read their limits from the tier
+    pub fn set_asset_risk_tier(
+        ctx: Context<SetAssetRiskTier>,
+        asset_id: [u8; 32],
+        tier: u8,
+    ) -> Result<()> {
+        instructions::admin::risk_tier::set_asset_handler(ctx, asset_id, tier)
+    }
+
+    /// Admin: Create an asset's withdrawal window, which tracks its
+    /// withdrawals against its risk tier's rate limit
+    pub fn init_asset_withdrawal_window(
+        ctx: Context<InitAssetWithdrawalWindow>,
+        asset_id: [u8; 32],
+    ) -> Result<()> {
+        instructions::admin::risk_tier::init_window_handler(ctx, asset_id)
+    }
+
+    /// Admin: Allow or forbid a deposit and a withdrawal against the pool in
+    /// the same transaction (forbidden by default)
+    pub fn set_same_tx_deposit_withdraw(
+        ctx: Context<SetSameTxDepositWithdraw>,
+        allowed: bool,
+    ) -> Result<()> {
+        instructions::admin::same_tx_flows::handler(ctx, allowed)
+    }
+
+    /// Admin: Emit a `RootUpdatedEvent` on every Merkle root change
+    pub fn set_root_events(ctx: Context<SetRootEvents>, enabled: bool) -> Result<()> {
+        instructions::admin::root_events::handler(ctx, enabled)
+    }
+
+    /// Admin: Refuse nullification receipts (`record_note_receipt`)
+    pub fn set_privacy_strict(ctx: Context<SetPrivacyStrict>, enabled: bool) -> Result<()> {
+        instructions::admin::privacy_strict::handler(ctx, enabled)
+    }
+
+    /// Admin: Allow withdrawals that disclose the note and verify its
+    /// Merkle path on-chain (REDUCED PRIVACY; small trees, until the
+    /// withdraw VK is set)
+    pub fn set_disclosed_path_withdrawals(
+        ctx: Context<SetDisclosedPathWithdrawals>,
+        enabled: bool,
+    ) -> Result<()> {
+        instructions::admin::disclosed_path::handler(ctx, enabled)
+    }
+
+    /// Admin: Lamports each deposit pays and each batched leaf earns the
+    /// batcher (`batch_process_deposits`); 0 disables the bounty
+    pub fn set_crank_bounty(ctx: Context<SetCrankBounty>, lamports_per_leaf: u32) -> Result<()> {
+        instructions::admin::crank_bounty::handler(ctx, lamports_per_leaf)
+    }
+
+    /// Admin: Share of each token withdrawal paid to the asset's treasury,
+    /// in basis points; 0 disables the protocol fee
+    pub fn set_protocol_fee(ctx: Context<SetProtocolFee>, fee_bps: u16) -> Result<()> {
+        instructions::admin::protocol_fee::set_fee_handler(ctx, fee_bps)
+    }
+
+    /// Admin: Create an asset's treasury token account, which collects its
+    /// protocol fees
+    pub fn init_treasury(ctx: Context<InitTreasury>, asset_id: [u8; 32]) -> Result<()> {
+        instructions::admin::protocol_fee::init_treasury_handler(ctx, asset_id)
+    }
+
+    /// Admin: Create the pool treasury, which collects native SOL fees and
+    /// timelocks every disbursement
+    pub fn init_pool_treasury(ctx: Context<InitPoolTreasury>) -> Result<()> {
+        instructions::admin::treasury::init_handler(ctx)
+    }
+
+    /// Admin: Move a native SOL vault's protocol fees (its lamports above
+    /// `shielded_balance`) into the pool treasury
+    pub fn collect_treasury_fees(
+        ctx: Context<CollectTreasuryFees>,
+        asset_id: [u8; 32],
+    ) -> Result<()> {
+        instructions::admin::treasury::collect_handler(ctx, asset_id)
+    }
+
+    /// An asset's collected and uncollected protocol fees and pending
+    /// disbursement (read-only; simulate and read the return data)
+    pub fn view_treasury(
+        ctx: Context<ViewTreasury>,
+        asset_id: [u8; 32],
+    ) -> Result<TreasuryBalance> {
+        instructions::admin::treasury::view_handler(ctx, asset_id)
+    }
+
+    /// Admin: Announce a disbursement of an asset's collected fees, to
+    /// execute after the timelock
+    pub fn request_treasury_disbursement(
+        ctx: Context<RequestTreasuryDisbursement>,
+        asset_id: [u8; 32],
+        amount: u64,
+    ) -> Result<()> {
+        instructions::admin::treasury::request_handler(ctx, asset_id, amount)
+    }
+
+    /// Admin: Pay the announced disbursement once its timelock has passed
+    pub fn execute_treasury_disbursement(
+        ctx: Context<ExecuteTreasuryDisbursement>,
+        asset_id: [u8; 32],
+    ) -> Result<()> {
+        instructions::admin::treasury::execute_handler(ctx, asset_id)
+    }
+
+    /// Admin: Withdraw the announced treasury disbursement
+    pub fn cancel_treasury_disbursement(ctx: Context<CancelTreasuryDisbursement>) -> Result<()> {
+        instructions::admin::treasury::cancel_handler(ctx)
+    }
+
+    /// Admin: Create an asset's parameter snapshot, publishing the current
+    /// fees, limits and flags as version 1
+    pub fn init_parameter_snapshot(ctx: Context<InitParameterSnapshot>) -> Result<()> {
+        instructions::admin::parameter_snapshot::init_handler(ctx)
+    }
+
+    /// Admin: Publish an asset's current fees, limits and flags under the
+    /// next snapshot version, for clients to pin (`withdraw_with_parameter_pin`)
+    pub fn publish_parameter_snapshot(ctx: Context<PublishParameterSnapshot>) -> Result<()> {
+        instructions::admin::parameter_snapshot::publish_handler(ctx)
+    }
+
+    /// Admin: Snapshot current roots so withdrawals keep accepting them
+    /// for `duration_seconds` while the tree is migrated
+    pub fn open_migration_window(
+        ctx: Context<OpenMigrationWindow>,
+        duration_seconds: i64,
+    ) -> Result<()> {
+        instructions::admin::migration_window::open_handler(ctx, duration_seconds)
+    }
+
+    /// Admin: End a migration window and reclaim its rent
+    pub fn close_migration_window(ctx: Context<CloseMigrationWindow>) -> Result<()> {
+        instructions::admin::migration_window::close_handler(ctx)
+    }
+
+    /// Admin: Announce a sweep of an asset vault's surplus (tokens above
+    /// its shielded balance) to `destination`, executable after a timelock
+    pub fn request_surplus_sweep(
+        ctx: Context<RequestSurplusSweep>,
+        asset_id: [u8; 32],
+    ) -> Result<()> {
+        instructions::admin::sweep_surplus::request_handler(ctx, asset_id)
+    }
+
+    /// Admin: Move an asset vault's current surplus to the announced
+    /// destination once the timelock has passed
+    pub fn sweep_surplus(ctx: Context<SweepSurplus>, asset_id: [u8; 32]) -> Result<()> {
+        instructions::admin::sweep_surplus::sweep_handler(ctx, asset_id)
+    }
+
+    /// Admin: Withdraw an announced surplus sweep and reclaim its rent
+    pub fn cancel_surplus_sweep(
+        ctx: Context<CancelSurplusSweep>,
+        asset_id: [u8; 32],
+    ) -> Result<()> {
+        instructions::admin::sweep_surplus::cancel_handler(ctx, asset_id)
+    }
+
+    /// Admin: Set whether deposits need an encrypted note and the auditor
+    /// key audit metadata is encrypted to (`Some(Pubkey::default())`
+    /// disables audit, `None` keeps the current key)
+    pub fn configure_compliance(
+        ctx: Context<ConfigureCompliance>,
+        require_encrypted_note: bool,
+        audit_pubkey: Option<Pubkey>,
+        metadata_schema_version: u8,
+    ) -> Result<()> {
+        instructions::compliance::configure_compliance::handler(
+            ctx,
+            require_encrypted_note,
+            audit_pubkey,
+            metadata_schema_version,
+        )
+    }
+
+    /// Attach audit metadata, encrypted to the auditor, to a commitment
+    pub fn attach_audit_metadata(
+        ctx: Context<AttachAuditMetadata>,
+        commitment: [u8; 32],
+        encrypted_metadata: Vec<u8>,
+    ) -> Result<()> {
+        instructions::compliance::attach_metadata::handler(ctx, commitment, encrypted_metadata)
+    }
+
+    /// Admin + auditor: Move a note's funds to a custodian under a court
+    /// order, from its opening disclosed through its audit metadata (pools
+    /// created with `compliance_recovery` only)
+    pub fn compliance_recovery_withdraw(
+        ctx: Context<ComplianceRecoveryWithdraw>,
+        commitment: [u8; 32],
+        asset_id: [u8; 32],
+        nullifier_hash: [u8; 32],
+        court_order_hash: [u8; 32],
+        note: DisclosedNote,
+    ) -> Result<()> {
+        instructions::compliance::recovery_withdraw::handler(
+            ctx,
+            commitment,
+            asset_id,
+            nullifier_hash,
+            court_order_hash,
+            note,
+        )
+    }
+
     pub fn initiate_authority_transfer_v2(
         ctx: Context<InitiateAuthorityTransferV2>,
         new_authority: Pubkey,
@@ -196,8 +887,9 @@ pub mod psol_privacy_v2 {
         ctx: Context<RegisterRelayer>,
         fee_bps: u16,
         metadata_uri: String,
+        metadata: state::RelayerMetadata,
     ) -> Result<()> {
-        instructions::relayer::register_relayer::handler(ctx, fee_bps, metadata_uri)
+        instructions::relayer::register_relayer::handler(ctx, fee_bps, metadata_uri, metadata)
     }
 
     pub fn update_relayer(
@@ -205,14 +897,241 @@ pub mod psol_privacy_v2 {
         fee_bps: Option<u16>,
         metadata_uri: Option<String>,
         is_active: Option<bool>,
+        metadata: Option<state::RelayerMetadata>,
+    ) -> Result<()> {
+        instructions::relayer::update_relayer::handler(
+            ctx,
+            fee_bps,
+            metadata_uri,
+            is_active,
+            metadata,
+        )
+    }
+
+    /// Apply an operator-signed fee/active update submitted by another key
+    pub fn update_relayer_signed(
+        ctx: Context<UpdateRelayerSigned>,
+        fee_bps: Option<u16>,
+        is_active: Option<bool>,
+        nonce: u64,
+        expires_at: i64,
     ) -> Result<()> {
-        instructions::relayer::update_relayer::handler(ctx, fee_bps, metadata_uri, is_active)
+        instructions::relayer::update_relayer::signed_handler(
+            ctx, fee_bps, is_active, nonce, expires_at,
+        )
     }
 
     pub fn deactivate_relayer(ctx: Context<DeactivateRelayer>) -> Result<()> {
         instructions::relayer::deactivate_relayer::handler(ctx)
     }
 
+    /// Mark a relayer node as draining: it accepts only `inflight_allowance`
+    /// more withdrawals, for jobs already queued, until the flag is cleared
+    pub fn set_relayer_draining(
+        ctx: Context<SetRelayerDraining>,
+        draining: bool,
+        inflight_allowance: u16,
+    ) -> Result<()> {
+        instructions::relayer::drain_relayer::handler(ctx, draining, inflight_allowance)
+    }
+
+    /// Close a deactivated relayer node, starting its re-registration cooldown
+    pub fn close_relayer(ctx: Context<CloseRelayer>) -> Result<()> {
+        instructions::relayer::close_relayer::handler(ctx)
+    }
+
+    /// Set the slots a closed relayer's operator must wait before registering again
+    pub fn set_relayer_cooldown(
+        ctx: Context<SetRelayerCooldown>,
+        reregistration_cooldown_slots: u64,
+    ) -> Result<()> {
+        instructions::relayer::configure_registry::set_cooldown_handler(
+            ctx,
+            reregistration_cooldown_slots,
+        )
+    }
+
+    /// Open or close the relayer registry to new registrations
+    pub fn set_relayer_registrations_open(
+        ctx: Context<SetRelayerRegistrationsOpen>,
+        open: bool,
+    ) -> Result<()> {
+        instructions::relayer::configure_registry::set_registrations_open_handler(ctx, open)
+    }
+
+    /// Create a relayer index shard (must exist before registrations land in it)
+    pub fn init_relayer_index_shard(
+        ctx: Context<InitRelayerIndexShard>,
+        shard_index: u32,
+    ) -> Result<()> {
+        instructions::relayer::relayer_index::init_shard_handler(ctx, shard_index)
+    }
+
+    /// Set the per-withdrawal gas rebate cap (lamports, 0 disables rebates)
+    /// and the most of a relayer's declared priority fee it counts
+    pub fn set_gas_rebate_cap(
+        ctx: Context<SetGasRebateCap>,
+        max_gas_rebate_lamports: u64,
+        max_priority_fee_hint: u64,
+    ) -> Result<()> {
+        instructions::relayer::gas_rebate::set_cap_handler(
+            ctx,
+            max_gas_rebate_lamports,
+            max_priority_fee_hint,
+        )
+    }
+
+    /// Open a gas rebate ledger for a registered relayer
+    pub fn init_relayer_rebate_ledger(
+        ctx: Context<InitRelayerRebateLedger>,
+        priority_fee_hint: u64,
+    ) -> Result<()> {
+        instructions::relayer::gas_rebate::init_ledger_handler(ctx, priority_fee_hint)
+    }
+
+    /// Update the priority fee a relayer declares per withdrawal
+    pub fn update_relayer_priority_fee_hint(
+        ctx: Context<UpdateRelayerPriorityFeeHint>,
+        priority_fee_hint: u64,
+    ) -> Result<()> {
+        instructions::relayer::gas_rebate::update_hint_handler(ctx, priority_fee_hint)
+    }
+
+    /// Open a relayer's fee quote (flat fee plus basis points, valid up to a slot)
+    pub fn init_relayer_fee_quote(
+        ctx: Context<InitRelayerFeeQuote>,
+        flat_fee: u64,
+        fee_bps: u16,
+        valid_until_slot: u64,
+    ) -> Result<()> {
+        instructions::relayer::fee_quote::init_handler(ctx, flat_fee, fee_bps, valid_until_slot)
+    }
+
+    /// Replace a relayer's fee quote
+    pub fn update_relayer_fee_quote(
+        ctx: Context<UpdateRelayerFeeQuote>,
+        flat_fee: u64,
+        fee_bps: u16,
+        valid_until_slot: u64,
+    ) -> Result<()> {
+        instructions::relayer::fee_quote::update_handler(ctx, flat_fee, fee_bps, valid_until_slot)
+    }
+
+    /// Claim accrued gas rebates from the pool treasury
+    pub fn claim_gas_rebate(ctx: Context<ClaimGasRebate>) -> Result<()> {
+        instructions::relayer::gas_rebate::claim_handler(ctx)
+    }
+
+    /// Admin: Create the pool's rent reserve, which advances nullifier rent
+    /// to relayers up to `max_outstanding_per_relayer` lamports each
+    pub fn init_rent_reserve(
+        ctx: Context<InitRentReserve>,
+        max_outstanding_per_relayer: u64,
+    ) -> Result<()> {
+        instructions::relayer::rent_reserve::init_handler(ctx, max_outstanding_per_relayer)
+    }
+
+    /// Admin: Set how many lamports a single relayer may owe the rent reserve
+    pub fn set_rent_advance_cap(
+        ctx: Context<SetRentAdvanceCap>,
+        max_outstanding_per_relayer: u64,
+    ) -> Result<()> {
+        instructions::relayer::rent_reserve::set_cap_handler(ctx, max_outstanding_per_relayer)
+    }
+
+    /// Add lamports to the pool's rent reserve
+    pub fn fund_rent_reserve(ctx: Context<FundRentReserve>, amount: u64) -> Result<()> {
+        instructions::relayer::rent_reserve::fund_handler(ctx, amount)
+    }
+
+    /// Open the account a registered relayer's rent advances accrue to
+    pub fn init_rent_advance(ctx: Context<InitRentAdvance>) -> Result<()> {
+        instructions::relayer::rent_reserve::init_advance_handler(ctx)
+    }
+
+    /// Repay rent the reserve advanced to a relayer
+    pub fn repay_rent_advance(ctx: Context<RepayRentAdvance>, amount: u64) -> Result<()> {
+        instructions::relayer::rent_reserve::repay_handler(ctx, amount)
+    }
+
+    /// Set absolute relayer fee bounds (token base units) for an asset
+    pub fn set_asset_relayer_fee_bounds(
+        ctx: Context<SetAssetRelayerFeeBounds>,
+        asset_id: [u8; 32],
+        min_relayer_fee: u64,
+        max_relayer_fee: u64,
+    ) -> Result<()> {
+        instructions::relayer::set_asset_fee_bounds::handler(
+            ctx,
+            asset_id,
+            min_relayer_fee,
+            max_relayer_fee,
+        )
+    }
+
+    /// Admin: Set the mint relayers stake (only while no stake is held)
+    pub fn set_relayer_stake_mint(ctx: Context<SetRelayerStakeMint>) -> Result<()> {
+        instructions::relayer::stake::set_mint_handler(ctx)
+    }
+
+    /// Create a relayer node's stake vault for the registry's stake mint
+    pub fn init_relayer_stake_vault(ctx: Context<InitRelayerStakeVault>) -> Result<()> {
+        instructions::relayer::stake::init_vault_handler(ctx)
+    }
+
+    /// Lock `amount` of the stake mint in the relayer node's stake vault
+    pub fn stake_relayer(ctx: Context<MoveRelayerStake>, amount: u64) -> Result<()> {
+        instructions::relayer::stake::stake_handler(ctx, amount)
+    }
+
+    /// Start unbonding `amount` of a relayer's stake
+    pub fn unstake_relayer(ctx: Context<UnstakeRelayer>, amount: u64) -> Result<()> {
+        instructions::relayer::stake::unstake_handler(ctx, amount)
+    }
+
+    /// Withdraw a relayer's unbonded stake once the unbonding period has passed
+    pub fn withdraw_relayer_stake(ctx: Context<MoveRelayerStake>) -> Result<()> {
+        instructions::relayer::stake::withdraw_handler(ctx)
+    }
+
+    /// Admin: Slash `slash_bps` of a relayer's stake into the treasury,
+    /// lower its reputation and optionally deactivate it
+    pub fn slash_relayer(
+        ctx: Context<SlashRelayer>,
+        slash_bps: u16,
+        reputation_penalty: u8,
+        deactivate: bool,
+        evidence_hash: [u8; 32],
+    ) -> Result<()> {
+        instructions::relayer::slash_relayer::handler(
+            ctx,
+            slash_bps,
+            reputation_penalty,
+            deactivate,
+            evidence_hash,
+        )
+    }
+
+    /// Admin: Record a relayer failure, lowering its reputation
+    pub fn report_relayer_failure(
+        ctx: Context<ReportRelayerFailure>,
+        evidence_hash: [u8; 32],
+    ) -> Result<()> {
+        instructions::relayer::report_relayer_failure::handler(ctx, evidence_hash)
+    }
+
+    /// Reserve a commitment for the signer's deposit, ahead of `deposit_masp`
+    pub fn reserve_commitment(ctx: Context<ReserveCommitment>, commitment: [u8; 32]) -> Result<()> {
+        instructions::commitment_reservation::reserve_handler(ctx, commitment)
+    }
+
+    /// Release a commitment reservation that is no longer needed
+    pub fn release_commitment_reservation(
+        ctx: Context<ReleaseCommitmentReservation>,
+    ) -> Result<()> {
+        instructions::commitment_reservation::release_handler(ctx)
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn deposit_masp(
         ctx: Context<DepositMasp>,
@@ -232,11 +1151,69 @@ pub mod psol_privacy_v2 {
         )
     }
 
-    pub fn batch_process_deposits(
-        ctx: Context<BatchProcessDeposits>,
+    /// Deposit several commitments of one asset with a single transfer.
+    ///
+    /// Each commitment has its own amount and deposit proof; the vault
+    /// receives their sum. Up to `MAX_DEPOSIT_COMMITMENTS` commitments, with
+    /// an optional encrypted note per commitment.
+    pub fn deposit_masp_batch(
+        ctx: Context<DepositMaspBatch>,
+        asset_id: [u8; 32],
+        amounts: Vec<u64>,
+        commitments: Vec<[u8; 32]>,
+        proofs: Vec<Vec<u8>>,
+        encrypted_notes: Option<Vec<Vec<u8>>>,
+    ) -> Result<()> {
+        instructions::deposit_masp_batch::handler(
+            ctx,
+            asset_id,
+            amounts,
+            commitments,
+            proofs,
+            encrypted_notes,
+        )
+    }
+
+    /// Process pending deposits into the Merkle tree.
+    ///
+    /// Returns the number of deposits processed (via return data).
+    pub fn batch_process_deposits<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchProcessDeposits<'info>>,
         max_to_process: u16,
+        target_cu_budget: Option<u32>,
+        expected_batch_seq: Option<u64>,
+    ) -> Result<u16> {
+        instructions::batch_process_deposits::handler(
+            ctx,
+            max_to_process,
+            target_cu_budget,
+            expected_batch_seq,
+        )
+    }
+
+    /// Register a bonded batcher (authority approves, batcher locks
+    /// `bond_lamports`)
+    pub fn register_batcher(ctx: Context<RegisterBatcher>, bond_lamports: u64) -> Result<()> {
+        instructions::batcher_bond::register_handler(ctx, bond_lamports)
+    }
+
+    /// Batcher: stop batching and start the unbonding period
+    pub fn request_batcher_exit(ctx: Context<ExitBatcher>) -> Result<()> {
+        instructions::batcher_bond::request_exit_handler(ctx)
+    }
+
+    /// Batcher: reclaim the bond after unbonding and close the role
+    pub fn withdraw_batcher_bond(ctx: Context<ExitBatcher>) -> Result<()> {
+        instructions::batcher_bond::withdraw_bond_handler(ctx)
+    }
+
+    /// Admin: Slash a batcher's bond to the insurance fund and revoke its
+    /// role; `evidence_hash` commits to the off-chain evidence
+    pub fn report_batcher_misbehavior(
+        ctx: Context<ReportBatcherMisbehavior>,
+        evidence_hash: [u8; 32],
     ) -> Result<()> {
-        instructions::batch_process_deposits::handler(ctx, max_to_process)
+        instructions::batcher_bond::report_handler(ctx, evidence_hash)
     }
 
     /// Settle a batch of deposits using off-chain ZK proof.
@@ -257,6 +1234,8 @@ pub mod psol_privacy_v2 {
     /// - Marks nullifier as spent to prevent double-spending
     /// - Enforces recipient_token_account.owner == recipient (from proof public inputs)
     /// - Enforces relayer_token_account.owner == relayer (from proof public inputs)
+    ///
+    /// Returns a `VerificationStatus` as return data for CPI callers.
     #[allow(clippy::too_many_arguments)]
     pub fn withdraw_masp(
         ctx: Context<WithdrawMasp>,
@@ -267,7 +1246,37 @@ pub mod psol_privacy_v2 {
         amount: u64,
         asset_id: [u8; 32],
         relayer_fee: u64,
-    ) -> Result<()> {
+    ) -> Result<VerificationStatus> {
+        instructions::withdraw_masp::handler(
+            ctx,
+            proof_data,
+            merkle_root,
+            nullifier_hash,
+            recipient,
+            amount,
+            asset_id,
+            relayer_fee,
+            0,
+            None,
+        )
+    }
+
+    /// `withdraw_masp` from the native SOL vault through a throwaway fee
+    /// payer: the `relayer` signer is refunded `fee_payer_refund` lamports
+    /// out of the withdrawal (bounded, and bound into the proof's
+    /// `public_data_hash`; see `withdraw_masp::fee_payer_refund_hash`).
+    #[allow(clippy::too_many_arguments)]
+    pub fn withdraw_with_fee_payer_refund(
+        ctx: Context<WithdrawMasp>,
+        proof_data: Vec<u8>,
+        merkle_root: [u8; 32],
+        nullifier_hash: [u8; 32],
+        recipient: Pubkey,
+        amount: u64,
+        asset_id: [u8; 32],
+        relayer_fee: u64,
+        fee_payer_refund: u64,
+    ) -> Result<VerificationStatus> {
         instructions::withdraw_masp::handler(
             ctx,
             proof_data,
@@ -277,9 +1286,168 @@ pub mod psol_privacy_v2 {
             amount,
             asset_id,
             relayer_fee,
+            fee_payer_refund,
+            None,
         )
     }
 
+    /// `withdraw_masp` pinned to the `ParameterSnapshot` version the client
+    /// displayed: fails if the asset's fees, limits or flags changed
+    /// adversely since (the version is bound into the proof's
+    /// `public_data_hash`; see `withdraw_masp::parameter_pin_hash`).
+    #[allow(clippy::too_many_arguments)]
+    pub fn withdraw_with_parameter_pin(
+        ctx: Context<WithdrawMasp>,
+        proof_data: Vec<u8>,
+        merkle_root: [u8; 32],
+        nullifier_hash: [u8; 32],
+        recipient: Pubkey,
+        amount: u64,
+        asset_id: [u8; 32],
+        relayer_fee: u64,
+        parameter_version: u64,
+    ) -> Result<VerificationStatus> {
+        instructions::withdraw_masp::handler(
+            ctx,
+            proof_data,
+            merkle_root,
+            nullifier_hash,
+            recipient,
+            amount,
+            asset_id,
+            relayer_fee,
+            0,
+            Some(parameter_version),
+        )
+    }
+
+    /// Seal a withdrawal's public inputs (`withdrawal_commit_hash`) ahead of
+    /// handing its proof to a relayer, which executes it with `withdraw_masp`
+    /// and the commit attached
+    pub fn commit_withdrawal(ctx: Context<CommitWithdrawal>, commit_hash: [u8; 32]) -> Result<()> {
+        instructions::withdrawal_commit::commit_handler(ctx, commit_hash)
+    }
+
+    /// Cancel a sealed withdrawal and reclaim its rent (committer only)
+    pub fn cancel_withdrawal_commit(ctx: Context<CancelWithdrawalCommit>) -> Result<()> {
+        instructions::withdrawal_commit::cancel_handler(ctx)
+    }
+
+    /// Commit to a disclosed-path withdrawal (`disclosed_withdraw_binding`
+    /// of the note and withdrawal terms) ahead of revealing it
+    pub fn commit_disclosed_withdraw(
+        ctx: Context<CommitDisclosedWithdraw>,
+        binding: [u8; 32],
+    ) -> Result<()> {
+        instructions::disclosed_path_withdraw::commit_handler(ctx, binding)
+    }
+
+    /// Withdraw a note by disclosing its opening and Merkle path, checked
+    /// on-chain instead of a withdraw proof (REDUCED PRIVACY: links the
+    /// withdrawal to its deposit)
+    pub fn disclosed_path_withdraw(
+        ctx: Context<DisclosedPathWithdraw>,
+        binding: [u8; 32],
+        nullifier_hash: [u8; 32],
+        asset_id: [u8; 32],
+        recipient: Pubkey,
+        relayer_fee: u64,
+        note: DisclosedNote,
+    ) -> Result<()> {
+        instructions::disclosed_path_withdraw::handler(
+            ctx,
+            binding,
+            nullifier_hash,
+            asset_id,
+            recipient,
+            relayer_fee,
+            note,
+        )
+    }
+
+    /// Create the token account of a one-time withdrawal owner and announce
+    /// its ephemeral key; sent ahead of `withdraw_masp` to that owner.
+    pub fn create_one_time_recipient(
+        ctx: Context<CreateOneTimeRecipient>,
+        asset_id: [u8; 32],
+        ephemeral_pubkey: [u8; 32],
+        view_tag: u8,
+    ) -> Result<()> {
+        instructions::create_one_time_recipient::handler(ctx, asset_id, ephemeral_pubkey, view_tag)
+    }
+
+    /// Admin: Create the deferred withdraw event queue; withdrawals passing
+    /// it hold their event back `delay_seconds` (0 = emit immediately)
+    pub fn init_deferred_withdraw_events(
+        ctx: Context<InitDeferredWithdrawEvents>,
+        delay_seconds: u32,
+    ) -> Result<()> {
+        instructions::deferred_withdraw_events::init_handler(ctx, delay_seconds)
+    }
+
+    /// Admin: Change the withdraw event delay (max 1 hour)
+    pub fn set_withdraw_event_delay(
+        ctx: Context<SetWithdrawEventDelay>,
+        delay_seconds: u32,
+    ) -> Result<()> {
+        instructions::deferred_withdraw_events::set_delay_handler(ctx, delay_seconds)
+    }
+
+    /// Crank: Emit up to `max_events` released withdraw events
+    /// (permissionless; returns the number emitted)
+    pub fn flush_deferred_withdraw_events(
+        ctx: Context<FlushDeferredWithdrawEvents>,
+        max_events: u8,
+    ) -> Result<u8> {
+        instructions::deferred_withdraw_events::flush_handler(ctx, max_events)
+    }
+
+    /// Admin: Register a program that `batch_process_deposits` calls for
+    /// every commitment it inserts
+    pub fn register_deposit_hook(ctx: Context<RegisterDepositHook>) -> Result<()> {
+        instructions::deposit_hook::register_handler(ctx)
+    }
+
+    /// Admin: Remove the deposit hook (rent returns to the authority)
+    pub fn remove_deposit_hook(ctx: Context<RemoveDepositHook>) -> Result<()> {
+        instructions::deposit_hook::remove_handler(ctx)
+    }
+
+    /// Estimate how well a withdrawal against `merkle_root` would hide
+    /// (read-only; simulate and read the return data).
+    pub fn estimate_privacy_score(
+        ctx: Context<EstimatePrivacyScore>,
+        merkle_root: [u8; 32],
+        asset_id: [u8; 32],
+    ) -> Result<PrivacyScore> {
+        instructions::estimate_privacy_score::handler(ctx, merkle_root, asset_id)
+    }
+
+    /// Recompute a pool verification key's, Merkle tree's or pending
+    /// deposits buffer's internal consistency, emit the result and return
+    /// the failed checks (permissionless health probe)
+    pub fn verify_account_integrity(ctx: Context<VerifyAccountIntegrity>) -> Result<u32> {
+        instructions::verify_account_integrity::handler(ctx)
+    }
+
+    /// Admin: Publish the current Merkle root and leaf count as an
+    /// authority-signed `AttestedRootRecord` (preceded by an ed25519
+    /// verification of the attestation message)
+    pub fn publish_attested_root(ctx: Context<PublishAttestedRoot>, root: [u8; 32]) -> Result<()> {
+        instructions::publish_attested_root::handler(ctx, root)
+    }
+
+    /// Record the spent note's receipt in a nullifier record, for wallet
+    /// sync (signed by the relayer that spent it; refused by
+    /// privacy-strict pools)
+    pub fn record_note_receipt(
+        ctx: Context<RecordNoteReceipt>,
+        nullifier_hash: [u8; 32],
+        note_receipt: [u8; 8],
+    ) -> Result<()> {
+        instructions::record_note_receipt::handler(ctx, nullifier_hash, note_receipt)
+    }
+
     /// Withdraw V2 (join-split with change output)
     #[allow(clippy::too_many_arguments)]
     pub fn withdraw_v2(
@@ -293,7 +1461,7 @@ pub mod psol_privacy_v2 {
         recipient: Pubkey,
         amount: u64,
         relayer_fee: u64,
-    ) -> Result<()> {
+    ) -> Result<VerificationStatus> {
         instructions::withdraw_v2::handler(
             ctx,
             proof_data,
@@ -308,6 +1476,77 @@ pub mod psol_privacy_v2 {
         )
     }
 
+    /// Private transfer (join-split): spend up to two notes into up to two
+    /// new ones, with an optional public inflow from the relayer's token
+    /// account or outflow to the proof's `recipient`. Spent nullifier PDAs
+    /// follow as remaining accounts, one per input.
+    #[allow(clippy::too_many_arguments)]
+    pub fn private_transfer_join_split<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PrivateTransferJoinSplit<'info>>,
+        proof_data: Vec<u8>,
+        merkle_root: [u8; 32],
+        input_nullifiers: Vec<[u8; 32]>,
+        output_commitments: Vec<[u8; 32]>,
+        public_amount: i64,
+        recipient: Pubkey,
+        asset_id: [u8; 32],
+        relayer_fee: u64,
+        encrypted_outputs: Option<Vec<Vec<u8>>>,
+    ) -> Result<()> {
+        instructions::private_transfer::handler(
+            ctx,
+            proof_data,
+            merkle_root,
+            input_nullifiers,
+            output_commitments,
+            public_amount,
+            recipient,
+            asset_id,
+            relayer_fee,
+            encrypted_outputs,
+        )
+    }
+
+    /// Admin: create the (empty) allowlist of programs `action_type`
+    /// actions may target
+    pub fn init_action_allowlist(
+        ctx: Context<InitActionAllowlist>,
+        action_type: ShieldedActionType,
+    ) -> Result<()> {
+        instructions::shielded_cpi::action_allowlist::init_handler(ctx, action_type)
+    }
+
+    /// Admin: let the allowlist's action type target `program`
+    pub fn add_action_program(ctx: Context<ManageActionAllowlist>, program: Pubkey) -> Result<()> {
+        instructions::shielded_cpi::action_allowlist::add_handler(ctx, program)
+    }
+
+    /// Admin: remove `program` from an action allowlist
+    pub fn remove_action_program(
+        ctx: Context<ManageActionAllowlist>,
+        program: Pubkey,
+    ) -> Result<()> {
+        instructions::shielded_cpi::action_allowlist::remove_handler(ctx, program)
+    }
+
+    /// Spend a note into an external protocol through the action type's
+    /// adapter and shield the output as a new note. `action_data` is a
+    /// `ShieldedSpend` followed by the adapter's parameters; the adapter's
+    /// accounts follow as remaining accounts.
+    pub fn execute_shielded_action<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteShieldedAction<'info>>,
+        action_type: ShieldedActionType,
+        proof_data: Vec<u8>,
+        action_data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::shielded_cpi::execute_action::handler(
+            ctx,
+            action_type,
+            proof_data,
+            action_data,
+        )
+    }
+
     /// Withdraw Yield V2 - Yield Mode with 5% performance fee
     ///
     /// Gated by yield_relayer signer for fee enforcement on positive yield
@@ -323,7 +1562,7 @@ pub mod psol_privacy_v2 {
         recipient: Pubkey,
         amount: u64,
         relayer_fee: u64,
-    ) -> Result<()> {
+    ) -> Result<VerificationStatus> {
         instructions::withdraw_yield_v2::handler(
             ctx,
             proof_data,
@@ -353,6 +1592,16 @@ pub mod psol_privacy_v2 {
         instructions::manage_yield_mints::remove_yield_mint(ctx, mint)
     }
 
+    /// Initialize per-proof-type verification counters
+    pub fn init_proof_stats(ctx: Context<InitProofStats>) -> Result<()> {
+        instructions::init_proof_stats::handler(ctx)
+    }
+
+    /// Initialize per-asset deposit-to-withdrawal latency statistics
+    pub fn init_anonymity_stats(ctx: Context<InitAnonymityStats>) -> Result<()> {
+        instructions::init_anonymity_stats::handler(ctx)
+    }
+
     /// Enable a feature flag (authority only)
     pub fn enable_feature(ctx: Context<SetFeatureFlags>, feature: u8) -> Result<()> {
         instructions::set_feature_flags::enable_feature(ctx, feature)
@@ -362,7 +1611,6 @@ pub mod psol_privacy_v2 {
     pub fn disable_feature(ctx: Context<SetFeatureFlags>, feature: u8) -> Result<()> {
         instructions::set_feature_flags::disable_feature(ctx, feature)
     }
-
 }
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
 pub enum ProofType {
@@ -0,0 +1,661 @@
+//! Cold-start pool recovery (host-side reference)
+//!
+//! Cross-validates a pool's accounts against its event history, for
+//! incident response when nothing off-chain (indexer, sequencer database)
+//! can be trusted any more. The caller fetches a [`PoolSnapshot`] and the
+//! pool's events (see `crates/psol-recover`); [`cross_validate`] replays
+//! the events and reports every place chain state and history disagree:
+//!
+//! - tree vs events: inserted leaves must be contiguous, match the tree's
+//!   leaf count and rebuild its current root
+//! - vault balances vs counters: the token account must hold at least the
+//!   vault's `shielded_balance`, and (without coarse stats) the lifetime
+//!   totals must net to it
+//! - nullifier PDAs vs withdraw events: every withdrawn nullifier has its
+//!   `SpentNullifierV2` PDA at the derived address, and every withdraw PDA
+//!   has an event
+//!
+//! A tree reset restarts leaf indices at zero; only the leaves inserted
+//! since the last restart are compared with the tree. Withdrawals whose
+//! events are still in the deferred event queue show up as
+//! `NullifierWithoutEvent` until the queue is flushed.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::crypto::precomputed_zeros::get_precomputed_zeros;
+use crate::events::{
+    CommitmentInsertedEvent, DepositMaspEvent, WithdrawMaspEvent, WithdrawV2Event,
+};
+use crate::state::{AssetVault, MerkleTreeV2, PoolConfigV2, SpendType, SpentNullifierV2};
+use crate::witness::merkle_path;
+
+/// A pool event relevant to recovery, decoded from `Program data:` bytes
+pub enum PoolEvent {
+    CommitmentInserted(CommitmentInsertedEvent),
+    DepositMasp(DepositMaspEvent),
+    WithdrawMasp(WithdrawMaspEvent),
+    WithdrawV2(WithdrawV2Event),
+}
+
+impl PoolEvent {
+    /// Decode an emitted event (discriminator followed by Borsh data);
+    /// `None` for events recovery does not use
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        fn parse<E: Discriminator + AnchorDeserialize>(data: &[u8]) -> Option<E> {
+            let mut body = data.strip_prefix(E::DISCRIMINATOR)?;
+            E::deserialize(&mut body).ok()
+        }
+
+        parse(data)
+            .map(PoolEvent::CommitmentInserted)
+            .or_else(|| parse(data).map(PoolEvent::DepositMasp))
+            .or_else(|| parse(data).map(PoolEvent::WithdrawMasp))
+            .or_else(|| parse(data).map(PoolEvent::WithdrawV2))
+    }
+
+    pub fn pool(&self) -> Pubkey {
+        match self {
+            PoolEvent::CommitmentInserted(e) => e.pool,
+            PoolEvent::DepositMasp(e) => e.pool,
+            PoolEvent::WithdrawMasp(e) => e.pool,
+            PoolEvent::WithdrawV2(e) => e.pool,
+        }
+    }
+
+    /// `(commitment, leaf_index)` if this event inserted a leaf
+    fn inserted_leaf(&self) -> Option<([u8; 32], u32)> {
+        match self {
+            PoolEvent::CommitmentInserted(e) => Some((e.commitment, e.leaf_index)),
+            PoolEvent::DepositMasp(e) => Some((e.commitment, e.leaf_index)),
+            _ => None,
+        }
+    }
+
+    /// Nullifier hashes this event spent through a withdrawal
+    fn withdrawn_nullifiers(&self) -> Vec<[u8; 32]> {
+        match self {
+            PoolEvent::WithdrawMasp(e) => vec![e.nullifier_hash],
+            PoolEvent::WithdrawV2(e) => [e.nullifier_hash_0, e.nullifier_hash_1]
+                .into_iter()
+                .filter(|n| *n != [0u8; 32])
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// An asset vault and the balance of its token account
+#[derive(Clone)]
+pub struct VaultSnapshot {
+    pub key: Pubkey,
+    pub vault: AssetVault,
+    /// `None` if the token account does not exist
+    pub token_balance: Option<u64>,
+}
+
+/// Every pool account recovery checks, as fetched from chain
+#[derive(Clone)]
+pub struct PoolSnapshot {
+    pub program_id: Pubkey,
+    pub pool: Pubkey,
+    pub config: PoolConfigV2,
+    pub tree: MerkleTreeV2,
+    pub vaults: Vec<VaultSnapshot>,
+    pub nullifiers: Vec<(Pubkey, SpentNullifierV2)>,
+}
+
+/// One disagreement between chain state and event history
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Divergence {
+    /// An account's `pool` field names another pool
+    ForeignAccount { account: Pubkey, what: &'static str },
+    /// An account is not at the PDA its contents derive
+    AddressMismatch { account: Pubkey, expected: Pubkey },
+    /// Insert events skip or repeat a leaf index
+    LeafGap { expected: u32, found: u32 },
+    /// Tree leaf count differs from the replayed inserts
+    LeafCountMismatch { tree: u32, replayed: u32 },
+    /// Root rebuilt from the replayed leaves differs from the tree's
+    RootMismatch { tree: [u8; 32], rebuilt: [u8; 32] },
+    /// Vault token account is missing
+    MissingTokenAccount { vault: Pubkey },
+    /// Token account holds less than the vault owes
+    VaultShortfall {
+        vault: Pubkey,
+        shielded_balance: u64,
+        token_balance: u64,
+    },
+    /// Lifetime totals do not net to the shielded balance
+    VaultCounterMismatch {
+        vault: Pubkey,
+        total_deposited: u64,
+        total_withdrawn: u64,
+        shielded_balance: u64,
+    },
+    /// A withdraw event's nullifier has no `SpentNullifierV2` account
+    MissingNullifierAccount { nullifier_hash: [u8; 32] },
+    /// A withdraw `SpentNullifierV2` account has no withdraw event
+    NullifierWithoutEvent {
+        account: Pubkey,
+        nullifier_hash: [u8; 32],
+    },
+    /// Pool withdrawal counter differs from the replayed withdrawals
+    WithdrawalCountMismatch { pool_counter: u64, replayed: u64 },
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Divergence::ForeignAccount { account, what } => {
+                write!(f, "{what} {account} belongs to another pool")
+            }
+            Divergence::AddressMismatch { account, expected } => {
+                write!(f, "account {account} should be at {expected}")
+            }
+            Divergence::LeafGap { expected, found } => {
+                write!(f, "leaf {found} inserted where {expected} was next")
+            }
+            Divergence::LeafCountMismatch { tree, replayed } => {
+                write!(f, "tree holds {tree} leaves, events insert {replayed}")
+            }
+            Divergence::RootMismatch { tree, rebuilt } => write!(
+                f,
+                "tree root {} but events rebuild {}",
+                hex::encode(tree),
+                hex::encode(rebuilt)
+            ),
+            Divergence::MissingTokenAccount { vault } => {
+                write!(f, "vault {vault} has no token account")
+            }
+            Divergence::VaultShortfall {
+                vault,
+                shielded_balance,
+                token_balance,
+            } => write!(
+                f,
+                "vault {vault} owes {shielded_balance} but holds {token_balance}"
+            ),
+            Divergence::VaultCounterMismatch {
+                vault,
+                total_deposited,
+                total_withdrawn,
+                shielded_balance,
+            } => write!(
+                f,
+                "vault {vault} deposited {total_deposited} - withdrawn {total_withdrawn} != balance {shielded_balance}"
+            ),
+            Divergence::MissingNullifierAccount { nullifier_hash } => write!(
+                f,
+                "nullifier {} withdrawn without a spent record",
+                hex::encode(nullifier_hash)
+            ),
+            Divergence::NullifierWithoutEvent {
+                account,
+                nullifier_hash,
+            } => write!(
+                f,
+                "spent record {account} ({}) has no withdraw event",
+                hex::encode(nullifier_hash)
+            ),
+            Divergence::WithdrawalCountMismatch {
+                pool_counter,
+                replayed,
+            } => write!(
+                f,
+                "pool counts {pool_counter} withdrawals, events show {replayed}"
+            ),
+        }
+    }
+}
+
+/// Result of [`cross_validate`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DivergenceReport {
+    /// Leaves replayed since the last tree restart
+    pub leaves_replayed: u32,
+    /// Withdraw events replayed
+    pub withdrawals_replayed: u64,
+    pub divergences: Vec<Divergence>,
+}
+
+impl DivergenceReport {
+    pub fn is_consistent(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+impl fmt::Display for DivergenceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "replayed {} leaves and {} withdrawals: {} divergence(s)",
+            self.leaves_replayed,
+            self.withdrawals_replayed,
+            self.divergences.len()
+        )?;
+        for divergence in &self.divergences {
+            writeln!(f, "  - {divergence}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Replay `events` (in chain order) against `snapshot` and report every
+/// divergence. Events of other pools are ignored.
+pub fn cross_validate(snapshot: &PoolSnapshot, events: &[PoolEvent]) -> Result<DivergenceReport> {
+    let mut report = DivergenceReport::default();
+    let events: Vec<&PoolEvent> = events
+        .iter()
+        .filter(|e| e.pool() == snapshot.pool)
+        .collect();
+
+    check_tree(snapshot, &events, &mut report)?;
+    check_vaults(snapshot, &mut report);
+    check_nullifiers(snapshot, &events, &mut report);
+
+    Ok(report)
+}
+
+fn check_tree(
+    snapshot: &PoolSnapshot,
+    events: &[&PoolEvent],
+    report: &mut DivergenceReport,
+) -> Result<()> {
+    let tree = &snapshot.tree;
+    if tree.pool != snapshot.pool {
+        report.divergences.push(Divergence::ForeignAccount {
+            account: snapshot.config.merkle_tree,
+            what: "merkle tree",
+        });
+    }
+
+    let mut leaves: Vec<[u8; 32]> = Vec::new();
+    for (commitment, leaf_index) in events.iter().filter_map(|e| e.inserted_leaf()) {
+        // A settled batch and its per-deposit event may both report a leaf
+        if leaves.get(leaf_index as usize) == Some(&commitment) {
+            continue;
+        }
+        if leaf_index == 0 {
+            // Tree reset
+            leaves.clear();
+        } else if leaf_index as usize != leaves.len() {
+            report.divergences.push(Divergence::LeafGap {
+                expected: leaves.len() as u32,
+                found: leaf_index,
+            });
+            continue;
+        }
+        leaves.push(commitment);
+    }
+    report.leaves_replayed = leaves.len() as u32;
+
+    if report.leaves_replayed != tree.next_leaf_index {
+        report.divergences.push(Divergence::LeafCountMismatch {
+            tree: tree.next_leaf_index,
+            replayed: report.leaves_replayed,
+        });
+        return Ok(());
+    }
+
+    let rebuilt = if leaves.is_empty() {
+        get_precomputed_zeros(tree.depth)[tree.depth as usize]
+    } else {
+        merkle_path(&leaves, 0, tree.depth)?.root
+    };
+    if rebuilt != tree.current_root {
+        report.divergences.push(Divergence::RootMismatch {
+            tree: tree.current_root,
+            rebuilt,
+        });
+    }
+    Ok(())
+}
+
+fn check_vaults(snapshot: &PoolSnapshot, report: &mut DivergenceReport) {
+    let exact_totals = !snapshot.config.has_coarse_liquidity_stats();
+
+    for VaultSnapshot {
+        key,
+        vault,
+        token_balance,
+    } in &snapshot.vaults
+    {
+        if vault.pool != snapshot.pool {
+            report.divergences.push(Divergence::ForeignAccount {
+                account: *key,
+                what: "asset vault",
+            });
+            continue;
+        }
+        let (expected, _) =
+            AssetVault::find_pda(&snapshot.program_id, &snapshot.pool, &vault.asset_id);
+        if *key != expected {
+            report.divergences.push(Divergence::AddressMismatch {
+                account: *key,
+                expected,
+            });
+        }
+
+        match token_balance {
+            None => report
+                .divergences
+                .push(Divergence::MissingTokenAccount { vault: *key }),
+            Some(balance) if *balance < vault.shielded_balance => {
+                report.divergences.push(Divergence::VaultShortfall {
+                    vault: *key,
+                    shielded_balance: vault.shielded_balance,
+                    token_balance: *balance,
+                })
+            }
+            Some(_) => {}
+        }
+
+        if exact_totals
+            && vault.total_deposited.checked_sub(vault.total_withdrawn)
+                != Some(vault.shielded_balance)
+        {
+            report.divergences.push(Divergence::VaultCounterMismatch {
+                vault: *key,
+                total_deposited: vault.total_deposited,
+                total_withdrawn: vault.total_withdrawn,
+                shielded_balance: vault.shielded_balance,
+            });
+        }
+    }
+}
+
+fn check_nullifiers(snapshot: &PoolSnapshot, events: &[&PoolEvent], report: &mut DivergenceReport) {
+    let mut records: HashMap<[u8; 32], Pubkey> = HashMap::new();
+    for (key, record) in &snapshot.nullifiers {
+        if record.pool != snapshot.pool {
+            report.divergences.push(Divergence::ForeignAccount {
+                account: *key,
+                what: "spent nullifier",
+            });
+            continue;
+        }
+        let (expected, _) = SpentNullifierV2::find_pda(
+            &snapshot.program_id,
+            &snapshot.pool,
+            &record.nullifier_hash,
+        );
+        if *key != expected {
+            report.divergences.push(Divergence::AddressMismatch {
+                account: *key,
+                expected,
+            });
+        }
+        if record.get_spend_type() == Some(SpendType::Withdraw) {
+            records.insert(record.nullifier_hash, *key);
+        }
+    }
+
+    let mut withdrawn: HashSet<[u8; 32]> = HashSet::new();
+    for event in events {
+        let nullifiers = event.withdrawn_nullifiers();
+        if nullifiers.is_empty() {
+            continue;
+        }
+        report.withdrawals_replayed += 1;
+        for nullifier_hash in nullifiers {
+            if !records.contains_key(&nullifier_hash) {
+                report
+                    .divergences
+                    .push(Divergence::MissingNullifierAccount { nullifier_hash });
+            }
+            withdrawn.insert(nullifier_hash);
+        }
+    }
+
+    let mut orphans: Vec<_> = records
+        .into_iter()
+        .filter(|(nullifier_hash, _)| !withdrawn.contains(nullifier_hash))
+        .collect();
+    orphans.sort();
+    for (nullifier_hash, account) in orphans {
+        report.divergences.push(Divergence::NullifierWithoutEvent {
+            account,
+            nullifier_hash,
+        });
+    }
+
+    if snapshot.config.total_withdrawals != report.withdrawals_replayed {
+        report
+            .divergences
+            .push(Divergence::WithdrawalCountMismatch {
+                pool_counter: snapshot.config.total_withdrawals,
+                replayed: report.withdrawals_replayed,
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Event;
+
+    const PROGRAM_ID: Pubkey = crate::ID;
+
+    /// All-zero account contents, fields set by each test
+    fn zeroed<T: AnchorDeserialize>() -> T {
+        T::deserialize(&mut &[0u8; 1024][..]).unwrap()
+    }
+
+    fn leaf(i: u8) -> [u8; 32] {
+        let mut leaf = [0u8; 32];
+        leaf[31] = i + 1;
+        leaf
+    }
+
+    fn inserted(pool: Pubkey, commitment: [u8; 32], leaf_index: u32) -> PoolEvent {
+        PoolEvent::CommitmentInserted(CommitmentInsertedEvent {
+            pool,
+            commitment,
+            leaf_index,
+            merkle_root: [0u8; 32],
+            timestamp: 0,
+        })
+    }
+
+    fn withdrawn(pool: Pubkey, nullifier_hash: [u8; 32]) -> PoolEvent {
+        PoolEvent::WithdrawMasp(WithdrawMaspEvent {
+            pool,
+            nullifier_hash,
+            asset_id: [9u8; 32],
+            relayer: Pubkey::default(),
+            relayer_fee: 0,
+            sequence: 0,
+            timestamp: 0,
+        })
+    }
+
+    /// Three leaves, one vault holding 200, one withdrawal
+    fn consistent_pool() -> (PoolSnapshot, Vec<PoolEvent>) {
+        let pool = Pubkey::new_unique();
+
+        let mut tree: MerkleTreeV2 = zeroed();
+        tree.initialize(pool, 4, 30).unwrap();
+        for i in 0..3 {
+            tree.insert_leaf(leaf(i), 0).unwrap();
+        }
+
+        let mut config: PoolConfigV2 = zeroed();
+        config.total_withdrawals = 1;
+
+        let mut vault: AssetVault = zeroed();
+        vault.pool = pool;
+        vault.asset_id = [9u8; 32];
+        vault.total_deposited = 300;
+        vault.total_withdrawn = 100;
+        vault.shielded_balance = 200;
+        let (vault_key, _) = AssetVault::find_pda(&PROGRAM_ID, &pool, &vault.asset_id);
+
+        let mut record: SpentNullifierV2 = zeroed();
+        record.pool = pool;
+        record.nullifier_hash = [7u8; 32];
+        let (record_key, _) = SpentNullifierV2::find_pda(&PROGRAM_ID, &pool, &[7u8; 32]);
+
+        let snapshot = PoolSnapshot {
+            program_id: PROGRAM_ID,
+            pool,
+            config,
+            tree,
+            vaults: vec![VaultSnapshot {
+                key: vault_key,
+                vault,
+                token_balance: Some(200),
+            }],
+            nullifiers: vec![(record_key, record)],
+        };
+        let events = vec![
+            inserted(pool, leaf(0), 0),
+            inserted(pool, leaf(1), 1),
+            withdrawn(pool, [7u8; 32]),
+            inserted(pool, leaf(2), 2),
+            // Other pools' events are ignored
+            inserted(Pubkey::new_unique(), leaf(5), 3),
+        ];
+        (snapshot, events)
+    }
+
+    #[test]
+    fn test_consistent_pool_has_no_divergences() {
+        let (snapshot, events) = consistent_pool();
+        let report = cross_validate(&snapshot, &events).unwrap();
+        assert!(report.is_consistent(), "{report}");
+        assert_eq!(report.leaves_replayed, 3);
+        assert_eq!(report.withdrawals_replayed, 1);
+    }
+
+    #[test]
+    fn test_reports_each_divergence() {
+        let (mut snapshot, mut events) = consistent_pool();
+        let pool = snapshot.pool;
+
+        // Leaf 1 missing from history
+        events.remove(1);
+        // Vault lost tokens
+        snapshot.vaults[0].token_balance = Some(150);
+        // A withdrawal whose record is missing, and a record without event
+        events.push(withdrawn(pool, [8u8; 32]));
+        let mut stray: SpentNullifierV2 = zeroed();
+        stray.pool = pool;
+        stray.nullifier_hash = [6u8; 32];
+        let (stray_key, _) = SpentNullifierV2::find_pda(&PROGRAM_ID, &pool, &[6u8; 32]);
+        snapshot.nullifiers.push((stray_key, stray));
+
+        let report = cross_validate(&snapshot, &events).unwrap();
+        let vault = snapshot.vaults[0].key;
+        for expected in [
+            Divergence::LeafGap {
+                expected: 1,
+                found: 2,
+            },
+            Divergence::LeafCountMismatch {
+                tree: 3,
+                replayed: 1,
+            },
+            Divergence::VaultShortfall {
+                vault,
+                shielded_balance: 200,
+                token_balance: 150,
+            },
+            Divergence::MissingNullifierAccount {
+                nullifier_hash: [8u8; 32],
+            },
+            Divergence::NullifierWithoutEvent {
+                account: stray_key,
+                nullifier_hash: [6u8; 32],
+            },
+            Divergence::WithdrawalCountMismatch {
+                pool_counter: 1,
+                replayed: 2,
+            },
+        ] {
+            assert!(
+                report.divergences.contains(&expected),
+                "missing {expected:?} in {report}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_root_and_address_mismatches() {
+        let (mut snapshot, events) = consistent_pool();
+        snapshot.tree.current_root = [1u8; 32];
+        snapshot.vaults[0].key = Pubkey::new_unique();
+        snapshot.vaults[0].vault.total_withdrawn = 50;
+
+        let report = cross_validate(&snapshot, &events).unwrap();
+        assert!(report
+            .divergences
+            .iter()
+            .any(|d| matches!(d, Divergence::RootMismatch { .. })));
+        assert!(report
+            .divergences
+            .iter()
+            .any(|d| matches!(d, Divergence::AddressMismatch { .. })));
+        assert!(report
+            .divergences
+            .iter()
+            .any(|d| matches!(d, Divergence::VaultCounterMismatch { .. })));
+
+        // Coarse stats bucket the totals, so they are not compared
+        snapshot.config.feature_flags = PoolConfigV2::FEATURE_COARSE_LIQUIDITY_STATS;
+        let report = cross_validate(&snapshot, &events).unwrap();
+        assert!(!report
+            .divergences
+            .iter()
+            .any(|d| matches!(d, Divergence::VaultCounterMismatch { .. })));
+    }
+
+    #[test]
+    fn test_tree_reset_restarts_replay() {
+        let (mut snapshot, mut events) = consistent_pool();
+        let pool = snapshot.pool;
+        events.push(inserted(pool, leaf(10), 0));
+
+        let mut tree: MerkleTreeV2 = zeroed();
+        tree.initialize(pool, 4, 30).unwrap();
+        tree.insert_leaf(leaf(10), 0).unwrap();
+        snapshot.tree = tree;
+
+        let report = cross_validate(&snapshot, &events).unwrap();
+        assert!(report.is_consistent(), "{report}");
+        assert_eq!(report.leaves_replayed, 1);
+    }
+
+    #[test]
+    fn test_decode_emitted_events() {
+        let pool = Pubkey::new_unique();
+        let event = CommitmentInsertedEvent {
+            pool,
+            commitment: leaf(0),
+            leaf_index: 4,
+            merkle_root: [2u8; 32],
+            timestamp: 5,
+        };
+        let decoded = PoolEvent::decode(&event.data()).unwrap();
+        assert_eq!(decoded.pool(), pool);
+        assert_eq!(decoded.inserted_leaf(), Some((leaf(0), 4)));
+
+        let event = WithdrawMaspEvent {
+            pool,
+            nullifier_hash: [7u8; 32],
+            asset_id: [9u8; 32],
+            relayer: Pubkey::default(),
+            relayer_fee: 0,
+            sequence: 1,
+            timestamp: 0,
+        };
+        let decoded = PoolEvent::decode(&event.data()).unwrap();
+        assert_eq!(decoded.withdrawn_nullifiers(), vec![[7u8; 32]]);
+        assert_eq!(decoded.inserted_leaf(), None);
+
+        assert!(PoolEvent::decode(&[0u8; 16]).is_none());
+    }
+}
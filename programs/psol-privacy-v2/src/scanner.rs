@@ -0,0 +1,701 @@
+//! Wallet note scanning (host-side reference)
+//!
+//! The program never sees note plaintexts: `deposit_masp` takes an optional
+//! `encrypted_note` argument it does not store, and events only carry
+//! commitments, leaf indices and nullifier hashes. A wallet rebuilds its
+//! notes from those pieces:
+//!
+//! 1. trial-decrypt every note ciphertext with its viewing key, rejecting
+//!    most foreign notes on the one-byte view tag before doing any more work
+//! 2. keep a note only if it opens its commitment under the program's
+//!    `compute_commitment`
+//! 3. learn the leaf index from `DepositMaspEvent` / `CommitmentInsertedEvent`
+//!    / `JoinSplitEvent`, which fixes the nullifier hash
+//!    (`compute_nullifier_hash(nullifier, secret, leaf_index)`)
+//! 4. drop notes whose nullifier hash shows up in a withdrawal or join-split
+//!
+//! A spent note is also recognized by its nullification receipt (the
+//! envelope prefix a relayer may record in the spent nullifier's record,
+//! see `note_receipt`), which needs no leaf index or nullifier hash.
+//!
+//! Inputs may arrive in any order; the scanner reconciles them as they come.
+//!
+//! # Note envelope (v1)
+//!
+//! `version (1) ‖ view_tag (1) ‖ ephemeral (32, compressed G1) ‖
+//! ciphertext (104) ‖ mac (16)`, where the shared secret is BN254 G1 ECDH
+//! between the sender's ephemeral key and the recipient's viewing key, and
+//! the cipher is a keccak256 keystream with a keccak256 MAC.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::crypto::{
+    compute_commitment, compute_nullifier_hash, g1_compress, g1_decompress, g1_mul,
+    keccak256_concat, G1_GENERATOR,
+};
+use crate::error::PrivacyErrorV2;
+use crate::events::{
+    CommitmentInsertedEvent, DepositMaspEvent, JoinSplitEvent, NoteReceiptRecorded,
+    WithdrawMaspEvent, WithdrawV2Event,
+};
+use crate::instruction;
+use crate::state::{SpentNullifierV2, NOTE_RECEIPT_LEN};
+
+pub const NOTE_ENVELOPE_VERSION: u8 = 1;
+pub const NOTE_PLAINTEXT_LEN: usize = 32 + 32 + 8 + 32;
+const MAC_LEN: usize = 16;
+pub const NOTE_ENVELOPE_LEN: usize = 1 + 1 + 32 + NOTE_PLAINTEXT_LEN + MAC_LEN;
+
+/// Note contents; `commitment()` is the leaf the program inserts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NotePlaintext {
+    pub secret: [u8; 32],
+    pub nullifier: [u8; 32],
+    pub amount: u64,
+    pub asset_id: [u8; 32],
+}
+
+impl NotePlaintext {
+    pub fn commitment(&self) -> Result<[u8; 32]> {
+        compute_commitment(&self.secret, &self.nullifier, self.amount, &self.asset_id)
+    }
+
+    pub fn nullifier_hash(&self, leaf_index: u32) -> Result<[u8; 32]> {
+        compute_nullifier_hash(&self.nullifier, &self.secret, leaf_index)
+    }
+}
+
+/// Canonical non-zero BN254 scalar from arbitrary seed bytes
+fn scalar_from_seed(domain: &[u8], seed: &[u8]) -> [u8; 32] {
+    let mut counter = 0u8;
+    loop {
+        let mut scalar = keccak256_concat(&[domain, seed, &[counter]]);
+        // Below the scalar modulus (0x30...)
+        scalar[0] &= 0x1f;
+        if scalar != [0u8; 32] {
+            return scalar;
+        }
+        counter += 1;
+    }
+}
+
+/// Keystream/MAC key and view tag from the ECDH shared point
+struct SharedKeys {
+    key: [u8; 32],
+    view_tag: u8,
+}
+
+impl SharedKeys {
+    fn derive(shared: &[u8; 64]) -> Self {
+        Self {
+            key: keccak256_concat(&[b"psol:note:key:v1", shared]),
+            view_tag: keccak256_concat(&[b"psol:note:tag:v1", shared])[0],
+        }
+    }
+
+    fn apply_keystream(&self, data: &mut [u8]) {
+        for (i, chunk) in data.chunks_mut(32).enumerate() {
+            let block = keccak256_concat(&[&self.key, &(i as u32).to_be_bytes()]);
+            for (byte, k) in chunk.iter_mut().zip(block) {
+                *byte ^= k;
+            }
+        }
+    }
+
+    fn mac(&self, header: &[u8], ciphertext: &[u8]) -> [u8; MAC_LEN] {
+        let full = keccak256_concat(&[b"psol:note:mac:v1", &self.key, header, ciphertext]);
+        full[..MAC_LEN].try_into().unwrap()
+    }
+}
+
+/// Viewing key: decrypts notes sent to `public_key()`
+pub struct ViewingKey {
+    secret: [u8; 32],
+    public: [u8; 64],
+}
+
+impl ViewingKey {
+    pub fn from_seed(seed: &[u8]) -> Result<Self> {
+        let secret = scalar_from_seed(b"psol:viewing_key:v1", seed);
+        let public = g1_mul(&G1_GENERATOR, &secret)?;
+        Ok(Self { secret, public })
+    }
+
+    /// Uncompressed G1 point senders encrypt to
+    pub fn public_key(&self) -> [u8; 64] {
+        self.public
+    }
+
+    /// Trial-decrypt `envelope`; `None` if it is not addressed to this key
+    pub fn decrypt(&self, envelope: &[u8]) -> Option<NotePlaintext> {
+        self.try_decrypt(envelope).ok().flatten()
+    }
+
+    /// `Ok(None)` on a view tag miss, `Err` if the tag matched but the
+    /// envelope did not authenticate or parse
+    fn try_decrypt(&self, envelope: &[u8]) -> Result<Option<NotePlaintext>> {
+        if envelope.len() != NOTE_ENVELOPE_LEN || envelope[0] != NOTE_ENVELOPE_VERSION {
+            return Ok(None);
+        }
+        let ephemeral: [u8; 32] = envelope[2..34].try_into().unwrap();
+        let shared = g1_mul(&g1_decompress(&ephemeral)?, &self.secret)?;
+        let keys = SharedKeys::derive(&shared);
+        if keys.view_tag != envelope[1] {
+            return Ok(None);
+        }
+
+        let (body, mac) = envelope.split_at(NOTE_ENVELOPE_LEN - MAC_LEN);
+        let (header, ciphertext) = body.split_at(34);
+        require!(
+            keys.mac(header, ciphertext) == mac,
+            PrivacyErrorV2::InvalidEncryptedNote
+        );
+        let mut plaintext = ciphertext.to_vec();
+        keys.apply_keystream(&mut plaintext);
+        let note = NotePlaintext::try_from_slice(&plaintext)
+            .map_err(|_| error!(PrivacyErrorV2::InvalidEncryptedNote))?;
+        Ok(Some(note))
+    }
+}
+
+/// Encrypt `note` to the viewing key `recipient`
+///
+/// `ephemeral_seed` must be fresh randomness for every note.
+pub fn encrypt_note(
+    note: &NotePlaintext,
+    recipient: &[u8; 64],
+    ephemeral_seed: &[u8; 32],
+) -> Result<Vec<u8>> {
+    let ephemeral = scalar_from_seed(b"psol:note:ephemeral:v1", ephemeral_seed);
+    let keys = SharedKeys::derive(&g1_mul(recipient, &ephemeral)?);
+
+    let mut envelope = Vec::with_capacity(NOTE_ENVELOPE_LEN);
+    envelope.push(NOTE_ENVELOPE_VERSION);
+    envelope.push(keys.view_tag);
+    envelope.extend_from_slice(&g1_compress(&g1_mul(&G1_GENERATOR, &ephemeral)?)?);
+    let mut ciphertext = note.try_to_vec()?;
+    keys.apply_keystream(&mut ciphertext);
+    let mac = keys.mac(&envelope, &ciphertext);
+    envelope.extend_from_slice(&ciphertext);
+    envelope.extend_from_slice(&mac);
+    Ok(envelope)
+}
+
+/// Nullification receipt of the note sealed in `envelope`: its first
+/// `NOTE_RECEIPT_LEN` bytes (version, view tag and the start of the
+/// ephemeral key)
+pub fn note_receipt(envelope: &[u8]) -> Option<[u8; NOTE_RECEIPT_LEN]> {
+    envelope.get(..NOTE_RECEIPT_LEN)?.try_into().ok()
+}
+
+/// One piece of chain data the scanner consumes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ScanInput {
+    /// A note ciphertext bound to the commitment it should open
+    Note {
+        commitment: [u8; 32],
+        ciphertext: Vec<u8>,
+    },
+    /// A commitment was inserted into the tree
+    Inserted {
+        commitment: [u8; 32],
+        leaf_index: u32,
+    },
+    /// A nullifier hash was spent
+    Nullified { nullifier_hash: [u8; 32] },
+    /// The note with this receipt was spent
+    Receipt {
+        note_receipt: [u8; NOTE_RECEIPT_LEN],
+    },
+}
+
+impl ScanInput {
+    /// The note carried by a `deposit_masp` instruction, if any
+    pub fn from_deposit_instruction(data: &[u8]) -> Option<Self> {
+        let args = data.strip_prefix(instruction::DepositMasp::DISCRIMINATOR)?;
+        let deposit = instruction::DepositMasp::deserialize(&mut &args[..]).ok()?;
+        Some(ScanInput::Note {
+            commitment: deposit.commitment,
+            ciphertext: deposit.encrypted_note?,
+        })
+    }
+
+    /// The notes carried by a `deposit_masp_batch` instruction, if any
+    pub fn from_deposit_batch_instruction(data: &[u8]) -> Vec<Self> {
+        let Some(args) = data.strip_prefix(instruction::DepositMaspBatch::DISCRIMINATOR) else {
+            return Vec::new();
+        };
+        let Ok(deposit) = instruction::DepositMaspBatch::deserialize(&mut &args[..]) else {
+            return Vec::new();
+        };
+        deposit
+            .commitments
+            .into_iter()
+            .zip(deposit.encrypted_notes.unwrap_or_default())
+            .map(|(commitment, ciphertext)| ScanInput::Note {
+                commitment,
+                ciphertext,
+            })
+            .collect()
+    }
+
+    pub fn from_join_split(event: &JoinSplitEvent) -> Vec<Self> {
+        let nullifiers = [event.nullifier_hash_0, event.nullifier_hash_1];
+        let outputs = [event.output_commitment_0, event.output_commitment_1];
+        let spent = nullifiers
+            .into_iter()
+            .take(event.input_count as usize)
+            .map(|nullifier_hash| ScanInput::Nullified { nullifier_hash });
+        let inserted = outputs
+            .into_iter()
+            .zip(event.output_leaf_indices)
+            .take(event.output_count as usize)
+            .map(|(commitment, leaf_index)| ScanInput::Inserted {
+                commitment,
+                leaf_index,
+            });
+        spent.chain(inserted).collect()
+    }
+
+    pub fn from_withdraw_v2(event: &WithdrawV2Event) -> Vec<Self> {
+        [event.nullifier_hash_0, event.nullifier_hash_1]
+            .into_iter()
+            .filter(|n| *n != [0u8; 32])
+            .map(|nullifier_hash| ScanInput::Nullified { nullifier_hash })
+            .collect()
+    }
+
+    /// The spend a `SpentNullifierV2` record shows, with its receipt if one
+    /// was recorded
+    pub fn from_spent_nullifier(record: &SpentNullifierV2) -> Vec<Self> {
+        let mut inputs = vec![ScanInput::Nullified {
+            nullifier_hash: record.nullifier_hash,
+        }];
+        if record.has_note_receipt() {
+            inputs.push(ScanInput::Receipt {
+                note_receipt: record.note_receipt,
+            });
+        }
+        inputs
+    }
+}
+
+impl From<&NoteReceiptRecorded> for ScanInput {
+    fn from(event: &NoteReceiptRecorded) -> Self {
+        ScanInput::Receipt {
+            note_receipt: event.note_receipt,
+        }
+    }
+}
+
+impl From<&DepositMaspEvent> for ScanInput {
+    fn from(event: &DepositMaspEvent) -> Self {
+        ScanInput::Inserted {
+            commitment: event.commitment,
+            leaf_index: event.leaf_index,
+        }
+    }
+}
+
+impl From<&CommitmentInsertedEvent> for ScanInput {
+    fn from(event: &CommitmentInsertedEvent) -> Self {
+        ScanInput::Inserted {
+            commitment: event.commitment,
+            leaf_index: event.leaf_index,
+        }
+    }
+}
+
+impl From<&WithdrawMaspEvent> for ScanInput {
+    fn from(event: &WithdrawMaspEvent) -> Self {
+        ScanInput::Nullified {
+            nullifier_hash: event.nullifier_hash,
+        }
+    }
+}
+
+/// A note owned by the scanning wallet
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OwnedNote {
+    pub note: NotePlaintext,
+    pub commitment: [u8; 32],
+    /// `None` until the commitment is inserted into the tree
+    pub leaf_index: Option<u32>,
+    pub nullifier_hash: Option<[u8; 32]>,
+    /// Receipt a spender may record for this note
+    pub receipt: Option<[u8; NOTE_RECEIPT_LEN]>,
+    pub spent: bool,
+}
+
+impl OwnedNote {
+    pub fn is_spendable(&self) -> bool {
+        self.leaf_index.is_some() && !self.spent
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ScanStats {
+    pub ciphertexts: u64,
+    /// Ciphertexts whose view tag matched (ours, or 1-in-256 false positives)
+    pub view_tag_matches: u64,
+    pub decrypted: u64,
+    /// Decrypted notes that did not open their commitment
+    pub mismatched: u64,
+}
+
+pub struct Scanner {
+    key: ViewingKey,
+    notes: HashMap<[u8; 32], OwnedNote>,
+    /// Leaf indices of every inserted commitment, for notes seen later
+    leaf_indices: HashMap<[u8; 32], u32>,
+    spent: HashSet<[u8; 32]>,
+    spent_receipts: HashSet<[u8; NOTE_RECEIPT_LEN]>,
+    pub stats: ScanStats,
+}
+
+impl Scanner {
+    pub fn new(key: ViewingKey) -> Self {
+        Self {
+            key,
+            notes: HashMap::new(),
+            leaf_indices: HashMap::new(),
+            spent: HashSet::new(),
+            spent_receipts: HashSet::new(),
+            stats: ScanStats::default(),
+        }
+    }
+
+    pub fn scan(&mut self, input: ScanInput) -> Result<()> {
+        match input {
+            ScanInput::Note {
+                commitment,
+                ciphertext,
+            } => self.scan_note(commitment, &ciphertext)?,
+            ScanInput::Inserted {
+                commitment,
+                leaf_index,
+            } => {
+                self.leaf_indices.insert(commitment, leaf_index);
+                if let Some(owned) = self.notes.get_mut(&commitment) {
+                    Self::place(owned, leaf_index, &self.spent)?;
+                }
+            }
+            ScanInput::Nullified { nullifier_hash } => {
+                self.spent.insert(nullifier_hash);
+                for owned in self.notes.values_mut() {
+                    if owned.nullifier_hash == Some(nullifier_hash) {
+                        owned.spent = true;
+                    }
+                }
+            }
+            ScanInput::Receipt { note_receipt } => {
+                self.spent_receipts.insert(note_receipt);
+                for owned in self.notes.values_mut() {
+                    if owned.receipt == Some(note_receipt) {
+                        owned.spent = true;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn scan_all(&mut self, inputs: impl IntoIterator<Item = ScanInput>) -> Result<()> {
+        inputs.into_iter().try_for_each(|input| self.scan(input))
+    }
+
+    fn scan_note(&mut self, commitment: [u8; 32], ciphertext: &[u8]) -> Result<()> {
+        self.stats.ciphertexts += 1;
+        let note = match self.key.try_decrypt(ciphertext) {
+            Ok(None) => return Ok(()),
+            Ok(Some(note)) => note,
+            // Tag collision on someone else's note
+            Err(_) => {
+                self.stats.view_tag_matches += 1;
+                return Ok(());
+            }
+        };
+        self.stats.view_tag_matches += 1;
+        self.stats.decrypted += 1;
+        if note.commitment()? != commitment {
+            self.stats.mismatched += 1;
+            return Ok(());
+        }
+
+        let receipt = note_receipt(ciphertext);
+        let owned = self.notes.entry(commitment).or_insert(OwnedNote {
+            note,
+            commitment,
+            leaf_index: None,
+            nullifier_hash: None,
+            receipt,
+            spent: receipt.is_some_and(|r| self.spent_receipts.contains(&r)),
+        });
+        if let Some(&leaf_index) = self.leaf_indices.get(&commitment) {
+            Self::place(owned, leaf_index, &self.spent)?;
+        }
+        Ok(())
+    }
+
+    fn place(owned: &mut OwnedNote, leaf_index: u32, spent: &HashSet<[u8; 32]>) -> Result<()> {
+        let nullifier_hash = owned.note.nullifier_hash(leaf_index)?;
+        owned.leaf_index = Some(leaf_index);
+        owned.nullifier_hash = Some(nullifier_hash);
+        owned.spent |= spent.contains(&nullifier_hash);
+        Ok(())
+    }
+
+    /// Inserted, unspent notes in leaf order
+    pub fn spendable(&self) -> Vec<&OwnedNote> {
+        let mut notes: Vec<_> = self.notes.values().filter(|n| n.is_spendable()).collect();
+        notes.sort_by_key(|n| n.leaf_index);
+        notes
+    }
+
+    /// Notes decrypted but not yet inserted (deposits waiting for a batch)
+    pub fn pending(&self) -> Vec<&OwnedNote> {
+        self.notes
+            .values()
+            .filter(|n| n.leaf_index.is_none())
+            .collect()
+    }
+
+    /// Spendable balance per asset id
+    pub fn balances(&self) -> BTreeMap<[u8; 32], u64> {
+        let mut balances = BTreeMap::new();
+        for owned in self.spendable() {
+            let balance = balances.entry(owned.note.asset_id).or_insert(0u64);
+            *balance = balance.saturating_add(owned.note.amount);
+        }
+        balances
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::InstructionData;
+
+    const ASSET: [u8; 32] = [0x01; 32];
+
+    fn note(seed: u8, amount: u64) -> NotePlaintext {
+        NotePlaintext {
+            secret: scalar_from_seed(b"test:secret", &[seed]),
+            nullifier: scalar_from_seed(b"test:nullifier", &[seed]),
+            amount,
+            asset_id: ASSET,
+        }
+    }
+
+    fn deposit_data(note: &NotePlaintext, ciphertext: Vec<u8>) -> Vec<u8> {
+        instruction::DepositMasp {
+            amount: note.amount,
+            commitment: note.commitment().unwrap(),
+            asset_id: note.asset_id,
+            proof_data: vec![0u8; 256],
+            encrypted_note: Some(ciphertext),
+        }
+        .data()
+    }
+
+    #[test]
+    fn test_envelope_roundtrip_and_tampering() {
+        let key = ViewingKey::from_seed(b"alice").unwrap();
+        let other = ViewingKey::from_seed(b"bob").unwrap();
+        let n = note(1, 5_000);
+        let envelope = encrypt_note(&n, &key.public_key(), &[7u8; 32]).unwrap();
+        assert_eq!(envelope.len(), NOTE_ENVELOPE_LEN);
+
+        assert_eq!(key.decrypt(&envelope), Some(n));
+        assert_eq!(other.decrypt(&envelope), None);
+
+        for i in [1, 40, NOTE_ENVELOPE_LEN - 1] {
+            let mut tampered = envelope.clone();
+            tampered[i] ^= 1;
+            assert_eq!(key.decrypt(&tampered), None, "byte {i}");
+        }
+        assert_eq!(key.decrypt(&envelope[..NOTE_ENVELOPE_LEN - 1]), None);
+    }
+
+    #[test]
+    fn test_scanner_tracks_deposits_and_spends() {
+        let alice = ViewingKey::from_seed(b"alice").unwrap();
+        let bob = ViewingKey::from_seed(b"bob").unwrap();
+        let mine = [note(1, 5_000), note(2, 700)];
+        let theirs = note(3, 9_999);
+
+        let mut inputs = Vec::new();
+        for (i, n) in mine.iter().enumerate() {
+            let envelope = encrypt_note(n, &alice.public_key(), &[i as u8; 32]).unwrap();
+            inputs.push(ScanInput::from_deposit_instruction(&deposit_data(n, envelope)).unwrap());
+        }
+        let envelope = encrypt_note(&theirs, &bob.public_key(), &[9u8; 32]).unwrap();
+        inputs.push(ScanInput::from_deposit_instruction(&deposit_data(&theirs, envelope)).unwrap());
+
+        let mut scanner = Scanner::new(alice);
+        scanner.scan_all(inputs).unwrap();
+        assert_eq!(scanner.stats.ciphertexts, 3);
+        assert_eq!(scanner.stats.decrypted, 2);
+        assert_eq!(scanner.pending().len(), 2);
+        assert!(scanner.balances().is_empty());
+
+        // Batched: leaf indices arrive through CommitmentInsertedEvent
+        for (i, n) in [&mine[0], &theirs, &mine[1]].into_iter().enumerate() {
+            let event = CommitmentInsertedEvent {
+                pool: Pubkey::default(),
+                commitment: n.commitment().unwrap(),
+                leaf_index: i as u32,
+                merkle_root: [0u8; 32],
+                timestamp: 0,
+            };
+            scanner.scan((&event).into()).unwrap();
+        }
+        assert_eq!(scanner.balances()[&ASSET], 5_700);
+        let spendable = scanner.spendable();
+        assert_eq!(spendable[1].leaf_index, Some(2));
+        assert_eq!(
+            spendable[1].nullifier_hash,
+            Some(compute_nullifier_hash(&mine[1].nullifier, &mine[1].secret, 2).unwrap())
+        );
+
+        let event = WithdrawMaspEvent {
+            pool: Pubkey::default(),
+            nullifier_hash: mine[0].nullifier_hash(0).unwrap(),
+            asset_id: ASSET,
+            relayer: Pubkey::default(),
+            relayer_fee: 0,
+            sequence: 0,
+            timestamp: 0,
+        };
+        scanner.scan((&event).into()).unwrap();
+        assert_eq!(scanner.balances()[&ASSET], 700);
+    }
+
+    #[test]
+    fn test_batch_deposit_notes() {
+        let alice = ViewingKey::from_seed(b"alice").unwrap();
+        let notes = [note(5, 300), note(6, 700)];
+        let envelopes: Vec<Vec<u8>> = notes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| encrypt_note(n, &alice.public_key(), &[i as u8; 32]).unwrap())
+            .collect();
+        let data = instruction::DepositMaspBatch {
+            asset_id: ASSET,
+            amounts: notes.iter().map(|n| n.amount).collect(),
+            commitments: notes.iter().map(|n| n.commitment().unwrap()).collect(),
+            proofs: vec![vec![0u8; 256]; 2],
+            encrypted_notes: Some(envelopes),
+        }
+        .data();
+
+        let mut scanner = Scanner::new(alice);
+        scanner
+            .scan_all(ScanInput::from_deposit_batch_instruction(&data))
+            .unwrap();
+        assert_eq!(scanner.stats.decrypted, 2);
+        assert!(ScanInput::from_deposit_batch_instruction(&data[1..]).is_empty());
+    }
+
+    #[test]
+    fn test_scanner_is_order_independent() {
+        let alice = ViewingKey::from_seed(b"alice").unwrap();
+        let n = note(4, 1_000);
+        let commitment = n.commitment().unwrap();
+
+        // Spend and insertion seen before the note itself
+        let mut scanner = Scanner::new(alice);
+        scanner
+            .scan(ScanInput::Nullified {
+                nullifier_hash: n.nullifier_hash(11).unwrap(),
+            })
+            .unwrap();
+        scanner
+            .scan(ScanInput::Inserted {
+                commitment,
+                leaf_index: 11,
+            })
+            .unwrap();
+        let ciphertext = encrypt_note(&n, &scanner.key.public_key(), &[1u8; 32]).unwrap();
+        scanner
+            .scan(ScanInput::Note {
+                commitment,
+                ciphertext,
+            })
+            .unwrap();
+        assert!(scanner.spendable().is_empty());
+        assert!(scanner.notes[&commitment].spent);
+    }
+
+    #[test]
+    fn test_note_must_open_its_commitment() {
+        let alice = ViewingKey::from_seed(b"alice").unwrap();
+        let n = note(5, 1_000);
+        let ciphertext = encrypt_note(&n, &alice.public_key(), &[2u8; 32]).unwrap();
+
+        // Same plaintext attached to someone else's commitment
+        let mut scanner = Scanner::new(alice);
+        scanner
+            .scan(ScanInput::Note {
+                commitment: note(6, 1_000).commitment().unwrap(),
+                ciphertext,
+            })
+            .unwrap();
+        assert_eq!(scanner.stats.decrypted, 1);
+        assert_eq!(scanner.stats.mismatched, 1);
+        assert!(scanner.pending().is_empty());
+    }
+
+    #[test]
+    fn test_receipt_marks_note_spent() {
+        let alice = ViewingKey::from_seed(b"alice").unwrap();
+        let [spent, kept] = [note(7, 1_000), note(8, 2_000)];
+        let mut scanner = Scanner::new(alice);
+        let mut receipt = None;
+        for (i, n) in [spent, kept].iter().enumerate() {
+            let ciphertext = encrypt_note(n, &scanner.key.public_key(), &[i as u8; 32]).unwrap();
+            receipt.get_or_insert(note_receipt(&ciphertext).unwrap());
+            let commitment = n.commitment().unwrap();
+            scanner
+                .scan_all([
+                    ScanInput::Note {
+                        commitment,
+                        ciphertext,
+                    },
+                    ScanInput::Inserted {
+                        commitment,
+                        leaf_index: i as u32,
+                    },
+                ])
+                .unwrap();
+        }
+
+        // Recognized from the receipt alone, no nullifier hash needed
+        scanner
+            .scan(ScanInput::Receipt {
+                note_receipt: receipt.unwrap(),
+            })
+            .unwrap();
+        assert!(scanner.notes[&spent.commitment().unwrap()].spent);
+        assert_eq!(scanner.balances()[&ASSET], 2_000);
+
+        // A receipt seen before its note still applies
+        let late = note(9, 500);
+        let ciphertext = encrypt_note(&late, &scanner.key.public_key(), &[9u8; 32]).unwrap();
+        scanner
+            .scan(ScanInput::Receipt {
+                note_receipt: note_receipt(&ciphertext).unwrap(),
+            })
+            .unwrap();
+        scanner
+            .scan(ScanInput::Note {
+                commitment: late.commitment().unwrap(),
+                ciphertext,
+            })
+            .unwrap();
+        assert!(scanner.notes[&late.commitment().unwrap()].spent);
+    }
+}
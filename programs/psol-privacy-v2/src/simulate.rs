@@ -0,0 +1,178 @@
+//! Batch insertion simulation (host-side)
+//!
+//! `batch_process_deposits` inserts pending commitments into the tree in
+//! FIFO order. Given the tree's frontier and the commitments a batch will
+//! take, `simulate_insertions` predicts the root the batch will publish and
+//! the leaf index each commitment will land at, so a wallet or relayer can
+//! build withdrawal witnesses against that root before the batch lands.
+//!
+//! Insertion goes through `MerkleTreeV2::insert_into_frontier`, the same
+//! code the program runs, and fails where the batch would: on a zero
+//! commitment or a full tree.
+
+use anchor_lang::prelude::*;
+
+use crate::crypto::precomputed_zeros::get_precomputed_zeros;
+use crate::error::PrivacyErrorV2;
+use crate::state::{MerkleTreeV2, MAX_TREE_DEPTH, MIN_TREE_DEPTH};
+
+/// The part of a `MerkleTreeV2` that insertions read and write
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TreeFrontier {
+    /// Tree depth
+    pub depth: u8,
+
+    /// Index the next inserted leaf will take
+    pub next_leaf_index: u32,
+
+    /// Current root
+    pub root: [u8; 32],
+
+    /// Rightmost filled node at each level (`MerkleTreeV2::filled_subtrees`)
+    pub filled_subtrees: Vec<[u8; 32]>,
+}
+
+impl TreeFrontier {
+    /// Frontier of an empty tree of `depth`
+    pub fn empty(depth: u8) -> Result<Self> {
+        require!(
+            (MIN_TREE_DEPTH..=MAX_TREE_DEPTH).contains(&depth),
+            PrivacyErrorV2::InvalidTreeDepth
+        );
+        let zeros = get_precomputed_zeros(depth);
+        Ok(Self {
+            depth,
+            next_leaf_index: 0,
+            root: zeros[depth as usize],
+            filled_subtrees: zeros[..depth as usize].to_vec(),
+        })
+    }
+
+    /// Frontier of a fetched tree account
+    pub fn from_tree(tree: &MerkleTreeV2) -> Self {
+        Self {
+            depth: tree.depth,
+            next_leaf_index: tree.next_leaf_index,
+            root: tree.current_root,
+            filled_subtrees: tree.filled_subtrees.clone(),
+        }
+    }
+}
+
+/// Insert `commitments`, in order, into a copy of `frontier`
+///
+/// Returns the root after the last insertion, the updated frontier and the
+/// leaf index of each commitment. With no commitments the root and frontier
+/// are unchanged.
+pub fn simulate_insertions(
+    frontier: &TreeFrontier,
+    commitments: &[[u8; 32]],
+) -> Result<([u8; 32], TreeFrontier, Vec<u32>)> {
+    require!(
+        frontier.filled_subtrees.len() == frontier.depth as usize,
+        PrivacyErrorV2::InvalidTreeDepth
+    );
+    let zeros = get_precomputed_zeros(frontier.depth);
+    let mut next = frontier.clone();
+    let mut leaf_indices = Vec::with_capacity(commitments.len());
+
+    for commitment in commitments {
+        let leaf_index = next.next_leaf_index;
+        next.root = MerkleTreeV2::insert_into_frontier(
+            &mut next.filled_subtrees,
+            &zeros,
+            leaf_index,
+            *commitment,
+        )?;
+        next.next_leaf_index = leaf_index
+            .checked_add(1)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        leaf_indices.push(leaf_index);
+    }
+
+    Ok((next.root, next, leaf_indices))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::witness::merkle_path;
+
+    fn leaf(i: u8) -> [u8; 32] {
+        let mut l = [0u8; 32];
+        l[31] = i + 1;
+        l
+    }
+
+    fn new_tree(depth: u8) -> MerkleTreeV2 {
+        let mut tree = MerkleTreeV2 {
+            pool: Pubkey::default(),
+            depth: 0,
+            next_leaf_index: 0,
+            current_root: [0u8; 32],
+            root_history: Vec::new(),
+            root_history_index: 0,
+            root_history_size: 0,
+            filled_subtrees: Vec::new(),
+            zeros: Vec::new(),
+            total_leaves: 0,
+            last_insertion_at: 0,
+            version: 0,
+            poseidon_params_hash: [0u8; 32],
+        };
+        tree.initialize(Pubkey::default(), depth, 64).unwrap();
+        tree
+    }
+
+    #[test]
+    fn test_simulation_matches_on_chain_batches() {
+        let depth = 6;
+        let mut tree = new_tree(depth);
+        assert_eq!(
+            TreeFrontier::from_tree(&tree),
+            TreeFrontier::empty(depth).unwrap()
+        );
+
+        let leaves: Vec<[u8; 32]> = (0..11).map(leaf).collect();
+        for batch in [&leaves[..3], &leaves[3..4], &leaves[4..11]] {
+            let frontier = TreeFrontier::from_tree(&tree);
+            let (root, next, indices) = simulate_insertions(&frontier, batch).unwrap();
+
+            let expected: Vec<u32> = batch
+                .iter()
+                .map(|l| tree.insert_leaf(*l, 0).unwrap())
+                .collect();
+            assert_eq!(indices, expected);
+            assert_eq!(root, tree.get_current_root());
+            assert_eq!(next, TreeFrontier::from_tree(&tree));
+        }
+
+        // Witnesses built against the predicted root verify against the tree
+        let (root, _, _) =
+            simulate_insertions(&TreeFrontier::empty(depth).unwrap(), &leaves).unwrap();
+        assert_eq!(merkle_path(&leaves, 7, depth).unwrap().root, root);
+        assert!(tree.is_known_root(&root));
+
+        let frontier = TreeFrontier::from_tree(&tree);
+        assert_eq!(
+            simulate_insertions(&frontier, &[]).unwrap(),
+            (frontier.root, frontier.clone(), vec![])
+        );
+    }
+
+    #[test]
+    fn test_simulation_fails_where_the_batch_would() {
+        let frontier = TreeFrontier::empty(4).unwrap();
+        assert_eq!(
+            simulate_insertions(&frontier, &[leaf(0), [0u8; 32]]).unwrap_err(),
+            PrivacyErrorV2::InvalidCommitment.into()
+        );
+
+        let full: Vec<[u8; 32]> = (0..16).map(leaf).collect();
+        let (_, frontier, _) = simulate_insertions(&frontier, &full).unwrap();
+        assert_eq!(
+            simulate_insertions(&frontier, &[leaf(16)]).unwrap_err(),
+            PrivacyErrorV2::MerkleTreeFull.into()
+        );
+    }
+}
@@ -0,0 +1,185 @@
+//! Action Allowlist - Programs a shielded action may call
+//!
+//! One list per pool and action type. `execute_shielded_action` only runs
+//! an action whose target program is on its type's list, so shielded funds
+//! flow into integrations the authority has vetted; an action type without
+//! a list cannot run at all.
+//!
+//! PDA Seeds: `[b"action_allowlist", pool.key().as_ref(), &[action_type]]`
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::ShieldedActionType;
+
+/// Maximum number of allowlisted programs per action type
+pub const MAX_ALLOWLISTED_PROGRAMS: usize = 8;
+
+#[account]
+#[derive(InitSpace)]
+pub struct ActionAllowlist {
+    /// Parent pool configuration
+    pub pool: Pubkey,
+
+    /// `ShieldedActionType` the list applies to
+    pub action_type: u8,
+
+    /// Programs actions of this type may target
+    pub programs: [Pubkey; MAX_ALLOWLISTED_PROGRAMS],
+
+    /// Number of allowlisted programs
+    pub program_count: u8,
+
+    /// Last update timestamp
+    pub updated_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved for future use
+    pub _reserved: [u8; 32],
+}
+
+impl ActionAllowlist {
+    pub const SEED_PREFIX: &'static [u8] = b"action_allowlist";
+
+    pub const LEN: usize = 8                    // discriminator
+        + 32                                    // pool
+        + 1                                     // action_type
+        + 32 * MAX_ALLOWLISTED_PROGRAMS         // programs
+        + 1                                     // program_count
+        + 8                                     // updated_at
+        + 1                                     // bump
+        + 32; // reserved
+
+    pub fn initialize(
+        &mut self,
+        pool: Pubkey,
+        action_type: ShieldedActionType,
+        bump: u8,
+        timestamp: i64,
+    ) {
+        self.pool = pool;
+        self.action_type = action_type as u8;
+        self.programs = [Pubkey::default(); MAX_ALLOWLISTED_PROGRAMS];
+        self.program_count = 0;
+        self.updated_at = timestamp;
+        self.bump = bump;
+        self._reserved = [0u8; 32];
+    }
+
+    pub fn allowlisted(&self) -> &[Pubkey] {
+        &self.programs[..self.program_count as usize]
+    }
+
+    pub fn contains(&self, program: &Pubkey) -> bool {
+        self.allowlisted().contains(program)
+    }
+
+    pub fn add_program(&mut self, program: Pubkey, timestamp: i64) -> Result<()> {
+        require!(
+            !self.contains(&program),
+            PrivacyErrorV2::ProgramAlreadyAllowlisted
+        );
+        let count = self.program_count as usize;
+        require!(
+            count < MAX_ALLOWLISTED_PROGRAMS,
+            PrivacyErrorV2::ActionAllowlistFull
+        );
+        self.programs[count] = program;
+        self.program_count += 1;
+        self.updated_at = timestamp;
+        Ok(())
+    }
+
+    pub fn remove_program(&mut self, program: &Pubkey, timestamp: i64) -> Result<()> {
+        let index = self
+            .allowlisted()
+            .iter()
+            .position(|p| p == program)
+            .ok_or(PrivacyErrorV2::TargetProgramNotAllowlisted)?;
+        let count = self.program_count as usize;
+        self.programs.copy_within(index + 1..count, index);
+        self.programs[count - 1] = Pubkey::default();
+        self.program_count -= 1;
+        self.updated_at = timestamp;
+        Ok(())
+    }
+
+    /// Check an action may target `program`
+    pub fn require_allowed(&self, program: &Pubkey) -> Result<()> {
+        require!(
+            self.contains(program),
+            PrivacyErrorV2::TargetProgramNotAllowlisted
+        );
+        Ok(())
+    }
+
+    pub fn find_pda(
+        program_id: &Pubkey,
+        pool: &Pubkey,
+        action_type: ShieldedActionType,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED_PREFIX, pool.as_ref(), &[action_type as u8]],
+            program_id,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowlist() -> ActionAllowlist {
+        let mut list = ActionAllowlist {
+            pool: Pubkey::default(),
+            action_type: 0,
+            programs: [Pubkey::default(); MAX_ALLOWLISTED_PROGRAMS],
+            program_count: 0,
+            updated_at: 0,
+            bump: 0,
+            _reserved: [0u8; 32],
+        };
+        list.initialize(Pubkey::new_unique(), ShieldedActionType::Stake, 255, 1);
+        list
+    }
+
+    #[test]
+    fn test_add_remove_programs() {
+        let mut list = allowlist();
+        assert_eq!(list.action_type, ShieldedActionType::Stake as u8);
+        let programs: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        for program in &programs {
+            list.add_program(*program, 2).unwrap();
+        }
+        assert!(list.add_program(programs[0], 3).is_err());
+
+        list.remove_program(&programs[0], 4).unwrap();
+        assert_eq!(list.allowlisted(), &programs[1..]);
+        assert!(list.remove_program(&programs[0], 5).is_err());
+        assert_eq!(list.updated_at, 4);
+
+        while list.add_program(Pubkey::new_unique(), 6).is_ok() {}
+        assert_eq!(list.program_count as usize, MAX_ALLOWLISTED_PROGRAMS);
+    }
+
+    #[test]
+    fn test_empty_list_allows_nothing() {
+        let mut list = allowlist();
+        let program = Pubkey::new_unique();
+        assert!(list.require_allowed(&program).is_err());
+
+        list.add_program(program, 2).unwrap();
+        assert!(list.require_allowed(&program).is_ok());
+        assert!(list.require_allowed(&Pubkey::new_unique()).is_err());
+    }
+
+    #[test]
+    fn test_lists_are_per_action_type() {
+        let pool = Pubkey::new_unique();
+        let (swap, _) = ActionAllowlist::find_pda(&crate::ID, &pool, ShieldedActionType::DexSwap);
+        let (stake, _) = ActionAllowlist::find_pda(&crate::ID, &pool, ShieldedActionType::Stake);
+        assert_ne!(swap, stake);
+    }
+}
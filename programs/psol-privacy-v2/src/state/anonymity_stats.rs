@@ -0,0 +1,285 @@
+//! Anonymity Stats - aggregate deposit-to-withdrawal latency per asset
+//!
+//! A withdrawal does not reveal which deposit it spends, so no individual
+//! latency exists on-chain. What is public is when notes enter and leave
+//! the pool. Each `StatsEpoch` counts the deposits and withdrawals of one
+//! epoch, and withdrawals are matched to the oldest deposits still
+//! outstanding (FIFO) to file them under a coarse latency bucket.
+//!
+//! FIFO matching is a model, not a linkage: the mean latency it yields is
+//! the true mean over the matched notes (any matching has the same sum),
+//! while its spread is a lower bound on the real one. Operators use the
+//! histogram to tune batching delay and denominations; it reveals nothing
+//! beyond the timing of the deposit and withdrawal events themselves.
+//!
+//! Separate per-asset PDA, taken as an optional account like `ProofStats`.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+
+/// Length of a statistics epoch (1 day)
+pub const STATS_EPOCH_SECONDS: i64 = 86_400;
+
+/// Epochs kept in the ring; older epochs fold into `expired_outstanding`
+pub const STATS_EPOCH_COUNT: usize = 16;
+
+/// Latency buckets, in whole epochs: 0, 1, 2-3, 4-7, 8-15, 16+
+pub const LATENCY_BUCKET_COUNT: usize = 6;
+
+/// Deposits and withdrawals of one epoch
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace,
+)]
+pub struct StatsEpoch {
+    /// Epoch index (`unix_timestamp / STATS_EPOCH_SECONDS`)
+    pub epoch: u64,
+
+    /// Deposits made during the epoch
+    pub deposits: u64,
+
+    /// Deposits of the epoch not yet matched to a withdrawal
+    pub outstanding: u64,
+
+    /// Withdrawals made during the epoch
+    pub withdrawals: u64,
+
+    /// Withdrawals of the epoch by latency bucket
+    ///
+    /// Withdrawals with no recorded deposit to match (notes deposited
+    /// before the account existed, or change notes) are counted in
+    /// `withdrawals` only.
+    pub latency_buckets: [u64; LATENCY_BUCKET_COUNT],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct AnonymityStats {
+    /// Parent pool configuration
+    pub pool: Pubkey,
+
+    /// Asset the statistics cover
+    pub asset_id: [u8; 32],
+
+    /// Ring of recent epochs, indexed by `epoch % STATS_EPOCH_COUNT`
+    pub epochs: [StatsEpoch; STATS_EPOCH_COUNT],
+
+    /// Unmatched deposits of epochs that have left the ring
+    pub expired_outstanding: u64,
+
+    /// Timestamp the statistics started at
+    pub since: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved for future use
+    pub _reserved: [u8; 32],
+}
+
+impl AnonymityStats {
+    /// Account size: 8 + 32 + 32 + 80 * 16 + 8 + 8 + 1 + 32 = 1401 bytes
+    pub const LEN: usize =
+        8 + 32 + 32 + (8 * (4 + LATENCY_BUCKET_COUNT)) * STATS_EPOCH_COUNT + 8 + 8 + 1 + 32;
+
+    /// Seed prefix for PDA derivation
+    pub const SEED_PREFIX: &'static [u8] = b"anonymity_stats";
+
+    pub fn initialize(&mut self, pool: Pubkey, asset_id: [u8; 32], bump: u8, timestamp: i64) {
+        self.pool = pool;
+        self.asset_id = asset_id;
+        self.epochs = [StatsEpoch::default(); STATS_EPOCH_COUNT];
+        self.expired_outstanding = 0;
+        self.since = timestamp;
+        self.bump = bump;
+        self._reserved = [0u8; 32];
+    }
+
+    /// Epoch index of `timestamp`
+    pub fn epoch_of(timestamp: i64) -> u64 {
+        (timestamp.max(0) / STATS_EPOCH_SECONDS) as u64
+    }
+
+    /// Latency bucket for a note deposited `epochs` epochs before its
+    /// withdrawal
+    pub fn latency_bucket(epochs: u64) -> usize {
+        std::cmp::min(
+            (u64::BITS - epochs.leading_zeros()) as usize,
+            LATENCY_BUCKET_COUNT - 1,
+        )
+    }
+
+    /// The ring slot for the epoch of `timestamp`, recycling the slot of an
+    /// epoch that has left the ring
+    fn current(&mut self, timestamp: i64) -> Result<&mut StatsEpoch> {
+        let epoch = Self::epoch_of(timestamp);
+        let slot = (epoch % STATS_EPOCH_COUNT as u64) as usize;
+
+        if self.epochs[slot].epoch != epoch {
+            self.expired_outstanding = self
+                .expired_outstanding
+                .checked_add(self.epochs[slot].outstanding)
+                .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+            self.epochs[slot] = StatsEpoch {
+                epoch,
+                ..StatsEpoch::default()
+            };
+        }
+        Ok(&mut self.epochs[slot])
+    }
+
+    pub fn record_deposit(&mut self, timestamp: i64) -> Result<()> {
+        let current = self.current(timestamp)?;
+        current.deposits = current
+            .deposits
+            .checked_add(1)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        current.outstanding = current
+            .outstanding
+            .checked_add(1)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        Ok(())
+    }
+
+    /// Count a withdrawal, matching it to the oldest outstanding deposit
+    pub fn record_withdrawal(&mut self, timestamp: i64) -> Result<()> {
+        let epoch = Self::epoch_of(timestamp);
+        self.current(timestamp)?;
+
+        let bucket = if self.expired_outstanding > 0 {
+            self.expired_outstanding -= 1;
+            Some(LATENCY_BUCKET_COUNT - 1)
+        } else {
+            self.epochs
+                .iter_mut()
+                .filter(|e| e.outstanding > 0)
+                .min_by_key(|e| e.epoch)
+                .map(|source| {
+                    source.outstanding -= 1;
+                    Self::latency_bucket(epoch.saturating_sub(source.epoch))
+                })
+        };
+
+        let current = self.current(timestamp)?;
+        current.withdrawals = current
+            .withdrawals
+            .checked_add(1)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        if let Some(bucket) = bucket {
+            current.latency_buckets[bucket] = current.latency_buckets[bucket]
+                .checked_add(1)
+                .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        }
+        Ok(())
+    }
+
+    /// Latency histogram over the epochs still in the ring
+    pub fn latency_histogram(&self) -> [u64; LATENCY_BUCKET_COUNT] {
+        let mut histogram = [0u64; LATENCY_BUCKET_COUNT];
+        for epoch in &self.epochs {
+            for (total, count) in histogram.iter_mut().zip(epoch.latency_buckets) {
+                *total = total.saturating_add(count);
+            }
+        }
+        histogram
+    }
+
+    pub fn find_pda(program_id: &Pubkey, pool: &Pubkey, asset_id: &[u8; 32]) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED_PREFIX, pool.as_ref(), asset_id.as_ref()],
+            program_id,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = STATS_EPOCH_SECONDS;
+
+    fn new_stats() -> AnonymityStats {
+        let mut stats = AnonymityStats {
+            pool: Pubkey::default(),
+            asset_id: [0u8; 32],
+            epochs: [StatsEpoch::default(); STATS_EPOCH_COUNT],
+            expired_outstanding: 0,
+            since: 0,
+            bump: 0,
+            _reserved: [0u8; 32],
+        };
+        stats.initialize(Pubkey::default(), [1u8; 32], 255, 100 * DAY);
+        stats
+    }
+
+    #[test]
+    fn test_latency_buckets_are_powers_of_two() {
+        let buckets: Vec<usize> = [0, 1, 2, 3, 4, 7, 8, 15, 16, 1_000]
+            .into_iter()
+            .map(AnonymityStats::latency_bucket)
+            .collect();
+        assert_eq!(buckets, vec![0, 1, 2, 2, 3, 3, 4, 4, 5, 5]);
+        assert_eq!(
+            AnonymityStats::latency_bucket(u64::MAX),
+            LATENCY_BUCKET_COUNT - 1
+        );
+    }
+
+    #[test]
+    fn test_latency_counts_epoch_boundaries() {
+        assert_eq!(AnonymityStats::epoch_of(-1), 0);
+        assert_eq!(AnonymityStats::epoch_of(DAY - 1), 0);
+        assert_eq!(AnonymityStats::epoch_of(DAY), 1);
+
+        // A second apart, but across midnight: one epoch
+        let mut stats = new_stats();
+        stats.record_deposit(101 * DAY - 1).unwrap();
+        stats.record_withdrawal(101 * DAY).unwrap();
+        assert_eq!(stats.latency_histogram(), [0, 1, 0, 0, 0, 0]);
+
+        // Almost a day apart within one epoch: bucket 0
+        stats.record_deposit(102 * DAY).unwrap();
+        stats.record_withdrawal(103 * DAY - 1).unwrap();
+        assert_eq!(stats.latency_histogram(), [1, 1, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_withdrawals_match_oldest_deposits_first() {
+        let mut stats = new_stats();
+        stats.record_deposit(100 * DAY).unwrap();
+        stats.record_deposit(100 * DAY + 5).unwrap();
+        stats.record_deposit(103 * DAY).unwrap();
+
+        // Day 103: both day-100 deposits go first (3 epochs -> bucket 2)
+        stats.record_withdrawal(103 * DAY + 1).unwrap();
+        stats.record_withdrawal(103 * DAY + 2).unwrap();
+        // Same-epoch withdrawal of the day-103 deposit
+        stats.record_withdrawal(103 * DAY + 3).unwrap();
+        // Nothing left to match: counted, but not bucketed
+        stats.record_withdrawal(104 * DAY).unwrap();
+
+        assert_eq!(stats.latency_histogram(), [1, 0, 2, 0, 0, 0]);
+        let day_103 = stats.epochs[103 % STATS_EPOCH_COUNT];
+        assert_eq!((day_103.deposits, day_103.withdrawals), (1, 3));
+        assert_eq!(stats.epochs[104 % STATS_EPOCH_COUNT].withdrawals, 1);
+        assert!(stats.epochs.iter().all(|e| e.outstanding == 0));
+    }
+
+    #[test]
+    fn test_expired_epochs_keep_outstanding_deposits() {
+        let mut stats = new_stats();
+        stats.record_deposit(100 * DAY).unwrap();
+        stats.record_deposit(101 * DAY).unwrap();
+
+        // Day 116 reuses day 100's slot; its deposit is still outstanding
+        stats.record_deposit(116 * DAY).unwrap();
+        assert_eq!(stats.expired_outstanding, 1);
+
+        stats.record_withdrawal(117 * DAY).unwrap();
+        stats.record_withdrawal(117 * DAY).unwrap();
+        assert_eq!(stats.expired_outstanding, 0);
+        // Day 117 pushed day 101 out too: both matches are 16+ epochs old
+        assert_eq!(stats.latency_histogram()[LATENCY_BUCKET_COUNT - 1], 2);
+        assert_eq!(stats.epochs[116 % STATS_EPOCH_COUNT].outstanding, 1);
+    }
+}
@@ -13,6 +13,7 @@
 use anchor_lang::prelude::*;
 
 use crate::error::PrivacyErrorV2;
+use crate::utils::token_metadata::{TokenMetadata, MAX_TOKEN_SYMBOL_LEN};
 
 /// Maximum length for asset metadata URI
 pub const MAX_METADATA_URI_LEN: usize = 200;
@@ -52,10 +53,10 @@ pub struct AssetVault {
     /// Maximum deposit amount per transaction
     pub max_deposit: u64,
 
-    /// Total value deposited (lifetime)
+    /// Total value deposited (lifetime; bucketed with coarse liquidity stats)
     pub total_deposited: u64,
 
-    /// Total value withdrawn (lifetime)
+    /// Total value withdrawn (lifetime; bucketed with coarse liquidity stats)
     pub total_withdrawn: u64,
 
     /// Current shielded balance (should match token account)
@@ -82,8 +83,37 @@ pub struct AssetVault {
     /// Optional metadata URI for asset info
     pub metadata_uri: String,
 
+    /// Minimum relayer fee in token base units (0 = no floor)
+    pub min_relayer_fee: u64,
+
+    /// Maximum relayer fee in token base units (0 = no cap)
+    pub max_relayer_fee: u64,
+
+    /// Registration order within the pool (bit index in relayer asset bitmaps)
+    pub asset_index: u16,
+
+    /// Minimum withdrawal amount in token base units
+    /// (0 = derived from `decimals`, see `default_min_withdrawal`)
+    pub min_withdrawal: u64,
+
+    /// Metaplex symbol cached at registration, zero-padded (all zeros if
+    /// metadata was not read)
+    pub token_symbol: [u8; MAX_TOKEN_SYMBOL_LEN],
+
+    /// Hash of the Metaplex name cached at registration (all zeros if
+    /// metadata was not read)
+    pub token_name_hash: [u8; 32],
+
+    /// Risk tier the asset is in, an index into the pool's
+    /// `RiskTierConfig` (see `state::risk_tier`)
+    pub risk_tier: u8,
+
+    /// Whether the asset was put in a risk tier; until then `risk_tier` is
+    /// meaningless and only the vault's own limits apply
+    pub risk_tier_assigned: bool,
+
     /// Reserved for future use
-    pub _reserved: [u8; 32],
+    pub _reserved: [u8; 4],
 }
 
 impl AssetVault {
@@ -109,11 +139,36 @@ impl AssetVault {
             + 1                     // decimals
             + 1                     // asset_type
             + 4 + metadata_uri_len  // metadata_uri (String)
-            + 32 // reserved
+            + 8                     // min_relayer_fee
+            + 8                     // max_relayer_fee
+            + 2                     // asset_index
+            + 8                     // min_withdrawal
+            + MAX_TOKEN_SYMBOL_LEN  // token_symbol
+            + 32                    // token_name_hash
+            + 1                     // risk_tier
+            + 1                     // risk_tier_assigned
+            + 4 // reserved
     }
 
     pub const DEFAULT_SPACE: usize = Self::space(MAX_METADATA_URI_LEN);
 
+    /// Size of vaults registered before the fields that follow
+    /// `metadata_uri`, when it was followed only by a 32-byte reserve;
+    /// grown to `DEFAULT_SPACE` by migrate_asset_vault
+    pub const LEGACY_SPACE: usize = Self::DEFAULT_SPACE - Self::LEGACY_GROWTH;
+
+    /// Bytes the fields after `metadata_uri` take beyond the legacy reserve
+    pub const LEGACY_GROWTH: usize = 8  // min_relayer_fee
+        + 8                     // max_relayer_fee
+        + 2                     // asset_index
+        + 8                     // min_withdrawal
+        + MAX_TOKEN_SYMBOL_LEN  // token_symbol
+        + 32                    // token_name_hash
+        + 1                     // risk_tier
+        + 1                     // risk_tier_assigned
+        + 4                     // reserved
+        - 32; // legacy reserved
+
     /// Asset type constants
     pub const ASSET_TYPE_SPL: u8 = 0;
     pub const ASSET_TYPE_NATIVE_SOL: u8 = 1;
@@ -130,6 +185,7 @@ impl AssetVault {
         bump: u8,
         decimals: u8,
         asset_type: u8,
+        asset_index: u16,
         timestamp: i64,
     ) {
         self.pool = pool;
@@ -152,13 +208,42 @@ impl AssetVault {
         self.decimals = decimals;
         self.asset_type = asset_type;
         self.metadata_uri = String::new();
-        self._reserved = [0u8; 32];
+        self.min_relayer_fee = 0;
+        self.max_relayer_fee = 0;
+        self.asset_index = asset_index;
+        self.min_withdrawal = 0;
+        self.token_symbol = [0u8; MAX_TOKEN_SYMBOL_LEN];
+        self.token_name_hash = [0u8; 32];
+        self.risk_tier = 0;
+        self.risk_tier_assigned = false;
+        self._reserved = [0u8; 4];
     }
 
     // =========================================================================
     // Guard Methods
     // =========================================================================
 
+    /// Whether this vault holds native SOL as lamports rather than tokens
+    #[inline]
+    pub fn is_native_sol(&self) -> bool {
+        self.asset_type == Self::ASSET_TYPE_NATIVE_SOL
+    }
+
+    /// Whether this vault holds a Token-2022 mint
+    #[inline]
+    pub fn is_token_2022(&self) -> bool {
+        self.asset_type == Self::ASSET_TYPE_TOKEN_2022
+    }
+
+    /// Asset type for a mint owned by `token_program`
+    pub fn token_asset_type(token_program: &Pubkey) -> u8 {
+        if *token_program == anchor_spl::token_2022::ID {
+            Self::ASSET_TYPE_TOKEN_2022
+        } else {
+            Self::ASSET_TYPE_SPL
+        }
+    }
+
     #[inline]
     pub fn require_active(&self) -> Result<()> {
         require!(self.is_active, PrivacyErrorV2::AssetNotActive);
@@ -192,6 +277,33 @@ impl AssetVault {
         Ok(())
     }
 
+    /// Default withdrawal floor for an asset with `decimals`:
+    /// `10^(decimals / 2)` base units (10_000 lamports for 9 decimals,
+    /// 1_000 for a 6-decimal stablecoin), so dust stays negligible at any
+    /// precision.
+    pub fn default_min_withdrawal(decimals: u8) -> u64 {
+        10u64.saturating_pow(decimals as u32 / 2)
+    }
+
+    /// Effective minimum withdrawal amount (falls back to the default when
+    /// unset)
+    pub fn min_withdrawal(&self) -> u64 {
+        match self.min_withdrawal {
+            0 => Self::default_min_withdrawal(self.decimals),
+            min => min,
+        }
+    }
+
+    /// Reject withdrawals below this asset's minimum (prevents dust attacks)
+    #[inline]
+    pub fn require_min_withdrawal(&self, amount: u64) -> Result<()> {
+        require!(
+            amount >= self.min_withdrawal(),
+            PrivacyErrorV2::BelowMinimumWithdrawal
+        );
+        Ok(())
+    }
+
     pub fn validate_withdrawal_amount(&self, amount: u64) -> Result<()> {
         require!(
             amount <= self.shielded_balance,
@@ -200,14 +312,55 @@ impl AssetVault {
         Ok(())
     }
 
+    /// Enforce the absolute relayer fee bounds for this asset.
+    ///
+    /// Applied alongside the registry's bps bounds. A zero fee (self-relayed
+    /// withdrawal) is exempt from the floor.
+    pub fn validate_relayer_fee(&self, relayer_fee: u64) -> Result<()> {
+        if relayer_fee > 0 && self.min_relayer_fee > 0 {
+            require!(
+                relayer_fee >= self.min_relayer_fee,
+                PrivacyErrorV2::RelayerFeeBelowMinimum
+            );
+        }
+        if self.max_relayer_fee > 0 {
+            require!(
+                relayer_fee <= self.max_relayer_fee,
+                PrivacyErrorV2::RelayerFeeAboveMaximum
+            );
+        }
+        Ok(())
+    }
+
     // =========================================================================
     // Balance Management
     // =========================================================================
 
-    pub fn record_deposit(&mut self, amount: u64, timestamp: i64) -> Result<()> {
+    /// Amount added to the lifetime totals when the pool keeps coarse
+    /// liquidity stats: rounded down to a power of two, so a single update
+    /// only reveals the amount's magnitude. `shielded_balance` stays exact
+    /// (it must match the public vault balance).
+    pub fn coarse_amount(amount: u64) -> u64 {
+        match amount {
+            0 => 0,
+            _ => 1 << (u64::BITS - 1 - amount.leading_zeros()),
+        }
+    }
+
+    pub fn record_deposit(
+        &mut self,
+        amount: u64,
+        timestamp: i64,
+        coarse_stats: bool,
+    ) -> Result<()> {
+        let counted = if coarse_stats {
+            Self::coarse_amount(amount)
+        } else {
+            amount
+        };
         self.total_deposited = self
             .total_deposited
-            .checked_add(amount)
+            .checked_add(counted)
             .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
 
         self.shielded_balance = self
@@ -224,10 +377,20 @@ impl AssetVault {
         Ok(())
     }
 
-    pub fn record_withdrawal(&mut self, amount: u64, timestamp: i64) -> Result<()> {
+    pub fn record_withdrawal(
+        &mut self,
+        amount: u64,
+        timestamp: i64,
+        coarse_stats: bool,
+    ) -> Result<()> {
+        let counted = if coarse_stats {
+            Self::coarse_amount(amount)
+        } else {
+            amount
+        };
         self.total_withdrawn = self
             .total_withdrawn
-            .checked_add(amount)
+            .checked_add(counted)
             .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
 
         self.shielded_balance = self
@@ -244,6 +407,31 @@ impl AssetVault {
         Ok(())
     }
 
+    /// Check the vault's token balance after a transfer: it must still
+    /// back every shielded note (surplus from direct transfers is allowed)
+    pub fn reconcile(&self, vault_balance: u64) -> Result<()> {
+        require!(
+            vault_balance >= self.shielded_balance,
+            PrivacyErrorV2::VaultBalanceDrift
+        );
+        Ok(())
+    }
+
+    /// Check the vault's token balance after paying out `amount`: debited
+    /// exactly that, and still backing every shielded note
+    pub fn reconcile_debit(
+        &self,
+        balance_before: u64,
+        balance_after: u64,
+        amount: u64,
+    ) -> Result<()> {
+        require!(
+            balance_before.checked_sub(balance_after) == Some(amount),
+            PrivacyErrorV2::VaultBalanceDrift
+        );
+        self.reconcile(balance_after)
+    }
+
     // =========================================================================
     // Configuration
     // =========================================================================
@@ -267,6 +455,22 @@ impl AssetVault {
         Ok(())
     }
 
+    /// Set the minimum withdrawal amount (0 restores the decimals-derived
+    /// default)
+    pub fn set_min_withdrawal(&mut self, min: u64) {
+        self.min_withdrawal = min;
+    }
+
+    pub fn set_relayer_fee_bounds(&mut self, min_fee: u64, max_fee: u64) -> Result<()> {
+        require!(
+            max_fee == 0 || min_fee <= max_fee,
+            PrivacyErrorV2::InvalidFeeConfiguration
+        );
+        self.min_relayer_fee = min_fee;
+        self.max_relayer_fee = max_fee;
+        Ok(())
+    }
+
     pub fn set_metadata_uri(&mut self, uri: String) -> Result<()> {
         require!(
             uri.len() <= MAX_METADATA_URI_LEN,
@@ -275,6 +479,68 @@ impl AssetVault {
         self.metadata_uri = uri;
         Ok(())
     }
+
+    /// Put the asset in risk tier `tier`; its limits are read from the
+    /// pool's tier table at deposit and withdrawal time
+    pub fn set_risk_tier(&mut self, tier: u8) {
+        self.risk_tier = tier;
+        self.risk_tier_assigned = true;
+    }
+
+    /// Whether the asset is in a risk tier
+    pub fn has_risk_tier(&self) -> bool {
+        self.risk_tier_assigned
+    }
+
+    /// Take over `source`'s configuration (`clone_asset_config`); balances
+    /// and counters are not carried over, nor is the risk tier, which
+    /// refers to the source pool's tier table
+    pub fn inherit_settings(&mut self, source: &AssetVault) {
+        self.is_active = source.is_active;
+        self.deposits_enabled = source.deposits_enabled;
+        self.withdrawals_enabled = source.withdrawals_enabled;
+        self.min_deposit = source.min_deposit;
+        self.max_deposit = source.max_deposit;
+        self.metadata_uri = source.metadata_uri.clone();
+        self.min_relayer_fee = source.min_relayer_fee;
+        self.max_relayer_fee = source.max_relayer_fee;
+        self.min_withdrawal = source.min_withdrawal;
+        self.token_symbol = source.token_symbol;
+        self.token_name_hash = source.token_name_hash;
+    }
+
+    /// Lamports a native SOL vault holds for notes, above its own rent
+    pub fn native_balance(vault: &AccountInfo) -> Result<u64> {
+        let reserve = Rent::get()?.minimum_balance(vault.data_len());
+        Ok(vault.lamports().saturating_sub(reserve))
+    }
+
+    /// Pay `amount` lamports out of a native SOL vault; the vault is owned
+    /// by this program, so no system transfer (or signer) is needed
+    pub fn pay_native(vault: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+        require!(
+            Self::native_balance(vault)? >= amount,
+            PrivacyErrorV2::InsufficientBalance
+        );
+        **vault.try_borrow_mut_lamports()? -= amount;
+        let credited = to
+            .lamports()
+            .checked_add(amount)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        **to.try_borrow_mut_lamports()? = credited;
+        Ok(())
+    }
+
+    /// Cache the mint's Metaplex symbol and name hash
+    pub fn set_token_metadata(&mut self, metadata: &TokenMetadata) {
+        self.token_symbol = metadata.symbol_bytes();
+        self.token_name_hash = metadata.name_hash();
+    }
+
+    /// Whether Metaplex metadata was cached at registration
+    pub fn has_token_metadata(&self) -> bool {
+        self.token_name_hash != [0u8; 32]
+    }
 }
 
 /// PDA seeds for AssetVault
@@ -291,6 +557,17 @@ impl AssetVault {
     pub fn seeds<'a>(pool: &'a Pubkey, asset_id: &'a [u8; 32], bump: &'a [u8; 1]) -> [&'a [u8]; 4] {
         [Self::SEED_PREFIX, pool.as_ref(), asset_id.as_ref(), bump]
     }
+
+    /// Seed prefix of the asset's treasury token account, which collects
+    /// protocol fees and is owned by the vault
+    pub const TREASURY_SEED_PREFIX: &'static [u8] = b"treasury";
+
+    pub fn find_treasury_pda(program_id: &Pubkey, asset_vault: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::TREASURY_SEED_PREFIX, asset_vault.as_ref()],
+            program_id,
+        )
+    }
 }
 
 /// Helper to compute asset_id from mint address
@@ -309,6 +586,9 @@ pub fn compute_asset_id(mint: &Pubkey) -> [u8; 32] {
 }
 
 /// Native SOL asset ID (special case)
+///
+/// Its vault (`register_native_sol_asset`) holds deposits as lamports on
+/// the `AssetVault` PDA itself; there is no mint or vault token account.
 pub const NATIVE_SOL_ASSET_ID: [u8; 32] = [
     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
@@ -330,9 +610,173 @@ mod tests {
         assert_ne!(id1, id3);
     }
 
+    fn test_vault() -> AssetVault {
+        AssetVault {
+            pool: Pubkey::default(),
+            asset_id: [0u8; 32],
+            mint: Pubkey::default(),
+            token_account: Pubkey::default(),
+            bump: 0,
+            is_active: true,
+            deposits_enabled: true,
+            withdrawals_enabled: true,
+            min_deposit: 0,
+            max_deposit: u64::MAX,
+            total_deposited: 0,
+            total_withdrawn: 0,
+            shielded_balance: 0,
+            deposit_count: 0,
+            withdrawal_count: 0,
+            registered_at: 0,
+            last_activity_at: 0,
+            decimals: 9,
+            asset_type: AssetVault::ASSET_TYPE_SPL,
+            metadata_uri: String::new(),
+            min_relayer_fee: 0,
+            max_relayer_fee: 0,
+            asset_index: 0,
+            min_withdrawal: 0,
+            token_symbol: [0u8; MAX_TOKEN_SYMBOL_LEN],
+            token_name_hash: [0u8; 32],
+            risk_tier: 0,
+            risk_tier_assigned: false,
+            _reserved: [0u8; 4],
+        }
+    }
+
+    #[test]
+    fn test_set_risk_tier() {
+        let mut vault = test_vault();
+        assert!(!vault.has_risk_tier());
+
+        // Tier 0 is a real tier once assigned
+        vault.set_risk_tier(0);
+        assert!(vault.has_risk_tier());
+        assert_eq!(vault.risk_tier, 0);
+
+        // The vault's own limits are left alone
+        vault.set_risk_tier(3);
+        assert_eq!(vault.risk_tier, 3);
+        assert!(vault.require_deposits_enabled().is_ok());
+        assert_eq!(vault.max_deposit, u64::MAX);
+    }
+
+    #[test]
+    fn test_relayer_fee_bounds() {
+        let mut vault = test_vault();
+
+        // Unset bounds accept anything
+        assert!(vault.validate_relayer_fee(u64::MAX).is_ok());
+
+        vault.set_relayer_fee_bounds(1_000, 50_000).unwrap();
+        assert!(vault.validate_relayer_fee(0).is_ok());
+        assert!(vault.validate_relayer_fee(999).is_err());
+        assert!(vault.validate_relayer_fee(1_000).is_ok());
+        assert!(vault.validate_relayer_fee(50_000).is_ok());
+        assert!(vault.validate_relayer_fee(50_001).is_err());
+
+        assert!(vault.set_relayer_fee_bounds(10, 5).is_err());
+        // Floor only
+        vault.set_relayer_fee_bounds(10, 0).unwrap();
+        assert!(vault.validate_relayer_fee(u64::MAX).is_ok());
+    }
+
+    #[test]
+    fn test_min_withdrawal() {
+        assert_eq!(AssetVault::default_min_withdrawal(0), 1);
+        assert_eq!(AssetVault::default_min_withdrawal(6), 1_000);
+        assert_eq!(AssetVault::default_min_withdrawal(9), 10_000);
+        assert_eq!(AssetVault::default_min_withdrawal(255), u64::MAX);
+
+        let mut vault = test_vault();
+        assert_eq!(vault.min_withdrawal(), 10_000);
+        assert!(vault.require_min_withdrawal(9_999).is_err());
+        assert!(vault.require_min_withdrawal(10_000).is_ok());
+
+        vault.set_min_withdrawal(100);
+        assert!(vault.require_min_withdrawal(100).is_ok());
+        assert!(vault.require_min_withdrawal(99).is_err());
+
+        vault.set_min_withdrawal(0);
+        assert_eq!(vault.min_withdrawal(), 10_000);
+    }
+
+    #[test]
+    fn test_coarse_liquidity_stats() {
+        assert_eq!(AssetVault::coarse_amount(0), 0);
+        assert_eq!(AssetVault::coarse_amount(1), 1);
+        assert_eq!(AssetVault::coarse_amount(1_000), 512);
+        assert_eq!(AssetVault::coarse_amount(u64::MAX), 1 << 63);
+
+        let mut vault = test_vault();
+        vault.record_deposit(1_000, 10, true).unwrap();
+        vault.record_deposit(3_000, 11, true).unwrap();
+        vault.record_withdrawal(1_500, 12, true).unwrap();
+        assert_eq!(vault.total_deposited, 512 + 2_048);
+        assert_eq!(vault.total_withdrawn, 1_024);
+        assert_eq!(vault.shielded_balance, 2_500);
+        assert_eq!(vault.deposit_count, 2);
+        assert_eq!(vault.withdrawal_count, 1);
+
+        // Exact stats are unchanged
+        let mut vault = test_vault();
+        vault.record_deposit(1_000, 10, false).unwrap();
+        vault.record_withdrawal(300, 11, false).unwrap();
+        assert_eq!(vault.total_deposited, 1_000);
+        assert_eq!(vault.total_withdrawn, 300);
+    }
+
+    #[test]
+    fn test_reconcile_requires_backed_balance() {
+        let mut vault = test_vault();
+        vault.record_deposit(1_000, 10, false).unwrap();
+
+        assert!(vault.reconcile(1_000).is_ok());
+        // Surplus from direct transfers is fine, a shortfall is drift
+        assert!(vault.reconcile(1_200).is_ok());
+        assert!(vault.reconcile(990).is_err());
+
+        // A payout must debit exactly its amount
+        vault.record_withdrawal(200, 20, false).unwrap();
+        assert!(vault.reconcile_debit(1_000, 800, 200).is_ok());
+        assert!(vault.reconcile_debit(1_000, 790, 200).is_err());
+        assert!(vault.reconcile_debit(1_000, 810, 200).is_err());
+        assert!(vault.reconcile_debit(800, 1_000, 200).is_err());
+        assert_eq!(
+            AssetVault::token_asset_type(&anchor_spl::token_2022::ID),
+            AssetVault::ASSET_TYPE_TOKEN_2022
+        );
+        assert_eq!(
+            AssetVault::token_asset_type(&anchor_spl::token::ID),
+            AssetVault::ASSET_TYPE_SPL
+        );
+    }
+
     #[test]
     fn test_space_calculation() {
         let space = AssetVault::DEFAULT_SPACE;
         assert!(space < 1000);
     }
+
+    #[test]
+    fn test_legacy_vault_parses_after_growth() {
+        let mut vault = test_vault();
+        vault.metadata_uri = "u".repeat(MAX_METADATA_URI_LEN);
+        let mut data = Vec::new();
+        vault.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), AssetVault::DEFAULT_SPACE);
+
+        // Legacy layout: the URI followed by a zeroed 32-byte reserve
+        let uri_end = AssetVault::LEGACY_SPACE - 32;
+        data.truncate(uri_end);
+        data.resize(AssetVault::LEGACY_SPACE, 0);
+        assert!(AssetVault::try_deserialize(&mut &data[..]).is_err());
+
+        data.resize(AssetVault::DEFAULT_SPACE, 0);
+        let migrated = AssetVault::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(migrated.metadata_uri, vault.metadata_uri);
+        assert_eq!(migrated.min_withdrawal(), 10_000);
+        assert!(!migrated.has_token_metadata());
+        assert!(!migrated.has_risk_tier());
+    }
 }
@@ -0,0 +1,163 @@
+//! Attested Root Record - Authority-signed Merkle root snapshots
+//!
+//! `publish_attested_root` records the tree's current root and leaf count
+//! together with an ed25519 signature by the pool authority over
+//! [`AttestedRootRecord::attestation_message`]. The signature is checked
+//! on-chain through the ed25519 program, then stored so that off-chain
+//! auditors and L2-style verifiers can check it again without trusting an
+//! RPC node: the record is self-contained.
+//!
+//! One record exists per attested root. Ordering records by `leaf_count`
+//! gives the tree's history; leaf counts only grow between resets, so a gap
+//! or a decrease is visible to anyone checking continuity.
+//!
+//! PDA Seeds: `[b"attested_root", pool.key().as_ref(), root.as_ref()]`
+
+use anchor_lang::prelude::*;
+
+/// Domain separator for root attestation signatures
+pub const ROOT_ATTESTATION_DOMAIN: &[u8] = b"psol-v2:attested-root";
+
+#[account]
+#[derive(InitSpace)]
+pub struct AttestedRootRecord {
+    /// Parent pool configuration
+    pub pool: Pubkey,
+
+    /// Merkle tree the root was read from
+    pub merkle_tree: Pubkey,
+
+    /// Attested Merkle root
+    pub root: [u8; 32],
+
+    /// Leaves in the tree at this root
+    pub leaf_count: u32,
+
+    /// Tree depth
+    pub depth: u8,
+
+    /// Pool authority that signed the attestation
+    pub authority: Pubkey,
+
+    /// Ed25519 signature by `authority` over `attestation_message`
+    pub signature: [u8; 64],
+
+    /// Slot the record was published in
+    pub slot: u64,
+
+    /// Publication timestamp
+    pub attested_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved for future use
+    pub _reserved: [u8; 16],
+}
+
+impl AttestedRootRecord {
+    pub const SEED_PREFIX: &'static [u8] = b"attested_root";
+
+    pub const LEN: usize = 8 // discriminator
+        + 32                 // pool
+        + 32                 // merkle_tree
+        + 32                 // root
+        + 4                  // leaf_count
+        + 1                  // depth
+        + 32                 // authority
+        + 64                 // signature
+        + 8                  // slot
+        + 8                  // attested_at
+        + 1                  // bump
+        + 16; // reserved
+
+    /// Message the authority signs for an attestation
+    ///
+    /// `domain || pool || merkle_tree || root || leaf_count (LE) || depth`
+    pub fn attestation_message(
+        pool: &Pubkey,
+        merkle_tree: &Pubkey,
+        root: &[u8; 32],
+        leaf_count: u32,
+        depth: u8,
+    ) -> Vec<u8> {
+        let mut message = Vec::with_capacity(ROOT_ATTESTATION_DOMAIN.len() + 32 * 3 + 4 + 1);
+        message.extend_from_slice(ROOT_ATTESTATION_DOMAIN);
+        message.extend_from_slice(pool.as_ref());
+        message.extend_from_slice(merkle_tree.as_ref());
+        message.extend_from_slice(root);
+        message.extend_from_slice(&leaf_count.to_le_bytes());
+        message.push(depth);
+        message
+    }
+
+    /// Message this record's signature covers
+    pub fn message(&self) -> Vec<u8> {
+        Self::attestation_message(
+            &self.pool,
+            &self.merkle_tree,
+            &self.root,
+            self.leaf_count,
+            self.depth,
+        )
+    }
+
+    pub fn find_pda(program_id: &Pubkey, pool: &Pubkey, root: &[u8; 32]) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, pool.as_ref(), root], program_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_len_matches_serialized_size() {
+        let record = AttestedRootRecord {
+            pool: Pubkey::new_unique(),
+            merkle_tree: Pubkey::new_unique(),
+            root: [1u8; 32],
+            leaf_count: 7,
+            depth: 20,
+            authority: Pubkey::new_unique(),
+            signature: [2u8; 64],
+            slot: 99,
+            attested_at: 1_000,
+            bump: 255,
+            _reserved: [0u8; 16],
+        };
+        let mut data = Vec::new();
+        record.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), AttestedRootRecord::LEN);
+    }
+
+    #[test]
+    fn test_attestation_message_binds_every_field() {
+        let pool = Pubkey::new_unique();
+        let tree = Pubkey::new_unique();
+        let base = AttestedRootRecord::attestation_message(&pool, &tree, &[1u8; 32], 7, 20);
+        assert!(base.starts_with(ROOT_ATTESTATION_DOMAIN));
+
+        for other in [
+            AttestedRootRecord::attestation_message(
+                &Pubkey::new_unique(),
+                &tree,
+                &[1u8; 32],
+                7,
+                20,
+            ),
+            AttestedRootRecord::attestation_message(
+                &pool,
+                &Pubkey::new_unique(),
+                &[1u8; 32],
+                7,
+                20,
+            ),
+            AttestedRootRecord::attestation_message(&pool, &tree, &[2u8; 32], 7, 20),
+            AttestedRootRecord::attestation_message(&pool, &tree, &[1u8; 32], 8, 20),
+            AttestedRootRecord::attestation_message(&pool, &tree, &[1u8; 32], 7, 21),
+        ] {
+            assert_ne!(base, other);
+        }
+    }
+}
@@ -9,6 +9,16 @@
 //! This prevents unauthorized batching and ensures designated batchers
 //! are controlled by on-chain state, not user-provided instruction arguments.
 //!
+//! # Bonding
+//!
+//! Designated batchers lock at least `MIN_BATCHER_BOND_LAMPORTS` in their
+//! role account. Misbehavior the program cannot see itself (reordering or
+//! withholding commitments off-chain, shown from emitted events) is
+//! reported by the pool authority, which moves the bond to the pool's
+//! insurance fund and revokes the role. A batcher that leaves gets its bond
+//! back after `BATCHER_UNBONDING_SECONDS`, so it cannot exit ahead of a
+//! pending report.
+//!
 //! # PDA Seeds
 //! `[b"batcher", pool.key().as_ref(), batcher.key().as_ref()]`
 //!
@@ -28,10 +38,25 @@
 use crate::error::PrivacyErrorV2;
 use anchor_lang::prelude::*; // CORRECTED: Import error type
 
+/// Minimum bond a designated batcher must lock (1 SOL)
+pub const MIN_BATCHER_BOND_LAMPORTS: u64 = 1_000_000_000;
+
+/// Delay between requesting exit and withdrawing the bond (7 days)
+pub const BATCHER_UNBONDING_SECONDS: i64 = 7 * 24 * 60 * 60;
+
+/// Seed of the pool's insurance fund, a data-less system account that
+/// receives slashed bonds: `[b"insurance_fund", pool]`
+pub const INSURANCE_FUND_SEED: &[u8] = b"insurance_fund";
+
+pub fn find_insurance_fund_pda(program_id: &Pubkey, pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[INSURANCE_FUND_SEED, pool.as_ref()], program_id)
+}
+
 /// Batcher Role PDA - On-Chain Authorization
 ///
 /// Seeds: `[b"batcher", pool, batcher]`
 #[account]
+#[derive(InitSpace)]
 pub struct BatcherRole {
     /// Pool this batcher is authorized for
     pub pool: Pubkey,
@@ -59,6 +84,15 @@ pub struct BatcherRole {
 
     /// Account version
     pub version: u8,
+
+    /// Lamports locked as bond (on top of rent)
+    pub bond_lamports: u64,
+
+    /// When the batcher asked to leave (0 = active)
+    pub exit_requested_at: i64,
+
+    /// When the bond was slashed (0 = never)
+    pub slashed_at: i64,
 }
 
 impl BatcherRole {
@@ -74,12 +108,26 @@ impl BatcherRole {
         + 8   // total_batches_processed
         + 8   // total_deposits_batched
         + 1   // bump
-        + 1; // version
+        + 1   // version
+        + 8   // bond_lamports
+        + 8   // exit_requested_at
+        + 8; // slashed_at
 
-    pub const VERSION: u8 = 1;
+    pub const VERSION: u8 = 2;
 
     /// Initialize a batcher role
-    pub fn initialize(&mut self, pool: Pubkey, batcher: Pubkey, bump: u8, timestamp: i64) {
+    pub fn initialize(
+        &mut self,
+        pool: Pubkey,
+        batcher: Pubkey,
+        bond_lamports: u64,
+        bump: u8,
+        timestamp: i64,
+    ) -> Result<()> {
+        require!(
+            bond_lamports >= MIN_BATCHER_BOND_LAMPORTS,
+            PrivacyErrorV2::BatcherBondTooLow
+        );
         self.pool = pool;
         self.batcher = batcher;
         self.is_enabled = true;
@@ -89,6 +137,51 @@ impl BatcherRole {
         self.total_deposits_batched = 0;
         self.bump = bump;
         self.version = Self::VERSION;
+        self.bond_lamports = bond_lamports;
+        self.exit_requested_at = 0;
+        self.slashed_at = 0;
+        Ok(())
+    }
+
+    /// Enabled, bonded and not leaving
+    pub fn can_batch(&self) -> bool {
+        self.is_enabled
+            && self.bond_lamports >= MIN_BATCHER_BOND_LAMPORTS
+            && self.exit_requested_at == 0
+            && self.slashed_at == 0
+    }
+
+    /// Stop batching and start the unbonding period
+    pub fn request_exit(&mut self, timestamp: i64) {
+        self.is_enabled = false;
+        if self.exit_requested_at == 0 {
+            self.exit_requested_at = timestamp;
+        }
+        self.updated_at = timestamp;
+    }
+
+    /// Whether the bond may be withdrawn at `timestamp`
+    pub fn require_unbonded(&self, timestamp: i64) -> Result<()> {
+        require!(
+            self.exit_requested_at > 0
+                && timestamp
+                    >= self
+                        .exit_requested_at
+                        .saturating_add(BATCHER_UNBONDING_SECONDS),
+            PrivacyErrorV2::BatcherUnbonding
+        );
+        Ok(())
+    }
+
+    /// Revoke the role and take the bond; returns the lamports to move
+    pub fn slash(&mut self, timestamp: i64) -> Result<u64> {
+        require!(self.slashed_at == 0, PrivacyErrorV2::BatcherAlreadySlashed);
+        let bond = self.bond_lamports;
+        self.bond_lamports = 0;
+        self.is_enabled = false;
+        self.slashed_at = timestamp;
+        self.updated_at = timestamp;
+        Ok(bond)
     }
 
     /// Record a batch processed by this batcher
@@ -126,8 +219,87 @@ impl BatcherRole {
 mod tests {
     use super::*;
 
+    fn role() -> BatcherRole {
+        let mut role = BatcherRole {
+            pool: Pubkey::default(),
+            batcher: Pubkey::default(),
+            is_enabled: false,
+            created_at: 0,
+            updated_at: 0,
+            total_batches_processed: 0,
+            total_deposits_batched: 0,
+            bump: 0,
+            version: 0,
+            bond_lamports: 0,
+            exit_requested_at: 0,
+            slashed_at: 0,
+        };
+        role.initialize(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            MIN_BATCHER_BOND_LAMPORTS,
+            255,
+            100,
+        )
+        .unwrap();
+        role
+    }
+
     #[test]
     fn test_batcher_role_size() {
-        assert_eq!(BatcherRole::LEN, 107);
+        assert_eq!(BatcherRole::LEN, 131);
+        let mut data = Vec::new();
+        role().try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), BatcherRole::LEN);
+    }
+
+    #[test]
+    fn test_bond_required() {
+        let mut unbonded = role();
+        assert_eq!(
+            unbonded
+                .initialize(
+                    Pubkey::new_unique(),
+                    Pubkey::new_unique(),
+                    MIN_BATCHER_BOND_LAMPORTS - 1,
+                    255,
+                    100,
+                )
+                .unwrap_err(),
+            PrivacyErrorV2::BatcherBondTooLow.into()
+        );
+        assert!(role().can_batch());
+    }
+
+    #[test]
+    fn test_slash_revokes_and_takes_bond() {
+        let mut role = role();
+        assert_eq!(role.slash(200).unwrap(), MIN_BATCHER_BOND_LAMPORTS);
+        assert!(!role.can_batch());
+        assert_eq!(role.bond_lamports, 0);
+        assert_eq!(
+            role.slash(201).unwrap_err(),
+            PrivacyErrorV2::BatcherAlreadySlashed.into()
+        );
+    }
+
+    #[test]
+    fn test_exit_waits_for_unbonding() {
+        let mut role = role();
+        assert!(role.require_unbonded(100).is_err());
+
+        role.request_exit(1_000);
+        assert!(!role.can_batch());
+        // A second request does not restart the clock
+        role.request_exit(2_000);
+        assert!(role
+            .require_unbonded(1_000 + BATCHER_UNBONDING_SECONDS - 1)
+            .is_err());
+        assert!(role
+            .require_unbonded(1_000 + BATCHER_UNBONDING_SECONDS)
+            .is_ok());
+
+        // Slashing still applies while unbonding
+        assert_eq!(role.slash(3_000).unwrap(), MIN_BATCHER_BOND_LAMPORTS);
     }
 }
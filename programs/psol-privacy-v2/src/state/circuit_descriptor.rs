@@ -0,0 +1,212 @@
+//! Circuit Descriptor - Parameters a verification key was generated for
+//!
+//! A Groth16 VK only proves statements about the circuit it came from. If
+//! that circuit was compiled for a different tree depth or Poseidon
+//! parameter set, every proof fails (or, worse, proves the wrong thing).
+//! The descriptor records those assumptions next to the VK so they can be
+//! checked against the pool before the VK is set.
+//!
+//! PDA Seeds: `[b"circuit_desc", proof_type.as_seed(), pool.key().as_ref()]`
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::state::VerificationKeyAccountV2;
+use crate::ProofType;
+
+#[account]
+#[derive(InitSpace)]
+pub struct CircuitDescriptor {
+    /// Parent pool configuration
+    pub pool: Pubkey,
+
+    /// Proof type this descriptor covers
+    pub proof_type: u8,
+
+    /// Circuit release version
+    pub circuit_version: u16,
+
+    /// Hash of the circuit's public input ordering/encoding
+    pub public_input_layout_hash: [u8; 32],
+
+    /// Number of public inputs the circuit exposes
+    pub num_public_inputs: u8,
+
+    /// Merkle tree depth the circuit was compiled for (0 if unused)
+    pub tree_depth: u8,
+
+    /// Hash of the Poseidon parameter set (round constants, MDS matrix)
+    pub poseidon_params_hash: [u8; 32],
+
+    /// Last update timestamp
+    pub updated_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved for future use
+    pub _reserved: [u8; 16],
+}
+
+impl CircuitDescriptor {
+    pub const LEN: usize = 8  // discriminator
+        + 32                  // pool
+        + 1                   // proof_type
+        + 2                   // circuit_version
+        + 32                  // public_input_layout_hash
+        + 1                   // num_public_inputs
+        + 1                   // tree_depth
+        + 32                  // poseidon_params_hash
+        + 8                   // updated_at
+        + 1                   // bump
+        + 16; // reserved
+
+    /// Whether circuits of this proof type include a Merkle path
+    pub fn uses_merkle_tree(proof_type: ProofType) -> bool {
+        !matches!(proof_type, ProofType::Deposit)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn set(
+        &mut self,
+        pool: Pubkey,
+        proof_type: ProofType,
+        circuit_version: u16,
+        public_input_layout_hash: [u8; 32],
+        tree_depth: u8,
+        poseidon_params_hash: [u8; 32],
+        timestamp: i64,
+        bump: u8,
+    ) {
+        self.pool = pool;
+        self.proof_type = proof_type as u8;
+        self.circuit_version = circuit_version;
+        self.public_input_layout_hash = public_input_layout_hash;
+        self.num_public_inputs =
+            VerificationKeyAccountV2::expected_public_inputs_for_type(proof_type);
+        self.tree_depth = tree_depth;
+        self.poseidon_params_hash = poseidon_params_hash;
+        self.updated_at = timestamp;
+        self.bump = bump;
+        self._reserved = [0u8; 16];
+    }
+
+    /// Check the descriptor is usable for `proof_type` in a pool whose tree
+    /// has depth `pool_tree_depth` and hashes with the Poseidon parameter
+    /// set `tree_poseidon_params_hash`
+    ///
+    /// # Errors
+    /// - `CircuitDescriptorMismatch` on wrong pool/proof type, a tree depth
+    ///   different from the pool's, or an unset layout hash
+    /// - `PoseidonParamsMismatch` if the circuit was built with a different
+    ///   Poseidon parameter set than the tree uses
+    pub fn validate_for_pool(
+        &self,
+        pool_key: &Pubkey,
+        pool_tree_depth: u8,
+        tree_poseidon_params_hash: &[u8; 32],
+        proof_type: ProofType,
+    ) -> Result<()> {
+        require!(
+            self.pool == *pool_key && self.proof_type == proof_type as u8,
+            PrivacyErrorV2::CircuitDescriptorMismatch
+        );
+        if Self::uses_merkle_tree(proof_type) {
+            require!(
+                self.tree_depth == pool_tree_depth,
+                PrivacyErrorV2::CircuitDescriptorMismatch
+            );
+        }
+        require!(
+            self.public_input_layout_hash != [0u8; 32],
+            PrivacyErrorV2::CircuitDescriptorMismatch
+        );
+        require!(
+            self.poseidon_params_hash == *tree_poseidon_params_hash,
+            PrivacyErrorV2::PoseidonParamsMismatch
+        );
+        Ok(())
+    }
+}
+
+impl CircuitDescriptor {
+    pub const SEED_PREFIX: &'static [u8] = b"circuit_desc";
+
+    pub fn find_pda(program_id: &Pubkey, pool: &Pubkey, proof_type: ProofType) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED_PREFIX, proof_type.as_seed(), pool.as_ref()],
+            program_id,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARAMS: [u8; 32] = [2u8; 32];
+
+    fn descriptor(pool: Pubkey, proof_type: ProofType, tree_depth: u8) -> CircuitDescriptor {
+        let mut d = CircuitDescriptor {
+            pool: Pubkey::default(),
+            proof_type: 0,
+            circuit_version: 0,
+            public_input_layout_hash: [0u8; 32],
+            num_public_inputs: 0,
+            tree_depth: 0,
+            poseidon_params_hash: [0u8; 32],
+            updated_at: 0,
+            bump: 0,
+            _reserved: [0u8; 16],
+        };
+        d.set(pool, proof_type, 1, [1u8; 32], tree_depth, PARAMS, 0, 0);
+        d
+    }
+
+    #[test]
+    fn test_depth_mismatch_rejected() {
+        let pool = Pubkey::new_unique();
+
+        let ok = descriptor(pool, ProofType::Withdraw, 20);
+        assert!(ok
+            .validate_for_pool(&pool, 20, &PARAMS, ProofType::Withdraw)
+            .is_ok());
+        assert_eq!(ok.num_public_inputs, 8);
+
+        let wrong_depth = descriptor(pool, ProofType::Withdraw, 24);
+        assert!(wrong_depth
+            .validate_for_pool(&pool, 20, &PARAMS, ProofType::Withdraw)
+            .is_err());
+
+        // Deposit circuits have no Merkle path: depth is not checked
+        let deposit = descriptor(pool, ProofType::Deposit, 0);
+        assert!(deposit
+            .validate_for_pool(&pool, 20, &PARAMS, ProofType::Deposit)
+            .is_ok());
+
+        // Descriptor for another proof type or pool does not apply
+        assert!(ok
+            .validate_for_pool(&pool, 20, &PARAMS, ProofType::JoinSplit)
+            .is_err());
+        assert!(ok
+            .validate_for_pool(&Pubkey::new_unique(), 20, &PARAMS, ProofType::Withdraw)
+            .is_err());
+    }
+
+    #[test]
+    fn test_poseidon_params_mismatch_rejected() {
+        let pool = Pubkey::new_unique();
+        let d = descriptor(pool, ProofType::Withdraw, 20);
+
+        let err = d
+            .validate_for_pool(&pool, 20, &[3u8; 32], ProofType::Withdraw)
+            .unwrap_err();
+        assert_eq!(err, PrivacyErrorV2::PoseidonParamsMismatch.into());
+
+        // Deposit circuits hash commitments with the same parameters
+        let deposit = descriptor(pool, ProofType::Deposit, 0);
+        assert!(deposit
+            .validate_for_pool(&pool, 20, &[3u8; 32], ProofType::Deposit)
+            .is_err());
+    }
+}
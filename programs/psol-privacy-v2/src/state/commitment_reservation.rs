@@ -0,0 +1,183 @@
+//! Commitment Reservation - Optional first step of a deposit
+//!
+//! A pending `deposit_masp` transaction shows its commitment and proof; on
+//! a pool whose deposit VK does not bind the depositor, anyone can copy
+//! both and insert the same commitment first, funded from elsewhere,
+//! leaving the wallet with a note it did not pay for and a deposit that
+//! queues a duplicate. A depositor who wants to rule that out reserves the
+//! commitment with `reserve_commitment` in an earlier transaction, which
+//! shows neither the amount nor the proof; for
+//! `COMMITMENT_RESERVATION_SLOTS` only that wallet can deposit the
+//! commitment, and its deposit closes the reservation.
+//!
+//! Deposits always pass the commitment's reservation PDA, so a copied
+//! deposit cannot leave the reservation out.
+//!
+//! The PDA is keyed by the commitment alone, so a wallet that sees a
+//! `reserve_commitment` can reserve the commitment first. A commitment is
+//! therefore reserved at most once: the account outlives its expiry and
+//! release, and only the depositor's deposit of the commitment closes it.
+//! Neither the wallet that held it nor any other can reserve it again, so
+//! a front-runner blocks a commitment for `COMMITMENT_RESERVATION_SLOTS`
+//! at most, after which anyone can deposit it.
+//!
+//! PDA Seeds: `[b"commitment_reservation", pool.key().as_ref(), commitment.as_ref()]`
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+
+/// Slots a reservation holds its commitment for; enough to sign and land
+/// the deposit once the reservation confirms (a blockhash stays valid for
+/// 150 slots)
+pub const COMMITMENT_RESERVATION_SLOTS: u64 = 300;
+
+#[account]
+#[derive(InitSpace)]
+pub struct CommitmentReservation {
+    /// Pool the commitment is to be deposited in
+    pub pool: Pubkey,
+
+    /// Wallet that reserved the commitment (the only one that may deposit
+    /// it while the reservation holds; receives the rent back)
+    pub depositor: Pubkey,
+
+    /// Reserved commitment
+    pub commitment: [u8; 32],
+
+    /// First slot at which the reservation no longer holds
+    pub expires_slot: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl CommitmentReservation {
+    pub const LEN: usize = 8  // discriminator
+        + 32                  // pool
+        + 32                  // depositor
+        + 32                  // commitment
+        + 8                   // expires_slot
+        + 1; // bump
+
+    pub fn initialize(
+        &mut self,
+        pool: Pubkey,
+        depositor: Pubkey,
+        commitment: [u8; 32],
+        slot: u64,
+        bump: u8,
+    ) {
+        self.pool = pool;
+        self.depositor = depositor;
+        self.commitment = commitment;
+        self.expires_slot = slot.saturating_add(COMMITMENT_RESERVATION_SLOTS);
+        self.bump = bump;
+    }
+
+    /// End the reservation at `slot` if it still holds; the account stays
+    /// so the commitment cannot be reserved again
+    pub fn release(&mut self, slot: u64) {
+        self.expires_slot = self.expires_slot.min(slot);
+    }
+
+    /// Whether the reservation still holds at `slot`
+    pub fn is_live(&self, slot: u64) -> bool {
+        slot < self.expires_slot
+    }
+
+    /// Check `depositor` may deposit the commitment at `slot`
+    pub fn require_depositor(&self, depositor: &Pubkey, slot: u64) -> Result<()> {
+        require!(
+            self.depositor == *depositor || !self.is_live(slot),
+            PrivacyErrorV2::CommitmentReserved
+        );
+        Ok(())
+    }
+
+    /// The reservation stored at `info`, or `None` where there is none
+    pub fn load(info: &AccountInfo) -> Result<Option<Self>> {
+        if info.owner != &crate::ID || info.data_is_empty() {
+            return Ok(None);
+        }
+        let data = info.try_borrow_data()?;
+        Ok(Some(Self::try_deserialize(&mut &data[..])?))
+    }
+}
+
+impl CommitmentReservation {
+    pub const SEED_PREFIX: &'static [u8] = b"commitment_reservation";
+
+    pub fn find_pda(program_id: &Pubkey, pool: &Pubkey, commitment: &[u8; 32]) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, pool.as_ref(), commitment], program_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reservation_admits_only_its_depositor_until_expiry() {
+        let depositor = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let mut r = CommitmentReservation {
+            pool: Pubkey::default(),
+            depositor: Pubkey::default(),
+            commitment: [0u8; 32],
+            expires_slot: 0,
+            bump: 0,
+        };
+        r.initialize(Pubkey::default(), depositor, [1u8; 32], 1_000, 0);
+        let expiry = 1_000 + COMMITMENT_RESERVATION_SLOTS;
+
+        assert!(r.require_depositor(&depositor, 1_000).is_ok());
+        assert!(r.require_depositor(&other, 1_000).is_err());
+        assert!(r.require_depositor(&other, expiry - 1).is_err());
+        assert!(r.require_depositor(&other, expiry).is_ok());
+        assert!(r.require_depositor(&depositor, expiry).is_ok());
+    }
+
+    #[test]
+    fn test_release_only_shortens_the_reservation() {
+        let depositor = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let mut r = CommitmentReservation {
+            pool: Pubkey::default(),
+            depositor: Pubkey::default(),
+            commitment: [0u8; 32],
+            expires_slot: 0,
+            bump: 0,
+        };
+        r.initialize(Pubkey::default(), depositor, [1u8; 32], 1_000, 0);
+        let expiry = r.expires_slot;
+
+        r.release(1_010);
+        assert_eq!(r.expires_slot, 1_010);
+        assert!(r.require_depositor(&other, 1_010).is_ok());
+
+        // Releasing again later cannot extend it
+        r.release(expiry + 10);
+        assert_eq!(r.expires_slot, 1_010);
+        assert_eq!(r.depositor, depositor);
+    }
+
+    #[test]
+    fn test_load_skips_missing_accounts() {
+        let key = Pubkey::new_unique();
+        let owner = anchor_lang::system_program::ID;
+        let mut lamports = 0u64;
+        let mut data = Vec::new();
+        let info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        assert!(CommitmentReservation::load(&info).unwrap().is_none());
+    }
+}
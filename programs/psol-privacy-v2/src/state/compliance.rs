@@ -23,6 +23,7 @@ pub const MAX_ENCRYPTED_METADATA_LEN: usize = 1024;
 ///
 /// PDA Seeds: `[b"compliance", pool.key().as_ref()]`
 #[account]
+#[derive(InitSpace)]
 pub struct ComplianceConfig {
     /// Reference to parent pool
     pub pool: Pubkey,
@@ -121,6 +122,16 @@ impl ComplianceConfig {
         }
     }
 
+    /// Take over `source`'s settings (`clone_pool_config`); attachments
+    /// are not carried over
+    pub fn inherit_settings(&mut self, source: &ComplianceConfig) {
+        self.require_encrypted_note = source.require_encrypted_note;
+        self.audit_pubkey = source.audit_pubkey;
+        self.audit_enabled = source.audit_enabled;
+        self.metadata_schema_version = source.metadata_schema_version;
+        self.compliance_level = source.compliance_level;
+    }
+
     /// Record an attachment
     pub fn record_attachment(&mut self, timestamp: i64) -> Result<()> {
         self.attachment_count = self
@@ -0,0 +1,234 @@
+//! Deferred Withdraw Events - Delayed emission of withdrawal metadata
+//!
+//! When a pool has this PDA and a relayer passes it to `withdraw_masp`, the
+//! withdrawal's `WithdrawMaspEvent` is queued here instead of being emitted
+//! with the spend. `flush_deferred_withdraw_events` (permissionless crank)
+//! emits every queued event whose delay has passed, in one batch and stamped
+//! with the flush time, so event-based indexers no longer see when each
+//! nullifier was spent.
+//!
+//! Withdrawals are never blocked by this account: with a zero delay or a
+//! full queue the event is emitted immediately as before.
+//!
+//! PDA Seeds: `[b"deferred_withdraw_events", pool.key().as_ref()]`
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+
+/// Hard upper bound on the per-pool delay (1 hour)
+pub const MAX_WITHDRAW_EVENT_DELAY_SECONDS: u32 = 60 * 60;
+
+/// Queue capacity; further withdrawals emit immediately until flushed
+pub const MAX_DEFERRED_WITHDRAW_EVENTS: usize = 32;
+
+/// `WithdrawMaspEvent` fields held back until `release_at`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DeferredWithdrawEvent {
+    pub nullifier_hash: [u8; 32],
+    pub asset_id: [u8; 32],
+    pub relayer: Pubkey,
+    pub relayer_fee: u64,
+    /// Event sequence number assigned at spend time
+    pub sequence: u64,
+    /// Earliest time the event may be emitted
+    pub release_at: i64,
+}
+
+impl DeferredWithdrawEvent {
+    pub const LEN: usize = 32 + 32 + 32 + 8 + 8 + 8;
+}
+
+#[account]
+pub struct DeferredWithdrawEvents {
+    /// Parent pool configuration
+    pub pool: Pubkey,
+
+    /// Seconds an event is held back (0 = emit immediately)
+    pub delay_seconds: u32,
+
+    /// Queued events, oldest first
+    pub events: Vec<DeferredWithdrawEvent>,
+
+    /// Events ever queued
+    pub total_deferred: u64,
+
+    /// Events ever flushed
+    pub total_flushed: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved for future use
+    pub _reserved: [u8; 16],
+}
+
+impl DeferredWithdrawEvents {
+    pub const SEED_PREFIX: &'static [u8] = b"deferred_withdraw_events";
+
+    pub const LEN: usize = 8                                               // discriminator
+        + 32                                                                // pool
+        + 4                                                                 // delay_seconds
+        + 4 + (DeferredWithdrawEvent::LEN * MAX_DEFERRED_WITHDRAW_EVENTS)  // events (vec)
+        + 8                                                                 // total_deferred
+        + 8                                                                 // total_flushed
+        + 1                                                                 // bump
+        + 16; // reserved
+
+    pub fn initialize(&mut self, pool: Pubkey, delay_seconds: u32, bump: u8) -> Result<()> {
+        self.pool = pool;
+        self.events = Vec::with_capacity(MAX_DEFERRED_WITHDRAW_EVENTS);
+        self.total_deferred = 0;
+        self.total_flushed = 0;
+        self.bump = bump;
+        self._reserved = [0u8; 16];
+        self.set_delay(delay_seconds)
+    }
+
+    pub fn set_delay(&mut self, delay_seconds: u32) -> Result<()> {
+        require!(
+            delay_seconds <= MAX_WITHDRAW_EVENT_DELAY_SECONDS,
+            PrivacyErrorV2::InvalidWithdrawEventDelay
+        );
+        self.delay_seconds = delay_seconds;
+        Ok(())
+    }
+
+    /// Queue an event spent at `timestamp`
+    ///
+    /// Returns the event back when it has to be emitted now instead (no
+    /// delay configured or queue full).
+    pub fn defer(
+        &mut self,
+        mut event: DeferredWithdrawEvent,
+        timestamp: i64,
+    ) -> Result<Option<DeferredWithdrawEvent>> {
+        if self.delay_seconds == 0 || self.events.len() >= MAX_DEFERRED_WITHDRAW_EVENTS {
+            return Ok(Some(event));
+        }
+
+        event.release_at = timestamp
+            .checked_add(self.delay_seconds as i64)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        self.events.push(event);
+        self.total_deferred = self
+            .total_deferred
+            .checked_add(1)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        Ok(None)
+    }
+
+    /// Remove and return up to `max` events released by `timestamp`
+    ///
+    /// Every event shares the same delay, so released events are a prefix
+    /// of the queue.
+    pub fn take_released(
+        &mut self,
+        timestamp: i64,
+        max: usize,
+    ) -> Result<Vec<DeferredWithdrawEvent>> {
+        let released = self
+            .events
+            .iter()
+            .take(max)
+            .take_while(|event| event.release_at <= timestamp)
+            .count();
+        let taken: Vec<_> = self.events.drain(..released).collect();
+        self.total_flushed = self
+            .total_flushed
+            .checked_add(taken.len() as u64)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        Ok(taken)
+    }
+
+    pub fn find_pda(program_id: &Pubkey, pool: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, pool.as_ref()], program_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue(delay_seconds: u32) -> DeferredWithdrawEvents {
+        let mut queue = DeferredWithdrawEvents {
+            pool: Pubkey::default(),
+            delay_seconds: 0,
+            events: Vec::new(),
+            total_deferred: 0,
+            total_flushed: 0,
+            bump: 0,
+            _reserved: [0u8; 16],
+        };
+        queue
+            .initialize(Pubkey::new_unique(), delay_seconds, 255)
+            .unwrap();
+        queue
+    }
+
+    fn event(sequence: u64) -> DeferredWithdrawEvent {
+        DeferredWithdrawEvent {
+            nullifier_hash: [sequence as u8; 32],
+            asset_id: [1u8; 32],
+            relayer: Pubkey::default(),
+            relayer_fee: 10,
+            sequence,
+            release_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_len_fits_full_queue() {
+        let mut full = queue(60);
+        for i in 0..MAX_DEFERRED_WITHDRAW_EVENTS as u64 {
+            full.defer(event(i), 1_000).unwrap();
+        }
+        let mut data = Vec::new();
+        full.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), DeferredWithdrawEvents::LEN);
+    }
+
+    #[test]
+    fn test_defer_and_release_in_order() {
+        let mut q = queue(60);
+        assert_eq!(q.defer(event(1), 1_000).unwrap(), None);
+        assert_eq!(q.defer(event(2), 1_030).unwrap(), None);
+        assert_eq!(q.events[0].release_at, 1_060);
+
+        assert!(q.take_released(1_059, 10).unwrap().is_empty());
+        let released = q.take_released(1_060, 10).unwrap();
+        assert_eq!(released.len(), 1);
+        assert_eq!(released[0].sequence, 1);
+
+        // `max` bounds one flush
+        q.defer(event(3), 1_031).unwrap();
+        assert_eq!(q.take_released(2_000, 1).unwrap()[0].sequence, 2);
+        assert_eq!(q.take_released(2_000, 1).unwrap()[0].sequence, 3);
+        assert_eq!(q.total_deferred, 3);
+        assert_eq!(q.total_flushed, 3);
+    }
+
+    #[test]
+    fn test_emits_immediately_without_delay_or_room() {
+        let mut q = queue(0);
+        assert_eq!(q.defer(event(1), 1_000).unwrap(), Some(event(1)));
+
+        let mut q = queue(60);
+        for i in 0..MAX_DEFERRED_WITHDRAW_EVENTS as u64 {
+            assert_eq!(q.defer(event(i), 1_000).unwrap(), None);
+        }
+        assert_eq!(q.defer(event(99), 1_000).unwrap(), Some(event(99)));
+        assert_eq!(q.total_deferred, MAX_DEFERRED_WITHDRAW_EVENTS as u64);
+    }
+
+    #[test]
+    fn test_delay_bounded() {
+        let mut q = queue(MAX_WITHDRAW_EVENT_DELAY_SECONDS);
+        assert_eq!(
+            q.set_delay(MAX_WITHDRAW_EVENT_DELAY_SECONDS + 1)
+                .unwrap_err(),
+            PrivacyErrorV2::InvalidWithdrawEventDelay.into()
+        );
+        assert_eq!(q.delay_seconds, MAX_WITHDRAW_EVENT_DELAY_SECONDS);
+    }
+}
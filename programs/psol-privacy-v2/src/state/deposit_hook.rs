@@ -0,0 +1,153 @@
+//! Deposit Hook - on-chain callback for finalized deposits
+//!
+//! An integrator program (points, deposit insurance, ...) that wants to
+//! react to deposits without polling events asks the pool authority to
+//! register it. From then on `batch_process_deposits` calls the program
+//! once per commitment it inserts, after the batch has been applied.
+//!
+//! The callback instruction uses the Anchor layout for a handler named
+//! `on_deposit_finalized` taking a [`DepositFinalized`] argument, so an
+//! Anchor program can implement it directly. Accounts passed:
+//!
+//! 0. this `DepositHook` PDA, as signer (proves the call comes from the
+//!    pool program for this pool)
+//! 1. the pool config, read-only
+//! 2. .. the batch instruction's remaining accounts, with signer
+//!    privileges stripped
+//!
+//! Only public data is passed: the commitment, its leaf index and the root
+//! after its insertion. A failing callback fails the whole batch, which is
+//! why registration is reserved to the pool authority.
+//!
+//! PDA Seeds: `[b"deposit_hook", pool.key().as_ref()]`
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+
+/// Anchor discriminator of `on_deposit_finalized`:
+/// `sha256("global:on_deposit_finalized")[..8]`
+pub const DEPOSIT_FINALIZED_DISCRIMINATOR: [u8; 8] = [245, 26, 223, 244, 203, 217, 70, 99];
+
+/// Callback argument describing one inserted commitment
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DepositFinalized {
+    pub pool: Pubkey,
+    pub commitment: [u8; 32],
+    pub leaf_index: u32,
+    /// Tree root right after this commitment was inserted
+    pub merkle_root: [u8; 32],
+    pub batch_seq: u64,
+    pub timestamp: i64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct DepositHook {
+    /// Parent pool configuration
+    pub pool: Pubkey,
+
+    /// Program called for every finalized deposit
+    pub callback_program: Pubkey,
+
+    /// Registration timestamp
+    pub registered_at: i64,
+
+    /// Callbacks made so far
+    pub callbacks_invoked: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved for future use
+    pub _reserved: [u8; 32],
+}
+
+impl DepositHook {
+    pub const SEED_PREFIX: &'static [u8] = b"deposit_hook";
+
+    pub const LEN: usize = 8 // discriminator
+        + 32 // pool
+        + 32 // callback_program
+        + 8  // registered_at
+        + 8  // callbacks_invoked
+        + 1  // bump
+        + 32; // reserved
+
+    pub fn find_pda(program_id: &Pubkey, pool: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, pool.as_ref()], program_id)
+    }
+
+    /// Callback instruction for `deposit`. `hook` is this account's
+    /// address; `extra` are forwarded without signer privileges.
+    pub fn callback_instruction(
+        &self,
+        hook: Pubkey,
+        deposit: &DepositFinalized,
+        extra: &[AccountInfo],
+    ) -> Result<Instruction> {
+        let mut data = DEPOSIT_FINALIZED_DISCRIMINATOR.to_vec();
+        deposit.serialize(&mut data)?;
+
+        let mut accounts = vec![
+            AccountMeta::new_readonly(hook, true),
+            AccountMeta::new_readonly(self.pool, false),
+        ];
+        accounts.extend(extra.iter().map(|info| AccountMeta {
+            pubkey: info.key(),
+            is_signer: false,
+            is_writable: info.is_writable,
+        }));
+
+        Ok(Instruction {
+            program_id: self.callback_program,
+            accounts,
+            data,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha2::{Digest, Sha256};
+
+    #[test]
+    fn test_discriminator_matches_anchor_layout() {
+        let hash = Sha256::digest(b"global:on_deposit_finalized");
+        assert_eq!(DEPOSIT_FINALIZED_DISCRIMINATOR, hash[..8]);
+    }
+
+    #[test]
+    fn test_callback_instruction() {
+        let pool = Pubkey::new_unique();
+        let hook = DepositHook {
+            pool,
+            callback_program: Pubkey::new_unique(),
+            registered_at: 0,
+            callbacks_invoked: 0,
+            bump: 255,
+            _reserved: [0u8; 32],
+        };
+        let deposit = DepositFinalized {
+            pool,
+            commitment: [7u8; 32],
+            leaf_index: 3,
+            merkle_root: [9u8; 32],
+            batch_seq: 1,
+            timestamp: 100,
+        };
+        let hook_key = Pubkey::new_unique();
+        let ix = hook.callback_instruction(hook_key, &deposit, &[]).unwrap();
+
+        assert_eq!(ix.program_id, hook.callback_program);
+        assert_eq!(ix.accounts.len(), 2);
+        assert!(ix.accounts[0].is_signer && !ix.accounts[0].is_writable);
+        assert_eq!(ix.accounts[0].pubkey, hook_key);
+        assert_eq!(ix.accounts[1].pubkey, pool);
+        assert_eq!(ix.data[..8], DEPOSIT_FINALIZED_DISCRIMINATOR);
+        assert_eq!(
+            DepositFinalized::try_from_slice(&ix.data[8..]).unwrap(),
+            deposit
+        );
+    }
+}
@@ -0,0 +1,158 @@
+//! Disclosed Withdraw Commitment - Front-running guard for disclosed-path withdrawals
+//!
+//! `disclosed_path_withdraw` reveals a note's opening on-chain, after which
+//! anyone could resubmit it with their own recipient. The relayer therefore
+//! first commits to a binding of the opening and the withdrawal terms
+//! (`disclosed_withdraw_binding`); the opening is only accepted against a
+//! commitment at least `DISCLOSED_WITHDRAW_MIN_DELAY_SLOTS` old. A copycat
+//! who learns the opening from the reveal has to wait out the same delay,
+//! longer than the reveal's blockhash stays valid.
+//!
+//! PDA Seeds: `[b"disclosed_withdraw", pool.key().as_ref(), binding.as_ref()]`
+
+use anchor_lang::prelude::*;
+
+use crate::crypto::keccak::keccak256_concat;
+use crate::error::PrivacyErrorV2;
+
+/// Deepest tree whose Merkle path the program recomputes on-chain (one
+/// Poseidon hash per level, paid for in compute units)
+pub const MAX_DISCLOSED_PATH_TREE_DEPTH: u8 = 16;
+
+/// Slots a commitment must age before its opening is accepted; longer than
+/// a transaction's blockhash stays valid (150 slots)
+pub const DISCLOSED_WITHDRAW_MIN_DELAY_SLOTS: u64 = 160;
+
+/// Domain separator of `disclosed_withdraw_binding`
+pub const DISCLOSED_WITHDRAW_DOMAIN: &[u8] = b"psol:disclosed_withdraw:v1";
+
+/// Binding a relayer commits to before revealing a note's opening: the
+/// pool, the note (`secret`, `nullifier_hash`) and the withdrawal terms
+#[allow(clippy::too_many_arguments)]
+pub fn disclosed_withdraw_binding(
+    pool: &Pubkey,
+    secret: &[u8; 32],
+    nullifier_hash: &[u8; 32],
+    asset_id: &[u8; 32],
+    recipient: &Pubkey,
+    relayer: &Pubkey,
+    relayer_fee: u64,
+) -> [u8; 32] {
+    keccak256_concat(&[
+        DISCLOSED_WITHDRAW_DOMAIN,
+        pool.as_ref(),
+        secret,
+        nullifier_hash,
+        asset_id,
+        recipient.as_ref(),
+        relayer.as_ref(),
+        &relayer_fee.to_le_bytes(),
+    ])
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct DisclosedWithdrawCommitment {
+    /// Pool the withdrawal is from
+    pub pool: Pubkey,
+
+    /// Relayer that committed (the only one that may reveal; receives the
+    /// rent back)
+    pub relayer: Pubkey,
+
+    /// `disclosed_withdraw_binding` of the withdrawal
+    pub binding: [u8; 32],
+
+    /// Slot the commitment was made in
+    pub committed_slot: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl DisclosedWithdrawCommitment {
+    pub const LEN: usize = 8  // discriminator
+        + 32                  // pool
+        + 32                  // relayer
+        + 32                  // binding
+        + 8                   // committed_slot
+        + 1; // bump
+
+    pub fn initialize(
+        &mut self,
+        pool: Pubkey,
+        relayer: Pubkey,
+        binding: [u8; 32],
+        committed_slot: u64,
+        bump: u8,
+    ) {
+        self.pool = pool;
+        self.relayer = relayer;
+        self.binding = binding;
+        self.committed_slot = committed_slot;
+        self.bump = bump;
+    }
+
+    /// Check the commitment is old enough to reveal against at `slot`
+    pub fn require_matured(&self, slot: u64) -> Result<()> {
+        require!(
+            slot.saturating_sub(self.committed_slot) >= DISCLOSED_WITHDRAW_MIN_DELAY_SLOTS,
+            PrivacyErrorV2::DisclosedWithdrawNotMatured
+        );
+        Ok(())
+    }
+}
+
+impl DisclosedWithdrawCommitment {
+    pub const SEED_PREFIX: &'static [u8] = b"disclosed_withdraw";
+
+    pub fn find_pda(program_id: &Pubkey, pool: &Pubkey, binding: &[u8; 32]) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, pool.as_ref(), binding], program_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_maturity_window() {
+        let mut c = DisclosedWithdrawCommitment {
+            pool: Pubkey::default(),
+            relayer: Pubkey::default(),
+            binding: [0u8; 32],
+            committed_slot: 0,
+            bump: 0,
+        };
+        c.initialize(Pubkey::default(), Pubkey::default(), [1u8; 32], 1_000, 0);
+
+        assert!(c.require_matured(1_000).is_err());
+        assert!(c
+            .require_matured(1_000 + DISCLOSED_WITHDRAW_MIN_DELAY_SLOTS - 1)
+            .is_err());
+        assert!(c
+            .require_matured(1_000 + DISCLOSED_WITHDRAW_MIN_DELAY_SLOTS)
+            .is_ok());
+        // A clock behind the commitment never matures it
+        assert!(c.require_matured(0).is_err());
+    }
+
+    #[test]
+    fn test_binding_covers_every_term() {
+        let pool = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let relayer = Pubkey::new_unique();
+        let binding = |secret: [u8; 32], recipient: &Pubkey, relayer: &Pubkey, fee: u64| {
+            disclosed_withdraw_binding(
+                &pool, &secret, &[2u8; 32], &[3u8; 32], recipient, relayer, fee,
+            )
+        };
+
+        let base = binding([1u8; 32], &recipient, &relayer, 10);
+        assert_eq!(base, binding([1u8; 32], &recipient, &relayer, 10));
+        assert_ne!(base, binding([9u8; 32], &recipient, &relayer, 10));
+        assert_ne!(base, binding([1u8; 32], &relayer, &relayer, 10));
+        assert_ne!(base, binding([1u8; 32], &recipient, &recipient, 10));
+        assert_ne!(base, binding([1u8; 32], &recipient, &relayer, 11));
+    }
+}
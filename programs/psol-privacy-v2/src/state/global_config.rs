@@ -0,0 +1,135 @@
+//! Global Config - Program-wide circuit breaker
+//!
+//! A single PDA holding the super-guardian key, which can pause every pool
+//! at once (e.g. on a circuit soundness bug shared by all deployments).
+//! Fund-moving instructions take it as an extra account and fail with
+//! `GlobalPaused` while it is set, on top of each pool's own pause.
+//!
+//! It also holds the lamport fee anyone pays the super guardian to open a
+//! pool with `create_pool`.
+//!
+//! The account is created by the program's upgrade authority; until then
+//! it does not exist and nothing is paused.
+//!
+//! PDA Seeds: `[b"global_config"]`
+
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct GlobalConfig {
+    /// Key allowed to pause and resume all pools
+    pub super_guardian: Pubkey,
+
+    /// Whether all pools are paused
+    pub is_paused: bool,
+
+    /// Last pause or resume timestamp
+    pub updated_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Lamports paid to the super guardian per `create_pool`
+    pub pool_creation_fee: u64,
+
+    /// Reserved for future use
+    pub _reserved: [u8; 24],
+}
+
+impl GlobalConfig {
+    pub const SEED_PREFIX: &'static [u8] = b"global_config";
+
+    pub const LEN: usize = 8                    // discriminator
+        + 32                                    // super_guardian
+        + 1                                     // is_paused
+        + 8                                     // updated_at
+        + 1                                     // bump
+        + 8                                     // pool_creation_fee
+        + 24; // reserved
+
+    pub fn initialize(&mut self, super_guardian: Pubkey, bump: u8, timestamp: i64) {
+        self.super_guardian = super_guardian;
+        self.is_paused = false;
+        self.updated_at = timestamp;
+        self.bump = bump;
+        self.pool_creation_fee = 0;
+        self._reserved = [0u8; 24];
+    }
+
+    /// Derive the PDA address
+    pub fn find_pda(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX], program_id)
+    }
+
+    /// Whether the account at the global config address pauses all pools
+    ///
+    /// An account not yet created pauses nothing; one owned by the program
+    /// that fails to deserialize counts as paused.
+    pub fn is_paused_account(info: &AccountInfo) -> bool {
+        if info.owner != &crate::ID || info.data_is_empty() {
+            return false;
+        }
+        let Ok(data) = info.try_borrow_data() else {
+            return true;
+        };
+        Self::try_deserialize(&mut &data[..]).map_or(true, |config| config.is_paused)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_data(is_paused: bool) -> Vec<u8> {
+        let config = GlobalConfig {
+            super_guardian: Pubkey::new_unique(),
+            is_paused,
+            updated_at: 0,
+            bump: 255,
+            pool_creation_fee: 0,
+            _reserved: [0u8; 24],
+        };
+        let mut data = Vec::with_capacity(GlobalConfig::LEN);
+        config.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), GlobalConfig::LEN);
+        data
+    }
+
+    fn paused(owner: &Pubkey, mut data: Vec<u8>) -> bool {
+        let key = Pubkey::new_unique();
+        let mut lamports = 1;
+        let info = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            owner,
+            false,
+            0,
+        );
+        GlobalConfig::is_paused_account(&info)
+    }
+
+    #[test]
+    fn test_missing_account_pauses_nothing() {
+        assert!(!paused(&System::id(), Vec::new()));
+        assert!(!paused(&crate::ID, Vec::new()));
+    }
+
+    #[test]
+    fn test_paused_flag_is_read() {
+        assert!(paused(&crate::ID, config_data(true)));
+        assert!(!paused(&crate::ID, config_data(false)));
+        // Not the program's account
+        assert!(!paused(&System::id(), config_data(true)));
+    }
+
+    #[test]
+    fn test_unreadable_config_counts_as_paused() {
+        let mut data = config_data(false);
+        data[0] ^= 0xff;
+        assert!(paused(&crate::ID, data));
+    }
+}
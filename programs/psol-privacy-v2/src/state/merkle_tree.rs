@@ -80,6 +80,10 @@ pub struct MerkleTreeV2 {
 
     /// Tree version (for potential upgrades)
     pub version: u8,
+
+    /// Hash of the Poseidon parameter set the tree hashes with
+    /// (`crypto::POSEIDON_PARAMS_HASH` at init)
+    pub poseidon_params_hash: [u8; 32],
 }
 
 impl MerkleTreeV2 {
@@ -100,10 +104,19 @@ impl MerkleTreeV2 {
             + 4 + (32 * (depth_usize + 1))      // zeros (vec)
             + 8                                 // total_leaves
             + 8                                 // last_insertion_at
-            + 1 // version
+            + 1                                 // version
+            + 32 // poseidon_params_hash
+    }
+
+    /// Size of a `LEGACY_VERSION` tree, which ends at `version`
+    pub fn legacy_space(depth: u8, root_history_size: u16) -> usize {
+        Self::space(depth, root_history_size) - 32
     }
 
-    pub const VERSION: u8 = 2;
+    pub const VERSION: u8 = 3;
+    /// Layout of trees created before `poseidon_params_hash`; grown by
+    /// `migrate_merkle_tree`
+    pub const LEGACY_VERSION: u8 = 2;
 
     /// Initialize the Merkle tree with empty state
     ///
@@ -135,6 +148,7 @@ impl MerkleTreeV2 {
         self.total_leaves = 0;
         self.last_insertion_at = 0;
         self.version = Self::VERSION;
+        self.poseidon_params_hash = crate::crypto::POSEIDON_PARAMS_HASH;
 
         // Compute and store zero values for all levels
         self.zeros = crate::crypto::precomputed_zeros::get_precomputed_zeros(depth);
@@ -154,6 +168,18 @@ impl MerkleTreeV2 {
         Ok(())
     }
 
+    /// Finish growing a `LEGACY_VERSION` tree: record the parameter set it
+    /// has always hashed with
+    pub fn migrate(&mut self) -> Result<()> {
+        require!(
+            self.version == Self::LEGACY_VERSION,
+            ErrorCode::AccountDidNotDeserialize
+        );
+        self.version = Self::VERSION;
+        self.poseidon_params_hash = crate::crypto::POSEIDON_PARAMS_HASH;
+        Ok(())
+    }
+
     /// Compute zero hash values for each tree level
     ///
     /// Level 0 = leaf level (zero leaf = 0)
@@ -200,6 +226,56 @@ impl MerkleTreeV2 {
     /// - `CryptographyError` if Poseidon hash fails
     pub fn insert_leaf(&mut self, commitment: [u8; 32], timestamp: i64) -> Result<u32> {
         cu("merkle: insert_leaf start");
+        let leaf_index = self.next_leaf_index;
+        let current_hash = Self::insert_into_frontier(
+            &mut self.filled_subtrees,
+            &self.zeros,
+            leaf_index,
+            commitment,
+        )?;
+
+        // Update current root
+        self.current_root = current_hash;
+
+        // Add to root history (circular buffer)
+        // Store first, then increment to avoid off-by-one
+        self.root_history[self.root_history_index as usize] = current_hash;
+        self.root_history_index = (self.root_history_index + 1) % self.root_history_size;
+
+        // Increment leaf counter
+        self.next_leaf_index = self
+            .next_leaf_index
+            .checked_add(1)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+
+        // Update statistics
+        self.total_leaves = self
+            .total_leaves
+            .checked_add(1)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        self.last_insertion_at = timestamp;
+
+        Ok(leaf_index)
+    }
+
+    /// Hash `commitment` into the tree as leaf `leaf_index`, updating the
+    /// frontier (`filled_subtrees`) and returning the new root
+    ///
+    /// The tree depth is `filled_subtrees.len()`. Shared by `insert_leaf`
+    /// and the host-side `simulate_insertions`, so a simulated batch yields
+    /// exactly the roots the program will.
+    ///
+    /// # Errors
+    /// - `InvalidCommitment` if commitment is zero
+    /// - `MerkleTreeFull` if `leaf_index` is past the tree's capacity
+    /// - `ArithmeticOverflow` if the depth is too large
+    /// - `CryptographyError` if Poseidon hash fails
+    pub fn insert_into_frontier(
+        filled_subtrees: &mut [[u8; 32]],
+        zeros: &[[u8; 32]],
+        leaf_index: u32,
+        commitment: [u8; 32],
+    ) -> Result<[u8; 32]> {
         // Reject zero commitments (these are reserved for empty leaves)
         require!(
             !crate::crypto::is_zero_hash(&commitment),
@@ -208,60 +284,31 @@ impl MerkleTreeV2 {
 
         // Check tree capacity
         let max_leaves = 1u32
-            .checked_shl(self.depth as u32)
+            .checked_shl(filled_subtrees.len() as u32)
             .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
 
-        require!(
-            self.next_leaf_index < max_leaves,
-            PrivacyErrorV2::MerkleTreeFull
-        );
+        require!(leaf_index < max_leaves, PrivacyErrorV2::MerkleTreeFull);
 
-        let leaf_index = self.next_leaf_index;
         let mut current_hash = commitment;
         let mut current_index = leaf_index;
 
         // Walk up the tree, updating hashes
-        for level in 0..self.depth {
-            let level_usize = level as usize;
-
+        for (level, filled_subtree) in filled_subtrees.iter_mut().enumerate() {
             // Determine if this node is a left (0) or right (1) child
             let is_right_child = (current_index & 1) == 1;
             current_index >>= 1;
 
             if is_right_child {
                 // Right child: hash with left sibling from filled_subtrees
-                let left_sibling = self.filled_subtrees[level_usize];
-                current_hash = crate::crypto::hash_two_to_one(&left_sibling, &current_hash)?;
+                current_hash = crate::crypto::hash_two_to_one(filled_subtree, &current_hash)?;
             } else {
                 // Left child: update filled_subtree, hash with zero
-                self.filled_subtrees[level_usize] = current_hash;
-                current_hash =
-                    crate::crypto::hash_two_to_one(&current_hash, &self.zeros[level_usize])?;
+                *filled_subtree = current_hash;
+                current_hash = crate::crypto::hash_two_to_one(&current_hash, &zeros[level])?;
             }
         }
 
-        // Update current root
-        self.current_root = current_hash;
-
-        // Add to root history (circular buffer)
-        // Store first, then increment to avoid off-by-one
-        self.root_history[self.root_history_index as usize] = current_hash;
-        self.root_history_index = (self.root_history_index + 1) % self.root_history_size;
-
-        // Increment leaf counter
-        self.next_leaf_index = self
-            .next_leaf_index
-            .checked_add(1)
-            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
-
-        // Update statistics
-        self.total_leaves = self
-            .total_leaves
-            .checked_add(1)
-            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
-        self.last_insertion_at = timestamp;
-
-        Ok(leaf_index)
+        Ok(current_hash)
     }
 
     /// Check if a root exists in recent history
@@ -298,6 +345,27 @@ impl MerkleTreeV2 {
         })
     }
 
+    /// Number of roots recorded after `root` (0 for the current root)
+    ///
+    /// Each leaf inserted on its own records a root; a settled batch records
+    /// one for the whole batch. `None` if `root` is not a known root.
+    pub fn root_updates_since(&self, root: &[u8; 32]) -> Option<u16> {
+        if !self.is_known_root(root) {
+            return None;
+        }
+        if *root == self.current_root {
+            return Some(0);
+        }
+
+        // root_history_index is the next slot to write; the newest root sits
+        // just before it
+        let size = self.root_history_size;
+        (1..size).find(|age| {
+            let slot = (self.root_history_index + size - 1 - age) % size;
+            self.root_history[slot as usize] == *root
+        })
+    }
+
     /// Get the current Merkle root
     #[inline]
     pub fn get_current_root(&self) -> [u8; 32] {
@@ -328,6 +396,18 @@ impl MerkleTreeV2 {
         self.capacity().saturating_sub(self.next_leaf_index)
     }
 
+    /// Ensure the tree can still absorb `additional` leaves.
+    ///
+    /// Used by deposit paths to reject new commitments up front with a
+    /// dedicated error rather than failing later inside insertion.
+    pub fn require_capacity_for(&self, additional: usize) -> Result<()> {
+        require!(
+            additional <= self.available_space() as usize,
+            PrivacyErrorV2::TreeCapacityExhausted
+        );
+        Ok(())
+    }
+
     /// Get fill percentage (0-100)
     #[inline]
     pub fn fill_percentage(&self) -> u8 {
@@ -396,6 +476,45 @@ impl MerkleTreeV2 {
 
         Ok(path)
     }
+
+    /// Root of this tree's shape with `leaf` at `leaf_index` and `path`
+    /// (sibling hashes, bottom level first) as its authentication path
+    ///
+    /// Checking the result with `is_known_root` proves the leaf was
+    /// inserted without a zero-knowledge proof; the caller learns which
+    /// leaf it is, so this is only for flows where the note is disclosed.
+    ///
+    /// # Errors
+    /// - `LeafIndexNotFound` if `leaf_index` has not been inserted
+    /// - `InvalidInput` if `path` does not have one sibling per level
+    pub fn root_from_path(
+        &self,
+        leaf: [u8; 32],
+        leaf_index: u32,
+        path: &[[u8; 32]],
+    ) -> Result<[u8; 32]> {
+        require!(
+            leaf_index < self.next_leaf_index,
+            PrivacyErrorV2::LeafIndexNotFound
+        );
+        require!(
+            path.len() == self.depth as usize,
+            PrivacyErrorV2::InvalidInput
+        );
+
+        let mut current_hash = leaf;
+        let mut current_index = leaf_index;
+        for sibling in path {
+            current_hash = if current_index & 1 == 1 {
+                crate::crypto::hash_two_to_one(sibling, &current_hash)?
+            } else {
+                crate::crypto::hash_two_to_one(&current_hash, sibling)?
+            };
+            current_index >>= 1;
+        }
+
+        Ok(current_hash)
+    }
 }
 
 /// PDA seeds for MerkleTreeV2
@@ -415,6 +534,49 @@ impl MerkleTreeV2 {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_legacy_tree_parses_after_growth() {
+        let mut tree = MerkleTreeV2 {
+            pool: Pubkey::new_unique(),
+            depth: 0,
+            next_leaf_index: 0,
+            current_root: [0u8; 32],
+            root_history: vec![],
+            root_history_index: 0,
+            root_history_size: 0,
+            filled_subtrees: vec![],
+            zeros: vec![],
+            total_leaves: 0,
+            last_insertion_at: 0,
+            version: 0,
+            poseidon_params_hash: [0u8; 32],
+        };
+        tree.initialize(Pubkey::new_unique(), 4, MIN_ROOT_HISTORY_SIZE)
+            .unwrap();
+        tree.version = MerkleTreeV2::LEGACY_VERSION;
+
+        // A tree written before the parameter hash ends at `version`
+        let mut data = Vec::new();
+        tree.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), MerkleTreeV2::space(4, MIN_ROOT_HISTORY_SIZE));
+        data.truncate(MerkleTreeV2::legacy_space(4, MIN_ROOT_HISTORY_SIZE));
+        assert!(MerkleTreeV2::try_deserialize(&mut &data[..]).is_err());
+
+        // Growing it zero-fills the hash, which the migration then records
+        data.resize(MerkleTreeV2::space(4, MIN_ROOT_HISTORY_SIZE), 0);
+        let mut grown = MerkleTreeV2::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(grown.poseidon_params_hash, [0u8; 32]);
+        assert_eq!(grown.current_root, tree.current_root);
+        grown.migrate().unwrap();
+        assert_eq!(grown.version, MerkleTreeV2::VERSION);
+        assert_eq!(
+            grown.poseidon_params_hash,
+            crate::crypto::POSEIDON_PARAMS_HASH
+        );
+        // Only once
+        assert!(grown.migrate().is_err());
+    }
+
     #[test]
     fn test_space_calculation() {
         let space = MerkleTreeV2::space(20, 100);
@@ -438,6 +600,7 @@ mod tests {
             total_leaves: 0,
             last_insertion_at: 0,
             version: 2,
+            poseidon_params_hash: [0u8; 32],
         };
 
         assert_eq!(tree.capacity(), 1 << 20); // 2^20 = 1,048,576
@@ -461,6 +624,7 @@ mod tests {
             total_leaves: 0,
             last_insertion_at: 0,
             version: 2,
+            poseidon_params_hash: [0u8; 32],
         };
         assert_eq!(tree4.capacity(), 16); // 2^4
 
@@ -478,6 +642,7 @@ mod tests {
             total_leaves: 0,
             last_insertion_at: 0,
             version: 2,
+            poseidon_params_hash: [0u8; 32],
         };
         assert_eq!(tree24.capacity(), 1 << 24); // ~16M
     }
@@ -501,6 +666,7 @@ mod tests {
             total_leaves: 0,
             last_insertion_at: 0,
             version: 2,
+            poseidon_params_hash: [0u8; 32],
         };
 
         assert!(tree.is_known_root(&root1)); // Current root
@@ -508,6 +674,35 @@ mod tests {
         assert!(!tree.is_known_root(&root3)); // Not known
     }
 
+    #[test]
+    fn test_root_updates_since_wraps_history() {
+        let root = |i: u8| [i; 32];
+
+        // Roots 1..=6 written into a 4-slot ring: 5 and 6 overwrote 1 and 2
+        let tree = MerkleTreeV2 {
+            pool: Pubkey::default(),
+            depth: 20,
+            next_leaf_index: 6,
+            current_root: root(6),
+            root_history: vec![root(5), root(6), root(3), root(4)],
+            root_history_index: 2,
+            root_history_size: 4,
+            filled_subtrees: vec![],
+            zeros: vec![],
+            total_leaves: 6,
+            last_insertion_at: 0,
+            version: 2,
+            poseidon_params_hash: [0u8; 32],
+        };
+
+        assert_eq!(tree.root_updates_since(&root(6)), Some(0));
+        assert_eq!(tree.root_updates_since(&root(5)), Some(1));
+        assert_eq!(tree.root_updates_since(&root(4)), Some(2));
+        assert_eq!(tree.root_updates_since(&root(3)), Some(3));
+        assert_eq!(tree.root_updates_since(&root(2)), None);
+        assert_eq!(tree.root_updates_since(&[0u8; 32]), None);
+    }
+
     /// CRITICAL SECURITY TEST: Zero root must always be rejected
     /// to prevent matching uninitialized history slots.
     #[test]
@@ -529,6 +724,7 @@ mod tests {
             total_leaves: 0,
             last_insertion_at: 0,
             version: 2,
+            poseidon_params_hash: [0u8; 32],
         };
 
         // Zero root must NEVER match, even when zeros are in history
@@ -554,6 +750,7 @@ mod tests {
             total_leaves: 0,
             last_insertion_at: 0,
             version: 2,
+            poseidon_params_hash: [0u8; 32],
         };
 
         // Even with zero current_root, zero input should be rejected
@@ -578,6 +775,7 @@ mod tests {
             total_leaves: 0,
             last_insertion_at: 0,
             version: 2,
+            poseidon_params_hash: [0u8; 32],
         };
 
         assert_eq!(tree.fill_percentage(), 0);
@@ -588,4 +786,82 @@ mod tests {
         tree.next_leaf_index = 16;
         assert_eq!(tree.fill_percentage(), 100);
     }
+
+    #[test]
+    fn test_require_capacity_for() {
+        let mut tree = MerkleTreeV2 {
+            pool: Pubkey::default(),
+            depth: 4, // capacity = 16
+            next_leaf_index: 14,
+            current_root: [0u8; 32],
+            root_history: vec![],
+            root_history_index: 0,
+            root_history_size: 30,
+            filled_subtrees: vec![],
+            zeros: vec![],
+            total_leaves: 0,
+            last_insertion_at: 0,
+            version: 2,
+            poseidon_params_hash: [0u8; 32],
+        };
+
+        assert!(tree.require_capacity_for(2).is_ok());
+        assert!(tree.require_capacity_for(3).is_err());
+
+        tree.next_leaf_index = 16;
+        assert!(tree.require_capacity_for(0).is_ok());
+        assert!(tree.require_capacity_for(1).is_err());
+    }
+
+    #[test]
+    fn test_root_from_path_matches_witness_paths() {
+        let leaves: Vec<[u8; 32]> = (1..=3u8)
+            .map(|i| {
+                let mut leaf = [0u8; 32];
+                leaf[31] = i;
+                leaf
+            })
+            .collect();
+        let tree = MerkleTreeV2 {
+            pool: Pubkey::default(),
+            depth: 4,
+            next_leaf_index: 3,
+            current_root: [0u8; 32],
+            root_history: vec![],
+            root_history_index: 0,
+            root_history_size: 30,
+            filled_subtrees: vec![],
+            zeros: vec![],
+            total_leaves: 3,
+            last_insertion_at: 0,
+            version: 2,
+            poseidon_params_hash: [0u8; 32],
+        };
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let path = crate::witness::merkle_path(&leaves, index as u32, 4).unwrap();
+            assert_eq!(
+                tree.root_from_path(*leaf, index as u32, &path.elements)
+                    .unwrap(),
+                path.root
+            );
+        }
+
+        // The wrong position or leaf gives another root
+        let path = crate::witness::merkle_path(&leaves, 1, 4).unwrap();
+        assert_ne!(
+            tree.root_from_path(leaves[1], 0, &path.elements).unwrap(),
+            path.root
+        );
+        assert_ne!(
+            tree.root_from_path(leaves[0], 1, &path.elements).unwrap(),
+            path.root
+        );
+
+        // Uninserted leaves and short paths are rejected
+        assert!(tree.root_from_path(leaves[0], 3, &path.elements).is_err());
+        assert!(tree
+            .root_from_path(leaves[1], 1, &path.elements[..3])
+            .is_err());
+    }
 }
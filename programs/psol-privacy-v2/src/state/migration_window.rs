@@ -0,0 +1,178 @@
+//! Migration Window - Dual-root acceptance during tree migrations
+//!
+//! Before resetting or reorganizing the Merkle tree, the authority opens a
+//! window that snapshots the tree's known roots. Until the window expires,
+//! withdrawals passing this account may prove against either the snapshot
+//! or the live root set, so proofs generated before the migration stay
+//! spendable.
+//!
+//! PDA Seeds: `[b"migration_window", pool.key().as_ref()]`
+
+use anchor_lang::prelude::*;
+
+use crate::state::MerkleTreeV2;
+
+/// Maximum length of a migration window (7 days)
+pub const MAX_MIGRATION_WINDOW_SECONDS: i64 = 7 * 24 * 60 * 60;
+
+#[account]
+pub struct MigrationWindow {
+    /// Parent pool configuration
+    pub pool: Pubkey,
+
+    /// Merkle tree the roots were taken from
+    pub merkle_tree: Pubkey,
+
+    /// Current root and non-zero history at the time the window opened
+    pub pre_migration_roots: Vec<[u8; 32]>,
+
+    /// Timestamp the window was opened
+    pub opened_at: i64,
+
+    /// Timestamp after which pre-migration roots are rejected
+    pub expires_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved for future use
+    pub _reserved: [u8; 16],
+}
+
+impl MigrationWindow {
+    pub const SEED_PREFIX: &'static [u8] = b"migration_window";
+
+    /// Account size for a snapshot of up to `max_roots` roots
+    pub fn space(max_roots: usize) -> usize {
+        8                           // discriminator
+            + 32                    // pool
+            + 32                    // merkle_tree
+            + 4 + (32 * max_roots)  // pre_migration_roots (vec)
+            + 8                     // opened_at
+            + 8                     // expires_at
+            + 1                     // bump
+            + 16 // reserved
+    }
+
+    /// Snapshot roots for a tree of this size: current root plus history
+    pub fn max_roots_for(tree: &MerkleTreeV2) -> usize {
+        tree.root_history.len() + 1
+    }
+
+    pub fn initialize(
+        &mut self,
+        pool: Pubkey,
+        merkle_tree: Pubkey,
+        tree: &MerkleTreeV2,
+        opened_at: i64,
+        expires_at: i64,
+        bump: u8,
+    ) {
+        let mut roots = Vec::with_capacity(Self::max_roots_for(tree));
+        roots.push(tree.current_root);
+        for root in tree.root_history.iter() {
+            if !root.iter().all(|&b| b == 0) && !roots.contains(root) {
+                roots.push(*root);
+            }
+        }
+
+        self.pool = pool;
+        self.merkle_tree = merkle_tree;
+        self.pre_migration_roots = roots;
+        self.opened_at = opened_at;
+        self.expires_at = expires_at;
+        self.bump = bump;
+        self._reserved = [0u8; 16];
+    }
+
+    pub fn is_active(&self, timestamp: i64) -> bool {
+        timestamp <= self.expires_at
+    }
+
+    /// Check a root against the pre-migration snapshot
+    ///
+    /// Zero roots are always rejected, matching `MerkleTreeV2::is_known_root`.
+    pub fn accepts_root(&self, root: &[u8; 32], timestamp: i64) -> bool {
+        if !self.is_active(timestamp) || root.iter().all(|&b| b == 0) {
+            return false;
+        }
+        self.pre_migration_roots.iter().any(|r| r == root)
+    }
+}
+
+/// Root check for withdrawals: live root set, or pre-migration roots while
+/// a migration window is open.
+pub fn is_withdraw_root_accepted(
+    tree: &MerkleTreeV2,
+    window: Option<&MigrationWindow>,
+    root: &[u8; 32],
+    timestamp: i64,
+) -> bool {
+    tree.is_known_root(root) || window.is_some_and(|w| w.accepts_root(root, timestamp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree_with_roots(current: [u8; 32], history: Vec<[u8; 32]>) -> MerkleTreeV2 {
+        MerkleTreeV2 {
+            pool: Pubkey::default(),
+            depth: 4,
+            next_leaf_index: 0,
+            current_root: current,
+            root_history: history,
+            root_history_index: 0,
+            root_history_size: 30,
+            filled_subtrees: vec![],
+            zeros: vec![],
+            total_leaves: 0,
+            last_insertion_at: 0,
+            version: 2,
+            poseidon_params_hash: [0u8; 32],
+        }
+    }
+
+    #[test]
+    fn test_window_accepts_pre_migration_roots_until_expiry() {
+        let old = tree_with_roots([1u8; 32], vec![[1u8; 32], [2u8; 32], [0u8; 32]]);
+        let mut window = MigrationWindow {
+            pool: Pubkey::default(),
+            merkle_tree: Pubkey::default(),
+            pre_migration_roots: vec![],
+            opened_at: 0,
+            expires_at: 0,
+            bump: 0,
+            _reserved: [0u8; 16],
+        };
+        window.initialize(Pubkey::default(), Pubkey::default(), &old, 100, 200, 0);
+
+        // Duplicates and empty history slots are skipped
+        assert_eq!(window.pre_migration_roots, vec![[1u8; 32], [2u8; 32]]);
+
+        // Tree after migration only knows the new root
+        let migrated = tree_with_roots([9u8; 32], vec![[9u8; 32]]);
+        assert!(is_withdraw_root_accepted(
+            &migrated,
+            Some(&window),
+            &[2u8; 32],
+            150
+        ));
+        assert!(is_withdraw_root_accepted(
+            &migrated,
+            Some(&window),
+            &[9u8; 32],
+            150
+        ));
+        assert!(!is_withdraw_root_accepted(&migrated, None, &[2u8; 32], 150));
+
+        // Expired window no longer vouches for old roots
+        assert!(!is_withdraw_root_accepted(
+            &migrated,
+            Some(&window),
+            &[2u8; 32],
+            201
+        ));
+        assert!(!window.accepts_root(&[0u8; 32], 150));
+    }
+}
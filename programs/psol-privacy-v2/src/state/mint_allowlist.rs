@@ -0,0 +1,169 @@
+//! Mint Allowlist - Mints anyone may register as pool assets
+//!
+//! While `registration_open` is set, `register_allowlisted_asset` lets any
+//! payer register an asset whose mint is on the list, so communities can
+//! list approved tokens themselves. The authority curates the list and can
+//! close registration again; `register_asset` stays authority-only.
+//!
+//! PDA Seeds: `[b"mint_allowlist", pool.key().as_ref()]`
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+
+/// Maximum number of allowlisted mints
+pub const MAX_ALLOWLISTED_MINTS: usize = 32;
+
+#[account]
+#[derive(InitSpace)]
+pub struct MintAllowlist {
+    /// Parent pool configuration
+    pub pool: Pubkey,
+
+    /// Mints open for permissionless registration
+    pub mints: [Pubkey; MAX_ALLOWLISTED_MINTS],
+
+    /// Number of allowlisted mints
+    pub mint_count: u8,
+
+    /// Whether anyone may register allowlisted mints
+    pub registration_open: bool,
+
+    /// Last update timestamp
+    pub updated_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved for future use
+    pub _reserved: [u8; 32],
+}
+
+impl MintAllowlist {
+    pub const SEED_PREFIX: &'static [u8] = b"mint_allowlist";
+
+    pub const LEN: usize = 8                    // discriminator
+        + 32                                    // pool
+        + 32 * MAX_ALLOWLISTED_MINTS            // mints
+        + 1                                     // mint_count
+        + 1                                     // registration_open
+        + 8                                     // updated_at
+        + 1                                     // bump
+        + 32; // reserved
+
+    pub fn initialize(&mut self, pool: Pubkey, bump: u8, timestamp: i64) {
+        self.pool = pool;
+        self.mints = [Pubkey::default(); MAX_ALLOWLISTED_MINTS];
+        self.mint_count = 0;
+        self.registration_open = false;
+        self.updated_at = timestamp;
+        self.bump = bump;
+        self._reserved = [0u8; 32];
+    }
+
+    pub fn allowlisted(&self) -> &[Pubkey] {
+        &self.mints[..self.mint_count as usize]
+    }
+
+    pub fn contains(&self, mint: &Pubkey) -> bool {
+        self.allowlisted().contains(mint)
+    }
+
+    pub fn add_mint(&mut self, mint: Pubkey, timestamp: i64) -> Result<()> {
+        require!(
+            !self.contains(&mint),
+            PrivacyErrorV2::MintAlreadyAllowlisted
+        );
+        let count = self.mint_count as usize;
+        require!(
+            count < MAX_ALLOWLISTED_MINTS,
+            PrivacyErrorV2::MintAllowlistFull
+        );
+        self.mints[count] = mint;
+        self.mint_count += 1;
+        self.updated_at = timestamp;
+        Ok(())
+    }
+
+    pub fn remove_mint(&mut self, mint: &Pubkey, timestamp: i64) -> Result<()> {
+        let index = self
+            .allowlisted()
+            .iter()
+            .position(|m| m == mint)
+            .ok_or(PrivacyErrorV2::MintNotAllowlisted)?;
+        let count = self.mint_count as usize;
+        self.mints.copy_within(index + 1..count, index);
+        self.mints[count - 1] = Pubkey::default();
+        self.mint_count -= 1;
+        self.updated_at = timestamp;
+        Ok(())
+    }
+
+    pub fn set_registration_open(&mut self, open: bool, timestamp: i64) {
+        self.registration_open = open;
+        self.updated_at = timestamp;
+    }
+
+    /// Check anyone may register `mint` right now
+    pub fn require_registrable(&self, mint: &Pubkey) -> Result<()> {
+        require!(
+            self.registration_open,
+            PrivacyErrorV2::AssetRegistrationClosed
+        );
+        require!(self.contains(mint), PrivacyErrorV2::MintNotAllowlisted);
+        Ok(())
+    }
+
+    pub fn find_pda(program_id: &Pubkey, pool: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, pool.as_ref()], program_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowlist() -> MintAllowlist {
+        let mut list = MintAllowlist {
+            pool: Pubkey::default(),
+            mints: [Pubkey::default(); MAX_ALLOWLISTED_MINTS],
+            mint_count: 0,
+            registration_open: false,
+            updated_at: 0,
+            bump: 0,
+            _reserved: [0u8; 32],
+        };
+        list.initialize(Pubkey::new_unique(), 255, 1);
+        list
+    }
+
+    #[test]
+    fn test_add_remove_mints() {
+        let mut list = allowlist();
+        let mints: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        for mint in &mints {
+            list.add_mint(*mint, 2).unwrap();
+        }
+        assert!(list.add_mint(mints[0], 3).is_err());
+
+        list.remove_mint(&mints[0], 4).unwrap();
+        assert_eq!(list.allowlisted(), &mints[1..]);
+        assert!(list.remove_mint(&mints[0], 5).is_err());
+        assert_eq!(list.updated_at, 4);
+
+        while list.add_mint(Pubkey::new_unique(), 6).is_ok() {}
+        assert_eq!(list.mint_count as usize, MAX_ALLOWLISTED_MINTS);
+    }
+
+    #[test]
+    fn test_registration_requires_open_and_listed() {
+        let mut list = allowlist();
+        let mint = Pubkey::new_unique();
+        list.add_mint(mint, 2).unwrap();
+        assert!(list.require_registrable(&mint).is_err());
+
+        list.set_registration_open(true, 3);
+        assert!(list.require_registrable(&mint).is_ok());
+        assert!(list.require_registrable(&Pubkey::new_unique()).is_err());
+    }
+}
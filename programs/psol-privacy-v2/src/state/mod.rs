@@ -9,13 +9,16 @@ pub mod spent_nullifier;
 pub mod verification_key;
 
 pub use asset_vault::AssetVault;
-pub use batcher_role::BatcherRole;
+pub use batcher_role::{
+    find_insurance_fund_pda, BatcherRole, BATCHER_UNBONDING_SECONDS, INSURANCE_FUND_SEED,
+    MIN_BATCHER_BOND_LAMPORTS,
+};
 pub use compliance::ComplianceConfig;
 pub use merkle_tree::MerkleTreeV2;
-pub use pending_deposits::{PendingDeposit, PendingDepositsBuffer};
+pub use pending_deposits::{LegacyPendingDepositsBuffer, PendingDeposit, PendingDepositsBuffer};
 pub use pool_config::PoolConfigV2;
-pub use relayer::{RelayerNode, RelayerRegistry};
-pub use spent_nullifier::{SpendType, SpentNullifierV2};
+pub use relayer::{LegacyRelayerNode, RelayerMetadata, RelayerNode, RelayerRegistry};
+pub use spent_nullifier::{SpendContext, SpendType, SpentNullifierV2, NOTE_RECEIPT_LEN};
 pub use verification_key::{VerificationKeyAccountV2, VerificationKeyV2};
 
 pub use merkle_tree::{
@@ -23,7 +26,130 @@ pub use merkle_tree::{
 };
 
 pub use compliance::{AuditMetadata, MAX_ENCRYPTED_METADATA_LEN};
-pub use relayer::MAX_RELAYER_METADATA_URI_LEN;
+pub use relayer::{MAX_RELAYER_METADATA_URI_LEN, RELAYER_UNBONDING_SECONDS};
 
 pub mod yield_registry;
 pub use yield_registry::YieldRegistry;
+
+pub mod relayer_rebate;
+pub use relayer_rebate::RelayerRebateLedger;
+
+pub mod relayer_fee_quote;
+pub use relayer_fee_quote::RelayerFeeQuote;
+
+pub mod risk_tier;
+pub use risk_tier::{AssetWithdrawalWindow, RiskTierConfig, RiskTierLimits, RISK_TIER_COUNT};
+
+pub mod proof_stats;
+pub use proof_stats::{ProofStats, PROOF_TYPE_COUNT};
+
+pub mod anonymity_stats;
+pub use anonymity_stats::{
+    AnonymityStats, StatsEpoch, LATENCY_BUCKET_COUNT, STATS_EPOCH_COUNT, STATS_EPOCH_SECONDS,
+};
+
+pub mod parameter_snapshot;
+pub use parameter_snapshot::{EconomicParameters, ParameterSnapshot, PARAMETER_SNAPSHOT_DOMAIN};
+
+pub mod migration_window;
+pub use migration_window::{MigrationWindow, MAX_MIGRATION_WINDOW_SECONDS};
+
+pub mod deferred_events;
+pub use deferred_events::{
+    DeferredWithdrawEvent, DeferredWithdrawEvents, MAX_DEFERRED_WITHDRAW_EVENTS,
+    MAX_WITHDRAW_EVENT_DELAY_SECONDS,
+};
+
+pub mod circuit_descriptor;
+pub use circuit_descriptor::CircuitDescriptor;
+
+pub mod relayer_index;
+pub use relayer_index::{RelayerIndexShard, RELAYER_INDEX_SHARD_CAPACITY};
+
+pub mod relayer_tombstone;
+pub use relayer_tombstone::RelayerTombstone;
+
+pub mod attested_root;
+pub use attested_root::{AttestedRootRecord, ROOT_ATTESTATION_DOMAIN};
+
+pub mod range_proof;
+pub use range_proof::{RangeProofParams, RANGE_PROOF_PARAMS_LEN};
+
+pub mod deposit_hook;
+pub use deposit_hook::{DepositFinalized, DepositHook, DEPOSIT_FINALIZED_DISCRIMINATOR};
+
+pub mod mint_allowlist;
+pub use mint_allowlist::{MintAllowlist, MAX_ALLOWLISTED_MINTS};
+
+pub mod surplus_sweep;
+pub use surplus_sweep::{SurplusSweep, SURPLUS_SWEEP_DELAY_SECONDS};
+
+pub mod treasury;
+pub use treasury::{Treasury, TREASURY_DISBURSEMENT_DELAY_SECONDS};
+
+pub mod rent_reserve;
+pub use rent_reserve::{RentAdvance, RentReserve};
+
+pub mod global_config;
+pub use global_config::GlobalConfig;
+
+pub mod pool_directory;
+pub use pool_directory::{PoolDirectory, PoolDirectoryEntry};
+
+pub mod action_allowlist;
+pub use action_allowlist::{ActionAllowlist, MAX_ALLOWLISTED_PROGRAMS};
+
+pub mod disclosed_withdraw;
+pub use disclosed_withdraw::{
+    disclosed_withdraw_binding, DisclosedWithdrawCommitment, DISCLOSED_WITHDRAW_MIN_DELAY_SLOTS,
+    MAX_DISCLOSED_PATH_TREE_DEPTH,
+};
+
+pub mod withdrawal_commit;
+pub use withdrawal_commit::{withdrawal_commit_hash, withdrawal_commit_pin_hash, WithdrawalCommit};
+
+pub mod commitment_reservation;
+pub use commitment_reservation::{CommitmentReservation, COMMITMENT_RESERVATION_SLOTS};
+
+/// Fail the build when a fixed-size account's hand-counted `LEN` drifts
+/// from its fields (discriminator plus the derived `INIT_SPACE`)
+macro_rules! assert_len_matches_fields {
+    ($($account:ty),* $(,)?) => {
+        $(const _: () = assert!(
+            <$account>::LEN == 8 + <$account as anchor_lang::Space>::INIT_SPACE,
+            concat!(stringify!($account), "::LEN does not match its fields"),
+        );)*
+    };
+}
+
+assert_len_matches_fields!(
+    ActionAllowlist,
+    AnonymityStats,
+    AttestedRootRecord,
+    BatcherRole,
+    CircuitDescriptor,
+    CommitmentReservation,
+    ComplianceConfig,
+    DepositHook,
+    DisclosedWithdrawCommitment,
+    GlobalConfig,
+    MintAllowlist,
+    ParameterSnapshot,
+    PoolConfigV2,
+    PoolDirectory,
+    PoolDirectoryEntry,
+    ProofStats,
+    RangeProofParams,
+    RelayerFeeQuote,
+    RelayerRebateLedger,
+    RelayerRegistry,
+    RelayerTombstone,
+    RentAdvance,
+    RentReserve,
+    RiskTierConfig,
+    SpentNullifierV2,
+    SurplusSweep,
+    Treasury,
+    WithdrawalCommit,
+    YieldRegistry,
+);
@@ -0,0 +1,344 @@
+//! Parameter Snapshot - authority-published economic parameters per asset
+//!
+//! Fees, limits and flags can change between the moment a client shows a
+//! user what a withdrawal will cost and the moment it lands. The pool
+//! authority publishes the current values of every economically relevant
+//! parameter into this account under a version that only ever increases;
+//! clients pin the version they displayed, and `withdraw_with_parameter_pin`
+//! refuses to run if the parameters have since changed against the user.
+//!
+//! Every publication compares the new values with the previous ones and,
+//! when any moved adversely, records its version in `adverse_version`: pins
+//! older than that are rejected. A withdrawal also re-reads the live values
+//! and is rejected if they are adverse compared to the snapshot, so an
+//! unpublished change cannot slip past a pin either.
+
+use anchor_lang::prelude::*;
+
+use crate::crypto::keccak::keccak256_concat;
+use crate::error::PrivacyErrorV2;
+use crate::state::{AssetVault, PoolConfigV2, RelayerRegistry};
+
+/// Domain separator for `EconomicParameters::hash`
+pub const PARAMETER_SNAPSHOT_DOMAIN: &[u8] = b"psol:parameters:v1";
+
+/// Every parameter that changes what a user pays or may do with an asset
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace,
+)]
+pub struct EconomicParameters {
+    /// `AssetVault::min_deposit`
+    pub min_deposit: u64,
+
+    /// `AssetVault::max_deposit`
+    pub max_deposit: u64,
+
+    /// `AssetVault::min_withdrawal`
+    pub min_withdrawal: u64,
+
+    /// `AssetVault::min_relayer_fee`
+    pub min_relayer_fee: u64,
+
+    /// `AssetVault::max_relayer_fee`
+    pub max_relayer_fee: u64,
+
+    /// `RelayerRegistry::min_fee_bps`
+    pub relayer_min_fee_bps: u16,
+
+    /// `RelayerRegistry::max_fee_bps`
+    pub relayer_max_fee_bps: u16,
+
+    /// `PoolConfigV2::yield_fee_bps`
+    pub yield_fee_bps: u16,
+
+    /// `AssetVault::deposits_enabled`
+    pub deposits_enabled: bool,
+
+    /// `AssetVault::withdrawals_enabled`
+    pub withdrawals_enabled: bool,
+
+    /// `PoolConfigV2::feature_flags`
+    pub feature_flags: u8,
+
+    /// `PoolConfigV2::crank_bounty_lamports`, charged on every deposit
+    pub crank_bounty_lamports: u32,
+
+    /// `PoolConfigV2::protocol_fee_bps`, taken from every token withdrawal
+    pub protocol_fee_bps: u16,
+}
+
+impl EconomicParameters {
+    /// Current values from the accounts that hold them
+    pub fn read(
+        pool_config: &PoolConfigV2,
+        asset_vault: &AssetVault,
+        relayer_registry: &RelayerRegistry,
+    ) -> Self {
+        Self {
+            min_deposit: asset_vault.min_deposit,
+            max_deposit: asset_vault.max_deposit,
+            min_withdrawal: asset_vault.min_withdrawal,
+            min_relayer_fee: asset_vault.min_relayer_fee,
+            max_relayer_fee: asset_vault.max_relayer_fee,
+            relayer_min_fee_bps: relayer_registry.min_fee_bps,
+            relayer_max_fee_bps: relayer_registry.max_fee_bps,
+            yield_fee_bps: pool_config.yield_fee_bps,
+            deposits_enabled: asset_vault.deposits_enabled,
+            withdrawals_enabled: asset_vault.withdrawals_enabled,
+            feature_flags: pool_config.feature_flags,
+            crank_bounty_lamports: pool_config.crank_bounty_lamports,
+            protocol_fee_bps: pool_config.protocol_fee_bps,
+        }
+    }
+
+    /// Hash clients compare against the parameters they displayed
+    pub fn hash(&self) -> [u8; 32] {
+        keccak256_concat(&[
+            PARAMETER_SNAPSHOT_DOMAIN,
+            &self.min_deposit.to_le_bytes(),
+            &self.max_deposit.to_le_bytes(),
+            &self.min_withdrawal.to_le_bytes(),
+            &self.min_relayer_fee.to_le_bytes(),
+            &self.max_relayer_fee.to_le_bytes(),
+            &self.relayer_min_fee_bps.to_le_bytes(),
+            &self.relayer_max_fee_bps.to_le_bytes(),
+            &self.yield_fee_bps.to_le_bytes(),
+            &[self.deposits_enabled as u8],
+            &[self.withdrawals_enabled as u8],
+            &[self.feature_flags],
+            &self.crank_bounty_lamports.to_le_bytes(),
+            &self.protocol_fee_bps.to_le_bytes(),
+        ])
+    }
+
+    /// Whether moving from `self` to `next` can make a user worse off
+    ///
+    /// Tighter limits, a narrower relayer fee range, a higher yield fee,
+    /// crank bounty or protocol fee, or a disabled flow are adverse. Feature flags gate
+    /// whole code paths, so any change to them counts.
+    pub fn is_adverse_change(&self, next: &Self) -> bool {
+        next.min_deposit > self.min_deposit
+            || next.max_deposit < self.max_deposit
+            || next.min_withdrawal > self.min_withdrawal
+            || next.min_relayer_fee > self.min_relayer_fee
+            || next.max_relayer_fee < self.max_relayer_fee
+            || next.relayer_min_fee_bps > self.relayer_min_fee_bps
+            || next.relayer_max_fee_bps < self.relayer_max_fee_bps
+            || next.yield_fee_bps > self.yield_fee_bps
+            || next.crank_bounty_lamports > self.crank_bounty_lamports
+            || next.protocol_fee_bps > self.protocol_fee_bps
+            || (self.deposits_enabled && !next.deposits_enabled)
+            || (self.withdrawals_enabled && !next.withdrawals_enabled)
+            || next.feature_flags != self.feature_flags
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ParameterSnapshot {
+    /// Parent pool configuration
+    pub pool: Pubkey,
+
+    /// Asset the parameters apply to
+    pub asset_id: [u8; 32],
+
+    /// Version of the latest publication (starts at 1)
+    pub version: u64,
+
+    /// Latest version whose publication changed parameters adversely;
+    /// pins older than this are rejected
+    pub adverse_version: u64,
+
+    /// Parameters as of `version`
+    pub parameters: EconomicParameters,
+
+    /// `parameters.hash()`
+    pub parameters_hash: [u8; 32],
+
+    /// Timestamp of the latest publication
+    pub published_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved for future use
+    pub _reserved: [u8; 26],
+}
+
+impl ParameterSnapshot {
+    /// Account size: 8 + 32 + 32 + 8 + 8 + 55 + 32 + 8 + 1 + 26 = 210 bytes
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + (5 * 8 + 3 * 2 + 3 + 4 + 2) + 32 + 8 + 1 + 26;
+
+    /// Seed prefix for PDA derivation
+    pub const SEED_PREFIX: &'static [u8] = b"parameter_snapshot";
+
+    pub fn initialize(&mut self, pool: Pubkey, asset_id: [u8; 32], bump: u8) {
+        self.pool = pool;
+        self.asset_id = asset_id;
+        self.version = 0;
+        self.adverse_version = 0;
+        self.parameters = EconomicParameters::default();
+        self.parameters_hash = [0u8; 32];
+        self.published_at = 0;
+        self.bump = bump;
+        self._reserved = [0u8; 26];
+    }
+
+    /// Publish `parameters` under the next version, returning it
+    pub fn publish(&mut self, parameters: EconomicParameters, timestamp: i64) -> Result<u64> {
+        let version = self
+            .version
+            .checked_add(1)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+
+        // The first publication has nothing to compare against
+        if self.version == 0 || self.parameters.is_adverse_change(&parameters) {
+            self.adverse_version = version;
+        }
+        self.version = version;
+        self.parameters = parameters;
+        self.parameters_hash = parameters.hash();
+        self.published_at = timestamp;
+        Ok(version)
+    }
+
+    /// Reject a withdrawal pinned to `pinned_version` if the parameters
+    /// changed adversely since, whether published (`adverse_version`) or
+    /// not yet (`live` compared to the snapshot)
+    pub fn check_pin(&self, pinned_version: u64, live: &EconomicParameters) -> Result<()> {
+        require!(
+            pinned_version <= self.version
+                && pinned_version >= self.adverse_version
+                && !self.parameters.is_adverse_change(live),
+            PrivacyErrorV2::ParametersChangedSincePin
+        );
+        Ok(())
+    }
+
+    pub fn find_pda(program_id: &Pubkey, pool: &Pubkey, asset_id: &[u8; 32]) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED_PREFIX, pool.as_ref(), asset_id.as_ref()],
+            program_id,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parameters() -> EconomicParameters {
+        EconomicParameters {
+            min_deposit: 1_000,
+            max_deposit: 1_000_000,
+            min_withdrawal: 500,
+            min_relayer_fee: 10,
+            max_relayer_fee: 10_000,
+            relayer_min_fee_bps: 10,
+            relayer_max_fee_bps: 100,
+            yield_fee_bps: 500,
+            deposits_enabled: true,
+            withdrawals_enabled: true,
+            feature_flags: 0b11,
+            crank_bounty_lamports: 5_000,
+            protocol_fee_bps: 20,
+        }
+    }
+
+    fn new_snapshot() -> ParameterSnapshot {
+        let mut snapshot = ParameterSnapshot {
+            pool: Pubkey::default(),
+            asset_id: [0u8; 32],
+            version: 0,
+            adverse_version: 0,
+            parameters: EconomicParameters::default(),
+            parameters_hash: [0u8; 32],
+            published_at: 0,
+            bump: 0,
+            _reserved: [0u8; 26],
+        };
+        snapshot.initialize(Pubkey::default(), [1u8; 32], 255);
+        snapshot
+    }
+
+    #[test]
+    fn test_adverse_changes() {
+        let base = parameters();
+        assert!(!base.is_adverse_change(&base));
+
+        // Looser limits and a wider fee range only help users
+        let looser = EconomicParameters {
+            min_withdrawal: 100,
+            max_deposit: 2_000_000,
+            relayer_max_fee_bps: 200,
+            yield_fee_bps: 400,
+            ..base
+        };
+        assert!(!base.is_adverse_change(&looser));
+        assert!(looser.is_adverse_change(&base));
+
+        for adverse in [
+            EconomicParameters {
+                min_relayer_fee: 11,
+                ..base
+            },
+            EconomicParameters {
+                max_relayer_fee: 9_999,
+                ..base
+            },
+            EconomicParameters {
+                withdrawals_enabled: false,
+                ..base
+            },
+            EconomicParameters {
+                feature_flags: 0b111,
+                ..base
+            },
+            EconomicParameters {
+                crank_bounty_lamports: 5_001,
+                ..base
+            },
+            EconomicParameters {
+                protocol_fee_bps: 21,
+                ..base
+            },
+        ] {
+            assert!(base.is_adverse_change(&adverse), "{adverse:?}");
+            assert_ne!(base.hash(), adverse.hash());
+        }
+    }
+
+    #[test]
+    fn test_pins_survive_only_favorable_changes() {
+        let mut snapshot = new_snapshot();
+        let base = parameters();
+        assert_eq!(snapshot.publish(base, 100).unwrap(), 1);
+        assert_eq!(snapshot.parameters_hash, base.hash());
+        snapshot.check_pin(1, &base).unwrap();
+        // Nothing was ever displayed as version 0, nor yet as version 2
+        assert!(snapshot.check_pin(0, &base).is_err());
+        assert!(snapshot.check_pin(2, &base).is_err());
+
+        // A favorable change keeps version 1 pins valid
+        let cheaper = EconomicParameters {
+            yield_fee_bps: 250,
+            ..base
+        };
+        assert_eq!(snapshot.publish(cheaper, 200).unwrap(), 2);
+        assert_eq!(snapshot.adverse_version, 1);
+        snapshot.check_pin(1, &cheaper).unwrap();
+
+        // An unpublished adverse change is caught against the snapshot
+        let pricier = EconomicParameters {
+            yield_fee_bps: 900,
+            ..cheaper
+        };
+        assert!(snapshot.check_pin(2, &pricier).is_err());
+
+        // Once published, it invalidates every older pin
+        assert_eq!(snapshot.publish(pricier, 300).unwrap(), 3);
+        assert_eq!(snapshot.adverse_version, 3);
+        assert!(snapshot.check_pin(2, &pricier).is_err());
+        snapshot.check_pin(3, &pricier).unwrap();
+    }
+}
@@ -1,3 +1,15 @@
+//! Pending Deposits Buffer - FIFO queue of deposits awaiting insertion
+//!
+//! A zero-copy account: the queue is a fixed-capacity ring buffer read and
+//! written in place, so deposits and batches touch only the entries they
+//! use instead of (de)serializing all `MAX_PENDING_DEPOSITS` of them. The
+//! oldest pending deposit sits at `head`; the next one is appended
+//! `total_pending` entries after it, wrapping at the end of the array.
+//!
+//! Buffers created before the ring layout (`LEGACY_VERSION`, a Borsh
+//! `Vec`) are rewritten in place by `migrate_pending_buffer`; until then
+//! the instructions using the buffer reject it.
+
 use anchor_lang::prelude::*;
 
 use crate::error::PrivacyErrorV2;
@@ -18,7 +30,8 @@ pub const MIN_BATCH_INTERVAL_SECONDS: i64 = 60;
 /// Contains ONLY:
 /// - Commitment (privacy-preserving hash)
 /// - Timestamp (for ordering/timing)
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[zero_copy]
+#[derive(Debug, PartialEq, Eq)]
 pub struct PendingDeposit {
     /// Poseidon commitment = H(secret, nullifier, amount, asset_id)
     ///
@@ -53,19 +66,16 @@ impl PendingDeposit {
 /// Pending Deposits Buffer Account
 ///
 /// PDA Seeds: `[b"pending_deposits", pool.key().as_ref()]`
-#[account]
+#[account(zero_copy)]
 pub struct PendingDepositsBuffer {
     /// Reference to parent pool
     pub pool: Pubkey,
 
-    /// Pending deposits (max 100)
+    /// Ring of pending deposits, oldest at `head`
     ///
     /// Privacy-safe: Contains only commitments and timestamps.
     /// No depositor addresses or amounts stored.
-    pub deposits: Vec<PendingDeposit>,
-
-    /// Total deposits currently pending
-    pub total_pending: u32,
+    pub deposits: [PendingDeposit; MAX_PENDING_DEPOSITS],
 
     /// Last batch processing timestamp
     pub last_batch_at: i64,
@@ -76,11 +86,58 @@ pub struct PendingDepositsBuffer {
     /// Total deposits ever batched (statistics)
     pub total_deposits_batched: u64,
 
+    /// Sequence number of the next batch to be processed
+    ///
+    /// Incremented on every processed batch. Batchers pass the value they
+    /// observed so a stale or concurrent submission fails instead of
+    /// processing a different slice of the buffer than intended.
+    pub batch_seq: u64,
+
+    /// Crank bounty paid in by the deposits still queued and not yet paid
+    /// out; the batcher is paid only against it
+    pub escrowed_bounty_lamports: u64,
+
+    /// Ring index of the oldest pending deposit
+    pub head: u32,
+
+    /// Total deposits currently pending
+    pub total_pending: u32,
+
     /// PDA bump seed
     pub bump: u8,
 
     /// Buffer version
     pub version: u8,
+
+    /// Keeps the size a multiple of the struct's alignment
+    pub _padding: [u8; 6],
+}
+
+// `from_bytes` needs the exact size: no implicit padding between fields
+const _: () =
+    assert!(PendingDepositsBuffer::LEN == 8 + std::mem::size_of::<PendingDepositsBuffer>());
+
+/// Borsh layout of buffers created before the ring buffer
+/// (`PendingDepositsBuffer::LEGACY_VERSION`), read once by
+/// `migrate_pending_buffer`
+#[derive(AnchorDeserialize)]
+pub struct LegacyPendingDepositsBuffer {
+    pub pool: Pubkey,
+    pub deposits: Vec<LegacyPendingDeposit>,
+    pub total_pending: u32,
+    pub last_batch_at: i64,
+    pub total_batches_processed: u64,
+    pub total_deposits_batched: u64,
+    pub bump: u8,
+    pub version: u8,
+    pub batch_seq: u64,
+}
+
+/// Borsh form of a `PendingDeposit` in a legacy buffer
+#[derive(AnchorDeserialize)]
+pub struct LegacyPendingDeposit {
+    pub commitment: [u8; 32],
+    pub timestamp: i64,
 }
 
 impl PendingDepositsBuffer {
@@ -88,6 +145,21 @@ impl PendingDepositsBuffer {
 
     /// Calculate space for pending deposits buffer
     pub const LEN: usize = 8                                    // discriminator
+        + 32                                                    // pool
+        + PendingDeposit::LEN * MAX_PENDING_DEPOSITS            // deposits ring
+        + 8                                                     // last_batch_at
+        + 8                                                     // total_batches_processed
+        + 8                                                     // total_deposits_batched
+        + 8                                                     // batch_seq
+        + 8                                                     // escrowed_bounty_lamports
+        + 4                                                     // head
+        + 4                                                     // total_pending
+        + 1                                                     // bump
+        + 1                                                     // version
+        + 6; // padding
+
+    /// Size of a `LEGACY_VERSION` buffer
+    pub const LEGACY_LEN: usize = 8                             // discriminator
         + 32                                                    // pool
         + 4 + (PendingDeposit::LEN * MAX_PENDING_DEPOSITS)     // deposits vec
         + 4                                                     // total_pending
@@ -95,20 +167,75 @@ impl PendingDepositsBuffer {
         + 8                                                     // total_batches_processed
         + 8                                                     // total_deposits_batched
         + 1                                                     // bump
-        + 1; // version
+        + 1                                                     // version
+        + 8; // batch_seq
+
+    pub const VERSION: u8 = 2;
+
+    /// Borsh `Vec` layout, before the ring buffer
+    pub const LEGACY_VERSION: u8 = 1;
+
+    /// Whether `account` holds the ring layout (and not a buffer still
+    /// awaiting `migrate_pending_buffer`)
+    pub fn has_current_layout(account: &AccountInfo) -> bool {
+        account.data_len() == Self::LEN
+    }
 
-    pub const VERSION: u8 = 1;
+    /// Stored bump, for `seeds` constraints: those run before any other
+    /// constraint, so a legacy buffer has to be refused here rather than
+    /// read as the ring layout
+    pub fn checked_bump(buffer: &AccountLoader<Self>) -> Result<u8> {
+        require!(
+            Self::has_current_layout(&buffer.to_account_info()),
+            PrivacyErrorV2::PendingBufferNeedsMigration
+        );
+        Ok(buffer.load()?.bump)
+    }
 
     /// Initialize the pending deposits buffer
     pub fn initialize(&mut self, pool: Pubkey, bump: u8, timestamp: i64) {
         self.pool = pool;
-        self.deposits = Vec::with_capacity(MAX_PENDING_DEPOSITS);
+        // In place: a whole-array temporary would not fit a BPF stack frame
+        self.deposits.fill(PendingDeposit::new([0u8; 32], 0));
+        self.head = 0;
         self.total_pending = 0;
         self.last_batch_at = timestamp;
         self.total_batches_processed = 0;
         self.total_deposits_batched = 0;
         self.bump = bump;
         self.version = Self::VERSION;
+        self._padding = [0u8; 6];
+        self.batch_seq = 0;
+        self.escrowed_bounty_lamports = 0;
+    }
+
+    /// Rewrite a legacy buffer in the ring layout, keeping its queue order
+    /// and statistics
+    pub fn migrate_from(&mut self, legacy: &LegacyPendingDepositsBuffer) -> Result<()> {
+        require!(
+            legacy.deposits.len() <= MAX_PENDING_DEPOSITS
+                && legacy.deposits.len() == legacy.total_pending as usize,
+            PrivacyErrorV2::InvalidBatchSize
+        );
+        self.initialize(legacy.pool, legacy.bump, legacy.last_batch_at);
+        for (slot, deposit) in self.deposits.iter_mut().zip(&legacy.deposits) {
+            *slot = PendingDeposit::new(deposit.commitment, deposit.timestamp);
+        }
+        self.total_pending = legacy.total_pending;
+        self.total_batches_processed = legacy.total_batches_processed;
+        self.total_deposits_batched = legacy.total_deposits_batched;
+        self.batch_seq = legacy.batch_seq;
+        Ok(())
+    }
+
+    /// Ring index of the `position`-th oldest pending deposit
+    fn slot(&self, position: usize) -> usize {
+        (self.head as usize + position) % MAX_PENDING_DEPOSITS
+    }
+
+    /// Pending deposits, oldest first
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &PendingDeposit> + '_ {
+        self.prepare_batch(self.total_pending.min(u16::MAX as u32) as u16)
     }
 
     /// Add a pending deposit to the buffer
@@ -118,14 +245,25 @@ impl PendingDepositsBuffer {
     /// * `timestamp` - Current timestamp
     ///
     /// # Errors
-    /// - `BufferFull` if buffer is at capacity
+    /// - `PendingBufferFull` if buffer is at capacity
     /// - `InvalidCommitment` if commitment is zero
+    /// - `InvalidTimestamp` if `timestamp` is older than the newest entry
     ///
     /// # Returns
-    /// Index of the deposit in the buffer
+    /// Queue position of the deposit (0 = next to be batched)
     pub fn add_pending(&mut self, commitment: [u8; 32], timestamp: i64) -> Result<usize> {
         // Check buffer not full
-        require!(!self.is_full(), PrivacyErrorV2::BufferFull);
+        require!(!self.is_full(), PrivacyErrorV2::PendingBufferFull);
+
+        // Keep the queue FIFO: entries are appended in timestamp order so the
+        // head of the ring is always the oldest deposit
+        let position = self.size();
+        if let Some(newest) = position.checked_sub(1).map(|p| self.deposits[self.slot(p)]) {
+            require!(
+                timestamp >= newest.timestamp,
+                PrivacyErrorV2::InvalidTimestamp
+            );
+        }
 
         // Validate commitment is not zero (reserved for empty Merkle leaves)
         require!(
@@ -133,35 +271,34 @@ impl PendingDepositsBuffer {
             PrivacyErrorV2::InvalidCommitment
         );
 
-        // Create pending deposit entry
-        let pending = PendingDeposit::new(commitment, timestamp);
-
-        // Add to buffer
-        self.deposits.push(pending);
+        // Write the entry at the tail of the ring
+        let tail = self.slot(position);
+        self.deposits[tail] = PendingDeposit::new(commitment, timestamp);
         self.total_pending = self
             .total_pending
             .checked_add(1)
             .ok_or(PrivacyErrorV2::ArithmeticOverflow)?;
 
-        // Return index in buffer
-        Ok(self.deposits.len() - 1)
+        Ok(position)
     }
 
     /// Process a batch of deposits
     ///
     /// # Arguments
     /// * `max_to_process` - Maximum number of deposits to process
-    /// * `timestamp` - Batch processing timestamp
     ///
     /// # Returns
-    /// Slice of deposits to process (up to max_to_process)
+    /// The oldest deposits (up to max_to_process), oldest first
     ///
     /// # Note
     /// Call `clear_processed()` after successful Merkle insertion
-    pub fn prepare_batch(&self, max_to_process: u16) -> &[PendingDeposit] {
-        let to_process = std::cmp::min(max_to_process as usize, self.deposits.len());
+    pub fn prepare_batch(
+        &self,
+        max_to_process: u16,
+    ) -> impl ExactSizeIterator<Item = &PendingDeposit> + '_ {
+        let to_process = std::cmp::min(max_to_process as usize, self.size());
 
-        &self.deposits[..to_process]
+        (0..to_process).map(move |position| &self.deposits[self.slot(position)])
     }
 
     /// Clear processed deposits from buffer
@@ -179,8 +316,8 @@ impl PendingDepositsBuffer {
             PrivacyErrorV2::InvalidBatchSize
         );
 
-        // Remove processed entries from front
-        self.deposits.drain(..count as usize);
+        // Advance the head past the processed entries
+        self.head = self.slot(count as usize) as u32;
 
         // Update statistics
         self.total_pending = self
@@ -198,24 +335,93 @@ impl PendingDepositsBuffer {
             .checked_add(count as u64)
             .ok_or(PrivacyErrorV2::ArithmeticOverflow)?;
 
+        self.batch_seq = self
+            .batch_seq
+            .checked_add(1)
+            .ok_or(PrivacyErrorV2::ArithmeticOverflow)?;
+
         self.last_batch_at = timestamp;
 
         Ok(())
     }
 
+    /// Drop every pending deposit without inserting it, along with the
+    /// bounty escrowed for them; returns that bounty
+    pub fn clear(&mut self) -> u64 {
+        self.head = 0;
+        self.total_pending = 0;
+        std::mem::take(&mut self.escrowed_bounty_lamports)
+    }
+
+    /// Record `lamports` of crank bounty paid into the buffer by a deposit
+    pub fn escrow_bounty(&mut self, lamports: u64) -> Result<()> {
+        self.escrowed_bounty_lamports = self
+            .escrowed_bounty_lamports
+            .checked_add(lamports)
+            .ok_or(PrivacyErrorV2::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Take up to `owed` lamports out of the escrowed bounty; returns the
+    /// amount taken
+    pub fn release_bounty(&mut self, owed: u64) -> u64 {
+        let released = owed.min(self.escrowed_bounty_lamports);
+        self.escrowed_bounty_lamports -= released;
+        released
+    }
+
+    /// Verify the caller's view of the batch sequence
+    ///
+    /// # Errors
+    /// - `BatchSequenceMismatch` if `expected` is set and differs from `batch_seq`
+    pub fn check_batch_seq(&self, expected: Option<u64>) -> Result<()> {
+        if let Some(expected) = expected {
+            require!(
+                expected == self.batch_seq,
+                PrivacyErrorV2::BatchSequenceMismatch
+            );
+        }
+        Ok(())
+    }
+
     /// Check if buffer is full
     pub fn is_full(&self) -> bool {
-        self.deposits.len() >= MAX_PENDING_DEPOSITS
+        self.size() >= MAX_PENDING_DEPOSITS
     }
 
     /// Check if buffer is empty
     pub fn is_empty(&self) -> bool {
-        self.deposits.is_empty()
+        self.total_pending == 0
     }
 
     /// Get current buffer size
     pub fn size(&self) -> usize {
-        self.deposits.len()
+        self.total_pending as usize
+    }
+
+    /// Age in seconds of the oldest pending deposit (0 when empty)
+    pub fn oldest_pending_age(&self, current_timestamp: i64) -> i64 {
+        self.iter()
+            .next()
+            .map(|d| current_timestamp.saturating_sub(d.timestamp).max(0))
+            .unwrap_or(0)
+    }
+
+    /// Estimated seconds until a slot frees up in a full buffer
+    ///
+    /// Based on the batch interval: the next batch is expected once
+    /// `MIN_BATCH_INTERVAL_SECONDS` have passed since the last one. If the
+    /// batcher is already overdue, one further interval is assumed.
+    pub fn estimated_wait_seconds(&self, current_timestamp: i64) -> i64 {
+        if !self.is_full() {
+            return 0;
+        }
+        let elapsed = current_timestamp.saturating_sub(self.last_batch_at);
+        if elapsed < MIN_BATCH_INTERVAL_SECONDS {
+            MIN_BATCH_INTERVAL_SECONDS - elapsed.max(0)
+        } else {
+            MIN_BATCH_INTERVAL_SECONDS
+        }
     }
 
     /// Check if enough time has passed since last batch
@@ -239,13 +445,22 @@ impl PendingDepositsBuffer {
 mod tests {
     use super::*;
 
+    fn new_buffer(timestamp: i64) -> PendingDepositsBuffer {
+        let mut buffer: PendingDepositsBuffer = bytemuck::Zeroable::zeroed();
+        buffer.initialize(Pubkey::default(), 0, timestamp);
+        buffer
+    }
+
     #[test]
     fn test_buffer_space_calculation() {
-        // Ensure buffer size is reasonable (< 5KB without data)
-        let base_size = 8 + 32 + 4 + 4 + 8 + 8 + 8 + 1 + 1;
-        assert!(base_size < 5_000);
+        // The zero-copy layout is exactly the field sum (no implicit padding)
+        assert_eq!(
+            PendingDepositsBuffer::LEN,
+            8 + std::mem::size_of::<PendingDepositsBuffer>()
+        );
+        assert_eq!(std::mem::size_of::<PendingDeposit>(), PendingDeposit::LEN);
 
-        // With full buffer: ~4KB + 40*100 = ~8KB (acceptable)
+        // Full ring: 40*100 + header (~4KB, acceptable)
         assert!(PendingDepositsBuffer::LEN < 10_000);
     }
 
@@ -264,4 +479,144 @@ mod tests {
         assert_eq!(deposit.commitment, [1u8; 32]);
         assert_eq!(deposit.timestamp, 1000);
     }
+
+    #[test]
+    fn test_batch_seq_advances_per_batch() {
+        let mut buffer = new_buffer(0);
+        for i in 1..=3u8 {
+            buffer.add_pending([i; 32], i as i64).unwrap();
+        }
+
+        // No expectation always passes
+        assert!(buffer.check_batch_seq(None).is_ok());
+        assert!(buffer.check_batch_seq(Some(0)).is_ok());
+
+        buffer.clear_processed(2, 100).unwrap();
+        assert_eq!(buffer.batch_seq, 1);
+
+        // A second batcher still holding seq 0 is rejected
+        assert!(buffer.check_batch_seq(Some(0)).is_err());
+        assert!(buffer.check_batch_seq(Some(1)).is_ok());
+    }
+
+    #[test]
+    fn test_bounty_paid_only_against_escrow() {
+        let mut buffer = new_buffer(0);
+        buffer.escrow_bounty(300).unwrap();
+        buffer.escrow_bounty(200).unwrap();
+
+        assert_eq!(buffer.release_bounty(400), 400);
+        // A batch of change outputs owes more than deposits paid in
+        assert_eq!(buffer.release_bounty(400), 100);
+        assert_eq!(buffer.release_bounty(400), 0);
+
+        // Dropping the queue drops its escrow too
+        buffer.escrow_bounty(50).unwrap();
+        assert_eq!(buffer.clear(), 50);
+        assert_eq!(buffer.escrowed_bounty_lamports, 0);
+    }
+
+    #[test]
+    fn test_full_buffer_reports_age_and_wait() {
+        let mut buffer = new_buffer(1_000);
+        assert_eq!(buffer.oldest_pending_age(1_000), 0);
+
+        for i in 0..MAX_PENDING_DEPOSITS {
+            buffer.add_pending([1u8; 32], 1_000 + i as i64).unwrap();
+        }
+        assert!(buffer.is_full());
+        assert_eq!(buffer.oldest_pending_age(1_150), 150);
+
+        // 20s after the last batch: next one expected in 40s
+        assert_eq!(buffer.estimated_wait_seconds(1_020), 40);
+        // Batcher overdue: assume one more interval
+        assert_eq!(
+            buffer.estimated_wait_seconds(5_000),
+            MIN_BATCH_INTERVAL_SECONDS
+        );
+
+        let err = buffer.add_pending([2u8; 32], 5_000).unwrap_err();
+        assert_eq!(err, PrivacyErrorV2::PendingBufferFull.into());
+    }
+
+    #[test]
+    fn test_add_pending_enforces_fifo_order() {
+        let mut buffer = new_buffer(0);
+        buffer.add_pending([1u8; 32], 200).unwrap();
+        buffer.add_pending([2u8; 32], 200).unwrap();
+        assert!(buffer.add_pending([3u8; 32], 199).is_err());
+        assert_eq!(
+            buffer.prepare_batch(1).next().unwrap().commitment,
+            [1u8; 32]
+        );
+    }
+
+    #[test]
+    fn test_ring_wraps_around_in_fifo_order() {
+        let mut buffer = new_buffer(0);
+        for i in 0..MAX_PENDING_DEPOSITS {
+            buffer.add_pending([i as u8 + 1; 32], i as i64).unwrap();
+        }
+        buffer.clear_processed(30, 100).unwrap();
+        assert_eq!(buffer.head, 30);
+
+        // New entries land in the slots freed at the start of the array
+        for i in 0..30u8 {
+            let position = buffer.add_pending([200 + i; 32], 200).unwrap();
+            assert_eq!(position, 70 + i as usize);
+        }
+        assert!(buffer.is_full());
+        assert_eq!(buffer.deposits[0].commitment, [200u8; 32]);
+
+        let order: Vec<u8> = buffer.iter().map(|d| d.commitment[0]).collect();
+        let expected: Vec<u8> = (31..=100).chain(200..230).collect();
+        assert_eq!(order, expected);
+        assert_eq!(buffer.oldest_pending_age(200), 200 - 30);
+
+        // Draining past the end of the array wraps the head back to the start
+        buffer.clear_processed(80, 300).unwrap();
+        assert_eq!(buffer.head, 10);
+        let rest: Vec<u8> = buffer.prepare_batch(50).map(|d| d.commitment[0]).collect();
+        assert_eq!(rest, (210..230).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn test_migrate_from_legacy_layout() {
+        assert_eq!(PendingDepositsBuffer::LEGACY_LEN, 4082);
+
+        let legacy = LegacyPendingDepositsBuffer {
+            pool: Pubkey::new_unique(),
+            deposits: (1..=3u8)
+                .map(|i| LegacyPendingDeposit {
+                    commitment: [i; 32],
+                    timestamp: i as i64,
+                })
+                .collect(),
+            total_pending: 3,
+            last_batch_at: 50,
+            total_batches_processed: 4,
+            total_deposits_batched: 40,
+            bump: 254,
+            version: PendingDepositsBuffer::LEGACY_VERSION,
+            batch_seq: 4,
+        };
+        let mut buffer = new_buffer(0);
+        buffer.migrate_from(&legacy).unwrap();
+
+        assert_eq!(buffer.pool, legacy.pool);
+        assert_eq!(buffer.bump, 254);
+        assert_eq!(buffer.version, PendingDepositsBuffer::VERSION);
+        assert_eq!(buffer.batch_seq, 4);
+        assert_eq!(buffer.total_deposits_batched, 40);
+        assert_eq!(buffer.last_batch_at, 50);
+        // Legacy buffers predate the crank bounty and escrow nothing
+        assert_eq!(buffer.escrowed_bounty_lamports, 0);
+        let order: Vec<u8> = buffer.iter().map(|d| d.commitment[0]).collect();
+        assert_eq!(order, vec![1, 2, 3]);
+
+        // Queue length must match the entries actually stored
+        let mut inconsistent = legacy;
+        inconsistent.total_pending = 2;
+        assert!(new_buffer(0).migrate_from(&inconsistent).is_err());
+    }
 }
@@ -1,9 +1,12 @@
 use anchor_lang::prelude::*;
 
+use crate::crypto::RangeProofScheme;
 use crate::error::PrivacyErrorV2;
+use crate::utils::{require_no_opposite_flow, PoolFlow};
 use crate::ProofType;
 
 #[account]
+#[derive(InitSpace)]
 pub struct PoolConfigV2 {
     pub authority: Pubkey,
     pub pending_authority: Pubkey,
@@ -33,7 +36,72 @@ pub struct PoolConfigV2 {
     pub last_activity_at: i64,
     pub version: u8,
     pub feature_flags: u8,
-    pub _reserved: [u8; 30],
+
+    /// Highest tree capacity level reached (see CAPACITY_LEVEL_*)
+    pub tree_capacity_level: u8,
+    /// Fill percentage that raises a capacity warning (0 = default)
+    pub capacity_warning_pct: u8,
+    /// Fill percentage that raises a critical capacity warning (0 = default)
+    pub capacity_critical_pct: u8,
+
+    /// Last sequence number assigned to a deposit/withdrawal event
+    pub event_sequence: u64,
+
+    /// Largest encrypted note accepted with a new commitment (0 = default)
+    pub max_encrypted_note_len: u16,
+
+    /// Range proof scheme checked on deposit/withdraw amounts
+    /// (0 = none, see `crypto::range_check`)
+    pub range_proof_scheme: u8,
+
+    /// Whether one transaction may both deposit into and withdraw from the
+    /// pool (see `utils::same_tx_guard`)
+    pub allow_same_tx_deposit_withdraw: bool,
+
+    /// Whether a `DepositHook` is registered (batches must then call it)
+    pub deposit_hook_enabled: bool,
+
+    /// Whether every Merkle root change emits a `RootUpdatedEvent`
+    pub emit_root_events: bool,
+
+    /// Last sequence number assigned to a `RootUpdatedEvent`
+    pub root_event_sequence: u64,
+
+    /// Whether spent nullifiers never carry a note receipt (see
+    /// `state::spent_nullifier`)
+    pub privacy_strict: bool,
+
+    /// Lamports charged per deposit and paid per inserted leaf to whoever
+    /// runs `batch_process_deposits` (0 = no crank bounty)
+    pub crank_bounty_lamports: u32,
+
+    /// Share of each token withdrawal paid to the asset's treasury, in basis
+    /// points (0 = no protocol fee)
+    pub protocol_fee_bps: u16,
+
+    /// Whether the authority and auditor may together move a disclosed
+    /// note's funds to a custodian (`compliance_recovery_withdraw`); chosen
+    /// at pool creation and never changed
+    pub compliance_recovery_enabled: bool,
+
+    /// Whether notes may be withdrawn by disclosing their opening and
+    /// Merkle path (`disclosed_path_withdraw`, reduced privacy); only until
+    /// the withdraw verification key is set
+    pub disclosed_path_withdrawals: bool,
+
+    /// Operator-chosen id telling apart the pools of one authority (part
+    /// of the PDA seeds)
+    pub pool_id: u64,
+
+    /// Whether the pool has a `PoolDirectoryEntry` (`list_pool`)
+    pub directory_listed: bool,
+
+    /// Whether `init_anonymity_stats` has run: deposits and withdrawals
+    /// must then pass their asset's `AnonymityStats`
+    pub anonymity_stats_enabled: bool,
+
+    /// Space for future fields, so they need no further migration
+    pub _reserved: [u8; 31],
 }
 
 impl PoolConfigV2 {
@@ -60,8 +128,31 @@ impl PoolConfigV2 {
         + 8
         + 1
         + 1
-        + 30;
-    pub const VERSION: u8 = 2;
+        + 1
+        + 1
+        + 1
+        + 8
+        + 2
+        + 1
+        + 1
+        + 1
+        + 1
+        + 8
+        + 1
+        + 4
+        + 2
+        + 1
+        + 1
+        + 8
+        + 1
+        + 1
+        + 31;
+    /// Size before `protocol_fee_bps`: pools created then are grown by
+    /// `migrate_pool_config`
+    pub const LEGACY_LEN: usize = 291;
+    pub const VERSION: u8 = 3;
+    /// Layout of `LEGACY_LEN` accounts
+    pub const LEGACY_VERSION: u8 = 2;
     pub const DEFAULT_MAX_ASSETS: u16 = 100;
     pub const FEATURE_MASP: u8 = 1 << 0;
     pub const FEATURE_JOIN_SPLIT: u8 = 1 << 1;
@@ -69,8 +160,28 @@ impl PoolConfigV2 {
     pub const FEATURE_SHIELDED_CPI: u8 = 1 << 3;
     pub const FEATURE_COMPLIANCE: u8 = 1 << 4;
     pub const FEATURE_YIELD_ENFORCEMENT: u8 = 1 << 5;
+    /// Withdrawals require the VK to have passed `submit_canary_proof`
+    pub const FEATURE_REQUIRE_VK_CANARY: u8 = 1 << 6;
+    /// Asset vaults keep lifetime deposit/withdrawal totals only as
+    /// power-of-two buckets (see `AssetVault::coarse_amount`)
+    pub const FEATURE_COARSE_LIQUIDITY_STATS: u8 = 1 << 7;
     pub const YIELD_FEE_BPS: u16 = 500; // 5% performance fee
 
+    pub const CAPACITY_LEVEL_NORMAL: u8 = 0;
+    pub const CAPACITY_LEVEL_WARNING: u8 = 1;
+    pub const CAPACITY_LEVEL_CRITICAL: u8 = 2;
+    pub const CAPACITY_LEVEL_FULL: u8 = 3;
+    pub const DEFAULT_CAPACITY_WARNING_PCT: u8 = 80;
+    pub const DEFAULT_CAPACITY_CRITICAL_PCT: u8 = 95;
+    /// Typical note ciphertext: ephemeral key, nonce, plaintext and tag
+    pub const DEFAULT_MAX_ENCRYPTED_NOTE_LEN: u16 = 580;
+    /// Upper bound for the configurable limit; a transaction is 1232 bytes
+    pub const MAX_ENCRYPTED_NOTE_LEN_LIMIT: u16 = 1024;
+    /// Upper bound for the per-leaf crank bounty (0.001 SOL)
+    pub const MAX_CRANK_BOUNTY_LAMPORTS: u32 = 1_000_000;
+    /// Upper bound for the protocol fee (1%)
+    pub const MAX_PROTOCOL_FEE_BPS: u16 = 100;
+
     #[allow(clippy::too_many_arguments)]
     pub fn initialize(
         &mut self,
@@ -107,7 +218,31 @@ impl PoolConfigV2 {
         self.registered_asset_count = 0;
         self.version = Self::VERSION;
         self.feature_flags = Self::FEATURE_MASP;
-        self._reserved = [0u8; 30];
+        self.tree_capacity_level = Self::CAPACITY_LEVEL_NORMAL;
+        self.capacity_warning_pct = Self::DEFAULT_CAPACITY_WARNING_PCT;
+        self.capacity_critical_pct = Self::DEFAULT_CAPACITY_CRITICAL_PCT;
+        self.event_sequence = 0;
+        self.max_encrypted_note_len = Self::DEFAULT_MAX_ENCRYPTED_NOTE_LEN;
+        self.range_proof_scheme = RangeProofScheme::None as u8;
+        self.allow_same_tx_deposit_withdraw = false;
+        self.deposit_hook_enabled = false;
+        self.emit_root_events = false;
+        self.root_event_sequence = 0;
+        self.privacy_strict = false;
+        self.crank_bounty_lamports = 0;
+        self.protocol_fee_bps = 0;
+        self.compliance_recovery_enabled = false;
+        self.disclosed_path_withdrawals = false;
+        self.pool_id = 0;
+        self.directory_listed = false;
+        self.anonymity_stats_enabled = false;
+        self._reserved = [0u8; 31];
+    }
+
+    /// Whether `account` holds the current layout (and not a config still
+    /// awaiting `migrate_pool_config`)
+    pub fn has_current_layout(account: &AccountInfo) -> bool {
+        account.data_len() == Self::LEN
     }
 
     #[inline]
@@ -116,6 +251,17 @@ impl PoolConfigV2 {
         Ok(())
     }
 
+    /// Refuse a deposit or withdrawal that leaves out the asset's
+    /// `AnonymityStats` once the pool keeps them
+    #[inline]
+    pub fn require_anonymity_stats(&self, passed: bool) -> Result<()> {
+        require!(
+            passed || !self.anonymity_stats_enabled,
+            PrivacyErrorV2::AnonymityStatsRequired
+        );
+        Ok(())
+    }
+
     #[inline]
     pub fn require_vk_configured(&self, proof_type: ProofType) -> Result<()> {
         let mask = 1u8 << (proof_type as u8);
@@ -160,6 +306,21 @@ impl PoolConfigV2 {
         self.require_feature_enabled(Self::FEATURE_SHIELDED_CPI)
     }
 
+    /// Require disclosed-path withdrawals to be switched on and the
+    /// withdraw verification key to be still unset
+    #[inline]
+    pub fn require_disclosed_path_withdrawals(&self) -> Result<()> {
+        require!(
+            self.disclosed_path_withdrawals,
+            PrivacyErrorV2::DisclosedPathWithdrawalsDisabled
+        );
+        require!(
+            !self.is_vk_configured(ProofType::Withdraw),
+            PrivacyErrorV2::WithdrawVerificationKeySet
+        );
+        Ok(())
+    }
+
     pub fn set_vk_configured(&mut self, proof_type: ProofType) {
         let mask = 1u8 << (proof_type as u8);
         self.vk_configured |= mask;
@@ -202,6 +363,31 @@ impl PoolConfigV2 {
         Ok(())
     }
 
+    /// Assign the next per-pool event sequence number (first is 1)
+    ///
+    /// Gives indexers a total order over deposit and withdrawal events that
+    /// does not depend on slot or transaction ordering reported by RPC.
+    pub fn next_event_sequence(&mut self) -> Result<u64> {
+        self.event_sequence = self
+            .event_sequence
+            .checked_add(1)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        Ok(self.event_sequence)
+    }
+
+    /// Sequence number for the next `RootUpdatedEvent`, or `None` when the
+    /// pool does not emit them
+    pub fn next_root_event_sequence(&mut self) -> Result<Option<u64>> {
+        if !self.emit_root_events {
+            return Ok(None);
+        }
+        self.root_event_sequence = self
+            .root_event_sequence
+            .checked_add(1)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        Ok(Some(self.root_event_sequence))
+    }
+
     pub fn record_withdrawal(&mut self, timestamp: i64) -> Result<()> {
         self.total_withdrawals = self
             .total_withdrawals
@@ -243,6 +429,16 @@ impl PoolConfigV2 {
         Ok(())
     }
 
+    /// Check if withdrawals require a canary-verified VK
+    pub fn requires_vk_canary(&self) -> bool {
+        (self.feature_flags & Self::FEATURE_REQUIRE_VK_CANARY) != 0
+    }
+
+    /// Check if asset vault totals are kept as coarse buckets
+    pub fn has_coarse_liquidity_stats(&self) -> bool {
+        (self.feature_flags & Self::FEATURE_COARSE_LIQUIDITY_STATS) != 0
+    }
+
     /// Check if yield enforcement is enabled
     pub fn is_yield_enforcement_enabled(&self) -> bool {
         (self.feature_flags & Self::FEATURE_YIELD_ENFORCEMENT) != 0
@@ -289,6 +485,137 @@ impl PoolConfigV2 {
         self.pending_authority != Pubkey::default()
     }
 
+    /// Effective (warning, critical) fill thresholds in percent.
+    ///
+    /// Pools created before thresholds were stored have zeroed fields,
+    /// which fall back to the defaults.
+    pub fn capacity_thresholds(&self) -> (u8, u8) {
+        let warning = match self.capacity_warning_pct {
+            0 => Self::DEFAULT_CAPACITY_WARNING_PCT,
+            pct => pct,
+        };
+        let critical = match self.capacity_critical_pct {
+            0 => Self::DEFAULT_CAPACITY_CRITICAL_PCT,
+            pct => pct,
+        };
+        (warning, critical)
+    }
+
+    pub fn set_capacity_thresholds(&mut self, warning_pct: u8, critical_pct: u8) -> Result<()> {
+        require!(
+            warning_pct > 0 && warning_pct < critical_pct && critical_pct < 100,
+            PrivacyErrorV2::InvalidCapacityThresholds
+        );
+        self.capacity_warning_pct = warning_pct;
+        self.capacity_critical_pct = critical_pct;
+        Ok(())
+    }
+
+    /// Raise the stored capacity level for the given tree fill percentage.
+    ///
+    /// Returns `Some((level, threshold_pct))` only when a new, higher level
+    /// was reached, so callers emit a single warning per threshold crossing.
+    pub fn update_tree_capacity_level(&mut self, fill_percentage: u8) -> Option<(u8, u8)> {
+        let (warning, critical) = self.capacity_thresholds();
+        let (level, threshold) = if fill_percentage >= 100 {
+            (Self::CAPACITY_LEVEL_FULL, 100)
+        } else if fill_percentage >= critical {
+            (Self::CAPACITY_LEVEL_CRITICAL, critical)
+        } else if fill_percentage >= warning {
+            (Self::CAPACITY_LEVEL_WARNING, warning)
+        } else {
+            return None;
+        };
+
+        if level <= self.tree_capacity_level {
+            return None;
+        }
+        self.tree_capacity_level = level;
+        Some((level, threshold))
+    }
+
+    /// Effective encrypted note size limit in bytes; zero (pools created
+    /// before the limit was stored) falls back to the default.
+    pub fn max_encrypted_note_len(&self) -> u16 {
+        match self.max_encrypted_note_len {
+            0 => Self::DEFAULT_MAX_ENCRYPTED_NOTE_LEN,
+            len => len,
+        }
+    }
+
+    pub fn set_max_encrypted_note_len(&mut self, max_len: u16) -> Result<()> {
+        require!(
+            max_len > 0 && max_len <= Self::MAX_ENCRYPTED_NOTE_LEN_LIMIT,
+            PrivacyErrorV2::InvalidEncryptedNoteLimit
+        );
+        self.max_encrypted_note_len = max_len;
+        Ok(())
+    }
+
+    /// Set the per-leaf crank bounty
+    pub fn set_crank_bounty(&mut self, lamports_per_leaf: u32) -> Result<()> {
+        require!(
+            lamports_per_leaf <= Self::MAX_CRANK_BOUNTY_LAMPORTS,
+            PrivacyErrorV2::CrankBountyTooLarge
+        );
+        self.crank_bounty_lamports = lamports_per_leaf;
+        Ok(())
+    }
+
+    /// Crank bounty owed for inserting `leaves` pending deposits
+    pub fn crank_bounty_for(&self, leaves: u32) -> u64 {
+        self.crank_bounty_lamports as u64 * leaves as u64
+    }
+
+    /// Set the protocol fee on withdrawals
+    pub fn set_protocol_fee(&mut self, fee_bps: u16) -> Result<()> {
+        require!(
+            fee_bps <= Self::MAX_PROTOCOL_FEE_BPS,
+            PrivacyErrorV2::ProtocolFeeTooHigh
+        );
+        self.protocol_fee_bps = fee_bps;
+        Ok(())
+    }
+
+    /// Protocol fee owed on withdrawing `amount` (rounded down)
+    pub fn protocol_fee_for(&self, amount: u64) -> u64 {
+        (amount as u128 * self.protocol_fee_bps as u128 / 10_000) as u64
+    }
+
+    /// Reject an encrypted note larger than the pool allows
+    pub fn require_encrypted_note_size(&self, note: Option<&[u8]>) -> Result<()> {
+        if let Some(note) = note {
+            require!(
+                note.len() <= self.max_encrypted_note_len() as usize,
+                PrivacyErrorV2::EncryptedNoteTooLarge
+            );
+        }
+        Ok(())
+    }
+
+    /// Range proof scheme for the amount range-check hook
+    pub fn range_proof_scheme(&self) -> Result<RangeProofScheme> {
+        RangeProofScheme::from_u8(self.range_proof_scheme)
+    }
+
+    /// Reject a deposit or withdrawal sharing its transaction with a flow in
+    /// the opposite direction, unless the pool allows that
+    pub fn require_no_same_tx_flow(
+        &self,
+        pool: &Pubkey,
+        instructions_sysvar: &AccountInfo,
+        flow: PoolFlow,
+    ) -> Result<()> {
+        if self.allow_same_tx_deposit_withdraw {
+            return Ok(());
+        }
+        require_no_opposite_flow(instructions_sysvar, pool, flow)
+    }
+
+    pub fn reset_tree_capacity_level(&mut self) {
+        self.tree_capacity_level = Self::CAPACITY_LEVEL_NORMAL;
+    }
+
     pub fn enable_feature(&mut self, feature: u8) {
         self.feature_flags |= feature;
     }
@@ -304,6 +631,7 @@ impl PoolConfigV2 {
     pub fn initialize_partial(
         &mut self,
         authority: Pubkey,
+        pool_id: u64,
         merkle_tree: Pubkey,
         tree_depth: u8,
         bump: u8,
@@ -326,7 +654,24 @@ impl PoolConfigV2 {
         self.max_assets = Self::DEFAULT_MAX_ASSETS;
         self.registered_asset_count = 0;
         self.feature_flags = Self::FEATURE_MASP;
-        self._reserved = [0u8; 30];
+        self.tree_capacity_level = Self::CAPACITY_LEVEL_NORMAL;
+        self.capacity_warning_pct = Self::DEFAULT_CAPACITY_WARNING_PCT;
+        self.capacity_critical_pct = Self::DEFAULT_CAPACITY_CRITICAL_PCT;
+        self.event_sequence = 0;
+        self.max_encrypted_note_len = Self::DEFAULT_MAX_ENCRYPTED_NOTE_LEN;
+        self.range_proof_scheme = RangeProofScheme::None as u8;
+        self.allow_same_tx_deposit_withdraw = false;
+        self.deposit_hook_enabled = false;
+        self.emit_root_events = false;
+        self.root_event_sequence = 0;
+        self.privacy_strict = false;
+        self.crank_bounty_lamports = 0;
+        self.protocol_fee_bps = 0;
+        self.compliance_recovery_enabled = false;
+        self.disclosed_path_withdrawals = false;
+        self.pool_id = pool_id;
+        self.directory_listed = false;
+        self.anonymity_stats_enabled = false;
     }
 
     pub fn set_registries(
@@ -340,17 +685,73 @@ impl PoolConfigV2 {
         self.yield_relayer = yield_relayer;
         self.yield_fee_bps = Self::YIELD_FEE_BPS;
     }
+
+    /// Take over `source`'s operator settings (`clone_pool_config`)
+    ///
+    /// Counters, verification key state, the deposit hook and
+    /// disclosed-path withdrawals stay as initialized: they belong to the
+    /// source pool's tree and accounts.
+    pub fn inherit_settings(&mut self, source: &PoolConfigV2) {
+        self.yield_relayer = source.yield_relayer;
+        self.yield_fee_bps = source.yield_fee_bps;
+        self.max_assets = source.max_assets;
+        self.feature_flags = source.feature_flags;
+        self.capacity_warning_pct = source.capacity_warning_pct;
+        self.capacity_critical_pct = source.capacity_critical_pct;
+        self.max_encrypted_note_len = source.max_encrypted_note_len;
+        self.range_proof_scheme = source.range_proof_scheme;
+        self.allow_same_tx_deposit_withdraw = source.allow_same_tx_deposit_withdraw;
+        self.emit_root_events = source.emit_root_events;
+        self.privacy_strict = source.privacy_strict;
+        self.crank_bounty_lamports = source.crank_bounty_lamports;
+        self.protocol_fee_bps = source.protocol_fee_bps;
+        self.compliance_recovery_enabled = source.compliance_recovery_enabled;
+    }
 }
 
 impl PoolConfigV2 {
     pub const SEED_PREFIX: &'static [u8] = b"pool_v2";
 
-    pub fn find_pda(program_id: &Pubkey, authority: &Pubkey) -> (Pubkey, u8) {
+    pub fn find_pda(program_id: &Pubkey, authority: &Pubkey, pool_id: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                Self::SEED_PREFIX,
+                authority.as_ref(),
+                &pool_id.to_le_bytes(),
+            ],
+            program_id,
+        )
+    }
+
+    pub fn seeds<'a>(
+        authority: &'a Pubkey,
+        pool_id: &'a [u8; 8],
+        bump: &'a [u8; 1],
+    ) -> [&'a [u8]; 4] {
+        [Self::SEED_PREFIX, authority.as_ref(), pool_id, bump]
+    }
+
+    /// Address of a pool created before pool ids, seeded by its authority
+    /// alone; such pools keep it, with `pool_id` 0
+    pub fn find_legacy_pda(program_id: &Pubkey, authority: &Pubkey) -> (Pubkey, u8) {
         Pubkey::find_program_address(&[Self::SEED_PREFIX, authority.as_ref()], program_id)
     }
 
-    pub fn seeds<'a>(authority: &'a Pubkey, bump: &'a [u8; 1]) -> [&'a [u8]; 3] {
-        [Self::SEED_PREFIX, authority.as_ref(), bump]
+    /// Whether `address` is this config's PDA under `authority`, with
+    /// either the current or (for pool id 0) the legacy seeds
+    pub fn is_pool_address(
+        &self,
+        program_id: &Pubkey,
+        address: &Pubkey,
+        authority: &Pubkey,
+    ) -> bool {
+        let bump = [self.bump];
+        let pool_id = self.pool_id.to_le_bytes();
+        let derives = |seeds: &[&[u8]]| {
+            Pubkey::create_program_address(seeds, program_id).is_ok_and(|pda| pda == *address)
+        };
+        derives(&Self::seeds(authority, &pool_id, &bump))
+            || (self.pool_id == 0 && derives(&[Self::SEED_PREFIX, authority.as_ref(), &bump]))
     }
 }
 
@@ -358,9 +759,8 @@ impl PoolConfigV2 {
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_vk_flags() {
-        let mut config = PoolConfigV2 {
+    fn test_config(feature_flags: u8) -> PoolConfigV2 {
+        PoolConfigV2 {
             authority: Pubkey::default(),
             pending_authority: Pubkey::default(),
             merkle_tree: Pubkey::default(),
@@ -382,9 +782,32 @@ mod tests {
             created_at: 0,
             last_activity_at: 0,
             version: 2,
-            feature_flags: 0,
-            _reserved: [0u8; 30],
-        };
+            feature_flags,
+            tree_capacity_level: 0,
+            capacity_warning_pct: 0,
+            capacity_critical_pct: 0,
+            event_sequence: 0,
+            max_encrypted_note_len: 0,
+            range_proof_scheme: 0,
+            allow_same_tx_deposit_withdraw: false,
+            deposit_hook_enabled: false,
+            emit_root_events: false,
+            root_event_sequence: 0,
+            privacy_strict: false,
+            crank_bounty_lamports: 0,
+            protocol_fee_bps: 0,
+            compliance_recovery_enabled: false,
+            disclosed_path_withdrawals: false,
+            pool_id: 0,
+            directory_listed: false,
+            anonymity_stats_enabled: false,
+            _reserved: [0u8; 31],
+        }
+    }
+
+    #[test]
+    fn test_vk_flags() {
+        let mut config = test_config(0);
 
         assert!(!config.is_vk_configured(ProofType::Withdraw));
         config.set_vk_configured(ProofType::Withdraw);
@@ -398,31 +821,7 @@ mod tests {
 
     #[test]
     fn test_feature_flags() {
-        let mut config = PoolConfigV2 {
-            authority: Pubkey::default(),
-            pending_authority: Pubkey::default(),
-            merkle_tree: Pubkey::default(),
-            relayer_registry: Pubkey::default(),
-            compliance_config: Pubkey::default(),
-            yield_relayer: Pubkey::default(),
-            yield_fee_bps: 500,
-            tree_depth: 20,
-            registered_asset_count: 0,
-            max_assets: 100,
-            bump: 0,
-            is_paused: false,
-            vk_configured: 0,
-            vk_locked: 0,
-            total_deposits: 0,
-            total_withdrawals: 0,
-            total_join_splits: 0,
-            total_membership_proofs: 0,
-            created_at: 0,
-            last_activity_at: 0,
-            version: 2,
-            feature_flags: PoolConfigV2::FEATURE_MASP,
-            _reserved: [0u8; 30],
-        };
+        let mut config = test_config(PoolConfigV2::FEATURE_MASP);
 
         assert!(config.is_feature_enabled(PoolConfigV2::FEATURE_MASP));
         assert!(!config.is_feature_enabled(PoolConfigV2::FEATURE_JOIN_SPLIT));
@@ -433,4 +832,185 @@ mod tests {
         config.disable_feature(PoolConfigV2::FEATURE_JOIN_SPLIT);
         assert!(!config.is_feature_enabled(PoolConfigV2::FEATURE_JOIN_SPLIT));
     }
+
+    #[test]
+    fn test_capacity_thresholds_default_when_unset() {
+        let config = test_config(PoolConfigV2::FEATURE_MASP);
+        assert_eq!(config.capacity_thresholds(), (80, 95));
+    }
+
+    #[test]
+    fn test_capacity_level_escalates_once_per_threshold() {
+        let mut config = test_config(PoolConfigV2::FEATURE_MASP);
+
+        assert_eq!(config.update_tree_capacity_level(79), None);
+        assert_eq!(
+            config.update_tree_capacity_level(80),
+            Some((PoolConfigV2::CAPACITY_LEVEL_WARNING, 80))
+        );
+        assert_eq!(config.update_tree_capacity_level(90), None);
+        assert_eq!(
+            config.update_tree_capacity_level(97),
+            Some((PoolConfigV2::CAPACITY_LEVEL_CRITICAL, 95))
+        );
+        assert_eq!(
+            config.update_tree_capacity_level(100),
+            Some((PoolConfigV2::CAPACITY_LEVEL_FULL, 100))
+        );
+        assert_eq!(config.update_tree_capacity_level(100), None);
+
+        config.reset_tree_capacity_level();
+        assert_eq!(
+            config.tree_capacity_level,
+            PoolConfigV2::CAPACITY_LEVEL_NORMAL
+        );
+    }
+
+    #[test]
+    fn test_set_capacity_thresholds_validation() {
+        let mut config = test_config(PoolConfigV2::FEATURE_MASP);
+
+        assert!(config.set_capacity_thresholds(0, 90).is_err());
+        assert!(config.set_capacity_thresholds(90, 90).is_err());
+        assert!(config.set_capacity_thresholds(50, 100).is_err());
+
+        config.set_capacity_thresholds(60, 90).unwrap();
+        assert_eq!(config.capacity_thresholds(), (60, 90));
+        assert_eq!(
+            config.update_tree_capacity_level(60),
+            Some((PoolConfigV2::CAPACITY_LEVEL_WARNING, 60))
+        );
+    }
+
+    #[test]
+    fn test_event_sequence_is_monotonic() {
+        let mut config = test_config(PoolConfigV2::FEATURE_MASP);
+        assert_eq!(config.next_event_sequence().unwrap(), 1);
+        assert_eq!(config.next_event_sequence().unwrap(), 2);
+
+        config.event_sequence = u64::MAX;
+        assert!(config.next_event_sequence().is_err());
+    }
+
+    #[test]
+    fn test_root_event_sequence_only_when_enabled() {
+        let mut config = test_config(PoolConfigV2::FEATURE_MASP);
+        assert_eq!(config.next_root_event_sequence().unwrap(), None);
+        assert_eq!(config.root_event_sequence, 0);
+
+        config.emit_root_events = true;
+        assert_eq!(config.next_root_event_sequence().unwrap(), Some(1));
+        assert_eq!(config.next_root_event_sequence().unwrap(), Some(2));
+        // Independent of the deposit/withdraw event sequence
+        assert_eq!(config.event_sequence, 0);
+    }
+
+    #[test]
+    fn test_encrypted_note_size_limit() {
+        let mut config = test_config(PoolConfigV2::FEATURE_MASP);
+
+        // Zeroed field falls back to the default, which fits the note
+        // envelope wallets produce
+        assert_eq!(config.max_encrypted_note_len(), 580);
+        assert!(
+            crate::scanner::NOTE_ENVELOPE_LEN
+                <= PoolConfigV2::DEFAULT_MAX_ENCRYPTED_NOTE_LEN as usize
+        );
+        config.require_encrypted_note_size(None).unwrap();
+        config
+            .require_encrypted_note_size(Some(&[0u8; 580]))
+            .unwrap();
+        assert!(config
+            .require_encrypted_note_size(Some(&[0u8; 581]))
+            .is_err());
+        assert!(config
+            .require_encrypted_note_size(Some(&vec![0u8; 10_000]))
+            .is_err());
+
+        assert!(config.set_max_encrypted_note_len(0).is_err());
+        assert!(config.set_max_encrypted_note_len(1025).is_err());
+        config.set_max_encrypted_note_len(256).unwrap();
+        assert!(config
+            .require_encrypted_note_size(Some(&[0u8; 257]))
+            .is_err());
+        config
+            .require_encrypted_note_size(Some(&[0u8; 256]))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_crank_bounty() {
+        let mut config = test_config(PoolConfigV2::FEATURE_MASP);
+        assert_eq!(config.crank_bounty_for(50), 0);
+
+        config.set_crank_bounty(5_000).unwrap();
+        assert_eq!(config.crank_bounty_for(50), 250_000);
+        assert!(config
+            .set_crank_bounty(PoolConfigV2::MAX_CRANK_BOUNTY_LAMPORTS + 1)
+            .is_err());
+        assert_eq!(config.crank_bounty_lamports, 5_000);
+    }
+
+    #[test]
+    fn test_protocol_fee() {
+        let mut config = test_config(PoolConfigV2::FEATURE_MASP);
+        assert_eq!(config.protocol_fee_for(1_000_000), 0);
+
+        config.set_protocol_fee(30).unwrap();
+        assert_eq!(config.protocol_fee_for(1_000_000), 3_000);
+        // Rounds down, so dust withdrawals pay nothing
+        assert_eq!(config.protocol_fee_for(333), 0);
+        assert_eq!(config.protocol_fee_for(u64::MAX), 55_340_232_221_128_654);
+        assert!(config
+            .set_protocol_fee(PoolConfigV2::MAX_PROTOCOL_FEE_BPS + 1)
+            .is_err());
+        assert_eq!(config.protocol_fee_bps, 30);
+    }
+
+    #[test]
+    fn test_legacy_layout_ends_before_protocol_fee() {
+        let mut config = test_config(PoolConfigV2::FEATURE_MASP);
+        config.protocol_fee_bps = 0xabcd;
+        let data = config.try_to_vec().unwrap();
+        assert_eq!(data.len() + 8, PoolConfigV2::LEN);
+
+        // Fields from `protocol_fee_bps` on start right after a legacy
+        // account, so zero-filling the grown tail gives their defaults
+        let offset = PoolConfigV2::LEGACY_LEN - 8;
+        assert_eq!(data[offset..offset + 2], 0xabcdu16.to_le_bytes());
+        assert!(data[offset + 2..].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_pool_address_accepts_legacy_seeds_for_pool_id_zero() {
+        let program_id = crate::ID;
+        let authority = Pubkey::new_unique();
+        let mut config = test_config(PoolConfigV2::FEATURE_MASP);
+
+        let (current, bump) = PoolConfigV2::find_pda(&program_id, &authority, 0);
+        config.bump = bump;
+        assert!(config.is_pool_address(&program_id, &current, &authority));
+        assert!(!config.is_pool_address(&program_id, &current, &Pubkey::new_unique()));
+
+        let (legacy, bump) = PoolConfigV2::find_legacy_pda(&program_id, &authority);
+        config.bump = bump;
+        assert!(config.is_pool_address(&program_id, &legacy, &authority));
+
+        // Only pool id 0 may sit at the legacy address
+        config.pool_id = 7;
+        assert!(!config.is_pool_address(&program_id, &legacy, &authority));
+        let (current, bump) = PoolConfigV2::find_pda(&program_id, &authority, 7);
+        config.bump = bump;
+        assert!(config.is_pool_address(&program_id, &current, &authority));
+    }
+
+    #[test]
+    fn test_anonymity_stats_required_once_enabled() {
+        let mut config = test_config(PoolConfigV2::FEATURE_MASP);
+        assert!(config.require_anonymity_stats(false).is_ok());
+
+        config.anonymity_stats_enabled = true;
+        assert!(config.require_anonymity_stats(true).is_ok());
+        assert!(config.require_anonymity_stats(false).is_err());
+    }
 }
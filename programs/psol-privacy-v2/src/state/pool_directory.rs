@@ -0,0 +1,125 @@
+//! Pool Directory - Program-wide list of pools for discovery
+//!
+//! Wallets and indexers find pools without scanning program accounts: the
+//! `PoolDirectory` singleton counts listed pools, and listing `i` lives at
+//! the `PoolDirectoryEntry` PDA seeded by `i`, so a client reads the count
+//! and derives entries `0..pool_count`.
+//!
+//! Listing is optional. Anyone may create the directory; a pool's authority
+//! lists it once (`PoolConfigV2::directory_listed`), and anyone may refresh
+//! an entry's asset count from its pool config.
+//!
+//! PDA Seeds:
+//! - directory: `[b"pool_directory"]`
+//! - entry: `[b"pool_directory_entry", index.to_le_bytes()]`
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+
+/// Program-wide directory header
+#[account]
+#[derive(InitSpace)]
+pub struct PoolDirectory {
+    /// Number of listed pools (index of the next entry)
+    pub pool_count: u64,
+
+    /// Last listing timestamp
+    pub updated_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved for future use
+    pub _reserved: [u8; 32],
+}
+
+impl PoolDirectory {
+    pub const SEED_PREFIX: &'static [u8] = b"pool_directory";
+
+    pub const LEN: usize = 8  // discriminator
+        + 8                   // pool_count
+        + 8                   // updated_at
+        + 1                   // bump
+        + 32; // reserved
+
+    pub fn initialize(&mut self, bump: u8, timestamp: i64) {
+        self.pool_count = 0;
+        self.updated_at = timestamp;
+        self.bump = bump;
+        self._reserved = [0u8; 32];
+    }
+
+    /// Take the next entry index
+    pub fn next_index(&mut self, timestamp: i64) -> Result<u64> {
+        let index = self.pool_count;
+        self.pool_count = index
+            .checked_add(1)
+            .ok_or(PrivacyErrorV2::ArithmeticOverflow)?;
+        self.updated_at = timestamp;
+        Ok(index)
+    }
+
+    pub fn find_pda(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX], program_id)
+    }
+}
+
+/// One listed pool
+#[account]
+#[derive(InitSpace)]
+pub struct PoolDirectoryEntry {
+    /// Position of the entry in the directory
+    pub index: u64,
+
+    /// Listed pool configuration
+    pub pool: Pubkey,
+
+    /// Pool authority when listed
+    pub authority: Pubkey,
+
+    /// Pool's Merkle tree
+    pub merkle_tree: Pubkey,
+
+    /// Pool's tree depth
+    pub tree_depth: u8,
+
+    /// Registered assets, as of `updated_at`
+    pub asset_count: u16,
+
+    /// Pool creation timestamp
+    pub created_at: i64,
+
+    /// Listing timestamp
+    pub listed_at: i64,
+
+    /// Last asset count refresh
+    pub updated_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved for future use
+    pub _reserved: [u8; 32],
+}
+
+impl PoolDirectoryEntry {
+    pub const SEED_PREFIX: &'static [u8] = b"pool_directory_entry";
+
+    pub const LEN: usize = 8  // discriminator
+        + 8                   // index
+        + 32                  // pool
+        + 32                  // authority
+        + 32                  // merkle_tree
+        + 1                   // tree_depth
+        + 2                   // asset_count
+        + 8                   // created_at
+        + 8                   // listed_at
+        + 8                   // updated_at
+        + 1                   // bump
+        + 32; // reserved
+
+    pub fn find_pda(program_id: &Pubkey, index: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, &index.to_le_bytes()], program_id)
+    }
+}
@@ -0,0 +1,116 @@
+//! Proof Statistics - Per-proof-type verification counters
+//!
+//! Separate PDA to avoid PoolConfig migration. Handlers take it as an
+//! optional account, so existing clients keep working without it.
+//!
+//! Solana transactions are atomic: a rejected proof reverts every state
+//! change made by the instruction, including a counter increment. Failed
+//! verifications are therefore surfaced through `ProofVerificationFailed`
+//! events (kept in the logs of failed transactions) and aggregated by
+//! indexers, while successful verifications are counted here on-chain.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::ProofType;
+
+/// Number of proof types tracked (ProofType discriminants 0..=5)
+pub const PROOF_TYPE_COUNT: usize = 6;
+
+const _: () = assert!(ProofType::WithdrawV2 as usize == PROOF_TYPE_COUNT - 1);
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProofStats {
+    /// Parent pool configuration
+    pub pool: Pubkey,
+
+    /// Successful verifications, indexed by `ProofType as usize`
+    pub verified: [u64; PROOF_TYPE_COUNT],
+
+    /// Timestamp of the last successful verification per proof type
+    pub last_verified_at: [i64; PROOF_TYPE_COUNT],
+
+    /// Timestamp the counters started at
+    pub since: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved for future use
+    pub _reserved: [u8; 32],
+}
+
+impl ProofStats {
+    /// Account size: 8 + 32 + 48 + 48 + 8 + 1 + 32 = 177 bytes
+    pub const LEN: usize = 8 + 32 + (8 * PROOF_TYPE_COUNT) + (8 * PROOF_TYPE_COUNT) + 8 + 1 + 32;
+
+    /// Seed prefix for PDA derivation
+    pub const SEED_PREFIX: &'static [u8] = b"proof_stats";
+
+    pub fn initialize(&mut self, pool: Pubkey, bump: u8, timestamp: i64) {
+        self.pool = pool;
+        self.verified = [0u64; PROOF_TYPE_COUNT];
+        self.last_verified_at = [0i64; PROOF_TYPE_COUNT];
+        self.since = timestamp;
+        self.bump = bump;
+        self._reserved = [0u8; 32];
+    }
+
+    pub fn record_verified(&mut self, proof_type: ProofType, timestamp: i64) -> Result<()> {
+        let idx = proof_type as usize;
+        self.verified[idx] = self.verified[idx]
+            .checked_add(1)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        self.last_verified_at[idx] = timestamp;
+        Ok(())
+    }
+
+    pub fn verified_count(&self, proof_type: ProofType) -> u64 {
+        self.verified[proof_type as usize]
+    }
+
+    pub fn total_verified(&self) -> u64 {
+        self.verified
+            .iter()
+            .fold(0u64, |acc, v| acc.saturating_add(*v))
+    }
+}
+
+impl ProofStats {
+    pub fn find_pda(program_id: &Pubkey, pool: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, pool.as_ref()], program_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_verified_per_type() {
+        let mut stats = ProofStats {
+            pool: Pubkey::default(),
+            verified: [0u64; PROOF_TYPE_COUNT],
+            last_verified_at: [0i64; PROOF_TYPE_COUNT],
+            since: 0,
+            bump: 0,
+            _reserved: [0u8; 32],
+        };
+
+        stats.record_verified(ProofType::Deposit, 10).unwrap();
+        stats.record_verified(ProofType::Deposit, 11).unwrap();
+        stats.record_verified(ProofType::WithdrawV2, 12).unwrap();
+
+        assert_eq!(stats.verified_count(ProofType::Deposit), 2);
+        assert_eq!(stats.verified_count(ProofType::Withdraw), 0);
+        assert_eq!(stats.verified_count(ProofType::WithdrawV2), 1);
+        assert_eq!(stats.last_verified_at[ProofType::Deposit as usize], 11);
+        assert_eq!(stats.total_verified(), 3);
+    }
+
+    #[test]
+    fn test_proof_type_count_covers_all_types() {
+        assert!((ProofType::WithdrawV2 as usize) < PROOF_TYPE_COUNT);
+    }
+}
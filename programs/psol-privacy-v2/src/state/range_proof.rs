@@ -0,0 +1,60 @@
+//! Range Proof Parameters - reserved layout for per-note range proofs
+//!
+//! No range proof scheme is live yet (see `crypto::range_check`), so no
+//! instruction creates this account. The layout is fixed now so that the
+//! first scheme only has to fill it in: a commitment to the generators,
+//! the proven bit width, and an opaque parameter area sized for
+//! Bulletproof-style setups.
+//!
+//! PDA Seeds: `[b"range_proof_params", pool.key().as_ref()]`
+
+use anchor_lang::prelude::*;
+
+/// Bytes reserved for scheme-specific parameters
+pub const RANGE_PROOF_PARAMS_LEN: usize = 512;
+
+#[account]
+#[derive(InitSpace)]
+pub struct RangeProofParams {
+    /// Parent pool configuration
+    pub pool: Pubkey,
+
+    /// Scheme these parameters belong to (`RangeProofScheme` as u8)
+    pub scheme: u8,
+
+    /// Bit width amounts are proven to fit in
+    pub bit_width: u8,
+
+    /// Hash of the scheme's generators / public setup
+    pub generators_hash: [u8; 32],
+
+    /// Scheme-specific parameters
+    pub params: [u8; RANGE_PROOF_PARAMS_LEN],
+
+    /// Last update timestamp
+    pub updated_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved for future use
+    pub _reserved: [u8; 32],
+}
+
+impl RangeProofParams {
+    pub const SEED_PREFIX: &'static [u8] = b"range_proof_params";
+
+    pub const LEN: usize = 8 // discriminator
+        + 32                     // pool
+        + 1                      // scheme
+        + 1                      // bit_width
+        + 32                     // generators_hash
+        + RANGE_PROOF_PARAMS_LEN // params
+        + 8                      // updated_at
+        + 1                      // bump
+        + 32; // reserved
+
+    pub fn find_pda(program_id: &Pubkey, pool: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, pool.as_ref()], program_id)
+    }
+}
@@ -12,6 +12,27 @@
 //! 2. Relayer operators register via register_relayer
 //! 3. Users can query active relayers and their fees
 //! 4. Withdrawals/transfers validate relayer is registered and active
+//!
+//! # Staking
+//! Once the authority sets the registry's stake mint, operators lock tokens
+//! of it in a per-node stake vault (`stake_relayer`). Unstaking starts an
+//! unbonding period of `RELAYER_UNBONDING_SECONDS`; the tokens stay in the
+//! vault until it has passed (`withdraw_relayer_stake`), so an operator
+//! cannot pull its stake out ahead of a slash.
+//!
+//! The authority slashes a misbehaving relayer (`slash_relayer`): a share
+//! of its held stake, bonded first and then unbonding, moves to the pool
+//! treasury of the stake mint's asset, and its reputation drops.
+//!
+//! # Reputation
+//! A node starts at `RELAYER_REPUTATION_NEUTRAL` out of
+//! `RELAYER_REPUTATION_MAX`. Every `RELAYER_REPUTATION_BOOST_INTERVAL`
+//! relayed withdrawals or join-splits raise it by a point; each failure the
+//! authority reports (`report_relayer_failure`) lowers it by
+//! `RELAYER_REPUTATION_FAILURE_PENALTY`. A score above neutral decays a
+//! point per `RELAYER_REPUTATION_DECAY_SECONDS` without a reputation
+//! change, so it has to be kept up by serving; a score below neutral does
+//! not recover by idling.
 
 use crate::error::PrivacyErrorV2;
 use anchor_lang::prelude::*;
@@ -19,10 +40,80 @@ use anchor_lang::prelude::*;
 /// Maximum metadata URI length
 pub const MAX_RELAYER_METADATA_URI_LEN: usize = 200;
 
+/// Domain separator for operator-signed relayer updates
+pub const RELAYER_UPDATE_DOMAIN: &[u8] = b"psol-v2:relayer-update";
+
+/// Number of assets a relayer can advertise in `supported_assets`
+pub const MAX_ADVERTISED_ASSETS: u16 = 128;
+
+/// Highest ISO 3166-1 numeric region code
+pub const MAX_REGION_CODE: u16 = 999;
+
+/// Delay between unstaking and withdrawing the stake (7 days)
+pub const RELAYER_UNBONDING_SECONDS: i64 = 7 * 24 * 60 * 60;
+
+/// Highest reputation score
+pub const RELAYER_REPUTATION_MAX: u8 = 100;
+
+/// Reputation a new relayer starts at
+pub const RELAYER_REPUTATION_NEUTRAL: u8 = 50;
+
+/// Relayed transactions per reputation point gained
+pub const RELAYER_REPUTATION_BOOST_INTERVAL: u64 = 10;
+
+/// Reputation lost per reported failure
+pub const RELAYER_REPUTATION_FAILURE_PENALTY: u8 = 5;
+
+/// Idle time per point a score above neutral decays (1 day)
+pub const RELAYER_REPUTATION_DECAY_SECONDS: i64 = 24 * 60 * 60;
+
+/// Structured relayer metadata wallets can filter on without fetching
+/// `metadata_uri`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RelayerMetadata {
+    /// Keccak256 of the relayer's https endpoint URL
+    pub endpoint_hash: [u8; 32],
+
+    /// Public key wallets encrypt notes to
+    pub note_encryption_pubkey: [u8; 32],
+
+    /// Bit i set = relayer serves the asset with `AssetVault::asset_index == i`
+    pub supported_assets: u128,
+
+    /// ISO 3166-1 numeric region code (0 = unspecified)
+    pub region_code: u16,
+}
+
+impl RelayerMetadata {
+    pub const LEN: usize = 32  // endpoint_hash
+        + 32                   // note_encryption_pubkey
+        + 16                   // supported_assets
+        + 2; // region_code
+
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.region_code <= MAX_REGION_CODE,
+            PrivacyErrorV2::InvalidMetadata
+        );
+        Ok(())
+    }
+
+    /// Whether the asset at `asset_index` is advertised
+    pub fn supports_asset(&self, asset_index: u16) -> bool {
+        asset_index < MAX_ADVERTISED_ASSETS && self.supported_assets & (1u128 << asset_index) != 0
+    }
+
+    /// Whether the relayer's endpoint URL matches the registered hash
+    pub fn matches_endpoint(&self, endpoint_url: &str) -> bool {
+        crate::crypto::keccak256(endpoint_url.as_bytes()) == self.endpoint_hash
+    }
+}
+
 /// Relayer Registry - global configuration for all relayers
 ///
 /// PDA Seeds: `[b"relayer_registry", pool.key().as_ref()]`
 #[account]
+#[derive(InitSpace)]
 pub struct RelayerRegistry {
     /// Reference to parent pool
     pub pool: Pubkey,
@@ -63,8 +154,27 @@ pub struct RelayerRegistry {
     /// Whether new registrations are allowed
     pub registrations_open: bool,
 
+    /// Per-withdrawal gas rebate cap in lamports (0 = rebates disabled)
+    pub max_gas_rebate_lamports: u64,
+
+    /// Total gas rebates paid out to relayers (lamports)
+    pub total_rebates_claimed: u64,
+
+    /// Slots a closed relayer's operator must wait before registering again
+    pub reregistration_cooldown_slots: u64,
+
+    /// Mint relayers stake (default = staking not set up)
+    pub stake_mint: Pubkey,
+
+    /// Tokens held across all stake vaults, unbonding included
+    pub total_staked: u64,
+
+    /// Most of a relayer's declared priority fee counted toward a gas
+    /// rebate, in lamports (0 = only unreimbursed rent is rebated)
+    pub max_priority_fee_hint: u64,
+
     /// Reserved for future use
-    pub _reserved: [u8; 32],
+    pub _reserved: [u8; 8],
 }
 
 impl RelayerRegistry {
@@ -82,12 +192,29 @@ impl RelayerRegistry {
         + 8                   // last_updated_at
         + 1                   // bump
         + 1                   // registrations_open
-        + 32; // reserved
+        + 8                   // max_gas_rebate_lamports
+        + 8                   // total_rebates_claimed
+        + 8                   // reregistration_cooldown_slots
+        + 32                  // stake_mint
+        + 8                   // total_staked
+        + 8                   // max_priority_fee_hint
+        + 8; // reserved
+
+    /// Size of registries created before relayer staking and the priority
+    /// fee cap, when `reregistration_cooldown_slots` was followed only by an
+    /// 8-byte reserve; grown to `LEN` by migrate_relayer_registry
+    pub const LEGACY_LEN: usize = Self::LEN
+        - 32  // stake_mint
+        - 8   // total_staked
+        - 8; // max_priority_fee_hint
 
     /// Default fee bounds
     pub const DEFAULT_MIN_FEE_BPS: u16 = 10; // 0.1%
     pub const DEFAULT_MAX_FEE_BPS: u16 = 500; // 5%
 
+    /// Default re-registration cooldown (~1 day at 400ms slots)
+    pub const DEFAULT_REREGISTRATION_COOLDOWN_SLOTS: u64 = 216_000;
+
     /// Initialize the registry
     pub fn initialize(&mut self, pool: Pubkey, bump: u8, timestamp: i64) {
         self.pool = pool;
@@ -103,7 +230,13 @@ impl RelayerRegistry {
         self.last_updated_at = timestamp;
         self.bump = bump;
         self.registrations_open = true;
-        self._reserved = [0u8; 32];
+        self.max_gas_rebate_lamports = 0;
+        self.total_rebates_claimed = 0;
+        self.reregistration_cooldown_slots = Self::DEFAULT_REREGISTRATION_COOLDOWN_SLOTS;
+        self.stake_mint = Pubkey::default();
+        self.total_staked = 0;
+        self.max_priority_fee_hint = 0;
+        self._reserved = [0u8; 8];
     }
 
     /// Configure registry parameters
@@ -197,6 +330,85 @@ impl RelayerRegistry {
         Ok(())
     }
 
+    /// Set the per-withdrawal gas rebate cap and the priority fee counted
+    /// toward it
+    pub fn set_gas_rebate_cap(
+        &mut self,
+        max_lamports: u64,
+        max_priority_fee_hint: u64,
+        timestamp: i64,
+    ) {
+        self.max_gas_rebate_lamports = max_lamports;
+        self.max_priority_fee_hint = max_priority_fee_hint;
+        self.last_updated_at = timestamp;
+    }
+
+    /// Record a gas rebate paid out to a relayer
+    pub fn record_rebate_claim(&mut self, amount: u64, timestamp: i64) -> Result<()> {
+        self.total_rebates_claimed = self
+            .total_rebates_claimed
+            .checked_add(amount)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        self.last_updated_at = timestamp;
+        Ok(())
+    }
+
+    /// Set the cooldown between closing a relayer and re-registering it
+    pub fn set_reregistration_cooldown(&mut self, slots: u64, timestamp: i64) {
+        self.reregistration_cooldown_slots = slots;
+        self.last_updated_at = timestamp;
+    }
+
+    /// Take over `source`'s fee bounds and registration policy
+    /// (`clone_pool_config`); relayers and totals are not carried over
+    pub fn inherit_settings(&mut self, source: &RelayerRegistry) {
+        self.min_fee_bps = source.min_fee_bps;
+        self.max_fee_bps = source.max_fee_bps;
+        self.require_stake = source.require_stake;
+        self.min_stake_amount = source.min_stake_amount;
+        self.registrations_open = source.registrations_open;
+        self.max_gas_rebate_lamports = source.max_gas_rebate_lamports;
+        self.max_priority_fee_hint = source.max_priority_fee_hint;
+        self.reregistration_cooldown_slots = source.reregistration_cooldown_slots;
+        self.stake_mint = source.stake_mint;
+    }
+
+    /// Set the mint relayers stake; only while no stake is held
+    pub fn set_stake_mint(&mut self, stake_mint: Pubkey, timestamp: i64) -> Result<()> {
+        require!(
+            self.total_staked == 0,
+            PrivacyErrorV2::RelayerStakeOutstanding
+        );
+        self.stake_mint = stake_mint;
+        self.last_updated_at = timestamp;
+        Ok(())
+    }
+
+    /// Whether the authority has set a stake mint
+    pub fn has_stake_mint(&self) -> bool {
+        self.stake_mint != Pubkey::default()
+    }
+
+    /// Record tokens moved into a stake vault
+    pub fn record_stake(&mut self, amount: u64, timestamp: i64) -> Result<()> {
+        self.total_staked = self
+            .total_staked
+            .checked_add(amount)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        self.last_updated_at = timestamp;
+        Ok(())
+    }
+
+    /// Record tokens leaving a stake vault
+    pub fn release_stake(&mut self, amount: u64, timestamp: i64) -> Result<()> {
+        self.total_staked = self
+            .total_staked
+            .checked_sub(amount)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        self.last_updated_at = timestamp;
+        Ok(())
+    }
+
     /// Set registrations open/closed
     pub fn set_registrations_open(&mut self, open: bool, timestamp: i64) {
         self.registrations_open = open;
@@ -245,17 +457,45 @@ pub struct RelayerNode {
     /// Last activity timestamp
     pub last_active_at: i64,
 
-    /// Metadata URI (endpoint info, etc.)
+    /// Metadata URI (extended, untrusted info)
     pub metadata_uri: String,
 
     /// PDA bump seed
     pub bump: u8,
 
-    /// Reputation score (0-100, for future use)
+    /// Reputation score (0-100, see the module docs)
     pub reputation_score: u8,
 
+    /// Structured metadata (endpoint, encryption key, assets, region)
+    pub metadata: RelayerMetadata,
+
+    /// Nonce the next operator-signed update must carry
+    pub update_nonce: u64,
+
+    /// Position in the registry's relayer index
+    pub index_position: u32,
+
+    /// Operator is winding the node down: only `drain_allowance` more
+    /// withdrawals are accepted through it
+    pub draining: bool,
+
+    /// Withdrawals still accepted while draining (jobs already queued)
+    pub drain_allowance: u16,
+
+    /// Stake waiting out the unbonding period (still in the stake vault)
+    pub unbonding_amount: u64,
+
+    /// When `unbonding_amount` may be withdrawn
+    pub unbonding_release_at: i64,
+
+    /// Failures reported against this relayer
+    pub failures_reported: u32,
+
+    /// When `reputation_score` last changed (decay runs from here)
+    pub reputation_updated_at: i64,
+
     /// Reserved for future use
-    pub _reserved: [u8; 16],
+    pub _reserved: [u8; 1],
 }
 
 impl RelayerNode {
@@ -273,18 +513,80 @@ impl RelayerNode {
             + 4 + metadata_uri_len  // metadata_uri
             + 1                     // bump
             + 1                     // reputation_score
-            + 16 // reserved
+            + RelayerMetadata::LEN  // metadata
+            + 8                     // update_nonce
+            + 4                     // index_position
+            + 1                     // draining
+            + 2                     // drain_allowance
+            + 8                     // unbonding_amount
+            + 8                     // unbonding_release_at
+            + 4                     // failures_reported
+            + 8                     // reputation_updated_at
+            + 1 // reserved
     }
 
     pub const DEFAULT_SPACE: usize = Self::space(MAX_RELAYER_METADATA_URI_LEN);
 
+    /// Size of nodes registered before the fields that follow
+    /// `reputation_score`, when it was followed only by a 16-byte reserve;
+    /// re-laid out at `DEFAULT_SPACE` by migrate_relayer_node
+    pub const LEGACY_SPACE: usize = Self::DEFAULT_SPACE - Self::LEGACY_GROWTH;
+
+    /// Bytes the fields after `reputation_score` take beyond the legacy
+    /// reserve
+    pub const LEGACY_GROWTH: usize = RelayerMetadata::LEN // metadata
+        + 8                     // update_nonce
+        + 4                     // index_position
+        + 1                     // draining
+        + 2                     // drain_allowance
+        + 8                     // unbonding_amount
+        + 8                     // unbonding_release_at
+        + 4                     // failures_reported
+        + 8                     // reputation_updated_at
+        + 1                     // reserved
+        - 16; // legacy reserved
+
+    /// Node in the current layout carrying `legacy`'s fields; the fields
+    /// added since start out as at registration, with the reputation
+    /// unchanged since then, and `index_position` is where the node is
+    /// appended to the relayer index
+    pub fn from_legacy(legacy: LegacyRelayerNode, index_position: u32) -> Self {
+        Self {
+            registry: legacy.registry,
+            operator: legacy.operator,
+            fee_bps: legacy.fee_bps,
+            is_active: legacy.is_active,
+            stake_amount: legacy.stake_amount,
+            transactions_processed: legacy.transactions_processed,
+            fees_earned: legacy.fees_earned,
+            registered_at: legacy.registered_at,
+            last_active_at: legacy.last_active_at,
+            metadata_uri: legacy.metadata_uri,
+            bump: legacy.bump,
+            reputation_score: legacy.reputation_score,
+            metadata: RelayerMetadata::default(),
+            update_nonce: 0,
+            index_position,
+            draining: false,
+            drain_allowance: 0,
+            unbonding_amount: 0,
+            unbonding_release_at: 0,
+            failures_reported: 0,
+            reputation_updated_at: legacy.registered_at,
+            _reserved: [0u8; 1],
+        }
+    }
+
     /// Initialize a new relayer node
+    #[allow(clippy::too_many_arguments)]
     pub fn initialize(
         &mut self,
         registry: Pubkey,
         operator: Pubkey,
         fee_bps: u16,
+        metadata: RelayerMetadata,
         metadata_uri: String,
+        index_position: u32,
         bump: u8,
         timestamp: i64,
     ) {
@@ -299,14 +601,24 @@ impl RelayerNode {
         self.last_active_at = timestamp;
         self.metadata_uri = metadata_uri;
         self.bump = bump;
-        self.reputation_score = 50; // Start at neutral
-        self._reserved = [0u8; 16];
+        self.reputation_score = RELAYER_REPUTATION_NEUTRAL;
+        self.metadata = metadata;
+        self.update_nonce = 0;
+        self.index_position = index_position;
+        self.draining = false;
+        self.drain_allowance = 0;
+        self.unbonding_amount = 0;
+        self.unbonding_release_at = 0;
+        self.failures_reported = 0;
+        self.reputation_updated_at = timestamp;
+        self._reserved = [0u8; 1];
     }
 
     /// Update relayer configuration
     pub fn update(
         &mut self,
         fee_bps: Option<u16>,
+        metadata: Option<RelayerMetadata>,
         metadata_uri: Option<String>,
         is_active: Option<bool>,
         timestamp: i64,
@@ -314,6 +626,10 @@ impl RelayerNode {
         if let Some(fee) = fee_bps {
             self.fee_bps = fee;
         }
+        if let Some(metadata) = metadata {
+            metadata.validate()?;
+            self.metadata = metadata;
+        }
         if let Some(uri) = metadata_uri {
             require!(
                 uri.len() <= MAX_RELAYER_METADATA_URI_LEN,
@@ -328,7 +644,53 @@ impl RelayerNode {
         Ok(())
     }
 
-    /// Record a completed transaction
+    /// Message the operator signs to authorize an update submitted by
+    /// another key (e.g. a hot key holding no operator authority)
+    ///
+    /// Layout: domain || relayer_node || fee_bps (Option, borsh) ||
+    /// is_active (Option, borsh) || nonce (u64 LE) || expires_at (i64 LE)
+    pub fn signed_update_message(
+        relayer_node: &Pubkey,
+        fee_bps: Option<u16>,
+        is_active: Option<bool>,
+        nonce: u64,
+        expires_at: i64,
+    ) -> Vec<u8> {
+        let mut message = Vec::with_capacity(RELAYER_UPDATE_DOMAIN.len() + 32 + 3 + 2 + 16);
+        message.extend_from_slice(RELAYER_UPDATE_DOMAIN);
+        message.extend_from_slice(relayer_node.as_ref());
+        match fee_bps {
+            Some(fee) => {
+                message.push(1);
+                message.extend_from_slice(&fee.to_le_bytes());
+            }
+            None => message.push(0),
+        }
+        match is_active {
+            Some(active) => message.extend_from_slice(&[1, active as u8]),
+            None => message.push(0),
+        }
+        message.extend_from_slice(&nonce.to_le_bytes());
+        message.extend_from_slice(&expires_at.to_le_bytes());
+        message
+    }
+
+    /// Consume `nonce` for a signed update; each nonce is usable once and
+    /// in order
+    pub fn consume_update_nonce(&mut self, nonce: u64) -> Result<()> {
+        require!(
+            nonce == self.update_nonce,
+            PrivacyErrorV2::RelayerUpdateNonceMismatch
+        );
+        self.update_nonce = self
+            .update_nonce
+            .checked_add(1)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        Ok(())
+    }
+
+    /// Record a completed transaction; every
+    /// `RELAYER_REPUTATION_BOOST_INTERVAL`th raises the reputation a point
     pub fn record_transaction(&mut self, fee_amount: u64, timestamp: i64) -> Result<()> {
         self.transactions_processed = self
             .transactions_processed
@@ -340,10 +702,79 @@ impl RelayerNode {
             .checked_add(fee_amount)
             .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
 
+        if self
+            .transactions_processed
+            .is_multiple_of(RELAYER_REPUTATION_BOOST_INTERVAL)
+        {
+            let score = self.current_reputation(timestamp);
+            self.set_reputation(
+                score.saturating_add(1).min(RELAYER_REPUTATION_MAX),
+                timestamp,
+            );
+        }
+
         self.last_active_at = timestamp;
         Ok(())
     }
 
+    /// Record a reported failure and lower the reputation by
+    /// `RELAYER_REPUTATION_FAILURE_PENALTY`
+    pub fn record_failure(&mut self, timestamp: i64) -> Result<()> {
+        self.failures_reported = self
+            .failures_reported
+            .checked_add(1)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        self.lower_reputation(RELAYER_REPUTATION_FAILURE_PENALTY, timestamp);
+        Ok(())
+    }
+
+    /// Reputation at `timestamp`: a score above neutral loses a point per
+    /// `RELAYER_REPUTATION_DECAY_SECONDS` since it last changed
+    pub fn current_reputation(&self, timestamp: i64) -> u8 {
+        if self.reputation_score <= RELAYER_REPUTATION_NEUTRAL {
+            return self.reputation_score;
+        }
+        let since = self.reputation_updated_at.max(self.registered_at);
+        let periods = timestamp.saturating_sub(since).max(0) / RELAYER_REPUTATION_DECAY_SECONDS;
+        let excess = self.reputation_score - RELAYER_REPUTATION_NEUTRAL;
+        self.reputation_score - excess.min(periods.min(i64::from(u8::MAX)) as u8)
+    }
+
+    /// Lower the (decayed) reputation by `penalty`, bottoming out at zero
+    pub fn lower_reputation(&mut self, penalty: u8, timestamp: i64) {
+        let score = self.current_reputation(timestamp);
+        self.set_reputation(score.saturating_sub(penalty), timestamp);
+    }
+
+    fn set_reputation(&mut self, score: u8, timestamp: i64) {
+        self.reputation_score = score;
+        self.reputation_updated_at = timestamp;
+    }
+
+    /// Start draining with `allowance` withdrawals left for queued jobs,
+    /// or stop draining
+    pub fn set_draining(&mut self, draining: bool, allowance: u16, timestamp: i64) {
+        self.draining = draining;
+        self.drain_allowance = if draining { allowance } else { 0 };
+        self.last_active_at = timestamp;
+    }
+
+    /// Whether a new withdrawal may go through this node
+    pub fn accepts_withdrawals(&self) -> bool {
+        self.is_active && (!self.draining || self.drain_allowance > 0)
+    }
+
+    /// Count a withdrawal against the drain allowance
+    pub fn consume_drain_allowance(&mut self) -> Result<()> {
+        if self.draining {
+            self.drain_allowance = self
+                .drain_allowance
+                .checked_sub(1)
+                .ok_or(error!(PrivacyErrorV2::RelayerDraining))?;
+        }
+        Ok(())
+    }
+
     /// Deactivate the relayer
     pub fn deactivate(&mut self, timestamp: i64) {
         self.is_active = false;
@@ -365,6 +796,72 @@ impl RelayerNode {
         Ok(())
     }
 
+    /// Move `amount` of stake into unbonding; unstaking again while
+    /// unbonding adds to it and restarts the period
+    pub fn begin_unbonding(&mut self, amount: u64, timestamp: i64) -> Result<()> {
+        require!(amount > 0, PrivacyErrorV2::InvalidAmount);
+        self.stake_amount = self
+            .stake_amount
+            .checked_sub(amount)
+            .ok_or(error!(PrivacyErrorV2::InsufficientStake))?;
+        self.unbonding_amount = self
+            .unbonding_amount
+            .checked_add(amount)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        self.unbonding_release_at = timestamp
+            .checked_add(RELAYER_UNBONDING_SECONDS)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        self.last_active_at = timestamp;
+        Ok(())
+    }
+
+    /// Take the unbonded stake once the period has passed
+    pub fn take_unbonded(&mut self, timestamp: i64) -> Result<u64> {
+        require!(
+            self.unbonding_amount > 0,
+            PrivacyErrorV2::NoRelayerStakeUnbonding
+        );
+        require!(
+            timestamp >= self.unbonding_release_at,
+            PrivacyErrorV2::RelayerStakeUnbonding
+        );
+        let amount = self.unbonding_amount;
+        self.unbonding_amount = 0;
+        self.unbonding_release_at = 0;
+        self.last_active_at = timestamp;
+        Ok(amount)
+    }
+
+    /// Take `slash_bps` of the held stake (bonded first, then unbonding)
+    /// and lower the reputation by `reputation_penalty`; returns the
+    /// amount taken
+    pub fn slash(&mut self, slash_bps: u16, reputation_penalty: u8, timestamp: i64) -> Result<u64> {
+        require!(
+            slash_bps > 0 && slash_bps <= 10_000,
+            PrivacyErrorV2::InvalidSlashFraction
+        );
+        let held = self
+            .stake_amount
+            .checked_add(self.unbonding_amount)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        let amount = (u128::from(held) * u128::from(slash_bps) / 10_000) as u64;
+
+        let from_stake = amount.min(self.stake_amount);
+        self.stake_amount -= from_stake;
+        self.unbonding_amount -= amount - from_stake;
+        if self.unbonding_amount == 0 {
+            self.unbonding_release_at = 0;
+        }
+        self.lower_reputation(reputation_penalty, timestamp);
+        self.last_active_at = timestamp;
+        Ok(amount)
+    }
+
+    /// Whether the node's stake vault still holds tokens
+    pub fn holds_stake(&self) -> bool {
+        self.stake_amount > 0 || self.unbonding_amount > 0
+    }
+
     /// Calculate fee for a given amount
     pub fn calculate_fee(&self, amount: u64) -> Result<u64> {
         amount
@@ -374,6 +871,25 @@ impl RelayerNode {
     }
 }
 
+/// Layout of relayer nodes registered before the structured metadata,
+/// read by migrate_relayer_node
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LegacyRelayerNode {
+    pub registry: Pubkey,
+    pub operator: Pubkey,
+    pub fee_bps: u16,
+    pub is_active: bool,
+    pub stake_amount: u64,
+    pub transactions_processed: u64,
+    pub fees_earned: u64,
+    pub registered_at: i64,
+    pub last_active_at: i64,
+    pub metadata_uri: String,
+    pub bump: u8,
+    pub reputation_score: u8,
+    pub _reserved: [u8; 16],
+}
+
 /// PDA seeds for RelayerNode
 impl RelayerNode {
     pub const SEED_PREFIX: &'static [u8] = b"relayer";
@@ -385,6 +901,25 @@ impl RelayerNode {
         )
     }
 
+    /// Seed prefix of a node's stake vault, a token account of the stake
+    /// mint owned by the node: `[b"relayer_stake", relayer_node, mint]`
+    pub const STAKE_VAULT_SEED_PREFIX: &'static [u8] = b"relayer_stake";
+
+    pub fn find_stake_vault_pda(
+        program_id: &Pubkey,
+        relayer_node: &Pubkey,
+        mint: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                Self::STAKE_VAULT_SEED_PREFIX,
+                relayer_node.as_ref(),
+                mint.as_ref(),
+            ],
+            program_id,
+        )
+    }
+
     /// Validates that this RelayerNode belongs to `expected_registry` and that the passed
     /// account key matches the canonical PDA derivation.
     ///
@@ -446,7 +981,13 @@ mod tests {
             last_updated_at: 0,
             bump: 0,
             registrations_open: true,
-            _reserved: [0u8; 32],
+            max_gas_rebate_lamports: 0,
+            total_rebates_claimed: 0,
+            reregistration_cooldown_slots: 0,
+            stake_mint: Pubkey::default(),
+            total_staked: 0,
+            max_priority_fee_hint: 0,
+            _reserved: [0u8; 8],
         };
 
         assert!(registry.validate_fee(100).is_ok());
@@ -456,6 +997,95 @@ mod tests {
         assert!(registry.validate_fee(1000).is_err()); // Above max
     }
 
+    #[test]
+    fn test_legacy_registry_parses_after_growth() {
+        let mut registry = RelayerRegistry {
+            pool: Pubkey::new_unique(),
+            min_fee_bps: 10,
+            max_fee_bps: 500,
+            require_stake: false,
+            min_stake_amount: 0,
+            relayer_count: 0,
+            active_relayer_count: 0,
+            total_fees_collected: 0,
+            total_transactions: 0,
+            created_at: 0,
+            last_updated_at: 0,
+            bump: 0,
+            registrations_open: true,
+            max_gas_rebate_lamports: 0,
+            total_rebates_claimed: 0,
+            reregistration_cooldown_slots: 0,
+            stake_mint: Pubkey::default(),
+            total_staked: 0,
+            max_priority_fee_hint: 0,
+            _reserved: [0u8; 8],
+        };
+        registry.initialize(registry.pool, 254, 7);
+        let mut data = Vec::new();
+        registry.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), RelayerRegistry::LEN);
+
+        // Legacy layout: the cooldown followed by a zeroed 8-byte reserve
+        data.truncate(RelayerRegistry::LEGACY_LEN);
+        assert!(RelayerRegistry::try_deserialize(&mut &data[..]).is_err());
+
+        data.resize(RelayerRegistry::LEN, 0);
+        let migrated = RelayerRegistry::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(migrated.pool, registry.pool);
+        assert_eq!(
+            migrated.reregistration_cooldown_slots,
+            RelayerRegistry::DEFAULT_REREGISTRATION_COOLDOWN_SLOTS
+        );
+        assert!(!migrated.has_stake_mint());
+        assert_eq!(migrated.total_staked, 0);
+        assert_eq!(migrated.max_priority_fee_hint, 0);
+    }
+
+    #[test]
+    fn test_legacy_node_relaid_out() {
+        let legacy = LegacyRelayerNode {
+            registry: Pubkey::new_unique(),
+            operator: Pubkey::new_unique(),
+            fee_bps: 100,
+            is_active: true,
+            stake_amount: 0,
+            transactions_processed: 42,
+            fees_earned: 4_200,
+            registered_at: 1_000,
+            last_active_at: 2_000,
+            metadata_uri: "u".repeat(MAX_RELAYER_METADATA_URI_LEN),
+            bump: 255,
+            reputation_score: 60,
+            _reserved: [0u8; 16],
+        };
+        let mut data = RelayerNode::DISCRIMINATOR.to_vec();
+        legacy.serialize(&mut data).unwrap();
+        assert_eq!(data.len(), RelayerNode::LEGACY_SPACE);
+        assert!(RelayerNode::try_deserialize(&mut &data[..]).is_err());
+
+        let node = RelayerNode::from_legacy(legacy.clone(), 7);
+        let mut data = Vec::new();
+        node.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), RelayerNode::DEFAULT_SPACE);
+
+        let migrated = RelayerNode::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(migrated.operator, legacy.operator);
+        assert_eq!(migrated.metadata_uri, legacy.metadata_uri);
+        assert_eq!(migrated.fees_earned, 4_200);
+        assert_eq!(migrated.bump, 255);
+        assert_eq!(migrated.metadata, RelayerMetadata::default());
+        assert_eq!(migrated.index_position, 7);
+        assert!(!migrated.holds_stake());
+        assert!(migrated.accepts_withdrawals());
+        // Decay of the above-neutral score runs from registration
+        assert_eq!(migrated.current_reputation(1_000), 60);
+        assert_eq!(
+            migrated.current_reputation(1_000 + RELAYER_REPUTATION_DECAY_SECONDS),
+            59
+        );
+    }
+
     #[test]
     fn test_fee_calculation() {
         let relayer = RelayerNode {
@@ -471,7 +1101,16 @@ mod tests {
             metadata_uri: String::new(),
             bump: 0,
             reputation_score: 50,
-            _reserved: [0u8; 16],
+            metadata: RelayerMetadata::default(),
+            update_nonce: 0,
+            index_position: 0,
+            draining: false,
+            drain_allowance: 0,
+            unbonding_amount: 0,
+            unbonding_release_at: 0,
+            failures_reported: 0,
+            reputation_updated_at: 0,
+            _reserved: [0u8; 1],
         };
 
         let fee = relayer.calculate_fee(10_000).unwrap();
@@ -509,7 +1148,16 @@ mod tests {
             metadata_uri: String::new(),
             bump,
             reputation_score: 50,
-            _reserved: [0u8; 16],
+            metadata: RelayerMetadata::default(),
+            update_nonce: 0,
+            index_position: 0,
+            draining: false,
+            drain_allowance: 0,
+            unbonding_amount: 0,
+            unbonding_release_at: 0,
+            failures_reported: 0,
+            reputation_updated_at: 0,
+            _reserved: [0u8; 1],
         };
 
         assert!(node
@@ -540,7 +1188,16 @@ mod tests {
             metadata_uri: String::new(),
             bump,
             reputation_score: 50,
-            _reserved: [0u8; 16],
+            metadata: RelayerMetadata::default(),
+            update_nonce: 0,
+            index_position: 0,
+            draining: false,
+            drain_allowance: 0,
+            unbonding_amount: 0,
+            unbonding_release_at: 0,
+            failures_reported: 0,
+            reputation_updated_at: 0,
+            _reserved: [0u8; 1],
         };
 
         let err = node
@@ -572,7 +1229,16 @@ mod tests {
             metadata_uri: String::new(),
             bump,
             reputation_score: 50,
-            _reserved: [0u8; 16],
+            metadata: RelayerMetadata::default(),
+            update_nonce: 0,
+            index_position: 0,
+            draining: false,
+            drain_allowance: 0,
+            unbonding_amount: 0,
+            unbonding_release_at: 0,
+            failures_reported: 0,
+            reputation_updated_at: 0,
+            _reserved: [0u8; 1],
         };
 
         let wrong_key = Pubkey::new_unique();
@@ -584,4 +1250,307 @@ mod tests {
         // Message comes from #[msg(...)] on PrivacyErrorV2::InvalidRelayerNodePda
         assert_err_contains(err, "Invalid RelayerNode PDA");
     }
+
+    #[test]
+    fn test_signed_update_nonce_is_single_use() {
+        let mut node = RelayerNode {
+            registry: Pubkey::default(),
+            operator: Pubkey::default(),
+            fee_bps: 100,
+            is_active: true,
+            stake_amount: 0,
+            transactions_processed: 0,
+            fees_earned: 0,
+            registered_at: 0,
+            last_active_at: 0,
+            metadata_uri: String::new(),
+            bump: 0,
+            reputation_score: 50,
+            metadata: RelayerMetadata::default(),
+            update_nonce: 0,
+            index_position: 0,
+            draining: false,
+            drain_allowance: 0,
+            unbonding_amount: 0,
+            unbonding_release_at: 0,
+            failures_reported: 0,
+            reputation_updated_at: 0,
+            _reserved: [0u8; 1],
+        };
+
+        assert!(node.consume_update_nonce(1).is_err());
+        node.consume_update_nonce(0).unwrap();
+        assert!(node.consume_update_nonce(0).is_err()); // replay
+        node.consume_update_nonce(1).unwrap();
+        assert_eq!(node.update_nonce, 2);
+
+        // Every signed field changes the message
+        let key = Pubkey::new_unique();
+        let base = RelayerNode::signed_update_message(&key, Some(50), None, 0, 100);
+        assert_ne!(
+            base,
+            RelayerNode::signed_update_message(&key, Some(51), None, 0, 100)
+        );
+        assert_ne!(
+            base,
+            RelayerNode::signed_update_message(&key, Some(50), Some(false), 0, 100)
+        );
+        assert_ne!(
+            base,
+            RelayerNode::signed_update_message(&key, Some(50), None, 1, 100)
+        );
+        assert_ne!(
+            base,
+            RelayerNode::signed_update_message(&Pubkey::new_unique(), Some(50), None, 0, 100)
+        );
+    }
+
+    #[test]
+    fn test_draining_allowance_counts_down() {
+        let mut node = RelayerNode {
+            registry: Pubkey::default(),
+            operator: Pubkey::default(),
+            fee_bps: 100,
+            is_active: true,
+            stake_amount: 0,
+            transactions_processed: 0,
+            fees_earned: 0,
+            registered_at: 0,
+            last_active_at: 0,
+            metadata_uri: String::new(),
+            bump: 0,
+            reputation_score: 50,
+            metadata: RelayerMetadata::default(),
+            update_nonce: 0,
+            index_position: 0,
+            draining: false,
+            drain_allowance: 0,
+            unbonding_amount: 0,
+            unbonding_release_at: 0,
+            failures_reported: 0,
+            reputation_updated_at: 0,
+            _reserved: [0u8; 1],
+        };
+
+        // Not draining: allowance is never touched
+        node.consume_drain_allowance().unwrap();
+        assert!(node.accepts_withdrawals());
+
+        node.set_draining(true, 2, 10);
+        assert!(node.accepts_withdrawals());
+        node.consume_drain_allowance().unwrap();
+        node.consume_drain_allowance().unwrap();
+        assert!(!node.accepts_withdrawals());
+        assert_err_contains(
+            node.consume_drain_allowance().unwrap_err(),
+            "RelayerDraining",
+        );
+
+        // Clearing the flag resets the allowance and resumes service
+        node.set_draining(false, 5, 20);
+        assert_eq!(node.drain_allowance, 0);
+        assert!(node.accepts_withdrawals());
+
+        node.deactivate(30);
+        assert!(!node.accepts_withdrawals());
+    }
+
+    #[test]
+    fn test_unbonding_releases_after_period() {
+        let mut node = RelayerNode {
+            registry: Pubkey::default(),
+            operator: Pubkey::default(),
+            fee_bps: 100,
+            is_active: true,
+            stake_amount: 0,
+            transactions_processed: 0,
+            fees_earned: 0,
+            registered_at: 0,
+            last_active_at: 0,
+            metadata_uri: String::new(),
+            bump: 0,
+            reputation_score: 50,
+            metadata: RelayerMetadata::default(),
+            update_nonce: 0,
+            index_position: 0,
+            draining: false,
+            drain_allowance: 0,
+            unbonding_amount: 0,
+            unbonding_release_at: 0,
+            failures_reported: 0,
+            reputation_updated_at: 0,
+            _reserved: [0u8; 1],
+        };
+        node.add_stake(1_000).unwrap();
+
+        assert_err_contains(
+            node.begin_unbonding(1_001, 10).unwrap_err(),
+            "InsufficientStake",
+        );
+        node.begin_unbonding(400, 10).unwrap();
+        assert_eq!(node.stake_amount, 600);
+        assert_eq!(node.unbonding_release_at, 10 + RELAYER_UNBONDING_SECONDS);
+
+        // Unstaking more restarts the period for the whole amount
+        node.begin_unbonding(100, 20).unwrap();
+        assert_eq!(node.unbonding_amount, 500);
+        assert_err_contains(
+            node.take_unbonded(10 + RELAYER_UNBONDING_SECONDS)
+                .unwrap_err(),
+            "RelayerStakeUnbonding",
+        );
+        assert_eq!(
+            node.take_unbonded(20 + RELAYER_UNBONDING_SECONDS).unwrap(),
+            500
+        );
+        assert_err_contains(
+            node.take_unbonded(i64::MAX).unwrap_err(),
+            "NoRelayerStakeUnbonding",
+        );
+        assert!(node.holds_stake());
+    }
+
+    #[test]
+    fn test_slash_takes_bonded_stake_first() {
+        let mut node = RelayerNode {
+            registry: Pubkey::default(),
+            operator: Pubkey::default(),
+            fee_bps: 100,
+            is_active: true,
+            stake_amount: 0,
+            transactions_processed: 0,
+            fees_earned: 0,
+            registered_at: 0,
+            last_active_at: 0,
+            metadata_uri: String::new(),
+            bump: 0,
+            reputation_score: 50,
+            metadata: RelayerMetadata::default(),
+            update_nonce: 0,
+            index_position: 0,
+            draining: false,
+            drain_allowance: 0,
+            unbonding_amount: 0,
+            unbonding_release_at: 0,
+            failures_reported: 0,
+            reputation_updated_at: 0,
+            _reserved: [0u8; 1],
+        };
+        node.add_stake(1_000).unwrap();
+        node.begin_unbonding(800, 10).unwrap();
+
+        assert_err_contains(node.slash(0, 0, 20).unwrap_err(), "InvalidSlashFraction");
+        assert_err_contains(
+            node.slash(10_001, 0, 20).unwrap_err(),
+            "InvalidSlashFraction",
+        );
+
+        // 50% of 1_000 held: the 200 bonded, then 300 of the unbonding
+        assert_eq!(node.slash(5_000, 20, 20).unwrap(), 500);
+        assert_eq!(node.stake_amount, 0);
+        assert_eq!(node.unbonding_amount, 500);
+        assert_eq!(node.reputation_score, 30);
+
+        // Reputation bottoms out at zero; a full slash empties the vault
+        assert_eq!(node.slash(10_000, 255, 30).unwrap(), 500);
+        assert!(!node.holds_stake());
+        assert_eq!(node.unbonding_release_at, 0);
+        assert_eq!(node.reputation_score, 0);
+    }
+
+    #[test]
+    fn test_reputation_boost_failure_and_decay() {
+        let mut node = RelayerNode {
+            registry: Pubkey::default(),
+            operator: Pubkey::default(),
+            fee_bps: 100,
+            is_active: true,
+            stake_amount: 0,
+            transactions_processed: 0,
+            fees_earned: 0,
+            registered_at: 0,
+            last_active_at: 0,
+            metadata_uri: String::new(),
+            bump: 0,
+            reputation_score: RELAYER_REPUTATION_NEUTRAL,
+            metadata: RelayerMetadata::default(),
+            update_nonce: 0,
+            index_position: 0,
+            draining: false,
+            drain_allowance: 0,
+            unbonding_amount: 0,
+            unbonding_release_at: 0,
+            failures_reported: 0,
+            reputation_updated_at: 0,
+            _reserved: [0u8; 1],
+        };
+
+        // A point per boost interval of relayed transactions
+        for _ in 0..RELAYER_REPUTATION_BOOST_INTERVAL * 3 - 1 {
+            node.record_transaction(10, 100).unwrap();
+        }
+        assert_eq!(node.reputation_score, RELAYER_REPUTATION_NEUTRAL + 2);
+        node.record_transaction(10, 100).unwrap();
+        assert_eq!(node.reputation_score, RELAYER_REPUTATION_NEUTRAL + 3);
+
+        // Capped at the maximum
+        node.reputation_score = RELAYER_REPUTATION_MAX;
+        for _ in 0..RELAYER_REPUTATION_BOOST_INTERVAL {
+            node.record_transaction(10, 100).unwrap();
+        }
+        assert_eq!(node.reputation_score, RELAYER_REPUTATION_MAX);
+
+        // Idle time decays the excess over neutral, and no further
+        node.reputation_score = RELAYER_REPUTATION_NEUTRAL + 3;
+        assert_eq!(
+            node.current_reputation(100 + 2 * RELAYER_REPUTATION_DECAY_SECONDS),
+            RELAYER_REPUTATION_NEUTRAL + 1
+        );
+        assert_eq!(
+            node.current_reputation(100 + 30 * RELAYER_REPUTATION_DECAY_SECONDS),
+            RELAYER_REPUTATION_NEUTRAL
+        );
+
+        // A failure applies the decay first, then the penalty
+        let at = 100 + 2 * RELAYER_REPUTATION_DECAY_SECONDS;
+        node.record_failure(at).unwrap();
+        assert_eq!(node.failures_reported, 1);
+        assert_eq!(
+            node.reputation_score,
+            RELAYER_REPUTATION_NEUTRAL + 1 - RELAYER_REPUTATION_FAILURE_PENALTY
+        );
+        assert_eq!(node.reputation_updated_at, at);
+
+        // Below neutral nothing is recovered by idling
+        assert_eq!(
+            node.current_reputation(at + 30 * RELAYER_REPUTATION_DECAY_SECONDS),
+            node.reputation_score
+        );
+    }
+
+    #[test]
+    fn test_relayer_metadata() {
+        let endpoint = "https://relayer.example.com";
+        let metadata = RelayerMetadata {
+            endpoint_hash: crate::crypto::keccak256(endpoint.as_bytes()),
+            note_encryption_pubkey: [5u8; 32],
+            supported_assets: (1 << 0) | (1 << 3) | (1 << 127),
+            region_code: 276,
+        };
+        assert!(metadata.validate().is_ok());
+        assert!(metadata.matches_endpoint(endpoint));
+        assert!(!metadata.matches_endpoint("https://evil.example.com"));
+
+        assert!(metadata.supports_asset(0));
+        assert!(metadata.supports_asset(3));
+        assert!(metadata.supports_asset(127));
+        assert!(!metadata.supports_asset(1));
+        assert!(!metadata.supports_asset(128));
+
+        let bad_region = RelayerMetadata {
+            region_code: 1000,
+            ..metadata
+        };
+        assert!(bad_region.validate().is_err());
+    }
 }
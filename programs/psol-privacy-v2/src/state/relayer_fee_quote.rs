@@ -0,0 +1,144 @@
+//! Relayer Fee Quote - pSOL v2
+//!
+//! A registered relayer publishes the fee it charges as a flat amount plus
+//! basis points of the withdrawal, valid up to a slot. Clients read the
+//! quote when generating a proof, and a withdrawal submitted with the
+//! quote attached is refused if its relayer fee is above what the quote
+//! allows or the quote has lapsed.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+
+/// Published fee quote of one relayer node
+///
+/// PDA Seeds: `[b"relayer_fee_quote", relayer_node.key().as_ref()]`
+#[account]
+#[derive(InitSpace)]
+pub struct RelayerFeeQuote {
+    /// Relayer node this quote belongs to
+    pub relayer_node: Pubkey,
+
+    /// Relayer operator (the only one that may update the quote)
+    pub operator: Pubkey,
+
+    /// Flat part of the fee (asset base units)
+    pub flat_fee: u64,
+
+    /// Proportional part of the fee, in basis points of the amount
+    pub fee_bps: u16,
+
+    /// Last slot the quote is valid in
+    pub valid_until_slot: u64,
+
+    /// Last update timestamp
+    pub updated_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl RelayerFeeQuote {
+    pub const LEN: usize = 8  // discriminator
+        + 32                  // relayer_node
+        + 32                  // operator
+        + 8                   // flat_fee
+        + 2                   // fee_bps
+        + 8                   // valid_until_slot
+        + 8                   // updated_at
+        + 1; // bump
+
+    pub fn initialize(&mut self, relayer_node: Pubkey, operator: Pubkey, bump: u8) {
+        self.relayer_node = relayer_node;
+        self.operator = operator;
+        self.bump = bump;
+    }
+
+    /// Replace the quote; it must still be valid at `slot`
+    pub fn set(
+        &mut self,
+        flat_fee: u64,
+        fee_bps: u16,
+        valid_until_slot: u64,
+        slot: u64,
+        timestamp: i64,
+    ) -> Result<()> {
+        require!(valid_until_slot >= slot, PrivacyErrorV2::FeeQuoteExpired);
+        self.flat_fee = flat_fee;
+        self.fee_bps = fee_bps;
+        self.valid_until_slot = valid_until_slot;
+        self.updated_at = timestamp;
+        Ok(())
+    }
+
+    /// Most the quote allows to charge on `amount`
+    pub fn max_fee(&self, amount: u64) -> Result<u64> {
+        amount
+            .checked_mul(self.fee_bps as u64)
+            .map(|v| v / 10_000)
+            .and_then(|v| v.checked_add(self.flat_fee))
+            .ok_or_else(|| error!(PrivacyErrorV2::ArithmeticOverflow))
+    }
+
+    /// Check `relayer_fee` on `amount` is within the quote at `slot`
+    pub fn check_fee(&self, relayer_fee: u64, amount: u64, slot: u64) -> Result<()> {
+        require!(
+            slot <= self.valid_until_slot,
+            PrivacyErrorV2::FeeQuoteExpired
+        );
+        require!(
+            relayer_fee <= self.max_fee(amount)?,
+            PrivacyErrorV2::RelayerFeeAboveQuote
+        );
+        Ok(())
+    }
+}
+
+/// PDA seeds for RelayerFeeQuote
+impl RelayerFeeQuote {
+    pub const SEED_PREFIX: &'static [u8] = b"relayer_fee_quote";
+
+    pub fn find_pda(program_id: &Pubkey, relayer_node: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, relayer_node.as_ref()], program_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote() -> RelayerFeeQuote {
+        let mut q = RelayerFeeQuote {
+            relayer_node: Pubkey::default(),
+            operator: Pubkey::default(),
+            flat_fee: 0,
+            fee_bps: 0,
+            valid_until_slot: 0,
+            updated_at: 0,
+            bump: 0,
+        };
+        q.initialize(Pubkey::new_unique(), Pubkey::new_unique(), 255);
+        q
+    }
+
+    #[test]
+    fn test_fee_within_quote() {
+        let mut q = quote();
+        q.set(5_000, 50, 1_000, 900, 0).unwrap();
+
+        // 5_000 flat + 0.5% of 1_000_000
+        assert_eq!(q.max_fee(1_000_000).unwrap(), 10_000);
+        assert!(q.check_fee(10_000, 1_000_000, 1_000).is_ok());
+        assert!(q.check_fee(10_001, 1_000_000, 1_000).is_err());
+        assert!(q.check_fee(0, 1_000_000, 1_001).is_err());
+        assert!(q.max_fee(u64::MAX).is_err());
+    }
+
+    #[test]
+    fn test_quote_must_not_be_stale_when_set() {
+        let mut q = quote();
+        assert!(q.set(0, 10, 99, 100, 0).is_err());
+        assert!(q.set(0, 10, 100, 100, 7).is_ok());
+        assert_eq!(q.updated_at, 7);
+    }
+}
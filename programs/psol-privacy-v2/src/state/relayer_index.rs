@@ -0,0 +1,169 @@
+//! Relayer Index - On-chain relayer enumeration
+//!
+//! Operators are appended in registration order across fixed-size shards so
+//! clients can page through relayers by index instead of scanning program
+//! accounts. Entry `i` lives in shard `i / RELAYER_INDEX_SHARD_CAPACITY`,
+//! slot `i % RELAYER_INDEX_SHARD_CAPACITY`, and `active_bitmap` mirrors each
+//! node's `is_active` flag.
+//!
+//! PDA Seeds: `[b"relayer_index", registry.key().as_ref(), shard_index.to_le_bytes()]`
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+
+/// Operators per index shard (one bit each in `active_bitmap`)
+pub const RELAYER_INDEX_SHARD_CAPACITY: u32 = 64;
+
+#[account]
+pub struct RelayerIndexShard {
+    /// Relayer registry this shard indexes
+    pub registry: Pubkey,
+
+    /// Position of this shard in the index
+    pub shard_index: u32,
+
+    /// Operators in registration order (append-only)
+    pub operators: Vec<Pubkey>,
+
+    /// Bit `slot` set = relayer at that slot is active
+    pub active_bitmap: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved for future use
+    pub _reserved: [u8; 16],
+}
+
+impl RelayerIndexShard {
+    pub const LEN: usize = 8  // discriminator
+        + 32                  // registry
+        + 4                   // shard_index
+        + 4 + (32 * RELAYER_INDEX_SHARD_CAPACITY as usize) // operators (vec)
+        + 8                   // active_bitmap
+        + 1                   // bump
+        + 16; // reserved
+
+    /// Shard holding index position `position`
+    pub fn shard_for(position: u32) -> u32 {
+        position / RELAYER_INDEX_SHARD_CAPACITY
+    }
+
+    fn slot_for(position: u32) -> usize {
+        (position % RELAYER_INDEX_SHARD_CAPACITY) as usize
+    }
+
+    pub fn initialize(&mut self, registry: Pubkey, shard_index: u32, bump: u8) {
+        self.registry = registry;
+        self.shard_index = shard_index;
+        self.operators = Vec::with_capacity(RELAYER_INDEX_SHARD_CAPACITY as usize);
+        self.active_bitmap = 0;
+        self.bump = bump;
+        self._reserved = [0u8; 16];
+    }
+
+    /// Append a newly registered operator at `position`
+    ///
+    /// Positions must arrive in order with no gaps.
+    pub fn append(&mut self, position: u32, operator: Pubkey) -> Result<()> {
+        let slot = Self::slot_for(position);
+        require!(
+            Self::shard_for(position) == self.shard_index && slot == self.operators.len(),
+            PrivacyErrorV2::RelayerIndexMismatch
+        );
+        self.operators.push(operator);
+        self.active_bitmap |= 1u64 << slot;
+        Ok(())
+    }
+
+    /// Position the next operator appended to this shard takes
+    pub fn next_position(&self) -> u32 {
+        self.shard_index * RELAYER_INDEX_SHARD_CAPACITY + self.operators.len() as u32
+    }
+
+    /// Mirror a relayer's active flag into the bitmap
+    pub fn set_active(&mut self, position: u32, operator: &Pubkey, active: bool) -> Result<()> {
+        let slot = Self::slot_for(position);
+        require!(
+            Self::shard_for(position) == self.shard_index
+                && self.operators.get(slot) == Some(operator),
+            PrivacyErrorV2::RelayerIndexMismatch
+        );
+        if active {
+            self.active_bitmap |= 1u64 << slot;
+        } else {
+            self.active_bitmap &= !(1u64 << slot);
+        }
+        Ok(())
+    }
+
+    pub fn is_active(&self, slot: usize) -> bool {
+        slot < self.operators.len() && self.active_bitmap & (1u64 << slot) != 0
+    }
+
+    /// Active operators in this shard, in index order
+    pub fn active_operators(&self) -> impl Iterator<Item = &Pubkey> {
+        self.operators
+            .iter()
+            .enumerate()
+            .filter(|(slot, _)| self.is_active(*slot))
+            .map(|(_, operator)| operator)
+    }
+}
+
+impl RelayerIndexShard {
+    pub const SEED_PREFIX: &'static [u8] = b"relayer_index";
+
+    pub fn find_pda(program_id: &Pubkey, registry: &Pubkey, shard_index: u32) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                Self::SEED_PREFIX,
+                registry.as_ref(),
+                &shard_index.to_le_bytes(),
+            ],
+            program_id,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shard(shard_index: u32) -> RelayerIndexShard {
+        let mut s = RelayerIndexShard {
+            registry: Pubkey::default(),
+            shard_index: 0,
+            operators: vec![],
+            active_bitmap: 0,
+            bump: 0,
+            _reserved: [0u8; 16],
+        };
+        s.initialize(Pubkey::default(), shard_index, 0);
+        s
+    }
+
+    #[test]
+    fn test_append_and_deactivate() {
+        let mut s = shard(1);
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+
+        // Position 64 is slot 0 of shard 1; skipping ahead is rejected
+        assert_eq!(s.next_position(), 64);
+        assert!(s.append(65, a).is_err());
+        assert!(s.append(0, a).is_err());
+        s.append(64, a).unwrap();
+        s.append(65, b).unwrap();
+        assert_eq!(s.next_position(), 66);
+
+        s.set_active(64, &a, false).unwrap();
+        assert_eq!(s.active_operators().collect::<Vec<_>>(), vec![&b]);
+
+        // Position must hold the operator being toggled
+        assert!(s.set_active(65, &a, true).is_err());
+        s.set_active(64, &a, true).unwrap();
+        assert_eq!(s.active_operators().count(), 2);
+    }
+}
@@ -0,0 +1,206 @@
+//! Relayer Gas Rebate Ledger - pSOL v2
+//!
+//! Tracks an estimated lamport cost per withdrawal submitted by a relayer
+//! (rent for the nullifier PDA plus the relayer's declared priority fee, the
+//! latter counted up to `RelayerRegistry::max_priority_fee_hint`) and how
+//! much of it has been reimbursed.
+//!
+//! Rebates are paid from the protocol fees collected into the pool's
+//! `Treasury` (see `collect_treasury_fees`), and each withdrawal accrues at
+//! most `RelayerRegistry::max_gas_rebate_lamports`.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+
+/// Per-relayer gas rebate ledger
+///
+/// PDA Seeds: `[b"relayer_rebate", relayer_node.key().as_ref()]`
+#[account]
+#[derive(InitSpace)]
+pub struct RelayerRebateLedger {
+    /// Relayer node this ledger belongs to
+    pub relayer_node: Pubkey,
+
+    /// Relayer operator (must sign withdrawals and claims)
+    pub operator: Pubkey,
+
+    /// Priority fee the relayer declares per withdrawal (lamports)
+    pub priority_fee_hint: u64,
+
+    /// Total rebate accrued (lamports)
+    pub accrued_lamports: u64,
+
+    /// Total rebate already paid out (lamports)
+    pub claimed_lamports: u64,
+
+    /// Number of withdrawals that accrued a rebate
+    pub withdrawals_accounted: u64,
+
+    /// Last accrual timestamp
+    pub last_accrued_at: i64,
+
+    /// Last claim timestamp
+    pub last_claimed_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved for future use
+    pub _reserved: [u8; 16],
+}
+
+impl RelayerRebateLedger {
+    pub const LEN: usize = 8  // discriminator
+        + 32                  // relayer_node
+        + 32                  // operator
+        + 8                   // priority_fee_hint
+        + 8                   // accrued_lamports
+        + 8                   // claimed_lamports
+        + 8                   // withdrawals_accounted
+        + 8                   // last_accrued_at
+        + 8                   // last_claimed_at
+        + 1                   // bump
+        + 16; // reserved
+
+    pub fn initialize(
+        &mut self,
+        relayer_node: Pubkey,
+        operator: Pubkey,
+        priority_fee_hint: u64,
+        bump: u8,
+    ) {
+        self.relayer_node = relayer_node;
+        self.operator = operator;
+        self.priority_fee_hint = priority_fee_hint;
+        self.accrued_lamports = 0;
+        self.claimed_lamports = 0;
+        self.withdrawals_accounted = 0;
+        self.last_accrued_at = 0;
+        self.last_claimed_at = 0;
+        self.bump = bump;
+        self._reserved = [0u8; 16];
+    }
+
+    /// Estimated lamport cost of one withdrawal for this relayer, counting
+    /// at most `max_priority_fee` of its declared priority fee
+    pub fn estimate_cost(&self, nullifier_rent: u64, max_priority_fee: u64) -> u64 {
+        nullifier_rent.saturating_add(self.priority_fee_hint.min(max_priority_fee))
+    }
+
+    /// Accrue a rebate for one withdrawal, capped at `cap` lamports.
+    ///
+    /// Returns the amount accrued (0 when rebates are disabled).
+    pub fn accrue(
+        &mut self,
+        nullifier_rent: u64,
+        max_priority_fee: u64,
+        cap: u64,
+        timestamp: i64,
+    ) -> Result<u64> {
+        if cap == 0 {
+            return Ok(0);
+        }
+        let amount = self
+            .estimate_cost(nullifier_rent, max_priority_fee)
+            .min(cap);
+
+        self.accrued_lamports = self
+            .accrued_lamports
+            .checked_add(amount)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        self.withdrawals_accounted = self
+            .withdrawals_accounted
+            .checked_add(1)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        self.last_accrued_at = timestamp;
+        Ok(amount)
+    }
+
+    /// Rebate accrued but not yet paid out
+    pub fn claimable(&self) -> u64 {
+        self.accrued_lamports.saturating_sub(self.claimed_lamports)
+    }
+
+    pub fn record_claim(&mut self, amount: u64, timestamp: i64) -> Result<()> {
+        require!(
+            amount <= self.claimable(),
+            PrivacyErrorV2::NoRebateAvailable
+        );
+        self.claimed_lamports = self
+            .claimed_lamports
+            .checked_add(amount)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        self.last_claimed_at = timestamp;
+        Ok(())
+    }
+}
+
+/// PDA seeds for RelayerRebateLedger
+impl RelayerRebateLedger {
+    pub const SEED_PREFIX: &'static [u8] = b"relayer_rebate";
+
+    pub fn find_pda(program_id: &Pubkey, relayer_node: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, relayer_node.as_ref()], program_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ledger(priority_fee_hint: u64) -> RelayerRebateLedger {
+        RelayerRebateLedger {
+            relayer_node: Pubkey::default(),
+            operator: Pubkey::default(),
+            priority_fee_hint,
+            accrued_lamports: 0,
+            claimed_lamports: 0,
+            withdrawals_accounted: 0,
+            last_accrued_at: 0,
+            last_claimed_at: 0,
+            bump: 0,
+            _reserved: [0u8; 16],
+        }
+    }
+
+    #[test]
+    fn test_accrue_is_capped() {
+        let mut l = ledger(10_000);
+
+        // Rebates disabled when cap is zero
+        assert_eq!(l.accrue(1_000_000, u64::MAX, 0, 1).unwrap(), 0);
+        assert_eq!(l.withdrawals_accounted, 0);
+
+        assert_eq!(l.accrue(1_000_000, u64::MAX, 500_000, 2).unwrap(), 500_000);
+        assert_eq!(l.accrue(100_000, u64::MAX, 500_000, 3).unwrap(), 110_000);
+        assert_eq!(l.claimable(), 610_000);
+        assert_eq!(l.withdrawals_accounted, 2);
+
+        // Rent already reimbursed leaves only the priority fee
+        assert_eq!(l.accrue(0, u64::MAX, 500_000, 4).unwrap(), 10_000);
+        assert_eq!(l.claimable(), 620_000);
+    }
+
+    #[test]
+    fn test_priority_fee_hint_is_clamped() {
+        let mut l = ledger(u64::MAX);
+
+        assert_eq!(l.accrue(1_000, 4_000, u64::MAX, 1).unwrap(), 5_000);
+        // Without a registry maximum only the rent is rebated
+        assert_eq!(l.accrue(1_000, 0, u64::MAX, 2).unwrap(), 1_000);
+        assert_eq!(l.claimable(), 6_000);
+    }
+
+    #[test]
+    fn test_claim_cannot_exceed_accrued() {
+        let mut l = ledger(0);
+        l.accrue(1_000, 0, 5_000, 1).unwrap();
+
+        assert!(l.record_claim(1_001, 2).is_err());
+        l.record_claim(600, 2).unwrap();
+        assert_eq!(l.claimable(), 400);
+        l.record_claim(400, 3).unwrap();
+        assert_eq!(l.claimable(), 0);
+    }
+}
@@ -0,0 +1,126 @@
+//! Relayer Tombstone - Re-registration cooldown for closed relayers
+//!
+//! Closing a RelayerNode frees its PDA, so nothing on the node itself can
+//! stop the operator from registering again straight away with a fresh
+//! reputation. The tombstone outlives the node, records when the operator
+//! may register again and what reputation they left with, and is consumed
+//! by the next successful registration.
+//!
+//! PDA Seeds: `[b"relayer_tombstone", registry.key().as_ref(), operator.key().as_ref()]`
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+
+#[account]
+#[derive(InitSpace)]
+pub struct RelayerTombstone {
+    /// Relayer registry the node was closed in
+    pub registry: Pubkey,
+
+    /// Operator of the closed node
+    pub operator: Pubkey,
+
+    /// Slot the node was closed in
+    pub closed_at_slot: u64,
+
+    /// First slot a new registration is accepted
+    pub reregister_after_slot: u64,
+
+    /// Reputation the closed node ended with (restored on re-registration)
+    pub reputation_score: u8,
+
+    /// Index position the closed node held
+    pub index_position: u32,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved for future use
+    pub _reserved: [u8; 16],
+}
+
+impl RelayerTombstone {
+    pub const LEN: usize = 8  // discriminator
+        + 32                  // registry
+        + 32                  // operator
+        + 8                   // closed_at_slot
+        + 8                   // reregister_after_slot
+        + 1                   // reputation_score
+        + 4                   // index_position
+        + 1                   // bump
+        + 16; // reserved
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize(
+        &mut self,
+        registry: Pubkey,
+        operator: Pubkey,
+        closed_at_slot: u64,
+        cooldown_slots: u64,
+        reputation_score: u8,
+        index_position: u32,
+        bump: u8,
+    ) -> Result<()> {
+        self.registry = registry;
+        self.operator = operator;
+        self.closed_at_slot = closed_at_slot;
+        self.reregister_after_slot = closed_at_slot
+            .checked_add(cooldown_slots)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        self.reputation_score = reputation_score;
+        self.index_position = index_position;
+        self.bump = bump;
+        self._reserved = [0u8; 16];
+        Ok(())
+    }
+
+    /// Check the cooldown has elapsed at `slot`
+    pub fn require_cooldown_elapsed(&self, slot: u64) -> Result<()> {
+        require!(
+            slot >= self.reregister_after_slot,
+            PrivacyErrorV2::RelayerCooldownActive
+        );
+        Ok(())
+    }
+}
+
+impl RelayerTombstone {
+    pub const SEED_PREFIX: &'static [u8] = b"relayer_tombstone";
+
+    pub fn find_pda(program_id: &Pubkey, registry: &Pubkey, operator: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED_PREFIX, registry.as_ref(), operator.as_ref()],
+            program_id,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cooldown_window() {
+        let mut t = RelayerTombstone {
+            registry: Pubkey::default(),
+            operator: Pubkey::default(),
+            closed_at_slot: 0,
+            reregister_after_slot: 0,
+            reputation_score: 0,
+            index_position: 0,
+            bump: 0,
+            _reserved: [0u8; 16],
+        };
+        t.initialize(Pubkey::default(), Pubkey::default(), 1_000, 500, 20, 3, 0)
+            .unwrap();
+
+        assert!(t.require_cooldown_elapsed(1_000).is_err());
+        assert!(t.require_cooldown_elapsed(1_499).is_err());
+        assert!(t.require_cooldown_elapsed(1_500).is_ok());
+
+        assert!(t
+            .initialize(Pubkey::default(), Pubkey::default(), u64::MAX, 1, 0, 0, 0)
+            .is_err());
+    }
+}
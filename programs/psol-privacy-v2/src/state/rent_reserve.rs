@@ -0,0 +1,266 @@
+//! Rent Reserve - pool-funded lamport float for relayer rent
+//!
+//! Every withdrawal makes the submitting relayer pay rent for a fresh
+//! nullifier PDA, so a burst of withdrawals ties up relayer capital long
+//! before fees are realised. The pool can hold a lamport reserve that tops
+//! up that rent as it is spent: the advance is recorded against the
+//! relayer's `RentAdvance` and repaid later with `repay_rent_advance`.
+//!
+//! Each relayer's outstanding advance is capped by
+//! `RentReserve::max_outstanding_per_relayer`; once a relayer reaches the
+//! cap (or the reserve runs dry) withdrawals proceed without a top-up.
+//!
+//! PDA Seeds:
+//! - RentReserve: `[b"rent_reserve", pool.key().as_ref()]`
+//! - RentAdvance: `[b"rent_advance", relayer_node.key().as_ref()]`
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+
+/// Pool-level lamport reserve for relayer rent
+#[account]
+#[derive(InitSpace)]
+pub struct RentReserve {
+    /// Parent pool configuration
+    pub pool: Pubkey,
+
+    /// Most a single relayer may owe the reserve (lamports)
+    pub max_outstanding_per_relayer: u64,
+
+    /// Total lamports funded into the reserve
+    pub total_funded: u64,
+
+    /// Total lamports advanced to relayers
+    pub total_advanced: u64,
+
+    /// Total lamports repaid by relayers
+    pub total_repaid: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved for future use
+    pub _reserved: [u8; 16],
+}
+
+impl RentReserve {
+    pub const SEED_PREFIX: &'static [u8] = b"rent_reserve";
+
+    pub const LEN: usize = 8  // discriminator
+        + 32  // pool
+        + 8   // max_outstanding_per_relayer
+        + 8   // total_funded
+        + 8   // total_advanced
+        + 8   // total_repaid
+        + 1   // bump
+        + 16; // reserved
+
+    pub fn initialize(&mut self, pool: Pubkey, max_outstanding_per_relayer: u64, bump: u8) {
+        self.pool = pool;
+        self.max_outstanding_per_relayer = max_outstanding_per_relayer;
+        self.total_funded = 0;
+        self.total_advanced = 0;
+        self.total_repaid = 0;
+        self.bump = bump;
+        self._reserved = [0u8; 16];
+    }
+
+    /// Lamports currently owed to the reserve across all relayers
+    pub fn outstanding(&self) -> u64 {
+        self.total_advanced.saturating_sub(self.total_repaid)
+    }
+
+    /// Size of the next advance for `rent` lamports: bounded by the
+    /// relayer's remaining headroom and by what the reserve can spend
+    pub fn advance_amount(&self, advance: &RentAdvance, rent: u64, available: u64) -> u64 {
+        let headroom = self
+            .max_outstanding_per_relayer
+            .saturating_sub(advance.outstanding);
+        rent.min(headroom).min(available)
+    }
+
+    pub fn record_funding(&mut self, amount: u64) -> Result<()> {
+        self.total_funded = self
+            .total_funded
+            .checked_add(amount)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        Ok(())
+    }
+
+    pub fn record_advance(&mut self, amount: u64) -> Result<()> {
+        self.total_advanced = self
+            .total_advanced
+            .checked_add(amount)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        Ok(())
+    }
+
+    pub fn record_repayment(&mut self, amount: u64) -> Result<()> {
+        self.total_repaid = self
+            .total_repaid
+            .checked_add(amount)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        Ok(())
+    }
+
+    pub fn find_pda(program_id: &Pubkey, pool: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, pool.as_ref()], program_id)
+    }
+}
+
+/// One relayer's debt to the pool's rent reserve
+#[account]
+#[derive(InitSpace)]
+pub struct RentAdvance {
+    /// Rent reserve the advances were drawn from
+    pub reserve: Pubkey,
+
+    /// Relayer node this account belongs to
+    pub relayer_node: Pubkey,
+
+    /// Relayer operator (receives advances, repays them)
+    pub operator: Pubkey,
+
+    /// Lamports advanced and not yet repaid
+    pub outstanding: u64,
+
+    /// Lifetime lamports advanced
+    pub total_advanced: u64,
+
+    /// Lifetime lamports repaid
+    pub total_repaid: u64,
+
+    /// Last advance timestamp
+    pub last_advanced_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved for future use
+    pub _reserved: [u8; 16],
+}
+
+impl RentAdvance {
+    pub const SEED_PREFIX: &'static [u8] = b"rent_advance";
+
+    pub const LEN: usize = 8  // discriminator
+        + 32  // reserve
+        + 32  // relayer_node
+        + 32  // operator
+        + 8   // outstanding
+        + 8   // total_advanced
+        + 8   // total_repaid
+        + 8   // last_advanced_at
+        + 1   // bump
+        + 16; // reserved
+
+    pub fn initialize(
+        &mut self,
+        reserve: Pubkey,
+        relayer_node: Pubkey,
+        operator: Pubkey,
+        bump: u8,
+    ) {
+        self.reserve = reserve;
+        self.relayer_node = relayer_node;
+        self.operator = operator;
+        self.outstanding = 0;
+        self.total_advanced = 0;
+        self.total_repaid = 0;
+        self.last_advanced_at = 0;
+        self.bump = bump;
+        self._reserved = [0u8; 16];
+    }
+
+    pub fn record_advance(&mut self, amount: u64, timestamp: i64) -> Result<()> {
+        self.outstanding = self
+            .outstanding
+            .checked_add(amount)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        self.total_advanced = self
+            .total_advanced
+            .checked_add(amount)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        self.last_advanced_at = timestamp;
+        Ok(())
+    }
+
+    pub fn record_repayment(&mut self, amount: u64) -> Result<()> {
+        require!(
+            amount > 0 && amount <= self.outstanding,
+            PrivacyErrorV2::InvalidRentRepayment
+        );
+        self.outstanding -= amount;
+        self.total_repaid = self
+            .total_repaid
+            .checked_add(amount)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        Ok(())
+    }
+
+    pub fn find_pda(program_id: &Pubkey, relayer_node: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, relayer_node.as_ref()], program_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reserve(cap: u64) -> RentReserve {
+        let mut r = RentReserve {
+            pool: Pubkey::default(),
+            max_outstanding_per_relayer: 0,
+            total_funded: 0,
+            total_advanced: 0,
+            total_repaid: 0,
+            bump: 0,
+            _reserved: [0u8; 16],
+        };
+        r.initialize(Pubkey::new_unique(), cap, 255);
+        r
+    }
+
+    fn advance() -> RentAdvance {
+        RentAdvance {
+            reserve: Pubkey::default(),
+            relayer_node: Pubkey::default(),
+            operator: Pubkey::default(),
+            outstanding: 0,
+            total_advanced: 0,
+            total_repaid: 0,
+            last_advanced_at: 0,
+            bump: 0,
+            _reserved: [0u8; 16],
+        }
+    }
+
+    #[test]
+    fn test_advance_bounded_by_headroom_and_balance() {
+        let r = reserve(3_000);
+        let mut a = advance();
+
+        assert_eq!(r.advance_amount(&a, 1_000, 10_000), 1_000);
+        a.record_advance(2_500, 1).unwrap();
+        // Only 500 left under the per-relayer cap
+        assert_eq!(r.advance_amount(&a, 1_000, 10_000), 500);
+        // A drained reserve advances nothing
+        assert_eq!(r.advance_amount(&advance(), 1_000, 0), 0);
+        a.record_advance(500, 2).unwrap();
+        assert_eq!(r.advance_amount(&a, 1_000, 10_000), 0);
+    }
+
+    #[test]
+    fn test_repayment_cannot_exceed_outstanding() {
+        let mut a = advance();
+        a.record_advance(1_000, 1).unwrap();
+
+        assert!(a.record_repayment(0).is_err());
+        assert!(a.record_repayment(1_001).is_err());
+        a.record_repayment(600).unwrap();
+        assert_eq!(a.outstanding, 400);
+        assert_eq!(a.total_repaid, 600);
+        assert_eq!(a.total_advanced, 1_000);
+    }
+}
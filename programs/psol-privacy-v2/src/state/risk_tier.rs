@@ -0,0 +1,412 @@
+//! Risk Tiers - Per-asset limits from one pool-level table
+//!
+//! Instead of toggling each vault's deposit and withdrawal limits one by
+//! one, the authority keeps a table of `RISK_TIER_COUNT` tiers per pool and
+//! puts each asset in a tier with `set_asset_risk_tier`. The vault only
+//! records which tier it is in (`AssetVault::risk_tier`); deposits and
+//! withdrawals read the tier's limits from this table, so editing a tier
+//! applies at once to every asset in it. The tier's limits apply on top of
+//! the vault's own switches and deposit bounds.
+//!
+//! A tier can also rate-limit withdrawals per asset, tracked in each
+//! asset's `AssetWithdrawalWindow`, and require withdrawals from a
+//! threshold up to be sealed with a `WithdrawalCommit` some time ahead.
+//!
+//! Default tiers (no withdrawal cap, rate limit or delay):
+//! - 0 `Normal`: deposits and withdrawals open, no deposit cap
+//! - 1 `Elevated`: as `Normal`; meant to be given a deposit cap
+//! - 2 `WithdrawOnly`: deposits closed, withdrawals open
+//! - 3 `Frozen`: deposits and withdrawals closed
+//!
+//! PDA Seeds:
+//! - RiskTierConfig: `[b"risk_tiers", pool.key().as_ref()]`
+//! - AssetWithdrawalWindow: `[b"withdrawal_window", asset_vault.key().as_ref()]`
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::state::AssetVault;
+
+/// Number of tiers in a pool's table
+pub const RISK_TIER_COUNT: usize = 4;
+
+/// Limits an asset takes on when put in a tier
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct RiskTierLimits {
+    /// Whether the asset accepts deposits
+    pub deposits_enabled: bool,
+
+    /// Whether the asset can be withdrawn
+    pub withdrawals_enabled: bool,
+
+    /// Largest single deposit (token base units, `u64::MAX` = no cap)
+    pub max_deposit: u64,
+
+    /// Largest single withdrawal (token base units, `u64::MAX` = no cap)
+    pub max_withdrawal: u64,
+
+    /// Length of the per-asset withdrawal rate window in seconds
+    /// (0 = no rate limit)
+    pub rate_limit_window: u32,
+
+    /// Most an asset may pay out within one rate window
+    pub rate_limit_amount: u64,
+
+    /// Withdrawals of at least this amount must be sealed with a
+    /// `WithdrawalCommit` `withdrawal_delay` seconds ahead
+    /// (`u64::MAX` = never)
+    pub delay_threshold: u64,
+
+    /// Seconds a withdrawal at or above `delay_threshold` must have been
+    /// committed for
+    pub withdrawal_delay: u32,
+}
+
+impl RiskTierLimits {
+    pub const OPEN: Self = Self {
+        deposits_enabled: true,
+        withdrawals_enabled: true,
+        max_deposit: u64::MAX,
+        max_withdrawal: u64::MAX,
+        rate_limit_window: 0,
+        rate_limit_amount: 0,
+        delay_threshold: u64::MAX,
+        withdrawal_delay: 0,
+    };
+    pub const WITHDRAW_ONLY: Self = Self {
+        deposits_enabled: false,
+        ..Self::OPEN
+    };
+    pub const FROZEN: Self = Self {
+        deposits_enabled: false,
+        withdrawals_enabled: false,
+        ..Self::OPEN
+    };
+
+    /// Whether withdrawals are rate-limited per asset
+    pub fn is_rate_limited(&self) -> bool {
+        self.rate_limit_window != 0
+    }
+
+    /// Check a deposit of `amount` against the tier
+    pub fn check_deposit(&self, amount: u64) -> Result<()> {
+        require!(self.deposits_enabled, PrivacyErrorV2::DepositsDisabled);
+        require!(
+            amount <= self.max_deposit,
+            PrivacyErrorV2::ExceedsMaximumDeposit
+        );
+        Ok(())
+    }
+
+    /// Check a withdrawal of `amount` against the tier; `committed_at` is
+    /// when the withdrawal was sealed with a `WithdrawalCommit`, if it was
+    pub fn check_withdrawal(&self, amount: u64, committed_at: Option<i64>, now: i64) -> Result<()> {
+        require!(
+            self.withdrawals_enabled,
+            PrivacyErrorV2::WithdrawalsDisabled
+        );
+        require!(
+            amount <= self.max_withdrawal,
+            PrivacyErrorV2::ExceedsTierWithdrawalLimit
+        );
+        if self.delay_threshold != u64::MAX && amount >= self.delay_threshold {
+            let committed_at = committed_at.ok_or(PrivacyErrorV2::WithdrawalDelayRequired)?;
+            require!(
+                now.saturating_sub(committed_at) >= self.withdrawal_delay as i64,
+                PrivacyErrorV2::WithdrawalDelayNotElapsed
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Risk tier table of one pool
+#[account]
+#[derive(InitSpace)]
+pub struct RiskTierConfig {
+    /// Pool this table belongs to
+    pub pool: Pubkey,
+
+    /// Limits per tier, indexed by tier
+    pub tiers: [RiskTierLimits; RISK_TIER_COUNT],
+
+    /// Last update timestamp
+    pub updated_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl RiskTierConfig {
+    pub const LEN: usize = 8  // discriminator
+        + 32                  // pool
+        + RISK_TIER_COUNT * (1 + 1 + 8 + 8 + 4 + 8 + 8 + 4) // tiers
+        + 8                   // updated_at
+        + 1; // bump
+
+    pub const TIER_NORMAL: u8 = 0;
+    pub const TIER_ELEVATED: u8 = 1;
+    pub const TIER_WITHDRAW_ONLY: u8 = 2;
+    pub const TIER_FROZEN: u8 = 3;
+
+    pub const DEFAULT_TIERS: [RiskTierLimits; RISK_TIER_COUNT] = [
+        RiskTierLimits::OPEN,
+        RiskTierLimits::OPEN,
+        RiskTierLimits::WITHDRAW_ONLY,
+        RiskTierLimits::FROZEN,
+    ];
+
+    pub fn initialize(&mut self, pool: Pubkey, bump: u8, timestamp: i64) {
+        self.pool = pool;
+        self.tiers = Self::DEFAULT_TIERS;
+        self.updated_at = timestamp;
+        self.bump = bump;
+    }
+
+    /// Limits of `tier`
+    pub fn limits(&self, tier: u8) -> Result<RiskTierLimits> {
+        self.tiers
+            .get(tier as usize)
+            .copied()
+            .ok_or_else(|| error!(PrivacyErrorV2::InvalidRiskTier))
+    }
+
+    /// Limits of the tier `asset_vault` is in, or `None` if it was never put
+    /// in one; `tiers` (the vault's pool's table) is required once it was
+    pub fn asset_limits(
+        tiers: Option<&RiskTierConfig>,
+        asset_vault: &AssetVault,
+    ) -> Result<Option<RiskTierLimits>> {
+        if !asset_vault.has_risk_tier() {
+            return Ok(None);
+        }
+        let tiers = tiers.ok_or(PrivacyErrorV2::RiskTiersRequired)?;
+        require_keys_eq!(
+            tiers.pool,
+            asset_vault.pool,
+            PrivacyErrorV2::InvalidVaultPool
+        );
+        tiers.limits(asset_vault.risk_tier).map(Some)
+    }
+
+    /// Replace the limits of `tier`
+    pub fn set_limits(&mut self, tier: u8, limits: RiskTierLimits, timestamp: i64) -> Result<()> {
+        let slot = self
+            .tiers
+            .get_mut(tier as usize)
+            .ok_or_else(|| error!(PrivacyErrorV2::InvalidRiskTier))?;
+        *slot = limits;
+        self.updated_at = timestamp;
+        Ok(())
+    }
+}
+
+/// PDA seeds for RiskTierConfig
+impl RiskTierConfig {
+    pub const SEED_PREFIX: &'static [u8] = b"risk_tiers";
+
+    pub fn find_pda(program_id: &Pubkey, pool: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, pool.as_ref()], program_id)
+    }
+}
+
+/// Withdrawals paid out of one asset in the current rate window
+#[account]
+#[derive(InitSpace)]
+pub struct AssetWithdrawalWindow {
+    /// Asset vault the window belongs to
+    pub asset_vault: Pubkey,
+
+    /// Unix timestamp the current window opened at
+    pub window_start: i64,
+
+    /// Amount withdrawn since `window_start`
+    pub withdrawn: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl AssetWithdrawalWindow {
+    pub const LEN: usize = 8  // discriminator
+        + 32                  // asset_vault
+        + 8                   // window_start
+        + 8                   // withdrawn
+        + 1; // bump
+
+    pub const SEED_PREFIX: &'static [u8] = b"withdrawal_window";
+
+    pub fn initialize(&mut self, asset_vault: Pubkey, bump: u8, timestamp: i64) {
+        self.asset_vault = asset_vault;
+        self.window_start = timestamp;
+        self.withdrawn = 0;
+        self.bump = bump;
+    }
+
+    /// Count a withdrawal of `amount` against `limits`' rate limit, opening
+    /// a new window once the current one has run out
+    pub fn record(&mut self, limits: &RiskTierLimits, amount: u64, now: i64) -> Result<()> {
+        if !limits.is_rate_limited() {
+            return Ok(());
+        }
+        if now.saturating_sub(self.window_start) >= limits.rate_limit_window as i64 {
+            self.window_start = now;
+            self.withdrawn = 0;
+        }
+        let withdrawn = self
+            .withdrawn
+            .checked_add(amount)
+            .ok_or(PrivacyErrorV2::ArithmeticOverflow)?;
+        require!(
+            withdrawn <= limits.rate_limit_amount,
+            PrivacyErrorV2::WithdrawalRateLimitExceeded
+        );
+        self.withdrawn = withdrawn;
+        Ok(())
+    }
+
+    pub fn find_pda(program_id: &Pubkey, asset_vault: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, asset_vault.as_ref()], program_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tier_table() {
+        let mut config = RiskTierConfig {
+            pool: Pubkey::default(),
+            tiers: [RiskTierLimits::OPEN; RISK_TIER_COUNT],
+            updated_at: 0,
+            bump: 0,
+        };
+        config.initialize(Pubkey::new_unique(), 255, 1);
+
+        assert_eq!(
+            config.limits(RiskTierConfig::TIER_FROZEN).unwrap(),
+            RiskTierLimits::FROZEN
+        );
+        assert!(config.limits(RISK_TIER_COUNT as u8).is_err());
+
+        let capped = RiskTierLimits {
+            max_deposit: 1_000,
+            ..RiskTierLimits::OPEN
+        };
+        config
+            .set_limits(RiskTierConfig::TIER_ELEVATED, capped, 2)
+            .unwrap();
+        assert_eq!(
+            config.limits(RiskTierConfig::TIER_ELEVATED).unwrap(),
+            capped
+        );
+        assert_eq!(config.updated_at, 2);
+        assert!(config.set_limits(RISK_TIER_COUNT as u8, capped, 3).is_err());
+    }
+
+    #[test]
+    fn test_tier_deposit_checks() {
+        let capped = RiskTierLimits {
+            max_deposit: 1_000,
+            ..RiskTierLimits::OPEN
+        };
+        assert!(capped.check_deposit(1_000).is_ok());
+        assert!(capped.check_deposit(1_001).is_err());
+        assert!(RiskTierLimits::OPEN.check_deposit(u64::MAX).is_ok());
+        assert!(RiskTierLimits::WITHDRAW_ONLY.check_deposit(1).is_err());
+    }
+
+    #[test]
+    fn test_tier_withdrawal_checks() {
+        let limits = RiskTierLimits {
+            max_withdrawal: 10_000,
+            delay_threshold: 5_000,
+            withdrawal_delay: 60,
+            ..RiskTierLimits::OPEN
+        };
+        assert!(limits.check_withdrawal(4_999, None, 100).is_ok());
+        assert!(limits.check_withdrawal(10_001, Some(0), 100).is_err());
+
+        // At the threshold a commit at least `withdrawal_delay` old is needed
+        assert!(limits.check_withdrawal(5_000, None, 100).is_err());
+        assert!(limits.check_withdrawal(5_000, Some(41), 100).is_err());
+        assert!(limits.check_withdrawal(5_000, Some(40), 100).is_ok());
+
+        assert!(RiskTierLimits::OPEN
+            .check_withdrawal(u64::MAX, None, 0)
+            .is_ok());
+        assert!(RiskTierLimits::FROZEN.check_withdrawal(1, None, 0).is_err());
+    }
+
+    #[test]
+    fn test_withdrawal_rate_limit() {
+        let limits = RiskTierLimits {
+            rate_limit_window: 3_600,
+            rate_limit_amount: 1_000,
+            ..RiskTierLimits::OPEN
+        };
+        let mut window = AssetWithdrawalWindow {
+            asset_vault: Pubkey::default(),
+            window_start: 0,
+            withdrawn: 0,
+            bump: 0,
+        };
+        window.initialize(Pubkey::new_unique(), 255, 100);
+
+        window.record(&limits, 600, 200).unwrap();
+        window.record(&limits, 400, 300).unwrap();
+        assert!(window.record(&limits, 1, 400).is_err());
+        assert_eq!(window.withdrawn, 1_000);
+
+        // A new window opens once the current one has run out
+        window.record(&limits, 1, 100 + 3_600).unwrap();
+        assert_eq!(window.window_start, 3_700);
+        assert_eq!(window.withdrawn, 1);
+        assert!(window.record(&limits, 1_000, 3_701).is_err());
+
+        // Without a rate limit nothing is counted
+        window
+            .record(&RiskTierLimits::OPEN, u64::MAX, 3_702)
+            .unwrap();
+        assert_eq!(window.withdrawn, 1);
+    }
+
+    #[test]
+    fn test_asset_limits_follow_the_table() {
+        let mut vault =
+            AssetVault::try_deserialize_unchecked(&mut &vec![0u8; AssetVault::DEFAULT_SPACE][..])
+                .unwrap();
+        vault.pool = Pubkey::new_unique();
+        let mut config = RiskTierConfig {
+            pool: Pubkey::default(),
+            tiers: [RiskTierLimits::OPEN; RISK_TIER_COUNT],
+            updated_at: 0,
+            bump: 0,
+        };
+        config.initialize(vault.pool, 255, 1);
+
+        // Never put in a tier: no table needed
+        assert_eq!(RiskTierConfig::asset_limits(None, &vault).unwrap(), None);
+
+        vault.set_risk_tier(RiskTierConfig::TIER_ELEVATED);
+        assert!(RiskTierConfig::asset_limits(None, &vault).is_err());
+        assert_eq!(
+            RiskTierConfig::asset_limits(Some(&config), &vault).unwrap(),
+            Some(RiskTierLimits::OPEN)
+        );
+
+        // Editing the tier applies to the asset without re-assigning it
+        config
+            .set_limits(RiskTierConfig::TIER_ELEVATED, RiskTierLimits::FROZEN, 2)
+            .unwrap();
+        assert_eq!(
+            RiskTierConfig::asset_limits(Some(&config), &vault).unwrap(),
+            Some(RiskTierLimits::FROZEN)
+        );
+
+        // Another pool's table is refused
+        config.pool = Pubkey::new_unique();
+        assert!(RiskTierConfig::asset_limits(Some(&config), &vault).is_err());
+    }
+}
@@ -7,9 +7,33 @@
 //! # Join-Split Support
 //! v2 nullifiers track which operation type spent them
 //! (withdrawal vs join-split) for analytics and debugging.
+//!
+//! # Spend Context
+//! All spend paths share one PDA namespace, so a nullifier can be spent
+//! exactly once across withdraw, join-split and CPI instructions. The
+//! record additionally stores the exact instruction (`SpendContext`); the
+//! coarse `spend_type` is derived from it. Records created before the
+//! field was added are shorter (`LEGACY_LEN`) and read as
+//! `SpendContext::Legacy` via `SpentNullifierV2::spend_context_from_data`.
+//!
+//! # Nullification Receipts
+//! The relayer that spent a nullifier may record a receipt: the first
+//! `NOTE_RECEIPT_LEN` bytes of the spent note's encrypted envelope (see
+//! `scanner`). A wallet holding the envelope then learns the note is spent
+//! from the record alone, without re-deriving nullifier hashes for every
+//! note it owns. The receipt links the spend to the deposit that carried
+//! the envelope, so it is optional and pools with `privacy_strict` set
+//! never record one; an unrecorded receipt reads as all zeroes. Legacy
+//! records predate the receipt too and never hold one;
+//! `SpentNullifierV2::from_account_data` reads both versions.
 
 use anchor_lang::prelude::*;
 
+use crate::error::PrivacyErrorV2;
+
+/// Bytes of the spent note's envelope kept as a nullification receipt
+pub const NOTE_RECEIPT_LEN: usize = 8;
+
 /// Operation type that spent the nullifier
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
 pub enum SpendType {
@@ -21,10 +45,62 @@ pub enum SpendType {
     ShieldedAction = 2,
 }
 
+/// Instruction that spent the nullifier
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SpendContext {
+    /// Record created before spend contexts were tracked
+    Legacy = 0,
+    /// `withdraw_masp`
+    WithdrawMasp = 1,
+    /// `withdraw_v2` (join-split withdrawal with change)
+    WithdrawV2 = 2,
+    /// `withdraw_yield_v2`
+    WithdrawYield = 3,
+    /// Join-split private transfer
+    JoinSplit = 4,
+    /// Shielded CPI action
+    ShieldedCpi = 5,
+    /// `compliance_recovery_withdraw` (disclosed note moved to a custodian)
+    ComplianceRecovery = 6,
+    /// `disclosed_path_withdraw` (note opened on-chain, reduced privacy)
+    DisclosedPath = 7,
+}
+
+impl SpendContext {
+    /// Coarse operation type recorded alongside the context
+    pub fn spend_type(self) -> SpendType {
+        match self {
+            Self::Legacy
+            | Self::WithdrawMasp
+            | Self::WithdrawV2
+            | Self::WithdrawYield
+            | Self::ComplianceRecovery
+            | Self::DisclosedPath => SpendType::Withdraw,
+            Self::JoinSplit => SpendType::JoinSplit,
+            Self::ShieldedCpi => SpendType::ShieldedAction,
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Legacy),
+            1 => Some(Self::WithdrawMasp),
+            2 => Some(Self::WithdrawV2),
+            3 => Some(Self::WithdrawYield),
+            4 => Some(Self::JoinSplit),
+            5 => Some(Self::ShieldedCpi),
+            6 => Some(Self::ComplianceRecovery),
+            7 => Some(Self::DisclosedPath),
+            _ => None,
+        }
+    }
+}
+
 /// Spent nullifier marker account - pSOL v2
 ///
 /// PDA Seeds: `[b"nullifier_v2", pool.key().as_ref(), nullifier_hash.as_ref()]`
 #[account]
+#[derive(InitSpace)]
 pub struct SpentNullifierV2 {
     /// Reference to parent pool
     pub pool: Pubkey,
@@ -49,6 +125,12 @@ pub struct SpentNullifierV2 {
 
     /// PDA bump seed
     pub bump: u8,
+
+    /// Instruction that spent this nullifier (`SpendContext`)
+    pub spend_context: u8,
+
+    /// Envelope prefix of the spent note (all zeroes when not recorded)
+    pub note_receipt: [u8; NOTE_RECEIPT_LEN],
 }
 
 impl SpentNullifierV2 {
@@ -60,7 +142,13 @@ impl SpentNullifierV2 {
         + 8                   // spent_at
         + 8                   // spent_slot
         + 32                  // relayer
-        + 1; // bump
+        + 1                   // bump
+        + 1                   // spend_context
+        + NOTE_RECEIPT_LEN; // note_receipt
+
+    /// Size of records created before `spend_context` and `note_receipt`
+    /// were added
+    pub const LEGACY_LEN: usize = Self::LEN - 1 - NOTE_RECEIPT_LEN;
 
     /// Initialize spent nullifier record
     #[allow(clippy::too_many_arguments)]
@@ -69,7 +157,7 @@ impl SpentNullifierV2 {
         pool: Pubkey,
         nullifier_hash: [u8; 32],
         asset_id: [u8; 32],
-        spend_type: SpendType,
+        spend_context: SpendContext,
         spent_at: i64,
         spent_slot: u64,
         relayer: Pubkey,
@@ -78,11 +166,31 @@ impl SpentNullifierV2 {
         self.pool = pool;
         self.nullifier_hash = nullifier_hash;
         self.asset_id = asset_id;
-        self.spend_type = spend_type as u8;
+        self.spend_type = spend_context.spend_type() as u8;
+        self.spend_context = spend_context as u8;
         self.spent_at = spent_at;
         self.spent_slot = spent_slot;
         self.relayer = relayer;
         self.bump = bump;
+        self.note_receipt = [0u8; NOTE_RECEIPT_LEN];
+    }
+
+    pub fn has_note_receipt(&self) -> bool {
+        self.note_receipt != [0u8; NOTE_RECEIPT_LEN]
+    }
+
+    /// Record the spent note's receipt; a record takes at most one
+    pub fn record_note_receipt(&mut self, note_receipt: [u8; NOTE_RECEIPT_LEN]) -> Result<()> {
+        require!(
+            note_receipt != [0u8; NOTE_RECEIPT_LEN],
+            PrivacyErrorV2::InvalidNoteReceipt
+        );
+        require!(
+            !self.has_note_receipt(),
+            PrivacyErrorV2::NoteReceiptAlreadyRecorded
+        );
+        self.note_receipt = note_receipt;
+        Ok(())
     }
 
     /// Get spend type
@@ -94,6 +202,34 @@ impl SpentNullifierV2 {
             _ => None,
         }
     }
+
+    /// Get spend context
+    pub fn get_spend_context(&self) -> Option<SpendContext> {
+        SpendContext::from_u8(self.spend_context)
+    }
+
+    /// Spend context from raw account data, including legacy records that
+    /// predate the field
+    pub fn spend_context_from_data(data: &[u8]) -> Option<SpendContext> {
+        match data.len() {
+            Self::LEGACY_LEN => Some(SpendContext::Legacy),
+            // `spend_context` directly follows the legacy fields
+            Self::LEN => SpendContext::from_u8(data[Self::LEGACY_LEN]),
+            _ => None,
+        }
+    }
+
+    /// Deserialize a record of any version; fields a shorter record
+    /// predates read as zero (`SpendContext::Legacy`, no receipt)
+    pub fn from_account_data(data: &[u8]) -> Result<Self> {
+        require!(
+            matches!(data.len(), Self::LEGACY_LEN | Self::LEN),
+            ErrorCode::AccountDidNotDeserialize
+        );
+        let mut padded = [0u8; Self::LEN];
+        padded[..data.len()].copy_from_slice(data);
+        Self::try_deserialize(&mut &padded[..])
+    }
 }
 
 /// PDA helpers for SpentNullifierV2
@@ -139,4 +275,96 @@ mod tests {
     fn test_space() {
         assert!(SpentNullifierV2::LEN < 200);
     }
+
+    #[test]
+    fn test_spend_context_recorded() {
+        let mut record = SpentNullifierV2 {
+            pool: Pubkey::default(),
+            nullifier_hash: [0u8; 32],
+            asset_id: [0u8; 32],
+            spend_type: 0,
+            spent_at: 0,
+            spent_slot: 0,
+            relayer: Pubkey::default(),
+            bump: 0,
+            spend_context: 0,
+            note_receipt: [0u8; NOTE_RECEIPT_LEN],
+        };
+        record.initialize(
+            Pubkey::new_unique(),
+            [1u8; 32],
+            [2u8; 32],
+            SpendContext::ShieldedCpi,
+            100,
+            10,
+            Pubkey::new_unique(),
+            255,
+        );
+        assert_eq!(record.get_spend_type(), Some(SpendType::ShieldedAction));
+        assert_eq!(record.get_spend_context(), Some(SpendContext::ShieldedCpi));
+
+        let mut data = Vec::new();
+        record.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), SpentNullifierV2::LEN);
+        assert_eq!(
+            SpentNullifierV2::spend_context_from_data(&data),
+            Some(SpendContext::ShieldedCpi)
+        );
+        assert_eq!(
+            SpentNullifierV2::spend_context_from_data(&data[..SpentNullifierV2::LEGACY_LEN]),
+            Some(SpendContext::Legacy)
+        );
+    }
+
+    #[test]
+    fn test_note_receipt() {
+        let mut record = SpentNullifierV2 {
+            pool: Pubkey::new_unique(),
+            nullifier_hash: [1u8; 32],
+            asset_id: [2u8; 32],
+            spend_type: 0,
+            spent_at: 100,
+            spent_slot: 10,
+            relayer: Pubkey::new_unique(),
+            bump: 255,
+            spend_context: SpendContext::WithdrawMasp as u8,
+            note_receipt: [0u8; NOTE_RECEIPT_LEN],
+        };
+        assert!(!record.has_note_receipt());
+        assert!(record.record_note_receipt([0u8; NOTE_RECEIPT_LEN]).is_err());
+        record.record_note_receipt([9u8; NOTE_RECEIPT_LEN]).unwrap();
+        assert!(record.record_note_receipt([8u8; NOTE_RECEIPT_LEN]).is_err());
+        assert_eq!(record.note_receipt, [9u8; NOTE_RECEIPT_LEN]);
+
+        let mut data = Vec::new();
+        record.try_serialize(&mut data).unwrap();
+        let read = SpentNullifierV2::from_account_data(&data).unwrap();
+        assert_eq!(read.note_receipt, [9u8; NOTE_RECEIPT_LEN]);
+
+        // Legacy records read with the fields they predate zeroed
+        let legacy =
+            SpentNullifierV2::from_account_data(&data[..SpentNullifierV2::LEGACY_LEN]).unwrap();
+        assert_eq!(legacy.get_spend_context(), Some(SpendContext::Legacy));
+        assert_eq!(legacy.relayer, record.relayer);
+        assert_eq!(legacy.nullifier_hash, [1u8; 32]);
+        assert!(!legacy.has_note_receipt());
+        assert!(SpentNullifierV2::from_account_data(&data[..40]).is_err());
+    }
+
+    #[test]
+    fn test_withdraw_contexts_are_distinct_withdrawals() {
+        let contexts = [
+            SpendContext::WithdrawMasp,
+            SpendContext::WithdrawV2,
+            SpendContext::WithdrawYield,
+            SpendContext::ComplianceRecovery,
+            SpendContext::DisclosedPath,
+        ];
+        for context in contexts {
+            assert_eq!(context.spend_type(), SpendType::Withdraw);
+            assert_eq!(SpendContext::from_u8(context as u8), Some(context));
+        }
+        assert_eq!(SpendContext::JoinSplit.spend_type(), SpendType::JoinSplit);
+        assert_eq!(SpendContext::from_u8(8), None);
+    }
 }
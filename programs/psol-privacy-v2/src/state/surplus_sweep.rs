@@ -0,0 +1,105 @@
+//! Surplus Sweep - timelocked recovery of tokens sent straight to a vault
+//!
+//! Tokens transferred to a vault token account outside `deposit_masp`
+//! (donations, airdrops, mistaken transfers) raise its balance above the
+//! asset's `shielded_balance` without backing any note. The authority can
+//! move that surplus into the asset's treasury token account, but only
+//! `SURPLUS_SWEEP_DELAY_SECONDS` after announcing the sweep, and never more
+//! than the surplus at the time it executes.
+//!
+//! PDA Seeds: `[b"surplus_sweep", asset_vault.key().as_ref()]`
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+
+/// Delay between requesting and executing a surplus sweep (2 days)
+pub const SURPLUS_SWEEP_DELAY_SECONDS: i64 = 2 * 24 * 60 * 60;
+
+/// Pending surplus sweep for one asset vault
+#[account]
+#[derive(InitSpace)]
+pub struct SurplusSweep {
+    /// Parent pool configuration
+    pub pool: Pubkey,
+
+    /// Asset vault being swept
+    pub asset_vault: Pubkey,
+
+    /// Timestamp the sweep was requested
+    pub requested_at: i64,
+
+    /// Timestamp from which the sweep may execute
+    pub executable_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved for future use
+    pub _reserved: [u8; 16],
+}
+
+impl SurplusSweep {
+    pub const SEED_PREFIX: &'static [u8] = b"surplus_sweep";
+
+    pub const LEN: usize = 8  // discriminator
+        + 32  // pool
+        + 32  // asset_vault
+        + 8   // requested_at
+        + 8   // executable_at
+        + 1   // bump
+        + 16; // reserved
+
+    pub fn initialize(&mut self, pool: Pubkey, asset_vault: Pubkey, bump: u8, timestamp: i64) {
+        self.pool = pool;
+        self.asset_vault = asset_vault;
+        self.requested_at = timestamp;
+        self.executable_at = timestamp.saturating_add(SURPLUS_SWEEP_DELAY_SECONDS);
+        self.bump = bump;
+        self._reserved = [0u8; 16];
+    }
+
+    /// Whether the timelock has passed at `timestamp`
+    pub fn require_executable(&self, timestamp: i64) -> Result<()> {
+        require!(
+            timestamp >= self.executable_at,
+            PrivacyErrorV2::SurplusSweepTimelocked
+        );
+        Ok(())
+    }
+
+    /// Tokens held beyond what shielded notes are owed
+    pub fn surplus(vault_balance: u64, shielded_balance: u64) -> u64 {
+        vault_balance.saturating_sub(shielded_balance)
+    }
+
+    pub fn find_pda(program_id: &Pubkey, asset_vault: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, asset_vault.as_ref()], program_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sweep_timelock_and_surplus() {
+        let mut sweep = SurplusSweep {
+            pool: Pubkey::default(),
+            asset_vault: Pubkey::default(),
+            requested_at: 0,
+            executable_at: 0,
+            bump: 0,
+            _reserved: [0u8; 16],
+        };
+        sweep.initialize(Pubkey::new_unique(), Pubkey::new_unique(), 255, 1_000);
+        assert_eq!(sweep.executable_at, 1_000 + SURPLUS_SWEEP_DELAY_SECONDS);
+        assert!(sweep.require_executable(sweep.executable_at - 1).is_err());
+        assert!(sweep.require_executable(sweep.executable_at).is_ok());
+
+        assert_eq!(SurplusSweep::surplus(1_500, 1_000), 500);
+        assert_eq!(SurplusSweep::surplus(1_000, 1_000), 0);
+        // A vault short of its notes has nothing to sweep
+        assert_eq!(SurplusSweep::surplus(900, 1_000), 0);
+    }
+}
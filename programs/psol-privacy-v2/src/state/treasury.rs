@@ -0,0 +1,265 @@
+//! Treasury - the pool's protocol fee custody and disbursement timelock
+//!
+//! Protocol fees on token withdrawals are paid into each asset's treasury
+//! token account (`AssetVault::TREASURY_SEED_PREFIX`), as is relayer stake
+//! taken by `slash_relayer`; fees on native SOL
+//! withdrawals stay in the SOL vault until the authority collects them into
+//! this account's lamports. Moving either out takes a disbursement that the
+//! authority announces with its asset, destination and amount, and may only
+//! execute `TREASURY_DISBURSEMENT_DELAY_SECONDS` later. One disbursement is
+//! pending at a time. Relayer gas rebates (`claim_gas_rebate`) are the one
+//! other way SOL leaves the treasury.
+//!
+//! PDA Seeds: `[b"pool_treasury", pool.key().as_ref()]`
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+
+/// Delay between requesting and executing a disbursement (2 days)
+pub const TREASURY_DISBURSEMENT_DELAY_SECONDS: i64 = 2 * 24 * 60 * 60;
+
+/// A pool's treasury: collected SOL fees and the pending disbursement
+#[account]
+#[derive(InitSpace)]
+pub struct Treasury {
+    /// Parent pool configuration
+    pub pool: Pubkey,
+
+    /// Lamports collected from native SOL vaults over the treasury's life
+    pub sol_collected: u64,
+
+    /// Lamports disbursed over the treasury's life
+    pub sol_disbursed: u64,
+
+    /// Lamports paid out as relayer gas rebates over the treasury's life
+    pub sol_rebated: u64,
+
+    /// Disbursements executed (SOL and tokens)
+    pub disbursement_count: u64,
+
+    /// Whether a disbursement is pending
+    pub has_pending: bool,
+
+    /// Asset of the pending disbursement
+    pub pending_asset_id: [u8; 32],
+
+    /// Account receiving the pending disbursement (a system account for
+    /// SOL, a token account of the asset's mint otherwise)
+    pub pending_destination: Pubkey,
+
+    /// Amount of the pending disbursement
+    pub pending_amount: u64,
+
+    /// Timestamp from which the pending disbursement may execute
+    pub pending_executable_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Reserved for future use
+    pub _reserved: [u8; 24],
+}
+
+impl Treasury {
+    pub const SEED_PREFIX: &'static [u8] = b"pool_treasury";
+
+    pub const LEN: usize = 8  // discriminator
+        + 32  // pool
+        + 8   // sol_collected
+        + 8   // sol_disbursed
+        + 8   // sol_rebated
+        + 8   // disbursement_count
+        + 1   // has_pending
+        + 32  // pending_asset_id
+        + 32  // pending_destination
+        + 8   // pending_amount
+        + 8   // pending_executable_at
+        + 1   // bump
+        + 24; // reserved
+
+    pub fn initialize(&mut self, pool: Pubkey, bump: u8) {
+        self.pool = pool;
+        self.sol_collected = 0;
+        self.sol_disbursed = 0;
+        self.sol_rebated = 0;
+        self.disbursement_count = 0;
+        self.clear_pending();
+        self.bump = bump;
+        self._reserved = [0u8; 24];
+    }
+
+    /// Record lamports collected from a native SOL vault
+    pub fn record_collected(&mut self, lamports: u64) -> Result<()> {
+        self.sol_collected = self
+            .sol_collected
+            .checked_add(lamports)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        Ok(())
+    }
+
+    /// Record lamports paid out as a relayer gas rebate
+    pub fn record_rebated(&mut self, lamports: u64) -> Result<()> {
+        self.sol_rebated = self
+            .sol_rebated
+            .checked_add(lamports)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        Ok(())
+    }
+
+    /// Announce a disbursement, executable after the timelock
+    pub fn request_disbursement(
+        &mut self,
+        asset_id: [u8; 32],
+        destination: Pubkey,
+        amount: u64,
+        timestamp: i64,
+    ) -> Result<()> {
+        require!(
+            !self.has_pending,
+            PrivacyErrorV2::TreasuryDisbursementPending
+        );
+        require!(amount > 0, PrivacyErrorV2::InvalidAmount);
+        self.has_pending = true;
+        self.pending_asset_id = asset_id;
+        self.pending_destination = destination;
+        self.pending_amount = amount;
+        self.pending_executable_at = timestamp.saturating_add(TREASURY_DISBURSEMENT_DELAY_SECONDS);
+        Ok(())
+    }
+
+    /// Take the pending disbursement for `asset_id` once its timelock has
+    /// passed at `timestamp`, returning its amount
+    pub fn take_disbursement(
+        &mut self,
+        asset_id: &[u8; 32],
+        native_sol: bool,
+        timestamp: i64,
+    ) -> Result<u64> {
+        self.require_pending()?;
+        require!(
+            self.pending_asset_id == *asset_id,
+            PrivacyErrorV2::AssetIdMismatch
+        );
+        require!(
+            timestamp >= self.pending_executable_at,
+            PrivacyErrorV2::TreasuryDisbursementTimelocked
+        );
+        let amount = self.pending_amount;
+        if native_sol {
+            self.sol_disbursed = self
+                .sol_disbursed
+                .checked_add(amount)
+                .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        }
+        self.disbursement_count = self
+            .disbursement_count
+            .checked_add(1)
+            .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow))?;
+        self.clear_pending();
+        Ok(amount)
+    }
+
+    /// Withdraw the pending disbursement without executing it
+    pub fn cancel_disbursement(&mut self) -> Result<()> {
+        self.require_pending()?;
+        self.clear_pending();
+        Ok(())
+    }
+
+    fn require_pending(&self) -> Result<()> {
+        require!(self.has_pending, PrivacyErrorV2::NoTreasuryDisbursement);
+        Ok(())
+    }
+
+    fn clear_pending(&mut self) {
+        self.has_pending = false;
+        self.pending_asset_id = [0u8; 32];
+        self.pending_destination = Pubkey::default();
+        self.pending_amount = 0;
+        self.pending_executable_at = 0;
+    }
+
+    pub fn find_pda(program_id: &Pubkey, pool: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, pool.as_ref()], program_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn treasury() -> Treasury {
+        let mut treasury = Treasury {
+            pool: Pubkey::default(),
+            sol_collected: 0,
+            sol_disbursed: 0,
+            sol_rebated: 0,
+            disbursement_count: 0,
+            has_pending: false,
+            pending_asset_id: [0u8; 32],
+            pending_destination: Pubkey::default(),
+            pending_amount: 0,
+            pending_executable_at: 0,
+            bump: 0,
+            _reserved: [0u8; 24],
+        };
+        treasury.initialize(Pubkey::new_unique(), 255);
+        treasury
+    }
+
+    #[test]
+    fn test_disbursement_timelock() {
+        let mut treasury = treasury();
+        let asset = [1u8; 32];
+        let destination = Pubkey::new_unique();
+
+        assert!(treasury.take_disbursement(&asset, true, 0).is_err());
+        assert!(treasury.cancel_disbursement().is_err());
+        assert!(treasury
+            .request_disbursement(asset, destination, 0, 1_000)
+            .is_err());
+
+        treasury
+            .request_disbursement(asset, destination, 500, 1_000)
+            .unwrap();
+        assert_eq!(
+            treasury.pending_executable_at,
+            1_000 + TREASURY_DISBURSEMENT_DELAY_SECONDS
+        );
+        // One pending disbursement at a time
+        assert!(treasury
+            .request_disbursement(asset, destination, 1, 1_000)
+            .is_err());
+
+        let executable_at = treasury.pending_executable_at;
+        assert!(treasury
+            .take_disbursement(&asset, true, executable_at - 1)
+            .is_err());
+        assert!(treasury
+            .take_disbursement(&[2u8; 32], true, executable_at)
+            .is_err());
+        assert_eq!(
+            treasury
+                .take_disbursement(&asset, true, executable_at)
+                .unwrap(),
+            500
+        );
+        assert!(!treasury.has_pending);
+        assert_eq!(treasury.sol_disbursed, 500);
+        assert_eq!(treasury.disbursement_count, 1);
+
+        // Token disbursements leave the SOL counter alone
+        treasury
+            .request_disbursement(asset, destination, 700, 2_000)
+            .unwrap();
+        treasury.cancel_disbursement().unwrap();
+        assert!(!treasury.has_pending);
+        treasury
+            .request_disbursement(asset, destination, 700, 2_000)
+            .unwrap();
+        treasury.take_disbursement(&asset, false, i64::MAX).unwrap();
+        assert_eq!(treasury.sol_disbursed, 500);
+        assert_eq!(treasury.disbursement_count, 2);
+    }
+}
@@ -1,5 +1,12 @@
 //! Verification Key storage for Groth16 proofs - pSOL v2
+//!
+//! Each VK may carry the hash of the trusted-setup (MPC ceremony) transcript
+//! it came out of and the number of contributors, so third parties can match
+//! the on-chain key to the published ceremony artifacts. A zero hash means
+//! no ceremony was recorded (e.g. a single-party development setup); keys
+//! set before these fields existed can record theirs once afterwards.
 
+use crate::error::PrivacyErrorV2;
 use crate::ProofType;
 use anchor_lang::prelude::*;
 
@@ -19,11 +26,18 @@ pub struct VerificationKeyAccountV2 {
     pub set_at: i64,
     pub locked_at: i64,
     pub vk_hash: [u8; 32],
-    pub _reserved: [u8; 32],
+    /// A known-good proof verified against this exact VK (reset on every set)
+    pub canary_passed: bool,
+    pub canary_passed_at: i64,
+    /// Hash of the MPC ceremony transcript the key came from (zero if none)
+    pub ceremony_transcript_hash: [u8; 32],
+    /// Number of ceremony contributors (zero if none)
+    pub ceremony_contributors: u32,
+    pub _reserved: [u8; 23],
 }
 
 impl VerificationKeyAccountV2 {
-    pub fn space(max_ic_points: u8) -> usize {
+    pub const fn space(max_ic_points: u8) -> usize {
         8 + 32
             + 1
             + 64
@@ -39,25 +53,48 @@ impl VerificationKeyAccountV2 {
             + 8
             + 8
             + 32
+            + 1
+            + 8
             + 32
+            + 4
+            + 23
     }
 
-    pub fn expected_ic_points(proof_type: ProofType) -> u8 {
+    pub const fn expected_ic_points(proof_type: ProofType) -> u8 {
         match proof_type {
             ProofType::Deposit => 4,
             ProofType::Withdraw => 9,
-            ProofType::JoinSplit => 10,
+            ProofType::JoinSplit => 11,
             ProofType::Membership => 5,
             ProofType::MerkleBatchUpdate => 6,
             ProofType::WithdrawV2 => 13,
         }
     }
 
+    /// Deposit VK of `deposit_bound.circom`, which adds the depositor as a
+    /// 4th public input; legacy 3-input deposit VKs remain accepted
+    pub const DEPOSITOR_BOUND_DEPOSIT_IC_POINTS: u8 = 5;
+
+    /// Whether a VK with `ic_len` IC points fits `proof_type`
+    pub fn accepts_ic_points(proof_type: ProofType, ic_len: u8) -> bool {
+        ic_len == Self::expected_ic_points(proof_type)
+            || (proof_type == ProofType::Deposit
+                && ic_len == Self::DEPOSITOR_BOUND_DEPOSIT_IC_POINTS)
+    }
+
     pub fn expected_public_inputs_for_type(proof_type: ProofType) -> u8 {
         Self::expected_ic_points(proof_type) - 1
     }
 
     pub const DEFAULT_MAX_IC_POINTS: u8 = 15;
+
+    /// Size of VK accounts created before the canary and ceremony fields,
+    /// when `vk_hash` was followed only by a 32-byte reserve; grown to
+    /// `space(DEFAULT_MAX_IC_POINTS)` by migrate_verification_key
+    pub const LEGACY_SPACE: usize = Self::space(Self::DEFAULT_MAX_IC_POINTS) - Self::LEGACY_GROWTH;
+
+    /// Bytes the fields after `vk_hash` take beyond the legacy reserve
+    pub const LEGACY_GROWTH: usize = 1 + 8 + 32 + 4 + 23 - 32;
     pub const SEED_PREFIX: &'static [u8] = b"vk_v2";
 
     pub fn initialize(&mut self, pool: Pubkey, proof_type: ProofType, bump: u8) {
@@ -75,7 +112,10 @@ impl VerificationKeyAccountV2 {
         self.set_at = 0;
         self.locked_at = 0;
         self.vk_hash = [0u8; 32];
-        self._reserved = [0u8; 32];
+        self.reset_canary();
+        self.ceremony_transcript_hash = [0u8; 32];
+        self.ceremony_contributors = 0;
+        self._reserved = [0u8; 23];
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -97,6 +137,49 @@ impl VerificationKeyAccountV2 {
         self.is_initialized = true;
         self.set_at = timestamp;
         self.vk_hash = self.compute_vk_hash();
+        self.reset_canary();
+    }
+
+    /// Record the ceremony the key came from; a transcript hash needs at
+    /// least one contributor, and no transcript means no contributors
+    pub fn set_ceremony(&mut self, transcript_hash: [u8; 32], contributors: u32) -> Result<()> {
+        require!(
+            (transcript_hash == [0u8; 32]) == (contributors == 0),
+            PrivacyErrorV2::InvalidCeremonyTranscript
+        );
+        self.ceremony_transcript_hash = transcript_hash;
+        self.ceremony_contributors = contributors;
+        Ok(())
+    }
+
+    /// Record the ceremony of a key that has none yet; unlike `set_ceremony`
+    /// this never overwrites or clears one
+    pub fn record_ceremony(&mut self, transcript_hash: [u8; 32], contributors: u32) -> Result<()> {
+        require!(
+            !self.has_ceremony(),
+            PrivacyErrorV2::CeremonyAlreadyRecorded
+        );
+        require!(
+            transcript_hash != [0u8; 32],
+            PrivacyErrorV2::InvalidCeremonyTranscript
+        );
+        self.set_ceremony(transcript_hash, contributors)
+    }
+
+    pub fn has_ceremony(&self) -> bool {
+        self.ceremony_transcript_hash != [0u8; 32]
+    }
+
+    /// Record that a canary proof verified against the current VK
+    pub fn mark_canary_passed(&mut self, timestamp: i64) {
+        self.canary_passed = true;
+        self.canary_passed_at = timestamp;
+    }
+
+    /// Invalidate the canary result; any change to the key requires a new one
+    pub fn reset_canary(&mut self) {
+        self.canary_passed = false;
+        self.canary_passed_at = 0;
     }
 
     pub fn lock(&mut self, timestamp: i64) {
@@ -118,14 +201,20 @@ impl VerificationKeyAccountV2 {
 
     pub fn validate_ic_length(&self) -> bool {
         if let Some(proof_type) = self.get_proof_type() {
-            self.vk_ic_len == Self::expected_ic_points(proof_type)
+            Self::accepts_ic_points(proof_type, self.vk_ic_len)
         } else {
             false
         }
     }
 
     pub fn validate_ic_length_for_type(proof_type: ProofType, ic_len: u8) -> bool {
-        ic_len == Self::expected_ic_points(proof_type)
+        Self::accepts_ic_points(proof_type, ic_len)
+    }
+
+    /// Deposit proofs against this VK must bind the depositor's key
+    pub fn binds_depositor(&self) -> bool {
+        self.proof_type == ProofType::Deposit as u8
+            && self.vk_ic_len == Self::DEPOSITOR_BOUND_DEPOSIT_IC_POINTS
     }
 
     pub fn get_proof_type(&self) -> Option<ProofType> {
@@ -218,3 +307,214 @@ impl VerificationKeyV2 {
         }
     }
 }
+
+// A VK has one IC point per public input plus one, so each proof type's IC
+// count must agree with the public inputs its instruction builds
+const _: () = {
+    use crate::crypto::groth16::MAX_PUBLIC_INPUTS;
+    use crate::crypto::{
+        DepositPublicInputs, JoinSplitPublicInputs, MembershipPublicInputs, WithdrawPublicInputs,
+        WithdrawV2PublicInputs,
+    };
+    use crate::instructions::private_transfer::{JOIN_SPLIT_INPUTS, JOIN_SPLIT_OUTPUTS};
+
+    const fn ic_inputs(proof_type: ProofType) -> usize {
+        VerificationKeyAccountV2::expected_ic_points(proof_type) as usize - 1
+    }
+
+    assert!(ic_inputs(ProofType::Deposit) == DepositPublicInputs::COUNT);
+    assert!(
+        VerificationKeyAccountV2::DEPOSITOR_BOUND_DEPOSIT_IC_POINTS as usize - 1
+            == DepositPublicInputs::BOUND_COUNT
+    );
+    assert!(ic_inputs(ProofType::Withdraw) == WithdrawPublicInputs::COUNT);
+    assert!(
+        ic_inputs(ProofType::JoinSplit)
+            == JoinSplitPublicInputs::BASE_COUNT + JOIN_SPLIT_INPUTS + JOIN_SPLIT_OUTPUTS
+    );
+    assert!(ic_inputs(ProofType::Membership) == MembershipPublicInputs::COUNT);
+    assert!(ic_inputs(ProofType::WithdrawV2) == WithdrawV2PublicInputs::COUNT);
+
+    // Every type's VK fits the default allocation and the verifier
+    let proof_types = [
+        ProofType::Deposit,
+        ProofType::Withdraw,
+        ProofType::JoinSplit,
+        ProofType::Membership,
+        ProofType::MerkleBatchUpdate,
+        ProofType::WithdrawV2,
+    ];
+    let mut i = 0;
+    while i < proof_types.len() {
+        let ic = VerificationKeyAccountV2::expected_ic_points(proof_types[i]);
+        assert!(ic <= VerificationKeyAccountV2::DEFAULT_MAX_IC_POINTS);
+        assert!(ic as usize - 1 <= MAX_PUBLIC_INPUTS);
+        i += 1;
+    }
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deposit_accepts_depositor_bound_vk() {
+        assert!(VerificationKeyAccountV2::accepts_ic_points(
+            ProofType::Deposit,
+            4
+        ));
+        assert!(VerificationKeyAccountV2::accepts_ic_points(
+            ProofType::Deposit,
+            5
+        ));
+        assert!(!VerificationKeyAccountV2::accepts_ic_points(
+            ProofType::Deposit,
+            6
+        ));
+        assert!(!VerificationKeyAccountV2::accepts_ic_points(
+            ProofType::Withdraw,
+            5
+        ));
+    }
+
+    #[test]
+    fn test_setting_vk_clears_canary() {
+        let mut vk = VerificationKeyAccountV2 {
+            pool: Pubkey::default(),
+            proof_type: 0,
+            vk_alpha_g1: [0u8; 64],
+            vk_beta_g2: [0u8; 128],
+            vk_gamma_g2: [0u8; 128],
+            vk_delta_g2: [0u8; 128],
+            vk_ic_len: 0,
+            vk_ic: Vec::new(),
+            is_initialized: false,
+            is_locked: false,
+            bump: 0,
+            set_at: 0,
+            locked_at: 0,
+            vk_hash: [0u8; 32],
+            canary_passed: false,
+            canary_passed_at: 0,
+            ceremony_transcript_hash: [0u8; 32],
+            ceremony_contributors: 0,
+            _reserved: [0u8; 23],
+        };
+        vk.initialize(Pubkey::default(), ProofType::Withdraw, 255);
+        vk.set_vk(
+            [1u8; 64],
+            [2u8; 128],
+            [3u8; 128],
+            [4u8; 128],
+            vec![[5u8; 64]; 9],
+            10,
+        );
+        vk.mark_canary_passed(11);
+        assert!(vk.canary_passed);
+
+        // Re-uploading a key invalidates the previous canary result
+        vk.set_vk(
+            [1u8; 64],
+            [2u8; 128],
+            [3u8; 128],
+            [9u8; 128],
+            vec![[5u8; 64]; 9],
+            12,
+        );
+        assert!(!vk.canary_passed);
+        assert_eq!(vk.canary_passed_at, 0);
+    }
+
+    #[test]
+    fn test_ceremony_requires_hash_and_contributors_together() {
+        let mut vk = VerificationKeyAccountV2 {
+            pool: Pubkey::default(),
+            proof_type: 0,
+            vk_alpha_g1: [0u8; 64],
+            vk_beta_g2: [0u8; 128],
+            vk_gamma_g2: [0u8; 128],
+            vk_delta_g2: [0u8; 128],
+            vk_ic_len: 0,
+            vk_ic: Vec::new(),
+            is_initialized: false,
+            is_locked: false,
+            bump: 0,
+            set_at: 0,
+            locked_at: 0,
+            vk_hash: [0u8; 32],
+            canary_passed: false,
+            canary_passed_at: 0,
+            ceremony_transcript_hash: [0u8; 32],
+            ceremony_contributors: 0,
+            _reserved: [0u8; 23],
+        };
+        assert!(vk.set_ceremony([1u8; 32], 0).is_err());
+        assert!(vk.set_ceremony([0u8; 32], 3).is_err());
+        assert!(!vk.has_ceremony());
+
+        vk.set_ceremony([1u8; 32], 42).unwrap();
+        assert!(vk.has_ceremony());
+        assert_eq!(vk.ceremony_contributors, 42);
+
+        vk.set_ceremony([0u8; 32], 0).unwrap();
+        assert!(!vk.has_ceremony());
+
+        // Recording afterwards is write-once and never clears
+        assert!(vk.record_ceremony([0u8; 32], 0).is_err());
+        vk.record_ceremony([2u8; 32], 7).unwrap();
+        assert!(vk.record_ceremony([3u8; 32], 9).is_err());
+        assert_eq!(vk.ceremony_transcript_hash, [2u8; 32]);
+        assert_eq!(vk.ceremony_contributors, 7);
+    }
+
+    #[test]
+    fn test_legacy_vk_parses_after_growth() {
+        let mut vk = VerificationKeyAccountV2 {
+            pool: Pubkey::new_unique(),
+            proof_type: 0,
+            vk_alpha_g1: [0u8; 64],
+            vk_beta_g2: [0u8; 128],
+            vk_gamma_g2: [0u8; 128],
+            vk_delta_g2: [0u8; 128],
+            vk_ic_len: 0,
+            vk_ic: Vec::new(),
+            is_initialized: false,
+            is_locked: false,
+            bump: 0,
+            set_at: 0,
+            locked_at: 0,
+            vk_hash: [0u8; 32],
+            canary_passed: false,
+            canary_passed_at: 0,
+            ceremony_transcript_hash: [0u8; 32],
+            ceremony_contributors: 0,
+            _reserved: [0u8; 23],
+        };
+        let max = VerificationKeyAccountV2::DEFAULT_MAX_IC_POINTS as usize;
+        vk.set_vk(
+            [1u8; 64],
+            [2u8; 128],
+            [3u8; 128],
+            [4u8; 128],
+            vec![[5u8; 64]; max],
+            10,
+        );
+        let mut data = Vec::new();
+        vk.try_serialize(&mut data).unwrap();
+        let full = VerificationKeyAccountV2::space(max as u8);
+        assert_eq!(data.len(), full);
+
+        // Legacy layout: `vk_hash` followed by a zeroed 32-byte reserve
+        let hash_end = VerificationKeyAccountV2::LEGACY_SPACE - 32;
+        data.truncate(hash_end);
+        data.resize(VerificationKeyAccountV2::LEGACY_SPACE, 0);
+        assert!(VerificationKeyAccountV2::try_deserialize(&mut &data[..]).is_err());
+
+        data.resize(full, 0);
+        let migrated = VerificationKeyAccountV2::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(migrated.vk_hash, vk.vk_hash);
+        assert!(migrated.verify_integrity());
+        assert!(!migrated.canary_passed);
+        assert!(!migrated.has_ceremony());
+    }
+}
@@ -0,0 +1,137 @@
+//! Withdrawal Commit - Sealed withdrawal parameters ahead of relaying
+//!
+//! A user seals a withdrawal before handing its proof to a relayer: the
+//! `WithdrawalCommit` records only `withdrawal_commit_hash` of the public
+//! inputs (root, nullifier hash, recipient, amount, asset, relayer fee),
+//! which the high-entropy nullifier hash keeps from being guessed. The
+//! proof binds the commit through `public_data_hash`
+//! (`withdrawal_commit_pin_hash`), so it is only valid through
+//! `withdraw_masp` with the commit attached and with exactly the sealed
+//! inputs; the relayer cannot alter the recipient, amount or fee, and the
+//! parameters stay off-chain until the withdrawal is included.
+//!
+//! The commit is closed by the withdrawal, its rent going to the relayer
+//! that executes it, or by its committer to cancel.
+//!
+//! PDA Seeds: `[b"withdrawal_commit", pool.key().as_ref(), commit_hash.as_ref()]`
+
+use anchor_lang::prelude::*;
+
+use crate::crypto::keccak::keccak256_concat;
+
+/// Domain separator of `withdrawal_commit_hash`
+pub const WITHDRAWAL_COMMIT_DOMAIN: &[u8] = b"psol:withdrawal_commit:v1";
+
+/// Hash sealed by a `WithdrawalCommit`: the pool and the withdrawal's
+/// public inputs, except the relayer the proof is handed to
+#[allow(clippy::too_many_arguments)]
+pub fn withdrawal_commit_hash(
+    pool: &Pubkey,
+    merkle_root: &[u8; 32],
+    nullifier_hash: &[u8; 32],
+    recipient: &Pubkey,
+    amount: u64,
+    asset_id: &[u8; 32],
+    relayer_fee: u64,
+) -> [u8; 32] {
+    keccak256_concat(&[
+        WITHDRAWAL_COMMIT_DOMAIN,
+        pool.as_ref(),
+        merkle_root,
+        nullifier_hash,
+        recipient.as_ref(),
+        &amount.to_le_bytes(),
+        asset_id,
+        &relayer_fee.to_le_bytes(),
+    ])
+}
+
+/// `public_data_hash` binding a withdraw proof to a sealed commit, reduced
+/// to a canonical field element (top byte cleared)
+pub fn withdrawal_commit_pin_hash(commit_hash: &[u8; 32]) -> [u8; 32] {
+    let h = keccak256_concat(&[b"psol:withdrawal_commit_pin:v1", commit_hash]);
+    let mut out = [0u8; 32];
+    out[1..32].copy_from_slice(&h[0..31]);
+    out
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct WithdrawalCommit {
+    /// Pool the withdrawal is from
+    pub pool: Pubkey,
+
+    /// Wallet that sealed the withdrawal (may cancel it)
+    pub committer: Pubkey,
+
+    /// `withdrawal_commit_hash` of the withdrawal
+    pub commit_hash: [u8; 32],
+
+    /// Unix timestamp of the commit
+    pub committed_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl WithdrawalCommit {
+    pub const LEN: usize = 8  // discriminator
+        + 32                  // pool
+        + 32                  // committer
+        + 32                  // commit_hash
+        + 8                   // committed_at
+        + 1; // bump
+
+    pub fn initialize(
+        &mut self,
+        pool: Pubkey,
+        committer: Pubkey,
+        commit_hash: [u8; 32],
+        committed_at: i64,
+        bump: u8,
+    ) {
+        self.pool = pool;
+        self.committer = committer;
+        self.commit_hash = commit_hash;
+        self.committed_at = committed_at;
+        self.bump = bump;
+    }
+}
+
+impl WithdrawalCommit {
+    pub const SEED_PREFIX: &'static [u8] = b"withdrawal_commit";
+
+    pub fn find_pda(program_id: &Pubkey, pool: &Pubkey, commit_hash: &[u8; 32]) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, pool.as_ref(), commit_hash], program_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::is_canonical_fr;
+
+    #[test]
+    fn test_commit_hash_covers_every_input() {
+        let pool = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let hash = |root: [u8; 32], recipient: &Pubkey, amount: u64, fee: u64| {
+            withdrawal_commit_hash(&pool, &root, &[2u8; 32], recipient, amount, &[3u8; 32], fee)
+        };
+
+        let base = hash([1u8; 32], &recipient, 1_000, 10);
+        assert_eq!(base, hash([1u8; 32], &recipient, 1_000, 10));
+        assert_ne!(base, hash([9u8; 32], &recipient, 1_000, 10));
+        assert_ne!(base, hash([1u8; 32], &pool, 1_000, 10));
+        assert_ne!(base, hash([1u8; 32], &recipient, 1_001, 10));
+        assert_ne!(base, hash([1u8; 32], &recipient, 1_000, 11));
+    }
+
+    #[test]
+    fn test_commit_pin_hash() {
+        let pin = withdrawal_commit_pin_hash(&[7u8; 32]);
+        assert!(is_canonical_fr(&pin));
+        assert_ne!(pin, [0u8; 32]);
+        assert_ne!(pin, withdrawal_commit_pin_hash(&[8u8; 32]));
+    }
+}
@@ -6,6 +6,7 @@ use anchor_lang::prelude::*;
 
 /// Registry of LST mints requiring yield-gated withdrawals
 #[account]
+#[derive(InitSpace)]
 pub struct YieldRegistry {
     /// Parent pool configuration
     pub pool_config: Pubkey,
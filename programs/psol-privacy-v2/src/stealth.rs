@@ -0,0 +1,263 @@
+//! One-time recipient addresses (host-side reference)
+//!
+//! Withdrawing every time to the same wallet links those withdrawals to each
+//! other even though the proofs do not. Here the sender withdraws to a fresh
+//! ed25519 owner that only the recipient can recognise and spend from:
+//!
+//! 1. the recipient publishes a meta address `(S, B)`: a scan key and a
+//!    spend key
+//! 2. the sender picks an ephemeral `r`, publishes `R = r·G` and derives
+//!    `h = H(8·r·S)` and the one-time owner `P = B + h·G`
+//! 3. the recipient recomputes `h` from `8·s·R`, recognises `P` and signs
+//!    for it with the scalar `b + h`
+//!
+//! `create_one_time_recipient` creates `P`'s token account and emits `R` plus
+//! a one-byte view tag in `OneTimeRecipientCreated`; `StealthKeys::detect`
+//! scans those events. The scan key alone can recognise one-time owners but
+//! not spend from them.
+
+use anchor_lang::prelude::*;
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::Scalar;
+use sha2::{Digest, Sha512};
+
+use crate::error::PrivacyErrorV2;
+use crate::events::OneTimeRecipientCreated;
+
+const SCAN_KEY_DOMAIN: &[u8] = b"psol:stealth:scan:v1";
+const SPEND_KEY_DOMAIN: &[u8] = b"psol:stealth:spend:v1";
+const EPHEMERAL_DOMAIN: &[u8] = b"psol:stealth:ephemeral:v1";
+const TWEAK_DOMAIN: &[u8] = b"psol:stealth:tweak:v1";
+const VIEW_TAG_DOMAIN: &[u8] = b"psol:stealth:tag:v1";
+const NONCE_DOMAIN: &[u8] = b"psol:stealth:nonce:v1";
+
+pub const STEALTH_META_ADDRESS_LEN: usize = 64;
+
+fn hash_to_scalar(parts: &[&[u8]]) -> Scalar {
+    let mut hasher = Sha512::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    Scalar::from_bytes_mod_order_wide(&hasher.finalize().into())
+}
+
+/// Full-order point from its compressed encoding
+fn decompress(bytes: &[u8; 32]) -> Option<EdwardsPoint> {
+    CompressedEdwardsY(*bytes)
+        .decompress()
+        .filter(|point| !point.is_small_order())
+}
+
+/// Tweak and view tag shared by sender and recipient
+fn shared_tweak(shared: &EdwardsPoint) -> (Scalar, u8) {
+    let shared = shared.mul_by_cofactor().compress().to_bytes();
+    let tag = Sha512::new()
+        .chain_update(VIEW_TAG_DOMAIN)
+        .chain_update(shared)
+        .finalize()[0];
+    (hash_to_scalar(&[TWEAK_DOMAIN, &shared]), tag)
+}
+
+/// What a recipient publishes so senders can derive one-time owners
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StealthMetaAddress {
+    pub scan_key: [u8; 32],
+    pub spend_key: [u8; 32],
+}
+
+impl StealthMetaAddress {
+    pub fn to_bytes(&self) -> [u8; STEALTH_META_ADDRESS_LEN] {
+        let mut out = [0u8; STEALTH_META_ADDRESS_LEN];
+        out[..32].copy_from_slice(&self.scan_key);
+        out[32..].copy_from_slice(&self.spend_key);
+        out
+    }
+
+    /// Parses and validates both keys
+    pub fn from_bytes(bytes: &[u8; STEALTH_META_ADDRESS_LEN]) -> Result<Self> {
+        let meta = Self {
+            scan_key: bytes[..32].try_into().unwrap(),
+            spend_key: bytes[32..].try_into().unwrap(),
+        };
+        meta.points()?;
+        Ok(meta)
+    }
+
+    fn points(&self) -> Result<(EdwardsPoint, EdwardsPoint)> {
+        let scan =
+            decompress(&self.scan_key).ok_or(error!(PrivacyErrorV2::InvalidOneTimeAddress))?;
+        let spend =
+            decompress(&self.spend_key).ok_or(error!(PrivacyErrorV2::InvalidOneTimeAddress))?;
+        Ok((scan, spend))
+    }
+}
+
+/// Sender side: the owner to withdraw to and the announcement arguments for
+/// `create_one_time_recipient`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OneTimeAddress {
+    pub owner: Pubkey,
+    pub ephemeral_pubkey: [u8; 32],
+    pub view_tag: u8,
+}
+
+/// Derives a one-time owner for `meta`; `ephemeral_seed` must be fresh
+/// randomness for every withdrawal
+pub fn derive_one_time_address(
+    meta: &StealthMetaAddress,
+    ephemeral_seed: &[u8],
+) -> Result<OneTimeAddress> {
+    let (scan, spend) = meta.points()?;
+    let ephemeral = hash_to_scalar(&[EPHEMERAL_DOMAIN, ephemeral_seed]);
+    let (tweak, view_tag) = shared_tweak(&(ephemeral * scan));
+    let owner = spend + EdwardsPoint::mul_base(&tweak);
+
+    Ok(OneTimeAddress {
+        owner: Pubkey::new_from_array(owner.compress().to_bytes()),
+        ephemeral_pubkey: EdwardsPoint::mul_base(&ephemeral).compress().to_bytes(),
+        view_tag,
+    })
+}
+
+/// Recipient side: scan and spend secrets
+pub struct StealthKeys {
+    scan: Scalar,
+    spend: Scalar,
+}
+
+impl StealthKeys {
+    pub fn from_seed(seed: &[u8]) -> Self {
+        Self {
+            scan: hash_to_scalar(&[SCAN_KEY_DOMAIN, seed]),
+            spend: hash_to_scalar(&[SPEND_KEY_DOMAIN, seed]),
+        }
+    }
+
+    pub fn meta_address(&self) -> StealthMetaAddress {
+        StealthMetaAddress {
+            scan_key: EdwardsPoint::mul_base(&self.scan).compress().to_bytes(),
+            spend_key: EdwardsPoint::mul_base(&self.spend).compress().to_bytes(),
+        }
+    }
+
+    /// Recognises `owner` as one of ours, returning the key that signs for it
+    pub fn detect(
+        &self,
+        ephemeral_pubkey: &[u8; 32],
+        view_tag: u8,
+        owner: &Pubkey,
+    ) -> Option<OneTimeKey> {
+        let ephemeral = decompress(ephemeral_pubkey)?;
+        let (tweak, tag) = shared_tweak(&(self.scan * ephemeral));
+        if tag != view_tag {
+            return None;
+        }
+
+        let secret = self.spend + tweak;
+        let expected = EdwardsPoint::mul_base(&secret).compress().to_bytes();
+        (expected == owner.to_bytes()).then_some(OneTimeKey {
+            owner: *owner,
+            secret,
+        })
+    }
+
+    pub fn detect_event(&self, event: &OneTimeRecipientCreated) -> Option<OneTimeKey> {
+        self.detect(&event.ephemeral_pubkey, event.view_tag, &event.owner)
+    }
+}
+
+/// Signing key of one detected one-time owner
+///
+/// There is no seed behind it, so it cannot become a `Keypair`; `sign`
+/// produces standard ed25519 signatures for transactions spending from the
+/// owner's token account.
+pub struct OneTimeKey {
+    pub owner: Pubkey,
+    secret: Scalar,
+}
+
+impl OneTimeKey {
+    pub fn sign(&self, message: &[u8]) -> [u8; 64] {
+        let nonce = hash_to_scalar(&[NONCE_DOMAIN, self.secret.as_bytes(), message]);
+        let r = EdwardsPoint::mul_base(&nonce).compress().to_bytes();
+        let challenge = hash_to_scalar(&[&r, self.owner.as_ref(), message]);
+        let s = nonce + challenge * self.secret;
+
+        let mut signature = [0u8; 64];
+        signature[..32].copy_from_slice(&r);
+        signature[32..].copy_from_slice(s.as_bytes());
+        signature
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn announced(address: &OneTimeAddress) -> OneTimeRecipientCreated {
+        OneTimeRecipientCreated {
+            pool: Pubkey::new_unique(),
+            asset_id: [1u8; 32],
+            owner: address.owner,
+            ephemeral_pubkey: address.ephemeral_pubkey,
+            view_tag: address.view_tag,
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_recipient_detects_and_signs() {
+        let keys = StealthKeys::from_seed(b"recipient");
+        let meta = StealthMetaAddress::from_bytes(&keys.meta_address().to_bytes()).unwrap();
+
+        let address = derive_one_time_address(&meta, b"withdrawal 1").unwrap();
+        let key = keys.detect_event(&announced(&address)).unwrap();
+        assert_eq!(key.owner, address.owner);
+
+        let message = b"spend from the one-time account";
+        let signature = key.sign(message);
+        let public = ed25519_dalek::PublicKey::from_bytes(address.owner.as_ref()).unwrap();
+        let signature = ed25519_dalek::Signature::from_bytes(&signature).unwrap();
+        public.verify_strict(message, &signature).unwrap();
+    }
+
+    #[test]
+    fn test_owners_are_unlinkable_and_private() {
+        let keys = StealthKeys::from_seed(b"recipient");
+        let meta = keys.meta_address();
+        let first = derive_one_time_address(&meta, b"withdrawal 1").unwrap();
+        let second = derive_one_time_address(&meta, b"withdrawal 2").unwrap();
+        assert_ne!(first.owner, second.owner);
+        assert_ne!(first.owner.to_bytes(), meta.spend_key);
+
+        // Someone else's keys do not recognise the owner
+        let other = StealthKeys::from_seed(b"someone else");
+        assert!(other.detect_event(&announced(&first)).is_none());
+
+        // A mismatched announcement is not ours either
+        let mut swapped = announced(&first);
+        swapped.owner = second.owner;
+        assert!(keys.detect_event(&swapped).is_none());
+    }
+
+    #[test]
+    fn test_invalid_keys_rejected() {
+        let meta = StealthKeys::from_seed(b"recipient").meta_address();
+
+        // Identity point (small order) as scan key
+        let mut identity = [0u8; 32];
+        identity[0] = 1;
+        let bad = StealthMetaAddress {
+            scan_key: identity,
+            ..meta
+        };
+        assert_eq!(
+            derive_one_time_address(&bad, b"seed").unwrap_err(),
+            PrivacyErrorV2::InvalidOneTimeAddress.into()
+        );
+        assert!(StealthMetaAddress::from_bytes(&bad.to_bytes()).is_err());
+
+        let keys = StealthKeys::from_seed(b"recipient");
+        assert!(keys.detect(&identity, 0, &Pubkey::new_unique()).is_none());
+    }
+}
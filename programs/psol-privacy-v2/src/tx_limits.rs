@@ -0,0 +1,208 @@
+//! Transaction size and account limits
+//!
+//! A transaction may lock at most `MAX_TRANSACTION_ACCOUNT_LOCKS` accounts
+//! (lookup tables included) and a CPI may pass at most
+//! `MAX_CPI_ACCOUNT_INFOS` account infos. Past those the runtime aborts
+//! without saying which input was too large, so instructions taking a
+//! variable number of remaining accounts, or variable-length data they
+//! forward to another program, check them against the limits below first
+//! and fail with `TooManyRemainingAccounts`, `RemainingAccountsMismatch` or
+//! `InstructionDataTooLarge`, logging the count and the limit. The limits
+//! are exported as IDL constants so clients can check before submitting.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::instructions::private_transfer::JOIN_SPLIT_INPUTS;
+use crate::instructions::register_assets_batch::{ACCOUNTS_PER_ASSET, MAX_ASSETS_PER_BATCH};
+use crate::instructions::shielded_cpi::dex_swap::MAX_ROUTE_DATA_LEN;
+
+/// Accounts one transaction may lock, lookup tables included
+#[constant]
+pub const MAX_TRANSACTION_ACCOUNT_LOCKS: u32 = 64;
+
+/// Account infos one CPI may pass
+#[constant]
+pub const MAX_CPI_ACCOUNT_INFOS: u32 = 64;
+
+/// Fixed accounts of `execute_shielded_action`
+pub const EXECUTE_SHIELDED_ACTION_ACCOUNTS: u32 = 22;
+
+/// Fixed accounts of `batch_process_deposits`
+pub const BATCH_PROCESS_DEPOSITS_ACCOUNTS: u32 = 8;
+
+/// Account infos `batch_process_deposits` adds to a deposit hook callback
+/// (hook, pool configuration, callback program)
+pub const DEPOSIT_HOOK_CALLBACK_ACCOUNTS: u32 = 3;
+
+/// `private_transfer_join_split`: one spent nullifier account per input
+#[constant]
+pub const MAX_JOIN_SPLIT_REMAINING_ACCOUNTS: u32 = JOIN_SPLIT_INPUTS as u32;
+
+/// `execute_shielded_action`: the adapter's accounts
+#[constant]
+pub const MAX_SHIELDED_ACTION_REMAINING_ACCOUNTS: u32 =
+    MAX_TRANSACTION_ACCOUNT_LOCKS - EXECUTE_SHIELDED_ACTION_ACCOUNTS;
+
+/// `batch_process_deposits`: accounts forwarded to the deposit hook
+#[constant]
+pub const MAX_DEPOSIT_HOOK_REMAINING_ACCOUNTS: u32 = min(
+    MAX_TRANSACTION_ACCOUNT_LOCKS - BATCH_PROCESS_DEPOSITS_ACCOUNTS,
+    MAX_CPI_ACCOUNT_INFOS - DEPOSIT_HOOK_CALLBACK_ACCOUNTS,
+);
+
+/// `register_assets_batch`: mint, vault and vault token account per asset
+#[constant]
+pub const MAX_REGISTER_ASSETS_REMAINING_ACCOUNTS: u32 =
+    (MAX_ASSETS_PER_BATCH * ACCOUNTS_PER_ASSET) as u32;
+
+/// `execute_shielded_action`: the largest valid action data, a
+/// `ShieldedSpend` followed by `DexSwapParams` with a full route
+#[constant]
+pub const MAX_ACTION_DATA_BYTES: u32 = 112 // ShieldedSpend
+    + 8  // quoted_output
+    + 8  // min_output
+    + 2  // slippage_bps
+    + 4 + MAX_ROUTE_DATA_LEN as u32; // route_data
+
+const fn min(a: u32, b: u32) -> u32 {
+    if a < b {
+        a
+    } else {
+        b
+    }
+}
+
+/// Require at most `max` remaining accounts for `instruction`
+pub fn require_remaining_accounts_within(instruction: &str, count: usize, max: u32) -> Result<()> {
+    if count > max as usize {
+        msg!(
+            "{}: {} remaining accounts, at most {} allowed",
+            instruction,
+            count,
+            max
+        );
+        return err!(PrivacyErrorV2::TooManyRemainingAccounts);
+    }
+    Ok(())
+}
+
+/// Require exactly `expected` remaining accounts for `instruction`
+pub fn require_remaining_accounts_exact(
+    instruction: &str,
+    count: usize,
+    expected: usize,
+) -> Result<()> {
+    if count != expected {
+        msg!(
+            "{}: {} remaining accounts, expected {}",
+            instruction,
+            count,
+            expected
+        );
+        return err!(PrivacyErrorV2::RemainingAccountsMismatch);
+    }
+    Ok(())
+}
+
+/// Require `what` of `instruction` to be at most `max` bytes
+pub fn require_data_within(instruction: &str, what: &str, len: usize, max: u32) -> Result<()> {
+    if len > max as usize {
+        msg!(
+            "{}: {} is {} bytes, at most {} allowed",
+            instruction,
+            what,
+            len,
+            max
+        );
+        return err!(PrivacyErrorV2::InstructionDataTooLarge);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::shielded_cpi::dex_swap::DexSwapParams;
+    use crate::instructions::shielded_cpi::execute_action::{parse_action_data, ShieldedSpend};
+    use anchor_lang::ToAccountMetas;
+
+    #[test]
+    fn test_fixed_account_counts() {
+        let key = Pubkey::new_unique();
+        let action = crate::accounts::ExecuteShieldedAction {
+            relayer: key,
+            pool_config: key,
+            global_config: key,
+            merkle_tree: key,
+            vk_account: key,
+            input_vault: key,
+            input_vault_token_account: key,
+            input_mint: key,
+            output_vault: key,
+            output_vault_token_account: key,
+            output_mint: key,
+            action_authority: key,
+            input_escrow: key,
+            output_escrow: key,
+            spent_nullifier: key,
+            relayer_token_account: None,
+            action_allowlist: key,
+            target_program: key,
+            token_program: key,
+            system_program: key,
+            risk_tiers: None,
+            withdrawal_window: None,
+        };
+        assert_eq!(
+            action.to_account_metas(None).len(),
+            EXECUTE_SHIELDED_ACTION_ACCOUNTS as usize
+        );
+
+        let batch = crate::accounts::BatchProcessDeposits {
+            batcher: key,
+            pool_config: key,
+            global_config: key,
+            merkle_tree: key,
+            pending_buffer: key,
+            batcher_role: None,
+            deposit_hook: None,
+            callback_program: None,
+        };
+        assert_eq!(
+            batch.to_account_metas(None).len(),
+            BATCH_PROCESS_DEPOSITS_ACCOUNTS as usize
+        );
+    }
+
+    #[test]
+    fn test_largest_action_data_parses() {
+        let spend = ShieldedSpend {
+            merkle_root: [1; 32],
+            nullifier_hash: [2; 32],
+            amount: 1_000,
+            relayer_fee: 0,
+            output_commitment: [3; 32],
+        };
+        let params = DexSwapParams {
+            quoted_output: 1_000,
+            min_output: 990,
+            slippage_bps: 100,
+            route_data: vec![0; MAX_ROUTE_DATA_LEN],
+        };
+        let mut data = spend.try_to_vec().unwrap();
+        params.serialize(&mut data).unwrap();
+        assert_eq!(data.len(), MAX_ACTION_DATA_BYTES as usize);
+        assert!(parse_action_data::<DexSwapParams>(&data).is_ok());
+    }
+
+    #[test]
+    fn test_limit_checks() {
+        assert!(require_remaining_accounts_within("ix", 44, 44).is_ok());
+        assert!(require_remaining_accounts_within("ix", 45, 44).is_err());
+        assert!(require_remaining_accounts_exact("ix", 2, 2).is_ok());
+        assert!(require_remaining_accounts_exact("ix", 1, 2).is_err());
+        assert!(require_data_within("ix", "data", 10, 10).is_ok());
+        assert!(require_data_within("ix", "data", 11, 10).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+//! Ed25519 signature instruction introspection for pSOL v2
+//!
+//! Programs cannot check ed25519 signatures themselves. Instead the client
+//! puts an Ed25519SigVerify instruction directly before ours, the runtime
+//! rejects the transaction if that signature is bad, and we read the
+//! instructions sysvar to confirm it covered the key and message we expect.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use solana_instructions_sysvar::{load_current_index_checked, load_instruction_at_checked};
+use solana_sdk_ids::ed25519_program;
+
+use crate::error::PrivacyErrorV2;
+
+/// Header: num_signatures (1) + padding (1)
+const HEADER_LEN: usize = 2;
+/// One Ed25519SignatureOffsets entry: 7 x u16
+const OFFSETS_LEN: usize = 14;
+const PUBKEY_LEN: usize = 32;
+const SIGNATURE_LEN: usize = 64;
+/// Instruction index meaning "data lives in this instruction"
+const THIS_INSTRUCTION: u16 = u16::MAX;
+
+fn slice(data: &[u8], offset: u16, len: usize) -> Option<&[u8]> {
+    let start = offset as usize;
+    data.get(start..start.checked_add(len)?)
+}
+
+/// Check that `ix` verifies exactly one signature by `signer` over `message`,
+/// with signature, key and message all inline in `ix`. Returns the signature.
+pub fn check_ed25519_instruction(
+    ix: &Instruction,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<[u8; SIGNATURE_LEN]> {
+    require_keys_eq!(
+        ix.program_id,
+        ed25519_program::ID,
+        PrivacyErrorV2::InvalidSignatureInstruction
+    );
+
+    let data = &ix.data;
+    require!(
+        data.len() >= HEADER_LEN + OFFSETS_LEN && data[0] == 1,
+        PrivacyErrorV2::InvalidSignatureInstruction
+    );
+
+    // Length checked above, so every offsets field is in bounds
+    let field = |i: usize| {
+        let at = HEADER_LEN + 2 * i;
+        u16::from_le_bytes([data[at], data[at + 1]])
+    };
+    let sig_offset = field(0);
+    let sig_ix = field(1);
+    let pubkey_offset = field(2);
+    let pubkey_ix = field(3);
+    let msg_offset = field(4);
+    let msg_size = field(5);
+    let msg_ix = field(6);
+
+    // Pointing at another instruction would let the caller swap in data we
+    // never look at
+    require!(
+        sig_ix == THIS_INSTRUCTION && pubkey_ix == THIS_INSTRUCTION && msg_ix == THIS_INSTRUCTION,
+        PrivacyErrorV2::InvalidSignatureInstruction
+    );
+
+    let signature = slice(data, sig_offset, SIGNATURE_LEN);
+    let ok = signature.is_some()
+        && slice(data, pubkey_offset, PUBKEY_LEN) == Some(signer.as_ref())
+        && slice(data, msg_offset, msg_size as usize) == Some(message);
+    require!(ok, PrivacyErrorV2::InvalidSignatureInstruction);
+
+    signature
+        .and_then(|sig| sig.try_into().ok())
+        .ok_or(error!(PrivacyErrorV2::InvalidSignatureInstruction))
+}
+
+/// Check the instruction immediately before the current one is an ed25519
+/// verification of `message` by `signer`, returning the signature.
+pub fn verify_preceding_ed25519_signature(
+    instructions_sysvar: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<[u8; SIGNATURE_LEN]> {
+    let current = load_current_index_checked(instructions_sysvar)?;
+    require!(current > 0, PrivacyErrorV2::InvalidSignatureInstruction);
+
+    let ix = load_instruction_at_checked((current - 1) as usize, instructions_sysvar)?;
+    check_ed25519_instruction(&ix, signer, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Same layout the ed25519 program's client helper produces
+    fn ed25519_ix(signer: &Pubkey, message: &[u8]) -> Instruction {
+        let pubkey_offset = (HEADER_LEN + OFFSETS_LEN) as u16;
+        let sig_offset = pubkey_offset + PUBKEY_LEN as u16;
+        let msg_offset = sig_offset + SIGNATURE_LEN as u16;
+
+        let mut data = vec![1u8, 0];
+        for v in [
+            sig_offset,
+            THIS_INSTRUCTION,
+            pubkey_offset,
+            THIS_INSTRUCTION,
+            msg_offset,
+            message.len() as u16,
+            THIS_INSTRUCTION,
+        ] {
+            data.extend_from_slice(&v.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(&[7u8; SIGNATURE_LEN]);
+        data.extend_from_slice(message);
+
+        Instruction {
+            program_id: ed25519_program::ID,
+            accounts: vec![],
+            data,
+        }
+    }
+
+    #[test]
+    fn test_check_ed25519_instruction() {
+        let signer = Pubkey::new_unique();
+        let ix = ed25519_ix(&signer, b"update");
+
+        assert_eq!(
+            check_ed25519_instruction(&ix, &signer, b"update").unwrap(),
+            [7u8; SIGNATURE_LEN]
+        );
+        assert!(check_ed25519_instruction(&ix, &signer, b"updatf").is_err());
+        assert!(check_ed25519_instruction(&ix, &Pubkey::new_unique(), b"update").is_err());
+
+        // Message taken from another instruction is not accepted
+        let mut external = ix.clone();
+        external.data[HEADER_LEN + 12] = 0;
+        external.data[HEADER_LEN + 13] = 0;
+        assert!(check_ed25519_instruction(&external, &signer, b"update").is_err());
+
+        let mut wrong_program = ix;
+        wrong_program.program_id = Pubkey::new_unique();
+        assert!(check_ed25519_instruction(&wrong_program, &signer, b"update").is_err());
+    }
+}
@@ -1,11 +1,32 @@
 //! Utility modules for pSOL v2
 
+pub mod ed25519;
 pub mod validation;
 
+pub use ed25519::{check_ed25519_instruction, verify_preceding_ed25519_signature};
+
 pub use validation::{
-    validate_metadata_uri, validate_pool_name, validate_relayer_name, validate_string_input,
-    MAX_METADATA_URI_LEN, MAX_POOL_NAME_LEN, MAX_RELAYER_NAME_LEN,
+    require_program_id, validate_metadata_uri, validate_pool_name, validate_relayer_name,
+    validate_string_input, MAX_METADATA_URI_LEN, MAX_POOL_NAME_LEN, MAX_RELAYER_NAME_LEN,
 };
 
 pub mod cu_debug;
 pub use cu_debug::cu;
+
+pub mod same_tx_guard;
+pub use same_tx_guard::{require_no_opposite_flow, PoolFlow};
+
+pub mod pda;
+pub use pda::{create_pda_account, reimburse_relayer_rent, spend_nullifier};
+
+pub mod token_metadata;
+pub use token_metadata::TokenMetadata;
+
+pub mod transfer_fee;
+pub use transfer_fee::MintTransferFee;
+
+pub mod time;
+pub use time::pool_clock;
+
+pub mod withdrawal_checks;
+pub use withdrawal_checks::{check_not_yield_asset, check_risk_tier_withdrawal, check_withdrawal};
@@ -0,0 +1,149 @@
+//! Runtime PDA account creation for pSOL v2
+//!
+//! For instructions that create a variable number of PDAs from
+//! `remaining_accounts`, where Anchor's `init` constraint cannot be used.
+//! Mirrors what `init` does, including for an address someone has already
+//! sent lamports to (create_account would fail on it).
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Allocate, Assign, CreateAccount, Transfer};
+
+use crate::error::PrivacyErrorV2;
+use crate::state::{SpendContext, SpentNullifierV2};
+
+/// Create `target` as a rent-exempt account of `space` bytes owned by
+/// `owner`, signing for it with `seeds`
+pub fn create_pda_account<'info>(
+    payer: &AccountInfo<'info>,
+    target: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    owner: &Pubkey,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    require!(
+        target.owner == &system_program::ID && target.data_is_empty(),
+        PrivacyErrorV2::AlreadyInitialized
+    );
+
+    let signer = &[seeds];
+    let required = Rent::get()?.minimum_balance(space);
+    let current = target.lamports();
+
+    if current == 0 {
+        return system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                CreateAccount {
+                    from: payer.clone(),
+                    to: target.clone(),
+                },
+                signer,
+            ),
+            required,
+            space as u64,
+            owner,
+        );
+    }
+
+    if required > current {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: payer.clone(),
+                    to: target.clone(),
+                },
+            ),
+            required - current,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            Allocate {
+                account_to_allocate: target.clone(),
+            },
+            signer,
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            Assign {
+                account_to_assign: target.clone(),
+            },
+            signer,
+        ),
+        owner,
+    )
+}
+
+/// Create the spent nullifier PDA for `nullifier_hash` at `account`, with
+/// `payer` paying its rent and `relayer` recorded as the spender
+#[allow(clippy::too_many_arguments)]
+pub fn spend_nullifier<'info>(
+    payer: &Signer<'info>,
+    relayer: Pubkey,
+    system_program: &Program<'info, System>,
+    account: &AccountInfo<'info>,
+    pool: Pubkey,
+    nullifier_hash: [u8; 32],
+    asset_id: [u8; 32],
+    context: SpendContext,
+    timestamp: i64,
+    slot: u64,
+) -> Result<()> {
+    let (expected, bump) = SpentNullifierV2::find_pda(&crate::ID, &pool, &nullifier_hash);
+    require_keys_eq!(account.key(), expected, ErrorCode::ConstraintSeeds);
+    require!(
+        account.data_is_empty(),
+        PrivacyErrorV2::NullifierAlreadySpent
+    );
+
+    let bump_seed = [bump];
+    create_pda_account(
+        &payer.to_account_info(),
+        account,
+        &system_program.to_account_info(),
+        SpentNullifierV2::LEN,
+        &crate::ID,
+        &SpentNullifierV2::seeds(&pool, &nullifier_hash, &bump_seed),
+    )?;
+
+    // Freshly allocated, so all zeroes: what `init` would deserialize
+    let mut record =
+        SpentNullifierV2::try_deserialize_unchecked(&mut &account.try_borrow_data()?[..])?;
+    record.initialize(
+        pool,
+        nullifier_hash,
+        asset_id,
+        context,
+        timestamp,
+        slot,
+        relayer,
+        bump,
+    );
+    record.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])
+}
+
+/// Pay `relayer` back the `rent` it fronted for accounts created with
+/// `init`, out of a separate `fee_payer`
+pub fn reimburse_relayer_rent<'info>(
+    fee_payer: &Signer<'info>,
+    relayer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    rent: u64,
+) -> Result<()> {
+    system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            Transfer {
+                from: fee_payer.to_account_info(),
+                to: relayer.to_account_info(),
+            },
+        ),
+        rent,
+    )
+}
@@ -0,0 +1,197 @@
+//! Same-transaction deposit/withdraw guard for pSOL v2
+//!
+//! A deposit and a withdrawal against the same pool in one transaction
+//! are trivially linked: anyone reading the transaction sees the money go
+//! in and come out together, and the pair still counts towards the pool's
+//! anonymity set statistics. Deposit and withdraw handlers scan the
+//! instructions sysvar for a top-level instruction of the opposite kind
+//! that references the same pool config, and reject the transaction unless
+//! the pool authority has explicitly allowed such flows.
+//!
+//! Only top-level instructions are visible in the sysvar; a flow wrapped in
+//! another program's CPI is not caught here.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::Discriminator;
+use solana_instructions_sysvar::load_instruction_at_checked;
+
+use crate::error::PrivacyErrorV2;
+use crate::instruction;
+
+/// Direction of a value-moving instruction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolFlow {
+    Deposit,
+    Withdraw,
+}
+
+impl PoolFlow {
+    /// Flow of a pool program instruction, if it moves value in or out
+    pub fn of(ix: &Instruction) -> Option<Self> {
+        if ix.program_id != crate::ID {
+            return None;
+        }
+        let data = ix.data.as_slice();
+        if data.starts_with(instruction::DepositMasp::DISCRIMINATOR)
+            || data.starts_with(instruction::DepositMaspBatch::DISCRIMINATOR)
+        {
+            Some(Self::Deposit)
+        } else if data.starts_with(instruction::WithdrawMasp::DISCRIMINATOR)
+            || data.starts_with(instruction::WithdrawWithFeePayerRefund::DISCRIMINATOR)
+            || data.starts_with(instruction::WithdrawWithParameterPin::DISCRIMINATOR)
+            || data.starts_with(instruction::DisclosedPathWithdraw::DISCRIMINATOR)
+            || data.starts_with(instruction::WithdrawV2::DISCRIMINATOR)
+            || data.starts_with(instruction::WithdrawYieldV2::DISCRIMINATOR)
+        {
+            Some(Self::Withdraw)
+        } else if data.starts_with(instruction::PrivateTransferJoinSplit::DISCRIMINATOR) {
+            Self::of_join_split(&data[instruction::PrivateTransferJoinSplit::DISCRIMINATOR.len()..])
+        } else {
+            None
+        }
+    }
+
+    /// A join-split moves value in or out by the sign of its
+    /// `public_amount`; a pure private transfer moves none
+    fn of_join_split(mut args: &[u8]) -> Option<Self> {
+        let transfer = instruction::PrivateTransferJoinSplit::deserialize(&mut args).ok()?;
+        match transfer.public_amount {
+            0 => None,
+            amount if amount > 0 => Some(Self::Deposit),
+            _ => Some(Self::Withdraw),
+        }
+    }
+
+    fn opposite(self) -> Self {
+        match self {
+            Self::Deposit => Self::Withdraw,
+            Self::Withdraw => Self::Deposit,
+        }
+    }
+}
+
+/// Whether `ix` moves value against `pool` in the direction opposite to
+/// `flow`
+pub fn is_opposite_flow(ix: &Instruction, pool: &Pubkey, flow: PoolFlow) -> bool {
+    PoolFlow::of(ix) == Some(flow.opposite()) && ix.accounts.iter().any(|meta| meta.pubkey == *pool)
+}
+
+/// Reject the transaction if any top-level instruction moves value against
+/// `pool` in the direction opposite to `flow`
+pub fn require_no_opposite_flow(
+    instructions_sysvar: &AccountInfo,
+    pool: &Pubkey,
+    flow: PoolFlow,
+) -> Result<()> {
+    let mut index = 0;
+    // Loading past the last instruction is the only error once the sysvar
+    // address has been checked
+    while let Ok(ix) = load_instruction_at_checked(index, instructions_sysvar) {
+        require!(
+            !is_opposite_flow(&ix, pool, flow),
+            PrivacyErrorV2::SameTransactionDepositWithdraw
+        );
+        index += 1;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::instruction::AccountMeta;
+
+    fn ix(program_id: Pubkey, discriminator: &[u8], pool: Pubkey) -> Instruction {
+        let mut data = discriminator.to_vec();
+        data.extend_from_slice(&[0u8; 40]);
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(Pubkey::new_unique(), true),
+                AccountMeta::new(pool, false),
+            ],
+            data,
+        }
+    }
+
+    #[test]
+    fn test_opposite_flow_detection() {
+        let pool = Pubkey::new_unique();
+        let deposit = ix(crate::ID, instruction::DepositMasp::DISCRIMINATOR, pool);
+        let withdraw = ix(crate::ID, instruction::WithdrawMasp::DISCRIMINATOR, pool);
+        let withdraw_v2 = ix(crate::ID, instruction::WithdrawV2::DISCRIMINATOR, pool);
+
+        assert_eq!(PoolFlow::of(&deposit), Some(PoolFlow::Deposit));
+        let deposit_batch = ix(
+            crate::ID,
+            instruction::DepositMaspBatch::DISCRIMINATOR,
+            pool,
+        );
+        assert_eq!(PoolFlow::of(&deposit_batch), Some(PoolFlow::Deposit));
+        assert_eq!(PoolFlow::of(&withdraw_v2), Some(PoolFlow::Withdraw));
+        for discriminator in [
+            instruction::WithdrawWithFeePayerRefund::DISCRIMINATOR,
+            instruction::WithdrawWithParameterPin::DISCRIMINATOR,
+            instruction::DisclosedPathWithdraw::DISCRIMINATOR,
+        ] {
+            let withdraw = ix(crate::ID, discriminator, pool);
+            assert_eq!(PoolFlow::of(&withdraw), Some(PoolFlow::Withdraw));
+        }
+
+        assert!(is_opposite_flow(&deposit, &pool, PoolFlow::Withdraw));
+        assert!(is_opposite_flow(&withdraw, &pool, PoolFlow::Deposit));
+        assert!(!is_opposite_flow(&withdraw, &pool, PoolFlow::Withdraw));
+
+        // Another pool, another program, or a non-value instruction is fine
+        assert!(!is_opposite_flow(
+            &deposit,
+            &Pubkey::new_unique(),
+            PoolFlow::Withdraw
+        ));
+        let foreign = ix(
+            Pubkey::new_unique(),
+            instruction::DepositMasp::DISCRIMINATOR,
+            pool,
+        );
+        assert!(!is_opposite_flow(&foreign, &pool, PoolFlow::Withdraw));
+        let pause = ix(crate::ID, instruction::PausePoolV2::DISCRIMINATOR, pool);
+        assert!(!is_opposite_flow(&pause, &pool, PoolFlow::Withdraw));
+    }
+
+    #[test]
+    fn test_join_split_flow_follows_public_amount() {
+        let pool = Pubkey::new_unique();
+        let join_split = |public_amount: i64| {
+            let mut data = instruction::PrivateTransferJoinSplit::DISCRIMINATOR.to_vec();
+            instruction::PrivateTransferJoinSplit {
+                proof_data: vec![0u8; 256],
+                merkle_root: [1u8; 32],
+                input_nullifiers: vec![[2u8; 32], [3u8; 32]],
+                output_commitments: vec![[4u8; 32]],
+                public_amount,
+                recipient: Pubkey::new_unique(),
+                asset_id: [5u8; 32],
+                relayer_fee: 0,
+                encrypted_outputs: None,
+            }
+            .serialize(&mut data)
+            .unwrap();
+            Instruction {
+                program_id: crate::ID,
+                accounts: vec![AccountMeta::new(pool, false)],
+                data,
+            }
+        };
+
+        assert_eq!(PoolFlow::of(&join_split(0)), None);
+        assert_eq!(PoolFlow::of(&join_split(10)), Some(PoolFlow::Deposit));
+        assert_eq!(PoolFlow::of(&join_split(-10)), Some(PoolFlow::Withdraw));
+        assert!(is_opposite_flow(&join_split(-10), &pool, PoolFlow::Deposit));
+
+        // Truncated arguments are not a value flow
+        let mut truncated = join_split(-10);
+        truncated.data.truncate(40);
+        assert_eq!(PoolFlow::of(&truncated), None);
+    }
+}
@@ -0,0 +1,123 @@
+//! Cluster clock sanity checks
+//!
+//! `Clock::unix_timestamp` is a stake-weighted estimate from validator
+//! votes, not a trusted time source. Timestamps that feed pool statistics
+//! or expiries (timelocks, cooldowns, windows) are read through
+//! `pool_clock`, which rejects a clock outside a plausible range:
+//!
+//! - not before the pool was created
+//! - not before the current epoch started (`Clock::epoch_start_timestamp`)
+//! - not further past the epoch start than the slots elapsed since allow,
+//!   at `MAX_SLOT_MILLIS` per slot
+//!
+//! Each bound allows `MAX_CLOCK_SKEW_SECONDS` of slack, so ordinary drift
+//! never trips it; a cluster clock anomaly fails the instruction with
+//! `InvalidTimestamp` instead of writing a skewed time into pool state.
+
+use anchor_lang::prelude::*;
+
+use crate::error::PrivacyErrorV2;
+use crate::state::PoolConfigV2;
+
+/// Slack on every clock bound, in seconds
+pub const MAX_CLOCK_SKEW_SECONDS: i64 = 60;
+
+/// Slowest slot time the checks accept (2.5x the 400ms target, the
+/// cluster's own bound on how far its clock may run ahead of slots)
+pub const MAX_SLOT_MILLIS: i64 = 1_000;
+
+/// Read the clock, checked against `pool`'s creation time and the slots
+/// elapsed in the current epoch
+pub fn pool_clock(pool: &PoolConfigV2) -> Result<Clock> {
+    let clock = Clock::get()?;
+    let first_slot = EpochSchedule::get()?.get_first_slot_in_epoch(clock.epoch);
+    check_clock(&clock, first_slot, pool.created_at)?;
+    Ok(clock)
+}
+
+/// Check `clock` against the first slot of its epoch and the pool's
+/// creation time
+pub fn check_clock(clock: &Clock, first_slot_in_epoch: u64, pool_created_at: i64) -> Result<()> {
+    let timestamp = clock.unix_timestamp;
+    require!(timestamp > 0, PrivacyErrorV2::InvalidTimestamp);
+    require!(
+        timestamp >= pool_created_at.saturating_sub(MAX_CLOCK_SKEW_SECONDS),
+        PrivacyErrorV2::InvalidTimestamp
+    );
+
+    let since_epoch_start = timestamp.saturating_sub(clock.epoch_start_timestamp);
+    require!(
+        since_epoch_start >= -MAX_CLOCK_SKEW_SECONDS,
+        PrivacyErrorV2::InvalidTimestamp
+    );
+    let slots = clock.slot.saturating_sub(first_slot_in_epoch);
+    let max_elapsed = i64::try_from(slots)
+        .unwrap_or(i64::MAX)
+        .saturating_mul(MAX_SLOT_MILLIS)
+        / 1_000;
+    require!(
+        since_epoch_start <= max_elapsed.saturating_add(MAX_CLOCK_SKEW_SECONDS),
+        PrivacyErrorV2::InvalidTimestamp
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPOCH_START: i64 = 1_700_000_000;
+    const FIRST_SLOT: u64 = 1_000;
+
+    fn clock(slot: u64, unix_timestamp: i64) -> Clock {
+        Clock {
+            slot,
+            epoch_start_timestamp: EPOCH_START,
+            epoch: 3,
+            leader_schedule_epoch: 4,
+            unix_timestamp,
+        }
+    }
+
+    #[test]
+    fn test_clock_following_slots_passes() {
+        // 1000 slots at 400ms
+        let now = clock(FIRST_SLOT + 1_000, EPOCH_START + 400);
+        assert!(check_clock(&now, FIRST_SLOT, EPOCH_START - 86_400).is_ok());
+        // At the epoch's first slot
+        let now = clock(FIRST_SLOT, EPOCH_START);
+        assert!(check_clock(&now, FIRST_SLOT, EPOCH_START).is_ok());
+    }
+
+    #[test]
+    fn test_clock_ahead_of_slots_fails() {
+        // 100 slots cannot take more than 100s (+ skew)
+        let ok = clock(FIRST_SLOT + 100, EPOCH_START + 100 + MAX_CLOCK_SKEW_SECONDS);
+        assert!(check_clock(&ok, FIRST_SLOT, 0).is_ok());
+        let ahead = clock(FIRST_SLOT + 100, EPOCH_START + 101 + MAX_CLOCK_SKEW_SECONDS);
+        assert!(check_clock(&ahead, FIRST_SLOT, 0).is_err());
+    }
+
+    #[test]
+    fn test_clock_before_epoch_start_fails() {
+        let behind = clock(FIRST_SLOT + 10, EPOCH_START - MAX_CLOCK_SKEW_SECONDS - 1);
+        assert!(check_clock(&behind, FIRST_SLOT, 0).is_err());
+    }
+
+    #[test]
+    fn test_clock_before_pool_creation_fails() {
+        let now = clock(FIRST_SLOT + 1_000, EPOCH_START + 400);
+        let created_at = EPOCH_START + 400 + MAX_CLOCK_SKEW_SECONDS;
+        assert!(check_clock(&now, FIRST_SLOT, created_at).is_ok());
+        assert!(check_clock(&now, FIRST_SLOT, created_at + 1).is_err());
+    }
+
+    #[test]
+    fn test_non_positive_timestamp_fails() {
+        let zero = Clock {
+            epoch_start_timestamp: 0,
+            ..clock(FIRST_SLOT, 0)
+        };
+        assert!(check_clock(&zero, FIRST_SLOT, 0).is_err());
+    }
+}
@@ -0,0 +1,179 @@
+//! Metaplex token metadata reader
+//!
+//! Registration can cache a mint's on-chain name and symbol in its
+//! `AssetVault`, so explorers and wallets can render an asset from the
+//! vault (or the registration event) alone. The values come from the
+//! Metaplex metadata PDA for the mint, never from the caller.
+//!
+//! Only the leading fields of `Metadata` are parsed; the program crate is
+//! not a dependency:
+//!
+//! ```text
+//! key: u8 (MetadataV1 = 4) | update_authority: Pubkey | mint: Pubkey
+//! | name: String | symbol: String | ...
+//! ```
+//!
+//! Metaplex pads `name` and `symbol` with NUL bytes to their maximum
+//! length; the padding is stripped before caching.
+
+use anchor_lang::prelude::*;
+
+use crate::crypto::keccak::keccak256_concat;
+use crate::error::PrivacyErrorV2;
+
+/// Metaplex Token Metadata program
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey =
+    pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+/// Metadata PDA seed prefix
+pub const METADATA_SEED_PREFIX: &[u8] = b"metadata";
+
+/// `Key::MetadataV1` discriminant
+const METADATA_V1_KEY: u8 = 4;
+
+/// Metaplex limits, in bytes
+pub const MAX_TOKEN_NAME_LEN: usize = 32;
+pub const MAX_TOKEN_SYMBOL_LEN: usize = 10;
+
+/// key + update_authority + mint
+const NAME_OFFSET: usize = 1 + 32 + 32;
+
+/// Name and symbol read from a mint's metadata account
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TokenMetadata {
+    pub name: Vec<u8>,
+    pub symbol: Vec<u8>,
+}
+
+impl TokenMetadata {
+    /// Metadata PDA for `mint`
+    pub fn find_pda(mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                METADATA_SEED_PREFIX,
+                TOKEN_METADATA_PROGRAM_ID.as_ref(),
+                mint.as_ref(),
+            ],
+            &TOKEN_METADATA_PROGRAM_ID,
+        )
+    }
+
+    /// Read the metadata for `mint` from `account`, which must be the
+    /// mint's metadata PDA owned by the Metaplex program
+    pub fn load(account: &AccountInfo, mint: &Pubkey) -> Result<Self> {
+        require_keys_eq!(
+            *account.owner,
+            TOKEN_METADATA_PROGRAM_ID,
+            PrivacyErrorV2::InvalidTokenMetadata
+        );
+        require_keys_eq!(
+            account.key(),
+            Self::find_pda(mint).0,
+            PrivacyErrorV2::InvalidTokenMetadata
+        );
+        Self::parse(&account.try_borrow_data()?, mint)
+    }
+
+    /// Parse metadata account data, checking it describes `mint`
+    pub fn parse(data: &[u8], mint: &Pubkey) -> Result<Self> {
+        require!(
+            data.len() >= NAME_OFFSET && data[0] == METADATA_V1_KEY,
+            PrivacyErrorV2::InvalidTokenMetadata
+        );
+        require!(
+            data[33..65] == mint.as_ref()[..],
+            PrivacyErrorV2::InvalidTokenMetadata
+        );
+
+        let (name, rest) = read_string(&data[NAME_OFFSET..], MAX_TOKEN_NAME_LEN)?;
+        let (symbol, _) = read_string(rest, MAX_TOKEN_SYMBOL_LEN)?;
+        Ok(Self {
+            name: name.to_vec(),
+            symbol: symbol.to_vec(),
+        })
+    }
+
+    /// Hash of the name, cached in place of the name itself
+    pub fn name_hash(&self) -> [u8; 32] {
+        keccak256_concat(&[b"psol:token_name:v1", &self.name])
+    }
+
+    /// Symbol zero-padded to its fixed cached width
+    pub fn symbol_bytes(&self) -> [u8; MAX_TOKEN_SYMBOL_LEN] {
+        let mut out = [0u8; MAX_TOKEN_SYMBOL_LEN];
+        out[..self.symbol.len()].copy_from_slice(&self.symbol);
+        out
+    }
+}
+
+/// Read a borsh string of at most `max_len` bytes, without NUL padding
+fn read_string(data: &[u8], max_len: usize) -> Result<(&[u8], &[u8])> {
+    require!(data.len() >= 4, PrivacyErrorV2::InvalidTokenMetadata);
+    let len = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
+    let data = &data[4..];
+    require!(
+        len <= max_len && len <= data.len(),
+        PrivacyErrorV2::InvalidTokenMetadata
+    );
+
+    let (value, rest) = data.split_at(len);
+    let trimmed = value.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+    Ok((&value[..trimmed], rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata_data(mint: &Pubkey, name: &str, symbol: &str, pad: bool) -> Vec<u8> {
+        let pad_to = |s: &str, n: usize| {
+            let mut b = s.as_bytes().to_vec();
+            if pad {
+                b.resize(n, 0);
+            }
+            b
+        };
+        let mut data = vec![METADATA_V1_KEY];
+        data.extend_from_slice(Pubkey::new_unique().as_ref());
+        data.extend_from_slice(mint.as_ref());
+        for s in [
+            pad_to(name, MAX_TOKEN_NAME_LEN),
+            pad_to(symbol, MAX_TOKEN_SYMBOL_LEN),
+        ] {
+            data.extend_from_slice(&(s.len() as u32).to_le_bytes());
+            data.extend_from_slice(&s);
+        }
+        data.extend_from_slice(&[0u8; 8]);
+        data
+    }
+
+    #[test]
+    fn test_parse_strips_padding() {
+        let mint = Pubkey::new_unique();
+        for pad in [true, false] {
+            let meta = TokenMetadata::parse(&metadata_data(&mint, "USD Coin", "USDC", pad), &mint)
+                .unwrap();
+            assert_eq!(meta.name, b"USD Coin");
+            assert_eq!(meta.symbol, b"USDC");
+            assert_eq!(&meta.symbol_bytes(), b"USDC\0\0\0\0\0\0");
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_foreign_or_malformed() {
+        let mint = Pubkey::new_unique();
+        let data = metadata_data(&mint, "Wrapped SOL", "SOL", true);
+
+        assert!(TokenMetadata::parse(&data, &Pubkey::new_unique()).is_err());
+        assert!(TokenMetadata::parse(&data[..NAME_OFFSET + 10], &mint).is_err());
+
+        let mut wrong_key = data.clone();
+        wrong_key[0] = 0;
+        assert!(TokenMetadata::parse(&wrong_key, &mint).is_err());
+
+        let mut long_symbol = data;
+        let symbol_len_at = NAME_OFFSET + 4 + MAX_TOKEN_NAME_LEN;
+        long_symbol[symbol_len_at..symbol_len_at + 4].copy_from_slice(&11u32.to_le_bytes());
+        assert!(TokenMetadata::parse(&long_symbol, &mint).is_err());
+    }
+}
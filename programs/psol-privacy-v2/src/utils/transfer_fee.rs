@@ -0,0 +1,133 @@
+//! Token-2022 transfer fee reader
+//!
+//! A Token-2022 mint with the `TransferFeeConfig` extension withholds a fee
+//! from every transfer, at the destination. Deposits commit to the amount
+//! the vault actually receives, so the depositor sends the gross amount
+//! that nets the committed value after the fee.
+//!
+//! Mints owned by the SPL Token program, and Token-2022 mints without the
+//! extension, charge no fee: every amount is its own gross.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        transfer_fee::{TransferFee, TransferFeeConfig},
+        BaseStateWithExtensions, StateWithExtensions,
+    },
+    state::Mint,
+};
+
+use crate::error::PrivacyErrorV2;
+
+/// Transfer fee a mint charges in the current epoch
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MintTransferFee {
+    fee: Option<TransferFee>,
+}
+
+impl MintTransferFee {
+    /// Read the fee for `epoch` from a mint's owner and data
+    pub fn from_mint_data(owner: &Pubkey, data: &[u8], epoch: u64) -> Result<Self> {
+        if *owner != spl_token_2022::ID {
+            return Ok(Self::default());
+        }
+        let mint = StateWithExtensions::<Mint>::unpack(data)
+            .map_err(|_| error!(PrivacyErrorV2::InvalidMint))?;
+        let fee = mint
+            .get_extension::<TransferFeeConfig>()
+            .ok()
+            .map(|config| *config.get_epoch_fee(epoch));
+        Ok(Self { fee })
+    }
+
+    /// Read the fee in effect now for `mint`
+    pub fn load(mint: &AccountInfo) -> Result<Self> {
+        let epoch = Clock::get()?.epoch;
+        Self::from_mint_data(mint.owner, &mint.try_borrow_data()?, epoch)
+    }
+
+    /// Fee withheld from a transfer of `amount`
+    pub fn fee(&self, amount: u64) -> Result<u64> {
+        match &self.fee {
+            Some(fee) => fee
+                .calculate_fee(amount)
+                .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow)),
+            None => Ok(0),
+        }
+    }
+
+    /// Smallest transfer amount that delivers `net` after the fee
+    pub fn gross_amount(&self, net: u64) -> Result<u64> {
+        match &self.fee {
+            Some(fee) => fee
+                .calculate_pre_fee_amount(net)
+                .ok_or(error!(PrivacyErrorV2::ArithmeticOverflow)),
+            None => Ok(net),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_spl::token_2022::spl_token_2022::extension::{
+        BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut,
+    };
+
+    /// Token-2022 mint data with a `bps` fee capped at `max_fee`, taking
+    /// effect at epoch 10 (no fee before)
+    fn fee_mint(bps: u16, max_fee: u64) -> Vec<u8> {
+        let len =
+            ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferFeeConfig])
+                .unwrap();
+        let mut data = vec![0u8; len];
+        let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+        let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+        config.newer_transfer_fee = TransferFee {
+            epoch: 10.into(),
+            maximum_fee: max_fee.into(),
+            transfer_fee_basis_points: bps.into(),
+        };
+        state.base = Mint {
+            decimals: 6,
+            is_initialized: true,
+            ..Mint::default()
+        };
+        state.pack_base();
+        state.init_account_type().unwrap();
+        data
+    }
+
+    #[test]
+    fn test_fee_mint_grosses_up_to_net() {
+        let data = fee_mint(100, 5_000);
+        let fee = MintTransferFee::from_mint_data(&spl_token_2022::ID, &data, 10).unwrap();
+
+        // 1% fee: 10_000 gross delivers 9_900
+        assert_eq!(fee.fee(10_000).unwrap(), 100);
+        let gross = fee.gross_amount(9_900).unwrap();
+        assert_eq!(gross - fee.fee(gross).unwrap(), 9_900);
+        // Capped at the maximum fee
+        assert_eq!(fee.gross_amount(1_000_000_000).unwrap(), 1_000_005_000);
+    }
+
+    #[test]
+    fn test_fee_follows_epoch() {
+        let data = fee_mint(100, 5_000);
+        let fee = MintTransferFee::from_mint_data(&spl_token_2022::ID, &data, 9).unwrap();
+
+        assert_eq!(fee.fee(10_000).unwrap(), 0);
+        assert_eq!(fee.gross_amount(10_000).unwrap(), 10_000);
+    }
+
+    #[test]
+    fn test_spl_token_mint_has_no_fee() {
+        let fee =
+            MintTransferFee::from_mint_data(&anchor_spl::token::ID, &fee_mint(100, 5_000), 10)
+                .unwrap();
+
+        assert_eq!(fee, MintTransferFee::default());
+        assert_eq!(fee.gross_amount(10_000).unwrap(), 10_000);
+    }
+}
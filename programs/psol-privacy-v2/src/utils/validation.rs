@@ -12,6 +12,23 @@ pub const MAX_RELAYER_NAME_LEN: usize = 64;
 /// Maximum length for pool names/descriptions
 pub const MAX_POOL_NAME_LEN: usize = 64;
 
+/// Require `program_id` to be the id declared for this build's cluster
+///
+/// Guards handlers that derive PDAs by hand, so a build for one cluster
+/// deployed under another id fails loudly instead of deriving addresses
+/// for the wrong program.
+pub fn require_program_id(program_id: &Pubkey) -> Result<()> {
+    if !crate::check_id(program_id) {
+        msg!(
+            "Program id mismatch: running as {}, built for {}",
+            program_id,
+            crate::ID
+        );
+        return Err(error!(PrivacyErrorV2::ProgramIdMismatch));
+    }
+    Ok(())
+}
+
 /// Validate metadata URI
 pub fn validate_metadata_uri(uri: &str) -> Result<()> {
     if uri.is_empty() {
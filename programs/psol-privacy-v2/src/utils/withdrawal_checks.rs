@@ -0,0 +1,107 @@
+//! Checks shared by every instruction paying a note out of a vault
+//!
+//! `withdraw_masp` proves the spend with a Groth16 proof and
+//! `disclosed_path_withdraw` by revealing the note's opening, but once the
+//! spend is established both release shielded value to a public account
+//! and must hold it to the same rules: the asset's minimum withdrawal, the
+//! amount range-check hook, the same-transaction deposit/withdraw guard,
+//! relayer fee bounds, the yield-exit requirement and the limits of the
+//! asset's risk tier.
+
+use anchor_lang::prelude::*;
+
+use crate::crypto::{check_amount_range, RangeCheckInput};
+use crate::error::PrivacyErrorV2;
+use crate::state::{
+    AssetVault, AssetWithdrawalWindow, PoolConfigV2, RiskTierConfig, YieldRegistry,
+};
+use crate::utils::PoolFlow;
+
+/// Validate a withdrawal of `amount` paying `relayer_fee` to the relayer,
+/// before any state changes
+pub fn check_withdrawal(
+    pool: &Pubkey,
+    pool_config: &PoolConfigV2,
+    asset_vault: &AssetVault,
+    instructions_sysvar: &AccountInfo,
+    amount: u64,
+    relayer_fee: u64,
+) -> Result<()> {
+    // Validate amount is above the asset's minimum (prevents dust attacks)
+    asset_vault.require_min_withdrawal(amount)?;
+
+    // Amount range-check hook (no-op until the pool enables a range proof scheme)
+    check_amount_range(
+        pool_config.range_proof_scheme()?,
+        None,
+        &RangeCheckInput::new(asset_vault.asset_id, amount),
+    )?;
+
+    pool_config.require_no_same_tx_flow(pool, instructions_sysvar, PoolFlow::Withdraw)?;
+
+    // Validate relayer fee doesn't exceed amount
+    require!(
+        relayer_fee <= amount,
+        PrivacyErrorV2::RelayerFeeExceedsAmount
+    );
+
+    // Validate relayer fee is reasonable (max 10% for safety)
+    // Using multiplication to avoid integer division edge cases:
+    // relayer_fee <= amount * 10% is equivalent to relayer_fee * 10 <= amount
+    // This correctly handles small amounts where amount/10 would truncate to 0
+    //
+    // SECURITY: Use checked_mul to reject overflow instead of silent saturation
+    let fee_times_ten = relayer_fee
+        .checked_mul(10)
+        .ok_or(error!(PrivacyErrorV2::RelayerFeeOverflow))?;
+    require!(
+        fee_times_ten <= amount,
+        PrivacyErrorV2::RelayerFeeOutOfRange
+    );
+
+    // Validate fee against the asset's absolute bounds (token base units)
+    asset_vault.validate_relayer_fee(relayer_fee)
+}
+
+/// Reject yield assets, which must leave through `withdraw_yield_v2`, while
+/// the pool enforces yield exits
+pub fn check_not_yield_asset(
+    pool_config: &PoolConfigV2,
+    yield_registry: Option<&YieldRegistry>,
+    asset_id: &[u8; 32],
+) -> Result<()> {
+    if !pool_config.is_yield_enforcement_enabled() {
+        return Ok(());
+    }
+    // CRITICAL: Require yield_registry when enforcement enabled
+    let yield_registry = yield_registry.ok_or(PrivacyErrorV2::YieldRegistryRequired)?;
+    require!(
+        !yield_registry.is_yield_asset(asset_id),
+        PrivacyErrorV2::YieldAssetRequiresYieldExit
+    );
+    Ok(())
+}
+
+/// Hold a withdrawal of `amount` to the limits of the asset's risk tier, if
+/// it is in one, counting it against the tier's rate limit in the asset's
+/// `withdrawal_window`; `committed_at` is when the withdrawal was sealed
+/// with a `WithdrawalCommit`, if it was
+pub fn check_risk_tier_withdrawal(
+    asset_vault: &AssetVault,
+    risk_tiers: Option<&RiskTierConfig>,
+    withdrawal_window: Option<&mut AssetWithdrawalWindow>,
+    amount: u64,
+    committed_at: Option<i64>,
+    now: i64,
+) -> Result<()> {
+    let Some(limits) = RiskTierConfig::asset_limits(risk_tiers, asset_vault)? else {
+        return Ok(());
+    };
+    limits.check_withdrawal(amount, committed_at, now)?;
+    if limits.is_rate_limited() {
+        withdrawal_window
+            .ok_or(PrivacyErrorV2::WithdrawalWindowRequired)?
+            .record(&limits, amount, now)?;
+    }
+    Ok(())
+}
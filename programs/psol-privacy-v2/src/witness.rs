@@ -0,0 +1,377 @@
+//! Withdraw circuit witness builder (host-side reference)
+//!
+//! Produces the `input.json` that `circuits/withdraw/withdraw.circom` expects
+//! from a note, its leaf index and the tree's leaves, so a prover never has to
+//! re-derive the Merkle path, nullifier hash or public-input encodings by
+//! hand. Public signals are taken from `WithdrawPublicInputs` — the same
+//! encoding `withdraw_masp` hands to the verifier — and every input name is
+//! listed once in `WITHDRAW_INPUT_NAMES`, which the tests check against the
+//! circuit source.
+//!
+//! Field elements are rendered as decimal strings, the way snarkjs reads
+//! them. Path indices follow `MerkleTreeChecker`: 0 means the node on the
+//! path is the left child.
+
+use anchor_lang::prelude::*;
+use ark_bn254::Fr;
+use ark_ff::PrimeField;
+
+use crate::crypto::precomputed_zeros::get_precomputed_zeros;
+use crate::crypto::{is_canonical_fr, poseidon2, WithdrawPublicInputs};
+use crate::error::PrivacyErrorV2;
+use crate::scanner::NotePlaintext;
+use crate::state::{MAX_TREE_DEPTH, MIN_TREE_DEPTH};
+
+/// `signal input` names of the withdraw circuit, in declaration order
+pub const WITHDRAW_INPUT_NAMES: [&str; 13] = [
+    "merkle_root",
+    "nullifier_hash",
+    "asset_id",
+    "recipient",
+    "amount",
+    "relayer",
+    "relayer_fee",
+    "public_data_hash",
+    "secret",
+    "nullifier",
+    "leaf_index",
+    "merkle_path",
+    "merkle_path_indices",
+];
+
+/// Number of public signals (the first entries of `WITHDRAW_INPUT_NAMES`)
+pub const WITHDRAW_PUBLIC_INPUTS: usize = 8;
+
+/// Authentication path of one leaf, bottom level first
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerklePath {
+    pub root: [u8; 32],
+    pub elements: Vec<[u8; 32]>,
+    pub indices: Vec<u8>,
+}
+
+/// Computes the path of `leaf_index` in a tree of `depth` holding `leaves`
+/// (every inserted leaf, in insertion order; the rest are empty)
+pub fn merkle_path(leaves: &[[u8; 32]], leaf_index: u32, depth: u8) -> Result<MerklePath> {
+    require!(
+        (MIN_TREE_DEPTH..=MAX_TREE_DEPTH).contains(&depth),
+        PrivacyErrorV2::InvalidTreeDepth
+    );
+    require!(
+        (leaves.len() as u64) <= 1u64 << depth,
+        PrivacyErrorV2::MerkleTreeFull
+    );
+    require!(
+        (leaf_index as usize) < leaves.len(),
+        PrivacyErrorV2::LeafIndexNotFound
+    );
+
+    let zeros = get_precomputed_zeros(depth);
+    let mut layer = leaves.to_vec();
+    let mut index = leaf_index as usize;
+    let mut elements = Vec::with_capacity(depth as usize);
+    let mut indices = Vec::with_capacity(depth as usize);
+
+    for zero in zeros.iter().take(depth as usize) {
+        let sibling = layer.get(index ^ 1).copied().unwrap_or(*zero);
+        elements.push(sibling);
+        indices.push((index & 1) as u8);
+
+        let mut next = Vec::with_capacity(layer.len().div_ceil(2));
+        for pair in layer.chunks(2) {
+            let right = pair.get(1).unwrap_or(zero);
+            next.push(poseidon2(&pair[0], right)?);
+        }
+        layer = next;
+        index >>= 1;
+    }
+
+    Ok(MerklePath {
+        root: layer[0],
+        elements,
+        indices,
+    })
+}
+
+/// Full input assignment for one withdraw proof
+#[derive(Clone, Debug)]
+pub struct WithdrawWitness {
+    pub public: WithdrawPublicInputs,
+    pub secret: [u8; 32],
+    pub nullifier: [u8; 32],
+    pub leaf_index: u32,
+    pub path: MerklePath,
+}
+
+impl WithdrawWitness {
+    /// Builds the witness for withdrawing all of `note`, which must be the
+    /// leaf at `leaf_index` of `leaves`
+    #[allow(clippy::too_many_arguments)]
+    pub fn build(
+        note: &NotePlaintext,
+        leaf_index: u32,
+        leaves: &[[u8; 32]],
+        depth: u8,
+        recipient: Pubkey,
+        relayer: Pubkey,
+        relayer_fee: u64,
+        public_data_hash: [u8; 32],
+    ) -> Result<Self> {
+        // The circuit enforces amount > 0 and relayer_fee <= amount
+        require!(note.amount > 0, PrivacyErrorV2::InvalidAmount);
+        require!(
+            relayer_fee <= note.amount,
+            PrivacyErrorV2::RelayerFeeExceedsAmount
+        );
+        require!(
+            is_canonical_fr(&public_data_hash),
+            PrivacyErrorV2::InvalidPublicInputs
+        );
+
+        let commitment = note.commitment()?;
+        require!(
+            leaves.get(leaf_index as usize) == Some(&commitment),
+            PrivacyErrorV2::InvalidCommitment
+        );
+
+        let path = merkle_path(leaves, leaf_index, depth)?;
+        let public = WithdrawPublicInputs::new(
+            path.root,
+            note.nullifier_hash(leaf_index)?,
+            note.asset_id,
+            recipient,
+            note.amount,
+            relayer,
+            relayer_fee,
+            public_data_hash,
+        );
+
+        Ok(Self {
+            public,
+            secret: note.secret,
+            nullifier: note.nullifier,
+            leaf_index,
+            path,
+        })
+    }
+
+    /// Public signals as snarkjs lists them in `public.json`
+    pub fn public_signals(&self) -> Vec<String> {
+        self.public
+            .to_field_elements()
+            .iter()
+            .map(to_decimal)
+            .collect()
+    }
+
+    /// Circuit inputs as `(name, values)` in `WITHDRAW_INPUT_NAMES` order;
+    /// scalar signals carry one value
+    pub fn inputs(&self) -> Vec<(&'static str, Vec<String>)> {
+        let mut values: Vec<Vec<String>> = self
+            .public_signals()
+            .into_iter()
+            .map(|signal| vec![signal])
+            .collect();
+        values.push(vec![to_decimal(&self.secret)]);
+        values.push(vec![to_decimal(&self.nullifier)]);
+        values.push(vec![self.leaf_index.to_string()]);
+        values.push(self.path.elements.iter().map(to_decimal).collect());
+        values.push(self.path.indices.iter().map(u8::to_string).collect());
+
+        WITHDRAW_INPUT_NAMES.iter().copied().zip(values).collect()
+    }
+
+    /// `input.json` for `snarkjs groth16 fullprove`
+    pub fn to_json(&self) -> String {
+        let fields: Vec<String> = self
+            .inputs()
+            .into_iter()
+            .map(|(name, values)| {
+                let quoted: Vec<String> = values.iter().map(|v| format!("\"{v}\"")).collect();
+                if name == "merkle_path" || name == "merkle_path_indices" {
+                    format!("  \"{name}\": [{}]", quoted.join(", "))
+                } else {
+                    format!("  \"{name}\": {}", quoted[0])
+                }
+            })
+            .collect();
+        format!("{{\n{}\n}}", fields.join(",\n"))
+    }
+}
+
+/// Big-endian field element as a decimal string
+fn to_decimal(bytes: &[u8; 32]) -> String {
+    Fr::from_be_bytes_mod_order(bytes).into_bigint().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::u64_to_be32;
+    use crate::state::MerkleTreeV2;
+
+    const FIXTURE: &str = include_str!("../../../test_data/e2e_fixture.json");
+    const CIRCUIT: &str = include_str!("../../../circuits/withdraw/withdraw.circom");
+
+    fn hex32(s: &str) -> [u8; 32] {
+        hex::decode(s).unwrap().try_into().unwrap()
+    }
+
+    fn leaf(i: u64) -> [u8; 32] {
+        poseidon2(&u64_to_be32(i), &u64_to_be32(i + 1)).unwrap()
+    }
+
+    #[test]
+    fn test_input_names_match_circuit() {
+        let declared: Vec<&str> = CIRCUIT
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("signal input "))
+            .map(|rest| rest.split([';', '[']).next().unwrap().trim())
+            .collect();
+        assert_eq!(declared, WITHDRAW_INPUT_NAMES);
+    }
+
+    #[test]
+    fn test_fixture_witness_reproduced() {
+        let fixture: serde_json::Value = serde_json::from_str(FIXTURE).unwrap();
+        let field = |name: &str| fixture[name].as_str().unwrap();
+        let depth = fixture["tree_depth"].as_u64().unwrap() as u8;
+
+        // Note and parties as in test_data/generate_e2e_fixture.mjs
+        let note = NotePlaintext {
+            secret: u64_to_be32(0x5eed5eed),
+            nullifier: u64_to_be32(0xdeadbeef),
+            amount: field("amount").parse().unwrap(),
+            asset_id: hex32(field("asset_id")),
+        };
+        let relayer_keypair = hex::decode(field("relayer_keypair")).unwrap();
+        let relayer = Pubkey::try_from(&relayer_keypair[32..]).unwrap();
+        let recipient = Pubkey::new_from_array(hex32(field("recipient")));
+        let commitment = hex32(field("commitment"));
+        assert_eq!(note.commitment().unwrap(), commitment);
+
+        let witness = WithdrawWitness::build(
+            &note,
+            0,
+            &[commitment],
+            depth,
+            recipient,
+            relayer,
+            field("relayer_fee").parse().unwrap(),
+            [0u8; 32],
+        )
+        .unwrap();
+
+        assert_eq!(witness.path.root, hex32(field("merkle_root")));
+        assert_eq!(
+            witness.public.nullifier_hash,
+            hex32(field("nullifier_hash"))
+        );
+        let expected: Vec<String> =
+            serde_json::from_value(fixture["withdraw"]["public_signals"].clone()).unwrap();
+        assert_eq!(expected.len(), WITHDRAW_PUBLIC_INPUTS);
+        assert_eq!(witness.public_signals(), expected);
+
+        let input: serde_json::Value = serde_json::from_str(&witness.to_json()).unwrap();
+        let mut keys: Vec<&str> = input
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        let mut names = WITHDRAW_INPUT_NAMES.to_vec();
+        names.sort_unstable();
+        keys.sort_unstable();
+        assert_eq!(keys, names);
+        assert_eq!(input["secret"], "1592614637");
+        assert_eq!(
+            input["merkle_path"].as_array().unwrap().len(),
+            depth as usize
+        );
+        assert!(input["merkle_path_indices"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|i| i == "0"));
+    }
+
+    #[test]
+    fn test_paths_match_incremental_tree() {
+        let depth = 8;
+        let leaves: Vec<[u8; 32]> = (0..13).map(leaf).collect();
+
+        let mut tree = MerkleTreeV2 {
+            pool: Pubkey::default(),
+            depth: 0,
+            next_leaf_index: 0,
+            current_root: [0u8; 32],
+            root_history: Vec::new(),
+            root_history_index: 0,
+            root_history_size: 0,
+            filled_subtrees: Vec::new(),
+            zeros: Vec::new(),
+            total_leaves: 0,
+            last_insertion_at: 0,
+            version: 0,
+            poseidon_params_hash: [0u8; 32],
+        };
+        tree.initialize(Pubkey::default(), depth, 64).unwrap();
+        for l in &leaves {
+            tree.insert_leaf(*l, 0).unwrap();
+        }
+
+        for index in [0u32, 1, 6, 12] {
+            let path = merkle_path(&leaves, index, depth).unwrap();
+            assert_eq!(path.root, tree.get_current_root());
+
+            // Fold the path back up to the root
+            let mut node = leaves[index as usize];
+            for (sibling, bit) in path.elements.iter().zip(&path.indices) {
+                node = if *bit == 0 {
+                    poseidon2(&node, sibling).unwrap()
+                } else {
+                    poseidon2(sibling, &node).unwrap()
+                };
+            }
+            assert_eq!(node, path.root);
+            assert_eq!(path.indices[0] as u32, index & 1);
+        }
+    }
+
+    #[test]
+    fn test_build_rejects_bad_inputs() {
+        let note = NotePlaintext {
+            secret: u64_to_be32(7),
+            nullifier: u64_to_be32(9),
+            amount: 1_000,
+            asset_id: u64_to_be32(1),
+        };
+        let commitment = note.commitment().unwrap();
+        let key = Pubkey::new_unique();
+        let build = |leaves: &[[u8; 32]], index: u32, depth: u8, fee: u64| {
+            WithdrawWitness::build(&note, index, leaves, depth, key, key, fee, [0u8; 32])
+        };
+
+        assert!(build(&[commitment], 0, 20, 10).is_ok());
+        assert_eq!(
+            build(&[leaf(0)], 0, 20, 10).unwrap_err(),
+            PrivacyErrorV2::InvalidCommitment.into()
+        );
+        assert_eq!(
+            build(&[commitment], 1, 20, 10).unwrap_err(),
+            PrivacyErrorV2::InvalidCommitment.into()
+        );
+        assert_eq!(
+            build(&[commitment], 0, 2, 10).unwrap_err(),
+            PrivacyErrorV2::InvalidTreeDepth.into()
+        );
+        assert_eq!(
+            build(&[commitment], 0, 20, 1_001).unwrap_err(),
+            PrivacyErrorV2::RelayerFeeExceedsAmount.into()
+        );
+        assert_eq!(
+            merkle_path(&[leaf(0)], 3, 20).unwrap_err(),
+            PrivacyErrorV2::LeafIndexNotFound.into()
+        );
+    }
+}
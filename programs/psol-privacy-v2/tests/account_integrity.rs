@@ -0,0 +1,90 @@
+//! `verify_account_integrity` against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test account_integrity
+//!
+//! A freshly set up pool's verification key, Merkle tree and pending
+//! deposits buffer pass every check. A tree whose root no longer matches
+//! its history is reported as failing without failing the instruction, and
+//! another pool's account is refused.
+//!
+//! Fixture: see tests/common/mod.rs.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::instructions::verify_account_integrity::TREE_ROOT_NOT_IN_HISTORY;
+use psol_privacy_v2::{accounts, instruction};
+
+use common::fixture;
+use common::pool::{assert_program_error, ix, Pool};
+
+/// Offset of `MerkleTreeV2::current_root`: discriminator, pool, depth,
+/// next_leaf_index
+const CURRENT_ROOT_OFFSET: usize = 8 + 32 + 1 + 4;
+
+/// Run the check on `account` and return the failed checks
+async fn failed_checks(pool: &mut Pool, account: Pubkey) -> u32 {
+    let authority = pool.authority.insecure_clone();
+    let data = pool
+        .send_for_return_data(
+            ix(
+                accounts::VerifyAccountIntegrity {
+                    pool_config: pool.pool_config,
+                    target_account: account,
+                },
+                instruction::VerifyAccountIntegrity {},
+            ),
+            &[&authority],
+        )
+        .await
+        .expect("verify_account_integrity");
+    // The runtime strips trailing zero bytes from return data
+    let mut bytes = [0u8; 4];
+    bytes[..data.len()].copy_from_slice(&data);
+    u32::from_le_bytes(bytes)
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_verify_account_integrity() {
+    let f = fixture();
+    let mut pool = Pool::start(&f, 0, |_| {}).await;
+
+    for account in [pool.withdraw_vk, pool.merkle_tree, pool.pending_buffer] {
+        assert_eq!(failed_checks(&mut pool, account).await, 0);
+    }
+
+    // Corrupt the stored root: reported, not rejected
+    let merkle_tree = pool.merkle_tree;
+    let mut tree = pool
+        .ctx
+        .banks_client
+        .get_account(merkle_tree)
+        .await
+        .unwrap()
+        .unwrap();
+    tree.data[CURRENT_ROOT_OFFSET] ^= 1;
+    pool.ctx.set_account(&merkle_tree, &tree.into());
+    pool.refresh_blockhash().await;
+    assert_eq!(
+        failed_checks(&mut pool, merkle_tree).await,
+        TREE_ROOT_NOT_IN_HISTORY
+    );
+
+    let (_, foreign) = pool.start_foreign_pool(&f).await;
+    assert_program_error(
+        pool.send_as_authority(ix(
+            accounts::VerifyAccountIntegrity {
+                pool_config: pool.pool_config,
+                target_account: foreign.merkle_tree,
+            },
+            instruction::VerifyAccountIntegrity {},
+        ))
+        .await,
+        PrivacyErrorV2::InvalidPoolReference,
+    );
+}
@@ -0,0 +1,136 @@
+//! Shielded action target-program allowlists against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test action_allowlist
+//!
+//! The authority creates one allowlist per action type and curates its
+//! programs; outsiders cannot, and each list only governs its own type.
+//!
+//! Fixture: see tests/common/mod.rs.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::system_program;
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::instructions::shielded_cpi::stake_pool::STAKE_POOL_PROGRAM_ID;
+use psol_privacy_v2::state::ActionAllowlist;
+use psol_privacy_v2::{accounts, instruction, ShieldedActionType};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer};
+
+use common::fixture;
+use common::pool::{assert_program_error, ix, Pool};
+
+fn allowlist(pool: &Pool, action_type: ShieldedActionType) -> Pubkey {
+    ActionAllowlist::find_pda(&psol_privacy_v2::ID, &pool.pool_config, action_type).0
+}
+
+fn init_ix(pool: &Pool, action_type: ShieldedActionType) -> Instruction {
+    ix(
+        accounts::InitActionAllowlist {
+            authority: pool.authority.pubkey(),
+            pool_config: pool.pool_config,
+            action_allowlist: allowlist(pool, action_type),
+            system_program: system_program::ID,
+        },
+        instruction::InitActionAllowlist { action_type },
+    )
+}
+
+fn manage(pool: &Pool, authority: Pubkey, list: Pubkey) -> accounts::ManageActionAllowlist {
+    accounts::ManageActionAllowlist {
+        authority,
+        pool_config: pool.pool_config,
+        action_allowlist: list,
+    }
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_action_allowlist_curation() {
+    let f = fixture();
+    let mut pool = Pool::start(&f, 0, |_| {}).await;
+    let authority = pool.authority.pubkey();
+    let stake_list = allowlist(&pool, ShieldedActionType::Stake);
+    let swap_list = allowlist(&pool, ShieldedActionType::DexSwap);
+
+    let outsider = Keypair::new();
+    pool.ctx.set_account(
+        &outsider.pubkey(),
+        &Account::new(10_000_000_000, 0, &system_program::ID).into(),
+    );
+
+    pool.send_as_authority(init_ix(&pool, ShieldedActionType::Stake))
+        .await
+        .expect("init_action_allowlist");
+    pool.send_as_authority(init_ix(&pool, ShieldedActionType::DexSwap))
+        .await
+        .expect("init_action_allowlist");
+    pool.send_as_authority(ix(
+        manage(&pool, authority, stake_list),
+        instruction::AddActionProgram {
+            program: STAKE_POOL_PROGRAM_ID,
+        },
+    ))
+    .await
+    .expect("add_action_program");
+
+    // Curation is authority-only
+    assert_program_error(
+        pool.send(
+            ix(
+                manage(&pool, outsider.pubkey(), stake_list),
+                instruction::AddActionProgram {
+                    program: Pubkey::new_unique(),
+                },
+            ),
+            &[&outsider],
+        )
+        .await,
+        PrivacyErrorV2::Unauthorized,
+    );
+    pool.refresh_blockhash().await;
+    assert_program_error(
+        pool.send_as_authority(ix(
+            manage(&pool, authority, stake_list),
+            instruction::AddActionProgram {
+                program: STAKE_POOL_PROGRAM_ID,
+            },
+        ))
+        .await,
+        PrivacyErrorV2::ProgramAlreadyAllowlisted,
+    );
+
+    let stake: ActionAllowlist = pool.account(stake_list).await;
+    assert_eq!(stake.action_type, ShieldedActionType::Stake as u8);
+    assert_eq!(stake.allowlisted(), &[STAKE_POOL_PROGRAM_ID]);
+    let swap: ActionAllowlist = pool.account(swap_list).await;
+    assert!(swap.allowlisted().is_empty());
+
+    pool.send_as_authority(ix(
+        manage(&pool, authority, stake_list),
+        instruction::RemoveActionProgram {
+            program: STAKE_POOL_PROGRAM_ID,
+        },
+    ))
+    .await
+    .expect("remove_action_program");
+    let stake: ActionAllowlist = pool.account(stake_list).await;
+    assert!(stake.allowlisted().is_empty());
+
+    pool.refresh_blockhash().await;
+    assert_program_error(
+        pool.send_as_authority(ix(
+            manage(&pool, authority, stake_list),
+            instruction::RemoveActionProgram {
+                program: STAKE_POOL_PROGRAM_ID,
+            },
+        ))
+        .await,
+        PrivacyErrorV2::TargetProgramNotAllowlisted,
+    );
+}
@@ -0,0 +1,431 @@
+//! Adversarial proof matrix
+//!
+//! Run with:
+//!   cargo test -p psol-privacy-v2 --test adversarial_proofs
+//!
+//! Starts from the real deposit and withdraw proofs in the e2e fixture and
+//! checks that every tampering is rejected with the error the instruction
+//! handlers would surface: each coordinate of A, B and C, the proof format
+//! tag, substituted-but-valid curve points, and every public input
+//! including the claimed root and nullifier.
+//!
+//! Verification goes through the same path as deposit_masp/withdraw_masp
+//! (`validate()`, `verify_proof_from_account`, `false` -> InvalidProof), so
+//! a verifier that stops checking pairings fails here rather than passing
+//! silently.
+
+mod common;
+
+use anchor_lang::error::Error;
+use anchor_lang::prelude::{Pubkey, Result};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, PrimeField};
+use psol_privacy_v2::crypto::{
+    g1_negate, verify_proof_from_account, DepositPublicInputs, WithdrawPublicInputs,
+    BN254_FR_MODULUS, PROOF_FORMAT_GROTH16_BN254_UNCOMPRESSED,
+};
+use psol_privacy_v2::error::PrivacyErrorV2;
+
+use common::{deposit_vk, fixture, fq, g1, g2, hex32, proof_bytes, withdraw_vk, SnarkjsVk};
+
+/// Proof byte regions in the `A || B || C` encoding
+const REGIONS: [(&str, usize); 8] = [
+    ("A.x", 0),
+    ("A.y", 32),
+    ("B.x_c1", 64),
+    ("B.x_c0", 96),
+    ("B.y_c1", 128),
+    ("B.y_c0", 160),
+    ("C.x", 192),
+    ("C.y", 224),
+];
+
+/// Offsets within a 32-byte coordinate to flip: top byte, middle, bottom
+const OFFSETS: [usize; 3] = [0, 15, 31];
+
+struct Vk {
+    alpha_g1: [u8; 64],
+    beta_g2: [u8; 128],
+    gamma_g2: [u8; 128],
+    delta_g2: [u8; 128],
+    ic: Vec<[u8; 64]>,
+}
+
+impl Vk {
+    fn from_snarkjs(vk: &SnarkjsVk) -> Self {
+        Self {
+            alpha_g1: g1(&vk.vk_alpha_1),
+            beta_g2: g2(&vk.vk_beta_2),
+            gamma_g2: g2(&vk.vk_gamma_2),
+            delta_g2: g2(&vk.vk_delta_2),
+            ic: vk.ic.iter().map(|p| g1(p)).collect(),
+        }
+    }
+
+    /// Mirrors the proof check in the deposit/withdraw handlers
+    fn check(&self, proof: &[u8], inputs: &[[u8; 32]]) -> Result<()> {
+        let is_valid = verify_proof_from_account(
+            &self.alpha_g1,
+            &self.beta_g2,
+            &self.gamma_g2,
+            &self.delta_g2,
+            &self.ic,
+            proof,
+            inputs,
+        )?;
+        if !is_valid {
+            return Err(PrivacyErrorV2::InvalidProof.into());
+        }
+        Ok(())
+    }
+}
+
+struct WithdrawCase {
+    vk: Vk,
+    proof: Vec<u8>,
+    inputs: WithdrawPublicInputs,
+}
+
+impl WithdrawCase {
+    fn load() -> Self {
+        let f = fixture();
+        let relayer = Pubkey::new_from_array(
+            hex::decode(&f.relayer_keypair).unwrap()[32..]
+                .try_into()
+                .unwrap(),
+        );
+        Self {
+            vk: Vk::from_snarkjs(&withdraw_vk()),
+            proof: proof_bytes(&f.withdraw.proof),
+            inputs: WithdrawPublicInputs::new(
+                hex32(&f.merkle_root),
+                hex32(&f.nullifier_hash),
+                hex32(&f.asset_id),
+                Pubkey::new_from_array(hex32(&f.recipient)),
+                f.amount.parse().unwrap(),
+                relayer,
+                f.relayer_fee.parse().unwrap(),
+                [0u8; 32],
+            ),
+        }
+    }
+
+    fn check(&self, proof: &[u8], inputs: &WithdrawPublicInputs) -> Result<()> {
+        inputs.validate()?;
+        self.vk.check(proof, &inputs.to_field_elements())
+    }
+}
+
+struct DepositCase {
+    vk: Vk,
+    proof: Vec<u8>,
+    inputs: DepositPublicInputs,
+}
+
+impl DepositCase {
+    fn load() -> Self {
+        let f = fixture();
+        Self {
+            vk: Vk::from_snarkjs(&deposit_vk()),
+            proof: proof_bytes(&f.deposit.proof),
+            inputs: DepositPublicInputs::new(
+                hex32(&f.commitment),
+                f.amount.parse().unwrap(),
+                hex32(&f.asset_id),
+            ),
+        }
+    }
+
+    fn check(&self, proof: &[u8], inputs: &DepositPublicInputs) -> Result<()> {
+        inputs.validate()?;
+        self.vk.check(proof, &inputs.to_field_elements())
+    }
+}
+
+fn expect_err(result: Result<()>, expected: PrivacyErrorV2, what: &str) {
+    match result {
+        Ok(()) => panic!("{what}: accepted"),
+        Err(err) => assert_eq!(err, Error::from(expected), "{what}"),
+    }
+}
+
+/// `x + 1 mod r` for a canonical big-endian scalar
+fn bump(x: &[u8; 32]) -> [u8; 32] {
+    let v = ark_bn254::Fr::from_be_bytes_mod_order(x) + ark_bn254::Fr::from(1u64);
+    v.into_bigint().to_bytes_be().try_into().unwrap()
+}
+
+fn negate_g2(p: &[u8; 128]) -> [u8; 128] {
+    let c = |i: usize| ark_bn254::Fq::from_be_bytes_mod_order(&p[i..i + 32]);
+    let point = ark_bn254::G2Affine::new(
+        ark_bn254::Fq2::new(c(32), c(0)),
+        ark_bn254::Fq2::new(c(96), c(64)),
+    );
+    let neg = -point;
+    let (x, y) = neg.xy().unwrap();
+    let mut out = [0u8; 128];
+    for (i, v) in [x.c1, x.c0, y.c1, y.c0].iter().enumerate() {
+        out[i * 32..(i + 1) * 32].copy_from_slice(&fq(&v.into_bigint().to_string()));
+    }
+    out
+}
+
+// ============================================================================
+// BASELINE
+// ============================================================================
+
+#[test]
+fn test_untampered_proofs_verify() {
+    let w = WithdrawCase::load();
+    w.check(&w.proof, &w.inputs).unwrap();
+
+    let mut tagged = vec![PROOF_FORMAT_GROTH16_BN254_UNCOMPRESSED];
+    tagged.extend_from_slice(&w.proof);
+    w.check(&tagged, &w.inputs).unwrap();
+
+    let d = DepositCase::load();
+    d.check(&d.proof, &d.inputs).unwrap();
+}
+
+// ============================================================================
+// PROOF BYTES
+// ============================================================================
+
+#[test]
+fn test_withdraw_proof_byte_flips_rejected() {
+    let w = WithdrawCase::load();
+    for (region, start) in REGIONS {
+        for offset in OFFSETS {
+            let mut proof = w.proof.clone();
+            proof[start + offset] ^= 0x01;
+            expect_err(
+                w.check(&proof, &w.inputs),
+                PrivacyErrorV2::CryptographyError,
+                &format!("withdraw {region} byte {offset}"),
+            );
+        }
+    }
+}
+
+#[test]
+fn test_deposit_proof_byte_flips_rejected() {
+    let d = DepositCase::load();
+    for (region, start) in REGIONS {
+        for offset in OFFSETS {
+            let mut proof = d.proof.clone();
+            proof[start + offset] ^= 0x01;
+            expect_err(
+                d.check(&proof, &d.inputs),
+                PrivacyErrorV2::CryptographyError,
+                &format!("deposit {region} byte {offset}"),
+            );
+        }
+    }
+}
+
+#[test]
+fn test_non_canonical_coordinates_rejected() {
+    let w = WithdrawCase::load();
+    for (region, start) in REGIONS {
+        let mut proof = w.proof.clone();
+        proof[start..start + 32].fill(0xff);
+        expect_err(
+            w.check(&proof, &w.inputs),
+            PrivacyErrorV2::CryptographyError,
+            &format!("{region} >= p"),
+        );
+    }
+}
+
+#[test]
+fn test_proof_format_tampering_rejected() {
+    let w = WithdrawCase::load();
+
+    let mut tagged = vec![0x7f];
+    tagged.extend_from_slice(&w.proof);
+    expect_err(
+        w.check(&tagged, &w.inputs),
+        PrivacyErrorV2::UnsupportedProofFormat,
+        "unknown format tag",
+    );
+
+    expect_err(
+        w.check(&w.proof[..255], &w.inputs),
+        PrivacyErrorV2::InvalidProofFormat,
+        "truncated proof",
+    );
+}
+
+#[test]
+fn test_valid_but_wrong_points_rejected() {
+    let w = WithdrawCase::load();
+    let a: [u8; 64] = w.proof[0..64].try_into().unwrap();
+    let b: [u8; 128] = w.proof[64..192].try_into().unwrap();
+    let c: [u8; 64] = w.proof[192..256].try_into().unwrap();
+
+    let build = |a: &[u8; 64], b: &[u8; 128], c: &[u8; 64]| {
+        let mut out = Vec::with_capacity(256);
+        out.extend_from_slice(a);
+        out.extend_from_slice(b);
+        out.extend_from_slice(c);
+        out
+    };
+
+    let neg_a = g1_negate(&a).unwrap();
+    let neg_b = negate_g2(&b);
+    let cases = [
+        ("-A", build(&neg_a, &b, &c)),
+        ("-B", build(&a, &neg_b, &c)),
+        ("A <-> C", build(&c, &b, &a)),
+        ("A = identity", build(&[0u8; 64], &b, &c)),
+        ("C = identity", build(&a, &b, &[0u8; 64])),
+        ("B = identity", build(&a, &[0u8; 128], &c)),
+    ];
+    for (what, proof) in cases {
+        expect_err(
+            w.check(&proof, &w.inputs),
+            PrivacyErrorV2::InvalidProof,
+            what,
+        );
+    }
+
+    // Negating both A and B is the standard Groth16 malleation and still
+    // verifies; double-spend protection rests on the nullifier PDA, not on
+    // the proof bytes being unique
+    w.check(&build(&neg_a, &neg_b, &c), &w.inputs).unwrap();
+
+    // A valid proof for a different circuit
+    let d = DepositCase::load();
+    expect_err(
+        w.check(&d.proof, &w.inputs),
+        PrivacyErrorV2::InvalidProof,
+        "deposit proof against withdraw VK",
+    );
+}
+
+// ============================================================================
+// PUBLIC INPUTS
+// ============================================================================
+
+#[test]
+fn test_withdraw_public_input_tampering_rejected() {
+    let w = WithdrawCase::load();
+    let other = Pubkey::new_from_array([9u8; 32]);
+
+    let mut cases: Vec<(&str, WithdrawPublicInputs)> = Vec::new();
+    let mut push = |what, edit: &dyn Fn(&mut WithdrawPublicInputs)| {
+        let mut inputs = w.inputs.clone();
+        edit(&mut inputs);
+        cases.push((what, inputs));
+    };
+    push("merkle_root", &|i| i.merkle_root = bump(&i.merkle_root));
+    push("nullifier_hash", &|i| {
+        i.nullifier_hash = bump(&i.nullifier_hash)
+    });
+    push("asset_id", &|i| i.asset_id = bump(&i.asset_id));
+    push("recipient", &|i| i.recipient = other);
+    push("amount + 1", &|i| i.amount += 1);
+    push("amount - 1", &|i| i.amount -= 1);
+    push("relayer", &|i| i.relayer = other);
+    push("relayer_fee", &|i| i.relayer_fee += 1);
+    push("public_data_hash", &|i| {
+        i.public_data_hash = bump(&i.public_data_hash)
+    });
+    for (what, inputs) in &cases {
+        expect_err(
+            w.check(&w.proof, inputs),
+            PrivacyErrorV2::InvalidProof,
+            what,
+        );
+    }
+
+    // The circuit truncates pubkeys to 31 bytes; a recipient that differs
+    // only in the dropped byte maps to the same scalar
+    let mut recipient = w.inputs.recipient.to_bytes();
+    recipient[31] ^= 0x01;
+    let mut inputs = w.inputs.clone();
+    inputs.recipient = Pubkey::new_from_array(recipient);
+    w.check(&w.proof, &inputs).unwrap();
+}
+
+#[test]
+fn test_withdraw_claimed_root_and_nullifier_rejected() {
+    let w = WithdrawCase::load();
+
+    let mut inputs = w.inputs.clone();
+    inputs.merkle_root = [0u8; 32];
+    expect_err(
+        w.check(&w.proof, &inputs),
+        PrivacyErrorV2::InvalidMerkleRoot,
+        "zero root",
+    );
+
+    let mut inputs = w.inputs.clone();
+    inputs.nullifier_hash = [0u8; 32];
+    expect_err(
+        w.check(&w.proof, &inputs),
+        PrivacyErrorV2::InvalidNullifier,
+        "zero nullifier",
+    );
+
+    // root + r and nullifier + r would alias the same field element if
+    // the verifier reduced instead of rejecting
+    for field in ["merkle_root", "nullifier_hash"] {
+        let mut inputs = w.inputs.clone();
+        let target = match field {
+            "merkle_root" => &mut inputs.merkle_root,
+            _ => &mut inputs.nullifier_hash,
+        };
+        *target = BN254_FR_MODULUS;
+        expect_err(
+            w.check(&w.proof, &inputs),
+            PrivacyErrorV2::InvalidPublicInputs,
+            &format!("{field} = r"),
+        );
+    }
+
+    let mut inputs = w.inputs.clone();
+    inputs.relayer_fee = inputs.amount + 1;
+    expect_err(
+        w.check(&w.proof, &inputs),
+        PrivacyErrorV2::RelayerFeeExceedsAmount,
+        "fee above amount",
+    );
+}
+
+#[test]
+fn test_deposit_public_input_tampering_rejected() {
+    let d = DepositCase::load();
+
+    let mut inputs = d.inputs.clone();
+    inputs.commitment = bump(&inputs.commitment);
+    expect_err(
+        d.check(&d.proof, &inputs),
+        PrivacyErrorV2::InvalidProof,
+        "commitment",
+    );
+
+    let mut inputs = d.inputs.clone();
+    inputs.amount += 1;
+    expect_err(
+        d.check(&d.proof, &inputs),
+        PrivacyErrorV2::InvalidProof,
+        "amount",
+    );
+
+    let mut inputs = d.inputs.clone();
+    inputs.asset_id = bump(&inputs.asset_id);
+    expect_err(
+        d.check(&d.proof, &inputs),
+        PrivacyErrorV2::InvalidProof,
+        "asset_id",
+    );
+
+    let mut inputs = d.inputs.clone();
+    inputs.commitment = BN254_FR_MODULUS;
+    expect_err(
+        d.check(&d.proof, &inputs),
+        PrivacyErrorV2::InvalidPublicInputs,
+        "commitment = r",
+    );
+}
@@ -0,0 +1,177 @@
+//! Pool cloning against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test clone_pool
+//!
+//! The harness pool is configured (protocol fee, relayer fee bounds,
+//! compliance, asset minimum withdrawal), cloned under another pool id and
+//! its asset copied over: the new pool starts with the same settings and a
+//! fresh tree and vault.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token;
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::state::{
+    AssetVault, ComplianceConfig, MerkleTreeV2, PoolConfigV2, RelayerRegistry,
+};
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::signature::Signer;
+
+use common::fixture;
+use common::pool::{assert_program_error, ix, Pool, PoolAccounts, ROOT_HISTORY_SIZE};
+
+const POOL_ID: u64 = 7;
+const PROTOCOL_FEE_BPS: u16 = 25;
+const MIN_WITHDRAWAL: u64 = 1_000;
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_clone_carries_settings_to_a_fresh_pool() {
+    let f = fixture();
+    let mut pool = Pool::start(&f, 0, |_| {}).await;
+    let authority = pool.authority.pubkey();
+    let audit_pubkey = Pubkey::new_unique();
+
+    let configure = [
+        (
+            "set_protocol_fee",
+            ix(
+                accounts::SetProtocolFee {
+                    authority,
+                    pool_config: pool.pool_config,
+                },
+                instruction::SetProtocolFee {
+                    fee_bps: PROTOCOL_FEE_BPS,
+                },
+            ),
+        ),
+        (
+            "configure_relayer_registry",
+            ix(
+                accounts::ConfigureRelayerRegistry {
+                    authority,
+                    pool_config: pool.pool_config,
+                    relayer_registry: pool.relayer_registry,
+                },
+                instruction::ConfigureRelayerRegistry {
+                    min_fee_bps: 20,
+                    max_fee_bps: 300,
+                    require_stake: false,
+                    min_stake_amount: 0,
+                },
+            ),
+        ),
+        (
+            "configure_compliance",
+            ix(
+                accounts::ConfigureCompliance {
+                    authority,
+                    pool_config: pool.pool_config,
+                    compliance_config: pool.compliance_config,
+                },
+                instruction::ConfigureCompliance {
+                    require_encrypted_note: true,
+                    audit_pubkey: Some(audit_pubkey),
+                    metadata_schema_version: 2,
+                },
+            ),
+        ),
+        (
+            "set_asset_min_withdrawal",
+            ix(
+                accounts::SetAssetMinWithdrawal {
+                    authority,
+                    pool_config: pool.pool_config,
+                    asset_vault: pool.asset_vault,
+                },
+                instruction::SetAssetMinWithdrawal {
+                    asset_id: pool.asset_id,
+                    min_withdrawal: MIN_WITHDRAWAL,
+                },
+            ),
+        ),
+    ];
+    for (name, ix) in configure {
+        pool.send_as_authority(ix).await.expect(name);
+    }
+
+    let clone = PoolAccounts::derive(&authority, POOL_ID, &pool.asset_id);
+    pool.send_as_authority(ix(
+        accounts::ClonePoolConfig {
+            authority,
+            source_pool_config: pool.pool_config,
+            source_relayer_registry: pool.relayer_registry,
+            source_compliance_config: pool.compliance_config,
+            pool_config: clone.pool_config,
+            merkle_tree: clone.merkle_tree,
+            relayer_registry: clone.relayer_registry,
+            compliance_config: clone.compliance_config,
+            system_program: system_program::ID,
+        },
+        instruction::ClonePoolConfig {
+            pool_id: POOL_ID,
+            root_history_size: ROOT_HISTORY_SIZE,
+        },
+    ))
+    .await
+    .expect("clone_pool_config");
+
+    let config: PoolConfigV2 = pool.account(clone.pool_config).await;
+    assert_eq!(config.pool_id, POOL_ID);
+    assert_eq!(config.protocol_fee_bps, PROTOCOL_FEE_BPS);
+    assert_eq!(config.tree_depth, f.tree_depth);
+    assert_eq!(config.relayer_registry, clone.relayer_registry);
+    assert_eq!(config.registered_asset_count, 0);
+    assert_eq!(config.vk_configured, 0);
+    let registry: RelayerRegistry = pool.account(clone.relayer_registry).await;
+    assert_eq!((registry.min_fee_bps, registry.max_fee_bps), (20, 300));
+    assert_eq!(registry.pool, clone.pool_config);
+    let compliance: ComplianceConfig = pool.account(clone.compliance_config).await;
+    assert!(compliance.require_encrypted_note);
+    assert_eq!(compliance.audit_pubkey, audit_pubkey);
+    let tree: MerkleTreeV2 = pool.account(clone.merkle_tree).await;
+    assert_eq!(tree.next_leaf_index, 0);
+
+    let (source_pool, source_vault, asset_id) = (pool.pool_config, pool.asset_vault, pool.asset_id);
+    let clone_asset = |mint: Option<Pubkey>, vault_token_account: Option<Pubkey>| {
+        ix(
+            accounts::CloneAssetConfig {
+                authority,
+                source_pool_config: source_pool,
+                source_asset_vault: source_vault,
+                pool_config: clone.pool_config,
+                mint,
+                asset_vault: clone.asset_vault,
+                vault_token_account,
+                token_program: spl_token::id(),
+                system_program: system_program::ID,
+            },
+            instruction::CloneAssetConfig { asset_id },
+        )
+    };
+
+    // A token asset needs its mint and a vault token account
+    assert_program_error(
+        pool.send_as_authority(clone_asset(None, None)).await,
+        PrivacyErrorV2::MissingAssetAccount,
+    );
+    let mint = pool.mint;
+    pool.send_as_authority(clone_asset(Some(mint), Some(clone.vault_token)))
+        .await
+        .expect("clone_asset_config");
+
+    let vault: AssetVault = pool.account(clone.asset_vault).await;
+    assert_eq!(vault.pool, clone.pool_config);
+    assert_eq!(vault.mint, pool.mint);
+    assert_eq!(vault.token_account, clone.vault_token);
+    assert_eq!(vault.min_withdrawal, MIN_WITHDRAWAL);
+    assert_eq!(vault.shielded_balance, 0);
+    assert_eq!(pool.token_balance(clone.vault_token).await, 0);
+    let config: PoolConfigV2 = pool.account(clone.pool_config).await;
+    assert_eq!(config.registered_asset_count, 1);
+}
@@ -0,0 +1,305 @@
+//! Commitment reservations against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test commitment_reservation
+//!
+//! A depositor reserves a commitment before showing its proof; another
+//! wallet replaying the deposit is refused while the reservation holds,
+//! and the depositor's own deposit consumes it. A reservation can also be
+//! released by the wallet that made it, and a commitment is reserved at
+//! most once, so a wallet that reserves another's commitment first cannot
+//! keep renewing the hold.
+//!
+//! Deposits use the native SOL vault and a trapdoor VK that does not bind
+//! the depositor, so the copied proof is otherwise valid.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token;
+use psol_privacy_v2::crypto::DepositPublicInputs;
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::state::asset_vault::NATIVE_SOL_ASSET_ID;
+use psol_privacy_v2::state::{
+    AssetVault, CommitmentReservation, PendingDepositsBuffer, COMMITMENT_RESERVATION_SLOTS,
+};
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::account::Account;
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::signature::{Keypair, Signer};
+
+use common::pool::{assert_program_error, global_config, ix, Pool};
+use common::trapdoor::Trapdoor;
+use common::{fixture, proof_bytes};
+
+const AMOUNT: u64 = LAMPORTS_PER_SOL / 10;
+const COMMITMENT: [u8; 32] = [3u8; 32];
+
+fn reserve_ix(pool: &Pool, depositor: Pubkey) -> Instruction {
+    ix(
+        accounts::ReserveCommitment {
+            depositor,
+            pool_config: pool.pool_config,
+            commitment_reservation: pool.commitment_reservation(&COMMITMENT),
+            system_program: system_program::ID,
+        },
+        instruction::ReserveCommitment {
+            commitment: COMMITMENT,
+        },
+    )
+}
+
+fn release_ix(pool: &Pool, depositor: Pubkey) -> Instruction {
+    ix(
+        accounts::ReleaseCommitmentReservation {
+            depositor,
+            pool_config: pool.pool_config,
+            commitment_reservation: pool.commitment_reservation(&COMMITMENT),
+        },
+        instruction::ReleaseCommitmentReservation {},
+    )
+}
+
+/// Deposit of `COMMITMENT` into the native SOL vault, funded by `depositor`
+fn deposit_ix(pool: &Pool, native_vault: Pubkey, depositor: Pubkey, proof: &[u8]) -> Instruction {
+    ix(
+        accounts::DepositMasp {
+            depositor,
+            pool_config: pool.pool_config,
+            global_config: global_config(),
+            authority: pool.authority.pubkey(),
+            merkle_tree: pool.merkle_tree,
+            pending_buffer: pool.pending_buffer,
+            asset_vault: native_vault,
+            vault_token_account: None,
+            user_token_account: None,
+            mint: None,
+            deposit_vk: pool.deposit_vk,
+            token_program: spl_token::id(),
+            system_program: system_program::ID,
+            instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+            proof_stats: None,
+            anonymity_stats: None,
+            commitment_reservation: pool.commitment_reservation(&COMMITMENT),
+            risk_tiers: None,
+        },
+        instruction::DepositMasp {
+            amount: AMOUNT,
+            commitment: COMMITMENT,
+            asset_id: NATIVE_SOL_ASSET_ID,
+            proof_data: proof.to_vec(),
+            encrypted_note: None,
+        },
+    )
+}
+
+fn funded_wallet(pool: &mut Pool) -> Keypair {
+    let wallet = Keypair::new();
+    pool.ctx.set_account(
+        &wallet.pubkey(),
+        &Account::new(LAMPORTS_PER_SOL, 0, &system_program::ID).into(),
+    );
+    wallet
+}
+
+async fn register_native_vault(pool: &mut Pool) -> Pubkey {
+    let (native_vault, _) = AssetVault::find_pda(
+        &psol_privacy_v2::ID,
+        &pool.pool_config,
+        &NATIVE_SOL_ASSET_ID,
+    );
+    pool.send_as_authority(ix(
+        accounts::RegisterNativeSolAsset {
+            authority: pool.authority.pubkey(),
+            pool_config: pool.pool_config,
+            asset_vault: native_vault,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterNativeSolAsset {},
+    ))
+    .await
+    .expect("register_native_sol_asset");
+    native_vault
+}
+
+async fn account_exists(pool: &mut Pool, address: Pubkey) -> bool {
+    pool.ctx
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .is_some()
+}
+
+#[tokio::test]
+#[cfg_attr(
+    not(sbf_build),
+    ignore = "needs the SBF build of the program (see module docs)"
+)]
+async fn test_reserved_commitment_only_deposited_by_its_depositor() {
+    let f = fixture();
+    let deposit = Trapdoor::new(40 << 8, 3);
+    let withdraw = Trapdoor::new((40 << 8) | 1, 8);
+    let mut pool = Pool::start_with_vks(&f, 0, &deposit.vk(), &withdraw.vk(), |_| {}).await;
+    let native_vault = register_native_vault(&mut pool).await;
+    let inputs =
+        DepositPublicInputs::new(COMMITMENT, AMOUNT, NATIVE_SOL_ASSET_ID).to_field_elements();
+    let proof = proof_bytes(&deposit.prove(&inputs, 1));
+
+    let user = funded_wallet(&mut pool);
+    let copycat = funded_wallet(&mut pool);
+    pool.send(reserve_ix(&pool, user.pubkey()), &[&user])
+        .await
+        .expect("reserve_commitment");
+    let reservation_key = pool.commitment_reservation(&COMMITMENT);
+    let reservation: CommitmentReservation = pool.account(reservation_key).await;
+    assert_eq!(reservation.depositor, user.pubkey());
+    assert_eq!(reservation.commitment, COMMITMENT);
+
+    // The same commitment cannot be reserved twice
+    assert!(pool
+        .send(reserve_ix(&pool, copycat.pubkey()), &[&copycat])
+        .await
+        .is_err());
+
+    // A copied deposit from another wallet is refused
+    assert_program_error(
+        pool.send(
+            deposit_ix(&pool, native_vault, copycat.pubkey(), &proof),
+            &[&copycat],
+        )
+        .await,
+        PrivacyErrorV2::CommitmentReserved,
+    );
+
+    pool.send(
+        deposit_ix(&pool, native_vault, user.pubkey(), &proof),
+        &[&user],
+    )
+    .await
+    .expect("deposit_masp");
+    assert!(!account_exists(&mut pool, reservation_key).await);
+    let buffer: PendingDepositsBuffer = pool.account(pool.pending_buffer).await;
+    assert_eq!(buffer.total_pending, 1);
+}
+
+#[tokio::test]
+#[cfg_attr(
+    not(sbf_build),
+    ignore = "needs the SBF build of the program (see module docs)"
+)]
+async fn test_expired_reservation_admits_others_and_is_released() {
+    let f = fixture();
+    let deposit = Trapdoor::new(41 << 8, 3);
+    let withdraw = Trapdoor::new((41 << 8) | 1, 8);
+    let mut pool = Pool::start_with_vks(&f, 0, &deposit.vk(), &withdraw.vk(), |_| {}).await;
+    let native_vault = register_native_vault(&mut pool).await;
+    let inputs =
+        DepositPublicInputs::new(COMMITMENT, AMOUNT, NATIVE_SOL_ASSET_ID).to_field_elements();
+    let proof = proof_bytes(&deposit.prove(&inputs, 1));
+
+    let user = funded_wallet(&mut pool);
+    let other = funded_wallet(&mut pool);
+    pool.send(reserve_ix(&pool, user.pubkey()), &[&user])
+        .await
+        .expect("reserve_commitment");
+
+    let mut clock: Clock = pool.ctx.banks_client.get_sysvar().await.unwrap();
+    clock.slot += COMMITMENT_RESERVATION_SLOTS;
+    pool.ctx.set_sysvar(&clock);
+    pool.refresh_blockhash().await;
+
+    pool.send(
+        deposit_ix(&pool, native_vault, other.pubkey(), &proof),
+        &[&other],
+    )
+    .await
+    .expect("deposit_masp after expiry");
+    let reservation_key = pool.commitment_reservation(&COMMITMENT);
+    assert!(account_exists(&mut pool, reservation_key).await);
+
+    assert_program_error(
+        pool.send(release_ix(&pool, other.pubkey()), &[&other])
+            .await,
+        PrivacyErrorV2::Unauthorized,
+    );
+    pool.send(release_ix(&pool, user.pubkey()), &[&user])
+        .await
+        .expect("release_commitment_reservation");
+    let reservation: CommitmentReservation = pool.account(reservation_key).await;
+    assert_eq!(reservation.depositor, user.pubkey());
+}
+
+#[tokio::test]
+#[cfg_attr(
+    not(sbf_build),
+    ignore = "needs the SBF build of the program (see module docs)"
+)]
+async fn test_front_run_reservation_cannot_be_renewed() {
+    let f = fixture();
+    let deposit = Trapdoor::new(42 << 8, 3);
+    let withdraw = Trapdoor::new((42 << 8) | 1, 8);
+    let mut pool = Pool::start_with_vks(&f, 0, &deposit.vk(), &withdraw.vk(), |_| {}).await;
+    let native_vault = register_native_vault(&mut pool).await;
+    let inputs =
+        DepositPublicInputs::new(COMMITMENT, AMOUNT, NATIVE_SOL_ASSET_ID).to_field_elements();
+    let proof = proof_bytes(&deposit.prove(&inputs, 1));
+
+    // A third party copies the commitment from the user's pending
+    // reservation and lands its own first
+    let user = funded_wallet(&mut pool);
+    let front_runner = funded_wallet(&mut pool);
+    pool.send(reserve_ix(&pool, front_runner.pubkey()), &[&front_runner])
+        .await
+        .expect("reserve_commitment");
+    assert!(pool
+        .send(reserve_ix(&pool, user.pubkey()), &[&user])
+        .await
+        .is_err());
+    assert_program_error(
+        pool.send(
+            deposit_ix(&pool, native_vault, user.pubkey(), &proof),
+            &[&user],
+        )
+        .await,
+        PrivacyErrorV2::CommitmentReserved,
+    );
+
+    // Releasing and reserving again does not renew the hold
+    pool.send(release_ix(&pool, front_runner.pubkey()), &[&front_runner])
+        .await
+        .expect("release_commitment_reservation");
+    pool.refresh_blockhash().await;
+    assert!(pool
+        .send(reserve_ix(&pool, front_runner.pubkey()), &[&front_runner])
+        .await
+        .is_err());
+
+    // Nor does waiting out the reservation
+    let mut clock: Clock = pool.ctx.banks_client.get_sysvar().await.unwrap();
+    clock.slot += COMMITMENT_RESERVATION_SLOTS;
+    pool.ctx.set_sysvar(&clock);
+    pool.refresh_blockhash().await;
+    assert!(pool
+        .send(reserve_ix(&pool, front_runner.pubkey()), &[&front_runner])
+        .await
+        .is_err());
+    let reservation: CommitmentReservation =
+        pool.account(pool.commitment_reservation(&COMMITMENT)).await;
+    assert_eq!(reservation.depositor, front_runner.pubkey());
+    assert!(reservation.expires_slot <= clock.slot);
+
+    pool.send(
+        deposit_ix(&pool, native_vault, user.pubkey(), &proof),
+        &[&user],
+    )
+    .await
+    .expect("deposit_masp once the hold has ended");
+    let buffer: PendingDepositsBuffer = pool.account(pool.pending_buffer).await;
+    assert_eq!(buffer.total_pending, 1);
+}
@@ -0,0 +1,134 @@
+//! Shared fixture loading for the integration tests
+//!
+//! Fixture: test_data/e2e_fixture.json plus the deposit/withdraw VKs
+//! (regenerate with test_data/generate_e2e_fixture.mjs). snarkjs points
+//! are converted to the program's big-endian encodings.
+
+#![allow(dead_code)]
+
+pub mod pool;
+pub mod trapdoor;
+
+use std::str::FromStr;
+
+use ark_ff::{BigInteger, PrimeField};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct Fixture {
+    pub tree_depth: u8,
+    pub mint: String,
+    pub relayer_keypair: String,
+    pub recipient: String,
+    pub amount: String,
+    pub relayer_fee: String,
+    pub asset_id: String,
+    pub commitment: String,
+    pub merkle_root: String,
+    pub nullifier_hash: String,
+    pub deposit: ProofCase,
+    pub withdraw: ProofCase,
+    /// Further proven notes of the same asset (amounts 1e6, 2e6, ...)
+    pub deposits: Vec<DepositNote>,
+}
+
+#[derive(Deserialize)]
+pub struct DepositNote {
+    pub amount: String,
+    pub commitment: String,
+    pub proof: SnarkjsProof,
+    pub public_signals: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ProofCase {
+    pub proof: SnarkjsProof,
+    pub public_signals: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct SnarkjsProof {
+    pub pi_a: Vec<String>,
+    pub pi_b: Vec<Vec<String>>,
+    pub pi_c: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct SnarkjsVk {
+    pub vk_alpha_1: Vec<String>,
+    pub vk_beta_2: Vec<Vec<String>>,
+    pub vk_gamma_2: Vec<Vec<String>>,
+    pub vk_delta_2: Vec<Vec<String>>,
+    #[serde(rename = "IC")]
+    pub ic: Vec<Vec<String>>,
+}
+
+pub fn load<T: for<'de> Deserialize<'de>>(json: &str) -> T {
+    serde_json::from_str(json).expect("parse fixture")
+}
+
+pub fn fixture() -> Fixture {
+    load(include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../test_data/e2e_fixture.json"
+    )))
+}
+
+pub fn deposit_vk() -> SnarkjsVk {
+    load(include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../test_data/deposit_vk.json"
+    )))
+}
+
+pub fn withdraw_vk() -> SnarkjsVk {
+    load(include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../test_data/withdraw_vk.json"
+    )))
+}
+
+pub fn hex32(s: &str) -> [u8; 32] {
+    hex::decode(s)
+        .expect("hex decode")
+        .try_into()
+        .expect("32 bytes")
+}
+
+/// Decimal base-field element to 32-byte big-endian
+pub fn fq(s: &str) -> [u8; 32] {
+    let v = ark_bn254::Fq::from_str(s).expect("Fq");
+    v.into_bigint().to_bytes_be().try_into().unwrap()
+}
+
+/// Decimal scalar-field element to 32-byte big-endian
+pub fn fr(s: &str) -> [u8; 32] {
+    let v = ark_bn254::Fr::from_str(s).expect("Fr");
+    v.into_bigint().to_bytes_be().try_into().unwrap()
+}
+
+/// snarkjs G1 `[x, y, z]` to `x || y`
+pub fn g1(p: &[String]) -> [u8; 64] {
+    let mut out = [0u8; 64];
+    out[..32].copy_from_slice(&fq(&p[0]));
+    out[32..].copy_from_slice(&fq(&p[1]));
+    out
+}
+
+/// snarkjs G2 `[[x0, x1], [y0, y1], ..]` to `x1 || x0 || y1 || y0`
+pub fn g2(p: &[Vec<String>]) -> [u8; 128] {
+    let mut out = [0u8; 128];
+    out[..32].copy_from_slice(&fq(&p[0][1]));
+    out[32..64].copy_from_slice(&fq(&p[0][0]));
+    out[64..96].copy_from_slice(&fq(&p[1][1]));
+    out[96..].copy_from_slice(&fq(&p[1][0]));
+    out
+}
+
+pub fn proof_bytes(p: &SnarkjsProof) -> Vec<u8> {
+    let mut out = Vec::with_capacity(256);
+    out.extend_from_slice(&g1(&p.pi_a));
+    out.extend_from_slice(&g2(&p.pi_b));
+    out.extend_from_slice(&g1(&p.pi_c));
+    out
+}
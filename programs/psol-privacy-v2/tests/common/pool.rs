@@ -0,0 +1,527 @@
+//! program-test harness for a single-asset pool
+//!
+//! `Pool::start` brings up the SBF build of the program with the fixture
+//! mint, initializes the pool, registries, pending buffer and asset vault,
+//! and uploads the deposit and withdraw VKs. The payer is the pool
+//! authority and the depositor. `start_foreign_pool` sets up a second pool
+//! for the same mint under another authority, `start_sibling_pool` one
+//! under the same authority with another pool id.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{system_program, AccountDeserialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use anchor_spl::token::spl_token::solana_program::program_option::COption;
+use anchor_spl::token::spl_token::solana_program::program_pack::Pack;
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::state::{
+    AssetVault, CommitmentReservation, ComplianceConfig, GlobalConfig, MerkleTreeV2,
+    PendingDepositsBuffer, PoolConfigV2, RelayerRegistry, VerificationKeyAccountV2,
+};
+use psol_privacy_v2::{accounts, instruction, ProofType};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+use super::{
+    deposit_vk, g1, g2, hex32, proof_bytes, withdraw_vk, Fixture, SnarkjsProof, SnarkjsVk,
+};
+
+pub const ROOT_HISTORY_SIZE: u16 = 64;
+
+pub fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    let mut data = vec![0u8; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+pub fn mint_account(supply: u64) -> Account {
+    let mut data = vec![0u8; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        mint_authority: COption::None,
+        supply,
+        decimals: 9,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+pub fn ix(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: psol_privacy_v2::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// The program-wide config PDA (absent unless a test creates it)
+pub fn global_config() -> Pubkey {
+    GlobalConfig::find_pda(&psol_privacy_v2::ID).0
+}
+
+/// Assert a transaction failed with `expected` from the program
+pub fn assert_program_error(result: Result<(), BanksClientError>, expected: PrivacyErrorV2) {
+    assert_error_code(result, u32::from(expected), &format!("{expected:?}"));
+}
+
+/// Assert a transaction failed with custom error `code` (program or Anchor)
+pub fn assert_error_code(result: Result<(), BanksClientError>, code: u32, what: &str) {
+    match result {
+        Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(actual),
+        ))) => assert_eq!(actual, code, "expected {what}"),
+        other => panic!("expected {what}, got {other:?}"),
+    }
+}
+
+/// PDAs of one pool for the fixture asset
+#[derive(Clone, Copy, Debug)]
+pub struct PoolAccounts {
+    pub pool_id: u64,
+    pub pool_config: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub relayer_registry: Pubkey,
+    pub compliance_config: Pubkey,
+    pub pending_buffer: Pubkey,
+    pub asset_vault: Pubkey,
+    pub vault_token: Pubkey,
+    pub deposit_vk: Pubkey,
+    pub withdraw_vk: Pubkey,
+}
+
+impl PoolAccounts {
+    pub fn derive(authority: &Pubkey, pool_id: u64, asset_id: &[u8; 32]) -> Self {
+        let program_id = psol_privacy_v2::ID;
+        let (pool_config, _) = PoolConfigV2::find_pda(&program_id, authority, pool_id);
+        let (merkle_tree, _) = MerkleTreeV2::find_pda(&program_id, &pool_config);
+        let (relayer_registry, _) = RelayerRegistry::find_pda(&program_id, &pool_config);
+        let (compliance_config, _) = ComplianceConfig::find_pda(&program_id, &pool_config);
+        let (pending_buffer, _) = Pubkey::find_program_address(
+            &[PendingDepositsBuffer::SEED_PREFIX, pool_config.as_ref()],
+            &program_id,
+        );
+        let (asset_vault, _) = AssetVault::find_pda(&program_id, &pool_config, asset_id);
+        let (vault_token, _) =
+            Pubkey::find_program_address(&[b"vault_token", asset_vault.as_ref()], &program_id);
+        let (deposit_vk, _) =
+            VerificationKeyAccountV2::find_pda(&program_id, &pool_config, ProofType::Deposit);
+        let (withdraw_vk, _) =
+            VerificationKeyAccountV2::find_pda(&program_id, &pool_config, ProofType::Withdraw);
+        Self {
+            pool_id,
+            pool_config,
+            merkle_tree,
+            relayer_registry,
+            compliance_config,
+            pending_buffer,
+            asset_vault,
+            vault_token,
+            deposit_vk,
+            withdraw_vk,
+        }
+    }
+}
+
+pub struct Pool {
+    pub ctx: ProgramTestContext,
+    pub authority: Keypair,
+    pub mint: Pubkey,
+    pub asset_id: [u8; 32],
+    pub pool_config: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub relayer_registry: Pubkey,
+    pub compliance_config: Pubkey,
+    pub pending_buffer: Pubkey,
+    pub asset_vault: Pubkey,
+    pub vault_token: Pubkey,
+    pub deposit_vk: Pubkey,
+    pub withdraw_vk: Pubkey,
+    pub user_token: Pubkey,
+}
+
+impl Pool {
+    /// Start the program and set up the pool; `user_token` holds `supply`
+    ///
+    /// `setup` may add accounts before the bank starts.
+    pub async fn start(f: &Fixture, supply: u64, setup: impl FnOnce(&mut ProgramTest)) -> Self {
+        Self::start_with_vks(f, supply, &deposit_vk(), &withdraw_vk(), setup).await
+    }
+
+    /// `start`, uploading the given deposit and withdraw VKs instead of the
+    /// circuit ones
+    pub async fn start_with_vks(
+        f: &Fixture,
+        supply: u64,
+        deposit: &SnarkjsVk,
+        withdraw: &SnarkjsVk,
+        setup: impl FnOnce(&mut ProgramTest),
+    ) -> Self {
+        let program_id = psol_privacy_v2::ID;
+        let mint = Pubkey::new_from_array(hex32(&f.mint));
+        let asset_id = hex32(&f.asset_id);
+        let user_token = Pubkey::new_unique();
+
+        let mut pt = ProgramTest::new("psol_privacy_v2", program_id, None);
+        pt.prefer_bpf(true);
+        pt.add_account(mint, mint_account(supply));
+        setup(&mut pt);
+
+        // Depositor is the payer, which program-test only creates on start;
+        // its token account is added afterwards
+        let mut ctx = pt.start_with_context().await;
+        let authority = ctx.payer.insecure_clone();
+        ctx.set_account(
+            &user_token,
+            &token_account(mint, authority.pubkey(), supply).into(),
+        );
+
+        let keys = PoolAccounts::derive(&authority.pubkey(), 0, &asset_id);
+
+        let mut pool = Self {
+            ctx,
+            authority: authority.insecure_clone(),
+            mint,
+            asset_id,
+            pool_config: keys.pool_config,
+            merkle_tree: keys.merkle_tree,
+            relayer_registry: keys.relayer_registry,
+            compliance_config: keys.compliance_config,
+            pending_buffer: keys.pending_buffer,
+            asset_vault: keys.asset_vault,
+            vault_token: keys.vault_token,
+            deposit_vk: keys.deposit_vk,
+            withdraw_vk: keys.withdraw_vk,
+            user_token,
+        };
+        pool.initialize(&authority, &keys, f.tree_depth, deposit, withdraw)
+            .await;
+        pool
+    }
+
+    /// Set up a second, fully initialized pool for the same mint owned by
+    /// a new authority
+    pub async fn start_foreign_pool(&mut self, f: &Fixture) -> (Keypair, PoolAccounts) {
+        let authority = Keypair::new();
+        self.ctx.set_account(
+            &authority.pubkey(),
+            &Account::new(100_000_000_000, 0, &system_program::ID).into(),
+        );
+        let keys = PoolAccounts::derive(&authority.pubkey(), 0, &self.asset_id);
+        self.initialize(
+            &authority,
+            &keys,
+            f.tree_depth,
+            &deposit_vk(),
+            &withdraw_vk(),
+        )
+        .await;
+        (authority, keys)
+    }
+
+    /// Set up another fully initialized pool for the same mint owned by
+    /// this pool's authority under `pool_id`
+    pub async fn start_sibling_pool(&mut self, f: &Fixture, pool_id: u64) -> PoolAccounts {
+        let authority = self.authority.insecure_clone();
+        let keys = PoolAccounts::derive(&authority.pubkey(), pool_id, &self.asset_id);
+        self.initialize(
+            &authority,
+            &keys,
+            f.tree_depth,
+            &deposit_vk(),
+            &withdraw_vk(),
+        )
+        .await;
+        keys
+    }
+
+    async fn initialize(
+        &mut self,
+        signer: &Keypair,
+        keys: &PoolAccounts,
+        tree_depth: u8,
+        deposit: &SnarkjsVk,
+        withdraw: &SnarkjsVk,
+    ) {
+        let authority = signer.pubkey();
+        let setup = [
+            (
+                "initialize_pool_v2",
+                ix(
+                    accounts::InitializePoolV2 {
+                        authority,
+                        pool_config: keys.pool_config,
+                        merkle_tree: keys.merkle_tree,
+                        system_program: system_program::ID,
+                    },
+                    instruction::InitializePoolV2 {
+                        tree_depth,
+                        root_history_size: ROOT_HISTORY_SIZE,
+                        compliance_recovery: false,
+                        pool_id: keys.pool_id,
+                    },
+                ),
+            ),
+            (
+                "initialize_pool_registries",
+                ix(
+                    accounts::InitializePoolRegistries {
+                        authority,
+                        pool_config: keys.pool_config,
+                        relayer_registry: keys.relayer_registry,
+                        compliance_config: keys.compliance_config,
+                        system_program: system_program::ID,
+                    },
+                    instruction::InitializePoolRegistries {},
+                ),
+            ),
+            (
+                "initialize_pending_deposits_buffer",
+                ix(
+                    accounts::InitializePendingDepositsBuffer {
+                        authority,
+                        pool_config: keys.pool_config,
+                        pending_buffer: keys.pending_buffer,
+                        system_program: system_program::ID,
+                    },
+                    instruction::InitializePendingDepositsBuffer {},
+                ),
+            ),
+            (
+                "register_asset",
+                ix(
+                    accounts::RegisterAsset {
+                        authority,
+                        pool_config: keys.pool_config,
+                        mint: self.mint,
+                        asset_vault: keys.asset_vault,
+                        vault_token_account: keys.vault_token,
+                        token_program: spl_token::id(),
+                        system_program: system_program::ID,
+                        token_metadata: None,
+                    },
+                    instruction::RegisterAsset {
+                        asset_id: self.asset_id,
+                    },
+                ),
+            ),
+        ];
+        for (name, ix) in setup {
+            self.send(ix, &[signer]).await.expect(name);
+        }
+
+        for (proof_type, vk, vk_account) in [
+            (ProofType::Deposit, deposit, keys.deposit_vk),
+            (ProofType::Withdraw, withdraw, keys.withdraw_vk),
+        ] {
+            let set = ix(
+                accounts::SetVerificationKeyData {
+                    authority,
+                    pool_config: keys.pool_config,
+                    vk_account,
+                    circuit_descriptor: None,
+                    merkle_tree: keys.merkle_tree,
+                },
+                instruction::SetVerificationKeyData {
+                    proof_type,
+                    vk_alpha_g1: g1(&vk.vk_alpha_1),
+                    vk_beta_g2: g2(&vk.vk_beta_2),
+                    vk_gamma_g2: g2(&vk.vk_gamma_2),
+                    vk_delta_g2: g2(&vk.vk_delta_2),
+                    vk_ic: vk.ic.iter().map(|p| g1(p)).collect(),
+                    ceremony_transcript_hash: [0u8; 32],
+                    ceremony_contributors: 0,
+                },
+            );
+            let create = create_vk_ix(authority, keys.pool_config, proof_type);
+            self.send_all(&[create, set], &[signer])
+                .await
+                .expect("set_verification_key_data");
+        }
+    }
+
+    pub async fn send(
+        &mut self,
+        ix: Instruction,
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        let tx = self.transaction(ix, signers).await;
+        self.ctx.banks_client.process_transaction(tx).await
+    }
+
+    /// Send several instructions in one transaction; the first signer pays
+    pub async fn send_all(
+        &mut self,
+        ixs: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        let blockhash = self.ctx.banks_client.get_latest_blockhash().await.unwrap();
+        let tx =
+            Transaction::new_signed_with_payer(ixs, Some(&signers[0].pubkey()), signers, blockhash);
+        self.ctx.banks_client.process_transaction(tx).await
+    }
+
+    /// Send and return the program's return data (empty if none was set)
+    pub async fn send_for_return_data(
+        &mut self,
+        ix: Instruction,
+        signers: &[&Keypair],
+    ) -> Result<Vec<u8>, BanksClientError> {
+        let tx = self.transaction(ix, signers).await;
+        let outcome = self
+            .ctx
+            .banks_client
+            .process_transaction_with_metadata(tx)
+            .await?;
+        outcome.result?;
+        Ok(outcome
+            .metadata
+            .and_then(|meta| meta.return_data)
+            .map(|ret| ret.data)
+            .unwrap_or_default())
+    }
+
+    pub async fn send_as_authority(&mut self, ix: Instruction) -> Result<(), BanksClientError> {
+        let authority = self.authority.insecure_clone();
+        self.send(ix, &[&authority]).await
+    }
+
+    /// Sign without submitting; the first signer pays
+    pub async fn transaction(&mut self, ix: Instruction, signers: &[&Keypair]) -> Transaction {
+        let blockhash = self.ctx.banks_client.get_latest_blockhash().await.unwrap();
+        Transaction::new_signed_with_payer(&[ix], Some(&signers[0].pubkey()), signers, blockhash)
+    }
+
+    /// Move to a fresh blockhash so an identical transaction is not deduped
+    pub async fn refresh_blockhash(&mut self) {
+        // `last_blockhash` goes stale as the bank advances; wait past the
+        // hash `transaction` would sign with right now
+        self.ctx.last_blockhash = self.ctx.banks_client.get_latest_blockhash().await.unwrap();
+        self.ctx.get_new_latest_blockhash().await.unwrap();
+    }
+
+    pub async fn account<T: AccountDeserialize>(&mut self, key: Pubkey) -> T {
+        let acct = self
+            .ctx
+            .banks_client
+            .get_account(key)
+            .await
+            .unwrap()
+            .expect("account exists");
+        T::try_deserialize(&mut acct.data.as_slice()).expect("deserialize")
+    }
+
+    pub async fn token_balance(&mut self, key: Pubkey) -> u64 {
+        let acct = self
+            .ctx
+            .banks_client
+            .get_account(key)
+            .await
+            .unwrap()
+            .unwrap();
+        spl_token::state::Account::unpack(&acct.data)
+            .unwrap()
+            .amount
+    }
+
+    /// Reservation PDA of `commitment`, passed by every deposit of it
+    pub fn commitment_reservation(&self, commitment: &[u8; 32]) -> Pubkey {
+        CommitmentReservation::find_pda(&psol_privacy_v2::ID, &self.pool_config, commitment).0
+    }
+
+    pub fn deposit_ix(
+        &self,
+        amount: u64,
+        commitment: [u8; 32],
+        proof: &SnarkjsProof,
+    ) -> Instruction {
+        let authority = self.authority.pubkey();
+        ix(
+            accounts::DepositMasp {
+                depositor: authority,
+                pool_config: self.pool_config,
+                global_config: global_config(),
+                authority,
+                merkle_tree: self.merkle_tree,
+                pending_buffer: self.pending_buffer,
+                asset_vault: self.asset_vault,
+                vault_token_account: Some(self.vault_token),
+                user_token_account: Some(self.user_token),
+                mint: Some(self.mint),
+                deposit_vk: self.deposit_vk,
+                token_program: spl_token::id(),
+                system_program: system_program::ID,
+                instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+                proof_stats: None,
+                anonymity_stats: None,
+                commitment_reservation: self.commitment_reservation(&commitment),
+                risk_tiers: None,
+            },
+            instruction::DepositMasp {
+                amount,
+                commitment,
+                asset_id: self.asset_id,
+                proof_data: proof_bytes(proof),
+                encrypted_note: None,
+            },
+        )
+    }
+
+    pub fn batch_ix(&self, max_to_process: u16, expected_batch_seq: Option<u64>) -> Instruction {
+        ix(
+            accounts::BatchProcessDeposits {
+                batcher: self.authority.pubkey(),
+                pool_config: self.pool_config,
+                global_config: global_config(),
+                merkle_tree: self.merkle_tree,
+                pending_buffer: self.pending_buffer,
+                batcher_role: None,
+                deposit_hook: None,
+                callback_program: None,
+            },
+            instruction::BatchProcessDeposits {
+                max_to_process,
+                target_cu_budget: None,
+                expected_batch_seq,
+            },
+        )
+    }
+}
+
+/// `create_verification_key_account` for `proof_type`; the VK is written by
+/// a following `set_verification_key_data`
+pub fn create_vk_ix(authority: Pubkey, pool_config: Pubkey, proof_type: ProofType) -> Instruction {
+    let (vk_account, _) =
+        VerificationKeyAccountV2::find_pda(&psol_privacy_v2::ID, &pool_config, proof_type);
+    ix(
+        accounts::CreateVerificationKeyAccount {
+            authority,
+            pool_config,
+            vk_account,
+            system_program: system_program::ID,
+        },
+        instruction::CreateVerificationKeyAccount { proof_type },
+    )
+}
@@ -0,0 +1,117 @@
+//! Groth16 verification keys with a known trapdoor
+//!
+//! Every VK element is a known multiple of the generators, so a valid proof
+//! for *any* public inputs can be produced on the host:
+//!
+//!   e(A, B) = e(α, β) · e(vk_x, γ) · e(C, δ)
+//!
+//! holds for B = G2, C = c·G1 and A = (αβ + γ·x + δ·c)·G1 where x is the
+//! discrete log of vk_x. The program still runs the full pairing check and
+//! every public input is bound, so the stateful tests can drive deposits
+//! and withdrawals against whatever tree state they reach without a
+//! circuit witness.
+
+use ark_bn254::{Fr, G1Affine, G2Affine};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, PrimeField};
+
+use super::{SnarkjsProof, SnarkjsVk};
+
+pub struct Trapdoor {
+    alpha: Fr,
+    beta: Fr,
+    gamma: Fr,
+    delta: Fr,
+    /// Discrete logs of the IC points
+    ic: Vec<Fr>,
+}
+
+/// splitmix64 stream of non-zero scalars
+fn scalars(mut seed: u64) -> impl Iterator<Item = Fr> {
+    std::iter::repeat_with(move || {
+        let mut bytes = [0u8; 32];
+        for chunk in bytes.chunks_mut(8) {
+            seed = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = seed;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            chunk.copy_from_slice(&(z ^ (z >> 31)).to_be_bytes());
+        }
+        Fr::from_be_bytes_mod_order(&bytes)
+    })
+    .filter(|s| *s != Fr::from(0u64))
+}
+
+fn g1(s: Fr) -> G1Affine {
+    (G1Affine::generator() * s).into_affine()
+}
+
+fn g2(s: Fr) -> G2Affine {
+    (G2Affine::generator() * s).into_affine()
+}
+
+fn dec(v: impl PrimeField) -> String {
+    v.into_bigint().to_string()
+}
+
+fn snarkjs_g1(p: G1Affine) -> Vec<String> {
+    vec![dec(p.x), dec(p.y), "1".into()]
+}
+
+fn snarkjs_g2(p: G2Affine) -> Vec<Vec<String>> {
+    vec![
+        vec![dec(p.x.c0), dec(p.x.c1)],
+        vec![dec(p.y.c0), dec(p.y.c1)],
+        vec!["1".into(), "0".into()],
+    ]
+}
+
+impl Trapdoor {
+    /// Trapdoor for a circuit with `public_inputs` public signals
+    pub fn new(seed: u64, public_inputs: usize) -> Self {
+        let mut s = scalars(seed);
+        let mut next = || s.next().unwrap();
+        Self {
+            alpha: next(),
+            beta: next(),
+            gamma: next(),
+            delta: next(),
+            ic: (0..=public_inputs).map(|_| next()).collect(),
+        }
+    }
+
+    pub fn vk(&self) -> SnarkjsVk {
+        SnarkjsVk {
+            vk_alpha_1: snarkjs_g1(g1(self.alpha)),
+            vk_beta_2: snarkjs_g2(g2(self.beta)),
+            vk_gamma_2: snarkjs_g2(g2(self.gamma)),
+            vk_delta_2: snarkjs_g2(g2(self.delta)),
+            ic: self.ic.iter().map(|u| snarkjs_g1(g1(*u))).collect(),
+        }
+    }
+
+    /// Proof accepted for exactly `inputs` (big-endian field elements)
+    ///
+    /// `salt` picks C, so distinct proofs for the same inputs are possible.
+    pub fn prove(&self, inputs: &[[u8; 32]], salt: u64) -> SnarkjsProof {
+        assert_eq!(inputs.len() + 1, self.ic.len(), "public input count");
+        let x = inputs
+            .iter()
+            .zip(&self.ic[1..])
+            .fold(self.ic[0], |acc, (input, u)| {
+                acc + Fr::from_be_bytes_mod_order(input) * u
+            });
+        let c = scalars(salt).next().unwrap();
+        let a = self.alpha * self.beta + self.gamma * x + self.delta * c;
+        SnarkjsProof {
+            pi_a: snarkjs_g1(g1(a)),
+            pi_b: snarkjs_g2(G2Affine::generator()),
+            pi_c: snarkjs_g1(g1(c)),
+        }
+    }
+}
+
+/// Big-endian bytes of a scalar, as the program encodes public inputs
+pub fn scalar_bytes(s: Fr) -> [u8; 32] {
+    s.into_bigint().to_bytes_be().try_into().unwrap()
+}
@@ -0,0 +1,248 @@
+//! Compliance recovery against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test compliance_recovery
+//!
+//! The authority and auditor move a note, disclosed through its audit
+//! metadata, to a custodian; the note's nullifier is then spent. Pools that
+//! did not opt in at creation refuse.
+//!
+//! Deposits are proved with a trapdoor VK.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::system_program;
+use anchor_lang::AccountSerialize;
+use anchor_spl::token::spl_token;
+use psol_privacy_v2::crypto::{compute_commitment, compute_nullifier_hash, DepositPublicInputs};
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::instructions::DisclosedNote;
+use psol_privacy_v2::state::{AuditMetadata, PoolConfigV2, SpendContext, SpentNullifierV2};
+use psol_privacy_v2::witness::merkle_path;
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer};
+
+use common::fixture;
+use common::pool::{assert_program_error, ix, token_account, Pool};
+use common::trapdoor::Trapdoor;
+
+const AMOUNT: u64 = 1_000_000;
+const COURT_ORDER: [u8; 32] = [0xc0; 32];
+
+struct Recovery {
+    custodian: Pubkey,
+    custodian_token: Pubkey,
+    commitment: [u8; 32],
+    nullifier_hash: [u8; 32],
+}
+
+fn recovery_ix(
+    pool: &Pool,
+    r: &Recovery,
+    auditor: Pubkey,
+    court_order_hash: [u8; 32],
+    note: DisclosedNote,
+) -> Instruction {
+    let id = psol_privacy_v2::ID;
+    let (audit_metadata, _) = AuditMetadata::find_pda(&id, &pool.pool_config, &r.commitment);
+    let (spent_nullifier, _) =
+        SpentNullifierV2::find_pda(&id, &pool.pool_config, &r.nullifier_hash);
+    ix(
+        accounts::ComplianceRecoveryWithdraw {
+            authority: pool.authority.pubkey(),
+            auditor,
+            pool_config: pool.pool_config,
+            compliance_config: pool.compliance_config,
+            merkle_tree: pool.merkle_tree,
+            audit_metadata,
+            asset_vault: pool.asset_vault,
+            vault_token_account: Some(pool.vault_token),
+            custodian: r.custodian,
+            custodian_token_account: Some(r.custodian_token),
+            mint: Some(pool.mint),
+            spent_nullifier,
+            token_program: Some(spl_token::id()),
+            system_program: system_program::ID,
+        },
+        instruction::ComplianceRecoveryWithdraw {
+            commitment: r.commitment,
+            asset_id: pool.asset_id,
+            nullifier_hash: r.nullifier_hash,
+            court_order_hash,
+            note,
+        },
+    )
+}
+
+/// Opt the pool into recovery, as `initialize_pool_v2` would have
+async fn enable_recovery(pool: &mut Pool) {
+    let mut config: PoolConfigV2 = pool.account(pool.pool_config).await;
+    config.compliance_recovery_enabled = true;
+    let mut data = Vec::with_capacity(PoolConfigV2::LEN);
+    config.try_serialize(&mut data).unwrap();
+    let current = pool
+        .ctx
+        .banks_client
+        .get_account(pool.pool_config)
+        .await
+        .unwrap()
+        .unwrap();
+    pool.ctx
+        .set_account(&pool.pool_config, &Account { data, ..current }.into());
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_recovery_moves_disclosed_note_to_custodian() {
+    let f = fixture();
+    let deposit = Trapdoor::new(35 << 8, 3);
+    let withdraw = Trapdoor::new((35 << 8) | 1, 8);
+    let mut pool = Pool::start_with_vks(&f, AMOUNT, &deposit.vk(), &withdraw.vk(), |_| {}).await;
+    let authority = pool.authority.insecure_clone();
+    let auditor = Keypair::new();
+
+    pool.send_as_authority(ix(
+        accounts::ConfigureCompliance {
+            authority: authority.pubkey(),
+            pool_config: pool.pool_config,
+            compliance_config: pool.compliance_config,
+        },
+        instruction::ConfigureCompliance {
+            require_encrypted_note: false,
+            audit_pubkey: Some(auditor.pubkey()),
+            metadata_schema_version: 1,
+        },
+    ))
+    .await
+    .expect("configure_compliance");
+
+    // One note, deposited and inserted at leaf 0
+    let (secret, nullifier) = ([0x05; 32], [0x06; 32]);
+    let commitment = compute_commitment(&secret, &nullifier, AMOUNT, &pool.asset_id).unwrap();
+    let inputs = DepositPublicInputs::new(commitment, AMOUNT, pool.asset_id).to_field_elements();
+    pool.send_as_authority(pool.deposit_ix(AMOUNT, commitment, &deposit.prove(&inputs, 1)))
+        .await
+        .expect("deposit_masp");
+    pool.send_as_authority(pool.batch_ix(1, None))
+        .await
+        .expect("batch_process_deposits");
+
+    let (audit_metadata, _) =
+        AuditMetadata::find_pda(&psol_privacy_v2::ID, &pool.pool_config, &commitment);
+    pool.send_as_authority(ix(
+        accounts::AttachAuditMetadata {
+            payer: authority.pubkey(),
+            pool_config: pool.pool_config,
+            compliance_config: pool.compliance_config,
+            audit_metadata,
+            system_program: system_program::ID,
+        },
+        instruction::AttachAuditMetadata {
+            commitment,
+            encrypted_metadata: vec![0xee; 96],
+        },
+    ))
+    .await
+    .expect("attach_audit_metadata");
+
+    let custodian = Pubkey::new_unique();
+    let custodian_token = Pubkey::new_unique();
+    pool.ctx.set_account(
+        &custodian_token,
+        &token_account(pool.mint, custodian, 0).into(),
+    );
+    let note = DisclosedNote {
+        secret,
+        nullifier,
+        amount: AMOUNT,
+        leaf_index: 0,
+        merkle_path: merkle_path(&[commitment], 0, f.tree_depth)
+            .unwrap()
+            .elements,
+    };
+    let recovery = Recovery {
+        custodian,
+        custodian_token,
+        commitment,
+        nullifier_hash: compute_nullifier_hash(&nullifier, &secret, 0).unwrap(),
+    };
+    let recover = |pool: &Pool, auditor: &Keypair, court_order_hash, note| {
+        recovery_ix(pool, &recovery, auditor.pubkey(), court_order_hash, note)
+    };
+    let signers = [&authority, &auditor];
+
+    // The pool was created without recovery
+    assert_program_error(
+        pool.send(
+            recover(&pool, &auditor, COURT_ORDER, note.clone()),
+            &signers,
+        )
+        .await,
+        PrivacyErrorV2::ComplianceRecoveryDisabled,
+    );
+    enable_recovery(&mut pool).await;
+
+    let impostor = Keypair::new();
+    assert_program_error(
+        pool.send(
+            recover(&pool, &impostor, COURT_ORDER, note.clone()),
+            &[&authority, &impostor],
+        )
+        .await,
+        PrivacyErrorV2::AuditorMismatch,
+    );
+    assert_program_error(
+        pool.send(recover(&pool, &auditor, [0u8; 32], note.clone()), &signers)
+            .await,
+        PrivacyErrorV2::CourtOrderRequired,
+    );
+    let inflated = DisclosedNote {
+        amount: AMOUNT + 1,
+        ..note.clone()
+    };
+    assert_program_error(
+        pool.send(recover(&pool, &auditor, COURT_ORDER, inflated), &signers)
+            .await,
+        PrivacyErrorV2::DisclosureMismatch,
+    );
+    let mut wrong_path = note.clone();
+    wrong_path.merkle_path[0] = [0x01; 32];
+    assert_program_error(
+        pool.send(recover(&pool, &auditor, COURT_ORDER, wrong_path), &signers)
+            .await,
+        PrivacyErrorV2::InvalidMerkleRoot,
+    );
+
+    pool.send(
+        recover(&pool, &auditor, COURT_ORDER, note.clone()),
+        &signers,
+    )
+    .await
+    .expect("compliance_recovery_withdraw");
+    assert_eq!(pool.token_balance(custodian_token).await, AMOUNT);
+    assert_eq!(pool.token_balance(pool.vault_token).await, 0);
+
+    let (spent_nullifier, _) = SpentNullifierV2::find_pda(
+        &psol_privacy_v2::ID,
+        &pool.pool_config,
+        &recovery.nullifier_hash,
+    );
+    let record: SpentNullifierV2 = pool.account(spent_nullifier).await;
+    assert_eq!(
+        record.get_spend_context(),
+        Some(SpendContext::ComplianceRecovery)
+    );
+    assert_eq!(record.relayer, authority.pubkey());
+
+    // The note is spent: neither its owner nor a second recovery can move it
+    pool.refresh_blockhash().await;
+    assert!(pool
+        .send(recover(&pool, &auditor, COURT_ORDER, note), &signers)
+        .await
+        .is_err());
+}
@@ -0,0 +1,65 @@
+//! Early compute budget check against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test compute_budget
+//!
+//! A deposit sent with a compute unit limit too small for its proof
+//! verification fails with `InsufficientComputeBudget` instead of running
+//! out during the pairing check; the same deposit with the recommended
+//! limit succeeds.
+//!
+//! Deposits are proved with a trapdoor VK.
+
+mod common;
+
+use psol_privacy_v2::compute_budget::{groth16_min_cu, DEPOSIT_MASP_CU};
+use psol_privacy_v2::crypto::DepositPublicInputs;
+use psol_privacy_v2::error::PrivacyErrorV2;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+
+use common::fixture;
+use common::pool::{assert_program_error, Pool};
+use common::trapdoor::Trapdoor;
+
+const AMOUNT: u64 = 1_000_000;
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_deposit_fails_early_without_budget() {
+    let f = fixture();
+    let deposit = Trapdoor::new(37 << 8, 3);
+    let withdraw = Trapdoor::new((37 << 8) | 1, 8);
+    let mut pool = Pool::start_with_vks(&f, AMOUNT, &deposit.vk(), &withdraw.vk(), |_| {}).await;
+    let authority = pool.authority.insecure_clone();
+
+    let commitment = [3u8; 32];
+    let inputs = DepositPublicInputs::new(commitment, AMOUNT, pool.asset_id).to_field_elements();
+    let deposit_ix = pool.deposit_ix(AMOUNT, commitment, &deposit.prove(&inputs, 1));
+
+    // Enough to reach verification, not enough to finish it
+    let short_limit = groth16_min_cu(inputs.len()) as u32 + 5_000;
+    assert_program_error(
+        pool.send_all(
+            &[
+                ComputeBudgetInstruction::set_compute_unit_limit(short_limit),
+                deposit_ix.clone(),
+            ],
+            &[&authority],
+        )
+        .await,
+        PrivacyErrorV2::InsufficientComputeBudget,
+    );
+
+    pool.send_all(
+        &[
+            ComputeBudgetInstruction::set_compute_unit_limit(DEPOSIT_MASP_CU),
+            deposit_ix,
+        ],
+        &[&authority],
+    )
+    .await
+    .expect("deposit_masp");
+    assert_eq!(pool.token_balance(pool.vault_token).await, AMOUNT);
+}
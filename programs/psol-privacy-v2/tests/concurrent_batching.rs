@@ -0,0 +1,464 @@
+//! Pending buffer / batch processing under interleaving and races
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test concurrent_batching
+//!
+//! Proven deposits from the fixture are queued and drained across many
+//! transactions and slots, and competing batch transactions built against
+//! the same buffer state are submitted together. After every step the tree
+//! root must equal a host-side Poseidon tree over exactly the commitments
+//! batched so far, in deposit order: a lost, duplicated or reordered
+//! commitment changes the root.
+//!
+//! With a crank bounty set, deposits escrow it on the pending buffer and
+//! each batch pays it to the batcher per inserted leaf.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{system_program, Discriminator};
+use psol_privacy_v2::crypto::poseidon2;
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::state::{MerkleTreeV2, PendingDepositsBuffer, PoolConfigV2};
+use psol_privacy_v2::{accounts, instruction};
+use solana_program_test::BanksClientError;
+use solana_sdk::account::Account;
+use solana_sdk::signature::Signer;
+use solana_sdk::transaction::Transaction;
+
+use common::pool::{assert_program_error, ix, Pool};
+use common::{fixture, hex32, DepositNote, Fixture};
+
+/// Root of a depth-`depth` tree whose first leaves are `leaves`
+fn reference_root(leaves: &[[u8; 32]], depth: u8) -> [u8; 32] {
+    let mut layer = leaves.to_vec();
+    let mut zero = [0u8; 32];
+    for _ in 0..depth {
+        if layer.len() % 2 == 1 {
+            layer.push(zero);
+        }
+        layer = layer
+            .chunks(2)
+            .map(|pair| poseidon2(&pair[0], &pair[1]).unwrap())
+            .collect();
+        zero = poseidon2(&zero, &zero).unwrap();
+    }
+    layer.first().copied().unwrap_or(zero)
+}
+
+fn total_supply(f: &Fixture) -> u64 {
+    f.deposits
+        .iter()
+        .map(|n| n.amount.parse::<u64>().unwrap())
+        .sum()
+}
+
+/// Pool state the program should have reached
+struct Expected {
+    depth: u8,
+    /// Commitments in deposit order
+    deposited: Vec<[u8; 32]>,
+    /// How many of `deposited` are in the tree
+    batched: usize,
+    amount: u64,
+}
+
+impl Expected {
+    async fn deposit(&mut self, pool: &mut Pool, note: &DepositNote) {
+        let amount: u64 = note.amount.parse().unwrap();
+        let commitment = hex32(&note.commitment);
+        pool.send_as_authority(pool.deposit_ix(amount, commitment, &note.proof))
+            .await
+            .expect("deposit_masp");
+        self.deposited.push(commitment);
+        self.amount += amount;
+    }
+
+    fn batched(&mut self, count: usize) {
+        self.batched += count;
+        assert!(self.batched <= self.deposited.len());
+    }
+
+    async fn check(&self, pool: &mut Pool) {
+        let tree: MerkleTreeV2 = pool.account(pool.merkle_tree).await;
+        let buffer: PendingDepositsBuffer = pool.account(pool.pending_buffer).await;
+        let config: PoolConfigV2 = pool.account(pool.pool_config).await;
+
+        assert_eq!(tree.next_leaf_index as usize, self.batched);
+        assert_eq!(
+            tree.get_current_root(),
+            reference_root(&self.deposited[..self.batched], self.depth)
+        );
+
+        // Everything not in the tree is still queued, oldest first
+        let pending: Vec<[u8; 32]> = buffer.iter().map(|d| d.commitment).collect();
+        assert_eq!(pending, self.deposited[self.batched..]);
+        assert_eq!(buffer.total_pending as usize, pending.len());
+        assert_eq!(buffer.total_deposits_batched as usize, self.batched);
+
+        assert_eq!(config.total_deposits as usize, self.batched);
+        assert_eq!(pool.token_balance(pool.vault_token).await, self.amount);
+    }
+}
+
+async fn start() -> (Fixture, Pool, Expected) {
+    let f = fixture();
+    let pool = Pool::start(&f, total_supply(&f), |_| {}).await;
+    let expected = Expected {
+        depth: f.tree_depth,
+        deposited: Vec::new(),
+        batched: 0,
+        amount: 0,
+    };
+    (f, pool, expected)
+}
+
+/// Submit both transactions before either is processed
+async fn race(
+    pool: &mut Pool,
+    first: Transaction,
+    second: Transaction,
+) -> (Result<(), BanksClientError>, Result<(), BanksClientError>) {
+    let a = pool.ctx.banks_client.clone();
+    let b = pool.ctx.banks_client.clone();
+    tokio::join!(a.process_transaction(first), b.process_transaction(second))
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_interleaved_deposits_and_batches() {
+    let (f, mut pool, mut expected) = start().await;
+    let notes = &f.deposits;
+
+    for note in &notes[0..3] {
+        expected.deposit(&mut pool, note).await;
+    }
+    expected.check(&mut pool).await;
+
+    pool.send_as_authority(pool.batch_ix(2, None))
+        .await
+        .unwrap();
+    expected.batched(2);
+    expected.check(&mut pool).await;
+
+    // Deposits landing in later slots queue behind the leftover entry
+    pool.refresh_blockhash().await;
+    for note in &notes[3..5] {
+        expected.deposit(&mut pool, note).await;
+    }
+    expected.check(&mut pool).await;
+
+    pool.send_as_authority(pool.batch_ix(10, None))
+        .await
+        .unwrap();
+    expected.batched(3);
+    expected.check(&mut pool).await;
+
+    pool.refresh_blockhash().await;
+    for note in &notes[5..8] {
+        expected.deposit(&mut pool, note).await;
+        pool.send_as_authority(pool.batch_ix(1, None))
+            .await
+            .unwrap();
+        expected.batched(1);
+        expected.check(&mut pool).await;
+    }
+
+    assert_program_error(
+        pool.send_as_authority(pool.batch_ix(10, None)).await,
+        PrivacyErrorV2::NoPendingDeposits,
+    );
+    expected.check(&mut pool).await;
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_racing_batchers_with_batch_seq() {
+    let (f, mut pool, mut expected) = start().await;
+    for note in &f.deposits[0..5] {
+        expected.deposit(&mut pool, note).await;
+    }
+
+    // Both batchers saw the same buffer; different sizes keep the
+    // transactions distinct
+    let seq = pool
+        .account::<PendingDepositsBuffer>(pool.pending_buffer)
+        .await
+        .batch_seq;
+    let authority = pool.authority.insecure_clone();
+    let first = pool
+        .transaction(pool.batch_ix(2, Some(seq)), &[&authority])
+        .await;
+    let second = pool
+        .transaction(pool.batch_ix(3, Some(seq)), &[&authority])
+        .await;
+    let (r1, r2) = race(&mut pool, first, second).await;
+
+    let processed = match (r1.is_ok(), r2.is_ok()) {
+        (true, false) => {
+            assert_program_error(r2, PrivacyErrorV2::BatchSequenceMismatch);
+            2
+        }
+        (false, true) => {
+            assert_program_error(r1, PrivacyErrorV2::BatchSequenceMismatch);
+            3
+        }
+        other => panic!("exactly one batcher must win: {other:?}"),
+    };
+    expected.batched(processed);
+    expected.check(&mut pool).await;
+
+    let buffer: PendingDepositsBuffer = pool.account(pool.pending_buffer).await;
+    assert_eq!(buffer.batch_seq, seq + 1);
+
+    // The loser retries against the new sequence and drains the rest
+    pool.send_as_authority(pool.batch_ix(10, Some(seq + 1)))
+        .await
+        .unwrap();
+    expected.batched(5 - processed);
+    expected.check(&mut pool).await;
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_racing_batchers_without_batch_seq() {
+    let (f, mut pool, mut expected) = start().await;
+    for note in &f.deposits[0..4] {
+        expected.deposit(&mut pool, note).await;
+    }
+
+    // Without the sequence guard both succeed, each taking from the front
+    // of whatever is left; together they drain the buffer exactly once
+    let authority = pool.authority.insecure_clone();
+    let first = pool
+        .transaction(pool.batch_ix(3, None), &[&authority])
+        .await;
+    let second = pool
+        .transaction(pool.batch_ix(2, None), &[&authority])
+        .await;
+    let (r1, r2) = race(&mut pool, first, second).await;
+    r1.unwrap();
+    r2.unwrap();
+    expected.batched(4);
+    expected.check(&mut pool).await;
+
+    let buffer: PendingDepositsBuffer = pool.account(pool.pending_buffer).await;
+    assert_eq!(buffer.batch_seq, 2);
+    assert_eq!(buffer.total_batches_processed, 2);
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_deposit_racing_batch() {
+    let (f, mut pool, mut expected) = start().await;
+    for note in &f.deposits[0..2] {
+        expected.deposit(&mut pool, note).await;
+    }
+
+    // A deposit and a drain-everything batch land together: the new
+    // commitment is either batched or left queued, never dropped
+    let note = &f.deposits[2];
+    let authority = pool.authority.insecure_clone();
+    let deposit = pool
+        .transaction(
+            pool.deposit_ix(
+                note.amount.parse().unwrap(),
+                hex32(&note.commitment),
+                &note.proof,
+            ),
+            &[&authority],
+        )
+        .await;
+    let batch = pool
+        .transaction(pool.batch_ix(10, None), &[&authority])
+        .await;
+    let (r1, r2) = race(&mut pool, deposit, batch).await;
+    r1.unwrap();
+    r2.unwrap();
+    expected.deposited.push(hex32(&note.commitment));
+    expected.amount += note.amount.parse::<u64>().unwrap();
+
+    let tree: MerkleTreeV2 = pool.account(pool.merkle_tree).await;
+    expected.batched(tree.next_leaf_index as usize);
+    expected.check(&mut pool).await;
+
+    if expected.batched < expected.deposited.len() {
+        pool.send_as_authority(pool.batch_ix(10, None))
+            .await
+            .unwrap();
+        expected.batched(1);
+    }
+    assert_eq!(expected.batched, 3);
+    expected.check(&mut pool).await;
+}
+
+/// Borsh bytes of `buffer` in the layout used before the ring buffer
+fn legacy_buffer_data(buffer: &PendingDepositsBuffer) -> Vec<u8> {
+    let mut data = PendingDepositsBuffer::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(buffer.pool.as_ref());
+    data.extend_from_slice(&buffer.total_pending.to_le_bytes());
+    for deposit in buffer.iter() {
+        data.extend_from_slice(&deposit.commitment);
+        data.extend_from_slice(&deposit.timestamp.to_le_bytes());
+    }
+    data.extend_from_slice(&buffer.total_pending.to_le_bytes());
+    data.extend_from_slice(&buffer.last_batch_at.to_le_bytes());
+    data.extend_from_slice(&buffer.total_batches_processed.to_le_bytes());
+    data.extend_from_slice(&buffer.total_deposits_batched.to_le_bytes());
+    data.push(buffer.bump);
+    data.push(PendingDepositsBuffer::LEGACY_VERSION);
+    data.extend_from_slice(&buffer.batch_seq.to_le_bytes());
+    // The legacy account was sized for a full Vec
+    data.resize(PendingDepositsBuffer::LEGACY_LEN, 0);
+    data
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_legacy_buffer_migrates_with_queue() {
+    let (f, mut pool, mut expected) = start().await;
+    for note in &f.deposits[0..3] {
+        expected.deposit(&mut pool, note).await;
+    }
+    pool.send_as_authority(pool.batch_ix(1, None))
+        .await
+        .unwrap();
+    expected.batched(1);
+
+    // Put the buffer back in the legacy layout, funded for that size only.
+    // The Borsh Vec is not a full ring, so trailing entries are padding
+    // bytes the legacy parser ignores
+    let buffer: PendingDepositsBuffer = pool.account(pool.pending_buffer).await;
+    let rent = pool.ctx.banks_client.get_rent().await.unwrap();
+    let legacy = Account {
+        lamports: rent.minimum_balance(PendingDepositsBuffer::LEGACY_LEN),
+        data: legacy_buffer_data(&buffer),
+        owner: psol_privacy_v2::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    pool.ctx.set_account(&pool.pending_buffer, &legacy.into());
+
+    let note = &f.deposits[3];
+    assert_program_error(
+        pool.send_as_authority(pool.deposit_ix(
+            note.amount.parse().unwrap(),
+            hex32(&note.commitment),
+            &note.proof,
+        ))
+        .await,
+        PrivacyErrorV2::PendingBufferNeedsMigration,
+    );
+
+    let migrate = ix(
+        accounts::MigratePendingBuffer {
+            authority: pool.authority.pubkey(),
+            pool_config: pool.pool_config,
+            pending_buffer: pool.pending_buffer,
+            system_program: system_program::ID,
+        },
+        instruction::MigratePendingBuffer {},
+    );
+    pool.send_as_authority(migrate.clone()).await.unwrap();
+
+    let account = pool
+        .ctx
+        .banks_client
+        .get_account(pool.pending_buffer)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), PendingDepositsBuffer::LEN);
+    assert!(rent.is_exempt(account.lamports, account.data.len()));
+
+    let migrated: PendingDepositsBuffer = pool.account(pool.pending_buffer).await;
+    assert_eq!(migrated.version, PendingDepositsBuffer::VERSION);
+    assert_eq!(migrated.batch_seq, buffer.batch_seq);
+    expected.check(&mut pool).await;
+
+    pool.refresh_blockhash().await;
+    assert_program_error(
+        pool.send_as_authority(migrate).await,
+        PrivacyErrorV2::PendingBufferAlreadyMigrated,
+    );
+
+    // The queue carries on where the legacy buffer left off
+    expected.deposit(&mut pool, note).await;
+    pool.send_as_authority(pool.batch_ix(10, Some(buffer.batch_seq)))
+        .await
+        .unwrap();
+    expected.batched(3);
+    expected.check(&mut pool).await;
+}
+
+async fn balance(pool: &mut Pool, address: Pubkey) -> u64 {
+    pool.ctx.banks_client.get_balance(address).await.unwrap()
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_crank_bounty_paid_per_leaf() {
+    let (f, mut pool, mut expected) = start().await;
+    let authority = pool.authority.pubkey();
+    let set_bounty = |pool: &Pool, lamports_per_leaf| {
+        ix(
+            accounts::SetCrankBounty {
+                authority,
+                pool_config: pool.pool_config,
+            },
+            instruction::SetCrankBounty { lamports_per_leaf },
+        )
+    };
+    let pending_buffer = pool.pending_buffer;
+    let fee = 5_000;
+
+    assert_program_error(
+        pool.send_as_authority(set_bounty(
+            &pool,
+            PoolConfigV2::MAX_CRANK_BOUNTY_LAMPORTS + 1,
+        ))
+        .await,
+        PrivacyErrorV2::CrankBountyTooLarge,
+    );
+    pool.send_as_authority(set_bounty(&pool, 10_000))
+        .await
+        .expect("set_crank_bounty");
+
+    // Each deposit escrows the bounty on the pending buffer
+    let escrow_before = balance(&mut pool, pending_buffer).await;
+    for note in &f.deposits[0..3] {
+        expected.deposit(&mut pool, note).await;
+    }
+    let escrow = balance(&mut pool, pending_buffer).await;
+    assert_eq!(escrow, escrow_before + 30_000);
+
+    let batcher_before = balance(&mut pool, authority).await;
+    pool.send_as_authority(pool.batch_ix(2, None))
+        .await
+        .expect("batch_process_deposits");
+    expected.batched(2);
+    expected.check(&mut pool).await;
+    assert_eq!(
+        balance(&mut pool, authority).await + fee,
+        batcher_before + 20_000
+    );
+    assert_eq!(balance(&mut pool, pending_buffer).await, escrow - 20_000);
+
+    // A raised bounty is only paid out of what was escrowed
+    pool.send_as_authority(set_bounty(&pool, 50_000))
+        .await
+        .expect("set_crank_bounty");
+    let batcher_before = balance(&mut pool, authority).await;
+    pool.send_as_authority(pool.batch_ix(1, None))
+        .await
+        .expect("batch_process_deposits");
+    expected.batched(1);
+    expected.check(&mut pool).await;
+    assert_eq!(
+        balance(&mut pool, authority).await + fee,
+        batcher_before + 10_000
+    );
+    assert_eq!(balance(&mut pool, pending_buffer).await, escrow_before);
+}
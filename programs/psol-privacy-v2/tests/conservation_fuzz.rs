@@ -0,0 +1,482 @@
+//! Value conservation under random operation sequences
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test conservation_fuzz
+//!
+//! Seeded random sequences of deposits, batch inserts, withdrawals (with
+//! replayed nullifiers, unknown and expired roots mixed in) and join-splits
+//! are driven against the program while a model tracks the notes each
+//! accepted proof implies. After every step the vault must hold exactly the
+//! value of the unspent notes, everything that left the vault must have
+//! reached the recipient or relayer, and the pool, asset vault, buffer and
+//! tree counters must agree with the model.
+//!
+//! Proofs come from trapdoor VKs (tests/common/trapdoor.rs), so withdrawals
+//! can target whatever root a sequence reaches. Join-splits are not exposed
+//! by the program yet (`private_transfer` is reserved for v2.1) and are
+//! mocked in the model: input notes are consumed and replaced by outputs of
+//! the same total value, which later withdrawals spend.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token;
+use psol_privacy_v2::crypto::{
+    verify_proof_from_account, DepositPublicInputs, WithdrawPublicInputs,
+};
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::instructions::batch_process_deposits::MAX_BATCH_SIZE;
+use psol_privacy_v2::state::pending_deposits::MAX_PENDING_DEPOSITS;
+use psol_privacy_v2::state::{
+    AssetVault, MerkleTreeV2, PendingDepositsBuffer, PoolConfigV2, SpentNullifierV2,
+};
+use psol_privacy_v2::{accounts, instruction};
+use solana_program_test::BanksClientError;
+use solana_sdk::signature::Signer;
+
+use common::pool::{
+    assert_program_error, global_config, ix, token_account, Pool, ROOT_HISTORY_SIZE,
+};
+use common::trapdoor::Trapdoor;
+use common::{fixture, g1, g2, proof_bytes, SnarkjsVk};
+
+const SEEDS: [u64; 3] = [1, 2, 3];
+const STEPS: usize = 200;
+const SUPPLY: u64 = 1_000_000_000_000;
+
+/// splitmix64
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    fn pick<T>(&mut self, items: &[T]) -> usize {
+        self.below(items.len() as u64) as usize
+    }
+
+    /// Non-zero canonical field element
+    fn scalar(&mut self) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        for chunk in out.chunks_mut(8) {
+            chunk.copy_from_slice(&self.next_u64().to_be_bytes());
+        }
+        out[0] &= 0x1f;
+        out[31] |= 1;
+        out
+    }
+}
+
+struct Note {
+    amount: u64,
+    nullifier_hash: [u8; 32],
+}
+
+/// What the program state should be, derived from accepted proofs only
+#[derive(Default)]
+struct Model {
+    /// Deposited, still in the pending buffer
+    pending: Vec<Note>,
+    /// In the tree (or produced by a join-split) and not yet withdrawn
+    unspent: Vec<Note>,
+    spent: Vec<[u8; 32]>,
+    /// Root after each batch, with the leaf count it covers
+    roots: Vec<([u8; 32], u64)>,
+    batched: u64,
+    deposits: u64,
+    withdrawals: u64,
+    deposited: u64,
+    withdrawn: u64,
+    fees: u64,
+}
+
+impl Model {
+    fn shielded(&self) -> u64 {
+        self.pending
+            .iter()
+            .chain(&self.unspent)
+            .map(|n| n.amount)
+            .sum()
+    }
+
+    fn known_root(&self, rng: &mut Rng) -> Option<[u8; 32]> {
+        let known: Vec<_> = self
+            .roots
+            .iter()
+            .filter(|(_, leaves)| self.batched - leaves < ROOT_HISTORY_SIZE as u64)
+            .collect();
+        (!known.is_empty()).then(|| known[rng.pick(&known)].0)
+    }
+
+    fn expired_root(&self) -> Option<[u8; 32]> {
+        self.roots
+            .iter()
+            .find(|(_, leaves)| self.batched - leaves >= ROOT_HISTORY_SIZE as u64)
+            .map(|(root, _)| *root)
+    }
+}
+
+struct Harness {
+    pool: Pool,
+    deposit: Trapdoor,
+    withdraw: Trapdoor,
+    recipient: Pubkey,
+    recipient_token: Pubkey,
+    relayer_token: Pubkey,
+    /// Varies every proof so no two transactions are identical
+    salt: u64,
+    /// Batch sizes sent under the current blockhash
+    batch_sizes: Vec<u16>,
+}
+
+impl Harness {
+    async fn start(seed: u64) -> Self {
+        let f = fixture();
+        let deposit = Trapdoor::new(seed << 8, 3);
+        let withdraw = Trapdoor::new((seed << 8) | 1, 8);
+        let mint = Pubkey::new_from_array(common::hex32(&f.mint));
+        let recipient = Pubkey::new_unique();
+        let recipient_token = Pubkey::new_unique();
+        let relayer_token = Pubkey::new_unique();
+
+        let mut pool = Pool::start_with_vks(&f, SUPPLY, &deposit.vk(), &withdraw.vk(), |pt| {
+            pt.add_account(recipient_token, token_account(mint, recipient, 0));
+        })
+        .await;
+        // The authority relays; its fee account needs the payer's key
+        let authority = pool.authority.pubkey();
+        pool.ctx
+            .set_account(&relayer_token, &token_account(mint, authority, 0).into());
+
+        Self {
+            pool,
+            deposit,
+            withdraw,
+            recipient,
+            recipient_token,
+            relayer_token,
+            salt: 0,
+            batch_sizes: Vec::new(),
+        }
+    }
+
+    fn next_salt(&mut self) -> u64 {
+        self.salt += 1;
+        self.salt
+    }
+
+    async fn deposit(&mut self, amount: u64, commitment: [u8; 32]) -> Result<(), BanksClientError> {
+        let inputs =
+            DepositPublicInputs::new(commitment, amount, self.pool.asset_id).to_field_elements();
+        let salt = self.next_salt();
+        let proof = self.deposit.prove(&inputs, salt);
+        let ix = self.pool.deposit_ix(amount, commitment, &proof);
+        self.pool.send_as_authority(ix).await
+    }
+
+    async fn batch(&mut self, max_to_process: u16) -> Result<(), BanksClientError> {
+        if self.batch_sizes.contains(&max_to_process) {
+            self.pool.refresh_blockhash().await;
+            self.batch_sizes.clear();
+        }
+        self.batch_sizes.push(max_to_process);
+        let ix = self.pool.batch_ix(max_to_process, None);
+        self.pool.send_as_authority(ix).await
+    }
+
+    async fn withdraw(
+        &mut self,
+        merkle_root: [u8; 32],
+        nullifier_hash: [u8; 32],
+        amount: u64,
+        relayer_fee: u64,
+    ) -> Result<(), BanksClientError> {
+        let salt = self.next_salt();
+        let pool = &self.pool;
+        let relayer = pool.authority.pubkey();
+        let inputs = WithdrawPublicInputs::new(
+            merkle_root,
+            nullifier_hash,
+            pool.asset_id,
+            self.recipient,
+            amount,
+            relayer,
+            relayer_fee,
+            [0u8; 32],
+        )
+        .to_field_elements();
+        let proof = self.withdraw.prove(&inputs, salt);
+        let (spent_nullifier, _) =
+            SpentNullifierV2::find_pda(&psol_privacy_v2::ID, &pool.pool_config, &nullifier_hash);
+        let ix = ix(
+            accounts::WithdrawMasp {
+                relayer,
+                pool_config: pool.pool_config,
+                global_config: global_config(),
+                merkle_tree: pool.merkle_tree,
+                vk_account: pool.withdraw_vk,
+                asset_vault: pool.asset_vault,
+                vault_token_account: Some(pool.vault_token),
+                recipient_token_account: Some(self.recipient_token),
+                relayer_token_account: Some(self.relayer_token),
+                spent_nullifier,
+                relayer_registry: pool.relayer_registry,
+                relayer_node: None,
+                yield_registry: None,
+                token_program: spl_token::id(),
+                system_program: system_program::ID,
+                instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+                proof_stats: None,
+                rebate_ledger: None,
+                migration_window: None,
+                deferred_events: None,
+                recipient_wallet: None,
+                rent_reserve: None,
+                rent_advance: None,
+                mint: None,
+                anonymity_stats: None,
+                parameter_snapshot: None,
+                recipient_ata: None,
+                associated_token_program: None,
+                fee_payer: None,
+                treasury_token_account: None,
+                withdrawal_commit: None,
+                fee_quote: None,
+                risk_tiers: None,
+                withdrawal_window: None,
+            },
+            instruction::WithdrawMasp {
+                proof_data: proof_bytes(&proof),
+                merkle_root,
+                nullifier_hash,
+                recipient: self.recipient,
+                amount,
+                asset_id: pool.asset_id,
+                relayer_fee,
+            },
+        );
+        self.pool.send_as_authority(ix).await
+    }
+
+    async fn check(&mut self, model: &Model, step: &str) {
+        let pool = &mut self.pool;
+        let vault_balance = pool.token_balance(pool.vault_token).await;
+        let recipient_balance = pool.token_balance(self.recipient_token).await;
+        let relayer_balance = pool.token_balance(self.relayer_token).await;
+        let user_balance = pool.token_balance(pool.user_token).await;
+        let config: PoolConfigV2 = pool.account(pool.pool_config).await;
+        let vault: AssetVault = pool.account(pool.asset_vault).await;
+        let buffer: PendingDepositsBuffer = pool.account(pool.pending_buffer).await;
+        let tree: MerkleTreeV2 = pool.account(pool.merkle_tree).await;
+
+        // Conservation: vault == unspent notes, and nothing created or lost
+        assert_eq!(vault_balance, model.shielded(), "{step}: vault");
+        assert_eq!(vault.shielded_balance, vault_balance, "{step}");
+        assert_eq!(
+            vault.total_deposited - vault.total_withdrawn,
+            vault_balance,
+            "{step}"
+        );
+        assert_eq!(
+            recipient_balance + relayer_balance,
+            model.withdrawn,
+            "{step}"
+        );
+        assert_eq!(relayer_balance, model.fees, "{step}");
+        assert_eq!(
+            user_balance + vault_balance + model.withdrawn,
+            SUPPLY,
+            "{step}"
+        );
+
+        // Statistics
+        assert_eq!(vault.total_deposited, model.deposited, "{step}");
+        assert_eq!(vault.total_withdrawn, model.withdrawn, "{step}");
+        assert_eq!(vault.deposit_count, model.deposits, "{step}");
+        assert_eq!(vault.withdrawal_count, model.withdrawals, "{step}");
+        assert_eq!(config.total_deposits, model.batched, "{step}");
+        assert_eq!(config.total_withdrawals, model.withdrawals, "{step}");
+        assert_eq!(config.total_join_splits, 0, "{step}");
+        assert_eq!(buffer.total_pending as usize, model.pending.len(), "{step}");
+        assert_eq!(buffer.total_deposits_batched, model.batched, "{step}");
+        assert_eq!(tree.next_leaf_index as u64, model.batched, "{step}");
+        assert_eq!(tree.total_leaves, model.batched, "{step}");
+    }
+}
+
+async fn run(seed: u64) {
+    let mut h = Harness::start(seed).await;
+    let mut rng = Rng(seed);
+    let mut m = Model::default();
+
+    for i in 0..STEPS {
+        let step = format!("seed {seed} step {i}");
+        match rng.below(100) {
+            // Deposit
+            0..=34 => {
+                let amount = 100 + rng.below(1_000_000);
+                let result = h.deposit(amount, rng.scalar()).await;
+                if m.pending.len() == MAX_PENDING_DEPOSITS {
+                    assert_program_error(result, PrivacyErrorV2::PendingBufferFull);
+                } else {
+                    result.expect(&step);
+                    m.pending.push(Note {
+                        amount,
+                        nullifier_hash: rng.scalar(),
+                    });
+                    m.deposits += 1;
+                    m.deposited += amount;
+                }
+            }
+            // Batch
+            35..=54 => {
+                let max = 1 + rng.below(12) as u16;
+                let result = h.batch(max).await;
+                if m.pending.is_empty() {
+                    assert_program_error(result, PrivacyErrorV2::NoPendingDeposits);
+                } else {
+                    result.expect(&step);
+                    let n = m.pending.len().min(max as usize);
+                    m.unspent.extend(m.pending.drain(..n));
+                    m.batched += n as u64;
+                    let tree: MerkleTreeV2 = h.pool.account(h.pool.merkle_tree).await;
+                    m.roots.push((tree.get_current_root(), m.batched));
+                }
+            }
+            // Withdraw a note against a known (possibly older) root
+            55..=79 => {
+                let Some(root) = m.known_root(&mut rng) else {
+                    continue;
+                };
+                if m.unspent.is_empty() {
+                    continue;
+                }
+                let note = m.unspent.swap_remove(rng.pick(&m.unspent));
+                let fee = rng.below(note.amount / 10 + 1);
+                h.withdraw(root, note.nullifier_hash, note.amount, fee)
+                    .await
+                    .expect(&step);
+                m.spent.push(note.nullifier_hash);
+                m.withdrawals += 1;
+                m.withdrawn += note.amount;
+                m.fees += fee;
+            }
+            // Replay a spent nullifier
+            80..=86 => {
+                let (Some(root), false) = (m.known_root(&mut rng), m.spent.is_empty()) else {
+                    continue;
+                };
+                let nullifier_hash = m.spent[rng.pick(&m.spent)];
+                let result = h.withdraw(root, nullifier_hash, 1_000, 0).await;
+                assert!(result.is_err(), "{step}: replay accepted");
+            }
+            // Unknown or expired root
+            87..=91 => {
+                let Some(note) = m.unspent.first() else {
+                    continue;
+                };
+                let root = m.expired_root().unwrap_or_else(|| rng.scalar());
+                let (nullifier_hash, amount) = (note.nullifier_hash, note.amount);
+                assert_program_error(
+                    h.withdraw(root, nullifier_hash, amount, 0).await,
+                    PrivacyErrorV2::InvalidMerkleRoot,
+                );
+            }
+            // Mocked join-split: 1-2 notes in, 1-2 notes out, same value
+            _ => {
+                if m.unspent.is_empty() {
+                    continue;
+                }
+                let inputs = 1 + (m.unspent.len() > 1 && rng.below(2) == 1) as usize;
+                let total: u64 = (0..inputs)
+                    .map(|_| m.unspent.swap_remove(rng.pick(&m.unspent)).amount)
+                    .sum();
+                let first = if total >= 200 && rng.below(2) == 1 {
+                    100 + rng.below(total - 199)
+                } else {
+                    total
+                };
+                for amount in [first, total - first] {
+                    if amount > 0 {
+                        m.unspent.push(Note {
+                            amount,
+                            nullifier_hash: rng.scalar(),
+                        });
+                    }
+                }
+            }
+        }
+        h.check(&m, &step).await;
+    }
+
+    // Drain: batch everything and withdraw every note; the vault empties
+    while !m.pending.is_empty() {
+        h.batch(MAX_BATCH_SIZE).await.unwrap();
+        let n = m.pending.len().min(MAX_BATCH_SIZE as usize);
+        m.batched += n as u64;
+        m.unspent.extend(m.pending.drain(..n));
+    }
+    let tree: MerkleTreeV2 = h.pool.account(h.pool.merkle_tree).await;
+    while let Some(note) = m.unspent.pop() {
+        h.withdraw(tree.get_current_root(), note.nullifier_hash, note.amount, 0)
+            .await
+            .unwrap();
+        m.withdrawals += 1;
+        m.withdrawn += note.amount;
+    }
+    h.check(&m, &format!("seed {seed} drained")).await;
+    assert_eq!(h.pool.token_balance(h.pool.vault_token).await, 0);
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_value_conservation_under_random_sequences() {
+    for seed in SEEDS {
+        run(seed).await;
+    }
+}
+
+/// The trapdoor proofs the sequences rely on verify, and only for the
+/// inputs they were made for
+#[test]
+fn test_trapdoor_proofs_bind_public_inputs() {
+    let check = |vk: &SnarkjsVk, proof: &common::SnarkjsProof, inputs: &[[u8; 32]]| {
+        verify_proof_from_account(
+            &g1(&vk.vk_alpha_1),
+            &g2(&vk.vk_beta_2),
+            &g2(&vk.vk_gamma_2),
+            &g2(&vk.vk_delta_2),
+            &vk.ic.iter().map(|p| g1(p)).collect::<Vec<_>>(),
+            &proof_bytes(proof),
+            inputs,
+        )
+        .unwrap()
+    };
+
+    let mut rng = Rng(7);
+    let trapdoor = Trapdoor::new(7, 8);
+    let vk = trapdoor.vk();
+    let inputs: Vec<[u8; 32]> = (0..8).map(|_| rng.scalar()).collect();
+    let proof = trapdoor.prove(&inputs, 1);
+    assert!(check(&vk, &proof, &inputs));
+    assert!(check(&vk, &trapdoor.prove(&inputs, 2), &inputs));
+
+    for i in 0..inputs.len() {
+        let mut tampered = inputs.clone();
+        tampered[i][31] ^= 1;
+        assert!(!check(&vk, &proof, &tampered), "input {i}");
+    }
+    assert!(!check(&Trapdoor::new(8, 8).vk(), &proof, &inputs));
+}
@@ -0,0 +1,719 @@
+//! Account substitution audit
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test constraint_audit
+//!
+//! The account-validation threat model as executable tests. Two fully set
+//! up pools share the fixture mint; every instruction that moves value or
+//! changes pool state is built once correctly and then with a single
+//! account swapped for a plausible impostor: the other pool's config, tree,
+//! buffer, vault or VK, a VK of the wrong proof type, an account of the
+//! wrong type or owner, a foreign relayer node, or a token account with
+//! the wrong mint or owner. Each substitution must fail with the error of
+//! the constraint that covers it, and the untouched instruction must still
+//! succeed afterwards, so the failures are due to the swap alone.
+
+mod common;
+
+use std::fmt::Debug;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{system_program, AccountSerialize};
+use anchor_spl::token::spl_token;
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::state::{RelayerMetadata, RelayerNode, SpentNullifierV2};
+use psol_privacy_v2::{accounts, instruction, ProofType};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer};
+
+use common::pool::{
+    assert_error_code, global_config, ix, mint_account, token_account, Pool, PoolAccounts,
+};
+use common::{fixture, hex32, proof_bytes, Fixture};
+
+/// Accounts outside the two pools that substitutions draw on
+struct World {
+    foreign_authority: Keypair,
+    foreign: PoolAccounts,
+    relayer: Keypair,
+    recipient: Pubkey,
+    recipient_token: Pubkey,
+    relayer_token: Pubkey,
+    /// Mint other than the pool's
+    other_mint: Pubkey,
+    /// Token account of `other_mint` owned by the depositor
+    depositor_other_mint_token: Pubkey,
+    /// Token account of the pool mint owned by the foreign authority
+    foreign_owner_token: Pubkey,
+    /// Token account of `other_mint` owned by the recipient
+    recipient_other_mint_token: Pubkey,
+}
+
+async fn start() -> (Fixture, Pool, World) {
+    let f = fixture();
+    let amount: u64 = f.amount.parse().unwrap();
+    let mint = Pubkey::new_from_array(hex32(&f.mint));
+    let relayer = Keypair::try_from(hex::decode(&f.relayer_keypair).unwrap().as_slice()).unwrap();
+    let recipient = Pubkey::new_from_array(hex32(&f.recipient));
+    let recipient_token = Pubkey::new_unique();
+    let relayer_token = Pubkey::new_unique();
+    let other_mint = Pubkey::new_unique();
+    let depositor_other_mint_token = Pubkey::new_unique();
+    let foreign_owner_token = Pubkey::new_unique();
+    let recipient_other_mint_token = Pubkey::new_unique();
+
+    let mut pool = Pool::start(&f, amount, |pt| {
+        pt.add_account(
+            relayer.pubkey(),
+            Account::new(10_000_000_000, 0, &system_program::ID),
+        );
+        pt.add_account(recipient_token, token_account(mint, recipient, 0));
+        pt.add_account(relayer_token, token_account(mint, relayer.pubkey(), 0));
+        pt.add_account(other_mint, mint_account(amount));
+        pt.add_account(
+            recipient_other_mint_token,
+            token_account(other_mint, recipient, 0),
+        );
+    })
+    .await;
+
+    let (foreign_authority, foreign) = pool.start_foreign_pool(&f).await;
+    let authority = pool.authority.pubkey();
+    pool.ctx.set_account(
+        &depositor_other_mint_token,
+        &token_account(other_mint, authority, amount).into(),
+    );
+    pool.ctx.set_account(
+        &foreign_owner_token,
+        &token_account(mint, foreign_authority.pubkey(), amount).into(),
+    );
+
+    let world = World {
+        foreign_authority,
+        foreign,
+        relayer,
+        recipient,
+        recipient_token,
+        relayer_token,
+        other_mint,
+        depositor_other_mint_token,
+        foreign_owner_token,
+        recipient_other_mint_token,
+    };
+    (f, pool, world)
+}
+
+/// Send `ix` and assert it fails with `expected`
+async fn reject(
+    pool: &mut Pool,
+    case: &str,
+    ix: Instruction,
+    signers: &[&Keypair],
+    expected: impl Into<u32> + Debug,
+) {
+    let what = format!("{case}: {expected:?}");
+    let result = pool.send(ix, signers).await;
+    assert_error_code(result, expected.into(), &what);
+}
+
+/// Place a relayer node owned by the program at `address`
+fn forge_relayer_node(pool: &mut Pool, address: Pubkey, registry: Pubkey, operator: Pubkey) {
+    let node = RelayerNode {
+        registry,
+        operator,
+        fee_bps: 100,
+        is_active: true,
+        stake_amount: 0,
+        transactions_processed: 0,
+        fees_earned: 0,
+        registered_at: 0,
+        last_active_at: 0,
+        metadata_uri: String::new(),
+        bump: 255,
+        reputation_score: 50,
+        metadata: RelayerMetadata::default(),
+        update_nonce: 0,
+        index_position: 0,
+        draining: false,
+        drain_allowance: 0,
+        unbonding_amount: 0,
+        unbonding_release_at: 0,
+        failures_reported: 0,
+        reputation_updated_at: 0,
+        _reserved: [0u8; 1],
+    };
+    let mut data = Vec::new();
+    node.try_serialize(&mut data).unwrap();
+    let account = Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: psol_privacy_v2::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    pool.ctx.set_account(&address, &account.into());
+}
+
+fn deposit_accounts(f: &Fixture, pool: &Pool) -> accounts::DepositMasp {
+    let authority = pool.authority.pubkey();
+    accounts::DepositMasp {
+        depositor: authority,
+        pool_config: pool.pool_config,
+        global_config: global_config(),
+        authority,
+        merkle_tree: pool.merkle_tree,
+        pending_buffer: pool.pending_buffer,
+        asset_vault: pool.asset_vault,
+        vault_token_account: Some(pool.vault_token),
+        user_token_account: Some(pool.user_token),
+        mint: Some(pool.mint),
+        deposit_vk: pool.deposit_vk,
+        token_program: spl_token::id(),
+        system_program: system_program::ID,
+        instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+        proof_stats: None,
+        anonymity_stats: None,
+        commitment_reservation: pool.commitment_reservation(&hex32(&f.commitment)),
+        risk_tiers: None,
+    }
+}
+
+fn deposit_data(f: &Fixture, pool: &Pool) -> instruction::DepositMasp {
+    instruction::DepositMasp {
+        amount: f.amount.parse().unwrap(),
+        commitment: hex32(&f.commitment),
+        asset_id: pool.asset_id,
+        proof_data: proof_bytes(&f.deposit.proof),
+        encrypted_note: None,
+    }
+}
+
+fn withdraw_accounts(pool: &Pool, w: &World, nullifier_hash: &[u8; 32]) -> accounts::WithdrawMasp {
+    let (spent_nullifier, _) =
+        SpentNullifierV2::find_pda(&psol_privacy_v2::ID, &pool.pool_config, nullifier_hash);
+    accounts::WithdrawMasp {
+        relayer: w.relayer.pubkey(),
+        pool_config: pool.pool_config,
+        global_config: global_config(),
+        merkle_tree: pool.merkle_tree,
+        vk_account: pool.withdraw_vk,
+        asset_vault: pool.asset_vault,
+        vault_token_account: Some(pool.vault_token),
+        recipient_token_account: Some(w.recipient_token),
+        relayer_token_account: Some(w.relayer_token),
+        spent_nullifier,
+        relayer_registry: pool.relayer_registry,
+        relayer_node: None,
+        yield_registry: None,
+        token_program: spl_token::id(),
+        system_program: system_program::ID,
+        instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+        proof_stats: None,
+        rebate_ledger: None,
+        migration_window: None,
+        deferred_events: None,
+        recipient_wallet: None,
+        rent_reserve: None,
+        rent_advance: None,
+        mint: None,
+        anonymity_stats: None,
+        parameter_snapshot: None,
+        recipient_ata: None,
+        associated_token_program: None,
+        fee_payer: None,
+        treasury_token_account: None,
+        withdrawal_commit: None,
+        fee_quote: None,
+        risk_tiers: None,
+        withdrawal_window: None,
+    }
+}
+
+fn withdraw_data(f: &Fixture, pool: &Pool, w: &World) -> instruction::WithdrawMasp {
+    instruction::WithdrawMasp {
+        proof_data: proof_bytes(&f.withdraw.proof),
+        merkle_root: hex32(&f.merkle_root),
+        nullifier_hash: hex32(&f.nullifier_hash),
+        recipient: w.recipient,
+        amount: f.amount.parse().unwrap(),
+        asset_id: pool.asset_id,
+        relayer_fee: f.relayer_fee.parse().unwrap(),
+    }
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_deposit_rejects_substituted_accounts() {
+    let (f, mut pool, w) = start().await;
+    let authority = pool.authority.insecure_clone();
+    let signers = [&authority];
+
+    let cases: Vec<(&str, accounts::DepositMasp, u32)> = vec![
+        (
+            "foreign pool_config",
+            accounts::DepositMasp {
+                pool_config: w.foreign.pool_config,
+                ..deposit_accounts(&f, &pool)
+            },
+            ErrorCode::ConstraintHasOne.into(),
+        ),
+        (
+            "foreign pool_config with its authority",
+            accounts::DepositMasp {
+                pool_config: w.foreign.pool_config,
+                authority: w.foreign_authority.pubkey(),
+                ..deposit_accounts(&f, &pool)
+            },
+            ErrorCode::ConstraintHasOne.into(),
+        ),
+        (
+            "foreign merkle_tree",
+            accounts::DepositMasp {
+                merkle_tree: w.foreign.merkle_tree,
+                ..deposit_accounts(&f, &pool)
+            },
+            ErrorCode::ConstraintHasOne.into(),
+        ),
+        (
+            "foreign pending_buffer",
+            accounts::DepositMasp {
+                pending_buffer: w.foreign.pending_buffer,
+                ..deposit_accounts(&f, &pool)
+            },
+            ErrorCode::ConstraintSeeds.into(),
+        ),
+        (
+            "foreign asset_vault",
+            accounts::DepositMasp {
+                asset_vault: w.foreign.asset_vault,
+                ..deposit_accounts(&f, &pool)
+            },
+            ErrorCode::ConstraintSeeds.into(),
+        ),
+        (
+            "foreign vault token account",
+            accounts::DepositMasp {
+                vault_token_account: Some(w.foreign.vault_token),
+                ..deposit_accounts(&f, &pool)
+            },
+            PrivacyErrorV2::InvalidVaultTokenAccount.into(),
+        ),
+        (
+            "withdraw VK as deposit VK",
+            accounts::DepositMasp {
+                deposit_vk: pool.withdraw_vk,
+                ..deposit_accounts(&f, &pool)
+            },
+            ErrorCode::ConstraintSeeds.into(),
+        ),
+        (
+            "foreign deposit VK",
+            accounts::DepositMasp {
+                deposit_vk: w.foreign.deposit_vk,
+                ..deposit_accounts(&f, &pool)
+            },
+            ErrorCode::ConstraintSeeds.into(),
+        ),
+        (
+            "user token account of another mint",
+            accounts::DepositMasp {
+                user_token_account: Some(w.depositor_other_mint_token),
+                ..deposit_accounts(&f, &pool)
+            },
+            PrivacyErrorV2::InvalidMint.into(),
+        ),
+        (
+            "user token account of another owner",
+            accounts::DepositMasp {
+                user_token_account: Some(w.foreign_owner_token),
+                ..deposit_accounts(&f, &pool)
+            },
+            PrivacyErrorV2::InvalidTokenOwner.into(),
+        ),
+        (
+            "another mint",
+            accounts::DepositMasp {
+                mint: Some(w.other_mint),
+                ..deposit_accounts(&f, &pool)
+            },
+            PrivacyErrorV2::InvalidMint.into(),
+        ),
+        (
+            "asset vault as pool_config",
+            accounts::DepositMasp {
+                pool_config: pool.asset_vault,
+                ..deposit_accounts(&f, &pool)
+            },
+            ErrorCode::AccountDiscriminatorMismatch.into(),
+        ),
+        (
+            "token account as merkle_tree",
+            accounts::DepositMasp {
+                merkle_tree: pool.user_token,
+                ..deposit_accounts(&f, &pool)
+            },
+            ErrorCode::AccountOwnedByWrongProgram.into(),
+        ),
+        (
+            "clock sysvar as instructions sysvar",
+            accounts::DepositMasp {
+                instructions_sysvar: solana_sdk::sysvar::clock::ID,
+                ..deposit_accounts(&f, &pool)
+            },
+            ErrorCode::ConstraintAddress.into(),
+        ),
+    ];
+    for (case, accounts, expected) in cases {
+        let ix = ix(accounts, deposit_data(&f, &pool));
+        reject(&mut pool, case, ix, &signers, expected).await;
+    }
+
+    pool.send(
+        ix(deposit_accounts(&f, &pool), deposit_data(&f, &pool)),
+        &signers,
+    )
+    .await
+    .expect("unmodified deposit_masp");
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_batch_rejects_substituted_accounts() {
+    let (f, mut pool, w) = start().await;
+    let authority = pool.authority.insecure_clone();
+    pool.send(
+        ix(deposit_accounts(&f, &pool), deposit_data(&f, &pool)),
+        &[&authority],
+    )
+    .await
+    .expect("deposit_masp");
+
+    let batch = |pool: &Pool| accounts::BatchProcessDeposits {
+        batcher: pool.authority.pubkey(),
+        pool_config: pool.pool_config,
+        global_config: global_config(),
+        merkle_tree: pool.merkle_tree,
+        pending_buffer: pool.pending_buffer,
+        batcher_role: None,
+        deposit_hook: None,
+        callback_program: None,
+    };
+    let data = || instruction::BatchProcessDeposits {
+        max_to_process: 10,
+        target_cu_budget: None,
+        expected_batch_seq: None,
+    };
+
+    let cases: Vec<(&str, accounts::BatchProcessDeposits, u32)> = vec![
+        (
+            "foreign pool_config",
+            accounts::BatchProcessDeposits {
+                pool_config: w.foreign.pool_config,
+                ..batch(&pool)
+            },
+            PrivacyErrorV2::InvalidMerkleTreePool.into(),
+        ),
+        (
+            "foreign merkle_tree",
+            accounts::BatchProcessDeposits {
+                merkle_tree: w.foreign.merkle_tree,
+                ..batch(&pool)
+            },
+            PrivacyErrorV2::InvalidMerkleTreePool.into(),
+        ),
+        (
+            "foreign pending_buffer",
+            accounts::BatchProcessDeposits {
+                pending_buffer: w.foreign.pending_buffer,
+                ..batch(&pool)
+            },
+            ErrorCode::ConstraintSeeds.into(),
+        ),
+    ];
+    for (case, accounts, expected) in cases {
+        reject(
+            &mut pool,
+            case,
+            ix(accounts, data()),
+            &[&authority],
+            expected,
+        )
+        .await;
+    }
+
+    // Another pool's authority is just an unbonded outsider here
+    let foreign_authority = w.foreign_authority.insecure_clone();
+    let outsider_batch = ix(
+        accounts::BatchProcessDeposits {
+            batcher: foreign_authority.pubkey(),
+            ..batch(&pool)
+        },
+        data(),
+    );
+    reject(
+        &mut pool,
+        "foreign authority as batcher",
+        outsider_batch,
+        &[&foreign_authority],
+        PrivacyErrorV2::Unauthorized,
+    )
+    .await;
+
+    pool.send(ix(batch(&pool), data()), &[&authority])
+        .await
+        .expect("unmodified batch_process_deposits");
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_withdraw_rejects_substituted_accounts() {
+    let (f, mut pool, w) = start().await;
+    let authority = pool.authority.insecure_clone();
+    pool.send(
+        ix(deposit_accounts(&f, &pool), deposit_data(&f, &pool)),
+        &[&authority],
+    )
+    .await
+    .expect("deposit_masp");
+    pool.send_as_authority(pool.batch_ix(10, None))
+        .await
+        .expect("batch_process_deposits");
+
+    let nullifier_hash = hex32(&f.nullifier_hash);
+    let (foreign_spent_nullifier, _) = SpentNullifierV2::find_pda(
+        &psol_privacy_v2::ID,
+        &w.foreign.pool_config,
+        &nullifier_hash,
+    );
+
+    // Relayer nodes: one of the other pool's registry, one of this registry
+    // for another operator, and one claiming this registry and relayer but
+    // not at the canonical address
+    let (foreign_node, _) = RelayerNode::find_pda(
+        &psol_privacy_v2::ID,
+        &w.foreign.relayer_registry,
+        &w.relayer.pubkey(),
+    );
+    forge_relayer_node(
+        &mut pool,
+        foreign_node,
+        w.foreign.relayer_registry,
+        w.relayer.pubkey(),
+    );
+    let registry = pool.relayer_registry;
+    let other_operator = Pubkey::new_unique();
+    let (other_operator_node, _) =
+        RelayerNode::find_pda(&psol_privacy_v2::ID, &registry, &other_operator);
+    forge_relayer_node(&mut pool, other_operator_node, registry, other_operator);
+    let stray_node = Pubkey::new_unique();
+    forge_relayer_node(&mut pool, stray_node, registry, w.relayer.pubkey());
+
+    let base = |pool: &Pool| withdraw_accounts(pool, &w, &nullifier_hash);
+    let cases: Vec<(&str, accounts::WithdrawMasp, u32)> = vec![
+        // The nullifier PDA is created from pool_config's seeds before the
+        // has_one checks run, so its seeds check fires first
+        (
+            "foreign pool_config",
+            accounts::WithdrawMasp {
+                pool_config: w.foreign.pool_config,
+                ..base(&pool)
+            },
+            ErrorCode::ConstraintSeeds.into(),
+        ),
+        (
+            "foreign merkle_tree",
+            accounts::WithdrawMasp {
+                merkle_tree: w.foreign.merkle_tree,
+                ..base(&pool)
+            },
+            ErrorCode::ConstraintHasOne.into(),
+        ),
+        (
+            "foreign relayer_registry",
+            accounts::WithdrawMasp {
+                relayer_registry: w.foreign.relayer_registry,
+                ..base(&pool)
+            },
+            ErrorCode::ConstraintHasOne.into(),
+        ),
+        (
+            "deposit VK as withdraw VK",
+            accounts::WithdrawMasp {
+                vk_account: pool.deposit_vk,
+                ..base(&pool)
+            },
+            ErrorCode::ConstraintSeeds.into(),
+        ),
+        (
+            "foreign withdraw VK",
+            accounts::WithdrawMasp {
+                vk_account: w.foreign.withdraw_vk,
+                ..base(&pool)
+            },
+            ErrorCode::ConstraintSeeds.into(),
+        ),
+        (
+            "foreign asset_vault",
+            accounts::WithdrawMasp {
+                asset_vault: w.foreign.asset_vault,
+                ..base(&pool)
+            },
+            ErrorCode::ConstraintSeeds.into(),
+        ),
+        (
+            "foreign vault token account",
+            accounts::WithdrawMasp {
+                vault_token_account: Some(w.foreign.vault_token),
+                ..base(&pool)
+            },
+            PrivacyErrorV2::InvalidVaultTokenAccount.into(),
+        ),
+        (
+            "recipient token account of another mint",
+            accounts::WithdrawMasp {
+                recipient_token_account: Some(w.recipient_other_mint_token),
+                ..base(&pool)
+            },
+            PrivacyErrorV2::InvalidMint.into(),
+        ),
+        (
+            "recipient token account of another owner",
+            accounts::WithdrawMasp {
+                recipient_token_account: Some(w.relayer_token),
+                ..base(&pool)
+            },
+            PrivacyErrorV2::RecipientMismatch.into(),
+        ),
+        (
+            "relayer token account of another owner",
+            accounts::WithdrawMasp {
+                relayer_token_account: Some(w.recipient_token),
+                ..base(&pool)
+            },
+            PrivacyErrorV2::RelayerMismatch.into(),
+        ),
+        (
+            "foreign spent nullifier PDA",
+            accounts::WithdrawMasp {
+                spent_nullifier: foreign_spent_nullifier,
+                ..base(&pool)
+            },
+            ErrorCode::ConstraintSeeds.into(),
+        ),
+        (
+            "relayer node of the other pool's registry",
+            accounts::WithdrawMasp {
+                relayer_node: Some(foreign_node),
+                ..base(&pool)
+            },
+            PrivacyErrorV2::RelayerNodeRegistryMismatch.into(),
+        ),
+        (
+            "relayer node at a non-canonical address",
+            accounts::WithdrawMasp {
+                relayer_node: Some(stray_node),
+                ..base(&pool)
+            },
+            PrivacyErrorV2::InvalidRelayerNodePda.into(),
+        ),
+        (
+            "relayer node of another operator",
+            accounts::WithdrawMasp {
+                relayer_node: Some(other_operator_node),
+                ..base(&pool)
+            },
+            PrivacyErrorV2::Unauthorized.into(),
+        ),
+        (
+            "pending buffer as merkle_tree",
+            accounts::WithdrawMasp {
+                merkle_tree: pool.pending_buffer,
+                ..base(&pool)
+            },
+            ErrorCode::AccountDiscriminatorMismatch.into(),
+        ),
+        (
+            "clock sysvar as instructions sysvar",
+            accounts::WithdrawMasp {
+                instructions_sysvar: solana_sdk::sysvar::clock::ID,
+                ..base(&pool)
+            },
+            ErrorCode::ConstraintAddress.into(),
+        ),
+    ];
+    let relayer = w.relayer.insecure_clone();
+    for (case, accounts, expected) in cases {
+        let ix = ix(accounts, withdraw_data(&f, &pool, &w));
+        reject(&mut pool, case, ix, &[&relayer], expected).await;
+    }
+
+    pool.send(ix(base(&pool), withdraw_data(&f, &pool, &w)), &[&relayer])
+        .await
+        .expect("unmodified withdraw_masp");
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_admin_rejects_foreign_authority() {
+    let (f, mut pool, w) = start().await;
+    let foreign_authority = w.foreign_authority.insecure_clone();
+    let vk = common::deposit_vk();
+
+    // Signed by the other pool's authority against this pool
+    let pause = ix(
+        accounts::PausePoolV2 {
+            authority: foreign_authority.pubkey(),
+            pool_config: pool.pool_config,
+        },
+        instruction::PausePoolV2 {},
+    );
+    reject(
+        &mut pool,
+        "pause by foreign authority",
+        pause,
+        &[&foreign_authority],
+        PrivacyErrorV2::Unauthorized,
+    )
+    .await;
+
+    let set_vk = ix(
+        accounts::SetVerificationKeyData {
+            authority: foreign_authority.pubkey(),
+            pool_config: pool.pool_config,
+            vk_account: pool.deposit_vk,
+            circuit_descriptor: None,
+            merkle_tree: pool.merkle_tree,
+        },
+        instruction::SetVerificationKeyData {
+            proof_type: ProofType::Deposit,
+            vk_alpha_g1: common::g1(&vk.vk_alpha_1),
+            vk_beta_g2: common::g2(&vk.vk_beta_2),
+            vk_gamma_g2: common::g2(&vk.vk_gamma_2),
+            vk_delta_g2: common::g2(&vk.vk_delta_2),
+            vk_ic: vk.ic.iter().map(|p| common::g1(p)).collect(),
+            ceremony_transcript_hash: [0u8; 32],
+            ceremony_contributors: 0,
+        },
+    );
+    reject(
+        &mut pool,
+        "set VK by foreign authority",
+        set_vk,
+        &[&foreign_authority],
+        PrivacyErrorV2::Unauthorized,
+    )
+    .await;
+
+    // The pool is untouched: its own authority can still deposit
+    let authority = pool.authority.insecure_clone();
+    pool.send(
+        ix(deposit_accounts(&f, &pool), deposit_data(&f, &pool)),
+        &[&authority],
+    )
+    .await
+    .expect("deposit_masp");
+}
@@ -0,0 +1,189 @@
+//! Permissionless pool creation against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test create_pool
+//!
+//! The global config is placed directly (see tests/global_pause.rs). The
+//! super guardian sets a creation fee; an unprivileged creator then opens a
+//! pool with its registries in one instruction and pays the fee, after
+//! shallow trees and a fee above the creator's limit are refused.
+
+mod common;
+
+use anchor_lang::{system_program, AccountSerialize};
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::instructions::create_pool::CREATED_POOL_MIN_TREE_DEPTH;
+use psol_privacy_v2::state::{
+    ComplianceConfig, GlobalConfig, MerkleTreeV2, PoolConfigV2, RelayerRegistry,
+    DEFAULT_ROOT_HISTORY_SIZE,
+};
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::signature::{Keypair, Signer};
+
+use common::fixture;
+use common::pool::{assert_program_error, global_config, ix, Pool, PoolAccounts};
+
+const CREATION_FEE: u64 = LAMPORTS_PER_SOL / 10;
+const POOL_ID: u64 = 3;
+
+/// Place an unpaused, fee-free global config naming `super_guardian`
+fn place_global_config(pool: &mut Pool, super_guardian: &Keypair) {
+    let (address, bump) = GlobalConfig::find_pda(&psol_privacy_v2::ID);
+    let config = GlobalConfig {
+        super_guardian: super_guardian.pubkey(),
+        is_paused: false,
+        updated_at: 0,
+        bump,
+        pool_creation_fee: 0,
+        _reserved: [0u8; 24],
+    };
+    let mut data = Vec::with_capacity(GlobalConfig::LEN);
+    config.try_serialize(&mut data).unwrap();
+    pool.ctx.set_account(
+        &address,
+        &Account {
+            lamports: LAMPORTS_PER_SOL,
+            data,
+            owner: psol_privacy_v2::ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+        .into(),
+    );
+    pool.ctx.set_account(
+        &super_guardian.pubkey(),
+        &Account::new(LAMPORTS_PER_SOL, 0, &system_program::ID).into(),
+    );
+}
+
+fn create_pool_ix(
+    creator: &Keypair,
+    super_guardian: &Keypair,
+    tree_depth: u8,
+    max_creation_fee: u64,
+) -> Instruction {
+    let created = PoolAccounts::derive(&creator.pubkey(), POOL_ID, &[0u8; 32]);
+    ix(
+        accounts::CreatePool {
+            creator: creator.pubkey(),
+            global_config: global_config(),
+            super_guardian: super_guardian.pubkey(),
+            pool_config: created.pool_config,
+            merkle_tree: created.merkle_tree,
+            relayer_registry: created.relayer_registry,
+            compliance_config: created.compliance_config,
+            system_program: system_program::ID,
+        },
+        instruction::CreatePool {
+            tree_depth,
+            pool_id: POOL_ID,
+            max_creation_fee,
+        },
+    )
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_anyone_creates_a_pool_for_the_fee() {
+    let f = fixture();
+    let mut pool = Pool::start(&f, 0, |_| {}).await;
+    let super_guardian = Keypair::new();
+    place_global_config(&mut pool, &super_guardian);
+
+    let payer = pool.authority.insecure_clone();
+    pool.send(
+        ix(
+            accounts::SetPoolCreationFee {
+                super_guardian: super_guardian.pubkey(),
+                global_config: global_config(),
+            },
+            instruction::SetPoolCreationFee { fee: CREATION_FEE },
+        ),
+        &[&payer, &super_guardian],
+    )
+    .await
+    .expect("set_pool_creation_fee");
+    let config: GlobalConfig = pool.account(global_config()).await;
+    assert_eq!(config.pool_creation_fee, CREATION_FEE);
+
+    let creator = Keypair::new();
+    pool.ctx.set_account(
+        &creator.pubkey(),
+        &Account::new(10 * LAMPORTS_PER_SOL, 0, &system_program::ID).into(),
+    );
+
+    // Defaults are enforced: no shallow trees
+    assert_program_error(
+        pool.send(
+            create_pool_ix(
+                &creator,
+                &super_guardian,
+                CREATED_POOL_MIN_TREE_DEPTH - 1,
+                CREATION_FEE,
+            ),
+            &[&creator],
+        )
+        .await,
+        PrivacyErrorV2::InvalidCreatedPoolDepth,
+    );
+    // The fee went up past what the creator agreed to
+    assert_program_error(
+        pool.send(
+            create_pool_ix(
+                &creator,
+                &super_guardian,
+                CREATED_POOL_MIN_TREE_DEPTH,
+                CREATION_FEE - 1,
+            ),
+            &[&creator],
+        )
+        .await,
+        PrivacyErrorV2::PoolCreationFeeTooHigh,
+    );
+
+    let guardian_before = pool
+        .ctx
+        .banks_client
+        .get_balance(super_guardian.pubkey())
+        .await
+        .unwrap();
+    pool.send(
+        create_pool_ix(
+            &creator,
+            &super_guardian,
+            CREATED_POOL_MIN_TREE_DEPTH,
+            CREATION_FEE,
+        ),
+        &[&creator],
+    )
+    .await
+    .expect("create_pool");
+    let guardian_after = pool
+        .ctx
+        .banks_client
+        .get_balance(super_guardian.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(guardian_after - guardian_before, CREATION_FEE);
+
+    let created = PoolAccounts::derive(&creator.pubkey(), POOL_ID, &[0u8; 32]);
+    let config: PoolConfigV2 = pool.account(created.pool_config).await;
+    assert_eq!(config.authority, creator.pubkey());
+    assert_eq!(config.pool_id, POOL_ID);
+    assert_eq!(config.tree_depth, CREATED_POOL_MIN_TREE_DEPTH);
+    assert_eq!(config.relayer_registry, created.relayer_registry);
+    assert_eq!(config.compliance_config, created.compliance_config);
+    assert!(!config.compliance_recovery_enabled);
+    let tree: MerkleTreeV2 = pool.account(created.merkle_tree).await;
+    assert_eq!(tree.pool, created.pool_config);
+    assert_eq!(tree.root_history_size, DEFAULT_ROOT_HISTORY_SIZE);
+    let registry: RelayerRegistry = pool.account(created.relayer_registry).await;
+    assert_eq!(registry.pool, created.pool_config);
+    let compliance: ComplianceConfig = pool.account(created.compliance_config).await;
+    assert_eq!(compliance.pool, created.pool_config);
+}
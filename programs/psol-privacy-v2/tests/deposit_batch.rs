@@ -0,0 +1,126 @@
+//! `deposit_masp_batch` against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test deposit_batch
+//!
+//! A depositor splits one token transfer into two notes: the vault receives
+//! the sum once, both commitments are queued and batched into the tree. A
+//! proof for another amount, or a list without a proof per commitment, is
+//! rejected before any tokens move.
+//!
+//! Deposits are proved with trapdoor VKs.
+
+mod common;
+
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token;
+use psol_privacy_v2::crypto::DepositPublicInputs;
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::state::{MerkleTreeV2, PendingDepositsBuffer};
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::signature::Signer;
+
+use common::pool::{assert_program_error, global_config, ix, Pool};
+use common::trapdoor::Trapdoor;
+use common::{fixture, proof_bytes};
+
+const AMOUNTS: [u64; 2] = [300_000, 700_000];
+const COMMITMENTS: [[u8; 32]; 2] = [[3u8; 32], [4u8; 32]];
+
+fn deposit_batch_ix(pool: &Pool, amounts: Vec<u64>, proofs: Vec<Vec<u8>>) -> Instruction {
+    let authority = pool.authority.pubkey();
+    let mut ix = ix(
+        accounts::DepositMaspBatch {
+            depositor: authority,
+            pool_config: pool.pool_config,
+            global_config: global_config(),
+            authority,
+            merkle_tree: pool.merkle_tree,
+            pending_buffer: pool.pending_buffer,
+            asset_vault: pool.asset_vault,
+            vault_token_account: Some(pool.vault_token),
+            user_token_account: Some(pool.user_token),
+            mint: Some(pool.mint),
+            deposit_vk: pool.deposit_vk,
+            token_program: spl_token::id(),
+            system_program: system_program::ID,
+            instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+            proof_stats: None,
+            anonymity_stats: None,
+            risk_tiers: None,
+        },
+        instruction::DepositMaspBatch {
+            asset_id: pool.asset_id,
+            amounts,
+            commitments: COMMITMENTS.to_vec(),
+            proofs,
+            encrypted_notes: None,
+        },
+    );
+    // One reservation PDA per commitment, in order
+    ix.accounts.extend(
+        COMMITMENTS
+            .iter()
+            .map(|c| AccountMeta::new(pool.commitment_reservation(c), false)),
+    );
+    ix
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_deposit_batch_single_transfer() {
+    let f = fixture();
+    let deposit = Trapdoor::new(33 << 8, 3);
+    let withdraw = Trapdoor::new((33 << 8) | 1, 8);
+    let total: u64 = AMOUNTS.iter().sum();
+    let mut pool = Pool::start_with_vks(&f, total, &deposit.vk(), &withdraw.vk(), |_| {}).await;
+
+    let proofs: Vec<Vec<u8>> = AMOUNTS
+        .iter()
+        .zip(COMMITMENTS)
+        .enumerate()
+        .map(|(i, (&amount, commitment))| {
+            let inputs =
+                DepositPublicInputs::new(commitment, amount, pool.asset_id).to_field_elements();
+            proof_bytes(&deposit.prove(&inputs, i as u64 + 1))
+        })
+        .collect();
+
+    // Each proof binds its own amount
+    assert_program_error(
+        pool.send_as_authority(deposit_batch_ix(
+            &pool,
+            vec![AMOUNTS[1], AMOUNTS[0]],
+            proofs.clone(),
+        ))
+        .await,
+        PrivacyErrorV2::InvalidProof,
+    );
+    assert_program_error(
+        pool.send_as_authority(deposit_batch_ix(
+            &pool,
+            AMOUNTS.to_vec(),
+            proofs[..1].to_vec(),
+        ))
+        .await,
+        PrivacyErrorV2::DepositBatchLengthMismatch,
+    );
+    assert_eq!(pool.token_balance(pool.user_token).await, total);
+
+    pool.send_as_authority(deposit_batch_ix(&pool, AMOUNTS.to_vec(), proofs))
+        .await
+        .expect("deposit_masp_batch");
+    assert_eq!(pool.token_balance(pool.user_token).await, 0);
+    assert_eq!(pool.token_balance(pool.vault_token).await, total);
+    let buffer: PendingDepositsBuffer = pool.account(pool.pending_buffer).await;
+    assert_eq!(buffer.total_pending, 2);
+
+    pool.send_as_authority(pool.batch_ix(2, None))
+        .await
+        .expect("batch_process_deposits");
+    let tree: MerkleTreeV2 = pool.account(pool.merkle_tree).await;
+    assert_eq!(tree.next_leaf_index, 2);
+}
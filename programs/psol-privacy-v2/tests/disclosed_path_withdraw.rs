@@ -0,0 +1,305 @@
+//! Disclosed-path withdrawals against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test disclosed_path_withdraw
+//!
+//! A small-tree pool whose withdraw VK is not set yet switches on the
+//! reduced-privacy mode; a relayer commits to a withdrawal, waits out the
+//! delay and reveals the note's opening and Merkle path.
+//!
+//! Deposits are proved with a trapdoor VK.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::system_program;
+use anchor_lang::AccountSerialize;
+use anchor_spl::token::spl_token;
+use psol_privacy_v2::crypto::{compute_commitment, compute_nullifier_hash, DepositPublicInputs};
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::instructions::DisclosedNote;
+use psol_privacy_v2::state::{
+    disclosed_withdraw_binding, AssetVault, DisclosedWithdrawCommitment, PoolConfigV2,
+    SpendContext, SpentNullifierV2, DISCLOSED_WITHDRAW_MIN_DELAY_SLOTS,
+};
+use psol_privacy_v2::witness::merkle_path;
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::account::Account;
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::Signer;
+
+use common::fixture;
+use common::pool::{assert_program_error, global_config, ix, token_account, Pool};
+use common::trapdoor::Trapdoor;
+
+const AMOUNT: u64 = 1_000_000;
+const RELAYER_FEE: u64 = 10_000;
+const TREE_DEPTH: u8 = 8;
+
+fn set_mode_ix(pool: &Pool, enabled: bool) -> Instruction {
+    ix(
+        accounts::SetDisclosedPathWithdrawals {
+            authority: pool.authority.pubkey(),
+            pool_config: pool.pool_config,
+        },
+        instruction::SetDisclosedPathWithdrawals { enabled },
+    )
+}
+
+fn commit_ix(pool: &Pool, binding: [u8; 32]) -> Instruction {
+    let (disclosed_commitment, _) =
+        DisclosedWithdrawCommitment::find_pda(&psol_privacy_v2::ID, &pool.pool_config, &binding);
+    ix(
+        accounts::CommitDisclosedWithdraw {
+            relayer: pool.authority.pubkey(),
+            pool_config: pool.pool_config,
+            disclosed_commitment,
+            system_program: system_program::ID,
+        },
+        instruction::CommitDisclosedWithdraw { binding },
+    )
+}
+
+struct Reveal {
+    binding: [u8; 32],
+    nullifier_hash: [u8; 32],
+    recipient: Pubkey,
+    recipient_token: Pubkey,
+    relayer_token: Pubkey,
+}
+
+fn reveal_ix(pool: &Pool, r: &Reveal, note: DisclosedNote) -> Instruction {
+    let id = psol_privacy_v2::ID;
+    let (disclosed_commitment, _) =
+        DisclosedWithdrawCommitment::find_pda(&id, &pool.pool_config, &r.binding);
+    let (spent_nullifier, _) =
+        SpentNullifierV2::find_pda(&id, &pool.pool_config, &r.nullifier_hash);
+    ix(
+        accounts::DisclosedPathWithdraw {
+            relayer: pool.authority.pubkey(),
+            pool_config: pool.pool_config,
+            global_config: global_config(),
+            merkle_tree: pool.merkle_tree,
+            disclosed_commitment,
+            asset_vault: pool.asset_vault,
+            vault_token_account: Some(pool.vault_token),
+            recipient_token_account: Some(r.recipient_token),
+            relayer_token_account: Some(r.relayer_token),
+            recipient_wallet: None,
+            treasury_token_account: None,
+            mint: Some(pool.mint),
+            spent_nullifier,
+            token_program: Some(spl_token::id()),
+            system_program: system_program::ID,
+            yield_registry: None,
+            instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+            risk_tiers: None,
+            withdrawal_window: None,
+        },
+        instruction::DisclosedPathWithdraw {
+            binding: r.binding,
+            nullifier_hash: r.nullifier_hash,
+            asset_id: pool.asset_id,
+            recipient: r.recipient,
+            relayer_fee: RELAYER_FEE,
+            note,
+        },
+    )
+}
+
+/// Clear the withdraw VK, as for a pool still waiting on its ceremony
+async fn unset_withdraw_vk(pool: &mut Pool) {
+    let mut config: PoolConfigV2 = pool.account(pool.pool_config).await;
+    config.vk_configured &= !(1 << psol_privacy_v2::ProofType::Withdraw as u8);
+    let mut data = Vec::with_capacity(PoolConfigV2::LEN);
+    config.try_serialize(&mut data).unwrap();
+    let current = pool
+        .ctx
+        .banks_client
+        .get_account(pool.pool_config)
+        .await
+        .unwrap()
+        .unwrap();
+    pool.ctx
+        .set_account(&pool.pool_config, &Account { data, ..current }.into());
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_mode_refused_for_deep_trees() {
+    let f = fixture();
+    let deposit = Trapdoor::new(36 << 8, 3);
+    let withdraw = Trapdoor::new((36 << 8) | 1, 8);
+    let mut pool = Pool::start_with_vks(&f, AMOUNT, &deposit.vk(), &withdraw.vk(), |_| {}).await;
+    unset_withdraw_vk(&mut pool).await;
+
+    assert_program_error(
+        pool.send_as_authority(set_mode_ix(&pool, true)).await,
+        PrivacyErrorV2::TreeTooDeepForDisclosedPath,
+    );
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_disclosed_path_withdraw_after_commitment_delay() {
+    let mut f = fixture();
+    f.tree_depth = TREE_DEPTH;
+    let deposit = Trapdoor::new(37 << 8, 3);
+    let withdraw = Trapdoor::new((37 << 8) | 1, 8);
+    let mut pool = Pool::start_with_vks(&f, AMOUNT, &deposit.vk(), &withdraw.vk(), |_| {}).await;
+    let relayer = pool.authority.pubkey();
+
+    // Proof withdrawals are available: the mode stays off
+    assert_program_error(
+        pool.send_as_authority(set_mode_ix(&pool, true)).await,
+        PrivacyErrorV2::WithdrawVerificationKeySet,
+    );
+    unset_withdraw_vk(&mut pool).await;
+    pool.send_as_authority(set_mode_ix(&pool, true))
+        .await
+        .expect("set_disclosed_path_withdrawals");
+
+    // One note, deposited and inserted at leaf 0
+    let (secret, nullifier) = ([0x07; 32], [0x08; 32]);
+    let commitment = compute_commitment(&secret, &nullifier, AMOUNT, &pool.asset_id).unwrap();
+    let inputs = DepositPublicInputs::new(commitment, AMOUNT, pool.asset_id).to_field_elements();
+    pool.send_as_authority(pool.deposit_ix(AMOUNT, commitment, &deposit.prove(&inputs, 1)))
+        .await
+        .expect("deposit_masp");
+    pool.send_as_authority(pool.batch_ix(1, None))
+        .await
+        .expect("batch_process_deposits");
+
+    let recipient = Pubkey::new_unique();
+    let recipient_token = Pubkey::new_unique();
+    let relayer_token = Pubkey::new_unique();
+    for (account, owner) in [(recipient_token, recipient), (relayer_token, relayer)] {
+        pool.ctx
+            .set_account(&account, &token_account(pool.mint, owner, 0).into());
+    }
+    let nullifier_hash = compute_nullifier_hash(&nullifier, &secret, 0).unwrap();
+    let reveal = Reveal {
+        binding: disclosed_withdraw_binding(
+            &pool.pool_config,
+            &secret,
+            &nullifier_hash,
+            &pool.asset_id,
+            &recipient,
+            &relayer,
+            RELAYER_FEE,
+        ),
+        nullifier_hash,
+        recipient,
+        recipient_token,
+        relayer_token,
+    };
+    let note = DisclosedNote {
+        secret,
+        nullifier,
+        amount: AMOUNT,
+        leaf_index: 0,
+        merkle_path: merkle_path(&[commitment], 0, TREE_DEPTH)
+            .unwrap()
+            .elements,
+    };
+
+    pool.send_as_authority(commit_ix(&pool, reveal.binding))
+        .await
+        .expect("commit_disclosed_withdraw");
+    assert_program_error(
+        pool.send_as_authority(reveal_ix(&pool, &reveal, note.clone()))
+            .await,
+        PrivacyErrorV2::DisclosedWithdrawNotMatured,
+    );
+
+    let mut clock: Clock = pool.ctx.banks_client.get_sysvar().await.unwrap();
+    clock.slot += DISCLOSED_WITHDRAW_MIN_DELAY_SLOTS;
+    pool.ctx.set_sysvar(&clock);
+    pool.refresh_blockhash().await;
+
+    // The opening only redeems the terms committed to
+    let thief = Pubkey::new_unique();
+    let thief_token = Pubkey::new_unique();
+    pool.ctx
+        .set_account(&thief_token, &token_account(pool.mint, thief, 0).into());
+    let redirected = Reveal {
+        recipient: thief,
+        recipient_token: thief_token,
+        ..reveal
+    };
+    assert_program_error(
+        pool.send_as_authority(reveal_ix(&pool, &redirected, note.clone()))
+            .await,
+        PrivacyErrorV2::DisclosedWithdrawBindingMismatch,
+    );
+    let reveal = Reveal {
+        recipient,
+        recipient_token,
+        ..redirected
+    };
+    let mut wrong_path = note.clone();
+    wrong_path.merkle_path[0] = [0x01; 32];
+    assert_program_error(
+        pool.send_as_authority(reveal_ix(&pool, &reveal, wrong_path))
+            .await,
+        PrivacyErrorV2::InvalidMerkleRoot,
+    );
+
+    pool.send_as_authority(reveal_ix(&pool, &reveal, note.clone()))
+        .await
+        .expect("disclosed_path_withdraw");
+    pool.refresh_blockhash().await;
+    assert!(pool
+        .send_as_authority(reveal_ix(&pool, &reveal, note))
+        .await
+        .is_err());
+    assert_eq!(
+        pool.token_balance(recipient_token).await,
+        AMOUNT - RELAYER_FEE
+    );
+    assert_eq!(pool.token_balance(relayer_token).await, RELAYER_FEE);
+    let vault: AssetVault = pool.account(pool.asset_vault).await;
+    assert_eq!(vault.shielded_balance, 0);
+
+    let (spent_nullifier, _) =
+        SpentNullifierV2::find_pda(&psol_privacy_v2::ID, &pool.pool_config, &nullifier_hash);
+    let record: SpentNullifierV2 = pool.account(spent_nullifier).await;
+    assert_eq!(
+        record.get_spend_context(),
+        Some(SpendContext::DisclosedPath)
+    );
+    let (disclosed_commitment, _) = DisclosedWithdrawCommitment::find_pda(
+        &psol_privacy_v2::ID,
+        &pool.pool_config,
+        &reveal.binding,
+    );
+    assert!(pool
+        .ctx
+        .banks_client
+        .get_account(disclosed_commitment)
+        .await
+        .unwrap()
+        .is_none());
+
+    // Once the withdraw VK is set the mode stops accepting commitments
+    let mut config: PoolConfigV2 = pool.account(pool.pool_config).await;
+    config.set_vk_configured(psol_privacy_v2::ProofType::Withdraw);
+    let mut data = Vec::with_capacity(PoolConfigV2::LEN);
+    config.try_serialize(&mut data).unwrap();
+    let current = pool
+        .ctx
+        .banks_client
+        .get_account(pool.pool_config)
+        .await
+        .unwrap()
+        .unwrap();
+    pool.ctx
+        .set_account(&pool.pool_config, &Account { data, ..current }.into());
+    assert_program_error(
+        pool.send_as_authority(commit_ix(&pool, [0x42; 32])).await,
+        PrivacyErrorV2::WithdrawVerificationKeySet,
+    );
+}
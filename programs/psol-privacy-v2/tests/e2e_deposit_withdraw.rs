@@ -0,0 +1,213 @@
+//! End-to-end pool test: deposit -> batch -> withdraw with real proofs
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test e2e_deposit_withdraw -- --nocapture
+//!
+//! Loads the SBF build of the program into solana-program-test (Anchor's
+//! CPI helpers only work on-chain, so the native entrypoint cannot be used)
+//! and drives a full note lifecycle with Groth16 proofs produced from the
+//! circuits/build artifacts: pool setup, VK upload, proven deposit, batch
+//! insertion, a deposit sharing the withdrawal's transaction (rejected),
+//! proven withdrawal and a replay of the same nullifier.
+//!
+//! Fixture: see tests/common/mod.rs.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token;
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::state::{
+    AssetVault, MerkleTreeV2, PendingDepositsBuffer, PoolConfigV2, SpendContext, SpendType,
+    SpentNullifierV2,
+};
+use psol_privacy_v2::{accounts, instruction, ProofType, VerificationStatus};
+use solana_sdk::account::Account;
+use solana_sdk::signature::{Keypair, Signer};
+
+use common::pool::{assert_error_code, global_config, ix, token_account, Pool};
+use common::{fixture, fr, hex32, proof_bytes};
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_deposit_batch_withdraw_with_real_proofs() {
+    let f = fixture();
+
+    let program_id = psol_privacy_v2::ID;
+    let amount: u64 = f.amount.parse().unwrap();
+    let relayer_fee: u64 = f.relayer_fee.parse().unwrap();
+    let asset_id = hex32(&f.asset_id);
+    let commitment = hex32(&f.commitment);
+    let expected_root = hex32(&f.merkle_root);
+    let nullifier_hash = hex32(&f.nullifier_hash);
+    let mint = Pubkey::new_from_array(hex32(&f.mint));
+    let recipient = Pubkey::new_from_array(hex32(&f.recipient));
+    let relayer = Keypair::try_from(hex::decode(&f.relayer_keypair).unwrap().as_slice()).unwrap();
+
+    // Fixture is internally consistent with the program's encodings
+    assert_eq!(
+        asset_id,
+        psol_privacy_v2::state::asset_vault::compute_asset_id(&mint)
+    );
+    assert_eq!(fr(&f.deposit.public_signals[0]), commitment);
+    assert_eq!(fr(&f.withdraw.public_signals[0]), expected_root);
+    assert_eq!(fr(&f.withdraw.public_signals[1]), nullifier_hash);
+
+    // ---- pool setup and VK upload ----
+    let recipient_token = Pubkey::new_unique();
+    let relayer_token = Pubkey::new_unique();
+    let mut pool = Pool::start(&f, amount, |pt| {
+        pt.add_account(
+            relayer.pubkey(),
+            Account::new(10_000_000_000, 0, &system_program::ID),
+        );
+        pt.add_account(recipient_token, token_account(mint, recipient, 0));
+        pt.add_account(relayer_token, token_account(mint, relayer.pubkey(), 0));
+    })
+    .await;
+    let (spent_nullifier, _) =
+        SpentNullifierV2::find_pda(&program_id, &pool.pool_config, &nullifier_hash);
+
+    // ---- deposit with proof ----
+    pool.send_as_authority(pool.deposit_ix(amount, commitment, &f.deposit.proof))
+        .await
+        .expect("deposit_masp");
+
+    assert_eq!(pool.token_balance(pool.vault_token).await, amount);
+    assert_eq!(pool.token_balance(pool.user_token).await, 0);
+    let buffer: PendingDepositsBuffer = pool.account(pool.pending_buffer).await;
+    assert_eq!(buffer.size(), 1);
+
+    // Queued but not yet in the tree: the withdraw root is unknown
+    let tree: MerkleTreeV2 = pool.account(pool.merkle_tree).await;
+    assert_eq!(tree.next_leaf_index, 0);
+    assert!(!tree.is_known_root(&expected_root));
+
+    // ---- batch insertion ----
+    pool.send_as_authority(pool.batch_ix(10, None))
+        .await
+        .expect("batch_process_deposits");
+
+    // On-chain Poseidon tree agrees with the circuit's view of the root
+    let tree: MerkleTreeV2 = pool.account(pool.merkle_tree).await;
+    assert_eq!(tree.next_leaf_index, 1);
+    assert_eq!(tree.get_current_root(), expected_root);
+    assert!(tree.is_known_root(&expected_root));
+    let buffer: PendingDepositsBuffer = pool.account(pool.pending_buffer).await;
+    assert!(buffer.is_empty());
+
+    // ---- withdraw with proof ----
+    let withdraw_ix = ix(
+        accounts::WithdrawMasp {
+            relayer: relayer.pubkey(),
+            pool_config: pool.pool_config,
+            global_config: global_config(),
+            merkle_tree: pool.merkle_tree,
+            vk_account: pool.withdraw_vk,
+            asset_vault: pool.asset_vault,
+            vault_token_account: Some(pool.vault_token),
+            recipient_token_account: Some(recipient_token),
+            relayer_token_account: Some(relayer_token),
+            spent_nullifier,
+            relayer_registry: pool.relayer_registry,
+            relayer_node: None,
+            yield_registry: None,
+            token_program: spl_token::id(),
+            system_program: system_program::ID,
+            instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+            proof_stats: None,
+            rebate_ledger: None,
+            migration_window: None,
+            deferred_events: None,
+            recipient_wallet: None,
+            rent_reserve: None,
+            rent_advance: None,
+            mint: None,
+            anonymity_stats: None,
+            parameter_snapshot: None,
+            recipient_ata: None,
+            associated_token_program: None,
+            fee_payer: None,
+            treasury_token_account: None,
+            withdrawal_commit: None,
+            fee_quote: None,
+            risk_tiers: None,
+            withdrawal_window: None,
+        },
+        instruction::WithdrawMasp {
+            proof_data: proof_bytes(&f.withdraw.proof),
+            merkle_root: expected_root,
+            nullifier_hash,
+            recipient,
+            amount,
+            asset_id,
+            relayer_fee,
+        },
+    );
+
+    // A deposit in the same transaction would link the two flows
+    let authority = pool.authority.insecure_clone();
+    let linked = pool
+        .send_all(
+            &[
+                pool.deposit_ix(amount, commitment, &f.deposit.proof),
+                withdraw_ix.clone(),
+            ],
+            &[&relayer, &authority],
+        )
+        .await;
+    assert_error_code(
+        linked,
+        PrivacyErrorV2::SameTransactionDepositWithdraw.into(),
+        "same-transaction deposit and withdrawal",
+    );
+
+    let return_data = pool
+        .send_for_return_data(withdraw_ix.clone(), &[&relayer])
+        .await
+        .expect("withdraw_masp");
+
+    // CPI callers get the outcome as return data
+    let status = VerificationStatus::from_return_data(&return_data).expect("status");
+    assert!(status.is_verified());
+    assert_eq!(status.proof_type, ProofType::Withdraw);
+    assert_eq!(status.merkle_root, expected_root);
+    assert_eq!(status.root_age, 0);
+    assert_eq!(status.nullifiers_spent, 1);
+    assert_eq!(status.amount, amount);
+    assert_eq!(status.relayer_fee, relayer_fee);
+
+    assert_eq!(pool.token_balance(pool.vault_token).await, 0);
+    assert_eq!(
+        pool.token_balance(recipient_token).await,
+        amount - relayer_fee
+    );
+    assert_eq!(pool.token_balance(relayer_token).await, relayer_fee);
+
+    let spent: SpentNullifierV2 = pool.account(spent_nullifier).await;
+    assert_eq!(spent.pool, pool.pool_config);
+    assert_eq!(spent.nullifier_hash, nullifier_hash);
+    assert_eq!(spent.asset_id, asset_id);
+    assert_eq!(spent.relayer, relayer.pubkey());
+    assert_eq!(spent.get_spend_type(), Some(SpendType::Withdraw));
+    assert_eq!(spent.get_spend_context(), Some(SpendContext::WithdrawMasp));
+
+    let vault: AssetVault = pool.account(pool.asset_vault).await;
+    assert_eq!(vault.total_deposited, amount);
+    assert_eq!(vault.total_withdrawn, amount);
+
+    // ---- replay: the nullifier PDA already exists ----
+    pool.refresh_blockhash().await;
+    assert!(pool.send(withdraw_ix, &[&relayer]).await.is_err());
+    assert_eq!(
+        pool.token_balance(recipient_token).await,
+        amount - relayer_fee
+    );
+
+    let pool_config: PoolConfigV2 = pool.account(pool.pool_config).await;
+    assert_eq!(pool_config.total_deposits, 1);
+    assert_eq!(pool_config.total_withdrawals, 1);
+}
@@ -0,0 +1,415 @@
+//! Zero-value leaf, empty-tree and leaf-index-0 edge cases
+//!
+//! Run with:
+//!   cargo test -p psol-privacy-v2 --test edge_values
+//!
+//! Encoding bugs hide at the edge values: the all-zero leaf that marks an
+//! empty slot, the root of a tree nothing was inserted into, leaf index 0
+//! (which hashes into the nullifier as the zero field element), and field
+//! elements that already occur inside Poseidon itself. Each is driven
+//! through the same tree, path and verification code the handlers use,
+//! with the fixture's withdraw proof (a note at leaf 0 of a depth-20 tree)
+//! as the honest baseline.
+
+mod common;
+
+use anchor_lang::error::Error;
+use anchor_lang::prelude::{Pubkey, Result};
+use ark_ff::{BigInteger, PrimeField};
+use psol_privacy_v2::crypto::poseidon::{C_T3, M_T3, S_T5};
+use psol_privacy_v2::crypto::precomputed_zeros::get_precomputed_zeros;
+use psol_privacy_v2::crypto::{
+    poseidon2, u64_to_be32, verify_proof_from_account, DepositPublicInputs, WithdrawPublicInputs,
+    BN254_FR_MODULUS,
+};
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::scanner::NotePlaintext;
+use psol_privacy_v2::simulate::{simulate_insertions, TreeFrontier};
+use psol_privacy_v2::state::{MerkleTreeV2, PendingDepositsBuffer, MIN_ROOT_HISTORY_SIZE};
+use psol_privacy_v2::witness::{merkle_path, WithdrawWitness};
+
+use common::{fixture, g1, g2, hex32, proof_bytes, withdraw_vk, Fixture};
+
+/// Note secrets of the fixture (test_data/generate_e2e_fixture.mjs)
+const FIXTURE_SECRET: u64 = 0x5eed_5eed;
+const FIXTURE_NULLIFIER: u64 = 0xdead_beef;
+
+fn new_tree(depth: u8) -> MerkleTreeV2 {
+    let mut tree = MerkleTreeV2 {
+        pool: Pubkey::default(),
+        depth: 0,
+        next_leaf_index: 0,
+        current_root: [0u8; 32],
+        root_history: Vec::new(),
+        root_history_index: 0,
+        root_history_size: 0,
+        filled_subtrees: Vec::new(),
+        zeros: Vec::new(),
+        total_leaves: 0,
+        last_insertion_at: 0,
+        version: 0,
+        poseidon_params_hash: [0u8; 32],
+    };
+    tree.initialize(Pubkey::new_unique(), depth, MIN_ROOT_HISTORY_SIZE)
+        .unwrap();
+    tree
+}
+
+fn fr_bytes(x: &ark_bn254::Fr) -> [u8; 32] {
+    x.into_bigint().to_bytes_be().try_into().unwrap()
+}
+
+fn expect_err<T: std::fmt::Debug>(result: Result<T>, expected: PrivacyErrorV2, what: &str) {
+    match result {
+        Ok(value) => panic!("{what}: accepted ({value:?})"),
+        Err(err) => assert_eq!(err, Error::from(expected), "{what}"),
+    }
+}
+
+/// The fixture's withdraw proof and the handler-side check it goes through
+struct Withdraw {
+    f: Fixture,
+    proof: Vec<u8>,
+    inputs: WithdrawPublicInputs,
+}
+
+impl Withdraw {
+    fn load() -> Self {
+        let f = fixture();
+        let relayer = Pubkey::new_from_array(
+            hex::decode(&f.relayer_keypair).unwrap()[32..]
+                .try_into()
+                .unwrap(),
+        );
+        let inputs = WithdrawPublicInputs::new(
+            hex32(&f.merkle_root),
+            hex32(&f.nullifier_hash),
+            hex32(&f.asset_id),
+            Pubkey::new_from_array(hex32(&f.recipient)),
+            f.amount.parse().unwrap(),
+            relayer,
+            f.relayer_fee.parse().unwrap(),
+            [0u8; 32],
+        );
+        Self {
+            proof: proof_bytes(&f.withdraw.proof),
+            f,
+            inputs,
+        }
+    }
+
+    fn note(&self) -> NotePlaintext {
+        NotePlaintext {
+            secret: u64_to_be32(FIXTURE_SECRET),
+            nullifier: u64_to_be32(FIXTURE_NULLIFIER),
+            amount: self.inputs.amount,
+            asset_id: self.inputs.asset_id,
+        }
+    }
+
+    /// `validate()`, the root check against `tree`, then the proof, as in
+    /// withdraw_masp
+    fn check(&self, tree: &MerkleTreeV2, inputs: &WithdrawPublicInputs) -> Result<()> {
+        inputs.validate()?;
+        if !tree.is_known_root(&inputs.merkle_root) {
+            return Err(PrivacyErrorV2::InvalidMerkleRoot.into());
+        }
+        let vk = withdraw_vk();
+        let ic: Vec<[u8; 64]> = vk.ic.iter().map(|p| g1(p)).collect();
+        let is_valid = verify_proof_from_account(
+            &g1(&vk.vk_alpha_1),
+            &g2(&vk.vk_beta_2),
+            &g2(&vk.vk_gamma_2),
+            &g2(&vk.vk_delta_2),
+            &ic,
+            &self.proof,
+            &inputs.to_field_elements(),
+        )?;
+        if !is_valid {
+            return Err(PrivacyErrorV2::InvalidProof.into());
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// EMPTY TREE
+// ============================================================================
+
+#[test]
+fn test_empty_tree_root_is_known_but_proves_nothing() {
+    let w = Withdraw::load();
+    let depth = w.f.tree_depth;
+    let tree = new_tree(depth);
+    let zeros = get_precomputed_zeros(depth);
+    let empty_root = zeros[depth as usize];
+
+    // The empty root is the tree's first root, and the only one
+    assert_eq!(tree.get_current_root(), empty_root);
+    assert_eq!(tree.zeros, zeros);
+    assert!(tree.is_known_root(&empty_root));
+    assert_eq!(tree.root_updates_since(&empty_root), Some(0));
+    assert!(!tree.is_known_root(&[0u8; 32]));
+    assert!(!tree.is_known_root(&hex32(&w.f.merkle_root)));
+
+    // A withdrawal naming the empty root gets past the root check and is
+    // refused by the verifier: no leaf opens an empty tree
+    let mut inputs = w.inputs.clone();
+    inputs.merkle_root = empty_root;
+    expect_err(
+        w.check(&tree, &inputs),
+        PrivacyErrorV2::InvalidProof,
+        "withdraw against the empty root",
+    );
+    // The honest proof's root was never produced by this tree
+    expect_err(
+        w.check(&tree, &w.inputs),
+        PrivacyErrorV2::InvalidMerkleRoot,
+        "withdraw against a root the tree never had",
+    );
+
+    // Nothing to open in an empty tree
+    expect_err(
+        tree.get_merkle_path(0),
+        PrivacyErrorV2::LeafIndexNotFound,
+        "path of leaf 0 in an empty tree",
+    );
+    expect_err(
+        tree.root_from_path([1u8; 32], 0, &zeros[..depth as usize]),
+        PrivacyErrorV2::LeafIndexNotFound,
+        "root from a path in an empty tree",
+    );
+    expect_err(
+        merkle_path(&[], 0, depth),
+        PrivacyErrorV2::LeafIndexNotFound,
+        "witness path in an empty tree",
+    );
+}
+
+#[test]
+fn test_empty_tree_root_forgotten_after_first_insert() {
+    let depth = fixture().tree_depth;
+    let mut tree = new_tree(depth);
+    let empty_root = tree.get_current_root();
+    assert_eq!(tree.root_history[0], empty_root);
+    assert_eq!(tree.root_history_index, 0);
+
+    // The first root overwrites the empty root's slot: once a leaf exists
+    // the empty root is no longer accepted
+    tree.insert_leaf(u64_to_be32(1), 0).unwrap();
+    let first_root = tree.get_current_root();
+    assert_eq!(tree.root_history[0], first_root);
+    assert!(!tree.is_known_root(&empty_root));
+    assert_eq!(tree.root_updates_since(&empty_root), None);
+
+    // Every later root is remembered for a full history's worth of inserts
+    for i in 1..MIN_ROOT_HISTORY_SIZE {
+        tree.insert_leaf(u64_to_be32(i as u64 + 1), 0).unwrap();
+        assert_eq!(tree.root_updates_since(&first_root), Some(i), "leaf {i}");
+    }
+    tree.insert_leaf(u64_to_be32(MIN_ROOT_HISTORY_SIZE as u64 + 1), 0)
+        .unwrap();
+    assert!(!tree.is_known_root(&first_root));
+    assert!(!tree.is_known_root(&empty_root));
+}
+
+// ============================================================================
+// ZERO-LEAF COMMITMENTS
+// ============================================================================
+
+#[test]
+fn test_zero_leaf_commitment_rejected_everywhere() {
+    let f = fixture();
+    let depth = f.tree_depth;
+    let zero_leaf = get_precomputed_zeros(depth)[0];
+    assert_eq!(zero_leaf, [0u8; 32]);
+
+    // Tree insertion leaves the tree untouched
+    let mut tree = new_tree(depth);
+    let empty_root = tree.get_current_root();
+    expect_err(
+        tree.insert_leaf(zero_leaf, 1),
+        PrivacyErrorV2::InvalidCommitment,
+        "insert zero leaf",
+    );
+    assert_eq!(tree.get_current_root(), empty_root);
+    assert_eq!(tree.next_leaf_index, 0);
+    assert_eq!(tree.root_history_index, 0);
+    assert_eq!(tree.last_insertion_at, 0);
+
+    // A zero leaf anywhere in a batch fails the whole simulation
+    let frontier = TreeFrontier::empty(depth).unwrap();
+    expect_err(
+        simulate_insertions(&frontier, &[[1u8; 32], zero_leaf, [2u8; 32]]),
+        PrivacyErrorV2::InvalidCommitment,
+        "simulated batch with a zero leaf",
+    );
+
+    // The pending buffer never queues it
+    let mut buffer: PendingDepositsBuffer = bytemuck::Zeroable::zeroed();
+    buffer.initialize(Pubkey::default(), 0, 0);
+    expect_err(
+        buffer.add_pending(zero_leaf, 1),
+        PrivacyErrorV2::InvalidCommitment,
+        "queue zero leaf",
+    );
+    assert!(buffer.is_empty());
+
+    // Nor does a deposit claim it
+    let inputs = DepositPublicInputs::new(zero_leaf, 1, hex32(&f.asset_id));
+    expect_err(
+        inputs.validate(),
+        PrivacyErrorV2::InvalidCommitment,
+        "deposit of the zero leaf",
+    );
+}
+
+#[test]
+fn test_zero_subtree_hashes_are_ordinary_leaves() {
+    const DEPTH: u8 = 8;
+    let zeros = get_precomputed_zeros(DEPTH);
+
+    // zeros[k] for k > 0 is a non-zero field element: it goes in as a
+    // leaf like any other, and the tree does not mistake it for empty
+    for (level, zero) in zeros.iter().enumerate().skip(1) {
+        let mut tree = new_tree(DEPTH);
+        assert_eq!(tree.insert_leaf(*zero, 0).unwrap(), 0);
+        let expected = merkle_path(&[*zero], 0, DEPTH).unwrap();
+        assert_eq!(tree.get_current_root(), expected.root, "zeros[{level}]");
+        assert_ne!(tree.get_current_root(), zeros[DEPTH as usize]);
+        assert_eq!(
+            tree.root_from_path(*zero, 0, &expected.elements).unwrap(),
+            expected.root
+        );
+    }
+}
+
+// ============================================================================
+// NULLIFIERS EQUAL TO POSEIDON CONSTANTS
+// ============================================================================
+
+#[test]
+fn test_nullifiers_equal_to_poseidon_constants_rejected() {
+    let w = Withdraw::load();
+    let depth = w.f.tree_depth;
+    let mut tree = new_tree(depth);
+    tree.insert_leaf(hex32(&w.f.commitment), 0).unwrap();
+    w.check(&tree, &w.inputs).unwrap();
+
+    let zeros = get_precomputed_zeros(depth);
+    let candidates = [
+        ("Poseidon(0, 0)", poseidon2(&[0u8; 32], &[0u8; 32]).unwrap()),
+        ("zeros[1]", zeros[1]),
+        ("empty root", zeros[depth as usize]),
+        ("C_T3[0]", fr_bytes(&C_T3[0])),
+        ("C_T3[last]", fr_bytes(&C_T3[C_T3.len() - 1])),
+        ("M_T3[0][0]", fr_bytes(&M_T3[0][0])),
+        ("S_T5[0]", fr_bytes(&S_T5[0])),
+        ("r - 1", fr_bytes(&-ark_bn254::Fr::from(1u64))),
+        ("1", u64_to_be32(1)),
+        ("commitment", hex32(&w.f.commitment)),
+    ];
+    assert_eq!(candidates[0].1, candidates[1].1);
+
+    for (name, nullifier_hash) in candidates {
+        assert_ne!(nullifier_hash, w.inputs.nullifier_hash, "{name}");
+        let mut inputs = w.inputs.clone();
+        inputs.nullifier_hash = nullifier_hash;
+        // Canonical and non-zero: only the proof can refuse it
+        inputs.validate().unwrap();
+        expect_err(
+            w.check(&tree, &inputs),
+            PrivacyErrorV2::InvalidProof,
+            &format!("nullifier = {name}"),
+        );
+    }
+
+    // The constants are encoded canonically: one past r - 1 is the modulus
+    let mut inputs = w.inputs.clone();
+    inputs.nullifier_hash = BN254_FR_MODULUS;
+    expect_err(
+        w.check(&tree, &inputs),
+        PrivacyErrorV2::InvalidPublicInputs,
+        "nullifier = r",
+    );
+}
+
+// ============================================================================
+// LEAF INDEX 0
+// ============================================================================
+
+#[test]
+fn test_leaf_index_zero_across_insertion_and_paths() {
+    let w = Withdraw::load();
+    let depth = w.f.tree_depth;
+    let note = w.note();
+    let commitment = note.commitment().unwrap();
+    assert_eq!(commitment, hex32(&w.f.commitment));
+    let zeros = get_precomputed_zeros(depth);
+
+    // The first insertion takes index 0 and reproduces the fixture root
+    let mut tree = new_tree(depth);
+    assert_eq!(tree.insert_leaf(commitment, 1).unwrap(), 0);
+    assert_eq!(tree.next_leaf_index, 1);
+    let root = tree.get_current_root();
+    assert_eq!(root, w.inputs.merkle_root);
+
+    // Leaf 0 of a one-leaf tree: every sibling is an empty subtree and
+    // every path index is 0
+    let path = merkle_path(&[commitment], 0, depth).unwrap();
+    assert_eq!(path.root, root);
+    assert_eq!(path.elements, zeros[..depth as usize]);
+    assert_eq!(path.indices, vec![0u8; depth as usize]);
+    assert_eq!(tree.get_merkle_path(0).unwrap(), path.elements);
+    assert_eq!(
+        tree.root_from_path(commitment, 0, &path.elements).unwrap(),
+        root
+    );
+
+    // Index 0 enters the nullifier hash as the zero field element
+    assert_eq!(note.nullifier_hash(0).unwrap(), w.inputs.nullifier_hash);
+    assert_ne!(note.nullifier_hash(1).unwrap(), w.inputs.nullifier_hash);
+
+    // The witness for leaf 0 is the one the fixture was proven with
+    let witness = WithdrawWitness::build(
+        &note,
+        0,
+        &[commitment],
+        depth,
+        w.inputs.recipient,
+        w.inputs.relayer,
+        w.inputs.relayer_fee,
+        [0u8; 32],
+    )
+    .unwrap();
+    assert_eq!(witness.public_signals(), w.f.withdraw.public_signals);
+    w.check(&tree, &witness.public).unwrap();
+
+    // Claiming the same note at index 1 changes the nullifier and fails
+    let mut inputs = witness.public.clone();
+    inputs.nullifier_hash = note.nullifier_hash(1).unwrap();
+    expect_err(
+        w.check(&tree, &inputs),
+        PrivacyErrorV2::InvalidProof,
+        "note claimed at leaf 1",
+    );
+
+    // A second leaf becomes leaf 0's level-0 sibling; the old root stays
+    // provable one update back
+    let second = u64_to_be32(2);
+    assert_eq!(tree.insert_leaf(second, 2).unwrap(), 1);
+    let path = merkle_path(&[commitment, second], 0, depth).unwrap();
+    assert_eq!(path.elements[0], second);
+    assert_eq!(path.elements[1..], zeros[1..depth as usize]);
+    assert_eq!(path.root, tree.get_current_root());
+    assert_eq!(tree.root_updates_since(&root), Some(1));
+    w.check(&tree, &w.inputs).unwrap();
+
+    // The host-side simulation numbers leaves from 0 the same way
+    let frontier = TreeFrontier::empty(depth).unwrap();
+    let (simulated_root, next, indices) =
+        simulate_insertions(&frontier, &[commitment, second]).unwrap();
+    assert_eq!(indices, [0, 1]);
+    assert_eq!(simulated_root, tree.get_current_root());
+    assert_eq!(next.next_leaf_index, 2);
+}
@@ -0,0 +1,202 @@
+//! Withdrawals funded by a separate fee payer, against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test fee_payer
+//!
+//! A relayer whose submissions are funded from a dedicated wallet passes it
+//! as `fee_payer`: the fee payer pays the transaction fee and the nullifier
+//! rent, the relayer's balance is left untouched, and the relayer is still
+//! the one recorded as spending the nullifier.
+//!
+//! Withdrawals use the native SOL vault so trapdoor VKs can prove them.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token;
+use psol_privacy_v2::crypto::{DepositPublicInputs, WithdrawPublicInputs};
+use psol_privacy_v2::state::asset_vault::NATIVE_SOL_ASSET_ID;
+use psol_privacy_v2::state::{AssetVault, MerkleTreeV2, SpentNullifierV2};
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::account::Account;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::signature::{Keypair, Signer};
+
+use common::pool::{global_config, ix, Pool};
+use common::trapdoor::Trapdoor;
+use common::{fixture, proof_bytes};
+
+const AMOUNT: u64 = LAMPORTS_PER_SOL;
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_fee_payer_funds_withdrawal() {
+    let f = fixture();
+    let deposit = Trapdoor::new(29 << 8, 3);
+    let withdraw = Trapdoor::new((29 << 8) | 1, 8);
+    let mut pool = Pool::start_with_vks(&f, 0, &deposit.vk(), &withdraw.vk(), |_| {}).await;
+    let authority = pool.authority.pubkey();
+    let (native_vault, _) = AssetVault::find_pda(
+        &psol_privacy_v2::ID,
+        &pool.pool_config,
+        &NATIVE_SOL_ASSET_ID,
+    );
+    pool.send_as_authority(ix(
+        accounts::RegisterNativeSolAsset {
+            authority,
+            pool_config: pool.pool_config,
+            asset_vault: native_vault,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterNativeSolAsset {},
+    ))
+    .await
+    .expect("register_native_sol_asset");
+
+    let commitment = [3u8; 32];
+    let inputs =
+        DepositPublicInputs::new(commitment, AMOUNT, NATIVE_SOL_ASSET_ID).to_field_elements();
+    pool.send_as_authority(ix(
+        accounts::DepositMasp {
+            depositor: authority,
+            pool_config: pool.pool_config,
+            global_config: global_config(),
+            authority,
+            merkle_tree: pool.merkle_tree,
+            pending_buffer: pool.pending_buffer,
+            asset_vault: native_vault,
+            vault_token_account: None,
+            user_token_account: None,
+            mint: None,
+            deposit_vk: pool.deposit_vk,
+            token_program: spl_token::id(),
+            system_program: system_program::ID,
+            instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+            proof_stats: None,
+            anonymity_stats: None,
+            commitment_reservation: pool.commitment_reservation(&commitment),
+            risk_tiers: None,
+        },
+        instruction::DepositMasp {
+            amount: AMOUNT,
+            commitment,
+            asset_id: NATIVE_SOL_ASSET_ID,
+            proof_data: proof_bytes(&deposit.prove(&inputs, 1)),
+            encrypted_note: None,
+        },
+    ))
+    .await
+    .expect("native deposit");
+    pool.send_as_authority(pool.batch_ix(1, None))
+        .await
+        .expect("batch_process_deposits");
+    let root = pool
+        .account::<MerkleTreeV2>(pool.merkle_tree)
+        .await
+        .current_root;
+
+    let fee_payer = Keypair::new();
+    pool.ctx.set_account(
+        &fee_payer.pubkey(),
+        &Account::new(LAMPORTS_PER_SOL, 0, &system_program::ID).into(),
+    );
+
+    let recipient = Pubkey::new_unique();
+    let nullifier_hash = [5u8; 32];
+    let inputs = WithdrawPublicInputs::new(
+        root,
+        nullifier_hash,
+        NATIVE_SOL_ASSET_ID,
+        recipient,
+        AMOUNT,
+        authority,
+        0,
+        [0u8; 32],
+    )
+    .to_field_elements();
+    let (spent_nullifier, _) =
+        SpentNullifierV2::find_pda(&psol_privacy_v2::ID, &pool.pool_config, &nullifier_hash);
+    let withdrawal = ix(
+        accounts::WithdrawMasp {
+            relayer: authority,
+            pool_config: pool.pool_config,
+            global_config: global_config(),
+            merkle_tree: pool.merkle_tree,
+            vk_account: pool.withdraw_vk,
+            asset_vault: native_vault,
+            vault_token_account: None,
+            recipient_token_account: None,
+            relayer_token_account: None,
+            spent_nullifier,
+            relayer_registry: pool.relayer_registry,
+            relayer_node: None,
+            yield_registry: None,
+            token_program: spl_token::id(),
+            system_program: system_program::ID,
+            instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+            proof_stats: None,
+            rebate_ledger: None,
+            migration_window: None,
+            deferred_events: None,
+            recipient_wallet: Some(recipient),
+            rent_reserve: None,
+            rent_advance: None,
+            mint: None,
+            anonymity_stats: None,
+            parameter_snapshot: None,
+            recipient_ata: None,
+            associated_token_program: None,
+            fee_payer: Some(fee_payer.pubkey()),
+            treasury_token_account: None,
+            withdrawal_commit: None,
+            fee_quote: None,
+            risk_tiers: None,
+            withdrawal_window: None,
+        },
+        instruction::WithdrawMasp {
+            proof_data: proof_bytes(&withdraw.prove(&inputs, 2)),
+            merkle_root: root,
+            nullifier_hash,
+            recipient,
+            amount: AMOUNT,
+            asset_id: NATIVE_SOL_ASSET_ID,
+            relayer_fee: 0,
+        },
+    );
+
+    let relayer_before = pool.ctx.banks_client.get_balance(authority).await.unwrap();
+    let authority_keypair = pool.authority.insecure_clone();
+    pool.send(withdrawal, &[&fee_payer, &authority_keypair])
+        .await
+        .expect("withdraw_masp with a fee payer");
+
+    assert_eq!(
+        pool.ctx.banks_client.get_balance(authority).await.unwrap(),
+        relayer_before
+    );
+    let nullifier_rent = pool
+        .ctx
+        .banks_client
+        .get_rent()
+        .await
+        .unwrap()
+        .minimum_balance(SpentNullifierV2::LEN);
+    let fee_payer_spent = LAMPORTS_PER_SOL
+        - pool
+            .ctx
+            .banks_client
+            .get_balance(fee_payer.pubkey())
+            .await
+            .unwrap();
+    assert!(fee_payer_spent > nullifier_rent);
+
+    let spent: SpentNullifierV2 = pool.account(spent_nullifier).await;
+    assert_eq!(spent.relayer, authority);
+    assert_eq!(
+        pool.ctx.banks_client.get_balance(recipient).await.unwrap(),
+        AMOUNT
+    );
+}
@@ -0,0 +1,220 @@
+//! Program-wide pause against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test global_pause
+//!
+//! The global config is placed directly (program-test does not deploy the
+//! program upgradeable, so `initialize_global_config` only rejects here).
+//! While the super guardian has paused all pools, deposits and batches
+//! fail with `GlobalPaused`; they go through again once it is lifted.
+//!
+//! Fixture: see tests/common/mod.rs.
+
+mod common;
+
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use anchor_lang::{system_program, AccountSerialize};
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::state::GlobalConfig;
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+
+use common::pool::{assert_program_error, global_config, ix, Pool};
+use common::{fixture, hex32};
+
+/// Place an unpaused global config naming `super_guardian`
+fn place_global_config(pool: &mut Pool, super_guardian: Pubkey) {
+    let (address, bump) = GlobalConfig::find_pda(&psol_privacy_v2::ID);
+    let config = GlobalConfig {
+        super_guardian,
+        is_paused: false,
+        updated_at: 0,
+        bump,
+        pool_creation_fee: 0,
+        _reserved: [0u8; 24],
+    };
+    let mut data = Vec::with_capacity(GlobalConfig::LEN);
+    config.try_serialize(&mut data).unwrap();
+    pool.ctx.set_account(
+        &address,
+        &Account {
+            lamports: 1_000_000_000,
+            data,
+            owner: psol_privacy_v2::ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+        .into(),
+    );
+}
+
+fn pause_ix(super_guardian: Pubkey) -> Instruction {
+    ix(
+        accounts::PauseAllPools {
+            super_guardian,
+            global_config: global_config(),
+        },
+        instruction::PauseAllPools {},
+    )
+}
+
+fn unpause_ix(super_guardian: Pubkey) -> Instruction {
+    ix(
+        accounts::UnpauseAllPools {
+            super_guardian,
+            global_config: global_config(),
+        },
+        instruction::UnpauseAllPools {},
+    )
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_global_pause_blocks_fund_movement() {
+    let f = fixture();
+    let amount: u64 = f.amount.parse().unwrap();
+    let commitment = hex32(&f.commitment);
+    let mut pool = Pool::start(&f, amount, |_| {}).await;
+    let payer = pool.authority.insecure_clone();
+    let guardian = Keypair::new();
+    place_global_config(&mut pool, guardian.pubkey());
+
+    // Only the super guardian may pause, and the pool authority is not it
+    assert_program_error(
+        pool.send_as_authority(pause_ix(payer.pubkey())).await,
+        PrivacyErrorV2::Unauthorized,
+    );
+    pool.send(pause_ix(guardian.pubkey()), &[&payer, &guardian])
+        .await
+        .expect("pause_all_pools");
+    let config: GlobalConfig = pool.account(global_config()).await;
+    assert!(config.is_paused);
+
+    assert_program_error(
+        pool.send_as_authority(pool.deposit_ix(amount, commitment, &f.deposit.proof))
+            .await,
+        PrivacyErrorV2::GlobalPaused,
+    );
+    assert_program_error(
+        pool.send_as_authority(pool.batch_ix(1, None)).await,
+        PrivacyErrorV2::GlobalPaused,
+    );
+    pool.refresh_blockhash().await;
+    assert_program_error(
+        pool.send(pause_ix(guardian.pubkey()), &[&payer, &guardian])
+            .await,
+        PrivacyErrorV2::GlobalPaused,
+    );
+
+    pool.send(unpause_ix(guardian.pubkey()), &[&payer, &guardian])
+        .await
+        .expect("unpause_all_pools");
+    pool.send_as_authority(pool.deposit_ix(amount, commitment, &f.deposit.proof))
+        .await
+        .expect("deposit_masp");
+    pool.send_as_authority(pool.batch_ix(1, None))
+        .await
+        .expect("batch_process_deposits");
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_super_guardian_handover() {
+    let f = fixture();
+    let mut pool = Pool::start(&f, 1_000_000, |_| {}).await;
+    let payer = pool.authority.insecure_clone();
+    let guardian = Keypair::new();
+    let successor = Keypair::new();
+    place_global_config(&mut pool, guardian.pubkey());
+
+    let handover = |super_guardian, new_super_guardian| {
+        ix(
+            accounts::SetSuperGuardian {
+                super_guardian,
+                global_config: global_config(),
+            },
+            instruction::SetSuperGuardian { new_super_guardian },
+        )
+    };
+    assert_program_error(
+        pool.send(
+            handover(successor.pubkey(), successor.pubkey()),
+            &[&payer, &successor],
+        )
+        .await,
+        PrivacyErrorV2::Unauthorized,
+    );
+    pool.send(
+        handover(guardian.pubkey(), successor.pubkey()),
+        &[&payer, &guardian],
+    )
+    .await
+    .expect("set_super_guardian");
+
+    // The previous guardian is out
+    assert_program_error(
+        pool.send(pause_ix(guardian.pubkey()), &[&payer, &guardian])
+            .await,
+        PrivacyErrorV2::Unauthorized,
+    );
+    pool.send(pause_ix(successor.pubkey()), &[&payer, &successor])
+        .await
+        .expect("pause_all_pools");
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_global_config_needs_upgrade_authority() {
+    let f = fixture();
+    let mut pool = Pool::start(&f, 1_000_000, |_| {}).await;
+    let authority = pool.authority.pubkey();
+
+    // Program data naming the caller, but not the program's own (the test
+    // deployment is not upgradeable)
+    let program_data = Pubkey::new_unique();
+    // bincode `UpgradeableLoaderState::ProgramData { slot: 0, Some(authority) }`
+    let mut data = 3u32.to_le_bytes().to_vec();
+    data.extend_from_slice(&0u64.to_le_bytes());
+    data.push(1);
+    data.extend_from_slice(authority.as_ref());
+    pool.ctx.set_account(
+        &program_data,
+        &Account {
+            lamports: 1_000_000_000,
+            data,
+            owner: bpf_loader_upgradeable::id(),
+            executable: false,
+            rent_epoch: 0,
+        }
+        .into(),
+    );
+
+    assert_program_error(
+        pool.send_as_authority(ix(
+            accounts::InitializeGlobalConfig {
+                authority,
+                global_config: global_config(),
+                program: psol_privacy_v2::ID,
+                program_data,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeGlobalConfig {
+                super_guardian: authority,
+            },
+        ))
+        .await,
+        PrivacyErrorV2::Unauthorized,
+    );
+    assert!(pool
+        .ctx
+        .banks_client
+        .get_account(global_config())
+        .await
+        .unwrap()
+        .is_none());
+}
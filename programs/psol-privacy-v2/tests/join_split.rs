@@ -0,0 +1,441 @@
+//! Join-split private transfers against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test join_split
+//!
+//! A 2-in-2-out transfer shields a public inflow from the relayer's token
+//! account, a second one pays a public outflow to the recipient bound by
+//! the proof and the relayer fee (under the withdrawal fee cap) to the
+//! relayer, and replaying spent nullifiers fails. An outflow to another
+//! wallet's token account or recipient is refused. With a protocol fee
+//! set, an outflow pays it into the asset's treasury. Proofs come from a
+//! trapdoor VK, as there is no join-split circuit fixture.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{system_program, AnchorDeserialize, AnchorSerialize};
+use anchor_spl::token;
+use psol_privacy_v2::crypto::JoinSplitPublicInputs;
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::state::{
+    AssetVault, MerkleTreeV2, PoolConfigV2, SpendType, SpentNullifierV2, VerificationKeyAccountV2,
+};
+use psol_privacy_v2::{accounts, instruction, ProofType};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::signature::Signer;
+
+use common::pool::{assert_program_error, create_vk_ix, global_config, ix, token_account, Pool};
+use common::trapdoor::Trapdoor;
+use common::{fixture, g1, g2, proof_bytes};
+
+const SUPPLY: u64 = 10_000_000;
+const INFLOW: u64 = 4_000_000;
+const OUTFLOW: u64 = 1_500_000;
+
+/// 2 nullifiers + 2 outputs + the 6 fixed inputs
+const PUBLIC_INPUTS: usize = 10;
+
+struct Transfer {
+    nullifiers: [[u8; 32]; 2],
+    outputs: [[u8; 32]; 2],
+    public_amount: i64,
+    relayer_fee: u64,
+    treasury: Option<Pubkey>,
+    recipient: Pubkey,
+    recipient_token: Option<Pubkey>,
+}
+
+impl Transfer {
+    fn ix(&self, pool: &Pool, circuit: &Trapdoor, root: [u8; 32], salt: u64) -> Instruction {
+        let relayer = pool.authority.pubkey();
+        let (vk_account, _) = VerificationKeyAccountV2::find_pda(
+            &psol_privacy_v2::ID,
+            &pool.pool_config,
+            ProofType::JoinSplit,
+        );
+        let inputs = JoinSplitPublicInputs::new(
+            root,
+            pool.asset_id,
+            self.nullifiers.to_vec(),
+            self.outputs.to_vec(),
+            self.public_amount,
+            self.recipient,
+            relayer,
+            self.relayer_fee,
+        )
+        .to_field_elements();
+
+        let mut ix = ix(
+            accounts::PrivateTransferJoinSplit {
+                relayer,
+                pool_config: pool.pool_config,
+                global_config: global_config(),
+                merkle_tree: pool.merkle_tree,
+                vk_account,
+                asset_vault: pool.asset_vault,
+                vault_token_account: Some(pool.vault_token),
+                relayer_token_account: Some(pool.user_token),
+                relayer_registry: pool.relayer_registry,
+                token_program: token::ID,
+                system_program: system_program::ID,
+                relayer_node: None,
+                mint: Some(pool.mint),
+                yield_registry: None,
+                instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+                treasury_token_account: self.treasury,
+                risk_tiers: None,
+                withdrawal_window: None,
+                anonymity_stats: None,
+                migration_window: None,
+                recipient_token_account: self.recipient_token,
+                proof_stats: None,
+            },
+            instruction::PrivateTransferJoinSplit {
+                proof_data: proof_bytes(&circuit.prove(&inputs, salt)),
+                merkle_root: root,
+                input_nullifiers: self.nullifiers.to_vec(),
+                output_commitments: self.outputs.to_vec(),
+                public_amount: self.public_amount,
+                recipient: self.recipient,
+                asset_id: pool.asset_id,
+                relayer_fee: self.relayer_fee,
+                encrypted_outputs: None,
+            },
+        );
+        for nullifier_hash in &self.nullifiers {
+            let (spent, _) =
+                SpentNullifierV2::find_pda(&psol_privacy_v2::ID, &pool.pool_config, nullifier_hash);
+            ix.accounts.push(AccountMeta::new(spent, false));
+        }
+        ix
+    }
+}
+
+async fn set_join_split_vk(pool: &mut Pool, circuit: &Trapdoor) {
+    let authority = pool.authority.pubkey();
+    let vk = circuit.vk();
+    let (vk_account, _) = VerificationKeyAccountV2::find_pda(
+        &psol_privacy_v2::ID,
+        &pool.pool_config,
+        ProofType::JoinSplit,
+    );
+    let set = ix(
+        accounts::SetVerificationKeyData {
+            authority,
+            pool_config: pool.pool_config,
+            vk_account,
+            circuit_descriptor: None,
+            merkle_tree: pool.merkle_tree,
+        },
+        instruction::SetVerificationKeyData {
+            proof_type: ProofType::JoinSplit,
+            vk_alpha_g1: g1(&vk.vk_alpha_1),
+            vk_beta_g2: g2(&vk.vk_beta_2),
+            vk_gamma_g2: g2(&vk.vk_gamma_2),
+            vk_delta_g2: g2(&vk.vk_delta_2),
+            vk_ic: vk.ic.iter().map(|p| g1(p)).collect(),
+            ceremony_transcript_hash: [0u8; 32],
+            ceremony_contributors: 0,
+        },
+    );
+    let create = create_vk_ix(authority, pool.pool_config, ProofType::JoinSplit);
+    let signer = pool.authority.insecure_clone();
+    pool.send_all(&[create, set], &[&signer])
+        .await
+        .expect("set_verification_key_data");
+}
+
+async fn enable_join_split(pool: &mut Pool) {
+    let authority = pool.authority.pubkey();
+    pool.send_as_authority(ix(
+        accounts::SetFeatureFlags {
+            authority,
+            pool_config: pool.pool_config,
+        },
+        instruction::EnableFeature {
+            feature: PoolConfigV2::FEATURE_JOIN_SPLIT,
+        },
+    ))
+    .await
+    .expect("enable_feature");
+}
+
+/// A fresh wallet and its empty token account for the pool's mint
+fn recipient_account(pool: &mut Pool) -> (Pubkey, Pubkey) {
+    let recipient = Pubkey::new_unique();
+    let recipient_token = Pubkey::new_unique();
+    pool.ctx.set_account(
+        &recipient_token,
+        &token_account(pool.mint, recipient, 0).into(),
+    );
+    (recipient, recipient_token)
+}
+
+async fn current_root(pool: &mut Pool) -> [u8; 32] {
+    pool.account::<MerkleTreeV2>(pool.merkle_tree)
+        .await
+        .current_root
+}
+
+#[tokio::test]
+#[cfg_attr(
+    not(sbf_build),
+    ignore = "needs the SBF build of the program (see module docs)"
+)]
+async fn test_join_split_shields_and_unshields() {
+    let f = fixture();
+    let circuit = Trapdoor::new(13 << 8, PUBLIC_INPUTS);
+    let mut pool = Pool::start(&f, SUPPLY, |_| {}).await;
+
+    let inflow = Transfer {
+        nullifiers: [[1u8; 32], [2u8; 32]],
+        outputs: [[3u8; 32], [4u8; 32]],
+        public_amount: INFLOW as i64,
+        relayer_fee: 0,
+        treasury: None,
+        recipient: Pubkey::default(),
+        recipient_token: None,
+    };
+    let root = current_root(&mut pool).await;
+
+    // The pool has to opt in
+    set_join_split_vk(&mut pool, &circuit).await;
+    assert_program_error(
+        pool.send_as_authority(inflow.ix(&pool, &circuit, root, 1))
+            .await,
+        PrivacyErrorV2::FeatureDisabled,
+    );
+    enable_join_split(&mut pool).await;
+
+    pool.send_as_authority(inflow.ix(&pool, &circuit, root, 2))
+        .await
+        .expect("join-split inflow");
+
+    assert_eq!(pool.token_balance(pool.vault_token).await, INFLOW);
+    assert_eq!(pool.token_balance(pool.user_token).await, SUPPLY - INFLOW);
+    let vault: AssetVault = pool.account(pool.asset_vault).await;
+    assert_eq!(vault.shielded_balance, INFLOW);
+    let tree: MerkleTreeV2 = pool.account(pool.merkle_tree).await;
+    assert_eq!(tree.next_leaf_index, 2);
+    for nullifier_hash in &inflow.nullifiers {
+        let (spent, _) =
+            SpentNullifierV2::find_pda(&psol_privacy_v2::ID, &pool.pool_config, nullifier_hash);
+        let record: SpentNullifierV2 = pool.account(spent).await;
+        assert_eq!(record.get_spend_type(), Some(SpendType::JoinSplit));
+        assert_eq!(record.relayer, pool.authority.pubkey());
+    }
+    let config: PoolConfigV2 = pool.account(pool.pool_config).await;
+    assert_eq!(config.total_join_splits, 1);
+
+    // Spent nullifiers cannot be reused, even against the new root
+    let root = tree.current_root;
+    pool.refresh_blockhash().await;
+    assert_program_error(
+        pool.send_as_authority(inflow.ix(&pool, &circuit, root, 3))
+            .await,
+        PrivacyErrorV2::NullifierAlreadySpent,
+    );
+
+    // An outflow is a withdrawal: the relayer fee is capped at 10% of it
+    let (recipient, recipient_token) = recipient_account(&mut pool);
+    let greedy = Transfer {
+        nullifiers: [[5u8; 32], [6u8; 32]],
+        outputs: [[7u8; 32], [8u8; 32]],
+        public_amount: -(OUTFLOW as i64),
+        relayer_fee: OUTFLOW / 5,
+        treasury: None,
+        recipient,
+        recipient_token: Some(recipient_token),
+    };
+    assert_program_error(
+        pool.send_as_authority(greedy.ix(&pool, &circuit, root, 5))
+            .await,
+        PrivacyErrorV2::RelayerFeeOutOfRange,
+    );
+
+    let outflow = Transfer {
+        relayer_fee: 1_000,
+        ..greedy
+    };
+
+    // The outflow only goes to a token account of the proof's recipient
+    let (other, other_token) = recipient_account(&mut pool);
+    let mut misdirected = outflow.ix(&pool, &circuit, root, 6);
+    for meta in misdirected.accounts.iter_mut() {
+        if meta.pubkey == recipient_token {
+            meta.pubkey = other_token;
+        }
+    }
+    assert_program_error(
+        pool.send_as_authority(misdirected.clone()).await,
+        PrivacyErrorV2::RecipientMismatch,
+    );
+    let mut args =
+        instruction::PrivateTransferJoinSplit::try_from_slice(&misdirected.data[8..]).unwrap();
+    args.recipient = other;
+    misdirected.data.truncate(8);
+    args.serialize(&mut misdirected.data).unwrap();
+    assert_program_error(
+        pool.send_as_authority(misdirected).await,
+        PrivacyErrorV2::InvalidProof,
+    );
+
+    // The recipient is paid the outflow less the relayer fee, which goes to
+    // the relayer's token account
+    pool.send_as_authority(outflow.ix(&pool, &circuit, root, 4))
+        .await
+        .expect("join-split outflow");
+
+    assert_eq!(pool.token_balance(pool.vault_token).await, INFLOW - OUTFLOW);
+    assert_eq!(
+        pool.token_balance(recipient_token).await,
+        OUTFLOW - outflow.relayer_fee
+    );
+    assert_eq!(
+        pool.token_balance(pool.user_token).await,
+        SUPPLY - INFLOW + outflow.relayer_fee
+    );
+    assert_eq!(pool.token_balance(other_token).await, 0);
+    let vault: AssetVault = pool.account(pool.asset_vault).await;
+    assert_eq!(vault.shielded_balance, INFLOW - OUTFLOW);
+    let tree: MerkleTreeV2 = pool.account(pool.merkle_tree).await;
+    assert_eq!(tree.next_leaf_index, 4);
+}
+
+#[tokio::test]
+#[cfg_attr(
+    not(sbf_build),
+    ignore = "needs the SBF build of the program (see module docs)"
+)]
+async fn test_join_split_rejects_misordered_nullifier_accounts() {
+    let f = fixture();
+    let circuit = Trapdoor::new((13 << 8) | 1, PUBLIC_INPUTS);
+    let mut pool = Pool::start(&f, SUPPLY, |_| {}).await;
+    set_join_split_vk(&mut pool, &circuit).await;
+    enable_join_split(&mut pool).await;
+
+    let transfer = Transfer {
+        nullifiers: [[1u8; 32], [2u8; 32]],
+        outputs: [[3u8; 32], [4u8; 32]],
+        public_amount: 0,
+        relayer_fee: 0,
+        treasury: None,
+        recipient: Pubkey::default(),
+        recipient_token: None,
+    };
+    let root = current_root(&mut pool).await;
+    let mut ix = transfer.ix(&pool, &circuit, root, 1);
+    let n = ix.accounts.len();
+    ix.accounts.swap(n - 1, n - 2);
+
+    let result = pool.send_as_authority(ix).await;
+    common::pool::assert_error_code(
+        result,
+        anchor_lang::error::ErrorCode::ConstraintSeeds.into(),
+        "ConstraintSeeds",
+    );
+
+    // The same transfer with the accounts in order is a pure private
+    // transfer: no tokens move
+    pool.refresh_blockhash().await;
+    pool.send_as_authority(transfer.ix(&pool, &circuit, root, 2))
+        .await
+        .expect("join-split");
+    assert_eq!(pool.token_balance(pool.vault_token).await, 0);
+}
+
+#[tokio::test]
+#[cfg_attr(
+    not(sbf_build),
+    ignore = "needs the SBF build of the program (see module docs)"
+)]
+async fn test_join_split_outflow_pays_protocol_fee() {
+    const FEE_BPS: u16 = 50;
+    let f = fixture();
+    let circuit = Trapdoor::new((13 << 8) | 2, PUBLIC_INPUTS);
+    let mut pool = Pool::start(&f, SUPPLY, |_| {}).await;
+    set_join_split_vk(&mut pool, &circuit).await;
+    enable_join_split(&mut pool).await;
+    let authority = pool.authority.pubkey();
+    let (treasury, _) = AssetVault::find_treasury_pda(&psol_privacy_v2::ID, &pool.asset_vault);
+
+    pool.send_as_authority(ix(
+        accounts::SetProtocolFee {
+            authority,
+            pool_config: pool.pool_config,
+        },
+        instruction::SetProtocolFee { fee_bps: FEE_BPS },
+    ))
+    .await
+    .expect("set_protocol_fee");
+    pool.send_as_authority(ix(
+        accounts::InitTreasury {
+            authority,
+            pool_config: pool.pool_config,
+            asset_vault: pool.asset_vault,
+            mint: pool.mint,
+            treasury_token_account: treasury,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::InitTreasury {
+            asset_id: pool.asset_id,
+        },
+    ))
+    .await
+    .expect("init_treasury");
+
+    let inflow = Transfer {
+        nullifiers: [[1u8; 32], [2u8; 32]],
+        outputs: [[3u8; 32], [4u8; 32]],
+        public_amount: INFLOW as i64,
+        relayer_fee: 0,
+        treasury: None,
+        recipient: Pubkey::default(),
+        recipient_token: None,
+    };
+    let root = current_root(&mut pool).await;
+    pool.send_as_authority(inflow.ix(&pool, &circuit, root, 1))
+        .await
+        .expect("join-split inflow");
+
+    // The fee cannot be skipped by leaving out the treasury
+    let root = current_root(&mut pool).await;
+    let (recipient, recipient_token) = recipient_account(&mut pool);
+    let outflow = Transfer {
+        nullifiers: [[5u8; 32], [6u8; 32]],
+        outputs: [[7u8; 32], [8u8; 32]],
+        public_amount: -(OUTFLOW as i64),
+        relayer_fee: 1_000,
+        treasury: None,
+        recipient,
+        recipient_token: Some(recipient_token),
+    };
+    assert_program_error(
+        pool.send_as_authority(outflow.ix(&pool, &circuit, root, 2))
+            .await,
+        PrivacyErrorV2::TreasuryRequired,
+    );
+    let outflow = Transfer {
+        treasury: Some(treasury),
+        ..outflow
+    };
+    pool.send_as_authority(outflow.ix(&pool, &circuit, root, 3))
+        .await
+        .expect("join-split outflow");
+
+    let protocol_fee = OUTFLOW * FEE_BPS as u64 / 10_000;
+    assert_eq!(pool.token_balance(treasury).await, protocol_fee);
+    assert_eq!(
+        pool.token_balance(recipient_token).await,
+        OUTFLOW - outflow.relayer_fee - protocol_fee
+    );
+    assert_eq!(
+        pool.token_balance(pool.user_token).await,
+        SUPPLY - INFLOW + outflow.relayer_fee
+    );
+    assert_eq!(pool.token_balance(pool.vault_token).await, INFLOW - OUTFLOW);
+}
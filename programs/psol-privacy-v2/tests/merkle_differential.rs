@@ -0,0 +1,271 @@
+//! Differential test of the on-chain Merkle tree against a reference tree
+//!
+//! The reference is a plain sparse tree that stores every non-empty node
+//! and hashes with light-poseidon's circomlib parameters, so it shares
+//! neither the program's constants-based Poseidon nor its incremental
+//! `filled_subtrees` update. Pseudo-random leaves are inserted into both,
+//! directly and through the pending buffer / batch loop that
+//! `batch_process_deposits` runs, and the roots must agree after every
+//! insertion and every batch.
+
+use anchor_lang::prelude::Pubkey;
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, PrimeField};
+use light_poseidon::{Poseidon, PoseidonHasher};
+use psol_privacy_v2::crypto::poseidon2;
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::state::pending_deposits::MAX_PENDING_DEPOSITS;
+use psol_privacy_v2::state::{MerkleTreeV2, PendingDepositsBuffer, MAX_TREE_DEPTH, MIN_TREE_DEPTH};
+
+const ROOT_HISTORY_SIZE: u16 = 64;
+
+fn to_fr(bytes: &[u8; 32]) -> Fr {
+    Fr::from_be_bytes_mod_order(bytes)
+}
+
+fn to_bytes(fr: Fr) -> [u8; 32] {
+    fr.into_bigint().to_bytes_be().try_into().unwrap()
+}
+
+/// Sparse Merkle tree keeping every node, hashed with circomlib Poseidon
+struct ReferenceTree {
+    depth: usize,
+    /// Non-empty nodes per level, left to right
+    levels: Vec<Vec<Fr>>,
+    zeros: Vec<Fr>,
+    hasher: Poseidon<Fr>,
+}
+
+impl ReferenceTree {
+    fn new(depth: u8) -> Self {
+        let depth = depth as usize;
+        let mut hasher = Poseidon::<Fr>::new_circom(2).unwrap();
+        let mut zeros = vec![Fr::from(0u64)];
+        for level in 0..depth {
+            let zero = zeros[level];
+            zeros.push(hasher.hash(&[zero, zero]).unwrap());
+        }
+        Self {
+            depth,
+            levels: vec![Vec::new(); depth + 1],
+            zeros,
+            hasher,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    fn node(&self, level: usize, index: usize) -> Fr {
+        self.levels[level]
+            .get(index)
+            .copied()
+            .unwrap_or(self.zeros[level])
+    }
+
+    /// Append `leaf` and rehash its path to the root
+    fn insert(&mut self, leaf: [u8; 32]) {
+        let mut index = self.len();
+        self.levels[0].push(to_fr(&leaf));
+        for level in 0..self.depth {
+            let left = self.node(level, index & !1);
+            let right = self.node(level, index | 1);
+            let parent = self.hasher.hash(&[left, right]).unwrap();
+            index >>= 1;
+            if index < self.levels[level + 1].len() {
+                self.levels[level + 1][index] = parent;
+            } else {
+                self.levels[level + 1].push(parent);
+            }
+        }
+    }
+
+    fn root(&self) -> [u8; 32] {
+        to_bytes(self.node(self.depth, 0))
+    }
+
+    /// Sibling hashes from leaf `index` up to the root
+    fn path(&self, index: usize) -> Vec<[u8; 32]> {
+        (0..self.depth)
+            .map(|level| to_bytes(self.node(level, (index >> level) ^ 1)))
+            .collect()
+    }
+}
+
+/// Fold a membership path the way the withdraw circuit does
+fn root_from_path(leaf: [u8; 32], index: usize, path: &[[u8; 32]]) -> [u8; 32] {
+    path.iter()
+        .enumerate()
+        .fold(leaf, |node, (level, sibling)| {
+            if (index >> level) & 1 == 0 {
+                poseidon2(&node, sibling).unwrap()
+            } else {
+                poseidon2(sibling, &node).unwrap()
+            }
+        })
+}
+
+/// Deterministic canonical, non-zero field elements (splitmix64)
+struct Leaves(u64);
+
+impl Iterator for Leaves {
+    type Item = [u8; 32];
+
+    fn next(&mut self) -> Option<[u8; 32]> {
+        let mut leaf = [0u8; 32];
+        for chunk in leaf.chunks_mut(8) {
+            self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = self.0;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            chunk.copy_from_slice(&(z ^ (z >> 31)).to_be_bytes());
+        }
+        // Below the BN254 scalar modulus (0x30...)
+        leaf[0] &= 0x1f;
+        assert_ne!(leaf, [0u8; 32]);
+        Some(leaf)
+    }
+}
+
+fn new_tree(depth: u8) -> MerkleTreeV2 {
+    let mut tree = MerkleTreeV2 {
+        pool: Pubkey::default(),
+        depth: 0,
+        next_leaf_index: 0,
+        current_root: [0u8; 32],
+        root_history: Vec::new(),
+        root_history_index: 0,
+        root_history_size: 0,
+        filled_subtrees: Vec::new(),
+        zeros: Vec::new(),
+        total_leaves: 0,
+        last_insertion_at: 0,
+        version: 0,
+        poseidon_params_hash: [0u8; 32],
+    };
+    tree.initialize(Pubkey::new_unique(), depth, ROOT_HISTORY_SIZE)
+        .unwrap();
+    tree
+}
+
+fn new_buffer() -> PendingDepositsBuffer {
+    let mut buffer: PendingDepositsBuffer = bytemuck::Zeroable::zeroed();
+    buffer.initialize(Pubkey::default(), 0, 0);
+    buffer
+}
+
+/// Reference roots after each of the first `count` leaves
+fn reference_roots(depth: u8, seed: u64, count: usize) -> Vec<[u8; 32]> {
+    let mut reference = ReferenceTree::new(depth);
+    Leaves(seed)
+        .take(count)
+        .map(|leaf| {
+            reference.insert(leaf);
+            reference.root()
+        })
+        .collect()
+}
+
+#[test]
+fn test_empty_roots_match_reference() {
+    for depth in MIN_TREE_DEPTH..=MAX_TREE_DEPTH {
+        let tree = new_tree(depth);
+        let reference = ReferenceTree::new(depth);
+        assert_eq!(tree.get_current_root(), reference.root(), "depth {depth}");
+        for (level, zero) in tree.zeros.iter().enumerate() {
+            assert_eq!(*zero, to_bytes(reference.zeros[level]), "depth {depth}");
+        }
+    }
+}
+
+#[test]
+fn test_inserts_match_reference_until_full() {
+    const DEPTH: u8 = 10;
+    let mut tree = new_tree(DEPTH);
+    let mut reference = ReferenceTree::new(DEPTH);
+    let leaves: Vec<[u8; 32]> = Leaves(1).take(1 << DEPTH).collect();
+
+    for (i, leaf) in leaves.iter().enumerate() {
+        assert_eq!(tree.insert_leaf(*leaf, i as i64).unwrap(), i as u32);
+        reference.insert(*leaf);
+        assert_eq!(tree.get_current_root(), reference.root(), "leaf {i}");
+
+        // The reference path for an earlier leaf opens the on-chain root
+        if i % 97 == 0 {
+            let j = (i * 31) % (i + 1);
+            assert_eq!(
+                root_from_path(leaves[j], j, &reference.path(j)),
+                tree.get_current_root(),
+                "path of leaf {j} after {i}"
+            );
+        }
+    }
+
+    // Full: the next insert fails and leaves the root untouched
+    let root = tree.get_current_root();
+    let err = tree.insert_leaf([7u8; 32], 0).unwrap_err();
+    assert_eq!(err, PrivacyErrorV2::MerkleTreeFull.into());
+    assert_eq!(tree.get_current_root(), root);
+    assert_eq!(tree.next_leaf_index, 1 << DEPTH);
+}
+
+#[test]
+fn test_batched_inserts_match_reference_for_any_batch_size() {
+    const DEPTH: u8 = 12;
+    const LEAVES: usize = 800;
+    const SEED: u64 = 2;
+    let roots = reference_roots(DEPTH, SEED, LEAVES);
+
+    for batch_size in [1usize, 3, 16, 50, MAX_PENDING_DEPOSITS] {
+        let mut tree = new_tree(DEPTH);
+        let mut buffer = new_buffer();
+        let mut leaves = Leaves(SEED).take(LEAVES);
+        let mut queued = 0usize;
+        let mut ts = 0i64;
+
+        // Deposits arrive in uneven bursts between batches
+        let mut burst = Leaves(SEED ^ batch_size as u64);
+        loop {
+            let arrivals = (burst.next().unwrap()[31] as usize) % (2 * batch_size + 1);
+            for leaf in leaves
+                .by_ref()
+                .take(arrivals.min(MAX_PENDING_DEPOSITS - buffer.size()))
+            {
+                ts += 1;
+                buffer.add_pending(leaf, ts).unwrap();
+                queued += 1;
+            }
+            if buffer.is_empty() {
+                if queued == LEAVES {
+                    break;
+                }
+                continue;
+            }
+
+            // Same loop as batch_process_deposits
+            let batch: Vec<_> = buffer.prepare_batch(batch_size as u16).copied().collect();
+            for deposit in &batch {
+                tree.insert_leaf(deposit.commitment, deposit.timestamp)
+                    .unwrap();
+            }
+            buffer.clear_processed(batch.len() as u32, ts).unwrap();
+
+            let inserted = tree.next_leaf_index as usize;
+            assert_eq!(
+                tree.get_current_root(),
+                roots[inserted - 1],
+                "batch size {batch_size}, {inserted} leaves"
+            );
+            // Every intermediate root of the batch stays provable
+            let window = batch.len().min(ROOT_HISTORY_SIZE as usize);
+            for root in &roots[inserted - window..inserted] {
+                assert!(tree.is_known_root(root), "batch size {batch_size}");
+            }
+        }
+
+        assert_eq!(tree.next_leaf_index as usize, LEAVES);
+        assert_eq!(buffer.total_deposits_batched as usize, LEAVES);
+        assert_eq!(tree.get_current_root(), roots[LEAVES - 1]);
+    }
+}
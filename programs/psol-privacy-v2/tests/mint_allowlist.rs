@@ -0,0 +1,146 @@
+//! Permissionless registration of allowlisted mints against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test mint_allowlist
+//!
+//! The authority allowlists a mint and opens registration; an outsider can
+//! then register that mint (paying for the vault) but no other, and only
+//! while registration is open. Curating the list stays authority-only.
+//!
+//! Fixture: see tests/common/mod.rs.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token;
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::state::asset_vault::compute_asset_id;
+use psol_privacy_v2::state::{AssetVault, MintAllowlist};
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer};
+
+use common::fixture;
+use common::pool::{assert_program_error, ix, mint_account, Pool};
+
+fn manage(pool: &Pool, authority: Pubkey, allowlist: Pubkey) -> accounts::ManageMintAllowlist {
+    accounts::ManageMintAllowlist {
+        authority,
+        pool_config: pool.pool_config,
+        mint_allowlist: allowlist,
+    }
+}
+
+fn register_ix(pool: &Pool, payer: Pubkey, allowlist: Pubkey, mint: Pubkey) -> Instruction {
+    let asset_id = compute_asset_id(&mint);
+    let (asset_vault, _) = AssetVault::find_pda(&psol_privacy_v2::ID, &pool.pool_config, &asset_id);
+    let (vault_token_account, _) = Pubkey::find_program_address(
+        &[b"vault_token", asset_vault.as_ref()],
+        &psol_privacy_v2::ID,
+    );
+    ix(
+        accounts::RegisterAllowlistedAsset {
+            payer,
+            pool_config: pool.pool_config,
+            mint_allowlist: allowlist,
+            mint,
+            asset_vault,
+            vault_token_account,
+            token_program: spl_token::id(),
+            system_program: system_program::ID,
+            token_metadata: None,
+        },
+        instruction::RegisterAllowlistedAsset { asset_id },
+    )
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_register_allowlisted_asset() {
+    let f = fixture();
+    let listed = Pubkey::new_unique();
+    let unlisted = Pubkey::new_unique();
+    let mut pool = Pool::start(&f, 0, |pt| {
+        pt.add_account(listed, mint_account(0));
+        pt.add_account(unlisted, mint_account(0));
+    })
+    .await;
+    let authority = pool.authority.pubkey();
+    let (allowlist, _) = MintAllowlist::find_pda(&psol_privacy_v2::ID, &pool.pool_config);
+
+    let outsider = Keypair::new();
+    pool.ctx.set_account(
+        &outsider.pubkey(),
+        &Account::new(10_000_000_000, 0, &system_program::ID).into(),
+    );
+
+    pool.send_as_authority(ix(
+        accounts::InitMintAllowlist {
+            authority,
+            pool_config: pool.pool_config,
+            mint_allowlist: allowlist,
+            system_program: system_program::ID,
+        },
+        instruction::InitMintAllowlist {},
+    ))
+    .await
+    .expect("init_mint_allowlist");
+    pool.send_as_authority(ix(
+        manage(&pool, authority, allowlist),
+        instruction::AddAllowlistedMint { mint: listed },
+    ))
+    .await
+    .expect("add_allowlisted_mint");
+
+    // Curation is authority-only
+    let add_by_outsider = ix(
+        manage(&pool, outsider.pubkey(), allowlist),
+        instruction::AddAllowlistedMint { mint: unlisted },
+    );
+    assert_program_error(
+        pool.send(add_by_outsider, &[&outsider]).await,
+        PrivacyErrorV2::Unauthorized,
+    );
+
+    // Registration starts closed
+    let register_listed = register_ix(&pool, outsider.pubkey(), allowlist, listed);
+    assert_program_error(
+        pool.send(register_listed.clone(), &[&outsider]).await,
+        PrivacyErrorV2::AssetRegistrationClosed,
+    );
+
+    pool.send_as_authority(ix(
+        manage(&pool, authority, allowlist),
+        instruction::SetOpenAssetRegistration { open: true },
+    ))
+    .await
+    .expect("set_open_asset_registration");
+
+    assert_program_error(
+        pool.send(
+            register_ix(&pool, outsider.pubkey(), allowlist, unlisted),
+            &[&outsider],
+        )
+        .await,
+        PrivacyErrorV2::MintNotAllowlisted,
+    );
+
+    pool.refresh_blockhash().await;
+    pool.send(register_listed, &[&outsider])
+        .await
+        .expect("register_allowlisted_asset");
+
+    let (vault_key, _) = AssetVault::find_pda(
+        &psol_privacy_v2::ID,
+        &pool.pool_config,
+        &compute_asset_id(&listed),
+    );
+    let vault = pool.account::<AssetVault>(vault_key).await;
+    assert_eq!(vault.mint, listed);
+    assert_eq!(vault.pool, pool.pool_config);
+    assert!(vault.is_active);
+}
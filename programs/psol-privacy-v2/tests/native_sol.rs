@@ -0,0 +1,459 @@
+//! Native SOL deposits and withdrawals against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test native_sol
+//!
+//! The native SOL vault takes lamports from the depositor's wallet and pays
+//! them out to the recipient's wallet and the relayer, with no token
+//! accounts involved. SPL vaults still require their token accounts.
+//!
+//! A throwaway fee payer can submit the withdrawal itself and be refunded
+//! its costs in lamports out of the note, as bound into the proof.
+//!
+//! Proofs come from trapdoor VKs (tests/common/trapdoor.rs), since the
+//! circuit fixture is for the SPL asset.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token;
+use psol_privacy_v2::crypto::{DepositPublicInputs, WithdrawPublicInputs};
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::instructions::withdraw_masp::{
+    fee_payer_refund_hash, MAX_FEE_PAYER_REFUND_LAMPORTS,
+};
+use psol_privacy_v2::state::asset_vault::NATIVE_SOL_ASSET_ID;
+use psol_privacy_v2::state::{AssetVault, MerkleTreeV2, SpentNullifierV2};
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::signature::{Keypair, Signer};
+
+use common::pool::{assert_program_error, global_config, ix, Pool};
+use common::trapdoor::Trapdoor;
+use common::{fixture, proof_bytes};
+
+fn deposit_ix(
+    pool: &Pool,
+    trapdoor: &Trapdoor,
+    asset_vault: Pubkey,
+    asset_id: [u8; 32],
+    amount: u64,
+    commitment: [u8; 32],
+) -> Instruction {
+    let inputs = DepositPublicInputs::new(commitment, amount, asset_id).to_field_elements();
+    let authority = pool.authority.pubkey();
+    ix(
+        accounts::DepositMasp {
+            depositor: authority,
+            pool_config: pool.pool_config,
+            global_config: global_config(),
+            authority,
+            merkle_tree: pool.merkle_tree,
+            pending_buffer: pool.pending_buffer,
+            asset_vault,
+            vault_token_account: None,
+            user_token_account: None,
+            mint: None,
+            deposit_vk: pool.deposit_vk,
+            token_program: spl_token::id(),
+            system_program: system_program::ID,
+            instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+            proof_stats: None,
+            anonymity_stats: None,
+            commitment_reservation: pool.commitment_reservation(&commitment),
+            risk_tiers: None,
+        },
+        instruction::DepositMasp {
+            amount,
+            commitment,
+            asset_id,
+            proof_data: proof_bytes(&trapdoor.prove(&inputs, 1)),
+            encrypted_note: None,
+        },
+    )
+}
+
+/// `withdraw_masp` accounts paying `recipient` from `asset_vault` in lamports
+fn withdraw_accounts(
+    pool: &Pool,
+    relayer: Pubkey,
+    asset_vault: Pubkey,
+    recipient: Pubkey,
+    nullifier_hash: [u8; 32],
+) -> accounts::WithdrawMasp {
+    let (spent_nullifier, _) =
+        SpentNullifierV2::find_pda(&psol_privacy_v2::ID, &pool.pool_config, &nullifier_hash);
+    accounts::WithdrawMasp {
+        relayer,
+        pool_config: pool.pool_config,
+        global_config: global_config(),
+        merkle_tree: pool.merkle_tree,
+        vk_account: pool.withdraw_vk,
+        asset_vault,
+        vault_token_account: None,
+        recipient_token_account: None,
+        relayer_token_account: None,
+        spent_nullifier,
+        relayer_registry: pool.relayer_registry,
+        relayer_node: None,
+        yield_registry: None,
+        token_program: spl_token::id(),
+        system_program: system_program::ID,
+        instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+        proof_stats: None,
+        rebate_ledger: None,
+        migration_window: None,
+        deferred_events: None,
+        recipient_wallet: Some(recipient),
+        rent_reserve: None,
+        rent_advance: None,
+        mint: None,
+        anonymity_stats: None,
+        parameter_snapshot: None,
+        recipient_ata: None,
+        associated_token_program: None,
+        fee_payer: None,
+        treasury_token_account: None,
+        withdrawal_commit: None,
+        fee_quote: None,
+        risk_tiers: None,
+        withdrawal_window: None,
+    }
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_native_sol_round_trip() {
+    let f = fixture();
+    let deposit = Trapdoor::new(7 << 8, 3);
+    let withdraw = Trapdoor::new((7 << 8) | 1, 8);
+    let mut pool = Pool::start_with_vks(&f, 0, &deposit.vk(), &withdraw.vk(), |_| {}).await;
+    let authority = pool.authority.pubkey();
+    let (native_vault, _) = AssetVault::find_pda(
+        &psol_privacy_v2::ID,
+        &pool.pool_config,
+        &NATIVE_SOL_ASSET_ID,
+    );
+
+    pool.send_as_authority(ix(
+        accounts::RegisterNativeSolAsset {
+            authority,
+            pool_config: pool.pool_config,
+            asset_vault: native_vault,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterNativeSolAsset {},
+    ))
+    .await
+    .expect("register_native_sol_asset");
+    let vault_rent = pool
+        .ctx
+        .banks_client
+        .get_balance(native_vault)
+        .await
+        .unwrap();
+
+    // An SPL vault still needs its token accounts
+    let spl_without_tokens = deposit_ix(
+        &pool,
+        &deposit,
+        pool.asset_vault,
+        pool.asset_id,
+        1_000,
+        [7u8; 32],
+    );
+    assert_program_error(
+        pool.send_as_authority(spl_without_tokens).await,
+        PrivacyErrorV2::MissingAssetAccount,
+    );
+
+    let amount = 2 * LAMPORTS_PER_SOL;
+    let commitment = [9u8; 32];
+    pool.send_as_authority(deposit_ix(
+        &pool,
+        &deposit,
+        native_vault,
+        NATIVE_SOL_ASSET_ID,
+        amount,
+        commitment,
+    ))
+    .await
+    .expect("native deposit");
+    assert_eq!(
+        pool.ctx
+            .banks_client
+            .get_balance(native_vault)
+            .await
+            .unwrap(),
+        vault_rent + amount
+    );
+
+    pool.send_as_authority(pool.batch_ix(1, None))
+        .await
+        .expect("batch_process_deposits");
+    let tree: MerkleTreeV2 = pool.account(pool.merkle_tree).await;
+
+    let recipient = Pubkey::new_unique();
+    let relayer_fee = LAMPORTS_PER_SOL / 10;
+    let nullifier_hash = [5u8; 32];
+    let inputs = WithdrawPublicInputs::new(
+        tree.current_root,
+        nullifier_hash,
+        NATIVE_SOL_ASSET_ID,
+        recipient,
+        amount,
+        authority,
+        relayer_fee,
+        [0u8; 32],
+    )
+    .to_field_elements();
+    let (spent_nullifier, _) =
+        SpentNullifierV2::find_pda(&psol_privacy_v2::ID, &pool.pool_config, &nullifier_hash);
+    pool.send_as_authority(ix(
+        accounts::WithdrawMasp {
+            relayer: authority,
+            pool_config: pool.pool_config,
+            global_config: global_config(),
+            merkle_tree: pool.merkle_tree,
+            vk_account: pool.withdraw_vk,
+            asset_vault: native_vault,
+            vault_token_account: None,
+            recipient_token_account: None,
+            relayer_token_account: None,
+            spent_nullifier,
+            relayer_registry: pool.relayer_registry,
+            relayer_node: None,
+            yield_registry: None,
+            token_program: spl_token::id(),
+            system_program: system_program::ID,
+            instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+            proof_stats: None,
+            rebate_ledger: None,
+            migration_window: None,
+            deferred_events: None,
+            recipient_wallet: Some(recipient),
+            rent_reserve: None,
+            rent_advance: None,
+            mint: None,
+            anonymity_stats: None,
+            parameter_snapshot: None,
+            recipient_ata: None,
+            associated_token_program: None,
+            fee_payer: None,
+            treasury_token_account: None,
+            withdrawal_commit: None,
+            fee_quote: None,
+            risk_tiers: None,
+            withdrawal_window: None,
+        },
+        instruction::WithdrawMasp {
+            proof_data: proof_bytes(&withdraw.prove(&inputs, 2)),
+            merkle_root: tree.current_root,
+            nullifier_hash,
+            recipient,
+            amount,
+            asset_id: NATIVE_SOL_ASSET_ID,
+            relayer_fee,
+        },
+    ))
+    .await
+    .expect("native withdraw");
+
+    assert_eq!(
+        pool.ctx.banks_client.get_balance(recipient).await.unwrap(),
+        amount - relayer_fee
+    );
+    assert_eq!(
+        pool.ctx
+            .banks_client
+            .get_balance(native_vault)
+            .await
+            .unwrap(),
+        vault_rent
+    );
+    let vault: AssetVault = pool.account(native_vault).await;
+    assert!(vault.is_native_sol());
+    assert_eq!(vault.shielded_balance, 0);
+    assert_eq!(vault.total_deposited, amount);
+    assert_eq!(vault.total_withdrawn, amount);
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_fee_payer_refund() {
+    let f = fixture();
+    let deposit = Trapdoor::new(17 << 8, 3);
+    let withdraw = Trapdoor::new((17 << 8) | 1, 8);
+    let mut pool = Pool::start_with_vks(&f, 0, &deposit.vk(), &withdraw.vk(), |_| {}).await;
+    let authority = pool.authority.pubkey();
+    let (native_vault, _) = AssetVault::find_pda(
+        &psol_privacy_v2::ID,
+        &pool.pool_config,
+        &NATIVE_SOL_ASSET_ID,
+    );
+    pool.send_as_authority(ix(
+        accounts::RegisterNativeSolAsset {
+            authority,
+            pool_config: pool.pool_config,
+            asset_vault: native_vault,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterNativeSolAsset {},
+    ))
+    .await
+    .expect("register_native_sol_asset");
+
+    let amount = LAMPORTS_PER_SOL;
+    pool.send_as_authority(deposit_ix(
+        &pool,
+        &deposit,
+        native_vault,
+        NATIVE_SOL_ASSET_ID,
+        amount,
+        [3u8; 32],
+    ))
+    .await
+    .expect("native deposit");
+    pool.send_as_authority(pool.batch_ix(1, None))
+        .await
+        .expect("batch_process_deposits");
+    let tree: MerkleTreeV2 = pool.account(pool.merkle_tree).await;
+
+    // A fresh key holding the nullifier rent and a few transaction fees
+    let fee_payer = Keypair::new();
+    let nullifier_rent = pool
+        .ctx
+        .banks_client
+        .get_rent()
+        .await
+        .unwrap()
+        .minimum_balance(SpentNullifierV2::LEN);
+    let funding = nullifier_rent + 50_000;
+    pool.ctx.set_account(
+        &fee_payer.pubkey(),
+        &Account::new(funding, 0, &system_program::ID).into(),
+    );
+
+    let recipient = Pubkey::new_unique();
+    let nullifier_hash = [4u8; 32];
+    let refund = nullifier_rent + 5_000;
+    let prove = |asset_id, fee_payer_refund| {
+        let inputs = WithdrawPublicInputs::new(
+            tree.current_root,
+            nullifier_hash,
+            asset_id,
+            recipient,
+            amount,
+            fee_payer.pubkey(),
+            0,
+            fee_payer_refund_hash(fee_payer_refund),
+        )
+        .to_field_elements();
+        proof_bytes(&withdraw.prove(&inputs, 2))
+    };
+    let refund_ix = |pool: &Pool, asset_vault, asset_id, proof_data, fee_payer_refund| {
+        ix(
+            withdraw_accounts(
+                pool,
+                fee_payer.pubkey(),
+                asset_vault,
+                recipient,
+                nullifier_hash,
+            ),
+            instruction::WithdrawWithFeePayerRefund {
+                proof_data,
+                merkle_root: tree.current_root,
+                nullifier_hash,
+                recipient,
+                amount,
+                asset_id,
+                relayer_fee: 0,
+                fee_payer_refund,
+            },
+        )
+    };
+
+    // The refund is bound into the proof
+    assert_program_error(
+        pool.send(
+            refund_ix(
+                &pool,
+                native_vault,
+                NATIVE_SOL_ASSET_ID,
+                prove(NATIVE_SOL_ASSET_ID, 0),
+                refund,
+            ),
+            &[&fee_payer],
+        )
+        .await,
+        PrivacyErrorV2::InvalidProof,
+    );
+    let too_large = MAX_FEE_PAYER_REFUND_LAMPORTS + 1;
+    assert_program_error(
+        pool.send(
+            refund_ix(
+                &pool,
+                native_vault,
+                NATIVE_SOL_ASSET_ID,
+                prove(NATIVE_SOL_ASSET_ID, too_large),
+                too_large,
+            ),
+            &[&fee_payer],
+        )
+        .await,
+        PrivacyErrorV2::FeePayerRefundTooLarge,
+    );
+    // SPL vaults hold no lamports to refund
+    assert_program_error(
+        pool.send(
+            refund_ix(
+                &pool,
+                pool.asset_vault,
+                pool.asset_id,
+                prove(pool.asset_id, refund),
+                refund,
+            ),
+            &[&fee_payer],
+        )
+        .await,
+        PrivacyErrorV2::FeePayerRefundRequiresNativeSol,
+    );
+
+    let fee_payer_before = pool
+        .ctx
+        .banks_client
+        .get_balance(fee_payer.pubkey())
+        .await
+        .unwrap();
+    pool.send(
+        refund_ix(
+            &pool,
+            native_vault,
+            NATIVE_SOL_ASSET_ID,
+            prove(NATIVE_SOL_ASSET_ID, refund),
+            refund,
+        ),
+        &[&fee_payer],
+    )
+    .await
+    .expect("withdraw_with_fee_payer_refund");
+
+    assert_eq!(
+        pool.ctx.banks_client.get_balance(recipient).await.unwrap(),
+        amount - refund
+    );
+    // Refunded the 5000-lamport signature fee and the nullifier rent
+    assert_eq!(
+        pool.ctx
+            .banks_client
+            .get_balance(fee_payer.pubkey())
+            .await
+            .unwrap(),
+        fee_payer_before
+    );
+}
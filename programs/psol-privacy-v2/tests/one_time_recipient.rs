@@ -0,0 +1,90 @@
+//! `create_one_time_recipient` against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test one_time_recipient
+//!
+//! A sender derives a one-time owner from a recipient's meta address, the
+//! program creates the owner's associated token account and announces the
+//! ephemeral key, and the recipient recognises the owner from the emitted
+//! arguments. A second creation for the same owner must fail.
+//!
+//! Fixture: see tests/common/mod.rs.
+
+mod common;
+
+use anchor_lang::system_program;
+use anchor_spl::associated_token::{self, get_associated_token_address};
+use anchor_spl::token::{spl_token, TokenAccount};
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::stealth::{derive_one_time_address, OneTimeAddress, StealthKeys};
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::Signer;
+
+use common::fixture;
+use common::pool::{assert_program_error, ix, Pool};
+
+fn create_ix(pool: &Pool, address: &OneTimeAddress) -> Instruction {
+    ix(
+        accounts::CreateOneTimeRecipient {
+            payer: pool.authority.pubkey(),
+            pool_config: pool.pool_config,
+            asset_vault: pool.asset_vault,
+            mint: pool.mint,
+            one_time_owner: address.owner,
+            recipient_token_account: get_associated_token_address(&address.owner, &pool.mint),
+            token_program: spl_token::id(),
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::CreateOneTimeRecipient {
+            asset_id: pool.asset_id,
+            ephemeral_pubkey: address.ephemeral_pubkey,
+            view_tag: address.view_tag,
+        },
+    )
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_create_one_time_recipient() {
+    let f = fixture();
+    let mut pool = Pool::start(&f, 0, |_| {}).await;
+
+    let keys = StealthKeys::from_seed(b"recipient wallet");
+    let address = derive_one_time_address(&keys.meta_address(), b"withdrawal 1").unwrap();
+
+    pool.send_as_authority(create_ix(&pool, &address))
+        .await
+        .expect("create_one_time_recipient");
+
+    let token: TokenAccount = pool
+        .account(get_associated_token_address(&address.owner, &pool.mint))
+        .await;
+    assert_eq!(token.owner, address.owner);
+    assert_eq!(token.mint, pool.mint);
+    assert_eq!(token.amount, 0);
+    assert!(keys
+        .detect(&address.ephemeral_pubkey, address.view_tag, &address.owner)
+        .is_some());
+
+    // The account already exists: a reused one-time owner is refused
+    pool.refresh_blockhash().await;
+    assert!(pool
+        .send_as_authority(create_ix(&pool, &address))
+        .await
+        .is_err());
+
+    // A missing ephemeral key is not an announcement
+    let fresh = derive_one_time_address(&keys.meta_address(), b"withdrawal 2").unwrap();
+    let blank = OneTimeAddress {
+        ephemeral_pubkey: [0u8; 32],
+        ..fresh
+    };
+    assert_program_error(
+        pool.send_as_authority(create_ix(&pool, &blank)).await,
+        PrivacyErrorV2::InvalidOneTimeAddress,
+    );
+}
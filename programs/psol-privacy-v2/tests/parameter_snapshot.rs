@@ -0,0 +1,282 @@
+//! Parameter snapshot pins against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test parameter_snapshot
+//!
+//! The authority publishes an asset's fees, limits and flags under an
+//! increasing version. A withdrawal pinned to the version a client
+//! displayed goes through only while nothing changed against the user
+//! since, whether the change was published or not, and the pin is bound
+//! into the proof.
+//!
+//! Withdrawals use the native SOL vault so trapdoor VKs can prove them.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token;
+use psol_privacy_v2::crypto::{DepositPublicInputs, WithdrawPublicInputs};
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::instructions::withdraw_masp::parameter_pin_hash;
+use psol_privacy_v2::state::asset_vault::NATIVE_SOL_ASSET_ID;
+use psol_privacy_v2::state::{AssetVault, MerkleTreeV2, ParameterSnapshot, SpentNullifierV2};
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::signature::Signer;
+
+use common::pool::{assert_program_error, global_config, ix, Pool};
+use common::trapdoor::Trapdoor;
+use common::{fixture, proof_bytes};
+
+const AMOUNT: u64 = LAMPORTS_PER_SOL;
+
+/// Pinned withdrawal of the whole note, proved for `proved_version` but
+/// claiming `parameter_version`
+#[allow(clippy::too_many_arguments)]
+fn pinned_withdraw_ix(
+    pool: &Pool,
+    trapdoor: &Trapdoor,
+    asset_vault: Pubkey,
+    root: [u8; 32],
+    recipient: Pubkey,
+    parameter_snapshot: Option<Pubkey>,
+    proved_version: u64,
+    parameter_version: u64,
+) -> Instruction {
+    let relayer = pool.authority.pubkey();
+    let nullifier_hash = [5u8; 32];
+    let inputs = WithdrawPublicInputs::new(
+        root,
+        nullifier_hash,
+        NATIVE_SOL_ASSET_ID,
+        recipient,
+        AMOUNT,
+        relayer,
+        0,
+        parameter_pin_hash(proved_version),
+    )
+    .to_field_elements();
+    let (spent_nullifier, _) =
+        SpentNullifierV2::find_pda(&psol_privacy_v2::ID, &pool.pool_config, &nullifier_hash);
+    ix(
+        accounts::WithdrawMasp {
+            relayer,
+            pool_config: pool.pool_config,
+            global_config: global_config(),
+            merkle_tree: pool.merkle_tree,
+            vk_account: pool.withdraw_vk,
+            asset_vault,
+            vault_token_account: None,
+            recipient_token_account: None,
+            relayer_token_account: None,
+            spent_nullifier,
+            relayer_registry: pool.relayer_registry,
+            relayer_node: None,
+            yield_registry: None,
+            token_program: spl_token::id(),
+            system_program: system_program::ID,
+            instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+            proof_stats: None,
+            rebate_ledger: None,
+            migration_window: None,
+            deferred_events: None,
+            recipient_wallet: Some(recipient),
+            rent_reserve: None,
+            rent_advance: None,
+            mint: None,
+            anonymity_stats: None,
+            parameter_snapshot,
+            recipient_ata: None,
+            associated_token_program: None,
+            fee_payer: None,
+            treasury_token_account: None,
+            withdrawal_commit: None,
+            fee_quote: None,
+            risk_tiers: None,
+            withdrawal_window: None,
+        },
+        instruction::WithdrawWithParameterPin {
+            proof_data: proof_bytes(&trapdoor.prove(&inputs, proved_version + 2)),
+            merkle_root: root,
+            nullifier_hash,
+            recipient,
+            amount: AMOUNT,
+            asset_id: NATIVE_SOL_ASSET_ID,
+            relayer_fee: 0,
+            parameter_version,
+        },
+    )
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_pinned_withdrawal_rejects_adverse_changes() {
+    let f = fixture();
+    let deposit = Trapdoor::new(25 << 8, 3);
+    let withdraw = Trapdoor::new((25 << 8) | 1, 8);
+    let mut pool = Pool::start_with_vks(&f, 0, &deposit.vk(), &withdraw.vk(), |_| {}).await;
+    let authority = pool.authority.pubkey();
+    let (native_vault, _) = AssetVault::find_pda(
+        &psol_privacy_v2::ID,
+        &pool.pool_config,
+        &NATIVE_SOL_ASSET_ID,
+    );
+    pool.send_as_authority(ix(
+        accounts::RegisterNativeSolAsset {
+            authority,
+            pool_config: pool.pool_config,
+            asset_vault: native_vault,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterNativeSolAsset {},
+    ))
+    .await
+    .expect("register_native_sol_asset");
+
+    let commitment = [3u8; 32];
+    let inputs =
+        DepositPublicInputs::new(commitment, AMOUNT, NATIVE_SOL_ASSET_ID).to_field_elements();
+    pool.send_as_authority(ix(
+        accounts::DepositMasp {
+            depositor: authority,
+            pool_config: pool.pool_config,
+            global_config: global_config(),
+            authority,
+            merkle_tree: pool.merkle_tree,
+            pending_buffer: pool.pending_buffer,
+            asset_vault: native_vault,
+            vault_token_account: None,
+            user_token_account: None,
+            mint: None,
+            deposit_vk: pool.deposit_vk,
+            token_program: spl_token::id(),
+            system_program: system_program::ID,
+            instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+            proof_stats: None,
+            anonymity_stats: None,
+            commitment_reservation: pool.commitment_reservation(&commitment),
+            risk_tiers: None,
+        },
+        instruction::DepositMasp {
+            amount: AMOUNT,
+            commitment,
+            asset_id: NATIVE_SOL_ASSET_ID,
+            proof_data: proof_bytes(&deposit.prove(&inputs, 1)),
+            encrypted_note: None,
+        },
+    ))
+    .await
+    .expect("native deposit");
+    pool.send_as_authority(pool.batch_ix(1, None))
+        .await
+        .expect("batch_process_deposits");
+    let root = pool
+        .account::<MerkleTreeV2>(pool.merkle_tree)
+        .await
+        .current_root;
+
+    let (snapshot, _) = ParameterSnapshot::find_pda(
+        &psol_privacy_v2::ID,
+        &pool.pool_config,
+        &NATIVE_SOL_ASSET_ID,
+    );
+    let snapshot_accounts = |pool: &Pool| accounts::PublishParameterSnapshot {
+        authority,
+        pool_config: pool.pool_config,
+        asset_vault: native_vault,
+        relayer_registry: pool.relayer_registry,
+        parameter_snapshot: snapshot,
+    };
+    pool.send_as_authority(ix(
+        accounts::InitParameterSnapshot {
+            authority,
+            pool_config: pool.pool_config,
+            asset_vault: native_vault,
+            relayer_registry: pool.relayer_registry,
+            parameter_snapshot: snapshot,
+            system_program: system_program::ID,
+        },
+        instruction::InitParameterSnapshot {},
+    ))
+    .await
+    .expect("init_parameter_snapshot");
+    let published: ParameterSnapshot = pool.account(snapshot).await;
+    assert_eq!((published.version, published.adverse_version), (1, 1));
+    assert_eq!(published.parameters_hash, published.parameters.hash());
+
+    let recipient = Pubkey::new_unique();
+    let pinned = |pool: &Pool, snapshot, proved_version, parameter_version| {
+        pinned_withdraw_ix(
+            pool,
+            &withdraw,
+            native_vault,
+            root,
+            recipient,
+            snapshot,
+            proved_version,
+            parameter_version,
+        )
+    };
+
+    // A pin is meaningless without the snapshot it refers to
+    assert_program_error(
+        pool.send_as_authority(pinned(&pool, None, 1, 1)).await,
+        PrivacyErrorV2::ParameterSnapshotRequired,
+    );
+
+    // Raising the minimum withdrawal is adverse even before it is published
+    let raise_min_withdrawal = ix(
+        accounts::SetAssetMinWithdrawal {
+            authority,
+            pool_config: pool.pool_config,
+            asset_vault: native_vault,
+        },
+        instruction::SetAssetMinWithdrawal {
+            asset_id: NATIVE_SOL_ASSET_ID,
+            min_withdrawal: AMOUNT / 2,
+        },
+    );
+    pool.send_as_authority(raise_min_withdrawal)
+        .await
+        .expect("set_asset_min_withdrawal");
+    assert_program_error(
+        pool.send_as_authority(pinned(&pool, Some(snapshot), 1, 1))
+            .await,
+        PrivacyErrorV2::ParametersChangedSincePin,
+    );
+
+    // Publishing it invalidates the version 1 pin for good
+    pool.send_as_authority(ix(
+        snapshot_accounts(&pool),
+        instruction::PublishParameterSnapshot {},
+    ))
+    .await
+    .expect("publish_parameter_snapshot");
+    let published: ParameterSnapshot = pool.account(snapshot).await;
+    assert_eq!((published.version, published.adverse_version), (2, 2));
+    assert_eq!(published.parameters.min_withdrawal, AMOUNT / 2);
+    assert_program_error(
+        pool.send_as_authority(pinned(&pool, Some(snapshot), 1, 1))
+            .await,
+        PrivacyErrorV2::ParametersChangedSincePin,
+    );
+
+    // A relayer cannot re-pin a proof made for version 1
+    assert_program_error(
+        pool.send_as_authority(pinned(&pool, Some(snapshot), 1, 2))
+            .await,
+        PrivacyErrorV2::InvalidProof,
+    );
+
+    pool.send_as_authority(pinned(&pool, Some(snapshot), 2, 2))
+        .await
+        .expect("withdraw_with_parameter_pin");
+    assert_eq!(
+        pool.ctx.banks_client.get_balance(recipient).await.unwrap(),
+        AMOUNT
+    );
+}
@@ -0,0 +1,146 @@
+//! Pool directory against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test pool_directory
+//!
+//! The harness authority lists two pools; a client reads the directory's
+//! count and finds both entries at their index PDAs. A pool is listed once
+//! and only by its authority, and a refresh picks up a newly registered
+//! asset.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::system_program;
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::state::asset_vault::NATIVE_SOL_ASSET_ID;
+use psol_privacy_v2::state::{AssetVault, PoolConfigV2, PoolDirectory, PoolDirectoryEntry};
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer};
+
+use common::fixture;
+use common::pool::{assert_program_error, ix, Pool};
+
+fn directory() -> Pubkey {
+    PoolDirectory::find_pda(&psol_privacy_v2::ID).0
+}
+
+fn entry(index: u64) -> Pubkey {
+    PoolDirectoryEntry::find_pda(&psol_privacy_v2::ID, index).0
+}
+
+fn list_ix(authority: Pubkey, pool_config: Pubkey, index: u64) -> Instruction {
+    ix(
+        accounts::ListPool {
+            authority,
+            pool_config,
+            pool_directory: directory(),
+            directory_entry: entry(index),
+            system_program: system_program::ID,
+        },
+        instruction::ListPool {},
+    )
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_listed_pools_are_found_by_index() {
+    let f = fixture();
+    let mut pool = Pool::start(&f, 0, |_| {}).await;
+    let sibling = pool.start_sibling_pool(&f, 1).await;
+    let authority = pool.authority.pubkey();
+
+    pool.send_as_authority(ix(
+        accounts::InitPoolDirectory {
+            payer: authority,
+            pool_directory: directory(),
+            system_program: system_program::ID,
+        },
+        instruction::InitPoolDirectory {},
+    ))
+    .await
+    .expect("init_pool_directory");
+
+    pool.send_as_authority(list_ix(authority, pool.pool_config, 0))
+        .await
+        .expect("list_pool");
+    assert_program_error(
+        pool.send_as_authority(list_ix(authority, pool.pool_config, 1))
+            .await,
+        PrivacyErrorV2::PoolAlreadyListed,
+    );
+
+    // Only the pool's authority lists it
+    let (payer, outsider) = (pool.authority.insecure_clone(), Keypair::new());
+    assert_program_error(
+        pool.send(
+            list_ix(outsider.pubkey(), sibling.pool_config, 1),
+            &[&payer, &outsider],
+        )
+        .await,
+        PrivacyErrorV2::Unauthorized,
+    );
+    pool.send_as_authority(list_ix(authority, sibling.pool_config, 1))
+        .await
+        .expect("list_pool");
+
+    let header: PoolDirectory = pool.account(directory()).await;
+    assert_eq!(header.pool_count, 2);
+    let mut listed = Vec::new();
+    for index in 0..header.pool_count {
+        let listing: PoolDirectoryEntry = pool.account(entry(index)).await;
+        assert_eq!(listing.index, index);
+        assert_eq!(listing.authority, authority);
+        assert_eq!(listing.asset_count, 1);
+        assert_eq!(listing.tree_depth, f.tree_depth);
+        listed.push((listing.pool, listing.merkle_tree));
+    }
+    assert_eq!(
+        listed,
+        [
+            (pool.pool_config, pool.merkle_tree),
+            (sibling.pool_config, sibling.merkle_tree),
+        ]
+    );
+    let config: PoolConfigV2 = pool.account(pool.pool_config).await;
+    assert!(config.directory_listed);
+
+    // A second asset shows up after a refresh
+    let (native_vault, _) = AssetVault::find_pda(
+        &psol_privacy_v2::ID,
+        &pool.pool_config,
+        &NATIVE_SOL_ASSET_ID,
+    );
+    pool.send_as_authority(ix(
+        accounts::RegisterNativeSolAsset {
+            authority,
+            pool_config: pool.pool_config,
+            asset_vault: native_vault,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterNativeSolAsset {},
+    ))
+    .await
+    .expect("register_native_sol_asset");
+    let refresh = |pool_config| {
+        ix(
+            accounts::RefreshPoolListing {
+                pool_config,
+                directory_entry: entry(0),
+            },
+            instruction::RefreshPoolListing {},
+        )
+    };
+    assert_program_error(
+        pool.send_as_authority(refresh(sibling.pool_config)).await,
+        PrivacyErrorV2::InvalidPoolReference,
+    );
+    pool.send_as_authority(refresh(pool.pool_config))
+        .await
+        .expect("refresh_pool_listing");
+    let listing: PoolDirectoryEntry = pool.account(entry(0)).await;
+    assert_eq!(listing.asset_count, 2);
+}
@@ -0,0 +1,74 @@
+//! Several pools under one authority against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test pool_id
+//!
+//! The pool config PDA is seeded by the authority and a pool id, so the
+//! harness authority sets up a second pool next to its first one; the two
+//! share nothing and pausing one leaves the other running.
+
+mod common;
+
+use anchor_lang::system_program;
+use psol_privacy_v2::state::{MerkleTreeV2, PoolConfigV2};
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::signature::Signer;
+
+use common::fixture;
+use common::pool::{ix, Pool, ROOT_HISTORY_SIZE};
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_one_authority_runs_isolated_pools() {
+    let f = fixture();
+    let mut pool = Pool::start(&f, 0, |_| {}).await;
+    let sibling = pool.start_sibling_pool(&f, 1).await;
+
+    assert_ne!(sibling.pool_config, pool.pool_config);
+    assert_ne!(sibling.merkle_tree, pool.merkle_tree);
+    assert_ne!(sibling.asset_vault, pool.asset_vault);
+    let first: PoolConfigV2 = pool.account(pool.pool_config).await;
+    let second: PoolConfigV2 = pool.account(sibling.pool_config).await;
+    assert_eq!(first.pool_id, 0);
+    assert_eq!(second.pool_id, 1);
+    assert_eq!(second.authority, first.authority);
+    assert_eq!(second.merkle_tree, sibling.merkle_tree);
+    let tree: MerkleTreeV2 = pool.account(sibling.merkle_tree).await;
+    assert_eq!(tree.pool, sibling.pool_config);
+
+    // A pool id is taken once per authority
+    let authority = pool.authority.pubkey();
+    assert!(pool
+        .send_as_authority(ix(
+            accounts::InitializePoolV2 {
+                authority,
+                pool_config: sibling.pool_config,
+                merkle_tree: sibling.merkle_tree,
+                system_program: system_program::ID,
+            },
+            instruction::InitializePoolV2 {
+                tree_depth: f.tree_depth,
+                root_history_size: ROOT_HISTORY_SIZE,
+                compliance_recovery: false,
+                pool_id: 1,
+            },
+        ))
+        .await
+        .is_err());
+
+    pool.send_as_authority(ix(
+        accounts::PausePoolV2 {
+            authority,
+            pool_config: pool.pool_config,
+        },
+        instruction::PausePoolV2 {},
+    ))
+    .await
+    .expect("pause_pool_v2");
+    let first: PoolConfigV2 = pool.account(pool.pool_config).await;
+    let second: PoolConfigV2 = pool.account(sibling.pool_config).await;
+    assert!(first.is_paused);
+    assert!(!second.is_paused);
+}
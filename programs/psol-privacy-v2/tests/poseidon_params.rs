@@ -0,0 +1,175 @@
+//! Poseidon parameter set cross-check against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test poseidon_params
+//!
+//! The pool's tree reports the parameter set hash of the on-chain Poseidon
+//! constants. A circuit descriptor claiming any other parameter set is
+//! refused; one carrying the reported hash is recorded. A tree written
+//! before the hash existed is grown by `migrate_merkle_tree`, which records
+//! it.
+
+mod common;
+
+use anchor_lang::{system_program, AccountSerialize};
+use psol_privacy_v2::crypto::{poseidon_params_hash, POSEIDON_PARAMS_HASH};
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::state::{CircuitDescriptor, MerkleTreeV2};
+use psol_privacy_v2::{accounts, instruction, ProofType};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::Signer;
+
+use common::fixture;
+use common::pool::{assert_error_code, assert_program_error, ix, Pool};
+
+fn set_descriptor_ix(pool: &Pool, tree_depth: u8, poseidon_params_hash: [u8; 32]) -> Instruction {
+    let (circuit_descriptor, _) =
+        CircuitDescriptor::find_pda(&psol_privacy_v2::ID, &pool.pool_config, ProofType::Withdraw);
+    ix(
+        accounts::SetCircuitDescriptor {
+            authority: pool.authority.pubkey(),
+            pool_config: pool.pool_config,
+            merkle_tree: pool.merkle_tree,
+            circuit_descriptor,
+            system_program: system_program::ID,
+        },
+        instruction::SetCircuitDescriptor {
+            proof_type: ProofType::Withdraw,
+            circuit_version: 1,
+            public_input_layout_hash: [1u8; 32],
+            tree_depth,
+            poseidon_params_hash,
+        },
+    )
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_descriptor_must_match_tree_poseidon_params() {
+    let f = fixture();
+    let mut pool = Pool::start(&f, 0, |_| {}).await;
+
+    let tree: MerkleTreeV2 = pool.account(pool.merkle_tree).await;
+    assert_eq!(tree.poseidon_params_hash, POSEIDON_PARAMS_HASH);
+    assert_eq!(poseidon_params_hash(), POSEIDON_PARAMS_HASH);
+
+    let payer = pool.authority.insecure_clone();
+    let view = ix(
+        accounts::ViewPoseidonParams {
+            pool_config: pool.pool_config,
+            merkle_tree: pool.merkle_tree,
+        },
+        instruction::ViewPoseidonParams {},
+    );
+    let reported = pool
+        .send_for_return_data(view, &[&payer])
+        .await
+        .expect("view_poseidon_params");
+    assert_eq!(reported, POSEIDON_PARAMS_HASH);
+
+    // Another pool's tree does not vouch for this one
+    let sibling = pool.start_sibling_pool(&f, 1).await;
+    let foreign = ix(
+        accounts::ViewPoseidonParams {
+            pool_config: pool.pool_config,
+            merkle_tree: sibling.merkle_tree,
+        },
+        instruction::ViewPoseidonParams {},
+    );
+    assert_program_error(
+        pool.send(foreign, &[&payer]).await,
+        PrivacyErrorV2::InvalidMerkleTreePool,
+    );
+
+    // A circuit compiled against a different constant set
+    let mut other_params = POSEIDON_PARAMS_HASH;
+    other_params[0] ^= 1;
+    assert_program_error(
+        pool.send_as_authority(set_descriptor_ix(&pool, f.tree_depth, other_params))
+            .await,
+        PrivacyErrorV2::PoseidonParamsMismatch,
+    );
+
+    pool.send_as_authority(set_descriptor_ix(&pool, f.tree_depth, POSEIDON_PARAMS_HASH))
+        .await
+        .expect("set_circuit_descriptor");
+    let (address, _) =
+        CircuitDescriptor::find_pda(&psol_privacy_v2::ID, &pool.pool_config, ProofType::Withdraw);
+    let descriptor: CircuitDescriptor = pool.account(address).await;
+    assert_eq!(descriptor.poseidon_params_hash, POSEIDON_PARAMS_HASH);
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_legacy_merkle_tree_migrates() {
+    let f = fixture();
+    let mut pool = Pool::start(&f, 0, |_| {}).await;
+    let authority = pool.authority.pubkey();
+
+    // Put the tree back in the layout ending at `version`, funded for that
+    // size only
+    let mut tree: MerkleTreeV2 = pool.account(pool.merkle_tree).await;
+    tree.version = MerkleTreeV2::LEGACY_VERSION;
+    let legacy_len = MerkleTreeV2::legacy_space(tree.depth, tree.root_history_size);
+    let mut data = Vec::new();
+    tree.try_serialize(&mut data).unwrap();
+    data.truncate(legacy_len);
+    let rent = pool.ctx.banks_client.get_rent().await.unwrap();
+    let legacy = Account {
+        lamports: rent.minimum_balance(legacy_len),
+        data,
+        owner: psol_privacy_v2::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    pool.ctx.set_account(&pool.merkle_tree, &legacy.into());
+
+    let descriptor = set_descriptor_ix(&pool, tree.depth, POSEIDON_PARAMS_HASH);
+    assert_error_code(
+        pool.send_as_authority(descriptor.clone()).await,
+        anchor_lang::error::ErrorCode::AccountDidNotDeserialize.into(),
+        "AccountDidNotDeserialize",
+    );
+
+    let migrate = ix(
+        accounts::MigrateMerkleTree {
+            authority,
+            pool_config: pool.pool_config,
+            merkle_tree: pool.merkle_tree,
+            system_program: system_program::ID,
+        },
+        instruction::MigrateMerkleTree {},
+    );
+    pool.send_as_authority(migrate.clone())
+        .await
+        .expect("migrate_merkle_tree");
+
+    let account = pool
+        .ctx
+        .banks_client
+        .get_account(pool.merkle_tree)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        account.data.len(),
+        MerkleTreeV2::space(tree.depth, tree.root_history_size)
+    );
+    assert!(rent.is_exempt(account.lamports, account.data.len()));
+    let migrated: MerkleTreeV2 = pool.account(pool.merkle_tree).await;
+    assert_eq!(migrated.version, MerkleTreeV2::VERSION);
+    assert_eq!(migrated.poseidon_params_hash, POSEIDON_PARAMS_HASH);
+    assert_eq!(migrated.current_root, tree.current_root);
+
+    pool.send_as_authority(descriptor)
+        .await
+        .expect("set_circuit_descriptor");
+    pool.refresh_blockhash().await;
+    assert_program_error(
+        pool.send_as_authority(migrate).await,
+        PrivacyErrorV2::MerkleTreeAlreadyMigrated,
+    );
+}
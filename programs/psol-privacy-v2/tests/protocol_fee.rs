@@ -0,0 +1,380 @@
+//! Protocol fee and asset treasury against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test protocol_fee
+//!
+//! With a protocol fee set, a token withdrawal pays its share into the
+//! asset's treasury out of what the recipient receives, and cannot skip
+//! the treasury. The authority then disburses what the treasury collected
+//! through the pool treasury's timelock.
+//!
+//! A pool config created before the protocol fee is grown to the current
+//! layout by `migrate_pool_config`, after which the fee can be set.
+//!
+//! Deposits and withdrawals are proved with trapdoor VKs.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::system_program;
+use anchor_lang::{AccountSerialize, AnchorDeserialize};
+use anchor_spl::token::spl_token;
+use psol_privacy_v2::crypto::{DepositPublicInputs, WithdrawPublicInputs};
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::instructions::TreasuryBalance;
+use psol_privacy_v2::state::{
+    AssetVault, MerkleTreeV2, PoolConfigV2, SpentNullifierV2, Treasury,
+    TREASURY_DISBURSEMENT_DELAY_SECONDS,
+};
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::account::Account;
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::Signer;
+
+use common::pool::{
+    assert_error_code, assert_program_error, global_config, ix, token_account, Pool,
+};
+use common::trapdoor::Trapdoor;
+use common::{fixture, proof_bytes};
+
+const AMOUNT: u64 = 1_000_000;
+const FEE_BPS: u16 = 50;
+
+fn withdraw_ix(
+    pool: &Pool,
+    trapdoor: &Trapdoor,
+    root: [u8; 32],
+    recipient: Pubkey,
+    recipient_token: Pubkey,
+    relayer_token: Pubkey,
+    treasury: Option<Pubkey>,
+) -> Instruction {
+    let relayer = pool.authority.pubkey();
+    let nullifier_hash = [7u8; 32];
+    let inputs = WithdrawPublicInputs::new(
+        root,
+        nullifier_hash,
+        pool.asset_id,
+        recipient,
+        AMOUNT,
+        relayer,
+        0,
+        [0u8; 32],
+    )
+    .to_field_elements();
+    let (spent_nullifier, _) =
+        SpentNullifierV2::find_pda(&psol_privacy_v2::ID, &pool.pool_config, &nullifier_hash);
+    ix(
+        accounts::WithdrawMasp {
+            relayer,
+            pool_config: pool.pool_config,
+            global_config: global_config(),
+            merkle_tree: pool.merkle_tree,
+            vk_account: pool.withdraw_vk,
+            asset_vault: pool.asset_vault,
+            vault_token_account: Some(pool.vault_token),
+            recipient_token_account: Some(recipient_token),
+            relayer_token_account: Some(relayer_token),
+            spent_nullifier,
+            relayer_registry: pool.relayer_registry,
+            relayer_node: None,
+            yield_registry: None,
+            token_program: spl_token::id(),
+            system_program: system_program::ID,
+            instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+            proof_stats: None,
+            rebate_ledger: None,
+            migration_window: None,
+            deferred_events: None,
+            recipient_wallet: None,
+            rent_reserve: None,
+            rent_advance: None,
+            mint: None,
+            anonymity_stats: None,
+            parameter_snapshot: None,
+            recipient_ata: None,
+            associated_token_program: None,
+            fee_payer: None,
+            treasury_token_account: treasury,
+            withdrawal_commit: None,
+            fee_quote: None,
+            risk_tiers: None,
+            withdrawal_window: None,
+        },
+        instruction::WithdrawMasp {
+            proof_data: proof_bytes(&trapdoor.prove(&inputs, 2)),
+            merkle_root: root,
+            nullifier_hash,
+            recipient,
+            amount: AMOUNT,
+            asset_id: pool.asset_id,
+            relayer_fee: 0,
+        },
+    )
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_protocol_fee_collected_and_disbursed() {
+    let f = fixture();
+    let deposit = Trapdoor::new(31 << 8, 3);
+    let withdraw = Trapdoor::new((31 << 8) | 1, 8);
+    let mut pool = Pool::start_with_vks(&f, AMOUNT, &deposit.vk(), &withdraw.vk(), |_| {}).await;
+    let authority = pool.authority.pubkey();
+    let (treasury, _) = AssetVault::find_treasury_pda(&psol_privacy_v2::ID, &pool.asset_vault);
+
+    let set_fee = |pool: &Pool, fee_bps| {
+        ix(
+            accounts::SetProtocolFee {
+                authority,
+                pool_config: pool.pool_config,
+            },
+            instruction::SetProtocolFee { fee_bps },
+        )
+    };
+    assert_program_error(
+        pool.send_as_authority(set_fee(&pool, PoolConfigV2::MAX_PROTOCOL_FEE_BPS + 1))
+            .await,
+        PrivacyErrorV2::ProtocolFeeTooHigh,
+    );
+    pool.send_as_authority(set_fee(&pool, FEE_BPS))
+        .await
+        .expect("set_protocol_fee");
+    pool.send_as_authority(ix(
+        accounts::InitTreasury {
+            authority,
+            pool_config: pool.pool_config,
+            asset_vault: pool.asset_vault,
+            mint: pool.mint,
+            treasury_token_account: treasury,
+            token_program: spl_token::id(),
+            system_program: system_program::ID,
+        },
+        instruction::InitTreasury {
+            asset_id: pool.asset_id,
+        },
+    ))
+    .await
+    .expect("init_treasury");
+
+    let commitment = [3u8; 32];
+    let inputs = DepositPublicInputs::new(commitment, AMOUNT, pool.asset_id).to_field_elements();
+    pool.send_as_authority(pool.deposit_ix(AMOUNT, commitment, &deposit.prove(&inputs, 1)))
+        .await
+        .expect("deposit_masp");
+    pool.send_as_authority(pool.batch_ix(1, None))
+        .await
+        .expect("batch_process_deposits");
+    let root = pool
+        .account::<MerkleTreeV2>(pool.merkle_tree)
+        .await
+        .current_root;
+
+    let recipient = Pubkey::new_unique();
+    let (recipient_token, relayer_token) = (Pubkey::new_unique(), Pubkey::new_unique());
+    for (key, owner) in [(recipient_token, recipient), (relayer_token, authority)] {
+        pool.ctx
+            .set_account(&key, &token_account(pool.mint, owner, 0).into());
+    }
+
+    // The fee cannot be skipped by leaving out the treasury
+    assert_program_error(
+        pool.send_as_authority(withdraw_ix(
+            &pool,
+            &withdraw,
+            root,
+            recipient,
+            recipient_token,
+            relayer_token,
+            None,
+        ))
+        .await,
+        PrivacyErrorV2::TreasuryRequired,
+    );
+    pool.send_as_authority(withdraw_ix(
+        &pool,
+        &withdraw,
+        root,
+        recipient,
+        recipient_token,
+        relayer_token,
+        Some(treasury),
+    ))
+    .await
+    .expect("withdraw_masp");
+
+    let fee = AMOUNT * FEE_BPS as u64 / 10_000;
+    assert_eq!(pool.token_balance(recipient_token).await, AMOUNT - fee);
+    assert_eq!(pool.token_balance(treasury).await, fee);
+    assert_eq!(pool.token_balance(pool.vault_token).await, 0);
+
+    let destination = Pubkey::new_unique();
+    pool.ctx
+        .set_account(&destination, &token_account(pool.mint, authority, 0).into());
+    let (pool_treasury, _) = Treasury::find_pda(&psol_privacy_v2::ID, &pool.pool_config);
+    pool.send_as_authority(ix(
+        accounts::InitPoolTreasury {
+            authority,
+            pool_config: pool.pool_config,
+            treasury: pool_treasury,
+            system_program: system_program::ID,
+        },
+        instruction::InitPoolTreasury {},
+    ))
+    .await
+    .expect("init_pool_treasury");
+
+    let request = |pool: &Pool, amount| {
+        ix(
+            accounts::RequestTreasuryDisbursement {
+                authority,
+                pool_config: pool.pool_config,
+                treasury: pool_treasury,
+                asset_vault: pool.asset_vault,
+                treasury_token_account: Some(treasury),
+                destination,
+            },
+            instruction::RequestTreasuryDisbursement {
+                asset_id: pool.asset_id,
+                amount,
+            },
+        )
+    };
+    assert_program_error(
+        pool.send_as_authority(request(&pool, fee + 1)).await,
+        PrivacyErrorV2::InsufficientBalance,
+    );
+    pool.send_as_authority(request(&pool, fee))
+        .await
+        .expect("request_treasury_disbursement");
+
+    let view = ix(
+        accounts::ViewTreasury {
+            pool_config: pool.pool_config,
+            treasury: pool_treasury,
+            asset_vault: pool.asset_vault,
+            treasury_token_account: Some(treasury),
+        },
+        instruction::ViewTreasury {
+            asset_id: pool.asset_id,
+        },
+    );
+    let signer = pool.authority.insecure_clone();
+    let mut data = pool
+        .send_for_return_data(view, &[&signer])
+        .await
+        .expect("view_treasury");
+    // The runtime strips trailing zero bytes from return data
+    data.resize(32, 0);
+    let balance = TreasuryBalance::deserialize(&mut &data[..]).unwrap();
+    assert_eq!(balance.available, 0);
+    assert_eq!(balance.pending_amount, fee);
+
+    let execute = ix(
+        accounts::ExecuteTreasuryDisbursement {
+            authority,
+            pool_config: pool.pool_config,
+            treasury: pool_treasury,
+            asset_vault: pool.asset_vault,
+            destination,
+            treasury_token_account: Some(treasury),
+            mint: Some(pool.mint),
+            token_program: Some(spl_token::id()),
+        },
+        instruction::ExecuteTreasuryDisbursement {
+            asset_id: pool.asset_id,
+        },
+    );
+    assert_program_error(
+        pool.send_as_authority(execute.clone()).await,
+        PrivacyErrorV2::TreasuryDisbursementTimelocked,
+    );
+
+    let mut clock: Clock = pool.ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += TREASURY_DISBURSEMENT_DELAY_SECONDS;
+    pool.ctx.set_sysvar(&clock);
+    pool.refresh_blockhash().await;
+
+    pool.send_as_authority(execute)
+        .await
+        .expect("execute_treasury_disbursement");
+    assert_eq!(pool.token_balance(destination).await, fee);
+    assert_eq!(pool.token_balance(treasury).await, 0);
+    let pool_treasury = pool.account::<Treasury>(pool_treasury).await;
+    assert!(!pool_treasury.has_pending);
+    assert_eq!(pool_treasury.disbursement_count, 1);
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_legacy_pool_config_migrates() {
+    let f = fixture();
+    let mut pool = Pool::start(&f, 0, |_| {}).await;
+    let authority = pool.authority.pubkey();
+
+    // Put the config back in the pre-fee layout, funded for that size only
+    let mut config: PoolConfigV2 = pool.account(pool.pool_config).await;
+    config.version = PoolConfigV2::LEGACY_VERSION;
+    let mut data = Vec::new();
+    config.try_serialize(&mut data).unwrap();
+    data.truncate(PoolConfigV2::LEGACY_LEN);
+    let rent = pool.ctx.banks_client.get_rent().await.unwrap();
+    let legacy = Account {
+        lamports: rent.minimum_balance(PoolConfigV2::LEGACY_LEN),
+        data,
+        owner: psol_privacy_v2::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    pool.ctx.set_account(&pool.pool_config, &legacy.into());
+
+    let set_fee = ix(
+        accounts::SetProtocolFee {
+            authority,
+            pool_config: pool.pool_config,
+        },
+        instruction::SetProtocolFee { fee_bps: FEE_BPS },
+    );
+    assert_error_code(
+        pool.send_as_authority(set_fee.clone()).await,
+        anchor_lang::error::ErrorCode::AccountDidNotDeserialize.into(),
+        "AccountDidNotDeserialize",
+    );
+
+    let migrate = ix(
+        accounts::MigratePoolConfig {
+            authority,
+            pool_config: pool.pool_config,
+            system_program: system_program::ID,
+        },
+        instruction::MigratePoolConfig {},
+    );
+    pool.send_as_authority(migrate.clone())
+        .await
+        .expect("migrate_pool_config");
+
+    let account = pool
+        .ctx
+        .banks_client
+        .get_account(pool.pool_config)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), PoolConfigV2::LEN);
+    assert!(rent.is_exempt(account.lamports, account.data.len()));
+    let migrated: PoolConfigV2 = pool.account(pool.pool_config).await;
+    assert_eq!(migrated.version, PoolConfigV2::VERSION);
+    assert_eq!(migrated.authority, config.authority);
+    assert_eq!(migrated.crank_bounty_lamports, config.crank_bounty_lamports);
+    assert_eq!(migrated.protocol_fee_bps, 0);
+
+    pool.send_as_authority(set_fee).await.expect("set_protocol_fee");
+    pool.refresh_blockhash().await;
+    assert_program_error(
+        pool.send_as_authority(migrate).await,
+        PrivacyErrorV2::PoolConfigAlreadyMigrated,
+    );
+}
@@ -0,0 +1,170 @@
+//! Withdrawals to a recipient without a token account, against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test recipient_ata
+//!
+//! A fresh wallet has no associated token account for the pool's mint.
+//! `withdraw_masp` creates it inside the withdrawal, with the relayer paying
+//! the rent, and pays the recipient into it. Only the recipient's canonical
+//! associated token account is accepted.
+//!
+//! Deposits and withdrawals are proved with trapdoor VKs.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::{self, get_associated_token_address};
+use anchor_spl::token::spl_token;
+use psol_privacy_v2::crypto::{DepositPublicInputs, WithdrawPublicInputs};
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::instructions::withdraw_masp::fee_payer_refund_hash;
+use psol_privacy_v2::state::{MerkleTreeV2, SpentNullifierV2};
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::Signer;
+
+use common::pool::{assert_program_error, global_config, ix, token_account, Pool};
+use common::trapdoor::Trapdoor;
+use common::{fixture, proof_bytes};
+
+const AMOUNT: u64 = 1_000_000;
+
+fn withdraw_to_ata_ix(
+    pool: &Pool,
+    trapdoor: &Trapdoor,
+    root: [u8; 32],
+    recipient: Pubkey,
+    recipient_ata: Pubkey,
+    relayer_token: Pubkey,
+) -> Instruction {
+    let relayer = pool.authority.pubkey();
+    let nullifier_hash = [7u8; 32];
+    let inputs = WithdrawPublicInputs::new(
+        root,
+        nullifier_hash,
+        pool.asset_id,
+        recipient,
+        AMOUNT,
+        relayer,
+        0,
+        fee_payer_refund_hash(0),
+    )
+    .to_field_elements();
+    let (spent_nullifier, _) =
+        SpentNullifierV2::find_pda(&psol_privacy_v2::ID, &pool.pool_config, &nullifier_hash);
+    ix(
+        accounts::WithdrawMasp {
+            relayer,
+            pool_config: pool.pool_config,
+            global_config: global_config(),
+            merkle_tree: pool.merkle_tree,
+            vk_account: pool.withdraw_vk,
+            asset_vault: pool.asset_vault,
+            vault_token_account: Some(pool.vault_token),
+            recipient_token_account: None,
+            relayer_token_account: Some(relayer_token),
+            spent_nullifier,
+            relayer_registry: pool.relayer_registry,
+            relayer_node: None,
+            yield_registry: None,
+            token_program: spl_token::id(),
+            system_program: system_program::ID,
+            instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+            proof_stats: None,
+            rebate_ledger: None,
+            migration_window: None,
+            deferred_events: None,
+            recipient_wallet: Some(recipient),
+            rent_reserve: None,
+            rent_advance: None,
+            mint: Some(pool.mint),
+            anonymity_stats: None,
+            parameter_snapshot: None,
+            recipient_ata: Some(recipient_ata),
+            associated_token_program: Some(associated_token::ID),
+            fee_payer: None,
+            treasury_token_account: None,
+            withdrawal_commit: None,
+            fee_quote: None,
+            risk_tiers: None,
+            withdrawal_window: None,
+        },
+        instruction::WithdrawMasp {
+            proof_data: proof_bytes(&trapdoor.prove(&inputs, 2)),
+            merkle_root: root,
+            nullifier_hash,
+            recipient,
+            amount: AMOUNT,
+            asset_id: pool.asset_id,
+            relayer_fee: 0,
+        },
+    )
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_withdraw_creates_recipient_ata() {
+    let f = fixture();
+    let deposit = Trapdoor::new(27 << 8, 3);
+    let withdraw = Trapdoor::new((27 << 8) | 1, 8);
+    let mut pool = Pool::start_with_vks(&f, AMOUNT, &deposit.vk(), &withdraw.vk(), |_| {}).await;
+    let relayer_token = Pubkey::new_unique();
+    pool.ctx.set_account(
+        &relayer_token,
+        &token_account(pool.mint, pool.authority.pubkey(), 0).into(),
+    );
+
+    let commitment = [3u8; 32];
+    let inputs = DepositPublicInputs::new(commitment, AMOUNT, pool.asset_id).to_field_elements();
+    pool.send_as_authority(pool.deposit_ix(AMOUNT, commitment, &deposit.prove(&inputs, 1)))
+        .await
+        .expect("deposit_masp");
+    pool.send_as_authority(pool.batch_ix(1, None))
+        .await
+        .expect("batch_process_deposits");
+    let root = pool
+        .account::<MerkleTreeV2>(pool.merkle_tree)
+        .await
+        .current_root;
+
+    let recipient = Pubkey::new_unique();
+    let ata = get_associated_token_address(&recipient, &pool.mint);
+    assert!(pool
+        .ctx
+        .banks_client
+        .get_account(ata)
+        .await
+        .unwrap()
+        .is_none());
+
+    // Only the recipient's own associated token account is accepted
+    let elsewhere = get_associated_token_address(&Pubkey::new_unique(), &pool.mint);
+    assert_program_error(
+        pool.send_as_authority(withdraw_to_ata_ix(
+            &pool,
+            &withdraw,
+            root,
+            recipient,
+            elsewhere,
+            relayer_token,
+        ))
+        .await,
+        PrivacyErrorV2::RecipientMismatch,
+    );
+
+    pool.send_as_authority(withdraw_to_ata_ix(
+        &pool,
+        &withdraw,
+        root,
+        recipient,
+        ata,
+        relayer_token,
+    ))
+    .await
+    .expect("withdraw_masp to a fresh recipient");
+    assert_eq!(pool.token_balance(ata).await, AMOUNT);
+    assert_eq!(pool.token_balance(pool.vault_token).await, 0);
+}
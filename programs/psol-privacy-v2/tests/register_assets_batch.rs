@@ -0,0 +1,140 @@
+//! `register_assets_batch` against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test register_assets_batch
+//!
+//! Several mints are registered in one call, each getting the same vault
+//! and vault token account `register_asset` would create. Batches that are
+//! too large, repeat an asset or pass the wrong vault address fail as a
+//! whole.
+//!
+//! Fixture: see tests/common/mod.rs.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::system_program;
+use anchor_spl::token::{spl_token, TokenAccount};
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::instructions::register_assets_batch::MAX_ASSETS_PER_BATCH;
+use psol_privacy_v2::state::asset_vault::compute_asset_id;
+use psol_privacy_v2::state::{AssetVault, PoolConfigV2};
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::signature::Signer;
+
+use common::fixture;
+use common::pool::{assert_error_code, assert_program_error, ix, mint_account, Pool};
+
+/// Vault and vault token addresses for `mint` in `pool`
+fn vault_keys(pool: &Pool, mint: &Pubkey) -> (Pubkey, Pubkey) {
+    let (vault, _) = AssetVault::find_pda(
+        &psol_privacy_v2::ID,
+        &pool.pool_config,
+        &compute_asset_id(mint),
+    );
+    let (token, _) =
+        Pubkey::find_program_address(&[b"vault_token", vault.as_ref()], &psol_privacy_v2::ID);
+    (vault, token)
+}
+
+fn batch_ix(pool: &Pool, mints: &[Pubkey]) -> Instruction {
+    let mut ix = ix(
+        accounts::RegisterAssetsBatch {
+            authority: pool.authority.pubkey(),
+            pool_config: pool.pool_config,
+            token_program: spl_token::id(),
+            system_program: system_program::ID,
+        },
+        instruction::RegisterAssetsBatch {
+            asset_ids: mints.iter().map(compute_asset_id).collect(),
+        },
+    );
+    for mint in mints {
+        let (vault, token) = vault_keys(pool, mint);
+        ix.accounts.extend([
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(token, false),
+        ]);
+    }
+    ix
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_register_assets_batch() {
+    let f = fixture();
+    let mints: Vec<Pubkey> = (0..MAX_ASSETS_PER_BATCH + 1)
+        .map(|_| Pubkey::new_unique())
+        .collect();
+    let mut pool = Pool::start(&f, 0, |pt| {
+        for mint in &mints {
+            pt.add_account(*mint, mint_account(0));
+        }
+    })
+    .await;
+    let before = pool
+        .account::<PoolConfigV2>(pool.pool_config)
+        .await
+        .registered_asset_count;
+
+    // More than the batch limit
+    assert_program_error(
+        pool.send_as_authority(batch_ix(&pool, &mints)).await,
+        PrivacyErrorV2::InvalidBatchSize,
+    );
+
+    // An asset id that is not the mint's
+    let mut mismatched = batch_ix(&pool, &mints[..2]);
+    mismatched.accounts.swap(4, 7);
+    assert_program_error(
+        pool.send_as_authority(mismatched).await,
+        PrivacyErrorV2::InvalidAssetId,
+    );
+
+    // A vault that is not the asset's PDA
+    let mut wrong_vault = batch_ix(&pool, &mints[..2]);
+    wrong_vault.accounts[5].pubkey = pool.asset_vault;
+    assert_error_code(
+        pool.send_as_authority(wrong_vault).await,
+        ErrorCode::ConstraintSeeds.into(),
+        "ConstraintSeeds",
+    );
+
+    let batch = &mints[..MAX_ASSETS_PER_BATCH];
+    pool.send_as_authority(batch_ix(&pool, batch))
+        .await
+        .expect("register_assets_batch");
+
+    for (i, mint) in batch.iter().enumerate() {
+        let (vault_key, token_key) = vault_keys(&pool, mint);
+        let vault = pool.account::<AssetVault>(vault_key).await;
+        assert_eq!(vault.pool, pool.pool_config);
+        assert_eq!(vault.mint, *mint);
+        assert_eq!(vault.asset_id, compute_asset_id(mint));
+        assert_eq!(vault.token_account, token_key);
+        assert_eq!(vault.asset_index, before + i as u16);
+        assert!(vault.is_active);
+
+        let token = pool.account::<TokenAccount>(token_key).await;
+        assert_eq!(token.mint, *mint);
+        assert_eq!(token.owner, vault_key);
+    }
+    assert_eq!(
+        pool.account::<PoolConfigV2>(pool.pool_config)
+            .await
+            .registered_asset_count,
+        before + MAX_ASSETS_PER_BATCH as u16
+    );
+
+    // Registering an asset again fails, even alongside a new one
+    let again = [mints[MAX_ASSETS_PER_BATCH], mints[0]];
+    assert_program_error(
+        pool.send_as_authority(batch_ix(&pool, &again)).await,
+        PrivacyErrorV2::AlreadyInitialized,
+    );
+}
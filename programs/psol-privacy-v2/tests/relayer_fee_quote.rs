@@ -0,0 +1,338 @@
+//! Relayer fee quotes against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test relayer_fee_quote
+//!
+//! A registered relayer publishes a fee quote; a withdrawal submitted with
+//! the quote is refused above the quoted fee or once the quote lapses, and
+//! goes through at the quoted fee.
+//!
+//! Withdrawals use the native SOL vault so trapdoor VKs can prove them.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token;
+use psol_privacy_v2::crypto::{DepositPublicInputs, WithdrawPublicInputs};
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::state::asset_vault::NATIVE_SOL_ASSET_ID;
+use psol_privacy_v2::state::{
+    AssetVault, MerkleTreeV2, RelayerFeeQuote, RelayerIndexShard, RelayerMetadata, RelayerNode,
+    RelayerTombstone, SpentNullifierV2,
+};
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::signature::{Keypair, Signer};
+
+use common::pool::{assert_program_error, global_config, ix, Pool};
+use common::trapdoor::Trapdoor;
+use common::{fixture, proof_bytes};
+
+const AMOUNT: u64 = LAMPORTS_PER_SOL;
+const NULLIFIER_HASH: [u8; 32] = [5u8; 32];
+const FLAT_FEE: u64 = 5_000;
+const FEE_BPS: u16 = 100;
+/// `FLAT_FEE` plus `FEE_BPS` of `AMOUNT`
+const QUOTED_FEE: u64 = FLAT_FEE + AMOUNT / 100;
+const QUOTE_SLOTS: u64 = 100;
+
+fn withdraw_ix(
+    pool: &Pool,
+    trapdoor: &Trapdoor,
+    asset_vault: Pubkey,
+    root: [u8; 32],
+    relayer_fee: u64,
+    fee_quote: Pubkey,
+) -> Instruction {
+    let relayer = pool.authority.pubkey();
+    let recipient = Pubkey::new_from_array([9u8; 32]);
+    let inputs = WithdrawPublicInputs::new(
+        root,
+        NULLIFIER_HASH,
+        NATIVE_SOL_ASSET_ID,
+        recipient,
+        AMOUNT,
+        relayer,
+        relayer_fee,
+        [0u8; 32],
+    )
+    .to_field_elements();
+    let (spent_nullifier, _) =
+        SpentNullifierV2::find_pda(&psol_privacy_v2::ID, &pool.pool_config, &NULLIFIER_HASH);
+    ix(
+        accounts::WithdrawMasp {
+            relayer,
+            pool_config: pool.pool_config,
+            global_config: global_config(),
+            merkle_tree: pool.merkle_tree,
+            vk_account: pool.withdraw_vk,
+            asset_vault,
+            vault_token_account: None,
+            recipient_token_account: None,
+            relayer_token_account: None,
+            spent_nullifier,
+            relayer_registry: pool.relayer_registry,
+            relayer_node: None,
+            yield_registry: None,
+            token_program: spl_token::id(),
+            system_program: system_program::ID,
+            instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+            proof_stats: None,
+            rebate_ledger: None,
+            migration_window: None,
+            deferred_events: None,
+            recipient_wallet: Some(recipient),
+            rent_reserve: None,
+            rent_advance: None,
+            mint: None,
+            anonymity_stats: None,
+            parameter_snapshot: None,
+            recipient_ata: None,
+            associated_token_program: None,
+            fee_payer: None,
+            treasury_token_account: None,
+            withdrawal_commit: None,
+            fee_quote: Some(fee_quote),
+            risk_tiers: None,
+            withdrawal_window: None,
+        },
+        instruction::WithdrawMasp {
+            proof_data: proof_bytes(&trapdoor.prove(&inputs, relayer_fee)),
+            merkle_root: root,
+            nullifier_hash: NULLIFIER_HASH,
+            recipient,
+            amount: AMOUNT,
+            asset_id: NATIVE_SOL_ASSET_ID,
+            relayer_fee,
+        },
+    )
+}
+
+/// Register the pool authority as a relayer; returns its node
+async fn register_relayer(pool: &mut Pool) -> Pubkey {
+    let operator = pool.authority.pubkey();
+    let (relayer_index_shard, _) =
+        RelayerIndexShard::find_pda(&psol_privacy_v2::ID, &pool.relayer_registry, 0);
+    let (relayer_node, _) =
+        RelayerNode::find_pda(&psol_privacy_v2::ID, &pool.relayer_registry, &operator);
+    let (relayer_tombstone, _) =
+        RelayerTombstone::find_pda(&psol_privacy_v2::ID, &pool.relayer_registry, &operator);
+    pool.send_as_authority(ix(
+        accounts::InitRelayerIndexShard {
+            payer: operator,
+            pool_config: pool.pool_config,
+            relayer_registry: pool.relayer_registry,
+            relayer_index_shard,
+            system_program: system_program::ID,
+        },
+        instruction::InitRelayerIndexShard { shard_index: 0 },
+    ))
+    .await
+    .expect("init_relayer_index_shard");
+    pool.send_as_authority(ix(
+        accounts::RegisterRelayer {
+            operator,
+            pool_config: pool.pool_config,
+            relayer_registry: pool.relayer_registry,
+            relayer_node,
+            system_program: system_program::ID,
+            relayer_index_shard,
+            relayer_tombstone,
+        },
+        instruction::RegisterRelayer {
+            fee_bps: FEE_BPS,
+            metadata_uri: String::new(),
+            metadata: RelayerMetadata::default(),
+        },
+    ))
+    .await
+    .expect("register_relayer");
+    relayer_node
+}
+
+/// Register the native SOL asset and insert one note; returns the vault
+/// and the root containing the note
+async fn native_note(pool: &mut Pool, deposit: &Trapdoor) -> (Pubkey, [u8; 32]) {
+    let authority = pool.authority.pubkey();
+    let (native_vault, _) = AssetVault::find_pda(
+        &psol_privacy_v2::ID,
+        &pool.pool_config,
+        &NATIVE_SOL_ASSET_ID,
+    );
+    pool.send_as_authority(ix(
+        accounts::RegisterNativeSolAsset {
+            authority,
+            pool_config: pool.pool_config,
+            asset_vault: native_vault,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterNativeSolAsset {},
+    ))
+    .await
+    .expect("register_native_sol_asset");
+
+    let commitment = [3u8; 32];
+    let inputs =
+        DepositPublicInputs::new(commitment, AMOUNT, NATIVE_SOL_ASSET_ID).to_field_elements();
+    pool.send_as_authority(ix(
+        accounts::DepositMasp {
+            depositor: authority,
+            pool_config: pool.pool_config,
+            global_config: global_config(),
+            authority,
+            merkle_tree: pool.merkle_tree,
+            pending_buffer: pool.pending_buffer,
+            asset_vault: native_vault,
+            vault_token_account: None,
+            user_token_account: None,
+            mint: None,
+            deposit_vk: pool.deposit_vk,
+            token_program: spl_token::id(),
+            system_program: system_program::ID,
+            instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+            proof_stats: None,
+            anonymity_stats: None,
+            commitment_reservation: pool.commitment_reservation(&commitment),
+            risk_tiers: None,
+        },
+        instruction::DepositMasp {
+            amount: AMOUNT,
+            commitment,
+            asset_id: NATIVE_SOL_ASSET_ID,
+            proof_data: proof_bytes(&deposit.prove(&inputs, 1)),
+            encrypted_note: None,
+        },
+    ))
+    .await
+    .expect("native deposit");
+    pool.send_as_authority(pool.batch_ix(1, None))
+        .await
+        .expect("batch_process_deposits");
+    let root = pool
+        .account::<MerkleTreeV2>(pool.merkle_tree)
+        .await
+        .current_root;
+    (native_vault, root)
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_withdrawal_fee_bounded_by_quote() {
+    let f = fixture();
+    let deposit = Trapdoor::new(42 << 8, 3);
+    let withdraw = Trapdoor::new((42 << 8) | 1, 8);
+    let mut pool = Pool::start_with_vks(&f, 0, &deposit.vk(), &withdraw.vk(), |_| {}).await;
+    let operator = pool.authority.pubkey();
+    let relayer_node = register_relayer(&mut pool).await;
+    let (native_vault, root) = native_note(&mut pool, &deposit).await;
+
+    let (fee_quote, _) = RelayerFeeQuote::find_pda(&psol_privacy_v2::ID, &relayer_node);
+    let clock: Clock = pool.ctx.banks_client.get_sysvar().await.unwrap();
+    let valid_until_slot = clock.slot + QUOTE_SLOTS;
+    pool.send_as_authority(ix(
+        accounts::InitRelayerFeeQuote {
+            operator,
+            pool_config: pool.pool_config,
+            relayer_registry: pool.relayer_registry,
+            relayer_node,
+            fee_quote,
+            system_program: system_program::ID,
+        },
+        instruction::InitRelayerFeeQuote {
+            flat_fee: FLAT_FEE,
+            fee_bps: FEE_BPS,
+            valid_until_slot,
+        },
+    ))
+    .await
+    .expect("init_relayer_fee_quote");
+    let quote: RelayerFeeQuote = pool.account(fee_quote).await;
+    assert_eq!(quote.operator, operator);
+    assert_eq!(quote.max_fee(AMOUNT).unwrap(), QUOTED_FEE);
+
+    // Only the operator updates its quote
+    let outsider = Keypair::new();
+    assert!(pool
+        .send(
+            ix(
+                accounts::UpdateRelayerFeeQuote {
+                    operator: outsider.pubkey(),
+                    pool_config: pool.pool_config,
+                    relayer_registry: pool.relayer_registry,
+                    relayer_node,
+                    fee_quote,
+                },
+                instruction::UpdateRelayerFeeQuote {
+                    flat_fee: u64::MAX / 2,
+                    fee_bps: FEE_BPS,
+                    valid_until_slot,
+                },
+            ),
+            &[&pool.authority.insecure_clone(), &outsider],
+        )
+        .await
+        .is_err());
+
+    assert_program_error(
+        pool.send_as_authority(withdraw_ix(
+            &pool,
+            &withdraw,
+            native_vault,
+            root,
+            QUOTED_FEE + 1,
+            fee_quote,
+        ))
+        .await,
+        PrivacyErrorV2::RelayerFeeAboveQuote,
+    );
+
+    // A lapsed quote backs no withdrawal
+    let mut later = clock.clone();
+    later.slot = valid_until_slot + 1;
+    pool.ctx.set_sysvar(&later);
+    pool.refresh_blockhash().await;
+    assert_program_error(
+        pool.send_as_authority(withdraw_ix(
+            &pool,
+            &withdraw,
+            native_vault,
+            root,
+            QUOTED_FEE,
+            fee_quote,
+        ))
+        .await,
+        PrivacyErrorV2::FeeQuoteExpired,
+    );
+
+    pool.send_as_authority(ix(
+        accounts::UpdateRelayerFeeQuote {
+            operator,
+            pool_config: pool.pool_config,
+            relayer_registry: pool.relayer_registry,
+            relayer_node,
+            fee_quote,
+        },
+        instruction::UpdateRelayerFeeQuote {
+            flat_fee: FLAT_FEE,
+            fee_bps: FEE_BPS,
+            valid_until_slot: later.slot + QUOTE_SLOTS,
+        },
+    ))
+    .await
+    .expect("update_relayer_fee_quote");
+    pool.send_as_authority(withdraw_ix(
+        &pool,
+        &withdraw,
+        native_vault,
+        root,
+        QUOTED_FEE,
+        fee_quote,
+    ))
+    .await
+    .expect("withdraw_masp at the quoted fee");
+}
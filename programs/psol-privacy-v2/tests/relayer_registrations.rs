@@ -0,0 +1,244 @@
+//! Opening and closing relayer registrations against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test relayer_registrations
+//!
+//! The authority closes the registry to new relayers and opens it again;
+//! nobody else can.
+//!
+//! A node registered before the structured metadata, with a long metadata
+//! URI, is re-laid out by `migrate_relayer_node` and takes the index
+//! position the registry counted for it.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{system_program, AccountSerialize, AnchorSerialize, Discriminator};
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::state::{
+    LegacyRelayerNode, RelayerIndexShard, RelayerMetadata, RelayerNode, RelayerRegistry,
+    RelayerTombstone, MAX_RELAYER_METADATA_URI_LEN,
+};
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer};
+
+use common::fixture;
+use common::pool::{assert_error_code, assert_program_error, ix, Pool};
+
+fn set_open_ix(pool: &Pool, authority: Pubkey, open: bool) -> Instruction {
+    ix(
+        accounts::SetRelayerRegistrationsOpen {
+            authority,
+            pool_config: pool.pool_config,
+            relayer_registry: pool.relayer_registry,
+        },
+        instruction::SetRelayerRegistrationsOpen { open },
+    )
+}
+
+fn init_shard_ix(pool: &Pool, payer: Pubkey, relayer_index_shard: Pubkey) -> Instruction {
+    ix(
+        accounts::InitRelayerIndexShard {
+            payer,
+            pool_config: pool.pool_config,
+            relayer_registry: pool.relayer_registry,
+            relayer_index_shard,
+            system_program: system_program::ID,
+        },
+        instruction::InitRelayerIndexShard { shard_index: 0 },
+    )
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_authority_closes_and_reopens_registrations() {
+    let f = fixture();
+    let mut pool = Pool::start(&f, 0, |_| {}).await;
+    let operator = pool.authority.pubkey();
+    let (relayer_index_shard, _) =
+        RelayerIndexShard::find_pda(&psol_privacy_v2::ID, &pool.relayer_registry, 0);
+    let (relayer_node, _) =
+        RelayerNode::find_pda(&psol_privacy_v2::ID, &pool.relayer_registry, &operator);
+    let (relayer_tombstone, _) =
+        RelayerTombstone::find_pda(&psol_privacy_v2::ID, &pool.relayer_registry, &operator);
+
+    pool.send_as_authority(init_shard_ix(&pool, operator, relayer_index_shard))
+        .await
+        .expect("init_relayer_index_shard");
+    let register = ix(
+        accounts::RegisterRelayer {
+            operator,
+            pool_config: pool.pool_config,
+            relayer_registry: pool.relayer_registry,
+            relayer_node,
+            system_program: system_program::ID,
+            relayer_index_shard,
+            relayer_tombstone,
+        },
+        instruction::RegisterRelayer {
+            fee_bps: 100,
+            metadata_uri: String::new(),
+            metadata: RelayerMetadata::default(),
+        },
+    );
+
+    let outsider = Keypair::new();
+    assert_program_error(
+        pool.send(
+            set_open_ix(&pool, outsider.pubkey(), false),
+            &[&pool.authority.insecure_clone(), &outsider],
+        )
+        .await,
+        PrivacyErrorV2::Unauthorized,
+    );
+
+    pool.send_as_authority(set_open_ix(&pool, operator, false))
+        .await
+        .expect("close registrations");
+    let registry: RelayerRegistry = pool.account(pool.relayer_registry).await;
+    assert!(!registry.registrations_open);
+    assert_program_error(
+        pool.send_as_authority(register.clone()).await,
+        PrivacyErrorV2::RegistrationsClosed,
+    );
+
+    pool.send_as_authority(set_open_ix(&pool, operator, true))
+        .await
+        .expect("open registrations");
+    pool.send_as_authority(register)
+        .await
+        .expect("register_relayer");
+    let node: RelayerNode = pool.account(relayer_node).await;
+    assert!(node.is_active);
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_legacy_relayer_node_migrates() {
+    let f = fixture();
+    let mut pool = Pool::start(&f, 0, |_| {}).await;
+    let authority = pool.authority.pubkey();
+    let operator = authority;
+    let (relayer_index_shard, _) =
+        RelayerIndexShard::find_pda(&psol_privacy_v2::ID, &pool.relayer_registry, 0);
+    let (relayer_node, bump) =
+        RelayerNode::find_pda(&psol_privacy_v2::ID, &pool.relayer_registry, &operator);
+    pool.send_as_authority(init_shard_ix(&pool, authority, relayer_index_shard))
+        .await
+        .expect("init_relayer_index_shard");
+
+    // A node registered in the legacy layout, with a URI too long for the
+    // current fields to fit, funded for that size only; the registry
+    // counted it before the index existed
+    let legacy = LegacyRelayerNode {
+        registry: pool.relayer_registry,
+        operator,
+        fee_bps: 100,
+        is_active: true,
+        stake_amount: 0,
+        transactions_processed: 42,
+        fees_earned: 4_200,
+        registered_at: 1_000,
+        last_active_at: 2_000,
+        metadata_uri: "u".repeat(MAX_RELAYER_METADATA_URI_LEN),
+        bump,
+        reputation_score: 50,
+        _reserved: [0u8; 16],
+    };
+    let mut data = RelayerNode::DISCRIMINATOR.to_vec();
+    legacy.serialize(&mut data).unwrap();
+    assert_eq!(data.len(), RelayerNode::LEGACY_SPACE);
+    let rent = pool.ctx.banks_client.get_rent().await.unwrap();
+    let account = Account {
+        lamports: rent.minimum_balance(RelayerNode::LEGACY_SPACE),
+        data,
+        owner: psol_privacy_v2::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    pool.ctx.set_account(&relayer_node, &account.into());
+
+    let mut registry: RelayerRegistry = pool.account(pool.relayer_registry).await;
+    registry.relayer_count = 1;
+    registry.active_relayer_count = 1;
+    let mut data = Vec::new();
+    registry.try_serialize(&mut data).unwrap();
+    let account = Account {
+        lamports: rent.minimum_balance(data.len()),
+        data,
+        owner: psol_privacy_v2::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    pool.ctx
+        .set_account(&pool.relayer_registry, &account.into());
+
+    let deactivate = ix(
+        accounts::DeactivateRelayer {
+            operator,
+            pool_config: pool.pool_config,
+            relayer_registry: pool.relayer_registry,
+            relayer_node,
+            relayer_index_shard,
+        },
+        instruction::DeactivateRelayer {},
+    );
+    assert_error_code(
+        pool.send_as_authority(deactivate.clone()).await,
+        anchor_lang::error::ErrorCode::AccountDidNotDeserialize.into(),
+        "AccountDidNotDeserialize",
+    );
+
+    let migrate = ix(
+        accounts::MigrateRelayerNode {
+            authority,
+            pool_config: pool.pool_config,
+            relayer_registry: pool.relayer_registry,
+            relayer_node,
+            relayer_index_shard,
+            system_program: system_program::ID,
+        },
+        instruction::MigrateRelayerNode {},
+    );
+    pool.send_as_authority(migrate.clone())
+        .await
+        .expect("migrate_relayer_node");
+
+    let account = pool
+        .ctx
+        .banks_client
+        .get_account(relayer_node)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), RelayerNode::DEFAULT_SPACE);
+    assert!(rent.is_exempt(account.lamports, account.data.len()));
+    let node: RelayerNode = pool.account(relayer_node).await;
+    assert_eq!(node.metadata_uri, legacy.metadata_uri);
+    assert_eq!(node.transactions_processed, 42);
+    assert_eq!(node.fees_earned, 4_200);
+    assert_eq!(node.reputation_score, 50);
+    assert_eq!(node.metadata, RelayerMetadata::default());
+    assert_eq!(node.index_position, 0);
+    assert_eq!(node.reputation_updated_at, legacy.registered_at);
+    let shard: RelayerIndexShard = pool.account(relayer_index_shard).await;
+    assert_eq!(shard.operators, vec![operator]);
+    assert!(shard.is_active(0));
+
+    // The index position now tracks the node
+    pool.send_as_authority(deactivate)
+        .await
+        .expect("deactivate_relayer");
+    let shard: RelayerIndexShard = pool.account(relayer_index_shard).await;
+    assert!(!shard.is_active(0));
+
+    pool.refresh_blockhash().await;
+    assert_program_error(
+        pool.send_as_authority(migrate).await,
+        PrivacyErrorV2::RelayerNodeAlreadyMigrated,
+    );
+}
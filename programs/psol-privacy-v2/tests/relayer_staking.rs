@@ -0,0 +1,412 @@
+//! Relayer staking against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test relayer_staking
+//!
+//! An operator stakes the registry's stake mint into its node's stake
+//! vault, unstakes part of it and can only withdraw that part once the
+//! unbonding period has passed. The stake mint cannot change while stake
+//! is held. The authority can slash part of it into the treasury.
+//!
+//! A registry created before staking is grown by `migrate_relayer_registry`
+//! before it parses.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{system_program, AccountSerialize};
+use anchor_spl::token::spl_token;
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::state::{
+    AssetVault, RelayerIndexShard, RelayerMetadata, RelayerNode, RelayerRegistry, RelayerTombstone,
+    RELAYER_UNBONDING_SECONDS,
+};
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::account::Account;
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::Signer;
+
+use common::fixture;
+use common::pool::{assert_error_code, assert_program_error, ix, mint_account, Pool};
+
+const SUPPLY: u64 = 1_000_000;
+
+/// Place an active relayer node for `operator` at its PDA
+fn forge_relayer_node(pool: &mut Pool, operator: Pubkey) -> Pubkey {
+    let (address, bump) =
+        RelayerNode::find_pda(&psol_privacy_v2::ID, &pool.relayer_registry, &operator);
+    let node = RelayerNode {
+        registry: pool.relayer_registry,
+        operator,
+        fee_bps: 100,
+        is_active: true,
+        stake_amount: 0,
+        transactions_processed: 0,
+        fees_earned: 0,
+        registered_at: 0,
+        last_active_at: 0,
+        metadata_uri: String::new(),
+        bump,
+        reputation_score: 50,
+        metadata: RelayerMetadata::default(),
+        update_nonce: 0,
+        index_position: 0,
+        draining: false,
+        drain_allowance: 0,
+        unbonding_amount: 0,
+        unbonding_release_at: 0,
+        failures_reported: 0,
+        reputation_updated_at: 0,
+        _reserved: [0u8; 1],
+    };
+    let mut data = Vec::new();
+    node.try_serialize(&mut data).unwrap();
+    let account = Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: psol_privacy_v2::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    pool.ctx.set_account(&address, &account.into());
+    address
+}
+
+fn set_mint_ix(pool: &Pool, stake_mint: Pubkey) -> Instruction {
+    ix(
+        accounts::SetRelayerStakeMint {
+            authority: pool.authority.pubkey(),
+            pool_config: pool.pool_config,
+            relayer_registry: pool.relayer_registry,
+            stake_mint,
+        },
+        instruction::SetRelayerStakeMint {},
+    )
+}
+
+fn move_stake_accounts(
+    pool: &Pool,
+    relayer_node: Pubkey,
+    stake_vault: Pubkey,
+) -> accounts::MoveRelayerStake {
+    accounts::MoveRelayerStake {
+        operator: pool.authority.pubkey(),
+        pool_config: pool.pool_config,
+        relayer_registry: pool.relayer_registry,
+        relayer_node,
+        stake_mint: pool.mint,
+        stake_vault,
+        operator_token_account: pool.user_token,
+        token_program: spl_token::id(),
+    }
+}
+
+fn init_stake_vault_ix(pool: &Pool, relayer_node: Pubkey, stake_vault: Pubkey) -> Instruction {
+    ix(
+        accounts::InitRelayerStakeVault {
+            operator: pool.authority.pubkey(),
+            pool_config: pool.pool_config,
+            relayer_registry: pool.relayer_registry,
+            relayer_node,
+            stake_mint: pool.mint,
+            stake_vault,
+            token_program: spl_token::id(),
+            system_program: system_program::ID,
+        },
+        instruction::InitRelayerStakeVault {},
+    )
+}
+
+fn unstake_ix(pool: &Pool, relayer_node: Pubkey, amount: u64) -> Instruction {
+    ix(
+        accounts::UnstakeRelayer {
+            operator: pool.authority.pubkey(),
+            pool_config: pool.pool_config,
+            relayer_registry: pool.relayer_registry,
+            relayer_node,
+        },
+        instruction::UnstakeRelayer { amount },
+    )
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_stake_unbonds_before_withdrawal() {
+    let f = fixture();
+    let other_mint = Pubkey::new_unique();
+    let mut pool = Pool::start(&f, SUPPLY, |pt| {
+        pt.add_account(other_mint, mint_account(0));
+    })
+    .await;
+    let operator = pool.authority.pubkey();
+    let relayer_node = forge_relayer_node(&mut pool, operator);
+    let (stake_vault, _) =
+        RelayerNode::find_stake_vault_pda(&psol_privacy_v2::ID, &relayer_node, &pool.mint);
+
+    pool.send_as_authority(set_mint_ix(&pool, pool.mint))
+        .await
+        .expect("set_relayer_stake_mint");
+    pool.send_as_authority(init_stake_vault_ix(&pool, relayer_node, stake_vault))
+        .await
+        .expect("init_relayer_stake_vault");
+
+    pool.send_as_authority(ix(
+        move_stake_accounts(&pool, relayer_node, stake_vault),
+        instruction::StakeRelayer { amount: 600 },
+    ))
+    .await
+    .expect("stake_relayer");
+    assert_eq!(pool.token_balance(stake_vault).await, 600);
+    assert_eq!(pool.token_balance(pool.user_token).await, SUPPLY - 600);
+    let node: RelayerNode = pool.account(relayer_node).await;
+    assert_eq!(node.stake_amount, 600);
+
+    // Held stake pins the mint
+    assert_program_error(
+        pool.send_as_authority(set_mint_ix(&pool, other_mint)).await,
+        PrivacyErrorV2::RelayerStakeOutstanding,
+    );
+
+    assert_program_error(
+        pool.send_as_authority(unstake_ix(&pool, relayer_node, 601))
+            .await,
+        PrivacyErrorV2::InsufficientStake,
+    );
+    pool.send_as_authority(unstake_ix(&pool, relayer_node, 400))
+        .await
+        .expect("unstake_relayer");
+    let node: RelayerNode = pool.account(relayer_node).await;
+    assert_eq!(node.stake_amount, 200);
+    assert_eq!(node.unbonding_amount, 400);
+
+    let withdraw = ix(
+        move_stake_accounts(&pool, relayer_node, stake_vault),
+        instruction::WithdrawRelayerStake {},
+    );
+    assert_program_error(
+        pool.send_as_authority(withdraw.clone()).await,
+        PrivacyErrorV2::RelayerStakeUnbonding,
+    );
+
+    let mut clock: Clock = pool.ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += RELAYER_UNBONDING_SECONDS;
+    pool.ctx.set_sysvar(&clock);
+    pool.refresh_blockhash().await;
+
+    pool.send_as_authority(withdraw.clone())
+        .await
+        .expect("withdraw_relayer_stake");
+    assert_eq!(pool.token_balance(stake_vault).await, 200);
+    assert_eq!(pool.token_balance(pool.user_token).await, SUPPLY - 200);
+    let registry: RelayerRegistry = pool.account(pool.relayer_registry).await;
+    assert_eq!(registry.total_staked, 200);
+
+    pool.refresh_blockhash().await;
+    assert_program_error(
+        pool.send_as_authority(withdraw).await,
+        PrivacyErrorV2::NoRelayerStakeUnbonding,
+    );
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_slash_moves_stake_to_treasury_and_deactivates() {
+    let f = fixture();
+    let mut pool = Pool::start(&f, SUPPLY, |_| {}).await;
+    let operator = pool.authority.pubkey();
+    let (relayer_index_shard, _) =
+        RelayerIndexShard::find_pda(&psol_privacy_v2::ID, &pool.relayer_registry, 0);
+    let (relayer_node, _) =
+        RelayerNode::find_pda(&psol_privacy_v2::ID, &pool.relayer_registry, &operator);
+    let (relayer_tombstone, _) =
+        RelayerTombstone::find_pda(&psol_privacy_v2::ID, &pool.relayer_registry, &operator);
+    let (stake_vault, _) =
+        RelayerNode::find_stake_vault_pda(&psol_privacy_v2::ID, &relayer_node, &pool.mint);
+    let (treasury, _) = AssetVault::find_treasury_pda(&psol_privacy_v2::ID, &pool.asset_vault);
+
+    pool.send_as_authority(ix(
+        accounts::InitRelayerIndexShard {
+            payer: operator,
+            pool_config: pool.pool_config,
+            relayer_registry: pool.relayer_registry,
+            relayer_index_shard,
+            system_program: system_program::ID,
+        },
+        instruction::InitRelayerIndexShard { shard_index: 0 },
+    ))
+    .await
+    .expect("init_relayer_index_shard");
+    pool.send_as_authority(ix(
+        accounts::RegisterRelayer {
+            operator,
+            pool_config: pool.pool_config,
+            relayer_registry: pool.relayer_registry,
+            relayer_node,
+            system_program: system_program::ID,
+            relayer_index_shard,
+            relayer_tombstone,
+        },
+        instruction::RegisterRelayer {
+            fee_bps: 100,
+            metadata_uri: String::new(),
+            metadata: RelayerMetadata::default(),
+        },
+    ))
+    .await
+    .expect("register_relayer");
+    pool.send_as_authority(set_mint_ix(&pool, pool.mint))
+        .await
+        .expect("set_relayer_stake_mint");
+    pool.send_as_authority(init_stake_vault_ix(&pool, relayer_node, stake_vault))
+        .await
+        .expect("init_relayer_stake_vault");
+    pool.send_as_authority(ix(
+        move_stake_accounts(&pool, relayer_node, stake_vault),
+        instruction::StakeRelayer { amount: 1_000 },
+    ))
+    .await
+    .expect("stake_relayer");
+    pool.send_as_authority(unstake_ix(&pool, relayer_node, 400))
+        .await
+        .expect("unstake_relayer");
+    pool.send_as_authority(ix(
+        accounts::InitTreasury {
+            authority: operator,
+            pool_config: pool.pool_config,
+            asset_vault: pool.asset_vault,
+            mint: pool.mint,
+            treasury_token_account: treasury,
+            token_program: spl_token::id(),
+            system_program: system_program::ID,
+        },
+        instruction::InitTreasury {
+            asset_id: pool.asset_id,
+        },
+    ))
+    .await
+    .expect("init_treasury");
+    let reputation_before = pool
+        .account::<RelayerNode>(relayer_node)
+        .await
+        .reputation_score;
+
+    let (pool_config, relayer_registry, mint, asset_vault) = (
+        pool.pool_config,
+        pool.relayer_registry,
+        pool.mint,
+        pool.asset_vault,
+    );
+    let slash_ix = |slash_bps: u16| {
+        ix(
+            accounts::SlashRelayer {
+                authority: operator,
+                pool_config,
+                relayer_registry,
+                relayer_node,
+                relayer_index_shard,
+                stake_mint: mint,
+                stake_vault,
+                asset_vault,
+                treasury_token_account: treasury,
+                token_program: spl_token::id(),
+            },
+            instruction::SlashRelayer {
+                slash_bps,
+                reputation_penalty: 10,
+                deactivate: true,
+                evidence_hash: [7u8; 32],
+            },
+        )
+    };
+    assert_program_error(
+        pool.send_as_authority(slash_ix(0)).await,
+        PrivacyErrorV2::InvalidSlashFraction,
+    );
+    pool.send_as_authority(slash_ix(5_000))
+        .await
+        .expect("slash_relayer");
+
+    // Half of the 1000 held, bonded stake first
+    assert_eq!(pool.token_balance(treasury).await, 500);
+    assert_eq!(pool.token_balance(stake_vault).await, 500);
+    let node: RelayerNode = pool.account(relayer_node).await;
+    assert_eq!(node.stake_amount, 100);
+    assert_eq!(node.unbonding_amount, 400);
+    assert_eq!(node.reputation_score, reputation_before.saturating_sub(10));
+    assert!(!node.is_active);
+    let registry: RelayerRegistry = pool.account(pool.relayer_registry).await;
+    assert_eq!(registry.total_staked, 500);
+    assert_eq!(registry.active_relayer_count, 0);
+    let shard: RelayerIndexShard = pool.account(relayer_index_shard).await;
+    assert!(!shard.is_active(0));
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_legacy_relayer_registry_migrates() {
+    let f = fixture();
+    let mut pool = Pool::start(&f, SUPPLY, |_| {}).await;
+    let authority = pool.authority.pubkey();
+
+    // Put the registry back in the pre-staking layout, funded for that
+    // size only
+    let registry: RelayerRegistry = pool.account(pool.relayer_registry).await;
+    let mut data = Vec::new();
+    registry.try_serialize(&mut data).unwrap();
+    data.truncate(RelayerRegistry::LEGACY_LEN);
+    let rent = pool.ctx.banks_client.get_rent().await.unwrap();
+    let legacy = Account {
+        lamports: rent.minimum_balance(RelayerRegistry::LEGACY_LEN),
+        data,
+        owner: psol_privacy_v2::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    pool.ctx.set_account(&pool.relayer_registry, &legacy.into());
+
+    assert_error_code(
+        pool.send_as_authority(set_mint_ix(&pool, pool.mint)).await,
+        anchor_lang::error::ErrorCode::AccountDidNotDeserialize.into(),
+        "AccountDidNotDeserialize",
+    );
+
+    let migrate = ix(
+        accounts::MigrateRelayerRegistry {
+            authority,
+            pool_config: pool.pool_config,
+            relayer_registry: pool.relayer_registry,
+            system_program: system_program::ID,
+        },
+        instruction::MigrateRelayerRegistry {},
+    );
+    pool.send_as_authority(migrate.clone())
+        .await
+        .expect("migrate_relayer_registry");
+
+    let account = pool
+        .ctx
+        .banks_client
+        .get_account(pool.relayer_registry)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), RelayerRegistry::LEN);
+    assert!(rent.is_exempt(account.lamports, account.data.len()));
+    let migrated: RelayerRegistry = pool.account(pool.relayer_registry).await;
+    assert_eq!(migrated.pool, registry.pool);
+    assert_eq!(migrated.relayer_count, registry.relayer_count);
+    assert!(!migrated.has_stake_mint());
+    assert_eq!(migrated.total_staked, 0);
+
+    pool.send_as_authority(set_mint_ix(&pool, pool.mint))
+        .await
+        .expect("set_relayer_stake_mint");
+    pool.refresh_blockhash().await;
+    assert_program_error(
+        pool.send_as_authority(migrate).await,
+        PrivacyErrorV2::RelayerRegistryAlreadyMigrated,
+    );
+}
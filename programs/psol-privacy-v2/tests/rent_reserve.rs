@@ -0,0 +1,372 @@
+//! Rent reserve advances against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test rent_reserve
+//!
+//! Withdrawals submitted with the pool's rent reserve and the relayer's
+//! rent advance account get the nullifier rent topped up from the reserve,
+//! up to the per-relayer cap, and the relayer repays it afterwards. Rent
+//! covered by a separate fee payer is not advanced.
+//!
+//! Withdrawals use the native SOL vault so trapdoor VKs can prove them.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{system_program, AccountSerialize};
+use anchor_spl::token::spl_token;
+use psol_privacy_v2::crypto::{DepositPublicInputs, WithdrawPublicInputs};
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::state::asset_vault::NATIVE_SOL_ASSET_ID;
+use psol_privacy_v2::state::{
+    AssetVault, MerkleTreeV2, RelayerMetadata, RelayerNode, RentAdvance, RentReserve,
+    SpentNullifierV2,
+};
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::signature::{Keypair, Signer};
+
+use common::pool::{assert_program_error, global_config, ix, Pool};
+use common::trapdoor::Trapdoor;
+use common::{fixture, proof_bytes};
+
+const AMOUNT: u64 = LAMPORTS_PER_SOL;
+
+/// Place an active relayer node for `operator` at its PDA
+fn forge_relayer_node(pool: &mut Pool, operator: Pubkey) -> Pubkey {
+    let (address, bump) =
+        RelayerNode::find_pda(&psol_privacy_v2::ID, &pool.relayer_registry, &operator);
+    let node = RelayerNode {
+        registry: pool.relayer_registry,
+        operator,
+        fee_bps: 100,
+        is_active: true,
+        stake_amount: 0,
+        transactions_processed: 0,
+        fees_earned: 0,
+        registered_at: 0,
+        last_active_at: 0,
+        metadata_uri: String::new(),
+        bump,
+        reputation_score: 50,
+        metadata: RelayerMetadata::default(),
+        update_nonce: 0,
+        index_position: 0,
+        draining: false,
+        drain_allowance: 0,
+        unbonding_amount: 0,
+        unbonding_release_at: 0,
+        failures_reported: 0,
+        reputation_updated_at: 0,
+        _reserved: [0u8; 1],
+    };
+    let mut data = Vec::new();
+    node.try_serialize(&mut data).unwrap();
+    let account = Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: psol_privacy_v2::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    pool.ctx.set_account(&address, &account.into());
+    address
+}
+
+fn deposit_ix(
+    pool: &Pool,
+    trapdoor: &Trapdoor,
+    asset_vault: Pubkey,
+    commitment: [u8; 32],
+) -> Instruction {
+    let inputs =
+        DepositPublicInputs::new(commitment, AMOUNT, NATIVE_SOL_ASSET_ID).to_field_elements();
+    let authority = pool.authority.pubkey();
+    ix(
+        accounts::DepositMasp {
+            depositor: authority,
+            pool_config: pool.pool_config,
+            global_config: global_config(),
+            authority,
+            merkle_tree: pool.merkle_tree,
+            pending_buffer: pool.pending_buffer,
+            asset_vault,
+            vault_token_account: None,
+            user_token_account: None,
+            mint: None,
+            deposit_vk: pool.deposit_vk,
+            token_program: spl_token::id(),
+            system_program: system_program::ID,
+            instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+            proof_stats: None,
+            anonymity_stats: None,
+            commitment_reservation: pool.commitment_reservation(&commitment),
+            risk_tiers: None,
+        },
+        instruction::DepositMasp {
+            amount: AMOUNT,
+            commitment,
+            asset_id: NATIVE_SOL_ASSET_ID,
+            proof_data: proof_bytes(&trapdoor.prove(&inputs, commitment[0].into())),
+            encrypted_note: None,
+        },
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn withdraw_ix(
+    pool: &Pool,
+    trapdoor: &Trapdoor,
+    asset_vault: Pubkey,
+    root: [u8; 32],
+    nullifier_hash: [u8; 32],
+    recipient: Pubkey,
+    rent_reserve: Pubkey,
+    rent_advance: Pubkey,
+    fee_payer: Option<Pubkey>,
+) -> Instruction {
+    let relayer = pool.authority.pubkey();
+    let inputs = WithdrawPublicInputs::new(
+        root,
+        nullifier_hash,
+        NATIVE_SOL_ASSET_ID,
+        recipient,
+        AMOUNT,
+        relayer,
+        0,
+        [0u8; 32],
+    )
+    .to_field_elements();
+    let (spent_nullifier, _) =
+        SpentNullifierV2::find_pda(&psol_privacy_v2::ID, &pool.pool_config, &nullifier_hash);
+    ix(
+        accounts::WithdrawMasp {
+            relayer,
+            pool_config: pool.pool_config,
+            global_config: global_config(),
+            merkle_tree: pool.merkle_tree,
+            vk_account: pool.withdraw_vk,
+            asset_vault,
+            vault_token_account: None,
+            recipient_token_account: None,
+            relayer_token_account: None,
+            spent_nullifier,
+            relayer_registry: pool.relayer_registry,
+            relayer_node: None,
+            yield_registry: None,
+            token_program: spl_token::id(),
+            system_program: system_program::ID,
+            instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+            proof_stats: None,
+            rebate_ledger: None,
+            migration_window: None,
+            deferred_events: None,
+            recipient_wallet: Some(recipient),
+            rent_reserve: Some(rent_reserve),
+            rent_advance: Some(rent_advance),
+            mint: None,
+            anonymity_stats: None,
+            parameter_snapshot: None,
+            recipient_ata: None,
+            associated_token_program: None,
+            fee_payer,
+            treasury_token_account: None,
+            withdrawal_commit: None,
+            fee_quote: None,
+            risk_tiers: None,
+            withdrawal_window: None,
+        },
+        instruction::WithdrawMasp {
+            proof_data: proof_bytes(&trapdoor.prove(&inputs, nullifier_hash[0].into())),
+            merkle_root: root,
+            nullifier_hash,
+            recipient,
+            amount: AMOUNT,
+            asset_id: NATIVE_SOL_ASSET_ID,
+            relayer_fee: 0,
+        },
+    )
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_rent_advanced_capped_and_repaid() {
+    let f = fixture();
+    let deposit = Trapdoor::new(9 << 8, 3);
+    let withdraw = Trapdoor::new((9 << 8) | 1, 8);
+    let mut pool = Pool::start_with_vks(&f, 0, &deposit.vk(), &withdraw.vk(), |_| {}).await;
+    let authority = pool.authority.pubkey();
+    let program_id = psol_privacy_v2::ID;
+    let (native_vault, _) =
+        AssetVault::find_pda(&program_id, &pool.pool_config, &NATIVE_SOL_ASSET_ID);
+    let (rent_reserve, _) = RentReserve::find_pda(&program_id, &pool.pool_config);
+    let relayer_node = forge_relayer_node(&mut pool, authority);
+    let (rent_advance, _) = RentAdvance::find_pda(&program_id, &relayer_node);
+
+    let nullifier_rent = pool
+        .ctx
+        .banks_client
+        .get_rent()
+        .await
+        .unwrap()
+        .minimum_balance(SpentNullifierV2::LEN);
+    // Room for one and a half nullifiers
+    let cap = nullifier_rent + nullifier_rent / 2;
+
+    let setup = [
+        ix(
+            accounts::RegisterNativeSolAsset {
+                authority,
+                pool_config: pool.pool_config,
+                asset_vault: native_vault,
+                system_program: system_program::ID,
+            },
+            instruction::RegisterNativeSolAsset {},
+        ),
+        ix(
+            accounts::InitRentReserve {
+                authority,
+                pool_config: pool.pool_config,
+                rent_reserve,
+                system_program: system_program::ID,
+            },
+            instruction::InitRentReserve {
+                max_outstanding_per_relayer: cap,
+            },
+        ),
+        ix(
+            accounts::FundRentReserve {
+                funder: authority,
+                pool_config: pool.pool_config,
+                rent_reserve,
+                system_program: system_program::ID,
+            },
+            instruction::FundRentReserve {
+                amount: LAMPORTS_PER_SOL,
+            },
+        ),
+        ix(
+            accounts::InitRentAdvance {
+                operator: authority,
+                pool_config: pool.pool_config,
+                rent_reserve,
+                relayer_node,
+                rent_advance,
+                system_program: system_program::ID,
+            },
+            instruction::InitRentAdvance {},
+        ),
+        deposit_ix(&pool, &deposit, native_vault, [1u8; 32]),
+        deposit_ix(&pool, &deposit, native_vault, [2u8; 32]),
+    ];
+    for ix in setup {
+        pool.send_as_authority(ix).await.expect("setup");
+    }
+    pool.send_as_authority(pool.batch_ix(2, None))
+        .await
+        .expect("batch_process_deposits");
+    let root = pool
+        .account::<MerkleTreeV2>(pool.merkle_tree)
+        .await
+        .current_root;
+    let funded = pool
+        .ctx
+        .banks_client
+        .get_balance(rent_reserve)
+        .await
+        .unwrap();
+
+    // Rent a fee payer covers is not advanced as well
+    let fee_payer = Keypair::new();
+    pool.ctx.set_account(
+        &fee_payer.pubkey(),
+        &Account::new(LAMPORTS_PER_SOL, 0, &system_program::ID).into(),
+    );
+    let authority_keypair = pool.authority.insecure_clone();
+    assert_program_error(
+        pool.send(
+            withdraw_ix(
+                &pool,
+                &withdraw,
+                native_vault,
+                root,
+                [3u8; 32],
+                Pubkey::new_unique(),
+                rent_reserve,
+                rent_advance,
+                Some(fee_payer.pubkey()),
+            ),
+            &[&authority_keypair, &fee_payer],
+        )
+        .await,
+        PrivacyErrorV2::RentAdvanceWithFeePayer,
+    );
+
+    // First withdrawal is reimbursed in full, the second up to the cap
+    for (nullifier, expected) in [([3u8; 32], nullifier_rent), ([4u8; 32], cap)] {
+        let recipient = Pubkey::new_unique();
+        pool.send_as_authority(withdraw_ix(
+            &pool,
+            &withdraw,
+            native_vault,
+            root,
+            nullifier,
+            recipient,
+            rent_reserve,
+            rent_advance,
+            None,
+        ))
+        .await
+        .expect("withdraw_masp");
+        let advance: RentAdvance = pool.account(rent_advance).await;
+        assert_eq!(advance.outstanding, expected);
+    }
+    assert_eq!(
+        pool.ctx
+            .banks_client
+            .get_balance(rent_reserve)
+            .await
+            .unwrap(),
+        funded - cap
+    );
+    let reserve: RentReserve = pool.account(rent_reserve).await;
+    assert_eq!(reserve.total_advanced, cap);
+    assert_eq!(reserve.total_funded, LAMPORTS_PER_SOL);
+
+    let repay = |amount| {
+        ix(
+            accounts::RepayRentAdvance {
+                operator: authority,
+                rent_reserve,
+                rent_advance,
+                system_program: system_program::ID,
+            },
+            instruction::RepayRentAdvance { amount },
+        )
+    };
+    assert_program_error(
+        pool.send_as_authority(repay(cap + 1)).await,
+        PrivacyErrorV2::InvalidRentRepayment,
+    );
+    pool.send_as_authority(repay(cap))
+        .await
+        .expect("repay_rent_advance");
+
+    let advance: RentAdvance = pool.account(rent_advance).await;
+    assert_eq!(advance.outstanding, 0);
+    assert_eq!(advance.total_repaid, cap);
+    assert_eq!(
+        pool.ctx
+            .banks_client
+            .get_balance(rent_reserve)
+            .await
+            .unwrap(),
+        funded
+    );
+    let reserve: RentReserve = pool.account(rent_reserve).await;
+    assert_eq!(reserve.outstanding(), 0);
+}
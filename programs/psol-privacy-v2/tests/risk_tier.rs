@@ -0,0 +1,170 @@
+//! Risk tiers against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test risk_tier
+//!
+//! The authority caps the `Elevated` tier and moves the harness asset into
+//! it. The vault only records the tier: deposits must pass the tier table,
+//! those above the cap are refused and smaller ones go through. Editing the
+//! tier to close deposits applies to the asset without moving it again.
+
+mod common;
+
+use anchor_lang::system_program;
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::state::{AssetVault, AssetWithdrawalWindow, RiskTierConfig, RiskTierLimits};
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::signature::{Keypair, Signer};
+
+use common::pool::{assert_program_error, ix, Pool};
+use common::{fixture, hex32, DepositNote};
+
+/// Between the fixture's first (1e6) and second (2e6) deposit note
+const CAP: u64 = 1_500_000;
+
+fn set_asset_tier(pool: &Pool, tier: u8) -> Instruction {
+    ix(
+        accounts::SetAssetRiskTier {
+            authority: pool.authority.pubkey(),
+            pool_config: pool.pool_config,
+            risk_tiers: RiskTierConfig::find_pda(&psol_privacy_v2::ID, &pool.pool_config).0,
+            asset_vault: pool.asset_vault,
+        },
+        instruction::SetAssetRiskTier {
+            asset_id: pool.asset_id,
+            tier,
+        },
+    )
+}
+
+/// `deposit_masp` of `note`, passing the pool's tier table if `with_tiers`
+fn deposit(pool: &Pool, note: &DepositNote, with_tiers: bool) -> Instruction {
+    let amount: u64 = note.amount.parse().unwrap();
+    let mut ix = pool.deposit_ix(amount, hex32(&note.commitment), &note.proof);
+    if with_tiers {
+        // `risk_tiers` is the last account
+        let risk_tiers = RiskTierConfig::find_pda(&psol_privacy_v2::ID, &pool.pool_config).0;
+        *ix.accounts.last_mut().unwrap() = AccountMeta::new_readonly(risk_tiers, false);
+    }
+    ix
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_asset_follows_its_risk_tier() {
+    let f = fixture();
+    let supply = f.deposits[..2]
+        .iter()
+        .map(|n| n.amount.parse::<u64>().unwrap())
+        .sum();
+    let mut pool = Pool::start(&f, supply, |_| {}).await;
+    let authority = pool.authority.pubkey();
+    let (risk_tiers, _) = RiskTierConfig::find_pda(&psol_privacy_v2::ID, &pool.pool_config);
+
+    pool.send_as_authority(ix(
+        accounts::InitRiskTiers {
+            authority,
+            pool_config: pool.pool_config,
+            risk_tiers,
+            system_program: system_program::ID,
+        },
+        instruction::InitRiskTiers {},
+    ))
+    .await
+    .expect("init_risk_tiers");
+    let config: RiskTierConfig = pool.account(risk_tiers).await;
+    assert_eq!(config.tiers, RiskTierConfig::DEFAULT_TIERS);
+
+    let capped = RiskTierLimits {
+        max_deposit: CAP,
+        ..RiskTierLimits::OPEN
+    };
+    let pool_config = pool.pool_config;
+    let set_limits = |authority, limits| {
+        ix(
+            accounts::SetRiskTierLimits {
+                authority,
+                pool_config,
+                risk_tiers,
+            },
+            instruction::SetRiskTierLimits {
+                tier: RiskTierConfig::TIER_ELEVATED,
+                limits,
+            },
+        )
+    };
+
+    // Only the pool authority edits tiers
+    let (payer, outsider) = (pool.authority.insecure_clone(), Keypair::new());
+    assert_program_error(
+        pool.send(set_limits(outsider.pubkey(), capped), &[&payer, &outsider])
+            .await,
+        PrivacyErrorV2::Unauthorized,
+    );
+    pool.send_as_authority(set_limits(authority, capped))
+        .await
+        .expect("set_risk_tier_limits");
+
+    pool.send_as_authority(set_asset_tier(&pool, RiskTierConfig::TIER_ELEVATED))
+        .await
+        .expect("set_asset_risk_tier");
+    let vault: AssetVault = pool.account(pool.asset_vault).await;
+    assert_eq!(vault.risk_tier, RiskTierConfig::TIER_ELEVATED);
+    assert!(vault.has_risk_tier());
+    // The cap stays in the tier table
+    assert_eq!(vault.max_deposit, u64::MAX);
+
+    // The tier table must be passed once the asset is in a tier
+    assert_program_error(
+        pool.send_as_authority(deposit(&pool, &f.deposits[0], false))
+            .await,
+        PrivacyErrorV2::RiskTiersRequired,
+    );
+    // Above the tier's cap
+    assert_program_error(
+        pool.send_as_authority(deposit(&pool, &f.deposits[1], true))
+            .await,
+        PrivacyErrorV2::ExceedsMaximumDeposit,
+    );
+    pool.send_as_authority(deposit(&pool, &f.deposits[0], true))
+        .await
+        .expect("deposit_masp");
+
+    // Editing the tier applies to the asset already in it
+    pool.send_as_authority(set_limits(authority, RiskTierLimits::WITHDRAW_ONLY))
+        .await
+        .expect("set_risk_tier_limits");
+    assert_program_error(
+        pool.send_as_authority(deposit(&pool, &f.deposits[1], true))
+            .await,
+        PrivacyErrorV2::DepositsDisabled,
+    );
+
+    assert_program_error(
+        pool.send_as_authority(set_asset_tier(&pool, RiskTierConfig::TIER_FROZEN + 1))
+            .await,
+        PrivacyErrorV2::InvalidRiskTier,
+    );
+
+    let (window, _) = AssetWithdrawalWindow::find_pda(&psol_privacy_v2::ID, &pool.asset_vault);
+    pool.send_as_authority(ix(
+        accounts::InitAssetWithdrawalWindow {
+            authority,
+            pool_config: pool.pool_config,
+            asset_vault: pool.asset_vault,
+            withdrawal_window: window,
+            system_program: system_program::ID,
+        },
+        instruction::InitAssetWithdrawalWindow {
+            asset_id: pool.asset_id,
+        },
+    ))
+    .await
+    .expect("init_asset_withdrawal_window");
+    let window: AssetWithdrawalWindow = pool.account(window).await;
+    assert_eq!(window.asset_vault, pool.asset_vault);
+    assert_eq!(window.withdrawn, 0);
+}
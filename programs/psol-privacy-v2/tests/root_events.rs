@@ -0,0 +1,70 @@
+//! Root update events against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test root_events
+//!
+//! Root events are off by default; once the authority enables them, each
+//! batch advances the pool's root event sequence.
+//!
+//! Fixture: see tests/common/mod.rs.
+
+mod common;
+
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::state::PoolConfigV2;
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::signature::{Keypair, Signer};
+
+use common::pool::{assert_program_error, ix, Pool};
+use common::{fixture, hex32};
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_root_events_follow_pool_flag() {
+    let f = fixture();
+    let amount: u64 = f.amount.parse().unwrap();
+    let commitment = hex32(&f.commitment);
+    let mut pool = Pool::start(&f, amount, |_| {}).await;
+
+    let set_root_events = |authority, pool: &Pool, enabled| {
+        ix(
+            accounts::SetRootEvents {
+                authority,
+                pool_config: pool.pool_config,
+            },
+            instruction::SetRootEvents { enabled },
+        )
+    };
+
+    let config: PoolConfigV2 = pool.account(pool.pool_config).await;
+    assert!(!config.emit_root_events);
+
+    let stranger = Keypair::new();
+    let payer = pool.authority.insecure_clone();
+    let ix = set_root_events(stranger.pubkey(), &pool, true);
+    assert_program_error(
+        pool.send(ix, &[&payer, &stranger]).await,
+        PrivacyErrorV2::Unauthorized,
+    );
+
+    let ix = set_root_events(pool.authority.pubkey(), &pool, true);
+    pool.send_as_authority(ix).await.expect("set_root_events");
+    pool.send_as_authority(pool.deposit_ix(amount, commitment, &f.deposit.proof))
+        .await
+        .expect("deposit_masp");
+
+    // A deposit only queues the commitment; the root changes with the batch
+    let config: PoolConfigV2 = pool.account(pool.pool_config).await;
+    assert!(config.emit_root_events);
+    assert_eq!(config.root_event_sequence, 0);
+
+    pool.send_as_authority(pool.batch_ix(1, None))
+        .await
+        .expect("batch_process_deposits");
+    let config: PoolConfigV2 = pool.account(pool.pool_config).await;
+    assert_eq!(config.root_event_sequence, 1);
+    // Deposit/withdraw event numbering is unaffected
+    assert_eq!(config.event_sequence, 1);
+}
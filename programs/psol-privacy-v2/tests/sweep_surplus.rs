@@ -0,0 +1,127 @@
+//! Timelocked surplus sweeps against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test sweep_surplus
+//!
+//! Tokens sent straight to the vault token account are swept into the
+//! asset's treasury token account once the timelock passes; deposited
+//! (shielded) tokens stay in the vault.
+//!
+//! Fixture: see tests/common/mod.rs.
+
+mod common;
+
+use anchor_lang::system_program;
+use anchor_spl::token::{self, spl_token};
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::state::{AssetVault, SurplusSweep, SURPLUS_SWEEP_DELAY_SECONDS};
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::clock::Clock;
+use solana_sdk::signature::Signer;
+
+use common::pool::{assert_program_error, ix, Pool};
+use common::{fixture, hex32};
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_sweep_surplus_after_timelock() {
+    let f = fixture();
+    let amount: u64 = f.amount.parse().unwrap();
+    let donation = 700;
+    let mut pool = Pool::start(&f, amount + donation, |_| {}).await;
+    let authority = pool.authority.pubkey();
+    let (surplus_sweep, _) = SurplusSweep::find_pda(&psol_privacy_v2::ID, &pool.asset_vault);
+    let (treasury, _) = AssetVault::find_treasury_pda(&psol_privacy_v2::ID, &pool.asset_vault);
+
+    pool.send_as_authority(ix(
+        accounts::InitTreasury {
+            authority,
+            pool_config: pool.pool_config,
+            asset_vault: pool.asset_vault,
+            mint: pool.mint,
+            treasury_token_account: treasury,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::InitTreasury {
+            asset_id: pool.asset_id,
+        },
+    ))
+    .await
+    .expect("init_treasury");
+
+    pool.send_as_authority(pool.deposit_ix(amount, hex32(&f.commitment), &f.deposit.proof))
+        .await
+        .expect("deposit_masp");
+    pool.send_as_authority(
+        spl_token::instruction::transfer(
+            &spl_token::id(),
+            &pool.user_token,
+            &pool.vault_token,
+            &authority,
+            &[],
+            donation,
+        )
+        .unwrap(),
+    )
+    .await
+    .expect("donation");
+
+    pool.send_as_authority(ix(
+        accounts::RequestSurplusSweep {
+            authority,
+            pool_config: pool.pool_config,
+            asset_vault: pool.asset_vault,
+            vault_token_account: pool.vault_token,
+            treasury_token_account: treasury,
+            surplus_sweep,
+            system_program: system_program::ID,
+        },
+        instruction::RequestSurplusSweep {
+            asset_id: pool.asset_id,
+        },
+    ))
+    .await
+    .expect("request_surplus_sweep");
+
+    let sweep = ix(
+        accounts::SweepSurplus {
+            authority,
+            pool_config: pool.pool_config,
+            asset_vault: pool.asset_vault,
+            vault_token_account: pool.vault_token,
+            treasury_token_account: treasury,
+            mint: pool.mint,
+            surplus_sweep,
+            token_program: spl_token::id(),
+        },
+        instruction::SweepSurplus {
+            asset_id: pool.asset_id,
+        },
+    );
+    assert_program_error(
+        pool.send_as_authority(sweep.clone()).await,
+        PrivacyErrorV2::SurplusSweepTimelocked,
+    );
+
+    let mut clock: Clock = pool.ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += SURPLUS_SWEEP_DELAY_SECONDS;
+    pool.ctx.set_sysvar(&clock);
+    pool.refresh_blockhash().await;
+
+    pool.send_as_authority(sweep).await.expect("sweep_surplus");
+
+    assert_eq!(pool.token_balance(treasury).await, donation);
+    assert_eq!(pool.token_balance(pool.vault_token).await, amount);
+    let vault = pool.account::<AssetVault>(pool.asset_vault).await;
+    assert_eq!(vault.shielded_balance, amount);
+    assert!(pool
+        .ctx
+        .banks_client
+        .get_account(surplus_sweep)
+        .await
+        .unwrap()
+        .is_none());
+}
@@ -0,0 +1,304 @@
+//! Token-2022 transfer-fee vaults against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test token_2022_fee
+//!
+//! A deposit of a fee mint costs the depositor the gross amount, so the
+//! vault (and the shielded balance) hold exactly the committed amount.
+//! Withdrawals need the mint and pay out net of the fee.
+//!
+//! Proofs come from trapdoor VKs, as the fixture circuit is for an SPL mint.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::system_program;
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::{
+    TransferFee, TransferFeeAmount, TransferFeeConfig,
+};
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    BaseStateWithExtensionsMut, ExtensionType, StateWithExtensions, StateWithExtensionsMut,
+};
+use anchor_spl::token_2022::spl_token_2022::state::{
+    Account as TokenState, AccountState, Mint as MintState,
+};
+use anchor_spl::token_2022::{self, spl_token_2022};
+use psol_privacy_v2::crypto::{DepositPublicInputs, WithdrawPublicInputs};
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::state::asset_vault::compute_asset_id;
+use psol_privacy_v2::state::{AssetVault, MerkleTreeV2, SpentNullifierV2};
+use psol_privacy_v2::utils::MintTransferFee;
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::Signer;
+
+use common::pool::{assert_program_error, global_config, ix, Pool};
+use common::trapdoor::Trapdoor;
+use common::{fixture, proof_bytes};
+
+const AMOUNT: u64 = 1_000_000;
+const FEE_BPS: u16 = 100;
+const MAX_FEE: u64 = 1_000_000;
+
+fn token_2022_account(data: Vec<u8>) -> Account {
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token_2022::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Mint charging `FEE_BPS` on every transfer
+fn fee_mint(supply: u64) -> Account {
+    let len =
+        ExtensionType::try_calculate_account_len::<MintState>(&[ExtensionType::TransferFeeConfig])
+            .unwrap();
+    let mut data = vec![0u8; len];
+    let mut state = StateWithExtensionsMut::<MintState>::unpack_uninitialized(&mut data).unwrap();
+    let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+    let fee = TransferFee {
+        epoch: 0.into(),
+        maximum_fee: MAX_FEE.into(),
+        transfer_fee_basis_points: FEE_BPS.into(),
+    };
+    config.older_transfer_fee = fee;
+    config.newer_transfer_fee = fee;
+    state.base = MintState {
+        supply,
+        decimals: 6,
+        is_initialized: true,
+        ..MintState::default()
+    };
+    state.pack_base();
+    state.init_account_type().unwrap();
+    token_2022_account(data)
+}
+
+fn fee_token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    let len =
+        ExtensionType::try_calculate_account_len::<TokenState>(&[ExtensionType::TransferFeeAmount])
+            .unwrap();
+    let mut data = vec![0u8; len];
+    let mut state = StateWithExtensionsMut::<TokenState>::unpack_uninitialized(&mut data).unwrap();
+    state.init_extension::<TransferFeeAmount>(true).unwrap();
+    state.base = TokenState {
+        mint,
+        owner,
+        amount,
+        state: AccountState::Initialized,
+        ..TokenState::default()
+    };
+    state.pack_base();
+    state.init_account_type().unwrap();
+    token_2022_account(data)
+}
+
+async fn balance(pool: &mut Pool, key: Pubkey) -> u64 {
+    let account = pool
+        .ctx
+        .banks_client
+        .get_account(key)
+        .await
+        .unwrap()
+        .unwrap();
+    StateWithExtensions::<TokenState>::unpack(&account.data)
+        .unwrap()
+        .base
+        .amount
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_fee_mint_vault_holds_committed_amount() {
+    let f = fixture();
+    let deposit = Trapdoor::new(11 << 8, 3);
+    let withdraw = Trapdoor::new((11 << 8) | 1, 8);
+    let mint = Pubkey::new_unique();
+    let mint_account = fee_mint(10 * AMOUNT);
+    let mint_data = mint_account.data.clone();
+    let mut pool = Pool::start_with_vks(&f, 0, &deposit.vk(), &withdraw.vk(), |pt| {
+        pt.add_account(mint, mint_account);
+    })
+    .await;
+    let authority = pool.authority.pubkey();
+    let program_id = psol_privacy_v2::ID;
+    let asset_id = compute_asset_id(&mint);
+    let (asset_vault, _) = AssetVault::find_pda(&program_id, &pool.pool_config, &asset_id);
+    let (vault_token, _) =
+        Pubkey::find_program_address(&[b"vault_token", asset_vault.as_ref()], &program_id);
+    let fee = MintTransferFee::from_mint_data(&spl_token_2022::ID, &mint_data, 0).unwrap();
+
+    let user_token = Pubkey::new_unique();
+    let relayer_token = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+    let recipient_token = Pubkey::new_unique();
+    for (key, owner, amount) in [
+        (user_token, authority, 10 * AMOUNT),
+        (relayer_token, authority, 0),
+        (recipient_token, recipient, 0),
+    ] {
+        pool.ctx
+            .set_account(&key, &fee_token_account(mint, owner, amount).into());
+    }
+
+    pool.send_as_authority(ix(
+        accounts::RegisterAsset {
+            authority,
+            pool_config: pool.pool_config,
+            mint,
+            asset_vault,
+            vault_token_account: vault_token,
+            token_program: token_2022::ID,
+            system_program: system_program::ID,
+            token_metadata: None,
+        },
+        instruction::RegisterAsset { asset_id },
+    ))
+    .await
+    .expect("register_asset");
+    let vault: AssetVault = pool.account(asset_vault).await;
+    assert!(vault.is_token_2022());
+
+    // The depositor pays the fee on top of the committed amount
+    let commitment = [1u8; 32];
+    let inputs = DepositPublicInputs::new(commitment, AMOUNT, asset_id).to_field_elements();
+    pool.send_as_authority(ix(
+        accounts::DepositMasp {
+            depositor: authority,
+            pool_config: pool.pool_config,
+            global_config: global_config(),
+            authority,
+            merkle_tree: pool.merkle_tree,
+            pending_buffer: pool.pending_buffer,
+            asset_vault,
+            vault_token_account: Some(vault_token),
+            user_token_account: Some(user_token),
+            mint: Some(mint),
+            deposit_vk: pool.deposit_vk,
+            token_program: token_2022::ID,
+            system_program: system_program::ID,
+            instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+            proof_stats: None,
+            anonymity_stats: None,
+            commitment_reservation: pool.commitment_reservation(&commitment),
+            risk_tiers: None,
+        },
+        instruction::DepositMasp {
+            amount: AMOUNT,
+            commitment,
+            asset_id,
+            proof_data: proof_bytes(&deposit.prove(&inputs, 1)),
+            encrypted_note: None,
+        },
+    ))
+    .await
+    .expect("deposit_masp");
+
+    let gross = fee.gross_amount(AMOUNT).unwrap();
+    assert!(gross > AMOUNT);
+    assert_eq!(balance(&mut pool, user_token).await, 10 * AMOUNT - gross);
+    assert_eq!(balance(&mut pool, vault_token).await, AMOUNT);
+    let vault: AssetVault = pool.account(asset_vault).await;
+    assert_eq!(vault.shielded_balance, AMOUNT);
+
+    pool.send_as_authority(pool.batch_ix(1, None))
+        .await
+        .expect("batch_process_deposits");
+    let root = pool
+        .account::<MerkleTreeV2>(pool.merkle_tree)
+        .await
+        .current_root;
+
+    let nullifier_hash = [2u8; 32];
+    let inputs = WithdrawPublicInputs::new(
+        root,
+        nullifier_hash,
+        asset_id,
+        recipient,
+        AMOUNT,
+        authority,
+        0,
+        [0u8; 32],
+    )
+    .to_field_elements();
+    let (spent_nullifier, _) =
+        SpentNullifierV2::find_pda(&program_id, &pool.pool_config, &nullifier_hash);
+    let (pool_config, merkle_tree, withdraw_vk, relayer_registry) = (
+        pool.pool_config,
+        pool.merkle_tree,
+        pool.withdraw_vk,
+        pool.relayer_registry,
+    );
+    let withdraw_ix = |mint: Option<Pubkey>| -> Instruction {
+        ix(
+            accounts::WithdrawMasp {
+                relayer: authority,
+                pool_config,
+                global_config: global_config(),
+                merkle_tree,
+                vk_account: withdraw_vk,
+                asset_vault,
+                vault_token_account: Some(vault_token),
+                recipient_token_account: Some(recipient_token),
+                relayer_token_account: Some(relayer_token),
+                spent_nullifier,
+                relayer_registry,
+                relayer_node: None,
+                yield_registry: None,
+                token_program: token_2022::ID,
+                system_program: system_program::ID,
+                instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+                proof_stats: None,
+                rebate_ledger: None,
+                migration_window: None,
+                deferred_events: None,
+                recipient_wallet: None,
+                rent_reserve: None,
+                rent_advance: None,
+                mint,
+                anonymity_stats: None,
+                parameter_snapshot: None,
+                recipient_ata: None,
+                associated_token_program: None,
+                fee_payer: None,
+                treasury_token_account: None,
+                withdrawal_commit: None,
+                fee_quote: None,
+                risk_tiers: None,
+                withdrawal_window: None,
+            },
+            instruction::WithdrawMasp {
+                proof_data: proof_bytes(&withdraw.prove(&inputs, 2)),
+                merkle_root: root,
+                nullifier_hash,
+                recipient,
+                amount: AMOUNT,
+                asset_id,
+                relayer_fee: 0,
+            },
+        )
+    };
+
+    assert_program_error(
+        pool.send_as_authority(withdraw_ix(None)).await,
+        PrivacyErrorV2::MissingAssetAccount,
+    );
+    pool.send_as_authority(withdraw_ix(Some(mint)))
+        .await
+        .expect("withdraw_masp");
+
+    // The vault is debited the full amount; the recipient receives it net
+    // of the fee
+    assert_eq!(balance(&mut pool, vault_token).await, 0);
+    assert_eq!(
+        balance(&mut pool, recipient_token).await,
+        AMOUNT - fee.fee(AMOUNT).unwrap()
+    );
+    let vault: AssetVault = pool.account(asset_vault).await;
+    assert_eq!(vault.shielded_balance, 0);
+}
@@ -0,0 +1,203 @@
+//! Caching Metaplex token metadata at registration, against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test token_metadata
+//!
+//! `register_asset` given the mint's metadata PDA caches its symbol and
+//! name hash in the vault; a metadata account for another mint is
+//! rejected. The metadata accounts are written directly into the bank, so
+//! the Metaplex program itself is not loaded.
+//!
+//! A vault registered before those fields, with a long metadata URI, is
+//! grown by `migrate_asset_vault` before it parses.
+//!
+//! Fixture: see tests/common/mod.rs.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{system_program, AccountSerialize};
+use anchor_spl::token::spl_token;
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::state::asset_vault::{compute_asset_id, MAX_METADATA_URI_LEN};
+use psol_privacy_v2::state::AssetVault;
+use psol_privacy_v2::utils::token_metadata::{TokenMetadata, TOKEN_METADATA_PROGRAM_ID};
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::Signer;
+
+use common::fixture;
+use common::pool::{assert_error_code, assert_program_error, ix, mint_account, Pool};
+
+/// A `MetadataV1` account for `mint`, with Metaplex's NUL padding
+fn metadata_account(mint: &Pubkey, name: &str, symbol: &str) -> Account {
+    let mut data = vec![4u8];
+    data.extend_from_slice(Pubkey::new_unique().as_ref());
+    data.extend_from_slice(mint.as_ref());
+    for (s, len) in [(name, 32), (symbol, 10), ("", 200)] {
+        let mut bytes = s.as_bytes().to_vec();
+        bytes.resize(len, 0);
+        data.extend_from_slice(&(len as u32).to_le_bytes());
+        data.extend_from_slice(&bytes);
+    }
+    data.extend_from_slice(&[0u8; 64]);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: TOKEN_METADATA_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn register_ix(pool: &Pool, mint: Pubkey, token_metadata: Pubkey) -> Instruction {
+    let asset_id = compute_asset_id(&mint);
+    let (asset_vault, _) = AssetVault::find_pda(&psol_privacy_v2::ID, &pool.pool_config, &asset_id);
+    let (vault_token_account, _) = Pubkey::find_program_address(
+        &[b"vault_token", asset_vault.as_ref()],
+        &psol_privacy_v2::ID,
+    );
+    ix(
+        accounts::RegisterAsset {
+            authority: pool.authority.pubkey(),
+            pool_config: pool.pool_config,
+            mint,
+            asset_vault,
+            vault_token_account,
+            token_program: spl_token::id(),
+            system_program: system_program::ID,
+            token_metadata: Some(token_metadata),
+        },
+        instruction::RegisterAsset { asset_id },
+    )
+}
+
+#[tokio::test]
+#[cfg_attr(
+    not(sbf_build),
+    ignore = "needs the SBF build of the program (see module docs)"
+)]
+async fn test_register_asset_caches_token_metadata() {
+    let f = fixture();
+    let mint = Pubkey::new_unique();
+    let other = Pubkey::new_unique();
+    let (metadata, _) = TokenMetadata::find_pda(&mint);
+    let (other_metadata, _) = TokenMetadata::find_pda(&other);
+    let mut pool = Pool::start(&f, 0, |pt| {
+        pt.add_account(mint, mint_account(0));
+        pt.add_account(other, mint_account(0));
+        pt.add_account(metadata, metadata_account(&mint, "USD Coin", "USDC"));
+        pt.add_account(other_metadata, metadata_account(&other, "Other", "OTH"));
+    })
+    .await;
+
+    // Another mint's metadata does not describe this one
+    assert_program_error(
+        pool.send_as_authority(register_ix(&pool, mint, other_metadata))
+            .await,
+        PrivacyErrorV2::InvalidTokenMetadata,
+    );
+
+    pool.send_as_authority(register_ix(&pool, mint, metadata))
+        .await
+        .expect("register_asset with metadata");
+
+    let (vault_key, _) = AssetVault::find_pda(
+        &psol_privacy_v2::ID,
+        &pool.pool_config,
+        &compute_asset_id(&mint),
+    );
+    let vault = pool.account::<AssetVault>(vault_key).await;
+    let expected = TokenMetadata {
+        name: b"USD Coin".to_vec(),
+        symbol: b"USDC".to_vec(),
+    };
+    assert!(vault.has_token_metadata());
+    assert_eq!(vault.token_symbol, expected.symbol_bytes());
+    assert_eq!(vault.token_name_hash, expected.name_hash());
+}
+
+#[tokio::test]
+#[cfg_attr(
+    not(sbf_build),
+    ignore = "needs the SBF build of the program (see module docs)"
+)]
+async fn test_legacy_asset_vault_migrates() {
+    let f = fixture();
+    let mut pool = Pool::start(&f, 0, |_| {}).await;
+    let authority = pool.authority.pubkey();
+
+    // Put the vault back in the legacy layout with a URI too long for the
+    // current fields to fit, funded for that size only
+    let mut vault: AssetVault = pool.account(pool.asset_vault).await;
+    vault.metadata_uri = "u".repeat(MAX_METADATA_URI_LEN);
+    let mut data = Vec::new();
+    vault.try_serialize(&mut data).unwrap();
+    data.truncate(AssetVault::LEGACY_SPACE - 32);
+    data.resize(AssetVault::LEGACY_SPACE, 0);
+    let rent = pool.ctx.banks_client.get_rent().await.unwrap();
+    let legacy = Account {
+        lamports: rent.minimum_balance(AssetVault::LEGACY_SPACE),
+        data,
+        owner: psol_privacy_v2::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    pool.ctx.set_account(&pool.asset_vault, &legacy.into());
+
+    let set_min = ix(
+        accounts::SetAssetMinWithdrawal {
+            authority,
+            pool_config: pool.pool_config,
+            asset_vault: pool.asset_vault,
+        },
+        instruction::SetAssetMinWithdrawal {
+            asset_id: pool.asset_id,
+            min_withdrawal: 100,
+        },
+    );
+    assert_error_code(
+        pool.send_as_authority(set_min.clone()).await,
+        anchor_lang::error::ErrorCode::AccountDidNotDeserialize.into(),
+        "AccountDidNotDeserialize",
+    );
+
+    let migrate = ix(
+        accounts::MigrateAssetVault {
+            authority,
+            pool_config: pool.pool_config,
+            asset_vault: pool.asset_vault,
+            system_program: system_program::ID,
+        },
+        instruction::MigrateAssetVault {},
+    );
+    pool.send_as_authority(migrate.clone())
+        .await
+        .expect("migrate_asset_vault");
+
+    let account = pool
+        .ctx
+        .banks_client
+        .get_account(pool.asset_vault)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), AssetVault::DEFAULT_SPACE);
+    assert!(rent.is_exempt(account.lamports, account.data.len()));
+    let migrated: AssetVault = pool.account(pool.asset_vault).await;
+    assert_eq!(migrated.metadata_uri, vault.metadata_uri);
+    assert_eq!(migrated.mint, vault.mint);
+    assert!(!migrated.has_token_metadata());
+
+    pool.send_as_authority(set_min)
+        .await
+        .expect("set_asset_min_withdrawal");
+    pool.refresh_blockhash().await;
+    assert_program_error(
+        pool.send_as_authority(migrate).await,
+        PrivacyErrorV2::AssetVaultAlreadyMigrated,
+    );
+}
@@ -0,0 +1,188 @@
+//! Two-step verification key creation against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test vk_lifecycle
+//!
+//! VK accounts are created once by `create_verification_key_account` and
+//! written by `set_verification_key_data` / the chunked upload. Neither path
+//! may re-initialize an existing account, and a locked key stays as it was.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::state::VerificationKeyAccountV2;
+use psol_privacy_v2::{accounts, instruction, ProofType};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::Signer;
+
+use common::pool::{assert_error_code, assert_program_error, create_vk_ix, ix, Pool};
+use common::trapdoor::Trapdoor;
+use common::{fixture, g1, g2, SnarkjsVk};
+
+/// System program `AccountAlreadyInUse`, raised by `init` on an existing account
+const ACCOUNT_ALREADY_IN_USE: u32 = 0;
+
+fn set_vk_data_ix(pool: &Pool, proof_type: ProofType, vk: &SnarkjsVk) -> Instruction {
+    let (vk_account, _) =
+        VerificationKeyAccountV2::find_pda(&psol_privacy_v2::ID, &pool.pool_config, proof_type);
+    ix(
+        accounts::SetVerificationKeyData {
+            authority: pool.authority.pubkey(),
+            pool_config: pool.pool_config,
+            vk_account,
+            circuit_descriptor: None,
+            merkle_tree: pool.merkle_tree,
+        },
+        instruction::SetVerificationKeyData {
+            proof_type,
+            vk_alpha_g1: g1(&vk.vk_alpha_1),
+            vk_beta_g2: g2(&vk.vk_beta_2),
+            vk_gamma_g2: g2(&vk.vk_gamma_2),
+            vk_delta_g2: g2(&vk.vk_delta_2),
+            vk_ic: vk.ic.iter().map(|p| g1(p)).collect(),
+            ceremony_transcript_hash: [0u8; 32],
+            ceremony_contributors: 0,
+        },
+    )
+}
+
+fn initialize_vk_ix(pool: &Pool, proof_type: ProofType, vk: &SnarkjsVk) -> Instruction {
+    let (vk_account, _) =
+        VerificationKeyAccountV2::find_pda(&psol_privacy_v2::ID, &pool.pool_config, proof_type);
+    ix(
+        accounts::InitializeVkV2 {
+            authority: pool.authority.pubkey(),
+            pool_config: pool.pool_config,
+            vk_account,
+            circuit_descriptor: None,
+            merkle_tree: pool.merkle_tree,
+        },
+        instruction::InitializeVkV2 {
+            proof_type,
+            vk_alpha_g1: g1(&vk.vk_alpha_1),
+            vk_beta_g2: g2(&vk.vk_beta_2),
+            vk_gamma_g2: g2(&vk.vk_gamma_2),
+            vk_delta_g2: g2(&vk.vk_delta_2),
+            expected_ic_count: vk.ic.len() as u8,
+            ceremony_transcript_hash: [0u8; 32],
+            ceremony_contributors: 0,
+        },
+    )
+}
+
+#[tokio::test]
+#[cfg_attr(
+    not(sbf_build),
+    ignore = "needs the SBF build of the program (see module docs)"
+)]
+async fn test_locked_vk_cannot_be_reinitialized() {
+    let f = fixture();
+    let mut pool = Pool::start(&f, 1_000_000, |_| {}).await;
+    let authority = pool.authority.pubkey();
+    let before: VerificationKeyAccountV2 = pool.account(pool.deposit_vk).await;
+
+    pool.send_as_authority(ix(
+        accounts::LockVerificationKeyV2 {
+            authority,
+            pool_config: pool.pool_config,
+            vk_account: pool.deposit_vk,
+        },
+        instruction::LockVerificationKeyV2 {
+            proof_type: ProofType::Deposit,
+        },
+    ))
+    .await
+    .expect("lock_verification_key_v2");
+
+    let other = Trapdoor::new(15 << 8, 3).vk();
+    assert_error_code(
+        pool.send_as_authority(create_vk_ix(
+            authority,
+            pool.pool_config,
+            ProofType::Deposit,
+        ))
+        .await,
+        ACCOUNT_ALREADY_IN_USE,
+        "AccountAlreadyInUse",
+    );
+    assert_program_error(
+        pool.send_as_authority(set_vk_data_ix(&pool, ProofType::Deposit, &other))
+            .await,
+        PrivacyErrorV2::VerificationKeyLocked,
+    );
+    assert_program_error(
+        pool.send_as_authority(initialize_vk_ix(&pool, ProofType::Deposit, &other))
+            .await,
+        PrivacyErrorV2::VerificationKeyLocked,
+    );
+
+    let after: VerificationKeyAccountV2 = pool.account(pool.deposit_vk).await;
+    assert!(after.is_locked && after.is_valid());
+    assert_eq!(after.vk_hash, before.vk_hash);
+    assert_eq!(after.vk_ic, before.vk_ic);
+}
+
+#[tokio::test]
+#[cfg_attr(
+    not(sbf_build),
+    ignore = "needs the SBF build of the program (see module docs)"
+)]
+async fn test_vk_data_needs_created_account() {
+    let f = fixture();
+    let mut pool = Pool::start(&f, 1_000_000, |_| {}).await;
+    let authority = pool.authority.pubkey();
+    let first = Trapdoor::new(15 << 8, 10).vk();
+    let second = Trapdoor::new((15 << 8) | 1, 10).vk();
+
+    // Writing a key does not allocate the account
+    assert_error_code(
+        pool.send_as_authority(set_vk_data_ix(&pool, ProofType::JoinSplit, &first))
+            .await,
+        ErrorCode::AccountNotInitialized.into(),
+        "AccountNotInitialized",
+    );
+
+    pool.send_as_authority(create_vk_ix(
+        authority,
+        pool.pool_config,
+        ProofType::JoinSplit,
+    ))
+    .await
+    .expect("create_verification_key_account");
+    let (vk_account, _) = VerificationKeyAccountV2::find_pda(
+        &psol_privacy_v2::ID,
+        &pool.pool_config,
+        ProofType::JoinSplit,
+    );
+    let created: VerificationKeyAccountV2 = pool.account(vk_account).await;
+    assert!(!created.is_initialized && !created.is_locked);
+
+    // Created once only
+    pool.refresh_blockhash().await;
+    assert_error_code(
+        pool.send_as_authority(create_vk_ix(
+            authority,
+            pool.pool_config,
+            ProofType::JoinSplit,
+        ))
+        .await,
+        ACCOUNT_ALREADY_IN_USE,
+        "AccountAlreadyInUse",
+    );
+
+    // An unlocked key may be replaced
+    pool.send_as_authority(set_vk_data_ix(&pool, ProofType::JoinSplit, &first))
+        .await
+        .expect("set_verification_key_data");
+    let set: VerificationKeyAccountV2 = pool.account(vk_account).await;
+    assert!(set.is_valid());
+    pool.send_as_authority(set_vk_data_ix(&pool, ProofType::JoinSplit, &second))
+        .await
+        .expect("replace unlocked VK");
+    let replaced: VerificationKeyAccountV2 = pool.account(vk_account).await;
+    assert!(replaced.is_valid());
+    assert_ne!(replaced.vk_hash, set.vk_hash);
+}
@@ -0,0 +1,447 @@
+//! Withdraw v2 (withdrawal with change) against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test withdraw_v2
+//!
+//! A deposited note is partly withdrawn through `withdraw_v2`: the amount
+//! is paid out, both nullifiers are spent, and the change commitment is
+//! queued in the pending buffer until the next batch inserts it. The
+//! spending relayer then records a note receipt in a nullifier record,
+//! unless the pool is privacy-strict, and per-asset anonymity statistics
+//! count the deposit and withdrawal. Proofs come from a trapdoor VK, as
+//! there is no withdraw v2 circuit fixture.
+//!
+//! Fixture: see tests/common/mod.rs.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::system_program;
+use anchor_spl::token;
+use psol_privacy_v2::crypto::WithdrawV2PublicInputs;
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::state::{
+    AnonymityStats, MerkleTreeV2, PendingDepositsBuffer, PoolConfigV2, SpendContext,
+    SpentNullifierV2, VerificationKeyAccountV2,
+};
+use psol_privacy_v2::{accounts, instruction, ProofType};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::signature::{Keypair, Signer};
+
+use common::pool::{assert_program_error, create_vk_ix, global_config, ix, Pool};
+use common::trapdoor::Trapdoor;
+use common::{fixture, g1, g2, hex32, proof_bytes};
+
+#[derive(Clone, Copy)]
+struct Withdrawal {
+    nullifiers: [[u8; 32]; 2],
+    change_commitment: [u8; 32],
+    amount: u64,
+    relayer_fee: u64,
+}
+
+impl Withdrawal {
+    fn ix(&self, pool: &Pool, circuit: &Trapdoor, root: [u8; 32], salt: u64) -> Instruction {
+        let relayer = pool.authority.pubkey();
+        let (vk_account, _) = VerificationKeyAccountV2::find_pda(
+            &psol_privacy_v2::ID,
+            &pool.pool_config,
+            ProofType::WithdrawV2,
+        );
+        let [spent_nullifier_0, spent_nullifier_1] = self.nullifiers.map(|nullifier_hash| {
+            SpentNullifierV2::find_pda(&psol_privacy_v2::ID, &pool.pool_config, &nullifier_hash).0
+        });
+        let inputs = WithdrawV2PublicInputs::new(
+            root,
+            pool.asset_id,
+            self.nullifiers[0],
+            self.nullifiers[1],
+            self.change_commitment,
+            relayer,
+            self.amount,
+            relayer,
+            self.relayer_fee,
+            [0u8; 32],
+        )
+        .to_field_elements();
+
+        ix(
+            accounts::WithdrawV2 {
+                relayer,
+                pool_config: pool.pool_config,
+                global_config: global_config(),
+                merkle_tree: pool.merkle_tree,
+                vk_account,
+                asset_vault: pool.asset_vault,
+                vault_token_account: pool.vault_token,
+                recipient_token_account: pool.user_token,
+                relayer_token_account: pool.user_token,
+                spent_nullifier_0,
+                spent_nullifier_1: Some(spent_nullifier_1),
+                pending_buffer: pool.pending_buffer,
+                relayer_registry: pool.relayer_registry,
+                relayer_node: None,
+                yield_registry: None,
+                token_program: token::ID,
+                system_program: system_program::ID,
+                instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+                proof_stats: None,
+                rebate_ledger: None,
+                migration_window: None,
+                rent_reserve: None,
+                rent_advance: None,
+                anonymity_stats: None,
+                fee_payer: None,
+                treasury_token_account: None,
+                risk_tiers: None,
+                withdrawal_window: None,
+            },
+            instruction::WithdrawV2 {
+                proof_data: proof_bytes(&circuit.prove(&inputs, salt)),
+                merkle_root: root,
+                asset_id: pool.asset_id,
+                nullifier_hash_0: self.nullifiers[0],
+                nullifier_hash_1: self.nullifiers[1],
+                change_commitment: self.change_commitment,
+                recipient: relayer,
+                amount: self.amount,
+                relayer_fee: self.relayer_fee,
+            },
+        )
+    }
+}
+
+/// Length of the `proof_data` argument of a `withdraw_v2` instruction
+fn proof_bytes_len(ix: &Instruction) -> usize {
+    u32::from_le_bytes(ix.data[8..12].try_into().unwrap()) as usize
+}
+
+async fn set_withdraw_v2_vk(pool: &mut Pool, circuit: &Trapdoor) {
+    let authority = pool.authority.pubkey();
+    let vk = circuit.vk();
+    let (vk_account, _) = VerificationKeyAccountV2::find_pda(
+        &psol_privacy_v2::ID,
+        &pool.pool_config,
+        ProofType::WithdrawV2,
+    );
+    let set = ix(
+        accounts::SetVerificationKeyData {
+            authority,
+            pool_config: pool.pool_config,
+            vk_account,
+            circuit_descriptor: None,
+            merkle_tree: pool.merkle_tree,
+        },
+        instruction::SetVerificationKeyData {
+            proof_type: ProofType::WithdrawV2,
+            vk_alpha_g1: g1(&vk.vk_alpha_1),
+            vk_beta_g2: g2(&vk.vk_beta_2),
+            vk_gamma_g2: g2(&vk.vk_gamma_2),
+            vk_delta_g2: g2(&vk.vk_delta_2),
+            vk_ic: vk.ic.iter().map(|p| g1(p)).collect(),
+            ceremony_transcript_hash: [0u8; 32],
+            ceremony_contributors: 0,
+        },
+    );
+    let create = create_vk_ix(authority, pool.pool_config, ProofType::WithdrawV2);
+    let signer = pool.authority.insecure_clone();
+    pool.send_all(&[create, set], &[&signer])
+        .await
+        .expect("set_verification_key_data");
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_withdraw_v2_queues_change() {
+    let f = fixture();
+    let deposited: u64 = f.amount.parse().unwrap();
+    let circuit = Trapdoor::new(19 << 8, WithdrawV2PublicInputs::COUNT);
+    let mut pool = Pool::start(&f, deposited, |_| {}).await;
+    set_withdraw_v2_vk(&mut pool, &circuit).await;
+
+    pool.send_as_authority(pool.deposit_ix(deposited, hex32(&f.commitment), &f.deposit.proof))
+        .await
+        .expect("deposit_masp");
+    pool.send_as_authority(pool.batch_ix(1, None))
+        .await
+        .expect("batch_process_deposits");
+    let root = pool
+        .account::<MerkleTreeV2>(pool.merkle_tree)
+        .await
+        .current_root;
+
+    let withdrawal = Withdrawal {
+        nullifiers: [[1u8; 32], [2u8; 32]],
+        change_commitment: [3u8; 32],
+        amount: deposited / 2,
+        relayer_fee: 0,
+    };
+
+    // A proof of different public inputs is rejected
+    let mut forged = withdrawal.ix(&pool, &circuit, root, 1);
+    let other = Withdrawal {
+        amount: deposited,
+        ..withdrawal
+    }
+    .ix(&pool, &circuit, root, 1);
+    // Anchor discriminator, then the proof's u32 length prefix
+    let proof = 12..12 + proof_bytes_len(&other);
+    forged.data[proof.clone()].copy_from_slice(&other.data[proof]);
+    assert_program_error(
+        pool.send_as_authority(forged).await,
+        PrivacyErrorV2::InvalidProof,
+    );
+
+    pool.send_as_authority(withdrawal.ix(&pool, &circuit, root, 2))
+        .await
+        .expect("withdraw_v2");
+
+    assert_eq!(pool.token_balance(pool.user_token).await, deposited / 2);
+    assert_eq!(
+        pool.token_balance(pool.vault_token).await,
+        deposited - deposited / 2
+    );
+    for nullifier_hash in &withdrawal.nullifiers {
+        let (spent, _) =
+            SpentNullifierV2::find_pda(&psol_privacy_v2::ID, &pool.pool_config, nullifier_hash);
+        let record: SpentNullifierV2 = pool.account(spent).await;
+        assert_eq!(record.get_spend_context(), Some(SpendContext::WithdrawV2));
+        assert_eq!(record.nullifier_hash, *nullifier_hash);
+    }
+    let buffer: PendingDepositsBuffer = pool.account(pool.pending_buffer).await;
+    assert_eq!(buffer.total_pending, 1);
+    assert_eq!(
+        buffer.iter().next().unwrap().commitment,
+        withdrawal.change_commitment
+    );
+
+    // A spent second nullifier cannot be reused alongside a fresh one
+    let reuse = Withdrawal {
+        nullifiers: [[4u8; 32], withdrawal.nullifiers[1]],
+        change_commitment: [5u8; 32],
+        amount: deposited / 4,
+        ..withdrawal
+    };
+    assert_program_error(
+        pool.send_as_authority(reuse.ix(&pool, &circuit, root, 3))
+            .await,
+        PrivacyErrorV2::NullifierAlreadySpent,
+    );
+
+    // The next batch inserts the change note
+    pool.send_as_authority(pool.batch_ix(1, None))
+        .await
+        .expect("batch_process_deposits");
+    let tree: MerkleTreeV2 = pool.account(pool.merkle_tree).await;
+    assert_eq!(tree.next_leaf_index, 2);
+}
+
+fn receipt_ix(
+    pool: &Pool,
+    relayer: Pubkey,
+    nullifier_hash: [u8; 32],
+    note_receipt: [u8; 8],
+) -> Instruction {
+    let (spent_nullifier, _) =
+        SpentNullifierV2::find_pda(&psol_privacy_v2::ID, &pool.pool_config, &nullifier_hash);
+    ix(
+        accounts::RecordNoteReceipt {
+            relayer,
+            pool_config: pool.pool_config,
+            spent_nullifier,
+        },
+        instruction::RecordNoteReceipt {
+            nullifier_hash,
+            note_receipt,
+        },
+    )
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_note_receipts() {
+    let f = fixture();
+    let deposited: u64 = f.amount.parse().unwrap();
+    let circuit = Trapdoor::new(21 << 8, WithdrawV2PublicInputs::COUNT);
+    let mut pool = Pool::start(&f, deposited, |_| {}).await;
+    set_withdraw_v2_vk(&mut pool, &circuit).await;
+    let relayer = pool.authority.pubkey();
+
+    pool.send_as_authority(pool.deposit_ix(deposited, hex32(&f.commitment), &f.deposit.proof))
+        .await
+        .expect("deposit_masp");
+    pool.send_as_authority(pool.batch_ix(1, None))
+        .await
+        .expect("batch_process_deposits");
+    let root = pool
+        .account::<MerkleTreeV2>(pool.merkle_tree)
+        .await
+        .current_root;
+
+    let withdrawal = Withdrawal {
+        nullifiers: [[1u8; 32], [2u8; 32]],
+        change_commitment: [3u8; 32],
+        amount: deposited / 2,
+        relayer_fee: 0,
+    };
+    pool.send_as_authority(withdrawal.ix(&pool, &circuit, root, 1))
+        .await
+        .expect("withdraw_v2");
+    let [first, second] = withdrawal.nullifiers;
+
+    // Only the relayer that spent the nullifier may record its receipt
+    let outsider = Keypair::new();
+    pool.ctx.set_account(
+        &outsider.pubkey(),
+        &Account::new(10_000_000_000, 0, &system_program::ID).into(),
+    );
+    assert_program_error(
+        pool.send(
+            receipt_ix(&pool, outsider.pubkey(), first, [7u8; 8]),
+            &[&outsider],
+        )
+        .await,
+        PrivacyErrorV2::Unauthorized,
+    );
+
+    pool.send_as_authority(receipt_ix(&pool, relayer, first, [7u8; 8]))
+        .await
+        .expect("record_note_receipt");
+    let (spent, _) = SpentNullifierV2::find_pda(&psol_privacy_v2::ID, &pool.pool_config, &first);
+    let record: SpentNullifierV2 = pool.account(spent).await;
+    assert_eq!(record.note_receipt, [7u8; 8]);
+    assert_program_error(
+        pool.send_as_authority(receipt_ix(&pool, relayer, first, [8u8; 8]))
+            .await,
+        PrivacyErrorV2::NoteReceiptAlreadyRecorded,
+    );
+
+    // Privacy-strict pools record none
+    pool.send_as_authority(ix(
+        accounts::SetPrivacyStrict {
+            authority: relayer,
+            pool_config: pool.pool_config,
+        },
+        instruction::SetPrivacyStrict { enabled: true },
+    ))
+    .await
+    .expect("set_privacy_strict");
+    assert_program_error(
+        pool.send_as_authority(receipt_ix(&pool, relayer, second, [9u8; 8]))
+            .await,
+        PrivacyErrorV2::NoteReceiptsDisabled,
+    );
+    let (spent, _) = SpentNullifierV2::find_pda(&psol_privacy_v2::ID, &pool.pool_config, &second);
+    let record: SpentNullifierV2 = pool.account(spent).await;
+    assert!(!record.has_note_receipt());
+}
+
+/// `ix` with the optional `anonymity_stats` account, followed by
+/// `trailing` more accounts, supplied
+fn with_anonymity_stats(
+    mut ix: Instruction,
+    anonymity_stats: Pubkey,
+    trailing: usize,
+) -> Instruction {
+    let slot = ix.accounts.len() - 1 - trailing;
+    ix.accounts[slot] = AccountMeta::new(anonymity_stats, false);
+    ix
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_anonymity_stats() {
+    let f = fixture();
+    let deposited: u64 = f.amount.parse().unwrap();
+    let circuit = Trapdoor::new(23 << 8, WithdrawV2PublicInputs::COUNT);
+    let mut pool = Pool::start(&f, deposited, |_| {}).await;
+    set_withdraw_v2_vk(&mut pool, &circuit).await;
+    let authority = pool.authority.pubkey();
+
+    let (anonymity_stats, _) =
+        AnonymityStats::find_pda(&psol_privacy_v2::ID, &pool.pool_config, &pool.asset_id);
+    pool.send_as_authority(ix(
+        accounts::InitAnonymityStats {
+            authority,
+            pool_config: pool.pool_config,
+            asset_vault: pool.asset_vault,
+            anonymity_stats,
+            system_program: system_program::ID,
+        },
+        instruction::InitAnonymityStats {},
+    ))
+    .await
+    .expect("init_anonymity_stats");
+    let config: PoolConfigV2 = pool.account(pool.pool_config).await;
+    assert!(config.anonymity_stats_enabled);
+
+    // Leaving the statistics out is refused from now on
+    assert_program_error(
+        pool.send_as_authority(pool.deposit_ix(deposited, hex32(&f.commitment), &f.deposit.proof))
+            .await,
+        PrivacyErrorV2::AnonymityStatsRequired,
+    );
+
+    pool.send_as_authority(with_anonymity_stats(
+        pool.deposit_ix(deposited, hex32(&f.commitment), &f.deposit.proof),
+        anonymity_stats,
+        2,
+    ))
+    .await
+    .expect("deposit_masp");
+    pool.send_as_authority(pool.batch_ix(1, None))
+        .await
+        .expect("batch_process_deposits");
+    let root = pool
+        .account::<MerkleTreeV2>(pool.merkle_tree)
+        .await
+        .current_root;
+
+    let withdrawal = Withdrawal {
+        nullifiers: [[1u8; 32], [2u8; 32]],
+        change_commitment: [3u8; 32],
+        amount: deposited / 2,
+        relayer_fee: 0,
+    };
+    pool.send_as_authority(with_anonymity_stats(
+        withdrawal.ix(&pool, &circuit, root, 1),
+        anonymity_stats,
+        4,
+    ))
+    .await
+    .expect("withdraw_v2");
+
+    // Withdrawn in the epoch it was deposited: latency bucket 0
+    let stats: AnonymityStats = pool.account(anonymity_stats).await;
+    assert_eq!(stats.asset_id, pool.asset_id);
+    assert_eq!(stats.latency_histogram(), [1, 0, 0, 0, 0, 0]);
+    let epoch = stats
+        .epochs
+        .iter()
+        .find(|e| e.deposits > 0)
+        .expect("deposit epoch");
+    assert_eq!(
+        (epoch.deposits, epoch.withdrawals, epoch.outstanding),
+        (1, 1, 0)
+    );
+
+    // Another asset's statistics cannot stand in for this one
+    let (wrong, _) = AnonymityStats::find_pda(&psol_privacy_v2::ID, &pool.pool_config, &[9u8; 32]);
+    assert!(pool
+        .send_as_authority(with_anonymity_stats(
+            Withdrawal {
+                nullifiers: [[4u8; 32], [5u8; 32]],
+                change_commitment: [6u8; 32],
+                amount: deposited / 4,
+                ..withdrawal
+            }
+            .ix(&pool, &circuit, root, 2),
+            wrong,
+            2,
+        ))
+        .await
+        .is_err());
+}
@@ -0,0 +1,330 @@
+//! Sealed withdrawals against the SBF build
+//!
+//! Run with:
+//!   anchor build
+//!   SBF_OUT_DIR=$PWD/target/deploy \
+//!     cargo test -p psol-privacy-v2 --test withdrawal_commit
+//!
+//! A user seals a withdrawal's public inputs with `commit_withdrawal`; the
+//! relayer executes the proof through `withdraw_masp` with the commit
+//! attached, and can neither skip the commit nor change what was sealed.
+//! The committer can cancel instead.
+//!
+//! Withdrawals use the native SOL vault so trapdoor VKs can prove them.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token;
+use psol_privacy_v2::crypto::{DepositPublicInputs, WithdrawPublicInputs};
+use psol_privacy_v2::error::PrivacyErrorV2;
+use psol_privacy_v2::state::asset_vault::NATIVE_SOL_ASSET_ID;
+use psol_privacy_v2::state::{
+    withdrawal_commit_hash, withdrawal_commit_pin_hash, AssetVault, MerkleTreeV2,
+    SpentNullifierV2, WithdrawalCommit,
+};
+use psol_privacy_v2::{accounts, instruction};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::signature::{Keypair, Signer};
+
+use common::pool::{assert_program_error, global_config, ix, Pool};
+use common::trapdoor::Trapdoor;
+use common::{fixture, proof_bytes};
+
+const AMOUNT: u64 = LAMPORTS_PER_SOL;
+const NULLIFIER_HASH: [u8; 32] = [5u8; 32];
+
+/// Withdrawal of the whole note to `recipient`, proved with
+/// `public_data_hash`
+fn withdraw_ix(
+    pool: &Pool,
+    trapdoor: &Trapdoor,
+    asset_vault: Pubkey,
+    root: [u8; 32],
+    recipient: Pubkey,
+    public_data_hash: [u8; 32],
+    withdrawal_commit: Option<Pubkey>,
+) -> Instruction {
+    let relayer = pool.authority.pubkey();
+    let inputs = WithdrawPublicInputs::new(
+        root,
+        NULLIFIER_HASH,
+        NATIVE_SOL_ASSET_ID,
+        recipient,
+        AMOUNT,
+        relayer,
+        0,
+        public_data_hash,
+    )
+    .to_field_elements();
+    let (spent_nullifier, _) =
+        SpentNullifierV2::find_pda(&psol_privacy_v2::ID, &pool.pool_config, &NULLIFIER_HASH);
+    ix(
+        accounts::WithdrawMasp {
+            relayer,
+            pool_config: pool.pool_config,
+            global_config: global_config(),
+            merkle_tree: pool.merkle_tree,
+            vk_account: pool.withdraw_vk,
+            asset_vault,
+            vault_token_account: None,
+            recipient_token_account: None,
+            relayer_token_account: None,
+            spent_nullifier,
+            relayer_registry: pool.relayer_registry,
+            relayer_node: None,
+            yield_registry: None,
+            token_program: spl_token::id(),
+            system_program: system_program::ID,
+            instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+            proof_stats: None,
+            rebate_ledger: None,
+            migration_window: None,
+            deferred_events: None,
+            recipient_wallet: Some(recipient),
+            rent_reserve: None,
+            rent_advance: None,
+            mint: None,
+            anonymity_stats: None,
+            parameter_snapshot: None,
+            recipient_ata: None,
+            associated_token_program: None,
+            fee_payer: None,
+            treasury_token_account: None,
+            withdrawal_commit,
+            fee_quote: None,
+            risk_tiers: None,
+            withdrawal_window: None,
+        },
+        instruction::WithdrawMasp {
+            proof_data: proof_bytes(&trapdoor.prove(&inputs, 2)),
+            merkle_root: root,
+            nullifier_hash: NULLIFIER_HASH,
+            recipient,
+            amount: AMOUNT,
+            asset_id: NATIVE_SOL_ASSET_ID,
+            relayer_fee: 0,
+        },
+    )
+}
+
+fn commit_ix(pool: &Pool, committer: Pubkey, commit_hash: [u8; 32]) -> (Instruction, Pubkey) {
+    let (withdrawal_commit, _) =
+        WithdrawalCommit::find_pda(&psol_privacy_v2::ID, &pool.pool_config, &commit_hash);
+    let ix = ix(
+        accounts::CommitWithdrawal {
+            committer,
+            pool_config: pool.pool_config,
+            withdrawal_commit,
+            system_program: system_program::ID,
+        },
+        instruction::CommitWithdrawal { commit_hash },
+    );
+    (ix, withdrawal_commit)
+}
+
+/// A funded wallet with no other history, as a user sealing a withdrawal
+fn fresh_wallet(pool: &mut Pool) -> Keypair {
+    let wallet = Keypair::new();
+    pool.ctx.set_account(
+        &wallet.pubkey(),
+        &Account::new(LAMPORTS_PER_SOL, 0, &system_program::ID).into(),
+    );
+    wallet
+}
+
+/// Register the native SOL asset and insert one note; returns the vault
+/// and the root containing the note
+async fn native_note(pool: &mut Pool, deposit: &Trapdoor) -> (Pubkey, [u8; 32]) {
+    let authority = pool.authority.pubkey();
+    let (native_vault, _) = AssetVault::find_pda(
+        &psol_privacy_v2::ID,
+        &pool.pool_config,
+        &NATIVE_SOL_ASSET_ID,
+    );
+    pool.send_as_authority(ix(
+        accounts::RegisterNativeSolAsset {
+            authority,
+            pool_config: pool.pool_config,
+            asset_vault: native_vault,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterNativeSolAsset {},
+    ))
+    .await
+    .expect("register_native_sol_asset");
+
+    let commitment = [3u8; 32];
+    let inputs =
+        DepositPublicInputs::new(commitment, AMOUNT, NATIVE_SOL_ASSET_ID).to_field_elements();
+    pool.send_as_authority(ix(
+        accounts::DepositMasp {
+            depositor: authority,
+            pool_config: pool.pool_config,
+            global_config: global_config(),
+            authority,
+            merkle_tree: pool.merkle_tree,
+            pending_buffer: pool.pending_buffer,
+            asset_vault: native_vault,
+            vault_token_account: None,
+            user_token_account: None,
+            mint: None,
+            deposit_vk: pool.deposit_vk,
+            token_program: spl_token::id(),
+            system_program: system_program::ID,
+            instructions_sysvar: solana_sdk::sysvar::instructions::ID,
+            proof_stats: None,
+            anonymity_stats: None,
+            commitment_reservation: pool.commitment_reservation(&commitment),
+            risk_tiers: None,
+        },
+        instruction::DepositMasp {
+            amount: AMOUNT,
+            commitment,
+            asset_id: NATIVE_SOL_ASSET_ID,
+            proof_data: proof_bytes(&deposit.prove(&inputs, 1)),
+            encrypted_note: None,
+        },
+    ))
+    .await
+    .expect("native deposit");
+    pool.send_as_authority(pool.batch_ix(1, None))
+        .await
+        .expect("batch_process_deposits");
+    let root = pool
+        .account::<MerkleTreeV2>(pool.merkle_tree)
+        .await
+        .current_root;
+    (native_vault, root)
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_sealed_withdrawal_executes_only_the_committed_inputs() {
+    let f = fixture();
+    let deposit = Trapdoor::new(38 << 8, 3);
+    let withdraw = Trapdoor::new((38 << 8) | 1, 8);
+    let mut pool = Pool::start_with_vks(&f, 0, &deposit.vk(), &withdraw.vk(), |_| {}).await;
+    let (native_vault, root) = native_note(&mut pool, &deposit).await;
+
+    let recipient = Pubkey::new_unique();
+    let commit_hash = withdrawal_commit_hash(
+        &pool.pool_config,
+        &root,
+        &NULLIFIER_HASH,
+        &recipient,
+        AMOUNT,
+        &NATIVE_SOL_ASSET_ID,
+        0,
+    );
+    let sealed_pin = withdrawal_commit_pin_hash(&commit_hash);
+    let withdraw_to = |pool: &Pool, recipient, withdrawal_commit| {
+        withdraw_ix(
+            pool,
+            &withdraw,
+            native_vault,
+            root,
+            recipient,
+            sealed_pin,
+            withdrawal_commit,
+        )
+    };
+
+    // The sealed proof is not valid without its commit
+    assert_program_error(
+        pool.send_as_authority(withdraw_to(&pool, recipient, None))
+            .await,
+        PrivacyErrorV2::InvalidProof,
+    );
+
+    let user = fresh_wallet(&mut pool);
+    let (commit, withdrawal_commit) = commit_ix(&pool, user.pubkey(), commit_hash);
+    pool.send(commit, &[&user]).await.expect("commit_withdrawal");
+    let sealed: WithdrawalCommit = pool.account(withdrawal_commit).await;
+    assert_eq!(sealed.committer, user.pubkey());
+    assert_eq!(sealed.commit_hash, commit_hash);
+
+    // Nothing but the sealed inputs goes through the commit
+    let thief = Pubkey::new_unique();
+    assert_program_error(
+        pool.send_as_authority(withdraw_to(&pool, thief, Some(withdrawal_commit)))
+            .await,
+        PrivacyErrorV2::WithdrawalCommitMismatch,
+    );
+
+    pool.send_as_authority(withdraw_to(&pool, recipient, Some(withdrawal_commit)))
+        .await
+        .expect("sealed withdraw_masp");
+    let paid = pool
+        .ctx
+        .banks_client
+        .get_account(recipient)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(paid.lamports, AMOUNT);
+    assert!(pool
+        .ctx
+        .banks_client
+        .get_account(withdrawal_commit)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+#[cfg_attr(not(sbf_build), ignore = "needs the SBF build of the program (see module docs)")]
+async fn test_committer_cancels_sealed_withdrawal() {
+    let f = fixture();
+    let deposit = Trapdoor::new(39 << 8, 3);
+    let withdraw = Trapdoor::new((39 << 8) | 1, 8);
+    let mut pool = Pool::start_with_vks(&f, 0, &deposit.vk(), &withdraw.vk(), |_| {}).await;
+
+    let user = fresh_wallet(&mut pool);
+    let (commit, withdrawal_commit) = commit_ix(&pool, user.pubkey(), [0x42; 32]);
+    pool.send(commit, &[&user]).await.expect("commit_withdrawal");
+
+    let cancel = |committer: Pubkey| {
+        ix(
+            accounts::CancelWithdrawalCommit {
+                committer,
+                withdrawal_commit,
+            },
+            instruction::CancelWithdrawalCommit {},
+        )
+    };
+    let impostor = fresh_wallet(&mut pool);
+    assert_program_error(
+        pool.send(cancel(impostor.pubkey()), &[&impostor]).await,
+        PrivacyErrorV2::Unauthorized,
+    );
+
+    let before = pool
+        .ctx
+        .banks_client
+        .get_balance(user.pubkey())
+        .await
+        .unwrap();
+    pool.send(cancel(user.pubkey()), &[&user])
+        .await
+        .expect("cancel_withdrawal_commit");
+    assert!(pool
+        .ctx
+        .banks_client
+        .get_account(withdrawal_commit)
+        .await
+        .unwrap()
+        .is_none());
+    // Rent back, net of the transaction fee
+    assert!(
+        pool.ctx
+            .banks_client
+            .get_balance(user.pubkey())
+            .await
+            .unwrap()
+            > before
+    );
+}
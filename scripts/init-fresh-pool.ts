@@ -8,6 +8,15 @@ import { PublicKey, SystemProgram, Keypair } from "@solana/web3.js";
 import * as fs from "fs";
 import * as path from "path";
 
+/** Pool id (part of the pool config seeds); set POOL_ID to open another pool */
+const POOL_ID = BigInt(process.env.POOL_ID ?? "0");
+
+function poolIdSeed(poolId: bigint): Buffer {
+  const seed = Buffer.alloc(8);
+  seed.writeBigUInt64LE(poolId);
+  return seed;
+}
+
 const HELIUS_RPC = "https://devnet.helius-rpc.com/?api-key=2f0116cb-6972-4a3d-bb9e-43de29619343";
 const PROGRAM_ID = new PublicKey("BmtMrkgvVML9Gk7Bt6JRqweHAwW69oFTohaBRaLbgqpb");
 const TREE_DEPTH = 20;
@@ -41,7 +50,7 @@ async function main() {
 
   // Derive PDAs
   const [poolConfig] = PublicKey.findProgramAddressSync(
-    [Buffer.from("pool_v2"), authorityKeypair.publicKey.toBuffer()],
+    [Buffer.from("pool_v2"), authorityKeypair.publicKey.toBuffer(), poolIdSeed(POOL_ID)],
     PROGRAM_ID
   );
 
@@ -74,7 +83,7 @@ async function main() {
 
   try {
     const tx1 = await program.methods
-      .initializePoolV2(TREE_DEPTH)
+      .initializePoolV2(TREE_DEPTH, 100, false, new anchor.BN(POOL_ID.toString()))
       .accounts({
         authority: authorityKeypair.publicKey,
         poolConfig: poolConfig,
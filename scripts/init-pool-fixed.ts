@@ -8,6 +8,15 @@ import { PublicKey, Keypair, SystemProgram, Transaction, sendAndConfirmTransacti
 import * as fs from "fs";
 import * as path from "path";
 
+/** Pool id (part of the pool config seeds); set POOL_ID to open another pool */
+const POOL_ID = BigInt(process.env.POOL_ID ?? "0");
+
+function poolIdSeed(poolId: bigint): Buffer {
+  const seed = Buffer.alloc(8);
+  seed.writeBigUInt64LE(poolId);
+  return seed;
+}
+
 const PROGRAM_ID = new PublicKey("BmtMrkgvVML9Gk7Bt6JRqweHAwW69oFTohaBRaLbgqpb");
 const TREE_DEPTH = 20;
 const ROOT_HISTORY_SIZE = 100;  // MISSING IN YOUR ORIGINAL SCRIPT!
@@ -41,7 +50,7 @@ async function main() {
 
   // Derive PDAs
   const [poolConfig] = PublicKey.findProgramAddressSync(
-    [Buffer.from("pool_v2"), authorityKeypair.publicKey.toBuffer()],
+    [Buffer.from("pool_v2"), authorityKeypair.publicKey.toBuffer(), poolIdSeed(POOL_ID)],
     PROGRAM_ID
   );
   const [merkleTree] = PublicKey.findProgramAddressSync(
@@ -78,7 +87,7 @@ async function main() {
     // KEY FIX: Use snake_case for account names (matches IDL)
     // KEY FIX: Pass BOTH arguments (tree_depth, root_history_size)
     const ix1 = await program.methods
-      .initializePoolV2(TREE_DEPTH, ROOT_HISTORY_SIZE)
+      .initializePoolV2(TREE_DEPTH, ROOT_HISTORY_SIZE, false, new anchor.BN(POOL_ID.toString()))
       .accounts({
         authority: authorityKeypair.publicKey,
         pool_config: poolConfig,           // snake_case!
@@ -14,6 +14,15 @@ import {
 } from "@solana/web3.js";
 import * as fs from "fs";
 
+/** Pool id (part of the pool config seeds); set POOL_ID to open another pool */
+const POOL_ID = BigInt(process.env.POOL_ID ?? "0");
+
+function poolIdSeed(poolId: bigint): Buffer {
+  const seed = Buffer.alloc(8);
+  seed.writeBigUInt64LE(poolId);
+  return seed;
+}
+
 const PROGRAM_ID = new PublicKey("BmtMrkgvVML9Gk7Bt6JRqweHAwW69oFTohaBRaLbgqpb");
 
 // From IDL: initialize_pool_v2 discriminator
@@ -45,7 +54,7 @@ async function main() {
 
   // Derive PDAs
   const [poolConfig] = PublicKey.findProgramAddressSync(
-    [Buffer.from("pool_v2"), authority.publicKey.toBuffer()],
+    [Buffer.from("pool_v2"), authority.publicKey.toBuffer(), poolIdSeed(POOL_ID)],
     PROGRAM_ID
   );
   
@@ -69,7 +78,7 @@ async function main() {
   const existingPool = await connection.getAccountInfo(poolConfig);
   if (existingPool) {
     console.log("⚠️  Pool already exists at this address!");
-    console.log("   Use a different authority wallet for a new pool.\n");
+    console.log("   Set POOL_ID (or use a different authority wallet) for a new pool.\n");
     return;
   }
 
@@ -77,10 +86,13 @@ async function main() {
   console.log("🔧 Step 1: Initializing Pool + Merkle Tree...");
   
   // Build instruction data: discriminator (8) + tree_depth (1) + root_history_size (2)
-  const initPoolData = Buffer.alloc(11);
+  // + compliance_recovery (1) + pool_id (8)
+  const initPoolData = Buffer.alloc(20);
   INIT_POOL_DISCRIMINATOR.copy(initPoolData, 0);
   initPoolData.writeUInt8(TREE_DEPTH, 8);
   initPoolData.writeUInt16LE(ROOT_HISTORY_SIZE, 9);
+  initPoolData.writeUInt8(0, 11); // no compliance recovery
+  initPoolData.writeBigUInt64LE(POOL_ID, 12);
 
   const initPoolIx = new TransactionInstruction({
     programId: PROGRAM_ID,
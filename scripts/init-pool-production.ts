@@ -7,6 +7,15 @@ import { PublicKey, Keypair, SystemProgram, Transaction, sendAndConfirmTransacti
 import * as fs from "fs";
 import * as path from "path";
 
+/** Pool id (part of the pool config seeds); set POOL_ID to open another pool */
+const POOL_ID = BigInt(process.env.POOL_ID ?? "0");
+
+function poolIdSeed(poolId: bigint): Buffer {
+  const seed = Buffer.alloc(8);
+  seed.writeBigUInt64LE(poolId);
+  return seed;
+}
+
 const PROGRAM_ID = new PublicKey("BmtMrkgvVML9Gk7Bt6JRqweHAwW69oFTohaBRaLbgqpb");
 const TREE_DEPTH = 20;
 
@@ -34,7 +43,7 @@ async function main() {
   const program = new anchor.Program(idl, provider);
 
   const [poolConfig] = PublicKey.findProgramAddressSync(
-    [Buffer.from("pool_v2"), authorityKeypair.publicKey.toBuffer()],
+    [Buffer.from("pool_v2"), authorityKeypair.publicKey.toBuffer(), poolIdSeed(POOL_ID)],
     PROGRAM_ID
   );
 
@@ -67,7 +76,7 @@ async function main() {
   try {
     // Build instruction manually
     const ix = await program.methods
-      .initializePoolV2(TREE_DEPTH)
+      .initializePoolV2(TREE_DEPTH, 100, false, new anchor.BN(POOL_ID.toString()))
       .accounts({
         authority: authorityKeypair.publicKey,
         pool_config: poolConfig,
@@ -2,6 +2,15 @@ import { Connection, Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
 import * as fs from "fs";
 import * as anchor from "@coral-xyz/anchor";
 
+/** Pool id (part of the pool config seeds); set POOL_ID to open another pool */
+const POOL_ID = BigInt(process.env.POOL_ID ?? "0");
+
+function poolIdSeed(poolId: bigint): Buffer {
+  const seed = Buffer.alloc(8);
+  seed.writeBigUInt64LE(poolId);
+  return seed;
+}
+
 const PROGRAM_ID = new PublicKey("BmtMrkgvVML9Gk7Bt6JRqweHAwW69oFTohaBRaLbgqpb");
 
 async function main() {
@@ -16,7 +25,7 @@ async function main() {
 
   // Derive PDAs
   const [poolConfig] = PublicKey.findProgramAddressSync(
-    [Buffer.from("pool_v2"), authority.publicKey.toBuffer()],
+    [Buffer.from("pool_v2"), authority.publicKey.toBuffer(), poolIdSeed(POOL_ID)],
     PROGRAM_ID
   );
   const [merkleTree] = PublicKey.findProgramAddressSync(
@@ -50,7 +59,7 @@ async function main() {
   
   // Try with accounts() instead of accountsStrict()
   const tx1 = await (program.methods as any)
-    .initializePoolV2(20)
+    .initializePoolV2(20, 100, false, new anchor.BN(POOL_ID.toString()))
     .accounts({
       authority: authority.publicKey,
       poolConfig: poolConfig,
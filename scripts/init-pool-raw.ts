@@ -1,6 +1,15 @@
 import { Connection, Keypair, PublicKey, SystemProgram, Transaction, sendAndConfirmTransaction, TransactionInstruction, ComputeBudgetProgram } from "@solana/web3.js";
 import * as fs from "fs";
 
+/** Pool id (part of the pool config seeds); set POOL_ID to open another pool */
+const POOL_ID = BigInt(process.env.POOL_ID ?? "0");
+
+function poolIdSeed(poolId: bigint): Buffer {
+  const seed = Buffer.alloc(8);
+  seed.writeBigUInt64LE(poolId);
+  return seed;
+}
+
 const PROGRAM_ID = new PublicKey("BmtMrkgvVML9Gk7Bt6JRqweHAwW69oFTohaBRaLbgqpb");
 
 const INIT_POOL_DISCRIMINATOR = Buffer.from([0xcf, 0x2d, 0x57, 0xf2, 0x1b, 0x3f, 0xcc, 0x43]);
@@ -20,7 +29,7 @@ async function main() {
   console.log("Balance:", await connection.getBalance(authority.publicKey) / 1e9, "SOL");
 
   const [poolConfig] = PublicKey.findProgramAddressSync(
-    [Buffer.from("pool_v2"), authority.publicKey.toBuffer()],
+    [Buffer.from("pool_v2"), authority.publicKey.toBuffer(), poolIdSeed(POOL_ID)],
     PROGRAM_ID
   );
   const [merkleTree] = PublicKey.findProgramAddressSync(
@@ -43,13 +52,15 @@ async function main() {
     return;
   }
 
-  // Args: tree_depth (u8), root_history_size (u16)
+  // Args: tree_depth (u8), root_history_size (u16), compliance_recovery (bool), pool_id (u64)
   const treeDepth = 20;
   const rootHistorySize = 100;
   
-  const argsBuffer = Buffer.alloc(3); // 1 byte u8 + 2 bytes u16
+  const argsBuffer = Buffer.alloc(12); // u8 + u16 + bool + u64
   argsBuffer.writeUInt8(treeDepth, 0);
   argsBuffer.writeUInt16LE(rootHistorySize, 1);
+  argsBuffer.writeUInt8(0, 3); // no compliance recovery
+  argsBuffer.writeBigUInt64LE(POOL_ID, 4);
   
   const initPoolData = Buffer.concat([INIT_POOL_DISCRIMINATOR, argsBuffer]);
 
@@ -458,6 +458,7 @@ async function submitWithRetry(
           proof: Buffer.from(proof),
           newRoot: newRootBytes,
           batchSize: batchSize,
+          expectedBatchSeq: null,
         })
         .accountsStrict({
           authority: authority.publicKey,
@@ -424,6 +424,7 @@ async function main() {
       proof: Buffer.from(proof),
       newRoot: newRootBytes,
       batchSize: batchSize,
+      expectedBatchSeq: null,
     })
     .accounts({
       authority: provider.wallet.publicKey,
@@ -132,19 +132,55 @@ async function main() {
     console.log("✓ Relayer already registered");
   } else {
     try {
+      // Registration appends to the relayer index shard for the next position
+      const registryState: any = await (program.account as any).relayerRegistry.fetch(relayerRegistry);
+      const shardIndex = Math.floor(registryState.relayerCount / 64);
+      const shardSeed = Buffer.alloc(4);
+      shardSeed.writeUInt32LE(shardIndex);
+      const [relayerIndexShard] = PublicKey.findProgramAddressSync(
+        [Buffer.from("relayer_index"), relayerRegistry.toBuffer(), shardSeed],
+        PROGRAM_ID
+      );
+      const [relayerTombstone] = PublicKey.findProgramAddressSync(
+        [Buffer.from("relayer_tombstone"), relayerRegistry.toBuffer(), authority.publicKey.toBuffer()],
+        PROGRAM_ID
+      );
+
+      const tx = new Transaction();
+      tx.add(ComputeBudgetProgram.setComputeUnitLimit({ units: 300_000 }));
+      if (!(await connection.getAccountInfo(relayerIndexShard))) {
+        tx.add(
+          await program.methods
+            .initRelayerIndexShard(shardIndex)
+            .accountsStrict({
+              payer: authority.publicKey,
+              poolConfig: POOL_CONFIG,
+              relayerRegistry: relayerRegistry,
+              relayerIndexShard: relayerIndexShard,
+              systemProgram: SystemProgram.programId,
+            })
+            .instruction()
+        );
+      }
+
       const ix = await program.methods
-        .registerRelayer(100, "")
+        .registerRelayer(100, "", {
+          endpointHash: Array(32).fill(0),
+          noteEncryptionPubkey: Array(32).fill(0),
+          supportedAssets: new BN(0),
+          regionCode: 0,
+        })
         .accountsStrict({
           operator: authority.publicKey,
           poolConfig: POOL_CONFIG,
           relayerRegistry: relayerRegistry,
           relayerNode: relayerNode,
           systemProgram: SystemProgram.programId,
+          relayerIndexShard: relayerIndexShard,
+          relayerTombstone: relayerTombstone,
         })
         .instruction();
 
-      const tx = new Transaction();
-      tx.add(ComputeBudgetProgram.setComputeUnitLimit({ units: 300_000 }));
       tx.add(ix);
       tx.feePayer = authority.publicKey;
       tx.recentBlockhash = (await connection.getLatestBlockhash()).blockhash;
@@ -22,6 +22,15 @@ import * as fs from "fs";
 import * as path from "path";
 import * as crypto from "crypto";
 
+/** Pool id (part of the pool config seeds); set POOL_ID to open another pool */
+const POOL_ID = BigInt(process.env.POOL_ID ?? "0");
+
+function poolIdSeed(poolId: bigint): Buffer {
+  const seed = Buffer.alloc(8);
+  seed.writeBigUInt64LE(poolId);
+  return seed;
+}
+
 // =============================================================================
 // CONFIGURATION
 // =============================================================================
@@ -120,7 +129,7 @@ async function main() {
 
   // Derive PDAs
   const [poolConfig] = PublicKey.findProgramAddressSync(
-    [Buffer.from("pool_v2"), authority.publicKey.toBuffer()],
+    [Buffer.from("pool_v2"), authority.publicKey.toBuffer(), poolIdSeed(POOL_ID)],
     PROGRAM_ID
   );
   const [merkleTree] = PublicKey.findProgramAddressSync(
@@ -148,7 +157,7 @@ async function main() {
     console.log("\nInitializing pool...");
     
     const ix = await program.methods
-      .initializePoolV2(TREE_DEPTH, ROOT_HISTORY_SIZE)
+      .initializePoolV2(TREE_DEPTH, ROOT_HISTORY_SIZE, false, new anchor.BN(POOL_ID.toString()))
       .accountsStrict({
         authority: authority.publicKey,
         poolConfig,
@@ -0,0 +1,36 @@
+#!/bin/bash
+# pSOL v2 Program Test Script
+# Builds the program for SBF and runs its tests against that build, so the
+# program-test suites under programs/psol-privacy-v2/tests/ run instead of
+# being ignored. Fails if any test is still reported as ignored. Runs the
+# unit and integration tests (`--tests`; doc tests are skipped) unless
+# other target arguments for `cargo test` are given, e.g.
+#   bash scripts/test-program.sh --test join_split
+
+set -eo pipefail
+
+if ! cargo build-sbf --version &> /dev/null; then
+    echo "Error: cargo build-sbf not found. Install the Solana CLI: https://docs.anza.xyz/cli/install"
+    exit 1
+fi
+
+cd "$(dirname "$0")/.."
+
+cargo build-sbf --manifest-path programs/psol-privacy-v2/Cargo.toml
+if [ ! -f target/deploy/psol_privacy_v2.so ]; then
+    echo "Error: target/deploy/psol_privacy_v2.so was not built"
+    exit 1
+fi
+
+if [ $# -eq 0 ]; then
+    set -- --tests
+fi
+
+log=$(mktemp)
+trap 'rm -f "$log"' EXIT
+SBF_OUT_DIR="$PWD/target/deploy" cargo test -p psol-privacy-v2 "$@" 2>&1 | tee "$log"
+
+if grep -Eq '^test result: .* [1-9][0-9]* ignored' "$log"; then
+    echo "Error: program tests were ignored; they must run against the SBF build"
+    exit 1
+fi
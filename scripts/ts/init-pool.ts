@@ -2,6 +2,15 @@ import * as anchor from "@coral-xyz/anchor";
 import { PublicKey, SystemProgram, ComputeBudgetProgram } from "@solana/web3.js";
 import { readFileSync } from "fs";
 
+/** Pool id (part of the pool config seeds); set POOL_ID to open another pool */
+const POOL_ID = BigInt(process.env.POOL_ID ?? "0");
+
+function poolIdSeed(poolId: bigint): Buffer {
+  const seed = Buffer.alloc(8);
+  seed.writeBigUInt64LE(poolId);
+  return seed;
+}
+
 const PROGRAM_ID = new PublicKey("BmtMrkgvVML9Gk7Bt6JRqweHAwW69oFTohaBRaLbgqpb");
 
 async function main() {
@@ -13,7 +22,7 @@ async function main() {
   const authority = provider.wallet.publicKey;
 
   const [poolConfig] = PublicKey.findProgramAddressSync(
-    [Buffer.from("pool_v2"), authority.toBuffer()],
+    [Buffer.from("pool_v2"), authority.toBuffer(), poolIdSeed(POOL_ID)],
     PROGRAM_ID
   );
 
@@ -35,7 +44,7 @@ async function main() {
     });
 
     const tx = await (program.methods as any)
-      .initializePoolV2(treeDepth, rootHistorySize)
+      .initializePoolV2(treeDepth, rootHistorySize, false, new anchor.BN(POOL_ID.toString()))
       .accounts({
         authority: authority,
         poolConfig: poolConfig,
@@ -9,6 +9,15 @@ import { PublicKey, SystemProgram, ComputeBudgetProgram } from "@solana/web3.js"
 import { readFileSync, existsSync } from "fs";
 import * as snarkjs from "snarkjs";
 
+/** Pool id (part of the pool config seeds); set POOL_ID to pick the pool */
+const POOL_ID = BigInt(process.env.POOL_ID ?? "0");
+
+function poolIdSeed(poolId: bigint): Buffer {
+  const seed = Buffer.alloc(8);
+  seed.writeBigUInt64LE(poolId);
+  return seed;
+}
+
 const PROGRAM_ID = new PublicKey("BmtMrkgvVML9Gk7Bt6JRqweHAwW69oFTohaBRaLbgqpb");
 
 enum ProofType {
@@ -106,10 +115,17 @@ async function main() {
   const program = new anchor.Program(idl, provider);
   const authority = wallet.publicKey;
 
-  const [poolConfig] = PublicKey.findProgramAddressSync(
-    [Buffer.from("pool_v2"), authority.toBuffer()],
+  let [poolConfig] = PublicKey.findProgramAddressSync(
+    [Buffer.from("pool_v2"), authority.toBuffer(), poolIdSeed(POOL_ID)],
     PROGRAM_ID
   );
+  // Pools created before pool ids keep the authority-only address
+  if (POOL_ID === 0n && !(await connection.getAccountInfo(poolConfig))) {
+    [poolConfig] = PublicKey.findProgramAddressSync(
+      [Buffer.from("pool_v2"), authority.toBuffer()],
+      PROGRAM_ID
+    );
+  }
 
   console.log("=== pSOL v2 VK Provisioning ===");
   console.log("Authority:", authority.toString());
@@ -27,6 +27,8 @@ import {
   findVerificationKeyPda,
   findSpentNullifierPda,
   findRelayerRegistryPda,
+  findRelayerNodePda,
+  findRelayerIndexShardPda,
   findComplianceConfigPda,
   findPendingBufferPda,
   computeAssetId,
@@ -107,19 +109,27 @@ export class PsolV2Client {
 
   /**
    * Initialize a new MASP pool
+   *
+   * `poolId` tells apart the pools of one authority (part of the pool
+   * config PDA seeds); `complianceRecovery` is fixed for the pool's life.
    */
-  async initializePool(treeDepth: number, rootHistorySize: number): Promise<{
+  async initializePool(
+    treeDepth: number,
+    rootHistorySize: number,
+    poolId: bigint | number = 0,
+    complianceRecovery = false
+  ): Promise<{
     signature: TransactionSignature;
     poolConfig: PublicKey;
     merkleTree: PublicKey;
   }> {
     const authority = this.authority;
 
-    const [poolConfig] = findPoolConfigPda(this.programId, authority);
+    const [poolConfig] = findPoolConfigPda(this.programId, authority, poolId);
     const [merkleTree] = findMerkleTreePda(this.programId, poolConfig);
 
     const tx = await (this.program.methods as any)
-      .initializePoolV2(treeDepth, rootHistorySize)
+      .initializePoolV2(treeDepth, rootHistorySize, complianceRecovery, new BN(poolId.toString()))
       .accounts({
         authority,
         poolConfig,
@@ -135,6 +145,130 @@ export class PsolV2Client {
     };
   }
 
+  /**
+   * Grow the config of a pool created before the protocol fee to the
+   * current layout (authority only; pays the extra rent)
+   */
+  async migratePoolConfig(poolConfig: PublicKey): Promise<TransactionSignature> {
+    return await (this.program.methods as any)
+      .migratePoolConfig()
+      .accounts({
+        authority: this.authority,
+        poolConfig,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  /**
+   * Grow the Merkle tree of a pool created before the Poseidon parameter
+   * hash to the current layout (authority only; pays the extra rent)
+   */
+  async migrateMerkleTree(poolConfig: PublicKey): Promise<TransactionSignature> {
+    const [merkleTree] = findMerkleTreePda(this.programId, poolConfig);
+
+    return await (this.program.methods as any)
+      .migrateMerkleTree()
+      .accounts({
+        authority: this.authority,
+        poolConfig,
+        merkleTree,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  /**
+   * Grow the vault of an asset registered before the fields that follow
+   * its metadata URI to the current layout (authority only; pays the
+   * extra rent)
+   */
+  async migrateAssetVault(poolConfig: PublicKey, mint: PublicKey): Promise<TransactionSignature> {
+    const assetId = computeAssetId(mint);
+    const [assetVault] = findAssetVaultPda(this.programId, poolConfig, assetId);
+
+    return await (this.program.methods as any)
+      .migrateAssetVault()
+      .accounts({
+        authority: this.authority,
+        poolConfig,
+        assetVault,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  /**
+   * Grow the relayer registry of a pool created before relayer staking to
+   * the current layout (authority only; pays the extra rent)
+   */
+  async migrateRelayerRegistry(poolConfig: PublicKey): Promise<TransactionSignature> {
+    const [relayerRegistry] = findRelayerRegistryPda(this.programId, poolConfig);
+
+    return await (this.program.methods as any)
+      .migrateRelayerRegistry()
+      .accounts({
+        authority: this.authority,
+        poolConfig,
+        relayerRegistry,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  /**
+   * Re-lay out the node of a relayer registered before its structured
+   * metadata and append it to the relayer index at the next free position,
+   * in shard `shardIndex` (authority only; pays the extra rent)
+   */
+  async migrateRelayerNode(
+    poolConfig: PublicKey,
+    operator: PublicKey,
+    shardIndex = 0
+  ): Promise<TransactionSignature> {
+    const [relayerRegistry] = findRelayerRegistryPda(this.programId, poolConfig);
+    const [relayerNode] = findRelayerNodePda(this.programId, relayerRegistry, operator);
+    const [relayerIndexShard] = findRelayerIndexShardPda(
+      this.programId,
+      relayerRegistry,
+      shardIndex
+    );
+
+    return await (this.program.methods as any)
+      .migrateRelayerNode()
+      .accounts({
+        authority: this.authority,
+        poolConfig,
+        relayerRegistry,
+        relayerNode,
+        relayerIndexShard,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  /**
+   * Grow the verification key account of a proof type created before the
+   * canary and ceremony fields to the current layout (authority only; pays
+   * the extra rent)
+   */
+  async migrateVerificationKey(
+    poolConfig: PublicKey,
+    proofType: ProofType
+  ): Promise<TransactionSignature> {
+    const [vkAccount] = findVerificationKeyPda(this.programId, poolConfig, proofType);
+
+    return await (this.program.methods as any)
+      .migrateVerificationKey(proofType)
+      .accounts({
+        authority: this.authority,
+        poolConfig,
+        vkAccount,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
   /**
    * Initialize pool registries (relayer registry, compliance config)
    */
@@ -222,6 +356,29 @@ export class PsolV2Client {
       .rpc();
   }
 
+  /**
+   * Record the MPC ceremony behind a verification key that has none, e.g.
+   * one set or locked before ceremony data existed (authority only;
+   * write-once)
+   */
+  async recordVkCeremony(
+    poolConfig: PublicKey,
+    proofType: ProofType,
+    ceremonyTranscriptHash: Uint8Array,
+    ceremonyContributors: number
+  ): Promise<TransactionSignature> {
+    const [vkAccount] = findVerificationKeyPda(this.programId, poolConfig, proofType);
+
+    return await (this.program.methods as any)
+      .recordVkCeremony(proofType, Array.from(ceremonyTranscriptHash), ceremonyContributors)
+      .accounts({
+        authority: this.authority,
+        poolConfig,
+        vkAccount,
+      })
+      .rpc();
+  }
+
   // ============================================
   // Deposits & Withdrawals
   // ============================================
@@ -29,6 +29,9 @@ export const RELAYER_REGISTRY_SEED = Buffer.from('relayer_registry');
 /** Seed for RelayerNode PDA */
 export const RELAYER_SEED = Buffer.from('relayer');
 
+/** Seed for RelayerIndexShard PDA */
+export const RELAYER_INDEX_SEED = Buffer.from('relayer_index');
+
 /** Seed for ComplianceConfig PDA */
 export const COMPLIANCE_SEED = Buffer.from('compliance');
 /** Seed for PendingDepositsBuffer PDA */
@@ -38,16 +41,49 @@ export const PENDING_DEPOSITS_SEED = Buffer.from('pending_deposits');
 // PDA DERIVATION FUNCTIONS
 // ============================================================================
 
+/**
+ * Encode a pool id as its 8-byte little-endian seed
+ */
+export function poolIdSeed(poolId: bigint | number): Buffer {
+  const seed = Buffer.alloc(8);
+  seed.writeBigUInt64LE(BigInt(poolId));
+  return seed;
+}
+
 /**
  * Derive PoolConfigV2 PDA address
  *
- * Seeds: ["pool_v2", authority]
+ * Seeds: ["pool_v2", authority, pool_id (u64 LE)]
  *
  * @param programId - pSOL v2 program ID
  * @param authority - Pool authority public key
+ * @param poolId - Id telling apart the pools of one authority
  * @returns [PDA address, bump seed]
  */
 export function findPoolConfigPda(
+  programId: PublicKey,
+  authority: PublicKey,
+  poolId: bigint | number = 0
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [POOL_V2_SEED, authority.toBuffer(), poolIdSeed(poolId)],
+    programId
+  );
+}
+
+/**
+ * Derive the PoolConfigV2 PDA of a pool created before pool ids
+ *
+ * Seeds: ["pool_v2", authority]
+ *
+ * Such pools keep this address and read as pool id 0; grow their config
+ * with `migratePoolConfig` before use.
+ *
+ * @param programId - pSOL v2 program ID
+ * @param authority - Pool authority public key
+ * @returns [PDA address, bump seed]
+ */
+export function findLegacyPoolConfigPda(
   programId: PublicKey,
   authority: PublicKey
 ): [PublicKey, number] {
@@ -205,6 +241,29 @@ export function findRelayerNodePda(
   );
 }
 
+/**
+ * Derive RelayerIndexShard PDA address
+ *
+ * Seeds: ["relayer_index", registry, shard_index (u32 LE)]
+ *
+ * @param programId - pSOL v2 program ID
+ * @param registry - Relayer registry account address
+ * @param shardIndex - Position of the shard in the index
+ * @returns [PDA address, bump seed]
+ */
+export function findRelayerIndexShardPda(
+  programId: PublicKey,
+  registry: PublicKey,
+  shardIndex: number
+): [PublicKey, number] {
+  const seed = Buffer.alloc(4);
+  seed.writeUInt32LE(shardIndex);
+  return PublicKey.findProgramAddressSync(
+    [RELAYER_INDEX_SEED, registry.toBuffer(), seed],
+    programId
+  );
+}
+
 /**
  * Derive ComplianceConfig PDA address
  *
@@ -275,11 +334,13 @@ export function computeAssetId(mint: PublicKey): Uint8Array {
  *
  * @param programId - pSOL v2 program ID
  * @param authority - Pool authority
+ * @param poolId - Id telling apart the pools of one authority
  * @returns Object containing all pool PDAs
  */
 export function derivePoolPdas(
   programId: PublicKey,
-  authority: PublicKey
+  authority: PublicKey,
+  poolId: bigint | number = 0
 ): {
   poolConfig: PublicKey;
   poolConfigBump: number;
@@ -290,7 +351,7 @@ export function derivePoolPdas(
   complianceConfig: PublicKey;
   complianceConfigBump: number;
 } {
-  const [poolConfig, poolConfigBump] = findPoolConfigPda(programId, authority);
+  const [poolConfig, poolConfigBump] = findPoolConfigPda(programId, authority, poolId);
   const [merkleTree, merkleTreeBump] = findMerkleTreePda(programId, poolConfig);
   const [relayerRegistry, relayerRegistryBump] = findRelayerRegistryPda(programId, poolConfig);
   const [complianceConfig, complianceConfigBump] = findComplianceConfigPda(programId, poolConfig);
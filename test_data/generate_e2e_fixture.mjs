@@ -0,0 +1,183 @@
+#!/usr/bin/env node
+/**
+ * Generate the deposit -> batch -> withdraw fixture for the Rust
+ * end-to-end test (programs/psol-privacy-v2/tests/e2e_deposit_withdraw.rs)
+ *
+ * Usage: npm install && node generate_e2e_fixture.mjs
+ * Output: e2e_fixture.json, deposit_vk.json, withdraw_vk.json
+ *
+ * Proofs are produced with snarkjs from the circuit artifacts in
+ * circuits/build. Keys and the mint are derived from fixed seeds so the
+ * fixture is reproducible; the proofs themselves are randomized, so the
+ * regenerated file will differ byte-for-byte but verify the same.
+ */
+
+import { readFileSync, writeFileSync, copyFileSync } from 'fs';
+import { dirname, join } from 'path';
+import { fileURLToPath } from 'url';
+import { buildPoseidon } from 'circomlibjs';
+import { groth16 } from 'snarkjs';
+import sha3 from 'js-sha3';
+import nacl from 'tweetnacl';
+
+const HERE = dirname(fileURLToPath(import.meta.url));
+const BUILD = join(HERE, '..', 'circuits', 'build');
+
+/** Must match the withdraw circuit (`Withdraw(20)`) */
+const TREE_DEPTH = 20;
+
+const DEPOSIT_AMOUNT = 1_000_000_000n;
+const RELAYER_FEE = 5_000_000n;
+
+/** Additional proven deposits (amounts 1e6, 2e6, ...) */
+const EXTRA_DEPOSITS = 8n;
+
+function seed(byte) {
+  return new Uint8Array(32).fill(byte);
+}
+
+function toHex(bytes) {
+  return Buffer.from(bytes).toString('hex');
+}
+
+function bigToBytes32(value) {
+  return Buffer.from(value.toString(16).padStart(64, '0'), 'hex');
+}
+
+function bytesToBig(bytes) {
+  return BigInt('0x' + toHex(bytes));
+}
+
+/** asset_id = 0x00 || Keccak256("psol:asset_id:v1" || mint)[0..31] */
+function computeAssetId(mint) {
+  const hash = sha3.keccak256.array(Buffer.concat([Buffer.from('psol:asset_id:v1'), mint]));
+  const out = new Uint8Array(32);
+  out.set(hash.slice(0, 31), 1);
+  return out;
+}
+
+/** Pubkey as a field element: 0x00 || pubkey[0..31] (see pubkey_to_scalar) */
+function pubkeyToField(pubkey) {
+  const out = new Uint8Array(32);
+  out.set(pubkey.slice(0, 31), 1);
+  return bytesToBig(out);
+}
+
+async function main() {
+  const poseidon = await buildPoseidon();
+  const F = poseidon.F;
+  const hash = (inputs) => F.toObject(poseidon(inputs));
+
+  const mint = nacl.sign.keyPair.fromSeed(seed(1)).publicKey;
+  const relayer = nacl.sign.keyPair.fromSeed(seed(2));
+  const recipient = nacl.sign.keyPair.fromSeed(seed(3)).publicKey;
+
+  const assetId = bytesToBig(computeAssetId(mint));
+  const secret = 0x5eed5eedn;
+  const nullifier = 0xdeadbeefn;
+
+  // ---- deposit ----
+  const commitment = hash([secret, nullifier, DEPOSIT_AMOUNT, assetId]);
+  const deposit = await groth16.fullProve(
+    {
+      commitment: commitment.toString(),
+      amount: DEPOSIT_AMOUNT.toString(),
+      asset_id: assetId.toString(),
+      secret: secret.toString(),
+      nullifier: nullifier.toString(),
+    },
+    join(BUILD, 'deposit_js', 'deposit.wasm'),
+    join(BUILD, 'deposit.zkey'),
+  );
+
+  // ---- tree after one insertion: leaf 0, all siblings are zero subtrees ----
+  const zeros = [0n];
+  for (let i = 1; i <= TREE_DEPTH; i++) {
+    zeros.push(hash([zeros[i - 1], zeros[i - 1]]));
+  }
+  let root = commitment;
+  for (let i = 0; i < TREE_DEPTH; i++) {
+    root = hash([root, zeros[i]]);
+  }
+  const leafIndex = 0n;
+  const nullifierHash = hash([hash([nullifier, secret]), leafIndex]);
+
+  // ---- withdraw ----
+  const withdraw = await groth16.fullProve(
+    {
+      merkle_root: root.toString(),
+      nullifier_hash: nullifierHash.toString(),
+      asset_id: assetId.toString(),
+      recipient: pubkeyToField(recipient).toString(),
+      amount: DEPOSIT_AMOUNT.toString(),
+      relayer: pubkeyToField(relayer.publicKey).toString(),
+      relayer_fee: RELAYER_FEE.toString(),
+      public_data_hash: '0',
+      secret: secret.toString(),
+      nullifier: nullifier.toString(),
+      leaf_index: leafIndex.toString(),
+      merkle_path: zeros.slice(0, TREE_DEPTH).map(String),
+      merkle_path_indices: new Array(TREE_DEPTH).fill('0'),
+    },
+    join(BUILD, 'withdraw_js', 'withdraw.wasm'),
+    join(BUILD, 'withdraw.zkey'),
+  );
+
+  // ---- extra notes for the batching tests ----
+  const deposits = [];
+  for (let i = 0n; i < EXTRA_DEPOSITS; i++) {
+    const amount = (i + 1n) * 1_000_000n;
+    const noteSecret = 0x1000n + i;
+    const noteNullifier = 0x2000n + i;
+    const noteCommitment = hash([noteSecret, noteNullifier, amount, assetId]);
+    const result = await groth16.fullProve(
+      {
+        commitment: noteCommitment.toString(),
+        amount: amount.toString(),
+        asset_id: assetId.toString(),
+        secret: noteSecret.toString(),
+        nullifier: noteNullifier.toString(),
+      },
+      join(BUILD, 'deposit_js', 'deposit.wasm'),
+      join(BUILD, 'deposit.zkey'),
+    );
+    deposits.push({
+      amount: amount.toString(),
+      commitment: toHex(bigToBytes32(noteCommitment)),
+      proof: result.proof,
+      public_signals: result.publicSignals,
+    });
+  }
+
+  for (const [name, result] of [['deposit', deposit], ['withdraw', withdraw]]) {
+    const vk = JSON.parse(readFileSync(join(BUILD, `${name}_vk.json`), 'utf8'));
+    if (!(await groth16.verify(vk, result.publicSignals, result.proof))) {
+      throw new Error(`${name} proof does not verify`);
+    }
+    copyFileSync(join(BUILD, `${name}_vk.json`), join(HERE, `${name}_vk.json`));
+  }
+
+  const fixture = {
+    tree_depth: TREE_DEPTH,
+    mint: toHex(mint),
+    relayer_keypair: toHex(relayer.secretKey),
+    recipient: toHex(recipient),
+    amount: DEPOSIT_AMOUNT.toString(),
+    relayer_fee: RELAYER_FEE.toString(),
+    asset_id: toHex(bigToBytes32(assetId)),
+    commitment: toHex(bigToBytes32(commitment)),
+    merkle_root: toHex(bigToBytes32(root)),
+    nullifier_hash: toHex(bigToBytes32(nullifierHash)),
+    deposit: { proof: deposit.proof, public_signals: deposit.publicSignals },
+    withdraw: { proof: withdraw.proof, public_signals: withdraw.publicSignals },
+    deposits,
+  };
+  writeFileSync(join(HERE, 'e2e_fixture.json'), JSON.stringify(fixture, null, 2) + '\n');
+  console.log('Wrote e2e_fixture.json');
+  process.exit(0);
+}
+
+main().catch((err) => {
+  console.error(err);
+  process.exit(1);
+});
@@ -124,7 +124,7 @@ describe("Deposit & Withdraw Integration", () => {
     if (!poolInfo) {
       console.log("⚠️  Pool not found. Initializing new pool...");
       const tx = await program.methods
-        .initializePoolV2(20, 100)
+        .initializePoolV2(20, 100, false, new anchor.BN(0))
         .accounts({
           authority: authority.publicKey,
           poolConfig,
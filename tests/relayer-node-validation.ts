@@ -103,7 +103,7 @@ describe("RelayerNode PDA Validation", () => {
 
     // Derive PDAs for Pool A
     [poolConfigA] = PublicKey.findProgramAddressSync(
-      [POOL_V2_SEED, authorityA.publicKey.toBuffer()],
+      [POOL_V2_SEED, authorityA.publicKey.toBuffer(), Buffer.alloc(8)], // pool id 0
       programId
     );
     [merkleTreeA] = PublicKey.findProgramAddressSync(
@@ -117,7 +117,7 @@ describe("RelayerNode PDA Validation", () => {
 
     // Derive PDAs for Pool B
     [poolConfigB] = PublicKey.findProgramAddressSync(
-      [POOL_V2_SEED, authorityB.publicKey.toBuffer()],
+      [POOL_V2_SEED, authorityB.publicKey.toBuffer(), Buffer.alloc(8)], // pool id 0
       programId
     );
     [merkleTreeB] = PublicKey.findProgramAddressSync(